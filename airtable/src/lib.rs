@@ -36,7 +36,7 @@
  * ```
  */
 #![allow(clippy::field_reassign_with_default)]
-use std::{env, fmt, fmt::Debug};
+use std::{collections::HashMap, env, fmt, fmt::Debug};
 
 use anyhow::{bail, Result};
 use chrono::{offset::Utc, DateTime};
@@ -49,6 +49,12 @@ use serde::{
 
 /// Endpoint for the Airtable API.
 const ENDPOINT: &str = "https://api.airtable.com/v0/";
+/// Endpoint for uploading attachments to the Airtable API.
+const CONTENT_ENDPOINT: &str = "https://content.airtable.com/v0/";
+
+/// The largest file we can upload to an attachment field. Bigger files have to be attached
+/// by a URL Airtable can download them from.
+pub const MAX_ATTACHMENT_UPLOAD_SIZE: usize = 5 * 1024 * 1024;
 
 /// Entrypoint for interacting with the Airtable API.
 pub struct Airtable {
@@ -87,18 +93,34 @@ impl Airtable {
                     .with(reqwest_retry::RetryTransientMiddleware::new_with_policy(retry_policy))
                     .build();
 
-                Self {
-                    key: key.to_string(),
-                    base_id: base_id.to_string(),
-                    enterprise_account_id: enterprise_account_id.to_string(),
-
-                    client,
-                }
+                Airtable::new_with_client(key, base_id, enterprise_account_id, client)
             }
             Err(e) => panic!("creating client failed: {:?}", e),
         }
     }
 
+    /// Create a new Airtable client struct that sends its requests with the given HTTP
+    /// client, for when the requests need to go through more middleware, like a rate limit.
+    pub fn new_with_client<K, B, E>(
+        key: K,
+        base_id: B,
+        enterprise_account_id: E,
+        client: reqwest_middleware::ClientWithMiddleware,
+    ) -> Self
+    where
+        K: ToString,
+        B: ToString,
+        E: ToString,
+    {
+        Self {
+            key: key.to_string(),
+            base_id: base_id.to_string(),
+            enterprise_account_id: enterprise_account_id.to_string(),
+
+            client,
+        }
+    }
+
     /// Create a new Airtable client struct from environment variables. It
     /// takes a type that can convert into
     /// an &str (`String` or `Vec<u8>` for example). As long as the function is
@@ -409,6 +431,54 @@ impl Airtable {
         }
     }
 
+    /// Upload a file to an attachment field of a record. The file is added after the
+    /// attachments already in the field, and we get back all the attachments of the field.
+    /// FROM: https://airtable.com/developers/web/api/upload-attachment
+    pub async fn upload_attachment(
+        &self,
+        record_id: &str,
+        field: &str,
+        filename: &str,
+        content_type: &str,
+        contents: &[u8],
+    ) -> Result<Vec<Attachment>> {
+        if contents.len() > MAX_ATTACHMENT_UPLOAD_SIZE {
+            bail!(
+                "`{}` is {} bytes, attachments we upload can be at most {} bytes",
+                filename,
+                contents.len(),
+                MAX_ATTACHMENT_UPLOAD_SIZE
+            );
+        }
+
+        let url = Url::parse(CONTENT_ENDPOINT)?
+            .join(&format!("{}/{}/{}/uploadAttachment", self.base_id, record_id, field))?;
+        let bearer = header::HeaderValue::from_str(&format!("Bearer {}", self.key))?;
+        let request = self
+            .client
+            .request(Method::POST, url)
+            .header(header::AUTHORIZATION, bearer)
+            .json(&UploadAttachmentRequest {
+                content_type: content_type.to_string(),
+                file: base64::encode(contents),
+                filename: filename.to_string(),
+            })
+            .build()?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        // The fields are keyed by their id, and we only get the one we uploaded to.
+        let r: UploadAttachmentResponse = resp.json().await?;
+
+        Ok(r.fields.into_values().next().unwrap_or_default())
+    }
+
     /// List users.
     /// This is for an enterprise admin to do only.
     /// FROM: https://airtable.com/api/enterprise
@@ -791,6 +861,21 @@ pub struct ErrorResponse {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct UploadAttachmentRequest {
+    #[serde(rename = "contentType")]
+    content_type: String,
+    /// The contents of the file, base64 encoded.
+    file: String,
+    filename: String,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct UploadAttachmentResponse {
+    #[serde(default)]
+    fields: HashMap<String, Vec<Attachment>>,
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct AttachmentShort {
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -1439,3 +1524,186 @@ pub mod deserialize_missing_timezone {
             .map_err(D::Error::custom)
     }
 }
+
+// Linked record fields hold the ids of the records they link to. Airtable returns them as an
+// array of ids, or of objects with an `id` when the links are expanded, and we always write
+// back the ids so the links we did not change are kept as they are.
+pub mod linked_records {
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    // The signature of a serialize_with function must follow the pattern:
+    //
+    //    fn serialize<S>(&T, S) -> Result<S::Ok, S::Error>
+    //    where
+    //        S: Serializer
+    //
+    // although it may also be generic over the input types T.
+    pub fn serialize<S>(ids: &[String], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(ids.iter().filter(|id| !id.trim().is_empty()))
+    }
+
+    // The signature of a deserialize_with function must follow the pattern:
+    //
+    //    fn deserialize<'de, D>(D) -> Result<T, D::Error>
+    //    where
+    //        D: Deserializer<'de>
+    //
+    // although it may also be generic over the output types T.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+
+        Ok(super::linked_record_ids(value.unwrap_or_default()))
+    }
+}
+
+/// Get the ids of the records a linked record field points to.
+fn linked_record_ids(value: serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::Array(values) => {
+            let mut ids: Vec<String> = Default::default();
+            for id in values.into_iter().flat_map(linked_record_ids) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            ids
+        }
+        serde_json::Value::Object(mut object) => match object.remove("id") {
+            Some(serde_json::Value::String(id)) if !id.trim().is_empty() => vec![id],
+            _ => Default::default(),
+        },
+        serde_json::Value::String(id) if !id.trim().is_empty() => vec![id],
+        _ => Default::default(),
+    }
+}
+
+// Formula, rollup and lookup fields are computed by Airtable, so we only ever read them. They
+// can be a string, a number, a boolean, an array of those for lookups, or an object when the
+// formula errors, like `{"error": "#ERROR!"}` or `{"specialValue": "NaN"}`.
+pub mod formula_format_as_string {
+    use serde::{self, Deserialize, Deserializer};
+
+    // The signature of a deserialize_with function must follow the pattern:
+    //
+    //    fn deserialize<'de, D>(D) -> Result<T, D::Error>
+    //    where
+    //        D: Deserializer<'de>
+    //
+    // although it may also be generic over the output types T.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<String, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+
+        Ok(super::formula_string(value.unwrap_or_default()))
+    }
+}
+
+pub mod formula_format_as_f32 {
+    use serde::{self, Deserialize, Deserializer};
+
+    // The signature of a deserialize_with function must follow the pattern:
+    //
+    //    fn deserialize<'de, D>(D) -> Result<T, D::Error>
+    //    where
+    //        D: Deserializer<'de>
+    //
+    // although it may also be generic over the output types T.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<f32, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+
+        Ok(super::formula_f64(value.unwrap_or_default()).unwrap_or_default() as f32)
+    }
+}
+
+/// Get the text of a formula field, errors read as an empty string.
+fn formula_string(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s,
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::Bool(b) => b.to_string(),
+        serde_json::Value::Array(values) => values
+            .into_iter()
+            .map(formula_string)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<String>>()
+            .join(", "),
+        serde_json::Value::Object(_) | serde_json::Value::Null => Default::default(),
+    }
+}
+
+/// Get the number of a formula field, if it is one.
+fn formula_f64(value: serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        // A lookup of a single number.
+        serde_json::Value::Array(values) if values.len() == 1 => values.into_iter().next().and_then(formula_f64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        #[serde(
+            default,
+            skip_serializing_if = "Vec::is_empty",
+            serialize_with = "super::linked_records::serialize",
+            deserialize_with = "super::linked_records::deserialize"
+        )]
+        links: Vec<String>,
+        #[serde(
+            default,
+            skip_serializing_if = "String::is_empty",
+            deserialize_with = "super::formula_format_as_string::deserialize"
+        )]
+        name: String,
+        #[serde(
+            default,
+            skip_serializing,
+            deserialize_with = "super::formula_format_as_f32::deserialize"
+        )]
+        total: f32,
+    }
+
+    #[test]
+    fn test_linked_records() {
+        let row: Row =
+            serde_json::from_str(r#"{"links": ["rec1", {"id": "rec2", "name": "Two"}, "rec1", ""]}"#).unwrap();
+        assert_eq!(vec!["rec1".to_string(), "rec2".to_string()], row.links);
+        assert_eq!(r#"{"links":["rec1","rec2"]}"#, serde_json::to_string(&row).unwrap());
+
+        let row: Row = serde_json::from_str(r#"{"links": null}"#).unwrap();
+        assert!(row.links.is_empty());
+        assert_eq!("{}", serde_json::to_string(&row).unwrap());
+    }
+
+    #[test]
+    fn test_formula_fields() {
+        let row: Row = serde_json::from_str(r#"{"name": ["Jane", "Doe"], "total": 12.5}"#).unwrap();
+        assert_eq!("Jane, Doe", row.name);
+        assert_eq!(12.5, row.total);
+
+        let row: Row =
+            serde_json::from_str(r##"{"name": {"error": "#ERROR!"}, "total": {"specialValue": "NaN"}}"##).unwrap();
+        assert_eq!(Row::default(), row);
+
+        let row: Row = serde_json::from_str(r#"{"name": 3, "total": "4"}"#).unwrap();
+        assert_eq!("3", row.name);
+        assert_eq!(4.0, row.total);
+    }
+}
@@ -1 +1,2 @@
+pub mod printer;
 pub mod swag_inventory;
@@ -0,0 +1,31 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The state of a printer, as reported by the print server it is connected to.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct PrinterStatus {
+    /// The IPP state of the printer: `idle`, `processing` or `stopped`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub state: String,
+    /// If the printer accepts jobs.
+    #[serde(default)]
+    pub accepting_jobs: bool,
+    /// The IPP state reasons of the printer, like `media-low-report` or `media-empty-error`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+    /// The levels of the media and ribbons in the printer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supplies: Vec<PrinterSupply>,
+}
+
+/// A supply of a printer, like a roll of labels or a ribbon.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct PrinterSupply {
+    pub name: String,
+    /// The IPP marker type, like `ink-ribbon` or `thermal`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub kind: String,
+    /// The percentage left, negative if the printer does not know.
+    #[serde(default)]
+    pub level: i32,
+}
@@ -0,0 +1,14 @@
+CREATE TABLE roles (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    title VARCHAR NOT NULL,
+    ladder VARCHAR NOT NULL DEFAULT '',
+    level INTEGER NOT NULL DEFAULT 0,
+    comp_band VARCHAR NOT NULL DEFAULT '',
+    checkr_package VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (cio_company_id, name)
+);
+
+ALTER TABLE roles ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
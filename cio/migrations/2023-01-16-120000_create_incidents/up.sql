@@ -0,0 +1,16 @@
+CREATE TABLE incidents (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    summary TEXT NOT NULL DEFAULT '',
+    channel_id VARCHAR NOT NULL,
+    thread_ts VARCHAR NOT NULL,
+    link VARCHAR NOT NULL DEFAULT '',
+    message_count INTEGER NOT NULL DEFAULT 0,
+    participants TEXT [] NOT NULL DEFAULT '{}',
+    summarized_by VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (cio_company_id, channel_id, thread_ts)
+);
+
+ALTER TABLE incidents ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
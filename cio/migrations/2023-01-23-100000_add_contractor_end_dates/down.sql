@@ -0,0 +1,5 @@
+ALTER TABLE users DROP COLUMN is_contractor;
+ALTER TABLE users DROP COLUMN end_date;
+ALTER TABLE users DROP COLUMN requested_end_date;
+ALTER TABLE users DROP COLUMN extended_end_date;
+ALTER TABLE users DROP COLUMN expiration_warning_sent_for;
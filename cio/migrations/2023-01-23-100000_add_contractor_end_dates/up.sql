@@ -0,0 +1,5 @@
+ALTER TABLE users ADD COLUMN is_contractor BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE users ADD COLUMN end_date DATE DEFAULT NULL;
+ALTER TABLE users ADD COLUMN requested_end_date DATE DEFAULT NULL;
+ALTER TABLE users ADD COLUMN extended_end_date DATE DEFAULT NULL;
+ALTER TABLE users ADD COLUMN expiration_warning_sent_for DATE DEFAULT NULL;
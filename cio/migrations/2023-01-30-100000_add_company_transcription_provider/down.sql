@@ -0,0 +1,2 @@
+ALTER TABLE companys DROP COLUMN transcription_provider;
+ALTER TABLE companys DROP COLUMN whisper_url;
@@ -0,0 +1,2 @@
+ALTER TABLE companys ADD COLUMN transcription_provider VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE companys ADD COLUMN whisper_url VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1,5 @@
+DROP TABLE focus_time_conflicts;
+
+ALTER TABLE users DROP COLUMN focus_blocks;
+ALTER TABLE users DROP COLUMN focus_time_zone;
+ALTER TABLE users DROP COLUMN decline_meetings_over_focus_time;
@@ -0,0 +1,19 @@
+ALTER TABLE users ADD COLUMN focus_blocks TEXT [] NOT NULL DEFAULT '{}';
+ALTER TABLE users ADD COLUMN focus_time_zone VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN decline_meetings_over_focus_time BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE focus_time_conflicts (
+    id SERIAL PRIMARY KEY,
+    user_email VARCHAR NOT NULL,
+    google_event_id VARCHAR NOT NULL,
+    event_summary VARCHAR NOT NULL DEFAULT '',
+    event_link VARCHAR NOT NULL DEFAULT '',
+    start_time TIMESTAMPTZ NOT NULL,
+    end_time TIMESTAMPTZ NOT NULL,
+    action VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (cio_company_id, user_email, google_event_id)
+);
+
+ALTER TABLE focus_time_conflicts ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
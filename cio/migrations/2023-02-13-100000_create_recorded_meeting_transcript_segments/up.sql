@@ -0,0 +1,12 @@
+CREATE TABLE recorded_meeting_transcript_segments (
+    id SERIAL PRIMARY KEY,
+    recorded_meeting_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    speaker VARCHAR NOT NULL DEFAULT '',
+    start_offset DOUBLE PRECISION NOT NULL DEFAULT 0,
+    end_offset DOUBLE PRECISION NOT NULL DEFAULT 0,
+    text TEXT NOT NULL DEFAULT '',
+    UNIQUE (recorded_meeting_id, position)
+);
+
+ALTER TABLE recorded_meeting_transcript_segments ADD FOREIGN KEY (recorded_meeting_id) REFERENCES recorded_meetings(id) ON DELETE CASCADE ON UPDATE CASCADE;
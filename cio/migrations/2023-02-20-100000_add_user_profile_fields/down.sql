@@ -0,0 +1,5 @@
+ALTER TABLE users DROP COLUMN phone;
+ALTER TABLE users DROP COLUMN pronouns;
+ALTER TABLE users DROP COLUMN emergency_contact_name;
+ALTER TABLE users DROP COLUMN emergency_contact_phone;
+ALTER TABLE users DROP COLUMN emergency_contact_relationship;
@@ -0,0 +1,5 @@
+ALTER TABLE users ADD COLUMN phone VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN pronouns VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN emergency_contact_name VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN emergency_contact_phone VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN emergency_contact_relationship VARCHAR NOT NULL DEFAULT '';
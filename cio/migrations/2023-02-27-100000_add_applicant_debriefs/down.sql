@@ -0,0 +1,2 @@
+ALTER TABLE applicants DROP COLUMN debrief_event_id;
+ALTER TABLE applicants DROP COLUMN debrief_event_link;
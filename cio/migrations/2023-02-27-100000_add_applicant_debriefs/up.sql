@@ -0,0 +1,2 @@
+ALTER TABLE applicants ADD COLUMN debrief_event_id VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicants ADD COLUMN debrief_event_link VARCHAR NOT NULL DEFAULT '';
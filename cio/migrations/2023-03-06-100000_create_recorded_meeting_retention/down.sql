@@ -0,0 +1,6 @@
+DROP TABLE recorded_meeting_purges;
+
+ALTER TABLE recorded_meetings DROP COLUMN tags;
+ALTER TABLE recorded_meetings DROP COLUMN purged_at;
+
+ALTER TABLE companys DROP COLUMN recorded_meeting_retention_days;
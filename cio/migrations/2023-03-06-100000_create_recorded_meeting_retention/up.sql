@@ -0,0 +1,22 @@
+ALTER TABLE companys ADD COLUMN recorded_meeting_retention_days INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE recorded_meetings ADD COLUMN tags TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
+ALTER TABLE recorded_meetings ADD COLUMN purged_at TIMESTAMPTZ;
+
+CREATE TABLE recorded_meeting_purges (
+    id SERIAL PRIMARY KEY,
+    recorded_meeting_id INTEGER NOT NULL,
+    name VARCHAR NOT NULL DEFAULT '',
+    google_event_id VARCHAR NOT NULL DEFAULT '',
+    start_time TIMESTAMPTZ NOT NULL,
+    video VARCHAR NOT NULL DEFAULT '',
+    chat_log_link VARCHAR NOT NULL DEFAULT '',
+    transcript_id VARCHAR NOT NULL DEFAULT '',
+    transcript_segments INTEGER NOT NULL DEFAULT 0,
+    deleted_files TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[],
+    retention_days INTEGER NOT NULL DEFAULT 0,
+    purged_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL
+);
+
+ALTER TABLE recorded_meeting_purges ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
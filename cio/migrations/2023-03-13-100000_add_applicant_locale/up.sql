@@ -0,0 +1,2 @@
+ALTER TABLE roles ADD COLUMN locale VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicants ADD COLUMN locale VARCHAR NOT NULL DEFAULT '';
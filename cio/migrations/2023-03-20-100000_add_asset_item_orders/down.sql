@@ -0,0 +1,3 @@
+ALTER TABLE asset_items DROP COLUMN supplier;
+ALTER TABLE asset_items DROP COLUMN order_number;
+ALTER TABLE asset_items DROP COLUMN expected_delivery_date;
\ No newline at end of file
@@ -0,0 +1,3 @@
+ALTER TABLE asset_items ADD COLUMN supplier VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE asset_items ADD COLUMN order_number VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE asset_items ADD COLUMN expected_delivery_date DATE;
\ No newline at end of file
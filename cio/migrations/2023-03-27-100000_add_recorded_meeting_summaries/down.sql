@@ -0,0 +1,3 @@
+ALTER TABLE recorded_meetings DROP COLUMN host;
+ALTER TABLE recorded_meetings DROP COLUMN summary;
+ALTER TABLE recorded_meetings DROP COLUMN action_items;
\ No newline at end of file
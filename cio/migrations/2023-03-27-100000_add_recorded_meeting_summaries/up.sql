@@ -0,0 +1,3 @@
+ALTER TABLE recorded_meetings ADD COLUMN host VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE recorded_meetings ADD COLUMN summary TEXT NOT NULL DEFAULT '';
+ALTER TABLE recorded_meetings ADD COLUMN action_items TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE companys DROP COLUMN zoom_sync_lookback_days;
+ALTER TABLE companys DROP COLUMN zoom_sync_cursor;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE companys ADD COLUMN zoom_sync_lookback_days INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE companys ADD COLUMN zoom_sync_cursor TIMESTAMPTZ;
\ No newline at end of file
@@ -0,0 +1,12 @@
+CREATE TABLE rfd_impacts (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    owner VARCHAR NOT NULL,
+    repo VARCHAR NOT NULL,
+    issue_number INTEGER NOT NULL DEFAULT 0,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rfd_impacts_repo ON rfd_impacts(cio_company_id,owner,repo);
+
+ALTER TABLE rfd_impacts ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
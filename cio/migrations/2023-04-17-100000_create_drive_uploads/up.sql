@@ -0,0 +1,21 @@
+CREATE TABLE drive_uploads (
+    id SERIAL PRIMARY KEY,
+    drive_id VARCHAR NOT NULL,
+    parent_id VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    mime_type VARCHAR NOT NULL,
+    content_path VARCHAR NOT NULL,
+    target_kind VARCHAR NOT NULL,
+    target_key VARCHAR NOT NULL,
+    target_field VARCHAR NOT NULL DEFAULT '',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error VARCHAR NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    drive_file_id VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_drive_uploads_pending ON drive_uploads(cio_company_id,completed_at);
+
+ALTER TABLE drive_uploads ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
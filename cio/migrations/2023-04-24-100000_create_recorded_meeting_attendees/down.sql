@@ -0,0 +1,2 @@
+DROP TABLE recorded_meeting_attendees;
+ALTER TABLE recorded_meetings DROP COLUMN link_to_people;
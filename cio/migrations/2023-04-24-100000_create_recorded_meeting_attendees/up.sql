@@ -0,0 +1,14 @@
+ALTER TABLE recorded_meetings ADD COLUMN link_to_people TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
+
+CREATE TABLE recorded_meeting_attendees (
+    id SERIAL PRIMARY KEY,
+    recorded_meeting_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    email VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (recorded_meeting_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_recorded_meeting_attendees_user ON recorded_meeting_attendees(user_id);
+
+ALTER TABLE recorded_meeting_attendees ADD FOREIGN KEY (recorded_meeting_id) REFERENCES recorded_meetings(id) ON DELETE CASCADE ON UPDATE CASCADE;
+ALTER TABLE recorded_meeting_attendees ADD FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE;
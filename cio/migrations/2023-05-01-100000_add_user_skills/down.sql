@@ -0,0 +1,3 @@
+ALTER TABLE users DROP COLUMN skills;
+ALTER TABLE users DROP COLUMN interests;
+ALTER TABLE users DROP COLUMN previous_domains;
@@ -0,0 +1,3 @@
+ALTER TABLE users ADD COLUMN skills TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
+ALTER TABLE users ADD COLUMN interests TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
+ALTER TABLE users ADD COLUMN previous_domains TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
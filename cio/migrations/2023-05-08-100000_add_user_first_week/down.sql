@@ -0,0 +1,2 @@
+ALTER TABLE users DROP COLUMN onboarding_buddy;
+ALTER TABLE users DROP COLUMN first_week_event_ids;
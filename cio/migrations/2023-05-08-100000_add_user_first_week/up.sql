@@ -0,0 +1,2 @@
+ALTER TABLE users ADD COLUMN onboarding_buddy VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN first_week_event_ids TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
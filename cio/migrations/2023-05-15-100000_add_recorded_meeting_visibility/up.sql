@@ -0,0 +1 @@
+ALTER TABLE recorded_meetings ADD COLUMN visibility VARCHAR NOT NULL DEFAULT 'public-internal';
@@ -0,0 +1,2 @@
+DROP TABLE asset_reservations;
+ALTER TABLE asset_items DROP COLUMN is_loaner;
@@ -0,0 +1,19 @@
+ALTER TABLE asset_items ADD COLUMN is_loaner BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE asset_reservations (
+    id SERIAL PRIMARY KEY,
+    asset_name VARCHAR NOT NULL,
+    email VARCHAR NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    purpose VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL DEFAULT 'reserved',
+    pickup_reminder_sent BOOLEAN NOT NULL DEFAULT false,
+    return_reminder_sent BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_asset_reservations_dates ON asset_reservations(cio_company_id,end_date);
+
+ALTER TABLE asset_reservations ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,3 @@
+ALTER TABLE asset_items DROP COLUMN qr_code;
+ALTER TABLE asset_items DROP COLUMN qr_code_png;
+ALTER TABLE asset_items DROP COLUMN qr_code_svg;
@@ -0,0 +1,12 @@
+CREATE TABLE asset_checkouts (
+    id SERIAL PRIMARY KEY,
+    asset_item_name VARCHAR NOT NULL,
+    email VARCHAR NOT NULL,
+    checked_out_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    checked_in_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_asset_checkouts_item ON asset_checkouts(cio_company_id,asset_item_name);
+
+ALTER TABLE asset_checkouts ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
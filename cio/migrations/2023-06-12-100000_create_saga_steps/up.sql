@@ -0,0 +1,14 @@
+CREATE TABLE saga_steps (
+    id SERIAL PRIMARY KEY,
+    saga_id VARCHAR NOT NULL,
+    node_id INTEGER NOT NULL,
+    name VARCHAR NOT NULL DEFAULT '',
+    label VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL DEFAULT '',
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    error VARCHAR NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_saga_steps_node ON saga_steps(saga_id,node_id);
@@ -0,0 +1 @@
+ALTER TABLE companys DROP COLUMN barcode_symbology;
@@ -0,0 +1 @@
+ALTER TABLE companys ADD COLUMN barcode_symbology VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1 @@
+ALTER TABLE recorded_meetings ADD COLUMN participants TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
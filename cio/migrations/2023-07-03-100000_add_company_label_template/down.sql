@@ -0,0 +1 @@
+ALTER TABLE companys DROP COLUMN label_template;
@@ -0,0 +1 @@
+ALTER TABLE companys ADD COLUMN label_template VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1,3 @@
+ALTER TABLE inbound_shipments DROP COLUMN requested_by;
+ALTER TABLE inbound_shipments DROP COLUMN received_time;
+ALTER TABLE inbound_shipments DROP COLUMN received_by;
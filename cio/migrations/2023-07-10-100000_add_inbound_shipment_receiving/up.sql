@@ -0,0 +1,3 @@
+ALTER TABLE inbound_shipments ADD COLUMN requested_by VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE inbound_shipments ADD COLUMN received_time TIMESTAMPTZ;
+ALTER TABLE inbound_shipments ADD COLUMN received_by VARCHAR NOT NULL DEFAULT '';
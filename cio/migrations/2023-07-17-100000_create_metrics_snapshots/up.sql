@@ -0,0 +1,16 @@
+CREATE TABLE metrics_snapshots (
+    id SERIAL PRIMARY KEY,
+    week DATE NOT NULL,
+    headcount INTEGER NOT NULL DEFAULT 0,
+    open_roles INTEGER NOT NULL DEFAULT 0,
+    applicants_in_pipeline INTEGER NOT NULL DEFAULT 0,
+    assets_deployed INTEGER NOT NULL DEFAULT 0,
+    rfds_published INTEGER NOT NULL DEFAULT 0,
+    mailing_list_subscribers INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_metrics_snapshots_week ON metrics_snapshots(cio_company_id,week);
+
+ALTER TABLE metrics_snapshots ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,4 @@
+DROP TABLE asset_depreciations;
+ALTER TABLE asset_items DROP COLUMN purchase_date;
+ALTER TABLE asset_items DROP COLUMN depreciation_schedule;
+ALTER TABLE asset_items DROP COLUMN current_book_value;
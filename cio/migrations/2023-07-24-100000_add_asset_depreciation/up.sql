@@ -0,0 +1,22 @@
+ALTER TABLE asset_items ADD COLUMN purchase_date DATE;
+ALTER TABLE asset_items ADD COLUMN depreciation_schedule VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE asset_items ADD COLUMN current_book_value REAL NOT NULL DEFAULT 0;
+UPDATE asset_items SET current_book_value = purchase_price;
+
+CREATE TABLE asset_depreciations (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    month DATE NOT NULL,
+    asset_type VARCHAR NOT NULL DEFAULT '',
+    item_count INTEGER NOT NULL DEFAULT 0,
+    purchase_price REAL NOT NULL DEFAULT 0,
+    book_value REAL NOT NULL DEFAULT 0,
+    depreciation REAL NOT NULL DEFAULT 0,
+    accumulated_depreciation REAL NOT NULL DEFAULT 0,
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_asset_depreciations_name ON asset_depreciations(cio_company_id,name);
+
+ALTER TABLE asset_depreciations ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,4 @@
+DROP TABLE asset_tasks;
+ALTER TABLE asset_items DROP COLUMN warranty_expiration_date;
+ALTER TABLE asset_items DROP COLUMN maintenance_interval_days;
+ALTER TABLE asset_items DROP COLUMN last_maintenance_date;
@@ -0,0 +1,19 @@
+ALTER TABLE asset_items ADD COLUMN warranty_expiration_date DATE;
+ALTER TABLE asset_items ADD COLUMN maintenance_interval_days INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE asset_items ADD COLUMN last_maintenance_date DATE;
+
+CREATE TABLE asset_tasks (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    asset_item_name VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL DEFAULT '',
+    due_date DATE NOT NULL,
+    status VARCHAR NOT NULL DEFAULT '',
+    notes VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_asset_tasks_name ON asset_tasks(cio_company_id,name);
+
+ALTER TABLE asset_tasks ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
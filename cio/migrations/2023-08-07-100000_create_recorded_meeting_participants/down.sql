@@ -0,0 +1 @@
+DROP TABLE recorded_meeting_participants;
@@ -0,0 +1,11 @@
+CREATE TABLE recorded_meeting_participants (
+    id SERIAL PRIMARY KEY,
+    recorded_meeting_id INTEGER NOT NULL,
+    name VARCHAR NOT NULL DEFAULT '',
+    email VARCHAR NOT NULL DEFAULT '',
+    join_time TIMESTAMPTZ,
+    leave_time TIMESTAMPTZ,
+    duration INTEGER NOT NULL DEFAULT 0
+);
+
+ALTER TABLE recorded_meeting_participants ADD FOREIGN KEY (recorded_meeting_id) REFERENCES recorded_meetings(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1 @@
+ALTER TABLE companys ADD COLUMN slack_channel_security VARCHAR NOT NULL DEFAULT '';
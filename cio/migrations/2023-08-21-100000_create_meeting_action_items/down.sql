@@ -0,0 +1,3 @@
+DROP TABLE meeting_action_items;
+
+ALTER TABLE recorded_meetings DROP COLUMN slack_channel;
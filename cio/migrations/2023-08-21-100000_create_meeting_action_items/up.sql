@@ -0,0 +1,19 @@
+ALTER TABLE recorded_meetings ADD COLUMN slack_channel VARCHAR NOT NULL DEFAULT '';
+
+CREATE TABLE meeting_action_items (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    recorded_meeting_id INTEGER NOT NULL,
+    meeting_name VARCHAR NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT '',
+    assignee VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL DEFAULT '',
+    last_reminded_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_meeting_action_items_name ON meeting_action_items(cio_company_id,name);
+
+ALTER TABLE meeting_action_items ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+ALTER TABLE meeting_action_items ADD FOREIGN KEY (recorded_meeting_id) REFERENCES recorded_meetings(id) ON DELETE CASCADE ON UPDATE CASCADE;
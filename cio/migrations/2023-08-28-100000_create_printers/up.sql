@@ -0,0 +1,13 @@
+CREATE TABLE printers (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    location VARCHAR NOT NULL DEFAULT '',
+    kind VARCHAR NOT NULL,
+    url VARCHAR NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT false,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_printers_name ON printers(cio_company_id,name);
+
+ALTER TABLE printers ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,2 @@
+DROP TABLE asset_audit_scans;
+DROP TABLE asset_audits;
@@ -0,0 +1,34 @@
+CREATE TABLE asset_audits (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    location VARCHAR NOT NULL DEFAULT '',
+    started_by VARCHAR NOT NULL DEFAULT '',
+    started_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ,
+    expected_count INTEGER NOT NULL DEFAULT 0,
+    scanned_count INTEGER NOT NULL DEFAULT 0,
+    not_scanned TEXT [] NOT NULL DEFAULT '{}',
+    unknown_barcodes TEXT [] NOT NULL DEFAULT '{}',
+    location_mismatches TEXT [] NOT NULL DEFAULT '{}',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_asset_audits_name ON asset_audits(cio_company_id,name);
+
+ALTER TABLE asset_audits ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+CREATE TABLE asset_audit_scans (
+    id SERIAL PRIMARY KEY,
+    asset_audit_id INTEGER NOT NULL,
+    barcode VARCHAR NOT NULL,
+    asset_item_name VARCHAR NOT NULL DEFAULT '',
+    location VARCHAR NOT NULL DEFAULT '',
+    scanned_by VARCHAR NOT NULL DEFAULT '',
+    scanned_at TIMESTAMPTZ NOT NULL,
+    cio_company_id INTEGER NOT NULL
+);
+
+ALTER TABLE asset_audit_scans ADD FOREIGN KEY (asset_audit_id) REFERENCES asset_audits(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE asset_audit_scans ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
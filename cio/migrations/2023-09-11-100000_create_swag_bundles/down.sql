@@ -0,0 +1 @@
+DROP TABLE swag_bundles;
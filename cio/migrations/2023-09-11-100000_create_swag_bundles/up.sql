@@ -0,0 +1,12 @@
+CREATE TABLE swag_bundles (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    description VARCHAR NOT NULL DEFAULT '',
+    contents VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_swag_bundles_name ON swag_bundles(cio_company_id,name);
+
+ALTER TABLE swag_bundles ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1 @@
+DROP TABLE shopify_orders;
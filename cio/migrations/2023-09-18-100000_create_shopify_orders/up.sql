@@ -0,0 +1,16 @@
+CREATE TABLE shopify_orders (
+    id SERIAL PRIMARY KEY,
+    shopify_order_id BIGINT NOT NULL,
+    name VARCHAR NOT NULL DEFAULT '',
+    email VARCHAR NOT NULL DEFAULT '',
+    outbound_shipment_id INTEGER,
+    unmatched_items TEXT [] NOT NULL DEFAULT '{}',
+    synced_at TIMESTAMPTZ NOT NULL,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_shopify_orders_shopify_order_id ON shopify_orders(cio_company_id,shopify_order_id);
+
+ALTER TABLE shopify_orders ADD FOREIGN KEY (outbound_shipment_id) REFERENCES outbound_shipments(id) ON DELETE SET NULL ON UPDATE CASCADE;
+
+ALTER TABLE shopify_orders ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
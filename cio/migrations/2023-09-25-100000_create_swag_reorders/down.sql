@@ -0,0 +1,3 @@
+DROP TABLE swag_reorders;
+ALTER TABLE companys DROP COLUMN slack_channel_low_stock;
+ALTER TABLE swag_inventory_items DROP COLUMN reorder_threshold;
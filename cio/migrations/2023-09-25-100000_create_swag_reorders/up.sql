@@ -0,0 +1,24 @@
+ALTER TABLE swag_inventory_items ADD COLUMN reorder_threshold INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE companys ADD COLUMN slack_channel_low_stock VARCHAR NOT NULL DEFAULT '';
+
+CREATE TABLE swag_reorders (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    swag_inventory_item_id INTEGER NOT NULL,
+    item VARCHAR NOT NULL DEFAULT '',
+    size VARCHAR NOT NULL DEFAULT '',
+    current_stock INTEGER NOT NULL DEFAULT 0,
+    reorder_threshold INTEGER NOT NULL DEFAULT 0,
+    status VARCHAR NOT NULL DEFAULT '',
+    requested_at TIMESTAMPTZ NOT NULL,
+    link_to_inventory TEXT [] NOT NULL DEFAULT '{}',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_swag_reorders_name ON swag_reorders(cio_company_id,name);
+
+ALTER TABLE swag_reorders ADD FOREIGN KEY (swag_inventory_item_id) REFERENCES swag_inventory_items(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE swag_reorders ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
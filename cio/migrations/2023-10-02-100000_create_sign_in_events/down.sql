@@ -0,0 +1 @@
+DROP TABLE sign_in_events;
\ No newline at end of file
@@ -0,0 +1,20 @@
+CREATE TABLE sign_in_events (
+    id SERIAL PRIMARY KEY,
+    provider VARCHAR NOT NULL,
+    event_id VARCHAR NOT NULL,
+    email VARCHAR NOT NULL DEFAULT '',
+    ip_address VARCHAR NOT NULL DEFAULT '',
+    country VARCHAR NOT NULL DEFAULT '',
+    city VARCHAR NOT NULL DEFAULT '',
+    latitude DOUBLE PRECISION,
+    longitude DOUBLE PRECISION,
+    signed_in_at TIMESTAMPTZ NOT NULL,
+    anomaly VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sign_in_events_event_id ON sign_in_events(cio_company_id,provider,event_id);
+
+CREATE INDEX IF NOT EXISTS idx_sign_in_events_signed_in_at ON sign_in_events(cio_company_id,signed_in_at);
+
+ALTER TABLE sign_in_events ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
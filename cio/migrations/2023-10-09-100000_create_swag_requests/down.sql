@@ -0,0 +1 @@
+DROP TABLE swag_requests;
\ No newline at end of file
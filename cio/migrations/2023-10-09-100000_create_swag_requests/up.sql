@@ -0,0 +1,31 @@
+CREATE TABLE swag_requests (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    email VARCHAR NOT NULL,
+    item VARCHAR NOT NULL,
+    size VARCHAR NOT NULL DEFAULT '',
+    quantity INTEGER NOT NULL DEFAULT 0,
+    swag_inventory_item_id INTEGER NOT NULL,
+    phone VARCHAR NOT NULL DEFAULT '',
+    street_1 VARCHAR NOT NULL DEFAULT '',
+    street_2 VARCHAR NOT NULL DEFAULT '',
+    city VARCHAR NOT NULL DEFAULT '',
+    state VARCHAR NOT NULL DEFAULT '',
+    zipcode VARCHAR NOT NULL DEFAULT '',
+    country VARCHAR NOT NULL DEFAULT '',
+    reason VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL DEFAULT '',
+    requested_at TIMESTAMPTZ NOT NULL,
+    outbound_shipment_id INTEGER,
+    link_to_inventory TEXT [] NOT NULL DEFAULT '{}',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_swag_requests_email_requested_at ON swag_requests(cio_company_id,email,requested_at);
+
+ALTER TABLE swag_requests ADD FOREIGN KEY (swag_inventory_item_id) REFERENCES swag_inventory_items(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE swag_requests ADD FOREIGN KEY (outbound_shipment_id) REFERENCES outbound_shipments(id) ON DELETE SET NULL ON UPDATE CASCADE;
+
+ALTER TABLE swag_requests ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,3 @@
+ALTER TABLE barcode_scans DROP COLUMN action;
+
+ALTER TABLE barcode_scans DROP COLUMN quantity;
@@ -0,0 +1,3 @@
+ALTER TABLE barcode_scans ADD COLUMN action VARCHAR NOT NULL DEFAULT 'ship';
+
+ALTER TABLE barcode_scans ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,3 @@
+ALTER TABLE outbound_shipments DROP COLUMN service_level;
+
+ALTER TABLE outbound_shipments DROP COLUMN overdue;
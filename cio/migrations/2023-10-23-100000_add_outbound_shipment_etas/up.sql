@@ -0,0 +1,10 @@
+ALTER TABLE outbound_shipments ADD COLUMN service_level VARCHAR NOT NULL DEFAULT '';
+
+ALTER TABLE outbound_shipments ADD COLUMN overdue BOOLEAN NOT NULL DEFAULT false;
+
+-- The shipments we already have are not news, count the ones still on the way as already
+-- flagged so the first sync does not alert about every one of them that is late.
+UPDATE outbound_shipments SET overdue = true
+    WHERE local_pickup = false
+    AND delivered_time IS NULL
+    AND status NOT IN ('Delivered', 'Returned', 'Cancelled', 'Picked up');
@@ -0,0 +1 @@
+DROP TABLE asset_refresh_forecasts;
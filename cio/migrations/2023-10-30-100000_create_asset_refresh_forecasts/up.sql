@@ -0,0 +1,18 @@
+CREATE TABLE asset_refresh_forecasts (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    asset_item_id INTEGER NOT NULL,
+    asset_type VARCHAR NOT NULL DEFAULT '',
+    employee VARCHAR NOT NULL DEFAULT '',
+    purchase_date DATE NOT NULL,
+    refresh_date DATE NOT NULL,
+    quarter VARCHAR NOT NULL DEFAULT '',
+    estimated_cost REAL NOT NULL DEFAULT 0,
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_asset_refresh_forecasts_asset_item_id ON asset_refresh_forecasts(cio_company_id,asset_item_id);
+
+ALTER TABLE asset_refresh_forecasts ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+ALTER TABLE asset_refresh_forecasts ADD FOREIGN KEY (asset_item_id) REFERENCES asset_items(id) ON DELETE CASCADE ON UPDATE CASCADE;
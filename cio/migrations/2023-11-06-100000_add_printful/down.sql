@@ -0,0 +1,2 @@
+ALTER TABLE companys DROP COLUMN printful_api_key;
+ALTER TABLE swag_inventory_items DROP COLUMN printful_variant_id;
@@ -0,0 +1,2 @@
+ALTER TABLE companys ADD COLUMN printful_api_key VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE swag_inventory_items ADD COLUMN printful_variant_id VARCHAR NOT NULL DEFAULT '';
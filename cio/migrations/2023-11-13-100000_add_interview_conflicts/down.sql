@@ -0,0 +1,4 @@
+ALTER TABLE users DROP COLUMN previous_employers;
+ALTER TABLE applicants DROP COLUMN previous_employers;
+ALTER TABLE applicants DROP COLUMN referred_by;
+ALTER TABLE applicant_interviews DROP COLUMN conflicts;
@@ -0,0 +1,4 @@
+ALTER TABLE users ADD COLUMN previous_employers TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
+ALTER TABLE applicants ADD COLUMN previous_employers TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
+ALTER TABLE applicants ADD COLUMN referred_by VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE applicant_interviews ADD COLUMN conflicts TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
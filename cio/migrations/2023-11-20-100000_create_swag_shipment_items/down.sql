@@ -0,0 +1 @@
+DROP TABLE swag_shipment_items;
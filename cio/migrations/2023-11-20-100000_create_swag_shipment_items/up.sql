@@ -0,0 +1,16 @@
+CREATE TABLE swag_shipment_items (
+    id SERIAL PRIMARY KEY,
+    outbound_shipment_id INTEGER NOT NULL,
+    swag_inventory_item_id INTEGER NOT NULL,
+    quantity INTEGER NOT NULL DEFAULT 0,
+    subtracted_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_swag_shipment_items_outbound_shipment_id ON swag_shipment_items(outbound_shipment_id);
+
+ALTER TABLE swag_shipment_items ADD FOREIGN KEY (outbound_shipment_id) REFERENCES outbound_shipments(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE swag_shipment_items ADD FOREIGN KEY (swag_inventory_item_id) REFERENCES swag_inventory_items(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE swag_shipment_items ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
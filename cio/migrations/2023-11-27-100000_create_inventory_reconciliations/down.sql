@@ -0,0 +1,6 @@
+ALTER TABLE asset_items DROP COLUMN last_counted_date;
+
+ALTER TABLE swag_inventory_items DROP COLUMN counted_at;
+ALTER TABLE swag_inventory_items DROP COLUMN counted_stock;
+
+DROP TABLE inventory_reconciliations;
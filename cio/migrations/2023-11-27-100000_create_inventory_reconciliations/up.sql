@@ -0,0 +1,16 @@
+CREATE TABLE inventory_reconciliations (
+    id SERIAL PRIMARY KEY,
+    generated_at TIMESTAMPTZ NOT NULL,
+    item_count INTEGER NOT NULL DEFAULT 0,
+    flagged INTEGER NOT NULL DEFAULT 0,
+    csv_link VARCHAR NOT NULL DEFAULT '',
+    pdf_link VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL
+);
+
+ALTER TABLE inventory_reconciliations ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE swag_inventory_items ADD COLUMN counted_stock INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE swag_inventory_items ADD COLUMN counted_at TIMESTAMPTZ;
+
+ALTER TABLE asset_items ADD COLUMN last_counted_date DATE;
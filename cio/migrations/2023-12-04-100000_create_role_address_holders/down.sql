@@ -0,0 +1,3 @@
+DROP TABLE gsuite_aliases;
+
+DROP TABLE role_address_holders;
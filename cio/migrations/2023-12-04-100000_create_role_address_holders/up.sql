@@ -0,0 +1,24 @@
+CREATE TABLE role_address_holders (
+    id SERIAL PRIMARY KEY,
+    address VARCHAR NOT NULL,
+    username VARCHAR NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_role_address_holders_cio_company_id_address ON role_address_holders(cio_company_id, address);
+
+ALTER TABLE role_address_holders ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+CREATE TABLE gsuite_aliases (
+    id SERIAL PRIMARY KEY,
+    alias VARCHAR NOT NULL,
+    email VARCHAR NOT NULL,
+    is_group BOOLEAN NOT NULL DEFAULT false,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_gsuite_aliases_cio_company_id_alias ON gsuite_aliases(cio_company_id, alias);
+
+ALTER TABLE gsuite_aliases ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1 @@
+DROP TABLE swag_item_variants;
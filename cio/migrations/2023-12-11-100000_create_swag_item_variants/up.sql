@@ -0,0 +1,21 @@
+CREATE TABLE swag_item_variants (
+    id SERIAL PRIMARY KEY,
+    item VARCHAR NOT NULL DEFAULT '',
+    size VARCHAR NOT NULL DEFAULT '',
+    color VARCHAR NOT NULL DEFAULT '',
+    sku VARCHAR NOT NULL DEFAULT '',
+    barcode VARCHAR NOT NULL DEFAULT '',
+    barcode_png VARCHAR NOT NULL DEFAULT '',
+    barcode_svg VARCHAR NOT NULL DEFAULT '',
+    barcode_pdf_label VARCHAR NOT NULL DEFAULT '',
+    price REAL NOT NULL DEFAULT 0,
+    current_stock INTEGER NOT NULL DEFAULT 0,
+    print_barcode_label_quantity INTEGER NOT NULL DEFAULT 0,
+    link_to_item TEXT[] NOT NULL DEFAULT '{}',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_swag_item_variants_cio_company_id_sku ON swag_item_variants(cio_company_id, sku);
+
+ALTER TABLE swag_item_variants ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_rfds_full_text_search;
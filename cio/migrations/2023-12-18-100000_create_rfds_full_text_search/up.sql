@@ -0,0 +1,2 @@
+-- This has to be the same expression as the one cio searches the RFDs with.
+CREATE INDEX IF NOT EXISTS idx_rfds_full_text_search ON rfds USING GIN ((setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', html), 'B')));
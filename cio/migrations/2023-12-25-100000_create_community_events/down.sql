@@ -0,0 +1,3 @@
+DROP TABLE event_registrants;
+DROP TABLE community_events;
+ALTER TABLE companys DROP COLUMN eventbrite_api_key;
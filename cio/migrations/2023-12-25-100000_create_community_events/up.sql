@@ -0,0 +1,40 @@
+ALTER TABLE companys ADD COLUMN eventbrite_api_key VARCHAR NOT NULL DEFAULT '';
+
+CREATE TABLE community_events (
+    id SERIAL PRIMARY KEY,
+    provider VARCHAR NOT NULL,
+    provider_event_id VARCHAR NOT NULL,
+    name VARCHAR NOT NULL DEFAULT '',
+    starts_at TIMESTAMPTZ,
+    ends_at TIMESTAMPTZ,
+    location VARCHAR NOT NULL DEFAULT '',
+    url VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_community_events_provider_event ON community_events(provider, provider_event_id);
+
+ALTER TABLE community_events ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+CREATE TABLE event_registrants (
+    id SERIAL PRIMARY KEY,
+    provider VARCHAR NOT NULL,
+    provider_registration_id VARCHAR NOT NULL,
+    provider_event_id VARCHAR NOT NULL,
+    event_name VARCHAR NOT NULL DEFAULT '',
+    email VARCHAR NOT NULL,
+    first_name VARCHAR NOT NULL DEFAULT '',
+    last_name VARCHAR NOT NULL DEFAULT '',
+    status VARCHAR NOT NULL,
+    checked_in BOOLEAN NOT NULL DEFAULT false,
+    marketing_consent BOOLEAN NOT NULL DEFAULT false,
+    consented_at TIMESTAMPTZ,
+    registered_at TIMESTAMPTZ NOT NULL,
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_event_registrants_provider_registration ON event_registrants(provider, provider_registration_id);
+
+ALTER TABLE event_registrants ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
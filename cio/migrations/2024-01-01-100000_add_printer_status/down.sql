@@ -0,0 +1,4 @@
+ALTER TABLE printers DROP COLUMN status_checked_at;
+ALTER TABLE printers DROP COLUMN status_alert;
+
+ALTER TABLE companys DROP COLUMN slack_channel_office;
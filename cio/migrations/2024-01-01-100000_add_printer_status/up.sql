@@ -0,0 +1,4 @@
+ALTER TABLE companys ADD COLUMN slack_channel_office VARCHAR NOT NULL DEFAULT '';
+
+ALTER TABLE printers ADD COLUMN status_alert VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE printers ADD COLUMN status_checked_at TIMESTAMPTZ;
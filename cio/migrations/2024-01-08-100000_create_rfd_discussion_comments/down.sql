@@ -0,0 +1,2 @@
+DROP TABLE rfd_discussion_comments;
+ALTER TABLE rfds DROP COLUMN unresolved_discussion_threads;
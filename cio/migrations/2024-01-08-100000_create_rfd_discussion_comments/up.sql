@@ -0,0 +1,23 @@
+ALTER TABLE rfds ADD COLUMN unresolved_discussion_threads INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE rfd_discussion_comments (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    kind VARCHAR NOT NULL,
+    github_id BIGINT NOT NULL,
+    thread_id BIGINT NOT NULL,
+    author VARCHAR NOT NULL DEFAULT '',
+    body TEXT NOT NULL DEFAULT '',
+    path VARCHAR NOT NULL DEFAULT '',
+    html_url VARCHAR NOT NULL DEFAULT '',
+    outdated BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    link_to_rfd TEXT [] NOT NULL DEFAULT '{}',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_rfd_discussion_comments_github_id ON rfd_discussion_comments(cio_company_id,kind,github_id);
+
+ALTER TABLE rfd_discussion_comments ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1 @@
+ALTER TABLE companys ADD COLUMN slack_channel_rfds VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1 @@
+ALTER TABLE users ADD COLUMN muted_notifications TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
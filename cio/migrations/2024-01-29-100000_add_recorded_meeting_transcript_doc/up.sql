@@ -0,0 +1 @@
+ALTER TABLE recorded_meetings ADD COLUMN transcript_doc VARCHAR NOT NULL DEFAULT '';
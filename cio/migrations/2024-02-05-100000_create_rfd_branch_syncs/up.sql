@@ -0,0 +1,12 @@
+CREATE TABLE rfd_branch_syncs (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    branch VARCHAR NOT NULL,
+    commit_sha VARCHAR NOT NULL,
+    synced_at TIMESTAMPTZ NOT NULL,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_rfd_branch_syncs_branch ON rfd_branch_syncs(cio_company_id,rfd_number,branch);
+
+ALTER TABLE rfd_branch_syncs ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1 @@
+ALTER TABLE companys DROP COLUMN rfd_pdf_renderer;
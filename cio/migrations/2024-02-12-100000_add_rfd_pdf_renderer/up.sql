@@ -0,0 +1 @@
+ALTER TABLE companys ADD COLUMN rfd_pdf_renderer VARCHAR NOT NULL DEFAULT '';
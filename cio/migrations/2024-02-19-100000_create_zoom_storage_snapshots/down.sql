@@ -0,0 +1,2 @@
+DROP TABLE zoom_storage_snapshots;
+ALTER TABLE companys DROP COLUMN zoom_cloud_storage_limit_gb;
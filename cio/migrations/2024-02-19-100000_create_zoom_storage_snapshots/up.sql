@@ -0,0 +1,14 @@
+ALTER TABLE companys ADD COLUMN zoom_cloud_storage_limit_gb INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE zoom_storage_snapshots (
+    id SERIAL PRIMARY KEY,
+    used_bytes BIGINT NOT NULL,
+    recordings INTEGER NOT NULL,
+    limit_bytes BIGINT NOT NULL,
+    taken_at TIMESTAMPTZ NOT NULL,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_zoom_storage_snapshots_taken_at ON zoom_storage_snapshots(cio_company_id,taken_at);
+
+ALTER TABLE zoom_storage_snapshots ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
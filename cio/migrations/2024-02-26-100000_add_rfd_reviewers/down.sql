@@ -0,0 +1,3 @@
+ALTER TABLE rfds DROP COLUMN reviewers;
+ALTER TABLE rfds DROP COLUMN approvals;
+ALTER TABLE companys DROP COLUMN rfd_review_reminder_days;
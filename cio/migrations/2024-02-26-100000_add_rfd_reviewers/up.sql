@@ -0,0 +1,3 @@
+ALTER TABLE rfds ADD COLUMN reviewers TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
+ALTER TABLE rfds ADD COLUMN approvals TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
+ALTER TABLE companys ADD COLUMN rfd_review_reminder_days INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+ALTER TABLE recorded_meetings DROP COLUMN location_id;
+ALTER TABLE asset_audits DROP COLUMN location_id;
+ALTER TABLE users DROP COLUMN location_id;
+ALTER TABLE package_pickups DROP COLUMN location_id;
+ALTER TABLE printers DROP COLUMN location_id;
+DROP TABLE locations;
@@ -0,0 +1,22 @@
+CREATE TABLE locations (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL,
+    address VARCHAR NOT NULL DEFAULT '',
+    timezone VARCHAR NOT NULL DEFAULT '',
+    building VARCHAR NOT NULL DEFAULT '',
+    aliases TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[],
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_locations_name ON locations(cio_company_id,name);
+
+ALTER TABLE locations ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- The existing free-form locations are matched to the locations by the configs sync, once
+-- the locations are in the configs.
+ALTER TABLE recorded_meetings ADD COLUMN location_id INTEGER REFERENCES locations(id) ON DELETE SET NULL;
+ALTER TABLE asset_audits ADD COLUMN location_id INTEGER REFERENCES locations(id) ON DELETE SET NULL;
+ALTER TABLE users ADD COLUMN location_id INTEGER REFERENCES locations(id) ON DELETE SET NULL;
+ALTER TABLE package_pickups ADD COLUMN location_id INTEGER REFERENCES locations(id) ON DELETE SET NULL;
+ALTER TABLE printers ADD COLUMN location_id INTEGER REFERENCES locations(id) ON DELETE SET NULL;
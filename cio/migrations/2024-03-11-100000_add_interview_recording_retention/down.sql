@@ -0,0 +1,2 @@
+ALTER TABLE companys DROP COLUMN interview_recording_retention_days;
+ALTER TABLE recorded_meeting_purges DROP COLUMN reason;
@@ -0,0 +1,2 @@
+ALTER TABLE companys ADD COLUMN interview_recording_retention_days INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE recorded_meeting_purges ADD COLUMN reason VARCHAR NOT NULL DEFAULT 'retention';
@@ -0,0 +1,2 @@
+DROP TABLE reconcile_plans;
+ALTER TABLE groups DROP COLUMN okta_apps;
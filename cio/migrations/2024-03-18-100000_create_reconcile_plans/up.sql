@@ -0,0 +1,16 @@
+CREATE TABLE reconcile_plans (
+    id SERIAL PRIMARY KEY,
+    reconciler VARCHAR NOT NULL,
+    changes VARCHAR NOT NULL,
+    status VARCHAR NOT NULL,
+    error VARCHAR NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    applied_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reconcile_plans_status ON reconcile_plans(cio_company_id,reconciler,status);
+
+ALTER TABLE reconcile_plans ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE groups ADD COLUMN okta_apps TEXT[] NOT NULL DEFAULT '{}';
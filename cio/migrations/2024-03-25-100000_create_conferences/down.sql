@@ -0,0 +1,3 @@
+DROP TABLE conference_leads;
+DROP TABLE conference_tasks;
+DROP TABLE conferences;
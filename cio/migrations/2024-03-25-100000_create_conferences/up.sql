@@ -0,0 +1,67 @@
+CREATE TABLE conferences (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    starts_on DATE,
+    ends_on DATE,
+    location VARCHAR NOT NULL DEFAULT '',
+    url VARCHAR NOT NULL DEFAULT '',
+    sponsorship_level VARCHAR NOT NULL DEFAULT '',
+    booth_staff TEXT [] NOT NULL DEFAULT '{}',
+    swag_bundles TEXT [] NOT NULL DEFAULT '{}',
+    ship_to_name VARCHAR NOT NULL DEFAULT '',
+    ship_to_email VARCHAR NOT NULL DEFAULT '',
+    ship_to_phone VARCHAR NOT NULL DEFAULT '',
+    ship_to_street_1 VARCHAR NOT NULL DEFAULT '',
+    ship_to_street_2 VARCHAR NOT NULL DEFAULT '',
+    ship_to_city VARCHAR NOT NULL DEFAULT '',
+    ship_to_state VARCHAR NOT NULL DEFAULT '',
+    ship_to_zipcode VARCHAR NOT NULL DEFAULT '',
+    ship_to_country VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_conferences_name ON conferences(cio_company_id,name);
+CREATE INDEX IF NOT EXISTS idx_conferences_airtable_record_id ON conferences(cio_company_id,airtable_record_id);
+
+ALTER TABLE conferences ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+CREATE TABLE conference_tasks (
+    id SERIAL PRIMARY KEY,
+    conference_id INTEGER NOT NULL,
+    conference VARCHAR NOT NULL DEFAULT '',
+    key VARCHAR NOT NULL,
+    title VARCHAR NOT NULL,
+    assignee VARCHAR NOT NULL DEFAULT '',
+    due_on DATE NOT NULL,
+    swag_bundle VARCHAR NOT NULL DEFAULT '',
+    outbound_shipment_id INTEGER,
+    completed_at TIMESTAMPTZ,
+    notified_at TIMESTAMPTZ,
+    error VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_conference_tasks_key ON conference_tasks(cio_company_id,conference_id,key);
+
+ALTER TABLE conference_tasks ADD FOREIGN KEY (conference_id) REFERENCES conferences(id) ON DELETE CASCADE ON UPDATE CASCADE;
+ALTER TABLE conference_tasks ADD FOREIGN KEY (outbound_shipment_id) REFERENCES outbound_shipments(id) ON DELETE SET NULL ON UPDATE CASCADE;
+ALTER TABLE conference_tasks ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+CREATE TABLE conference_leads (
+    id SERIAL PRIMARY KEY,
+    conference VARCHAR NOT NULL,
+    email VARCHAR NOT NULL,
+    first_name VARCHAR NOT NULL DEFAULT '',
+    last_name VARCHAR NOT NULL DEFAULT '',
+    organization VARCHAR NOT NULL DEFAULT '',
+    job_title VARCHAR NOT NULL DEFAULT '',
+    notes VARCHAR NOT NULL DEFAULT '',
+    cio_company_id INTEGER NOT NULL,
+    airtable_record_id VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_conference_leads_email ON conference_leads(cio_company_id,conference,email);
+
+ALTER TABLE conference_leads ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,20 @@
+CREATE TABLE rfd_links (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    referenced_rfd_number INTEGER NOT NULL,
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rfd_links_rfd ON rfd_links(cio_company_id,rfd_number);
+
+ALTER TABLE rfd_links ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- Link the RFDs we already have, the same way rfd_references does, so the graph is right
+-- before every RFD is updated again.
+INSERT INTO rfd_links (rfd_number, referenced_rfd_number, cio_company_id)
+SELECT DISTINCT refs.number, refs.referenced_rfd_number, refs.cio_company_id
+FROM (
+    SELECT rfds.number, rfds.cio_company_id, COALESCE(m[1], m[2])::INTEGER AS referenced_rfd_number
+    FROM rfds, regexp_matches(rfds.content, '\yrfd[ /-]?0*(\d{1,4})\y|\y0*(\d{1,4})\.rfd\.', 'gi') AS m
+) refs
+WHERE refs.referenced_rfd_number > 0 AND refs.referenced_rfd_number <> refs.number;
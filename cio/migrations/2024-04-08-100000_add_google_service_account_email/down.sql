@@ -0,0 +1 @@
+ALTER TABLE companys DROP COLUMN google_service_account_email;
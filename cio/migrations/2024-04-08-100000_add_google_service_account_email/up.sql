@@ -0,0 +1 @@
+ALTER TABLE companys ADD COLUMN google_service_account_email VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1,13 @@
+CREATE TABLE rfd_number_reservations (
+    id SERIAL PRIMARY KEY,
+    number INTEGER NOT NULL,
+    title VARCHAR NOT NULL DEFAULT '',
+    reserved_by VARCHAR NOT NULL DEFAULT '',
+    reserved_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL,
+    UNIQUE (cio_company_id, number)
+);
+
+ALTER TABLE rfd_number_reservations ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
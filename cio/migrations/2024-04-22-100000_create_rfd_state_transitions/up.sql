@@ -0,0 +1,12 @@
+CREATE TABLE rfd_state_transitions (
+    id SERIAL PRIMARY KEY,
+    rfd_number INTEGER NOT NULL,
+    from_state VARCHAR NOT NULL DEFAULT '',
+    to_state VARCHAR NOT NULL,
+    transitioned_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL
+);
+
+CREATE INDEX rfd_state_transitions_rfd_number ON rfd_state_transitions (cio_company_id, rfd_number);
+
+ALTER TABLE rfd_state_transitions ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
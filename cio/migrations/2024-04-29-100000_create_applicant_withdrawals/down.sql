@@ -0,0 +1,2 @@
+DROP TABLE applicant_withdrawals;
+ALTER TABLE applicant_interviews DROP COLUMN cancelled_at;
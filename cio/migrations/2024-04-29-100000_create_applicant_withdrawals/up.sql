@@ -0,0 +1,17 @@
+CREATE TABLE applicant_withdrawals (
+    id SERIAL PRIMARY KEY,
+    applicant_id INTEGER NOT NULL REFERENCES applicants(id) ON DELETE CASCADE,
+    email VARCHAR NOT NULL,
+    role VARCHAR NOT NULL DEFAULT '',
+    stage VARCHAR NOT NULL DEFAULT '',
+    reason TEXT NOT NULL DEFAULT '',
+    reason_category VARCHAR NOT NULL DEFAULT '',
+    source VARCHAR NOT NULL DEFAULT '',
+    withdrawn_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    confirmed_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL
+);
+
+ALTER TABLE applicant_withdrawals ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE applicant_interviews ADD COLUMN cancelled_at TIMESTAMPTZ;
@@ -0,0 +1,3 @@
+DROP TABLE greenhouse_applications;
+ALTER TABLE companys DROP COLUMN greenhouse_api_key;
+ALTER TABLE companys DROP COLUMN greenhouse_user_id;
@@ -0,0 +1,15 @@
+ALTER TABLE companys ADD COLUMN greenhouse_api_key VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE companys ADD COLUMN greenhouse_user_id VARCHAR NOT NULL DEFAULT '';
+
+CREATE TABLE greenhouse_applications (
+    id SERIAL PRIMARY KEY,
+    greenhouse_application_id BIGINT NOT NULL,
+    greenhouse_candidate_id BIGINT NOT NULL,
+    applicant_id INTEGER NOT NULL REFERENCES applicants(id) ON DELETE CASCADE,
+    synced_status VARCHAR NOT NULL DEFAULT '',
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL,
+    UNIQUE (cio_company_id, greenhouse_application_id)
+);
+
+ALTER TABLE greenhouse_applications ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,2 @@
+DROP TABLE lever_opportunities;
+ALTER TABLE companys DROP COLUMN lever_api_key;
@@ -0,0 +1,13 @@
+ALTER TABLE companys ADD COLUMN lever_api_key VARCHAR NOT NULL DEFAULT '';
+
+CREATE TABLE lever_opportunities (
+    id SERIAL PRIMARY KEY,
+    lever_opportunity_id VARCHAR NOT NULL,
+    applicant_id INTEGER NOT NULL REFERENCES applicants(id) ON DELETE CASCADE,
+    synced_stage VARCHAR NOT NULL DEFAULT '',
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    cio_company_id INTEGER NOT NULL,
+    UNIQUE (cio_company_id, lever_opportunity_id)
+);
+
+ALTER TABLE lever_opportunities ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
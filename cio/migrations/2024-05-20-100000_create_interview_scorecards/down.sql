@@ -0,0 +1,3 @@
+DROP TABLE interview_scorecards;
+ALTER TABLE applicants DROP COLUMN interview_scorecard_average;
+ALTER TABLE applicants DROP COLUMN interview_scorecards_count;
@@ -0,0 +1,20 @@
+ALTER TABLE applicants ADD COLUMN interview_scorecards_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE applicants ADD COLUMN interview_scorecard_average REAL NOT NULL DEFAULT 0;
+
+CREATE TABLE interview_scorecards (
+    id SERIAL PRIMARY KEY,
+    interview_id INTEGER NOT NULL REFERENCES applicant_interviews(id) ON DELETE CASCADE,
+    applicant_id INTEGER NOT NULL REFERENCES applicants(id) ON DELETE CASCADE,
+    interviewer VARCHAR NOT NULL,
+    technical_rating INTEGER NOT NULL DEFAULT 0,
+    communication_rating INTEGER NOT NULL DEFAULT 0,
+    values_rating INTEGER NOT NULL DEFAULT 0,
+    overall_rating INTEGER NOT NULL DEFAULT 0,
+    notes TEXT NOT NULL DEFAULT '',
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    submitted_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL,
+    UNIQUE (interview_id, interviewer)
+);
+
+ALTER TABLE interview_scorecards ADD FOREIGN KEY (cio_company_id) REFERENCES companys(id) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -0,0 +1,3 @@
+ALTER TABLE recorded_meetings DROP COLUMN thumbnail;
+ALTER TABLE recorded_meetings DROP COLUMN transcript_file;
+ALTER TABLE recorded_meetings DROP COLUMN chat_log_file;
@@ -0,0 +1,3 @@
+ALTER TABLE recorded_meetings ADD COLUMN chat_log_file VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE recorded_meetings ADD COLUMN transcript_file VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE recorded_meetings ADD COLUMN thumbnail VARCHAR NOT NULL DEFAULT '';
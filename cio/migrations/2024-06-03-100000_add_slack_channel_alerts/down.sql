@@ -0,0 +1 @@
+ALTER TABLE companys DROP COLUMN slack_channel_alerts;
@@ -0,0 +1,2 @@
+ALTER TABLE drive_uploads DROP COLUMN zoom_recording_id;
+ALTER TABLE drive_uploads DROP COLUMN zoom_meeting_id;
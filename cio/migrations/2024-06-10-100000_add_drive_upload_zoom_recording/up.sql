@@ -0,0 +1,2 @@
+ALTER TABLE drive_uploads ADD COLUMN zoom_meeting_id VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE drive_uploads ADD COLUMN zoom_recording_id VARCHAR NOT NULL DEFAULT '';
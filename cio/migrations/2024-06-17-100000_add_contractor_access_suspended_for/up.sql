@@ -0,0 +1 @@
+ALTER TABLE users ADD COLUMN access_suspended_for DATE DEFAULT NULL;
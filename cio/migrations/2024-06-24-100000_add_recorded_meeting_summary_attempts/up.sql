@@ -0,0 +1 @@
+ALTER TABLE recorded_meetings ADD COLUMN summary_attempts INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE recorded_meetings DROP COLUMN transcript_callback_secret;
@@ -0,0 +1 @@
+ALTER TABLE recorded_meetings ADD COLUMN transcript_callback_secret VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1 @@
+ALTER TABLE asset_items DROP COLUMN barcode_symbology;
@@ -0,0 +1 @@
+ALTER TABLE asset_items ADD COLUMN barcode_symbology VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1 @@
+ALTER TABLE meeting_action_items DROP COLUMN reminder_count;
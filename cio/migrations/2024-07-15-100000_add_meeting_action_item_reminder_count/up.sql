@@ -0,0 +1 @@
+ALTER TABLE meeting_action_items ADD COLUMN reminder_count INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+CREATE TABLE rate_limit_windows (
+    provider VARCHAR NOT NULL,
+    window_start BIGINT NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (provider, window_start)
+);
@@ -0,0 +1 @@
+ALTER TABLE asset_items DROP COLUMN snipeit_asset_tag;
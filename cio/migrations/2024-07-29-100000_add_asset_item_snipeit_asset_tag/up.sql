@@ -0,0 +1 @@
+ALTER TABLE asset_items ADD COLUMN snipeit_asset_tag VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1 @@
+ALTER TABLE companys DROP COLUMN zoom_account_id;
@@ -0,0 +1 @@
+ALTER TABLE companys ADD COLUMN zoom_account_id VARCHAR NOT NULL DEFAULT '';
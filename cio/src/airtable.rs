@@ -1,4 +1,9 @@
 pub static AIRTABLE_MAILING_LIST_SIGNUPS_TABLE: &str = "Mailing List Signups";
+pub static AIRTABLE_COMMUNITY_EVENTS_TABLE: &str = "Events";
+pub static AIRTABLE_EVENT_REGISTRANTS_TABLE: &str = "Event Registrants";
+pub static AIRTABLE_CONFERENCES_TABLE: &str = "Conferences";
+pub static AIRTABLE_CONFERENCE_LEADS_TABLE: &str = "Conference Leads";
+pub static AIRTABLE_CONFERENCE_TASKS_TABLE: &str = "Conference Tasks";
 pub static AIRTABLE_RACK_LINE_SIGNUPS_TABLE: &str = "Rack Line Signups";
 pub static AIRTABLE_CUSTOMER_INTERACTIONS_TABLE: &str = "Interactions";
 pub static AIRTABLE_AUTH_USERS_TABLE: &str = "Auth Users";
@@ -16,13 +21,18 @@ pub static AIRTABLE_JOURNAL_CLUB_MEETINGS_TABLE: &str = "Journal Club Meetings";
 pub static AIRTABLE_JOURNAL_CLUB_PAPERS_TABLE: &str = "Journal Club Papers";
 pub static AIRTABLE_GITHUB_REPOS_TABLE: &str = "GitHub Repos";
 pub static AIRTABLE_RECORDED_MEETINGS_TABLE: &str = "Recorded Meetings";
+pub static AIRTABLE_MEETING_ACTION_ITEMS_TABLE: &str = "Action Items";
+pub static AIRTABLE_INCIDENTS_TABLE: &str = "Incidents";
+pub static AIRTABLE_FOCUS_TIME_CONFLICTS_TABLE: &str = "Focus Time Conflicts";
 
 pub static AIRTABLE_RFD_TABLE: &str = "RFDs";
+pub static AIRTABLE_RFD_DISCUSSION_COMMENTS_TABLE: &str = "Discussion Comments";
 
 pub static AIRTABLE_APPLICATIONS_TABLE: &str = "Applicants";
 pub static AIRTABLE_INTERVIEWS_TABLE: &str = "Interviews";
 pub static AIRTABLE_REVIEWER_LEADERBOARD_TABLE: &str = "Reviewer Leaderboard";
 pub static AIRTABLE_REVIEWS_TABLE: &str = "Reviews";
+pub static AIRTABLE_ROLES_TABLE: &str = "Roles";
 
 pub static AIRTABLE_DISCUSSION_TOPICS_TABLE: &str = "Discussion topics";
 pub static AIRTABLE_MEETING_SCHEDULE_TABLE: &str = "Meeting schedule";
@@ -35,12 +45,20 @@ pub static AIRTABLE_SOFTWARE_VENDORS_TABLE: &str = "Vendors";
 pub static AIRTABLE_CREDIT_CARD_TRANSACTIONS_TABLE: &str = "Credit Card Transactions";
 pub static AIRTABLE_ACCOUNTS_PAYABLE_TABLE: &str = "Accounts Payable";
 pub static AIRTABLE_EXPENSED_ITEMS_TABLE: &str = "Expensed Items";
+pub static AIRTABLE_ASSET_DEPRECIATION_TABLE: &str = "Asset Depreciation";
+pub static AIRTABLE_ASSET_REFRESH_FORECAST_TABLE: &str = "Refresh Forecast";
 
 pub static AIRTABLE_SWAG_INVENTORY_ITEMS_TABLE: &str = "Inventory";
 pub static AIRTABLE_BARCODE_SCANS_TABLE: &str = "Barcode Scans";
 pub static AIRTABLE_SWAG_ITEMS_TABLE: &str = "Items";
+pub static AIRTABLE_SWAG_ITEM_VARIANTS_TABLE: &str = "Variants";
+pub static AIRTABLE_SWAG_BUNDLES_TABLE: &str = "Bundles";
+pub static AIRTABLE_SWAG_REORDERS_TABLE: &str = "To Order";
+pub static AIRTABLE_SWAG_REQUESTS_TABLE: &str = "Requests";
 
 pub static AIRTABLE_ASSET_ITEMS_TABLE: &str = "Items";
+pub static AIRTABLE_ASSET_AUDITS_TABLE: &str = "Audits";
+pub static AIRTABLE_ASSET_TASKS_TABLE: &str = "Tasks";
 
 pub static AIRTABLE_API_TOKENS_TABLE: &str = "API Tokens";
 pub static AIRTABLE_COMPANIES_TABLE: &str = "Companies";
@@ -0,0 +1,641 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl};
+use log::info;
+use md5::{Digest, Md5};
+
+use crate::{
+    applicants::Applicants,
+    companies::Company,
+    configs::Users,
+    db::Database,
+    mailing_list::MailingListSubscribers,
+    recorded_meetings::RecordedMeetings,
+    schema::{
+        api_tokens, applicant_interviews, applicant_reviewers, applicant_reviews, applicant_withdrawals, applicants,
+        asset_checkouts, asset_items, asset_reservations, auth_user_logins, auth_users, conference_leads,
+        conference_tasks, conferences, credit_card_transactions, event_registrants, expensed_items,
+        focus_time_conflicts, incidents, interview_scorecards, meeting_action_items, page_views, rack_line_subscribers,
+        recorded_meeting_attendees, recorded_meeting_participants, recorded_meeting_transcript_segments,
+        recorded_meetings, role_address_holders, shopify_orders, sign_in_events, swag_requests, upload_tokens,
+    },
+    shipments::OutboundShipments,
+};
+
+/// Rewrite every distinct value of a column of a table to its fake, along with the other
+/// columns given, from the real value. Rewriting by value rather than by row keeps the same
+/// person on the same fake across every row and table. Returns the number of values.
+macro_rules! anonymize_values {
+    ($db:expr, $table:ident, $filter:expr, $column:ident => $fake:expr $(, $other:ident => $fake_other:expr)*) => {{
+        let values = $table::dsl::$table
+            .filter($filter)
+            .select($table::dsl::$column)
+            .distinct()
+            .load_async::<String>($db.pool())
+            .await?;
+        for value in values.iter().filter(|v| !v.trim().is_empty()) {
+            diesel::update($table::dsl::$table)
+                .filter($filter)
+                .filter($table::dsl::$column.eq(value.to_string()))
+                .set((
+                    $table::dsl::$column.eq($fake(value)),
+                    $($table::dsl::$other.eq($fake_other(value)),)*
+                ))
+                .execute_async($db.pool())
+                .await?;
+        }
+        values.len()
+    }};
+}
+
+/// Rewrite a column of every row of a table to its fake, for the values that do not
+/// identify the person on their own, like free-form notes or lists of emails. Returns the
+/// number of rows.
+macro_rules! anonymize_rows {
+    ($db:expr, $table:ident, $filter:expr, $column:ident: $type:ty => $fake:expr) => {{
+        let rows = $table::dsl::$table
+            .filter($filter)
+            .select(($table::dsl::id, $table::dsl::$column))
+            .load_async::<(i32, $type)>($db.pool())
+            .await?;
+        for (id, value) in &rows {
+            diesel::update($table::dsl::$table)
+                .filter($table::dsl::id.eq(*id))
+                .set($table::dsl::$column.eq($fake(value)))
+                .execute_async($db.pool())
+                .await?;
+        }
+        rows.len()
+    }};
+}
+
+static FIRST_NAMES: &[&str] = &[
+    "Ada",
+    "Alan",
+    "Barbara",
+    "Charles",
+    "Dennis",
+    "Donald",
+    "Edsger",
+    "Frances",
+    "Grace",
+    "Hedy",
+    "Ivan",
+    "Jean",
+    "John",
+    "Ken",
+    "Leslie",
+    "Lynn",
+    "Margaret",
+    "Niklaus",
+    "Radia",
+    "Robert",
+    "Sophie",
+    "Tim",
+    "Whitfield",
+    "Yukihiro",
+];
+
+static LAST_NAMES: &[&str] = &[
+    "Allen",
+    "Backus",
+    "Bartik",
+    "Cerf",
+    "Clarke",
+    "Dijkstra",
+    "Engelbart",
+    "Floyd",
+    "Hamilton",
+    "Hopper",
+    "Kernighan",
+    "Knuth",
+    "Lamport",
+    "Liskov",
+    "Lovelace",
+    "McCarthy",
+    "Perlman",
+    "Ritchie",
+    "Shannon",
+    "Sutherland",
+    "Thompson",
+    "Turing",
+    "Wilson",
+    "Wirth",
+];
+
+static STREETS: &[&str] = &[
+    "Main St",
+    "Oak Ave",
+    "Pine St",
+    "Maple Ave",
+    "Cedar Ln",
+    "Elm St",
+    "Park Blvd",
+    "Lake Dr",
+    "Hill Rd",
+    "River Rd",
+];
+
+static WORDS: &[&str] = &[
+    "the", "system", "rack", "we", "should", "ship", "board", "firmware", "service", "test", "today", "next", "week",
+    "review", "design", "update", "and", "think", "about", "that", "power", "control", "plane", "is", "working",
+];
+
+/// The domain used for fake email addresses that do not belong to the company.
+pub static ANONYMIZED_EMAIL_DOMAIN: &str = "example.com";
+
+/// Rewrites PII with realistic fakes. The fakes are derived from a hash of the real value
+/// and a salt, so the same real value always maps to the same fake value. This keeps
+/// references between tables (ie. an applicant's email in a review) intact.
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    salt: String,
+    company_domains: Vec<String>,
+}
+
+impl Anonymizer {
+    pub fn new(salt: &str, company: &Company) -> Self {
+        Anonymizer {
+            salt: salt.to_string(),
+            company_domains: vec![company.domain.to_lowercase(), company.gsuite_domain.to_lowercase()],
+        }
+    }
+
+    fn hash(&self, kind: &str, value: &str) -> u64 {
+        let mut hasher = Md5::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(kind.as_bytes());
+        hasher.update(value.trim().to_lowercase().as_bytes());
+        let result = hasher.finalize();
+
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&result[..8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn pick(&self, kind: &str, value: &str, list: &[&'static str]) -> &'static str {
+        list[(self.hash(kind, value) % list.len() as u64) as usize]
+    }
+
+    /// Return a fake first and last name for a key. The key should be the most stable
+    /// identifier we have for the person, ideally their email.
+    pub fn fake_name(&self, key: &str) -> (String, String) {
+        (
+            self.pick("first_name", key, FIRST_NAMES).to_string(),
+            self.pick("last_name", key, LAST_NAMES).to_string(),
+        )
+    }
+
+    /// Return the fake full name for a key, like `fake_name`.
+    pub fn fake_full_name(&self, key: &str) -> String {
+        let (first_name, last_name) = self.fake_name(key);
+        format!("{} {}", first_name, last_name)
+    }
+
+    /// Return a fake username for a real username or email local part.
+    pub fn fake_username(&self, real: &str) -> String {
+        if real.is_empty() {
+            return String::new();
+        }
+
+        let (first, last) = self.fake_name(real);
+        format!(
+            "{}.{}{}",
+            first.to_lowercase(),
+            last.to_lowercase(),
+            self.hash("username", real) % 1000
+        )
+    }
+
+    /// Return a fake email for a real email. Emails in the company's domains keep their
+    /// domain so that they still resolve to users in staging.
+    pub fn fake_email(&self, real: &str) -> String {
+        let real = real.trim().to_lowercase();
+        if real.is_empty() {
+            return String::new();
+        }
+
+        let (local, domain) = match real.split_once('@') {
+            Some((local, domain)) => (local.to_string(), domain.to_string()),
+            None => (real.to_string(), String::new()),
+        };
+
+        let domain = if self.company_domains.contains(&domain) {
+            domain
+        } else {
+            ANONYMIZED_EMAIL_DOMAIN.to_string()
+        };
+
+        // Hash the full email for external addresses so that the same local part at two
+        // different domains does not collide.
+        let key = if domain == ANONYMIZED_EMAIL_DOMAIN {
+            real.to_string()
+        } else {
+            local
+        };
+
+        format!("{}@{}", self.fake_username(&key), domain)
+    }
+
+    /// Return a fake phone number.
+    pub fn fake_phone(&self, real: &str) -> String {
+        if real.is_empty() {
+            return String::new();
+        }
+
+        format!("+1 555-01{:02}", self.hash("phone", real) % 100)
+    }
+
+    /// Return a fake street address.
+    pub fn fake_street(&self, real: &str) -> String {
+        if real.is_empty() {
+            return String::new();
+        }
+
+        format!(
+            "{} {}",
+            self.hash("street_number", real) % 9900 + 100,
+            self.pick("street", real, STREETS)
+        )
+    }
+
+    /// Return fake text of roughly the same length as the real text. This is used for
+    /// free-form content like transcripts and chat logs.
+    pub fn fake_text(&self, real: &str) -> String {
+        if real.is_empty() {
+            return String::new();
+        }
+
+        let count = real.split_whitespace().count().max(1);
+        let seed = self.hash("text", real);
+        (0..count)
+            .map(|i| WORDS[((seed.wrapping_add(i as u64 * 7919)) % WORDS.len() as u64) as usize])
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+}
+
+/// A report of how many rows were rewritten by the anonymizer.
+#[derive(Debug, Default, Clone)]
+pub struct AnonymizeReport {
+    pub users: usize,
+    pub applicants: usize,
+    pub recorded_meetings: usize,
+    pub outbound_shipments: usize,
+    pub mailing_list_subscribers: usize,
+    /// The number of values or rows we rewrote in each of the other tables with personal data.
+    pub other_tables: BTreeMap<&'static str, usize>,
+}
+
+/// Anonymize all of the PII in the database for a company. This only updates the database
+/// and never writes to any third party services, since it is meant to be run against a
+/// staging database restored from a production snapshot.
+pub async fn anonymize_database(db: &Database, company: &Company, salt: &str) -> Result<AnonymizeReport> {
+    let a = Anonymizer::new(salt, company);
+    let mut report = AnonymizeReport::default();
+
+    // These go first, we find the upload links of applicants by their real emails.
+    anonymize_other_tables(db, &a, company, &mut report).await?;
+
+    for mut user in Users::get_from_db(db, company.id).await? {
+        // Usernames are what other records reference users by, so derive everything
+        // from the username to keep the fakes consistent with each other.
+        let (first_name, last_name) = a.fake_name(&user.username);
+        user.first_name = first_name;
+        user.last_name = last_name;
+        user.username = a.fake_username(&user.username);
+        user.email = a.fake_email(&user.email);
+        user.aliases = user.aliases.iter().map(|alias| a.fake_username(alias)).collect();
+        user.manager = a.fake_username(&user.manager);
+        user.link_to_manager = user.link_to_manager.iter().map(|m| a.fake_username(m)).collect();
+        user.recovery_email = a.fake_email(&user.recovery_email);
+        user.recovery_phone = a.fake_phone(&user.recovery_phone);
+        user.github = a.fake_username(&user.github);
+        user.twitter = a.fake_username(&user.twitter);
+        user.home_address_street_1 = a.fake_street(&user.home_address_street_1);
+        user.home_address_street_2 = String::new();
+        user.home_address_formatted = String::new();
+        user.home_address_latitude = 0.0;
+        user.home_address_longitude = 0.0;
+        user.geocode_cache = String::new();
+        user.public_ssh_keys = Default::default();
+        user.update_in_db(db).await?;
+
+        report.users += 1;
+    }
+
+    for mut applicant in Applicants::get_from_db(db, company.id).await? {
+        let (first_name, last_name) = a.fake_name(&applicant.email);
+        applicant.name = format!("{} {}", first_name, last_name);
+        applicant.email = a.fake_email(&applicant.email);
+        applicant.phone = a.fake_phone(&applicant.phone);
+        applicant.github = a.fake_username(&applicant.github);
+        applicant.gitlab = a.fake_username(&applicant.gitlab);
+        applicant.linkedin = String::new();
+        applicant.website = String::new();
+        applicant.geocode_cache = String::new();
+        applicant.resume_contents = a.fake_text(&applicant.resume_contents);
+        applicant.materials_contents = a.fake_text(&applicant.materials_contents);
+        applicant.scorers = applicant.scorers.iter().map(|s| a.fake_email(s)).collect();
+        applicant.scorers_completed = applicant.scorers_completed.iter().map(|s| a.fake_email(s)).collect();
+        applicant.update_in_db(db).await?;
+
+        report.applicants += 1;
+    }
+
+    for mut meeting in RecordedMeetings::get_from_db(db, company.id).await? {
+        meeting.attendees = meeting
+            .attendees
+            .iter()
+            .map(|attendee| a.fake_email(attendee))
+            .collect();
+        meeting.transcript = a.fake_text(&meeting.transcript);
+        meeting.chat_log = a.fake_text(&meeting.chat_log);
+        meeting.update_in_db(db).await?;
+
+        report.recorded_meetings += 1;
+    }
+
+    for mut shipment in OutboundShipments::get_from_db(db, company.id).await? {
+        let (first_name, last_name) = a.fake_name(&shipment.email);
+        shipment.name = format!("{} {}", first_name, last_name);
+        shipment.email = a.fake_email(&shipment.email);
+        shipment.phone = a.fake_phone(&shipment.phone);
+        shipment.street_1 = a.fake_street(&shipment.street_1);
+        shipment.street_2 = String::new();
+        shipment.address_formatted = String::new();
+        shipment.latitude = 0.0;
+        shipment.longitude = 0.0;
+        shipment.geocode_cache = String::new();
+        shipment.update_in_db(db).await?;
+
+        report.outbound_shipments += 1;
+    }
+
+    for mut subscriber in MailingListSubscribers::get_from_db(db, company.id).await? {
+        let (first_name, last_name) = a.fake_name(&subscriber.email);
+        subscriber.name = format!("{} {}", first_name, last_name);
+        subscriber.first_name = first_name;
+        subscriber.last_name = last_name;
+        subscriber.email = a.fake_email(&subscriber.email);
+        subscriber.phone = a.fake_phone(&subscriber.phone);
+        subscriber.street_1 = a.fake_street(&subscriber.street_1);
+        subscriber.street_2 = String::new();
+        subscriber.address_formatted = String::new();
+        subscriber.update_in_db(db).await?;
+
+        report.mailing_list_subscribers += 1;
+    }
+
+    info!("anonymized database for company {}: {:?}", company.name, report);
+
+    Ok(report)
+}
+
+/// Anonymize the tables with personal data that are not records of their own, like the
+/// people on interviews, scorecards, sign-ins, orders and event registrations. Any table
+/// with personal data needs to be covered here.
+async fn anonymize_other_tables(
+    db: &Database,
+    a: &Anonymizer,
+    company: &Company,
+    report: &mut AnonymizeReport,
+) -> Result<()> {
+    let id = company.id;
+    let email = |v: &String| a.fake_email(v);
+    let full_name = |v: &String| a.fake_full_name(v);
+    let text = |v: &String| a.fake_text(v);
+    let emails = |v: &Vec<String>| v.iter().map(|e| a.fake_email(e)).collect::<Vec<String>>();
+    let blank = |_: &String| String::new();
+    let tables = &mut report.other_tables;
+
+    // The upload links are sent to applicants by email, nobody needs them in staging.
+    let applicant_emails = applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(id))
+        .select(applicants::dsl::email)
+        .load_async::<String>(db.pool())
+        .await?;
+    let upload_tokens = diesel::delete(upload_tokens::dsl::upload_tokens)
+        .filter(upload_tokens::dsl::email.eq_any(applicant_emails))
+        .execute_async(db.pool())
+        .await?;
+    tables.insert("upload_tokens", upload_tokens);
+
+    tables.insert(
+        "applicant_interviews",
+        anonymize_values!(db, applicant_interviews, applicant_interviews::dsl::cio_company_id.eq(id),
+            email => email, name => full_name)
+            + anonymize_rows!(db, applicant_interviews, applicant_interviews::dsl::cio_company_id.eq(id),
+                interviewers: Vec<String> => emails),
+    );
+    tables.insert(
+        "applicant_reviewers",
+        anonymize_values!(db, applicant_reviewers, applicant_reviewers::dsl::cio_company_id.eq(id),
+            email => email, name => full_name),
+    );
+    tables.insert(
+        "applicant_reviews",
+        anonymize_values!(db, applicant_reviews, applicant_reviews::dsl::cio_company_id.eq(id), reviewer => email)
+            + anonymize_rows!(db, applicant_reviews, applicant_reviews::dsl::cio_company_id.eq(id),
+                notes: String => text),
+    );
+    tables.insert(
+        "applicant_withdrawals",
+        anonymize_values!(db, applicant_withdrawals, applicant_withdrawals::dsl::cio_company_id.eq(id),
+            email => email)
+            + anonymize_rows!(db, applicant_withdrawals, applicant_withdrawals::dsl::cio_company_id.eq(id),
+                reason: String => text),
+    );
+    tables.insert(
+        "interview_scorecards",
+        anonymize_values!(db, interview_scorecards, interview_scorecards::dsl::cio_company_id.eq(id),
+            interviewer => email)
+            + anonymize_rows!(db, interview_scorecards, interview_scorecards::dsl::cio_company_id.eq(id),
+                notes: String => text),
+    );
+    tables.insert(
+        "api_tokens",
+        anonymize_values!(db, api_tokens, api_tokens::dsl::cio_company_id.eq(id), user_email => email),
+    );
+    tables.insert(
+        "asset_items",
+        anonymize_values!(db, asset_items, asset_items::dsl::cio_company_id.eq(id),
+            current_employee_borrowing => email),
+    );
+    tables.insert(
+        "asset_checkouts",
+        anonymize_values!(db, asset_checkouts, asset_checkouts::dsl::cio_company_id.eq(id), email => email),
+    );
+    tables.insert(
+        "asset_reservations",
+        anonymize_values!(db, asset_reservations, asset_reservations::dsl::cio_company_id.eq(id), email => email),
+    );
+    tables.insert(
+        "auth_users",
+        anonymize_values!(db, auth_users, auth_users::dsl::cio_company_id.eq(id),
+            email => email, name => full_name, nickname => blank, username => blank, phone => blank,
+            last_ip => blank),
+    );
+    tables.insert(
+        "auth_user_logins",
+        anonymize_values!(db, auth_user_logins, auth_user_logins::dsl::cio_company_id.eq(id),
+            email => email, user_name => full_name, ip => blank, user_agent => blank),
+    );
+    tables.insert(
+        "conference_leads",
+        anonymize_values!(db, conference_leads, conference_leads::dsl::cio_company_id.eq(id),
+            email => email, first_name => |v: &String| a.fake_name(v).0, last_name => |v: &String| a.fake_name(v).1)
+            + anonymize_rows!(db, conference_leads, conference_leads::dsl::cio_company_id.eq(id),
+                notes: String => text),
+    );
+    tables.insert(
+        "conferences",
+        anonymize_values!(db, conferences, conferences::dsl::cio_company_id.eq(id),
+            ship_to_email => email, ship_to_name => full_name,
+            ship_to_phone => |v: &String| a.fake_phone(v), ship_to_street_1 => |v: &String| a.fake_street(v),
+            ship_to_street_2 => blank)
+            + anonymize_rows!(db, conferences, conferences::dsl::cio_company_id.eq(id),
+                booth_staff: Vec<String> => emails),
+    );
+    tables.insert(
+        "conference_tasks",
+        anonymize_values!(db, conference_tasks, conference_tasks::dsl::cio_company_id.eq(id), assignee => email),
+    );
+    tables.insert(
+        "event_registrants",
+        anonymize_values!(db, event_registrants, event_registrants::dsl::cio_company_id.eq(id),
+            email => email, first_name => |v: &String| a.fake_name(v).0, last_name => |v: &String| a.fake_name(v).1),
+    );
+    tables.insert(
+        "credit_card_transactions",
+        anonymize_values!(db, credit_card_transactions, credit_card_transactions::dsl::cio_company_id.eq(id),
+            employee_email => email),
+    );
+    tables.insert(
+        "expensed_items",
+        anonymize_values!(db, expensed_items, expensed_items::dsl::cio_company_id.eq(id), employee_email => email),
+    );
+    tables.insert(
+        "focus_time_conflicts",
+        anonymize_values!(db, focus_time_conflicts, focus_time_conflicts::dsl::cio_company_id.eq(id),
+            user_email => email),
+    );
+    tables.insert(
+        "incidents",
+        anonymize_rows!(db, incidents, incidents::dsl::cio_company_id.eq(id), participants: Vec<String> => emails)
+            + anonymize_rows!(db, incidents, incidents::dsl::cio_company_id.eq(id), summary: String => text),
+    );
+    tables.insert(
+        "meeting_action_items",
+        anonymize_values!(db, meeting_action_items, meeting_action_items::dsl::cio_company_id.eq(id),
+            assignee => email)
+            + anonymize_rows!(db, meeting_action_items, meeting_action_items::dsl::cio_company_id.eq(id),
+                description: String => text),
+    );
+    tables.insert(
+        "page_views",
+        anonymize_values!(db, page_views, page_views::dsl::cio_company_id.eq(id), user_email => email),
+    );
+    tables.insert(
+        "rack_line_subscribers",
+        anonymize_values!(db, rack_line_subscribers, rack_line_subscribers::dsl::cio_company_id.eq(id),
+            email => email, name => full_name)
+            + anonymize_rows!(db, rack_line_subscribers, rack_line_subscribers::dsl::cio_company_id.eq(id),
+                notes: String => text),
+    );
+    tables.insert(
+        "role_address_holders",
+        anonymize_values!(db, role_address_holders, role_address_holders::dsl::cio_company_id.eq(id),
+            username => |v: &String| a.fake_username(v)),
+    );
+    tables.insert(
+        "shopify_orders",
+        anonymize_values!(db, shopify_orders, shopify_orders::dsl::cio_company_id.eq(id),
+            email => email, name => full_name),
+    );
+    tables.insert(
+        "sign_in_events",
+        anonymize_values!(db, sign_in_events, sign_in_events::dsl::cio_company_id.eq(id),
+            email => email, ip_address => blank),
+    );
+    tables.insert(
+        "swag_requests",
+        anonymize_values!(db, swag_requests, swag_requests::dsl::cio_company_id.eq(id),
+            email => email, name => full_name, phone => |v: &String| a.fake_phone(v),
+            street_1 => |v: &String| a.fake_street(v), street_2 => blank),
+    );
+
+    // The attendees, participants and transcripts of meetings belong to the company through
+    // their meeting.
+    let meeting_ids = recorded_meetings::dsl::recorded_meetings
+        .filter(recorded_meetings::dsl::cio_company_id.eq(id))
+        .select(recorded_meetings::dsl::id)
+        .load_async::<i32>(db.pool())
+        .await?;
+    tables.insert(
+        "recorded_meeting_attendees",
+        anonymize_values!(db, recorded_meeting_attendees,
+            recorded_meeting_attendees::dsl::recorded_meeting_id.eq_any(meeting_ids.clone()), email => email),
+    );
+    tables.insert(
+        "recorded_meeting_participants",
+        anonymize_values!(db, recorded_meeting_participants,
+            recorded_meeting_participants::dsl::recorded_meeting_id.eq_any(meeting_ids.clone()),
+            email => email, name => full_name),
+    );
+    tables.insert(
+        "recorded_meeting_transcript_segments",
+        anonymize_values!(db, recorded_meeting_transcript_segments,
+            recorded_meeting_transcript_segments::dsl::recorded_meeting_id.eq_any(meeting_ids.clone()),
+            speaker => full_name)
+            + anonymize_rows!(db, recorded_meeting_transcript_segments,
+                recorded_meeting_transcript_segments::dsl::recorded_meeting_id.eq_any(meeting_ids.clone()),
+                text: String => text),
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_anonymizer() -> Anonymizer {
+        Anonymizer {
+            salt: "test-salt".to_string(),
+            company_domains: vec!["oxide.computer".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_fake_email_is_stable() {
+        let a = mock_anonymizer();
+
+        assert_eq!(a.fake_email("Jane@Gmail.com"), a.fake_email("jane@gmail.com"));
+        assert_ne!(a.fake_email("jane@gmail.com"), a.fake_email("jane@yahoo.com"));
+        assert!(a.fake_email("jane@gmail.com").ends_with("@example.com"));
+        assert!(a.fake_email("jane@oxide.computer").ends_with("@oxide.computer"));
+        assert_eq!("", a.fake_email(""));
+    }
+
+    #[test]
+    fn test_fake_email_matches_username() {
+        let a = mock_anonymizer();
+
+        assert_eq!(
+            a.fake_email("jane@oxide.computer"),
+            format!("{}@oxide.computer", a.fake_username("jane"))
+        );
+    }
+
+    #[test]
+    fn test_fake_text_keeps_length() {
+        let a = mock_anonymizer();
+
+        let text = a.fake_text("this is a transcript with seven words");
+        assert_eq!(7, text.split_whitespace().count());
+        assert_ne!("this is a transcript with seven words", text);
+    }
+}
@@ -75,7 +75,7 @@ impl UpdateAirtableRecord<APIToken> for APIToken {
     async fn update_airtable_record(&mut self, _record: APIToken) -> Result<()> {
         // Link to the correct company.
         let db = Database::new().await;
-        let company = Company::get_by_id(&db, self.auth_company_id).await?;
+        let company = Company::get_from_id(&db, self.auth_company_id).await?;
         self.company = vec![company.airtable_record_id];
 
         Ok(())
@@ -2,7 +2,13 @@ use docusign::Envelope;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::{applicants::Applicant, companies::Company, configs::User};
+use crate::{
+    applicants::Applicant, asset_inventory::AssetAssignmentConfig, asset_refresh::AssetRefreshConfig,
+    business_calendar::BusinessCalendarsConfig, community_events::CommunityEventsConfig, companies::Company,
+    conferences::ConferencesConfig, configs::User, first_week::FirstWeekTemplate,
+    inventory_reconciliation::InventoryReconciliationConfig, rfd::RFDReservationsConfig,
+    webhook_transforms::WebhookTransformsConfig,
+};
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct DocuSignConfig {
@@ -25,6 +31,24 @@ impl DocuSignConfig {
         envelope
     }
 
+    /// Return if someone at the company signs the offer letters, before the applicant does.
+    pub fn signs_offer_letters(&self, email: &str) -> bool {
+        DocuSignConfig::signs(&self.offer, email)
+    }
+
+    /// Return if someone at the company signs the PIIAs, before the applicant does.
+    pub fn signs_piia_letters(&self, email: &str) -> bool {
+        DocuSignConfig::signs(&self.piia, email)
+    }
+
+    fn signs(envelope: &Envelope, email: &str) -> bool {
+        !email.trim().is_empty()
+            && envelope
+                .template_roles
+                .iter()
+                .any(|r| r.email.trim().eq_ignore_ascii_case(email.trim()))
+    }
+
     fn fill_envelope(envelope: &mut Envelope, applicant: &Applicant) {
         for template_role in envelope.template_roles.iter_mut() {
             template_role.name = template_role.name.replace("{applicant_name}", &applicant.name);
@@ -50,6 +74,8 @@ impl DocuSignConfig {
 pub struct OnboardingConfig {
     pub new_hire_issue: NewHireIssue,
     welcome_letter: Letter,
+    #[serde(default)]
+    pub first_week: FirstWeekTemplate,
 }
 
 impl OnboardingConfig {
@@ -96,15 +122,64 @@ pub struct Letter {
     pub bcc: Vec<String>,
 }
 
+/// The locale we send applicant-facing emails in when we don't have a translation.
+pub static DEFAULT_LOCALE: &str = "en";
+
+/// The locales we support for applicant-facing emails and forms.
+pub static SUPPORTED_LOCALES: &[&str] = &["en", "de", "fr"];
+
+/// Normalize a locale like "de-CH" or "FR" to one of our supported locales, falling back
+/// to the default locale.
+pub fn normalize_locale(locale: &str) -> String {
+    let language = locale
+        .trim()
+        .split(|c| c == '-' || c == '_')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    if SUPPORTED_LOCALES.contains(&language.as_str()) {
+        language
+    } else {
+        DEFAULT_LOCALE.to_string()
+    }
+}
+
+/// The translations of the applicant-facing letters for a locale.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ApplyTranslations {
+    #[serde(default)]
+    received: Option<Letter>,
+    #[serde(default)]
+    rejection: HashMap<String, Letter>,
+}
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct ApplyConfig {
     received: Letter,
     rejection: HashMap<String, Letter>,
+    /// The translations of the letters above, keyed by locale. We only use the translations
+    /// of a locale once every letter is translated, so an applicant never gets emails in
+    /// different languages.
+    #[serde(default)]
+    translations: HashMap<String, ApplyTranslations>,
+    /// Take interviewers with a conflict of interest off the interview, instead of only
+    /// warning the recruiters.
+    #[serde(default)]
+    pub exclude_conflicted_interviewers: bool,
+    /// How many years after leaving an employer someone is still close to the people they
+    /// worked with there, 3 when it is not set.
+    #[serde(default)]
+    conflict_employer_years: Option<i32>,
 }
 
 impl ApplyConfig {
-    pub fn create_received_letter(&self, applicant: &Applicant) -> Letter {
-        let mut letter = self.received.clone();
+    pub fn conflict_employer_years(&self) -> i32 {
+        self.conflict_employer_years.unwrap_or(3)
+    }
+
+    fn fill_letter(letter: &Letter, applicant: &Applicant) -> Letter {
+        let mut letter = letter.clone();
         letter.subject = letter
             .subject
             .replace("{applicant_name}", &applicant.name)
@@ -119,22 +194,41 @@ impl ApplyConfig {
         letter
     }
 
-    pub fn create_rejection_letter(&self, letter_key: &str, applicant: &Applicant) -> Option<Letter> {
-        self.rejection.get(letter_key).map(|letter| {
-            let mut letter = letter.clone();
-            letter.subject = letter
-                .subject
-                .replace("{applicant_name}", &applicant.name)
-                .replace("{applicant_email}", &applicant.email)
-                .replace("{applicant_role}", &applicant.role);
-            letter.body = letter
-                .body
-                .replace("{applicant_name}", &applicant.name)
-                .replace("{applicant_email}", &applicant.email)
-                .replace("{applicant_role}", &applicant.role);
+    fn translations(&self, applicant: &Applicant) -> Option<&ApplyTranslations> {
+        let locale = normalize_locale(&applicant.locale);
+        let translations = self.translations.get(&locale)?;
+
+        let is_complete = translations.received.is_some()
+            && self
+                .rejection
+                .keys()
+                .all(|key| translations.rejection.contains_key(key));
+        if !is_complete {
+            log::warn!(
+                "the translations of the applicant letters for `{}` are incomplete, sending them in `{}`",
+                locale,
+                DEFAULT_LOCALE
+            );
+            return None;
+        }
+
+        Some(translations)
+    }
+
+    pub fn create_received_letter(&self, applicant: &Applicant) -> Letter {
+        let letter = self
+            .translations(applicant)
+            .and_then(|t| t.received.as_ref())
+            .unwrap_or(&self.received);
+
+        ApplyConfig::fill_letter(letter, applicant)
+    }
 
-            letter
-        })
+    pub fn create_rejection_letter(&self, letter_key: &str, applicant: &Applicant) -> Option<Letter> {
+        self.translations(applicant)
+            .and_then(|t| t.rejection.get(letter_key))
+            .or_else(|| self.rejection.get(letter_key))
+            .map(|letter| ApplyConfig::fill_letter(letter, applicant))
     }
 }
 
@@ -165,11 +259,35 @@ pub struct AppConfig {
     pub finance: FinanceConfig,
     #[serde(default)]
     pub github: GitHubConfig,
+    /// The working hours and holidays reminders, escalations, and scheduling follow.
+    #[serde(default)]
+    pub business_calendar: BusinessCalendarsConfig,
+    /// How often we replace each type of asset.
+    #[serde(default)]
+    pub asset_refresh: AssetRefreshConfig,
+    /// Who can assign asset items to people.
+    #[serde(default)]
+    pub asset_assignment: AssetAssignmentConfig,
+    /// How much inventory drift we accept before flagging it.
+    #[serde(default)]
+    pub inventory_reconciliation: InventoryReconciliationConfig,
+    /// The changes we make to the payloads of webhooks before decoding them, by provider.
+    #[serde(default)]
+    pub webhook_transforms: WebhookTransformsConfig,
+    /// How we read the registrations to community events.
+    #[serde(default)]
+    pub community_events: CommunityEventsConfig,
+    /// The timeline of tasks of the conferences we sponsor.
+    #[serde(default)]
+    pub conferences: ConferencesConfig,
+    /// How long we hold the numbers reserved for new RFDs.
+    #[serde(default)]
+    pub rfd_reservations: RFDReservationsConfig,
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ApplyConfig, DocuSignConfig, GitHubConfig, OnboardingConfig};
+    use super::{normalize_locale, ApplyConfig, DocuSignConfig, GitHubConfig, OnboardingConfig};
     use crate::{applicants::tests::mock_applicant, companies::tests::mock_company, configs::tests::mock_user};
 
     fn mock_docusign_toml(label: &str) -> String {
@@ -291,6 +409,58 @@ from = 'test@testemaildomain.com'
         );
     }
 
+    fn mock_translated_apply_toml() -> String {
+        format!(
+            r#"{}
+[translations.de.received]
+subject = 'Eingegangen: {{applicant_name}} ({{applicant_role}})'
+body = '{{applicant_name}}, danke für deine Bewerbung als {{applicant_role}}.'
+from = 'test@testemaildomain.com'
+
+[translations.de.rejection.test-rejection]
+subject = 'Absage: {{applicant_name}} ({{applicant_role}})'
+body = 'Absage für {{applicant_name}}'
+from = 'test@testemaildomain.com'
+
+[translations.fr.received]
+subject = 'Reçu : {{applicant_name}} ({{applicant_role}})'
+body = 'Merci {{applicant_name}}.'
+from = 'test@testemaildomain.com'
+"#,
+            mock_apply_toml()
+        )
+    }
+
+    #[test]
+    fn test_translated_letters() {
+        let config: ApplyConfig = toml::from_str(&mock_translated_apply_toml()).unwrap();
+        let mut applicant = mock_applicant();
+        applicant.locale = "de-CH".to_string();
+
+        let letter = config.create_received_letter(&applicant);
+        assert_eq!("Eingegangen: Test User (Engineering)", letter.subject);
+        assert_eq!("Test User, danke für deine Bewerbung als Engineering.", letter.body);
+
+        let letter = config.create_rejection_letter("test-rejection", &applicant).unwrap();
+        assert_eq!("Absage: Test User (Engineering)", letter.subject);
+
+        // The French rejection is not translated, so every French letter is sent in English.
+        applicant.locale = "fr".to_string();
+        let letter = config.create_received_letter(&applicant);
+        assert_eq!("Received for Test User (Engineering)", letter.subject);
+        let letter = config.create_rejection_letter("test-rejection", &applicant).unwrap();
+        assert_eq!("Rejection for Test User (Engineering)", letter.subject);
+    }
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!("de", normalize_locale("de-CH"));
+        assert_eq!("fr", normalize_locale(" FR "));
+        assert_eq!("en", normalize_locale("en_US"));
+        assert_eq!("en", normalize_locale("es"));
+        assert_eq!("en", normalize_locale(""));
+    }
+
     #[test]
     fn test_missing_rejection_letter() {
         let config: ApplyConfig = toml::from_str(&mock_apply_toml()).unwrap();
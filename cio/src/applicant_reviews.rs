@@ -22,13 +22,21 @@ use crate::{
     match_on = {
         "name" = "String",
     },
+    airtable_read_only = ["name"],
+    airtable_links = ["applicant", "link_to_leaderboard"],
 }]
 #[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
 #[diesel(table_name = applicant_reviews)]
 pub struct NewApplicantReview {
     // TODO: We don't have to do this crazy rename after we update to not use the
     // Airtable form.
-    #[serde(default, skip_serializing_if = "String::is_empty", rename = "Name", alias = "name")]
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::formula_format_as_string::deserialize",
+        rename = "Name",
+        alias = "name"
+    )]
     pub name: String,
     #[serde(
         default,
@@ -86,6 +94,8 @@ pub struct NewApplicantReview {
     #[serde(
         default,
         skip_serializing_if = "Vec::is_empty",
+        serialize_with = "airtable_api::linked_records::serialize",
+        deserialize_with = "airtable_api::linked_records::deserialize",
         rename = "Applicant",
         alias = "applicant"
     )]
@@ -94,6 +104,8 @@ pub struct NewApplicantReview {
     #[serde(
         default,
         skip_serializing_if = "Vec::is_empty",
+        serialize_with = "airtable_api::linked_records::serialize",
+        deserialize_with = "airtable_api::linked_records::deserialize",
         rename = "Link to Leaderboard",
         alias = "link_to_leaderboard"
     )]
@@ -108,9 +120,6 @@ pub struct NewApplicantReview {
 #[async_trait]
 impl UpdateAirtableRecord<ApplicantReview> for ApplicantReview {
     async fn update_airtable_record(&mut self, _record: ApplicantReview) -> Result<()> {
-        // Set name to empty since it is a function we cannot update it.
-        self.name = "".to_string();
-
         Ok(())
     }
 }
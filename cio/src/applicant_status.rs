@@ -16,6 +16,9 @@ pub enum Status {
     /// We are in the process of interviewing the applicant.
     Interviewing,
 
+    /// The applicant is done interviewing and the panel is debriefing.
+    Debrief,
+
     /// The applicant has been deferred.
     Deferred,
 
@@ -56,6 +59,8 @@ impl FromStr for Status {
             Ok(Status::Declined)
         } else if s.contains("hired") {
             Ok(Status::Hired)
+        } else if s.contains("debrief") {
+            Ok(Status::Debrief)
         } else if s.contains("interviewing") {
             Ok(Status::Interviewing)
         } else if s.contains("onboarding") {
@@ -83,6 +88,7 @@ impl ToString for Status {
             Status::Contractor => "Contractor".to_string(),
             Status::NeedsToBeTriaged => "Needs to be triaged".to_string(),
             Status::Interviewing => "Interviewing".to_string(),
+            Status::Debrief => "Debrief".to_string(),
             Status::Onboarding => "Onboarding".to_string(),
             Status::Withdrawn => "Withdrawn".to_string(),
         }
@@ -0,0 +1,635 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, PgTextExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use sendgrid_api::{traits::MailOps, Client as SendGrid};
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    applicant_status::Status,
+    applicants::Applicant,
+    asset_inventory::escape_like,
+    companies::Company,
+    db::Database,
+    inbound_email::InboundEmail,
+    interviews::{get_interviews_calendar_id, ApplicantInterview},
+    schema::{applicant_interviews, applicant_withdrawals, applicants},
+    tenancy::CompanyScoped,
+};
+
+/// What candidates write when they want out of the process, in the reply to one of our emails.
+static WITHDRAWAL_PHRASES: &[&str] = &[
+    "withdraw",
+    "withdrawing",
+    "no longer interested",
+    "not interested anymore",
+    "accepted another offer",
+    "accepted an offer",
+    "decline the offer",
+    "declining the offer",
+    "decline your offer",
+    "remove my application",
+    "cancel my application",
+];
+
+/// The words that turn a withdrawal phrase after them in the same clause around, like "I will
+/// not withdraw". The words ending in "n't" do too.
+static NEGATIONS: &[&str] = &["not", "no", "never", "cannot", "nor", "neither", "without"];
+
+/// The words that make a withdrawal phrase after them in the same clause hypothetical, like "if
+/// I withdraw".
+static CONDITIONALS: &[&str] = &["if", "unless", "whether"];
+
+/// The reasons we group withdrawals by in the funnel, with what candidates write about them.
+static WITHDRAWAL_REASON_CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "other offer",
+        &["another offer", "other offer", "accepted an offer", "another position"],
+    ),
+    ("compensation", &["compensation", "salary", "pay"]),
+    ("location", &["location", "relocate", "remote", "commute"]),
+    ("timing", &["timing", "not the right time", "right now", "personal"]),
+];
+
+/// Where a withdrawal came from.
+pub static WITHDRAWAL_SOURCE_REQUEST: &str = "request";
+pub static WITHDRAWAL_SOURCE_EMAIL: &str = "email";
+
+/// A candidate that withdrew from the process, or declined our offer. We keep the stage they
+/// were in and why, so we know where and why we lose candidates.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicantWithdrawal {
+    pub id: i32,
+    pub applicant_id: i32,
+    pub email: String,
+    pub role: String,
+    /// The status of the applicant before they withdrew.
+    pub stage: String,
+    pub reason: String,
+    pub reason_category: String,
+    pub source: String,
+    pub withdrawn_at: DateTime<Utc>,
+    /// When the applicant was withdrawn. The withdrawals we find in emails wait for someone on
+    /// the recruiting team to confirm them.
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+}
+
+impl CompanyScoped for ApplicantWithdrawal {
+    fn cio_company_id(&self) -> i32 {
+        self.cio_company_id
+    }
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = applicant_withdrawals)]
+struct NewApplicantWithdrawal {
+    applicant_id: i32,
+    email: String,
+    role: String,
+    stage: String,
+    reason: String,
+    reason_category: String,
+    source: String,
+    confirmed_at: Option<DateTime<Utc>>,
+    cio_company_id: i32,
+}
+
+/// A withdrawal found in an email from a candidate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithdrawalEmail {
+    pub email: String,
+    pub reason: String,
+}
+
+/// Return what the candidate wrote in a reply, without the email they replied to and their
+/// signature.
+fn reply_text(text: &str) -> String {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('>')
+            || trimmed == "--"
+            || trimmed.starts_with("-----Original Message")
+            || (trimmed.starts_with("On ") && trimmed.ends_with("wrote:"))
+        {
+            break;
+        }
+        lines.push(trimmed);
+    }
+
+    lines.join("\n").trim().to_string()
+}
+
+/// Returns if a clause has the phrase, with nothing before it in the clause to negate it or make
+/// it hypothetical.
+fn clause_has_phrase(words: &[&str], phrase: &str) -> bool {
+    let phrase: Vec<&str> = phrase.split_whitespace().collect();
+    if phrase.is_empty() || words.len() < phrase.len() {
+        return false;
+    }
+
+    (0..=words.len() - phrase.len())
+        .filter(|start| words[*start..*start + phrase.len()] == phrase[..])
+        .any(|start| {
+            !words[..start]
+                .iter()
+                .any(|w| w.ends_with("n't") || NEGATIONS.contains(w) || CONDITIONALS.contains(w))
+        })
+}
+
+/// Returns if the text asks to withdraw. Questions do not count, "should I withdraw my other
+/// applications?" is not a withdrawal.
+fn is_withdrawal(text: &str) -> bool {
+    let text = text.to_lowercase().replace('\u{2019}', "'");
+
+    let mut sentence = String::new();
+    // The last sentence might not end with any punctuation.
+    for c in text.chars().chain(std::iter::once('\n')) {
+        if !matches!(c, '.' | '!' | '?' | '\n') {
+            sentence.push(c);
+            continue;
+        }
+
+        let asks = c != '?'
+            && sentence.split(|c| matches!(c, ',' | ';' | ':')).any(|clause| {
+                let words: Vec<&str> = clause
+                    .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+                    .filter(|w| !w.is_empty())
+                    .collect();
+                WITHDRAWAL_PHRASES.iter().any(|p| clause_has_phrase(&words, p))
+            });
+        if asks {
+            return true;
+        }
+        sentence.clear();
+    }
+
+    false
+}
+
+/// Return the category of the reason a candidate gave, for the funnel.
+pub fn categorize_withdrawal_reason(reason: &str) -> String {
+    let reason = reason.to_lowercase();
+    for (category, phrases) in WITHDRAWAL_REASON_CATEGORIES {
+        if phrases.iter().any(|p| reason.contains(p)) {
+            return category.to_string();
+        }
+    }
+
+    "other".to_string()
+}
+
+/// Parse an email from a candidate, and return their withdrawal if they are asking for one.
+pub fn parse_withdrawal_email(email: &InboundEmail) -> Option<WithdrawalEmail> {
+    let reply = reply_text(&email.text);
+    if !is_withdrawal(&format!("{}\n{}", email.subject, reply)) {
+        return None;
+    }
+
+    let address = email.from_address();
+    if address.is_empty() {
+        return None;
+    }
+
+    Some(WithdrawalEmail {
+        email: address,
+        reason: reply,
+    })
+}
+
+/// Find the newest application of a candidate, or the one for the role.
+async fn find_application(
+    db: &Database,
+    company: &Company,
+    email: &str,
+    role: Option<&str>,
+) -> Result<Option<Applicant>> {
+    // Applicants are not unique on their email, see the applicant info handler.
+    let applicant = applicants::dsl::applicants
+        .filter(applicants::dsl::email.ilike(escape_like(email.trim())))
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .order_by(applicants::dsl::id.desc())
+        .load_async::<Applicant>(db.pool())
+        .await?
+        .into_iter()
+        .find(|a| role.map(|r| a.role.eq_ignore_ascii_case(r.trim())).unwrap_or(true));
+
+    Ok(applicant)
+}
+
+/// Return the stage of an application, if it can still be withdrawn.
+fn withdrawable_stage(applicant: &Applicant) -> Result<Status> {
+    let stage = Status::from_str(&applicant.status).unwrap_or_default();
+    if matches!(
+        stage,
+        Status::Hired | Status::Onboarding | Status::Contractor | Status::Declined | Status::Withdrawn
+    ) {
+        bail!(
+            "the application of {} for {} is {}, it cannot be withdrawn",
+            applicant.name,
+            applicant.role,
+            applicant.status
+        );
+    }
+
+    Ok(stage)
+}
+
+/// Cancel the interviews still to come and the debrief of an applicant, and withdraw them.
+/// Returns the applicant and the panel of the cancelled interviews.
+async fn complete_withdrawal(
+    db: &Database,
+    company: &Company,
+    mut applicant: Applicant,
+    stage: &Status,
+) -> Result<(Applicant, Vec<String>)> {
+    let interviews = applicant_interviews::dsl::applicant_interviews
+        .filter(applicant_interviews::dsl::email.eq(applicant.email.to_string()))
+        .filter(applicant_interviews::dsl::cio_company_id.eq(company.id))
+        .filter(applicant_interviews::dsl::cancelled_at.is_null())
+        .filter(applicant_interviews::dsl::start_time.gt(Utc::now()))
+        .load_async::<ApplicantInterview>(db.pool())
+        .await?;
+
+    let mut panel: Vec<String> = interviews.iter().flat_map(|i| i.interviewers.clone()).collect();
+    panel.sort();
+    panel.dedup();
+
+    let mut events: Vec<String> = interviews.iter().map(|i| i.google_event_id.to_string()).collect();
+    // The debrief is only still to come while the panel is interviewing or debriefing.
+    if !applicant.debrief_event_id.is_empty() && matches!(stage, Status::Interviewing | Status::Debrief) {
+        events.push(applicant.debrief_event_id.to_string());
+        applicant.debrief_event_id = "".to_string();
+        applicant.debrief_event_link = "".to_string();
+    }
+    if !events.is_empty() {
+        cancel_interview_events(db, company, &events).await?;
+    }
+    // We keep the interviews, so we know how far the applicant got.
+    for mut interview in interviews {
+        interview.cancelled_at = Some(Utc::now());
+        interview.update(db).await?;
+    }
+
+    applicant.status = Status::Withdrawn.to_string();
+    let applicant = applicant.update(db).await?;
+
+    Ok((applicant, panel))
+}
+
+/// Withdraw the newest application of a candidate, or the one for the role. The interviews
+/// still to come and the debrief are cancelled, and the panel learns why.
+pub async fn withdraw_applicant(
+    db: &Database,
+    company: &Company,
+    email: &str,
+    role: Option<&str>,
+    reason: &str,
+    source: &str,
+) -> Result<ApplicantWithdrawal> {
+    let applicant = match find_application(db, company, email, role).await? {
+        Some(applicant) => applicant,
+        None => bail!("no application from `{}` to withdraw", email),
+    };
+    let stage = withdrawable_stage(&applicant)?;
+
+    let (applicant, panel) = complete_withdrawal(db, company, applicant, &stage).await?;
+
+    let reason = reason.trim().to_string();
+    let withdrawal = diesel::insert_into(applicant_withdrawals::table)
+        .values(NewApplicantWithdrawal {
+            applicant_id: applicant.id,
+            email: applicant.email.to_string(),
+            role: applicant.role.to_string(),
+            stage: stage.to_string(),
+            reason_category: categorize_withdrawal_reason(&reason),
+            reason,
+            source: source.to_string(),
+            confirmed_at: Some(Utc::now()),
+            cio_company_id: company.id,
+        })
+        .get_result_async::<ApplicantWithdrawal>(db.pool())
+        .await?;
+    info!(
+        "{} withdrew from {} while in {}",
+        applicant.name, applicant.role, withdrawal.stage
+    );
+
+    if !panel.is_empty() {
+        notify_panel_of_withdrawal(company, &applicant, &withdrawal, &panel).await?;
+    }
+
+    Ok(withdrawal)
+}
+
+/// Queue the withdrawal of the candidate who sent an email, if they are asking for it. Someone
+/// on the recruiting team confirms it before the candidate is withdrawn, we can read an email
+/// wrong. Returns `None` for the emails that are not withdrawals.
+pub async fn queue_withdrawal_from_email(
+    db: &Database,
+    company: &Company,
+    email: &InboundEmail,
+) -> Result<Option<ApplicantWithdrawal>> {
+    // Anyone can send an email from the address of a candidate.
+    if !email.is_authenticated() {
+        info!(
+            "email `{}` from {} is not authenticated, ignoring",
+            email.subject, email.from
+        );
+        return Ok(None);
+    }
+
+    let parsed = match parse_withdrawal_email(email) {
+        Some(parsed) => parsed,
+        None => {
+            info!("email `{}` from {} is not a withdrawal", email.subject, email.from);
+            return Ok(None);
+        }
+    };
+
+    let applicant = match find_application(db, company, &parsed.email, None).await? {
+        Some(applicant) => applicant,
+        None => {
+            info!(
+                "no application from `{}` to withdraw, ignoring their email",
+                parsed.email
+            );
+            return Ok(None);
+        }
+    };
+    let stage = match withdrawable_stage(&applicant) {
+        Ok(stage) => stage,
+        Err(e) => {
+            info!("{}, ignoring their email", e);
+            return Ok(None);
+        }
+    };
+
+    // Candidates often follow up, one withdrawal to confirm is enough.
+    if let Ok(pending) = applicant_withdrawals::dsl::applicant_withdrawals
+        .filter(applicant_withdrawals::dsl::applicant_id.eq(applicant.id))
+        .filter(applicant_withdrawals::dsl::cio_company_id.eq(company.id))
+        .filter(applicant_withdrawals::dsl::confirmed_at.is_null())
+        .first_async::<ApplicantWithdrawal>(db.pool())
+        .await
+    {
+        info!(
+            "the withdrawal of {} is already waiting for a confirmation",
+            applicant.name
+        );
+        return Ok(Some(pending));
+    }
+
+    let withdrawal = diesel::insert_into(applicant_withdrawals::table)
+        .values(NewApplicantWithdrawal {
+            applicant_id: applicant.id,
+            email: applicant.email.to_string(),
+            role: applicant.role.to_string(),
+            stage: stage.to_string(),
+            reason_category: categorize_withdrawal_reason(&parsed.reason),
+            reason: parsed.reason,
+            source: WITHDRAWAL_SOURCE_EMAIL.to_string(),
+            confirmed_at: None,
+            cio_company_id: company.id,
+        })
+        .get_result_async::<ApplicantWithdrawal>(db.pool())
+        .await?;
+    info!(
+        "queued the withdrawal of {} from {} for a confirmation",
+        applicant.name, applicant.role
+    );
+
+    notify_recruiting_of_withdrawal(db, company, &applicant, &withdrawal).await?;
+
+    Ok(Some(withdrawal))
+}
+
+/// Confirm a withdrawal we found in an email, and withdraw the applicant.
+pub async fn confirm_applicant_withdrawal(db: &Database, company: &Company, id: i32) -> Result<ApplicantWithdrawal> {
+    // Claim the withdrawal, so it is only confirmed once.
+    let withdrawal = match diesel::update(applicant_withdrawals::dsl::applicant_withdrawals)
+        .filter(applicant_withdrawals::dsl::id.eq(id))
+        .filter(applicant_withdrawals::dsl::cio_company_id.eq(company.id))
+        .filter(applicant_withdrawals::dsl::confirmed_at.is_null())
+        .set(applicant_withdrawals::dsl::confirmed_at.eq(Utc::now()))
+        .get_results_async::<ApplicantWithdrawal>(db.pool())
+        .await?
+        .pop()
+    {
+        Some(withdrawal) => withdrawal,
+        None => bail!("no withdrawal {} waiting for a confirmation", id),
+    };
+
+    match complete_confirmed_withdrawal(db, company, withdrawal.clone()).await {
+        Ok(withdrawal) => Ok(withdrawal),
+        Err(e) => {
+            // Give the withdrawal back, so it can be confirmed again.
+            diesel::update(applicant_withdrawals::dsl::applicant_withdrawals)
+                .filter(applicant_withdrawals::dsl::id.eq(withdrawal.id))
+                .set(applicant_withdrawals::dsl::confirmed_at.eq(None::<DateTime<Utc>>))
+                .execute_async(db.pool())
+                .await?;
+
+            Err(e)
+        }
+    }
+}
+
+async fn complete_confirmed_withdrawal(
+    db: &Database,
+    company: &Company,
+    withdrawal: ApplicantWithdrawal,
+) -> Result<ApplicantWithdrawal> {
+    let applicant = Applicant::get_by_id_for_company(db, company.id, withdrawal.applicant_id).await?;
+    let stage = withdrawable_stage(&applicant)?;
+
+    let (applicant, panel) = complete_withdrawal(db, company, applicant, &stage).await?;
+
+    // The applicant might have moved on since they emailed us.
+    let withdrawal = diesel::update(applicant_withdrawals::dsl::applicant_withdrawals)
+        .filter(applicant_withdrawals::dsl::id.eq(withdrawal.id))
+        .set(applicant_withdrawals::dsl::stage.eq(stage.to_string()))
+        .get_result_async::<ApplicantWithdrawal>(db.pool())
+        .await?;
+    info!(
+        "{} withdrew from {} while in {}",
+        applicant.name, applicant.role, withdrawal.stage
+    );
+
+    if !panel.is_empty() {
+        notify_panel_of_withdrawal(company, &applicant, &withdrawal, &panel).await?;
+    }
+
+    Ok(withdrawal)
+}
+
+/// Cancel events on the interviews calendar, letting the attendees know.
+async fn cancel_interview_events(db: &Database, company: &Company, event_ids: &[String]) -> Result<()> {
+    let gcal = company.authenticate_google_calendar(db).await?;
+
+    let calendar_id = match get_interviews_calendar_id(&gcal).await? {
+        Some(calendar_id) => calendar_id,
+        None => bail!("could not find the interviews calendar for company {}", company.name),
+    };
+
+    for event_id in event_ids {
+        if let Err(e) = gcal
+            .events()
+            .delete(
+                &calendar_id,
+                event_id,
+                true, // send notifications
+                google_calendar::types::SendUpdates::All,
+            )
+            .await
+        {
+            // The event might have been cancelled on the calendar already.
+            warn!("could not cancel event {} on the interviews calendar: {}", event_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn notify_panel_of_withdrawal(
+    company: &Company,
+    applicant: &Applicant,
+    withdrawal: &ApplicantWithdrawal,
+    panel: &[String],
+) -> Result<()> {
+    let mut msg = format!(
+        "{} withdrew their application for {}, so their upcoming interviews are cancelled.",
+        applicant.name, applicant.role
+    );
+    if !withdrawal.reason.is_empty() {
+        msg += &format!("\n\nThey told us:\n\n{}", withdrawal.reason);
+    }
+    msg += "\n\nThank you for the time you set aside for them!";
+
+    let sendgrid_client = SendGrid::new_from_env();
+    sendgrid_client
+        .mail_send()
+        .send_plain_text(
+            &format!("[applicants] {} withdrew", applicant.name),
+            &msg,
+            panel,
+            &[],
+            &[],
+            &format!("careers@{}", company.gsuite_domain),
+        )
+        .await?;
+    info!("told the panel of {} that they withdrew", applicant.name);
+
+    Ok(())
+}
+
+/// Ask the recruiting team to confirm a withdrawal we found in an email.
+async fn notify_recruiting_of_withdrawal(
+    db: &Database,
+    company: &Company,
+    applicant: &Applicant,
+    withdrawal: &ApplicantWithdrawal,
+) -> Result<()> {
+    if company.slack_channel_applicants.is_empty() {
+        warn!(
+            "company {} has no applicants channel, nobody will confirm the withdrawal of {}",
+            company.name, applicant.name
+        );
+        return Ok(());
+    }
+
+    let msg = FormattedMessage {
+        channel: company.slack_channel_applicants.to_string(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "{} emailed us about withdrawing their application for {}:\n>{}\nIf they are \
+                     withdrawing, confirm withdrawal {} with `POST /applicant/withdrawals/{}/confirm` and their \
+                     upcoming interviews will be cancelled.",
+                    applicant.name,
+                    applicant.role,
+                    withdrawal.reason.replace('\n', "\n>"),
+                    withdrawal.id,
+                    withdrawal.id
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+        attachments: Default::default(),
+    };
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{categorize_withdrawal_reason, is_withdrawal, parse_withdrawal_email, WithdrawalEmail};
+    use crate::inbound_email::InboundEmail;
+
+    #[test]
+    fn test_parse_withdrawal_email() {
+        let email = InboundEmail {
+            from: "Jane Doe <Jane@Example.com>".to_string(),
+            subject: "Re: Your interviews".to_string(),
+            text: "Hi,\n\nI accepted another offer, so I'd like to withdraw.\n\nThanks!\n\nOn Mon, Jan 1, 2024 at \
+                   9:00 AM Careers <careers@example.com> wrote:\n> We are not moving forward with interviews \
+                   yet.\n"
+                .to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Some(WithdrawalEmail {
+                email: "jane@example.com".to_string(),
+                reason: "Hi,\n\nI accepted another offer, so I'd like to withdraw.\n\nThanks!".to_string(),
+            }),
+            parse_withdrawal_email(&email)
+        );
+
+        // What we wrote them does not count, only their reply does.
+        let email = InboundEmail {
+            from: "jane@example.com".to_string(),
+            subject: "Re: Your interviews".to_string(),
+            text: "Sounds good, see you then!\n\n> You can withdraw at any time.\n".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(None, parse_withdrawal_email(&email));
+    }
+
+    #[test]
+    fn test_is_withdrawal() {
+        assert!(is_withdrawal("I accepted another offer, so I'd like to withdraw."));
+        assert!(is_withdrawal("I am withdrawing from the process"));
+        assert!(is_withdrawal("Thanks for your time! I am no longer interested."));
+        assert!(is_withdrawal("Re: Interviews\nPlease remove my application"));
+
+        assert!(!is_withdrawal("I will not withdraw"));
+        assert!(!is_withdrawal("Should I withdraw my other applications?"));
+        assert!(!is_withdrawal("I won\u{2019}t decline the offer"));
+        assert!(!is_withdrawal("I can't decline the offer, it is great."));
+        assert!(!is_withdrawal("If I withdraw, can I apply again later?"));
+        assert!(!is_withdrawal(
+            "Let me know whether I need to withdraw my other application."
+        ));
+        assert!(!is_withdrawal("Looking forward to it!"));
+    }
+
+    #[test]
+    fn test_categorize_withdrawal_reason() {
+        assert_eq!("other offer", categorize_withdrawal_reason("I Accepted Another Offer."));
+        assert_eq!("compensation", categorize_withdrawal_reason("The salary is too low"));
+        assert_eq!("other", categorize_withdrawal_reason(""));
+    }
+}
@@ -33,7 +33,9 @@ use crate::{
     core::UpdateAirtableRecord,
     db::Database,
     enclose,
+    greenhouse::GREENHOUSE_SHEET_ID_PREFIX,
     interviews::ApplicantInterview,
+    roles::{Role, DEFAULT_CHECKR_PACKAGE},
     schema::{applicant_interviews, applicant_reviewers, applicants, users},
     utils::{check_if_github_issue_exists, truncate},
 };
@@ -243,6 +245,32 @@ pub struct NewApplicant {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub link_to_reviews: Vec<String>,
 
+    /// The debrief we scheduled with the panel once their interviews were done.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub debrief_event_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub debrief_event_link: String,
+
+    /// The locale we send the applicant emails in. If the applicant did not pick one, we use
+    /// the locale of the role they applied for.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub locale: String,
+
+    /// The employers the applicant worked at, like `Acme (2016-2020)`, and who at the company
+    /// referred them. Recruiters fill these in Airtable, we check the interview panel against
+    /// them for conflicts of interest.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_employers: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub referred_by: String,
+
+    /// The roll-up of the scorecards the interviewers submitted in Slack: how many, and the
+    /// average of their overall ratings, from 1 for a strong no to 4 for a strong yes.
+    #[serde(default)]
+    pub interview_scorecards_count: i32,
+    #[serde(default)]
+    pub interview_scorecard_average: f32,
+
     /// The CIO company ID.
     #[serde(default)]
     pub cio_company_id: i32,
@@ -290,6 +318,7 @@ fn get_color_based_on_status(s: &str) -> String {
         crate::applicant_status::Status::Contractor => crate::colors::Colors::Green,
         crate::applicant_status::Status::NeedsToBeTriaged => crate::colors::Colors::Yellow,
         crate::applicant_status::Status::Interviewing => crate::colors::Colors::Blue,
+        crate::applicant_status::Status::Debrief => crate::colors::Colors::Blue,
         crate::applicant_status::Status::Onboarding => crate::colors::Colors::Green,
         crate::applicant_status::Status::Withdrawn => crate::colors::Colors::Red,
     };
@@ -540,6 +569,7 @@ impl Applicant {
         // Let's query the interviews for this candidate.
         let data = applicant_interviews::dsl::applicant_interviews
             .filter(applicant_interviews::dsl::applicant.contains(vec![self.airtable_record_id.to_string()]))
+            .filter(applicant_interviews::dsl::cancelled_at.is_null())
             .order_by(applicant_interviews::dsl::start_time.asc())
             .load_async::<ApplicantInterview>(db.pool())
             .await
@@ -568,7 +598,7 @@ impl Applicant {
         self.keep_fields_from_airtable(db).await;
 
         // Create the Airtable client.
-        let company = Company::get_by_id(db, self.cio_company_id).await?;
+        let company = Company::get_from_id(db, self.cio_company_id).await?;
         let airtable = company.authenticate_airtable(&company.airtable_base_id_hiring);
 
         // We need to capture the existing score count prior to mutations to ensure that
@@ -620,7 +650,7 @@ impl Applicant {
                         .await?;
 
                     // Delete the record if it exists in the Database.
-                    let r = ApplicantReview::get_by_id(db, record.fields.id).await?;
+                    let r = ApplicantReview::get_by_id_for_company(db, self.cio_company_id, record.fields.id).await?;
 
                     // Delete it.
                     r.delete(db).await?;
@@ -747,6 +777,11 @@ impl Applicant {
         HumanTime::from(dur)
     }
 
+    /// Get the role from our roles catalog that matches the role the applicant applied for.
+    pub async fn catalog_role(&self, db: &Database) -> Result<Option<Role>> {
+        Role::get_for_applicant_role(db, self.cio_company_id, &self.role).await
+    }
+
     /// Send an invite to the applicant to do a background check.
     pub async fn send_background_check_invitation(&mut self, db: &Database) -> Result<()> {
         // Keep the fields from Airtable we need just in case they changed.
@@ -761,6 +796,13 @@ impl Applicant {
 
         let checkr = checkr_auth.unwrap();
 
+        // Get the background check package for the applicant's role.
+        let package = self
+            .catalog_role(db)
+            .await?
+            .map(|role| role.background_check_package().to_string())
+            .unwrap_or_else(|| DEFAULT_CHECKR_PACKAGE.to_string());
+
         // Check if we already sent them an invitation.
         let candidates = checkr.list_candidates().await?;
         for candidate in candidates {
@@ -768,7 +810,7 @@ impl Applicant {
                 // Check if we already have sent their invitation.
                 if self.criminal_background_check_status.is_empty() {
                     // Create an invitation for the candidate.
-                    checkr.create_invitation(&candidate.id, "premium_criminal").await?;
+                    checkr.create_invitation(&candidate.id, &package).await?;
 
                     // Update the database.
                     self.criminal_background_check_status = "requested".to_string();
@@ -787,7 +829,7 @@ impl Applicant {
         let candidate = checkr.create_candidate(&self.email).await?;
 
         // Create an invitation for the candidate.
-        checkr.create_invitation(&candidate.id, "premium_criminal").await?;
+        checkr.create_invitation(&candidate.id, &package).await?;
 
         // Update the database.
         self.criminal_background_check_status = "requested".to_string();
@@ -1172,6 +1214,8 @@ impl UpdateAirtableRecord<Applicant> for Applicant {
         self.interviews = record.interviews;
         self.geocode_cache = record.geocode_cache;
         self.link_to_reviews = record.link_to_reviews;
+        self.previous_employers = record.previous_employers;
+        self.referred_by = record.referred_by;
         self.resume_contents = truncate(&self.resume_contents, 100000);
         self.materials_contents = truncate(&self.materials_contents, 100000);
         self.question_why_oxide = truncate(&self.question_why_oxide, 100000);
@@ -1542,6 +1586,16 @@ impl Applicant {
             self.scoring_form_url = format!("https://apply.oxide.computer/review/{}", self.email.replace('+', "%2B"));
         }
 
+        // Send them emails in the locale of the role they applied for, unless they picked
+        // one themselves.
+        if self.locale.is_empty() {
+            if let Some(role) = self.catalog_role(db).await? {
+                if !role.locale.is_empty() {
+                    self.locale = crate::app_config::normalize_locale(&role.locale);
+                }
+            }
+        }
+
         // Check if we have sent them an email that we received their application.
         if !self.sent_email_received {
             let letter = config.create_received_letter(self);
@@ -1972,7 +2026,7 @@ The applicants Airtable \
         &mut self,
         db: &Database,
         ds: &DocuSign,
-        new_envelope: docusign::Envelope,
+        mut new_envelope: docusign::Envelope,
     ) -> Result<()> {
         // Keep the fields from Airtable we need just in case they changed.
         self.keep_fields_from_airtable(db).await;
@@ -1995,6 +2049,11 @@ The applicants Airtable \
                 self.name
             );
 
+            // Fill in the details of the role from our roles catalog.
+            if let Some(role) = self.catalog_role(db).await? {
+                role.fill_envelope(&mut new_envelope);
+            }
+
             // Let's create the envelope.
             let envelope = ds.create_envelope(new_envelope).await?;
 
@@ -2416,9 +2475,14 @@ pub async fn refresh_new_applicants_and_reviews(
         .await?;
 
     // We want all the applicants without a sheet id, since this is the list of applicants we care
-    // about. Everything else came from Google Sheets and therefore uses the old system.
+    // about, and the ones we import from Greenhouse. Everything else came from Google Sheets and
+    // therefore uses the old system.
     let applicants_id_range: (Option<i32>, Option<i32>) = applicants::dsl::applicants
-        .filter(applicants::dsl::sheet_id.eq("".to_string()))
+        .filter(
+            applicants::dsl::sheet_id
+                .eq("".to_string())
+                .or(applicants::dsl::sheet_id.like(format!("{}%", GREENHOUSE_SHEET_ID_PREFIX))),
+        )
         .select((diesel::dsl::min(applicants::id), diesel::dsl::max(applicants::id)))
         .first_async(db.pool())
         .await?;
@@ -2433,7 +2497,11 @@ pub async fn refresh_new_applicants_and_reviews(
             log::info!("Fetching applicants {:?} through {:?}", chunk.first(), chunk.last());
 
             let applicants = applicants::dsl::applicants
-                .filter(applicants::dsl::sheet_id.eq("".to_string()))
+                .filter(
+                    applicants::dsl::sheet_id
+                        .eq("".to_string())
+                        .or(applicants::dsl::sheet_id.like(format!("{}%", GREENHOUSE_SHEET_ID_PREFIX))),
+                )
                 .filter(applicants::dsl::id.eq_any(chunk.to_vec()))
                 .order_by(applicants::dsl::id.asc())
                 .load_async::<Applicant>(db.pool())
@@ -2573,6 +2641,13 @@ pub mod tests {
             piia_envelope_created: None,
             piia_envelope_completed: None,
             link_to_reviews: vec![],
+            debrief_event_id: String::default(),
+            debrief_event_link: String::default(),
+            locale: String::default(),
+            previous_employers: Vec::default(),
+            referred_by: String::default(),
+            interview_scorecards_count: 0,
+            interview_scorecard_average: 0.0,
             cio_company_id: 0,
             airtable_record_id: String::default(),
         }
@@ -34,6 +34,9 @@ pub struct ApplicationForm {
     pub materials: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub portfolio_pdf: String,
+    /// The locale the applicant would like to hear from us in, ie. "fr".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub locale: String,
     #[serde(default)]
     pub cio_company_id: i32,
 }
@@ -100,7 +103,7 @@ impl ApplicationForm {
         // Add the applicant to the database.
         let mut applicant = new_applicant.upsert(db).await?;
 
-        let company = Company::get_by_id(db, applicant.cio_company_id).await?;
+        let company = Company::get_from_id(db, applicant.cio_company_id).await?;
 
         // Initialize the GSuite sheets client.
         let drive_client = company.authenticate_google_drive(db).await?;
@@ -192,6 +195,17 @@ impl From<ApplicationForm> for NewApplicant {
             piia_envelope_created: Default::default(),
             piia_envelope_completed: Default::default(),
             link_to_reviews: Default::default(),
+            debrief_event_id: Default::default(),
+            debrief_event_link: Default::default(),
+            locale: if form.locale.trim().is_empty() {
+                Default::default()
+            } else {
+                crate::app_config::normalize_locale(&form.locale)
+            },
+            previous_employers: Default::default(),
+            referred_by: Default::default(),
+            interview_scorecards_count: Default::default(),
+            interview_scorecard_average: Default::default(),
             cio_company_id: form.cio_company_id,
         }
     }
@@ -0,0 +1,380 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::info;
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    airtable::AIRTABLE_ASSET_AUDITS_TABLE,
+    asset_insurance::AssetLocator,
+    asset_inventory::{AssetItem, AssetItems},
+    asset_orders::ORDERED_STATUS,
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    locations::{get_locations, match_location},
+    schema::{asset_audit_scans, asset_audits},
+};
+
+/// An audit of the asset items, where people walk around and scan everything they find. When
+/// the audit is finished, it records what did not match the database.
+#[db {
+    new_struct_name = "AssetAudit",
+    airtable_base = "assets",
+    airtable_table = "AIRTABLE_ASSET_AUDITS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "name" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = asset_audits)]
+pub struct NewAssetAudit {
+    /// The location and when the audit started, like `Oakland 2023-09-04 10:00`.
+    pub name: String,
+    /// The location being audited, or empty if we are auditing everything.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub location: String,
+    /// The location being audited, matched from `location`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub started_by: String,
+    pub started_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// The number of items we expected to find.
+    #[serde(default)]
+    pub expected_count: i32,
+    /// The number of items in the database that were scanned.
+    #[serde(default)]
+    pub scanned_count: i32,
+    /// The items we expected to find but nobody scanned.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not_scanned: Vec<String>,
+    /// The barcodes that were scanned but are not in the database.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unknown_barcodes: Vec<String>,
+    /// The items that were scanned somewhere else than where we think they are.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub location_mismatches: Vec<String>,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a AssetAudit.
+#[async_trait]
+impl UpdateAirtableRecord<AssetAudit> for AssetAudit {
+    async fn update_airtable_record(&mut self, _record: AssetAudit) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A scan of a barcode during an audit.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct AssetAuditScan {
+    pub id: i32,
+    pub asset_audit_id: i32,
+    pub barcode: String,
+    /// The item with the barcode, or empty if it is not in the database.
+    pub asset_item_name: String,
+    /// Where it was scanned.
+    pub location: String,
+    pub scanned_by: String,
+    pub scanned_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = asset_audit_scans)]
+struct NewAssetAuditScan {
+    asset_audit_id: i32,
+    barcode: String,
+    asset_item_name: String,
+    location: String,
+    scanned_by: String,
+    scanned_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// A barcode scanned during an audit.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct AuditScan {
+    /// The value of the barcode, or the link in the QR code.
+    pub barcode: String,
+    /// Where it was scanned, the location of the audit if it is empty.
+    #[serde(default)]
+    pub location: String,
+    pub email: String,
+}
+
+/// What did not match between the scans of an audit and the database.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssetAuditReconciliation {
+    pub expected_count: i32,
+    pub scanned_count: i32,
+    pub not_scanned: Vec<String>,
+    pub unknown_barcodes: Vec<String>,
+    pub location_mismatches: Vec<String>,
+}
+
+/// Compare the scans of an audit of a location, or of everything if it is empty, to the items
+/// we have. We expect to find the items we think are at the location, except the ones that were
+/// ordered and did not arrive yet.
+pub fn reconcile_asset_audit<F>(
+    location: &str,
+    items: &[AssetItem],
+    scans: &[AssetAuditScan],
+    locate: F,
+) -> AssetAuditReconciliation
+where
+    F: Fn(&AssetItem) -> String,
+{
+    let location = location.trim();
+    let expected: Vec<&AssetItem> = items
+        .iter()
+        .filter(|i| i.status != ORDERED_STATUS)
+        .filter(|i| location.is_empty() || locate(i).eq_ignore_ascii_case(location))
+        .collect();
+
+    // Where each item was last scanned.
+    let mut scanned: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut unknown_barcodes: BTreeSet<String> = BTreeSet::new();
+    for scan in scans {
+        if scan.asset_item_name.is_empty() {
+            unknown_barcodes.insert(scan.barcode.to_string());
+        } else {
+            scanned.insert(&scan.asset_item_name, scan.location.trim());
+        }
+    }
+
+    let not_scanned = expected
+        .iter()
+        .filter(|i| !scanned.contains_key(i.name.as_str()))
+        .map(|i| i.name.to_string())
+        .collect();
+
+    let mut location_mismatches = Vec::new();
+    for (name, scanned_at) in &scanned {
+        if scanned_at.is_empty() {
+            continue;
+        }
+        if let Some(item) = items.iter().find(|i| i.name == *name) {
+            let expected_at = locate(item);
+            if !expected_at.eq_ignore_ascii_case(scanned_at) {
+                location_mismatches.push(format!("{} found in {}, expected in {}", name, scanned_at, expected_at));
+            }
+        }
+    }
+
+    AssetAuditReconciliation {
+        expected_count: expected.len() as i32,
+        scanned_count: scanned.len() as i32,
+        not_scanned,
+        unknown_barcodes: unknown_barcodes.into_iter().collect(),
+        location_mismatches,
+    }
+}
+
+/// Get an audit of the company.
+async fn get_asset_audit(db: &Database, company: &Company, id: i32) -> Result<AssetAudit> {
+    match AssetAudit::get_by_id_for_company(db, company.id, id).await {
+        Ok(audit) => Ok(audit),
+        Err(e) => bail!("could not find asset audit `{}`: {}", id, e),
+    }
+}
+
+/// Start an audit of a location, or of everything if it is empty.
+pub async fn start_asset_audit(db: &Database, company: &Company, location: &str, email: &str) -> Result<AssetAudit> {
+    let email = email.trim().to_lowercase();
+    if email.is_empty() {
+        bail!("an audit needs the email of who started it");
+    }
+
+    let now = Utc::now();
+    let location = location.trim();
+    let locations = get_locations(db, company.id).await?;
+    let audit = NewAssetAudit {
+        name: format!(
+            "{} {}",
+            if location.is_empty() { "All" } else { location },
+            now.format("%Y-%m-%d %H:%M")
+        ),
+        location: location.to_string(),
+        location_id: match_location(&locations, location).map(|l| l.id),
+        started_by: email,
+        started_at: now,
+        finished_at: None,
+        expected_count: 0,
+        scanned_count: 0,
+        not_scanned: Default::default(),
+        unknown_barcodes: Default::default(),
+        location_mismatches: Default::default(),
+        cio_company_id: company.id,
+    }
+    .upsert(db)
+    .await?;
+    info!("{} started asset audit `{}`", audit.started_by, audit.name);
+
+    Ok(audit)
+}
+
+/// Record the scan of a barcode during an audit.
+pub async fn scan_asset_audit(
+    db: &Database,
+    company: &Company,
+    audit_id: i32,
+    scan: &AuditScan,
+) -> Result<AssetAuditScan> {
+    let code = scan.barcode.trim();
+    let email = scan.email.trim().to_lowercase();
+    if code.is_empty() || email.is_empty() {
+        bail!("a scan needs both the barcode and the email of who scanned it");
+    }
+
+    let audit = get_asset_audit(db, company, audit_id).await?;
+    if audit.finished_at.is_some() {
+        bail!("asset audit `{}` is already finished", audit.name);
+    }
+
+    // Barcodes we do not know are recorded too, they end up in the report.
+    let asset_item_name = AssetItems::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .find(|i| i.barcode == code || i.qr_code == code)
+        .map(|i| i.name)
+        .unwrap_or_default();
+
+    let location = if scan.location.trim().is_empty() {
+        audit.location.to_string()
+    } else {
+        scan.location.trim().to_string()
+    };
+
+    let scan = diesel::insert_into(asset_audit_scans::table)
+        .values(NewAssetAuditScan {
+            asset_audit_id: audit.id,
+            barcode: code.to_string(),
+            asset_item_name,
+            location,
+            scanned_by: email,
+            scanned_at: Utc::now(),
+            cio_company_id: company.id,
+        })
+        .get_result_async::<AssetAuditScan>(db.pool())
+        .await?;
+
+    Ok(scan)
+}
+
+/// Finish an audit and save what did not match to Airtable.
+pub async fn finish_asset_audit(db: &Database, company: &Company, audit_id: i32) -> Result<AssetAudit> {
+    let mut audit = get_asset_audit(db, company, audit_id).await?;
+    if audit.finished_at.is_some() {
+        bail!("asset audit `{}` is already finished", audit.name);
+    }
+
+    let scans = asset_audit_scans::dsl::asset_audit_scans
+        .filter(asset_audit_scans::dsl::asset_audit_id.eq(audit.id))
+        .order_by(asset_audit_scans::dsl::scanned_at)
+        .load_async::<AssetAuditScan>(db.pool())
+        .await?;
+    let items: Vec<AssetItem> = AssetItems::get_from_db(db, company.id).await?.into_iter().collect();
+    let locator = AssetLocator::new(db, company).await?;
+
+    let reconciliation = reconcile_asset_audit(&audit.location, &items, &scans, |item| locator.locate(item));
+
+    audit.finished_at = Some(Utc::now());
+    audit.expected_count = reconciliation.expected_count;
+    audit.scanned_count = reconciliation.scanned_count;
+    audit.not_scanned = reconciliation.not_scanned;
+    audit.unknown_barcodes = reconciliation.unknown_barcodes;
+    audit.location_mismatches = reconciliation.location_mismatches;
+
+    let audit = audit.update(db).await?;
+    info!(
+        "finished asset audit `{}`: {} of {} items scanned",
+        audit.name, audit.scanned_count, audit.expected_count
+    );
+
+    Ok(audit)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::Utc;
+
+    use super::{reconcile_asset_audit, AssetAuditScan};
+    use crate::asset_inventory::AssetItem;
+    use crate::tests::mock_record;
+
+    fn mock_item(name: &str, status: &str) -> AssetItem {
+        let mut item: AssetItem = mock_record(json!({}));
+        item.name = name.to_string();
+        item.status = status.to_string();
+        item
+    }
+
+    fn mock_scan(barcode: &str, asset_item_name: &str, location: &str) -> AssetAuditScan {
+        AssetAuditScan {
+            id: 1,
+            asset_audit_id: 1,
+            barcode: barcode.to_string(),
+            asset_item_name: asset_item_name.to_string(),
+            location: location.to_string(),
+            scanned_by: "jane@example.com".to_string(),
+            scanned_at: Utc::now(),
+            cio_company_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_asset_audit() {
+        let items = vec![
+            mock_item("zeus", ""),
+            mock_item("apollo", ""),
+            mock_item("hera", ""),
+            mock_item("ares", "Ordered"),
+        ];
+        let locations: HashMap<&str, &str> = [
+            ("zeus", "Oakland"),
+            ("apollo", "Oakland"),
+            ("hera", "Emeryville"),
+            ("ares", "Oakland"),
+        ]
+        .iter()
+        .copied()
+        .collect();
+        let locate = |i: &AssetItem| locations[i.name.as_str()].to_string();
+        let scans = vec![
+            mock_scan("1", "zeus", "Oakland"),
+            mock_scan("3", "hera", "Oakland"),
+            mock_scan("99", "", "Oakland"),
+            mock_scan("99", "", "Oakland"),
+        ];
+
+        let reconciliation = reconcile_asset_audit("oakland", &items, &scans, locate);
+        assert_eq!(2, reconciliation.expected_count);
+        assert_eq!(2, reconciliation.scanned_count);
+        assert_eq!(vec!["apollo".to_string()], reconciliation.not_scanned);
+        assert_eq!(vec!["99".to_string()], reconciliation.unknown_barcodes);
+        assert_eq!(
+            vec!["hera found in Oakland, expected in Emeryville".to_string()],
+            reconciliation.location_mismatches
+        );
+
+        // Auditing everything expects every item that arrived.
+        let reconciliation = reconcile_asset_audit("", &items, &scans, locate);
+        assert_eq!(3, reconciliation.expected_count);
+        assert_eq!(vec!["apollo".to_string()], reconciliation.not_scanned);
+    }
+}
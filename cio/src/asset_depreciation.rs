@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate, Utc};
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    airtable::AIRTABLE_ASSET_DEPRECIATION_TABLE,
+    asset_inventory::{AssetItem, AssetItems},
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    schema::asset_depreciations,
+};
+
+/// The category we use for assets that do not have a type.
+static UNCATEGORIZED_ASSET_TYPE: &str = "Uncategorized";
+
+/// A straight-line depreciation schedule, down to nothing over a number of months.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepreciationSchedule {
+    pub months: u32,
+}
+
+impl DepreciationSchedule {
+    /// Parse a schedule like `36 months`, `3 years`, or `36`. Returns None for an empty
+    /// schedule, the item does not depreciate.
+    pub fn parse(schedule: &str) -> Result<Option<Self>> {
+        let schedule = schedule.trim().to_lowercase();
+        if schedule.is_empty() || schedule == "none" {
+            return Ok(None);
+        }
+
+        let mut parts = schedule.split_whitespace();
+        let count: u32 = match parts.next().map(|c| c.parse()) {
+            Some(Ok(count)) if count > 0 => count,
+            _ => bail!("invalid depreciation schedule `{}`", schedule),
+        };
+        let months = match parts.next() {
+            None | Some("month") | Some("months") => count,
+            Some("year") | Some("years") => count * 12,
+            Some(unit) => bail!("invalid depreciation schedule unit `{}`", unit),
+        };
+
+        Ok(Some(DepreciationSchedule { months }))
+    }
+
+    /// Return the value left after depreciating from a purchase date to the start of a month.
+    /// Items lose value for each whole month they have been owned, so the value only changes
+    /// once a month.
+    pub fn book_value(&self, purchase_price: f32, purchase_date: NaiveDate, month: NaiveDate) -> f32 {
+        let elapsed = (month.year() - purchase_date.year()) * 12 + month.month() as i32 - purchase_date.month() as i32;
+        let elapsed = elapsed.clamp(0, self.months as i32) as f32;
+        let value = purchase_price * (1.0 - elapsed / self.months as f32);
+
+        (value * 100.0).round() / 100.0
+    }
+}
+
+/// Return the first day of the month a date is in.
+pub fn month_start(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd(date.year(), date.month(), 1)
+}
+
+/// Return the first day of the month before.
+fn previous_month(month: NaiveDate) -> NaiveDate {
+    if month.month() == 1 {
+        NaiveDate::from_ymd(month.year() - 1, 12, 1)
+    } else {
+        NaiveDate::from_ymd(month.year(), month.month() - 1, 1)
+    }
+}
+
+impl AssetItem {
+    /// Return the item's book value at the start of a month. Items without a purchase date
+    /// or a depreciation schedule are worth what we paid for them.
+    pub fn book_value(&self, month: NaiveDate) -> Result<f32> {
+        let schedule = DepreciationSchedule::parse(&self.depreciation_schedule)?;
+        Ok(match (schedule, self.purchase_date) {
+            (Some(schedule), Some(purchase_date)) => schedule.book_value(self.purchase_price, purchase_date, month),
+            _ => self.purchase_price,
+        })
+    }
+}
+
+/// The depreciation of one type of asset for a month, as we report it to finance.
+#[db {
+    new_struct_name = "AssetDepreciation",
+    airtable_base = "finance",
+    airtable_table = "AIRTABLE_ASSET_DEPRECIATION_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "name" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = asset_depreciations)]
+pub struct NewAssetDepreciation {
+    /// The month and the type, like `2023-07 Laptop`.
+    pub name: String,
+    /// The first day of the month.
+    pub month: NaiveDate,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub asset_type: String,
+    #[serde(default)]
+    pub item_count: i32,
+    /// What we paid for the items.
+    #[serde(default)]
+    pub purchase_price: f32,
+    /// What the items are worth on our books at the start of the month.
+    #[serde(default)]
+    pub book_value: f32,
+    /// How much value the items lost since the start of the month before.
+    #[serde(default)]
+    pub depreciation: f32,
+    #[serde(default)]
+    pub accumulated_depreciation: f32,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a AssetDepreciation.
+#[async_trait]
+impl UpdateAirtableRecord<AssetDepreciation> for AssetDepreciation {
+    async fn update_airtable_record(&mut self, _record: AssetDepreciation) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Summarize the depreciation of the items for a month, by type of asset.
+pub fn depreciation_summary(company: &Company, items: &[AssetItem], month: NaiveDate) -> Vec<NewAssetDepreciation> {
+    let previous = previous_month(month);
+
+    let mut types: BTreeMap<String, NewAssetDepreciation> = BTreeMap::new();
+    for item in items {
+        let (value, previous_value) = match (item.book_value(month), item.book_value(previous)) {
+            (Ok(value), Ok(previous_value)) => (value, previous_value),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("could not depreciate asset item {}: {}", item.name, e);
+                continue;
+            }
+        };
+
+        let asset_type = if item.type_.trim().is_empty() {
+            UNCATEGORIZED_ASSET_TYPE.to_string()
+        } else {
+            item.type_.trim().to_string()
+        };
+        let summary = types
+            .entry(asset_type.to_string())
+            .or_insert_with(|| NewAssetDepreciation {
+                name: format!("{} {}", month.format("%Y-%m"), asset_type),
+                month,
+                asset_type,
+                item_count: 0,
+                purchase_price: 0.0,
+                book_value: 0.0,
+                depreciation: 0.0,
+                accumulated_depreciation: 0.0,
+                cio_company_id: company.id,
+            });
+        summary.item_count += 1;
+        summary.purchase_price += item.purchase_price;
+        summary.book_value += value;
+        summary.depreciation += previous_value - value;
+        summary.accumulated_depreciation += item.purchase_price - value;
+    }
+
+    types.into_values().collect()
+}
+
+/// Recalculate the book value of the asset items for the month, and push the summary of
+/// their depreciation to the finance base.
+pub async fn refresh_asset_depreciation(db: &Database, company: &Company) -> Result<()> {
+    let month = month_start(Utc::now().date().naive_utc());
+
+    let mut items: Vec<AssetItem> = Vec::new();
+    for mut item in AssetItems::get_from_db(db, company.id).await? {
+        match item.book_value(month) {
+            Ok(value) if (value - item.current_book_value).abs() >= 0.01 => {
+                item.current_book_value = value;
+                item = item.update(db).await?;
+            }
+            Ok(_) => (),
+            Err(e) => warn!("could not depreciate asset item {}: {}", item.name, e),
+        }
+
+        items.push(item);
+    }
+
+    if company.airtable_base_id_finance.is_empty() {
+        return Ok(());
+    }
+
+    let summary = depreciation_summary(company, &items, month);
+    for depreciation in &summary {
+        depreciation.upsert(db).await?;
+    }
+    info!(
+        "updated the depreciation of {} asset types for {}",
+        summary.len(),
+        month.format("%Y-%m")
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{depreciation_summary, DepreciationSchedule};
+    use crate::tests::mock_record;
+    use crate::{asset_inventory::AssetItem, companies::tests::mock_company};
+
+    fn mock_asset(type_: &str, purchase_price: f32, purchase_date: NaiveDate, schedule: &str) -> AssetItem {
+        let mut item: AssetItem = mock_record(json!({}));
+        item.type_ = type_.to_string();
+        item.purchase_price = purchase_price;
+        item.purchase_date = Some(purchase_date);
+        item.depreciation_schedule = schedule.to_string();
+        item
+    }
+
+    #[test]
+    fn test_depreciation_schedule() {
+        assert_eq!(None, DepreciationSchedule::parse("").unwrap());
+        assert_eq!(36, DepreciationSchedule::parse("3 years").unwrap().unwrap().months);
+        assert_eq!(24, DepreciationSchedule::parse("24").unwrap().unwrap().months);
+        assert!(DepreciationSchedule::parse("3 decades").is_err());
+        assert!(DepreciationSchedule::parse("0 months").is_err());
+
+        let schedule = DepreciationSchedule { months: 36 };
+        let purchased = NaiveDate::from_ymd(2023, 1, 20);
+        assert!((schedule.book_value(3600.0, purchased, NaiveDate::from_ymd(2023, 1, 1)) - 3600.0).abs() < 0.001);
+        assert!((schedule.book_value(3600.0, purchased, NaiveDate::from_ymd(2023, 7, 1)) - 3000.0).abs() < 0.001);
+        assert!(
+            schedule
+                .book_value(3600.0, purchased, NaiveDate::from_ymd(2027, 1, 1))
+                .abs()
+                < 0.001
+        );
+    }
+
+    #[test]
+    fn test_depreciation_summary() {
+        let purchased = NaiveDate::from_ymd(2023, 1, 1);
+        let items = vec![
+            mock_asset("Laptop", 3600.0, purchased, "36 months"),
+            mock_asset("Laptop", 1200.0, purchased, "1 year"),
+            mock_asset("", 500.0, purchased, ""),
+        ];
+
+        let summary = depreciation_summary(&mock_company(), &items, NaiveDate::from_ymd(2023, 7, 1));
+        assert_eq!(2, summary.len());
+
+        let laptops = &summary[0];
+        assert_eq!("2023-07 Laptop", laptops.name);
+        assert_eq!(2, laptops.item_count);
+        assert!((laptops.book_value - 3600.0).abs() < 0.001);
+        assert!((laptops.depreciation - 200.0).abs() < 0.001);
+        assert!((laptops.accumulated_depreciation - 1200.0).abs() < 0.001);
+
+        assert_eq!("Uncategorized", summary[1].asset_type);
+        assert!(summary[1].depreciation.abs() < 0.001);
+    }
+}
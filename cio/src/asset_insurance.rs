@@ -0,0 +1,394 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::BufWriter,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use google_drive::traits::{DriveOps, FileOps};
+use log::info;
+use printpdf::{Mm, PdfDocument};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_inventory::{AssetItem, AssetItems},
+    companies::Company,
+    configs::{Resources, Users},
+    db::Database,
+};
+
+/// The location we use for assets that are with an employee who is not assigned to a building.
+static REMOTE_LOCATION: &str = "Remote";
+/// The location we use for assets that are not with anyone or in any room.
+static UNASSIGNED_LOCATION: &str = "Unassigned";
+
+/// An asset as it appears in the insurance report.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InsuranceReportItem {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub manufacturer: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub model_number: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub serial_number: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    #[serde(default)]
+    pub replacement_value: f64,
+}
+
+/// The assets of one category at one location.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InsuranceReportGroup {
+    pub location: String,
+    pub category: String,
+    #[serde(default)]
+    pub count: i32,
+    #[serde(default)]
+    pub replacement_value: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<InsuranceReportItem>,
+}
+
+/// A summary of the replacement value of our assets by location and category, for the
+/// insurance renewal.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InsuranceReport {
+    pub generated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub count: i32,
+    #[serde(default)]
+    pub replacement_value: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<InsuranceReportGroup>,
+    /// The link to the CSV export in Google Drive.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub csv_link: String,
+    /// The link to the PDF export in Google Drive.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pdf_link: String,
+}
+
+#[derive(Debug, Serialize)]
+struct InsuranceReportRow<'a> {
+    #[serde(rename = "Location")]
+    location: &'a str,
+    #[serde(rename = "Category")]
+    category: &'a str,
+    #[serde(rename = "Name")]
+    name: &'a str,
+    #[serde(rename = "Manufacturer")]
+    manufacturer: &'a str,
+    #[serde(rename = "Model Number")]
+    model_number: &'a str,
+    #[serde(rename = "Serial Number")]
+    serial_number: &'a str,
+    #[serde(rename = "Status")]
+    status: &'a str,
+    #[serde(rename = "Replacement Value")]
+    replacement_value: String,
+}
+
+impl InsuranceReport {
+    /// Build the report from a list of assets, using `locate` to figure out where each asset is.
+    pub fn new<F>(items: &[AssetItem], locate: F) -> Self
+    where
+        F: Fn(&AssetItem) -> String,
+    {
+        let mut groups: BTreeMap<(String, String), InsuranceReportGroup> = BTreeMap::new();
+        for item in items {
+            let location = locate(item);
+            let category = if item.type_.trim().is_empty() {
+                "Other".to_string()
+            } else {
+                item.type_.trim().to_string()
+            };
+
+            let group = groups
+                .entry((location.to_string(), category.to_string()))
+                .or_insert_with(|| InsuranceReportGroup {
+                    location,
+                    category,
+                    ..Default::default()
+                });
+
+            let replacement_value = item.purchase_price as f64;
+            group.count += 1;
+            group.replacement_value += replacement_value;
+            group.items.push(InsuranceReportItem {
+                name: item.name.to_string(),
+                manufacturer: item.manufacturer.to_string(),
+                model_number: item.model_number.to_string(),
+                serial_number: item.serial_number.to_string(),
+                status: item.status.to_string(),
+                replacement_value,
+            });
+        }
+
+        let mut groups: Vec<InsuranceReportGroup> = groups.into_values().collect();
+        for group in groups.iter_mut() {
+            group.items.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        InsuranceReport {
+            generated_at: Utc::now(),
+            count: groups.iter().map(|g| g.count).sum(),
+            replacement_value: groups.iter().map(|g| g.replacement_value).sum(),
+            groups,
+            csv_link: String::new(),
+            pdf_link: String::new(),
+        }
+    }
+
+    /// Export the report as a CSV with one row per asset.
+    pub fn to_csv(&self) -> Result<Vec<u8>> {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        for group in &self.groups {
+            for item in &group.items {
+                wtr.serialize(InsuranceReportRow {
+                    location: &group.location,
+                    category: &group.category,
+                    name: &item.name,
+                    manufacturer: &item.manufacturer,
+                    model_number: &item.model_number,
+                    serial_number: &item.serial_number,
+                    status: &item.status,
+                    replacement_value: format!("{:.2}", item.replacement_value),
+                })?;
+            }
+        }
+
+        Ok(wtr.into_inner()?)
+    }
+
+    /// Export the report as a PDF, with a section for each location and category.
+    pub fn to_pdf(&self, company: &Company) -> Result<Vec<u8>> {
+        let pdf_width = Mm(215.9);
+        let pdf_height = Mm(279.4);
+        let pdf_margin = Mm(15.0);
+        let line_height = Mm(5.0);
+        let font_size = 9.0;
+
+        let title = format!("{} Asset Insurance Report", company.name);
+        let (doc, page1, layer1) = PdfDocument::new(&title, pdf_width, pdf_height, "Layer 1");
+        let font_bytes = include_bytes!("Inconsolata/Inconsolata-Regular.ttf").to_vec();
+        let font = doc.add_external_font(&*font_bytes)?;
+
+        let mut lines: Vec<String> = vec![
+            title.to_string(),
+            format!("Generated {}", self.generated_at.format("%Y-%m-%d %H:%M UTC")),
+            format!(
+                "{} assets, total replacement value ${:.2}",
+                self.count, self.replacement_value
+            ),
+        ];
+        for group in &self.groups {
+            lines.push(String::new());
+            lines.push(format!(
+                "{} / {}: {} assets, ${:.2}",
+                group.location, group.category, group.count, group.replacement_value
+            ));
+            for item in &group.items {
+                lines.push(format!(
+                    "  {:<28} {:<30} S/N {:<20} ${:>10.2}",
+                    truncate_column(&item.name, 28),
+                    truncate_column(&format!("{} {}", item.manufacturer, item.model_number), 30),
+                    truncate_column(&item.serial_number, 20),
+                    item.replacement_value
+                ));
+            }
+        }
+
+        let mut current_layer = doc.get_page(page1).get_layer(layer1);
+        let mut y = pdf_height - pdf_margin;
+        for line in lines {
+            if y < pdf_margin {
+                let (page, layer) = doc.add_page(pdf_width, pdf_height, "Layer 1");
+                current_layer = doc.get_page(page).get_layer(layer);
+                y = pdf_height - pdf_margin;
+            }
+
+            if !line.is_empty() {
+                current_layer.use_text(line, font_size, pdf_margin, y, &font);
+            }
+            y -= line_height;
+        }
+
+        // Save the PDF
+        let mut bw = BufWriter::new(Vec::new());
+
+        doc.save(&mut bw)?;
+
+        Ok(bw.into_inner()?)
+    }
+}
+
+fn truncate_column(s: &str, width: usize) -> String {
+    s.trim().chars().take(width).collect()
+}
+
+/// Figures out where asset items are: in the building of the conference room using them, or
+/// of the employee borrowing them.
+pub struct AssetLocator {
+    /// The location of the conference rooms, by their Airtable record id.
+    rooms: HashMap<String, String>,
+    /// The building of the employees, by their email.
+    users: HashMap<String, String>,
+}
+
+impl AssetLocator {
+    pub async fn new(db: &Database, company: &Company) -> Result<Self> {
+        // Conference rooms are linked by their Airtable record id.
+        let rooms: HashMap<String, String> = Resources::get_from_db(db, company.id)
+            .await?
+            .into_iter()
+            .map(|r| {
+                let location = if r.building.is_empty() { r.name } else { r.building };
+                (r.airtable_record_id, location)
+            })
+            .collect();
+        let users: HashMap<String, String> = Users::get_from_db(db, company.id)
+            .await?
+            .into_iter()
+            .map(|u| (u.email.to_lowercase(), u.building))
+            .collect();
+
+        Ok(AssetLocator { rooms, users })
+    }
+
+    /// Return where an item is, `Remote` if it is with an employee outside of our buildings
+    /// and `Unassigned` if it is not with anyone or in any room.
+    pub fn locate(&self, item: &AssetItem) -> String {
+        if let Some(location) = item.conference_room_using.iter().find_map(|id| self.rooms.get(id)) {
+            return location.to_string();
+        }
+
+        let borrower = item.current_employee_borrowing.trim().to_lowercase();
+        if !borrower.is_empty() {
+            return match self.users.get(&borrower) {
+                Some(building) if !building.is_empty() => building.to_string(),
+                _ => REMOTE_LOCATION.to_string(),
+            };
+        }
+
+        UNASSIGNED_LOCATION.to_string()
+    }
+}
+
+/// Generate the insurance report for all of a company's assets, and upload the CSV and PDF
+/// exports to Google Drive.
+pub async fn generate_insurance_report(db: &Database, company: &Company) -> Result<InsuranceReport> {
+    let items: Vec<AssetItem> = AssetItems::get_from_db(db, company.id).await?.into_iter().collect();
+    let locator = AssetLocator::new(db, company).await?;
+
+    let mut report = InsuranceReport::new(&items, |item| locator.locate(item));
+
+    // Initialize the Google Drive client.
+    let drive_client = company.authenticate_google_drive(db).await?;
+
+    // Figure out where our directory is.
+    // It should be in the shared drive : "Automated Documents"/"assets"/"insurance"
+    let shared_drive = drive_client.drives().get_by_name("Automated Documents").await?;
+    let drive_id = shared_drive.id.to_string();
+    let assets_id = drive_client.files().create_folder(&drive_id, "", "assets").await?;
+    let parent_id = drive_client
+        .files()
+        .create_folder(&drive_id, &assets_id, "insurance")
+        .await?;
+
+    let file_name = format!("Insurance Report {}", report.generated_at.format("%Y-%m-%d"));
+
+    let csv_file = drive_client
+        .files()
+        .create_or_update(
+            &drive_id,
+            &parent_id,
+            &format!("{}.csv", file_name),
+            "text/csv",
+            &report.to_csv()?,
+        )
+        .await?;
+    report.csv_link = format!("https://drive.google.com/uc?export=download&id={}", csv_file.id);
+
+    let pdf_file = drive_client
+        .files()
+        .create_or_update(
+            &drive_id,
+            &parent_id,
+            &format!("{}.pdf", file_name),
+            "application/pdf",
+            &report.to_pdf(company)?,
+        )
+        .await?;
+    report.pdf_link = format!("https://drive.google.com/uc?export=download&id={}", pdf_file.id);
+
+    info!(
+        "generated insurance report for {} assets worth ${:.2}",
+        report.count, report.replacement_value
+    );
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::mock_record;
+
+    fn mock_asset(name: &str, type_: &str, serial_number: &str, purchase_price: f32, borrower: &str) -> AssetItem {
+        let mut item: AssetItem = mock_record(json!({}));
+        item.name = name.to_string();
+        item.type_ = type_.to_string();
+        item.serial_number = serial_number.to_string();
+        item.purchase_price = purchase_price;
+        item.current_employee_borrowing = borrower.to_string();
+        item
+    }
+
+    #[test]
+    fn test_insurance_report_groups_by_location_and_category() {
+        let items = vec![
+            mock_asset("zeus", "Laptop", "SN3", 2000.0, "jane@example.com"),
+            mock_asset("apollo", "Laptop", "SN1", 1500.5, "jane@example.com"),
+            mock_asset("hermes", "Monitor", "SN2", 400.0, ""),
+            mock_asset("athena", "", "SN4", 100.0, ""),
+        ];
+
+        let report = InsuranceReport::new(&items, |item| {
+            if item.current_employee_borrowing.is_empty() {
+                "Oakland".to_string()
+            } else {
+                "Remote".to_string()
+            }
+        });
+
+        assert_eq!(4, report.count);
+        assert!((report.replacement_value - 4000.5).abs() < 0.001);
+
+        let groups: Vec<(String, String, i32)> = report
+            .groups
+            .iter()
+            .map(|g| (g.location.to_string(), g.category.to_string(), g.count))
+            .collect();
+        assert_eq!(
+            vec![
+                ("Oakland".to_string(), "Monitor".to_string(), 1),
+                ("Oakland".to_string(), "Other".to_string(), 1),
+                ("Remote".to_string(), "Laptop".to_string(), 2),
+            ],
+            groups
+        );
+        assert_eq!("apollo", report.groups[2].items[0].name);
+
+        let csv = String::from_utf8(report.to_csv().unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            "Location,Category,Name,Manufacturer,Model Number,Serial Number,Status,Replacement Value",
+            lines[0]
+        );
+        assert_eq!("Remote,Laptop,apollo,,,SN1,,1500.50", lines[3]);
+        assert_eq!(5, lines.len());
+    }
+}
@@ -1,23 +1,36 @@
+use std::collections::HashMap;
+
 use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
 use barcoders::{
     generators::{image::Image, svg::SVG},
-    sym::code39::Code39,
+    sym::{code128::Code128, code39::Code39},
 };
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use diesel::{BoolExpressionMethods, ExpressionMethods, PgTextExpressionMethods, QueryDsl};
 use google_drive::{
     traits::{DriveOps, FileOps},
     Client as GoogleDrive,
 };
-use log::warn;
+use image::{ImageOutputFormat, Luma};
+use log::{info, warn};
 use macros::db;
+use qrcode::{render::svg, QrCode};
 use reqwest::StatusCode;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    airtable::AIRTABLE_ASSET_ITEMS_TABLE, companies::Company, core::UpdateAirtableRecord, db::Database,
-    printer::Printer, schema::asset_items, swag_inventory::generate_pdf_barcode_label,
+    airtable::AIRTABLE_ASSET_ITEMS_TABLE,
+    companies::Company,
+    configs::User,
+    core::UpdateAirtableRecord,
+    db::Database,
+    drive_uploads::{upload_to_drive_or_enqueue, DriveUploadTarget},
+    printer::{find_print_url, PrintTarget, Printer, PRINTER_KIND_ZEBRA},
+    schema::{asset_checkouts, asset_items},
+    swag_inventory::{generate_pdf_barcode_label, rasterize_pdf_label, LabelTemplate},
 };
 
 #[db {
@@ -54,6 +67,16 @@ pub struct NewAssetItem {
     pub serial_number: String,
     #[serde(default)]
     pub purchase_price: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_date: Option<NaiveDate>,
+    /// How the item depreciates, like `36 months` or `3 years` of straight-line
+    /// depreciation. Items without one keep their purchase price as their book value.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub depreciation_schedule: String,
+    /// (generated) The value of the item on our books, recalculated every month from its
+    /// purchase price, purchase date, and depreciation schedule.
+    #[serde(default)]
+    pub current_book_value: f32,
     #[serde(
         default,
         skip_serializing_if = "String::is_empty",
@@ -73,6 +96,10 @@ pub struct NewAssetItem {
         deserialize_with = "airtable_api::barcode_format_as_string::deserialize"
     )]
     pub barcode: String,
+    /// (generated) The symbology the barcode was printed in, `code39` or `code128`. Labels
+    /// printed before we recorded it are `code39`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub barcode_symbology: String,
     #[serde(
         default,
         skip_serializing_if = "String::is_empty",
@@ -91,6 +118,51 @@ pub struct NewAssetItem {
         deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
     )]
     pub barcode_pdf_label: String,
+    /// The link to the item's Airtable record, encoded in its QR code.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub qr_code: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub qr_code_png: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub qr_code_svg: String,
+
+    /// The supplier we ordered the item from.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub supplier: String,
+    /// The supplier's order number for the order the item was on.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub order_number: String,
+    /// When the supplier expects to deliver the item, if it has been ordered but not received.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_delivery_date: Option<NaiveDate>,
+    /// If the item is in the loaner pool, and can be reserved for a few days, like a
+    /// conference laptop or a demo unit.
+    #[serde(default)]
+    pub is_loaner: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warranty_expiration_date: Option<NaiveDate>,
+    /// How often the item needs maintenance, like a printer or a 3D printer. Zero if it
+    /// does not need any.
+    #[serde(default)]
+    pub maintenance_interval_days: i32,
+    /// Set in Airtable when the item was last maintained.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_maintenance_date: Option<NaiveDate>,
+    /// Set in Airtable when someone last saw the item while counting the inventory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_counted_date: Option<NaiveDate>,
+    /// The asset tag of the item in Snipe-IT, if we imported it from there. The tag is what
+    /// identifies the asset there, its name can be anything, and shared by other assets.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub snipeit_asset_tag: String,
 
     /// The CIO company ID.
     #[serde(default)]
@@ -105,8 +177,101 @@ impl UpdateAirtableRecord<AssetItem> for AssetItem {
     }
 }
 
+/// Return the link to an asset item's record in Airtable, or an empty string if the item is
+/// not in Airtable yet.
+pub fn asset_item_airtable_link(company: &Company, airtable_record_id: &str) -> String {
+    if company.airtable_base_id_assets.is_empty() || airtable_record_id.is_empty() {
+        return String::new();
+    }
+
+    format!(
+        "https://airtable.com/{}/{}/{}",
+        company.airtable_base_id_assets,
+        AssetItem::airtable_table(),
+        airtable_record_id
+    )
+}
+
+/// The symbology of the barcodes on asset labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarcodeSymbology {
+    /// Only fits 13 characters on a label, so longer names are cut.
+    Code39,
+    /// Denser than Code39, it fits longer names on the same label width.
+    Code128,
+}
+
+impl BarcodeSymbology {
+    /// Return the symbology the company chose for its asset labels, Code39 if it did not.
+    pub fn for_company(company: &Company) -> Self {
+        BarcodeSymbology::from_field(&company.barcode_symbology).unwrap_or(BarcodeSymbology::Code39)
+    }
+
+    fn from_field(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "" => None,
+            "code39" => Some(BarcodeSymbology::Code39),
+            "code128" => Some(BarcodeSymbology::Code128),
+            other => {
+                warn!("unknown barcode symbology `{}`, using code39", other);
+                Some(BarcodeSymbology::Code39)
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BarcodeSymbology::Code39 => "code39",
+            BarcodeSymbology::Code128 => "code128",
+        }
+    }
+
+    /// The longest barcode value that fits on a label with the right DPI.
+    pub fn max_len(&self) -> usize {
+        match self {
+            BarcodeSymbology::Code39 => 20,
+            BarcodeSymbology::Code128 => 40,
+        }
+    }
+
+    /// Encode a barcode value into the bars and spaces the image generators draw.
+    pub fn encode(&self, value: &str) -> Result<Vec<u8>> {
+        Ok(match self {
+            BarcodeSymbology::Code39 => Code39::new(value)?.encode(),
+            // Character set B has the upper and lower case letters, the digits, and the
+            // punctuation. It is selected by the first character.
+            BarcodeSymbology::Code128 => Code128::new(format!("Ɓ{}", value))?.encode(),
+        })
+    }
+}
+
+/// Generate the PNG and SVG images of a QR code. Unlike our barcodes, QR codes can hold a
+/// whole link.
+pub fn generate_qr_code_images(data: &str) -> Result<(Vec<u8>, String)> {
+    let code = QrCode::new(data.as_bytes())?;
+
+    let png = code.render::<Luma<u8>>().min_dimensions(200, 200).build();
+    let mut png_bytes: Vec<u8> = Vec::new();
+    image::DynamicImage::ImageLuma8(png).write_to(&mut png_bytes, ImageOutputFormat::Png)?;
+
+    let svg = code.render::<svg::Color>().min_dimensions(200, 200).build();
+
+    Ok((png_bytes, svg))
+}
+
 impl NewAssetItem {
-    pub fn generate_barcode(&self) -> String {
+    /// Return the symbology of the item's label. Items that already have a barcode keep the
+    /// symbology it was printed in, so the labels stuck on them still scan, and only new
+    /// items get `new_labels`, the one the company picked.
+    pub fn label_symbology(&self, new_labels: BarcodeSymbology) -> BarcodeSymbology {
+        if self.barcode.is_empty() {
+            return new_labels;
+        }
+
+        BarcodeSymbology::from_field(&self.barcode_symbology).unwrap_or(BarcodeSymbology::Code39)
+    }
+
+    pub fn generate_barcode(&self, symbology: BarcodeSymbology) -> String {
         let mut barcode = self
             .name
             .to_uppercase()
@@ -123,8 +288,9 @@ impl NewAssetItem {
         // This makes sure the barcodes are all of uniform length.
         // To fit on the barcode label with the right DPI we CANNOT exceed this
         // legth.
-        let max_barcode_len = 20;
-        while barcode.len() < max_barcode_len {
+        // Code128 barcodes are narrow enough that we don't need to pad them.
+        let max_barcode_len = symbology.max_len();
+        while symbology == BarcodeSymbology::Code39 && barcode.len() < max_barcode_len {
             barcode = format!("0{}", barcode);
         }
         if barcode.len() > max_barcode_len {
@@ -139,21 +305,32 @@ impl NewAssetItem {
         barcode
     }
 
+    /// Generate the barcode and QR code images of the item, and its label. `airtable_link` is
+    /// what the QR code encodes, we skip the QR code if it is empty. `symbology` is only used
+    /// for items that don't have a barcode yet, see `label_symbology`.
+    #[allow(clippy::too_many_arguments)]
     pub async fn generate_barcode_images(
         &mut self,
+        db: &Database,
         drive_client: &GoogleDrive,
         drive_id: &str,
         parent_id: &str,
+        airtable_link: &str,
+        symbology: BarcodeSymbology,
+        template: &LabelTemplate,
     ) -> Result<String> {
-        let barcode_value = self.generate_barcode();
+        let symbology = self.label_symbology(symbology);
+        let barcode_value = self.generate_barcode(symbology);
 
         // if the barcode value has changed since the last time it was generated then we
         // need to regenerate the barcode images. Additionally if we do not have values
         // stored for any of the barcode images, then we need to generate
         if self.barcode != barcode_value
+            || self.qr_code != airtable_link
             || self.barcode_png.is_empty()
             || self.barcode_svg.is_empty()
             || self.barcode_pdf_label.is_empty()
+            || (!airtable_link.is_empty() && (self.qr_code_png.is_empty() || self.qr_code_svg.is_empty()))
         {
             // Generate the barcode.
             // "Name" is automatically generated by Airtable from the item and the size.
@@ -165,22 +342,30 @@ impl NewAssetItem {
             // Store the updated value after checking that the name field exists. This ensures that
             // we do not store a barcode value and then fail to generate images
             self.barcode = barcode_value;
+            self.barcode_symbology = symbology.as_str().to_string();
 
             // Generate the barcode svg and png.
-            let barcode = Code39::new(&self.barcode)?;
             let png = Image::png(60); // You must specify the height in pixels.
-            let encoded = barcode.encode();
+            let encoded = symbology.encode(&self.barcode)?;
 
             // Image generators return a Result<Vec<u8>, barcoders::error::Error) of encoded bytes.
             let png_bytes = png.generate(&encoded[..])?;
             let mut file_name = format!("{} {}.png", self.type_, self.name.replace('/', ""));
 
-            // Create or update the file in the google drive.
-            let png_file = drive_client
-                .files()
-                .create_or_update(drive_id, parent_id, &file_name, "image/png", &png_bytes)
-                .await?;
-            self.barcode_png = format!("https://drive.google.com/uc?export=download&id={}", png_file.id);
+            // Create or update the file in the google drive. If the upload fails it is retried
+            // by the upload queue, which sets the link once it goes through.
+            self.barcode_png = upload_to_drive_or_enqueue(
+                db,
+                self.cio_company_id,
+                drive_client,
+                drive_id,
+                parent_id,
+                &file_name,
+                "image/png",
+                &png_bytes,
+                &DriveUploadTarget::asset_item(&self.name, "barcode_png"),
+            )
+            .await?;
 
             // Now do the SVG.
             let svg = SVG::new(200); // You must specify the height in pixels.
@@ -190,35 +375,128 @@ impl NewAssetItem {
             file_name = format!("{} {}.svg", self.type_, self.name.replace('/', ""));
 
             // Create or update the file in the google drive.
-            let svg_file = drive_client
-                .files()
-                .create_or_update(drive_id, parent_id, &file_name, "image/svg+xml", svg_bytes)
+            self.barcode_svg = upload_to_drive_or_enqueue(
+                db,
+                self.cio_company_id,
+                drive_client,
+                drive_id,
+                parent_id,
+                &file_name,
+                "image/svg+xml",
+                svg_bytes,
+                &DriveUploadTarget::asset_item(&self.name, "barcode_svg"),
+            )
+            .await?;
+
+            // Generate the QR code svg and png.
+            self.qr_code = airtable_link.to_string();
+            let mut qr_code_png: Option<Vec<u8>> = None;
+            if !self.qr_code.is_empty() {
+                let (png_bytes, svg_data) = generate_qr_code_images(&self.qr_code)?;
+
+                file_name = format!("{} {} - QR Code.png", self.type_, self.name.replace('/', ""));
+                self.qr_code_png = upload_to_drive_or_enqueue(
+                    db,
+                    self.cio_company_id,
+                    drive_client,
+                    drive_id,
+                    parent_id,
+                    &file_name,
+                    "image/png",
+                    &png_bytes,
+                    &DriveUploadTarget::asset_item(&self.name, "qr_code_png"),
+                )
+                .await?;
+
+                file_name = format!("{} {} - QR Code.svg", self.type_, self.name.replace('/', ""));
+                self.qr_code_svg = upload_to_drive_or_enqueue(
+                    db,
+                    self.cio_company_id,
+                    drive_client,
+                    drive_id,
+                    parent_id,
+                    &file_name,
+                    "image/svg+xml",
+                    svg_data.as_bytes(),
+                    &DriveUploadTarget::asset_item(&self.name, "qr_code_svg"),
+                )
                 .await?;
-            self.barcode_svg = format!("https://drive.google.com/uc?export=download&id={}", svg_file.id);
+
+                qr_code_png = Some(png_bytes);
+            }
 
             // Generate the barcode label.
-            let im = Image::jpeg(400);
-            let b = im.generate(&encoded[..])?;
-            let label_bytes = generate_pdf_barcode_label(
-                &b,
-                &self.barcode,
-                &self.name,
-                &format!("{} {} {}", self.manufacturer, self.type_, self.model_number),
-            )?;
+            let label_bytes = self
+                .generate_barcode_label(symbology, template, qr_code_png.as_deref())
+                .await?;
             file_name = format!("{} {} - Barcode Label.pdf", self.type_, self.name.replace('/', ""));
             // Create or update the file in the google drive.
-            let label_file = drive_client
-                .files()
-                .create_or_update(drive_id, parent_id, &file_name, "application/pdf", &label_bytes)
-                .await?;
-            self.barcode_pdf_label = format!("https://drive.google.com/uc?export=download&id={}", label_file.id);
+            self.barcode_pdf_label = upload_to_drive_or_enqueue(
+                db,
+                self.cio_company_id,
+                drive_client,
+                drive_id,
+                parent_id,
+                &file_name,
+                "application/pdf",
+                &label_bytes,
+                &DriveUploadTarget::asset_item(&self.name, "barcode_pdf_label"),
+            )
+            .await?;
         }
 
         Ok(self.barcode_pdf_label.to_string())
     }
 
-    pub async fn expand(&mut self, drive_client: &GoogleDrive, drive_id: &str, parent_id: &str) -> Result<String> {
-        self.generate_barcode_images(drive_client, drive_id, parent_id).await
+    /// Generate the PDF label of the item, with its current barcode.
+    pub async fn generate_barcode_label(
+        &self,
+        symbology: BarcodeSymbology,
+        template: &LabelTemplate,
+        qr_code_png: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        let encoded = symbology.encode(&self.barcode)?;
+        let im = Image::jpeg(400);
+        let b = im.generate(&encoded[..])?;
+        let logo = template.logo().await?;
+
+        generate_pdf_barcode_label(
+            template,
+            logo.as_deref(),
+            &b,
+            qr_code_png,
+            &[
+                ("barcode", &self.barcode),
+                ("name", &self.name),
+                (
+                    "description",
+                    &format!("{} {} {}", self.manufacturer, self.type_, self.model_number),
+                ),
+            ],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn expand(
+        &mut self,
+        db: &Database,
+        drive_client: &GoogleDrive,
+        drive_id: &str,
+        parent_id: &str,
+        airtable_link: &str,
+        symbology: BarcodeSymbology,
+        template: &LabelTemplate,
+    ) -> Result<String> {
+        self.generate_barcode_images(
+            db,
+            drive_client,
+            drive_id,
+            parent_id,
+            airtable_link,
+            symbology,
+            template,
+        )
+        .await
     }
 }
 
@@ -232,14 +510,42 @@ pub struct PrintLabelsRequest {
 }
 
 impl AssetItem {
-    /// Send the label to our printer.
-    pub async fn print_label(&self, db: &Database) -> Result<()> {
+    /// Render the label of the item as a PNG, to check it on screen before it is printed: its
+    /// size, the text that does not fit, and whether the barcode is readable.
+    pub async fn preview_label(&self, company: &Company) -> Result<Vec<u8>> {
+        let template = LabelTemplate::for_company(company);
+
+        let mut item: NewAssetItem = From::from(self.clone());
+        let symbology = item.label_symbology(BarcodeSymbology::for_company(company));
+        // The barcode the label would be printed with, it changes with the name.
+        item.barcode = item.generate_barcode(symbology);
+        if item.barcode.is_empty() {
+            bail!("asset item {} has no name to make a barcode from", self.id);
+        }
+
+        let airtable_link = asset_item_airtable_link(company, &self.airtable_record_id);
+        let qr_code_png = if airtable_link.is_empty() {
+            None
+        } else {
+            Some(generate_qr_code_images(&airtable_link)?.0)
+        };
+
+        let pdf = item
+            .generate_barcode_label(symbology, &template, qr_code_png.as_deref())
+            .await?;
+
+        rasterize_pdf_label(&pdf).await
+    }
+
+    /// Send the label to the printer for the target.
+    pub async fn print_label(&self, db: &Database, target: &PrintTarget) -> Result<()> {
         let company = self.company(db).await?;
 
-        if company.printer_url.is_empty() {
+        let printer_url = match find_print_url(db, &company, PRINTER_KIND_ZEBRA, target).await? {
+            Some(printer_url) => printer_url,
             // Return early.
-            return Ok(());
-        }
+            None => return Ok(()),
+        };
 
         let url = if self.barcode_pdf_label.trim().is_empty() {
             // Get the URL to the google item directly.
@@ -255,12 +561,27 @@ impl AssetItem {
             let parent_id = drive_client.files().create_folder(&drive_id, "", "assets").await?;
 
             let mut sw: NewAssetItem = From::from(self.clone());
-            sw.expand(&drive_client, &drive_id, &parent_id).await?
+            let airtable_link = asset_item_airtable_link(&company, &self.airtable_record_id);
+            let url = sw
+                .expand(
+                    db,
+                    &drive_client,
+                    &drive_id,
+                    &parent_id,
+                    &airtable_link,
+                    BarcodeSymbology::for_company(&company),
+                    &LabelTemplate::for_company(&company),
+                )
+                .await?;
+            if url.is_empty() {
+                bail!("the label for asset item {} is still being uploaded", self.name);
+            }
+
+            url
         } else {
             self.barcode_pdf_label.trim().to_string()
         };
 
-        let printer_url = format!("{}/zebra", company.printer_url);
         let printer_key = Printer::key();
 
         let client = reqwest::Client::new();
@@ -281,6 +602,689 @@ impl AssetItem {
     }
 }
 
+/// A record of someone borrowing an asset item, from when they scanned it out to when they
+/// scanned it back in.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct AssetCheckout {
+    pub id: i32,
+    pub asset_item_name: String,
+    pub email: String,
+    pub checked_out_at: DateTime<Utc>,
+    /// When the item was returned, None while the person still has it.
+    pub checked_in_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = asset_checkouts)]
+struct NewAssetCheckout {
+    asset_item_name: String,
+    email: String,
+    checked_out_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// A scan of an asset item's barcode or QR code by someone checking it in or out.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct AssetScan {
+    /// The value of the barcode, or the link in the QR code.
+    pub barcode: String,
+    pub email: String,
+}
+
+/// What a scan does to the item, given who has it now.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetScanAction {
+    /// Nobody has the item, the person scanning it takes it.
+    CheckOut,
+    /// The person scanning the item has it, they return it.
+    CheckIn,
+    /// Someone else has the item, it is handed over to the person scanning it.
+    HandOver { from: String },
+}
+
+/// Decide what a scan does to an item, from its open checkout if it has one.
+pub fn asset_scan_action(open: Option<&AssetCheckout>, email: &str) -> AssetScanAction {
+    match open {
+        None => AssetScanAction::CheckOut,
+        Some(checkout) if checkout.email.eq_ignore_ascii_case(email.trim()) => AssetScanAction::CheckIn,
+        Some(checkout) => AssetScanAction::HandOver {
+            from: checkout.email.to_string(),
+        },
+    }
+}
+
+impl AssetItem {
+    /// Get the history of who borrowed the item, latest first.
+    pub async fn checkouts(&self, db: &Database) -> Result<Vec<AssetCheckout>> {
+        Ok(asset_checkouts::dsl::asset_checkouts
+            .filter(
+                asset_checkouts::dsl::cio_company_id
+                    .eq(self.cio_company_id)
+                    .and(asset_checkouts::dsl::asset_item_name.eq(self.name.to_string())),
+            )
+            .order_by(asset_checkouts::dsl::checked_out_at.desc())
+            .load_async::<AssetCheckout>(db.pool())
+            .await?)
+    }
+
+    /// Set who is borrowing the item from its checkouts. Items nobody ever scanned keep what
+    /// was set in Airtable.
+    pub async fn sync_borrower_from_checkouts(&mut self, db: &Database) -> Result<()> {
+        let checkouts = self.checkouts(db).await?;
+        if checkouts.is_empty() {
+            return Ok(());
+        }
+
+        let borrower = checkouts
+            .iter()
+            .find(|c| c.checked_in_at.is_none())
+            .map(|c| c.email.to_string())
+            .unwrap_or_default();
+        if self.current_employee_borrowing != borrower {
+            self.current_employee_borrowing = borrower;
+            self.update(db).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Check an asset item in or out from a scan of its barcode or QR code. Returns the item's
+/// open checkout, or None if it was checked back in.
+pub async fn scan_asset_item(db: &Database, company: &Company, scan: &AssetScan) -> Result<Option<AssetCheckout>> {
+    let code = scan.barcode.trim();
+    let email = scan.email.trim().to_lowercase();
+    if code.is_empty() || email.is_empty() {
+        bail!("a scan needs both the barcode and the email of who scanned it");
+    }
+
+    let mut item = match asset_items::dsl::asset_items
+        .filter(
+            asset_items::dsl::cio_company_id.eq(company.id).and(
+                asset_items::dsl::barcode
+                    .eq(code.to_string())
+                    .or(asset_items::dsl::qr_code.eq(code.to_string())),
+            ),
+        )
+        .first_async::<AssetItem>(db.pool())
+        .await
+    {
+        Ok(item) => item,
+        Err(e) => bail!("could not find asset item with barcode `{}`: {}", code, e),
+    };
+
+    let checkouts = item.checkouts(db).await?;
+    let open = checkouts.iter().find(|c| c.checked_in_at.is_none());
+    let action = asset_scan_action(open, &email);
+
+    let now = Utc::now();
+    if let Some(open) = open {
+        diesel::update(asset_checkouts::dsl::asset_checkouts)
+            .filter(asset_checkouts::dsl::id.eq(open.id))
+            .set(asset_checkouts::dsl::checked_in_at.eq(Some(now)))
+            .execute_async(db.pool())
+            .await?;
+    }
+
+    let checkout = if action == AssetScanAction::CheckIn {
+        info!("{} checked in asset item {}", email, item.name);
+        None
+    } else {
+        if let AssetScanAction::HandOver { from } = &action {
+            info!("asset item {} handed over from {} to {}", item.name, from, email);
+        } else {
+            info!("{} checked out asset item {}", email, item.name);
+        }
+
+        Some(open_asset_checkout(db, &item, &email, now).await?)
+    };
+
+    item.sync_borrower_from_checkouts(db).await?;
+
+    Ok(checkout)
+}
+
+async fn open_asset_checkout(
+    db: &Database,
+    item: &AssetItem,
+    email: &str,
+    now: DateTime<Utc>,
+) -> Result<AssetCheckout> {
+    Ok(diesel::insert_into(asset_checkouts::table)
+        .values(NewAssetCheckout {
+            asset_item_name: item.name.to_string(),
+            email: email.to_string(),
+            checked_out_at: now,
+            cio_company_id: item.cio_company_id,
+        })
+        .get_result_async::<AssetCheckout>(db.pool())
+        .await?)
+}
+
+/// Escape the characters `LIKE` patterns give a meaning to, so they match themselves.
+pub(crate) fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Find an asset item by its barcode, the link in its QR code, or its name. A part of a name
+/// is enough if only one item matches it.
+pub async fn find_asset_item(db: &Database, company: &Company, query: &str) -> Result<AssetItem> {
+    let query = query.trim();
+    if query.is_empty() {
+        bail!("give me the barcode or the name of an asset item");
+    }
+
+    if let Ok(item) = asset_items::dsl::asset_items
+        .filter(
+            asset_items::dsl::cio_company_id.eq(company.id).and(
+                asset_items::dsl::barcode
+                    .eq(query.to_string())
+                    .or(asset_items::dsl::qr_code.eq(query.to_string()))
+                    .or(asset_items::dsl::name.ilike(escape_like(query))),
+            ),
+        )
+        .first_async::<AssetItem>(db.pool())
+        .await
+    {
+        return Ok(item);
+    }
+
+    let items = asset_items::dsl::asset_items
+        .filter(
+            asset_items::dsl::cio_company_id
+                .eq(company.id)
+                .and(asset_items::dsl::name.ilike(format!("%{}%", escape_like(query)))),
+        )
+        .order_by(asset_items::dsl::name)
+        .limit(6)
+        .load_async::<AssetItem>(db.pool())
+        .await?;
+    match items.len() {
+        0 => bail!("could not find an asset item matching `{}`", query),
+        1 => Ok(items[0].clone()),
+        _ => bail!(
+            "`{}` matches more than one asset item, like {}",
+            query,
+            items
+                .iter()
+                .map(|i| format!("`{}`", i.name))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Who can assign asset items to people.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AssetAssignmentConfig {
+    /// The group whose members can assign any item, to anyone.
+    pub managers_group: String,
+}
+
+impl Default for AssetAssignmentConfig {
+    fn default() -> Self {
+        AssetAssignmentConfig {
+            managers_group: "it".to_string(),
+        }
+    }
+}
+
+/// Returns if someone can assign an item. The asset managers can assign anything, everybody
+/// else can only hand over the items they have.
+pub fn can_assign_asset_item(config: &AssetAssignmentConfig, assigned_by: &User, open: Option<&AssetCheckout>) -> bool {
+    if !config.managers_group.is_empty() && assigned_by.groups.iter().any(|g| g == &config.managers_group) {
+        return true;
+    }
+
+    open.map(|c| c.email.eq_ignore_ascii_case(assigned_by.email.trim()))
+        .unwrap_or_default()
+}
+
+/// Assign an asset item to someone, as if they scanned it out. Whoever had it hands it over.
+pub async fn assign_asset_item(
+    db: &Database,
+    company: &Company,
+    config: &AssetAssignmentConfig,
+    assigned_by: &User,
+    query: &str,
+    email: &str,
+) -> Result<AssetItem> {
+    let email = email.trim().to_lowercase();
+    if email.is_empty() {
+        bail!("an asset item needs someone to be assigned to");
+    }
+
+    let mut item = find_asset_item(db, company, query).await?;
+
+    let checkouts = item.checkouts(db).await?;
+    let open = checkouts.iter().find(|c| c.checked_in_at.is_none());
+    if !can_assign_asset_item(config, assigned_by, open) {
+        bail!(
+            "only the members of the `{}` group can assign `{}`, or whoever has it",
+            config.managers_group,
+            item.name
+        );
+    }
+    if let AssetScanAction::CheckIn = asset_scan_action(open, &email) {
+        // They already have it.
+        item.sync_borrower_from_checkouts(db).await?;
+        return Ok(item);
+    }
+
+    let now = Utc::now();
+    if let Some(open) = open {
+        diesel::update(asset_checkouts::dsl::asset_checkouts)
+            .filter(asset_checkouts::dsl::id.eq(open.id))
+            .set(asset_checkouts::dsl::checked_in_at.eq(Some(now)))
+            .execute_async(db.pool())
+            .await?;
+        info!("asset item {} assigned from {} to {}", item.name, open.email, email);
+    } else {
+        info!("asset item {} assigned to {}", item.name, email);
+    }
+
+    open_asset_checkout(db, &item, &email, now).await?;
+    item.sync_borrower_from_checkouts(db).await?;
+
+    Ok(item)
+}
+
+/// How many records we get from Snipe-IT at once, it caps it at 500.
+static SNIPEIT_PAGE_SIZE: usize = 500;
+
+/// A record Snipe-IT links another to, like the model or manufacturer of an asset.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SnipeItLink {
+    #[serde(default)]
+    id: i64,
+    #[serde(default)]
+    name: String,
+}
+
+/// Snipe-IT dates come as `{"date": "2020-01-06", "formatted": "..."}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SnipeItDate {
+    #[serde(default)]
+    date: String,
+}
+
+/// Snipe-IT times come as `{"datetime": "2020-01-06 10:20:32", "formatted": "..."}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SnipeItDateTime {
+    #[serde(default)]
+    datetime: String,
+}
+
+/// Who or where an asset is checked out to.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SnipeItAssignee {
+    #[serde(default)]
+    id: i64,
+    /// Either `user`, `location`, or `asset`.
+    #[serde(default, rename = "type")]
+    type_: String,
+    #[serde(default)]
+    email: Option<String>,
+}
+
+/// An asset in Snipe-IT, they call them hardware.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SnipeItAsset {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    asset_tag: String,
+    #[serde(default)]
+    serial: Option<String>,
+    #[serde(default)]
+    model: Option<SnipeItLink>,
+    #[serde(default)]
+    model_number: Option<String>,
+    #[serde(default)]
+    manufacturer: Option<SnipeItLink>,
+    #[serde(default)]
+    category: Option<SnipeItLink>,
+    #[serde(default)]
+    status_label: Option<SnipeItLink>,
+    #[serde(default)]
+    supplier: Option<SnipeItLink>,
+    #[serde(default)]
+    order_number: Option<String>,
+    #[serde(default)]
+    purchase_date: Option<SnipeItDate>,
+    /// A formatted amount like `1,299.00`.
+    #[serde(default)]
+    purchase_cost: Option<serde_json::Value>,
+    #[serde(default)]
+    warranty_expires: Option<SnipeItDate>,
+    #[serde(default)]
+    assigned_to: Option<SnipeItAssignee>,
+    /// When the asset was last checked out, to whoever it is checked out to now.
+    #[serde(default)]
+    last_checkout: Option<SnipeItDateTime>,
+    #[serde(default)]
+    notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SnipeItModel {
+    #[serde(default)]
+    id: i64,
+    #[serde(default)]
+    model_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SnipeItUser {
+    #[serde(default)]
+    id: i64,
+    #[serde(default)]
+    email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnipeItPage<T> {
+    #[serde(default)]
+    total: usize,
+    #[serde(default = "Vec::new")]
+    rows: Vec<T>,
+}
+
+/// A client for the Snipe-IT API, to import the assets of companies migrating off it.
+struct SnipeIt {
+    url: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl SnipeIt {
+    /// The client for the Snipe-IT at `SNIPEIT_URL`, with the API token in `SNIPEIT_API_TOKEN`.
+    fn new_from_env() -> Result<Self> {
+        let url = std::env::var("SNIPEIT_URL").unwrap_or_default();
+        let token = std::env::var("SNIPEIT_API_TOKEN").unwrap_or_default();
+        if url.trim().is_empty() || token.trim().is_empty() {
+            bail!("importing from Snipe-IT needs SNIPEIT_URL and SNIPEIT_API_TOKEN");
+        }
+
+        Ok(SnipeIt {
+            url: url.trim().trim_end_matches('/').to_string(),
+            token: token.trim().to_string(),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Get all the records of a kind, like `hardware` or `users`, page by page.
+    async fn list<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Vec<T>> {
+        let mut records: Vec<T> = Vec::new();
+        loop {
+            let resp = self
+                .client
+                .get(&format!("{}/api/v1/{}", self.url, path))
+                .bearer_auth(&self.token)
+                .header("Accept", "application/json")
+                .query(&[("limit", SNIPEIT_PAGE_SIZE), ("offset", records.len())])
+                .send()
+                .await?;
+            if resp.status() != StatusCode::OK {
+                bail!(
+                    "listing Snipe-IT {} failed, status_code: {}, body: {}",
+                    path,
+                    resp.status(),
+                    resp.text().await?
+                );
+            }
+
+            let page: SnipeItPage<T> = resp.json().await?;
+            let last = page.rows.len() < SNIPEIT_PAGE_SIZE;
+            records.extend(page.rows);
+            if last || records.len() >= page.total {
+                return Ok(records);
+            }
+        }
+    }
+}
+
+/// Parse a Snipe-IT amount, which is either a number or formatted like `1,299.00`.
+fn parse_snipeit_amount(value: &Option<serde_json::Value>) -> f32 {
+    match value {
+        Some(serde_json::Value::Number(n)) => n.as_f64().unwrap_or_default() as f32,
+        Some(serde_json::Value::String(s)) => s.replace(',', "").trim().parse::<f32>().unwrap_or_default(),
+        _ => 0.0,
+    }
+}
+
+fn parse_snipeit_date(date: &Option<SnipeItDate>) -> Option<NaiveDate> {
+    date.as_ref()
+        .and_then(|d| NaiveDate::parse_from_str(d.date.trim(), "%Y-%m-%d").ok())
+}
+
+fn parse_snipeit_datetime(datetime: &Option<SnipeItDateTime>) -> Option<DateTime<Utc>> {
+    datetime
+        .as_ref()
+        .and_then(|d| NaiveDateTime::parse_from_str(d.datetime.trim(), "%Y-%m-%d %H:%M:%S").ok())
+        .map(|d| DateTime::<Utc>::from_utc(d, Utc))
+}
+
+impl SnipeItAsset {
+    /// The email of the person the asset is checked out to, if it is checked out to someone.
+    fn assignee_email(&self, user_emails: &HashMap<i64, String>) -> String {
+        match &self.assigned_to {
+            Some(assignee) if assignee.type_ == "user" => assignee
+                .email
+                .clone()
+                .filter(|e| !e.trim().is_empty())
+                .or_else(|| user_emails.get(&assignee.id).cloned())
+                .unwrap_or_default()
+                .trim()
+                .to_lowercase(),
+            _ => String::new(),
+        }
+    }
+
+    /// Map the asset to an asset item. Items are named after the asset, or after its asset tag
+    /// if it has no name, and keep the tag in their notes so people can find them by it.
+    fn to_asset_item(
+        &self,
+        company: &Company,
+        model_numbers: &HashMap<i64, String>,
+        user_emails: &HashMap<i64, String>,
+    ) -> NewAssetItem {
+        let link_name =
+            |link: &Option<SnipeItLink>| link.as_ref().map(|l| l.name.trim().to_string()).unwrap_or_default();
+        let text = |s: &Option<String>| s.as_deref().unwrap_or_default().trim().to_string();
+
+        let name = match text(&self.name) {
+            name if name.is_empty() => self.asset_tag.trim().to_lowercase(),
+            name => name,
+        };
+        let model = link_name(&self.model);
+        let category = link_name(&self.category);
+        let model_number = match text(&self.model_number) {
+            n if n.is_empty() => self
+                .model
+                .as_ref()
+                .and_then(|m| model_numbers.get(&m.id).cloned())
+                .unwrap_or_default(),
+            n => n,
+        };
+        let type_ = match crate::asset_orders::guess_asset_type(&format!("{} {}", category, model)) {
+            t if t.is_empty() => category.trim_end_matches('s').to_string(),
+            t => t,
+        };
+
+        let mut notes = format!("Imported from Snipe-IT asset tag {}.", self.asset_tag.trim());
+        if !text(&self.notes).is_empty() {
+            notes = format!("{}\n\n{}", notes, text(&self.notes));
+        }
+
+        let purchase_price = parse_snipeit_amount(&self.purchase_cost);
+
+        NewAssetItem {
+            name,
+            picture: Default::default(),
+            type_,
+            qualities: Default::default(),
+            status: link_name(&self.status_label),
+            manufacturer: link_name(&self.manufacturer),
+            model_number,
+            serial_number: text(&self.serial),
+            purchase_price,
+            purchase_date: parse_snipeit_date(&self.purchase_date),
+            depreciation_schedule: Default::default(),
+            current_book_value: purchase_price,
+            current_employee_borrowing: self.assignee_email(user_emails),
+            conference_room_using: Default::default(),
+            notes,
+            barcode: Default::default(),
+            barcode_symbology: Default::default(),
+            barcode_png: Default::default(),
+            barcode_svg: Default::default(),
+            barcode_pdf_label: Default::default(),
+            qr_code: Default::default(),
+            qr_code_png: Default::default(),
+            qr_code_svg: Default::default(),
+            supplier: link_name(&self.supplier),
+            order_number: text(&self.order_number),
+            expected_delivery_date: Default::default(),
+            is_loaner: false,
+            warranty_expiration_date: parse_snipeit_date(&self.warranty_expires),
+            maintenance_interval_days: 0,
+            last_maintenance_date: Default::default(),
+            last_counted_date: Default::default(),
+            snipeit_asset_tag: self.asset_tag.trim().to_string(),
+            cio_company_id: company.id,
+        }
+    }
+}
+
+/// Get the item we imported from a Snipe-IT asset before, by its asset tag.
+async fn get_snipeit_asset_item(db: &Database, company: &Company, asset_tag: &str) -> Result<Option<AssetItem>> {
+    let items = asset_items::dsl::asset_items
+        .filter(asset_items::dsl::cio_company_id.eq(company.id))
+        .filter(asset_items::dsl::snipeit_asset_tag.eq(asset_tag.to_string()))
+        .limit(1)
+        .load_async::<AssetItem>(db.pool())
+        .await?;
+
+    Ok(items.into_iter().next())
+}
+
+/// Import the assets of a company migrating off Snipe-IT. Assets are matched to the items we
+/// imported before by their asset tag, and those items keep their name and what we generated or
+/// set for them, like their barcodes. The people the assets are checked out to get an open
+/// checkout from when Snipe-IT checked it out to them, so scanning the item back in works.
+pub async fn import_snipeit_assets(db: &Database, company: &Company) -> Result<Vec<AssetItem>> {
+    let snipeit = SnipeIt::new_from_env()?;
+
+    let assets: Vec<SnipeItAsset> = snipeit.list("hardware").await?;
+    let model_numbers: HashMap<i64, String> = snipeit
+        .list::<SnipeItModel>("models")
+        .await?
+        .into_iter()
+        .filter_map(|m| m.model_number.map(|n| (m.id, n.trim().to_string())))
+        .collect();
+    let user_emails: HashMap<i64, String> = snipeit
+        .list::<SnipeItUser>("users")
+        .await?
+        .into_iter()
+        .filter_map(|u| u.email.map(|e| (u.id, e)))
+        .collect();
+
+    let mut imported = Vec::new();
+    for asset in &assets {
+        let mut new_item = asset.to_asset_item(company, &model_numbers, &user_emails);
+        if new_item.snipeit_asset_tag.is_empty() {
+            warn!("skipping Snipe-IT asset `{}` without an asset tag", new_item.name);
+            continue;
+        }
+
+        let existing = match get_snipeit_asset_item(db, company, &new_item.snipeit_asset_tag).await? {
+            Some(existing) => Some(existing),
+            None => {
+                // Names are not unique in Snipe-IT, so an item we have with the same name can be
+                // another asset, or one that never came from Snipe-IT. Name the item after its
+                // asset tag instead of taking that one over.
+                if new_item.name.is_empty()
+                    || AssetItem::get_from_db(db, company.id, new_item.name.to_string())
+                        .await
+                        .is_some()
+                {
+                    new_item.name = new_item.snipeit_asset_tag.to_lowercase();
+                    if AssetItem::get_from_db(db, company.id, new_item.name.to_string())
+                        .await
+                        .is_some()
+                    {
+                        warn!(
+                            "skipping Snipe-IT asset tag {}, we already have another item named `{}`",
+                            new_item.snipeit_asset_tag, new_item.name
+                        );
+                        continue;
+                    }
+                }
+
+                None
+            }
+        };
+
+        if let Some(existing) = existing {
+            // The item keeps the name it has, even if the asset was renamed in Snipe-IT.
+            new_item.name = existing.name;
+            new_item.picture = existing.picture;
+            new_item.qualities = existing.qualities;
+            new_item.depreciation_schedule = existing.depreciation_schedule;
+            new_item.conference_room_using = existing.conference_room_using;
+            new_item.barcode = existing.barcode;
+            new_item.barcode_symbology = existing.barcode_symbology;
+            new_item.barcode_png = existing.barcode_png;
+            new_item.barcode_svg = existing.barcode_svg;
+            new_item.barcode_pdf_label = existing.barcode_pdf_label;
+            new_item.qr_code = existing.qr_code;
+            new_item.qr_code_png = existing.qr_code_png;
+            new_item.qr_code_svg = existing.qr_code_svg;
+            new_item.is_loaner = existing.is_loaner;
+            new_item.maintenance_interval_days = existing.maintenance_interval_days;
+            new_item.last_maintenance_date = existing.last_maintenance_date;
+        }
+
+        let item = new_item.upsert(db).await?;
+
+        if !item.current_employee_borrowing.is_empty()
+            && !item.checkouts(db).await?.iter().any(|c| c.checked_in_at.is_none())
+        {
+            diesel::insert_into(asset_checkouts::table)
+                .values(NewAssetCheckout {
+                    asset_item_name: item.name.to_string(),
+                    email: item.current_employee_borrowing.to_string(),
+                    checked_out_at: parse_snipeit_datetime(&asset.last_checkout).unwrap_or_else(Utc::now),
+                    cio_company_id: company.id,
+                })
+                .execute_async(db.pool())
+                .await?;
+        }
+
+        imported.push(item);
+    }
+
+    info!(
+        "imported {} of {} assets from Snipe-IT for company {}",
+        imported.len(),
+        assets.len(),
+        company.name
+    );
+
+    Ok(imported)
+}
+
 /// Sync asset items from Airtable.
 pub async fn refresh_asset_items(db: &Database, company: &Company) -> Result<()> {
     if company.airtable_base_id_assets.is_empty() {
@@ -305,6 +1309,8 @@ pub async fn refresh_asset_items(db: &Database, company: &Company) -> Result<()>
         .list_records(&AssetItem::airtable_table(), "Grid view", vec![])
         .await?;
 
+    let symbology = BarcodeSymbology::for_company(company);
+    let template = LabelTemplate::for_company(company);
     for item_record in results {
         let mut item: NewAssetItem = item_record.fields.into();
         if item.name.is_empty() {
@@ -315,7 +1321,21 @@ pub async fn refresh_asset_items(db: &Database, company: &Company) -> Result<()>
         // Iterating through and processing all of the asset items can take over an hour. This
         // exceeds the time limit that Google Drive allots for a single token. Therefore we may
         // need to refresh the access token mid processing if an item expansion fails
-        match item.expand(&drive_client, &drive_id, &parent_id).await {
+        item.cio_company_id = company.id;
+        let airtable_link = asset_item_airtable_link(company, &item_record.id);
+
+        match item
+            .expand(
+                db,
+                &drive_client,
+                &drive_id,
+                &parent_id,
+                &airtable_link,
+                symbology,
+                &template,
+            )
+            .await
+        {
             Ok(_) => (),
             Err(err) => {
                 log::info!("Handling drive error. This is likely to be an authentication error. Further work is needed to differentiate. {:?}", err);
@@ -324,15 +1344,25 @@ pub async fn refresh_asset_items(db: &Database, company: &Company) -> Result<()>
 
                 // Now using a client with fresh credentials, we can retry the expansion. If this
                 // again, it is unlikely due to an authentication error
-                item.expand(&drive_client, &drive_id, &parent_id).await?;
+                item.expand(
+                    db,
+                    &drive_client,
+                    &drive_id,
+                    &parent_id,
+                    &airtable_link,
+                    symbology,
+                    &template,
+                )
+                .await?;
             }
         }
 
-        item.cio_company_id = company.id;
-
         let mut db_item = item.upsert_in_db(db).await?;
         db_item.airtable_record_id = item_record.id.to_string();
         db_item.update(db).await?;
+
+        // Who has the item comes from the scans, not from what is in Airtable.
+        db_item.sync_borrower_from_checkouts(db).await?;
     }
 
     AssetItems::get_from_db(db, company.id)
@@ -342,3 +1372,215 @@ pub async fn refresh_asset_items(db: &Database, company: &Company) -> Result<()>
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{
+        asset_item_airtable_link, asset_scan_action, can_assign_asset_item, escape_like, generate_qr_code_images,
+        parse_snipeit_datetime, AssetAssignmentConfig, AssetCheckout, AssetScanAction, BarcodeSymbology, NewAssetItem,
+        SnipeItAsset,
+    };
+    use crate::{
+        companies::tests::mock_company,
+        configs::tests::mock_user,
+        swag_inventory::{generate_pdf_barcode_label, LabelTemplate},
+    };
+
+    #[test]
+    fn test_asset_item_qr_code() {
+        let mut company = mock_company();
+        company.airtable_base_id_assets = "appAssets".to_string();
+
+        let link = asset_item_airtable_link(&company, "recItem");
+        assert_eq!("https://airtable.com/appAssets/Items/recItem", link);
+        assert_eq!("", asset_item_airtable_link(&company, ""));
+
+        let (png, svg) = generate_qr_code_images(&link).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert!(svg.contains("<svg"));
+    }
+
+    #[test]
+    fn test_barcode_symbology() {
+        let mut company = mock_company();
+        assert_eq!(BarcodeSymbology::Code39, BarcodeSymbology::for_company(&company));
+        company.barcode_symbology = "Code128".to_string();
+        assert_eq!(BarcodeSymbology::Code128, BarcodeSymbology::for_company(&company));
+
+        let mut item: NewAssetItem = serde_json::from_str(r#"{"name":"Dev Laptop (Jane's)"}"#).unwrap();
+        assert_eq!("000000DEVLAPTOPJANES", item.generate_barcode(BarcodeSymbology::Code39));
+        assert_eq!("DEVLAPTOPJANES", item.generate_barcode(BarcodeSymbology::Code128));
+
+        // Code128 encodes long names narrower than Code39.
+        item.name = "Framework Laptop 13 DIY Edition".to_string();
+        let barcode = item.generate_barcode(BarcodeSymbology::Code128);
+        assert_eq!("FRAMEWORKLAPTOP13DIYEDITION", barcode);
+        let code128 = BarcodeSymbology::Code128.encode(&barcode).unwrap();
+        let code39 = BarcodeSymbology::Code39.encode(&barcode).unwrap();
+        assert!(code128.len() < code39.len());
+
+        // Items keep the symbology of the label they have, the labels printed before we recorded
+        // it are Code39.
+        assert_eq!(
+            BarcodeSymbology::Code128,
+            item.label_symbology(BarcodeSymbology::Code128)
+        );
+        item.barcode = "000000DEVLAPTOPJANES".to_string();
+        assert_eq!(
+            BarcodeSymbology::Code39,
+            item.label_symbology(BarcodeSymbology::Code128)
+        );
+        item.barcode_symbology = "code128".to_string();
+        assert_eq!(
+            BarcodeSymbology::Code128,
+            item.label_symbology(BarcodeSymbology::Code39)
+        );
+    }
+
+    #[test]
+    fn test_label_template() {
+        let mut company = mock_company();
+        assert_eq!(LabelTemplate::default(), LabelTemplate::for_company(&company));
+
+        company.label_template =
+            r#"{"width_inches":4,"hide_logo":true,"fields":["name","Barcode","serial"]}"#.to_string();
+        let template = LabelTemplate::for_company(&company);
+        assert_eq!(4.0, template.width_inches);
+        assert_eq!(2.0, template.height_inches);
+
+        let fields = [
+            ("barcode", "0000LAPTOP"),
+            ("name", "Laptop"),
+            ("description", "Framework"),
+        ];
+        // Fields the label does not have are skipped.
+        assert_eq!(vec!["Laptop", "0000LAPTOP"], template.lines(&fields));
+        assert_eq!(
+            vec!["0000LAPTOP", "Laptop", "Framework"],
+            LabelTemplate::default().lines(&fields)
+        );
+
+        let encoded = BarcodeSymbology::Code39.encode("0000LAPTOP").unwrap();
+        let barcode = barcoders::generators::image::Image::jpeg(400)
+            .generate(&encoded[..])
+            .unwrap();
+        let (qr_code, _) = generate_qr_code_images("https://airtable.com").unwrap();
+        let pdf = generate_pdf_barcode_label(&template, None, &barcode, Some(&qr_code), &fields).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+
+    #[test]
+    fn test_asset_scan_action() {
+        let checkout = AssetCheckout {
+            id: 1,
+            asset_item_name: "Laptop 1".to_string(),
+            email: "jane@example.com".to_string(),
+            checked_out_at: Utc::now(),
+            checked_in_at: None,
+            cio_company_id: 1,
+        };
+
+        assert_eq!(AssetScanAction::CheckOut, asset_scan_action(None, "jane@example.com"));
+        assert_eq!(
+            AssetScanAction::CheckIn,
+            asset_scan_action(Some(&checkout), "Jane@Example.com ")
+        );
+        assert_eq!(
+            AssetScanAction::HandOver {
+                from: "jane@example.com".to_string()
+            },
+            asset_scan_action(Some(&checkout), "sam@example.com")
+        );
+    }
+
+    #[test]
+    fn test_snipeit_asset_item() {
+        let company = mock_company();
+        let asset: SnipeItAsset = serde_json::from_str(
+            r#"{
+                "id": 12,
+                "name": "",
+                "asset_tag": "OX-0042",
+                "serial": "C02XK1",
+                "model": {"id": 3, "name": "MacBook Pro 14"},
+                "model_number": null,
+                "manufacturer": {"id": 1, "name": "Apple"},
+                "category": {"id": 2, "name": "Computers"},
+                "status_label": {"id": 1, "name": "Deployed", "status_meta": "deployed"},
+                "purchase_date": {"date": "2022-03-01", "formatted": "Mar 1, 2022"},
+                "purchase_cost": "2,499.00",
+                "warranty_expires": null,
+                "assigned_to": {"id": 7, "username": "jane", "name": "Jane Doe", "type": "user"},
+                "last_checkout": {"datetime": "2022-03-02 09:30:00", "formatted": "Mar 2, 2022 9:30AM"},
+                "notes": "Has a dent"
+            }"#,
+        )
+        .unwrap();
+
+        let model_numbers = [(3, "MKGP3LL/A".to_string())].iter().cloned().collect();
+        let user_emails = [(7, "Jane@example.com".to_string())].iter().cloned().collect();
+        let item = asset.to_asset_item(&company, &model_numbers, &user_emails);
+
+        assert_eq!("ox-0042", item.name);
+        assert_eq!("OX-0042", item.snipeit_asset_tag);
+        assert_eq!("Laptop", item.type_);
+        assert_eq!("Apple", item.manufacturer);
+        assert_eq!("MKGP3LL/A", item.model_number);
+        assert_eq!("C02XK1", item.serial_number);
+        assert!((item.purchase_price - 2499.0).abs() < f32::EPSILON);
+        assert_eq!(Some(chrono::NaiveDate::from_ymd(2022, 3, 1)), item.purchase_date);
+        assert_eq!("jane@example.com", item.current_employee_borrowing);
+        assert_eq!("Imported from Snipe-IT asset tag OX-0042.\n\nHas a dent", item.notes);
+        assert_eq!(
+            Some(Utc.ymd(2022, 3, 2).and_hms(9, 30, 0)),
+            parse_snipeit_datetime(&asset.last_checkout)
+        );
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!("laptop", escape_like("laptop"));
+        assert_eq!("100\\% cotton\\_tee\\\\", escape_like("100% cotton_tee\\"));
+    }
+
+    #[test]
+    fn test_can_assign_asset_item() {
+        let config = AssetAssignmentConfig::default();
+        let mut user = mock_user();
+        user.email = "sam@example.com".to_string();
+        user.groups = vec!["engineering".to_string()];
+
+        let checkout = |email: &str| AssetCheckout {
+            id: 1,
+            asset_item_name: "thing".to_string(),
+            email: email.to_string(),
+            checked_out_at: Utc::now(),
+            checked_in_at: None,
+            cio_company_id: 1,
+        };
+
+        // People can hand over what they have, not what someone else has.
+        assert!(can_assign_asset_item(
+            &config,
+            &user,
+            Some(&checkout("Sam@example.com"))
+        ));
+        assert!(!can_assign_asset_item(
+            &config,
+            &user,
+            Some(&checkout("jane@example.com"))
+        ));
+        assert!(!can_assign_asset_item(&config, &user, None));
+
+        // The asset managers can assign anything.
+        user.groups.push("it".to_string());
+        assert!(can_assign_asset_item(
+            &config,
+            &user,
+            Some(&checkout("jane@example.com"))
+        ));
+        assert!(can_assign_asset_item(&config, &user, None));
+    }
+}
@@ -0,0 +1,562 @@
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use sendgrid_api::{traits::MailOps, Client as SendGrid};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_inventory::{AssetItem, AssetItems},
+    companies::Company,
+    db::Database,
+    schema::asset_reservations,
+};
+
+/// The longest we lend a loaner for in one reservation.
+static MAX_LOANER_RESERVATION_DAYS: i64 = 30;
+
+/// The status of a reservation that is waiting to be picked up.
+pub static RESERVATION_RESERVED: &str = "reserved";
+/// The status of a reservation whose loaner is with the borrower.
+pub static RESERVATION_PICKED_UP: &str = "picked up";
+/// The status of a reservation whose loaner was brought back.
+pub static RESERVATION_RETURNED: &str = "returned";
+/// The status of a reservation that was cancelled before the loaner was picked up.
+pub static RESERVATION_CANCELLED: &str = "cancelled";
+
+/// A reservation of a loaner asset, like a conference laptop or a demo unit, for a range of
+/// days. The range includes both the start and the end date.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct AssetReservation {
+    pub id: i32,
+    /// The name of the reserved asset item.
+    pub asset_name: String,
+    /// The email of the person borrowing the asset.
+    pub email: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub purpose: String,
+    pub status: String,
+    pub pickup_reminder_sent: bool,
+    pub return_reminder_sent: bool,
+    pub created_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = asset_reservations)]
+struct NewAssetReservation {
+    asset_name: String,
+    email: String,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    purpose: String,
+    status: String,
+    created_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// A request to reserve a loaner, either a specific asset or any free loaner of a type.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct LoanerReservationRequest {
+    /// The name of the asset to reserve. If empty, we pick a free loaner of `type_`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub asset_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "type")]
+    pub type_: String,
+    pub email: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub purpose: String,
+}
+
+impl AssetReservation {
+    /// Returns if the reservation still holds the loaner during its dates.
+    pub fn is_active(&self) -> bool {
+        self.status == RESERVATION_RESERVED || self.status == RESERVATION_PICKED_UP
+    }
+
+    /// Returns if the reservation holds the loaner on any day between two dates.
+    pub fn overlaps(&self, start_date: NaiveDate, end_date: NaiveDate) -> bool {
+        self.is_active() && self.start_date <= end_date && start_date <= self.end_date
+    }
+
+    async fn set_status(&self, db: &Database, status: &str) -> Result<AssetReservation> {
+        Ok(diesel::update(asset_reservations::dsl::asset_reservations)
+            .filter(asset_reservations::dsl::id.eq(self.id))
+            .set(asset_reservations::dsl::status.eq(status.to_string()))
+            .get_result_async::<AssetReservation>(db.pool())
+            .await?)
+    }
+
+    pub async fn cancel(&self, db: &Database) -> Result<AssetReservation> {
+        if self.status != RESERVATION_RESERVED {
+            bail!("reservation {} is {}, it can't be cancelled", self.id, self.status);
+        }
+
+        self.set_status(db, RESERVATION_CANCELLED).await
+    }
+
+    pub async fn pick_up(&self, db: &Database) -> Result<AssetReservation> {
+        if self.status != RESERVATION_RESERVED {
+            bail!("reservation {} is {}, it can't be picked up", self.id, self.status);
+        }
+
+        self.set_status(db, RESERVATION_PICKED_UP).await
+    }
+
+    pub async fn return_loaner(&self, db: &Database) -> Result<AssetReservation> {
+        if self.status != RESERVATION_PICKED_UP {
+            bail!("reservation {} is {}, it can't be returned", self.id, self.status);
+        }
+
+        self.set_status(db, RESERVATION_RETURNED).await
+    }
+
+    /// Format the reservation for a Slack message.
+    pub fn summary(&self) -> String {
+        format!(
+            "#{} `{}` for {} from {} to {} ({})",
+            self.id, self.asset_name, self.email, self.start_date, self.end_date, self.status
+        )
+    }
+}
+
+/// Get a reservation by its id.
+pub async fn get_reservation(db: &Database, cio_company_id: i32, id: i32) -> Result<AssetReservation> {
+    match asset_reservations::dsl::asset_reservations
+        .filter(asset_reservations::dsl::cio_company_id.eq(cio_company_id))
+        .filter(asset_reservations::dsl::id.eq(id))
+        .first_async::<AssetReservation>(db.pool())
+        .await
+    {
+        Ok(reservation) => Ok(reservation),
+        Err(e) => bail!("could not find reservation {}: {}", id, e),
+    }
+}
+
+/// Get the reservations that end on or after a date, soonest first.
+pub async fn list_reservations(db: &Database, cio_company_id: i32, from: NaiveDate) -> Result<Vec<AssetReservation>> {
+    Ok(asset_reservations::dsl::asset_reservations
+        .filter(asset_reservations::dsl::cio_company_id.eq(cio_company_id))
+        .filter(asset_reservations::dsl::end_date.ge(from))
+        .order_by(asset_reservations::dsl::start_date.asc())
+        .load_async::<AssetReservation>(db.pool())
+        .await?)
+}
+
+/// Pick the loaner to reserve for a request, or explain why none is free.
+pub fn pick_loaner<'a>(
+    loaners: &'a [AssetItem],
+    reservations: &[AssetReservation],
+    request: &LoanerReservationRequest,
+) -> Result<&'a AssetItem> {
+    let conflicts = |item: &AssetItem| -> Vec<&AssetReservation> {
+        reservations
+            .iter()
+            .filter(|r| r.asset_name == item.name && r.overlaps(request.start_date, request.end_date))
+            .collect()
+    };
+
+    if !request.asset_name.is_empty() {
+        let item = match loaners.iter().find(|i| i.name == request.asset_name) {
+            Some(item) => item,
+            None => bail!("`{}` is not a loaner", request.asset_name),
+        };
+        if let Some(conflict) = conflicts(item).first() {
+            bail!("`{}` is already reserved: {}", item.name, conflict.summary());
+        }
+
+        return Ok(item);
+    }
+
+    let candidates: Vec<&AssetItem> = loaners
+        .iter()
+        .filter(|i| i.type_.to_lowercase() == request.type_.trim().to_lowercase())
+        .collect();
+    if candidates.is_empty() {
+        bail!("there are no `{}` loaners", request.type_);
+    }
+
+    match candidates.into_iter().find(|i| conflicts(i).is_empty()) {
+        Some(item) => Ok(item),
+        None => bail!(
+            "all the `{}` loaners are reserved between {} and {}",
+            request.type_,
+            request.start_date,
+            request.end_date
+        ),
+    }
+}
+
+/// Get the assets that are in the loaner pool.
+pub async fn list_loaners(db: &Database, cio_company_id: i32) -> Result<Vec<AssetItem>> {
+    Ok(AssetItems::get_from_db(db, cio_company_id)
+        .await?
+        .into_iter()
+        .filter(|i| i.is_loaner)
+        .collect())
+}
+
+/// Reserve a loaner for a range of days.
+pub async fn reserve_loaner(
+    db: &Database,
+    company: &Company,
+    request: &LoanerReservationRequest,
+) -> Result<AssetReservation> {
+    if request.email.is_empty() {
+        bail!("who the loaner is for must be set");
+    }
+    if request.asset_name.is_empty() && request.type_.trim().is_empty() {
+        bail!("either the asset or the type of loaner must be set");
+    }
+    if request.end_date < request.start_date {
+        bail!("the reservation must end after it starts");
+    }
+    if request.start_date < Utc::now().date().naive_utc() {
+        bail!("the reservation can't start in the past");
+    }
+    if request.end_date - request.start_date >= Duration::days(MAX_LOANER_RESERVATION_DAYS) {
+        bail!(
+            "loaners can be reserved for at most {} days",
+            MAX_LOANER_RESERVATION_DAYS
+        );
+    }
+
+    let loaners = list_loaners(db, company.id).await?;
+    let reservations = list_reservations(db, company.id, request.start_date).await?;
+    let item = pick_loaner(&loaners, &reservations, request)?;
+
+    let reservation = diesel::insert_into(asset_reservations::table)
+        .values(NewAssetReservation {
+            asset_name: item.name.to_string(),
+            email: request.email.to_string(),
+            start_date: request.start_date,
+            end_date: request.end_date,
+            purpose: request.purpose.trim().to_string(),
+            status: RESERVATION_RESERVED.to_string(),
+            created_at: Utc::now(),
+            cio_company_id: company.id,
+        })
+        .get_result_async::<AssetReservation>(db.pool())
+        .await?;
+    info!("reserved loaner {}", reservation.summary());
+
+    Ok(reservation)
+}
+
+/// Parse the text of `/loaner reserve`, like `2023-06-12 2023-06-16 Laptop: RustConf`. What
+/// comes after the dates is the name of a loaner, or a type of loaner, and an optional purpose
+/// after a colon.
+pub fn parse_slack_reservation(text: &str, email: &str, loaners: &[AssetItem]) -> Result<LoanerReservationRequest> {
+    let mut parts = text.trim().splitn(3, ' ');
+    let (start, end, rest) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(start), Some(end), Some(rest)) => (start, end, rest),
+        _ => bail!("reserve a loaner with `/loaner reserve <start> <end> <loaner or type>[: purpose]`"),
+    };
+
+    let (what, purpose) = match rest.split_once(':') {
+        Some((what, purpose)) => (what.trim(), purpose.trim()),
+        None => (rest.trim(), ""),
+    };
+
+    let mut request = LoanerReservationRequest {
+        email: email.to_string(),
+        start_date: NaiveDate::parse_from_str(start, "%Y-%m-%d")?,
+        end_date: NaiveDate::parse_from_str(end, "%Y-%m-%d")?,
+        purpose: purpose.to_string(),
+        ..Default::default()
+    };
+    if loaners.iter().any(|i| i.name == what) {
+        request.asset_name = what.to_string();
+    } else {
+        request.type_ = what.to_string();
+    }
+
+    Ok(request)
+}
+
+/// How much a loaner was used over a period of time.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct LoanerUtilization {
+    pub asset_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "type")]
+    pub type_: String,
+    #[serde(default)]
+    pub reservations: i32,
+    /// The days the loaner was reserved for.
+    #[serde(default)]
+    pub days_reserved: i64,
+    /// The share of the days of the period the loaner was reserved for, from 0 to 1.
+    #[serde(default)]
+    pub utilization: f64,
+}
+
+/// Compute how much each loaner was used between two dates, least used first so the report
+/// shows right away what the pool could do without.
+pub fn loaner_utilization(
+    loaners: &[AssetItem],
+    reservations: &[AssetReservation],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<LoanerUtilization> {
+    let period = (to - from).num_days() + 1;
+    let mut report: Vec<LoanerUtilization> = loaners
+        .iter()
+        .map(|item| {
+            let mut entry = LoanerUtilization {
+                asset_name: item.name.to_string(),
+                type_: item.type_.to_string(),
+                ..Default::default()
+            };
+            for r in reservations
+                .iter()
+                .filter(|r| r.asset_name == item.name && r.status != RESERVATION_CANCELLED)
+                .filter(|r| r.start_date <= to && from <= r.end_date)
+            {
+                entry.reservations += 1;
+                entry.days_reserved += (r.end_date.min(to) - r.start_date.max(from)).num_days() + 1;
+            }
+            if period > 0 {
+                entry.utilization = entry.days_reserved as f64 / period as f64;
+            }
+
+            entry
+        })
+        .collect();
+    report.sort_by(|a, b| {
+        a.utilization
+            .partial_cmp(&b.utilization)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.asset_name.cmp(&b.asset_name))
+    });
+
+    report
+}
+
+/// Generate the utilization report of the loaner pool over the past days.
+pub async fn generate_loaner_utilization_report(
+    db: &Database,
+    company: &Company,
+    days: i64,
+) -> Result<Vec<LoanerUtilization>> {
+    let to = Utc::now().date().naive_utc();
+    let from = to - Duration::days(days.max(1) - 1);
+
+    let loaners = list_loaners(db, company.id).await?;
+    let reservations = list_reservations(db, company.id, from).await?;
+
+    Ok(loaner_utilization(&loaners, &reservations, from, to))
+}
+
+async fn send_loaner_reminder(
+    company: &Company,
+    reservation: &AssetReservation,
+    subject: &str,
+    body: &str,
+) -> Result<()> {
+    let sendgrid_client = SendGrid::new_from_env();
+    sendgrid_client
+        .mail_send()
+        .send_plain_text(
+            subject,
+            &format!(
+                "Hi,
+
+{}
+
+Reservation {}.
+
+xoxo,
+  The Asset Bot",
+                body,
+                reservation.summary()
+            ),
+            &[reservation.email.to_string()],
+            &[],
+            &[],
+            &format!("admin@{}", company.gsuite_domain),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Remind the borrowers the day before they should pick up a loaner, and on the day they
+/// should return it, or every day after if it is late.
+pub async fn send_loaner_reminders(db: &Database, company: &Company) -> Result<()> {
+    let today = Utc::now().date().naive_utc();
+
+    for reservation in list_reservations(db, company.id, today - Duration::days(MAX_LOANER_RESERVATION_DAYS)).await? {
+        if reservation.status == RESERVATION_RESERVED
+            && !reservation.pickup_reminder_sent
+            && reservation.start_date <= today + Duration::days(1)
+        {
+            if let Err(e) = send_loaner_reminder(
+                company,
+                &reservation,
+                &format!("Pick up {}", reservation.asset_name),
+                &format!(
+                    "The loaner `{}` you reserved is ready to be picked up on {}.",
+                    reservation.asset_name, reservation.start_date
+                ),
+            )
+            .await
+            {
+                warn!("sending pickup reminder for {} failed: {}", reservation.summary(), e);
+                continue;
+            }
+
+            diesel::update(asset_reservations::dsl::asset_reservations)
+                .filter(asset_reservations::dsl::id.eq(reservation.id))
+                .set(asset_reservations::dsl::pickup_reminder_sent.eq(true))
+                .execute_async(db.pool())
+                .await?;
+        }
+
+        if reservation.status == RESERVATION_PICKED_UP && reservation.end_date <= today {
+            let late = reservation.end_date < today;
+            if reservation.return_reminder_sent && !late {
+                continue;
+            }
+
+            let body = if late {
+                format!(
+                    "The loaner `{}` was due back on {}, please return it as soon as you can.",
+                    reservation.asset_name, reservation.end_date
+                )
+            } else {
+                format!("The loaner `{}` is due back today.", reservation.asset_name)
+            };
+            if let Err(e) = send_loaner_reminder(
+                company,
+                &reservation,
+                &format!("Return {}", reservation.asset_name),
+                &body,
+            )
+            .await
+            {
+                warn!("sending return reminder for {} failed: {}", reservation.summary(), e);
+                continue;
+            }
+
+            diesel::update(asset_reservations::dsl::asset_reservations)
+                .filter(asset_reservations::dsl::id.eq(reservation.id))
+                .set(asset_reservations::dsl::return_reminder_sent.eq(true))
+                .execute_async(db.pool())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+
+    use super::{
+        loaner_utilization, parse_slack_reservation, pick_loaner, AssetReservation, LoanerReservationRequest,
+        RESERVATION_CANCELLED, RESERVATION_RESERVED,
+    };
+    use crate::asset_inventory::AssetItem;
+    use crate::tests::mock_record;
+
+    fn mock_loaner(name: &str, type_: &str) -> AssetItem {
+        let mut item: AssetItem = mock_record(json!({}));
+        item.name = name.to_string();
+        item.type_ = type_.to_string();
+        item.is_loaner = true;
+        item
+    }
+
+    fn mock_reservation(asset_name: &str, start: u32, end: u32, status: &str) -> AssetReservation {
+        AssetReservation {
+            id: 1,
+            asset_name: asset_name.to_string(),
+            email: "someone@example.com".to_string(),
+            start_date: NaiveDate::from_ymd(2023, 5, start),
+            end_date: NaiveDate::from_ymd(2023, 5, end),
+            purpose: Default::default(),
+            status: status.to_string(),
+            pickup_reminder_sent: false,
+            return_reminder_sent: false,
+            created_at: Utc::now(),
+            cio_company_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_pick_loaner() {
+        let loaners = vec![mock_loaner("Laptop 1", "Laptop"), mock_loaner("Laptop 2", "Laptop")];
+        let reservations = vec![
+            mock_reservation("Laptop 1", 10, 12, RESERVATION_RESERVED),
+            mock_reservation("Laptop 2", 1, 10, RESERVATION_CANCELLED),
+        ];
+
+        let mut request = LoanerReservationRequest {
+            type_: "laptop".to_string(),
+            email: "new@example.com".to_string(),
+            start_date: NaiveDate::from_ymd(2023, 5, 12),
+            end_date: NaiveDate::from_ymd(2023, 5, 14),
+            ..Default::default()
+        };
+        assert_eq!("Laptop 2", pick_loaner(&loaners, &reservations, &request).unwrap().name);
+
+        // Reservations include their last day.
+        request.asset_name = "Laptop 1".to_string();
+        assert!(pick_loaner(&loaners, &reservations, &request).is_err());
+        request.start_date = NaiveDate::from_ymd(2023, 5, 13);
+        assert_eq!("Laptop 1", pick_loaner(&loaners, &reservations, &request).unwrap().name);
+
+        request.asset_name = "Demo unit".to_string();
+        assert!(pick_loaner(&loaners, &reservations, &request).is_err());
+    }
+
+    #[test]
+    fn test_parse_slack_reservation() {
+        let loaners = vec![mock_loaner("Demo unit", "Gimlet")];
+
+        let request = parse_slack_reservation(
+            "2023-05-10 2023-05-12 Demo unit: customer visit",
+            "me@example.com",
+            &loaners,
+        )
+        .unwrap();
+        assert_eq!("Demo unit", request.asset_name);
+        assert_eq!("customer visit", request.purpose);
+        assert_eq!(NaiveDate::from_ymd(2023, 5, 12), request.end_date);
+
+        let request = parse_slack_reservation("2023-05-10 2023-05-12 laptop", "me@example.com", &loaners).unwrap();
+        assert_eq!("laptop", request.type_);
+        assert!(request.asset_name.is_empty());
+
+        assert!(parse_slack_reservation("2023-05-10 laptop", "me@example.com", &loaners).is_err());
+    }
+
+    #[test]
+    fn test_loaner_utilization() {
+        let loaners = vec![mock_loaner("Laptop 1", "Laptop"), mock_loaner("Laptop 2", "Laptop")];
+        let reservations = vec![
+            mock_reservation("Laptop 1", 1, 5, RESERVATION_RESERVED),
+            // Only the days in the period count.
+            mock_reservation("Laptop 1", 9, 15, RESERVATION_RESERVED),
+            mock_reservation("Laptop 2", 1, 10, RESERVATION_CANCELLED),
+        ];
+
+        let report = loaner_utilization(
+            &loaners,
+            &reservations,
+            NaiveDate::from_ymd(2023, 5, 1),
+            NaiveDate::from_ymd(2023, 5, 10),
+        );
+        assert_eq!("Laptop 2", report[0].asset_name);
+        assert_eq!(0, report[0].days_reserved);
+        assert_eq!(2, report[1].reservations);
+        assert_eq!(7, report[1].days_reserved);
+        assert!((report[1].utilization - 0.7).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,244 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDate, Utc};
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    airtable::AIRTABLE_ASSET_TASKS_TABLE,
+    asset_inventory::{asset_item_airtable_link, AssetItem, AssetItems},
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    schema::asset_tasks,
+};
+
+/// How many days before a warranty expires we remind people about it.
+static WARRANTY_REMINDER_DAYS: i64 = 30;
+
+pub static ASSET_TASK_WARRANTY: &str = "Warranty";
+pub static ASSET_TASK_MAINTENANCE: &str = "Maintenance";
+
+/// The status of the tasks we create, people move them along in Airtable.
+static ASSET_TASK_TODO: &str = "Todo";
+
+/// Something that needs doing for an asset item, like renewing its warranty or maintaining
+/// it.
+#[db {
+    new_struct_name = "AssetTask",
+    airtable_base = "assets",
+    airtable_table = "AIRTABLE_ASSET_TASKS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "name" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = asset_tasks)]
+pub struct NewAssetTask {
+    /// The item, the kind of task, and when it is due, like `zeus maintenance due
+    /// 2023-08-01`, so we only create each task once.
+    pub name: String,
+    pub asset_item_name: String,
+    /// What needs doing, either `Warranty` or `Maintenance`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub kind: String,
+    pub due_date: NaiveDate,
+    /// These fields are filled in by the Airtable and should not be edited by the
+    /// API updating.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a AssetTask.
+#[async_trait]
+impl UpdateAirtableRecord<AssetTask> for AssetTask {
+    async fn update_airtable_record(&mut self, record: AssetTask) -> Result<()> {
+        if !record.status.is_empty() {
+            self.status = record.status;
+        }
+        if self.notes.is_empty() {
+            self.notes = record.notes;
+        }
+
+        Ok(())
+    }
+}
+
+impl AssetItem {
+    /// Return when the item is next due for maintenance, or None if it does not need any.
+    /// Items that were never maintained are due from when we bought them, or right away if
+    /// we do not know when that was.
+    pub fn next_maintenance_date(&self, today: NaiveDate) -> Option<NaiveDate> {
+        if self.maintenance_interval_days <= 0 {
+            return None;
+        }
+
+        match self.last_maintenance_date.or(self.purchase_date) {
+            Some(date) => Some(date + Duration::days(self.maintenance_interval_days as i64)),
+            None => Some(today),
+        }
+    }
+
+    /// Return the tasks the item needs: renewing its warranty if it expires within the next
+    /// 30 days, and maintaining it if it is due.
+    pub fn tasks(&self, today: NaiveDate) -> Vec<NewAssetTask> {
+        let mut tasks = Vec::new();
+
+        if let Some(expires) = self.warranty_expiration_date {
+            if expires >= today && expires <= today + Duration::days(WARRANTY_REMINDER_DAYS) {
+                tasks.push(self.task(ASSET_TASK_WARRANTY, "warranty expires", expires));
+            }
+        }
+
+        if let Some(due) = self.next_maintenance_date(today) {
+            if due <= today {
+                tasks.push(self.task(ASSET_TASK_MAINTENANCE, "maintenance due", due));
+            }
+        }
+
+        tasks
+    }
+
+    fn task(&self, kind: &str, what: &str, due_date: NaiveDate) -> NewAssetTask {
+        NewAssetTask {
+            name: format!("{} {} {}", self.name, what, due_date),
+            asset_item_name: self.name.to_string(),
+            kind: kind.to_string(),
+            due_date,
+            status: ASSET_TASK_TODO.to_string(),
+            notes: Default::default(),
+            cio_company_id: self.cio_company_id,
+        }
+    }
+}
+
+/// Create the tasks for the asset items whose warranty is expiring or that are due for
+/// maintenance, and remind people about them in Slack. Each task is only created, and posted,
+/// once.
+pub async fn refresh_asset_maintenance(db: &Database, company: &Company) -> Result<()> {
+    let today = Utc::now().date().naive_utc();
+
+    let mut reminders: Vec<String> = Vec::new();
+    for item in AssetItems::get_from_db(db, company.id).await? {
+        for task in item.tasks(today) {
+            if AssetTask::get_from_db(db, company.id, task.name.to_string())
+                .await
+                .is_some()
+            {
+                continue;
+            }
+
+            let task = task.upsert(db).await?;
+            info!("created asset task `{}`", task.name);
+
+            let link = asset_item_airtable_link(company, &item.airtable_record_id);
+            let item_name = if link.is_empty() {
+                format!("*{}*", item.name)
+            } else {
+                format!("*<{}|{}>*", link, item.name)
+            };
+            reminders.push(if task.kind == ASSET_TASK_WARRANTY {
+                format!("• The warranty of {} expires on {}", item_name, task.due_date)
+            } else {
+                format!("• {} is due for maintenance since {}", item_name, task.due_date)
+            });
+        }
+    }
+
+    if reminders.is_empty() {
+        return Ok(());
+    }
+
+    let channel = match company.alerts_channel("") {
+        Some(channel) => channel,
+        None => {
+            warn!(
+                "company `{}` has no alerts channel, not reminding about {} assets",
+                company.name,
+                reminders.len()
+            );
+            return Ok(());
+        }
+    };
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: "Assets needing attention".to_string(),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: reminders.join("\n"),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{ASSET_TASK_MAINTENANCE, ASSET_TASK_WARRANTY};
+    use crate::asset_inventory::AssetItem;
+    use crate::tests::mock_record;
+
+    #[test]
+    fn test_asset_tasks() {
+        let today = NaiveDate::from_ymd(2023, 7, 31);
+
+        let mut item: AssetItem = mock_record(json!({}));
+        item.name = "printer".to_string();
+        assert!(item.tasks(today).is_empty());
+
+        item.warranty_expiration_date = Some(NaiveDate::from_ymd(2023, 8, 15));
+        item.maintenance_interval_days = 90;
+        item.last_maintenance_date = Some(NaiveDate::from_ymd(2023, 6, 1));
+        let tasks = item.tasks(today);
+        assert_eq!(1, tasks.len());
+        assert_eq!(ASSET_TASK_WARRANTY, tasks[0].kind);
+        assert_eq!("printer warranty expires 2023-08-15", tasks[0].name);
+
+        // Maintenance was due at the end of July.
+        item.warranty_expiration_date = Some(NaiveDate::from_ymd(2023, 7, 1));
+        item.last_maintenance_date = Some(NaiveDate::from_ymd(2023, 4, 1));
+        let tasks = item.tasks(today);
+        assert_eq!(1, tasks.len());
+        assert_eq!(ASSET_TASK_MAINTENANCE, tasks[0].kind);
+        assert_eq!(NaiveDate::from_ymd(2023, 6, 30), tasks[0].due_date);
+
+        // Never maintained nor bought, it is due right away.
+        item.last_maintenance_date = None;
+        assert_eq!(Some(today), item.next_maintenance_date(today));
+    }
+}
@@ -0,0 +1,479 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use log::info;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_inventory::{AssetItem, NewAssetItem},
+    companies::Company,
+    db::Database,
+    inbound_email::{address_domain, InboundEmail},
+};
+
+/// The status of asset items we have ordered but not received yet.
+pub static ORDERED_STATUS: &str = "Ordered";
+
+/// The status of asset items we have received at the office.
+pub static RECEIVED_STATUS: &str = "Received";
+
+/// An item on an order confirmation from a supplier.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SupplierOrderItem {
+    pub description: String,
+    pub part_number: String,
+    pub quantity: i32,
+    pub unit_price: f32,
+    /// The serial numbers of the item, if the supplier sent them with the order.
+    pub serial_numbers: Vec<String>,
+}
+
+/// An order confirmation from a supplier.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SupplierOrder {
+    pub supplier: String,
+    pub order_number: String,
+    pub expected_delivery_date: Option<NaiveDate>,
+    pub items: Vec<SupplierOrderItem>,
+}
+
+/// The labels a supplier uses in the plain text version of their order confirmation emails.
+struct SupplierFormat {
+    supplier: &'static str,
+    /// The manufacturer of the items, if the supplier only sells their own.
+    manufacturer: &'static str,
+    domains: &'static [&'static str],
+    order_number: &'static [&'static str],
+    description: &'static [&'static str],
+    part_number: &'static [&'static str],
+    quantity: &'static [&'static str],
+    price: &'static [&'static str],
+    serial_numbers: &'static [&'static str],
+    delivery: &'static [&'static str],
+}
+
+static SUPPLIER_FORMATS: &[SupplierFormat] = &[
+    SupplierFormat {
+        supplier: "Apple",
+        manufacturer: "Apple",
+        domains: &["apple.com"],
+        order_number: &["Order Number"],
+        // Apple puts the name of the product on its own line, right before the part number.
+        description: &[],
+        part_number: &["Part Number"],
+        quantity: &["Quantity", "Qty"],
+        price: &["Item Price", "Price"],
+        serial_numbers: &["Serial Numbers", "Serial Number"],
+        delivery: &["Delivers", "Delivery", "Estimated Delivery"],
+    },
+    SupplierFormat {
+        supplier: "CDW",
+        manufacturer: "",
+        domains: &["cdw.com"],
+        order_number: &["Order #", "Order Number"],
+        description: &["Item"],
+        part_number: &["Mfg. Part#", "Mfg Part#", "Mfg. Part #"],
+        quantity: &["Qty", "Quantity"],
+        price: &["Unit Price", "Price"],
+        serial_numbers: &["Serial Numbers", "Serial Number"],
+        delivery: &["Estimated Delivery", "Estimated Arrival"],
+    },
+];
+
+/// Return the value of a `Label: value` line if it has one of the labels.
+fn labeled<'a>(line: &'a str, labels: &[&str]) -> Option<&'a str> {
+    for label in labels {
+        if let Some(start) = line.get(..label.len()) {
+            if start.eq_ignore_ascii_case(label) {
+                if let Some(value) = line[label.len()..].trim_start().strip_prefix(':') {
+                    return Some(value.trim());
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn parse_price(s: &str) -> f32 {
+    s.trim()
+        .trim_start_matches("USD")
+        .trim()
+        .trim_start_matches('$')
+        .replace(',', "")
+        .parse()
+        .unwrap_or_default()
+}
+
+/// Parse the last date in a string, since suppliers give delivery windows like
+/// "Mar 3 - Mar 7, 2023" and we want to know when everything will be there.
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    let re = Regex::new(r"\d{4}-\d{2}-\d{2}|\d{1,2}/\d{1,2}/\d{4}|[A-Za-z]{3,9}\.? \d{1,2}, \d{4}").unwrap();
+
+    re.find_iter(s)
+        .filter_map(|m| {
+            let date = m.as_str().replace('.', "");
+            NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .or_else(|_| NaiveDate::parse_from_str(&date, "%m/%d/%Y"))
+                .or_else(|_| NaiveDate::parse_from_str(&date, "%b %d, %Y"))
+                .or_else(|_| NaiveDate::parse_from_str(&date, "%B %d, %Y"))
+                .ok()
+        })
+        .last()
+}
+
+/// Returns if an email address is at one of the domains, or a subdomain of them.
+fn is_from_domain(address: &str, domains: &[&str]) -> bool {
+    let domain = address_domain(address);
+
+    domains
+        .iter()
+        .any(|d| domain == *d || domain.ends_with(&format!(".{}", d)))
+}
+
+fn find_supplier_format(email: &InboundEmail) -> Option<&'static SupplierFormat> {
+    SUPPLIER_FORMATS
+        .iter()
+        .find(|f| is_from_domain(&email.from, f.domains))
+        .or_else(|| {
+            // The email might have been forwarded to us by whoever placed the order.
+            email
+                .text
+                .lines()
+                .filter_map(|line| labeled(line.trim().trim_start_matches('>').trim(), &["From"]))
+                .find_map(|from| SUPPLIER_FORMATS.iter().find(|f| is_from_domain(from, f.domains)))
+        })
+}
+
+fn parse_with_format(format: &SupplierFormat, text: &str) -> SupplierOrder {
+    let mut order = SupplierOrder {
+        supplier: format.supplier.to_string(),
+        ..Default::default()
+    };
+
+    let mut current: Option<SupplierOrderItem> = None;
+    let mut last_unlabeled = "";
+    for line in text.lines() {
+        let line = line.trim().trim_start_matches('>').trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(v) = labeled(line, format.order_number) {
+            if order.order_number.is_empty() {
+                order.order_number = v.trim_start_matches('#').to_string();
+            }
+        } else if let Some(v) = labeled(line, format.description) {
+            order.items.extend(current.take());
+            current = Some(SupplierOrderItem {
+                description: v.to_string(),
+                quantity: 1,
+                ..Default::default()
+            });
+        } else if let Some(v) = labeled(line, format.part_number) {
+            match current.as_mut() {
+                Some(item) if item.part_number.is_empty() => {
+                    if item.description.is_empty() {
+                        item.description = last_unlabeled.to_string();
+                    }
+                    item.part_number = v.to_string();
+                }
+                _ => {
+                    order.items.extend(current.take());
+                    current = Some(SupplierOrderItem {
+                        description: last_unlabeled.to_string(),
+                        part_number: v.to_string(),
+                        quantity: 1,
+                        ..Default::default()
+                    });
+                }
+            }
+        } else if let Some(v) = labeled(line, format.quantity) {
+            if let Some(item) = current.as_mut() {
+                item.quantity = v.parse().unwrap_or(1);
+            }
+        } else if let Some(v) = labeled(line, format.price) {
+            if let Some(item) = current.as_mut() {
+                item.unit_price = parse_price(v);
+            }
+        } else if let Some(v) = labeled(line, format.serial_numbers) {
+            if let Some(item) = current.as_mut() {
+                item.serial_numbers.extend(
+                    v.split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|s| !s.is_empty())
+                        .map(|s| s.to_string()),
+                );
+            }
+        } else if let Some(v) = labeled(line, format.delivery) {
+            if let Some(date) = parse_date(v) {
+                if order.expected_delivery_date.map(|d| date > d).unwrap_or(true) {
+                    order.expected_delivery_date = Some(date);
+                }
+            }
+        } else {
+            last_unlabeled = line;
+        }
+    }
+    order.items.extend(current.take());
+    order
+        .items
+        .retain(|item| !item.description.is_empty() || !item.part_number.is_empty());
+
+    order
+}
+
+/// Parse an order confirmation email from one of the suppliers we know. Returns None if the
+/// email is not from a supplier we know or does not look like an order confirmation.
+pub fn parse_supplier_order(email: &InboundEmail) -> Option<SupplierOrder> {
+    let format = find_supplier_format(email)?;
+
+    let order = parse_with_format(format, &email.text);
+    if order.order_number.is_empty() || order.items.is_empty() {
+        return None;
+    }
+
+    Some(order)
+}
+
+/// Guess the type of an asset item from its description.
+pub(crate) fn guess_asset_type(description: &str) -> String {
+    let description = description.to_lowercase();
+    let types = [
+        ("Laptop", &["macbook", "laptop", "thinkpad", "notebook"][..]),
+        ("Monitor", &["monitor", "display"][..]),
+        ("Desktop", &["mac mini", "mac studio", "imac", "desktop"][..]),
+        ("Tablet", &["ipad", "tablet"][..]),
+        ("Phone", &["iphone", "phone"][..]),
+    ];
+
+    types
+        .iter()
+        .find(|(_, words)| words.iter().any(|w| description.contains(w)))
+        .map(|(t, _)| t.to_string())
+        .unwrap_or_default()
+}
+
+impl SupplierOrder {
+    /// Return the asset items for the order, one for each unit we ordered. We name the items
+    /// after the order so that getting the same email twice does not create duplicates.
+    pub fn asset_items(&self, company: &Company) -> Vec<NewAssetItem> {
+        let manufacturer = SUPPLIER_FORMATS
+            .iter()
+            .find(|f| f.supplier == self.supplier)
+            .map(|f| f.manufacturer)
+            .unwrap_or_default();
+
+        let mut items = Vec::new();
+        for (i, item) in self.items.iter().enumerate() {
+            let count = std::cmp::max(item.quantity, item.serial_numbers.len() as i32);
+            for n in 0..count {
+                items.push(NewAssetItem {
+                    name: format!("{}-{}-{}", self.order_number, i + 1, n + 1).to_lowercase(),
+                    picture: Default::default(),
+                    type_: guess_asset_type(&item.description),
+                    qualities: Default::default(),
+                    status: ORDERED_STATUS.to_string(),
+                    manufacturer: manufacturer.to_string(),
+                    model_number: item.part_number.to_string(),
+                    serial_number: item.serial_numbers.get(n as usize).cloned().unwrap_or_default(),
+                    purchase_price: item.unit_price,
+                    purchase_date: Default::default(),
+                    depreciation_schedule: Default::default(),
+                    current_book_value: item.unit_price,
+                    current_employee_borrowing: Default::default(),
+                    conference_room_using: Default::default(),
+                    notes: format!(
+                        "Ordered from {} in order {}: {}",
+                        self.supplier, self.order_number, item.description
+                    ),
+                    barcode: Default::default(),
+                    barcode_symbology: Default::default(),
+                    barcode_png: Default::default(),
+                    barcode_svg: Default::default(),
+                    barcode_pdf_label: Default::default(),
+                    qr_code: Default::default(),
+                    qr_code_png: Default::default(),
+                    qr_code_svg: Default::default(),
+                    supplier: self.supplier.to_string(),
+                    order_number: self.order_number.to_string(),
+                    expected_delivery_date: self.expected_delivery_date,
+                    is_loaner: false,
+                    warranty_expiration_date: Default::default(),
+                    maintenance_interval_days: 0,
+                    last_maintenance_date: Default::default(),
+                    last_counted_date: Default::default(),
+                    snipeit_asset_tag: Default::default(),
+                    cio_company_id: company.id,
+                });
+            }
+        }
+
+        items
+    }
+}
+
+/// Pre-create the asset items for an order confirmation email from a supplier.
+pub async fn create_asset_items_from_email(
+    db: &Database,
+    company: &Company,
+    email: &InboundEmail,
+) -> Result<Vec<AssetItem>> {
+    // Anyone can send us an email pretending to be a supplier.
+    if !email.is_authenticated() {
+        info!(
+            "email `{}` from {} is not authenticated, ignoring",
+            email.subject, email.from
+        );
+        return Ok(vec![]);
+    }
+    // We only trust the supplier in the body of a forwarded email if one of us forwarded it.
+    let is_from_supplier = SUPPLIER_FORMATS.iter().any(|f| is_from_domain(&email.from, f.domains));
+    if !is_from_supplier && (company.gsuite_domain.is_empty() || !email.is_from_domain(&company.gsuite_domain)) {
+        info!(
+            "email `{}` from {} is not from a supplier or forwarded by us, ignoring",
+            email.subject, email.from
+        );
+        return Ok(vec![]);
+    }
+
+    let order = match parse_supplier_order(email) {
+        Some(order) => order,
+        None => {
+            info!(
+                "email `{}` from {} is not a supplier order, ignoring",
+                email.subject, email.from
+            );
+            return Ok(vec![]);
+        }
+    };
+
+    let mut created = Vec::new();
+    for item in order.asset_items(company) {
+        if let Some(existing) = AssetItem::get_from_db(db, company.id, item.name.to_string()).await {
+            if existing.status != ORDERED_STATUS {
+                // We already received the item, don't put it back to ordered.
+                continue;
+            }
+        }
+
+        created.push(item.upsert(db).await?);
+    }
+
+    info!(
+        "created {} ordered asset items from {} order {}",
+        created.len(),
+        order.supplier,
+        order.order_number
+    );
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{guess_asset_type, parse_supplier_order, InboundEmail, SupplierOrderItem};
+
+    #[test]
+    fn test_parse_apple_order() {
+        let email = InboundEmail {
+            from: "Apple Store <order_acknowledgment@orders.apple.com>".to_string(),
+            subject: "We're processing your order W1234567890".to_string(),
+            text: r#"Thanks for your order.
+
+Order Number: W1234567890
+Ordered on: February 20, 2023
+
+MacBook Pro 14-inch - Space Gray
+Part Number: MPHE3LL/A
+Quantity: 2
+Item Price: $1,999.00
+Serial Numbers: C02ABC123, C02DEF456
+Delivers: Mar 3 - Mar 7, 2023
+
+Studio Display - Standard glass
+Part Number: MK0U3LL/A
+Quantity: 1
+Item Price: $1,599.00
+Delivers: Mar 2, 2023
+"#
+            .to_string(),
+            ..Default::default()
+        };
+
+        let order = parse_supplier_order(&email).unwrap();
+        assert_eq!("Apple", order.supplier);
+        assert_eq!("W1234567890", order.order_number);
+        assert_eq!(NaiveDate::from_ymd_opt(2023, 3, 7), order.expected_delivery_date);
+        assert_eq!(
+            vec![
+                SupplierOrderItem {
+                    description: "MacBook Pro 14-inch - Space Gray".to_string(),
+                    part_number: "MPHE3LL/A".to_string(),
+                    quantity: 2,
+                    unit_price: 1999.0,
+                    serial_numbers: vec!["C02ABC123".to_string(), "C02DEF456".to_string()],
+                },
+                SupplierOrderItem {
+                    description: "Studio Display - Standard glass".to_string(),
+                    part_number: "MK0U3LL/A".to_string(),
+                    quantity: 1,
+                    unit_price: 1599.0,
+                    serial_numbers: vec![],
+                },
+            ],
+            order.items
+        );
+    }
+
+    #[test]
+    fn test_parse_forwarded_cdw_order() {
+        let email = InboundEmail {
+            from: "Jane <jane@example.com>".to_string(),
+            subject: "Fwd: CDW Order Confirmation".to_string(),
+            text: r#"---------- Forwarded message ---------
+From: CDW <orderconfirmation@cdw.com>
+Subject: Order Confirmation
+
+Order #: NXQ4821
+Item: Dell UltraSharp 27 4K USB-C Hub Monitor
+Mfg. Part#: U2723QE
+Qty: 3
+Unit Price: $579.99
+Estimated Delivery: 03/10/2023
+"#
+            .to_string(),
+            ..Default::default()
+        };
+
+        let order = parse_supplier_order(&email).unwrap();
+        assert_eq!("CDW", order.supplier);
+        assert_eq!("NXQ4821", order.order_number);
+        assert_eq!(NaiveDate::from_ymd_opt(2023, 3, 10), order.expected_delivery_date);
+        assert_eq!(1, order.items.len());
+        assert_eq!("U2723QE", order.items[0].part_number);
+        assert_eq!(3, order.items[0].quantity);
+        assert_eq!(579.99, order.items[0].unit_price);
+    }
+
+    #[test]
+    fn test_parse_unknown_supplier() {
+        let email = InboundEmail {
+            from: "someone@example.com".to_string(),
+            subject: "Order Number: 1234".to_string(),
+            text: "Order Number: 1234\nPart Number: ABC\n".to_string(),
+            ..Default::default()
+        };
+
+        assert!(parse_supplier_order(&email).is_none());
+    }
+
+    #[test]
+    fn test_guess_asset_type() {
+        assert_eq!("Laptop", guess_asset_type("MacBook Pro 14-inch"));
+        assert_eq!("Monitor", guess_asset_type("Dell UltraSharp 27 Monitor"));
+        assert_eq!("", guess_asset_type("USB-C Cable"));
+    }
+}
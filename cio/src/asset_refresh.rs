@@ -0,0 +1,375 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate, Utc};
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    airtable::AIRTABLE_ASSET_REFRESH_FORECAST_TABLE,
+    asset_depreciation::DepreciationSchedule,
+    asset_inventory::{AssetItem, AssetItems},
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    schema::asset_refresh_forecasts,
+};
+
+/// How often we replace one type of asset.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AssetRefreshPolicy {
+    /// How long we keep an item, like `3 years` or `18 months`.
+    pub every: String,
+    /// What a replacement costs. When it is not set, we expect to pay what we paid for the
+    /// item we replace.
+    #[serde(default)]
+    pub cost: f32,
+}
+
+/// The refresh policies of the asset types, by type, like `Laptop`.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct AssetRefreshConfig {
+    #[serde(default)]
+    pub policies: HashMap<String, AssetRefreshPolicy>,
+    /// Items with these statuses are not ours to replace anymore, like `Retired`.
+    #[serde(default)]
+    pub excluded_statuses: Vec<String>,
+}
+
+/// An item that is due to be replaced.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct AssetRefreshItem {
+    pub asset_item_id: i32,
+    pub name: String,
+    pub asset_type: String,
+    /// Who has the item.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub employee: String,
+    pub purchase_date: NaiveDate,
+    pub refresh_date: NaiveDate,
+    pub estimated_cost: f32,
+}
+
+/// The items due to be replaced in a quarter. Items that are overdue are in the current
+/// quarter.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct AssetRefreshQuarter {
+    /// The quarter, like `2024 Q1`.
+    pub quarter: String,
+    pub start: NaiveDate,
+    pub item_count: i32,
+    pub estimated_cost: f32,
+    pub items: Vec<AssetRefreshItem>,
+}
+
+/// Return the first day of the quarter a date is in.
+pub fn quarter_start(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd(date.year(), (date.month0() / 3) * 3 + 1, 1)
+}
+
+/// Return the name of the quarter a date is in, like `2024 Q1`.
+pub fn quarter_name(date: NaiveDate) -> String {
+    format!("{} Q{}", date.year(), date.month0() / 3 + 1)
+}
+
+/// Return the same day a number of months later, or the last day of the month when it is
+/// shorter.
+fn add_months(date: NaiveDate, months: u32) -> NaiveDate {
+    let month0 = date.month0() + months;
+    let (year, month) = (date.year() + (month0 / 12) as i32, month0 % 12 + 1);
+
+    (1..=date.day())
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .unwrap()
+}
+
+/// The most quarters a forecast covers, past that our policies are guesses anyway.
+pub const MAX_REFRESH_FORECAST_QUARTERS: u32 = 20;
+
+/// Forecast the items due to be replaced over a number of quarters, starting with the
+/// current one. The number of quarters is clamped to between one and
+/// `MAX_REFRESH_FORECAST_QUARTERS`.
+pub fn refresh_forecast(
+    config: &AssetRefreshConfig,
+    items: &[AssetItem],
+    today: NaiveDate,
+    quarters: u32,
+) -> Vec<AssetRefreshQuarter> {
+    let quarters = quarters.clamp(1, MAX_REFRESH_FORECAST_QUARTERS);
+    let first = quarter_start(today);
+    let mut forecast: BTreeMap<NaiveDate, AssetRefreshQuarter> = BTreeMap::new();
+    for i in 0..quarters {
+        let start = add_months(first, i * 3);
+        forecast.insert(
+            start,
+            AssetRefreshQuarter {
+                quarter: quarter_name(start),
+                start,
+                item_count: 0,
+                estimated_cost: 0.0,
+                items: Vec::new(),
+            },
+        );
+    }
+    let end = add_months(first, quarters * 3);
+
+    for item in items {
+        if config
+            .excluded_statuses
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(&item.status))
+        {
+            continue;
+        }
+
+        let (policy, purchase_date) = match (config.policies.get(item.type_.trim()), item.purchase_date) {
+            (Some(policy), Some(purchase_date)) => (policy, purchase_date),
+            _ => continue,
+        };
+        let schedule = match DepreciationSchedule::parse(&policy.every) {
+            Ok(Some(schedule)) => schedule,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("invalid refresh policy for asset type {}: {}", item.type_, e);
+                continue;
+            }
+        };
+
+        let refresh_date = add_months(purchase_date, schedule.months);
+        if refresh_date >= end {
+            continue;
+        }
+
+        let estimated_cost = if policy.cost > 0.0 {
+            policy.cost
+        } else {
+            item.purchase_price
+        };
+        let quarter = match forecast.get_mut(&quarter_start(refresh_date).max(first)) {
+            Some(quarter) => quarter,
+            None => continue,
+        };
+        quarter.item_count += 1;
+        quarter.estimated_cost += estimated_cost;
+        quarter.items.push(AssetRefreshItem {
+            asset_item_id: item.id,
+            name: item.name.to_string(),
+            asset_type: item.type_.trim().to_string(),
+            employee: item.current_employee_borrowing.to_string(),
+            purchase_date,
+            refresh_date,
+            estimated_cost,
+        });
+    }
+
+    let mut forecast: Vec<AssetRefreshQuarter> = forecast.into_values().collect();
+    for quarter in &mut forecast {
+        quarter.items.sort_by(|a, b| a.refresh_date.cmp(&b.refresh_date));
+    }
+
+    forecast
+}
+
+/// An item due to be replaced, as we show it to finance for budgeting.
+#[db {
+    new_struct_name = "AssetRefreshForecast",
+    airtable_base = "finance",
+    airtable_table = "AIRTABLE_ASSET_REFRESH_FORECAST_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "asset_item_id" = "i32",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = asset_refresh_forecasts)]
+pub struct NewAssetRefreshForecast {
+    pub name: String,
+    #[serde(default)]
+    pub asset_item_id: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub asset_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub employee: String,
+    pub purchase_date: NaiveDate,
+    pub refresh_date: NaiveDate,
+    /// The quarter we budget the replacement in, like `2024 Q1`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub quarter: String,
+    #[serde(default)]
+    pub estimated_cost: f32,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a AssetRefreshForecast.
+#[async_trait]
+impl UpdateAirtableRecord<AssetRefreshForecast> for AssetRefreshForecast {
+    async fn update_airtable_record(&mut self, _record: AssetRefreshForecast) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The number of quarters we push to the finance base.
+const REFRESH_FORECAST_QUARTERS: u32 = 4;
+
+/// Forecast the asset refreshes of a company.
+pub async fn get_refresh_forecast(
+    db: &Database,
+    company: &Company,
+    config: &AssetRefreshConfig,
+    quarters: u32,
+) -> Result<Vec<AssetRefreshQuarter>> {
+    if quarters < 1 || quarters > MAX_REFRESH_FORECAST_QUARTERS {
+        bail!(
+            "a refresh forecast covers between 1 and {} quarters, not {}",
+            MAX_REFRESH_FORECAST_QUARTERS,
+            quarters
+        );
+    }
+
+    let items: Vec<AssetItem> = AssetItems::get_from_db(db, company.id).await?.into_iter().collect();
+
+    Ok(refresh_forecast(
+        config,
+        &items,
+        Utc::now().date().naive_utc(),
+        quarters,
+    ))
+}
+
+/// Push the rolling refresh forecast to the finance base, and remove the items that are not
+/// due anymore.
+pub async fn refresh_asset_refresh_forecast(
+    db: &Database,
+    company: &Company,
+    config: &AssetRefreshConfig,
+) -> Result<()> {
+    if company.airtable_base_id_finance.is_empty() || config.policies.is_empty() {
+        return Ok(());
+    }
+
+    let forecast = get_refresh_forecast(db, company, config, REFRESH_FORECAST_QUARTERS).await?;
+
+    let mut due = Vec::new();
+    for quarter in &forecast {
+        for item in &quarter.items {
+            let refresh = NewAssetRefreshForecast {
+                name: item.name.to_string(),
+                asset_item_id: item.asset_item_id,
+                asset_type: item.asset_type.to_string(),
+                employee: item.employee.to_string(),
+                purchase_date: item.purchase_date,
+                refresh_date: item.refresh_date,
+                quarter: quarter.quarter.to_string(),
+                estimated_cost: item.estimated_cost,
+                cio_company_id: company.id,
+            };
+            refresh.upsert(db).await?;
+            due.push(item.asset_item_id);
+        }
+    }
+
+    // Items that were replaced or have a new policy are not due anymore.
+    for refresh in AssetRefreshForecasts::get_from_db(db, company.id).await? {
+        if !due.contains(&refresh.asset_item_id) {
+            refresh.delete(db).await?;
+        }
+    }
+
+    info!(
+        "updated the refresh forecast of {} asset items over {} quarters",
+        due.len(),
+        forecast.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{
+        quarter_name, refresh_forecast, AssetRefreshConfig, AssetRefreshPolicy, MAX_REFRESH_FORECAST_QUARTERS,
+    };
+    use crate::asset_inventory::AssetItem;
+    use crate::tests::mock_record;
+
+    fn mock_asset(id: i32, type_: &str, purchase_price: f32, purchase_date: NaiveDate) -> AssetItem {
+        let mut item: AssetItem = mock_record(json!({}));
+        item.id = id;
+        item.type_ = type_.to_string();
+        item.purchase_price = purchase_price;
+        item.purchase_date = Some(purchase_date);
+        item
+    }
+
+    #[test]
+    fn test_refresh_forecast() {
+        let mut config = AssetRefreshConfig::default();
+        config.policies.insert(
+            "Laptop".to_string(),
+            AssetRefreshPolicy {
+                every: "3 years".to_string(),
+                cost: 0.0,
+            },
+        );
+        config.policies.insert(
+            "Monitor".to_string(),
+            AssetRefreshPolicy {
+                every: "5 years".to_string(),
+                cost: 400.0,
+            },
+        );
+        config.excluded_statuses.push("Retired".to_string());
+
+        let mut retired = mock_asset(5, "Laptop", 2000.0, NaiveDate::from_ymd(2019, 1, 1));
+        retired.status = "retired".to_string();
+        let items = vec![
+            // Overdue, so in the current quarter.
+            mock_asset(1, "Laptop", 2500.0, NaiveDate::from_ymd(2020, 6, 1)),
+            mock_asset(2, "Laptop", 3000.0, NaiveDate::from_ymd(2021, 2, 10)),
+            mock_asset(3, "Monitor", 300.0, NaiveDate::from_ymd(2019, 3, 1)),
+            // Not due for a while.
+            mock_asset(4, "Laptop", 3000.0, NaiveDate::from_ymd(2023, 1, 1)),
+            retired,
+            // No policy.
+            mock_asset(6, "Chair", 500.0, NaiveDate::from_ymd(2010, 1, 1)),
+        ];
+
+        let forecast = refresh_forecast(&config, &items, NaiveDate::from_ymd(2023, 11, 15), 4);
+        assert_eq!(
+            vec!["2023 Q4", "2024 Q1", "2024 Q2", "2024 Q3"],
+            forecast.iter().map(|q| q.quarter.as_str()).collect::<Vec<_>>()
+        );
+
+        assert_eq!(1, forecast[0].item_count);
+        assert!((forecast[0].estimated_cost - 2500.0).abs() < 0.001);
+        assert_eq!(NaiveDate::from_ymd(2023, 6, 1), forecast[0].items[0].refresh_date);
+
+        assert_eq!(2, forecast[1].item_count);
+        assert_eq!(
+            vec![2, 3],
+            forecast[1].items.iter().map(|i| i.asset_item_id).collect::<Vec<_>>()
+        );
+        assert!((forecast[1].estimated_cost - 3400.0).abs() < 0.001);
+
+        assert_eq!(0, forecast[2].item_count);
+        assert_eq!(0, forecast[3].item_count);
+
+        assert_eq!("2024 Q4", quarter_name(NaiveDate::from_ymd(2024, 12, 31)));
+
+        // No quarters is the current one, and there is a limit to how far out we look.
+        let forecast = refresh_forecast(&config, &items, NaiveDate::from_ymd(2023, 11, 15), 0);
+        assert_eq!(1, forecast.len());
+        assert_eq!(1, forecast[0].item_count);
+        let forecast = refresh_forecast(&config, &items, NaiveDate::from_ymd(2023, 11, 15), u32::MAX);
+        assert_eq!(MAX_REFRESH_FORECAST_QUARTERS as usize, forecast.len());
+    }
+}
@@ -0,0 +1,286 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::configs::User;
+
+/// The time zone of a business calendar that does not set one.
+static DEFAULT_BUSINESS_TIME_ZONE: Tz = chrono_tz::America::Los_Angeles;
+
+/// The working hours and holidays of an office, as written in the config files. Every field
+/// is optional, and falls back to 9am to 5pm Pacific, Monday to Friday.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BusinessHoursConfig {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub time_zone: String,
+    /// The days people work, like `Mon` or `Monday`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub working_days: Vec<String>,
+    /// When the working day starts, like `09:00`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub start: String,
+    /// When the working day ends, like `17:00`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub end: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holidays: Vec<NaiveDate>,
+}
+
+/// The business calendars of a company: the default one, and the ones of the offices that
+/// differ from it, keyed by building name.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BusinessCalendarsConfig {
+    #[serde(default, flatten)]
+    pub default: BusinessHoursConfig,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub offices: HashMap<String, BusinessHoursConfig>,
+}
+
+/// When people are working: the days, the hours in the office's time zone, and the holidays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusinessCalendar {
+    pub time_zone: Tz,
+    pub working_days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl Default for BusinessCalendar {
+    fn default() -> Self {
+        BusinessCalendar {
+            time_zone: DEFAULT_BUSINESS_TIME_ZONE,
+            working_days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            start: NaiveTime::from_hms(9, 0, 0),
+            end: NaiveTime::from_hms(17, 0, 0),
+            holidays: vec![],
+        }
+    }
+}
+
+impl BusinessHoursConfig {
+    /// Parse the config into a calendar, the unset fields are taken from `base`.
+    pub fn parse(&self, base: &BusinessCalendar) -> Result<BusinessCalendar> {
+        let mut calendar = base.clone();
+
+        if !self.time_zone.is_empty() {
+            calendar.time_zone = Tz::from_str(&self.time_zone)
+                .map_err(|e| anyhow!("invalid business time zone `{}`: {}", self.time_zone, e))?;
+        }
+        if !self.working_days.is_empty() {
+            calendar.working_days = self
+                .working_days
+                .iter()
+                .map(|d| Weekday::from_str(d.trim()).map_err(|_| anyhow!("invalid working day `{}`", d)))
+                .collect::<Result<Vec<Weekday>>>()?;
+        }
+        if !self.start.is_empty() {
+            calendar.start = NaiveTime::parse_from_str(self.start.trim(), "%H:%M")?;
+        }
+        if !self.end.is_empty() {
+            calendar.end = NaiveTime::parse_from_str(self.end.trim(), "%H:%M")?;
+        }
+        // Office holidays come on top of the company ones.
+        calendar.holidays.extend(self.holidays.iter().copied());
+        calendar.holidays.sort();
+        calendar.holidays.dedup();
+
+        if calendar.end <= calendar.start {
+            bail!(
+                "business hours end at {} before they start at {}",
+                calendar.end,
+                calendar.start
+            );
+        }
+        if calendar.working_days.is_empty() {
+            bail!("business calendar must have at least one working day");
+        }
+
+        Ok(calendar)
+    }
+}
+
+impl BusinessCalendarsConfig {
+    /// Return the company's default business calendar.
+    pub fn default_calendar(&self) -> BusinessCalendar {
+        self.default.parse(&BusinessCalendar::default()).unwrap_or_else(|e| {
+            warn!("invalid default business calendar: {}", e);
+            BusinessCalendar::default()
+        })
+    }
+
+    /// Return the business calendar of an office, or the default one if the office does not
+    /// have its own.
+    pub fn for_office(&self, building: &str) -> BusinessCalendar {
+        let default = self.default_calendar();
+        match self.offices.get(building) {
+            Some(office) => office.parse(&default).unwrap_or_else(|e| {
+                warn!("invalid business calendar for office `{}`: {}", building, e);
+                default
+            }),
+            None => default,
+        }
+    }
+
+    /// Return the business calendar of the office the user works from.
+    pub fn for_user(&self, user: &User) -> BusinessCalendar {
+        self.for_office(&user.building)
+    }
+}
+
+impl BusinessCalendar {
+    /// Return if people work on that date.
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        self.working_days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Return the business day after a date.
+    pub fn next_business_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut date = date.succ();
+        while !self.is_business_day(date) {
+            date = date.succ();
+        }
+
+        date
+    }
+
+    /// Return the nth business day starting from a date, 1 being the date itself if it is a
+    /// business day.
+    pub fn nth_business_day(&self, from: NaiveDate, n: u32) -> NaiveDate {
+        let mut date = from;
+        while !self.is_business_day(date) {
+            date = date.succ();
+        }
+        for _ in 1..n.max(1) {
+            date = self.next_business_day(date);
+        }
+
+        date
+    }
+
+    /// Return the date it is at a point in time in the calendar's time zone.
+    pub fn local_date(&self, time: DateTime<Utc>) -> NaiveDate {
+        time.with_timezone(&self.time_zone).date().naive_local()
+    }
+
+    /// Return the point in time it is at a local time on a date, in the calendar's time zone.
+    pub fn at(&self, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+        self.time_zone
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+    }
+
+    /// Return the business hours of a date, or None if it is not a business day.
+    pub fn business_hours(&self, date: NaiveDate) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if !self.is_business_day(date) {
+            return None;
+        }
+
+        Some((self.at(date, self.start)?, self.at(date, self.end)?))
+    }
+
+    /// Return if a point in time is during business hours.
+    pub fn is_business_hours(&self, time: DateTime<Utc>) -> bool {
+        match self.business_hours(self.local_date(time)) {
+            Some((start, end)) => start <= time && time < end,
+            None => false,
+        }
+    }
+
+    /// Return how much business time there is between two points in time. Reminders and
+    /// escalations use it so a weekend or a holiday does not count against anyone.
+    pub fn business_time_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+        let mut total = Duration::zero();
+        if to <= from {
+            return total;
+        }
+
+        let mut date = self.local_date(from);
+        let last = self.local_date(to);
+        while date <= last {
+            if let Some((start, end)) = self.business_hours(date) {
+                let start = start.max(from);
+                let end = end.min(to);
+                if start < end {
+                    total = total + (end - start);
+                }
+            }
+            date = date.succ();
+        }
+
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use super::{BusinessCalendarsConfig, BusinessHoursConfig};
+
+    fn mock_calendars() -> BusinessCalendarsConfig {
+        toml::from_str(
+            r#"
+holidays = ['2023-05-29']
+
+[offices.Berlin]
+time_zone = 'Europe/Berlin'
+working_days = ['Mon', 'Tue', 'Wed', 'Thu']
+start = '08:00'
+end = '16:00'
+holidays = ['2023-05-01']
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_business_days() {
+        let calendars = mock_calendars();
+        let default = calendars.default_calendar();
+        let berlin = calendars.for_office("Berlin");
+
+        // Friday before Memorial Day.
+        let friday = NaiveDate::from_ymd(2023, 5, 26);
+        assert_eq!(NaiveDate::from_ymd(2023, 5, 30), default.next_business_day(friday));
+        assert_eq!(NaiveDate::from_ymd(2023, 5, 31), default.nth_business_day(friday, 3));
+        // Berlin does not work on Fridays, nor on Memorial Day.
+        assert_eq!(NaiveDate::from_ymd(2023, 5, 30), berlin.nth_business_day(friday, 1));
+        assert!(!berlin.is_business_day(NaiveDate::from_ymd(2023, 5, 1)));
+        assert!(default.is_business_day(NaiveDate::from_ymd(2023, 5, 1)));
+
+        assert_eq!(default, calendars.for_office("Nowhere"));
+    }
+
+    #[test]
+    fn test_business_hours() {
+        let berlin = mock_calendars().for_office("Berlin");
+        let (start, end) = berlin.business_hours(NaiveDate::from_ymd(2023, 5, 2)).unwrap();
+        assert_eq!(Utc.ymd(2023, 5, 2).and_hms(6, 0, 0), start);
+        assert_eq!(Utc.ymd(2023, 5, 2).and_hms(14, 0, 0), end);
+        assert!(berlin.is_business_hours(Utc.ymd(2023, 5, 2).and_hms(13, 0, 0)));
+        assert!(!berlin.is_business_hours(Utc.ymd(2023, 5, 2).and_hms(15, 0, 0)));
+
+        // From Thursday noon to Tuesday 10am is 4 hours on Thursday, 8 on Monday, and 2 on
+        // Tuesday.
+        assert_eq!(
+            chrono::Duration::hours(14),
+            berlin.business_time_between(
+                Utc.ymd(2023, 5, 4).and_hms(10, 0, 0),
+                Utc.ymd(2023, 5, 9).and_hms(8, 0, 0)
+            )
+        );
+
+        let invalid = BusinessHoursConfig {
+            start: "17:00".to_string(),
+            end: "09:00".to_string(),
+            ..Default::default()
+        };
+        assert!(invalid.parse(&Default::default()).is_err());
+    }
+}
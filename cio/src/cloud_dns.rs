@@ -11,7 +11,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use crate::dns_providers::{DNSProviderOps, DnsRecord, DnsUpdateMode};
+use crate::dns_providers::{DNSProviderOps, DnsRecord, DnsRecordType, DnsUpdateMode};
 
 struct ZoneCache {
     zones: Vec<ManagedZone>,
@@ -397,4 +397,31 @@ impl DNSProviderOps for CloudDnsClient {
 
         Ok(())
     }
+
+    /// Get the contents of the records with a name and type.
+    async fn get_records(&self, name: &str, type_: DnsRecordType) -> Result<Vec<String>> {
+        let zone = self
+            .translate_domain_to_zone(name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("[CloudDNS] Failed to find zone for {}", name))?;
+        let zone_name = zone.name.ok_or_else(|| {
+            anyhow::anyhow!(
+                "[CloudDNS] Unable to operate on zone that does not have a name for {}",
+                name
+            )
+        })?;
+
+        let record = DnsRecord {
+            name: name.to_string(),
+            type_,
+            content: String::new(),
+        };
+
+        let mut contents = vec![];
+        for record_set in self.find_name_and_type_matches(&zone_name, &record).await? {
+            contents.extend(record_set.rrdatas.unwrap_or_default());
+        }
+
+        Ok(contents)
+    }
 }
@@ -461,6 +461,33 @@ impl DNSProviderOps for CloudFlareClient {
 
         Ok(())
     }
+
+    async fn get_records(&self, name: &str, type_: DnsRecordType) -> Result<Vec<String>> {
+        let domain = name.to_lowercase();
+        let zone_identifier = self.get_zone_identifier(&domain).await?.id;
+
+        self.with_zone(&zone_identifier, |zone| {
+            zone.get_records_for_domain(&domain)
+                .into_iter()
+                .filter_map(|record| content_of_type(&record.content, &type_))
+                .collect()
+        })
+        .await
+    }
+}
+
+/// Returns the content of a record if it has the type.
+fn content_of_type(content: &DnsContent, type_: &DnsRecordType) -> Option<String> {
+    match (content, type_) {
+        (DnsContent::A { content }, DnsRecordType::A) => Some(content.to_string()),
+        (DnsContent::AAAA { content }, DnsRecordType::AAAA) => Some(content.to_string()),
+        (DnsContent::CNAME { content }, DnsRecordType::CNAME) => Some(content.to_string()),
+        (DnsContent::NS { content }, DnsRecordType::NS) => Some(content.to_string()),
+        (DnsContent::MX { content, .. }, DnsRecordType::MX) => Some(content.to_string()),
+        (DnsContent::TXT { content }, DnsRecordType::TXT) => Some(content.to_string()),
+        (DnsContent::SRV { content }, DnsRecordType::SRV) => Some(content.to_string()),
+        _ => None,
+    }
 }
 
 /// TODO: remove this stupid function when cloudflare has PartialEq on their types...
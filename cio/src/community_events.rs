@@ -0,0 +1,659 @@
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::info;
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    airtable::{AIRTABLE_COMMUNITY_EVENTS_TABLE, AIRTABLE_EVENT_REGISTRANTS_TABLE},
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    mailing_list::{MailingListSubscriber, NewMailingListSubscriber},
+    schema::{community_events, event_registrants},
+};
+
+/// How we decide who agreed to hear from us when they registered to an event.
+#[derive(Debug, Default, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct CommunityEventsConfig {
+    /// A part of the label of the registration question asking for consent, like `hear about
+    /// future events`. Nobody consents if it is empty, so nobody is added to the mailing list.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub consent_question: String,
+}
+
+impl CommunityEventsConfig {
+    /// Return if the answers to the registration questions consent to the mailing list.
+    pub fn consents(&self, answers: &[RegistrationAnswer]) -> bool {
+        let question = self.consent_question.trim().to_lowercase();
+        if question.is_empty() {
+            return false;
+        }
+
+        answers
+            .iter()
+            .filter(|a| a.question.to_lowercase().contains(&question))
+            .any(|a| {
+                ["yes", "true", "y", "1", "checked", "accepted", "agree", "i agree"]
+                    .contains(&a.answer.trim().to_lowercase().as_str())
+            })
+    }
+}
+
+/// The answer of a registrant to a question of the registration form.
+#[derive(Debug, Default, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RegistrationAnswer {
+    pub question: String,
+    pub answer: String,
+}
+
+/// Where someone stands for an event, whatever the registration platform calls it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub enum RegistrationStatus {
+    /// Registered, but waiting for us to approve them.
+    Registered,
+    /// Has a spot at the event.
+    Confirmed,
+    Waitlisted,
+    Cancelled,
+}
+
+impl std::fmt::Display for RegistrationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RegistrationStatus::Registered => write!(f, "Registered"),
+            RegistrationStatus::Confirmed => write!(f, "Confirmed"),
+            RegistrationStatus::Waitlisted => write!(f, "Waitlisted"),
+            RegistrationStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+/// A meetup, conference booth, or any other event we run for the community.
+#[db {
+    new_struct_name = "CommunityEvent",
+    airtable_base = "customer_leads",
+    airtable_table = "AIRTABLE_COMMUNITY_EVENTS_TABLE",
+    match_on = {
+        "provider" = "String",
+        "provider_event_id" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = community_events)]
+pub struct NewCommunityEvent {
+    /// Where people register, `eventbrite` or `luma`.
+    pub provider: String,
+    pub provider_event_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub location: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a CommunityEvent.
+#[async_trait]
+impl UpdateAirtableRecord<CommunityEvent> for CommunityEvent {
+    async fn update_airtable_record(&mut self, _record: CommunityEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Someone who registered to a community event.
+#[db {
+    new_struct_name = "EventRegistrant",
+    airtable_base = "customer_leads",
+    airtable_table = "AIRTABLE_EVENT_REGISTRANTS_TABLE",
+    match_on = {
+        "provider" = "String",
+        "provider_registration_id" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = event_registrants)]
+pub struct NewEventRegistrant {
+    pub provider: String,
+    pub provider_registration_id: String,
+    pub provider_event_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event_name: String,
+    pub email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub first_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub last_name: String,
+    /// One of the registration statuses, like `Confirmed`.
+    pub status: String,
+    /// This field will be set and updated in Airtable, at the door.
+    #[serde(default)]
+    pub checked_in: bool,
+    /// If they agreed to join our mailing list when they registered.
+    #[serde(default)]
+    pub marketing_consent: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consented_at: Option<DateTime<Utc>>,
+    pub registered_at: DateTime<Utc>,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for an EventRegistrant.
+#[async_trait]
+impl UpdateAirtableRecord<EventRegistrant> for EventRegistrant {
+    async fn update_airtable_record(&mut self, record: EventRegistrant) -> Result<()> {
+        // People are checked in at the door, in Airtable.
+        self.checked_in = self.checked_in || record.checked_in;
+
+        Ok(())
+    }
+}
+
+/// A registration to an event, from one of the registration platforms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registration {
+    pub event: NewCommunityEvent,
+    pub registration_id: String,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub status: RegistrationStatus,
+    pub checked_in: bool,
+    pub registered_at: DateTime<Utc>,
+    pub answers: Vec<RegistrationAnswer>,
+}
+
+/// The notification Eventbrite sends us, we have to get what changed from its API.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct EventbriteWebhook {
+    #[serde(default)]
+    pub config: EventbriteWebhookConfig,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub api_url: String,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct EventbriteWebhookConfig {
+    /// What happened, like `order.placed` or `attendee.checked_in`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub action: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EventbriteText {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EventbriteTime {
+    #[serde(default)]
+    utc: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EventbriteVenue {
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EventbriteEvent {
+    id: String,
+    #[serde(default)]
+    name: EventbriteText,
+    #[serde(default)]
+    start: EventbriteTime,
+    #[serde(default)]
+    end: EventbriteTime,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    venue: Option<EventbriteVenue>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EventbriteProfile {
+    #[serde(default)]
+    first_name: String,
+    #[serde(default)]
+    last_name: String,
+    #[serde(default)]
+    email: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EventbriteAnswer {
+    #[serde(default)]
+    question: String,
+    #[serde(default)]
+    answer: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EventbriteAttendee {
+    id: String,
+    #[serde(default)]
+    created: Option<DateTime<Utc>>,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    cancelled: bool,
+    #[serde(default)]
+    refunded: bool,
+    #[serde(default)]
+    checked_in: bool,
+    #[serde(default)]
+    profile: EventbriteProfile,
+    #[serde(default)]
+    answers: Vec<EventbriteAnswer>,
+    #[serde(default)]
+    event: Option<EventbriteEvent>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct EventbriteOrder {
+    #[serde(default)]
+    attendees: Vec<EventbriteAttendee>,
+    #[serde(default)]
+    event: Option<EventbriteEvent>,
+}
+
+impl EventbriteAttendee {
+    fn into_registration(self, event: &EventbriteEvent, company: &Company) -> Registration {
+        let status = if self.cancelled || self.refunded || self.status.eq_ignore_ascii_case("not attending") {
+            RegistrationStatus::Cancelled
+        } else {
+            RegistrationStatus::Confirmed
+        };
+
+        Registration {
+            event: NewCommunityEvent {
+                provider: "eventbrite".to_string(),
+                provider_event_id: event.id.to_string(),
+                name: event.name.text.to_string(),
+                starts_at: event.start.utc,
+                ends_at: event.end.utc,
+                location: event.venue.as_ref().map(|v| v.name.to_string()).unwrap_or_default(),
+                url: event.url.to_string(),
+                cio_company_id: company.id,
+            },
+            registration_id: self.id,
+            email: self.profile.email,
+            first_name: self.profile.first_name,
+            last_name: self.profile.last_name,
+            status,
+            checked_in: self.checked_in || self.status.eq_ignore_ascii_case("checked in"),
+            registered_at: self.created.unwrap_or_else(Utc::now),
+            answers: self
+                .answers
+                .into_iter()
+                .map(|a| RegistrationAnswer {
+                    question: a.question,
+                    answer: a.answer,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl EventbriteWebhook {
+    /// Get the registrations the notification is about from the Eventbrite API.
+    pub async fn registrations(&self, company: &Company) -> Result<Vec<Registration>> {
+        if company.eventbrite_api_key.is_empty() {
+            bail!("no eventbrite api key");
+        }
+        // We only follow links to the API, with our token.
+        if !self.api_url.starts_with("https://www.eventbriteapi.com/") {
+            bail!("`{}` is not a link to the eventbrite api", self.api_url);
+        }
+
+        let client = reqwest::Client::new();
+        let get = |expand: &'static str| {
+            client
+                .get(&self.api_url)
+                .bearer_auth(&company.eventbrite_api_key)
+                .query(&[("expand", expand)])
+                .send()
+        };
+
+        let action = self.config.action.as_str();
+        let attendees = if action.starts_with("order.") {
+            let order: EventbriteOrder = get("attendees,event,event.venue")
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let event = order.event.unwrap_or_default();
+            order
+                .attendees
+                .into_iter()
+                .map(|a| (a, event.clone()))
+                .collect::<Vec<_>>()
+        } else if action.starts_with("attendee.") {
+            let mut attendee: EventbriteAttendee = get("event,event.venue").await?.error_for_status()?.json().await?;
+            let event = attendee.event.take().unwrap_or_default();
+            vec![(attendee, event)]
+        } else {
+            info!("ignoring eventbrite webhook `{}`", action);
+            return Ok(vec![]);
+        };
+
+        Ok(attendees
+            .into_iter()
+            .map(|(attendee, event)| attendee.into_registration(&event, company))
+            .collect())
+    }
+}
+
+/// A webhook from Luma, it has the guest in it.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct LumaWebhook {
+    /// What happened, like `guest.registered` or `guest.updated`.
+    #[serde(default, rename = "type", skip_serializing_if = "String::is_empty")]
+    pub event_type: String,
+    #[serde(default)]
+    pub data: LumaGuest,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct LumaGuest {
+    #[serde(default, alias = "id")]
+    pub api_id: String,
+    #[serde(default, alias = "email")]
+    pub user_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user_first_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user_last_name: String,
+    /// `approved`, `pending_approval`, `waitlist`, `declined`, or `invited`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub approval_status: String,
+    #[serde(default)]
+    pub registered_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub checked_in_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registration_answers: Vec<LumaAnswer>,
+    #[serde(default)]
+    pub event: LumaEvent,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct LumaAnswer {
+    #[serde(default, alias = "question")]
+    pub label: String,
+    #[serde(default)]
+    pub answer: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct LumaEvent {
+    #[serde(default, alias = "id")]
+    pub api_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default)]
+    pub start_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    #[serde(default)]
+    pub geo_address_json: Option<LumaAddress>,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct LumaAddress {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub full_address: String,
+}
+
+impl LumaWebhook {
+    pub fn registration(self, company: &Company) -> Registration {
+        let guest = self.data;
+        let status = match guest.approval_status.as_str() {
+            "approved" => RegistrationStatus::Confirmed,
+            "waitlist" => RegistrationStatus::Waitlisted,
+            "declined" => RegistrationStatus::Cancelled,
+            _ => RegistrationStatus::Registered,
+        };
+
+        Registration {
+            event: NewCommunityEvent {
+                provider: "luma".to_string(),
+                provider_event_id: guest.event.api_id,
+                name: guest.event.name,
+                starts_at: guest.event.start_at,
+                ends_at: guest.event.end_at,
+                location: guest.event.geo_address_json.map(|a| a.full_address).unwrap_or_default(),
+                url: guest.event.url,
+                cio_company_id: company.id,
+            },
+            registration_id: guest.api_id,
+            email: guest.user_email,
+            first_name: guest.user_first_name,
+            last_name: guest.user_last_name,
+            status,
+            checked_in: guest.checked_in_at.is_some(),
+            registered_at: guest.registered_at.unwrap_or_else(Utc::now),
+            answers: guest
+                .registration_answers
+                .into_iter()
+                .map(|a| RegistrationAnswer {
+                    question: a.label,
+                    answer: match a.answer {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Save a registration and its event, and sync them to Airtable for check-in. Confirmed
+/// registrants who consented are added to the mailing list, tagged with the event.
+pub async fn record_registration(
+    db: &Database,
+    company: &Company,
+    config: &CommunityEventsConfig,
+    registration: Registration,
+) -> Result<EventRegistrant> {
+    if registration.email.trim().is_empty() {
+        bail!("registration `{}` has no email", registration.registration_id);
+    }
+
+    let event = registration.event.upsert(db).await?;
+
+    let existing =
+        EventRegistrant::get_from_db(db, event.provider.to_string(), registration.registration_id.to_string()).await;
+    // Consent is only given once, people who change their answers later are not opted in.
+    let existing_consent = existing.as_ref().and_then(|r| r.consented_at);
+    let existing_status = existing.as_ref().map(|r| r.status.to_string()).unwrap_or_default();
+    let consented_at = existing_consent.or_else(|| config.consents(&registration.answers).then(Utc::now));
+
+    let registrant = NewEventRegistrant {
+        provider: event.provider.to_string(),
+        provider_registration_id: registration.registration_id.to_string(),
+        provider_event_id: event.provider_event_id.to_string(),
+        event_name: event.name.to_string(),
+        email: registration.email.trim().to_lowercase(),
+        first_name: registration.first_name.trim().to_string(),
+        last_name: registration.last_name.trim().to_string(),
+        status: registration.status.to_string(),
+        checked_in: registration.checked_in || existing.map(|r| r.checked_in).unwrap_or_default(),
+        marketing_consent: consented_at.is_some(),
+        consented_at,
+        registered_at: registration.registered_at,
+        cio_company_id: company.id,
+    }
+    .upsert(db)
+    .await?;
+    info!(
+        "recorded {} registration of `{}` to `{}`",
+        registrant.provider, registrant.email, registrant.event_name
+    );
+
+    // Only add them the first time they are confirmed with their consent, the later webhooks
+    // for the same registration, like a check in, should not undo an unsubscribe.
+    let was_added = existing_consent.is_some() && existing_status == RegistrationStatus::Confirmed.to_string();
+    if registration.status == RegistrationStatus::Confirmed && !was_added {
+        add_registrant_to_mailing_list(db, &registrant).await?;
+    }
+
+    Ok(registrant)
+}
+
+/// The tag of the mailing list segment of the attendees of an event.
+pub fn event_tag(event_name: &str) -> String {
+    format!("event: {}", event_name.trim())
+}
+
+/// Add a confirmed registrant to the mailing list segment of the event, if they consented.
+/// Subscribers who unsubscribed stay unsubscribed.
+async fn add_registrant_to_mailing_list(db: &Database, registrant: &EventRegistrant) -> Result<()> {
+    let consented_at = match registrant.consented_at {
+        Some(consented_at) => consented_at,
+        // Return early, we do not add anyone who did not say we could.
+        None => return Ok(()),
+    };
+
+    let tag = event_tag(&registrant.event_name);
+    let mut subscriber: NewMailingListSubscriber =
+        match MailingListSubscriber::get_from_db(db, registrant.email.to_string()).await {
+            Some(existing) => {
+                if !existing.wants_newsletter || existing.tags.contains(&tag) {
+                    // Return early, they unsubscribed or they are already in the segment.
+                    return Ok(());
+                }
+                existing.into()
+            }
+            None => NewMailingListSubscriber {
+                email: registrant.email.to_string(),
+                first_name: registrant.first_name.to_string(),
+                last_name: registrant.last_name.to_string(),
+                name: format!("{} {}", registrant.first_name, registrant.last_name)
+                    .trim()
+                    .to_string(),
+                source: format!("{} registration", registrant.provider),
+                date_added: consented_at,
+                date_optin: consented_at,
+                wants_newsletter: true,
+                cio_company_id: registrant.cio_company_id,
+                ..Default::default()
+            },
+        };
+
+    subscriber.date_last_changed = Utc::now();
+    if !subscriber.tags.contains(&tag) {
+        subscriber.tags.push(tag);
+    }
+    subscriber.upsert(db).await?;
+
+    Ok(())
+}
+
+/// Sync the registrants from Airtable, so we know who was checked in at the door.
+pub async fn refresh_event_registrants(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_customer_leads.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    // Get all the records from Airtable.
+    let results: Vec<airtable_api::Record<EventRegistrant>> = company
+        .authenticate_airtable(&company.airtable_base_id_customer_leads)
+        .list_records(&EventRegistrant::airtable_table(), "Grid view", vec![])
+        .await?;
+    for record in results {
+        if !record.fields.checked_in {
+            continue;
+        }
+
+        if let Some(mut registrant) = EventRegistrant::get_from_db(
+            db,
+            record.fields.provider.to_string(),
+            record.fields.provider_registration_id.to_string(),
+        )
+        .await
+        {
+            if registrant.checked_in {
+                continue;
+            }
+
+            registrant.checked_in = true;
+            registrant.airtable_record_id = record.id.to_string();
+            registrant.update(db).await?;
+            info!("`{}` checked in to `{}`", registrant.email, registrant.event_name);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommunityEventsConfig, LumaWebhook, RegistrationAnswer, RegistrationStatus};
+    use crate::companies::tests::mock_company;
+
+    #[test]
+    fn test_consents() {
+        let answers = vec![
+            RegistrationAnswer {
+                question: "Company".to_string(),
+                answer: "yes".to_string(),
+            },
+            RegistrationAnswer {
+                question: "Can we email you about future events?".to_string(),
+                answer: "Yes".to_string(),
+            },
+        ];
+
+        let config = CommunityEventsConfig {
+            consent_question: "future events".to_string(),
+        };
+        assert!(config.consents(&answers));
+        assert!(!config.consents(&answers[..1]));
+
+        // Nobody consents if we do not ask.
+        assert!(!CommunityEventsConfig::default().consents(&answers));
+    }
+
+    #[test]
+    fn test_luma_registration() {
+        let webhook: LumaWebhook = serde_json::from_str(
+            r#"{
+                "type": "guest.updated",
+                "data": {
+                    "api_id": "gst-1",
+                    "user_email": "jess@example.com",
+                    "user_first_name": "Jess",
+                    "approval_status": "waitlist",
+                    "registration_answers": [{"label": "Can we email you about future events?", "answer": true}],
+                    "event": {"api_id": "evt-1", "name": "Rust Meetup", "geo_address_json": {"full_address": "1 Main St"}}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let registration = webhook.registration(&mock_company());
+        assert_eq!(RegistrationStatus::Waitlisted, registration.status);
+        assert_eq!("evt-1", registration.event.provider_event_id);
+        assert_eq!("1 Main St", registration.event.location);
+        assert_eq!("true", registration.answers[0].answer);
+        assert!(!registration.checked_in);
+    }
+}
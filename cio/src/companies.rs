@@ -5,7 +5,7 @@ use anyhow::{anyhow, bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
 use checkr::Checkr;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use cloudflare::framework::{
     async_api::Client as Cloudflare, auth::Credentials as CloudflareCredentials, Environment, HttpApiClientConfig,
 };
@@ -54,7 +54,12 @@ use crate::{
     core::UpdateAirtableRecord,
     db::Database,
     dns_proxy::DnsProviderProxy,
+    greenhouse::Greenhouse,
+    lever::Lever,
+    printful::Printful,
+    rate_limit::{rate_limited_client, Provider},
     schema::{api_tokens, companys},
+    workload_identity::{get_keyless_service_account_token, GOOGLE_CLOUD_PLATFORM_SCOPE},
 };
 
 #[db {
@@ -96,12 +101,19 @@ pub struct NewCompany {
     pub cloudflare_api_key: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub checkr_api_key: String,
+    /// The print server we print on if the company did not set up its printers.
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub printer_url: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub tailscale_api_key: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub shipbob_pat: String,
+    /// The private token of the Printful store that fulfills our swag.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub printful_api_key: String,
+    /// The private token we get the details of registrations to our events with.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub eventbrite_api_key: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub tripactions_client_id: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -149,13 +161,100 @@ pub struct NewCompany {
     pub slack_channel_finance: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub slack_channel_debug: String,
+    /// The channel we alert about operational issues, like failing syncs, printers or storage
+    /// running out. We do not alert if empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub slack_channel_alerts: String,
+    /// The channel we alert about security issues, like infected uploads.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub slack_channel_security: String,
+    /// The channel we alert when swag runs low, the swag channel if empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub slack_channel_low_stock: String,
+    /// The channel we alert about the offices, like printers running out of labels, the alerts
+    /// channel if empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub slack_channel_office: String,
+    /// The channel we announce the RFDs changing state in, the alerts channel if empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub slack_channel_rfds: String,
 
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub google_service_account: String,
+    /// The email of the Google service account we act as without its key, with the identity
+    /// of the workload we run as. The key in `google_service_account` is only used if this is
+    /// empty, or if getting a token this way fails.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub google_service_account_email: String,
 
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub nginx_ip: String,
 
+    /// The provider we use to transcribe recorded meetings, either `revai` or `whisper`.
+    /// Defaults to `revai` if empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub transcription_provider: String,
+    /// The url of our self-hosted Whisper endpoint, if the transcription provider is `whisper`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub whisper_url: String,
+    /// How we render the PDFs of RFDs, either `asciidoctor` or `chromium`. Defaults to
+    /// `asciidoctor` if empty or unknown.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub rfd_pdf_renderer: String,
+    /// The number of days an RFD can sit in discussion before we remind its reviewers about
+    /// it. Defaults to 7 days if zero.
+    #[serde(default)]
+    pub rfd_review_reminder_days: i32,
+
+    /// The number of days we keep the video and transcript of recorded meetings for, unless
+    /// the meeting is tagged "keep". Zero means we keep them forever.
+    #[serde(default)]
+    pub recorded_meeting_retention_days: i32,
+    /// The number of days after the hire or no-hire decision on an applicant we keep the
+    /// recordings and transcripts of their interviews for, unless the meeting is tagged
+    /// "legal hold". Zero means we keep them like the other meetings.
+    #[serde(default)]
+    pub interview_recording_retention_days: i32,
+
+    /// The number of days of Zoom cloud recordings we look at when syncing. Defaults to 30
+    /// days if zero.
+    #[serde(default)]
+    pub zoom_sync_lookback_days: i32,
+    /// The start time of the newest Zoom recording we synced, so the next sync only looks at
+    /// newer recordings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zoom_sync_cursor: Option<DateTime<Utc>>,
+    /// The cloud recording storage of the Zoom plan, in gigabytes, for when Zoom does not tell
+    /// us the storage of the plan. We alert when the recordings get close to it.
+    #[serde(default)]
+    pub zoom_cloud_storage_limit_gb: i32,
+    /// The id of the Zoom account of the company, the webhooks of Zoom are for the company
+    /// of the account they are sent for.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub zoom_account_id: String,
+
+    /// The symbology of the barcodes on new asset labels, `code39` or `code128`. Defaults to
+    /// `code39` if empty. Items that already have a label keep its symbology.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub barcode_symbology: String,
+    /// The layout of the barcode labels we print, as the JSON of a `LabelTemplate`. Defaults
+    /// to the 3"x2" label with the Oxide logo if empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label_template: String,
+
+    /// The key of the Greenhouse Harvest API, we sync the candidates of Greenhouse with our
+    /// applicants if it is set.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub greenhouse_api_key: String,
+    /// The id of the Greenhouse user we push the changes to the applicants as. We only pull
+    /// from Greenhouse if it is empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub greenhouse_user_id: String,
+    /// The key of the Lever API, we mirror the opportunities of Lever into our applicants if
+    /// it is set.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub lever_api_key: String,
+
     /// The CIO company ID.
     #[serde(default)]
     pub cio_company_id: i32,
@@ -197,6 +296,16 @@ impl Company {
         })
     }
 
+    /// Returns the channel to alert in: the given channel if the company set it, and the
+    /// alerts channel if not. The debug channel is not for alerts, so if the company set
+    /// neither we do not alert.
+    pub fn alerts_channel(&self, channel: &str) -> Option<String> {
+        [channel, self.slack_channel_alerts.as_str()]
+            .iter()
+            .find(|c| !c.trim().is_empty())
+            .map(|c| c.trim().to_string())
+    }
+
     pub async fn post_to_slack_channel(&self, db: &Database, msg: &slack_chat_api::FormattedMessage) -> Result<()> {
         // Create the Slack client.
         let r = self.authenticate_slack(db).await;
@@ -224,6 +333,15 @@ impl Company {
         Ok(())
     }
 
+    /// Get a company by its id. The id of a company is what the other records are scoped by,
+    /// so unlike theirs it can be looked up on its own.
+    pub async fn get_from_id(db: &Database, id: i32) -> Result<Self> {
+        Ok(companys::dsl::companys
+            .filter(companys::dsl::id.eq(id))
+            .first_async::<Company>(db.pool())
+            .await?)
+    }
+
     pub async fn get_from_slack_team_id(db: &Database, team_id: &str) -> Result<Self> {
         // We need to get the token first with the matching team id.
         let token = api_tokens::dsl::api_tokens
@@ -236,7 +354,7 @@ impl Company {
             .await?;
 
         // Now we can get the company.
-        Company::get_by_id(db, token.auth_company_id).await
+        Company::get_from_id(db, token.auth_company_id).await
     }
 
     pub async fn get_from_github_org(db: &Database, org: &str) -> Result<Self> {
@@ -260,7 +378,14 @@ impl Company {
             .first_async::<APIToken>(db.pool())
             .await?;
 
-        Company::get_by_id(db, token.auth_company_id).await
+        Company::get_from_id(db, token.auth_company_id).await
+    }
+
+    pub async fn get_from_zoom_account_id(db: &Database, account_id: &str) -> Result<Self> {
+        Ok(companys::dsl::companys
+            .filter(companys::dsl::zoom_account_id.eq(account_id.to_string()))
+            .first_async::<Company>(db.pool())
+            .await?)
     }
 
     pub async fn get_from_mailchimp_list_id(db: &Database, list_id: &str) -> Result<Self> {
@@ -329,7 +454,7 @@ impl Company {
         Some(Okta::new(&self.okta_api_key).with_host(self.okta_endpoint()))
     }
 
-    fn okta_endpoint(&self) -> String {
+    pub fn okta_endpoint(&self) -> String {
         format!(
             "https://{}.okta.com",
             self.okta_domain
@@ -341,9 +466,25 @@ impl Company {
         )
     }
 
-    /// Authenticate with Airtable.
+    /// Authenticate with Airtable. Every request the client sends takes its turn in the
+    /// Airtable budget all our jobs share, unless we fail to create the rate limited client.
     pub fn authenticate_airtable(&self, base_id: &str) -> Airtable {
-        Airtable::new(&self.airtable_api_key, base_id, &self.airtable_enterprise_account_id)
+        match rate_limited_client(Provider::Airtable) {
+            Ok(client) => Airtable::new_with_client(
+                &self.airtable_api_key,
+                base_id,
+                &self.airtable_enterprise_account_id,
+                client,
+            ),
+            Err(e) => {
+                // A job without the budget is better than a job that does not run.
+                warn!(
+                    "creating the rate limited airtable client failed, not rate limiting it: {}",
+                    e
+                );
+                Airtable::new(&self.airtable_api_key, base_id, &self.airtable_enterprise_account_id)
+            }
+        }
     }
 
     /// Authenticate with ShipBob.
@@ -355,6 +496,15 @@ impl Company {
         Ok(ShipBob::new(&self.shipbob_pat))
     }
 
+    /// Authenticate with Printful.
+    pub fn authenticate_printful(&self) -> Result<Printful> {
+        if self.printful_api_key.is_empty() {
+            bail!("no printful api key");
+        }
+
+        Ok(Printful::new(&self.printful_api_key))
+    }
+
     /// Ensure the company has ShipBob webhooks setup.
     pub async fn ensure_shipbob_webhooks(&self) -> Result<()> {
         let shipbob_auth = self.authenticate_shipbob().await;
@@ -634,6 +784,16 @@ impl Company {
         Tailscale::new(&self.tailscale_api_key, &self.gsuite_domain)
     }
 
+    /// Authenticate with Greenhouse.
+    pub fn authenticate_greenhouse(&self) -> Greenhouse {
+        Greenhouse::new(&self.greenhouse_api_key, &self.greenhouse_user_id)
+    }
+
+    /// Authenticate with Lever.
+    pub fn authenticate_lever(&self) -> Lever {
+        Lever::new(&self.lever_api_key)
+    }
+
     /// Authenticate with TripActions.
     pub async fn authenticate_tripactions(&self, db: &Database) -> Result<TripActions> {
         if self.tripactions_client_id.is_empty() || self.tripactions_client_secret.is_empty() {
@@ -860,6 +1020,18 @@ impl Company {
         bail!("no token");
     }
 
+    /// Get an access token for the Google APIs our clients do not cover, like ranged
+    /// downloads of Drive files.
+    pub async fn google_access_token(&self, db: &Database) -> Result<String> {
+        // Authenticating refreshes the token if it expired.
+        self.authenticate_google_drive(db).await?;
+
+        match APIToken::get_from_db(db, self.id, "google".to_string()).await {
+            Some(t) => Ok(t.access_token),
+            None => bail!("no token"),
+        }
+    }
+
     /// Authenticate Google Drive with Service Account.
     /// This allows mocking as another user.
     /// TODO: figure out why we can't mock with the standard token.
@@ -871,7 +1043,39 @@ impl Company {
     }
 
     async fn get_google_service_account_token(&self, as_user: &str) -> Result<String> {
-        if self.google_service_account.is_empty() {
+        self.get_google_service_account_token_with_scopes(
+            as_user,
+            &[
+                "https://www.googleapis.com/auth/admin.directory.group",
+                "https://www.googleapis.com/auth/admin.directory.resource.calendar",
+                "https://www.googleapis.com/auth/admin.directory.user",
+                "https://www.googleapis.com/auth/calendar",
+                "https://www.googleapis.com/auth/apps.groups.settings",
+                "https://www.googleapis.com/auth/spreadsheets",
+                "https://www.googleapis.com/auth/drive",
+            ],
+        )
+        .await
+    }
+
+    /// Get a token to read the audit reports of the domain, like the sign-in logs. It has its
+    /// own scope so companies that did not grant it to the service account can still use the
+    /// rest of it.
+    pub async fn google_reports_token(&self) -> Result<String> {
+        self.get_google_service_account_token_with_scopes(
+            "",
+            &["https://www.googleapis.com/auth/admin.reports.audit.readonly"],
+        )
+        .await
+    }
+
+    /// If the company has a Google service account, with its key or only its email.
+    pub fn has_google_service_account(&self) -> bool {
+        !self.google_service_account.is_empty() || !self.google_service_account_email.is_empty()
+    }
+
+    async fn get_google_service_account_token_with_scopes(&self, as_user: &str, scopes: &[&str]) -> Result<String> {
+        if !self.has_google_service_account() {
             bail!("no service account");
         }
 
@@ -881,23 +1085,26 @@ impl Company {
             as_user.to_string()
         };
 
+        if !self.google_service_account_email.is_empty() {
+            match self.get_keyless_google_service_account_token(&subject, scopes).await {
+                Ok(token) => return Ok(token),
+                Err(e) if !self.google_service_account.is_empty() => {
+                    warn!(
+                        "getting a token for service account `{}` without its key failed, using its key: {}",
+                        self.google_service_account_email, e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
         let client_secret = yup_oauth2::parse_service_account_key(&self.google_service_account)?;
         let auth = yup_oauth2::ServiceAccountAuthenticator::builder(client_secret)
             .subject(subject)
             .build()
             .await?;
 
-        let token = auth
-            .token(&[
-                "https://www.googleapis.com/auth/admin.directory.group",
-                "https://www.googleapis.com/auth/admin.directory.resource.calendar",
-                "https://www.googleapis.com/auth/admin.directory.user",
-                "https://www.googleapis.com/auth/calendar",
-                "https://www.googleapis.com/auth/apps.groups.settings",
-                "https://www.googleapis.com/auth/spreadsheets",
-                "https://www.googleapis.com/auth/drive",
-            ])
-            .await?;
+        let token = auth.token(scopes).await?;
 
         let token_string = token.as_str().to_string();
         if token_string.is_empty() {
@@ -907,6 +1114,24 @@ impl Company {
         Ok(token_string)
     }
 
+    /// Get a token of the service account with the identity of the workload we run as, so we do
+    /// not need a long-lived key of the service account.
+    async fn get_keyless_google_service_account_token(&self, subject: &str, scopes: &[&str]) -> Result<String> {
+        let workload_token = self
+            .authenticate_gcp()
+            .await?
+            .token(&[GOOGLE_CLOUD_PLATFORM_SCOPE])
+            .await?;
+
+        get_keyless_service_account_token(
+            workload_token.as_str(),
+            &self.google_service_account_email,
+            subject,
+            scopes,
+        )
+        .await
+    }
+
     /// Authenticate Google Sheets.
     pub async fn authenticate_google_sheets(&self, db: &Database) -> Result<GoogleSheets> {
         // Get the APIToken from the database.
@@ -1019,6 +1244,32 @@ impl Company {
         ))
     }
 
+    /// Get a token for the GitHub App installation of the company, for the GitHub APIs octorust
+    /// does not cover, like GraphQL.
+    pub async fn github_installation_token(&self) -> Result<String> {
+        let app_id = env::var("GH_APP_ID")?.parse::<u64>()?;
+        let private_key = base64::decode(env::var("GH_PRIVATE_KEY")?)?;
+        let key = match nom_pem::decode_block(&private_key) {
+            Ok(k) => k,
+            Err(e) => bail!("nom_pem decode_block failed: {:?}", e),
+        };
+
+        // Only the app itself can create installation tokens.
+        let github = octorust::Client::custom(
+            "https://api.github.com",
+            concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")),
+            Credentials::JWT(JWTCredentials::new(app_id, key.data)?),
+            reqwest_middleware::ClientBuilder::new(reqwest::Client::builder().build()?).build(),
+            Box::new(FileBasedCache::new("/tmp/.cache/github")),
+        );
+        let token = github
+            .apps()
+            .create_installation_access_token(self.github_app_installation_id.into(), &Default::default())
+            .await?;
+
+        Ok(token.token)
+    }
+
     // Authenticate with GCP using the instances assigned permissions
     pub async fn authenticate_gcp(&self) -> Result<Authenticator<HttpsConnector<HttpConnector>>> {
         let opts = yup_oauth2::ApplicationDefaultCredentialsFlowOpts::default();
@@ -1144,7 +1395,12 @@ pub async fn refresh_companies(db: &Database) -> Result<()> {
             continue;
         }
 
-        let new_company: NewCompany = record.fields.into();
+        let mut new_company: NewCompany = record.fields.into();
+        // We move the Zoom sync cursor forward in the database as we sync, what is in Airtable
+        // is stale.
+        if let Some(existing) = Company::get_from_db(db, new_company.name.to_string()).await {
+            new_company.zoom_sync_cursor = existing.zoom_sync_cursor;
+        }
 
         let mut company = new_company.upsert_in_db(db).await?;
         if company.airtable_record_id.is_empty() {
@@ -1239,6 +1495,8 @@ pub mod tests {
             printer_url: String::default(),
             tailscale_api_key: String::default(),
             shipbob_pat: String::default(),
+            printful_api_key: String::default(),
+            eventbrite_api_key: String::default(),
             tripactions_client_id: String::default(),
             tripactions_client_secret: String::default(),
             airtable_api_key: String::default(),
@@ -1262,8 +1520,29 @@ pub mod tests {
             slack_channel_mailing_lists: String::default(),
             slack_channel_finance: String::default(),
             slack_channel_debug: String::default(),
+            slack_channel_alerts: String::default(),
+            slack_channel_security: String::default(),
+            slack_channel_low_stock: String::default(),
+            slack_channel_office: String::default(),
+            slack_channel_rfds: String::default(),
             google_service_account: String::default(),
+            google_service_account_email: String::default(),
             nginx_ip: String::default(),
+            transcription_provider: String::default(),
+            whisper_url: String::default(),
+            rfd_pdf_renderer: String::default(),
+            rfd_review_reminder_days: 0,
+            recorded_meeting_retention_days: 0,
+            interview_recording_retention_days: 0,
+            zoom_sync_lookback_days: 0,
+            zoom_sync_cursor: None,
+            zoom_cloud_storage_limit_gb: 0,
+            zoom_account_id: String::default(),
+            barcode_symbology: String::default(),
+            label_template: String::default(),
+            greenhouse_api_key: String::default(),
+            greenhouse_user_id: String::default(),
+            lever_api_key: String::default(),
             cio_company_id: 0,
             airtable_record_id: String::default(),
         }
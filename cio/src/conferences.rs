@@ -0,0 +1,878 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    airtable::{AIRTABLE_CONFERENCES_TABLE, AIRTABLE_CONFERENCE_LEADS_TABLE, AIRTABLE_CONFERENCE_TASKS_TABLE},
+    companies::Company,
+    configs::{User, Users},
+    core::UpdateAirtableRecord,
+    db::Database,
+    notifications::NOTIFICATION_CONFERENCE_TASKS,
+    recorded_meetings::find_user_by_email,
+    schema::{conference_leads, conference_tasks, conferences},
+    swag_bundles::{create_shipment_from_bundle, SwagBundleRecipient},
+};
+
+/// The tasks we plan for the conferences we sponsor, on a timeline from their start.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ConferencesConfig {
+    pub tasks: Vec<ConferenceTaskTemplate>,
+    /// How many days before a conference starts we ship its swag bundles.
+    pub swag_lead_days: i64,
+}
+
+impl Default for ConferencesConfig {
+    fn default() -> Self {
+        ConferencesConfig {
+            tasks: vec![],
+            swag_lead_days: 21,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ConferenceTaskTemplate {
+    /// What to do, it can contain `{conference}`.
+    pub title: String,
+    /// How many days before the conference starts the task is due, negative for after.
+    pub days_before: i64,
+    /// Who does the task, by email. Empty for anyone.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub assignee: String,
+    /// Make the task once for each member of the booth staff, like booking their travel,
+    /// instead of once.
+    #[serde(default)]
+    pub for_each_booth_staff: bool,
+}
+
+/// A conference we sponsor, and what we bring to its booth.
+#[db {
+    new_struct_name = "Conference",
+    airtable_base = "customer_leads",
+    airtable_table = "AIRTABLE_CONFERENCES_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "name" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = conferences)]
+pub struct NewConference {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starts_on: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_on: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub location: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sponsorship_level: String,
+    /// The usernames of the users working the booth.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub booth_staff: Vec<String>,
+    /// The names of the swag bundles we ship to the booth.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub swag_bundles: Vec<String>,
+    /// Where the venue takes the packages for the booths.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ship_to_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ship_to_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ship_to_phone: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ship_to_street_1: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ship_to_street_2: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ship_to_city: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ship_to_state: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ship_to_zipcode: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ship_to_country: String,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a Conference.
+#[async_trait]
+impl UpdateAirtableRecord<Conference> for Conference {
+    async fn update_airtable_record(&mut self, _record: Conference) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Conference {
+    /// Who to send the swag bundles of the conference to, if we know where.
+    pub fn swag_recipient(&self) -> Option<SwagBundleRecipient> {
+        if self.ship_to_street_1.trim().is_empty() {
+            return None;
+        }
+
+        Some(SwagBundleRecipient {
+            name: self.ship_to_name.to_string(),
+            email: self.ship_to_email.to_string(),
+            phone: self.ship_to_phone.to_string(),
+            street_1: self.ship_to_street_1.to_string(),
+            street_2: self.ship_to_street_2.to_string(),
+            city: self.ship_to_city.to_string(),
+            state: self.ship_to_state.to_string(),
+            zipcode: self.ship_to_zipcode.to_string(),
+            country: self.ship_to_country.to_string(),
+            notes: format!("For our booth at {}.", self.name),
+        })
+    }
+
+    /// The users working the booth. Booth staff can be by username or by email.
+    pub fn booth_staff(&self, users: &[User]) -> Vec<User> {
+        let mut staff = Vec::new();
+        for member in &self.booth_staff {
+            let member = member.trim();
+            match users
+                .iter()
+                .find(|u| u.username == member || u.email.eq_ignore_ascii_case(member))
+            {
+                Some(user) => staff.push(user.clone()),
+                None => warn!("booth staff `{}` of conference `{}` is not a user", member, self.name),
+            }
+        }
+
+        staff
+    }
+}
+
+/// Get the conference of an Airtable record. We follow conferences by their record, so one
+/// renamed in Airtable keeps its tasks.
+async fn get_conference_for_record(db: &Database, company: &Company, record_id: &str) -> Result<Option<Conference>> {
+    Ok(conferences::dsl::conferences
+        .filter(conferences::dsl::cio_company_id.eq(company.id))
+        .filter(conferences::dsl::airtable_record_id.eq(record_id.to_string()))
+        .load_async::<Conference>(db.pool())
+        .await?
+        .into_iter()
+        .next())
+}
+
+/// Sync the conferences from Airtable.
+pub async fn refresh_conferences(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_customer_leads.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    // Get all the records from Airtable.
+    let results: Vec<airtable_api::Record<Conference>> = company
+        .authenticate_airtable(&company.airtable_base_id_customer_leads)
+        .list_records(&Conference::airtable_table(), "Grid view", vec![])
+        .await?;
+    for conference_record in results {
+        let mut conference: NewConference = conference_record.fields.into();
+        conference.cio_company_id = company.id;
+
+        match get_conference_for_record(db, company, &conference_record.id).await? {
+            Some(existing) => {
+                diesel::update(conferences::dsl::conferences)
+                    .filter(conferences::dsl::id.eq(existing.id))
+                    .set(&conference)
+                    .execute_async(db.pool())
+                    .await?;
+            }
+            None => {
+                let mut db_conference = conference.upsert_in_db(db).await?;
+                db_conference.airtable_record_id = conference_record.id.to_string();
+                db_conference.update(db).await?;
+            }
+        }
+    }
+
+    Conferences::get_from_db(db, company.id)
+        .await?
+        .update_airtable(db)
+        .await?;
+
+    Ok(())
+}
+
+/// A task of the timeline of a conference. The tasks that ship a swag bundle are done by us,
+/// once they are due, people mark the others done in Airtable.
+#[db {
+    new_struct_name = "ConferenceTask",
+    airtable_base = "customer_leads",
+    airtable_table = "AIRTABLE_CONFERENCE_TASKS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "conference_id" = "i32",
+        "key" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = conference_tasks)]
+pub struct NewConferenceTask {
+    pub conference_id: i32,
+    /// The name of the conference.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub conference: String,
+    /// What the task is for, it does not change when the conference is renamed.
+    pub key: String,
+    pub title: String,
+    /// The email of who does the task, empty for anyone.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub assignee: String,
+    pub due_on: NaiveDate,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub swag_bundle: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outbound_shipment_id: Option<i32>,
+    /// This field is filled in by the Airtable for the tasks we do not do ourselves.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// When we told the assignee about the task.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notified_at: Option<DateTime<Utc>>,
+    /// Why we could not ship the swag bundle of the task, the last time we tried.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a ConferenceTask.
+#[async_trait]
+impl UpdateAirtableRecord<ConferenceTask> for ConferenceTask {
+    async fn update_airtable_record(&mut self, record: ConferenceTask) -> Result<()> {
+        if self.completed_at.is_none() {
+            self.completed_at = record.completed_at;
+        }
+
+        Ok(())
+    }
+}
+
+/// A task the timeline of a conference has.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedConferenceTask {
+    pub key: String,
+    pub title: String,
+    pub assignee: String,
+    pub due_on: NaiveDate,
+    pub swag_bundle: String,
+}
+
+/// The tasks of the timeline of a conference, from the templates and its swag bundles. It has
+/// none until we know when it starts.
+pub fn plan_conference_tasks(
+    config: &ConferencesConfig,
+    conference: &Conference,
+    staff: &[User],
+) -> Vec<PlannedConferenceTask> {
+    let starts_on = match conference.starts_on {
+        Some(starts_on) => starts_on,
+        None => return vec![],
+    };
+
+    let mut tasks = Vec::new();
+    for template in &config.tasks {
+        let title = template.title.replace("{conference}", &conference.name);
+        let due_on = starts_on - Duration::days(template.days_before);
+        let assignees: Vec<String> = if template.for_each_booth_staff {
+            staff.iter().map(|u| u.email.to_string()).collect()
+        } else {
+            vec![template.assignee.to_string()]
+        };
+
+        for assignee in assignees {
+            tasks.push(PlannedConferenceTask {
+                key: format!("{}:{}", template.title, assignee),
+                title: title.to_string(),
+                assignee,
+                due_on,
+                swag_bundle: String::new(),
+            });
+        }
+    }
+
+    for bundle in &conference.swag_bundles {
+        tasks.push(PlannedConferenceTask {
+            key: format!("swag:{}", bundle.trim()),
+            title: format!("Ship the {} swag bundle to {}", bundle.trim(), conference.name),
+            assignee: String::new(),
+            due_on: starts_on - Duration::days(config.swag_lead_days),
+            swag_bundle: bundle.trim().to_string(),
+        });
+    }
+
+    tasks.sort_by(|a, b| a.due_on.cmp(&b.due_on));
+    tasks
+}
+
+/// Get the tasks of a conference, by when they are due.
+pub async fn get_conference_tasks(db: &Database, conference: &Conference) -> Result<Vec<ConferenceTask>> {
+    Ok(conference_tasks::dsl::conference_tasks
+        .filter(conference_tasks::dsl::cio_company_id.eq(conference.cio_company_id))
+        .filter(conference_tasks::dsl::conference_id.eq(conference.id))
+        .order_by(conference_tasks::dsl::due_on.asc())
+        .load_async::<ConferenceTask>(db.pool())
+        .await?)
+}
+
+/// Get when people marked the tasks done in Airtable, so we stop telling anyone about them.
+async fn refresh_conference_tasks_from_airtable(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_customer_leads.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let records: Vec<airtable_api::Record<ConferenceTask>> = company
+        .authenticate_airtable(&company.airtable_base_id_customer_leads)
+        .list_records(&ConferenceTask::airtable_table(), "Grid view", vec![])
+        .await?;
+    for record in records {
+        if record.fields.completed_at.is_none() {
+            continue;
+        }
+
+        if let Some(mut task) = ConferenceTask::get_from_db(
+            db,
+            company.id,
+            record.fields.conference_id,
+            record.fields.key.to_string(),
+        )
+        .await
+        {
+            if task.completed_at.is_none() {
+                task.completed_at = record.fields.completed_at;
+                task.update_in_db(db).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Create the tasks of the conferences that are not over, and move the ones that are not done
+/// if the conference moved. Then ship the swag bundles that are due, tell the assignees about
+/// their new tasks and update the tasks in Airtable.
+pub async fn refresh_conference_tasks(db: &Database, company: &Company, config: &ConferencesConfig) -> Result<()> {
+    refresh_conference_tasks_from_airtable(db, company).await?;
+
+    let today = Utc::now().date().naive_utc();
+    let users = Users::get_from_db(db, company.id).await?;
+    for conference in Conferences::get_from_db(db, company.id).await? {
+        if conference
+            .ends_on
+            .or(conference.starts_on)
+            .map(|d| d < today)
+            .unwrap_or(true)
+        {
+            continue;
+        }
+
+        let staff = conference.booth_staff(&users.0);
+        let existing = get_conference_tasks(db, &conference).await?;
+        for planned in plan_conference_tasks(config, &conference, &staff) {
+            match existing.iter().find(|t| t.key == planned.key) {
+                Some(task)
+                    if task.completed_at.is_none()
+                        && (task.due_on != planned.due_on
+                            || task.title != planned.title
+                            || task.conference != conference.name) =>
+                {
+                    let mut task = task.clone();
+                    if task.due_on != planned.due_on {
+                        // Tell the assignee about the new due date.
+                        task.notified_at = None;
+                    }
+                    task.due_on = planned.due_on;
+                    task.title = planned.title.to_string();
+                    task.conference = conference.name.to_string();
+                    task.update_in_db(db).await?;
+                }
+                Some(_) => {}
+                None => {
+                    NewConferenceTask {
+                        conference_id: conference.id,
+                        conference: conference.name.to_string(),
+                        key: planned.key.to_string(),
+                        title: planned.title.to_string(),
+                        assignee: planned.assignee.to_string(),
+                        due_on: planned.due_on,
+                        swag_bundle: planned.swag_bundle.to_string(),
+                        outbound_shipment_id: None,
+                        completed_at: None,
+                        notified_at: None,
+                        error: String::new(),
+                        cio_company_id: company.id,
+                    }
+                    .upsert_in_db(db)
+                    .await?;
+
+                    info!("created task `{}` of conference `{}`", planned.title, conference.name);
+                }
+            }
+        }
+
+        for task in get_conference_tasks(db, &conference).await? {
+            if !task.swag_bundle.is_empty() && task.completed_at.is_none() && task.due_on <= today {
+                ship_conference_swag(db, company, &conference, &task).await?;
+            }
+        }
+    }
+
+    notify_conference_task_assignees(db, company).await?;
+
+    ConferenceTasks::get_from_db(db, company.id)
+        .await?
+        .update_airtable(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Create the shipment of the swag bundle of a task, and record it on the task so we only ship
+/// it once.
+async fn ship_conference_swag(
+    db: &Database,
+    company: &Company,
+    conference: &Conference,
+    task: &ConferenceTask,
+) -> Result<()> {
+    // Mark the task done before we create its shipment, so if another sync is running at the
+    // same time, or we fail to record the shipment, the bundle is not shipped twice.
+    let claimed = diesel::update(conference_tasks::dsl::conference_tasks)
+        .filter(conference_tasks::dsl::id.eq(task.id))
+        .filter(conference_tasks::dsl::completed_at.is_null())
+        .set(conference_tasks::dsl::completed_at.eq(Some(Utc::now())))
+        .execute_async(db.pool())
+        .await?;
+    if claimed == 0 {
+        return Ok(());
+    }
+
+    let shipment = match conference.swag_recipient() {
+        Some(recipient) => create_shipment_from_bundle(db, company, &task.swag_bundle, &BTreeMap::new(), &recipient)
+            .await
+            .map_err(|e| e.to_string()),
+        None => Err("the conference has no address to ship to".to_string()),
+    };
+
+    match shipment {
+        Ok(shipment) => {
+            diesel::update(conference_tasks::dsl::conference_tasks)
+                .filter(conference_tasks::dsl::id.eq(task.id))
+                .set((
+                    conference_tasks::dsl::outbound_shipment_id.eq(Some(shipment.shipment.id)),
+                    conference_tasks::dsl::error.eq(String::new()),
+                ))
+                .execute_async(db.pool())
+                .await?;
+
+            info!(
+                "shipped the `{}` swag bundle to conference `{}`",
+                task.swag_bundle, conference.name
+            );
+        }
+        Err(e) => {
+            warn!(
+                "shipping the `{}` swag bundle to conference `{}` failed: {}",
+                task.swag_bundle, conference.name, e
+            );
+
+            // Give the task back so the next sync tries it again.
+            diesel::update(conference_tasks::dsl::conference_tasks)
+                .filter(conference_tasks::dsl::id.eq(task.id))
+                .set((
+                    conference_tasks::dsl::completed_at.eq(None::<DateTime<Utc>>),
+                    conference_tasks::dsl::error.eq(e),
+                ))
+                .execute_async(db.pool())
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the open tasks we have not told their assignees about yet, by assignee.
+pub fn conference_tasks_to_notify(tasks: Vec<ConferenceTask>) -> BTreeMap<String, Vec<ConferenceTask>> {
+    let mut by_assignee: BTreeMap<String, Vec<ConferenceTask>> = BTreeMap::new();
+    for task in tasks {
+        if task.assignee.is_empty() || task.completed_at.is_some() || task.notified_at.is_some() {
+            continue;
+        }
+
+        by_assignee.entry(task.assignee.to_lowercase()).or_default().push(task);
+    }
+
+    by_assignee
+}
+
+/// Tell the assignees about their new tasks, and the ones that moved, in a Slack direct
+/// message.
+async fn notify_conference_task_assignees(db: &Database, company: &Company) -> Result<()> {
+    let tasks = conference_tasks::dsl::conference_tasks
+        .filter(conference_tasks::dsl::cio_company_id.eq(company.id))
+        .order_by(conference_tasks::dsl::due_on.asc())
+        .load_async::<ConferenceTask>(db.pool())
+        .await?;
+
+    let by_assignee = conference_tasks_to_notify(tasks);
+    if by_assignee.is_empty() {
+        return Ok(());
+    }
+
+    let users: Vec<User> = Users::get_from_db(db, company.id).await?.into_iter().collect();
+    let slack = company.authenticate_slack(db).await?;
+    let now = Utc::now();
+    for (assignee, tasks) in by_assignee {
+        let muted = find_user_by_email(&users, company, &assignee)
+            .map(|u| !u.wants_notification(NOTIFICATION_CONFERENCE_TASKS))
+            .unwrap_or_default();
+
+        if !muted {
+            let channel = match slack.lookup_user_by_email(&assignee).await {
+                Ok(user) => user.id,
+                Err(e) => {
+                    warn!("could not find the slack user of {}: {}", assignee, e);
+                    continue;
+                }
+            };
+
+            let lines: Vec<String> = tasks
+                .iter()
+                .map(|t| format!("• {} _(due {})_", t.title, t.due_on.format("%b %-d")))
+                .collect();
+            let msg = FormattedMessage {
+                channel,
+                blocks: vec![MessageBlock {
+                    block_type: MessageBlockType::Section,
+                    text: Some(MessageBlockText {
+                        text_type: MessageType::Markdown,
+                        text: format!(
+                            "You have these conference tasks:\n{}\n\nMark them done in Airtable once they are.",
+                            lines.join("\n")
+                        ),
+                    }),
+                    elements: Default::default(),
+                    accessory: Default::default(),
+                    block_id: Default::default(),
+                    fields: Default::default(),
+                }],
+                attachments: Default::default(),
+            };
+            company.post_to_slack_channel(db, &msg).await?;
+            info!("told {} about {} conference tasks", assignee, tasks.len());
+        }
+
+        for mut task in tasks {
+            task.notified_at = Some(now);
+            task.update_in_db(db).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Someone whose badge was scanned at our booth.
+#[db {
+    new_struct_name = "ConferenceLead",
+    airtable_base = "customer_leads",
+    airtable_table = "AIRTABLE_CONFERENCE_LEADS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "conference" = "String",
+        "email" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = conference_leads)]
+pub struct NewConferenceLead {
+    /// The name of the conference.
+    pub conference: String,
+    pub email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub first_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub last_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub organization: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub job_title: String,
+    /// What the booth staff wrote down when they scanned the badge.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a ConferenceLead.
+#[async_trait]
+impl UpdateAirtableRecord<ConferenceLead> for ConferenceLead {
+    async fn update_airtable_record(&mut self, _record: ConferenceLead) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The names of the columns of the lead capture exports for each of the fields we import, in
+/// the order we look for them. Every badge scanner names them a bit differently.
+fn lead_columns(field: &str) -> &'static [&'static str] {
+    match field {
+        "email" => &["Email", "Email Address", "E-mail"],
+        "first_name" => &["First Name", "First", "Given Name"],
+        "last_name" => &["Last Name", "Last", "Surname"],
+        "organization" => &["Company", "Company Name", "Organization"],
+        "job_title" => &["Title", "Job Title", "Position"],
+        "notes" => &["Notes", "Comments"],
+        _ => &[],
+    }
+}
+
+/// Parse the export of the leads captured at the booth of a conference. Leads without an email
+/// are skipped, and the ones scanned twice are only kept once.
+pub fn parse_conference_leads(conference: &str, csv: &[u8], cio_company_id: i32) -> Result<Vec<NewConferenceLead>> {
+    let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(csv);
+    let headers: Vec<String> = rdr.headers()?.iter().map(|h| h.trim().to_string()).collect();
+
+    let column = |field: &str| -> Option<usize> {
+        lead_columns(field)
+            .iter()
+            .find_map(|name| headers.iter().position(|h| h.eq_ignore_ascii_case(name)))
+    };
+    let email = match column("email") {
+        Some(c) => c,
+        None => bail!("the lead capture export has no email column"),
+    };
+
+    let mut leads: Vec<NewConferenceLead> = Vec::new();
+    for record in rdr.records() {
+        let record = record?;
+        let value = |c: Option<usize>| -> String {
+            c.and_then(|c| record.get(c))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        let email = value(Some(email)).to_lowercase();
+        if !email.contains('@') || leads.iter().any(|l| l.email == email) {
+            continue;
+        }
+
+        leads.push(NewConferenceLead {
+            conference: conference.to_string(),
+            email,
+            first_name: value(column("first_name")),
+            last_name: value(column("last_name")),
+            organization: value(column("organization")),
+            job_title: value(column("job_title")),
+            notes: value(column("notes")),
+            cio_company_id,
+        });
+    }
+
+    Ok(leads)
+}
+
+/// Import the leads captured at the booth of a conference.
+pub async fn import_conference_leads(
+    db: &Database,
+    company: &Company,
+    conference_name: &str,
+    csv: &[u8],
+) -> Result<Vec<ConferenceLead>> {
+    let conference = match Conference::get_from_db(db, company.id, conference_name.trim().to_string()).await {
+        Some(conference) => conference,
+        None => bail!("there is no conference named `{}`", conference_name),
+    };
+
+    let mut leads = Vec::new();
+    for lead in parse_conference_leads(&conference.name, csv, company.id)? {
+        leads.push(lead.upsert(db).await?);
+    }
+
+    info!("imported {} leads of conference `{}`", leads.len(), conference.name);
+
+    Ok(leads)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    use super::{
+        conference_tasks_to_notify, parse_conference_leads, plan_conference_tasks, Conference, ConferenceTask,
+        ConferenceTaskTemplate, ConferencesConfig,
+    };
+    use crate::{configs::tests::mock_user, tests::mock_record};
+
+    fn mock_conference() -> Conference {
+        mock_record(json!({
+            "name": "OSFC",
+            "starts_on": "2024-09-10",
+            "ends_on": "2024-09-12",
+            "booth_staff": ["one", "two@example.com"],
+            "swag_bundles": ["Conference Kit"],
+            "cio_company_id": 1,
+        }))
+    }
+
+    #[test]
+    fn test_plan_conference_tasks() {
+        let config = ConferencesConfig {
+            tasks: vec![
+                ConferenceTaskTemplate {
+                    title: "Follow up with the leads of {conference}".to_string(),
+                    days_before: -5,
+                    assignee: "sales@example.com".to_string(),
+                    for_each_booth_staff: false,
+                },
+                ConferenceTaskTemplate {
+                    title: "Book travel".to_string(),
+                    days_before: 30,
+                    assignee: String::new(),
+                    for_each_booth_staff: true,
+                },
+            ],
+            swag_lead_days: 21,
+        };
+
+        let mut one = mock_user();
+        one.username = "one".to_string();
+        one.email = "one@example.com".to_string();
+        let mut two = mock_user();
+        two.username = "two".to_string();
+        two.email = "two@example.com".to_string();
+        let users = vec![one, two];
+
+        let conference = mock_conference();
+        let staff = conference.booth_staff(&users);
+        assert_eq!(2, staff.len());
+
+        let tasks = plan_conference_tasks(&config, &conference, &staff);
+        let summary: Vec<(String, String, NaiveDate, String)> = tasks
+            .into_iter()
+            .map(|t| (t.title, t.assignee, t.due_on, t.swag_bundle))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "Book travel".to_string(),
+                    "one@example.com".to_string(),
+                    NaiveDate::from_ymd(2024, 8, 11),
+                    String::new()
+                ),
+                (
+                    "Book travel".to_string(),
+                    "two@example.com".to_string(),
+                    NaiveDate::from_ymd(2024, 8, 11),
+                    String::new()
+                ),
+                (
+                    "Ship the Conference Kit swag bundle to OSFC".to_string(),
+                    String::new(),
+                    NaiveDate::from_ymd(2024, 8, 20),
+                    "Conference Kit".to_string()
+                ),
+                (
+                    "Follow up with the leads of OSFC".to_string(),
+                    "sales@example.com".to_string(),
+                    NaiveDate::from_ymd(2024, 9, 15),
+                    String::new()
+                ),
+            ],
+            summary
+        );
+
+        // The tasks keep their key when the conference is renamed.
+        let mut renamed = mock_conference();
+        renamed.name = "OSFC 2024".to_string();
+        let keys = |c: &Conference| -> Vec<String> {
+            plan_conference_tasks(&config, c, &staff)
+                .into_iter()
+                .map(|t| t.key)
+                .collect()
+        };
+        assert_eq!(
+            vec![
+                "Book travel:one@example.com".to_string(),
+                "Book travel:two@example.com".to_string(),
+                "swag:Conference Kit".to_string(),
+                "Follow up with the leads of {conference}:sales@example.com".to_string(),
+            ],
+            keys(&conference)
+        );
+        assert_eq!(keys(&conference), keys(&renamed));
+
+        // We can't plan anything until we know when the conference is.
+        let mut undated = mock_conference();
+        undated.starts_on = None;
+        assert!(plan_conference_tasks(&config, &undated, &staff).is_empty());
+    }
+
+    #[test]
+    fn test_conference_tasks_to_notify() {
+        let task = |id: i32, assignee: &str, extra: serde_json::Value| -> ConferenceTask {
+            let mut fields = json!({
+                "id": id,
+                "conference_id": 1,
+                "key": format!("task-{}", id),
+                "title": format!("Task {}", id),
+                "assignee": assignee,
+                "due_on": "2024-08-11",
+                "cio_company_id": 1,
+            });
+            fields
+                .as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            mock_record(fields)
+        };
+
+        let by_assignee = conference_tasks_to_notify(vec![
+            task(1, "One@Example.com", json!({})),
+            task(2, "one@example.com", json!({})),
+            task(3, "", json!({})),
+            task(4, "two@example.com", json!({"notified_at": "2024-08-01T00:00:00Z"})),
+            task(5, "two@example.com", json!({"completed_at": "2024-08-01T00:00:00Z"})),
+        ]);
+        assert_eq!(vec!["one@example.com"], by_assignee.keys().collect::<Vec<_>>());
+        assert_eq!(
+            vec![1, 2],
+            by_assignee["one@example.com"].iter().map(|t| t.id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_conference_leads() {
+        let csv = "First Name,Last Name,E-mail,Company Name,Job Title,Comments\n\
+                   Ada,Lovelace,Ada@Example.com,Engines,Engineer,Wants a demo\n\
+                   Ada,Lovelace,ada@example.com,Engines,Engineer,\n\
+                   No,Email,,Nowhere,,\n";
+
+        let leads = parse_conference_leads("OSFC", csv.as_bytes(), 1).unwrap();
+        assert_eq!(1, leads.len());
+        assert_eq!("ada@example.com", leads[0].email);
+        assert_eq!("Engines", leads[0].organization);
+        assert_eq!("Wants a demo", leads[0].notes);
+        assert_eq!("OSFC", leads[0].conference);
+
+        assert!(parse_conference_leads("OSFC", b"Name,Company\nAda,Engines\n", 1).is_err());
+    }
+}
@@ -8,7 +8,7 @@ use std::{
 use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
-use chrono::naive::NaiveDate;
+use chrono::{naive::NaiveDate, Utc};
 use diesel::{
     deserialize::{self, FromSql},
     pg::{Pg, PgValue},
@@ -41,7 +41,11 @@ use crate::{
     db::Database,
     features::Features,
     gsuite::{update_gsuite_building, update_gsuite_calendar_resource},
+    locations::{assign_location_ids, sync_locations, LocationConfig},
     providers::{ProviderReadOps, ProviderWriteOps},
+    rate_limit::{acquire, Provider},
+    role_addresses::{assign_role_addresses, sync_role_address_holders, RoleAddressConfig},
+    roles::{sync_roles, NewRole},
     schema::{applicants, buildings, groups, links, resources, users},
     shipments::NewOutboundShipment,
     utils::{get_file_content_from_repo, get_github_user_public_ssh_keys},
@@ -61,6 +65,11 @@ pub struct Config {
     #[serde(default)]
     pub buildings: BTreeMap<String, BuildingConfig>,
 
+    /// The locations the free-form locations of meetings, audits and users are matched to,
+    /// by name.
+    #[serde(default)]
+    pub locations: BTreeMap<String, LocationConfig>,
+
     #[serde(default)]
     pub resources: BTreeMap<String, NewResourceConfig>,
 
@@ -75,6 +84,13 @@ pub struct Config {
 
     #[serde(default)]
     pub certificates: BTreeMap<String, NewCertificate>,
+
+    #[serde(default)]
+    pub roles: BTreeMap<String, NewRole>,
+
+    /// The addresses that belong to a role rather than a person, like `press`, by address.
+    #[serde(default, alias = "role-addresses")]
+    pub role_addresses: BTreeMap<String, RoleAddressConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone, JsonSchema, Serialize, PartialEq, FromSqlRow, AsExpression)]
@@ -199,6 +215,9 @@ pub struct UserConfig {
     pub manager: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub link_to_manager: Vec<String>,
+    /// The username of the person who helps the user through their first weeks.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub onboarding_buddy: String,
 
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub groups: Vec<String>,
@@ -210,6 +229,10 @@ pub struct UserConfig {
     pub building: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub link_to_building: Vec<String>,
+    /// The location the user works from, matched from `building` by the sync. It is not in
+    /// the config files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location_id: Option<i32>,
 
     #[serde(default, alias = "aws_role", skip_serializing_if = "String::is_empty")]
     pub aws_role: String,
@@ -288,6 +311,9 @@ pub struct UserConfig {
     /// This field is automatically populated by airtable based on the user's start date.
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub google_anniversary_event_id: String,
+    /// This field is automatically populated once the user's first week is on their calendar.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub first_week_event_ids: Vec<String>,
 
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub email: String,
@@ -314,6 +340,70 @@ pub struct UserConfig {
     #[serde(default)]
     pub gusto_pull_permission: bool,
 
+    /// Contractors get accounts that expire at the end of their engagement.
+    #[serde(default)]
+    pub is_contractor: bool,
+    /// The last day of a contractor's engagement. Their accounts are suspended after
+    /// this date unless an extension is approved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<NaiveDate>,
+    /// This field is set in Airtable to request an extension of a contractor's end date.
+    /// It has no effect until the extension is approved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_end_date: Option<NaiveDate>,
+    /// This field is automatically populated when an extension is approved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended_end_date: Option<NaiveDate>,
+    /// The expiration date we last sent a warning for, so we only warn once per date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_warning_sent_for: Option<NaiveDate>,
+    /// The expiration date we suspended the contractor's accounts for, so we only
+    /// suspend them once per date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_suspended_for: Option<NaiveDate>,
+
+    /// The focus blocks the user opted into protecting from meetings. Either a day, like
+    /// `Wednesday`, for a meeting-free day, or a day and a time range, like `Tue 09:00-12:00`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub focus_blocks: Vec<String>,
+    /// The time zone of the user's focus blocks. Defaults to `America/Los_Angeles`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub focus_time_zone: String,
+    /// Decline meetings booked over the user's focus blocks, instead of only flagging them.
+    #[serde(default)]
+    pub decline_meetings_over_focus_time: bool,
+    /// The employers the user worked at before, like `Acme (2016-2020)`, so we keep them off
+    /// the interview panels of their former coworkers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_employers: Vec<String>,
+    /// The notifications the user does not want, like `weekly-digest`, see
+    /// `notifications::NOTIFICATIONS`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub muted_notifications: Vec<String>,
+
+    /// The following fields do not exist in the config files but are set by the
+    /// user through the self-service profile page.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub phone: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pronouns: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub emergency_contact_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub emergency_contact_phone: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub emergency_contact_relationship: String,
+
+    /// The following fields do not exist in the config files but are set by the
+    /// user through the skills profile in Slack.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interests: Vec<String>,
+    /// The fields the user worked in before joining, like `storage` or `payments`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_domains: Vec<String>,
+
     /// The CIO company ID.
     #[serde(default)]
     pub cio_company_id: i32,
@@ -416,6 +506,18 @@ impl UserConfig {
         // Update or create the user in the database.
         if let Some(e) = existing.clone() {
             self.google_anniversary_event_id = e.google_anniversary_event_id;
+            self.first_week_event_ids = e.first_week_event_ids;
+            self.extended_end_date = e.extended_end_date;
+            self.expiration_warning_sent_for = e.expiration_warning_sent_for;
+            self.access_suspended_for = e.access_suspended_for;
+            self.phone = e.phone;
+            self.pronouns = e.pronouns;
+            self.emergency_contact_name = e.emergency_contact_name;
+            self.emergency_contact_phone = e.emergency_contact_phone;
+            self.emergency_contact_relationship = e.emergency_contact_relationship;
+            self.skills = e.skills;
+            self.interests = e.interests;
+            self.previous_domains = e.previous_domains;
         }
 
         // See if we have a gsuite user for the user.
@@ -458,6 +560,7 @@ impl UserConfig {
                 self.home_address_zipcode = airtable_record.fields.home_address_zipcode.to_string();
                 self.home_address_country = airtable_record.fields.home_address_country.to_string();
                 self.birthday = airtable_record.fields.birthday;
+                self.requested_end_date = airtable_record.fields.requested_end_date;
 
                 log::info!(
                     "Fetched address data from existing Airtable record for user {} during sync",
@@ -509,8 +612,32 @@ impl UserConfig {
         // Expand the user.
         self.expand(db, company).await?;
 
+        // Contractor accounts always expire, so we need to know when.
+        if self.is_contractor && self.end_date.is_none() {
+            bail!(
+                "contractor `{}` must have an end_date before being provisioned",
+                self.username
+            );
+        }
+
         let mut new_user = self.upsert(db).await?;
 
+        // Do not re-provision a contractor whose access has expired, suspend them instead.
+        let today = Utc::now().date().naive_utc();
+        if new_user.is_access_expired(today) {
+            if !new_user.needs_suspension(today) {
+                // We already suspended them.
+                return Ok(());
+            }
+
+            info!(
+                "access for contractor `{}` expired on {:?}, suspending instead of provisioning",
+                new_user.username,
+                new_user.access_expires_on()
+            );
+            return new_user.suspend_expired_access(db, company).await;
+        }
+
         // Attempt to provision this user with our known external services
 
         if let Some(ref okta) = okta_auth {
@@ -745,7 +872,7 @@ impl UserConfig {
         Ok(())
     }
 
-    async fn populate_home_address(&mut self) -> Result<()> {
+    pub async fn populate_home_address(&mut self) -> Result<()> {
         let mut street_address = self.home_address_street_1.to_string();
         if !self.home_address_street_2.is_empty() {
             street_address = format!("{}\n{}", self.home_address_street_1, self.home_address_street_2,);
@@ -1444,6 +1571,10 @@ pub struct GroupConfig {
     /// Specifies whether a collaborative inbox will remain turned on for the group.
     #[serde(default)]
     pub enable_collaborative_inbox: bool,
+
+    /// The labels of the Okta apps this group should be assigned to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub okta_apps: Vec<String>,
     /// The CIO company ID.
     #[serde(default)]
     pub cio_company_id: i32,
@@ -2283,6 +2414,7 @@ pub async fn sync_buildings(
     let gsuite = company.authenticate_google_admin(db).await?;
 
     // Get the existing google buildings.
+    acquire(Provider::Google).await;
     let gsuite_buildings = gsuite
         .resources()
         .buildings_list_all(&company.gsuite_account_id)
@@ -2313,6 +2445,7 @@ pub async fn sync_buildings(
         building.delete(db).await?;
 
         // Delete the building from GSuite.
+        acquire(Provider::Google).await;
         gsuite
             .resources()
             .buildings_delete(&company.gsuite_account_id, &name)
@@ -2339,6 +2472,7 @@ pub async fn sync_buildings(
                 // If the building does not exist in our map we need to delete
                 // them from GSuite.
                 info!("deleting building {} from gsuite", id);
+                acquire(Provider::Google).await;
                 gsuite
                     .resources()
                     .buildings_delete(&company.gsuite_account_id, &id)
@@ -2353,6 +2487,7 @@ pub async fn sync_buildings(
         let new_b = update_gsuite_building(&b, &building, &id);
 
         // Update the building with the given settings.
+        acquire(Provider::Google).await;
         gsuite
             .resources()
             .buildings_update(
@@ -2377,6 +2512,7 @@ pub async fn sync_buildings(
 
         let new_b = update_gsuite_building(&b, &building, &id);
 
+        acquire(Provider::Google).await;
         gsuite
             .resources()
             .buildings_insert(
@@ -2409,6 +2545,7 @@ pub async fn sync_resources(
     let gsuite = company.authenticate_google_admin(db).await?;
 
     // Get the existing GSuite calendar resources.
+    acquire(Provider::Google).await;
     let g_suite_calendar_resources = gsuite
         .resources()
         .calendars_list_all(
@@ -2479,6 +2616,7 @@ pub async fn sync_resources(
         let new_r = update_gsuite_calendar_resource(&r, &resource, &r.resource_id);
 
         // Update the resource with the given settings.
+        acquire(Provider::Google).await;
         gsuite
             .resources()
             .calendars_update(&company.gsuite_account_id, &new_r.resource_id, &new_r)
@@ -2498,6 +2636,7 @@ pub async fn sync_resources(
 
         let new_r = update_gsuite_calendar_resource(&r, &resource, &id);
 
+        acquire(Provider::Google).await;
         gsuite
             .resources()
             .calendars_insert(&company.gsuite_account_id, &new_r)
@@ -2555,9 +2694,14 @@ pub async fn sync_groups(db: &Database, groups: BTreeMap<String, GroupConfig>, c
 
     info!("updated configs groups in the database");
 
-    // Update the groups in GitHub and GSuite.
+    // Update the groups in GitHub and GSuite, unless they are changed by applying the plans
+    // of the group reconcilers.
     // Get all the groups.
-    let db_groups = Groups::get_from_db(db, company.id).await?;
+    let mut db_groups: Vec<Group> = Groups::get_from_db(db, company.id).await?.into_iter().collect();
+    if Features::is_enabled("RECONCILE_PLANS") {
+        info!("reconcile plans are enabled, not syncing the groups to GitHub, GSuite and Okta");
+        db_groups.clear();
+    }
     // Iterate over all the groups in our database.
     // TODO: delete any groups that are not in the database for each vendor.
     for g in db_groups {
@@ -2733,6 +2877,10 @@ pub async fn refresh_db_configs_and_airtable(db: &Database, company: &Company, c
     // Syncing buildings must happen before we sync resource.
     sync_buildings(db, configs.buildings, company).await?;
 
+    // Sync locations.
+    // Syncing locations must happen after we sync buildings.
+    sync_locations(db, company, &configs.locations).await?;
+
     // Sync resources.
     sync_resources(db, configs.resources, company).await?;
 
@@ -2740,17 +2888,25 @@ pub async fn refresh_db_configs_and_airtable(db: &Database, company: &Company, c
     // Syncing groups must happen before we sync the users.
     sync_groups(db, configs.groups, company).await?;
 
-    // Sync users.
-    sync_users(db, &github, configs.users, company, config).await?;
+    // Sync users, with the role addresses they hold as aliases.
+    let mut users = configs.users;
+    assign_role_addresses(&mut users, &configs.role_addresses);
+    sync_users(db, &github, users, company, config).await?;
+    sync_role_address_holders(db, company, &configs.role_addresses).await?;
+
+    // Match the free-form locations to the locations, once the users are synced.
+    assign_location_ids(db, company).await?;
 
     // Sync links.
-    let (links, certs, ghout, ann) = tokio::join!(
+    let (links, certs, ghout, ann, roles) = tokio::join!(
         sync_links(db, configs.links, configs.huddles, company),
         // Sync certificates.
         sync_certificates(db, &github, configs.certificates, company),
         // Sync github outside collaborators.
         sync_github_outside_collaborators(db, &github, configs.github_outside_collaborators, company),
         refresh_anniversary_events(db, company),
+        // Sync roles.
+        sync_roles(db, configs.roles, company),
     );
 
     if let Err(e) = links {
@@ -2765,6 +2921,9 @@ pub async fn refresh_db_configs_and_airtable(db: &Database, company: &Company, c
     if let Err(e) = ann {
         warn!("error refreshing anniversary events: {}", e);
     }
+    if let Err(e) = roles {
+        warn!("error syncing roles: {}", e);
+    }
 
     Ok(())
 }
@@ -2925,10 +3084,12 @@ pub mod tests {
             department: String::default(),
             manager: String::default(),
             link_to_manager: vec![],
+            onboarding_buddy: String::default(),
             groups: vec![],
             is_group_admin: false,
             building: String::default(),
             link_to_building: vec![],
+            location_id: None,
             aws_role: String::default(),
             denied_services: vec![],
             home_address_street_1: String::default(),
@@ -2954,6 +3115,7 @@ pub mod tests {
             public_ssh_keys: vec![],
             typev: String::default(),
             google_anniversary_event_id: String::default(),
+            first_week_event_ids: vec![],
             email: "random-test@testemaildomain.com".to_string(),
             gusto_id: String::default(),
             okta_id: String::default(),
@@ -2964,6 +3126,25 @@ pub mod tests {
             geocode_cache: String::default(),
             working_on: vec![],
             gusto_pull_permission: false,
+            is_contractor: false,
+            end_date: None,
+            requested_end_date: None,
+            extended_end_date: None,
+            expiration_warning_sent_for: None,
+            access_suspended_for: None,
+            focus_blocks: vec![],
+            focus_time_zone: String::new(),
+            decline_meetings_over_focus_time: false,
+            previous_employers: vec![],
+            muted_notifications: vec![],
+            phone: String::new(),
+            pronouns: String::new(),
+            emergency_contact_name: String::new(),
+            emergency_contact_phone: String::new(),
+            emergency_contact_relationship: String::new(),
+            skills: vec![],
+            interests: vec![],
+            previous_domains: vec![],
             cio_company_id: 1,
             airtable_record_id: String::default(),
         }
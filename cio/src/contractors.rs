@@ -0,0 +1,290 @@
+use anyhow::{bail, Result};
+use chrono::{naive::NaiveDate, Duration, Utc};
+use log::{info, warn};
+use sendgrid_api::{traits::MailOps, Client as SendGrid};
+
+use crate::{
+    companies::Company,
+    configs::{ExternalServices, User, Users},
+    db::Database,
+};
+
+/// How many days before a contractor's access expires that we warn them and their manager.
+pub static CONTRACTOR_EXPIRATION_WARNING_DAYS: i64 = 14;
+
+impl User {
+    /// Return the last day a contractor has access, taking approved extensions into
+    /// account. Returns None for anyone who is not a contractor.
+    pub fn access_expires_on(&self) -> Option<NaiveDate> {
+        if !self.is_contractor {
+            return None;
+        }
+
+        match (self.end_date, self.extended_end_date) {
+            (Some(end), Some(extended)) => Some(end.max(extended)),
+            (end, extended) => end.or(extended),
+        }
+    }
+
+    /// Returns if a contractor's access has expired as of the given day.
+    pub fn is_access_expired(&self, today: NaiveDate) -> bool {
+        match self.access_expires_on() {
+            Some(expires) => today > expires,
+            None => false,
+        }
+    }
+
+    /// Returns if we still need to suspend a contractor whose access has expired. We only
+    /// suspend once per expiration date, so we don't hit every service each sync.
+    pub fn needs_suspension(&self, today: NaiveDate) -> bool {
+        self.is_access_expired(today) && self.access_suspended_for != self.access_expires_on()
+    }
+
+    /// Returns if we still need to warn about the upcoming expiration of a contractor's access.
+    pub fn needs_expiration_warning(&self, today: NaiveDate) -> bool {
+        match self.access_expires_on() {
+            Some(expires) => {
+                today <= expires
+                    && expires - today <= Duration::days(CONTRACTOR_EXPIRATION_WARNING_DAYS)
+                    && self.expiration_warning_sent_for != Some(expires)
+            }
+            None => false,
+        }
+    }
+
+    /// Suspend the user in every service we provision them in. This is the same as
+    /// denying the user access to all of our external services.
+    pub async fn suspend_expired_access(&mut self, db: &Database, company: &Company) -> Result<()> {
+        let mut services = vec![
+            ExternalServices::GitHub,
+            ExternalServices::Zoom,
+            ExternalServices::Ramp,
+            ExternalServices::Airtable,
+        ];
+        // We only manage one of Okta or GSuite directly, see User::sync.
+        if company.okta_domain.is_empty() {
+            services.push(ExternalServices::Google);
+        } else {
+            services.push(ExternalServices::Okta);
+        }
+
+        let mut has_failures = false;
+        for service in services {
+            match service.get_provider_writer(db, company).await {
+                Ok(provider) => match provider.delete_user(db, company, self).await {
+                    Ok(_) => info!("Suspended expired contractor {} in {}", self.username, service),
+                    Err(err) => {
+                        warn!(
+                            "Failed to suspend expired contractor {} in {}. err: {:?}",
+                            self.username, service, err
+                        );
+                        has_failures = true;
+                    }
+                },
+                Err(err) => warn!(
+                    "Failed to create provider client for {} when suspending contractor {}. err: {}",
+                    service, self.username, err
+                ),
+            }
+        }
+
+        if has_failures {
+            bail!("failed to suspend expired contractor {} in all services", self.username);
+        }
+
+        self.access_suspended_for = self.access_expires_on();
+        self.update(db).await?;
+
+        Ok(())
+    }
+
+    /// Approve the end date extension that was requested in Airtable.
+    pub async fn approve_end_date_extension(&mut self, db: &Database, requested: Option<NaiveDate>) -> Result<()> {
+        if !self.is_contractor {
+            bail!(
+                "user `{}` is not a contractor, their access does not expire",
+                self.username
+            );
+        }
+
+        let requested = match requested {
+            Some(requested) => requested,
+            None => bail!("no end date extension was requested for contractor `{}`", self.username),
+        };
+
+        if let Some(expires) = self.access_expires_on() {
+            if requested <= expires {
+                bail!(
+                    "requested end date {} for contractor `{}` does not extend their current end date {}",
+                    requested,
+                    self.username,
+                    expires
+                );
+            }
+        }
+
+        self.extended_end_date = Some(requested);
+        self.requested_end_date = None;
+        self.update(db).await?;
+
+        info!(
+            "approved end date extension for contractor `{}` until {}",
+            self.username, requested
+        );
+
+        Ok(())
+    }
+
+    /// Send an email to a contractor and their manager that the contractor's access is
+    /// about to expire.
+    async fn send_expiration_warning(&self, db: &Database, company: &Company, expires: NaiveDate) -> Result<()> {
+        let manager = self.manager(db).await;
+
+        let sendgrid_client = SendGrid::new_from_env();
+        sendgrid_client
+            .mail_send()
+            .send_plain_text(
+                &format!("{}'s accounts expire on {}", self.full_name(), expires),
+                &format!(
+                    "Hi {},
+
+The accounts for {} ({}) are set to expire at the end of their engagement
+on {}. After that date they will be suspended in all of our services.
+
+If the engagement is being extended, set the requested end date for them
+in the Employees table in Airtable and have the extension approved before
+{}.
+
+xoxo,
+  The Onboarding Bot",
+                    manager.first_name,
+                    self.full_name(),
+                    self.email,
+                    expires,
+                    expires,
+                ),
+                &[manager.email.to_string(), self.email.to_string()],
+                &[],
+                &[],
+                &format!("admin@{}", company.gsuite_domain),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Warn contractors and their managers before the contractors' access expires, and suspend
+/// any contractors whose access has expired.
+pub async fn refresh_contractor_accounts(db: &Database, company: &Company) -> Result<()> {
+    let today = Utc::now().date().naive_utc();
+
+    for mut user in Users::get_from_db(db, company.id).await? {
+        if !user.is_contractor {
+            continue;
+        }
+
+        if user.is_access_expired(today) {
+            if !user.needs_suspension(today) {
+                continue;
+            }
+            if let Err(e) = user.suspend_expired_access(db, company).await {
+                warn!("suspending expired contractor `{}` failed: {}", user.username, e);
+            }
+            continue;
+        }
+
+        if user.needs_expiration_warning(today) {
+            // We checked above that this user has an expiration.
+            let expires = user.access_expires_on().unwrap();
+            match user.send_expiration_warning(db, company, expires).await {
+                Ok(_) => {
+                    user.expiration_warning_sent_for = Some(expires);
+                    user.update(db).await?;
+                    info!(
+                        "warned contractor `{}` that their access expires on {}",
+                        user.username, expires
+                    );
+                }
+                Err(e) => {
+                    warn!(
+                        "sending expiration warning to contractor `{}` failed: {}",
+                        user.username, e
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::naive::NaiveDate;
+
+    use crate::{configs::User, tests::mock_record};
+
+    fn mock_contractor(end_date: Option<NaiveDate>, extended_end_date: Option<NaiveDate>) -> User {
+        let mut user: User = mock_record(json!({"first_name": "Test", "last_name": "User", "username": "test"}));
+        user.is_contractor = true;
+        user.end_date = end_date;
+        user.extended_end_date = extended_end_date;
+        user
+    }
+
+    #[test]
+    fn test_access_expires_on_uses_extension() {
+        let end = NaiveDate::from_ymd(2023, 3, 31);
+        let extended = NaiveDate::from_ymd(2023, 6, 30);
+
+        assert_eq!(Some(end), mock_contractor(Some(end), None).access_expires_on());
+        assert_eq!(
+            Some(extended),
+            mock_contractor(Some(end), Some(extended)).access_expires_on()
+        );
+
+        let mut employee = mock_contractor(Some(end), None);
+        employee.is_contractor = false;
+        assert_eq!(None, employee.access_expires_on());
+        assert!(!employee.is_access_expired(NaiveDate::from_ymd(2024, 1, 1)));
+    }
+
+    #[test]
+    fn test_access_expires_after_end_date() {
+        let user = mock_contractor(Some(NaiveDate::from_ymd(2023, 3, 31)), None);
+
+        assert!(!user.is_access_expired(NaiveDate::from_ymd(2023, 3, 31)));
+        assert!(user.is_access_expired(NaiveDate::from_ymd(2023, 4, 1)));
+    }
+
+    #[test]
+    fn test_needs_expiration_warning_once() {
+        let end = NaiveDate::from_ymd(2023, 3, 31);
+        let mut user = mock_contractor(Some(end), None);
+
+        assert!(!user.needs_expiration_warning(NaiveDate::from_ymd(2023, 3, 1)));
+        assert!(user.needs_expiration_warning(NaiveDate::from_ymd(2023, 3, 20)));
+
+        user.expiration_warning_sent_for = Some(end);
+        assert!(!user.needs_expiration_warning(NaiveDate::from_ymd(2023, 3, 20)));
+    }
+
+    #[test]
+    fn test_needs_suspension_once() {
+        let end = NaiveDate::from_ymd(2023, 3, 31);
+        let extended = NaiveDate::from_ymd(2023, 6, 30);
+        let mut user = mock_contractor(Some(end), None);
+
+        assert!(!user.needs_suspension(NaiveDate::from_ymd(2023, 3, 31)));
+        assert!(user.needs_suspension(NaiveDate::from_ymd(2023, 4, 1)));
+
+        user.access_suspended_for = Some(end);
+        assert!(!user.needs_suspension(NaiveDate::from_ymd(2023, 4, 1)));
+
+        // An approved extension gets its own suspension once it runs out.
+        user.extended_end_date = Some(extended);
+        assert!(!user.needs_suspension(NaiveDate::from_ymd(2023, 4, 1)));
+        assert!(user.needs_suspension(NaiveDate::from_ymd(2023, 7, 1)));
+    }
+}
@@ -0,0 +1,242 @@
+use std::io::{Read, Write};
+
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use google_drive::traits::{DriveOps, FileOps};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::{box_, sealedbox};
+
+use crate::{api_tokens::APIToken, companies::Company, db::Database, schema::api_tokens};
+
+/// The environment variable holding the base64 encoded public key the export is encrypted
+/// to. Its private key is kept offline, so only the people holding it can read the export.
+static CREDENTIALS_EXPORT_PUBLIC_KEY: &str = "CREDENTIALS_EXPORT_PUBLIC_KEY";
+
+/// An integration we have a token for, without the token itself.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct CredentialsInventoryToken {
+    pub product: String,
+    /// The account the token is for at the provider.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub account_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub endpoint: String,
+    pub has_refresh_token: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_date: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token_expires_date: Option<DateTime<Utc>>,
+    pub last_updated_at: DateTime<Utc>,
+}
+
+impl From<&APIToken> for CredentialsInventoryToken {
+    fn from(token: &APIToken) -> Self {
+        CredentialsInventoryToken {
+            product: token.product.to_string(),
+            account_id: token.company_id.to_string(),
+            user_email: token.user_email.to_string(),
+            endpoint: token.endpoint.to_string(),
+            has_refresh_token: !token.refresh_token.is_empty(),
+            expires_date: token.expires_date,
+            refresh_token_expires_date: token.refresh_token_expires_date,
+            last_updated_at: token.last_updated_at,
+        }
+    }
+}
+
+/// What we need to know to set a company's integrations back up after a total outage: which
+/// providers we use, with which accounts, and when their tokens expire. It never contains the
+/// secrets themselves.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct CredentialsInventory {
+    pub company: String,
+    pub gsuite_domain: String,
+    pub github_org: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub github_app_installation_id: String,
+    /// The email of the Google service account we act as.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub google_service_account: String,
+    /// The integrations configured with an API key on the company, like `okta` or `checkr`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<CredentialsInventoryToken>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl CredentialsInventory {
+    pub fn new(company: &Company, tokens: &[APIToken], generated_at: DateTime<Utc>) -> Self {
+        let api_keys = [
+            ("airtable", &company.airtable_api_key),
+            ("checkr", &company.checkr_api_key),
+            ("cloudflare", &company.cloudflare_api_key),
+            ("eventbrite", &company.eventbrite_api_key),
+            ("greenhouse", &company.greenhouse_api_key),
+            ("lever", &company.lever_api_key),
+            ("okta", &company.okta_api_key),
+            ("printful", &company.printful_api_key),
+            ("shipbob", &company.shipbob_pat),
+            ("tailscale", &company.tailscale_api_key),
+            ("tripactions", &company.tripactions_client_secret),
+        ]
+        .iter()
+        .filter(|(_, key)| !key.trim().is_empty())
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+        // Only keep who the service account is, the rest of it is its private key. Companies
+        // acting as it without its key only have its email.
+        let google_service_account = serde_json::from_str::<serde_json::Value>(&company.google_service_account)
+            .ok()
+            .and_then(|v| v.get("client_email").and_then(|e| e.as_str()).map(|e| e.to_string()))
+            .unwrap_or_else(|| company.google_service_account_email.to_string());
+
+        let mut tokens: Vec<CredentialsInventoryToken> = tokens.iter().map(CredentialsInventoryToken::from).collect();
+        tokens.sort_by(|a, b| a.product.cmp(&b.product).then_with(|| a.account_id.cmp(&b.account_id)));
+
+        CredentialsInventory {
+            company: company.name.to_string(),
+            gsuite_domain: company.gsuite_domain.to_string(),
+            github_org: company.github_org.to_string(),
+            github_app_installation_id: if company.github_app_installation_id > 0 {
+                company.github_app_installation_id.to_string()
+            } else {
+                String::new()
+            },
+            google_service_account,
+            api_keys,
+            tokens,
+            generated_at,
+        }
+    }
+
+    /// Compress the inventory and encrypt it to a public key, so only the holder of the
+    /// matching private key can read it. Open it with libsodium's `crypto_box_seal_open`, then
+    /// gunzip it.
+    pub fn seal(&self, public_key: &box_::PublicKey) -> Result<Vec<u8>> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&serde_json::to_vec_pretty(self)?)?;
+        let compressed = encoder.finish()?;
+
+        Ok(sealedbox::seal(&compressed, public_key))
+    }
+
+    /// Decrypt an inventory sealed with `seal`.
+    pub fn open(sealed: &[u8], public_key: &box_::PublicKey, secret_key: &box_::SecretKey) -> Result<Self> {
+        let compressed = sealedbox::open(sealed, public_key, secret_key)
+            .map_err(|_| anyhow!("decrypting the credentials inventory failed"))?;
+        let mut json = Vec::new();
+        GzDecoder::new(&compressed[..]).read_to_end(&mut json)?;
+
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+fn export_public_key() -> Result<box_::PublicKey> {
+    let encoded = std::env::var(CREDENTIALS_EXPORT_PUBLIC_KEY).unwrap_or_default();
+    if encoded.trim().is_empty() {
+        bail!(
+            "{} must be set to export the credentials inventory",
+            CREDENTIALS_EXPORT_PUBLIC_KEY
+        );
+    }
+
+    let decoded = base64::decode(encoded.trim())?;
+    box_::PublicKey::from_slice(&decoded).ok_or_else(|| anyhow!("{} is not a valid key", CREDENTIALS_EXPORT_PUBLIC_KEY))
+}
+
+/// Export the encrypted credentials inventory of a company to Google Drive, so we can recover
+/// from an outage of everything we run.
+pub async fn export_credentials_inventory(db: &Database, company: &Company) -> Result<()> {
+    sodiumoxide::init().map_err(|_| anyhow!("initializing sodiumoxide failed!"))?;
+    let public_key = export_public_key()?;
+
+    let tokens = api_tokens::dsl::api_tokens
+        .filter(api_tokens::dsl::auth_company_id.eq(company.id))
+        .load_async::<APIToken>(db.pool())
+        .await?;
+    let inventory = CredentialsInventory::new(company, &tokens, Utc::now());
+    let sealed = inventory.seal(&public_key)?;
+
+    // Initialize the Google Drive client.
+    let drive_client = company.authenticate_google_drive(db).await?;
+
+    // It should be in the shared drive : "Automated Documents"/"break-glass"
+    let shared_drive = drive_client.drives().get_by_name("Automated Documents").await?;
+    let drive_id = shared_drive.id.to_string();
+    let parent_id = drive_client.files().create_folder(&drive_id, "", "break-glass").await?;
+
+    drive_client
+        .files()
+        .create_or_update(
+            &drive_id,
+            &parent_id,
+            &format!(
+                "Credentials Inventory {}.json.gz.sealed",
+                inventory.generated_at.format("%Y-%m-%d")
+            ),
+            "application/octet-stream",
+            &sealed,
+        )
+        .await?;
+
+    info!(
+        "exported the credentials inventory of `{}` with {} tokens",
+        company.name,
+        inventory.tokens.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sodiumoxide::crypto::box_;
+
+    use super::CredentialsInventory;
+    use crate::tests::mock_record;
+    use crate::{api_tokens::APIToken, companies::tests::mock_company};
+
+    #[test]
+    fn test_credentials_inventory() {
+        let mut company = mock_company();
+        company.okta_api_key = "okta-secret".to_string();
+        company.google_service_account =
+            r#"{"client_email":"cio@project.iam.gserviceaccount.com","private_key":"google-secret"}"#.to_string();
+
+        let mut token: APIToken = mock_record(json!({"last_updated_at": "2023-05-01T00:00:00Z"}));
+        token.product = "zoom".to_string();
+        token.company_id = "account-1".to_string();
+        token.access_token = "access-secret".to_string();
+        token.refresh_token = "refresh-secret".to_string();
+
+        let inventory = CredentialsInventory::new(&company, &[token], Utc::now());
+        assert_eq!(vec!["okta".to_string()], inventory.api_keys);
+        assert_eq!("cio@project.iam.gserviceaccount.com", inventory.google_service_account);
+        assert_eq!("account-1", inventory.tokens[0].account_id);
+        assert!(inventory.tokens[0].has_refresh_token);
+
+        let json = serde_json::to_string(&inventory).unwrap();
+        assert!(!json.contains("secret"));
+
+        sodiumoxide::init().unwrap();
+        let (public_key, secret_key) = box_::gen_keypair();
+        let sealed = inventory.seal(&public_key).unwrap();
+        assert_eq!(
+            inventory,
+            CredentialsInventory::open(&sealed, &public_key, &secret_key).unwrap()
+        );
+
+        let (other_public_key, other_secret_key) = box_::gen_keypair();
+        assert!(CredentialsInventory::open(&sealed, &other_public_key, &other_secret_key).is_err());
+    }
+}
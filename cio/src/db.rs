@@ -50,6 +50,10 @@ impl steno::SecStore for Database {
     }
 
     async fn record_event(&self, event: steno::SagaNodeEvent) {
+        if let Err(e) = crate::saga_steps::record_saga_step_event(self, &event).await {
+            log::warn!("recording the step of saga {} failed: {}", event.saga_id, e);
+        }
+
         crate::functions::Function::from_saga_node_event(self, &event)
             .await
             .unwrap();
@@ -49,4 +49,7 @@ pub trait DNSProviderOps {
 
     /// Delete the record if it exists.
     async fn delete_record(&self, record: DnsRecord) -> Result<()>;
+
+    /// Get the contents of the records with a name and type.
+    async fn get_records(&self, name: &str, type_: DnsRecordType) -> Result<Vec<String>>;
 }
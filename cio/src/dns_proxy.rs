@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use crate::{
     cloud_dns::CloudDnsClient,
     cloudflare::CloudFlareClient,
-    dns_providers::{DNSProviderOps, DnsRecord, DnsUpdateMode},
+    dns_providers::{DNSProviderOps, DnsRecord, DnsRecordType, DnsUpdateMode},
 };
 
 pub struct DnsProviderProxy {
@@ -43,4 +43,10 @@ impl DNSProviderOps for DnsProviderProxy {
 
         Ok(())
     }
+
+    /// Get the contents of the records with a name and type. CloudFlare is not always
+    /// configured for a domain, so we read what is in Cloud DNS.
+    async fn get_records(&self, name: &str, type_: DnsRecordType) -> Result<Vec<String>> {
+        self.cloud_dns.get_records(name, type_).await
+    }
 }
@@ -0,0 +1,421 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use google_drive::{traits::FileOps, Client as GoogleDrive};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_inventory::AssetItem, companies::Company, db::Database, recorded_meetings::RecordedMeeting,
+    schema::drive_uploads,
+};
+
+/// The record a queued upload belongs to, and the field of it that links to the uploaded file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriveUploadTarget {
+    pub kind: &'static str,
+    /// The key we look the record up by: the name of an asset item, or the event id of a
+    /// recorded meeting.
+    pub key: String,
+    /// The field of the record that links to the file, or empty if nothing links to it.
+    pub field: &'static str,
+    /// The meeting and the id of the Zoom cloud recording the file is, if any. We only delete
+    /// the recording from Zoom once the file is in Google Drive.
+    pub zoom_recording: Option<(String, String)>,
+}
+
+impl DriveUploadTarget {
+    pub fn asset_item(name: &str, field: &'static str) -> Self {
+        DriveUploadTarget {
+            kind: "asset_item",
+            key: name.to_string(),
+            field,
+            zoom_recording: None,
+        }
+    }
+
+    pub fn recorded_meeting(google_event_id: &str, field: &'static str) -> Self {
+        DriveUploadTarget {
+            kind: "recorded_meeting",
+            key: google_event_id.to_string(),
+            field,
+            zoom_recording: None,
+        }
+    }
+
+    /// Delete the Zoom cloud recording the file is once it is uploaded.
+    pub fn deleting_zoom_recording(mut self, meeting_id: &str, recording_id: &str) -> Self {
+        self.zoom_recording = Some((meeting_id.to_string(), recording_id.to_string()));
+        self
+    }
+
+    /// Return the link we store on the record for an uploaded file.
+    fn link(&self, file_id: &str) -> String {
+        if self.kind == "asset_item" {
+            // The labels are fetched by the printer, so they need to be a direct download.
+            format!("https://drive.google.com/uc?export=download&id={}", file_id)
+        } else {
+            format!("https://drive.google.com/open?id={}", file_id)
+        }
+    }
+}
+
+/// An upload to Google Drive that failed and is waiting to be retried. The contents are kept
+/// on disk until the upload succeeds.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct DriveUpload {
+    pub id: i32,
+    pub drive_id: String,
+    pub parent_id: String,
+    pub name: String,
+    pub mime_type: String,
+    /// Where the contents of the file are stored until they are uploaded.
+    pub content_path: String,
+    pub target_kind: String,
+    pub target_key: String,
+    pub target_field: String,
+    pub attempts: i32,
+    pub last_error: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub drive_file_id: String,
+    /// The Zoom cloud recording we delete once the file is uploaded, if any.
+    pub zoom_meeting_id: String,
+    pub zoom_recording_id: String,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = drive_uploads)]
+struct NewDriveUpload {
+    drive_id: String,
+    parent_id: String,
+    name: String,
+    mime_type: String,
+    content_path: String,
+    target_kind: String,
+    target_key: String,
+    target_field: String,
+    attempts: i32,
+    last_error: String,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    drive_file_id: String,
+    zoom_meeting_id: String,
+    zoom_recording_id: String,
+    cio_company_id: i32,
+}
+
+/// The directory we keep the contents of queued uploads in, from `DRIVE_UPLOAD_QUEUE_DIR`. It
+/// has to survive restarts, since the queue is the only copy of a recording we already
+/// downloaded, so we do not fall back to a temporary directory.
+fn queue_dir() -> Result<PathBuf> {
+    match std::env::var("DRIVE_UPLOAD_QUEUE_DIR") {
+        Ok(dir) if !dir.trim().is_empty() => Ok(PathBuf::from(dir.trim())),
+        _ => Err(anyhow!("DRIVE_UPLOAD_QUEUE_DIR is not set, we cannot queue uploads")),
+    }
+}
+
+/// Get the pending uploads for a target.
+async fn pending_uploads(db: &Database, cio_company_id: i32, target: &DriveUploadTarget) -> Result<Vec<DriveUpload>> {
+    Ok(drive_uploads::dsl::drive_uploads
+        .filter(drive_uploads::dsl::cio_company_id.eq(cio_company_id))
+        .filter(drive_uploads::dsl::target_kind.eq(target.kind))
+        .filter(drive_uploads::dsl::target_key.eq(target.key.to_string()))
+        .filter(drive_uploads::dsl::target_field.eq(target.field))
+        .filter(drive_uploads::dsl::completed_at.is_null())
+        .load_async::<DriveUpload>(db.pool())
+        .await?)
+}
+
+/// Returns if there is an upload waiting to be retried for the target.
+pub async fn has_pending_drive_upload(db: &Database, cio_company_id: i32, target: &DriveUploadTarget) -> Result<bool> {
+    Ok(!pending_uploads(db, cio_company_id, target).await?.is_empty())
+}
+
+/// Store the contents of a file and queue it to be uploaded later. Any upload already queued
+/// for the same target is replaced.
+#[allow(clippy::too_many_arguments)]
+async fn enqueue_drive_upload(
+    db: &Database,
+    cio_company_id: i32,
+    drive_id: &str,
+    parent_id: &str,
+    name: &str,
+    mime_type: &str,
+    contents: &[u8],
+    target: &DriveUploadTarget,
+    error: &str,
+) -> Result<()> {
+    for old in pending_uploads(db, cio_company_id, target).await? {
+        old.remove(db).await?;
+    }
+
+    let dir = queue_dir()?;
+    tokio::fs::create_dir_all(&dir).await?;
+    let path = dir.join(uuid::Uuid::new_v4().to_string());
+    tokio::fs::write(&path, contents).await?;
+
+    diesel::insert_into(drive_uploads::table)
+        .values(NewDriveUpload {
+            drive_id: drive_id.to_string(),
+            parent_id: parent_id.to_string(),
+            name: name.to_string(),
+            mime_type: mime_type.to_string(),
+            content_path: path.to_string_lossy().to_string(),
+            target_kind: target.kind.to_string(),
+            target_key: target.key.to_string(),
+            target_field: target.field.to_string(),
+            attempts: 1,
+            last_error: error.to_string(),
+            created_at: Utc::now(),
+            completed_at: None,
+            drive_file_id: Default::default(),
+            zoom_meeting_id: target
+                .zoom_recording
+                .as_ref()
+                .map(|r| r.0.to_string())
+                .unwrap_or_default(),
+            zoom_recording_id: target
+                .zoom_recording
+                .as_ref()
+                .map(|r| r.1.to_string())
+                .unwrap_or_default(),
+            cio_company_id,
+        })
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Upload a file to Google Drive, or queue it to be retried if the upload fails. Returns the
+/// link to the file if it was uploaded now, or an empty string if it was queued. The queue sets
+/// the link on the target record once the upload goes through.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_to_drive_or_enqueue(
+    db: &Database,
+    cio_company_id: i32,
+    drive: &GoogleDrive,
+    drive_id: &str,
+    parent_id: &str,
+    name: &str,
+    mime_type: &str,
+    contents: &[u8],
+    target: &DriveUploadTarget,
+) -> Result<String> {
+    match drive
+        .files()
+        .create_or_update(drive_id, parent_id, name, mime_type, contents)
+        .await
+    {
+        Ok(file) => Ok(target.link(&file.id)),
+        Err(e) => {
+            warn!(
+                "uploading `{}` to google drive failed, queueing it to retry: {}",
+                name, e
+            );
+            enqueue_drive_upload(
+                db,
+                cio_company_id,
+                drive_id,
+                parent_id,
+                name,
+                mime_type,
+                contents,
+                target,
+                &e.to_string(),
+            )
+            .await?;
+
+            Ok(String::new())
+        }
+    }
+}
+
+impl DriveUpload {
+    fn target(&self) -> Result<DriveUploadTarget> {
+        let field = match self.target_field.as_str() {
+            "barcode_png" => "barcode_png",
+            "barcode_svg" => "barcode_svg",
+            "barcode_pdf_label" => "barcode_pdf_label",
+            "qr_code_png" => "qr_code_png",
+            "qr_code_svg" => "qr_code_svg",
+            "video" => "video",
+            "chat_log_link" => "chat_log_link",
+            "" => "",
+            f => bail!("drive upload {} has unknown target field `{}`", self.id, f),
+        };
+
+        let target = match self.target_kind.as_str() {
+            "asset_item" => DriveUploadTarget::asset_item(&self.target_key, field),
+            "recorded_meeting" => DriveUploadTarget::recorded_meeting(&self.target_key, field),
+            k => bail!("drive upload {} has unknown target kind `{}`", self.id, k),
+        };
+        if self.zoom_recording_id.is_empty() {
+            return Ok(target);
+        }
+
+        Ok(target.deleting_zoom_recording(&self.zoom_meeting_id, &self.zoom_recording_id))
+    }
+
+    /// Set the link to the uploaded file on the target record. Returns false if the record
+    /// does not exist yet, so we try again later.
+    async fn link_target(&self, db: &Database, target: &DriveUploadTarget, link: String) -> Result<bool> {
+        if target.field.is_empty() {
+            // Nothing links to the file, like the transcripts we also store in the record.
+            return Ok(true);
+        }
+
+        match target.kind {
+            "asset_item" => {
+                let mut item = match AssetItem::get_from_db(db, self.cio_company_id, target.key.to_string()).await {
+                    Some(item) => item,
+                    None => return Ok(false),
+                };
+                match target.field {
+                    "barcode_png" => item.barcode_png = link,
+                    "barcode_svg" => item.barcode_svg = link,
+                    "qr_code_png" => item.qr_code_png = link,
+                    "qr_code_svg" => item.qr_code_svg = link,
+                    _ => item.barcode_pdf_label = link,
+                }
+                item.update(db).await?;
+            }
+            _ => {
+                let mut meeting = match RecordedMeeting::get_from_db(db, target.key.to_string()).await {
+                    Some(meeting) => meeting,
+                    None => return Ok(false),
+                };
+                match target.field {
+                    "video" => meeting.video = link,
+                    _ => meeting.chat_log_link = link,
+                }
+                meeting.update(db).await?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Try the upload again.
+    async fn retry(&mut self, db: &Database, company: &Company, drive: &GoogleDrive) -> Result<()> {
+        let target = self.target()?;
+
+        if self.drive_file_id.is_empty() {
+            let contents = tokio::fs::read(&self.content_path).await?;
+            let file = drive
+                .files()
+                .create_or_update(&self.drive_id, &self.parent_id, &self.name, &self.mime_type, &contents)
+                .await?;
+            self.drive_file_id = file.id.to_string();
+
+            // Save the file id so we don't upload it again if linking the record fails.
+            diesel::update(drive_uploads::dsl::drive_uploads.filter(drive_uploads::dsl::id.eq(self.id)))
+                .set(drive_uploads::dsl::drive_file_id.eq(self.drive_file_id.to_string()))
+                .execute_async(db.pool())
+                .await?;
+        }
+
+        if !self.link_target(db, &target, target.link(&self.drive_file_id)).await? {
+            bail!("{} `{}` does not exist yet", target.kind, target.key);
+        }
+
+        // The file is in Google Drive now, so we no longer need the recording in Zoom.
+        if let Some((meeting_id, recording_id)) = &target.zoom_recording {
+            company
+                .authenticate_zoom(db)
+                .await?
+                .cloud_recording()
+                .recording_delete_one(meeting_id, recording_id, zoom_api::types::RecordingDeleteAction::Trash)
+                .await?;
+            info!(
+                "zoom deleted recording {} of meeting {} since it is now in Google drive",
+                recording_id, meeting_id
+            );
+        }
+
+        diesel::update(drive_uploads::dsl::drive_uploads.filter(drive_uploads::dsl::id.eq(self.id)))
+            .set(drive_uploads::dsl::completed_at.eq(Some(Utc::now())))
+            .execute_async(db.pool())
+            .await?;
+        if let Err(e) = tokio::fs::remove_file(&self.content_path).await {
+            warn!("removing queued upload contents `{}` failed: {}", self.content_path, e);
+        }
+
+        Ok(())
+    }
+
+    /// Delete the upload and its contents, when a newer upload for the same target replaces it.
+    async fn remove(&self, db: &Database) -> Result<()> {
+        diesel::delete(drive_uploads::dsl::drive_uploads.filter(drive_uploads::dsl::id.eq(self.id)))
+            .execute_async(db.pool())
+            .await?;
+        if let Err(e) = tokio::fs::remove_file(&self.content_path).await {
+            warn!("removing queued upload contents `{}` failed: {}", self.content_path, e);
+        }
+
+        Ok(())
+    }
+}
+
+/// Retry the uploads to Google Drive that failed.
+pub async fn process_drive_upload_queue(db: &Database, company: &Company) -> Result<()> {
+    let uploads = drive_uploads::dsl::drive_uploads
+        .filter(drive_uploads::dsl::cio_company_id.eq(company.id))
+        .filter(drive_uploads::dsl::completed_at.is_null())
+        .order_by(drive_uploads::dsl::created_at)
+        .load_async::<DriveUpload>(db.pool())
+        .await?;
+    if uploads.is_empty() {
+        return Ok(());
+    }
+
+    let drive = company.authenticate_google_drive(db).await?;
+
+    for mut upload in uploads {
+        if let Err(e) = upload.retry(db, company, &drive).await {
+            warn!(
+                "retrying drive upload {} `{}` failed after {} attempts: {}",
+                upload.id, upload.name, upload.attempts, e
+            );
+
+            diesel::update(drive_uploads::dsl::drive_uploads.filter(drive_uploads::dsl::id.eq(upload.id)))
+                .set((
+                    drive_uploads::dsl::attempts.eq(upload.attempts + 1),
+                    drive_uploads::dsl::last_error.eq(e.to_string()),
+                ))
+                .execute_async(db.pool())
+                .await?;
+            continue;
+        }
+
+        info!(
+            "uploaded queued file `{}` to google drive for {} `{}`",
+            upload.name, upload.target_kind, upload.target_key
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DriveUploadTarget;
+
+    #[test]
+    fn test_drive_upload_target_link() {
+        assert_eq!(
+            "https://drive.google.com/uc?export=download&id=abc",
+            DriveUploadTarget::asset_item("laptop", "barcode_png").link("abc")
+        );
+        assert_eq!(
+            "https://drive.google.com/open?id=abc",
+            DriveUploadTarget::recorded_meeting("event", "video").link("abc")
+        );
+    }
+}
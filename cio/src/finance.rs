@@ -34,6 +34,7 @@ use crate::{
         "cio_company_id" = "i32",
         "name" = "String",
     },
+    airtable_read_only = ["total_cost_per_month"],
 }]
 #[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
 #[diesel(table_name = software_vendors)]
@@ -64,16 +65,35 @@ pub struct NewSoftwareVendor {
     pub users: i32,
     #[serde(default)]
     pub flat_cost_per_month: f32,
-    #[serde(default, skip_serializing_if = "is_zero")]
+    #[serde(
+        default,
+        skip_serializing_if = "is_zero",
+        deserialize_with = "airtable_api::formula_format_as_f32::deserialize"
+    )]
     pub total_cost_per_month: f32,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub groups: Vec<String>,
     /// This is linked to another table.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "airtable_api::linked_records::serialize",
+        deserialize_with = "airtable_api::linked_records::deserialize"
+    )]
     pub link_to_transactions: Vec<String>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "airtable_api::linked_records::serialize",
+        deserialize_with = "airtable_api::linked_records::deserialize"
+    )]
     pub link_to_accounts_payable: Vec<String>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "airtable_api::linked_records::serialize",
+        deserialize_with = "airtable_api::linked_records::deserialize"
+    )]
     pub link_to_expensed_items: Vec<String>,
     /// The CIO company ID.
     #[serde(default)]
@@ -89,8 +109,6 @@ fn is_zero(num: &f32) -> bool {
 #[async_trait]
 impl UpdateAirtableRecord<SoftwareVendor> for SoftwareVendor {
     async fn update_airtable_record(&mut self, record: SoftwareVendor) -> Result<()> {
-        // This is a function so we can't change it through the API.
-        self.total_cost_per_month = 0.0;
         // Keep this the same, we update it from the transactions.
         self.link_to_transactions = record.link_to_transactions;
         // Keep this the same, we update it from the accounts payable.
@@ -0,0 +1,415 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use google_calendar::types::{Event, EventAttendee, EventDateTime};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    business_calendar::{BusinessCalendar, BusinessCalendarsConfig},
+    companies::Company,
+    configs::{User, Users},
+    db::Database,
+};
+
+/// How many days before their start date we put a new hire's first week on their calendar.
+static FIRST_WEEK_LOOKAHEAD_DAYS: i64 = 14;
+
+/// How far up the management chain we go for the intro meetings.
+static MAX_MANAGER_CHAIN_LENGTH: usize = 5;
+
+/// The template of the events we put on a new hire's calendar for their first week, like
+/// orientation, a lunch with their buddy, and intro meetings with their managers.
+///
+/// The summaries and descriptions can contain `{user_first_name}`, `{user_full_name}`,
+/// `{manager_name}`, and `{buddy_name}`.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct FirstWeekTemplate {
+    /// The time zone of the event times, defaults to the one of the new hire's office.
+    #[serde(default)]
+    pub time_zone: String,
+    /// The link to the onboarding checklist, added to every event. It can contain
+    /// `{user_name}` and `{company_github}`.
+    #[serde(default)]
+    pub checklist_link: String,
+    #[serde(default)]
+    pub events: Vec<FirstWeekEvent>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct FirstWeekEvent {
+    pub summary: String,
+    #[serde(default)]
+    pub description: String,
+    /// The business day the event is on, 1 being the new hire's first day. Weekends and
+    /// holidays of the new hire's office are skipped.
+    pub day: u32,
+    /// The time the event starts, like `10:00`.
+    pub start: String,
+    pub duration_minutes: i64,
+    /// The people invited besides the new hire. Entries are emails, `{manager}`, or `{buddy}`.
+    #[serde(default)]
+    pub attendees: Vec<String>,
+    /// Schedule the event once with each manager up the new hire's management chain, on
+    /// consecutive business days, instead of once.
+    #[serde(default)]
+    pub with_each_manager: bool,
+}
+
+/// Return the user's managers, from their direct manager up.
+pub fn manager_chain(users: &[User], user: &User) -> Vec<User> {
+    let mut chain: Vec<User> = Vec::new();
+    let mut manager = user.manager.to_string();
+    while !manager.is_empty() && chain.len() < MAX_MANAGER_CHAIN_LENGTH {
+        // Stop if someone is their own manager, or the chain loops.
+        if manager == user.username || chain.iter().any(|m| m.username == manager) {
+            break;
+        }
+
+        match users.iter().find(|u| u.username == manager) {
+            Some(m) => {
+                manager = m.manager.to_string();
+                chain.push(m.clone());
+            }
+            None => break,
+        }
+    }
+
+    chain
+}
+
+fn attendee(email: &str) -> EventAttendee {
+    EventAttendee {
+        id: Default::default(),
+        email: email.to_string(),
+        display_name: Default::default(),
+        organizer: false,
+        resource: false,
+        optional: false,
+        response_status: Default::default(),
+        comment: Default::default(),
+        additional_guests: 0,
+        self_: false,
+    }
+}
+
+impl FirstWeekTemplate {
+    fn parsed_time_zone(&self, calendar: &BusinessCalendar) -> Result<Tz> {
+        if self.time_zone.is_empty() {
+            return Ok(calendar.time_zone);
+        }
+
+        Tz::from_str(&self.time_zone).map_err(|e| anyhow!("invalid first week time zone `{}`: {}", self.time_zone, e))
+    }
+
+    /// Build the calendar events of the new hire's first week from the template.
+    pub fn events(
+        &self,
+        company: &Company,
+        calendar: &BusinessCalendar,
+        user: &User,
+        managers: &[User],
+        buddy: Option<&User>,
+    ) -> Result<Vec<Event>> {
+        let tz = self.parsed_time_zone(calendar)?;
+        let checklist_link = self
+            .checklist_link
+            .replace("{user_name}", &user.username)
+            .replace("{company_github}", &company.github_org);
+
+        let mut events: Vec<Event> = Vec::new();
+        for template in &self.events {
+            let start_time = NaiveTime::parse_from_str(template.start.trim(), "%H:%M")
+                .map_err(|e| anyhow!("invalid start `{}` for `{}`: {}", template.start, template.summary, e))?;
+            if template.duration_minutes <= 0 {
+                bail!("`{}` must last at least a minute", template.summary);
+            }
+
+            // The managers each event is with, `None` if it is not with a manager.
+            let with: Vec<(u32, Option<&User>)> = if template.with_each_manager {
+                managers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, m)| (template.day + i as u32, Some(m)))
+                    .collect()
+            } else {
+                vec![(template.day, managers.first())]
+            };
+
+            for (day, manager) in with {
+                let fill = |text: &str| {
+                    text.replace("{user_first_name}", &user.first_name)
+                        .replace("{user_full_name}", &user.full_name())
+                        .replace("{manager_name}", &manager.map(|m| m.full_name()).unwrap_or_default())
+                        .replace("{buddy_name}", &buddy.map(|b| b.full_name()).unwrap_or_default())
+                };
+
+                let date = calendar.nth_business_day(user.start_date, day);
+                let start = tz
+                    .from_local_datetime(&date.and_time(start_time))
+                    .earliest()
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "`{}` starts at a time that does not exist on {}",
+                            template.summary,
+                            date
+                        )
+                    })?
+                    .with_timezone(&Utc);
+                let end = start + Duration::minutes(template.duration_minutes);
+
+                let mut attendees = vec![attendee(&user.email)];
+                for entry in &template.attendees {
+                    let email = match entry.trim() {
+                        "{manager}" => match manager {
+                            Some(m) => m.email.to_string(),
+                            None => continue,
+                        },
+                        "{buddy}" => match buddy {
+                            Some(b) => b.email.to_string(),
+                            None => continue,
+                        },
+                        email => email.to_string(),
+                    };
+                    if !email.is_empty() && !attendees.iter().any(|a| a.email.to_lowercase() == email.to_lowercase()) {
+                        attendees.push(attendee(&email));
+                    }
+                }
+
+                let mut description = fill(&template.description).trim().to_string();
+                if !checklist_link.is_empty() {
+                    description = format!("{}\n\nOnboarding checklist: {}", description, checklist_link)
+                        .trim()
+                        .to_string();
+                }
+
+                let mut event: Event = Default::default();
+                event.summary = fill(&template.summary);
+                event.description = description;
+                event.start = Some(EventDateTime {
+                    time_zone: tz.name().to_string(),
+                    date: None,
+                    date_time: Some(start),
+                });
+                event.end = Some(EventDateTime {
+                    time_zone: tz.name().to_string(),
+                    date: None,
+                    date_time: Some(end),
+                });
+                event.attendees = attendees;
+
+                events.push(event);
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+/// The id of the calendar event we create for an event of a new hire's first week. It is the
+/// same on every run, so we can tell which events we already created when a run fails half
+/// way. Google only allows the characters of base32hex in event ids.
+/// FROM: https://developers.google.com/calendar/api/v3/reference/events/insert
+fn first_week_event_id(user: &User, index: usize) -> String {
+    format!("onboard{:08}{:04}", user.id, index)
+}
+
+/// Put the first week on the calendar of the new hires starting soon, if we have not already.
+/// Events that failed to be created are retried until the new hire starts.
+pub async fn refresh_first_week_schedules(
+    db: &Database,
+    company: &Company,
+    template: &FirstWeekTemplate,
+    calendars: &BusinessCalendarsConfig,
+) -> Result<()> {
+    if template.events.is_empty() {
+        return Ok(());
+    }
+
+    let today = Utc::now().date().naive_utc();
+    let users = Users::get_from_db(db, company.id).await?;
+    for user in users.0.iter() {
+        if user.is_system_account()
+            || user.start_date < today
+            || user.start_date > today + Duration::days(FIRST_WEEK_LOOKAHEAD_DAYS)
+        {
+            continue;
+        }
+
+        let managers = manager_chain(&users.0, user);
+        let buddy = users.0.iter().find(|u| u.username == user.onboarding_buddy);
+        if !user.onboarding_buddy.is_empty() && buddy.is_none() {
+            warn!(
+                "onboarding buddy `{}` of `{}` is not a user",
+                user.onboarding_buddy, user.username
+            );
+        }
+
+        let calendar = calendars.for_user(user);
+        if let Err(e) = schedule_first_week(db, company, template, &calendar, user, &managers, buddy).await {
+            warn!("scheduling the first week of `{}` failed: {}", user.username, e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn schedule_first_week(
+    db: &Database,
+    company: &Company,
+    template: &FirstWeekTemplate,
+    calendar: &BusinessCalendar,
+    user: &User,
+    managers: &[User],
+    buddy: Option<&User>,
+) -> Result<()> {
+    // We scheduled these before event ids were stable, and only saved them once done.
+    if !user.first_week_event_ids.is_empty() && !user.first_week_event_ids.iter().any(|id| id.starts_with("onboard")) {
+        return Ok(());
+    }
+
+    let events = template.events(company, calendar, user, managers, buddy)?;
+    let missing: Vec<(String, Event)> = events
+        .into_iter()
+        .enumerate()
+        .map(|(i, event)| (first_week_event_id(user, i), event))
+        .filter(|(id, _)| !user.first_week_event_ids.contains(id))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let gcal = company
+        .authenticate_google_calendar_with_service_account(&user.email)
+        .await?;
+
+    let mut user = user.clone();
+    for (id, mut event) in missing {
+        // The insert might have gone through before we failed to save it.
+        if gcal.events().get(&user.email, &id, 0, "").await.is_ok() {
+            user.first_week_event_ids.push(id);
+            user.update(db).await?;
+            continue;
+        }
+
+        event.id = id.to_string();
+        let event = gcal
+            .events()
+            .insert(
+                &user.email,
+                0,                                        // conference data version, leave blank
+                0,                                        // max attendees
+                true,                                     // send notifications
+                google_calendar::types::SendUpdates::All, // send updates
+                true,                                     // supports_attachments
+                &event,
+            )
+            .await?;
+        info!("created first week event `{}` for `{}`", event.summary, user.username);
+
+        user.first_week_event_ids.push(id);
+        // Save as we go so we don't create the same event twice if a later one fails.
+        user.update(db).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{first_week_event_id, manager_chain, FirstWeekEvent, FirstWeekTemplate};
+    use crate::{business_calendar::BusinessCalendar, companies::tests::mock_company, configs::tests::mock_user};
+
+    #[test]
+    fn test_first_week_event_id() {
+        let mut user = mock_user();
+        user.id = 42;
+
+        let id = first_week_event_id(&user, 3);
+        assert_eq!("onboard000000420003", id);
+        // Google only allows base32hex characters in event ids.
+        assert!(id.chars().all(|c| c.is_ascii_digit() || ('a'..='v').contains(&c)));
+        assert_ne!(id, first_week_event_id(&user, 4));
+    }
+
+    #[test]
+    fn test_first_week_events() {
+        let mut user = mock_user();
+        user.username = "new".to_string();
+        user.email = "new@example.com".to_string();
+        user.manager = "lead".to_string();
+        user.start_date = NaiveDate::from_ymd(2023, 5, 4);
+
+        let mut lead = mock_user();
+        lead.username = "lead".to_string();
+        lead.email = "lead@example.com".to_string();
+        lead.manager = "ceo".to_string();
+
+        let mut ceo = mock_user();
+        ceo.username = "ceo".to_string();
+        ceo.email = "ceo@example.com".to_string();
+        // A loop in the chain must not go on forever.
+        ceo.manager = "lead".to_string();
+
+        let managers = manager_chain(&[user.clone(), lead, ceo], &user);
+        assert_eq!(
+            vec!["lead", "ceo"],
+            managers.iter().map(|m| m.username.as_str()).collect::<Vec<&str>>()
+        );
+
+        let template = FirstWeekTemplate {
+            time_zone: "UTC".to_string(),
+            checklist_link: "https://github.com/{company_github}/meta/issues?q={user_name}".to_string(),
+            events: vec![
+                FirstWeekEvent {
+                    summary: "Buddy lunch".to_string(),
+                    description: String::new(),
+                    day: 1,
+                    start: "12:00".to_string(),
+                    duration_minutes: 60,
+                    attendees: vec!["{buddy}".to_string(), "{manager}".to_string()],
+                    with_each_manager: false,
+                },
+                FirstWeekEvent {
+                    summary: "Intro with {manager_name}".to_string(),
+                    description: String::new(),
+                    day: 2,
+                    start: "10:00".to_string(),
+                    duration_minutes: 30,
+                    attendees: vec!["{manager}".to_string()],
+                    with_each_manager: true,
+                },
+            ],
+        };
+
+        let events = template
+            .events(&mock_company(), &BusinessCalendar::default(), &user, &managers, None)
+            .unwrap();
+        assert_eq!(3, events.len());
+
+        // Without a buddy, the lunch is only with the new hire and their manager.
+        assert_eq!(
+            vec!["new@example.com", "lead@example.com"],
+            events[0]
+                .attendees
+                .iter()
+                .map(|a| a.email.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!(
+            "Onboarding checklist: https://github.com/super_computer_org/meta/issues?q=new",
+            events[0].description
+        );
+
+        // The intro with the second manager is on the next business day, after the weekend.
+        assert_eq!("ceo@example.com", events[2].attendees[1].email);
+        assert_eq!(
+            "2023-05-08T10:00:00+00:00",
+            events[2].start.as_ref().unwrap().date_time.unwrap().to_rfc3339()
+        );
+    }
+}
@@ -0,0 +1,639 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use sendgrid_api::{traits::MailOps, Client as SendGrid};
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    airtable::AIRTABLE_FOCUS_TIME_CONFLICTS_TABLE,
+    companies::Company,
+    configs::{User, Users},
+    core::UpdateAirtableRecord,
+    db::Database,
+    schema::focus_time_conflicts,
+};
+
+/// The time zone we use for focus blocks if the user did not set one.
+static DEFAULT_FOCUS_TIME_ZONE: Tz = chrono_tz::America::Los_Angeles;
+
+/// How far ahead we look for meetings booked over focus blocks.
+static FOCUS_TIME_LOOKAHEAD_WEEKS: i64 = 2;
+
+/// A block of time a user protects from meetings every week.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocusBlock {
+    pub weekday: Weekday,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl FromStr for FocusBlock {
+    type Err = anyhow::Error;
+
+    /// Parse a focus block like `Wednesday`, which protects the whole working day, or
+    /// `Tue 09:00-12:00`.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (day, range) = match s.split_once(' ') {
+            Some((day, range)) => (day, Some(range.trim())),
+            None => (s, None),
+        };
+
+        let weekday = Weekday::from_str(day).map_err(|_| anyhow!("invalid day `{}` in focus block `{}`", day, s))?;
+
+        let (start, end) = match range {
+            Some(range) => {
+                let (start, end) = range
+                    .split_once('-')
+                    .ok_or_else(|| anyhow!("invalid time range `{}` in focus block `{}`", range, s))?;
+                (
+                    NaiveTime::parse_from_str(start.trim(), "%H:%M")?,
+                    NaiveTime::parse_from_str(end.trim(), "%H:%M")?,
+                )
+            }
+            // A meeting-free day covers the working day.
+            None => (NaiveTime::from_hms(9, 0, 0), NaiveTime::from_hms(17, 0, 0)),
+        };
+
+        if end <= start {
+            bail!("focus block `{}` ends before it starts", s);
+        }
+
+        Ok(FocusBlock { weekday, start, end })
+    }
+}
+
+/// Return the concrete intervals of the focus blocks between two points in time.
+pub fn focus_intervals(
+    blocks: &[FocusBlock],
+    tz: &Tz,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)> = Default::default();
+
+    let mut day = from.with_timezone(tz).date().naive_local();
+    let last = to.with_timezone(tz).date().naive_local();
+    while day <= last {
+        for block in blocks.iter().filter(|b| b.weekday == day.weekday()) {
+            let start = tz.from_local_datetime(&day.and_time(block.start)).earliest();
+            let end = tz.from_local_datetime(&day.and_time(block.end)).latest();
+            if let (Some(start), Some(end)) = (start, end) {
+                let start = start.with_timezone(&Utc).max(from);
+                let end = end.with_timezone(&Utc).min(to);
+                if start < end {
+                    intervals.push((start, end));
+                }
+            }
+        }
+        day = day.succ();
+    }
+
+    intervals
+}
+
+/// Return how much of two intervals overlap.
+fn overlap(a: (DateTime<Utc>, DateTime<Utc>), b: (DateTime<Utc>, DateTime<Utc>)) -> Duration {
+    let start = a.0.max(b.0);
+    let end = a.1.min(b.1);
+    if start < end {
+        end - start
+    } else {
+        Duration::zero()
+    }
+}
+
+/// Return how much of the focus intervals was not taken up by meetings.
+pub fn surviving_focus_time(
+    intervals: &[(DateTime<Utc>, DateTime<Utc>)],
+    meetings: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Duration {
+    // Merge the meetings so overlapping meetings are not counted twice.
+    let mut meetings = meetings.to_vec();
+    meetings.sort();
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = Default::default();
+    for m in meetings {
+        match merged.last_mut() {
+            Some(last) if m.0 <= last.1 => last.1 = last.1.max(m.1),
+            _ => merged.push(m),
+        }
+    }
+
+    intervals.iter().fold(Duration::zero(), |total, interval| {
+        let lost = merged
+            .iter()
+            .fold(Duration::zero(), |lost, m| lost + overlap(*interval, *m));
+        total + (interval.1 - interval.0) - lost
+    })
+}
+
+impl User {
+    /// Return the user's focus blocks, skipping any we can't parse.
+    pub fn parsed_focus_blocks(&self) -> Vec<FocusBlock> {
+        self.focus_blocks
+            .iter()
+            .filter_map(|b| match FocusBlock::from_str(b) {
+                Ok(block) => Some(block),
+                Err(e) => {
+                    warn!("skipping focus block for `{}`: {}", self.username, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Return the time zone of the user's focus blocks.
+    pub fn parsed_focus_time_zone(&self) -> Tz {
+        if self.focus_time_zone.is_empty() {
+            return DEFAULT_FOCUS_TIME_ZONE;
+        }
+
+        Tz::from_str(&self.focus_time_zone).unwrap_or_else(|e| {
+            warn!(
+                "invalid focus time zone `{}` for `{}`: {}",
+                self.focus_time_zone, self.username, e
+            );
+            DEFAULT_FOCUS_TIME_ZONE
+        })
+    }
+}
+
+/// A meeting that was booked over someone's focus time.
+#[db {
+    new_struct_name = "FocusTimeConflict",
+    airtable_base = "misc",
+    airtable_table = "AIRTABLE_FOCUS_TIME_CONFLICTS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "user_email" = "String",
+        "google_event_id" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = focus_time_conflicts)]
+pub struct NewFocusTimeConflict {
+    pub user_email: String,
+    pub google_event_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event_summary: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event_link: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// Either `declined` or `flagged`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub action: String,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a FocusTimeConflict.
+#[async_trait]
+impl UpdateAirtableRecord<FocusTimeConflict> for FocusTimeConflict {
+    async fn update_airtable_record(&mut self, _record: FocusTimeConflict) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Return the start and end of a calendar event the user is busy for, or None if the event
+/// does not take up the user's time.
+fn busy_interval(user: &User, event: &google_calendar::types::Event) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if event.status == "cancelled" || event.transparency == "transparent" {
+        return None;
+    }
+
+    let declined = event
+        .attendees
+        .iter()
+        .any(|a| a.email.to_lowercase() == user.email.to_lowercase() && a.response_status == "declined");
+    if declined {
+        return None;
+    }
+
+    // All day events don't have a time, and are not meetings.
+    let start = event.start.as_ref()?.date_time?;
+    let end = event.end.as_ref()?.date_time?;
+
+    Some((start, end))
+}
+
+/// Decline or flag the meetings booked over the users' focus blocks.
+pub async fn refresh_focus_time(db: &Database, company: &Company) -> Result<()> {
+    let now = Utc::now();
+    let until = now + Duration::weeks(FOCUS_TIME_LOOKAHEAD_WEEKS);
+
+    for user in Users::get_from_db(db, company.id).await? {
+        if user.focus_blocks.is_empty() || user.is_system_account() {
+            continue;
+        }
+
+        let intervals = focus_intervals(&user.parsed_focus_blocks(), &user.parsed_focus_time_zone(), now, until);
+        if intervals.is_empty() {
+            continue;
+        }
+
+        if let Err(e) = enforce_user_focus_time(db, company, &user, &intervals, now, until).await {
+            warn!("enforcing focus time for `{}` failed: {}", user.username, e);
+        }
+    }
+
+    FocusTimeConflicts::get_from_db(db, company.id)
+        .await?
+        .update_airtable(db)
+        .await?;
+
+    Ok(())
+}
+
+async fn enforce_user_focus_time(
+    db: &Database,
+    company: &Company,
+    user: &User,
+    intervals: &[(DateTime<Utc>, DateTime<Utc>)],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<()> {
+    let gcal = company
+        .authenticate_google_calendar_with_service_account(&user.email)
+        .await?;
+
+    let events = gcal
+        .events()
+        .list_all(
+            &user.email, // Calendar id.
+            "",          // iCalID
+            0,           // Max attendees, set to 0 to ignore.
+            google_calendar::types::OrderBy::StartTime,
+            &[],                // private_extended_property
+            "",                 // q
+            &[],                // shared_extended_property
+            false,              // show_deleted
+            false,              // show_hidden_invitations
+            true,               // single_events
+            &to.to_rfc3339(),   // time_max
+            &from.to_rfc3339(), // time_min
+            "",                 // time_zone
+            "",                 // updated_min
+        )
+        .await?;
+
+    let mut flagged: Vec<NewFocusTimeConflict> = Default::default();
+    for mut event in events {
+        // The user booked it themselves, so it's on them.
+        if let Some(organizer) = &event.organizer {
+            if organizer.email.to_lowercase() == user.email.to_lowercase() {
+                continue;
+            }
+        }
+
+        let interval = match busy_interval(user, &event) {
+            Some(interval) => interval,
+            None => continue,
+        };
+        if !intervals.iter().any(|i| overlap(*i, interval) > Duration::zero()) {
+            continue;
+        }
+
+        let attendee = match event
+            .attendees
+            .iter()
+            .position(|a| a.email.to_lowercase() == user.email.to_lowercase())
+        {
+            Some(attendee) => attendee,
+            None => continue,
+        };
+
+        // We already handled this meeting.
+        if FocusTimeConflict::get_from_db(db, company.id, user.email.to_string(), event.id.to_string())
+            .await
+            .is_some()
+        {
+            continue;
+        }
+
+        let mut conflict = NewFocusTimeConflict {
+            user_email: user.email.to_string(),
+            google_event_id: event.id.to_string(),
+            event_summary: event.summary.trim().to_string(),
+            event_link: event.html_link.to_string(),
+            start_time: interval.0,
+            end_time: interval.1,
+            action: "flagged".to_string(),
+            cio_company_id: company.id,
+        };
+
+        // Only decline meetings the user has not responded to yet, if they accepted it
+        // they meant to.
+        if user.decline_meetings_over_focus_time && event.attendees[attendee].response_status == "needsAction" {
+            event.attendees[attendee].response_status = "declined".to_string();
+            event.attendees[attendee].comment = "This meeting is during my focus time.".to_string();
+            if !event.recurring_event_id.is_empty() {
+                // Individual instances are similar to single events. Unlike their parent recurring events, instances do not have the recurrence field set.
+                // FROM: https://developers.google.com/calendar/recurringevents#ruby_1
+                event.recurrence = vec![];
+            }
+
+            match gcal
+                .events()
+                .update(
+                    &user.email,
+                    &event.id,
+                    0,     // conference data version
+                    0,     // max attendees, 0 to ignore
+                    false, // send notifications
+                    google_calendar::types::SendUpdates::Noop,
+                    true, // supports_attachments
+                    &event,
+                )
+                .await
+            {
+                Ok(_) => {
+                    conflict.action = "declined".to_string();
+                    info!(
+                        "declined `{}` for `{}` since it is during their focus time",
+                        conflict.event_summary, user.username
+                    );
+                }
+                Err(e) => warn!(
+                    "declining `{}` for `{}` failed, flagging it instead: {}",
+                    conflict.event_summary, user.username, e
+                ),
+            }
+        }
+
+        if conflict.action == "flagged" {
+            flagged.push(conflict.clone());
+        }
+
+        conflict.upsert(db).await?;
+    }
+
+    if !flagged.is_empty() {
+        send_focus_time_conflicts(company, user, &flagged).await?;
+    }
+
+    Ok(())
+}
+
+/// Email the user the meetings that were booked over their focus time.
+async fn send_focus_time_conflicts(company: &Company, user: &User, conflicts: &[NewFocusTimeConflict]) -> Result<()> {
+    let tz = user.parsed_focus_time_zone();
+    let meetings = conflicts
+        .iter()
+        .map(|c| {
+            format!(
+                "- {} on {}: {}",
+                c.event_summary,
+                c.start_time.with_timezone(&tz).format("%A %B %-d at %-I:%M %p"),
+                c.event_link
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let sendgrid_client = SendGrid::new_from_env();
+    sendgrid_client
+        .mail_send()
+        .send_plain_text(
+            "Meetings booked over your focus time",
+            &format!(
+                "Hi {},
+
+The following meetings were booked over your focus time:
+
+{}
+
+You might want to decline them or ask the organizer to find another time.
+
+xoxo,
+  The Calendar Bot",
+                user.first_name, meetings
+            ),
+            &[user.email.to_string()],
+            &[],
+            &[],
+            &format!("admin@{}", company.gsuite_domain),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Post a report of how much of the protected focus time survived meetings last week, per team.
+pub async fn send_focus_time_report(db: &Database, company: &Company) -> Result<()> {
+    let to = Utc::now();
+    let from = to - Duration::weeks(1);
+
+    // The protected and surviving focus time per team.
+    let mut teams: BTreeMap<String, (Duration, Duration)> = Default::default();
+    for user in Users::get_from_db(db, company.id).await? {
+        if user.focus_blocks.is_empty() || user.is_system_account() {
+            continue;
+        }
+
+        let intervals = focus_intervals(&user.parsed_focus_blocks(), &user.parsed_focus_time_zone(), from, to);
+        if intervals.is_empty() {
+            continue;
+        }
+
+        let gcal = company
+            .authenticate_google_calendar_with_service_account(&user.email)
+            .await?;
+        let events = match gcal
+            .events()
+            .list_all(
+                &user.email, // Calendar id.
+                "",          // iCalID
+                0,           // Max attendees, set to 0 to ignore.
+                google_calendar::types::OrderBy::StartTime,
+                &[],                // private_extended_property
+                "",                 // q
+                &[],                // shared_extended_property
+                false,              // show_deleted
+                false,              // show_hidden_invitations
+                true,               // single_events
+                &to.to_rfc3339(),   // time_max
+                &from.to_rfc3339(), // time_min
+                "",                 // time_zone
+                "",                 // updated_min
+            )
+            .await
+        {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("getting calendar events for `{}` failed: {}", user.username, e);
+                continue;
+            }
+        };
+
+        let meetings: Vec<(DateTime<Utc>, DateTime<Utc>)> =
+            events.iter().filter_map(|e| busy_interval(&user, e)).collect();
+
+        let protected = intervals.iter().fold(Duration::zero(), |total, i| total + (i.1 - i.0));
+        let survived = surviving_focus_time(&intervals, &meetings);
+
+        let team = if user.department.is_empty() {
+            "No team".to_string()
+        } else {
+            user.department.to_string()
+        };
+        let totals = teams.entry(team).or_insert((Duration::zero(), Duration::zero()));
+        totals.0 = totals.0 + protected;
+        totals.1 = totals.1 + survived;
+    }
+
+    if teams.is_empty() {
+        return Ok(());
+    }
+
+    let channel = match company.alerts_channel("") {
+        Some(channel) => channel,
+        None => {
+            warn!(
+                "company `{}` has no alerts channel, not posting the focus time report",
+                company.name
+            );
+            return Ok(());
+        }
+    };
+
+    let text = format_focus_time_report(&teams);
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: "Focus time last week".to_string(),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text,
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+fn format_focus_time_report(teams: &BTreeMap<String, (Duration, Duration)>) -> String {
+    teams
+        .iter()
+        .map(|(team, (protected, survived))| {
+            let percent = if protected.num_minutes() > 0 {
+                survived.num_minutes() * 100 / protected.num_minutes()
+            } else {
+                100
+            };
+            format!(
+                "*{}*: {:.1}h of {:.1}h of focus time survived ({}%)",
+                team,
+                survived.num_minutes() as f64 / 60.0,
+                protected.num_minutes() as f64 / 60.0,
+                percent
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_focus_block() {
+        assert_eq!(
+            FocusBlock {
+                weekday: Weekday::Wed,
+                start: NaiveTime::from_hms(9, 0, 0),
+                end: NaiveTime::from_hms(17, 0, 0),
+            },
+            FocusBlock::from_str("Wednesday").unwrap()
+        );
+        assert_eq!(
+            FocusBlock {
+                weekday: Weekday::Tue,
+                start: NaiveTime::from_hms(9, 30, 0),
+                end: NaiveTime::from_hms(12, 0, 0),
+            },
+            FocusBlock::from_str("tue 09:30-12:00").unwrap()
+        );
+        assert!(FocusBlock::from_str("Someday").is_err());
+        assert!(FocusBlock::from_str("Mon 12:00-09:00").is_err());
+    }
+
+    #[test]
+    fn test_focus_intervals_and_surviving_time() {
+        let blocks = vec![FocusBlock::from_str("Wed 09:00-12:00").unwrap()];
+        let tz = chrono_tz::America::Los_Angeles;
+        // Monday 2023-02-06 to Monday 2023-02-13.
+        let from = Utc.ymd(2023, 2, 6).and_hms(8, 0, 0);
+        let to = Utc.ymd(2023, 2, 13).and_hms(8, 0, 0);
+
+        let intervals = focus_intervals(&blocks, &tz, from, to);
+        // 09:00 PST is 17:00 UTC.
+        assert_eq!(
+            vec![(
+                Utc.ymd(2023, 2, 8).and_hms(17, 0, 0),
+                Utc.ymd(2023, 2, 8).and_hms(20, 0, 0)
+            )],
+            intervals
+        );
+
+        let meetings = vec![
+            (
+                Utc.ymd(2023, 2, 8).and_hms(17, 30, 0),
+                Utc.ymd(2023, 2, 8).and_hms(18, 30, 0),
+            ),
+            // Overlaps the meeting above, so only 30 more minutes are lost.
+            (
+                Utc.ymd(2023, 2, 8).and_hms(18, 0, 0),
+                Utc.ymd(2023, 2, 8).and_hms(19, 0, 0),
+            ),
+            // Outside of the focus block.
+            (
+                Utc.ymd(2023, 2, 9).and_hms(17, 0, 0),
+                Utc.ymd(2023, 2, 9).and_hms(18, 0, 0),
+            ),
+        ];
+        assert_eq!(Duration::minutes(90), surviving_focus_time(&intervals, &meetings));
+    }
+
+    #[test]
+    fn test_format_focus_time_report() {
+        let mut teams: BTreeMap<String, (Duration, Duration)> = Default::default();
+        teams.insert("Hardware".to_string(), (Duration::hours(8), Duration::hours(6)));
+        teams.insert("Software".to_string(), (Duration::hours(4), Duration::hours(4)));
+
+        assert_eq!(
+            "*Hardware*: 6.0h of 8.0h of focus time survived (75%)\n*Software*: 4.0h of 4.0h of focus time survived (100%)",
+            format_focus_time_report(&teams)
+        );
+    }
+}
@@ -0,0 +1,749 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    applicant_reviews::NewApplicantReview,
+    applicant_status::Status,
+    applicants::{Applicant, NewApplicant},
+    application_form::ApplicationForm,
+    companies::Company,
+    db::Database,
+    schema::{applicants, greenhouse_applications},
+};
+
+static HARVEST_ENDPOINT: &str = "https://harvest.greenhouse.io/v1/";
+
+/// The prefix of the sheet id of the applicants we import from Greenhouse, followed by the id
+/// of their application. Applicants are matched on their email and sheet id, so every
+/// application gets its own applicant.
+pub(crate) static GREENHOUSE_SHEET_ID_PREFIX: &str = "greenhouse-";
+
+/// The type of the rejection reasons for the candidates who withdrew.
+static REJECTION_TYPE_WITHDREW: &str = "They rejected us";
+/// The type of the rejection reasons for the candidates we declined.
+static REJECTION_TYPE_DECLINED: &str = "We rejected them";
+
+/// A minimal client for the Greenhouse Harvest API. We only use it for syncing candidates and
+/// their scorecards with our applicants, so it only implements what we need for that.
+pub struct Greenhouse {
+    client: reqwest::Client,
+    api_key: String,
+    /// The Greenhouse user the changes we push are made as, they are not pushed if it is empty.
+    on_behalf_of: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GreenhouseValue {
+    #[serde(default)]
+    pub value: String,
+    #[serde(default, rename = "type")]
+    pub value_type: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GreenhouseRef {
+    pub id: i64,
+    #[serde(default)]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GreenhouseRejectionReason {
+    pub id: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, rename = "type")]
+    pub reason_type: Option<GreenhouseRef>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GreenhouseApplication {
+    pub id: i64,
+    pub candidate_id: i64,
+    #[serde(default)]
+    pub applied_at: Option<DateTime<Utc>>,
+    /// One of `active`, `rejected`, `hired` or `converted`.
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub jobs: Vec<GreenhouseRef>,
+    #[serde(default)]
+    pub current_stage: Option<GreenhouseRef>,
+    #[serde(default)]
+    pub rejection_reason: Option<GreenhouseRejectionReason>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GreenhouseAttachment {
+    #[serde(default)]
+    pub filename: String,
+    #[serde(default)]
+    pub url: String,
+    /// One of `resume`, `cover_letter`, `admin_only` or `offer_packet`.
+    #[serde(default, rename = "type")]
+    pub attachment_type: String,
+}
+
+/// A candidate in Greenhouse.
+/// FROM: https://developers.greenhouse.io/harvest.html#candidates
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GreenhouseCandidate {
+    pub id: i64,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub last_name: String,
+    #[serde(default)]
+    pub emails: Vec<GreenhouseValue>,
+    #[serde(default)]
+    pub phone_numbers: Vec<GreenhouseValue>,
+    #[serde(default)]
+    pub addresses: Vec<GreenhouseValue>,
+    #[serde(default)]
+    pub website_addresses: Vec<GreenhouseValue>,
+    #[serde(default)]
+    pub social_media_addresses: Vec<GreenhouseValue>,
+    #[serde(default)]
+    pub attachments: Vec<GreenhouseAttachment>,
+    #[serde(default)]
+    pub applications: Vec<GreenhouseApplication>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GreenhouseInterviewer {
+    pub id: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GreenhouseScorecardQuestion {
+    #[serde(default)]
+    pub question: String,
+    #[serde(default)]
+    pub answer: String,
+}
+
+/// The interview feedback of an interviewer.
+/// FROM: https://developers.greenhouse.io/harvest.html#scorecards
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GreenhouseScorecard {
+    pub id: i64,
+    pub application_id: i64,
+    #[serde(default)]
+    pub interview: String,
+    #[serde(default)]
+    pub submitted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub interviewer: Option<GreenhouseInterviewer>,
+    /// One of `definitely_not`, `no`, `yes`, `strong_yes` or `no_decision`.
+    #[serde(default)]
+    pub overall_recommendation: String,
+    #[serde(default)]
+    pub questions: Vec<GreenhouseScorecardQuestion>,
+}
+
+/// Return the url of the next page from the `Link` header of a Harvest response.
+fn next_page_url(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let (url, rel) = part.split_once(';')?;
+        if rel.trim() != "rel=\"next\"" {
+            return None;
+        }
+
+        Some(url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+impl Greenhouse {
+    pub fn new(api_key: &str, on_behalf_of: &str) -> Self {
+        Greenhouse {
+            client: reqwest::Client::new(),
+            api_key: api_key.to_string(),
+            on_behalf_of: on_behalf_of.to_string(),
+        }
+    }
+
+    async fn get_all<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>> {
+        let mut all: Vec<T> = Default::default();
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut url = format!("{}{}{}per_page=500", HARVEST_ENDPOINT, path, separator);
+        loop {
+            let resp = self.client.get(&url).basic_auth(&self.api_key, Some("")).send().await?;
+            let status = resp.status();
+            if !status.is_success() {
+                bail!(
+                    "greenhouse request to {} failed: status code: {}, body: {}",
+                    url,
+                    status,
+                    resp.text().await?
+                );
+            }
+
+            let next = resp
+                .headers()
+                .get(reqwest::header::LINK)
+                .and_then(|l| l.to_str().ok())
+                .and_then(next_page_url);
+            let page: Vec<T> = resp.json().await?;
+            all.extend(page);
+
+            match next {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+
+        Ok(all)
+    }
+
+    async fn post(&self, path: &str, body: &serde_json::Value) -> Result<()> {
+        if self.on_behalf_of.is_empty() {
+            bail!("no greenhouse user to make the changes as");
+        }
+
+        let url = format!("{}{}", HARVEST_ENDPOINT, path);
+        let resp = self
+            .client
+            .post(&url)
+            .basic_auth(&self.api_key, Some(""))
+            .header("On-Behalf-Of", &self.on_behalf_of)
+            .json(body)
+            .send()
+            .await?;
+        let status = resp.status();
+        if !status.is_success() {
+            bail!(
+                "greenhouse request to {} failed: status code: {}, body: {}",
+                url,
+                status,
+                resp.text().await?
+            );
+        }
+
+        Ok(())
+    }
+
+    /// List all the candidates, with their applications.
+    pub async fn list_candidates(&self) -> Result<Vec<GreenhouseCandidate>> {
+        self.get_all("candidates").await
+    }
+
+    /// List all the scorecards submitted by interviewers.
+    pub async fn list_scorecards(&self) -> Result<Vec<GreenhouseScorecard>> {
+        self.get_all("scorecards").await
+    }
+
+    /// List the stages of the interview plan of a job, in order.
+    pub async fn list_job_stages(&self, job_id: i64) -> Result<Vec<GreenhouseRef>> {
+        self.get_all(&format!("jobs/{}/stages", job_id)).await
+    }
+
+    pub async fn list_rejection_reasons(&self) -> Result<Vec<GreenhouseRejectionReason>> {
+        self.get_all("rejection_reasons").await
+    }
+
+    pub async fn move_application(&self, application_id: i64, from_stage_id: i64, to_stage_id: i64) -> Result<()> {
+        self.post(
+            &format!("applications/{}/move", application_id),
+            &json!({ "from_stage_id": from_stage_id, "to_stage_id": to_stage_id }),
+        )
+        .await
+    }
+
+    pub async fn reject_application(&self, application_id: i64, rejection_reason_id: i64) -> Result<()> {
+        self.post(
+            &format!("applications/{}/reject", application_id),
+            &json!({ "rejection_reason_id": rejection_reason_id }),
+        )
+        .await
+    }
+
+    pub async fn hire_application(&self, application_id: i64) -> Result<()> {
+        self.post(&format!("applications/{}/hire", application_id), &json!({}))
+            .await
+    }
+}
+
+/// Return the status of our applicants for a stage of a Greenhouse interview plan. Teams name
+/// their stages as they like, so we go with the words they usually use.
+pub fn status_from_greenhouse_stage(stage: &str) -> Status {
+    let stage = stage.to_lowercase();
+    if stage.contains("offer") {
+        Status::GivingOffer
+    } else if stage.contains("debrief") {
+        Status::Debrief
+    } else if stage.contains("interview") || stage.contains("onsite") || stage.contains("face to face") {
+        Status::Interviewing
+    } else if stage.contains("screen") || stage.contains("take home") || stage.contains("assessment") {
+        Status::NextSteps
+    } else {
+        Status::NeedsToBeTriaged
+    }
+}
+
+/// Return the status of our applicants for a Greenhouse application.
+pub fn status_from_greenhouse(application: &GreenhouseApplication) -> Status {
+    match application.status.as_str() {
+        "hired" => Status::Hired,
+        "rejected" => {
+            let withdrew = application
+                .rejection_reason
+                .as_ref()
+                .and_then(|r| r.reason_type.as_ref())
+                .map(|t| t.name == REJECTION_TYPE_WITHDREW)
+                .unwrap_or_default();
+            if withdrew {
+                Status::Withdrawn
+            } else {
+                Status::Declined
+            }
+        }
+        _ => application
+            .current_stage
+            .as_ref()
+            .map(|s| status_from_greenhouse_stage(&s.name))
+            .unwrap_or_default(),
+    }
+}
+
+/// Return the evaluation of our reviews for the overall recommendation of a scorecard.
+pub fn evaluation_from_greenhouse(recommendation: &str) -> String {
+    match recommendation {
+        "strong_yes" => "Emphatic Yes: strong yes in Greenhouse",
+        "yes" => "Yes: yes in Greenhouse",
+        "no" => "Pass: no in Greenhouse",
+        "definitely_not" => "No: definitely not in Greenhouse",
+        _ => "N/A: no decision in Greenhouse",
+    }
+    .to_string()
+}
+
+/// What to do with the status of an applicant, once we know its status in Greenhouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GreenhouseStatusSync {
+    Unchanged,
+    /// Greenhouse moved the application, we move the applicant.
+    Pull(Status),
+    /// We moved the applicant, we move the application in Greenhouse.
+    Push(Status),
+}
+
+/// Compare the statuses of an applicant and of its application in Greenhouse with the one they
+/// had when we last synced them. Greenhouse wins when both moved, since the team uses it.
+pub fn greenhouse_status_sync(synced: Option<Status>, local: Status, remote: Status) -> GreenhouseStatusSync {
+    match synced {
+        // We never synced the application, it comes from Greenhouse.
+        None if local != remote => GreenhouseStatusSync::Pull(remote),
+        None => GreenhouseStatusSync::Unchanged,
+        Some(synced) if remote != synced => GreenhouseStatusSync::Pull(remote),
+        Some(synced) if local != synced => GreenhouseStatusSync::Push(local),
+        Some(_) => GreenhouseStatusSync::Unchanged,
+    }
+}
+
+/// What we last synced of a Greenhouse application.
+#[derive(Debug, Queryable, PartialEq, Clone)]
+pub struct GreenhouseApplicationSync {
+    pub id: i32,
+    pub greenhouse_application_id: i64,
+    pub greenhouse_candidate_id: i64,
+    pub applicant_id: i32,
+    pub synced_status: String,
+    pub synced_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone)]
+#[diesel(table_name = greenhouse_applications)]
+struct NewGreenhouseApplicationSync {
+    greenhouse_application_id: i64,
+    greenhouse_candidate_id: i64,
+    applicant_id: i32,
+    synced_status: String,
+    synced_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+async fn get_greenhouse_application_syncs(
+    db: &Database,
+    cio_company_id: i32,
+) -> Result<BTreeMap<i64, GreenhouseApplicationSync>> {
+    let syncs = greenhouse_applications::dsl::greenhouse_applications
+        .filter(greenhouse_applications::dsl::cio_company_id.eq(cio_company_id))
+        .load_async::<GreenhouseApplicationSync>(db.pool())
+        .await?;
+
+    Ok(syncs.into_iter().map(|s| (s.greenhouse_application_id, s)).collect())
+}
+
+async fn set_greenhouse_application_sync(
+    db: &Database,
+    application: &GreenhouseApplication,
+    applicant: &Applicant,
+    status: Status,
+) -> Result<()> {
+    let sync = NewGreenhouseApplicationSync {
+        greenhouse_application_id: application.id,
+        greenhouse_candidate_id: application.candidate_id,
+        applicant_id: applicant.id,
+        synced_status: status.to_string(),
+        synced_at: Utc::now(),
+        cio_company_id: applicant.cio_company_id,
+    };
+
+    diesel::insert_into(greenhouse_applications::table)
+        .values(sync.clone())
+        .on_conflict((
+            greenhouse_applications::dsl::cio_company_id,
+            greenhouse_applications::dsl::greenhouse_application_id,
+        ))
+        .do_update()
+        .set(sync)
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Convert a Greenhouse application of a candidate to an applicant.
+fn new_applicant_from_greenhouse(
+    candidate: &GreenhouseCandidate,
+    application: &GreenhouseApplication,
+    cio_company_id: i32,
+) -> NewApplicant {
+    let first = |values: &[GreenhouseValue]| values.first().map(|v| v.value.to_string()).unwrap_or_default();
+    let social = |domain: &str| {
+        candidate
+            .social_media_addresses
+            .iter()
+            .chain(candidate.website_addresses.iter())
+            .find(|v| v.value.contains(domain))
+            .map(|v| v.value.to_string())
+            .unwrap_or_default()
+    };
+    let attachment = |attachment_type: &str| {
+        candidate
+            .attachments
+            .iter()
+            .find(|a| a.attachment_type == attachment_type)
+            .map(|a| a.url.to_string())
+            .unwrap_or_default()
+    };
+
+    let form = ApplicationForm {
+        name: format!("{} {}", candidate.first_name, candidate.last_name)
+            .trim()
+            .to_string(),
+        email: first(&candidate.emails),
+        phone: first(&candidate.phone_numbers),
+        role: application.jobs.first().map(|j| j.name.to_string()).unwrap_or_default(),
+        interested_in: Default::default(),
+        location: first(&candidate.addresses),
+        github: social("github.com"),
+        linkedin: social("linkedin.com"),
+        portfolio: Default::default(),
+        website: candidate
+            .website_addresses
+            .iter()
+            .find(|v| v.value_type == "personal")
+            .map(|v| v.value.to_string())
+            .unwrap_or_default(),
+        resume: attachment("resume"),
+        materials: attachment("cover_letter"),
+        portfolio_pdf: Default::default(),
+        locale: Default::default(),
+        cio_company_id,
+    };
+
+    let mut applicant: NewApplicant = form.into();
+    applicant.sheet_id = format!("{}{}", GREENHOUSE_SHEET_ID_PREFIX, application.id);
+    applicant.status = status_from_greenhouse(application).to_string();
+    applicant.submitted_time = application.applied_at.unwrap_or_else(Utc::now);
+
+    applicant
+}
+
+/// Push the status of an applicant to its application in Greenhouse.
+async fn push_status_to_greenhouse(
+    greenhouse: &Greenhouse,
+    job_stages: &mut BTreeMap<i64, Vec<GreenhouseRef>>,
+    rejection_reasons: &[GreenhouseRejectionReason],
+    application: &GreenhouseApplication,
+    status: Status,
+) -> Result<()> {
+    match status {
+        Status::Hired => greenhouse.hire_application(application.id).await,
+        Status::Declined | Status::Withdrawn => {
+            let reason_type = if status == Status::Withdrawn {
+                REJECTION_TYPE_WITHDREW
+            } else {
+                REJECTION_TYPE_DECLINED
+            };
+            let reason = rejection_reasons.iter().find(|r| {
+                r.reason_type
+                    .as_ref()
+                    .map(|t| t.name == reason_type)
+                    .unwrap_or_default()
+            });
+            match reason {
+                Some(reason) => greenhouse.reject_application(application.id, reason.id).await,
+                None => bail!("greenhouse has no rejection reason of type `{}`", reason_type),
+            }
+        }
+        _ => {
+            let (job, from) = match (application.jobs.first(), &application.current_stage) {
+                (Some(job), Some(from)) => (job, from),
+                _ => bail!("greenhouse application {} is not in a stage of a job", application.id),
+            };
+            if !job_stages.contains_key(&job.id) {
+                job_stages.insert(job.id, greenhouse.list_job_stages(job.id).await?);
+            }
+
+            let to = job_stages[&job.id]
+                .iter()
+                .find(|s| status_from_greenhouse_stage(&s.name) == status);
+            match to {
+                Some(to) => greenhouse.move_application(application.id, from.id, to.id).await,
+                None => bail!(
+                    "job `{}` has no stage in greenhouse for `{}`",
+                    job.name,
+                    status.to_string()
+                ),
+            }
+        }
+    }
+}
+
+/// Sync the candidates of Greenhouse with our applicants, and their scorecards with our
+/// reviews. The changes to the status of our applicants are pushed back to Greenhouse, so the
+/// teams using it can keep our automation.
+pub async fn refresh_greenhouse(db: &Database, company: &Company) -> Result<()> {
+    if company.greenhouse_api_key.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let greenhouse = company.authenticate_greenhouse();
+    let candidates = greenhouse.list_candidates().await?;
+    let syncs = get_greenhouse_application_syncs(db, company.id).await?;
+    let rejection_reasons = if company.greenhouse_user_id.is_empty() {
+        Default::default()
+    } else {
+        greenhouse.list_rejection_reasons().await?
+    };
+    let mut job_stages: BTreeMap<i64, Vec<GreenhouseRef>> = BTreeMap::new();
+
+    let mut applicants_by_application: BTreeMap<i64, Applicant> = BTreeMap::new();
+    for candidate in &candidates {
+        for application in &candidate.applications {
+            // The prospects are not applying to a job yet.
+            if application.jobs.is_empty() {
+                continue;
+            }
+
+            let new_applicant = new_applicant_from_greenhouse(candidate, application, company.id);
+            if new_applicant.email.is_empty() {
+                warn!("greenhouse candidate {} has no email, skipping", candidate.id);
+                continue;
+            }
+
+            let existing = applicants::dsl::applicants
+                .filter(applicants::dsl::cio_company_id.eq(company.id))
+                .filter(applicants::dsl::sheet_id.eq(new_applicant.sheet_id.to_string()))
+                .first_async::<Applicant>(db.pool())
+                .await
+                .ok();
+            let remote = status_from_greenhouse(application);
+            let (mut applicant, synced) = match existing {
+                Some(applicant) => {
+                    let synced = syncs
+                        .get(&application.id)
+                        .and_then(|s| Status::from_str(&s.synced_status).ok());
+                    (applicant, synced)
+                }
+                None => {
+                    info!(
+                        "importing greenhouse application {} of {}",
+                        application.id, new_applicant.name
+                    );
+                    (new_applicant.upsert(db).await?, Some(remote))
+                }
+            };
+
+            let local = Status::from_str(&applicant.status).unwrap_or_default();
+            let status = match greenhouse_status_sync(synced, local, remote) {
+                GreenhouseStatusSync::Unchanged => local,
+                GreenhouseStatusSync::Pull(status) => {
+                    info!(
+                        "greenhouse moved {} for {} to {}",
+                        applicant.name,
+                        applicant.role,
+                        status.to_string()
+                    );
+                    applicant.status = status.to_string();
+                    applicant = applicant.update(db).await?;
+                    status
+                }
+                GreenhouseStatusSync::Push(status) => {
+                    match push_status_to_greenhouse(
+                        &greenhouse,
+                        &mut job_stages,
+                        &rejection_reasons,
+                        application,
+                        status,
+                    )
+                    .await
+                    {
+                        Ok(()) => {
+                            info!(
+                                "moved {} for {} to {} in greenhouse",
+                                applicant.name,
+                                applicant.role,
+                                status.to_string()
+                            );
+                            status
+                        }
+                        Err(e) => {
+                            // Try again on the next sync.
+                            warn!("could not move {} in greenhouse: {}", applicant.name, e);
+                            remote
+                        }
+                    }
+                }
+            };
+
+            set_greenhouse_application_sync(db, application, &applicant, status).await?;
+            applicants_by_application.insert(application.id, applicant);
+        }
+    }
+
+    for scorecard in greenhouse.list_scorecards().await? {
+        if scorecard.submitted_at.is_none() {
+            // The interviewer is still writing it.
+            continue;
+        }
+        let applicant = match applicants_by_application.get(&scorecard.application_id) {
+            Some(applicant) if !applicant.airtable_record_id.is_empty() => applicant,
+            _ => continue,
+        };
+        let reviewer = match &scorecard.interviewer {
+            Some(interviewer) if !interviewer.email.is_empty() => interviewer.email.to_string(),
+            _ => continue,
+        };
+
+        let notes = scorecard
+            .questions
+            .iter()
+            .filter(|q| !q.answer.trim().is_empty())
+            .map(|q| format!("{}\n{}", q.question, q.answer.trim()))
+            .collect::<Vec<String>>()
+            .join("\n\n");
+
+        let review = NewApplicantReview {
+            name: format!("Greenhouse scorecard {}", scorecard.id),
+            value_reflected: Default::default(),
+            value_violated: Default::default(),
+            values_in_tension: Default::default(),
+            evaluation: evaluation_from_greenhouse(&scorecard.overall_recommendation),
+            rationale: Default::default(),
+            notes: format!("{}\n\n{}", scorecard.interview, notes).trim().to_string(),
+            reviewer,
+            applicant: vec![applicant.airtable_record_id.to_string()],
+            link_to_leaderboard: Default::default(),
+            cio_company_id: company.id,
+        };
+        let mut review = review.upsert(db).await?;
+        review.expand(db).await?;
+        review.update(db).await?;
+    }
+
+    info!("synced {} greenhouse applications", applicants_by_application.len());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        greenhouse_status_sync, next_page_url, status_from_greenhouse, GreenhouseApplication, GreenhouseRef,
+        GreenhouseRejectionReason, GreenhouseStatusSync,
+    };
+    use crate::applicant_status::Status;
+
+    #[test]
+    fn test_next_page_url() {
+        let link = "<https://harvest.greenhouse.io/v1/candidates?page=2&per_page=500>; rel=\"next\", \
+                    <https://harvest.greenhouse.io/v1/candidates?page=9&per_page=500>; rel=\"last\"";
+        assert_eq!(
+            Some("https://harvest.greenhouse.io/v1/candidates?page=2&per_page=500".to_string()),
+            next_page_url(link)
+        );
+        assert_eq!(
+            None,
+            next_page_url("<https://harvest.greenhouse.io/v1/candidates?page=1>; rel=\"prev\"")
+        );
+    }
+
+    #[test]
+    fn test_status_from_greenhouse() {
+        let mut application = GreenhouseApplication {
+            status: "active".to_string(),
+            current_stage: Some(GreenhouseRef {
+                id: 1,
+                name: "Onsite Interview".to_string(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(Status::Interviewing, status_from_greenhouse(&application));
+
+        application.status = "rejected".to_string();
+        assert_eq!(Status::Declined, status_from_greenhouse(&application));
+
+        application.rejection_reason = Some(GreenhouseRejectionReason {
+            id: 2,
+            name: "Accepted another offer".to_string(),
+            reason_type: Some(GreenhouseRef {
+                id: 3,
+                name: "They rejected us".to_string(),
+            }),
+        });
+        assert_eq!(Status::Withdrawn, status_from_greenhouse(&application));
+    }
+
+    #[test]
+    fn test_greenhouse_status_sync() {
+        use Status::*;
+
+        assert_eq!(
+            GreenhouseStatusSync::Pull(Interviewing),
+            greenhouse_status_sync(Some(NextSteps), NextSteps, Interviewing)
+        );
+        assert_eq!(
+            GreenhouseStatusSync::Push(Declined),
+            greenhouse_status_sync(Some(NextSteps), Declined, NextSteps)
+        );
+        // Greenhouse wins when both moved.
+        assert_eq!(
+            GreenhouseStatusSync::Pull(GivingOffer),
+            greenhouse_status_sync(Some(Interviewing), Debrief, GivingOffer)
+        );
+        assert_eq!(
+            GreenhouseStatusSync::Unchanged,
+            greenhouse_status_sync(Some(Debrief), Debrief, Debrief)
+        );
+        assert_eq!(
+            GreenhouseStatusSync::Pull(Hired),
+            greenhouse_status_sync(None, NeedsToBeTriaged, Hired)
+        );
+    }
+}
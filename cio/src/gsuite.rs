@@ -1,6 +1,8 @@
 use std::{collections::HashMap, time};
 
 use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl};
 use gsuite_api::{
     types::{
         Building as GSuiteBuilding, BuildingAddress, CalendarResource as GSuiteCalendarResource, Group as GSuiteGroup,
@@ -10,6 +12,8 @@ use gsuite_api::{
     Client as GSuite,
 };
 use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
@@ -17,6 +21,8 @@ use crate::{
     configs::{Building, Group, Resource, User},
     db::Database,
     providers::{ProviderReadOps, ProviderWriteOps},
+    rate_limit::{acquire, Provider},
+    schema::gsuite_aliases,
     utils::generate_password,
 };
 
@@ -217,47 +223,184 @@ pub async fn update_gsuite_user(gu: &GSuiteUser, user: &User, change_password: b
     gsuite_user
 }
 
+/// An alias we added to a GSuite user or group. We only ever remove the aliases we added, the
+/// ones people made by hand stay.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct GSuiteAlias {
+    pub id: i32,
+    /// The full address of the alias, lowercased.
+    pub alias: String,
+    /// The primary email of the user or group that has the alias.
+    pub email: String,
+    pub is_group: bool,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone)]
+#[diesel(table_name = gsuite_aliases)]
+struct NewGSuiteAlias {
+    alias: String,
+    email: String,
+    is_group: bool,
+    cio_company_id: i32,
+}
+
+/// Get the aliases we added in GSuite, by alias.
+async fn get_gsuite_aliases(db: &Database, company: &Company) -> Result<HashMap<String, GSuiteAlias>> {
+    Ok(gsuite_aliases::dsl::gsuite_aliases
+        .filter(gsuite_aliases::dsl::cio_company_id.eq(company.id))
+        .load_async::<GSuiteAlias>(db.pool())
+        .await?
+        .into_iter()
+        .map(|a| (a.alias.to_string(), a))
+        .collect())
+}
+
+/// Record that we added an alias to a user or group, or that we moved it there.
+async fn record_gsuite_alias(db: &Database, company: &Company, alias: &str, email: &str, is_group: bool) -> Result<()> {
+    let alias = NewGSuiteAlias {
+        alias: alias.to_lowercase(),
+        email: email.to_lowercase(),
+        is_group,
+        cio_company_id: company.id,
+    };
+    diesel::insert_into(gsuite_aliases::table)
+        .values(&alias)
+        .on_conflict((gsuite_aliases::dsl::cio_company_id, gsuite_aliases::dsl::alias))
+        .do_update()
+        .set(&alias)
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+async fn forget_gsuite_alias(db: &Database, company: &Company, alias: &str) -> Result<()> {
+    diesel::delete(
+        gsuite_aliases::dsl::gsuite_aliases
+            .filter(gsuite_aliases::dsl::cio_company_id.eq(company.id))
+            .filter(gsuite_aliases::dsl::alias.eq(alias.to_lowercase())),
+    )
+    .execute_async(db.pool())
+    .await?;
+
+    Ok(())
+}
+
+/// Return the aliases of a user or group that we added, and that are not in our configs
+/// anymore. The other ones were made by hand, they are not ours to remove.
+fn stale_aliases(
+    current: &[String],
+    desired: &[String],
+    email: &str,
+    ours: &HashMap<String, GSuiteAlias>,
+) -> Vec<String> {
+    current
+        .iter()
+        .filter(|a| {
+            ours.get(&a.to_lowercase())
+                .map(|o| o.email.eq_ignore_ascii_case(email))
+                .unwrap_or(false)
+                && !desired.iter().any(|d| d.eq_ignore_ascii_case(a))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Remove an alias from the user or group we added it to.
+async fn remove_gsuite_alias(db: &Database, gsuite: &GSuite, company: &Company, alias: &GSuiteAlias) -> Result<()> {
+    acquire(Provider::Google).await;
+    let result = if alias.is_group {
+        gsuite.groups().aliases_delete(&alias.email, &alias.alias).await
+    } else {
+        gsuite.users().aliases_delete(&alias.email, &alias.alias).await
+    };
+    if let Err(e) = result {
+        if !e.to_string().contains("404") {
+            bail!(
+                "removing gsuite alias {} from {} failed: {}",
+                alias.alias,
+                alias.email,
+                e
+            );
+        }
+    }
+    forget_gsuite_alias(db, company, &alias.alias).await?;
+
+    info!("removed alias `{}` from GSuite `{}`", alias.alias, alias.email);
+    Ok(())
+}
+
+/// Add an alias to a user or group. If we gave the alias to someone else before, like a role
+/// address being handed over, it moves from them to its new owner right away.
+async fn add_gsuite_alias(
+    db: &Database,
+    gsuite: &GSuite,
+    company: &Company,
+    email: &str,
+    is_group: bool,
+    alias: &str,
+    ours: &HashMap<String, GSuiteAlias>,
+) -> Result<()> {
+    let new_alias = gsuite_api::types::Alias {
+        alias: alias.to_string(),
+        etag: Default::default(),
+        id: Default::default(),
+        kind: Default::default(),
+        primary_email: Default::default(),
+    };
+
+    let mut moved = false;
+    loop {
+        acquire(Provider::Google).await;
+        let result = if is_group {
+            gsuite.groups().aliases_insert(email, &new_alias).await
+        } else {
+            gsuite.users().aliases_insert(email, &new_alias).await
+        };
+        match result {
+            Ok(_) => break,
+            Err(e) if e.to_string().contains("Entity already exists") => {
+                match ours.get(&alias.to_lowercase()) {
+                    Some(previous) if !moved && !previous.email.eq_ignore_ascii_case(email) => {
+                        // We gave it to someone else, take it back from them.
+                        remove_gsuite_alias(db, gsuite, company, previous).await?;
+                        moved = true;
+                    }
+                    // It is already there.
+                    _ => break,
+                }
+            }
+            Err(e) => bail!("adding gsuite alias {} to {} failed: {}", alias, email, e),
+        }
+    }
+
+    record_gsuite_alias(db, company, alias, email, is_group).await
+}
+
 /// Update a user's aliases in GSuite to match our database.
 pub async fn update_user_aliases(
+    db: &Database,
     gsuite: &GSuite,
     u: &GSuiteUser,
     aliases: Vec<String>,
     company: &Company,
 ) -> Result<()> {
-    if aliases.is_empty() {
-        // Return early.
-        return Ok(());
-    }
-
     let mut formatted_aliases: Vec<String> = Default::default();
     for a in aliases {
         formatted_aliases.push(format!("{}@{}", a, company.gsuite_domain));
     }
 
-    // Update the user's aliases.
-    for alias in formatted_aliases {
-        match gsuite
-            .users()
-            .aliases_insert(
-                &u.primary_email,
-                &gsuite_api::types::Alias {
-                    alias: alias.to_string(),
-                    etag: Default::default(),
-                    id: Default::default(),
-                    kind: Default::default(),
-                    primary_email: Default::default(),
-                },
-            )
-            .await
-        {
-            Ok(_) => (),
-            Err(e) => {
-                if e.to_string().contains("Entity already exists") {
-                    // Ignore the error.
-                    continue;
-                }
-                bail!("updating gsuite user {} aliases failed: {}", u.primary_email, e);
-            }
+    let ours = get_gsuite_aliases(db, company).await?;
+
+    // Add the new aliases first, so the ones moving here from someone else are never missing.
+    for alias in &formatted_aliases {
+        add_gsuite_alias(db, gsuite, company, &u.primary_email, false, alias, &ours).await?;
+    }
+
+    for alias in stale_aliases(&u.aliases, &formatted_aliases, &u.primary_email, &ours) {
+        if let Some(alias) = ours.get(&alias.to_lowercase()) {
+            remove_gsuite_alias(db, gsuite, company, alias).await?;
         }
     }
 
@@ -300,37 +443,24 @@ pub async fn update_user_google_groups(gsuite: &GSuite, user: &User, company: &C
     Ok(())
 }
 
-/// Update a group's aliases in GSuite to match our configuration files.
-pub async fn update_group_aliases(gsuite: &GSuite, g: &GSuiteGroup) -> Result<()> {
-    if g.aliases.is_empty() {
-        // return early
-        return Ok(());
-    }
+/// Update a group's aliases in GSuite to match our configuration files, removing the ones we
+/// added that are not in them anymore.
+pub async fn update_group_aliases(
+    db: &Database,
+    gsuite: &GSuite,
+    g: &GSuiteGroup,
+    current: &[String],
+    company: &Company,
+) -> Result<()> {
+    let ours = get_gsuite_aliases(db, company).await?;
 
-    // Update the user's aliases.
     for alias in &g.aliases {
-        match gsuite
-            .groups()
-            .aliases_insert(
-                &g.email,
-                &gsuite_api::types::Alias {
-                    alias: alias.to_string(),
-                    etag: Default::default(),
-                    id: Default::default(),
-                    kind: Default::default(),
-                    primary_email: Default::default(),
-                },
-            )
-            .await
-        {
-            Ok(_) => (),
-            Err(e) => {
-                if e.to_string().contains("Entity already exists") {
-                    // Ignore the error.
-                    continue;
-                }
-                bail!("updating gsuite group {} aliases failed: {}", g.email, e);
-            }
+        add_gsuite_alias(db, gsuite, company, &g.email, true, alias, &ours).await?;
+    }
+
+    for alias in stale_aliases(current, &g.aliases, &g.email, &ours) {
+        if let Some(alias) = ours.get(&alias.to_lowercase()) {
+            remove_gsuite_alias(db, gsuite, company, alias).await?;
         }
     }
 
@@ -344,10 +474,12 @@ pub async fn update_google_group_settings(db: &Database, group: &Group, company:
 
     // Get the current group settings.
     let email = format!("{}@{}", group.name, company.gsuite_domain);
+    acquire(Provider::Google).await;
     let mut result = ggs.groups().get(google_groups_settings::types::Alt::Json, &email).await;
     if result.is_err() {
         // Try again.
         tokio::time::sleep(time::Duration::from_secs(1)).await;
+        acquire(Provider::Google).await;
         result = ggs.groups().get(google_groups_settings::types::Alt::Json, &email).await;
     }
     let mut settings = result?;
@@ -368,6 +500,7 @@ pub async fn update_google_group_settings(db: &Database, group: &Group, company:
     settings.who_can_contact_owner = "ALL_IN_DOMAIN_CAN_CONTACT".to_string();
 
     // Update the group with the given settings.
+    acquire(Provider::Google).await;
     let result2 = ggs
         .groups()
         .update(google_groups_settings::types::Alt::Json, &email, &settings)
@@ -375,6 +508,7 @@ pub async fn update_google_group_settings(db: &Database, group: &Group, company:
     if result2.is_err() {
         // Try again.
         tokio::time::sleep(time::Duration::from_secs(1)).await;
+        acquire(Provider::Google).await;
         ggs.groups()
             .update(google_groups_settings::types::Alt::Json, &email, &settings)
             .await?;
@@ -427,3 +561,51 @@ pub fn update_gsuite_calendar_resource(
 
     gsuite_resource
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{stale_aliases, GSuiteAlias};
+
+    #[test]
+    fn test_stale_aliases() {
+        let ours: HashMap<String, GSuiteAlias> = vec![
+            ("old@example.com", "jane@example.com"),
+            ("press@example.com", "alex@example.com"),
+        ]
+        .into_iter()
+        .map(|(alias, email)| {
+            (
+                alias.to_string(),
+                GSuiteAlias {
+                    id: 1,
+                    alias: alias.to_string(),
+                    email: email.to_string(),
+                    is_group: false,
+                    cio_company_id: 1,
+                },
+            )
+        })
+        .collect();
+        let current = vec![
+            "jane.doe@example.com".to_string(),
+            "Old@example.com".to_string(),
+            "press@example.com".to_string(),
+            "by-hand@example.com".to_string(),
+        ];
+
+        // Only the alias we gave Jane and took out of the configs goes, the one made by hand
+        // stays, and so does the one we gave someone else.
+        assert_eq!(
+            vec!["Old@example.com".to_string()],
+            stale_aliases(
+                &current,
+                &["jane.doe@example.com".to_string()],
+                "jane@example.com",
+                &ours
+            )
+        );
+        assert!(stale_aliases(&current, &current, "jane@example.com", &ours).is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{anyhow, bail, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// An email forwarded to us by the SendGrid inbound parse webhook.
+/// FROM: https://docs.sendgrid.com/for-developers/parsing-email/setting-up-the-inbound-parse-webhook
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InboundEmail {
+    #[serde(default)]
+    pub from: String,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub text: String,
+    /// The DKIM results of the email, ie. `{@example.com : pass}`.
+    #[serde(default)]
+    pub dkim: String,
+    /// The SPF result of the email, ie. `pass`.
+    #[serde(default)]
+    pub spf: String,
+    /// The address of the envelope sender, this is what SPF checks.
+    #[serde(default)]
+    pub envelope_from: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct Envelope {
+    #[serde(default)]
+    from: String,
+}
+
+impl InboundEmail {
+    /// Parse the multipart form data the inbound parse webhook posts. Attachments are skipped.
+    pub fn from_multipart(content_type: &str, body: &[u8]) -> Result<InboundEmail> {
+        if !content_type.trim().to_lowercase().starts_with("multipart/form-data") {
+            bail!("expected multipart form data, got `{}`", content_type);
+        }
+        let boundary = content_type
+            .split(';')
+            .map(|p| p.trim())
+            .find_map(|p| p.strip_prefix("boundary="))
+            .map(|b| b.trim_matches('"'))
+            .filter(|b| !b.is_empty())
+            .ok_or_else(|| anyhow!("content type `{}` has no boundary", content_type))?;
+
+        let body = String::from_utf8_lossy(body);
+        let mut email = InboundEmail::default();
+        for part in body.split(&format!("--{}", boundary)) {
+            let (headers, value) = match part.split_once("\r\n\r\n") {
+                Some(part) => part,
+                None => continue,
+            };
+
+            let disposition = headers
+                .lines()
+                .find(|l| l.to_lowercase().starts_with("content-disposition:"))
+                .unwrap_or_default();
+            if disposition.contains("filename=") {
+                continue;
+            }
+            let name = match disposition
+                .split(';')
+                .map(|p| p.trim())
+                .find_map(|p| p.strip_prefix("name="))
+            {
+                Some(name) => name.trim_matches('"'),
+                None => continue,
+            };
+            let value = value.strip_suffix("\r\n").unwrap_or(value).to_string();
+
+            match name {
+                "from" => email.from = value,
+                "subject" => email.subject = value,
+                "text" => email.text = value,
+                "dkim" => email.dkim = value,
+                "SPF" => email.spf = value,
+                "envelope" => {
+                    let envelope: Envelope = serde_json::from_str(&value).unwrap_or_default();
+                    email.envelope_from = envelope.from;
+                }
+                _ => (),
+            }
+        }
+
+        Ok(email)
+    }
+
+    /// The lowercased address in the From header, without the name.
+    pub fn from_address(&self) -> String {
+        address(&self.from)
+    }
+
+    /// Returns if the email is from the domain, or one of its subdomains.
+    pub fn is_from_domain(&self, domain: &str) -> bool {
+        is_aligned(&address_domain(&self.from), domain)
+    }
+
+    /// Returns if the sender in the From header is who they say they are. Anyone can put
+    /// anything in the From header, so we need a passing DKIM signature for the domain of the
+    /// sender, or a passing SPF check for an envelope sender on that domain.
+    pub fn is_authenticated(&self) -> bool {
+        let domain = address_domain(&self.from);
+        if domain.is_empty() {
+            return false;
+        }
+
+        let dkim_pass = self
+            .dkim
+            .trim_matches(|c| c == '{' || c == '}')
+            .split(',')
+            .filter_map(|result| result.split_once(':'))
+            .any(|(signed, result)| {
+                result.trim() == "pass" && is_aligned(&domain, signed.trim().trim_start_matches('@'))
+            });
+        let spf_pass =
+            self.spf.trim().eq_ignore_ascii_case("pass") && is_aligned(&domain, &address_domain(&self.envelope_from));
+
+        dkim_pass || spf_pass
+    }
+}
+
+/// Get the lowercased address out of a header like `Jane Doe <jane@example.com>`.
+pub fn address(header: &str) -> String {
+    let address = match (header.rfind('<'), header.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &header[start + 1..end],
+        _ => header,
+    };
+
+    address.trim().to_lowercase()
+}
+
+/// Get the lowercased domain out of a header like `Jane Doe <jane@example.com>`.
+pub fn address_domain(header: &str) -> String {
+    match address(header).rsplit_once('@') {
+        Some((_, domain)) => domain.to_string(),
+        None => String::new(),
+    }
+}
+
+/// Returns if the domain is the other domain, or one of its subdomains.
+fn is_aligned(domain: &str, other: &str) -> bool {
+    let other = other.trim().to_lowercase();
+    !other.is_empty() && (domain == other || domain.ends_with(&format!(".{}", other)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InboundEmail;
+
+    #[test]
+    fn test_from_multipart() {
+        let body = "--xYzZY\r\nContent-Disposition: form-data; name=\"from\"\r\n\r\nJane Doe \
+                    <Jane@Example.com>\r\n--xYzZY\r\nContent-Disposition: form-data; \
+                    name=\"subject\"\r\n\r\nRe: Hi\r\n--xYzZY\r\nContent-Disposition: form-data; \
+                    name=\"text\"\r\n\r\nline one\r\nline two\r\n--xYzZY\r\nContent-Disposition: form-data; \
+                    name=\"attachment1\"; filename=\"a.txt\"\r\nContent-Type: text/plain\r\n\r\nignored\r\n\
+                    --xYzZY\r\nContent-Disposition: form-data; name=\"dkim\"\r\n\r\n{@example.com : \
+                    pass}\r\n--xYzZY\r\nContent-Disposition: form-data; name=\"SPF\"\r\n\r\npass\r\n--xYzZY\r\n\
+                    Content-Disposition: form-data; \
+                    name=\"envelope\"\r\n\r\n{\"to\":[\"in@ours.com\"],\"from\":\"jane@example.com\"}\r\n--xYzZY--\r\n";
+
+        let email = InboundEmail::from_multipart("multipart/form-data; boundary=xYzZY", body.as_bytes()).unwrap();
+        assert_eq!(
+            InboundEmail {
+                from: "Jane Doe <Jane@Example.com>".to_string(),
+                subject: "Re: Hi".to_string(),
+                text: "line one\r\nline two".to_string(),
+                dkim: "{@example.com : pass}".to_string(),
+                spf: "pass".to_string(),
+                envelope_from: "jane@example.com".to_string(),
+            },
+            email
+        );
+        assert_eq!("jane@example.com", email.from_address());
+
+        assert!(InboundEmail::from_multipart("application/json", body.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_is_authenticated() {
+        let mut email = InboundEmail {
+            from: "Apple Store <order_acknowledgment@orders.apple.com>".to_string(),
+            dkim: "{@apple.com : pass, @sendgrid.net : pass}".to_string(),
+            ..Default::default()
+        };
+        assert!(email.is_authenticated());
+
+        // Someone else signing the email does not say anything about the sender.
+        email.dkim = "{@evil.com : pass}".to_string();
+        assert!(!email.is_authenticated());
+        email.dkim = "{@apple.com : fail}".to_string();
+        assert!(!email.is_authenticated());
+
+        email.spf = "pass".to_string();
+        email.envelope_from = "bounces@evil.com".to_string();
+        assert!(!email.is_authenticated());
+        email.envelope_from = "bounces@orders.apple.com".to_string();
+        assert!(email.is_authenticated());
+
+        assert!(!InboundEmail::default().is_authenticated());
+    }
+}
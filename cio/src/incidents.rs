@@ -0,0 +1,193 @@
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use log::info;
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::ThreadMessage;
+
+use crate::{
+    airtable::AIRTABLE_INCIDENTS_TABLE, companies::Company, core::UpdateAirtableRecord, db::Database,
+    schema::incidents, summarization::SummarizationProvider, utils::truncate,
+};
+
+static THREAD_SUMMARY_INSTRUCTIONS: &str = "You summarize Slack threads about incidents. Reply with a short title \
+on the first line, followed by a summary of what happened, the impact, and any follow-up items. Do not include \
+anything else.";
+
+/// The data type for an incident. Incidents are created from Slack threads with the
+/// summarize thread shortcut.
+#[db {
+    new_struct_name = "Incident",
+    airtable_base = "misc",
+    airtable_table = "AIRTABLE_INCIDENTS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "channel_id" = "String",
+        "thread_ts" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = incidents)]
+pub struct NewIncident {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub summary: String,
+    pub channel_id: String,
+    /// The timestamp of the parent message of the thread.
+    pub thread_ts: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub link: String,
+    #[serde(default)]
+    pub message_count: i32,
+    /// The Slack user ids of everyone who posted in the thread.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub participants: Vec<String>,
+    /// The Slack user id of the person who asked for the summary.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub summarized_by: String,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for an Incident.
+#[async_trait]
+impl UpdateAirtableRecord<Incident> for Incident {
+    async fn update_airtable_record(&mut self, _record: Incident) -> Result<()> {
+        // Airtable limits the size of long text fields.
+        self.summary = truncate(&self.summary, 100000);
+
+        Ok(())
+    }
+}
+
+/// Format the messages of a thread as a plain text transcript for the summarization provider.
+pub fn format_slack_thread(messages: &[ThreadMessage]) -> String {
+    messages
+        .iter()
+        .filter(|m| !m.text.trim().is_empty())
+        .map(|m| {
+            let author = if m.user.is_empty() { &m.bot_id } else { &m.user };
+            format!("<@{}>: {}", author, m.text.trim())
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Split the summary returned by the provider into a title and the rest of the summary.
+fn split_summary(summary: &str) -> (String, String) {
+    let summary = summary.trim();
+    match summary.split_once('\n') {
+        Some((title, rest)) => (
+            title.trim().trim_start_matches('#').trim().to_string(),
+            rest.trim().to_string(),
+        ),
+        None => (truncate(summary, 80), summary.to_string()),
+    }
+}
+
+/// Summarize a Slack thread, post the summary back to the thread, and record it as an incident.
+pub async fn summarize_slack_thread(
+    db: &Database,
+    company: &Company,
+    provider: &dyn SummarizationProvider,
+    channel_id: &str,
+    thread_ts: &str,
+    summarized_by: &str,
+) -> Result<Incident> {
+    let slack = company.authenticate_slack(db).await?;
+
+    let messages = slack.thread_messages(channel_id, thread_ts).await?;
+    if messages.is_empty() {
+        bail!("slack thread {} in {} has no messages", thread_ts, channel_id);
+    }
+
+    let summary = provider
+        .summarize(THREAD_SUMMARY_INSTRUCTIONS, &format_slack_thread(&messages))
+        .await?;
+    let (name, summary) = split_summary(&summary);
+
+    let mut participants: Vec<String> = messages
+        .iter()
+        .filter(|m| !m.user.is_empty())
+        .map(|m| m.user.to_string())
+        .collect();
+    participants.sort();
+    participants.dedup();
+
+    // The permalink is nice to have, don't fail the summary over it.
+    let link = slack.permalink(channel_id, thread_ts).await.unwrap_or_default();
+
+    slack
+        .post_thread_reply(channel_id, thread_ts, &format!("*Summary: {}*\n{}", name, summary))
+        .await?;
+
+    let incident = NewIncident {
+        name,
+        summary,
+        channel_id: channel_id.to_string(),
+        thread_ts: thread_ts.to_string(),
+        link,
+        message_count: messages.len() as i32,
+        participants,
+        summarized_by: summarized_by.to_string(),
+        cio_company_id: company.id,
+    }
+    .upsert(db)
+    .await?;
+
+    info!(
+        "summarized slack thread {} in {} as incident `{}`",
+        thread_ts, channel_id, incident.name
+    );
+
+    Ok(incident)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_slack_thread() {
+        let messages = vec![
+            ThreadMessage {
+                user: "U1".to_string(),
+                text: "the build rack is down".to_string(),
+                ts: "1.0".to_string(),
+                ..Default::default()
+            },
+            ThreadMessage {
+                user: "U2".to_string(),
+                text: " ".to_string(),
+                ts: "2.0".to_string(),
+                ..Default::default()
+            },
+            ThreadMessage {
+                bot_id: "B1".to_string(),
+                text: "paging on-call".to_string(),
+                ts: "3.0".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            "<@U1>: the build rack is down\n<@B1>: paging on-call",
+            format_slack_thread(&messages)
+        );
+    }
+
+    #[test]
+    fn test_split_summary() {
+        assert_eq!(
+            (
+                "Build rack outage".to_string(),
+                "The build rack lost power.".to_string()
+            ),
+            split_summary("# Build rack outage\n\nThe build rack lost power.\n")
+        );
+        assert_eq!(("Short".to_string(), "Short".to_string()), split_summary("Short"));
+    }
+}
@@ -0,0 +1,344 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{Datelike, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use google_calendar::Client as GoogleCalendar;
+use log::info;
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::ApplyConfig,
+    applicants::Applicant,
+    companies::Company,
+    configs::User,
+    db::Database,
+    interviews::{ApplicantInterview, NewApplicantInterview},
+    schema::users,
+};
+
+/// A stint at an employer, as people declare it, like `Acme (2016-2020)`. Someone who did not
+/// say when they left may still be there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Employment {
+    pub employer: String,
+    pub start: Option<i32>,
+    pub end: Option<i32>,
+}
+
+impl Employment {
+    pub fn parse(entry: &str) -> Self {
+        let entry = entry.trim();
+        let (employer, years) = match (entry.rfind('('), entry.ends_with(')')) {
+            (Some(i), true) => (&entry[..i], &entry[i + 1..entry.len() - 1]),
+            _ => (entry, ""),
+        };
+
+        let mut years = years.splitn(2, '-').map(|y| y.trim().parse::<i32>().ok());
+        let start = years.next().flatten();
+        let end = years.next().flatten();
+
+        Employment {
+            employer: employer.trim().to_string(),
+            start,
+            end,
+        }
+    }
+
+    /// Return the employer, in a way we can compare to the same employer written differently.
+    fn key(&self) -> String {
+        let name = self
+            .employer
+            .to_lowercase()
+            .replace(|c: char| !c.is_alphanumeric() && c != ' ', "");
+        name.split_whitespace()
+            .filter(|w| !["inc", "llc", "ltd", "corp", "corporation", "co"].contains(w))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Return if the stint ended within the last years, when we do not know it did not.
+    fn is_recent(&self, year: i32, years: i32) -> bool {
+        self.end.map(|end| end >= year - years).unwrap_or(true)
+    }
+}
+
+/// An interviewer who should not be on the panel of an applicant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanelConflict {
+    pub interviewer: String,
+    pub reason: String,
+}
+
+impl PanelConflict {
+    fn describe(&self) -> String {
+        format!("{}: {}", self.interviewer, self.reason)
+    }
+}
+
+/// Return if someone, like the referrer of an applicant, is a user. We take their email, any
+/// email at one of their aliases, or their username.
+fn is_user(user: &User, who: &str) -> bool {
+    let who = who.trim().to_lowercase();
+    if who.is_empty() {
+        return false;
+    }
+    if who == user.email.to_lowercase() {
+        return true;
+    }
+
+    let domain = user.email.rsplit('@').next().unwrap_or_default().to_lowercase();
+    let name = match who.split_once('@') {
+        Some((name, d)) if d == domain => name.to_string(),
+        Some(_) => return false,
+        None => who,
+    };
+
+    name == user.username.to_lowercase() || user.aliases.iter().any(|a| a.to_lowercase() == name)
+}
+
+/// Find the interviewers of a panel with a declared conflict of interest with an applicant:
+/// they referred the applicant, or worked at the same employer as them in the last years.
+pub fn panel_conflicts(applicant: &Applicant, interviewers: &[User], year: i32, years: i32) -> Vec<PanelConflict> {
+    let employments: Vec<Employment> = applicant
+        .previous_employers
+        .iter()
+        .map(|e| Employment::parse(e))
+        .filter(|e| !e.employer.is_empty() && e.is_recent(year, years))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for interviewer in interviewers {
+        if is_user(interviewer, &applicant.referred_by) {
+            conflicts.push(PanelConflict {
+                interviewer: interviewer.email.to_string(),
+                reason: "referred the applicant".to_string(),
+            });
+            continue;
+        }
+
+        let shared = interviewer
+            .previous_employers
+            .iter()
+            .map(|e| Employment::parse(e))
+            .find(|e| e.is_recent(year, years) && employments.iter().any(|a| a.key() == e.key()));
+        if let Some(employment) = shared {
+            conflicts.push(PanelConflict {
+                interviewer: interviewer.email.to_string(),
+                reason: format!(
+                    "worked at {} in the last {} years, like the applicant",
+                    employment.employer, years
+                ),
+            });
+        }
+    }
+
+    conflicts
+}
+
+/// Find the conflicts of interest of a panel with an applicant, with the employers and
+/// referrer recruiters filled in for them in Airtable. Check a panel with this before booking
+/// the interview, so nobody with a conflict is ever invited.
+pub async fn find_panel_conflicts(
+    db: &Database,
+    company: &Company,
+    config: &ApplyConfig,
+    applicant: &Applicant,
+    interviewers: &[String],
+) -> Result<Vec<PanelConflict>> {
+    let mut applicant = applicant.clone();
+    if let Some(existing) = applicant.get_existing_airtable_record(db).await {
+        applicant.previous_employers = existing.fields.previous_employers;
+        applicant.referred_by = existing.fields.referred_by;
+    }
+
+    let interviewers = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .filter(users::dsl::email.eq_any(interviewers.to_vec()))
+        .load_async::<User>(db.pool())
+        .await?;
+
+    Ok(panel_conflicts(
+        &applicant,
+        &interviewers,
+        Utc::now().year(),
+        config.conflict_employer_years(),
+    ))
+}
+
+/// Check the panel of an interview that is already on the calendar for conflicts of interest,
+/// and warn the recruiters about new ones. This catches the interviews booked by hand. If the
+/// company excludes conflicted interviewers, we also take them off the calendar event and the
+/// interview.
+#[allow(clippy::too_many_arguments)]
+pub async fn check_panel_conflicts(
+    db: &Database,
+    company: &Company,
+    config: &ApplyConfig,
+    gcal: &GoogleCalendar,
+    calendar_id: &str,
+    event: &mut google_calendar::types::Event,
+    applicant: &Applicant,
+    interview: &mut NewApplicantInterview,
+) -> Result<()> {
+    let known = match ApplicantInterview::get_from_db(db, interview.google_event_id.to_string()).await {
+        Some(existing) => existing.conflicts,
+        None => Default::default(),
+    };
+
+    // There is nothing to do about the panel of an interview that already happened.
+    if interview.start_time <= Utc::now() {
+        interview.conflicts = known;
+        return Ok(());
+    }
+
+    let conflicts = find_panel_conflicts(db, company, config, applicant, &interview.interviewers).await?;
+    interview.conflicts = conflicts.iter().map(|c| c.describe()).collect();
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+
+    let new: Vec<&PanelConflict> = conflicts.iter().filter(|c| !known.contains(&c.describe())).collect();
+
+    if config.exclude_conflicted_interviewers {
+        let before = event.attendees.len();
+        event
+            .attendees
+            .retain(|a| !conflicts.iter().any(|c| c.interviewer.eq_ignore_ascii_case(&a.email)));
+        if event.attendees.len() != before {
+            gcal.events()
+                .update(
+                    calendar_id,
+                    &event.id,
+                    0,     // conference data version
+                    0,     // max attendees, 0 to ignore
+                    false, // send notifications
+                    // The applicant does not need to know the panel changed.
+                    google_calendar::types::SendUpdates::None,
+                    true, // supports_attachments
+                    event,
+                )
+                .await?;
+        }
+
+        interview
+            .interviewers
+            .retain(|i| !conflicts.iter().any(|c| c.interviewer.eq_ignore_ascii_case(i)));
+    }
+
+    if new.is_empty() {
+        return Ok(());
+    }
+
+    let action = if config.exclude_conflicted_interviewers {
+        "We took them off the interview."
+    } else {
+        "Find someone else for the interview, or let us know why it is fine."
+    };
+    let msg = FormattedMessage {
+        channel: company.slack_channel_applicants.to_string(),
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: format!("Conflict of interest on the panel of {}", applicant.name),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        "<{}|{}>\n{}\n\n{}",
+                        interview.event_link,
+                        interview.start_time.format("%A %B %-d"),
+                        new.iter()
+                            .map(|c| format!("• *{}* {}", c.interviewer, c.reason))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        action
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+    company.post_to_slack_channel(db, &msg).await?;
+
+    info!(
+        "found {} conflicts of interest on the panel of {}",
+        new.len(),
+        applicant.name
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{panel_conflicts, Employment};
+    use crate::{applicants::tests::mock_applicant, configs::tests::mock_user};
+
+    #[test]
+    fn test_parse_employment() {
+        assert_eq!(
+            Employment {
+                employer: "Acme, Inc.".to_string(),
+                start: Some(2016),
+                end: Some(2020),
+            },
+            Employment::parse(" Acme, Inc. (2016-2020)")
+        );
+        assert_eq!(
+            Employment {
+                employer: "Initech".to_string(),
+                start: Some(2019),
+                end: None,
+            },
+            Employment::parse("Initech (2019-present)")
+        );
+        assert_eq!(None, Employment::parse("Globex").start);
+    }
+
+    #[test]
+    fn test_panel_conflicts() {
+        let mut applicant = mock_applicant();
+        applicant.previous_employers = vec!["Acme Inc (2018-2022)".to_string(), "Hooli (2010-2012)".to_string()];
+        applicant.referred_by = "friend".to_string();
+
+        let mut coworker = mock_user();
+        coworker.email = "coworker@example.com".to_string();
+        coworker.previous_employers = vec!["acme (2020-2021)".to_string()];
+
+        let mut old_coworker = mock_user();
+        old_coworker.email = "old@example.com".to_string();
+        old_coworker.previous_employers = vec!["Hooli (2011-2013)".to_string()];
+
+        let mut referrer = mock_user();
+        referrer.username = "jess".to_string();
+        referrer.aliases = vec!["friend".to_string()];
+        referrer.email = "jess@example.com".to_string();
+
+        let conflicts = panel_conflicts(&applicant, &[coworker.clone(), old_coworker, referrer], 2023, 3);
+        assert_eq!(2, conflicts.len());
+        assert_eq!("coworker@example.com", conflicts[0].interviewer);
+        assert_eq!(
+            "worked at acme in the last 3 years, like the applicant",
+            conflicts[0].reason
+        );
+        assert_eq!("jess@example.com", conflicts[1].interviewer);
+        assert_eq!("referred the applicant", conflicts[1].reason);
+
+        // How long we care about an old employer is up to the company.
+        let conflicts = panel_conflicts(&applicant, &[coworker], 2023, 1);
+        assert!(conflicts.is_empty(), "{:?}", conflicts);
+    }
+}
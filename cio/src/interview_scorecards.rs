@@ -0,0 +1,412 @@
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{
+    FormattedMessage, MessageBlock, MessageBlockAccessory, MessageBlockText, MessageBlockType, MessageType,
+};
+
+use crate::{
+    applicants::Applicant,
+    companies::Company,
+    db::Database,
+    interviews::ApplicantInterview,
+    schema::{applicant_interviews, applicants, interview_scorecards},
+    tenancy::CompanyScoped,
+};
+
+/// The action of the button in the direct message asking an interviewer for their scorecard.
+pub static INTERVIEW_SCORECARD_ACTION_ID: &str = "interview_scorecard";
+
+/// The ratings of a scorecard, from worst to best. We store a rating as its place in this
+/// list, starting at 1, so 0 means the interviewer did not rate it.
+pub static SCORECARD_RATINGS: &[&str] = &["Strong no", "No", "Yes", "Strong yes"];
+
+/// We only ask for the scorecards of the interviews that ended in the last few days, so an
+/// interviewer is not asked about an interview they no longer remember.
+static SCORECARD_REQUEST_WITHIN_DAYS: i64 = 3;
+
+/// A scorecard we asked an interviewer for, after they interviewed an applicant. It is
+/// submitted once they filled it in Slack.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct InterviewScorecard {
+    pub id: i32,
+    pub interview_id: i32,
+    pub applicant_id: i32,
+    /// The email of the interviewer.
+    pub interviewer: String,
+    pub technical_rating: i32,
+    pub communication_rating: i32,
+    pub values_rating: i32,
+    pub overall_rating: i32,
+    pub notes: String,
+    pub requested_at: DateTime<Utc>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+}
+
+impl CompanyScoped for InterviewScorecard {
+    fn cio_company_id(&self) -> i32 {
+        self.cio_company_id
+    }
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = interview_scorecards)]
+struct NewInterviewScorecard {
+    interview_id: i32,
+    applicant_id: i32,
+    interviewer: String,
+    cio_company_id: i32,
+}
+
+/// What an interviewer filled in their scorecard.
+#[derive(Debug, Default, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct ScorecardSubmission {
+    pub technical_rating: i32,
+    pub communication_rating: i32,
+    pub values_rating: i32,
+    pub overall_rating: i32,
+    pub notes: String,
+}
+
+impl ScorecardSubmission {
+    /// Check the ratings are ones we know, and that the interviewer gave an overall rating.
+    pub fn validate(&self) -> Result<()> {
+        for (name, rating) in [
+            ("technical", self.technical_rating),
+            ("communication", self.communication_rating),
+            ("values", self.values_rating),
+            ("overall", self.overall_rating),
+        ] {
+            if !(0..=SCORECARD_RATINGS.len() as i32).contains(&rating) {
+                bail!("the {} rating `{}` is not one of the ratings", name, rating);
+            }
+        }
+
+        if self.overall_rating == 0 {
+            bail!("the overall rating cannot be empty");
+        }
+
+        Ok(())
+    }
+}
+
+/// Return the rating for the value of a rating option, or 0 if it is not one.
+pub fn parse_scorecard_rating(value: &str) -> i32 {
+    match value.trim().parse::<i32>() {
+        Ok(rating) if (1..=SCORECARD_RATINGS.len() as i32).contains(&rating) => rating,
+        _ => 0,
+    }
+}
+
+/// Return how a rating reads, like `Strong yes`.
+pub fn scorecard_rating_label(rating: i32) -> &'static str {
+    if rating < 1 {
+        return "";
+    }
+
+    SCORECARD_RATINGS.get(rating as usize - 1).copied().unwrap_or_default()
+}
+
+/// Return the number of submitted scorecards, and the average of their overall ratings.
+pub fn scorecards_rollup(scorecards: &[InterviewScorecard]) -> (i32, f32) {
+    let ratings: Vec<i32> = scorecards
+        .iter()
+        .filter(|s| s.submitted_at.is_some() && s.overall_rating > 0)
+        .map(|s| s.overall_rating)
+        .collect();
+    if ratings.is_empty() {
+        return (0, 0.0);
+    }
+
+    let average = ratings.iter().sum::<i32>() as f32 / ratings.len() as f32;
+    (ratings.len() as i32, (average * 100.0).round() / 100.0)
+}
+
+impl InterviewScorecard {
+    /// Get a scorecard by its id, only if it belongs to the company. Use this rather than
+    /// getting it by id alone for the ids that come from Slack.
+    pub async fn get_by_id_for_company(db: &Database, cio_company_id: i32, id: i32) -> Result<Self> {
+        let scorecard = interview_scorecards::dsl::interview_scorecards
+            .find(id)
+            .filter(interview_scorecards::dsl::cio_company_id.eq(cio_company_id))
+            .first_async::<InterviewScorecard>(db.pool())
+            .await?;
+
+        Ok(scorecard)
+    }
+
+    /// Get the applicant the scorecard is about.
+    pub async fn applicant(&self, db: &Database) -> Result<Applicant> {
+        Applicant::get_by_id_for_company(db, self.cio_company_id, self.applicant_id).await
+    }
+
+    /// Save what the interviewer filled in their scorecard, and update the roll-up of the
+    /// scorecards of the applicant.
+    pub async fn submit(&self, db: &Database, submission: &ScorecardSubmission) -> Result<InterviewScorecard> {
+        submission.validate()?;
+
+        let scorecard = diesel::update(interview_scorecards::dsl::interview_scorecards)
+            .filter(interview_scorecards::dsl::id.eq(self.id))
+            .set((
+                interview_scorecards::dsl::technical_rating.eq(submission.technical_rating),
+                interview_scorecards::dsl::communication_rating.eq(submission.communication_rating),
+                interview_scorecards::dsl::values_rating.eq(submission.values_rating),
+                interview_scorecards::dsl::overall_rating.eq(submission.overall_rating),
+                interview_scorecards::dsl::notes.eq(submission.notes.trim().to_string()),
+                interview_scorecards::dsl::submitted_at.eq(Some(Utc::now())),
+            ))
+            .get_result_async::<InterviewScorecard>(db.pool())
+            .await?;
+        info!(
+            "{} submitted their scorecard for applicant {}: {}",
+            scorecard.interviewer,
+            scorecard.applicant_id,
+            scorecard_rating_label(scorecard.overall_rating)
+        );
+
+        update_applicant_scorecards_rollup(db, &scorecard.applicant(db).await?).await?;
+
+        Ok(scorecard)
+    }
+}
+
+/// Update the roll-up of the scorecards on the applicant, so it shows in their Airtable record.
+pub async fn update_applicant_scorecards_rollup(db: &Database, applicant: &Applicant) -> Result<()> {
+    let scorecards = interview_scorecards::dsl::interview_scorecards
+        .filter(interview_scorecards::dsl::applicant_id.eq(applicant.id))
+        .filter(interview_scorecards::dsl::cio_company_id.eq(applicant.cio_company_id))
+        .load_async::<InterviewScorecard>(db.pool())
+        .await?;
+
+    let (count, average) = scorecards_rollup(&scorecards);
+    if applicant.interview_scorecards_count == count && applicant.interview_scorecard_average == average {
+        return Ok(());
+    }
+
+    let mut applicant = applicant.clone();
+    applicant.interview_scorecards_count = count;
+    applicant.interview_scorecard_average = average;
+    applicant.update(db).await?;
+
+    Ok(())
+}
+
+/// Find the applicant of an interview: the one the interview links to, or else the newest
+/// application with the email of the interview, since applicants are not unique on their email.
+async fn find_interview_applicant(
+    db: &Database,
+    company: &Company,
+    interview: &ApplicantInterview,
+) -> Result<Applicant> {
+    if let Some(record_id) = interview.applicant.iter().find(|r| !r.is_empty()) {
+        if let Ok(applicant) = applicants::dsl::applicants
+            .filter(applicants::dsl::airtable_record_id.eq(record_id.to_string()))
+            .filter(applicants::dsl::cio_company_id.eq(company.id))
+            .first_async::<Applicant>(db.pool())
+            .await
+        {
+            return Ok(applicant);
+        }
+    }
+
+    Ok(applicants::dsl::applicants
+        .filter(applicants::dsl::email.eq(interview.email.to_string()))
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .order_by(applicants::dsl::id.desc())
+        .first_async::<Applicant>(db.pool())
+        .await?)
+}
+
+/// Ask each interviewer of the interviews that just ended for their scorecard, in a Slack
+/// direct message with a button that opens it. We ask once per interview and interviewer.
+pub async fn request_interview_scorecards(db: &Database, company: &Company) -> Result<()> {
+    let now = Utc::now();
+    let interviews = applicant_interviews::dsl::applicant_interviews
+        .filter(applicant_interviews::dsl::cio_company_id.eq(company.id))
+        .filter(applicant_interviews::dsl::cancelled_at.is_null())
+        .filter(applicant_interviews::dsl::end_time.lt(now))
+        .filter(applicant_interviews::dsl::end_time.gt(now - Duration::days(SCORECARD_REQUEST_WITHIN_DAYS)))
+        .load_async::<ApplicantInterview>(db.pool())
+        .await?;
+    if interviews.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let requested = interview_scorecards::dsl::interview_scorecards
+        .filter(interview_scorecards::dsl::cio_company_id.eq(company.id))
+        .filter(interview_scorecards::dsl::interview_id.eq_any(interviews.iter().map(|i| i.id).collect::<Vec<i32>>()))
+        .load_async::<InterviewScorecard>(db.pool())
+        .await?;
+
+    let slack = company.authenticate_slack(db).await?;
+    for interview in interviews {
+        if interview.email.is_empty() && interview.applicant.is_empty() {
+            continue;
+        }
+        let applicant = match find_interview_applicant(db, company, &interview).await {
+            Ok(applicant) => applicant,
+            Err(e) => {
+                warn!(
+                    "could not find the applicant {} of interview {}: {}",
+                    interview.email, interview.id, e
+                );
+                continue;
+            }
+        };
+
+        for interviewer in &interview.interviewers {
+            let interviewer = interviewer.trim().to_lowercase();
+            if interviewer.is_empty()
+                || requested
+                    .iter()
+                    .any(|s| s.interview_id == interview.id && s.interviewer == interviewer)
+            {
+                continue;
+            }
+
+            let channel = match slack.lookup_user_by_email(&interviewer).await {
+                Ok(user) => user.id,
+                Err(e) => {
+                    warn!("could not find the slack user of {}: {}", interviewer, e);
+                    continue;
+                }
+            };
+
+            let scorecard = diesel::insert_into(interview_scorecards::table)
+                .values(NewInterviewScorecard {
+                    interview_id: interview.id,
+                    applicant_id: applicant.id,
+                    interviewer: interviewer.to_string(),
+                    cio_company_id: company.id,
+                })
+                .get_result_async::<InterviewScorecard>(db.pool())
+                .await?;
+
+            let msg = scorecard_request_message(channel, &scorecard, &applicant, &interview);
+            if let Err(e) = company.post_to_slack_channel(db, &msg).await {
+                warn!(
+                    "asking {} for their scorecard of {} failed: {}",
+                    interviewer, applicant.name, e
+                );
+
+                // Forget we asked, so we ask again the next time.
+                diesel::delete(
+                    interview_scorecards::dsl::interview_scorecards
+                        .filter(interview_scorecards::dsl::id.eq(scorecard.id)),
+                )
+                .execute_async(db.pool())
+                .await?;
+                continue;
+            }
+            info!("asked {} for their scorecard of {}", interviewer, applicant.name);
+        }
+    }
+
+    Ok(())
+}
+
+/// The direct message asking an interviewer for their scorecard.
+fn scorecard_request_message(
+    channel: String,
+    scorecard: &InterviewScorecard,
+    applicant: &Applicant,
+    interview: &ApplicantInterview,
+) -> FormattedMessage {
+    FormattedMessage {
+        channel,
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "How did your interview with *{}* for {} on {} go? Fill in your scorecard while it is fresh.",
+                    applicant.name,
+                    applicant.role,
+                    interview.start_time.format("%B %-d")
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Some(MessageBlockAccessory {
+                accessory_type: MessageType::Button,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: "Fill in scorecard".to_string(),
+                }),
+                action_id: INTERVIEW_SCORECARD_ACTION_ID.to_string(),
+                value: scorecard.id.to_string(),
+                image_url: Default::default(),
+                alt_text: Default::default(),
+            }),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+        attachments: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::{
+        parse_scorecard_rating, scorecard_rating_label, scorecards_rollup, InterviewScorecard, ScorecardSubmission,
+    };
+
+    #[test]
+    fn test_scorecard_ratings() {
+        assert_eq!(parse_scorecard_rating("4"), 4);
+        assert_eq!(parse_scorecard_rating(" 1 "), 1);
+        assert_eq!(parse_scorecard_rating("5"), 0);
+        assert_eq!(parse_scorecard_rating(""), 0);
+        assert_eq!(scorecard_rating_label(4), "Strong yes");
+        assert_eq!(scorecard_rating_label(0), "");
+
+        let mut submission = ScorecardSubmission {
+            technical_rating: 3,
+            overall_rating: 4,
+            ..Default::default()
+        };
+        assert!(submission.validate().is_ok());
+        submission.values_rating = 7;
+        assert!(submission.validate().is_err());
+        submission.values_rating = 0;
+        submission.overall_rating = 0;
+        assert!(submission.validate().is_err());
+    }
+
+    #[test]
+    fn test_scorecards_rollup() {
+        let scorecard = |overall_rating: i32, submitted: bool| InterviewScorecard {
+            id: 1,
+            interview_id: 1,
+            applicant_id: 1,
+            interviewer: "jane@example.com".to_string(),
+            technical_rating: 0,
+            communication_rating: 0,
+            values_rating: 0,
+            overall_rating,
+            notes: String::new(),
+            requested_at: Utc::now(),
+            submitted_at: if submitted { Some(Utc::now()) } else { None },
+            cio_company_id: 1,
+        };
+
+        assert_eq!(scorecards_rollup(&[]), (0, 0.0));
+        assert_eq!(scorecards_rollup(&[scorecard(0, false)]), (0, 0.0));
+        assert_eq!(
+            scorecards_rollup(&[
+                scorecard(4, true),
+                scorecard(3, true),
+                scorecard(3, true),
+                scorecard(1, false)
+            ]),
+            (3, 3.33)
+        );
+    }
+}
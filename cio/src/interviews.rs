@@ -10,8 +10,9 @@ use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
 use chrono::Duration;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveTime, TimeZone, Utc};
 use chrono_tz::Tz;
+use google_calendar::Client as GoogleCalendar;
 use google_drive::{
     traits::{DriveOps, FileOps, PermissionOps},
     Client as GoogleDrive,
@@ -20,15 +21,19 @@ use log::{info, warn};
 use lopdf::{Bookmark, Document, Object, ObjectId};
 use macros::db;
 use schemars::JsonSchema;
+use sendgrid_api::{traits::MailOps, Client as SendGrid};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     airtable::AIRTABLE_INTERVIEWS_TABLE,
+    app_config::ApplyConfig,
     applicants::Applicant,
+    business_calendar::{BusinessCalendar, BusinessCalendarsConfig},
     companies::Company,
     configs::{User, Users},
     core::UpdateAirtableRecord,
     db::Database,
+    interview_conflicts::{check_panel_conflicts, find_panel_conflicts},
     schema::{applicant_interviews, applicants, users},
 };
 
@@ -39,6 +44,7 @@ use crate::{
     match_on = {
         "google_event_id" = "String",
     },
+    airtable_links = ["applicant"],
 }]
 #[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
 #[diesel(table_name = applicant_interviews)]
@@ -61,8 +67,20 @@ pub struct NewApplicantInterview {
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub event_link: String,
     /// link to another table in Airtable
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "airtable_api::linked_records::serialize",
+        deserialize_with = "airtable_api::linked_records::deserialize"
+    )]
     pub applicant: Vec<String>,
+    /// The conflicts of interest of the panel with the applicant, like an interviewer who
+    /// referred them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<String>,
+    /// When the interview was cancelled, we keep the interviews the applicant withdrew from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancelled_at: Option<DateTime<Utc>>,
     /// The CIO company ID.
     #[serde(default)]
     pub cio_company_id: i32,
@@ -76,8 +94,8 @@ impl UpdateAirtableRecord<ApplicantInterview> for ApplicantInterview {
     }
 }
 
-/// Sync interviews.
-pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()> {
+/// Sync interviews, and check their panels for conflicts of interest.
+pub async fn refresh_interviews(db: &Database, company: &Company, config: &ApplyConfig) -> Result<()> {
     if company.airtable_base_id_hiring.is_empty() {
         // Return early.
         return Ok(());
@@ -121,12 +139,15 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
             )
             .await?;
 
-        for event in events {
-            // If the event has been cancelled, clear it out of the database.
+        for mut event in events {
+            // If the event has been cancelled, mark it cancelled in the database.
             if event.status == "cancelled" {
                 // See if we have the event.
-                if let Some(db_event) = ApplicantInterview::get_from_db(db, event.id.to_string()).await {
-                    db_event.delete(db).await?;
+                if let Some(mut db_event) = ApplicantInterview::get_from_db(db, event.id.to_string()).await {
+                    if db_event.cancelled_at.is_none() {
+                        db_event.cancelled_at = Some(Utc::now());
+                        db_event.update(db).await?;
+                    }
                 }
 
                 // Continue since we don't want to save this event again.
@@ -145,10 +166,12 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
                 google_event_id: event.id.to_string(),
                 event_link: event.html_link.to_string(),
                 applicant: Default::default(),
+                conflicts: Default::default(),
+                cancelled_at: None,
                 cio_company_id: company.id,
             };
 
-            for attendee in event.attendees {
+            for attendee in &event.attendees {
                 // Skip the Interviews calendar.
                 if attendee.email.ends_with("@group.calendar.google.com") {
                     continue;
@@ -262,11 +285,12 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
                 }
             }
 
-            if let Ok(mut a) = applicants::dsl::applicants
+            let applicant = applicants::dsl::applicants
                 .filter(applicants::dsl::email.eq(interview.email.to_string()))
                 .first_async::<Applicant>(db.pool())
                 .await
-            {
+                .ok();
+            if let Some(mut a) = applicant.clone() {
                 // Set the applicant to interviewing.
                 if a.status != crate::applicant_status::Status::Interviewing.to_string()
                     && (a.status == crate::applicant_status::Status::NextSteps.to_string()
@@ -281,6 +305,20 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
                 interview.name = a.name.to_string();
             }
 
+            if let Some(applicant) = &applicant {
+                check_panel_conflicts(
+                    db,
+                    company,
+                    config,
+                    &gcal,
+                    &calendar.id,
+                    &mut event,
+                    applicant,
+                    &mut interview,
+                )
+                .await?;
+            }
+
             let name = interview.name.to_string();
             if name.is_empty() {
                 // Continue early.
@@ -374,7 +412,11 @@ pub async fn compile_packets(db: &Database, company: &Company) -> Result<()> {
         }
     }
 
-    let interviews = ApplicantInterviews::get_from_db(db, company.id).await?;
+    let interviews: Vec<ApplicantInterview> = ApplicantInterviews::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .filter(|interview| interview.cancelled_at.is_none())
+        .collect();
 
     // TODO: There is probably a bunch of ways this logic could be improved and better.
     // Especially if we join tables.
@@ -613,6 +655,558 @@ The Oxide Team
     Ok(())
 }
 
+/// How long the debrief with the interview panel is.
+static DEBRIEF_LENGTH_MINUTES: i64 = 30;
+
+/// The number of business days after the last interview of a loop that we schedule the
+/// debrief within.
+static DEBRIEF_WITHIN_BUSINESS_DAYS: usize = 2;
+
+/// The earliest we schedule a debrief in the day, so the panel has time to write their
+/// feedback in the morning.
+static DEBRIEF_EARLIEST_HOUR: u32 = 10;
+
+/// Return the slots we could schedule a debrief in after an interview loop ended at the given
+/// time: every half hour from 10am to the end of business hours on the following business
+/// days. If we only get to the loop days later, the business days follow now instead, so we
+/// never schedule a debrief in the past.
+fn debrief_slots(
+    calendar: &BusinessCalendar,
+    loop_ended: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut slots = Vec::new();
+
+    let mut date = calendar.local_date(loop_ended.max(now));
+    for _ in 0..DEBRIEF_WITHIN_BUSINESS_DAYS {
+        date = calendar.next_business_day(date);
+
+        let (open, close) = match calendar.business_hours(date) {
+            Some(hours) => hours,
+            None => continue,
+        };
+        let mut start = match calendar.at(date, NaiveTime::from_hms(DEBRIEF_EARLIEST_HOUR, 0, 0)) {
+            Some(start) => start.max(open),
+            None => open,
+        };
+        while start + Duration::minutes(DEBRIEF_LENGTH_MINUTES) <= close {
+            if start > now {
+                slots.push((start, start + Duration::minutes(DEBRIEF_LENGTH_MINUTES)));
+            }
+            start = start + Duration::minutes(30);
+        }
+    }
+
+    slots
+}
+
+/// Pick the slot where the fewest people on the panel are busy, preferring earlier slots.
+fn pick_debrief_slot(
+    slots: &[(DateTime<Utc>, DateTime<Utc>)],
+    busy: &[Vec<(DateTime<Utc>, DateTime<Utc>)>],
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    slots
+        .iter()
+        .min_by_key(|(start, end)| {
+            busy.iter()
+                .filter(|intervals| intervals.iter().any(|(s, e)| s < end && e > start))
+                .count()
+        })
+        .copied()
+}
+
+/// Get when each person is busy between two points in time. We use the free/busy API so we
+/// only see when people are busy, not what they are busy with. The people whose calendar we
+/// cannot read are left out.
+async fn get_busy_intervals(
+    db: &Database,
+    company: &Company,
+    emails: &[String],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<BTreeMap<String, Vec<(DateTime<Utc>, DateTime<Utc>)>>> {
+    let gcal = company.authenticate_google_calendar(db).await?;
+
+    let response = gcal
+        .freebusy()
+        .query(&google_calendar::types::FreeBusyRequest {
+            items: emails
+                .iter()
+                .map(|email| google_calendar::types::FreeBusyRequestItem { id: email.to_string() })
+                .collect(),
+            time_min: Some(from),
+            time_max: Some(to),
+            ..Default::default()
+        })
+        .await?;
+
+    let mut busy = BTreeMap::new();
+    for email in emails {
+        match response.calendars.get(email) {
+            Some(calendar) if calendar.errors.is_empty() => {
+                busy.insert(
+                    email.to_string(),
+                    calendar
+                        .busy
+                        .iter()
+                        .filter_map(|period| Some((period.start?, period.end?)))
+                        .collect(),
+                );
+            }
+            Some(calendar) => warn!("could not get when {} is busy: {:?}", email, calendar.errors),
+            None => warn!("could not get when {} is busy", email),
+        }
+    }
+
+    Ok(busy)
+}
+
+/// Get the id of the interviews calendar, where the interviews and the debriefs are.
+pub(crate) async fn get_interviews_calendar_id(gcal: &GoogleCalendar) -> Result<Option<String>> {
+    let calendars = gcal
+        .calendar_list()
+        .list_all(google_calendar::types::MinAccessRole::Noop, false, false)
+        .await?;
+
+    Ok(calendars.into_iter().find(|c| c.summary == "Interviews").map(|c| c.id))
+}
+
+/// Schedule a debrief with the panel for each applicant whose interview loop is done, and
+/// move the applicant to the debrief stage.
+pub async fn schedule_debriefs(db: &Database, company: &Company, calendars: &BusinessCalendarsConfig) -> Result<()> {
+    if company.airtable_base_id_hiring.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let applicants = applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .filter(applicants::dsl::status.eq(crate::applicant_status::Status::Interviewing.to_string()))
+        .filter(applicants::dsl::debrief_event_id.eq("".to_string()))
+        .load_async::<Applicant>(db.pool())
+        .await?;
+    if applicants.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let gcal = company.authenticate_google_calendar(db).await?;
+
+    // The debriefs go on the interviews calendar, next to the interviews.
+    let calendar_id = match get_interviews_calendar_id(&gcal).await? {
+        Some(calendar_id) => calendar_id,
+        None => {
+            warn!("could not find the interviews calendar for company {}", company.name);
+            return Ok(());
+        }
+    };
+
+    for mut applicant in applicants {
+        let interviews = applicant_interviews::dsl::applicant_interviews
+            .filter(applicant_interviews::dsl::email.eq(applicant.email.to_string()))
+            .filter(applicant_interviews::dsl::cio_company_id.eq(company.id))
+            .filter(applicant_interviews::dsl::cancelled_at.is_null())
+            .order_by(applicant_interviews::dsl::end_time.asc())
+            .load_async::<ApplicantInterview>(db.pool())
+            .await?;
+
+        let loop_ended = match interviews.last() {
+            Some(interview) => interview.end_time,
+            None => continue,
+        };
+        // We only schedule the debrief once the last interview is over, and we don't
+        // want to schedule debriefs for loops that ended long ago.
+        if loop_ended > Utc::now() || Utc::now() - loop_ended > Duration::weeks(1) {
+            continue;
+        }
+
+        let mut panel: Vec<String> = interviews.iter().flat_map(|i| i.interviewers.clone()).collect();
+        panel.sort();
+        panel.dedup();
+        if panel.is_empty() {
+            continue;
+        }
+
+        let slots = debrief_slots(&calendars.default_calendar(), loop_ended, Utc::now());
+        let (from, to) = match (slots.first(), slots.last()) {
+            (Some(first), Some(last)) => (first.0, last.1),
+            _ => {
+                warn!("no business hours to schedule the debrief of {} in", applicant.name);
+                continue;
+            }
+        };
+        let busy: Vec<Vec<(DateTime<Utc>, DateTime<Utc>)>> =
+            match get_busy_intervals(db, company, &panel, from, to).await {
+                Ok(busy) => busy.into_values().collect(),
+                Err(e) => {
+                    warn!(
+                        "could not get the calendars of the panel of {} for debrief: {}",
+                        applicant.name, e
+                    );
+                    continue;
+                }
+            };
+        let (start, end) = match pick_debrief_slot(&slots, &busy) {
+            Some(slot) => slot,
+            None => continue,
+        };
+
+        let mut description = format!("Debrief for the interviews of {}.", applicant.name);
+        let mut attachments = Vec::new();
+        if !applicant.interview_packet.is_empty() {
+            description += &format!("\n\nInterview packet: {}", applicant.interview_packet);
+            attachments.push(google_calendar::types::EventAttachment {
+                file_url: applicant.interview_packet.to_string(),
+                title: format!("Interview Packet - {}", applicant.name),
+                ..Default::default()
+            });
+        }
+        if !applicant.scoring_form_responses_url.is_empty() {
+            description += &format!("\nScorecards: {}", applicant.scoring_form_responses_url);
+        }
+
+        let event = google_calendar::types::Event {
+            summary: format!("Debrief: {}", applicant.name),
+            description,
+            start: Some(google_calendar::types::EventDateTime {
+                date_time: Some(start),
+                time_zone: "America/Los_Angeles".to_string(),
+                ..Default::default()
+            }),
+            end: Some(google_calendar::types::EventDateTime {
+                date_time: Some(end),
+                time_zone: "America/Los_Angeles".to_string(),
+                ..Default::default()
+            }),
+            attendees: panel
+                .iter()
+                .map(|email| google_calendar::types::EventAttendee {
+                    email: email.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            attachments,
+            ..Default::default()
+        };
+
+        let event = gcal
+            .events()
+            .insert(
+                &calendar_id,
+                0,    // conference data version
+                0,    // max attendees, 0 to ignore
+                true, // send notifications
+                google_calendar::types::SendUpdates::All,
+                true, // supports_attachments
+                &event,
+            )
+            .await?;
+        info!(
+            "scheduled debrief for {} at {} with {}",
+            applicant.name,
+            start,
+            panel.join(", ")
+        );
+
+        applicant.debrief_event_id = event.id.to_string();
+        applicant.debrief_event_link = event.html_link.to_string();
+        applicant.status = crate::applicant_status::Status::Debrief.to_string();
+        applicant.update(db).await?;
+    }
+
+    Ok(())
+}
+
+/// How long an interview we schedule is, when the request does not say.
+static INTERVIEW_LENGTH_MINUTES: i64 = 60;
+
+/// The number of days we look ahead for a time the whole panel is free.
+static INTERVIEW_WITHIN_DAYS: i64 = 14;
+
+/// How much notice the applicant gets before an interview we schedule, unless the request
+/// says when the interview can be.
+static INTERVIEW_NOTICE_HOURS: i64 = 24;
+
+/// An interview to schedule with a panel.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ScheduleInterviewRequest {
+    /// The email of the applicant.
+    pub email: String,
+    /// The role of the application, the newest application is used if there is none.
+    #[serde(default)]
+    pub role: Option<String>,
+    /// The emails of the interviewers on the panel.
+    pub interviewers: Vec<String>,
+    #[serde(default)]
+    pub duration_minutes: Option<i64>,
+    /// The earliest the interview can be.
+    #[serde(default)]
+    pub earliest: Option<DateTime<Utc>>,
+}
+
+/// Return the slots we could schedule an interview in between two points in time: every half
+/// hour that is during the business hours of everyone on the panel.
+fn interview_slots(
+    calendars: &[BusinessCalendar],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    length: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut slots = Vec::new();
+
+    // Start on the half hour.
+    let half_hour = Duration::minutes(30).num_seconds();
+    let mut start = Utc.timestamp((from.timestamp() + half_hour - 1) / half_hour * half_hour, 0);
+    while start + length <= to {
+        let end = start + length;
+        if calendars
+            .iter()
+            .all(|c| c.is_business_hours(start) && c.is_business_hours(end - Duration::minutes(1)))
+        {
+            slots.push((start, end));
+        }
+        start = start + Duration::minutes(30);
+    }
+
+    slots
+}
+
+/// Return the first slot where no one on the panel is busy.
+fn pick_interview_slot(
+    slots: &[(DateTime<Utc>, DateTime<Utc>)],
+    busy: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    slots
+        .iter()
+        .find(|(start, end)| !busy.iter().any(|(s, e)| s < end && e > start))
+        .copied()
+}
+
+/// Schedule an interview with a panel at the first time they are all free, with a Meet link,
+/// and let the applicant know when it is.
+pub async fn schedule_interview(
+    db: &Database,
+    company: &Company,
+    config: &ApplyConfig,
+    calendars: &BusinessCalendarsConfig,
+    request: &ScheduleInterviewRequest,
+) -> Result<ApplicantInterview> {
+    let mut interviewers: Vec<String> = Default::default();
+    for email in &request.interviewers {
+        let email = email.trim().to_string();
+        if !email.is_empty() && !interviewers.contains(&email) {
+            interviewers.push(email);
+        }
+    }
+    if interviewers.is_empty() {
+        bail!("an interview needs at least one interviewer");
+    }
+
+    // Applicants are not unique on their email, we schedule the newest application.
+    let mut applicant = match applicants::dsl::applicants
+        .filter(applicants::dsl::email.eq(request.email.trim().to_string()))
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .order_by(applicants::dsl::id.desc())
+        .load_async::<Applicant>(db.pool())
+        .await?
+        .into_iter()
+        .find(|a| {
+            request
+                .role
+                .as_ref()
+                .map(|r| a.role.eq_ignore_ascii_case(r.trim()))
+                .unwrap_or(true)
+        }) {
+        Some(applicant) => applicant,
+        None => bail!("no application from `{}` to schedule an interview for", request.email),
+    };
+
+    // Nobody with a conflict of interest is ever invited.
+    let conflicts = find_panel_conflicts(db, company, config, &applicant, &interviewers).await?;
+    if !conflicts.is_empty() {
+        if !config.exclude_conflicted_interviewers {
+            bail!(
+                "find someone else for the interview of {}: {}",
+                applicant.name,
+                conflicts
+                    .iter()
+                    .map(|c| format!("{} {}", c.interviewer, c.reason))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        interviewers.retain(|i| !conflicts.iter().any(|c| c.interviewer.eq_ignore_ascii_case(i)));
+        if interviewers.is_empty() {
+            bail!("everyone on the panel of {} has a conflict of interest", applicant.name);
+        }
+    }
+
+    let length = Duration::minutes(
+        request
+            .duration_minutes
+            .filter(|m| *m > 0)
+            .unwrap_or(INTERVIEW_LENGTH_MINUTES),
+    );
+    let from = request
+        .earliest
+        .unwrap_or_else(|| Utc::now() + Duration::hours(INTERVIEW_NOTICE_HOURS))
+        .max(Utc::now());
+    let to = from + Duration::days(INTERVIEW_WITHIN_DAYS);
+
+    // Everyone on the panel works from their own office.
+    let users = Users::get_from_db(db, company.id).await?;
+    let panel_calendars: Vec<BusinessCalendar> = interviewers
+        .iter()
+        .map(|email| match users.0.iter().find(|u| u.email == *email) {
+            Some(user) => calendars.for_user(user),
+            None => calendars.default_calendar(),
+        })
+        .collect();
+    let slots = interview_slots(&panel_calendars, from, to, length);
+    let busy = get_busy_intervals(db, company, &interviewers, from, to).await?;
+    if let Some(email) = interviewers.iter().find(|email| !busy.contains_key(*email)) {
+        bail!("could not get when {} is busy", email);
+    }
+    let busy: Vec<(DateTime<Utc>, DateTime<Utc>)> = busy.into_values().flatten().collect();
+    let (start, end) = match pick_interview_slot(&slots, &busy) {
+        Some(slot) => slot,
+        None => bail!(
+            "the panel of {} has no time in common in the next {} days",
+            applicant.name,
+            INTERVIEW_WITHIN_DAYS
+        ),
+    };
+
+    let gcal = company.authenticate_google_calendar(db).await?;
+    let calendar_id = match get_interviews_calendar_id(&gcal).await? {
+        Some(calendar_id) => calendar_id,
+        None => bail!("could not find the interviews calendar for company {}", company.name),
+    };
+
+    let usernames: Vec<String> = interviewers
+        .iter()
+        .map(|email| {
+            email
+                .trim_end_matches(&company.gsuite_domain)
+                .trim_end_matches(&company.domain)
+                .trim_end_matches('@')
+                .to_string()
+        })
+        .collect();
+    let name = format!("{} ({})", applicant.name, usernames.join(", "));
+
+    let mut attendees: Vec<google_calendar::types::EventAttendee> = interviewers
+        .iter()
+        .map(|email| google_calendar::types::EventAttendee {
+            email: email.to_string(),
+            ..Default::default()
+        })
+        .collect();
+    attendees.push(google_calendar::types::EventAttendee {
+        email: applicant.email.to_string(),
+        display_name: applicant.name.to_string(),
+        ..Default::default()
+    });
+
+    let time_zone = calendars.default_calendar().time_zone;
+    let event = google_calendar::types::Event {
+        summary: name.to_string(),
+        description: format!("Interview with {} for {}.", applicant.name, applicant.role),
+        start: Some(google_calendar::types::EventDateTime {
+            date_time: Some(start),
+            time_zone: time_zone.name().to_string(),
+            ..Default::default()
+        }),
+        end: Some(google_calendar::types::EventDateTime {
+            date_time: Some(end),
+            time_zone: time_zone.name().to_string(),
+            ..Default::default()
+        }),
+        attendees,
+        conference_data: Some(google_calendar::types::ConferenceData {
+            create_request: Some(google_calendar::types::CreateConferenceRequest {
+                conference_solution_key: Some(google_calendar::types::ConferenceSolutionKey {
+                    type_: "hangoutsMeet".to_string(),
+                }),
+                request_id: format!("interview-{}-{}", applicant.id, start.timestamp()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let event = gcal
+        .events()
+        .insert(
+            &calendar_id,
+            1,    // conference data version, 1 to create the Meet link
+            0,    // max attendees, 0 to ignore
+            true, // send notifications
+            google_calendar::types::SendUpdates::All,
+            true, // supports_attachments
+            &event,
+        )
+        .await?;
+    info!("scheduled interview {} at {}", name, start);
+
+    let mut msg = format!(
+        "Hi {},\n\nWe scheduled your interview for {} on {}, for {} minutes. You will get a \
+         calendar invite for it shortly.",
+        applicant.name,
+        applicant.role,
+        start.with_timezone(&time_zone).format("%A %B %-d at %-I:%M %p %Z"),
+        length.num_minutes()
+    );
+    if !event.hangout_link.is_empty() {
+        msg += &format!("\n\nYou can join it on Google Meet: {}", event.hangout_link);
+    }
+    msg += "\n\nIf the time does not work for you, reply to this email and we will find another one.";
+
+    let sendgrid_client = SendGrid::new_from_env();
+    sendgrid_client
+        .mail_send()
+        .send_plain_text(
+            &format!("Your interview with {}", company.name),
+            &msg,
+            &[applicant.email.to_string()],
+            &[],
+            &[],
+            &format!("careers@{}", company.gsuite_domain),
+        )
+        .await?;
+
+    let interview = NewApplicantInterview {
+        start_time: start,
+        end_time: end,
+        name,
+        email: applicant.email.to_string(),
+        interviewers,
+        google_event_id: event.id.to_string(),
+        event_link: event.html_link.to_string(),
+        applicant: if applicant.airtable_record_id.is_empty() {
+            Default::default()
+        } else {
+            vec![applicant.airtable_record_id.to_string()]
+        },
+        conflicts: Default::default(),
+        cancelled_at: None,
+        cio_company_id: company.id,
+    }
+    .upsert(db)
+    .await?;
+
+    if applicant.status == crate::applicant_status::Status::NextSteps.to_string()
+        || applicant.status == crate::applicant_status::Status::NeedsToBeTriaged.to_string()
+    {
+        applicant.status = crate::applicant_status::Status::Interviewing.to_string();
+        applicant.update(db).await?;
+    }
+
+    Ok(interview)
+}
+
 /// Download materials file from Google drive and save it as a pdf under the persons username.
 pub async fn download_materials_as_pdf(drive_client: &GoogleDrive, url: &str, username: &str) -> Result<()> {
     let id = url.replace("https://drive.google.com/open?id=", "");
@@ -900,3 +1494,124 @@ pub fn combine_pdfs(pdfs: Vec<String>) -> Result<Vec<u8>> {
     document.save_to(&mut buffer)?;
     Ok(buffer)
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{debrief_slots, interview_slots, pick_debrief_slot, pick_interview_slot, ApplicantInterview};
+    use crate::business_calendar::BusinessCalendar;
+
+    #[test]
+    fn test_keep_airtable_links() {
+        let mut existing: ApplicantInterview =
+            serde_json::from_str(r#"{"start_time":"2024-01-09T17:00:00Z","end_time":"2024-01-09T18:00:00Z"}"#).unwrap();
+        existing.applicant = vec!["recOurs".to_string(), "recAddedInAirtable".to_string()];
+
+        let mut interview = existing.clone();
+        interview.applicant = vec!["recOurs".to_string(), "".to_string()];
+        interview.keep_airtable_links(&existing);
+        assert_eq!(
+            vec!["recOurs".to_string(), "recAddedInAirtable".to_string()],
+            interview.applicant
+        );
+
+        interview.applicant = Default::default();
+        interview.keep_airtable_links(&existing);
+        assert_eq!(existing.applicant, interview.applicant);
+    }
+
+    #[test]
+    fn test_debrief_slots() {
+        // A Friday afternoon, Pacific time.
+        let loop_ended = Utc.ymd(2023, 2, 24).and_hms(23, 0, 0);
+        let slots = debrief_slots(&BusinessCalendar::default(), loop_ended, loop_ended);
+
+        // Every half hour from 10am to 5pm on Monday and Tuesday.
+        assert_eq!(28, slots.len());
+        assert_eq!(Utc.ymd(2023, 2, 27).and_hms(18, 0, 0), slots[0].0);
+        assert_eq!(Utc.ymd(2023, 2, 27).and_hms(18, 30, 0), slots[0].1);
+        assert_eq!(Utc.ymd(2023, 3, 1).and_hms(1, 0, 0), slots[27].1);
+    }
+
+    #[test]
+    fn test_debrief_slots_are_never_in_the_past() {
+        // We only get to a Friday afternoon loop on Monday at noon, Pacific time.
+        let loop_ended = Utc.ymd(2023, 2, 24).and_hms(23, 0, 0);
+        let now = Utc.ymd(2023, 2, 27).and_hms(20, 0, 0);
+        let slots = debrief_slots(&BusinessCalendar::default(), loop_ended, now);
+
+        // Every half hour from 10am to 5pm on Tuesday and Wednesday.
+        assert_eq!(28, slots.len());
+        assert!(slots.iter().all(|(start, _)| *start > now));
+        assert_eq!(Utc.ymd(2023, 2, 28).and_hms(18, 0, 0), slots[0].0);
+    }
+
+    #[test]
+    fn test_pick_debrief_slot() {
+        let loop_ended = Utc.ymd(2023, 2, 24).and_hms(23, 0, 0);
+        let slots = debrief_slots(&BusinessCalendar::default(), loop_ended, loop_ended);
+
+        let busy = vec![
+            vec![(
+                Utc.ymd(2023, 2, 27).and_hms(18, 0, 0),
+                Utc.ymd(2023, 2, 27).and_hms(19, 0, 0),
+            )],
+            vec![(
+                Utc.ymd(2023, 2, 27).and_hms(19, 0, 0),
+                Utc.ymd(2023, 2, 27).and_hms(19, 30, 0),
+            )],
+        ];
+        assert_eq!(Some(slots[3]), pick_debrief_slot(&slots, &busy),);
+
+        assert_eq!(Some(slots[0]), pick_debrief_slot(&slots, &[]));
+        assert_eq!(None, pick_debrief_slot(&[], &busy));
+    }
+
+    #[test]
+    fn test_interview_slots() {
+        // From a Friday afternoon to the Monday afternoon, Pacific time.
+        let slots = interview_slots(
+            &[BusinessCalendar::default()],
+            Utc.ymd(2023, 2, 25).and_hms(0, 10, 0),
+            Utc.ymd(2023, 2, 27).and_hms(20, 0, 0),
+            Duration::hours(1),
+        );
+
+        // The Friday is over before an hour long interview fits, so every half hour from 9am
+        // to 11am on Monday.
+        assert_eq!(5, slots.len());
+        assert_eq!(Utc.ymd(2023, 2, 27).and_hms(17, 0, 0), slots[0].0);
+        assert_eq!(Utc.ymd(2023, 2, 27).and_hms(20, 0, 0), slots[4].1);
+    }
+
+    #[test]
+    fn test_pick_interview_slot() {
+        let slots = interview_slots(
+            &[BusinessCalendar::default()],
+            Utc.ymd(2023, 2, 27).and_hms(17, 0, 0),
+            Utc.ymd(2023, 2, 28).and_hms(1, 0, 0),
+            Duration::hours(1),
+        );
+
+        let busy = vec![
+            (
+                Utc.ymd(2023, 2, 27).and_hms(17, 0, 0),
+                Utc.ymd(2023, 2, 27).and_hms(18, 0, 0),
+            ),
+            (
+                Utc.ymd(2023, 2, 27).and_hms(18, 30, 0),
+                Utc.ymd(2023, 2, 27).and_hms(19, 0, 0),
+            ),
+        ];
+        // The first hour no one on the panel is busy.
+        assert_eq!(
+            Some((
+                Utc.ymd(2023, 2, 27).and_hms(19, 0, 0),
+                Utc.ymd(2023, 2, 27).and_hms(20, 0, 0)
+            )),
+            pick_interview_slot(&slots, &busy)
+        );
+        assert_eq!(None, pick_interview_slot(&[], &busy));
+    }
+}
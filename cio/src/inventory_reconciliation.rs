@@ -0,0 +1,633 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::BufWriter,
+};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use google_drive::traits::{DriveOps, FileOps};
+use log::info;
+use printpdf::{Mm, PdfDocument};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    asset_inventory::{AssetItem, AssetItems},
+    companies::Company,
+    db::Database,
+    schema::{inventory_reconciliations, swag_shipment_items},
+    swag_inventory::{BarcodeScans, SwagBarcodeScanAction, SwagInventoryItem, SwagInventoryItems},
+    swag_shipments::SwagShipmentItem,
+};
+
+/// How much drift we accept between what we expect to have and what was counted.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct InventoryReconciliationConfig {
+    /// Items drifting by more than this many units are flagged, 0 flags any drift.
+    #[serde(default)]
+    pub max_drift: i32,
+}
+
+/// A monthly reconciliation of the inventory of a company.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct InventoryReconciliation {
+    pub id: i32,
+    pub generated_at: DateTime<Utc>,
+    pub item_count: i32,
+    pub flagged: i32,
+    pub csv_link: String,
+    pub pdf_link: String,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = inventory_reconciliations)]
+struct NewInventoryReconciliation {
+    generated_at: DateTime<Utc>,
+    item_count: i32,
+    flagged: i32,
+    csv_link: String,
+    pdf_link: String,
+    cio_company_id: i32,
+}
+
+/// What we recorded coming in and going out of the stock of a swag item since it was counted.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct InventoryMovement {
+    pub received: i32,
+    pub shipped: i32,
+}
+
+/// The difference between what we expect to have of an item and what was counted.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InventoryVariance {
+    /// `Swag` or `Asset`.
+    pub kind: String,
+    /// The swag inventory item, or the type of asset.
+    pub name: String,
+    pub expected: i32,
+    pub counted: i32,
+    /// How many more were counted than we expect, negative when some are missing.
+    pub drift: i32,
+    /// If the drift is more than we accept.
+    pub flagged: bool,
+}
+
+impl InventoryVariance {
+    fn new(kind: &str, name: &str, expected: i32, counted: i32, max_drift: i32) -> Self {
+        let drift = counted - expected;
+        InventoryVariance {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            expected,
+            counted,
+            drift,
+            flagged: drift.abs() > max_drift,
+        }
+    }
+}
+
+/// Compare what people counted of each swag item in Airtable with the stock the receipts and
+/// shipments we recorded give us. The stock we have now is taken back to when the item was
+/// counted, with what came in and went out since. Items not counted since `since` are skipped.
+pub fn swag_variances(
+    inventory: &[SwagInventoryItem],
+    movements: &HashMap<i32, InventoryMovement>,
+    since: Option<DateTime<Utc>>,
+    max_drift: i32,
+) -> Vec<InventoryVariance> {
+    inventory
+        .iter()
+        .filter(|item| match (item.counted_at, since) {
+            (Some(counted_at), Some(since)) => counted_at > since,
+            (Some(_), None) => true,
+            (None, _) => false,
+        })
+        .map(|item| {
+            let movement = movements.get(&item.id).copied().unwrap_or_default();
+            let expected = (item.current_stock - movement.received + movement.shipped).max(0);
+
+            InventoryVariance::new("Swag", &item.name, expected, item.counted_stock, max_drift)
+        })
+        .collect()
+}
+
+/// Compare the assets we have on record with the ones people saw in the last count, by type.
+/// Retired assets are not expected, and nothing is compared if nobody counted since `since`.
+pub fn asset_variances(recorded: &[AssetItem], since: Option<NaiveDate>, max_drift: i32) -> Vec<InventoryVariance> {
+    let is_counted = |item: &AssetItem| match (item.last_counted_date, since) {
+        (Some(counted), Some(since)) => counted > since,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    if !recorded.iter().any(is_counted) {
+        return Default::default();
+    }
+
+    let mut types: BTreeMap<String, (i32, i32)> = BTreeMap::new();
+    for item in recorded {
+        if item.status.eq_ignore_ascii_case("retired") {
+            continue;
+        }
+
+        let counts = types.entry(asset_type(item)).or_default();
+        counts.0 += 1;
+        if is_counted(item) {
+            counts.1 += 1;
+        }
+    }
+
+    types
+        .into_iter()
+        .map(|(name, (expected, counted))| InventoryVariance::new("Asset", &name, expected, counted, max_drift))
+        .collect()
+}
+
+fn asset_type(item: &AssetItem) -> String {
+    if item.type_.trim().is_empty() {
+        "Other".to_string()
+    } else {
+        item.type_.trim().to_string()
+    }
+}
+
+/// The variance of our inventory, for the monthly reconciliation.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InventoryReconciliationReport {
+    pub generated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub flagged: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variances: Vec<InventoryVariance>,
+    /// The link to the CSV export in Google Drive.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub csv_link: String,
+    /// The link to the PDF export in Google Drive.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pdf_link: String,
+}
+
+#[derive(Debug, Serialize)]
+struct InventoryReconciliationRow<'a> {
+    #[serde(rename = "Kind")]
+    kind: &'a str,
+    #[serde(rename = "Name")]
+    name: &'a str,
+    #[serde(rename = "Expected")]
+    expected: i32,
+    #[serde(rename = "Counted")]
+    counted: i32,
+    #[serde(rename = "Drift")]
+    drift: i32,
+    #[serde(rename = "Flagged")]
+    flagged: &'a str,
+}
+
+impl InventoryReconciliationReport {
+    pub fn new(mut variances: Vec<InventoryVariance>) -> Self {
+        variances.sort_by(|a, b| (&a.kind, &a.name).cmp(&(&b.kind, &b.name)));
+
+        InventoryReconciliationReport {
+            generated_at: Utc::now(),
+            flagged: variances.iter().filter(|v| v.flagged).count() as i32,
+            variances,
+            csv_link: String::new(),
+            pdf_link: String::new(),
+        }
+    }
+
+    /// Export the report as a CSV with one row per item.
+    pub fn to_csv(&self) -> Result<Vec<u8>> {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        for variance in &self.variances {
+            wtr.serialize(InventoryReconciliationRow {
+                kind: &variance.kind,
+                name: &variance.name,
+                expected: variance.expected,
+                counted: variance.counted,
+                drift: variance.drift,
+                flagged: if variance.flagged { "Yes" } else { "" },
+            })?;
+        }
+
+        Ok(wtr.into_inner()?)
+    }
+
+    pub fn to_pdf(&self, company: &Company) -> Result<Vec<u8>> {
+        let pdf_width = Mm(215.9);
+        let pdf_height = Mm(279.4);
+        let pdf_margin = Mm(15.0);
+        let line_height = Mm(5.0);
+        let font_size = 9.0;
+
+        let title = format!("{} Inventory Reconciliation", company.name);
+        let (doc, page1, layer1) = PdfDocument::new(&title, pdf_width, pdf_height, "Layer 1");
+        let font_bytes = include_bytes!("Inconsolata/Inconsolata-Regular.ttf").to_vec();
+        let font = doc.add_external_font(&*font_bytes)?;
+
+        let mut lines: Vec<String> = vec![
+            title.to_string(),
+            format!("Generated {}", self.generated_at.format("%Y-%m-%d %H:%M UTC")),
+            format!(
+                "{} items reconciled, {} drifting more than we accept",
+                self.variances.len(),
+                self.flagged
+            ),
+            String::new(),
+            format!(
+                "  {:<6} {:<40} {:>9} {:>9} {:>7}",
+                "Kind", "Name", "Expected", "Counted", "Drift"
+            ),
+        ];
+        for variance in &self.variances {
+            lines.push(format!(
+                "{} {:<6} {:<40} {:>9} {:>9} {:>+7}",
+                if variance.flagged { "!" } else { " " },
+                variance.kind,
+                variance.name.trim().chars().take(40).collect::<String>(),
+                variance.expected,
+                variance.counted,
+                variance.drift
+            ));
+        }
+
+        let mut current_layer = doc.get_page(page1).get_layer(layer1);
+        let mut y = pdf_height - pdf_margin;
+        for line in lines {
+            if y < pdf_margin {
+                let (page, layer) = doc.add_page(pdf_width, pdf_height, "Layer 1");
+                current_layer = doc.get_page(page).get_layer(layer);
+                y = pdf_height - pdf_margin;
+            }
+
+            if !line.is_empty() {
+                current_layer.use_text(line, font_size, pdf_margin, y, &font);
+            }
+            y -= line_height;
+        }
+
+        // Save the PDF
+        let mut bw = BufWriter::new(Vec::new());
+
+        doc.save(&mut bw)?;
+
+        Ok(bw.into_inner()?)
+    }
+}
+
+/// Find what came in and went out of each swag item since it was counted, from the barcode
+/// scans and the shipments we took it out of the inventory for.
+async fn swag_movements(
+    db: &Database,
+    company: &Company,
+    inventory: &[SwagInventoryItem],
+) -> Result<HashMap<i32, InventoryMovement>> {
+    let mut movements: HashMap<i32, InventoryMovement> = HashMap::new();
+
+    let counts: HashMap<i32, DateTime<Utc>> = inventory.iter().filter_map(|i| Some((i.id, i.counted_at?))).collect();
+    let by_barcode: HashMap<&str, i32> = inventory.iter().map(|i| (i.barcode.as_str(), i.id)).collect();
+    for scan in BarcodeScans::get_from_db(db, company.id).await? {
+        let id = match by_barcode.get(scan.barcode.trim().to_uppercase().as_str()) {
+            Some(id) => *id,
+            None => continue,
+        };
+        match counts.get(&id) {
+            Some(counted_at) if scan.time > *counted_at => {}
+            _ => continue,
+        }
+
+        let movement = movements.entry(id).or_default();
+        if scan.action == SwagBarcodeScanAction::Receive.to_string() {
+            movement.received += scan.quantity;
+        } else {
+            movement.shipped += scan.quantity;
+        }
+    }
+
+    let shipped = swag_shipment_items::dsl::swag_shipment_items
+        .filter(swag_shipment_items::dsl::cio_company_id.eq(company.id))
+        .filter(swag_shipment_items::dsl::subtracted_at.is_not_null())
+        .load_async::<SwagShipmentItem>(db.pool())
+        .await?;
+    for item in shipped {
+        match (counts.get(&item.swag_inventory_item_id), item.subtracted_at) {
+            (Some(counted_at), Some(subtracted_at)) if subtracted_at > *counted_at => {}
+            _ => continue,
+        }
+        movements.entry(item.swag_inventory_item_id).or_default().shipped += item.quantity;
+    }
+
+    Ok(movements)
+}
+
+/// Let a channel know about the items drifting more than we accept.
+async fn post_flagged_variances(
+    db: &Database,
+    company: &Company,
+    channel: &str,
+    report: &InventoryReconciliationReport,
+    kind: &str,
+) -> Result<()> {
+    let flagged: Vec<&InventoryVariance> = report
+        .variances
+        .iter()
+        .filter(|v| v.flagged && v.kind == kind)
+        .collect();
+    if flagged.is_empty() || channel.is_empty() {
+        return Ok(());
+    }
+
+    let msg = FormattedMessage {
+        channel: channel.to_string(),
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: format!("{} inventory drift", kind),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        "{}\n\n<{}|Full report>",
+                        flagged
+                            .iter()
+                            .map(|v| format!(
+                                "• *{}* expected {}, counted {} ({:+})",
+                                v.name, v.expected, v.counted, v.drift
+                            ))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        report.pdf_link
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+/// Reconcile the swag and asset inventory of a company once a month: compare it with the
+/// counts in Airtable, upload the variance report to Google Drive, and flag the items
+/// drifting more than we accept.
+pub async fn reconcile_inventory(
+    db: &Database,
+    company: &Company,
+    config: &InventoryReconciliationConfig,
+) -> Result<Option<InventoryReconciliationReport>> {
+    let now = Utc::now();
+    let last = inventory_reconciliations::dsl::inventory_reconciliations
+        .filter(inventory_reconciliations::dsl::cio_company_id.eq(company.id))
+        .order_by(inventory_reconciliations::dsl::generated_at.desc())
+        .first_async::<InventoryReconciliation>(db.pool())
+        .await
+        .ok();
+    if let Some(last) = &last {
+        if last.generated_at.year() == now.year() && last.generated_at.month() == now.month() {
+            // We already reconciled this month.
+            return Ok(None);
+        }
+    }
+    // Only the counts people made since the last reconciliation are new.
+    let since = last.map(|l| l.generated_at);
+
+    let mut variances = Vec::new();
+
+    if !company.airtable_base_id_swag.is_empty() {
+        // The stock in the database is what the receipts and shipments we recorded give us,
+        // the counts people made are in Airtable.
+        let records: Vec<airtable_api::Record<SwagInventoryItem>> = company
+            .authenticate_airtable(&company.airtable_base_id_swag)
+            .list_records(&SwagInventoryItem::airtable_table(), "Grid view", vec![])
+            .await?;
+        let mut inventory: Vec<SwagInventoryItem> = SwagInventoryItems::get_from_db(db, company.id)
+            .await?
+            .into_iter()
+            .collect();
+        for item in inventory.iter_mut() {
+            if let Some(record) = records.iter().find(|r| r.id == item.airtable_record_id) {
+                item.counted_stock = record.fields.counted_stock;
+                item.counted_at = record.fields.counted_at;
+            }
+        }
+
+        let movements = swag_movements(db, company, &inventory).await?;
+        variances.append(&mut swag_variances(&inventory, &movements, since, config.max_drift));
+    }
+
+    if !company.airtable_base_id_assets.is_empty() {
+        let counted: HashMap<String, Option<NaiveDate>> = company
+            .authenticate_airtable(&company.airtable_base_id_assets)
+            .list_records(&AssetItem::airtable_table(), "Grid view", vec![])
+            .await?
+            .into_iter()
+            .map(|r| (r.id, r.fields.last_counted_date))
+            .collect();
+        let mut recorded: Vec<AssetItem> = AssetItems::get_from_db(db, company.id).await?.into_iter().collect();
+        for item in recorded.iter_mut() {
+            if let Some(last_counted_date) = counted.get(&item.airtable_record_id) {
+                item.last_counted_date = *last_counted_date;
+            }
+        }
+        variances.append(&mut asset_variances(
+            &recorded,
+            since.map(|s| s.date().naive_utc()),
+            config.max_drift,
+        ));
+    }
+
+    if variances.is_empty() {
+        // Nobody counted anything since the last reconciliation.
+        return Ok(None);
+    }
+
+    let mut report = InventoryReconciliationReport::new(variances);
+
+    // Initialize the Google Drive client.
+    let drive_client = company.authenticate_google_drive(db).await?;
+
+    // Figure out where our directory is.
+    // It should be in the shared drive : "Automated Documents"/"inventory"/"reconciliations"
+    let shared_drive = drive_client.drives().get_by_name("Automated Documents").await?;
+    let drive_id = shared_drive.id.to_string();
+    let inventory_id = drive_client.files().create_folder(&drive_id, "", "inventory").await?;
+    let parent_id = drive_client
+        .files()
+        .create_folder(&drive_id, &inventory_id, "reconciliations")
+        .await?;
+
+    let file_name = format!("Inventory Reconciliation {}", report.generated_at.format("%Y-%m"));
+
+    let csv_file = drive_client
+        .files()
+        .create_or_update(
+            &drive_id,
+            &parent_id,
+            &format!("{}.csv", file_name),
+            "text/csv",
+            &report.to_csv()?,
+        )
+        .await?;
+    report.csv_link = format!("https://drive.google.com/uc?export=download&id={}", csv_file.id);
+
+    let pdf_file = drive_client
+        .files()
+        .create_or_update(
+            &drive_id,
+            &parent_id,
+            &format!("{}.pdf", file_name),
+            "application/pdf",
+            &report.to_pdf(company)?,
+        )
+        .await?;
+    report.pdf_link = format!("https://drive.google.com/uc?export=download&id={}", pdf_file.id);
+
+    post_flagged_variances(db, company, &company.slack_channel_swag, &report, "Swag").await?;
+    post_flagged_variances(db, company, &company.slack_channel_finance, &report, "Asset").await?;
+
+    diesel::insert_into(inventory_reconciliations::table)
+        .values(NewInventoryReconciliation {
+            generated_at: now,
+            item_count: report.variances.len() as i32,
+            flagged: report.flagged,
+            csv_link: report.csv_link.to_string(),
+            pdf_link: report.pdf_link.to_string(),
+            cio_company_id: company.id,
+        })
+        .execute_async(db.pool())
+        .await?;
+
+    info!(
+        "reconciled the inventory of {} items, {} flagged",
+        report.variances.len(),
+        report.flagged
+    );
+
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use super::{asset_variances, swag_variances, InventoryMovement, InventoryReconciliationReport};
+    use crate::tests::mock_record;
+    use crate::{asset_inventory::AssetItem, swag_inventory::SwagInventoryItem};
+
+    fn mock_swag(
+        id: i32,
+        name: &str,
+        current_stock: i32,
+        counted_stock: i32,
+        counted_day: Option<u32>,
+    ) -> SwagInventoryItem {
+        let mut item: SwagInventoryItem = mock_record(json!({}));
+        item.id = id;
+        item.name = name.to_string();
+        item.current_stock = current_stock;
+        item.counted_stock = counted_stock;
+        item.counted_at = counted_day.map(|d| Utc.ymd(2023, 11, d).and_hms(12, 0, 0));
+        item
+    }
+
+    fn mock_asset(type_: &str, status: &str, counted: Option<NaiveDate>) -> AssetItem {
+        let mut item: AssetItem = mock_record(json!({}));
+        item.type_ = type_.to_string();
+        item.status = status.to_string();
+        item.last_counted_date = counted;
+        item
+    }
+
+    #[test]
+    fn test_inventory_variances() {
+        let since = Utc.ymd(2023, 11, 10).and_hms(0, 0, 0);
+        let inventory = vec![
+            mock_swag(1, "Hoodie - M", 18, 11, Some(20)),
+            mock_swag(2, "Hat", 4, 10, Some(20)),
+            // Counted before the last reconciliation, so we already compared it.
+            mock_swag(3, "Sticker", 500, 20, Some(5)),
+            // Never counted.
+            mock_swag(4, "Pin", 30, 0, None),
+        ];
+        // What we recorded since the hoodies were counted.
+        let movements: HashMap<i32, InventoryMovement> = vec![(
+            1,
+            InventoryMovement {
+                received: 12,
+                shipped: 4,
+            },
+        )]
+        .into_iter()
+        .collect();
+
+        let mut variances = swag_variances(&inventory, &movements, Some(since), 2);
+        assert_eq!(2, variances.len());
+        assert_eq!(
+            (10, 11, 1, false),
+            (
+                variances[0].expected,
+                variances[0].counted,
+                variances[0].drift,
+                variances[0].flagged
+            )
+        );
+        assert_eq!(
+            (4, 10, 6, true),
+            (
+                variances[1].expected,
+                variances[1].counted,
+                variances[1].drift,
+                variances[1].flagged
+            )
+        );
+
+        let counted = Some(NaiveDate::from_ymd(2023, 11, 20));
+        let before = Some(NaiveDate::from_ymd(2023, 11, 5));
+        let since = Some(since.date().naive_utc());
+        // Nothing is compared until someone counts.
+        assert!(asset_variances(&[mock_asset("Laptop", "", before)], since, 0).is_empty());
+
+        let recorded = vec![
+            mock_asset("Laptop", "", counted),
+            mock_asset("Laptop", "", before),
+            mock_asset(" ", "", counted),
+            mock_asset("Monitor", "", None),
+            mock_asset("Monitor", "retired", None),
+        ];
+        variances.append(&mut asset_variances(&recorded, since, 0));
+
+        let report = InventoryReconciliationReport::new(variances);
+        assert_eq!(3, report.flagged);
+        let csv = String::from_utf8(report.to_csv().unwrap()).unwrap();
+        assert_eq!(
+            vec![
+                "Kind,Name,Expected,Counted,Drift,Flagged",
+                "Asset,Laptop,2,1,-1,Yes",
+                "Asset,Monitor,1,0,-1,Yes",
+                "Asset,Other,1,1,0,",
+                "Swag,Hat,4,10,6,Yes",
+                "Swag,Hoodie - M,10,11,1,",
+            ],
+            csv.lines().collect::<Vec<_>>()
+        );
+    }
+}
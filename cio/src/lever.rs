@@ -0,0 +1,721 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use diesel::{ExpressionMethods, PgTextExpressionMethods, QueryDsl, TextExpressionMethods};
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    applicant_status::Status,
+    applicants::{Applicant, NewApplicant},
+    application_form::ApplicationForm,
+    asset_inventory::escape_like,
+    companies::Company,
+    db::Database,
+    interviews::{ApplicantInterview, NewApplicantInterview},
+    schema::{applicants, lever_opportunities},
+    tenancy::CompanyScoped,
+};
+
+static LEVER_ENDPOINT: &str = "https://api.lever.co/v1/";
+
+/// The prefix of the sheet id of the applicants we import from Lever, followed by the id of
+/// their opportunity. The applicants who also applied with our form keep their sheet id.
+static LEVER_SHEET_ID_PREFIX: &str = "lever-";
+
+/// A minimal client for the Lever API. We only use it for mirroring opportunities and their
+/// interviews into our applicants, so it only implements what we need for that.
+pub struct Lever {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LeverPage<T> {
+    data: Vec<T>,
+    #[serde(default, rename = "hasNext")]
+    has_next: bool,
+    #[serde(default)]
+    next: Option<String>,
+}
+
+/// A stage, a posting or an archive reason.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LeverRef {
+    pub id: String,
+    #[serde(default)]
+    pub text: String,
+    /// For archive reasons, one of `hired` or `non-hired`.
+    #[serde(default)]
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LeverPhone {
+    #[serde(default)]
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LeverArchived {
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default, rename = "archivedAt")]
+    pub archived_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LeverApplication {
+    pub id: String,
+    #[serde(default)]
+    pub posting: Option<String>,
+}
+
+/// A candidate moving through the pipeline of a posting.
+/// FROM: https://hire.lever.co/developer/documentation#opportunities
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LeverOpportunity {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub emails: Vec<String>,
+    #[serde(default)]
+    pub phones: Vec<LeverPhone>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub links: Vec<String>,
+    #[serde(default, rename = "createdAt")]
+    pub created_at: Option<i64>,
+    #[serde(default, rename = "updatedAt")]
+    pub updated_at: Option<i64>,
+    /// The id of the stage of the pipeline the opportunity is in.
+    #[serde(default)]
+    pub stage: Option<String>,
+    #[serde(default)]
+    pub archived: Option<LeverArchived>,
+    #[serde(default)]
+    pub applications: Vec<LeverApplication>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LeverInterviewer {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub email: String,
+}
+
+/// An interview of an opportunity, with its panel.
+/// FROM: https://hire.lever.co/developer/documentation#interviews
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LeverInterview {
+    pub id: String,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub interviewers: Vec<LeverInterviewer>,
+    /// When the interview starts, in milliseconds.
+    #[serde(default)]
+    pub date: Option<i64>,
+    /// How long the interview is, in minutes.
+    #[serde(default)]
+    pub duration: Option<i64>,
+    #[serde(default, rename = "gcalEventUrl")]
+    pub gcal_event_url: Option<String>,
+    #[serde(default, rename = "canceledAt")]
+    pub canceled_at: Option<i64>,
+}
+
+impl Lever {
+    pub fn new(api_key: &str) -> Self {
+        Lever {
+            client: reqwest::Client::new(),
+            api_key: api_key.to_string(),
+        }
+    }
+
+    async fn get_all<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<Vec<T>> {
+        let url = format!("{}{}", LEVER_ENDPOINT, path);
+        let mut all: Vec<T> = Default::default();
+        let mut offset: Option<String> = None;
+        loop {
+            let mut req = self
+                .client
+                .get(&url)
+                .basic_auth(&self.api_key, Some(""))
+                .query(query)
+                .query(&[("limit", "100")]);
+            if let Some(offset) = &offset {
+                req = req.query(&[("offset", offset)]);
+            }
+
+            let resp = req.send().await?;
+            let status = resp.status();
+            if !status.is_success() {
+                bail!(
+                    "lever request to {} failed: status code: {}, body: {}",
+                    url,
+                    status,
+                    resp.text().await?
+                );
+            }
+
+            let page: LeverPage<T> = resp.json().await?;
+            all.extend(page.data);
+
+            match page.next {
+                Some(next) if page.has_next && !next.is_empty() => offset = Some(next),
+                _ => break,
+            }
+        }
+
+        Ok(all)
+    }
+
+    /// List all the opportunities, with their applications.
+    pub async fn list_opportunities(&self) -> Result<Vec<LeverOpportunity>> {
+        self.get_all("opportunities", &[("expand", "applications")]).await
+    }
+
+    /// List the interviews of an opportunity.
+    pub async fn list_interviews(&self, opportunity_id: &str) -> Result<Vec<LeverInterview>> {
+        self.get_all(&format!("opportunities/{}/interviews", opportunity_id), &[])
+            .await
+    }
+
+    pub async fn list_stages(&self) -> Result<Vec<LeverRef>> {
+        self.get_all("stages", &[]).await
+    }
+
+    pub async fn list_postings(&self) -> Result<Vec<LeverRef>> {
+        self.get_all("postings", &[]).await
+    }
+
+    pub async fn list_archive_reasons(&self) -> Result<Vec<LeverRef>> {
+        self.get_all("archive_reasons", &[]).await
+    }
+}
+
+/// Return the status of our applicants for a stage of a Lever pipeline. Teams rename the
+/// stages as they like, so we go with the words of the stages Lever comes with.
+pub fn status_from_lever_stage(stage: &str) -> Status {
+    let stage = stage.to_lowercase();
+    if stage.contains("offer") {
+        Status::GivingOffer
+    } else if stage.contains("reference") || stage.contains("debrief") {
+        Status::Debrief
+    } else if stage.contains("interview") || stage.contains("on-site") || stage.contains("onsite") {
+        Status::Interviewing
+    } else if stage.contains("screen") || stage.contains("assessment") || stage.contains("exercise") {
+        Status::NextSteps
+    } else {
+        Status::NeedsToBeTriaged
+    }
+}
+
+/// Return the status of our applicants for the reason an opportunity was archived.
+pub fn status_from_lever_archive_reason(reason: &LeverRef) -> Status {
+    let text = reason.text.to_lowercase();
+    if reason.status == "hired" {
+        Status::Hired
+    } else if text.contains("withdr") || text.contains("offer declined") {
+        Status::Withdrawn
+    } else {
+        Status::Declined
+    }
+}
+
+/// Return the key of where an opportunity is in the pipeline, its stage or why it was
+/// archived. We keep the last one we synced to know when Lever moved the opportunity.
+pub fn lever_stage_key(opportunity: &LeverOpportunity) -> String {
+    match &opportunity.archived {
+        Some(archived) => format!("archived:{}", archived.reason.as_deref().unwrap_or_default()),
+        None => opportunity.stage.clone().unwrap_or_default(),
+    }
+}
+
+/// Return the status of our applicants for an opportunity in Lever.
+pub fn status_from_lever(
+    opportunity: &LeverOpportunity,
+    stages: &BTreeMap<String, LeverRef>,
+    archive_reasons: &BTreeMap<String, LeverRef>,
+) -> Status {
+    if let Some(archived) = &opportunity.archived {
+        return archived
+            .reason
+            .as_ref()
+            .and_then(|r| archive_reasons.get(r))
+            .map(status_from_lever_archive_reason)
+            .unwrap_or(Status::Declined);
+    }
+
+    opportunity
+        .stage
+        .as_ref()
+        .and_then(|s| stages.get(s))
+        .map(|s| status_from_lever_stage(&s.text))
+        .unwrap_or_default()
+}
+
+/// Return the status to move an applicant to, once we know where its opportunity is in Lever.
+/// We follow Lever only when it moved the opportunity since the last sync. The first time we
+/// sync an applicant who also applied with our form, we leave it alone unless it is still
+/// waiting to be triaged, so we never move it back.
+pub fn lever_status_update(synced_stage: Option<&str>, stage: &str, local: Status, remote: Status) -> Option<Status> {
+    match synced_stage {
+        Some(synced) if synced == stage => None,
+        None if local != Status::NeedsToBeTriaged => None,
+        _ if local == remote => None,
+        _ => Some(remote),
+    }
+}
+
+/// Return the id of the Google Calendar event of a Lever interview from its link, so we do not
+/// get the interview twice when the event is also on our interviews calendar. The `eid` of the
+/// link is the id of the event and its calendar, encoded in base64.
+pub fn google_event_id_from_url(url: &str) -> Option<String> {
+    let eid = url
+        .split(|c: char| c == '?' || c == '&')
+        .find_map(|part| part.strip_prefix("eid="))?
+        .trim_end_matches('=');
+    let decoded = base64::decode_config(eid, base64::URL_SAFE_NO_PAD)
+        .or_else(|_| base64::decode_config(eid, base64::STANDARD_NO_PAD))
+        .ok()?;
+
+    String::from_utf8(decoded)
+        .ok()?
+        .split_whitespace()
+        .next()
+        .map(|id| id.to_string())
+}
+
+/// Returns if we need to sync the interviews of an opportunity. Each opportunity costs us a
+/// request, so we skip the archived ones Lever did not update since we last synced them.
+pub fn needs_lever_interviews_sync(opportunity: &LeverOpportunity, synced_at: Option<DateTime<Utc>>) -> bool {
+    let archived = match &opportunity.archived {
+        Some(archived) => archived,
+        None => return true,
+    };
+
+    match (synced_at, opportunity.updated_at.or(archived.archived_at)) {
+        (Some(synced_at), Some(updated_at)) => Utc.timestamp_millis(updated_at) > synced_at,
+        (Some(_), None) => false,
+        (None, _) => true,
+    }
+}
+
+/// What we last synced of a Lever opportunity.
+#[derive(Debug, Queryable, PartialEq, Clone)]
+pub struct LeverOpportunitySync {
+    pub id: i32,
+    pub lever_opportunity_id: String,
+    pub applicant_id: i32,
+    pub synced_stage: String,
+    pub synced_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone)]
+#[diesel(table_name = lever_opportunities)]
+struct NewLeverOpportunitySync {
+    lever_opportunity_id: String,
+    applicant_id: i32,
+    synced_stage: String,
+    synced_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+async fn get_lever_opportunity_syncs(
+    db: &Database,
+    cio_company_id: i32,
+) -> Result<BTreeMap<String, LeverOpportunitySync>> {
+    let syncs = lever_opportunities::dsl::lever_opportunities
+        .filter(lever_opportunities::dsl::cio_company_id.eq(cio_company_id))
+        .load_async::<LeverOpportunitySync>(db.pool())
+        .await?;
+
+    Ok(syncs
+        .into_iter()
+        .map(|s| (s.lever_opportunity_id.to_string(), s))
+        .collect())
+}
+
+async fn set_lever_opportunity_sync(
+    db: &Database,
+    opportunity: &LeverOpportunity,
+    applicant: &Applicant,
+) -> Result<()> {
+    let sync = NewLeverOpportunitySync {
+        lever_opportunity_id: opportunity.id.to_string(),
+        applicant_id: applicant.id,
+        synced_stage: lever_stage_key(opportunity),
+        synced_at: Utc::now(),
+        cio_company_id: applicant.cio_company_id,
+    };
+
+    diesel::insert_into(lever_opportunities::table)
+        .values(sync.clone())
+        .on_conflict((
+            lever_opportunities::dsl::cio_company_id,
+            lever_opportunities::dsl::lever_opportunity_id,
+        ))
+        .do_update()
+        .set(sync)
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Convert a Lever opportunity to an applicant.
+fn new_applicant_from_lever(
+    opportunity: &LeverOpportunity,
+    role: &str,
+    status: Status,
+    cio_company_id: i32,
+) -> NewApplicant {
+    let link = |domain: &str| {
+        opportunity
+            .links
+            .iter()
+            .find(|l| l.contains(domain))
+            .map(|l| l.to_string())
+            .unwrap_or_default()
+    };
+
+    let form = ApplicationForm {
+        name: opportunity.name.trim().to_string(),
+        email: opportunity.emails.first().map(|e| e.to_string()).unwrap_or_default(),
+        phone: opportunity
+            .phones
+            .first()
+            .map(|p| p.value.to_string())
+            .unwrap_or_default(),
+        role: role.to_string(),
+        interested_in: Default::default(),
+        location: opportunity.location.clone().unwrap_or_default(),
+        github: link("github.com"),
+        linkedin: link("linkedin.com"),
+        portfolio: Default::default(),
+        website: opportunity
+            .links
+            .iter()
+            .find(|l| !l.contains("github.com") && !l.contains("linkedin.com"))
+            .map(|l| l.to_string())
+            .unwrap_or_default(),
+        resume: Default::default(),
+        materials: Default::default(),
+        portfolio_pdf: Default::default(),
+        locale: Default::default(),
+        cio_company_id,
+    };
+
+    let mut applicant: NewApplicant = form.into();
+    applicant.sheet_id = format!("{}{}", LEVER_SHEET_ID_PREFIX, opportunity.id);
+    applicant.status = status.to_string();
+    applicant.submitted_time = opportunity
+        .created_at
+        .map(|ms| Utc.timestamp_millis(ms))
+        .unwrap_or_else(Utc::now);
+
+    applicant
+}
+
+/// Find the applicant of an opportunity: the one we synced it with, or one who applied to the
+/// same role with our form.
+async fn find_lever_applicant(
+    db: &Database,
+    sync: Option<&LeverOpportunitySync>,
+    new_applicant: &NewApplicant,
+) -> Option<Applicant> {
+    if let Some(sync) = sync {
+        return Applicant::get_by_id_for_company(db, new_applicant.cio_company_id, sync.applicant_id)
+            .await
+            .ok();
+    }
+
+    // People do not always write their email the same way, and our roles are not always named
+    // like the postings.
+    applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(new_applicant.cio_company_id))
+        .filter(applicants::dsl::email.ilike(escape_like(new_applicant.email.trim())))
+        .filter(applicants::dsl::sheet_id.not_like(format!("{}%", LEVER_SHEET_ID_PREFIX)))
+        .order_by(applicants::dsl::id.desc())
+        .load_async::<Applicant>(db.pool())
+        .await
+        .ok()?
+        .into_iter()
+        .find(|a| a.role.trim().eq_ignore_ascii_case(new_applicant.role.trim()))
+}
+
+/// Sync the interviews of an opportunity, with their panels, into our interviews.
+async fn refresh_lever_interviews(
+    db: &Database,
+    company: &Company,
+    lever: &Lever,
+    opportunity: &LeverOpportunity,
+    applicant: &Applicant,
+) -> Result<()> {
+    for lever_interview in lever.list_interviews(&opportunity.id).await? {
+        let event_link = lever_interview.gcal_event_url.clone().unwrap_or_default();
+        let google_event_id = google_event_id_from_url(&event_link)
+            .unwrap_or_else(|| format!("{}{}", LEVER_SHEET_ID_PREFIX, lever_interview.id));
+        let existing = ApplicantInterview::get_from_db(db, google_event_id.to_string())
+            .await
+            .filter(|existing| existing.belongs_to(company.id));
+
+        if lever_interview.canceled_at.is_some() {
+            if let Some(mut existing) = existing.filter(|existing| existing.cancelled_at.is_none()) {
+                existing.cancelled_at = Some(Utc::now());
+                existing.update(db).await?;
+            }
+            continue;
+        }
+
+        let start_time = match lever_interview.date {
+            Some(ms) => Utc.timestamp_millis(ms),
+            None => continue,
+        };
+        let interviewers: Vec<String> = lever_interview
+            .interviewers
+            .iter()
+            .filter(|i| !i.email.is_empty())
+            .map(|i| i.email.to_string())
+            .collect();
+        if interviewers.is_empty() {
+            // We only care about interviews where the candidate has interviewers.
+            continue;
+        }
+
+        let usernames: Vec<String> = interviewers
+            .iter()
+            .map(|email| {
+                email
+                    .trim_end_matches(&company.gsuite_domain)
+                    .trim_end_matches(&company.domain)
+                    .trim_end_matches('@')
+                    .to_string()
+            })
+            .collect();
+
+        let interview = NewApplicantInterview {
+            start_time,
+            end_time: start_time + Duration::minutes(lever_interview.duration.unwrap_or(60)),
+            name: format!("{} ({})", applicant.name, usernames.join(", ")),
+            email: applicant.email.to_string(),
+            interviewers,
+            google_event_id,
+            event_link,
+            applicant: if applicant.airtable_record_id.is_empty() {
+                Default::default()
+            } else {
+                vec![applicant.airtable_record_id.to_string()]
+            },
+            // We check the panels for conflicts when we sync the interviews calendar.
+            conflicts: existing.map(|e| e.conflicts).unwrap_or_default(),
+            cancelled_at: None,
+            cio_company_id: company.id,
+        };
+        interview.upsert(db).await?;
+    }
+
+    Ok(())
+}
+
+/// Mirror the opportunities of Lever into our applicants, and their interviews into our
+/// interviews, so the hiring data is in one place whichever tool the applicants came from.
+pub async fn refresh_lever(db: &Database, company: &Company) -> Result<()> {
+    if company.lever_api_key.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let lever = company.authenticate_lever();
+    let by_id = |refs: Vec<LeverRef>| -> BTreeMap<String, LeverRef> {
+        refs.into_iter().map(|r| (r.id.to_string(), r)).collect()
+    };
+    let stages = by_id(lever.list_stages().await?);
+    let postings = by_id(lever.list_postings().await?);
+    let archive_reasons = by_id(lever.list_archive_reasons().await?);
+    let syncs = get_lever_opportunity_syncs(db, company.id).await?;
+
+    let mut synced = 0;
+    for opportunity in lever.list_opportunities().await? {
+        // The leads are not applying to a posting yet.
+        let role = match opportunity
+            .applications
+            .iter()
+            .find_map(|a| a.posting.as_ref().and_then(|p| postings.get(p)))
+        {
+            Some(posting) => posting.text.to_string(),
+            None => continue,
+        };
+
+        let remote = status_from_lever(&opportunity, &stages, &archive_reasons);
+        let new_applicant = new_applicant_from_lever(&opportunity, &role, remote, company.id);
+        if new_applicant.email.is_empty() {
+            warn!("lever opportunity {} has no email, skipping", opportunity.id);
+            continue;
+        }
+
+        let sync = syncs.get(&opportunity.id);
+        let mut applicant = match find_lever_applicant(db, sync, &new_applicant).await {
+            Some(applicant) => applicant,
+            None => {
+                info!(
+                    "importing lever opportunity {} of {}",
+                    opportunity.id, new_applicant.name
+                );
+                new_applicant.upsert(db).await?
+            }
+        };
+
+        let local: Status = applicant.status.parse().unwrap_or_default();
+        let stage = lever_stage_key(&opportunity);
+        if let Some(status) = lever_status_update(sync.map(|s| s.synced_stage.as_str()), &stage, local, remote) {
+            info!(
+                "lever moved {} for {} to {}",
+                applicant.name,
+                applicant.role,
+                status.to_string()
+            );
+            applicant.status = status.to_string();
+            applicant = applicant.update(db).await?;
+        }
+
+        set_lever_opportunity_sync(db, &opportunity, &applicant).await?;
+
+        if needs_lever_interviews_sync(&opportunity, sync.map(|s| s.synced_at)) {
+            if let Err(e) = refresh_lever_interviews(db, company, &lever, &opportunity, &applicant).await {
+                // Try again on the next sync.
+                warn!("could not sync the lever interviews of {}: {}", applicant.name, e);
+            }
+        }
+        synced += 1;
+    }
+
+    info!("synced {} lever opportunities", synced);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{
+        google_event_id_from_url, lever_stage_key, lever_status_update, needs_lever_interviews_sync, status_from_lever,
+        LeverArchived, LeverOpportunity, LeverRef,
+    };
+    use crate::applicant_status::Status;
+
+    #[test]
+    fn test_status_from_lever() {
+        let refs = |refs: &[(&str, &str, &str)]| -> BTreeMap<String, LeverRef> {
+            refs.iter()
+                .map(|(id, text, status)| {
+                    (
+                        id.to_string(),
+                        LeverRef {
+                            id: id.to_string(),
+                            text: text.to_string(),
+                            status: status.to_string(),
+                        },
+                    )
+                })
+                .collect()
+        };
+        let stages = refs(&[("s1", "New applicant", ""), ("s2", "On-site interview", "")]);
+        let reasons = refs(&[
+            ("r1", "Hired", "hired"),
+            ("r2", "Withdrew", "non-hired"),
+            ("r3", "Underqualified", "non-hired"),
+        ]);
+
+        let mut opportunity = LeverOpportunity {
+            id: "o1".to_string(),
+            stage: Some("s2".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(Status::Interviewing, status_from_lever(&opportunity, &stages, &reasons));
+        assert_eq!("s2", lever_stage_key(&opportunity));
+
+        opportunity.stage = Some("s1".to_string());
+        assert_eq!(
+            Status::NeedsToBeTriaged,
+            status_from_lever(&opportunity, &stages, &reasons)
+        );
+
+        for (reason, status) in [
+            ("r1", Status::Hired),
+            ("r2", Status::Withdrawn),
+            ("r3", Status::Declined),
+        ] {
+            opportunity.archived = Some(LeverArchived {
+                reason: Some(reason.to_string()),
+                archived_at: None,
+            });
+            assert_eq!(status, status_from_lever(&opportunity, &stages, &reasons));
+            assert_eq!(format!("archived:{}", reason), lever_stage_key(&opportunity));
+        }
+    }
+
+    #[test]
+    fn test_lever_status_update() {
+        use Status::*;
+
+        // Lever moved the opportunity.
+        assert_eq!(
+            Some(Interviewing),
+            lever_status_update(Some("s1"), "s2", NextSteps, Interviewing)
+        );
+        // Lever did not move it, we keep what we did.
+        assert_eq!(None, lever_status_update(Some("s2"), "s2", Debrief, Interviewing));
+        // An applicant who applied with our form too, and is further along.
+        assert_eq!(None, lever_status_update(None, "s1", Interviewing, NeedsToBeTriaged));
+        assert_eq!(
+            Some(NextSteps),
+            lever_status_update(None, "s3", NeedsToBeTriaged, NextSteps)
+        );
+    }
+
+    #[test]
+    fn test_needs_lever_interviews_sync() {
+        let synced_at = Utc.ymd(2024, 3, 1).and_hms(12, 0, 0);
+        let mut opportunity = LeverOpportunity {
+            id: "o1".to_string(),
+            ..Default::default()
+        };
+        // The opportunities in the pipeline always sync.
+        assert!(needs_lever_interviews_sync(&opportunity, Some(synced_at)));
+
+        opportunity.archived = Some(LeverArchived {
+            reason: Some("r1".to_string()),
+            archived_at: Some((synced_at - Duration::days(30)).timestamp_millis()),
+        });
+        assert!(needs_lever_interviews_sync(&opportunity, None));
+        assert!(!needs_lever_interviews_sync(&opportunity, Some(synced_at)));
+
+        // Archived since the last sync.
+        opportunity.updated_at = Some((synced_at + Duration::hours(1)).timestamp_millis());
+        assert!(needs_lever_interviews_sync(&opportunity, Some(synced_at)));
+    }
+
+    #[test]
+    fn test_google_event_id_from_url() {
+        assert_eq!(
+            Some("4f8k2j9d0s1a".to_string()),
+            google_event_id_from_url(
+                "https://www.google.com/calendar/event?eid=NGY4azJqOWQwczFhIGludGVydmlld3NAZ3JvdXAuY2FsZW5kYXIuZ29vZ2xlLmNvbQ=="
+            )
+        );
+        assert_eq!(None, google_event_id_from_url("https://hire.lever.co/interviews/1234"));
+        assert_eq!(None, google_event_id_from_url(""));
+    }
+}
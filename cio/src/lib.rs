@@ -4,58 +4,118 @@
 
 pub mod airtable;
 pub mod analytics;
+pub mod anonymize;
 pub mod api_tokens;
 pub mod app_config;
 pub mod applicant_reviews;
 pub mod applicant_status;
 pub mod applicant_uploads;
+pub mod applicant_withdrawals;
 pub mod applicants;
 pub mod application_form;
+pub mod asset_audits;
+pub mod asset_depreciation;
+pub mod asset_insurance;
 pub mod asset_inventory;
+pub mod asset_loaners;
+pub mod asset_maintenance;
+pub mod asset_orders;
+pub mod asset_refresh;
 pub mod auth_logins;
+pub mod business_calendar;
 pub mod certs;
 pub mod cloud_dns;
 pub mod cloudflare;
 pub mod colors;
+pub mod community_events;
 pub mod companies;
+pub mod conferences;
 pub mod configs;
+pub mod contractors;
 pub mod core;
+pub mod credentials_export;
 pub mod customers;
 pub mod db;
 pub mod dns_providers;
 pub mod dns_proxy;
+pub mod drive_uploads;
 #[macro_use]
 pub mod enclose;
 pub mod features;
 pub mod finance;
+pub mod first_week;
+pub mod focus_time;
 pub mod functions;
 pub mod github_commits;
 pub mod github_prs;
+pub mod greenhouse;
 pub mod gsuite;
 pub mod huddles;
+pub mod inbound_email;
+pub mod incidents;
+pub mod interview_conflicts;
+pub mod interview_scorecards;
 pub mod interviews;
+pub mod inventory_reconciliation;
 pub mod journal_clubs;
+pub mod lever;
+pub mod locations;
 pub mod mailerlite;
 pub mod mailing_list;
+pub mod meeting_action_items;
+pub mod meeting_attachments;
+pub mod meeting_retention;
+pub mod meeting_summaries;
+pub mod meeting_transcript_docs;
+pub mod metrics;
+pub mod microsoft_graph;
+pub mod notifications;
 pub mod octorust_utils;
 pub mod printer;
+pub mod printful;
+pub mod profiles;
 pub mod providers;
 pub mod rack_line;
+pub mod rate_limit;
+pub mod receiving;
+pub mod reconcile;
 pub mod recorded_meetings;
 pub mod repos;
 pub mod rfd;
+pub mod role_addresses;
+pub mod roles;
+pub mod saga_steps;
 pub mod schema;
+pub mod shipment_eta;
 pub mod shipment_status;
 pub mod shipments;
+pub mod shopify;
 pub mod shorturls;
+pub mod sign_in_audit;
+pub mod skills;
 pub mod states;
+pub mod summarization;
+pub mod swag_bundles;
 pub mod swag_inventory;
+pub mod swag_reorders;
+pub mod swag_requests;
+pub mod swag_shipments;
 pub mod swag_store;
 pub mod tailscale;
 pub mod templates;
+pub mod tenancy;
+#[cfg(test)]
+pub mod tests;
+pub mod transcription;
 pub mod travel;
+pub mod user_import;
 pub mod utils;
+pub mod virus_scan;
+pub mod webhook_transforms;
+pub mod weekly_digest;
+pub mod workload_identity;
 pub mod zoho;
+pub mod zoom_storage;
 
 #[macro_use]
 extern crate diesel;
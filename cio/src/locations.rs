@@ -0,0 +1,485 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono_tz::Tz;
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    companies::Company,
+    configs::{Building, Buildings},
+    db::Database,
+    schema::{asset_audits, locations, package_pickups, printers, recorded_meetings, users},
+};
+
+/// The kinds of locations.
+pub static LOCATION_KIND_OFFICE: &str = "office";
+pub static LOCATION_KIND_WAREHOUSE: &str = "warehouse";
+pub static LOCATION_KIND_REMOTE: &str = "remote";
+pub static LOCATION_KIND_VIRTUAL: &str = "virtual";
+static LOCATION_KINDS: &[&str] = &[
+    LOCATION_KIND_OFFICE,
+    LOCATION_KIND_WAREHOUSE,
+    LOCATION_KIND_REMOTE,
+    LOCATION_KIND_VIRTUAL,
+];
+
+fn default_location_kind() -> String {
+    LOCATION_KIND_OFFICE.to_string()
+}
+
+/// A place things happen at, like an office or the video calls, by name in the configs. The
+/// free-form locations of the meetings, audits, package pickups and printers are matched to
+/// them, and the buildings of the users and printers to their `building`.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct LocationConfig {
+    /// `office`, `warehouse`, `remote`, or `virtual`.
+    #[serde(default = "default_location_kind", rename = "type")]
+    pub kind: String,
+    /// The address of the location, the one of its building if it is empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub address: String,
+    /// The time zone of the location, like `America/Los_Angeles`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub timezone: String,
+    /// The building of the location in the configs, if it is in one. The users and printers
+    /// in the building are at the location.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub building: String,
+    /// The other names people write the location as, like `HQ` or `zoom`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+impl Default for LocationConfig {
+    fn default() -> Self {
+        LocationConfig {
+            kind: default_location_kind(),
+            address: Default::default(),
+            timezone: Default::default(),
+            building: Default::default(),
+            aliases: Default::default(),
+        }
+    }
+}
+
+/// A location of the company.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct Location {
+    pub id: i32,
+    pub name: String,
+    pub kind: String,
+    pub address: String,
+    pub timezone: String,
+    pub building: String,
+    pub aliases: Vec<String>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone)]
+#[diesel(table_name = locations)]
+struct NewLocation {
+    name: String,
+    kind: String,
+    address: String,
+    timezone: String,
+    building: String,
+    aliases: Vec<String>,
+    cio_company_id: i32,
+}
+
+/// Split a location into lowercase words, without the punctuation, so `Oakland (HQ)` and
+/// `oakland hq` are the same.
+fn location_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// The number of characters to add, remove, or change to turn one string into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let change = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(change.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Returns if the words contain the words of a name, in order.
+fn contains_words(words: &[String], name: &[String]) -> bool {
+    words.windows(name.len()).any(|w| w == name)
+}
+
+/// Match free-form text, like the location of a calendar event, to a location. We match the
+/// name or an alias of the location, then the text containing one of them, like `Oakland,
+/// room 2`, then one of them with a typo. We do not match if the text matches two locations
+/// as well as each other.
+pub fn match_location<'a>(locations: &'a [Location], text: &str) -> Option<&'a Location> {
+    let words = location_words(text);
+    if words.is_empty() {
+        return None;
+    }
+    let joined = words.join(" ");
+
+    // The names matching, with how well they match.
+    let mut matches: Vec<(u8, Vec<String>, &Location)> = Vec::new();
+    for location in locations {
+        for name in std::iter::once(&location.name).chain(location.aliases.iter()) {
+            let name_words = location_words(name);
+            if name_words.is_empty() {
+                continue;
+            }
+            let name_joined = name_words.join(" ");
+
+            let quality = if name_words == words {
+                3
+            } else if contains_words(&words, &name_words) {
+                2
+            } else if name_joined.len() >= 5 && edit_distance(&joined, &name_joined) <= name_joined.len() / 5 {
+                1
+            } else {
+                continue;
+            };
+            matches.push((quality, name_words, location));
+        }
+    }
+
+    let quality = matches.iter().map(|(q, _, _)| *q).max()?;
+    let best: Vec<&(u8, Vec<String>, &Location)> = matches.iter().filter(|(q, _, _)| *q == quality).collect();
+
+    // A name within a longer one that matches too is not the one the text is about, like
+    // `Oakland` in `Oakland Lab`.
+    let mut found: Vec<&Location> = best
+        .iter()
+        .filter(|(_, name, _)| {
+            !best
+                .iter()
+                .any(|(_, other, _)| other.len() > name.len() && contains_words(other, name))
+        })
+        .map(|(_, _, location)| *location)
+        .collect();
+    found.dedup_by_key(|location| location.id);
+
+    if found.len() == 1 {
+        found.pop()
+    } else {
+        None
+    }
+}
+
+/// Match the building of a user or a printer to the location in that building. We match the
+/// building as text, like any other location, if none or more than one location is in it.
+pub fn match_building_location<'a>(locations: &'a [Location], building: &str) -> Option<&'a Location> {
+    let building = building.trim();
+    if building.is_empty() {
+        return None;
+    }
+
+    let in_building: Vec<&Location> = locations
+        .iter()
+        .filter(|l| l.building.trim().eq_ignore_ascii_case(building))
+        .collect();
+    if in_building.len() == 1 {
+        return in_building.first().copied();
+    }
+
+    match_location(locations, building)
+}
+
+/// Get the locations of a company.
+pub async fn get_locations(db: &Database, cio_company_id: i32) -> Result<Vec<Location>> {
+    Ok(locations::dsl::locations
+        .filter(locations::dsl::cio_company_id.eq(cio_company_id))
+        .order_by(locations::dsl::name)
+        .load_async::<Location>(db.pool())
+        .await?)
+}
+
+/// Sync the locations in the configs with the database, removing the ones no longer there.
+/// Syncing the buildings must happen before, since locations take the address of their
+/// building.
+pub async fn sync_locations(
+    db: &Database,
+    company: &Company,
+    configs: &BTreeMap<String, LocationConfig>,
+) -> Result<()> {
+    let buildings: Vec<Building> = Buildings::get_from_db(db, company.id).await?.into_iter().collect();
+
+    for (name, config) in configs {
+        let name = name.trim().to_string();
+        let kind = config.kind.trim().to_lowercase();
+        if !LOCATION_KINDS.contains(&kind.as_str()) {
+            warn!("location `{}` has unknown type `{}`", name, config.kind);
+            continue;
+        }
+        if !config.timezone.is_empty() && config.timezone.parse::<Tz>().is_err() {
+            warn!("location `{}` has unknown time zone `{}`", name, config.timezone);
+        }
+
+        let mut address = config.address.trim().to_string();
+        if address.is_empty() && !config.building.is_empty() {
+            match buildings.iter().find(|b| b.name == config.building) {
+                Some(building) => address = building.address_formatted.to_string(),
+                None => warn!("location `{}` is in unknown building `{}`", name, config.building),
+            }
+        }
+
+        let location = NewLocation {
+            name,
+            kind,
+            address,
+            timezone: config.timezone.trim().to_string(),
+            building: config.building.trim().to_string(),
+            aliases: config.aliases.iter().map(|a| a.trim().to_string()).collect(),
+            cio_company_id: company.id,
+        };
+
+        diesel::insert_into(locations::table)
+            .values(location.clone())
+            .on_conflict((locations::dsl::cio_company_id, locations::dsl::name))
+            .do_update()
+            .set(location)
+            .execute_async(db.pool())
+            .await?;
+    }
+
+    for location in get_locations(db, company.id).await? {
+        if configs.keys().any(|name| name.trim() == location.name) {
+            continue;
+        }
+
+        // The meetings, audits and users at the location lose it with the foreign keys.
+        diesel::delete(locations::dsl::locations.filter(locations::dsl::id.eq(location.id)))
+            .execute_async(db.pool())
+            .await?;
+        info!("deleted location `{}` of `{}`", location.name, company.name);
+    }
+
+    Ok(())
+}
+
+/// Return the location to set on each record, by id, for the records whose location text now
+/// matches another location than the one they reference. Records whose text matches nothing
+/// anymore lose their location.
+fn location_changes<'a, F>(
+    locations: &'a [Location],
+    records: Vec<(i32, String, Option<i32>)>,
+    matcher: F,
+) -> Vec<(i32, Option<i32>)>
+where
+    F: Fn(&'a [Location], &str) -> Option<&'a Location>,
+{
+    records
+        .into_iter()
+        .filter_map(|(id, text, location_id)| {
+            let matched = matcher(locations, &text).map(|l| l.id);
+            if location_id == matched {
+                None
+            } else {
+                Some((id, matched))
+            }
+        })
+        .collect()
+}
+
+/// Point the recorded meetings, asset audits, package pickups, printers, and users at the
+/// location their free-form location, or building, matches.
+pub async fn assign_location_ids(db: &Database, company: &Company) -> Result<()> {
+    let locations = get_locations(db, company.id).await?;
+
+    let meetings = recorded_meetings::dsl::recorded_meetings
+        .filter(recorded_meetings::dsl::cio_company_id.eq(company.id))
+        .filter(
+            recorded_meetings::dsl::location
+                .ne("")
+                .or(recorded_meetings::dsl::location_id.is_not_null()),
+        )
+        .select((
+            recorded_meetings::dsl::id,
+            recorded_meetings::dsl::location,
+            recorded_meetings::dsl::location_id,
+        ))
+        .load_async::<(i32, String, Option<i32>)>(db.pool())
+        .await?;
+    let changes = location_changes(&locations, meetings, match_location);
+    for (id, location_id) in &changes {
+        diesel::update(recorded_meetings::dsl::recorded_meetings.filter(recorded_meetings::dsl::id.eq(*id)))
+            .set(recorded_meetings::dsl::location_id.eq(*location_id))
+            .execute_async(db.pool())
+            .await?;
+    }
+    info!("updated the location of {} recorded meetings", changes.len());
+
+    let audits = asset_audits::dsl::asset_audits
+        .filter(asset_audits::dsl::cio_company_id.eq(company.id))
+        .filter(
+            asset_audits::dsl::location
+                .ne("")
+                .or(asset_audits::dsl::location_id.is_not_null()),
+        )
+        .select((
+            asset_audits::dsl::id,
+            asset_audits::dsl::location,
+            asset_audits::dsl::location_id,
+        ))
+        .load_async::<(i32, String, Option<i32>)>(db.pool())
+        .await?;
+    let changes = location_changes(&locations, audits, match_location);
+    for (id, location_id) in &changes {
+        diesel::update(asset_audits::dsl::asset_audits.filter(asset_audits::dsl::id.eq(*id)))
+            .set(asset_audits::dsl::location_id.eq(*location_id))
+            .execute_async(db.pool())
+            .await?;
+    }
+    info!("updated the location of {} asset audits", changes.len());
+
+    let pickups = package_pickups::dsl::package_pickups
+        .filter(package_pickups::dsl::cio_company_id.eq(company.id))
+        .filter(
+            package_pickups::dsl::location
+                .ne("")
+                .or(package_pickups::dsl::location_id.is_not_null()),
+        )
+        .select((
+            package_pickups::dsl::id,
+            package_pickups::dsl::location,
+            package_pickups::dsl::location_id,
+        ))
+        .load_async::<(i32, String, Option<i32>)>(db.pool())
+        .await?;
+    let changes = location_changes(&locations, pickups, match_location);
+    for (id, location_id) in &changes {
+        diesel::update(package_pickups::dsl::package_pickups.filter(package_pickups::dsl::id.eq(*id)))
+            .set(package_pickups::dsl::location_id.eq(*location_id))
+            .execute_async(db.pool())
+            .await?;
+    }
+    info!("updated the location of {} package pickups", changes.len());
+
+    let office_printers = printers::dsl::printers
+        .filter(printers::dsl::cio_company_id.eq(company.id))
+        .filter(
+            printers::dsl::location
+                .ne("")
+                .or(printers::dsl::location_id.is_not_null()),
+        )
+        .select((printers::dsl::id, printers::dsl::location, printers::dsl::location_id))
+        .load_async::<(i32, String, Option<i32>)>(db.pool())
+        .await?;
+    let changes = location_changes(&locations, office_printers, match_building_location);
+    for (id, location_id) in &changes {
+        diesel::update(printers::dsl::printers.filter(printers::dsl::id.eq(*id)))
+            .set(printers::dsl::location_id.eq(*location_id))
+            .execute_async(db.pool())
+            .await?;
+    }
+    info!("updated the location of {} printers", changes.len());
+
+    let people = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .filter(users::dsl::building.ne("").or(users::dsl::location_id.is_not_null()))
+        .select((users::dsl::id, users::dsl::building, users::dsl::location_id))
+        .load_async::<(i32, String, Option<i32>)>(db.pool())
+        .await?;
+    let changes = location_changes(&locations, people, match_building_location);
+    for (id, location_id) in &changes {
+        diesel::update(users::dsl::users.filter(users::dsl::id.eq(*id)))
+            .set(users::dsl::location_id.eq(*location_id))
+            .execute_async(db.pool())
+            .await?;
+    }
+    info!("updated the location of {} users", changes.len());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, location_changes, match_building_location, match_location, Location};
+
+    fn mock_location(id: i32, name: &str, aliases: &[&str]) -> Location {
+        Location {
+            id,
+            name: name.to_string(),
+            kind: "office".to_string(),
+            address: Default::default(),
+            timezone: "America/Los_Angeles".to_string(),
+            building: Default::default(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            cio_company_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_match_location() {
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+        assert_eq!(0, edit_distance("", ""));
+
+        let locations = vec![
+            mock_location(1, "Oakland", &["HQ", "Oakland Office"]),
+            mock_location(2, "Emeryville Warehouse", &[]),
+            mock_location(3, "Video call", &["zoom", "meet google com"]),
+            mock_location(4, "Oakland Lab", &[]),
+        ];
+        let matched = |text: &str| match_location(&locations, text).map(|l| l.id);
+
+        assert_eq!(Some(1), matched("oakland"));
+        assert_eq!(Some(1), matched("  Oakland (HQ) "));
+        assert_eq!(Some(1), matched("HQ, conference room 2"));
+        assert_eq!(Some(4), matched("Oakland Lab bench 3"));
+        assert_eq!(Some(2), matched("Emeryville Warehose"));
+        assert_eq!(Some(3), matched("https://oxide.zoom.us/j/123456"));
+        assert_eq!(Some(3), matched("https://meet.google.com/abc-defg-hij"));
+        assert_eq!(None, matched("Meeting hosted by Jane Doe"));
+        assert_eq!(None, matched(""));
+
+        // Two locations match as well as each other.
+        let ambiguous = vec![mock_location(1, "Oakland", &[]), mock_location(2, "Berkeley", &[])];
+        assert_eq!(None, match_location(&ambiguous, "Oakland or Berkeley"));
+    }
+
+    #[test]
+    fn test_match_building_location() {
+        let mut office = mock_location(1, "Oakland Office", &[]);
+        office.building = "Oakland".to_string();
+        let mut lab = mock_location(2, "Oakland Lab", &[]);
+        lab.building = "Oakland Lab Building".to_string();
+        let boston = mock_location(3, "Boston", &[]);
+        let locations = vec![office, lab, boston];
+        let matched = |building: &str| match_building_location(&locations, building).map(|l| l.id);
+
+        assert_eq!(Some(1), matched("oakland"));
+        assert_eq!(Some(2), matched("Oakland Lab Building"));
+        // No location is in the building, so we match it as text.
+        assert_eq!(Some(3), matched("Boston"));
+        assert_eq!(None, matched(""));
+    }
+
+    #[test]
+    fn test_location_changes() {
+        let locations = vec![mock_location(1, "Oakland", &[]), mock_location(2, "Boston", &[])];
+        let records = vec![
+            (10, "Oakland".to_string(), None),
+            (11, "Boston".to_string(), Some(2)),
+            (12, "Boston".to_string(), Some(1)),
+            (13, "Somewhere".to_string(), Some(1)),
+            (14, "".to_string(), Some(2)),
+            (15, "Somewhere".to_string(), None),
+        ];
+        assert_eq!(
+            vec![(10, Some(1)), (12, Some(2)), (13, None), (14, None)],
+            location_changes(&locations, records, match_location)
+        );
+    }
+}
@@ -0,0 +1,516 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType, Slack};
+
+use crate::{
+    airtable::AIRTABLE_MEETING_ACTION_ITEMS_TABLE,
+    companies::Company,
+    configs::{User, Users},
+    core::UpdateAirtableRecord,
+    db::Database,
+    notifications::NOTIFICATION_ACTION_ITEM_REMINDERS,
+    recorded_meetings::{find_user_by_email, MeetingVisibility, RecordedMeeting},
+    schema::{meeting_action_items, recorded_meetings},
+};
+
+/// The status of the action items we create, people mark them done in Airtable.
+static ACTION_ITEM_TODO: &str = "Todo";
+
+/// The statuses of the action items we stop reminding people about.
+static ACTION_ITEM_CLOSED_STATUSES: &[&str] = &["done", "won't do"];
+
+/// How long we wait between reminding owners about their open action items.
+static ACTION_ITEM_REMINDER_INTERVAL_HOURS: i64 = 20;
+
+/// How many times we remind owners about an action item before we give up on it.
+static MAX_ACTION_ITEM_REMINDERS: i32 = 5;
+
+/// The words that separate who owns an action item from what needs doing, like `Jane to
+/// update the checklist`.
+static OWNER_SEPARATORS: &[&str] = &[" to ", " will ", ": "];
+
+/// The most words we take to be the name of an owner.
+static MAX_OWNER_WORDS: usize = 3;
+
+/// An action item from a recorded meeting, that we follow up on until it is done.
+#[db {
+    new_struct_name = "MeetingActionItem",
+    airtable_base = "misc",
+    airtable_table = "AIRTABLE_MEETING_ACTION_ITEMS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "name" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = meeting_action_items)]
+pub struct NewMeetingActionItem {
+    /// The meeting and the position of the item in its action items, like `42-1`.
+    pub name: String,
+    pub recorded_meeting_id: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub meeting_name: String,
+    pub description: String,
+    /// The email of who owns the item, empty if we could not tell.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub assignee: String,
+    /// This field is filled in by the Airtable and should not be edited by the API
+    /// updating.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    /// When we last reminded the owner about the item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_reminded_at: Option<DateTime<Utc>>,
+    /// How many times we reminded the owner about the item.
+    #[serde(default)]
+    pub reminder_count: i32,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a MeetingActionItem.
+#[async_trait]
+impl UpdateAirtableRecord<MeetingActionItem> for MeetingActionItem {
+    async fn update_airtable_record(&mut self, record: MeetingActionItem) -> Result<()> {
+        if !record.status.is_empty() {
+            self.status = record.status;
+        }
+        // People reassign the items in Airtable when we got the owner wrong.
+        if !record.assignee.is_empty() {
+            self.assignee = record.assignee;
+        }
+
+        Ok(())
+    }
+}
+
+impl MeetingActionItem {
+    pub fn is_open(&self) -> bool {
+        !ACTION_ITEM_CLOSED_STATUSES.contains(&self.status.trim().to_lowercase().as_str())
+    }
+}
+
+/// Return the name of who owns an action item, if it says: either before what needs doing,
+/// like `Jane to update the checklist`, or at the end in parentheses, like `Update the
+/// checklist (Jane)`.
+fn action_item_owner_name(item: &str) -> Option<String> {
+    let item = item.trim();
+
+    if let Some(rest) = item.strip_suffix(')') {
+        if let Some((_, owner)) = rest.rsplit_once('(') {
+            if !owner.trim().is_empty() {
+                return Some(owner.trim().to_lowercase());
+            }
+        }
+    }
+
+    OWNER_SEPARATORS
+        .iter()
+        .filter_map(|s| item.find(s).map(|i| &item[..i]))
+        .min_by_key(|owner| owner.len())
+        .map(|owner| owner.trim().to_lowercase())
+        .filter(|owner| !owner.is_empty() && owner.split_whitespace().count() <= MAX_OWNER_WORDS)
+}
+
+/// Find the user who owns an action item. The name has to match a single user, by their full
+/// name, first name, or username.
+fn find_action_item_owner<'a>(item: &str, users: &[&'a User]) -> Option<&'a User> {
+    let name = action_item_owner_name(item)?;
+
+    let matches: Vec<&'a User> = users
+        .iter()
+        .filter(|u| {
+            u.full_name().to_lowercase() == name
+                || u.first_name.to_lowercase() == name
+                || u.username.to_lowercase() == name
+        })
+        .copied()
+        .collect();
+
+    match matches.as_slice() {
+        [user] => Some(*user),
+        _ => None,
+    }
+}
+
+/// Return the users who own the action items of a meeting. We first look for the owner among
+/// the people at the meeting, so a first name is enough, then among everyone.
+pub fn assign_action_items<'a>(
+    meeting: &RecordedMeeting,
+    company: &Company,
+    users: &'a [User],
+) -> Vec<Option<&'a User>> {
+    let mut attendees: Vec<&'a User> = Vec::new();
+    for email in meeting.attendees.iter().chain(meeting.participants.iter()) {
+        if let Some(user) = find_user_by_email(users, company, email) {
+            if !attendees.iter().any(|a| a.id == user.id) {
+                attendees.push(user);
+            }
+        }
+    }
+    let everyone: Vec<&'a User> = users.iter().collect();
+
+    meeting
+        .action_items
+        .iter()
+        .map(|item| find_action_item_owner(item, &attendees).or_else(|| find_action_item_owner(item, &everyone)))
+        .collect()
+}
+
+/// Return how to mention someone in Slack, by their Slack user if we can find it.
+async fn slack_mention(slack: &Slack, email: &str) -> String {
+    match slack.lookup_user_by_email(email).await {
+        Ok(user) if !user.id.is_empty() => format!("<@{}>", user.id),
+        _ => email.to_string(),
+    }
+}
+
+fn section(text: String) -> MessageBlock {
+    MessageBlock {
+        block_type: MessageBlockType::Section,
+        text: Some(MessageBlockText {
+            text_type: MessageType::Markdown,
+            text,
+        }),
+        elements: Default::default(),
+        accessory: Default::default(),
+        block_id: Default::default(),
+        fields: Default::default(),
+    }
+}
+
+fn header(text: String) -> MessageBlock {
+    MessageBlock {
+        block_type: MessageBlockType::Header,
+        text: Some(MessageBlockText {
+            text_type: MessageType::PlainText,
+            text,
+        }),
+        elements: Default::default(),
+        accessory: Default::default(),
+        block_id: Default::default(),
+        fields: Default::default(),
+    }
+}
+
+impl RecordedMeeting {
+    /// Return the Slack channel of the meeting. Recurring meetings that do not have one use
+    /// the channel of their last occurrence that did.
+    pub async fn find_slack_channel(&self, db: &Database) -> Result<String> {
+        if !self.slack_channel.is_empty() || !self.is_recurring {
+            return Ok(self.slack_channel.to_string());
+        }
+
+        let previous = recorded_meetings::dsl::recorded_meetings
+            .filter(recorded_meetings::dsl::cio_company_id.eq(self.cio_company_id))
+            .filter(recorded_meetings::dsl::name.eq(self.name.to_string()))
+            .filter(recorded_meetings::dsl::slack_channel.ne(""))
+            .order_by(recorded_meetings::dsl::start_time.desc())
+            .first_async::<RecordedMeeting>(db.pool())
+            .await;
+
+        Ok(previous.map(|m| m.slack_channel).unwrap_or_default())
+    }
+
+    /// Track the action items of the meeting as tasks, assigned to the users who own them,
+    /// and post them to the meeting's Slack channel. The action items of meetings that are not
+    /// public to the company are left alone, they would end up in Airtable.
+    pub async fn track_action_items(&self, db: &Database, company: &Company) -> Result<Vec<MeetingActionItem>> {
+        if self.action_items.is_empty()
+            || MeetingVisibility::from_field(&self.visibility) != MeetingVisibility::PublicInternal
+        {
+            return Ok(vec![]);
+        }
+
+        let users: Vec<User> = Users::get_from_db(db, company.id).await?.into_iter().collect();
+        let owners = assign_action_items(self, company, &users);
+
+        let channel = self.find_slack_channel(db).await?;
+        let now = Utc::now();
+
+        let mut items: Vec<MeetingActionItem> = Vec::new();
+        for (i, (description, owner)) in self.action_items.iter().zip(owners).enumerate() {
+            let name = format!("{}-{}", self.id, i + 1);
+            // Keep what people changed on the items we already track.
+            if let Some(existing) = MeetingActionItem::get_from_db(db, company.id, name.to_string()).await {
+                items.push(existing);
+                continue;
+            }
+
+            let item = NewMeetingActionItem {
+                name,
+                recorded_meeting_id: self.id,
+                meeting_name: self.name.to_string(),
+                description: description.to_string(),
+                assignee: owner.map(|u| u.email.to_string()).unwrap_or_default(),
+                status: ACTION_ITEM_TODO.to_string(),
+                // The post in the channel counts as the first reminder.
+                last_reminded_at: if channel.is_empty() { None } else { Some(now) },
+                reminder_count: if channel.is_empty() { 0 } else { 1 },
+                cio_company_id: company.id,
+            }
+            .upsert(db)
+            .await?;
+            info!(
+                "tracking action item `{}` of recorded meeting {} for `{}`",
+                item.description, self.id, item.assignee
+            );
+
+            items.push(item);
+        }
+
+        if channel.is_empty() {
+            return Ok(items);
+        }
+
+        let slack = company.authenticate_slack(db).await?;
+        let mut lines: Vec<String> = Vec::new();
+        for item in &items {
+            let owner = if item.assignee.is_empty() {
+                "_unassigned_".to_string()
+            } else {
+                slack_mention(&slack, &item.assignee).await
+            };
+            lines.push(format!("• {} — {}", item.description, owner));
+        }
+
+        let msg = FormattedMessage {
+            channel,
+            blocks: vec![
+                header(format!("Action items from {}", self.name)),
+                section(lines.join("\n")),
+            ],
+            attachments: Default::default(),
+        };
+        company.post_to_slack_channel(db, &msg).await?;
+
+        Ok(items)
+    }
+}
+
+/// Return the open action items we should remind their owners about, by owner.
+pub fn action_items_to_remind(
+    items: Vec<MeetingActionItem>,
+    now: DateTime<Utc>,
+) -> BTreeMap<String, Vec<MeetingActionItem>> {
+    let since = now - Duration::hours(ACTION_ITEM_REMINDER_INTERVAL_HOURS);
+
+    let mut by_owner: BTreeMap<String, Vec<MeetingActionItem>> = BTreeMap::new();
+    for item in items {
+        if item.assignee.is_empty()
+            || !item.is_open()
+            || item.reminder_count >= MAX_ACTION_ITEM_REMINDERS
+            || item.last_reminded_at.map(|t| t > since).unwrap_or(false)
+        {
+            continue;
+        }
+
+        by_owner.entry(item.assignee.to_lowercase()).or_default().push(item);
+    }
+
+    by_owner
+}
+
+/// Get the statuses and the owners people set on the action items in Airtable, so we stop
+/// reminding about the items marked done.
+async fn refresh_action_items_from_airtable(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_misc.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let records: Vec<airtable_api::Record<MeetingActionItem>> = company
+        .authenticate_airtable(&company.airtable_base_id_misc)
+        .list_records(&MeetingActionItem::airtable_table(), "Grid view", vec![])
+        .await?;
+    for record in records {
+        if let Some(mut item) = MeetingActionItem::get_from_db(db, company.id, record.fields.name.to_string()).await {
+            let status = if record.fields.status.is_empty() {
+                item.status.to_string()
+            } else {
+                record.fields.status.to_string()
+            };
+            let assignee = if record.fields.assignee.is_empty() {
+                item.assignee.to_string()
+            } else {
+                record.fields.assignee.to_string()
+            };
+            if item.status == status && item.assignee == assignee {
+                continue;
+            }
+
+            item.status = status;
+            item.assignee = assignee;
+            item.update_in_db(db).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remind the owners of open action items about them in a Slack direct message, until they
+/// mark them done or we reminded them `MAX_ACTION_ITEM_REMINDERS` times.
+pub async fn send_action_item_reminders(db: &Database, company: &Company) -> Result<()> {
+    refresh_action_items_from_airtable(db, company).await?;
+
+    let now = Utc::now();
+    let items = meeting_action_items::dsl::meeting_action_items
+        .filter(meeting_action_items::dsl::cio_company_id.eq(company.id))
+        .order_by(meeting_action_items::dsl::id)
+        .load_async::<MeetingActionItem>(db.pool())
+        .await?;
+
+    let by_owner = action_items_to_remind(items, now);
+    if by_owner.is_empty() {
+        return Ok(());
+    }
+
+    let users: Vec<User> = Users::get_from_db(db, company.id).await?.into_iter().collect();
+    let slack = company.authenticate_slack(db).await?;
+    for (owner, items) in by_owner {
+        if let Some(user) = find_user_by_email(&users, company, &owner) {
+            if !user.wants_notification(NOTIFICATION_ACTION_ITEM_REMINDERS) {
+                continue;
+            }
+        }
+
+        let channel = match slack.lookup_user_by_email(&owner).await {
+            Ok(user) => user.id,
+            Err(e) => {
+                warn!("could not find the slack user of {}: {}", owner, e);
+                continue;
+            }
+        };
+
+        let lines: Vec<String> = items
+            .iter()
+            .map(|i| format!("• {} _({})_", i.description, i.meeting_name))
+            .collect();
+        let msg = FormattedMessage {
+            channel,
+            blocks: vec![
+                header("Your open action items".to_string()),
+                section(format!(
+                    "{}\n\nMark them done in Airtable and I will stop reminding you.",
+                    lines.join("\n")
+                )),
+            ],
+            attachments: Default::default(),
+        };
+        company.post_to_slack_channel(db, &msg).await?;
+        info!("reminded {} about {} open action items", owner, items.len());
+
+        for mut item in items {
+            item.last_reminded_at = Some(now);
+            item.reminder_count += 1;
+            item.update(db).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::{action_item_owner_name, action_items_to_remind, assign_action_items, MeetingActionItem};
+    use crate::tests::mock_record;
+    use crate::{companies::tests::mock_company, configs::tests::mock_user, recorded_meetings::RecordedMeeting};
+
+    #[test]
+    fn test_assign_action_items() {
+        assert_eq!(
+            Some("jane".to_string()),
+            action_item_owner_name("Jane to update the checklist")
+        );
+        assert_eq!(
+            Some("sam lee".to_string()),
+            action_item_owner_name("Order more cables (Sam Lee)")
+        );
+        assert_eq!(
+            None,
+            action_item_owner_name("Everyone on the team should read the RFD to prepare")
+        );
+
+        let mut company = mock_company();
+        company.gsuite_domain = "example.com".to_string();
+        let user = |id: i32, first_name: &str, last_name: &str, username: &str| {
+            let mut user = mock_user();
+            user.id = id;
+            user.first_name = first_name.to_string();
+            user.last_name = last_name.to_string();
+            user.username = username.to_string();
+            user.email = format!("{}@example.com", username);
+            user
+        };
+        let users = vec![
+            user(1, "Jane", "Doe", "jane"),
+            user(2, "Jane", "Roe", "jroe"),
+            user(3, "Sam", "Lee", "sam"),
+        ];
+
+        let mut meeting: RecordedMeeting = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "airtable_record_id": "",
+            "start_time": "2023-08-17T17:00:00Z",
+            "end_time": "2023-08-17T17:30:00Z",
+        }))
+        .unwrap();
+        meeting.attendees = vec!["jroe@example.com".to_string()];
+        meeting.action_items = vec![
+            // Only one of the Janes was at the meeting.
+            "Jane to update the checklist".to_string(),
+            "Order more cables (Sam Lee)".to_string(),
+            "Figure out the shipping date".to_string(),
+        ];
+
+        let owners = assign_action_items(&meeting, &company, &users);
+        assert_eq!(
+            vec![Some(2), Some(3), None],
+            owners.iter().map(|o| o.map(|u| u.id)).collect::<Vec<Option<i32>>>()
+        );
+    }
+
+    #[test]
+    fn test_action_items_to_remind() {
+        let now = Utc::now();
+        let item = |assignee: &str, status: &str, hours_ago: Option<i64>| -> MeetingActionItem {
+            let mut item: MeetingActionItem =
+                mock_record(json!({"name": "", "recorded_meeting_id": 1, "description": ""}));
+            item.assignee = assignee.to_string();
+            item.status = status.to_string();
+            item.last_reminded_at = hours_ago.map(|h| now - Duration::hours(h));
+            item
+        };
+
+        let by_owner = action_items_to_remind(
+            vec![
+                item("jane@example.com", "Todo", None),
+                item("Jane@example.com", "In progress", Some(24)),
+                item("jane@example.com", "Done", None),
+                item("sam@example.com", "Todo", Some(2)),
+                item("", "Todo", None),
+                {
+                    // We gave up on this one.
+                    let mut item = item("bob@example.com", "Todo", Some(48));
+                    item.reminder_count = 5;
+                    item
+                },
+            ],
+            now,
+        );
+        assert_eq!(vec!["jane@example.com"], by_owner.keys().collect::<Vec<&String>>());
+        assert_eq!(2, by_owner["jane@example.com"].len());
+    }
+}
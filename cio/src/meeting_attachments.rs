@@ -0,0 +1,358 @@
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+
+use crate::{
+    companies::Company,
+    db::Database,
+    meeting_retention::drive_file_id,
+    recorded_meetings::{MeetingVisibility, RecordedMeeting},
+    schema::recorded_meetings,
+    utils::truncate,
+};
+
+/// What we attach to the Airtable record of a recorded meeting, so its gallery views show
+/// more than links.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MeetingAttachment {
+    ChatLog,
+    Transcript,
+    Thumbnail,
+}
+
+impl MeetingAttachment {
+    /// The attachment field of the Airtable record.
+    pub fn field(&self) -> &'static str {
+        match self {
+            MeetingAttachment::ChatLog => "chat_log_file",
+            MeetingAttachment::Transcript => "transcript_file",
+            MeetingAttachment::Thumbnail => "thumbnail",
+        }
+    }
+
+    fn get<'a>(&self, meeting: &'a RecordedMeeting) -> &'a str {
+        match self {
+            MeetingAttachment::ChatLog => &meeting.chat_log_file,
+            MeetingAttachment::Transcript => &meeting.transcript_file,
+            MeetingAttachment::Thumbnail => &meeting.thumbnail,
+        }
+    }
+
+    fn set(&self, meeting: &mut RecordedMeeting, url: String) {
+        match self {
+            MeetingAttachment::ChatLog => meeting.chat_log_file = url,
+            MeetingAttachment::Transcript => meeting.transcript_file = url,
+            MeetingAttachment::Thumbnail => meeting.thumbnail = url,
+        }
+    }
+}
+
+/// Returns what we still have to attach to the Airtable record of the meeting. The transcript
+/// and the thumbnail of a restricted or private meeting stay out of Airtable, like the
+/// transcript text does.
+pub fn missing_meeting_attachments(meeting: &RecordedMeeting) -> Vec<MeetingAttachment> {
+    if meeting.purged_at.is_some() || meeting.airtable_record_id.is_empty() {
+        return vec![];
+    }
+
+    let public = MeetingVisibility::from_field(&meeting.visibility) == MeetingVisibility::PublicInternal;
+    let mut missing = Vec::new();
+    if meeting.chat_log_file.is_empty() && !meeting.chat_log.trim().is_empty() {
+        missing.push(MeetingAttachment::ChatLog);
+    }
+    if public
+        && meeting.transcript_file.is_empty()
+        && (!meeting.transcript_doc.is_empty() || !meeting.transcript.trim().is_empty())
+    {
+        missing.push(MeetingAttachment::Transcript);
+    }
+    if public && meeting.thumbnail.is_empty() && drive_file_id(&meeting.video).is_some() {
+        missing.push(MeetingAttachment::Thumbnail);
+    }
+
+    missing
+}
+
+/// Returns the name of the file we attach, like `Product sync - 2024-01-09 - Chat log.txt`.
+pub fn meeting_attachment_filename(meeting: &RecordedMeeting, what: &str, extension: &str) -> String {
+    let name = meeting.name.replace(|c: char| c == '/' || c == '\\', "-");
+    format!(
+        "{} - {} - {}.{}",
+        name.trim(),
+        meeting.start_time.format("%Y-%m-%d"),
+        what,
+        extension
+    )
+}
+
+/// A file to attach in Airtable.
+struct AttachmentFile {
+    filename: String,
+    content_type: String,
+    contents: Vec<u8>,
+}
+
+/// Get a file from the Drive API with the token of the company, like the export of a Doc.
+async fn get_from_drive(token: &str, url: &str) -> Result<(String, Vec<u8>)> {
+    crate::rate_limit::acquire(crate::rate_limit::Provider::Google).await;
+    let resp = reqwest::Client::new().get(url).bearer_auth(token).send().await?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        bail!(
+            "getting `{}` from Drive failed with {}: {}",
+            url,
+            status,
+            resp.text().await.unwrap_or_default()
+        );
+    }
+
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    Ok((content_type, resp.bytes().await?.to_vec()))
+}
+
+impl RecordedMeeting {
+    /// Get the file to attach in Airtable. The transcript is the PDF of its Google Doc if we
+    /// exported it, and the transcript text if not. The thumbnail is the one Drive made of the
+    /// recording.
+    async fn attachment_file(&self, token: &str, attachment: MeetingAttachment) -> Result<AttachmentFile> {
+        match attachment {
+            MeetingAttachment::ChatLog => Ok(AttachmentFile {
+                filename: meeting_attachment_filename(self, "Chat log", "txt"),
+                content_type: "text/plain".to_string(),
+                contents: self.chat_log.as_bytes().to_vec(),
+            }),
+            MeetingAttachment::Transcript => {
+                if let Some(doc_id) = drive_file_id(&self.transcript_doc) {
+                    let (_, contents) = get_from_drive(
+                        token,
+                        &format!(
+                            "https://www.googleapis.com/drive/v3/files/{}/export?mimeType=application/pdf",
+                            doc_id
+                        ),
+                    )
+                    .await?;
+
+                    return Ok(AttachmentFile {
+                        filename: meeting_attachment_filename(self, "Transcript", "pdf"),
+                        content_type: "application/pdf".to_string(),
+                        contents,
+                    });
+                }
+
+                // Keep the text under what we can upload, a character is at most 4 bytes.
+                Ok(AttachmentFile {
+                    filename: meeting_attachment_filename(self, "Transcript", "txt"),
+                    content_type: "text/plain".to_string(),
+                    contents: truncate(&self.transcript, airtable_api::MAX_ATTACHMENT_UPLOAD_SIZE / 4)
+                        .as_bytes()
+                        .to_vec(),
+                })
+            }
+            MeetingAttachment::Thumbnail => {
+                let video_id = drive_file_id(&self.video).unwrap_or_default();
+                let (_, file) = get_from_drive(
+                    token,
+                    &format!(
+                        "https://www.googleapis.com/drive/v3/files/{}?fields=thumbnailLink&supportsAllDrives=true",
+                        video_id
+                    ),
+                )
+                .await?;
+                let file: serde_json::Value = serde_json::from_slice(&file)?;
+                let link = file
+                    .get("thumbnailLink")
+                    .and_then(|l| l.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                if link.is_empty() {
+                    bail!("Drive has no thumbnail of the recording of meeting `{}` yet", self.name);
+                }
+
+                // The thumbnails are only shared with whoever can see the recording.
+                let (content_type, contents) = get_from_drive(token, &link).await?;
+                let content_type = if content_type.starts_with("image/") {
+                    content_type
+                } else {
+                    "image/png".to_string()
+                };
+
+                Ok(AttachmentFile {
+                    filename: meeting_attachment_filename(self, "Thumbnail", content_type.trim_start_matches("image/")),
+                    content_type,
+                    contents,
+                })
+            }
+        }
+    }
+
+    /// Upload the chat log, transcript and a thumbnail of the meeting as attachments of its
+    /// Airtable record, if we did not already. Returns what we attached.
+    pub async fn upload_airtable_attachments(
+        &mut self,
+        db: &Database,
+        company: &Company,
+    ) -> Result<Vec<MeetingAttachment>> {
+        let missing = missing_meeting_attachments(self);
+        if missing.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Uploading adds to the attachments of the field, so if Airtable already has some we
+        // keep those rather than adding the same file again.
+        if let Some(existing) = self.get_existing_airtable_record(db).await {
+            for attachment in &missing {
+                let url = attachment.get(&existing.fields).to_string();
+                if !url.is_empty() {
+                    attachment.set(self, url);
+                }
+            }
+        }
+
+        let before = (
+            self.chat_log_file.to_string(),
+            self.transcript_file.to_string(),
+            self.thumbnail.to_string(),
+        );
+        let airtable = company.authenticate_airtable(&company.airtable_base_id_misc);
+        let token = company.google_access_token(db).await?;
+        let mut attached = Vec::new();
+        for attachment in missing {
+            if !attachment.get(self).is_empty() {
+                continue;
+            }
+
+            let file = match self.attachment_file(&token, attachment).await {
+                Ok(file) => file,
+                Err(e) => {
+                    warn!(
+                        "getting the {} to attach to meeting `{}` failed: {}",
+                        attachment.field(),
+                        self.name,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            match airtable
+                .upload_attachment(
+                    &self.airtable_record_id,
+                    attachment.field(),
+                    &file.filename,
+                    &file.content_type,
+                    &file.contents,
+                )
+                .await
+            {
+                Ok(uploaded) => {
+                    let url = uploaded.last().map(|a| a.url.to_string()).unwrap_or_default();
+                    attachment.set(self, url);
+                    attached.push(attachment);
+                }
+                Err(e) => warn!(
+                    "uploading `{}` to the Airtable record of meeting `{}` failed: {}",
+                    file.filename, self.name, e
+                ),
+            }
+        }
+
+        if before
+            == (
+                self.chat_log_file.to_string(),
+                self.transcript_file.to_string(),
+                self.thumbnail.to_string(),
+            )
+        {
+            return Ok(attached);
+        }
+
+        // Airtable already has the attachments, we only need to remember them.
+        diesel::update(recorded_meetings::dsl::recorded_meetings)
+            .filter(recorded_meetings::dsl::id.eq(self.id))
+            .set((
+                recorded_meetings::dsl::chat_log_file.eq(self.chat_log_file.to_string()),
+                recorded_meetings::dsl::transcript_file.eq(self.transcript_file.to_string()),
+                recorded_meetings::dsl::thumbnail.eq(self.thumbnail.to_string()),
+            ))
+            .execute_async(db.pool())
+            .await?;
+        if !attached.is_empty() {
+            info!(
+                "attached {:?} to the Airtable record of meeting `{}`",
+                attached, self.name
+            );
+        }
+
+        Ok(attached)
+    }
+}
+
+/// Upload the attachments of the recorded meetings of the company that do not have them yet.
+pub async fn upload_recorded_meetings_airtable_attachments(db: &Database, company: &Company) -> Result<()> {
+    let meetings = recorded_meetings::dsl::recorded_meetings
+        .filter(recorded_meetings::dsl::cio_company_id.eq(company.id))
+        .filter(recorded_meetings::dsl::purged_at.is_null())
+        .filter(recorded_meetings::dsl::airtable_record_id.ne("".to_string()))
+        .load_async::<RecordedMeeting>(db.pool())
+        .await?;
+
+    for mut meeting in meetings {
+        if missing_meeting_attachments(&meeting).is_empty() {
+            continue;
+        }
+
+        if let Err(e) = meeting.upload_airtable_attachments(db, company).await {
+            warn!("attaching files to meeting `{}` failed: {}", meeting.name, e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{meeting_attachment_filename, missing_meeting_attachments, MeetingAttachment};
+    use crate::recorded_meetings::RecordedMeeting;
+    use crate::tests::mock_record;
+
+    #[test]
+    fn test_missing_meeting_attachments() {
+        let mut meeting: RecordedMeeting = mock_record(
+            json!({"airtable_record_id": "rec1", "name": "Product/sync", "start_time": "2024-01-09T17:00:00Z", "end_time": "2024-01-09T18:00:00Z"}),
+        );
+        assert!(missing_meeting_attachments(&meeting).is_empty());
+
+        meeting.chat_log = "hi".to_string();
+        meeting.transcript = "Hello everyone".to_string();
+        meeting.video = "https://drive.google.com/open?id=abc".to_string();
+        assert_eq!(
+            missing_meeting_attachments(&meeting),
+            vec![
+                MeetingAttachment::ChatLog,
+                MeetingAttachment::Transcript,
+                MeetingAttachment::Thumbnail
+            ]
+        );
+
+        meeting.chat_log_file = "https://dl.airtable.com/chat.txt".to_string();
+        meeting.visibility = "restricted".to_string();
+        assert!(missing_meeting_attachments(&meeting).is_empty());
+
+        meeting.chat_log_file = String::new();
+        meeting.airtable_record_id = String::new();
+        assert!(missing_meeting_attachments(&meeting).is_empty());
+
+        assert_eq!(
+            meeting_attachment_filename(&meeting, "Chat log", "txt"),
+            "Product-sync - 2024-01-09 - Chat log.txt"
+        );
+    }
+}
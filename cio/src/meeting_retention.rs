@@ -0,0 +1,633 @@
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    airtable::AIRTABLE_RECORDED_MEETINGS_TABLE,
+    applicant_status::Status,
+    applicants::{Applicant, Applicants},
+    companies::Company,
+    db::Database,
+    interviews::{ApplicantInterview, ApplicantInterviews},
+    recorded_meetings::RecordedMeeting,
+    schema::{recorded_meeting_purges, recorded_meetings},
+};
+
+/// The tag that keeps a recorded meeting from being purged by the retention policy.
+pub static KEEP_TAG: &str = "keep";
+/// The tag that keeps a recorded meeting from being purged at all, including the recordings
+/// of interviews.
+pub static LEGAL_HOLD_TAG: &str = "legal hold";
+
+/// Why we purged a recorded meeting.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PurgeReason {
+    /// The meeting is older than the retention policy of the company.
+    Retention,
+    /// The meeting is an interview of an applicant we made a decision on.
+    InterviewDecision,
+}
+
+impl PurgeReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PurgeReason::Retention => "retention",
+            PurgeReason::InterviewDecision => "interview decision",
+        }
+    }
+}
+
+/// An audit record of what the retention policy purged from a recorded meeting.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RecordedMeetingPurge {
+    pub id: i32,
+    pub recorded_meeting_id: i32,
+    pub name: String,
+    pub google_event_id: String,
+    pub start_time: DateTime<Utc>,
+    /// The link to the video we deleted.
+    pub video: String,
+    /// The link to the chat log we deleted.
+    pub chat_log_link: String,
+    pub transcript_id: String,
+    /// The number of diarized transcript segments we deleted.
+    pub transcript_segments: i32,
+    /// The ids of the Google Drive files we deleted.
+    pub deleted_files: Vec<String>,
+    /// The retention policy of the company at the time we purged the meeting.
+    pub retention_days: i32,
+    /// `retention` or `interview decision`.
+    pub reason: String,
+    pub purged_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = recorded_meeting_purges)]
+struct NewRecordedMeetingPurge {
+    recorded_meeting_id: i32,
+    name: String,
+    google_event_id: String,
+    start_time: DateTime<Utc>,
+    video: String,
+    chat_log_link: String,
+    transcript_id: String,
+    transcript_segments: i32,
+    deleted_files: Vec<String>,
+    retention_days: i32,
+    reason: String,
+    purged_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// Get the id of a Google Drive file from its link, or the link of a Google Doc.
+pub(crate) fn drive_file_id(link: &str) -> Option<String> {
+    if !link.starts_with("https://drive.google.com/") && !link.starts_with("https://docs.google.com/document/d/") {
+        return None;
+    }
+
+    let id = link
+        .trim_start_matches("https://drive.google.com/open?id=")
+        .trim_start_matches("https://drive.google.com/file/d/")
+        .trim_start_matches("https://docs.google.com/document/d/")
+        .trim_end_matches("/view?usp=drive_web")
+        .trim_end_matches("/view")
+        .trim_end_matches("/edit")
+        .to_string();
+    if id.is_empty() || id.contains('/') {
+        return None;
+    }
+
+    Some(id)
+}
+
+impl RecordedMeeting {
+    /// Returns if the meeting is tagged to be kept forever.
+    pub fn is_kept(&self) -> bool {
+        self.tags.iter().any(|t| t.trim().eq_ignore_ascii_case(KEEP_TAG))
+    }
+
+    /// Returns if the meeting is tagged to be kept for a legal hold.
+    pub fn is_on_legal_hold(&self) -> bool {
+        self.tags.iter().any(|t| t.trim().eq_ignore_ascii_case(LEGAL_HOLD_TAG))
+    }
+
+    /// Get the latest tags of the meeting from Airtable, where they are set. Returns false if
+    /// the meeting is in Airtable but we could not read it, since we would not know if it is
+    /// kept or on legal hold.
+    async fn refresh_tags_from_airtable(&mut self, db: &Database) -> bool {
+        if self.airtable_record_id.is_empty() {
+            // Nobody could tag it in Airtable, the tags we have are the latest.
+            return true;
+        }
+
+        match self.get_existing_airtable_record(db).await {
+            Some(existing) => {
+                self.tags = existing.fields.tags;
+                true
+            }
+            None => {
+                warn!(
+                    "could not get the tags of recorded meeting {} `{}` from airtable, not purging it",
+                    self.id, self.name
+                );
+                false
+            }
+        }
+    }
+
+    /// Returns if the meeting still has a video or transcript to purge.
+    pub fn has_recording(&self) -> bool {
+        self.purged_at.is_none()
+            && !(self.video.is_empty()
+                && self.chat_log_link.is_empty()
+                && self.chat_log.is_empty()
+                && self.transcript.is_empty()
+                && self.transcript_id.is_empty()
+                && self.transcript_doc.is_empty())
+    }
+
+    /// Returns if the retention policy says the video and transcript of the meeting should
+    /// be purged.
+    pub fn is_expired(&self, retention_days: i32, now: DateTime<Utc>) -> bool {
+        if retention_days <= 0 || self.is_kept() || self.is_on_legal_hold() || !self.has_recording() {
+            return false;
+        }
+
+        self.end_time + Duration::days(retention_days.into()) < now
+    }
+
+    /// Delete the video, chat log and transcript doc from Google Drive, blank them and the
+    /// transcript in the database and Airtable, and record what we purged. We also blank the
+    /// summary and action items of interviews, since they are about the applicant.
+    async fn purge(
+        &mut self,
+        db: &Database,
+        company: &Company,
+        drive: &google_drive::Client,
+        reason: PurgeReason,
+        retention_days: i32,
+    ) -> Result<RecordedMeetingPurge> {
+        let mut deleted_files: Vec<String> = Default::default();
+        for link in [&self.video, &self.chat_log_link, &self.transcript_doc] {
+            let id = match drive_file_id(link) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            if let Err(e) = drive
+                .files()
+                .delete(
+                    &id, false, // enforce_single_parent
+                    true,  // supports_all_drives
+                    true,  // supports_team_drives
+                )
+                .await
+            {
+                if !e.to_string().contains("404") {
+                    // Try again the next time, rather than losing track of the file.
+                    bail!("deleting drive file `{}` failed: {}", id, e);
+                }
+
+                info!("drive file `{}` for meeting {} was already deleted", id, self.name);
+            }
+            deleted_files.push(id);
+        }
+
+        let transcript_segments = self.transcript_segments(db).await?.len() as i32;
+        self.set_transcript_segments(db, &[]).await?;
+
+        let purge = NewRecordedMeetingPurge {
+            recorded_meeting_id: self.id,
+            name: self.name.to_string(),
+            google_event_id: self.google_event_id.to_string(),
+            start_time: self.start_time,
+            video: self.video.to_string(),
+            chat_log_link: self.chat_log_link.to_string(),
+            transcript_id: self.transcript_id.to_string(),
+            transcript_segments,
+            deleted_files,
+            retention_days,
+            reason: reason.as_str().to_string(),
+            purged_at: Utc::now(),
+            cio_company_id: company.id,
+        };
+
+        self.video = String::new();
+        self.chat_log_link = String::new();
+        self.chat_log = String::new();
+        self.transcript = String::new();
+        self.transcript_id = String::new();
+        self.transcript_doc = String::new();
+        self.chat_log_file = String::new();
+        self.transcript_file = String::new();
+        self.thumbnail = String::new();
+        let mut blanked = serde_json::json!({
+            "video": "",
+            "chat_log_link": "",
+            "chat_log": "",
+            "transcript": "",
+            "transcript_id": "",
+            "transcript_doc": "",
+            "chat_log_file": [],
+            "transcript_file": [],
+            "thumbnail": [],
+        });
+        if reason == PurgeReason::InterviewDecision {
+            self.summary = String::new();
+            self.action_items = Default::default();
+            blanked["summary"] = serde_json::json!("");
+            blanked["action_items"] = serde_json::json!([]);
+        }
+        self.purged_at = Some(purge.purged_at);
+        self.update(db).await?;
+
+        // Our Airtable records skip empty fields, so updating the record above leaves the
+        // old values in Airtable. We need to blank them out explicitly.
+        if !self.airtable_record_id.is_empty() {
+            let airtable = company.authenticate_airtable(&company.airtable_base_id_misc);
+            airtable
+                .update_records(
+                    AIRTABLE_RECORDED_MEETINGS_TABLE,
+                    vec![airtable_api::Record {
+                        id: self.airtable_record_id.to_string(),
+                        fields: blanked,
+                        created_time: None,
+                    }],
+                )
+                .await?;
+        }
+
+        let purge = diesel::insert_into(recorded_meeting_purges::table)
+            .values(purge)
+            .get_result_async::<RecordedMeetingPurge>(db.pool())
+            .await?;
+
+        Ok(purge)
+    }
+}
+
+/// Apply the company's retention policy to its recorded meetings.
+pub async fn apply_recorded_meetings_retention(db: &Database, company: &Company) -> Result<()> {
+    if company.recorded_meeting_retention_days <= 0 {
+        // Return early, the company keeps its recordings forever.
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let meetings = recorded_meetings::dsl::recorded_meetings
+        .filter(recorded_meetings::dsl::cio_company_id.eq(company.id))
+        .filter(recorded_meetings::dsl::purged_at.is_null())
+        .filter(
+            recorded_meetings::dsl::end_time.lt(now - Duration::days(company.recorded_meeting_retention_days.into())),
+        )
+        .load_async::<RecordedMeeting>(db.pool())
+        .await?;
+    if meetings.is_empty() {
+        return Ok(());
+    }
+
+    let drive = company.authenticate_google_drive(db).await?;
+
+    for mut meeting in meetings {
+        if !meeting.refresh_tags_from_airtable(db).await {
+            continue;
+        }
+
+        if !meeting.is_expired(company.recorded_meeting_retention_days, now) {
+            continue;
+        }
+
+        if let Err(e) = meeting
+            .purge(
+                db,
+                company,
+                &drive,
+                PurgeReason::Retention,
+                company.recorded_meeting_retention_days,
+            )
+            .await
+        {
+            warn!(
+                "purging recorded meeting {} `{}` failed: {}",
+                meeting.id, meeting.name, e
+            );
+            continue;
+        }
+
+        info!(
+            "purged recorded meeting {} `{}` after {} days",
+            meeting.id, meeting.name, company.recorded_meeting_retention_days
+        );
+    }
+
+    Ok(())
+}
+
+impl Applicant {
+    /// Returns when we made the hire or no-hire decision on the applicant, if we did.
+    pub fn decided_at(&self) -> Option<DateTime<Utc>> {
+        match self.status.parse::<Status>().unwrap_or_default() {
+            Status::Hired | Status::Onboarding | Status::Contractor => {
+                self.offer_completed.or(self.interviews_completed)
+            }
+            Status::Declined | Status::Withdrawn => self.rejection_sent_date_time.or(self.interviews_completed),
+            _ => None,
+        }
+    }
+}
+
+/// Return the calendar events of the interviews of the applicants we decided on more than
+/// `retention_days` ago. Someone who applied again and is still in the process keeps their
+/// interviews until we decide on them again.
+pub fn expired_interview_events(
+    applicants: &[Applicant],
+    interviews: &[ApplicantInterview],
+    retention_days: i32,
+    now: DateTime<Utc>,
+) -> BTreeSet<String> {
+    let mut expired: BTreeSet<String> = Default::default();
+    let mut undecided: BTreeSet<String> = Default::default();
+    for applicant in applicants {
+        let email = applicant.email.trim().to_lowercase();
+        match applicant.decided_at() {
+            Some(decided_at) if decided_at + Duration::days(retention_days.into()) < now => {
+                expired.insert(email);
+            }
+            _ => {
+                undecided.insert(email);
+            }
+        }
+    }
+
+    interviews
+        .iter()
+        .filter(|i| !i.google_event_id.is_empty())
+        .filter(|i| {
+            let email = i.email.trim().to_lowercase();
+            expired.contains(&email) && !undecided.contains(&email)
+        })
+        .map(|i| i.google_event_id.to_string())
+        .collect()
+}
+
+/// Purge the recordings and transcripts of the interviews of the applicants we decided on,
+/// once the company's retention for them is up, and post the receipts in the applicants
+/// channel. Interviews tagged "legal hold" are kept.
+pub async fn apply_interview_recordings_retention(db: &Database, company: &Company) -> Result<()> {
+    let retention_days = company.interview_recording_retention_days;
+    if retention_days <= 0 {
+        // Return early, the company keeps interview recordings like the other meetings.
+        return Ok(());
+    }
+
+    let applicants: Vec<Applicant> = Applicants::get_from_db(db, company.id).await?.into_iter().collect();
+    let interviews: Vec<ApplicantInterview> = ApplicantInterviews::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .collect();
+    let events = expired_interview_events(&applicants, &interviews, retention_days, Utc::now());
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let meetings = recorded_meetings::dsl::recorded_meetings
+        .filter(recorded_meetings::dsl::cio_company_id.eq(company.id))
+        .filter(recorded_meetings::dsl::purged_at.is_null())
+        .filter(recorded_meetings::dsl::google_event_id.eq_any(events.into_iter().collect::<Vec<String>>()))
+        .load_async::<RecordedMeeting>(db.pool())
+        .await?;
+    if meetings.is_empty() {
+        return Ok(());
+    }
+
+    let drive = company.authenticate_google_drive(db).await?;
+
+    let mut receipts: Vec<RecordedMeetingPurge> = Default::default();
+    for mut meeting in meetings {
+        if !meeting.refresh_tags_from_airtable(db).await {
+            continue;
+        }
+
+        if meeting.is_on_legal_hold() {
+            info!(
+                "keeping interview recording {} `{}` on legal hold",
+                meeting.id, meeting.name
+            );
+            continue;
+        }
+        if !meeting.has_recording() {
+            continue;
+        }
+
+        match meeting
+            .purge(db, company, &drive, PurgeReason::InterviewDecision, retention_days)
+            .await
+        {
+            Ok(purge) => receipts.push(purge),
+            Err(e) => warn!(
+                "purging interview recording {} `{}` failed: {}",
+                meeting.id, meeting.name, e
+            ),
+        }
+    }
+    if receipts.is_empty() {
+        return Ok(());
+    }
+    info!(
+        "purged {} interview recordings {} days after the decision",
+        receipts.len(),
+        retention_days
+    );
+
+    let lines: Vec<String> = receipts
+        .iter()
+        .map(|p| {
+            format!(
+                "• {} _({}: {} files and {} transcript segments)_",
+                p.name,
+                p.start_time.format("%B %-d, %Y"),
+                p.deleted_files.len(),
+                p.transcript_segments
+            )
+        })
+        .collect();
+    let msg = FormattedMessage {
+        channel: company.slack_channel_applicants.to_string(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "Deleted the recordings and transcripts of these interviews, {} days after the decision on the \
+                     applicant:\n{}",
+                    retention_days,
+                    lines.join("\n")
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+        attachments: Default::default(),
+    };
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{drive_file_id, expired_interview_events};
+    use crate::{
+        applicants::tests::mock_applicant, interviews::ApplicantInterview, recorded_meetings::RecordedMeeting,
+    };
+
+    fn mock_meeting() -> RecordedMeeting {
+        RecordedMeeting {
+            id: 1,
+            name: "All hands".to_string(),
+            description: "".to_string(),
+            start_time: Utc.ymd(2023, 1, 2).and_hms(17, 0, 0),
+            end_time: Utc.ymd(2023, 1, 2).and_hms(18, 0, 0),
+            video: "https://drive.google.com/open?id=abc".to_string(),
+            chat_log_link: "".to_string(),
+            chat_log: "".to_string(),
+            is_recurring: false,
+            attendees: vec![],
+            transcript: "Hello.".to_string(),
+            transcript_id: "".to_string(),
+            transcript_callback_secret: "".to_string(),
+            google_event_id: "event".to_string(),
+            event_link: "".to_string(),
+            location: "".to_string(),
+            location_id: None,
+            host: "".to_string(),
+            tags: vec![],
+            purged_at: None,
+            summary: "".to_string(),
+            action_items: vec![],
+            summary_attempts: 0,
+            link_to_people: vec![],
+            visibility: "".to_string(),
+            participants: vec![],
+            slack_channel: "".to_string(),
+            transcript_doc: "".to_string(),
+            chat_log_file: "".to_string(),
+            transcript_file: "".to_string(),
+            thumbnail: "".to_string(),
+            cio_company_id: 1,
+            airtable_record_id: "".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_recorded_meeting_is_expired() {
+        let meeting = mock_meeting();
+        let now = meeting.end_time + Duration::days(31);
+
+        assert!(meeting.is_expired(30, now));
+        assert!(!meeting.is_expired(60, now));
+        assert!(!meeting.is_expired(0, now));
+
+        let mut kept = mock_meeting();
+        kept.tags = vec!["Keep ".to_string()];
+        assert!(!kept.is_expired(30, now));
+
+        let mut purged = mock_meeting();
+        purged.purged_at = Some(now);
+        assert!(!purged.is_expired(30, now));
+
+        let mut empty = mock_meeting();
+        empty.video = "".to_string();
+        empty.transcript = "".to_string();
+        assert!(!empty.is_expired(30, now));
+
+        let mut held = mock_meeting();
+        held.tags = vec!["Legal Hold".to_string()];
+        assert!(!held.is_expired(30, now));
+        assert!(held.has_recording());
+    }
+
+    #[test]
+    fn test_expired_interview_events() {
+        let now = Utc.ymd(2024, 3, 1).and_hms(12, 0, 0);
+        let interview = |email: &str, event: &str| -> ApplicantInterview {
+            serde_json::from_value(serde_json::json!({
+                "start_time": "2024-01-08T17:00:00Z",
+                "end_time": "2024-01-08T18:00:00Z",
+                "email": email,
+                "google_event_id": event,
+            }))
+            .unwrap()
+        };
+
+        let mut declined = mock_applicant();
+        declined.email = "declined@example.com".to_string();
+        declined.status = "Declined".to_string();
+        declined.rejection_sent_date_time = Some(now - Duration::days(40));
+
+        let mut recent = mock_applicant();
+        recent.email = "recent@example.com".to_string();
+        recent.status = "Hired".to_string();
+        recent.offer_completed = Some(now - Duration::days(10));
+
+        let mut interviewing = mock_applicant();
+        interviewing.email = "interviewing@example.com".to_string();
+        interviewing.status = "Interviewing".to_string();
+        interviewing.interviews_completed = Some(now - Duration::days(60));
+
+        // They applied again, and we have not decided yet.
+        let mut again = declined.clone();
+        again.email = "again@example.com".to_string();
+        let mut reapplied = mock_applicant();
+        reapplied.email = "Again@example.com".to_string();
+        reapplied.status = "Interviewing".to_string();
+
+        assert_eq!(None, interviewing.decided_at());
+        assert_eq!(recent.offer_completed, recent.decided_at());
+
+        let interviews = vec![
+            interview("Declined@example.com", "event-1"),
+            interview("declined@example.com", ""),
+            interview("recent@example.com", "event-2"),
+            interview("interviewing@example.com", "event-3"),
+            interview("again@example.com", "event-4"),
+        ];
+        let events = expired_interview_events(
+            &[declined, recent, interviewing, again, reapplied],
+            &interviews,
+            30,
+            now,
+        );
+        assert_eq!(vec!["event-1"], events.into_iter().collect::<Vec<String>>());
+    }
+
+    #[test]
+    fn test_drive_file_id() {
+        assert_eq!(
+            Some("abc".to_string()),
+            drive_file_id("https://drive.google.com/open?id=abc")
+        );
+        assert_eq!(
+            Some("abc".to_string()),
+            drive_file_id("https://drive.google.com/file/d/abc/view?usp=drive_web")
+        );
+        assert_eq!(
+            Some("abc".to_string()),
+            drive_file_id("https://docs.google.com/document/d/abc/edit")
+        );
+        assert_eq!(None, drive_file_id("https://zoom.us/rec/share/abc"));
+        assert_eq!(None, drive_file_id(""));
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{Duration, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    companies::Company,
+    db::Database,
+    recorded_meetings::RecordedMeeting,
+    schema::recorded_meetings,
+    summarization::{get_summarization_provider, SummarizationProvider},
+    utils::truncate,
+};
+
+static MEETING_SUMMARY_INSTRUCTIONS: &str = "You summarize transcripts of recorded meetings. Reply with a short \
+summary of what was discussed and decided, then a line with `Action items:`, followed by each action item on its \
+own line starting with `- `, including who owns it if the transcript says so. Reply with `- None` if there are no \
+action items. Do not include anything else.";
+
+/// The most of a transcript we send to the summarization provider, so long meetings still fit
+/// in the context of the model.
+static MAX_TRANSCRIPT_CHARS: usize = 48000;
+
+/// How many days after a meeting we still summarize it. Older meetings are not worth
+/// messaging the host about.
+static SUMMARIZE_WITHIN_DAYS: i64 = 7;
+
+/// How many times we try to summarize a meeting before giving up on it.
+static MAX_SUMMARY_ATTEMPTS: i32 = 3;
+
+/// Split the response of the provider into the summary and the action items.
+fn parse_meeting_summary(response: &str) -> (String, Vec<String>) {
+    let response = response.trim();
+    let (summary, action_items) = match response.find("Action items:") {
+        Some(i) => (&response[..i], &response[i + "Action items:".len()..]),
+        None => (response, ""),
+    };
+
+    let action_items = action_items
+        .lines()
+        .map(|l| l.trim().trim_start_matches(|c| c == '-' || c == '*').trim())
+        .filter(|l| !l.is_empty() && !l.eq_ignore_ascii_case("none"))
+        .map(|l| l.to_string())
+        .collect();
+
+    (
+        summary.trim().trim_start_matches("Summary:").trim().to_string(),
+        action_items,
+    )
+}
+
+fn section(text: String) -> MessageBlock {
+    MessageBlock {
+        block_type: MessageBlockType::Section,
+        text: Some(MessageBlockText {
+            text_type: MessageType::Markdown,
+            text,
+        }),
+        elements: Default::default(),
+        accessory: Default::default(),
+        block_id: Default::default(),
+        fields: Default::default(),
+    }
+}
+
+impl RecordedMeeting {
+    /// Generate the summary and action items of the meeting from its transcript. Failures are
+    /// counted, so we stop trying after a few.
+    pub async fn summarize(&mut self, db: &Database, provider: &dyn SummarizationProvider) -> Result<()> {
+        let response = match provider
+            .summarize(
+                MEETING_SUMMARY_INSTRUCTIONS,
+                &truncate(&self.transcript, MAX_TRANSCRIPT_CHARS),
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                self.summary_attempts += 1;
+                self.update(db).await?;
+                return Err(e);
+            }
+        };
+        let (summary, action_items) = parse_meeting_summary(&response);
+        if summary.is_empty() {
+            self.summary_attempts += 1;
+            self.update(db).await?;
+            bail!("the summarization provider returned an empty summary");
+        }
+
+        self.summary = summary;
+        self.action_items = action_items;
+        self.update(db).await?;
+
+        Ok(())
+    }
+
+    /// Convert the summary of the meeting into a Slack message.
+    fn summary_slack_message(&self) -> FormattedMessage {
+        let mut blocks = vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: format!("Summary of {}", self.name),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            section(self.summary.to_string()),
+        ];
+
+        if !self.action_items.is_empty() {
+            blocks.push(section(format!(
+                "*Action items*\n{}",
+                self.action_items
+                    .iter()
+                    .map(|a| format!("• {}", a))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            )));
+        }
+
+        if !self.video.is_empty() {
+            blocks.push(MessageBlock {
+                block_type: MessageBlockType::Context,
+                elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!("<{}|video>", self.video),
+                })],
+                text: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            });
+        }
+
+        FormattedMessage {
+            channel: Default::default(),
+            blocks,
+            attachments: Default::default(),
+        }
+    }
+
+    /// Send the summary of the meeting to its host as a Slack direct message.
+    pub async fn send_summary_to_host(&self, db: &Database, company: &Company) -> Result<()> {
+        if self.host.is_empty() || self.summary.is_empty() {
+            return Ok(());
+        }
+
+        let slack = company.authenticate_slack(db).await?;
+
+        let mut msg = self.summary_slack_message();
+        msg.channel = slack.lookup_user_by_email(&self.host).await?.id;
+
+        company.post_to_slack_channel(db, &msg).await
+    }
+}
+
+/// Summarize the recent recorded meetings that have a transcript but no summary yet, and send
+/// the summaries to the hosts.
+pub async fn summarize_recorded_meetings(db: &Database, company: &Company) -> Result<()> {
+    let meetings = recorded_meetings::dsl::recorded_meetings
+        .filter(recorded_meetings::dsl::cio_company_id.eq(company.id))
+        .filter(recorded_meetings::dsl::purged_at.is_null())
+        .filter(recorded_meetings::dsl::transcript.ne(""))
+        .filter(recorded_meetings::dsl::summary.eq(""))
+        .filter(recorded_meetings::dsl::summary_attempts.lt(MAX_SUMMARY_ATTEMPTS))
+        .filter(recorded_meetings::dsl::start_time.ge(Utc::now() - Duration::days(SUMMARIZE_WITHIN_DAYS)))
+        .load_async::<RecordedMeeting>(db.pool())
+        .await?;
+    if meetings.is_empty() {
+        return Ok(());
+    }
+
+    let provider = match get_summarization_provider() {
+        Ok(provider) => provider,
+        Err(e) => {
+            // Return early, summaries are optional.
+            info!("not summarizing recorded meetings: {}", e);
+            return Ok(());
+        }
+    };
+
+    for mut meeting in meetings {
+        if let Err(e) = meeting.summarize(db, provider.as_ref()).await {
+            warn!(
+                "summarizing recorded meeting {} `{}` failed: {}",
+                meeting.id, meeting.name, e
+            );
+            continue;
+        }
+        info!("summarized recorded meeting {} `{}`", meeting.id, meeting.name);
+
+        if let Err(e) = meeting.send_summary_to_host(db, company).await {
+            warn!(
+                "sending summary of recorded meeting {} `{}` to {} failed: {}",
+                meeting.id, meeting.name, meeting.host, e
+            );
+        }
+
+        if let Err(e) = meeting.track_action_items(db, company).await {
+            warn!(
+                "tracking the action items of recorded meeting {} `{}` failed: {}",
+                meeting.id, meeting.name, e
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_meeting_summary;
+
+    #[test]
+    fn test_parse_meeting_summary() {
+        let (summary, action_items) = parse_meeting_summary(
+            "Summary: We went over the rack bring-up and agreed to ship on Friday.
+
+Action items:
+- Jane to update the checklist
+* Sam to order more cables
+",
+        );
+        assert_eq!("We went over the rack bring-up and agreed to ship on Friday.", summary);
+        assert_eq!(
+            vec![
+                "Jane to update the checklist".to_string(),
+                "Sam to order more cables".to_string()
+            ],
+            action_items
+        );
+
+        let (summary, action_items) = parse_meeting_summary("A quick sync.\nAction items:\n- None");
+        assert_eq!("A quick sync.", summary);
+        assert!(action_items.is_empty());
+
+        let (summary, action_items) = parse_meeting_summary("Just a summary.");
+        assert_eq!("Just a summary.", summary);
+        assert!(action_items.is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{bail, Result};
+use google_drive::traits::{DriveOps, FileOps};
+use log::{info, warn};
+use serde::Deserialize;
+
+use crate::{
+    companies::Company,
+    db::Database,
+    meeting_retention::drive_file_id,
+    recorded_meetings::{format_offset, MeetingVisibility, RecordedMeeting, RecordedMeetingTranscriptSegment},
+};
+
+/// Drive converts the HTML we upload into a Google Doc when the file has this mime type.
+static GOOGLE_DOC_MIME_TYPE: &str = "application/vnd.google-apps.document";
+
+/// The boundary of the multipart upload of a Doc, it cannot appear in the escaped HTML.
+static UPLOAD_BOUNDARY: &str = "transcript_doc_boundary";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct UploadedFile {
+    #[serde(default)]
+    id: String,
+}
+
+/// Escape text for the HTML we upload to Drive.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render the transcript of a meeting as HTML, with its attendees, summary and action items
+/// first. We use the transcript text for meetings without segments.
+pub fn render_transcript_doc(meeting: &RecordedMeeting, segments: &[RecordedMeetingTranscriptSegment]) -> String {
+    let mut html = format!(
+        "<html><body><h1>{}</h1><p><i>{} to {}</i></p>",
+        escape_html(&meeting.name),
+        meeting.start_time.format("%A, %B %-d, %Y %H:%M UTC"),
+        meeting.end_time.format("%H:%M UTC")
+    );
+
+    if !meeting.attendees.is_empty() {
+        html.push_str("<h2>Attendees</h2><ul>");
+        for attendee in &meeting.attendees {
+            html.push_str(&format!("<li>{}</li>", escape_html(attendee)));
+        }
+        html.push_str("</ul>");
+    }
+
+    if !meeting.summary.is_empty() {
+        html.push_str(&format!("<h2>Summary</h2><p>{}</p>", escape_html(&meeting.summary)));
+    }
+
+    if !meeting.action_items.is_empty() {
+        html.push_str("<h2>Action items</h2><ul>");
+        for item in &meeting.action_items {
+            html.push_str(&format!("<li>{}</li>", escape_html(item)));
+        }
+        html.push_str("</ul>");
+    }
+
+    html.push_str("<h2>Transcript</h2>");
+    if segments.is_empty() {
+        for line in meeting.transcript.lines().filter(|l| !l.trim().is_empty()) {
+            html.push_str(&format!("<p>{}</p>", escape_html(line)));
+        }
+    } else {
+        for segment in segments {
+            html.push_str(&format!(
+                "<p><span style=\"color:#888888\">{}</span> <b>{}</b>: {}</p>",
+                format_offset(segment.start_offset),
+                escape_html(&segment.speaker),
+                escape_html(&segment.text)
+            ));
+        }
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+/// Upload HTML to Drive as a Google Doc, and return the id of the Doc. Our Drive client uploads
+/// the media with the mime type of the file, but Drive only converts media it can read, so we
+/// upload the HTML as `text/html` ourselves. The Doc is updated if we already have one.
+async fn upload_html_as_doc(
+    token: &str,
+    existing_id: Option<&str>,
+    folder_id: &str,
+    name: &str,
+    html: &str,
+) -> Result<String> {
+    crate::rate_limit::acquire(crate::rate_limit::Provider::Google).await;
+    let client = reqwest::Client::new();
+    let resp = match existing_id {
+        Some(id) => {
+            client
+                .patch(&format!(
+                    "https://www.googleapis.com/upload/drive/v3/files/{}?uploadType=media&supportsAllDrives=true",
+                    id
+                ))
+                .bearer_auth(token)
+                .header(reqwest::header::CONTENT_TYPE, "text/html; charset=UTF-8")
+                .body(html.to_string())
+                .send()
+                .await?
+        }
+        None => {
+            let metadata = json!({
+                "name": name,
+                "mimeType": GOOGLE_DOC_MIME_TYPE,
+                "parents": [folder_id],
+            });
+            let body = format!(
+                "--{b}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{}\r\n--{b}\r\nContent-Type: \
+                 text/html; charset=UTF-8\r\n\r\n{}\r\n--{b}--\r\n",
+                metadata,
+                html,
+                b = UPLOAD_BOUNDARY
+            );
+            client
+                .post("https://www.googleapis.com/upload/drive/v3/files?uploadType=multipart&supportsAllDrives=true")
+                .bearer_auth(token)
+                .header(
+                    reqwest::header::CONTENT_TYPE,
+                    format!("multipart/related; boundary={}", UPLOAD_BOUNDARY),
+                )
+                .body(body)
+                .send()
+                .await?
+        }
+    };
+
+    let status = resp.status();
+    if !status.is_success() {
+        bail!(
+            "uploading `{}` to Drive failed with {}: {}",
+            name,
+            status,
+            resp.text().await.unwrap_or_default()
+        );
+    }
+
+    let file: UploadedFile = resp.json().await?;
+    Ok(file.id)
+}
+
+impl RecordedMeeting {
+    /// Export the transcript of the meeting to a Google Doc next to its recording in Drive, and
+    /// link it on the meeting. Exporting again updates the same Doc. The transcript of a
+    /// restricted or private meeting is not exported, like it is not uploaded to Airtable.
+    pub async fn export_transcript_to_doc(&mut self, db: &Database, company: &Company) -> Result<String> {
+        if MeetingVisibility::from_field(&self.visibility) != MeetingVisibility::PublicInternal {
+            bail!(
+                "meeting `{}` is not public, its transcript stays out of Google Docs",
+                self.name
+            );
+        }
+
+        let segments = self.transcript_segments(db).await?;
+        if segments.is_empty() && self.transcript.trim().is_empty() {
+            bail!("meeting `{}` has no transcript", self.name);
+        }
+
+        let drive = company.authenticate_google_drive(db).await?;
+        let shared_drive = drive.drives().get_by_name("Automated Documents").await?;
+
+        // The folder of the meeting is the one its recording is in.
+        let mut folder_id = String::new();
+        if let Some(video_id) = drive_file_id(&self.video) {
+            match drive
+                .files()
+                .get(
+                    &video_id, false, // acknowledge_abuse
+                    "",    // include_permissions_for_view
+                    true,  // supports_all_drives
+                    true,  // supports_team_drives
+                )
+                .await
+            {
+                Ok(video) => folder_id = video.parents.first().cloned().unwrap_or_default(),
+                Err(e) => warn!("getting the recording of meeting `{}` failed: {}", self.name, e),
+            }
+        }
+        if folder_id.is_empty() {
+            folder_id = drive
+                .files()
+                .create_folder(&shared_drive.id, "", "meeting_transcripts")
+                .await?;
+        }
+
+        let doc_id = upload_html_as_doc(
+            &company.google_access_token(db).await?,
+            drive_file_id(&self.transcript_doc).as_deref(),
+            &folder_id,
+            &format!("{} - Transcript", self.name),
+            &render_transcript_doc(self, &segments),
+        )
+        .await?;
+
+        self.transcript_doc = format!("https://docs.google.com/document/d/{}/edit", doc_id);
+        self.update(db).await?;
+        info!(
+            "exported the transcript of meeting `{}` to {}",
+            self.name, self.transcript_doc
+        );
+
+        Ok(self.transcript_doc.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::render_transcript_doc;
+    use crate::recorded_meetings::{RecordedMeeting, RecordedMeetingTranscriptSegment};
+    use crate::tests::mock_record;
+
+    #[test]
+    fn test_render_transcript_doc() {
+        let mut meeting: RecordedMeeting = mock_record(
+            json!({"name": "Product <sync>", "start_time": "2024-01-09T17:00:00Z", "end_time": "2024-01-09T18:00:00Z"}),
+        );
+        meeting.attendees = vec!["test@example.com".to_string()];
+        meeting.summary = "We shipped & celebrated.".to_string();
+        meeting.transcript = "Fallback line".to_string();
+        assert_eq!(Utc.ymd(2024, 1, 9).and_hms(17, 0, 0), meeting.start_time);
+
+        let segments = vec![RecordedMeetingTranscriptSegment {
+            id: 1,
+            recorded_meeting_id: 1,
+            position: 0,
+            speaker: "Test".to_string(),
+            start_offset: 62.5,
+            end_offset: 70.0,
+            text: "Hello <everyone>".to_string(),
+        }];
+
+        let html = render_transcript_doc(&meeting, &segments);
+        assert!(html.contains("<h1>Product &lt;sync&gt;</h1>"));
+        assert!(html.contains("<li>test@example.com</li>"));
+        assert!(html.contains("<p>We shipped &amp; celebrated.</p>"));
+        assert!(!html.contains("Action items"));
+        assert!(html.contains("00:01:02</span> <b>Test</b>: Hello &lt;everyone&gt;</p>"));
+        assert!(!html.contains("Fallback line"));
+
+        assert!(render_transcript_doc(&meeting, &[]).contains("<p>Fallback line</p>"));
+    }
+}
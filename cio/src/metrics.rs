@@ -0,0 +1,403 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    applicant_status::Status,
+    applicants::{Applicant, Applicants},
+    asset_inventory::{AssetItem, AssetItems},
+    companies::Company,
+    configs::{User, Users},
+    db::Database,
+    rfd::{RFDState, RFDs, RFD},
+    roles::Role,
+    schema::{mailing_list_subscribers, metrics_snapshots},
+};
+
+/// The most weeks of snapshots we return at once.
+static MAX_METRICS_WEEKS: i64 = 520;
+
+/// The key operational numbers of a company for a week.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct MetricsSnapshot {
+    pub id: i32,
+    /// The Monday of the week the snapshot is for.
+    pub week: NaiveDate,
+    /// The full-time employees working at the company.
+    pub headcount: i32,
+    /// The roles we have applicants in the pipeline for. The applicants for a role of our
+    /// catalog count for it, whatever they wrote the role as.
+    pub open_roles: i32,
+    pub applicants_in_pipeline: i32,
+    /// The asset items someone is borrowing, or a conference room is using.
+    pub assets_deployed: i32,
+    pub rfds_published: i32,
+    pub mailing_list_subscribers: i32,
+    pub created_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone)]
+#[diesel(table_name = metrics_snapshots)]
+struct NewMetricsSnapshot {
+    week: NaiveDate,
+    headcount: i32,
+    open_roles: i32,
+    applicants_in_pipeline: i32,
+    assets_deployed: i32,
+    rfds_published: i32,
+    mailing_list_subscribers: i32,
+    cio_company_id: i32,
+}
+
+/// Return the Monday of the week a date is in.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Return if an applicant is still in the hiring pipeline, as opposed to hired, declined, or
+/// gone.
+fn is_in_pipeline(applicant: &Applicant) -> bool {
+    matches!(
+        applicant.status.parse::<Status>().unwrap_or_default(),
+        Status::NeedsToBeTriaged | Status::NextSteps | Status::Interviewing | Status::Debrief | Status::GivingOffer
+    )
+}
+
+/// Get the catalog roles of the roles the applicants in the pipeline applied for, by the role
+/// they applied for.
+async fn get_pipeline_catalog_roles(
+    db: &Database,
+    company: &Company,
+    applicants: &[Applicant],
+) -> Result<BTreeMap<String, Role>> {
+    let mut catalog_roles = BTreeMap::new();
+    for applicant in applicants.iter().filter(|a| is_in_pipeline(a)) {
+        let role = applicant.role.trim();
+        if role.is_empty() || catalog_roles.contains_key(role) {
+            continue;
+        }
+
+        if let Some(catalog_role) = Role::get_for_applicant_role(db, company.id, role).await? {
+            catalog_roles.insert(role.to_string(), catalog_role);
+        }
+    }
+
+    Ok(catalog_roles)
+}
+
+/// Return the role an applicant counts for: the title of its catalog role, or what they wrote
+/// if it is not in the catalog.
+fn pipeline_role(applicant: &Applicant, catalog_roles: &BTreeMap<String, Role>) -> String {
+    let role = applicant.role.trim();
+
+    match catalog_roles.get(role) {
+        Some(catalog_role) if !catalog_role.title.trim().is_empty() => catalog_role.title.trim().to_lowercase(),
+        _ => role.to_lowercase(),
+    }
+}
+
+impl NewMetricsSnapshot {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        company: &Company,
+        today: NaiveDate,
+        users: &[User],
+        applicants: &[Applicant],
+        catalog_roles: &BTreeMap<String, Role>,
+        asset_items: &[AssetItem],
+        rfds: &[RFD],
+        mailing_list_subscribers: i64,
+    ) -> Self {
+        let headcount = users
+            .iter()
+            .filter(|u| u.is_full_time() && u.start_date <= today && u.end_date.map(|d| d > today).unwrap_or(true))
+            .count();
+
+        let pipeline: Vec<&Applicant> = applicants.iter().filter(|a| is_in_pipeline(a)).collect();
+        let mut open_roles: Vec<String> = pipeline
+            .iter()
+            .map(|a| pipeline_role(a, catalog_roles))
+            .filter(|r| !r.is_empty())
+            .collect();
+        open_roles.sort();
+        open_roles.dedup();
+
+        let assets_deployed = asset_items
+            .iter()
+            .filter(|i| !i.current_employee_borrowing.is_empty() || !i.conference_room_using.is_empty())
+            .count();
+
+        let rfds_published = rfds
+            .iter()
+            .filter(|r| matches!(r.state.parse(), Ok(RFDState::Published | RFDState::Committed)))
+            .count();
+
+        NewMetricsSnapshot {
+            week: week_start(today),
+            headcount: headcount as i32,
+            open_roles: open_roles.len() as i32,
+            applicants_in_pipeline: pipeline.len() as i32,
+            assets_deployed: assets_deployed as i32,
+            rfds_published: rfds_published as i32,
+            mailing_list_subscribers: mailing_list_subscribers as i32,
+            cio_company_id: company.id,
+        }
+    }
+}
+
+/// Snapshot the company's metrics for the current week. Taking it again in the same week
+/// replaces the week's numbers.
+pub async fn take_metrics_snapshot(db: &Database, company: &Company) -> Result<MetricsSnapshot> {
+    let today = Utc::now().date().naive_utc();
+
+    let users: Vec<User> = Users::get_from_db(db, company.id).await?.into_iter().collect();
+    let applicants: Vec<Applicant> = Applicants::get_from_db(db, company.id).await?.into_iter().collect();
+    let asset_items: Vec<AssetItem> = AssetItems::get_from_db(db, company.id).await?.into_iter().collect();
+    let rfds: Vec<RFD> = RFDs::get_from_db(db, company.id).await?.into_iter().collect();
+    let catalog_roles = get_pipeline_catalog_roles(db, company, &applicants).await?;
+    let subscribers = mailing_list_subscribers::dsl::mailing_list_subscribers
+        .filter(mailing_list_subscribers::dsl::cio_company_id.eq(company.id))
+        .count()
+        .get_result_async::<i64>(db.pool())
+        .await?;
+
+    let snapshot = NewMetricsSnapshot::new(
+        company,
+        today,
+        &users,
+        &applicants,
+        &catalog_roles,
+        &asset_items,
+        &rfds,
+        subscribers,
+    );
+
+    let snapshot = diesel::insert_into(metrics_snapshots::table)
+        .values(snapshot.clone())
+        .on_conflict((metrics_snapshots::dsl::cio_company_id, metrics_snapshots::dsl::week))
+        .do_update()
+        .set(snapshot)
+        .get_result_async::<MetricsSnapshot>(db.pool())
+        .await?;
+    info!("took the metrics snapshot of `{}` for {}", company.name, snapshot.week);
+
+    Ok(snapshot)
+}
+
+/// Get the company's metrics snapshots for the last weeks, oldest first.
+pub async fn list_metrics_snapshots(db: &Database, cio_company_id: i32, weeks: i64) -> Result<Vec<MetricsSnapshot>> {
+    let since = week_start(Utc::now().date().naive_utc()) - Duration::weeks(weeks.clamp(1, MAX_METRICS_WEEKS) - 1);
+
+    Ok(metrics_snapshots::dsl::metrics_snapshots
+        .filter(metrics_snapshots::dsl::cio_company_id.eq(cio_company_id))
+        .filter(metrics_snapshots::dsl::week.ge(since))
+        .order_by(metrics_snapshots::dsl::week.asc())
+        .load_async::<MetricsSnapshot>(db.pool())
+        .await?)
+}
+
+/// Format a metric with how much it changed since the previous snapshot.
+fn format_metric(name: &str, value: i32, previous: Option<i32>) -> String {
+    match previous.map(|p| value - p) {
+        Some(delta) if delta > 0 => format!("• {}: *{}* (+{})", name, value, delta),
+        Some(delta) if delta < 0 => format!("• {}: *{}* ({})", name, value, delta),
+        _ => format!("• {}: *{}*", name, value),
+    }
+}
+
+/// Format the weekly digest of the metrics, compared to the week before.
+pub fn format_metrics_digest(current: &MetricsSnapshot, previous: Option<&MetricsSnapshot>) -> String {
+    [
+        format_metric("Headcount", current.headcount, previous.map(|p| p.headcount)),
+        format_metric("Open roles", current.open_roles, previous.map(|p| p.open_roles)),
+        format_metric(
+            "Applicants in the pipeline",
+            current.applicants_in_pipeline,
+            previous.map(|p| p.applicants_in_pipeline),
+        ),
+        format_metric(
+            "Assets deployed",
+            current.assets_deployed,
+            previous.map(|p| p.assets_deployed),
+        ),
+        format_metric(
+            "RFDs published",
+            current.rfds_published,
+            previous.map(|p| p.rfds_published),
+        ),
+        format_metric(
+            "Mailing list subscribers",
+            current.mailing_list_subscribers,
+            previous.map(|p| p.mailing_list_subscribers),
+        ),
+    ]
+    .join("\n")
+}
+
+/// Snapshot the company's metrics for the week, and post the digest to Slack.
+pub async fn send_metrics_digest(db: &Database, company: &Company) -> Result<()> {
+    let current = take_metrics_snapshot(db, company).await?;
+    let snapshots = list_metrics_snapshots(db, company.id, 2).await?;
+    let previous = snapshots.iter().find(|s| s.week < current.week);
+
+    let channel = match company.alerts_channel("") {
+        Some(channel) => channel,
+        None => {
+            warn!(
+                "company `{}` has no alerts channel, not posting the metrics digest",
+                company.name
+            );
+            return Ok(());
+        }
+    };
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: format!("Metrics for the week of {}", current.week.format("%B %-d")),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format_metrics_digest(&current, previous),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{NaiveDate, Utc};
+    use serde_json::json;
+
+    use super::{format_metrics_digest, week_start, MetricsSnapshot, NewMetricsSnapshot};
+    use crate::{
+        applicants::{tests::mock_applicant, Applicant},
+        asset_inventory::AssetItem,
+        companies::tests::mock_company,
+        configs::tests::mock_user,
+        rfd::RFD,
+        roles::Role,
+    };
+
+    #[test]
+    fn test_metrics_snapshot() {
+        let today = NaiveDate::from_ymd(2023, 7, 12);
+        assert_eq!(NaiveDate::from_ymd(2023, 7, 10), week_start(today));
+
+        let mut employee = mock_user();
+        employee.typev = "full-time".to_string();
+        employee.start_date = NaiveDate::from_ymd(2021, 1, 4);
+        let mut departed = employee.clone();
+        departed.end_date = Some(NaiveDate::from_ymd(2023, 6, 30));
+        let mut future = employee.clone();
+        future.start_date = NaiveDate::from_ymd(2023, 8, 1);
+
+        let applicant = |role: &str, status: &str| -> Applicant {
+            let mut applicant = mock_applicant();
+            applicant.role = role.to_string();
+            applicant.status = status.to_string();
+            applicant
+        };
+        let item = |borrower: &str| -> AssetItem {
+            serde_json::from_value(json!({"id": 1, "airtable_record_id": "", "current_employee_borrowing": borrower}))
+                .unwrap()
+        };
+        let rfd = |state: &str| -> RFD {
+            serde_json::from_value(json!({
+                "id": 1,
+                "airtable_record_id": "",
+                "number": 1,
+                "title": "Things",
+                "state": state,
+                "link": "",
+            }))
+            .unwrap()
+        };
+
+        // The applicants who wrote the name of a catalog role count for the role with its title.
+        let mut catalog_roles = BTreeMap::new();
+        catalog_roles.insert(
+            "product-engineer".to_string(),
+            serde_json::from_value::<Role>(json!({
+                "id": 1,
+                "airtable_record_id": "",
+                "name": "product-engineer",
+                "title": "Product Engineer",
+            }))
+            .unwrap(),
+        );
+
+        let snapshot = NewMetricsSnapshot::new(
+            &mock_company(),
+            today,
+            &[employee, departed, future],
+            &[
+                applicant("Product Engineer", "Interviewing"),
+                applicant("product engineer", "Needs to be triaged"),
+                applicant("product-engineer", "Next steps"),
+                applicant("Operations", "Declined"),
+            ],
+            &catalog_roles,
+            &[item("jane@example.com"), item("")],
+            &[rfd("published"), rfd("committed"), rfd("discussion")],
+            42,
+        );
+        assert_eq!(1, snapshot.headcount);
+        assert_eq!(1, snapshot.open_roles);
+        assert_eq!(3, snapshot.applicants_in_pipeline);
+        assert_eq!(1, snapshot.assets_deployed);
+        assert_eq!(2, snapshot.rfds_published);
+
+        let current = MetricsSnapshot {
+            id: 2,
+            week: snapshot.week,
+            headcount: snapshot.headcount,
+            open_roles: snapshot.open_roles,
+            applicants_in_pipeline: snapshot.applicants_in_pipeline,
+            assets_deployed: snapshot.assets_deployed,
+            rfds_published: snapshot.rfds_published,
+            mailing_list_subscribers: snapshot.mailing_list_subscribers,
+            created_at: Utc::now(),
+            cio_company_id: 1,
+        };
+        let mut previous = current.clone();
+        previous.headcount = 3;
+        previous.mailing_list_subscribers = 40;
+
+        let digest = format_metrics_digest(&current, Some(&previous));
+        assert!(digest.contains("• Headcount: *1* (-2)"));
+        assert!(digest.contains("• Mailing list subscribers: *42* (+2)"));
+        assert!(digest.contains("• Open roles: *1*\n"));
+    }
+}
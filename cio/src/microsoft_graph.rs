@@ -0,0 +1,293 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::info;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+static GRAPH_ENDPOINT: &str = "https://graph.microsoft.com/v1.0/";
+
+/// A minimal client for the Microsoft Graph API. We only use it for pulling Teams
+/// meeting recordings and transcripts, so it only implements what we need for that.
+pub struct MicrosoftGraph {
+    client: reqwest::Client,
+    token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AccessTokenResponse {
+    access_token: String,
+}
+
+/// A page of results from the Graph API.
+#[derive(Debug, Clone, Deserialize)]
+struct GraphPage<T> {
+    #[serde(default = "Vec::new")]
+    value: Vec<T>,
+    #[serde(default, rename = "@odata.nextLink")]
+    next_link: String,
+}
+
+/// A recording of a Teams meeting.
+/// FROM: https://learn.microsoft.com/en-us/graph/api/resources/callrecording
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallRecording {
+    pub id: String,
+    #[serde(default)]
+    pub meeting_id: String,
+    #[serde(default)]
+    pub created_date_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end_date_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub recording_content_url: String,
+}
+
+/// A transcript of a Teams meeting.
+/// FROM: https://learn.microsoft.com/en-us/graph/api/resources/calltranscript
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallTranscript {
+    pub id: String,
+    #[serde(default)]
+    pub meeting_id: String,
+    #[serde(default)]
+    pub created_date_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub transcript_content_url: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingParticipantInfo {
+    #[serde(default)]
+    pub upn: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingParticipants {
+    #[serde(default)]
+    pub organizer: Option<MeetingParticipantInfo>,
+    #[serde(default)]
+    pub attendees: Vec<MeetingParticipantInfo>,
+}
+
+/// A Teams online meeting.
+/// FROM: https://learn.microsoft.com/en-us/graph/api/resources/onlinemeeting
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnlineMeeting {
+    pub id: String,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub start_date_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end_date_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub join_web_url: String,
+    #[serde(default)]
+    pub participants: MeetingParticipants,
+}
+
+impl OnlineMeeting {
+    /// Return the emails of everyone invited to the meeting, including the organizer.
+    pub fn attendee_emails(&self) -> Vec<String> {
+        let mut emails: Vec<String> = self
+            .participants
+            .organizer
+            .iter()
+            .chain(self.participants.attendees.iter())
+            .filter(|p| !p.upn.is_empty())
+            .map(|p| p.upn.to_lowercase())
+            .collect();
+        emails.sort();
+        emails.dedup();
+        emails
+    }
+}
+
+impl MicrosoftGraph {
+    /// Authenticate with the Graph API using the client credentials of our app registration,
+    /// from the `MICROSOFT_TENANT_ID`, `MICROSOFT_CLIENT_ID` and `MICROSOFT_CLIENT_SECRET`
+    /// environment variables. Returns None if they are not set, since not every company
+    /// uses Teams.
+    pub async fn new_from_env() -> Result<Option<Self>> {
+        let (tenant_id, client_id, client_secret) = match (
+            std::env::var("MICROSOFT_TENANT_ID"),
+            std::env::var("MICROSOFT_CLIENT_ID"),
+            std::env::var("MICROSOFT_CLIENT_SECRET"),
+        ) {
+            (Ok(t), Ok(i), Ok(s)) if !t.is_empty() && !i.is_empty() && !s.is_empty() => (t, i, s),
+            _ => return Ok(None),
+        };
+
+        let client = reqwest::Client::new();
+        let resp = client
+            .post(&format!(
+                "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
+                tenant_id
+            ))
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", &client_id),
+                ("client_secret", &client_secret),
+                ("scope", "https://graph.microsoft.com/.default"),
+            ])
+            .send()
+            .await?;
+        let status = resp.status();
+        if !status.is_success() {
+            bail!(
+                "authenticating with microsoft graph failed: status code: {}, body: {}",
+                status,
+                resp.text().await?
+            );
+        }
+        let token: AccessTokenResponse = resp.json().await?;
+
+        Ok(Some(MicrosoftGraph {
+            client,
+            token: token.access_token,
+        }))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let resp = self.client.get(url).bearer_auth(&self.token).send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            bail!(
+                "microsoft graph request to {} failed: status code: {}, body: {}",
+                url,
+                status,
+                resp.text().await?
+            );
+        }
+
+        Ok(resp.json().await?)
+    }
+
+    async fn get_all<T: DeserializeOwned>(&self, url: &str) -> Result<Vec<T>> {
+        let mut all: Vec<T> = Default::default();
+        let mut url = url.to_string();
+        loop {
+            let page: GraphPage<T> = self.get(&url).await?;
+            all.extend(page.value);
+
+            if page.next_link.is_empty() {
+                break;
+            }
+            url = page.next_link;
+        }
+
+        Ok(all)
+    }
+
+    /// List all the recordings of meetings organized by a user since a point in time.
+    pub async fn list_recordings(&self, user_id: &str, since: DateTime<Utc>) -> Result<Vec<CallRecording>> {
+        self.get_all(&format!(
+            "{}users/{}/onlineMeetings/getAllRecordings(meetingOrganizerUserId='{}',startDateTime={})",
+            GRAPH_ENDPOINT,
+            user_id,
+            user_id,
+            since.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ))
+        .await
+    }
+
+    /// List all the transcripts of meetings organized by a user since a point in time.
+    pub async fn list_transcripts(&self, user_id: &str, since: DateTime<Utc>) -> Result<Vec<CallTranscript>> {
+        self.get_all(&format!(
+            "{}users/{}/onlineMeetings/getAllTranscripts(meetingOrganizerUserId='{}',startDateTime={})",
+            GRAPH_ENDPOINT,
+            user_id,
+            user_id,
+            since.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ))
+        .await
+    }
+
+    /// Get an online meeting organized by a user.
+    pub async fn get_online_meeting(&self, user_id: &str, meeting_id: &str) -> Result<OnlineMeeting> {
+        self.get(&format!(
+            "{}users/{}/onlineMeetings/{}",
+            GRAPH_ENDPOINT, user_id, meeting_id
+        ))
+        .await
+    }
+
+    /// Download the content of a recording or transcript.
+    pub async fn get_content(&self, url: &str) -> Result<Vec<u8>> {
+        info!(
+            "downloading microsoft graph content {}... This might take a bit...",
+            url
+        );
+
+        let resp = self.client.get(url).bearer_auth(&self.token).send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            bail!(
+                "downloading microsoft graph content {} failed: status code: {}, body: {}",
+                url,
+                status,
+                resp.text().await?
+            );
+        }
+
+        Ok(resp.bytes().await?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_recordings_page() {
+        let page: GraphPage<CallRecording> = serde_json::from_str(
+            r#"{
+                "@odata.nextLink": "https://graph.microsoft.com/v1.0/next",
+                "value": [{
+                    "id": "rec-1",
+                    "meetingId": "meeting-1",
+                    "createdDateTime": "2023-01-10T17:00:00Z",
+                    "endDateTime": "2023-01-10T17:45:00Z",
+                    "recordingContentUrl": "https://graph.microsoft.com/v1.0/content"
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!("https://graph.microsoft.com/v1.0/next", page.next_link);
+        assert_eq!(1, page.value.len());
+        assert_eq!("meeting-1", page.value[0].meeting_id);
+        assert!(page.value[0].end_date_time.is_some());
+    }
+
+    #[test]
+    fn test_online_meeting_attendee_emails() {
+        let meeting = OnlineMeeting {
+            id: "meeting-1".to_string(),
+            participants: MeetingParticipants {
+                organizer: Some(MeetingParticipantInfo {
+                    upn: "Jane@Example.com".to_string(),
+                }),
+                attendees: vec![
+                    MeetingParticipantInfo {
+                        upn: "bob@example.com".to_string(),
+                    },
+                    MeetingParticipantInfo {
+                        upn: "jane@example.com".to_string(),
+                    },
+                    MeetingParticipantInfo { upn: "".to_string() },
+                ],
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            vec!["bob@example.com".to_string(), "jane@example.com".to_string()],
+            meeting.attendee_emails()
+        );
+    }
+}
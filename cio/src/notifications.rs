@@ -0,0 +1,45 @@
+use crate::configs::User;
+
+/// The weekly summary of everything on someone's plate, sent on Mondays.
+pub static NOTIFICATION_WEEKLY_DIGEST: &str = "weekly-digest";
+/// The reminders about open meeting action items.
+pub static NOTIFICATION_ACTION_ITEM_REMINDERS: &str = "action-item-reminders";
+/// The reminders about the RFDs waiting on someone's review.
+pub static NOTIFICATION_RFD_REVIEW_REMINDERS: &str = "rfd-review-reminders";
+/// The tasks of the conferences we sponsor, when they are assigned to someone.
+pub static NOTIFICATION_CONFERENCE_TASKS: &str = "conference-tasks";
+
+/// The notifications we send people directly, that they can turn off with
+/// `muted_notifications` in their config.
+pub static NOTIFICATIONS: &[&str] = &[
+    NOTIFICATION_WEEKLY_DIGEST,
+    NOTIFICATION_ACTION_ITEM_REMINDERS,
+    NOTIFICATION_RFD_REVIEW_REMINDERS,
+    NOTIFICATION_CONFERENCE_TASKS,
+];
+
+impl User {
+    /// Return if the user wants a kind of notification, which they do unless they muted it.
+    pub fn wants_notification(&self, notification: &str) -> bool {
+        !self
+            .muted_notifications
+            .iter()
+            .any(|n| n.trim().eq_ignore_ascii_case(notification))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NOTIFICATION_ACTION_ITEM_REMINDERS, NOTIFICATION_WEEKLY_DIGEST};
+    use crate::configs::tests::mock_user;
+
+    #[test]
+    fn test_wants_notification() {
+        let mut user = mock_user();
+        assert!(user.wants_notification(NOTIFICATION_WEEKLY_DIGEST));
+
+        user.muted_notifications = vec!["Weekly-Digest ".to_string()];
+        assert!(!user.wants_notification(NOTIFICATION_WEEKLY_DIGEST));
+        assert!(user.wants_notification(NOTIFICATION_ACTION_ITEM_REMINDERS));
+    }
+}
@@ -1,7 +1,427 @@
-pub struct Printer;
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use cio_api_types::printer::PrinterStatus;
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    companies::Company,
+    configs::User,
+    db::Database,
+    schema::{printers, users},
+};
+
+/// The kinds of printers, they are also the path of the print job on the print server.
+pub static PRINTER_KIND_ZEBRA: &str = "zebra";
+pub static PRINTER_KIND_ROLLO: &str = "rollo";
+pub static PRINTER_KIND_RECEIPT: &str = "receipt";
+
+/// The percentage of labels or ribbon left below which we warn that a printer is running out.
+pub static PRINTER_SUPPLY_LOW_LEVEL: i32 = 15;
+
+/// A printer in one of the offices.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct Printer {
+    pub id: i32,
+    pub name: String,
+    /// The office the printer is in, like the building of the people sitting next to it.
+    pub location: String,
+    /// What it prints: `zebra` for barcode labels, `rollo` for shipping labels, `receipt` for
+    /// receipts.
+    pub kind: String,
+    /// The url of the print server the printer is connected to.
+    pub url: String,
+    /// If the printer is the default of its kind for its location.
+    pub is_default: bool,
+    pub cio_company_id: i32,
+    /// What was wrong with the printer when we last checked on it, empty if nothing was.
+    pub status_alert: String,
+    pub status_checked_at: Option<DateTime<Utc>>,
+    /// The location the printer is in, matched from `location` by the config sync.
+    pub location_id: Option<i32>,
+}
 
 impl Printer {
     pub fn key() -> String {
         std::env::var("PRINT_TOKEN").unwrap_or_else(|_| "".to_string())
     }
+
+    /// The url to send the print jobs to.
+    pub fn print_url(&self) -> String {
+        format!("{}/{}", self.url.trim_end_matches('/'), self.kind)
+    }
+
+    /// The url to get the status of the printer from, on the same print server.
+    pub fn status_url(&self) -> String {
+        format!(
+            "{}/status/{}",
+            self.url.trim_end_matches('/').trim_end_matches("/print"),
+            self.kind
+        )
+    }
+
+    /// Ask the print server how the printer is doing.
+    pub async fn get_status(&self) -> Result<PrinterStatus> {
+        let client = reqwest::Client::new();
+        let resp = client.get(self.status_url()).bearer_auth(Printer::key()).send().await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("printer status status_code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        Ok(resp.json().await?)
+    }
+}
+
+/// Return what is wrong with a printer that would keep labels from coming out, like a ribbon
+/// running out or a paper jam. Nothing means it is fine.
+pub fn printer_issues(status: &PrinterStatus) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    if status.state == "stopped" {
+        issues.push("it is stopped".to_string());
+    } else if !status.accepting_jobs {
+        issues.push("it is not accepting print jobs".to_string());
+    }
+
+    for reason in &status.reasons {
+        // The reasons end in how bad they are, the reports are informational except for the
+        // ones about supplies, which come before they run out.
+        let (what, severity) = match reason.rsplit_once('-') {
+            Some((what, severity @ ("error" | "warning" | "report"))) => (what, severity),
+            _ => (reason.as_str(), ""),
+        };
+        let is_supply = what.starts_with("media") || what.starts_with("marker") || what.starts_with("ribbon");
+        match severity {
+            "error" | "warning" => issues.push(what.replace('-', " ")),
+            "report" | "" if is_supply || what == "paused" => issues.push(what.replace('-', " ")),
+            _ => (),
+        }
+    }
+
+    for supply in &status.supplies {
+        if supply.level >= 0 && supply.level <= PRINTER_SUPPLY_LOW_LEVEL {
+            issues.push(format!("{} is at {}%", supply.name, supply.level));
+        }
+    }
+
+    issues
+}
+
+/// Check on the supplies and errors of the printers of a company, and tell the office when
+/// one needs attention. We only post when what is wrong with a printer changes, and once more
+/// when it is fixed, so a printer running low does not post every hour.
+pub async fn check_printer_supplies(db: &Database, company: &Company) -> Result<()> {
+    let printers = printers::dsl::printers
+        .filter(printers::dsl::cio_company_id.eq(company.id))
+        .order_by(printers::dsl::id)
+        .load_async::<Printer>(db.pool())
+        .await?;
+
+    for printer in printers {
+        let issues = match printer.get_status().await {
+            Ok(status) => printer_issues(&status),
+            Err(e) => {
+                warn!("getting the status of printer `{}` failed: {}", printer.name, e);
+                vec!["its print server is not answering".to_string()]
+            }
+        };
+        let alert = issues
+            .iter()
+            .map(|i| format!("• {}", i))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        if alert != printer.status_alert {
+            post_printer_alert(db, company, &printer, &alert).await?;
+            info!("printer `{}` status changed to `{}`", printer.name, alert);
+        }
+
+        diesel::update(printers::dsl::printers.filter(printers::dsl::id.eq(printer.id)))
+            .set((
+                printers::dsl::status_alert.eq(alert),
+                printers::dsl::status_checked_at.eq(Some(Utc::now())),
+            ))
+            .execute_async(db.pool())
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn post_printer_alert(db: &Database, company: &Company, printer: &Printer, alert: &str) -> Result<()> {
+    let channel = match company.alerts_channel(&company.slack_channel_office) {
+        Some(channel) => channel,
+        None => {
+            warn!(
+                "company `{}` has no office or alerts channel, not alerting about printer `{}`",
+                company.name, printer.name
+            );
+            return Ok(());
+        }
+    };
+
+    let location = if printer.location.is_empty() {
+        String::new()
+    } else {
+        format!(" in {}", printer.location)
+    };
+    let (header, text) = if alert.is_empty() {
+        (
+            format!("{} printer is back to normal", printer.name),
+            format!("The {} printer{} is ready to print again.", printer.kind, location),
+        )
+    } else {
+        (
+            format!("{} printer needs attention", printer.name),
+            format!(
+                "The {} printer{} needs someone to look at it:\n{}",
+                printer.kind, location, alert
+            ),
+        )
+    };
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: header,
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text,
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+/// Where to print something. An empty target prints on the company's default printer.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct PrintTarget {
+    /// The name of the printer to print on.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub printer: String,
+    /// The office to print in, on its default printer.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub location: String,
+}
+
+impl PrintTarget {
+    pub fn printer(name: &str) -> Self {
+        PrintTarget {
+            printer: name.trim().to_string(),
+            location: Default::default(),
+        }
+    }
+
+    pub fn location(location: &str) -> Self {
+        PrintTarget {
+            printer: Default::default(),
+            location: location.trim().to_string(),
+        }
+    }
+
+    /// Print in the office of the person asking, so it comes out next to them. People we do
+    /// not know print on the default printer.
+    pub async fn for_user(db: &Database, company: &Company, email: &str) -> Self {
+        let email = email.trim().to_lowercase();
+        if email.is_empty() {
+            return Default::default();
+        }
+
+        match users::dsl::users
+            .filter(users::dsl::cio_company_id.eq(company.id))
+            .filter(users::dsl::email.eq(email))
+            .first_async::<User>(db.pool())
+            .await
+        {
+            Ok(user) => PrintTarget::location(&user.building),
+            Err(_) => Default::default(),
+        }
+    }
+}
+
+/// Pick the printer of a kind for a target. A named printer has to exist. Otherwise we take
+/// the default printer of the location, or any printer there, and fall back to the default
+/// printer of the company.
+pub fn pick_printer<'a>(printers: &'a [Printer], kind: &str, target: &PrintTarget) -> Result<Option<&'a Printer>> {
+    let printers: Vec<&'a Printer> = printers.iter().filter(|p| p.kind == kind).collect();
+
+    if !target.printer.is_empty() {
+        return match printers.iter().find(|p| p.name.eq_ignore_ascii_case(&target.printer)) {
+            Some(printer) => Ok(Some(*printer)),
+            None => bail!("there is no {} printer named `{}`", kind, target.printer),
+        };
+    }
+
+    let at = |location: &str| -> Vec<&'a Printer> {
+        printers
+            .iter()
+            .filter(|p| p.location.trim().eq_ignore_ascii_case(location.trim()))
+            .copied()
+            .collect()
+    };
+    let default_of = |printers: &[&'a Printer]| -> Option<&'a Printer> {
+        printers
+            .iter()
+            .find(|p| p.is_default)
+            .or_else(|| printers.first())
+            .copied()
+    };
+
+    if !target.location.is_empty() {
+        if let Some(printer) = default_of(&at(&target.location)) {
+            return Ok(Some(printer));
+        }
+    }
+
+    // The company's default is the default printer without a location, or any default.
+    Ok(printers
+        .iter()
+        .find(|p| p.is_default && p.location.trim().is_empty())
+        .or_else(|| printers.iter().find(|p| p.is_default))
+        .or_else(|| printers.first())
+        .copied())
+}
+
+/// Return the url to send a print job of a kind to for a target, or None if the company has no
+/// printer for it. Companies that did not set up their printers use their print server.
+pub async fn find_print_url(
+    db: &Database,
+    company: &Company,
+    kind: &str,
+    target: &PrintTarget,
+) -> Result<Option<String>> {
+    let printers = printers::dsl::printers
+        .filter(printers::dsl::cio_company_id.eq(company.id))
+        .order_by(printers::dsl::id)
+        .load_async::<Printer>(db.pool())
+        .await?;
+
+    if printers.iter().any(|p| p.kind == kind) {
+        return Ok(pick_printer(&printers, kind, target)?.map(|p| p.print_url()));
+    }
+
+    if company.printer_url.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(format!("{}/{}", company.printer_url, kind)))
+}
+
+#[cfg(test)]
+mod tests {
+    use cio_api_types::printer::{PrinterStatus, PrinterSupply};
+
+    use super::{pick_printer, printer_issues, PrintTarget, Printer, PRINTER_KIND_ROLLO, PRINTER_KIND_ZEBRA};
+
+    fn mock_printer(id: i32, name: &str, location: &str, kind: &str, is_default: bool) -> Printer {
+        Printer {
+            id,
+            name: name.to_string(),
+            location: location.to_string(),
+            kind: kind.to_string(),
+            url: "https://print.example.com/".to_string(),
+            is_default,
+            cio_company_id: 1,
+            status_alert: String::new(),
+            status_checked_at: None,
+            location_id: None,
+        }
+    }
+
+    #[test]
+    fn test_pick_printer() {
+        let printers = vec![
+            mock_printer(1, "Oakland front desk", "Oakland", PRINTER_KIND_ZEBRA, false),
+            mock_printer(2, "Oakland lab", "Oakland", PRINTER_KIND_ZEBRA, true),
+            mock_printer(3, "Emeryville", "Emeryville", PRINTER_KIND_ZEBRA, false),
+            mock_printer(4, "Shipping", "", PRINTER_KIND_ROLLO, true),
+        ];
+        let pick = |kind: &str, target: PrintTarget| -> Option<i32> {
+            pick_printer(&printers, kind, &target).unwrap().map(|p| p.id)
+        };
+
+        assert_eq!(Some(2), pick(PRINTER_KIND_ZEBRA, PrintTarget::location("oakland")));
+        assert_eq!(Some(3), pick(PRINTER_KIND_ZEBRA, PrintTarget::location("Emeryville")));
+        assert_eq!(
+            Some(1),
+            pick(PRINTER_KIND_ZEBRA, PrintTarget::printer("oakland front desk"))
+        );
+        // Offices without a printer print on the default.
+        assert_eq!(Some(2), pick(PRINTER_KIND_ZEBRA, PrintTarget::location("Remote")));
+        assert_eq!(Some(4), pick(PRINTER_KIND_ROLLO, PrintTarget::location("Oakland")));
+        assert_eq!(None, pick("receipt", Default::default()));
+        assert!(pick_printer(&printers, PRINTER_KIND_ZEBRA, &PrintTarget::printer("Nope")).is_err());
+
+        assert_eq!("https://print.example.com/zebra", printers[0].print_url());
+    }
+
+    #[test]
+    fn test_printer_issues() {
+        let mut status = PrinterStatus {
+            state: "idle".to_string(),
+            accepting_jobs: true,
+            reasons: vec!["cups-waiting-for-job-completed-report".to_string()],
+            supplies: vec![
+                PrinterSupply {
+                    name: "Labels".to_string(),
+                    kind: "thermal".to_string(),
+                    level: 60,
+                },
+                PrinterSupply {
+                    name: "Ribbon".to_string(),
+                    kind: "ink-ribbon".to_string(),
+                    level: -1,
+                },
+            ],
+        };
+        assert!(printer_issues(&status).is_empty());
+
+        status.reasons.push("media-low-report".to_string());
+        status.reasons.push("media-jam-error".to_string());
+        status.supplies[0].level = 10;
+        assert_eq!(
+            vec![
+                "media low".to_string(),
+                "media jam".to_string(),
+                "Labels is at 10%".to_string()
+            ],
+            printer_issues(&status)
+        );
+
+        status.state = "stopped".to_string();
+        assert_eq!("it is stopped", printer_issues(&status)[0]);
+
+        let mut printer = mock_printer(1, "Front desk", "Oakland", PRINTER_KIND_ZEBRA, true);
+        printer.url = "https://print.example.com/print".to_string();
+        assert_eq!("https://print.example.com/status/zebra", printer.status_url());
+    }
 }
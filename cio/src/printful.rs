@@ -0,0 +1,530 @@
+use anyhow::{bail, Result};
+use chrono::{Duration, TimeZone, Utc};
+use log::{info, warn};
+use reqwest::{Method, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    companies::Company,
+    db::Database,
+    shipment_status::Status,
+    shipments::{clean_carrier_name, OutboundShipment, OutboundShipments},
+    swag_inventory::{SwagInventoryItem, SwagInventoryItems},
+    swag_shipments::get_swag_shipment_items,
+    swag_store::{Order, OrderItem},
+};
+
+static PRINTFUL_ENDPOINT: &str = "https://api.printful.com";
+/// How long we try to create the Printful order of a shipment before we mark it as failed and
+/// ask someone to look at it. We keep trying after that.
+static PRINTFUL_ORDER_RETRY_HOURS: i64 = 24;
+/// The provider of the outbound shipments Printful fulfills.
+pub static PRINTFUL_PROVIDER: &str = "Printful";
+
+/// A client for the Printful API, with a private token of the store we keep our swag in.
+pub struct Printful {
+    client: reqwest::Client,
+    token: String,
+}
+
+/// Printful wraps every response in the same envelope.
+#[derive(Debug, Deserialize)]
+struct PrintfulResponse<T> {
+    result: T,
+    #[serde(default)]
+    paging: Option<PrintfulPaging>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrintfulPaging {
+    total: i64,
+    offset: i64,
+    limit: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PrintfulRecipient {
+    pub name: String,
+    pub address1: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub address2: String,
+    pub city: String,
+    pub state_code: String,
+    pub country_code: String,
+    pub zip: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub phone: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub email: String,
+}
+
+/// An item of an order, from the products we keep in the Printful warehouse.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PrintfulOrderItem {
+    pub warehouse_product_variant_id: i64,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NewPrintfulOrder {
+    /// Our id of the order, Printful does not take the same one twice.
+    pub external_id: String,
+    pub recipient: PrintfulRecipient,
+    pub items: Vec<PrintfulOrderItem>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct PrintfulShipment {
+    #[serde(default)]
+    pub carrier: String,
+    #[serde(default)]
+    pub service: String,
+    #[serde(default)]
+    pub tracking_number: String,
+    #[serde(default)]
+    pub tracking_url: String,
+    /// When the package left the warehouse, as a unix timestamp.
+    #[serde(default)]
+    pub shipped_at: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct PrintfulOrder {
+    pub id: i64,
+    #[serde(default)]
+    pub external_id: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub shipments: Vec<PrintfulShipment>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct PrintfulWarehouseProduct {
+    pub id: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub variants: Vec<PrintfulWarehouseVariant>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct PrintfulWarehouseVariant {
+    pub id: i64,
+    #[serde(default)]
+    pub sku: String,
+    /// What is in stock at the warehouse.
+    #[serde(default)]
+    pub quantity: i32,
+}
+
+impl Printful {
+    pub fn new(token: &str) -> Self {
+        Printful {
+            client: reqwest::Client::new(),
+            token: token.to_string(),
+        }
+    }
+
+    async fn request<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<PrintfulResponse<T>> {
+        let mut req = self
+            .client
+            .request(
+                method,
+                &format!("{}/{}", PRINTFUL_ENDPOINT, path.trim_start_matches('/')),
+            )
+            .bearer_auth(&self.token);
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+
+        let resp = req.send().await?;
+        if resp.status() != StatusCode::OK {
+            bail!(
+                "requesting `{}` from Printful failed, status_code: {}, body: {}",
+                path,
+                resp.status(),
+                resp.text().await?
+            );
+        }
+
+        Ok(resp.json().await?)
+    }
+
+    /// Create an order, and confirm it so Printful fulfills it right away.
+    pub async fn create_order(&self, order: &NewPrintfulOrder) -> Result<PrintfulOrder> {
+        Ok(self
+            .request(Method::POST, "/orders?confirm=true", Some(order))
+            .await?
+            .result)
+    }
+
+    pub async fn get_order(&self, id: &str) -> Result<PrintfulOrder> {
+        Ok(self
+            .request::<(), _>(Method::GET, &format!("/orders/{}", id), None)
+            .await?
+            .result)
+    }
+
+    /// List the products we keep in the Printful warehouse, with their stock.
+    pub async fn list_warehouse_products(&self) -> Result<Vec<PrintfulWarehouseProduct>> {
+        let mut products: Vec<PrintfulWarehouseProduct> = Vec::new();
+        let mut offset = 0;
+        loop {
+            let resp = self
+                .request::<(), Vec<PrintfulWarehouseProduct>>(
+                    Method::GET,
+                    &format!("/warehouse/products?limit=100&offset={}", offset),
+                    None,
+                )
+                .await?;
+            let count = resp.result.len() as i64;
+            products.extend(resp.result);
+
+            match resp.paging {
+                Some(paging) if count > 0 && paging.offset + paging.limit < paging.total => {
+                    offset = paging.offset + paging.limit
+                }
+                _ => break,
+            }
+        }
+
+        // The list does not have the variants, so get them for each product.
+        let mut detailed = Vec::new();
+        for product in products {
+            let resp = self
+                .request::<(), PrintfulWarehouseProduct>(
+                    Method::GET,
+                    &format!("/warehouse/products/{}", product.id),
+                    None,
+                )
+                .await?;
+            detailed.push(resp.result);
+        }
+
+        Ok(detailed)
+    }
+}
+
+/// Return the status of a shipment from the status of its Printful order.
+pub fn printful_order_status(status: &str) -> Status {
+    match status {
+        "draft" | "pending" => Status::Queued,
+        "inprocess" => Status::Processing,
+        "onhold" => Status::OnHold,
+        "partial" => Status::PartiallyFulfilled,
+        "fulfilled" => Status::Shipped,
+        "canceled" => Status::Cancelled,
+        "failed" => Status::Failure,
+        _ => Status::None,
+    }
+}
+
+/// Get the Printful items of an order, if we can fulfill it with Printful. We only send orders
+/// to Printful when the company set it up and every item of the order is stocked there.
+pub async fn printful_items_for_order(
+    db: &Database,
+    company: &Company,
+    order: &Order,
+) -> Result<Option<Vec<PrintfulOrderItem>>> {
+    printful_items(db, company, &order.items).await
+}
+
+async fn printful_items(
+    db: &Database,
+    company: &Company,
+    order_items: &[OrderItem],
+) -> Result<Option<Vec<PrintfulOrderItem>>> {
+    if company.printful_api_key.is_empty() {
+        return Ok(None);
+    }
+
+    let mut items = Vec::new();
+    for item in order_items {
+        let swag_inventory_item = SwagInventoryItem::get_by_id_for_company(db, company.id, item.id).await?;
+        match swag_inventory_item.printful_variant_id.trim().parse() {
+            Ok(id) => items.push(PrintfulOrderItem {
+                warehouse_product_variant_id: id,
+                quantity: item.quantity,
+            }),
+            Err(_) => return Ok(None),
+        }
+    }
+
+    Ok(Some(items))
+}
+
+fn printful_external_id(shipment: &OutboundShipment) -> String {
+    format!("cio-{}", shipment.id)
+}
+
+/// Have Printful fulfill a shipment, instead of printing a label with Shippo.
+pub async fn fulfill_with_printful(
+    company: &Company,
+    shipment: &mut OutboundShipment,
+    items: Vec<PrintfulOrderItem>,
+) -> Result<()> {
+    let printful = company.authenticate_printful()?;
+
+    let order = NewPrintfulOrder {
+        external_id: printful_external_id(shipment),
+        recipient: PrintfulRecipient {
+            name: shipment.name.to_string(),
+            address1: shipment.street_1.to_string(),
+            address2: shipment.street_2.to_string(),
+            city: shipment.city.to_string(),
+            state_code: shipment.state.to_string(),
+            country_code: if shipment.country.is_empty() {
+                "US".to_string()
+            } else {
+                shipment.country.to_string()
+            },
+            zip: shipment.zipcode.to_string(),
+            phone: shipment.phone.to_string(),
+            email: shipment.email.to_string(),
+        },
+        items,
+    };
+    let order = printful.create_order(&order).await?;
+    info!("created Printful order `{}` for shipment `{}`", order.id, shipment.id);
+
+    shipment.provider = PRINTFUL_PROVIDER.to_string();
+    shipment.provider_id = order.id.to_string();
+    apply_printful_order(shipment, &order);
+
+    Ok(())
+}
+
+/// Set the status and tracking of a shipment from its Printful order.
+fn apply_printful_order(shipment: &mut OutboundShipment, order: &PrintfulOrder) {
+    // Printful does not tell us when a package was delivered, the tracking does.
+    if shipment.status != Status::Delivered.to_string() && shipment.status != Status::Returned.to_string() {
+        let status = printful_order_status(&order.status);
+        if status != Status::None {
+            shipment.status = status.to_string();
+        }
+    }
+
+    let printful_shipment = match order.shipments.iter().find(|s| !s.tracking_number.is_empty()) {
+        Some(s) => s,
+        None => return,
+    };
+    shipment.carrier = clean_carrier_name(&printful_shipment.carrier);
+    shipment.tracking_number = printful_shipment.tracking_number.to_string();
+    shipment.tracking_link = printful_shipment.tracking_url.to_string();
+    shipment.service_level = printful_shipment.service.to_lowercase().replace(' ', "_");
+    if shipment.shipped_time.is_none() && printful_shipment.shipped_at > 0 {
+        shipment.shipped_time = Some(Utc.timestamp(printful_shipment.shipped_at, 0));
+    }
+}
+
+/// Create the Printful order of a shipment we could not create it for before. Printful takes
+/// every external id once, so if the order was created and we never heard back, we link it.
+async fn retry_printful_order(
+    db: &Database,
+    company: &Company,
+    printful: &Printful,
+    shipment: &mut OutboundShipment,
+) -> Result<()> {
+    if let Ok(order) = printful
+        .get_order(&format!("@{}", printful_external_id(shipment)))
+        .await
+    {
+        shipment.provider_id = order.id.to_string();
+        apply_printful_order(shipment, &order);
+        shipment.update(db).await?;
+        return Ok(());
+    }
+
+    let items = match printful_items(db, company, &get_swag_shipment_items(db, shipment).await?).await? {
+        Some(items) if !items.is_empty() => items,
+        _ => bail!("shipment `{}` has items Printful does not stock", shipment.id),
+    };
+    fulfill_with_printful(company, shipment, items).await?;
+    shipment.update(db).await?;
+
+    Ok(())
+}
+
+/// Mark a shipment whose Printful order we could not create for a while as failed, and let the
+/// shipments channel know so someone looks at it.
+async fn flag_failed_printful_order(
+    db: &Database,
+    company: &Company,
+    shipment: &mut OutboundShipment,
+    error: &anyhow::Error,
+) -> Result<()> {
+    if shipment.status == Status::Failure.to_string()
+        || Utc::now() - shipment.created_time < Duration::hours(PRINTFUL_ORDER_RETRY_HOURS)
+    {
+        return Ok(());
+    }
+
+    shipment.status = Status::Failure.to_string();
+    shipment.update(db).await?;
+
+    let msg = FormattedMessage {
+        channel: company.slack_channel_shipments.to_string(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "We could not create the Printful order of the shipment to *{}* for {} hours, someone \
+                     should look at it. We keep trying in the meantime. The last error was: `{}`",
+                    shipment.name, PRINTFUL_ORDER_RETRY_HOURS, error
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+        attachments: Default::default(),
+    };
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+/// Sync the status and tracking numbers of the shipments Printful fulfills, and create the
+/// orders we could not create before.
+pub async fn refresh_printful_shipments(db: &Database, company: &Company) -> Result<()> {
+    let printful = match company.authenticate_printful() {
+        Ok(printful) => printful,
+        // Return early, the company does not use Printful.
+        Err(_) => return Ok(()),
+    };
+
+    for mut shipment in OutboundShipments::get_from_db(db, company.id).await? {
+        if shipment.provider != PRINTFUL_PROVIDER || shipment.is_done() {
+            continue;
+        }
+
+        if shipment.provider_id.is_empty() {
+            if let Err(e) = retry_printful_order(db, company, &printful, &mut shipment).await {
+                warn!(
+                    "creating the Printful order of shipment `{}` failed again: {}",
+                    shipment.id, e
+                );
+                flag_failed_printful_order(db, company, &mut shipment, &e).await?;
+            }
+            continue;
+        }
+
+        let order = match printful.get_order(&shipment.provider_id).await {
+            Ok(order) => order,
+            Err(e) => {
+                warn!("getting Printful order `{}` failed: {}", shipment.provider_id, e);
+                continue;
+            }
+        };
+
+        let before = shipment.clone();
+        apply_printful_order(&mut shipment, &order);
+        if shipment != before {
+            shipment.update(db).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Set the stock of the swag inventory items Printful keeps to what is in their warehouse.
+pub async fn reconcile_printful_stock(db: &Database, company: &Company) -> Result<()> {
+    let printful = match company.authenticate_printful() {
+        Ok(printful) => printful,
+        // Return early, the company does not use Printful.
+        Err(_) => return Ok(()),
+    };
+
+    let variants: Vec<PrintfulWarehouseVariant> = printful
+        .list_warehouse_products()
+        .await?
+        .into_iter()
+        .flat_map(|p| p.variants)
+        .collect();
+
+    for mut item in SwagInventoryItems::get_from_db(db, company.id).await? {
+        if item.printful_variant_id.trim().is_empty() {
+            continue;
+        }
+
+        let variant = match variants
+            .iter()
+            .find(|v| v.id.to_string() == item.printful_variant_id.trim())
+        {
+            Some(variant) => variant,
+            None => {
+                warn!(
+                    "swag inventory item `{}` has Printful variant `{}`, but it is not in the warehouse",
+                    item.name, item.printful_variant_id
+                );
+                continue;
+            }
+        };
+
+        if variant.quantity != item.current_stock {
+            info!(
+                "Printful has `{}` of `{}`, we thought `{}`",
+                variant.quantity, item.name, item.current_stock
+            );
+            // This will also set the value.
+            item.send_slack_notification_if_inventory_changed(db, company, variant.quantity)
+                .await?;
+            item.update(db).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_printful_order, printful_order_status, PrintfulOrder, PrintfulShipment};
+    use crate::tests::mock_record;
+    use crate::{shipment_status::Status, shipments::OutboundShipment};
+
+    #[test]
+    fn test_printful_order_status() {
+        assert_eq!(Status::Queued, printful_order_status("pending"));
+        assert_eq!(Status::Shipped, printful_order_status("fulfilled"));
+        assert_eq!(Status::Cancelled, printful_order_status("canceled"));
+        assert_eq!(Status::None, printful_order_status("whatever"));
+    }
+
+    #[test]
+    fn test_apply_printful_order() {
+        let mut shipment: OutboundShipment = mock_record(json!({"created_time": "2023-10-02T00:00:00Z"}));
+        shipment.status = Status::Queued.to_string();
+
+        let mut order = PrintfulOrder {
+            id: 42,
+            status: "inprocess".to_string(),
+            ..Default::default()
+        };
+        apply_printful_order(&mut shipment, &order);
+        assert_eq!("Processing", shipment.status);
+        assert!(shipment.tracking_number.is_empty());
+
+        order.status = "fulfilled".to_string();
+        order.shipments.push(PrintfulShipment {
+            carrier: "USPS".to_string(),
+            service: "USPS Priority Mail".to_string(),
+            tracking_number: "9400".to_string(),
+            tracking_url: "https://tools.usps.com/?tLabels=9400".to_string(),
+            shipped_at: 1696348800,
+        });
+        apply_printful_order(&mut shipment, &order);
+        assert_eq!("Shipped", shipment.status);
+        assert_eq!("9400", shipment.tracking_number);
+        assert_eq!("usps_priority_mail", shipment.service_level);
+        assert_eq!("2023-10-03T16:00:00+00:00", shipment.shipped_time.unwrap().to_rfc3339());
+    }
+}
@@ -0,0 +1,316 @@
+use anyhow::{bail, Result};
+use log::info;
+use schemars::JsonSchema;
+use sendgrid_api::{traits::MailOps, Client as SendGrid};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    companies::Company,
+    configs::{User, UserConfig},
+    db::Database,
+};
+
+/// The longest value we accept for any profile field.
+static MAX_PROFILE_FIELD_LENGTH: usize = 200;
+
+/// The parts of a user's profile they can update themselves from the self-service
+/// profile page.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct UserProfile {
+    #[serde(default)]
+    pub home_address_street_1: String,
+    #[serde(default)]
+    pub home_address_street_2: String,
+    #[serde(default)]
+    pub home_address_city: String,
+    #[serde(default)]
+    pub home_address_state: String,
+    #[serde(default)]
+    pub home_address_zipcode: String,
+    #[serde(default)]
+    pub home_address_country: String,
+    #[serde(default)]
+    pub phone: String,
+    #[serde(default)]
+    pub pronouns: String,
+    #[serde(default)]
+    pub emergency_contact_name: String,
+    #[serde(default)]
+    pub emergency_contact_phone: String,
+    #[serde(default)]
+    pub emergency_contact_relationship: String,
+}
+
+impl From<&User> for UserProfile {
+    fn from(user: &User) -> Self {
+        UserProfile {
+            home_address_street_1: user.home_address_street_1.to_string(),
+            home_address_street_2: user.home_address_street_2.to_string(),
+            home_address_city: user.home_address_city.to_string(),
+            home_address_state: user.home_address_state.to_string(),
+            home_address_zipcode: user.home_address_zipcode.to_string(),
+            home_address_country: user.home_address_country.to_string(),
+            phone: user.phone.to_string(),
+            pronouns: user.pronouns.to_string(),
+            emergency_contact_name: user.emergency_contact_name.to_string(),
+            emergency_contact_phone: user.emergency_contact_phone.to_string(),
+            emergency_contact_relationship: user.emergency_contact_relationship.to_string(),
+        }
+    }
+}
+
+/// A change to a field of a user's profile.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ProfileChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+fn is_valid_phone(phone: &str) -> bool {
+    let digits = phone.chars().filter(|c| c.is_ascii_digit()).count();
+    (7..=15).contains(&digits)
+        && phone
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '(' | ')' | '.' | ' '))
+}
+
+impl UserProfile {
+    fn fields(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("home address street 1", &self.home_address_street_1),
+            ("home address street 2", &self.home_address_street_2),
+            ("home address city", &self.home_address_city),
+            ("home address state", &self.home_address_state),
+            ("home address zipcode", &self.home_address_zipcode),
+            ("home address country", &self.home_address_country),
+            ("phone", &self.phone),
+            ("pronouns", &self.pronouns),
+            ("emergency contact name", &self.emergency_contact_name),
+            ("emergency contact phone", &self.emergency_contact_phone),
+            ("emergency contact relationship", &self.emergency_contact_relationship),
+        ]
+    }
+
+    /// Trim the whitespace around all the fields.
+    pub fn normalize(&mut self) {
+        for field in [
+            &mut self.home_address_street_1,
+            &mut self.home_address_street_2,
+            &mut self.home_address_city,
+            &mut self.home_address_state,
+            &mut self.home_address_zipcode,
+            &mut self.home_address_country,
+            &mut self.phone,
+            &mut self.pronouns,
+            &mut self.emergency_contact_name,
+            &mut self.emergency_contact_phone,
+            &mut self.emergency_contact_relationship,
+        ] {
+            *field = field.trim().to_string();
+        }
+    }
+
+    /// Validate the profile before we save it.
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in self.fields() {
+            if value.len() > MAX_PROFILE_FIELD_LENGTH {
+                bail!("{} must be at most {} characters", name, MAX_PROFILE_FIELD_LENGTH);
+            }
+        }
+
+        let has_address = !self.home_address_street_1.is_empty()
+            || !self.home_address_street_2.is_empty()
+            || !self.home_address_city.is_empty()
+            || !self.home_address_state.is_empty()
+            || !self.home_address_zipcode.is_empty();
+        if has_address {
+            if self.home_address_street_1.is_empty()
+                || self.home_address_city.is_empty()
+                || self.home_address_zipcode.is_empty()
+            {
+                bail!("home address must have a street, city, and zipcode");
+            }
+
+            let us = self.home_address_country.is_empty() || self.home_address_country == "United States";
+            if us && self.home_address_state.is_empty() {
+                bail!("home address in the United States must have a state");
+            }
+        }
+
+        if !self.phone.is_empty() && !is_valid_phone(&self.phone) {
+            bail!("phone `{}` is not a valid phone number", self.phone);
+        }
+
+        if !self.emergency_contact_name.is_empty() && self.emergency_contact_phone.is_empty() {
+            bail!("emergency contact must have a phone number");
+        }
+        if !self.emergency_contact_phone.is_empty() && !is_valid_phone(&self.emergency_contact_phone) {
+            bail!(
+                "emergency contact phone `{}` is not a valid phone number",
+                self.emergency_contact_phone
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Return the fields that changed from the old profile to this one.
+    pub fn changes_from(&self, old: &UserProfile) -> Vec<ProfileChange> {
+        old.fields()
+            .into_iter()
+            .zip(self.fields())
+            .filter(|((_, o), (_, n))| o != n)
+            .map(|((field, o), (_, n))| ProfileChange {
+                field: field.to_string(),
+                old: o.to_string(),
+                new: n.to_string(),
+            })
+            .collect()
+    }
+}
+
+impl User {
+    /// Update the user's profile from the self-service profile page, and let HR know
+    /// what changed.
+    pub async fn update_profile(
+        &self,
+        db: &Database,
+        company: &Company,
+        mut profile: UserProfile,
+    ) -> Result<(User, Vec<ProfileChange>)> {
+        profile.normalize();
+        profile.validate()?;
+
+        let changes = profile.changes_from(&UserProfile::from(self));
+        if changes.is_empty() {
+            return Ok((self.clone(), changes));
+        }
+
+        let mut config: UserConfig = self.clone().into();
+        config.home_address_street_1 = profile.home_address_street_1;
+        config.home_address_street_2 = profile.home_address_street_2;
+        config.home_address_city = profile.home_address_city;
+        config.home_address_state = profile.home_address_state;
+        config.home_address_zipcode = profile.home_address_zipcode;
+        config.home_address_country = profile.home_address_country;
+        config.phone = profile.phone;
+        config.pronouns = profile.pronouns;
+        config.emergency_contact_name = profile.emergency_contact_name;
+        config.emergency_contact_phone = profile.emergency_contact_phone;
+        config.emergency_contact_relationship = profile.emergency_contact_relationship;
+        config.populate_home_address().await?;
+
+        let user = config.upsert(db).await?;
+        info!(
+            "user `{}` updated their profile: {}",
+            user.username,
+            changes
+                .iter()
+                .map(|c| c.field.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        );
+
+        user.send_profile_change_notification(company, &changes).await?;
+
+        Ok((user, changes))
+    }
+
+    /// Email HR the changes a user made to their profile.
+    async fn send_profile_change_notification(&self, company: &Company, changes: &[ProfileChange]) -> Result<()> {
+        let changes = changes
+            .iter()
+            .map(|c| format!("- {}: `{}` -> `{}`", c.field, c.old, c.new))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let sendgrid_client = SendGrid::new_from_env();
+        sendgrid_client
+            .mail_send()
+            .send_plain_text(
+                &format!("{} updated their profile", self.full_name()),
+                &format!(
+                    "{} ({}) updated their profile:
+
+{}
+
+If any of this needs to be changed in payroll or benefits, make sure to update
+it there as well.
+
+xoxo,
+  The Onboarding Bot",
+                    self.full_name(),
+                    self.email,
+                    changes
+                ),
+                &[format!("hr@{}", company.gsuite_domain)],
+                &[],
+                &[],
+                &format!("admin@{}", company.gsuite_domain),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_profile() -> UserProfile {
+        UserProfile {
+            home_address_street_1: "1 Main St".to_string(),
+            home_address_city: "Oakland".to_string(),
+            home_address_state: "CA".to_string(),
+            home_address_zipcode: "94607".to_string(),
+            phone: "+1 (555) 555-5555".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_profile() {
+        assert!(mock_profile().validate().is_ok());
+        assert!(UserProfile::default().validate().is_ok());
+
+        let mut profile = mock_profile();
+        profile.home_address_city = String::new();
+        assert!(profile.validate().is_err());
+
+        let mut profile = mock_profile();
+        profile.home_address_state = String::new();
+        assert!(profile.validate().is_err());
+        profile.home_address_country = "Canada".to_string();
+        assert!(profile.validate().is_ok());
+
+        let mut profile = mock_profile();
+        profile.phone = "call me".to_string();
+        assert!(profile.validate().is_err());
+
+        let mut profile = mock_profile();
+        profile.emergency_contact_name = "Sam".to_string();
+        assert!(profile.validate().is_err());
+        profile.emergency_contact_phone = "555-555-1234".to_string();
+        assert!(profile.validate().is_ok());
+    }
+
+    #[test]
+    fn test_profile_changes() {
+        let old = mock_profile();
+        let mut new = mock_profile();
+        new.pronouns = " they/them ".to_string();
+        new.normalize();
+
+        assert_eq!(
+            vec![ProfileChange {
+                field: "pronouns".to_string(),
+                old: "".to_string(),
+                new: "they/them".to_string(),
+            }],
+            new.changes_from(&old)
+        );
+        assert!(old.changes_from(&old).is_empty());
+    }
+}
@@ -10,6 +10,7 @@ use crate::{
     configs::{ExternalServices, Group, User},
     db::Database,
     octorust_utils::{into_octorust_error, OctorustErrorKind},
+    rate_limit::{acquire, Provider},
 };
 
 /// This trait defines how to implement a provider for a vendor that manages users
@@ -540,6 +541,7 @@ impl ProviderReadOps for octorust::Client {
     }
 }
 
+// Each GSuite request takes its turn in the Google budget all our jobs share.
 #[async_trait]
 impl ProviderWriteOps for gsuite_api::Client {
     async fn ensure_user(&self, db: &Database, company: &Company, user: &User, config: &AppConfig) -> Result<String> {
@@ -554,6 +556,7 @@ impl ProviderWriteOps for gsuite_api::Client {
         }
 
         // First get the user from gsuite.
+        acquire(Provider::Google).await;
         match self
             .users()
             .get(
@@ -567,6 +570,7 @@ impl ProviderWriteOps for gsuite_api::Client {
                 // Update the user with the settings from the config for the user.
                 let gsuite_user = crate::gsuite::update_gsuite_user(&u, user, false, company).await;
 
+                acquire(Provider::Google).await;
                 match self.users().update(&gsuite_user.id, &gsuite_user).await {
                     Ok(_) => {}
                     Err(e) => {
@@ -574,7 +578,7 @@ impl ProviderWriteOps for gsuite_api::Client {
                     }
                 };
 
-                crate::gsuite::update_user_aliases(self, &gsuite_user, user.aliases.clone(), company).await?;
+                crate::gsuite::update_user_aliases(db, self, &gsuite_user, user.aliases.clone(), company).await?;
 
                 // Add the user to their teams and groups.
                 crate::gsuite::update_user_google_groups(self, user, company).await?;
@@ -600,6 +604,7 @@ impl ProviderWriteOps for gsuite_api::Client {
         // Make sure it is set to true.
         let gsuite_user = crate::gsuite::update_gsuite_user(&u, user, true, company).await;
 
+        acquire(Provider::Google).await;
         let new_gsuite_user = self.users().insert(&gsuite_user).await?;
 
         // Send an email to the new user.
@@ -607,7 +612,7 @@ impl ProviderWriteOps for gsuite_api::Client {
         user.send_email_new_gsuite_user(db, &gsuite_user.password, &config.onboarding)
             .await?;
 
-        crate::gsuite::update_user_aliases(self, &gsuite_user, user.aliases.clone(), company).await?;
+        crate::gsuite::update_user_aliases(db, self, &gsuite_user, user.aliases.clone(), company).await?;
 
         crate::gsuite::update_user_google_groups(self, user, company).await?;
 
@@ -617,6 +622,8 @@ impl ProviderWriteOps for gsuite_api::Client {
     }
 
     async fn ensure_group(&self, db: &Database, company: &Company, group: &Group) -> Result<()> {
+        acquire(Provider::Google).await;
+
         match self
             .groups()
             .get(&format!("{}@{}", &group.name, &company.gsuite_domain))
@@ -624,6 +631,7 @@ impl ProviderWriteOps for gsuite_api::Client {
         {
             Ok(mut google_group) => {
                 google_group.description = group.description.to_string();
+                let current_aliases = google_group.aliases.clone();
 
                 // Write the group aliases.
                 let mut aliases: Vec<String> = Default::default();
@@ -632,11 +640,12 @@ impl ProviderWriteOps for gsuite_api::Client {
                 }
                 google_group.aliases = aliases;
 
+                acquire(Provider::Google).await;
                 self.groups()
                     .update(&format!("{}@{}", group.name, company.gsuite_domain), &google_group)
                     .await?;
 
-                crate::gsuite::update_group_aliases(self, &google_group).await?;
+                crate::gsuite::update_group_aliases(db, self, &google_group, &current_aliases, company).await?;
 
                 // Update the groups settings.
                 crate::gsuite::update_google_group_settings(db, group, company).await?;
@@ -670,9 +679,10 @@ impl ProviderWriteOps for gsuite_api::Client {
         }
         g.aliases = aliases;
 
+        acquire(Provider::Google).await;
         let new_group = self.groups().insert(&g).await?;
 
-        crate::gsuite::update_group_aliases(self, &new_group).await?;
+        crate::gsuite::update_group_aliases(db, self, &new_group, &[], company).await?;
 
         // Update the groups settings.
         crate::gsuite::update_google_group_settings(db, group, company).await?;
@@ -683,6 +693,8 @@ impl ProviderWriteOps for gsuite_api::Client {
     }
 
     async fn check_user_is_member_of_group(&self, company: &Company, user: &User, group: &str) -> Result<bool> {
+        acquire(Provider::Google).await;
+
         let role = if user.is_group_admin {
             "OWNER".to_string()
         } else {
@@ -730,6 +742,7 @@ impl ProviderWriteOps for gsuite_api::Client {
         let is_member = self.check_user_is_member_of_group(company, user, group).await?;
         if !is_member {
             // Create the member of the group.
+            acquire(Provider::Google).await;
             if let Err(e) = self
                 .members()
                 .insert(
@@ -750,6 +763,7 @@ impl ProviderWriteOps for gsuite_api::Client {
                 if e.to_string().contains("Member already exists") {
                     // We can ignore this error.
                     // Update their role instead.
+                    acquire(Provider::Google).await;
                     self.members()
                         .update(
                             &format!("{}@{}", group, company.gsuite_domain),
@@ -787,6 +801,8 @@ impl ProviderWriteOps for gsuite_api::Client {
     }
 
     async fn remove_user_from_group(&self, company: &Company, user: &User, group: &str) -> Result<()> {
+        acquire(Provider::Google).await;
+
         self.members()
             .delete(&format!("{}@{}", group, company.gsuite_domain), &user.email)
             .await?;
@@ -796,6 +812,8 @@ impl ProviderWriteOps for gsuite_api::Client {
     }
 
     async fn delete_user(&self, _db: &Database, _company: &Company, user: &User) -> Result<()> {
+        acquire(Provider::Google).await;
+
         // First get the user from gsuite.
         let mut gsuite_user = self
             .users()
@@ -811,6 +829,7 @@ impl ProviderWriteOps for gsuite_api::Client {
         gsuite_user.suspension_reason = "No longer in config file.".to_string();
 
         // Update the user.
+        acquire(Provider::Google).await;
         self.users().update(&user.email, &gsuite_user).await?;
 
         info!("suspended user `{}` from gsuite", user.email);
@@ -819,6 +838,8 @@ impl ProviderWriteOps for gsuite_api::Client {
     }
 
     async fn delete_group(&self, company: &Company, group: &Group) -> Result<()> {
+        acquire(Provider::Google).await;
+
         self.groups()
             .delete(&format!("{}@{}", &group.name, &company.gsuite_domain))
             .await?;
@@ -835,6 +856,8 @@ impl ProviderReadOps for gsuite_api::Client {
     type ProviderGroup = gsuite_api::types::Group;
 
     async fn list_provider_users(&self, company: &Company) -> Result<Vec<gsuite_api::types::User>> {
+        acquire(Provider::Google).await;
+
         self.users()
             .list_all(
                 &company.gsuite_account_id,
@@ -851,6 +874,8 @@ impl ProviderReadOps for gsuite_api::Client {
     }
 
     async fn list_provider_groups(&self, company: &Company) -> Result<Vec<gsuite_api::types::Group>> {
+        acquire(Provider::Google).await;
+
         self.groups()
             .list_all(
                 &company.gsuite_account_id,
@@ -0,0 +1,313 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::sql_types::{BigInt, Integer, Text};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+
+use crate::db::Database;
+
+/// The providers whose quotas all our jobs share. Jobs run concurrently, in the same process
+/// and in other replicas, so each of them staying under the quota is not enough, they have to
+/// share a budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Airtable,
+    Google,
+}
+
+impl Provider {
+    fn all() -> [Provider; 2] {
+        [Provider::Airtable, Provider::Google]
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Provider::Airtable => "airtable",
+            Provider::Google => "google",
+        }
+    }
+
+    /// The requests per second we allow by default. Airtable allows 5 requests per second per
+    /// base, and the Admin SDK a bit more than 20 per second per user.
+    fn default_rate(&self) -> f64 {
+        match self {
+            Provider::Airtable => 5.0,
+            Provider::Google => 20.0,
+        }
+    }
+
+    /// The requests per second we allow, which can be set with `RATE_LIMIT_AIRTABLE` or
+    /// `RATE_LIMIT_GOOGLE`.
+    fn rate(&self) -> f64 {
+        let var = format!("RATE_LIMIT_{:?}", self).to_uppercase();
+        match std::env::var(var).ok().and_then(|r| r.trim().parse::<f64>().ok()) {
+            Some(rate) if rate > 0.0 => rate,
+            _ => self.default_rate(),
+        }
+    }
+
+    /// The requests all our processes can send in the same second.
+    fn requests_per_window(&self) -> i32 {
+        self.rate().ceil().max(1.0) as i32
+    }
+}
+
+/// A token bucket. Requests take a token each, and the bucket refills at the rate up to a
+/// second worth of tokens. Tokens can be taken ahead of time, the request then waits for its
+/// turn, which keeps requests in the order they asked.
+#[derive(Debug, Clone)]
+struct Bucket {
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    fn new(rate: f64, now: Instant) -> Self {
+        Bucket {
+            rate,
+            tokens: rate,
+            last: now,
+        }
+    }
+
+    /// Take a token, and return how long to wait before sending the request.
+    fn take(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+/// How much a provider's budget throttled us since we started.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RateLimitStats {
+    /// The requests per second we allow.
+    pub rate: f64,
+    pub requests: u64,
+    /// The requests that had to wait.
+    pub throttled: u64,
+    /// How long the requests waited in total.
+    pub throttled_seconds: f64,
+}
+
+struct RateLimiter {
+    bucket: Mutex<Bucket>,
+    stats: Mutex<RateLimitStats>,
+}
+
+static RATE_LIMITERS: OnceCell<BTreeMap<Provider, RateLimiter>> = OnceCell::const_new();
+
+async fn rate_limiters() -> &'static BTreeMap<Provider, RateLimiter> {
+    RATE_LIMITERS
+        .get_or_init(|| async {
+            let now = Instant::now();
+            Provider::all()
+                .iter()
+                .map(|&provider| {
+                    let rate = provider.rate();
+                    let limiter = RateLimiter {
+                        bucket: Mutex::new(Bucket::new(rate, now)),
+                        stats: Mutex::new(RateLimitStats {
+                            rate,
+                            ..Default::default()
+                        }),
+                    };
+                    (provider, limiter)
+                })
+                .collect()
+        })
+        .await
+}
+
+// Claim a request in the window of the provider, unless the window is full. The row is only
+// updated when there is room left, so two processes can never both take the last request.
+const CLAIM_RATE_LIMIT_WINDOW_QUERY: &str = r#"
+INSERT INTO rate_limit_windows (provider, window_start, requests) VALUES ($1, $2, 1)
+ON CONFLICT (provider, window_start) DO UPDATE SET requests = rate_limit_windows.requests + 1
+    WHERE rate_limit_windows.requests < $3
+RETURNING requests
+"#;
+
+#[derive(QueryableByName)]
+struct ClaimedWindow {
+    #[diesel(sql_type = Integer)]
+    requests: i32,
+}
+
+static SHARED_BUDGET: OnceCell<Option<Database>> = OnceCell::const_new();
+
+/// The database the processes share their budget through. Without one, like in tests, each
+/// process only keeps to its own budget.
+async fn shared_budget() -> Option<&'static Database> {
+    SHARED_BUDGET
+        .get_or_init(|| async {
+            if env::var("CIO_DATABASE_URL").is_ok() {
+                Some(Database::new().await)
+            } else {
+                None
+            }
+        })
+        .await
+        .as_ref()
+}
+
+/// Claim a request in the second starting at `window_start`. Returns false when all our
+/// processes already sent as many requests as the provider allows in that second.
+async fn claim_window(db: &Database, provider: Provider, window_start: i64) -> Result<bool> {
+    let claimed = diesel::sql_query(CLAIM_RATE_LIMIT_WINDOW_QUERY)
+        .bind::<Text, _>(provider.as_str().to_string())
+        .bind::<BigInt, _>(window_start)
+        .bind::<Integer, _>(provider.requests_per_window())
+        .load_async::<ClaimedWindow>(db.pool())
+        .await?;
+
+    match claimed.first() {
+        Some(claimed) => {
+            // The first request of a window cleans up the windows that are long gone.
+            if claimed.requests == 1 {
+                diesel::sql_query("DELETE FROM rate_limit_windows WHERE provider = $1 AND window_start < $2")
+                    .bind::<Text, _>(provider.as_str().to_string())
+                    .bind::<BigInt, _>(window_start - 60)
+                    .execute_async(db.pool())
+                    .await?;
+            }
+
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Wait until a request to a provider fits in the budget all our jobs share.
+pub async fn acquire(provider: Provider) {
+    let limiter = &rate_limiters().await[&provider];
+
+    // Keep to the budget in the process first, so our own jobs do not all hit the database
+    // at once.
+    let mut wait = limiter.bucket.lock().unwrap().take(Instant::now());
+    if wait > Duration::from_secs(1) {
+        info!(
+            "throttling a {:?} request for {:?} to stay under our quota",
+            provider, wait
+        );
+    }
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+
+    // Then wait for a second all our processes have room left in.
+    if let Some(db) = shared_budget().await {
+        loop {
+            let now = Utc::now();
+            match claim_window(db, provider, now.timestamp()).await {
+                Ok(true) => break,
+                Ok(false) => {
+                    let next_window = Duration::from_millis(1000 - u64::from(now.timestamp_subsec_millis().min(999)));
+                    wait += next_window;
+                    tokio::time::sleep(next_window).await;
+                }
+                Err(e) => {
+                    // We would rather go over the quota than stop talking to the provider.
+                    warn!(
+                        "claiming a {:?} request in the shared budget failed, only keeping to our own: {}",
+                        provider, e
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut stats = limiter.stats.lock().unwrap();
+    stats.requests += 1;
+    if !wait.is_zero() {
+        stats.throttled += 1;
+        stats.throttled_seconds += wait.as_secs_f64();
+    }
+}
+
+/// A middleware making every request a client sends, retries included, take its turn in the
+/// budget of the provider.
+pub struct RateLimitMiddleware(pub Provider);
+
+#[async_trait]
+impl reqwest_middleware::Middleware for RateLimitMiddleware {
+    async fn handle(
+        &self,
+        req: reqwest::Request,
+        extensions: &mut task_local_extensions::Extensions,
+        next: reqwest_middleware::Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        acquire(self.0).await;
+        next.run(req, extensions).await
+    }
+}
+
+/// Return an HTTP client whose requests all take their turn in the budget of the provider.
+pub fn rate_limited_client(provider: Provider) -> Result<reqwest_middleware::ClientWithMiddleware> {
+    let http = reqwest::Client::builder().build()?;
+    let retry_policy = reqwest_retry::policies::ExponentialBackoff::builder().build_with_max_retries(3);
+
+    Ok(reqwest_middleware::ClientBuilder::new(http)
+        // Trace HTTP requests. See the tracing crate to make use of these traces.
+        .with(reqwest_tracing::TracingMiddleware)
+        // Retry failed requests.
+        .with(reqwest_retry::RetryTransientMiddleware::new_with_policy(retry_policy))
+        // Every attempt counts against the quota, so this goes after the retries.
+        .with(RateLimitMiddleware(provider))
+        .build())
+}
+
+/// Return how much each provider's budget throttled us.
+pub async fn rate_limit_stats() -> BTreeMap<Provider, RateLimitStats> {
+    rate_limiters()
+        .await
+        .iter()
+        .map(|(provider, limiter)| (*provider, limiter.stats.lock().unwrap().clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::Bucket;
+
+    #[test]
+    fn test_bucket() {
+        let start = Instant::now();
+        let mut bucket = Bucket::new(2.0, start);
+
+        // A second worth of requests go right away, the next ones wait for their turn.
+        assert_eq!(Duration::ZERO, bucket.take(start));
+        assert_eq!(Duration::ZERO, bucket.take(start));
+        assert_eq!(Duration::from_millis(500), bucket.take(start));
+        assert_eq!(Duration::from_millis(1000), bucket.take(start));
+
+        // Once the waiting requests went, the bucket refills up to a second worth.
+        let later = start + Duration::from_secs(10);
+        assert_eq!(Duration::ZERO, bucket.take(later));
+        assert_eq!(Duration::ZERO, bucket.take(later));
+        assert_eq!(Duration::from_millis(500), bucket.take(later));
+    }
+}
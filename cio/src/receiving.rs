@@ -0,0 +1,339 @@
+use anyhow::{bail, Result};
+use chrono::Utc;
+use log::{info, warn};
+use schemars::JsonSchema;
+use sendgrid_api::{traits::MailOps, Client as SendGrid};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_inventory::{AssetItem, AssetItems},
+    asset_orders::{ORDERED_STATUS, RECEIVED_STATUS},
+    companies::Company,
+    db::Database,
+    printer::PrintTarget,
+    shipments::{InboundShipment, InboundShipments},
+};
+
+/// The shortest tracking number we match at the end of a longer barcode. Carriers like FedEx
+/// and USPS print barcodes with routing digits before the tracking number itself.
+static MIN_EMBEDDED_TRACKING_NUMBER_LEN: usize = 10;
+
+/// A scan of a package arriving at the office.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct PackageScan {
+    /// The tracking number on the package, or the supplier's order number if it does not
+    /// have one.
+    pub tracking_number: String,
+    /// The email of the person receiving the package.
+    pub email: String,
+    /// The names of the asset items in the package. Suppliers ship an order in more than one
+    /// package, so unless `whole_order` is set, only these items are received.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub asset_items: Vec<String>,
+    /// If the package has everything we are still waiting for on the order.
+    #[serde(default)]
+    pub whole_order: bool,
+}
+
+/// What a package we received matched.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ReceivedPackage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shipment: Option<InboundShipment>,
+    /// The asset items we ordered that came in the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub asset_items: Vec<AssetItem>,
+    /// The asset items on the order we are still waiting for. If the scan did not say what
+    /// is in the package, this is all of them, so whoever received it can pick.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending_asset_items: Vec<AssetItem>,
+    /// The emails of the people we let know the package is here.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notified: Vec<String>,
+}
+
+/// Return the tracking number without the spaces and dashes people type in it.
+pub fn normalize_tracking_number(tracking_number: &str) -> String {
+    tracking_number
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Return if a scanned barcode is for the tracking number we expect.
+pub fn tracking_matches(scanned: &str, expected: &str) -> bool {
+    let scanned = normalize_tracking_number(scanned);
+    let expected = normalize_tracking_number(expected);
+    if scanned.is_empty() || expected.is_empty() {
+        return false;
+    }
+
+    scanned == expected || (expected.len() >= MIN_EMBEDDED_TRACKING_NUMBER_LEN && scanned.ends_with(&expected))
+}
+
+/// Return the asset items on an order that we are still waiting for.
+pub fn expected_asset_items(items: Vec<AssetItem>, order_number: &str) -> Vec<AssetItem> {
+    let order_number = order_number.trim();
+    if order_number.is_empty() {
+        return vec![];
+    }
+
+    items
+        .into_iter()
+        .filter(|i| i.status == ORDERED_STATUS && i.order_number.trim().eq_ignore_ascii_case(order_number))
+        .collect()
+}
+
+/// Split the asset items we are waiting for on an order into the ones in the package and the
+/// ones that are still to come.
+pub fn received_asset_items(expected: Vec<AssetItem>, scan: &PackageScan) -> Result<(Vec<AssetItem>, Vec<AssetItem>)> {
+    if scan.whole_order {
+        return Ok((expected, vec![]));
+    }
+
+    let names: Vec<String> = scan
+        .asset_items
+        .iter()
+        .map(|n| n.trim().to_lowercase())
+        .filter(|n| !n.is_empty())
+        .collect();
+    if let Some(name) = names
+        .iter()
+        .find(|n| !expected.iter().any(|i| i.name.to_lowercase() == **n))
+    {
+        bail!("asset item `{}` is not one we are waiting for on the order", name);
+    }
+
+    Ok(expected
+        .into_iter()
+        .partition(|i| names.contains(&i.name.to_lowercase())))
+}
+
+/// Return who to let know a package is here: whoever asked us to track it, and whoever the
+/// items in it are for.
+pub fn package_recipients(shipment: Option<&InboundShipment>, items: &[AssetItem]) -> Vec<String> {
+    let mut recipients: Vec<String> = Vec::new();
+    let emails = shipment
+        .map(|s| s.requested_by.to_string())
+        .into_iter()
+        .chain(items.iter().map(|i| i.current_employee_borrowing.to_string()));
+    for email in emails {
+        let email = email.trim().to_lowercase();
+        if !email.is_empty() && !recipients.contains(&email) {
+            recipients.push(email);
+        }
+    }
+
+    recipients
+}
+
+/// Receive a package scanned at the office: match it to the shipment or the asset order we
+/// expect, mark it received, print the labels of the asset items in it, and let the people
+/// waiting for it know. The items on the order that were not in the package stay ordered.
+pub async fn receive_package(db: &Database, company: &Company, scan: &PackageScan) -> Result<ReceivedPackage> {
+    let email = scan.email.trim().to_lowercase();
+    if normalize_tracking_number(&scan.tracking_number).is_empty() || email.is_empty() {
+        bail!("a scan needs both the tracking number and the email of who received the package");
+    }
+
+    let mut shipment = InboundShipments::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .find(|s| tracking_matches(&scan.tracking_number, &s.tracking_number));
+
+    // The items we ordered are on the supplier's order number. If the package is not a
+    // shipment we track, it is what was scanned.
+    let order_number = match &shipment {
+        Some(s) => s.order_number.to_string(),
+        None => scan.tracking_number.to_string(),
+    };
+    let expected = expected_asset_items(
+        AssetItems::get_from_db(db, company.id).await?.into_iter().collect(),
+        &order_number,
+    );
+
+    if shipment.is_none() && expected.is_empty() {
+        bail!(
+            "no expected shipment or asset order matches `{}`",
+            scan.tracking_number.trim()
+        );
+    }
+
+    let (items, pending_asset_items) = received_asset_items(expected, scan)?;
+
+    let now = Utc::now();
+    if let Some(s) = shipment.as_mut() {
+        if s.received_time.is_some() {
+            info!(
+                "inbound shipment {} was already received, receiving it again",
+                s.tracking_number
+            );
+        }
+        s.received_time = Some(now);
+        s.received_by = email.to_string();
+        if s.delivered_time.is_none() {
+            s.delivered_time = Some(now);
+        }
+        *s = s.update(db).await?;
+        info!("{} received inbound shipment {}", email, s.tracking_number);
+    }
+
+    // Print the labels at the desk of whoever received the package.
+    let print_target = PrintTarget::for_user(db, company, &email).await;
+    let mut asset_items: Vec<AssetItem> = Vec::new();
+    for mut item in items {
+        item.status = RECEIVED_STATUS.to_string();
+        let item = item.update(db).await?;
+        info!("{} received asset item {}", email, item.name);
+
+        // Label the item now, before it leaves the receiving desk.
+        if let Err(e) = item.print_label(db, &print_target).await {
+            warn!("printing the label of asset item {} failed: {}", item.name, e);
+        }
+
+        asset_items.push(item);
+    }
+
+    let notified = package_recipients(shipment.as_ref(), &asset_items);
+    if !notified.is_empty() {
+        let (subject, body) = received_package_email(
+            shipment.as_ref(),
+            &order_number,
+            &asset_items,
+            pending_asset_items.len(),
+            &email,
+        );
+        SendGrid::new_from_env()
+            .mail_send()
+            .send_plain_text(
+                &subject,
+                &body,
+                &notified,
+                &[],
+                &[],
+                &format!("packages@{}", &company.gsuite_domain),
+            )
+            .await?;
+    }
+
+    Ok(ReceivedPackage {
+        shipment,
+        asset_items,
+        pending_asset_items,
+        notified,
+    })
+}
+
+fn received_package_email(
+    shipment: Option<&InboundShipment>,
+    order_number: &str,
+    items: &[AssetItem],
+    pending: usize,
+    received_by: &str,
+) -> (String, String) {
+    let name = match shipment {
+        Some(s) if !s.name.is_empty() => s.name.to_string(),
+        Some(s) => format!("{} {}", s.carrier, s.tracking_number),
+        None => format!("order {}", order_number.trim()),
+    };
+
+    let mut body = format!(
+        "Your package {} arrived at the office, {} received it.",
+        name, received_by
+    );
+    if !items.is_empty() {
+        body.push_str("\n\nIt had the following items, they are labeled and ready to pick up:");
+        for item in items {
+            body.push_str(&format!("\n- {} ({})", item.name, item.type_));
+        }
+    }
+    if pending > 0 {
+        body.push_str(&format!(
+            "\n\nWe are still waiting for {} more item(s) on the order.",
+            pending
+        ));
+    }
+    body.push_str("\n\nxoxo,\n  The Shipping Bot");
+
+    (format!("Your package {} is here!", name), body)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{expected_asset_items, package_recipients, received_asset_items, tracking_matches, PackageScan};
+    use crate::{asset_inventory::AssetItem, shipments::InboundShipment};
+
+    #[test]
+    fn test_tracking_matches() {
+        assert!(tracking_matches("1Z 999-AA1 0123456784", "1z999aa10123456784"));
+        // FedEx barcodes end with the tracking number.
+        assert!(tracking_matches("9632001960000000000400012345678901", "123456789012"));
+        assert!(!tracking_matches("9632001960000000000400012345678901", "8901"));
+        assert!(!tracking_matches("", ""));
+    }
+
+    #[test]
+    fn test_package_recipients() {
+        let item = |name: &str, status: &str, borrower: &str| -> AssetItem {
+            serde_json::from_value(json!({
+                "id": 1,
+                "airtable_record_id": "",
+                "name": name,
+                "status": status,
+                "order_number": "W123",
+                "current_employee_borrowing": borrower,
+            }))
+            .unwrap()
+        };
+        let items = expected_asset_items(
+            vec![
+                item("w123-1-1", "Ordered", "Jane@example.com"),
+                item("w123-1-2", "Received", "bob@example.com"),
+                item("w123-2-1", "Ordered", ""),
+            ],
+            "w123",
+        );
+        assert_eq!(
+            vec!["w123-1-1", "w123-2-1"],
+            items.iter().map(|i| i.name.as_str()).collect::<Vec<&str>>()
+        );
+
+        // Only the items in the package are received, unless it has the whole order.
+        let names = |items: &[AssetItem]| items.iter().map(|i| i.name.to_string()).collect::<Vec<String>>();
+        let mut scan = PackageScan {
+            asset_items: vec!["W123-2-1".to_string()],
+            ..Default::default()
+        };
+        let (received, pending) = received_asset_items(items.clone(), &scan).unwrap();
+        assert_eq!(vec!["w123-2-1"], names(&received));
+        assert_eq!(vec!["w123-1-1"], names(&pending));
+        scan.asset_items = vec![];
+        let (received, pending) = received_asset_items(items.clone(), &scan).unwrap();
+        assert!(received.is_empty());
+        assert_eq!(2, pending.len());
+        scan.whole_order = true;
+        let (received, pending) = received_asset_items(items.clone(), &scan).unwrap();
+        assert_eq!(2, received.len());
+        assert!(pending.is_empty());
+        scan.whole_order = false;
+        scan.asset_items = vec!["w123-1-2".to_string()];
+        assert!(received_asset_items(items.clone(), &scan).is_err());
+
+        let shipment: InboundShipment = serde_json::from_value(json!({
+            "id": 1,
+            "airtable_record_id": "",
+            "tracking_number": "1Z999AA10123456784",
+            "carrier": "UPS",
+            "requested_by": "jane@example.com",
+        }))
+        .unwrap();
+        assert_eq!(
+            vec!["jane@example.com".to_string()],
+            package_recipients(Some(&shipment), &items)
+        );
+        assert!(package_recipients(None, &[]).is_empty());
+    }
+}
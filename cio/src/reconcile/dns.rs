@@ -0,0 +1,94 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{ReconcileAction, ReconcileChange, ReconcileState, Reconciler};
+use crate::{
+    companies::Company,
+    db::Database,
+    dns_providers::{DNSProviderOps, DnsRecord, DnsRecordType, DnsUpdateMode},
+    dns_proxy::DnsProviderProxy,
+    shorturls::{get_shorturls_for_configs_links, get_shorturls_for_repos, get_shorturls_for_rfds},
+};
+
+/// Reconcile the DNS records of the short URLs. Other records of the domain are not ours, so
+/// we never plan to delete records.
+pub struct DnsReconciler {
+    dns: DnsProviderProxy,
+}
+
+impl DnsReconciler {
+    pub fn new(dns: DnsProviderProxy) -> Self {
+        DnsReconciler { dns }
+    }
+}
+
+/// The state of the A records of a name.
+fn a_records_state(mut contents: Vec<String>) -> serde_json::Value {
+    contents.sort();
+    contents.dedup();
+
+    json!({ "A": contents })
+}
+
+#[async_trait]
+impl Reconciler for DnsReconciler {
+    fn name(&self) -> &str {
+        "dns"
+    }
+
+    async fn desired_state(&self, db: &Database, company: &Company) -> Result<ReconcileState> {
+        let mut links = get_shorturls_for_repos(db, company).await?;
+        links.append(&mut get_shorturls_for_rfds(db, company).await?);
+        links.append(&mut get_shorturls_for_configs_links(db, company).await?);
+
+        Ok(links
+            .iter()
+            .map(|l| (l.dns_name(company), a_records_state(vec![company.nginx_ip.to_string()])))
+            .collect())
+    }
+
+    async fn current_state(
+        &self,
+        _db: &Database,
+        _company: &Company,
+        desired: &ReconcileState,
+    ) -> Result<ReconcileState> {
+        let mut current = ReconcileState::new();
+        for name in desired.keys() {
+            let contents = self.dns.get_records(name, DnsRecordType::A).await?;
+            if !contents.is_empty() {
+                current.insert(name.to_string(), a_records_state(contents));
+            }
+        }
+
+        Ok(current)
+    }
+
+    async fn apply_change(&self, _db: &Database, _company: &Company, change: &ReconcileChange) -> Result<()> {
+        let after = match (&change.action, &change.after) {
+            (ReconcileAction::Delete, _) | (_, None) => bail!("we do not delete DNS records"),
+            (_, Some(after)) => after,
+        };
+
+        let contents: Vec<String> = serde_json::from_value(after["A"].clone())?;
+        let mut mode = DnsUpdateMode::Replace;
+        for content in contents {
+            self.dns
+                .ensure_record(
+                    DnsRecord {
+                        name: change.resource.to_string(),
+                        type_: DnsRecordType::A,
+                        content,
+                    },
+                    mode,
+                )
+                .await?;
+
+            // Keep the other records we planned for the name.
+            mode = DnsUpdateMode::Append;
+        }
+
+        Ok(())
+    }
+}
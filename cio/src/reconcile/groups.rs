@@ -0,0 +1,329 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use log::info;
+use serde_json::json;
+
+use super::{ReconcileAction, ReconcileChange, ReconcileState, Reconciler};
+use crate::{
+    companies::Company,
+    configs::{ExternalServices, Group, Groups},
+    db::Database,
+    providers::{ProviderReadOps, ProviderWriteOps},
+    rate_limit::{acquire, Provider},
+};
+
+/// Get the groups of our configs that we provision in a service.
+async fn get_provisioned_groups(db: &Database, company: &Company, service: &ExternalServices) -> Result<Vec<Group>> {
+    Ok(Groups::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .filter(|g| g.supports_provisioning_in(service))
+        .collect())
+}
+
+/// Get the group of our configs for a change that creates or updates it.
+async fn get_changed_group(db: &Database, company: &Company, change: &ReconcileChange) -> Result<Group> {
+    match Group::get_from_db(db, company.id, change.resource.to_string()).await {
+        Some(group) => Ok(group),
+        None => bail!("group `{}` is not in our configs anymore", change.resource),
+    }
+}
+
+/// Reconcile the teams of the GitHub organization with our groups. Teams that are not in our
+/// configs are not ours, so we never plan to delete teams.
+pub struct GitHubTeamsReconciler {
+    github: octorust::Client,
+}
+
+impl GitHubTeamsReconciler {
+    pub fn new(github: octorust::Client) -> Self {
+        GitHubTeamsReconciler { github }
+    }
+}
+
+#[async_trait]
+impl Reconciler for GitHubTeamsReconciler {
+    fn name(&self) -> &str {
+        "github-teams"
+    }
+
+    async fn desired_state(&self, db: &Database, company: &Company) -> Result<ReconcileState> {
+        Ok(get_provisioned_groups(db, company, &ExternalServices::GitHub)
+            .await?
+            .into_iter()
+            .map(|g| (g.name.to_string(), json!({ "description": g.description })))
+            .collect())
+    }
+
+    async fn current_state(
+        &self,
+        _db: &Database,
+        company: &Company,
+        desired: &ReconcileState,
+    ) -> Result<ReconcileState> {
+        Ok(self
+            .github
+            .list_provider_groups(company)
+            .await?
+            .into_iter()
+            .filter(|t| desired.contains_key(&t.name))
+            .map(|t| (t.name.to_string(), json!({ "description": t.description })))
+            .collect())
+    }
+
+    async fn apply_change(&self, db: &Database, company: &Company, change: &ReconcileChange) -> Result<()> {
+        let after = match (&change.action, &change.after) {
+            (ReconcileAction::Delete, _) | (_, None) => bail!("we do not delete GitHub teams"),
+            (ReconcileAction::Create, _) => {
+                let group = get_changed_group(db, company, change).await?;
+                return self.github.ensure_group(db, company, &group).await;
+            }
+            (ReconcileAction::Update, Some(after)) => after,
+        };
+
+        // The API wants the slug of the team, not its name.
+        let team = match self
+            .github
+            .list_provider_groups(company)
+            .await?
+            .into_iter()
+            .find(|t| t.name == change.resource)
+        {
+            Some(team) => team,
+            None => bail!(
+                "team `{}` is not in github org `{}` anymore",
+                change.resource,
+                company.github_org
+            ),
+        };
+        let parent_team_id = if let Some(parent) = team.parent { parent.id } else { 0 };
+
+        self.github
+            .teams()
+            .update_in_org(
+                &company.github_org,
+                &team.slug,
+                &octorust::types::TeamsUpdateInOrgRequest {
+                    name: team.name.to_string(),
+                    description: after["description"].as_str().unwrap_or_default().to_string(),
+                    parent_team_id,
+                    permission: None, // This is depreciated, so just pass none.
+                    privacy: None,
+                },
+            )
+            .await?;
+
+        info!(
+            "updated group `{}` in github org `{}`",
+            change.resource, company.github_org
+        );
+
+        Ok(())
+    }
+}
+
+/// Reconcile the Google Groups with our groups. Groups that are not in our configs are not
+/// ours, so we never plan to delete groups.
+pub struct GoogleGroupsReconciler {
+    gsuite: gsuite_api::Client,
+}
+
+impl GoogleGroupsReconciler {
+    pub fn new(gsuite: gsuite_api::Client) -> Self {
+        GoogleGroupsReconciler { gsuite }
+    }
+}
+
+/// The state of a Google Group we manage, the aliases are full email addresses.
+fn google_group_state(description: &str, mut aliases: Vec<String>) -> serde_json::Value {
+    aliases.sort();
+
+    json!({ "description": description, "aliases": aliases })
+}
+
+#[async_trait]
+impl Reconciler for GoogleGroupsReconciler {
+    fn name(&self) -> &str {
+        "google-groups"
+    }
+
+    async fn desired_state(&self, db: &Database, company: &Company) -> Result<ReconcileState> {
+        Ok(get_provisioned_groups(db, company, &ExternalServices::Google)
+            .await?
+            .into_iter()
+            .map(|g| {
+                let aliases = g
+                    .aliases
+                    .iter()
+                    .map(|alias| format!("{}@{}", alias, company.gsuite_domain))
+                    .collect();
+                (g.name.to_string(), google_group_state(&g.description, aliases))
+            })
+            .collect())
+    }
+
+    async fn current_state(
+        &self,
+        _db: &Database,
+        company: &Company,
+        desired: &ReconcileState,
+    ) -> Result<ReconcileState> {
+        let suffix = format!("@{}", company.gsuite_domain);
+
+        Ok(self
+            .gsuite
+            .list_provider_groups(company)
+            .await?
+            .into_iter()
+            .map(|g| {
+                (
+                    g.email.trim_end_matches(&suffix).to_string(),
+                    google_group_state(&g.description, g.aliases),
+                )
+            })
+            .filter(|(name, _)| desired.contains_key(name))
+            .collect())
+    }
+
+    async fn apply_change(&self, db: &Database, company: &Company, change: &ReconcileChange) -> Result<()> {
+        let after = match (&change.action, &change.after) {
+            (ReconcileAction::Delete, _) | (_, None) => bail!("we do not delete Google Groups"),
+            (ReconcileAction::Create, _) => {
+                let group = get_changed_group(db, company, change).await?;
+                return self.gsuite.ensure_group(db, company, &group).await;
+            }
+            (ReconcileAction::Update, Some(after)) => after,
+        };
+
+        let email = format!("{}@{}", change.resource, company.gsuite_domain);
+
+        acquire(Provider::Google).await;
+        let mut google_group = self.gsuite.groups().get(&email).await?;
+        let current_aliases = google_group.aliases.clone();
+
+        google_group.description = after["description"].as_str().unwrap_or_default().to_string();
+        google_group.aliases = serde_json::from_value(after["aliases"].clone())?;
+
+        acquire(Provider::Google).await;
+        self.gsuite.groups().update(&email, &google_group).await?;
+
+        crate::gsuite::update_group_aliases(db, &self.gsuite, &google_group, &current_aliases, company).await?;
+
+        info!("updated group `{}` in GSuite", change.resource);
+
+        Ok(())
+    }
+}
+
+/// Reconcile the groups of Okta with our groups. Groups that are not in our configs are not
+/// ours, so we never plan to delete groups.
+pub struct OktaGroupsReconciler {
+    okta: okta::Client,
+}
+
+impl OktaGroupsReconciler {
+    pub fn new(okta: okta::Client) -> Self {
+        OktaGroupsReconciler { okta }
+    }
+}
+
+/// Returns if an Okta group is one we can manage. Okta has groups of its own, like
+/// "Everyone", and groups from the apps and directories it is connected to.
+pub(super) fn is_managed_okta_group(group: &okta::types::Group) -> bool {
+    group.object_class.iter().any(|c| c == "okta:user_group")
+        && group.profile.as_ref().map(|p| p.name != "Everyone").unwrap_or_default()
+}
+
+#[async_trait]
+impl Reconciler for OktaGroupsReconciler {
+    fn name(&self) -> &str {
+        "okta-groups"
+    }
+
+    async fn desired_state(&self, db: &Database, company: &Company) -> Result<ReconcileState> {
+        Ok(get_provisioned_groups(db, company, &ExternalServices::Okta)
+            .await?
+            .into_iter()
+            .filter(|g| g.name != "Everyone")
+            .map(|g| (g.name.to_string(), json!({ "description": g.description })))
+            .collect())
+    }
+
+    async fn current_state(
+        &self,
+        _db: &Database,
+        company: &Company,
+        desired: &ReconcileState,
+    ) -> Result<ReconcileState> {
+        Ok(self
+            .okta
+            .list_provider_groups(company)
+            .await?
+            .into_iter()
+            .filter(is_managed_okta_group)
+            .filter_map(|g| g.profile)
+            .filter(|p| desired.contains_key(&p.name))
+            .map(|p| (p.name.to_string(), json!({ "description": p.description })))
+            .collect())
+    }
+
+    async fn apply_change(&self, db: &Database, company: &Company, change: &ReconcileChange) -> Result<()> {
+        let after = match (&change.action, &change.after) {
+            (ReconcileAction::Delete, _) | (_, None) => bail!("we do not delete Okta groups"),
+            (ReconcileAction::Create, _) => {
+                let group = get_changed_group(db, company, change).await?;
+                return self.okta.ensure_group(db, company, &group).await;
+            }
+            (ReconcileAction::Update, Some(after)) => after,
+        };
+
+        let mut group = find_managed_okta_group(&self.okta, &change.resource).await?;
+        if let Some(profile) = group.profile.as_mut() {
+            profile.description = after["description"].as_str().unwrap_or_default().to_string();
+        }
+
+        self.okta.groups().update(&group.id, &group).await?;
+
+        info!("updated group `{}` in Okta", change.resource);
+
+        Ok(())
+    }
+}
+
+/// Find a group we manage in Okta by its name.
+pub(super) async fn find_managed_okta_group(okta: &okta::Client, name: &str) -> Result<okta::types::Group> {
+    let results = okta
+        .groups()
+        .list_all(
+            name, // query
+            "",   // search
+            "",   // expand
+        )
+        .await?;
+
+    match results
+        .into_iter()
+        .find(|g| is_managed_okta_group(g) && g.profile.as_ref().map(|p| p.name == name).unwrap_or_default())
+    {
+        Some(group) => Ok(group),
+        None => bail!("group `{}` is not in Okta anymore", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::google_group_state;
+
+    #[test]
+    fn test_google_group_state() {
+        assert_eq!(
+            json!({"description": "Engineering", "aliases": ["devs@example.com", "eng-team@example.com"]}),
+            google_group_state(
+                "Engineering",
+                vec!["eng-team@example.com".to_string(), "devs@example.com".to_string()]
+            )
+        );
+    }
+}
@@ -0,0 +1,477 @@
+//! Plan and apply the changes of the syncs that reconcile a service with our configs.
+//!
+//! A plan is the list of changes a reconciler would make, stored so someone can review it.
+//! Applying a plan makes exactly those changes, and nothing else. If a resource of the plan
+//! changed in the service or in our configs since we planned, the plan is stale and applying
+//! it aborts before making any change.
+
+mod dns;
+mod groups;
+mod okta_apps;
+mod repo_settings;
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+pub use dns::DnsReconciler;
+pub use groups::{GitHubTeamsReconciler, GoogleGroupsReconciler, OktaGroupsReconciler};
+pub use okta_apps::OktaAppsReconciler;
+pub use repo_settings::RepoSettingsReconciler;
+
+use crate::{app_config::AppConfig, companies::Company, db::Database, schema::reconcile_plans};
+
+pub const RECONCILE_PLAN_PLANNED: &str = "planned";
+pub const RECONCILE_PLAN_SUPERSEDED: &str = "superseded";
+pub const RECONCILE_PLAN_APPLYING: &str = "applying";
+pub const RECONCILE_PLAN_APPLIED: &str = "applied";
+pub const RECONCILE_PLAN_ABORTED: &str = "aborted";
+pub const RECONCILE_PLAN_FAILED: &str = "failed";
+
+/// The most changes of a plan we list in Slack, the rest are in the stored plan.
+static MAX_CHANGES_POSTED: usize = 10;
+
+/// The state of the resources of a reconciler, by the name of the resource.
+pub type ReconcileState = BTreeMap<String, serde_json::Value>;
+
+#[derive(Debug, Clone, Copy, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReconcileAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// A change to a resource. `before` is what the service had when we planned, and `after`
+/// what our configs wanted.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ReconcileChange {
+    pub resource: String,
+    pub action: ReconcileAction,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+/// Something that reconciles the resources of a service with our configs.
+#[async_trait]
+pub trait Reconciler: Send + Sync {
+    /// The name of the reconciler, plans are stored under it.
+    fn name(&self) -> &str;
+
+    /// Get the state our configs want the resources in.
+    async fn desired_state(&self, db: &Database, company: &Company) -> Result<ReconcileState>;
+
+    /// Get the state the resources are in the service. Reconcilers that only manage some of
+    /// the resources of the service look up the ones we want.
+    async fn current_state(&self, db: &Database, company: &Company, desired: &ReconcileState)
+        -> Result<ReconcileState>;
+
+    /// Make a change in the service.
+    async fn apply_change(&self, db: &Database, company: &Company, change: &ReconcileChange) -> Result<()>;
+}
+
+/// Get the reconcilers of a company.
+pub async fn get_reconcilers(
+    db: &Database,
+    company: &Company,
+    app_config: &AppConfig,
+) -> Result<Vec<Box<dyn Reconciler>>> {
+    let github = company.authenticate_github()?;
+
+    let mut reconcilers: Vec<Box<dyn Reconciler>> = vec![
+        Box::new(GitHubTeamsReconciler::new(github.clone())),
+        Box::new(GoogleGroupsReconciler::new(
+            company.authenticate_google_admin(db).await?,
+        )),
+        Box::new(DnsReconciler::new(company.authenticate_dns_providers().await?)),
+        Box::new(RepoSettingsReconciler::new(github, &app_config.github.ignored_repos)),
+    ];
+    if let Some(okta) = company.authenticate_okta() {
+        reconcilers.push(Box::new(OktaGroupsReconciler::new(okta)));
+    }
+    if let Some(okta) = company.authenticate_okta() {
+        reconcilers.push(Box::new(OktaAppsReconciler::new(okta)));
+    }
+
+    Ok(reconcilers)
+}
+
+/// Returns the changes that get the resources from their current state to the desired one.
+pub fn plan_changes(current: &ReconcileState, desired: &ReconcileState) -> Vec<ReconcileChange> {
+    let mut changes: Vec<ReconcileChange> = Vec::new();
+    for (resource, after) in desired {
+        match current.get(resource) {
+            Some(before) if before == after => {}
+            Some(before) => changes.push(ReconcileChange {
+                resource: resource.to_string(),
+                action: ReconcileAction::Update,
+                before: Some(before.clone()),
+                after: Some(after.clone()),
+            }),
+            None => changes.push(ReconcileChange {
+                resource: resource.to_string(),
+                action: ReconcileAction::Create,
+                before: None,
+                after: Some(after.clone()),
+            }),
+        }
+    }
+    for (resource, before) in current {
+        if !desired.contains_key(resource) {
+            changes.push(ReconcileChange {
+                resource: resource.to_string(),
+                action: ReconcileAction::Delete,
+                before: Some(before.clone()),
+                after: None,
+            });
+        }
+    }
+
+    changes
+}
+
+/// Returns the resources of the changes that moved away from what we planned, in the service
+/// or in our configs.
+pub fn plan_drift(changes: &[ReconcileChange], current: &ReconcileState, desired: &ReconcileState) -> Vec<String> {
+    changes
+        .iter()
+        .filter(|c| current.get(&c.resource) != c.before.as_ref() || desired.get(&c.resource) != c.after.as_ref())
+        .map(|c| c.resource.to_string())
+        .collect()
+}
+
+/// A stored plan of a reconciler.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct ReconcilePlan {
+    pub id: i32,
+    pub reconciler: String,
+    /// The changes of the plan, as JSON.
+    pub changes: String,
+    pub status: String,
+    pub error: String,
+    pub created_at: DateTime<Utc>,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+}
+
+/// A plan with its changes, for review.
+#[derive(Debug, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct ReconcilePlanEntry {
+    pub plan: ReconcilePlan,
+    pub changes: Vec<ReconcileChange>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = reconcile_plans)]
+struct NewReconcilePlan {
+    reconciler: String,
+    changes: String,
+    status: String,
+    error: String,
+    created_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+impl ReconcilePlan {
+    pub fn get_changes(&self) -> Result<Vec<ReconcileChange>> {
+        Ok(serde_json::from_str(&self.changes)?)
+    }
+
+    pub fn into_entry(self) -> Result<ReconcilePlanEntry> {
+        Ok(ReconcilePlanEntry {
+            changes: self.get_changes()?,
+            plan: self,
+        })
+    }
+
+    /// Summarize the changes of the plan, like `2 to create, 1 to update`.
+    pub fn summary(&self) -> Result<String> {
+        let changes = self.get_changes()?;
+        let count = |action: ReconcileAction| changes.iter().filter(|c| c.action == action).count();
+
+        Ok(format!(
+            "{} to create, {} to update, {} to delete",
+            count(ReconcileAction::Create),
+            count(ReconcileAction::Update),
+            count(ReconcileAction::Delete)
+        ))
+    }
+
+    async fn set_status(&self, db: &Database, status: &str, error: &str) -> Result<ReconcilePlan> {
+        let applied_at = if status == RECONCILE_PLAN_APPLIED {
+            Some(Utc::now())
+        } else {
+            None
+        };
+
+        Ok(diesel::update(reconcile_plans::dsl::reconcile_plans.find(self.id))
+            .set((
+                reconcile_plans::dsl::status.eq(status.to_string()),
+                reconcile_plans::dsl::error.eq(error.to_string()),
+                reconcile_plans::dsl::applied_at.eq(applied_at),
+            ))
+            .get_result_async::<ReconcilePlan>(db.pool())
+            .await?)
+    }
+}
+
+/// Get a plan of a company.
+pub async fn get_reconcile_plan(db: &Database, cio_company_id: i32, id: i32) -> Result<Option<ReconcilePlan>> {
+    let plans = reconcile_plans::dsl::reconcile_plans
+        .filter(reconcile_plans::dsl::id.eq(id))
+        .filter(reconcile_plans::dsl::cio_company_id.eq(cio_company_id))
+        .load_async::<ReconcilePlan>(db.pool())
+        .await?;
+
+    Ok(plans.into_iter().next())
+}
+
+/// Plan the changes of a reconciler, and store them. The plans of the reconciler that were
+/// not applied yet are superseded by the new one. Returns `None` if there is nothing to change.
+pub async fn plan_reconciler(
+    db: &Database,
+    company: &Company,
+    reconciler: &dyn Reconciler,
+) -> Result<Option<ReconcilePlan>> {
+    let desired = reconciler.desired_state(db, company).await?;
+    let current = reconciler.current_state(db, company, &desired).await?;
+    let changes = plan_changes(&current, &desired);
+
+    diesel::update(
+        reconcile_plans::dsl::reconcile_plans
+            .filter(reconcile_plans::dsl::cio_company_id.eq(company.id))
+            .filter(reconcile_plans::dsl::reconciler.eq(reconciler.name().to_string()))
+            .filter(reconcile_plans::dsl::status.eq(RECONCILE_PLAN_PLANNED.to_string())),
+    )
+    .set(reconcile_plans::dsl::status.eq(RECONCILE_PLAN_SUPERSEDED.to_string()))
+    .execute_async(db.pool())
+    .await?;
+
+    if changes.is_empty() {
+        info!("[reconcile] {} has nothing to change", reconciler.name());
+        return Ok(None);
+    }
+
+    let plan = diesel::insert_into(reconcile_plans::table)
+        .values(NewReconcilePlan {
+            reconciler: reconciler.name().to_string(),
+            changes: serde_json::to_string(&changes)?,
+            status: RECONCILE_PLAN_PLANNED.to_string(),
+            error: String::new(),
+            created_at: Utc::now(),
+            cio_company_id: company.id,
+        })
+        .get_result_async::<ReconcilePlan>(db.pool())
+        .await?;
+    info!("[reconcile] planned {}: {}", reconciler.name(), plan.summary()?);
+
+    Ok(Some(plan))
+}
+
+/// Format a change for Slack, like `+ github-teams/eng`.
+fn format_change(change: &ReconcileChange) -> String {
+    let sign = match change.action {
+        ReconcileAction::Create => "+",
+        ReconcileAction::Update => "~",
+        ReconcileAction::Delete => "-",
+    };
+
+    format!("`{} {}`", sign, change.resource)
+}
+
+/// Plan the changes of every reconciler, and post the plans to the alerts channel for review.
+pub async fn plan_reconcilers(db: &Database, company: &Company, app_config: &AppConfig) -> Result<()> {
+    let mut lines: Vec<String> = Vec::new();
+    for reconciler in get_reconcilers(db, company, app_config).await? {
+        let plan = match plan_reconciler(db, company, reconciler.as_ref()).await? {
+            Some(plan) => plan,
+            None => continue,
+        };
+
+        let changes = plan.get_changes()?;
+        lines.push(format!(
+            "*Plan {}* for `{}`: {}",
+            plan.id,
+            plan.reconciler,
+            plan.summary()?
+        ));
+        for change in changes.iter().take(MAX_CHANGES_POSTED) {
+            lines.push(format!("• {}", format_change(change)));
+        }
+        if changes.len() > MAX_CHANGES_POSTED {
+            lines.push(format!("• _and {} more_", changes.len() - MAX_CHANGES_POSTED));
+        }
+    }
+
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let channel = match company.alerts_channel("") {
+        Some(channel) => channel,
+        None => {
+            warn!(
+                "company `{}` has no alerts channel, not posting the plans to review",
+                company.name
+            );
+            return Ok(());
+        }
+    };
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "The syncs would make these changes, review the plans and apply them:\n{}",
+                    lines.join("\n")
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+/// Apply a plan. The plan is aborted without any change if what it changes moved since we
+/// planned, and marked as failed with the error if a change fails.
+pub async fn apply_reconcile_plan(
+    db: &Database,
+    company: &Company,
+    app_config: &AppConfig,
+    plan: &ReconcilePlan,
+) -> Result<ReconcilePlan> {
+    if plan.status != RECONCILE_PLAN_PLANNED {
+        bail!("plan {} is {}, only planned plans can be applied", plan.id, plan.status);
+    }
+
+    let reconciler = match get_reconcilers(db, company, app_config)
+        .await?
+        .into_iter()
+        .find(|r| r.name() == plan.reconciler)
+    {
+        Some(reconciler) => reconciler,
+        None => bail!("plan {} is for unknown reconciler `{}`", plan.id, plan.reconciler),
+    };
+
+    // Claim the plan, so it is not applied twice at the same time.
+    let claimed = diesel::update(
+        reconcile_plans::dsl::reconcile_plans
+            .filter(reconcile_plans::dsl::id.eq(plan.id))
+            .filter(reconcile_plans::dsl::status.eq(RECONCILE_PLAN_PLANNED.to_string())),
+    )
+    .set(reconcile_plans::dsl::status.eq(RECONCILE_PLAN_APPLYING.to_string()))
+    .execute_async(db.pool())
+    .await?;
+    if claimed == 0 {
+        bail!("plan {} is already being applied", plan.id);
+    }
+
+    let changes = plan.get_changes()?;
+    let state = async {
+        let desired = reconciler.desired_state(db, company).await?;
+        let current = reconciler.current_state(db, company, &desired).await?;
+        Ok::<_, anyhow::Error>((desired, current))
+    };
+    let (desired, current) = match state.await {
+        Ok(state) => state,
+        Err(e) => {
+            plan.set_status(db, RECONCILE_PLAN_FAILED, &format!("could not check the plan: {}", e))
+                .await?;
+            return Err(e);
+        }
+    };
+    let drift = plan_drift(&changes, &current, &desired);
+    if !drift.is_empty() {
+        let error = format!("changed since the plan: {}", drift.join(", "));
+        plan.set_status(db, RECONCILE_PLAN_ABORTED, &error).await?;
+        bail!("aborted plan {}, {}", plan.id, error);
+    }
+
+    for (i, change) in changes.iter().enumerate() {
+        if let Err(e) = reconciler.apply_change(db, company, change).await {
+            let error = format!(
+                "applied {} of {} changes, `{}` failed: {}",
+                i,
+                changes.len(),
+                change.resource,
+                e
+            );
+            plan.set_status(db, RECONCILE_PLAN_FAILED, &error).await?;
+            bail!("plan {} failed, {}", plan.id, error);
+        }
+
+        info!(
+            "[reconcile] {}: applied {:?} of {}",
+            plan.reconciler, change.action, change.resource
+        );
+    }
+
+    plan.set_status(db, RECONCILE_PLAN_APPLIED, "").await
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{plan_changes, plan_drift, ReconcileAction, ReconcileState};
+
+    #[test]
+    fn test_plan_changes() {
+        let mut current = ReconcileState::new();
+        current.insert("eng".to_string(), json!({"description": "Engineering"}));
+        current.insert("old".to_string(), json!({"description": "Old"}));
+        current.insert("all".to_string(), json!({"description": "Everyone"}));
+
+        let mut desired = ReconcileState::new();
+        desired.insert("eng".to_string(), json!({"description": "Engineers"}));
+        desired.insert("new".to_string(), json!({"description": "New"}));
+        desired.insert("all".to_string(), json!({"description": "Everyone"}));
+
+        let changes = plan_changes(&current, &desired);
+        let actions: Vec<(&str, ReconcileAction)> = changes.iter().map(|c| (c.resource.as_str(), c.action)).collect();
+        assert_eq!(
+            vec![
+                ("eng", ReconcileAction::Update),
+                ("new", ReconcileAction::Create),
+                ("old", ReconcileAction::Delete),
+            ],
+            actions
+        );
+        assert_eq!(Some(json!({"description": "Engineering"})), changes[0].before);
+        assert_eq!(None, changes[1].before);
+        assert_eq!(None, changes[2].after);
+
+        // Nothing moved since the plan.
+        assert!(plan_drift(&changes, &current, &desired).is_empty());
+
+        // Someone changed a team by hand, and someone else the configs of another.
+        let mut moved = current.clone();
+        moved.insert("eng".to_string(), json!({"description": "Eng"}));
+        let mut edited = desired.clone();
+        edited.insert("new".to_string(), json!({"description": "Newer"}));
+        assert_eq!(vec!["eng", "new"], plan_drift(&changes, &moved, &edited));
+
+        // The resources out of the plan do not matter.
+        let mut other = desired;
+        other.insert("other".to_string(), json!({}));
+        assert!(plan_drift(&changes, &current, &other).is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use log::info;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+
+use super::{
+    groups::{find_managed_okta_group, is_managed_okta_group},
+    ReconcileAction, ReconcileChange, ReconcileState, Reconciler,
+};
+use crate::{
+    companies::Company,
+    configs::{ExternalServices, Groups},
+    db::Database,
+    providers::ProviderReadOps,
+};
+
+/// Reconcile the groups assigned to the Okta apps with the apps of our groups. Other
+/// assignments are not ours, so we only ever assign groups.
+pub struct OktaAppsReconciler {
+    okta: okta::Client,
+}
+
+impl OktaAppsReconciler {
+    pub fn new(okta: okta::Client) -> Self {
+        OktaAppsReconciler { okta }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OktaApp {
+    id: String,
+    #[serde(default)]
+    label: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OktaAppGroupAssignment {
+    /// The id of the group.
+    id: String,
+}
+
+/// The state of an app, the names of the groups assigned to it.
+fn app_groups_state(groups: BTreeSet<String>) -> serde_json::Value {
+    json!({ "groups": groups })
+}
+
+/// Get the names of the groups of an app state.
+fn get_app_groups(state: &Option<serde_json::Value>) -> Result<BTreeSet<String>> {
+    match state {
+        Some(state) => Ok(serde_json::from_value(state["groups"].clone())?),
+        None => Ok(Default::default()),
+    }
+}
+
+/// Get every page of a list from the Okta API, following the `next` links.
+async fn okta_get_all<T: DeserializeOwned>(company: &Company, path: &str) -> Result<Vec<T>> {
+    let client = reqwest::Client::new();
+
+    let mut items: Vec<T> = Vec::new();
+    let mut next = Some(format!("{}{}", company.okta_endpoint(), path));
+    while let Some(url) = next {
+        let resp = client
+            .get(&url)
+            .header("Authorization", format!("SSWS {}", company.okta_api_key))
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        next = resp
+            .headers()
+            .get_all("link")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find(|v| v.contains("rel=\"next\""))
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().trim_start_matches('<').trim_end_matches('>').to_string());
+
+        let mut page: Vec<T> = resp.json().await?;
+        items.append(&mut page);
+    }
+
+    Ok(items)
+}
+
+/// Get the active Okta apps, by their label.
+async fn get_okta_apps(company: &Company) -> Result<BTreeMap<String, String>> {
+    let apps: Vec<OktaApp> = okta_get_all(company, "/api/v1/apps?limit=200&filter=status%20eq%20%22ACTIVE%22").await?;
+
+    Ok(apps.into_iter().map(|a| (a.label, a.id)).collect())
+}
+
+#[async_trait]
+impl Reconciler for OktaAppsReconciler {
+    fn name(&self) -> &str {
+        "okta-apps"
+    }
+
+    async fn desired_state(&self, db: &Database, company: &Company) -> Result<ReconcileState> {
+        let mut apps: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for group in Groups::get_from_db(db, company.id).await? {
+            if !group.supports_provisioning_in(&ExternalServices::Okta) || group.name == "Everyone" {
+                continue;
+            }
+
+            for app in &group.okta_apps {
+                apps.entry(app.trim().to_string())
+                    .or_default()
+                    .insert(group.name.to_string());
+            }
+        }
+
+        Ok(apps
+            .into_iter()
+            .map(|(app, groups)| (app, app_groups_state(groups)))
+            .collect())
+    }
+
+    async fn current_state(
+        &self,
+        _db: &Database,
+        company: &Company,
+        desired: &ReconcileState,
+    ) -> Result<ReconcileState> {
+        if desired.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let apps = get_okta_apps(company).await?;
+        let group_names: BTreeMap<String, String> = self
+            .okta
+            .list_provider_groups(company)
+            .await?
+            .into_iter()
+            .filter(is_managed_okta_group)
+            .filter_map(|g| g.profile.map(|p| (g.id, p.name)))
+            .collect();
+
+        let mut current = ReconcileState::new();
+        for (label, state) in desired {
+            // Apps we do not have in Okta are planned as created, and fail to apply.
+            let app_id = match apps.get(label) {
+                Some(app_id) => app_id,
+                None => continue,
+            };
+
+            let wanted = get_app_groups(&Some(state.clone()))?;
+            let assignments: Vec<OktaAppGroupAssignment> =
+                okta_get_all(company, &format!("/api/v1/apps/{}/groups?limit=200", app_id)).await?;
+            let groups = assignments
+                .into_iter()
+                .filter_map(|a| group_names.get(&a.id).cloned())
+                .filter(|name| wanted.contains(name))
+                .collect();
+
+            current.insert(label.to_string(), app_groups_state(groups));
+        }
+
+        Ok(current)
+    }
+
+    async fn apply_change(&self, _db: &Database, company: &Company, change: &ReconcileChange) -> Result<()> {
+        if change.action == ReconcileAction::Delete || change.after.is_none() {
+            bail!("we do not remove groups from Okta apps");
+        }
+
+        let app_id = match get_okta_apps(company).await?.remove(&change.resource) {
+            Some(app_id) => app_id,
+            None => bail!("app `{}` is not in Okta, add it there first", change.resource),
+        };
+
+        let before = get_app_groups(&change.before)?;
+        for name in get_app_groups(&change.after)?.difference(&before) {
+            let group = find_managed_okta_group(&self.okta, name).await?;
+
+            reqwest::Client::new()
+                .put(&format!(
+                    "{}/api/v1/apps/{}/groups/{}",
+                    company.okta_endpoint(),
+                    app_id,
+                    group.id
+                ))
+                .header("Authorization", format!("SSWS {}", company.okta_api_key))
+                .header("Accept", "application/json")
+                .json(&json!({}))
+                .send()
+                .await?
+                .error_for_status()?;
+
+            info!("assigned group `{}` to app `{}` in Okta", name, change.resource);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use serde_json::json;
+
+    use super::{app_groups_state, get_app_groups};
+
+    #[test]
+    fn test_app_groups_state() {
+        let groups: BTreeSet<String> = ["eng", "all"].iter().map(|g| g.to_string()).collect();
+        let state = app_groups_state(groups.clone());
+        assert_eq!(json!({"groups": ["all", "eng"]}), state);
+
+        assert_eq!(groups, get_app_groups(&Some(state)).unwrap());
+        assert!(get_app_groups(&None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{ReconcileAction, ReconcileChange, ReconcileState, Reconciler};
+use crate::{
+    companies::Company,
+    db::Database,
+    repos::{team_has_push_access, GithubRepo, GithubRepos, DEFAULT_REPO_TEAMS},
+};
+
+/// Reconcile the settings of the repositories of the GitHub organization: the protection of
+/// their default branch, and the teams that can push to them.
+pub struct RepoSettingsReconciler {
+    github: octorust::Client,
+    ignored_repos: Vec<String>,
+}
+
+impl RepoSettingsReconciler {
+    pub fn new(github: octorust::Client, ignored_repos: &[String]) -> Self {
+        RepoSettingsReconciler {
+            github,
+            ignored_repos: ignored_repos.to_vec(),
+        }
+    }
+
+    async fn get_managed_repos(&self, db: &Database, company: &Company) -> Result<BTreeMap<String, GithubRepo>> {
+        Ok(GithubRepos::get_from_db(db, company.id)
+            .await?
+            .into_iter()
+            .filter(|r| r.has_managed_settings() && !self.ignored_repos.contains(&r.github_id))
+            .map(|r| (r.name.to_string(), r))
+            .collect())
+    }
+}
+
+/// The state of the settings of a repository. The teams are by name, with whether they can push.
+fn repo_settings_state(protected: bool, teams: BTreeMap<String, bool>) -> serde_json::Value {
+    json!({ "protected": protected, "push_teams": teams })
+}
+
+#[async_trait]
+impl Reconciler for RepoSettingsReconciler {
+    fn name(&self) -> &str {
+        "repo-settings"
+    }
+
+    async fn desired_state(&self, db: &Database, company: &Company) -> Result<ReconcileState> {
+        let teams: BTreeMap<String, bool> = DEFAULT_REPO_TEAMS.iter().map(|t| (t.to_string(), true)).collect();
+
+        Ok(self
+            .get_managed_repos(db, company)
+            .await?
+            .into_keys()
+            .map(|name| (name, repo_settings_state(true, teams.clone())))
+            .collect())
+    }
+
+    async fn current_state(
+        &self,
+        db: &Database,
+        company: &Company,
+        desired: &ReconcileState,
+    ) -> Result<ReconcileState> {
+        let repos = self.get_managed_repos(db, company).await?;
+
+        let mut current = ReconcileState::new();
+        for name in desired.keys() {
+            let repo = match repos.get(name) {
+                Some(repo) => repo,
+                None => continue,
+            };
+
+            // Repositories without their default branch yet cannot be protected, there is
+            // nothing to change until they have one.
+            let protected = repo
+                .is_default_branch_protected(&self.github, company)
+                .await?
+                .unwrap_or(true);
+
+            let repo_teams = repo.list_teams(&self.github, company).await?;
+            let teams: BTreeMap<String, bool> = DEFAULT_REPO_TEAMS
+                .iter()
+                .map(|t| {
+                    (
+                        t.to_string(),
+                        repo_teams.get(*t).map(team_has_push_access).unwrap_or_default(),
+                    )
+                })
+                .collect();
+
+            current.insert(name.to_string(), repo_settings_state(protected, teams));
+        }
+
+        Ok(current)
+    }
+
+    async fn apply_change(&self, db: &Database, company: &Company, change: &ReconcileChange) -> Result<()> {
+        let (before, after) = match (&change.action, &change.before, &change.after) {
+            (ReconcileAction::Update, Some(before), Some(after)) => (before, after),
+            _ => bail!(
+                "we only update the settings of repositories, not {:?} them",
+                change.action
+            ),
+        };
+
+        let repos = self.get_managed_repos(db, company).await?;
+        let repo = match repos.get(&change.resource) {
+            Some(repo) => repo,
+            None => bail!("we do not manage the settings of repo `{}` anymore", change.resource),
+        };
+
+        if after["protected"] == json!(true) && before["protected"] != json!(true) {
+            repo.protect_default_branch(&self.github, company).await?;
+        }
+
+        for team in DEFAULT_REPO_TEAMS {
+            if after["push_teams"][team] == json!(true) && before["push_teams"][team] != json!(true) {
+                repo.give_team_push_access(&self.github, company, team).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
 #![allow(clippy::from_over_into)]
-use std::str::from_utf8;
+use std::{
+    collections::HashMap,
+    fmt,
+    str::{from_utf8, FromStr},
+};
 
 use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
@@ -10,26 +14,33 @@ use google_drive::traits::{DriveOps, FileOps, PermissionOps};
 use inflector::cases::kebabcase::to_kebab_case;
 use log::{debug, info, warn};
 use macros::db;
-use revai::{
-    traits::JobOps,
-    types::{SubmitJobMediaUrlOptions, SubmitJobMediaUrlOptionsAllOf},
-    Client as RevAI,
-};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slack_chat_api::{
     FormattedMessage, MessageAttachment, MessageBlock, MessageBlockText, MessageBlockType, MessageType,
 };
-use zoom_api::types::GetAccountCloudRecordingResponseMeetingsFilesFileType;
+use zoom_api::types::{
+    GetAccountCloudRecordingResponseMeetings, GetAccountCloudRecordingResponseMeetingsFilesFileType,
+};
 
 use crate::{
     airtable::AIRTABLE_RECORDED_MEETINGS_TABLE,
     companies::Company,
-    configs::User,
+    configs::{User, Users},
     core::UpdateAirtableRecord,
     db::Database,
-    schema::{recorded_meetings, users},
+    drive_uploads::{has_pending_drive_upload, upload_to_drive_or_enqueue, DriveUploadTarget},
+    microsoft_graph::{CallRecording, CallTranscript, MicrosoftGraph},
+    schema::{
+        companys, recorded_meeting_attendees, recorded_meeting_participants, recorded_meeting_transcript_segments,
+        recorded_meetings, users,
+    },
+    transcription::{
+        generate_callback_secret, get_transcription_provider, verify_callback_secret, RevAIJobNotification,
+        TranscriptSegment, TranscriptionProvider,
+    },
     utils::truncate,
+    zoom_storage::{prioritize_zoom_recordings, record_zoom_storage_usage},
 };
 
 /// The data type for a recorded meeting.
@@ -40,6 +51,7 @@ use crate::{
     match_on = {
         "google_event_id" = "String",
     },
+    airtable_read_only = ["chat_log_file", "transcript_file", "thumbnail"],
 }]
 #[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
 #[diesel(table_name = recorded_meetings)]
@@ -68,12 +80,78 @@ pub struct NewRecordedMeeting {
     pub transcript: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub transcript_id: String,
+    /// The secret Rev.ai sends back when the transcription job is done, so we know the
+    /// callback is for a job we submitted. This never leaves the database.
+    #[serde(default, skip_serializing)]
+    pub transcript_callback_secret: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub google_event_id: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub event_link: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub location: String,
+    /// The location the meeting happened at, matched from `location` by the config sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location_id: Option<i32>,
+    /// The email of the person who hosted the meeting, if we know who it was.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub host: String,
+    /// Tags set on the meeting in Airtable. Meetings tagged "keep" are never purged by the
+    /// retention policy.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// When the video and transcript were purged by the retention policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purged_at: Option<DateTime<Utc>>,
+    /// A short summary of the meeting, generated from the transcript.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub summary: String,
+    /// The action items from the meeting, generated from the transcript.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub action_items: Vec<String>,
+    /// How many times summarizing the meeting failed, we give up after a few.
+    #[serde(default)]
+    pub summary_attempts: i32,
+    /// The Airtable records of the attendees we could match to users.
+    /// link to another table in Airtable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub link_to_people: Vec<String>,
+    /// Who can see the meeting: `public-internal`, `restricted`, or `private`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub visibility: String,
+    /// The emails of the people who actually joined the meeting, from the Zoom participant
+    /// report. Empty if we do not know who joined.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub participants: Vec<String>,
+    /// The Slack channel of the meeting, set in Airtable. We post the action items of the
+    /// meeting there.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub slack_channel: String,
+    /// The Google Doc the transcript of the meeting was exported to.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub transcript_doc: String,
+    /// The chat log, transcript and a thumbnail of the recording, uploaded as attachments in
+    /// Airtable for its gallery views. We upload them once, and never write them with the
+    /// rest of the record. Airtable links to attachments expire, we only keep them to know
+    /// what we uploaded.
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub chat_log_file: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub transcript_file: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub thumbnail: String,
     /// The CIO company ID.
     #[serde(default)]
     pub cio_company_id: i32,
@@ -83,19 +161,753 @@ pub struct NewRecordedMeeting {
 #[async_trait]
 impl UpdateAirtableRecord<RecordedMeeting> for RecordedMeeting {
     async fn update_airtable_record(&mut self, record: RecordedMeeting) -> Result<()> {
-        if !record.transcript_id.is_empty() {
-            self.transcript_id = record.transcript_id;
+        // The tags and the Slack channel are set in Airtable.
+        self.tags = record.tags;
+        if self.slack_channel.is_empty() {
+            self.slack_channel = record.slack_channel;
         }
-        if !record.transcript.is_empty() {
-            self.transcript = record.transcript;
+
+        // Once the meeting is purged we don't want to bring the transcript back.
+        if self.purged_at.is_none() {
+            if !record.transcript_id.is_empty() {
+                self.transcript_id = record.transcript_id;
+            }
+            if !record.transcript.is_empty() {
+                self.transcript = record.transcript;
+            }
         }
 
         self.transcript = truncate(&self.transcript, 100000);
 
+        // The transcript of a restricted or private meeting stays out of Airtable, and so does
+        // what we generated from it.
+        if MeetingVisibility::from_field(&self.visibility) != MeetingVisibility::PublicInternal {
+            self.transcript = Default::default();
+            self.summary = Default::default();
+            self.action_items = Default::default();
+        }
+
+        Ok(())
+    }
+}
+
+/// The key of the calendar event extended property setting who can see the meeting's
+/// recording, like `meeting-visibility=restricted`. It can be a private or a shared property.
+pub static MEETING_VISIBILITY_PROPERTY: &str = "meeting-visibility";
+
+/// Who can see a recorded meeting.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MeetingVisibility {
+    /// Anyone in the company can see the recording and the transcript.
+    PublicInternal,
+    /// Only the attendees can see the recording, and the transcript is not uploaded to
+    /// Airtable.
+    Restricted,
+    /// Only the host can see the recording, the transcript is not uploaded to Airtable, and
+    /// the meeting is not announced in Slack.
+    Private,
+}
+
+impl Default for MeetingVisibility {
+    fn default() -> Self {
+        MeetingVisibility::PublicInternal
+    }
+}
+
+impl FromStr for MeetingVisibility {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "public" | "public-internal" => Ok(MeetingVisibility::PublicInternal),
+            "restricted" => Ok(MeetingVisibility::Restricted),
+            "private" => Ok(MeetingVisibility::Private),
+            _ => bail!("invalid meeting visibility `{}`", s),
+        }
+    }
+}
+
+impl fmt::Display for MeetingVisibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeetingVisibility::PublicInternal => write!(f, "public-internal"),
+            MeetingVisibility::Restricted => write!(f, "restricted"),
+            MeetingVisibility::Private => write!(f, "private"),
+        }
+    }
+}
+
+impl MeetingVisibility {
+    /// Parse the visibility saved on a meeting. Anything we do not know is treated as private,
+    /// so a typo never makes a meeting more visible.
+    pub fn from_field(s: &str) -> Self {
+        MeetingVisibility::from_str(s).unwrap_or_else(|e| {
+            warn!("{}, treating it as private", e);
+            MeetingVisibility::Private
+        })
+    }
+}
+
+/// A part of a recorded meeting's transcript spoken by a single speaker.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RecordedMeetingTranscriptSegment {
+    pub id: i32,
+    pub recorded_meeting_id: i32,
+    /// The position of the segment in the transcript.
+    pub position: i32,
+    pub speaker: String,
+    /// The offset from the start of the recording, in seconds.
+    pub start_offset: f64,
+    /// The offset of the end of the segment from the start of the recording, in seconds.
+    pub end_offset: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = recorded_meeting_transcript_segments)]
+struct NewRecordedMeetingTranscriptSegment {
+    recorded_meeting_id: i32,
+    position: i32,
+    speaker: String,
+    start_offset: f64,
+    end_offset: f64,
+    text: String,
+}
+
+/// Format an offset in seconds as `HH:MM:SS`.
+pub(crate) fn format_offset(offset: f64) -> String {
+    let seconds = offset.max(0.0) as i64;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, (seconds % 3600) / 60, seconds % 60)
+}
+
+/// Render transcript segments as text, with a line per segment.
+pub fn render_transcript_segments(segments: &[RecordedMeetingTranscriptSegment]) -> String {
+    segments
+        .iter()
+        .map(|s| format!("[{}] {}: {}", format_offset(s.start_offset), s.speaker, s.text))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Parse a cue timestamp, like `00:01:02.500` in VTT or `00:01:02,500` in SRT, into seconds.
+/// VTT timestamps can leave out the hours.
+fn parse_cue_timestamp(timestamp: &str) -> Option<f64> {
+    let timestamp = timestamp.trim().replace(',', ".");
+    if timestamp.is_empty() {
+        return None;
+    }
+
+    let mut seconds = 0.0;
+    for part in timestamp.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+
+    Some(seconds)
+}
+
+/// Remove the formatting tags, like `<b>` or `<00:01:02.000>`, from the text of a cue.
+fn strip_cue_tags(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => stripped.push(c),
+            _ => (),
+        }
+    }
+
+    stripped.trim().to_string()
+}
+
+/// Split the text of a cue into the speaker and what they said. VTT can mark the speaker with
+/// a voice tag, like `<v Jane>Hello`, and Zoom and Teams start the text with `Jane: `.
+fn split_cue_speaker(text: &str) -> (String, String) {
+    if let Some(rest) = text.strip_prefix("<v") {
+        if let Some((voice, said)) = rest.split_once('>') {
+            // Skip the classes of the voice tag, like `<v.loud Jane>`.
+            let speaker = voice.trim_start_matches(|c: char| c != ' ').trim();
+            return (speaker.to_string(), strip_cue_tags(said));
+        }
+    }
+
+    let text = strip_cue_tags(text);
+    match text.split_once(": ") {
+        Some((speaker, said)) if !speaker.trim().is_empty() && speaker.len() <= 64 => {
+            (speaker.trim().to_string(), said.trim().to_string())
+        }
+        _ => (String::new(), text),
+    }
+}
+
+/// Parse a VTT or SRT transcript into its timestamped cues. Blocks without a timing line, like
+/// the `WEBVTT` header or `NOTE` blocks, are skipped.
+pub fn parse_transcript_cues(transcript: &str) -> Vec<TranscriptSegment> {
+    let mut blocks: Vec<Vec<&str>> = vec![vec![]];
+    for line in transcript.lines().map(|l| l.trim()) {
+        if line.is_empty() {
+            blocks.push(vec![]);
+        } else if let Some(block) = blocks.last_mut() {
+            block.push(line);
+        }
+    }
+
+    let mut segments: Vec<TranscriptSegment> = Vec::new();
+    for block in blocks {
+        // The cue number in SRT, or the optional cue id in VTT, comes before the timing line.
+        let timing_line = match block.iter().position(|l| l.contains("-->")) {
+            Some(i) => i,
+            None => continue,
+        };
+        let (start, end) = match block[timing_line].split_once("-->") {
+            Some((start, end)) => (start, end.split_whitespace().next().unwrap_or_default()),
+            None => continue,
+        };
+        let (start_offset, end_offset) = match (parse_cue_timestamp(start), parse_cue_timestamp(end)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => continue,
+        };
+
+        let (speaker, text) = split_cue_speaker(&block[timing_line + 1..].join(" "));
+        if text.is_empty() {
+            continue;
+        }
+
+        segments.push(TranscriptSegment {
+            speaker,
+            start_offset,
+            end_offset,
+            text,
+        });
+    }
+
+    segments
+}
+
+/// Convert the cues of a transcript into plain text for the `transcript` column, with a line
+/// each time the speaker changes.
+pub fn transcript_cues_to_text(segments: &[TranscriptSegment]) -> String {
+    let mut lines: Vec<(String, String)> = Vec::new();
+    for segment in segments {
+        match lines.last_mut() {
+            Some((speaker, text)) if *speaker == segment.speaker => {
+                text.push(' ');
+                text.push_str(&segment.text);
+            }
+            _ => lines.push((segment.speaker.to_string(), segment.text.to_string())),
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|(speaker, text)| {
+            if speaker.is_empty() {
+                text
+            } else {
+                format!("{}: {}", speaker, text)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Get the plain text of a VTT transcript, and its cues. If we can't find any cues we keep the
+/// transcript as it is.
+fn transcript_from_vtt(vtt: &str) -> (String, Vec<TranscriptSegment>) {
+    let segments = parse_transcript_cues(vtt);
+    if segments.is_empty() {
+        return (vtt.to_string(), segments);
+    }
+
+    (transcript_cues_to_text(&segments), segments)
+}
+
+impl RecordedMeeting {
+    /// Get the transcript segments of the meeting, in order.
+    pub async fn transcript_segments(&self, db: &Database) -> Result<Vec<RecordedMeetingTranscriptSegment>> {
+        Ok(
+            recorded_meeting_transcript_segments::dsl::recorded_meeting_transcript_segments
+                .filter(recorded_meeting_transcript_segments::dsl::recorded_meeting_id.eq(self.id))
+                .order_by(recorded_meeting_transcript_segments::dsl::position)
+                .load_async::<RecordedMeetingTranscriptSegment>(db.pool())
+                .await?,
+        )
+    }
+
+    /// Replace the transcript segments of the meeting.
+    pub async fn set_transcript_segments(&self, db: &Database, segments: &[TranscriptSegment]) -> Result<()> {
+        diesel::delete(
+            recorded_meeting_transcript_segments::dsl::recorded_meeting_transcript_segments
+                .filter(recorded_meeting_transcript_segments::dsl::recorded_meeting_id.eq(self.id)),
+        )
+        .execute_async(db.pool())
+        .await?;
+
+        let new: Vec<NewRecordedMeetingTranscriptSegment> = segments
+            .iter()
+            .enumerate()
+            .map(|(i, s)| NewRecordedMeetingTranscriptSegment {
+                recorded_meeting_id: self.id,
+                position: i as i32,
+                speaker: s.speaker.to_string(),
+                start_offset: s.start_offset,
+                end_offset: s.end_offset,
+                text: s.text.to_string(),
+            })
+            .collect();
+        if !new.is_empty() {
+            diesel::insert_into(recorded_meeting_transcript_segments::table)
+                .values(new)
+                .execute_async(db.pool())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Render the transcript segments of the meeting back into text, for Airtable. Returns
+    /// an empty string if the meeting has no segments.
+    pub async fn render_transcript(&self, db: &Database) -> Result<String> {
+        Ok(render_transcript_segments(&self.transcript_segments(db).await?))
+    }
+
+    /// Fetch the transcript of the meeting from the transcription provider, with who said
+    /// what if the provider can tell the speakers apart. The meeting is not saved.
+    pub async fn fetch_transcript(&mut self, db: &Database, transcriber: &dyn TranscriptionProvider) -> Result<()> {
+        self.transcript = transcriber
+            .get_transcript(&self.transcript_id)
+            .await?
+            .trim()
+            .to_string();
+        info!(
+            "Fetched transcript to be stored. meeting: {} transcript: {}",
+            self.id, self.transcript_id
+        );
+        if self.transcript.is_empty() {
+            return Ok(());
+        }
+
+        match transcriber.get_transcript_segments(&self.transcript_id).await {
+            Ok(segments) if !segments.is_empty() => {
+                self.set_transcript_segments(db, &segments).await?;
+                self.transcript = self.render_transcript(db).await?;
+            }
+            Ok(_) => (),
+            Err(e) => {
+                info!(
+                    "getting transcript segments for id `{}` failed: {}",
+                    self.transcript_id, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the users who attended the meeting.
+    pub async fn attendee_users(&self, db: &Database) -> Result<Vec<User>> {
+        Ok(users::dsl::users
+            .inner_join(recorded_meeting_attendees::table)
+            .filter(recorded_meeting_attendees::dsl::recorded_meeting_id.eq(self.id))
+            .select(users::all_columns)
+            .order_by(users::dsl::username)
+            .load_async::<User>(db.pool())
+            .await?)
+    }
+
+    /// Match the attendees of the meeting to users, replacing the links we had before, and
+    /// link the meeting to their records in Airtable.
+    pub async fn set_attendee_users(&mut self, db: &Database, company: &Company) -> Result<()> {
+        let users = Users::get_from_db(db, company.id).await?;
+
+        let mut attendees: Vec<NewRecordedMeetingAttendee> = Vec::new();
+        let mut link_to_people: Vec<String> = Vec::new();
+        for email in &self.attendees {
+            let user = match find_user_by_email(&users.0, company, email) {
+                Some(user) => user,
+                None => continue,
+            };
+            if attendees.iter().any(|a| a.user_id == user.id) {
+                continue;
+            }
+
+            attendees.push(NewRecordedMeetingAttendee {
+                recorded_meeting_id: self.id,
+                user_id: user.id,
+                email: email.to_string(),
+            });
+            if !user.airtable_record_id.is_empty() {
+                link_to_people.push(user.airtable_record_id.to_string());
+            }
+        }
+
+        diesel::delete(
+            recorded_meeting_attendees::dsl::recorded_meeting_attendees
+                .filter(recorded_meeting_attendees::dsl::recorded_meeting_id.eq(self.id)),
+        )
+        .execute_async(db.pool())
+        .await?;
+        if !attendees.is_empty() {
+            diesel::insert_into(recorded_meeting_attendees::table)
+                .values(attendees)
+                .execute_async(db.pool())
+                .await?;
+        }
+
+        if self.link_to_people != link_to_people {
+            self.link_to_people = link_to_people;
+            self.update(db).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl User {
+    /// Get the recorded meetings the user attended, most recent first.
+    pub async fn recorded_meetings(&self, db: &Database) -> Result<Vec<RecordedMeeting>> {
+        Ok(recorded_meetings::dsl::recorded_meetings
+            .inner_join(recorded_meeting_attendees::table)
+            .filter(recorded_meeting_attendees::dsl::user_id.eq(self.id))
+            .select(recorded_meetings::all_columns)
+            .order_by(recorded_meetings::dsl::start_time.desc())
+            .load_async::<RecordedMeeting>(db.pool())
+            .await?)
+    }
+}
+
+/// A link from a recorded meeting to a user who attended it.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RecordedMeetingAttendee {
+    pub id: i32,
+    pub recorded_meeting_id: i32,
+    pub user_id: i32,
+    /// The email the user attended the meeting with.
+    pub email: String,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = recorded_meeting_attendees)]
+struct NewRecordedMeetingAttendee {
+    recorded_meeting_id: i32,
+    user_id: i32,
+    email: String,
+}
+
+/// Find the user an email belongs to. The email can be the user's email or recovery email, or
+/// their username or one of their aliases at either of the company's domains.
+pub(crate) fn find_user_by_email<'a>(users: &'a [User], company: &Company, email: &str) -> Option<&'a User> {
+    let email = email.trim().to_lowercase();
+    if email.is_empty() {
+        return None;
+    }
+
+    let (local, domain) = match email.split_once('@') {
+        Some((local, domain)) => (local, domain),
+        None => return None,
+    };
+    let is_company_domain = [&company.domain, &company.gsuite_domain]
+        .iter()
+        .any(|d| !d.is_empty() && d.to_lowercase() == domain);
+
+    users.iter().find(|u| {
+        u.email.to_lowercase() == email
+            || (!u.recovery_email.is_empty() && u.recovery_email.to_lowercase() == email)
+            || (is_company_domain
+                && (u.username.to_lowercase() == local || u.aliases.iter().any(|a| a.to_lowercase() == local)))
+    })
+}
+
+impl RecordedMeeting {
+    /// Return the invitees who never joined the meeting, empty if we do not know who joined.
+    pub fn no_shows(&self) -> Vec<String> {
+        if self.participants.is_empty() {
+            return vec![];
+        }
+
+        self.attendees
+            .iter()
+            .filter(|a| !self.participants.iter().any(|p| p.eq_ignore_ascii_case(a)))
+            .cloned()
+            .collect()
+    }
+}
+
+/// How often an invitee of a recurring meeting joined it.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InviteeAttendance {
+    pub email: String,
+    pub invited: usize,
+    pub attended: usize,
+}
+
+/// The attendance of a recurring meeting, over the occurrences we know who joined.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RecurringMeetingAttendance {
+    pub name: String,
+    pub meetings: usize,
+    /// The share of the invitations that were attended, from 0 to 1.
+    pub attendance_rate: f64,
+    /// The invitees, the ones who attend the least first.
+    pub invitees: Vec<InviteeAttendance>,
+}
+
+/// Compute the attendance of the recurring meetings, the least attended first. Occurrences
+/// are grouped by name, and the ones we do not know who joined are skipped.
+pub fn recurring_meeting_attendance(meetings: &[RecordedMeeting]) -> Vec<RecurringMeetingAttendance> {
+    let mut by_name: HashMap<String, Vec<&RecordedMeeting>> = HashMap::new();
+    for meeting in meetings {
+        if meeting.is_recurring && !meeting.participants.is_empty() {
+            by_name.entry(meeting.name.to_string()).or_default().push(meeting);
+        }
+    }
+
+    let mut report: Vec<RecurringMeetingAttendance> = by_name
+        .into_iter()
+        .map(|(name, occurrences)| {
+            let mut invitees: Vec<InviteeAttendance> = Vec::new();
+            for meeting in &occurrences {
+                let no_shows = meeting.no_shows();
+                for email in &meeting.attendees {
+                    let email = email.trim().to_lowercase();
+                    let attended = !no_shows.iter().any(|n| n.eq_ignore_ascii_case(&email));
+                    match invitees.iter_mut().find(|i| i.email == email) {
+                        Some(invitee) => {
+                            invitee.invited += 1;
+                            invitee.attended += attended as usize;
+                        }
+                        None => invitees.push(InviteeAttendance {
+                            email,
+                            invited: 1,
+                            attended: attended as usize,
+                        }),
+                    }
+                }
+            }
+            invitees.sort_by(|a, b| {
+                (a.attended * b.invited)
+                    .cmp(&(b.attended * a.invited))
+                    .then_with(|| a.email.cmp(&b.email))
+            });
+
+            let invited: usize = invitees.iter().map(|i| i.invited).sum();
+            let attended: usize = invitees.iter().map(|i| i.attended).sum();
+            RecurringMeetingAttendance {
+                name,
+                meetings: occurrences.len(),
+                attendance_rate: if invited > 0 {
+                    attended as f64 / invited as f64
+                } else {
+                    0.0
+                },
+                invitees,
+            }
+        })
+        .collect();
+    report.sort_by(|a, b| {
+        a.attendance_rate
+            .partial_cmp(&b.attendance_rate)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    report
+}
+
+/// Generate the attendance report of the company's recurring meetings over the last days.
+pub async fn generate_recurring_meeting_attendance_report(
+    db: &Database,
+    company: &Company,
+    days: i64,
+) -> Result<Vec<RecurringMeetingAttendance>> {
+    let from = Utc::now() - Duration::days(days.max(1));
+    let meetings = recorded_meetings::dsl::recorded_meetings
+        .filter(recorded_meetings::dsl::cio_company_id.eq(company.id))
+        .filter(recorded_meetings::dsl::is_recurring.eq(true))
+        .filter(recorded_meetings::dsl::start_time.ge(from))
+        .load_async::<RecordedMeeting>(db.pool())
+        .await?;
+
+    Ok(recurring_meeting_attendance(&meetings))
+}
+
+/// One time someone joined a recorded meeting, from the Zoom participant report.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RecordedMeetingParticipant {
+    pub id: i32,
+    pub recorded_meeting_id: i32,
+    pub name: String,
+    /// The email they joined with, empty for guests who did not sign in.
+    pub email: String,
+    pub join_time: Option<DateTime<Utc>>,
+    pub leave_time: Option<DateTime<Utc>>,
+    /// How long they stayed, in seconds.
+    pub duration: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = recorded_meeting_participants)]
+struct NewRecordedMeetingParticipant {
+    recorded_meeting_id: i32,
+    name: String,
+    email: String,
+    join_time: Option<DateTime<Utc>>,
+    leave_time: Option<DateTime<Utc>>,
+    duration: i32,
+}
+
+impl RecordedMeeting {
+    /// Get the times people joined the meeting, in the order they joined.
+    pub async fn participant_sessions(&self, db: &Database) -> Result<Vec<RecordedMeetingParticipant>> {
+        Ok(recorded_meeting_participants::dsl::recorded_meeting_participants
+            .filter(recorded_meeting_participants::dsl::recorded_meeting_id.eq(self.id))
+            .order_by((
+                recorded_meeting_participants::dsl::join_time,
+                recorded_meeting_participants::dsl::id,
+            ))
+            .load_async::<RecordedMeetingParticipant>(db.pool())
+            .await?)
+    }
+
+    /// Replace the times people joined the meeting with the ones in its participant report.
+    async fn set_participant_sessions(&self, db: &Database, report: &[ZoomParticipant]) -> Result<()> {
+        diesel::delete(
+            recorded_meeting_participants::dsl::recorded_meeting_participants
+                .filter(recorded_meeting_participants::dsl::recorded_meeting_id.eq(self.id)),
+        )
+        .execute_async(db.pool())
+        .await?;
+
+        let new: Vec<NewRecordedMeetingParticipant> = report
+            .iter()
+            .map(|p| NewRecordedMeetingParticipant {
+                recorded_meeting_id: self.id,
+                name: p.name.trim().to_string(),
+                email: p.user_email.trim().to_lowercase(),
+                join_time: p.join_time,
+                leave_time: p.leave_time,
+                duration: p.duration.max(0),
+            })
+            .collect();
+        if !new.is_empty() {
+            diesel::insert_into(recorded_meeting_participants::table)
+                .values(new)
+                .execute_async(db.pool())
+                .await?;
+        }
+
         Ok(())
     }
 }
 
+/// How engaged the people who joined a meeting were.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct MeetingEngagement {
+    pub name: String,
+    pub start_time: DateTime<Utc>,
+    /// The people who joined, counting guests by name.
+    pub participants: usize,
+    /// How long people stayed on average, over all the times they joined, in minutes.
+    pub average_minutes: f64,
+    /// The share of the people who stayed for most of the meeting, from 0 to 1.
+    pub stayed_rate: f64,
+    /// The most people in the meeting at the same time.
+    pub peak_participants: usize,
+}
+
+/// The share of a meeting someone has to attend to count as having stayed for it.
+static STAYED_SHARE: f64 = 0.8;
+
+/// Compute the engagement of a meeting from the times people joined it.
+pub fn meeting_engagement(meeting: &RecordedMeeting, sessions: &[RecordedMeetingParticipant]) -> MeetingEngagement {
+    // Guests who did not sign in only have a name.
+    let mut seconds_by_person: HashMap<String, i64> = HashMap::new();
+    for session in sessions {
+        let person = if session.email.is_empty() {
+            session.name.trim().to_lowercase()
+        } else {
+            session.email.to_string()
+        };
+        *seconds_by_person.entry(person).or_default() += session.duration as i64;
+    }
+
+    let meeting_seconds = (meeting.end_time - meeting.start_time).num_seconds().max(0);
+    let stayed = seconds_by_person
+        .values()
+        .filter(|s| meeting_seconds > 0 && **s as f64 >= meeting_seconds as f64 * STAYED_SHARE)
+        .count();
+    let total_seconds: i64 = seconds_by_person.values().sum();
+
+    // Walk the joins and leaves in order to find how many people were there at once, leaving
+    // before joining when both happen at the same time.
+    let mut changes: Vec<(DateTime<Utc>, i64)> = Vec::new();
+    for session in sessions {
+        if let (Some(join), Some(leave)) = (session.join_time, session.leave_time) {
+            changes.push((join, 1));
+            changes.push((leave, -1));
+        }
+    }
+    changes.sort();
+    let mut current = 0;
+    let mut peak = 0;
+    for (_, change) in changes {
+        current += change;
+        peak = peak.max(current);
+    }
+
+    let participants = seconds_by_person.len();
+    MeetingEngagement {
+        name: meeting.name.to_string(),
+        start_time: meeting.start_time,
+        participants,
+        average_minutes: if participants > 0 {
+            total_seconds as f64 / participants as f64 / 60.0
+        } else {
+            0.0
+        },
+        stayed_rate: if participants > 0 {
+            stayed as f64 / participants as f64
+        } else {
+            0.0
+        },
+        peak_participants: peak as usize,
+    }
+}
+
+/// Generate the engagement report of the company's meetings over the last days that at least
+/// a number of people joined, the most recent first. Meetings without a participant report
+/// are skipped.
+pub async fn generate_meeting_engagement_report(
+    db: &Database,
+    company: &Company,
+    days: i64,
+    min_participants: usize,
+) -> Result<Vec<MeetingEngagement>> {
+    let from = Utc::now() - Duration::days(days.max(1));
+    let meetings = recorded_meetings::dsl::recorded_meetings
+        .filter(recorded_meetings::dsl::cio_company_id.eq(company.id))
+        .filter(recorded_meetings::dsl::start_time.ge(from))
+        .order_by(recorded_meetings::dsl::start_time.desc())
+        .load_async::<RecordedMeeting>(db.pool())
+        .await?;
+
+    let mut report: Vec<MeetingEngagement> = Vec::new();
+    for meeting in meetings {
+        let sessions = meeting.participant_sessions(db).await?;
+        if sessions.is_empty() {
+            continue;
+        }
+
+        let engagement = meeting_engagement(&meeting, &sessions);
+        if engagement.participants >= min_participants {
+            report.push(engagement);
+        }
+    }
+
+    Ok(report)
+}
+
 /// Convert the recorded meeting into a Slack message.
 impl From<NewRecordedMeeting> for FormattedMessage {
     fn from(item: NewRecordedMeeting) -> Self {
@@ -207,33 +1019,132 @@ impl RecordedMeeting {
     }
 }
 
-/// Sync the recorded meetings from zoom.
-pub async fn refresh_zoom_recorded_meetings(db: &Database, company: &Company) -> Result<()> {
-    let zoom_auth = company.authenticate_zoom(db).await;
-    if let Err(e) = zoom_auth {
-        if e.to_string().contains("no token") {
-            // Return early, this company does not use Zoom.
-            return Ok(());
+/// The number of days of Zoom recordings we look at if the company has not configured it.
+static DEFAULT_ZOOM_SYNC_LOOKBACK_DAYS: i32 = 30;
+
+/// A recorded meeting we could not sync, and why.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SyncFailure {
+    pub meeting: String,
+    pub error: String,
+}
+
+/// The outcome of a sync of recorded meetings. A meeting that fails to sync is added to the
+/// failures, and the sync goes on with the next one.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SyncReport {
+    pub synced: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<SyncFailure>,
+}
+
+impl SyncReport {
+    fn add_failure(&mut self, meeting: &str, error: anyhow::Error) {
+        warn!("syncing recorded meeting `{}` failed: {}", meeting, error);
+        self.failures.push(SyncFailure {
+            meeting: meeting.to_string(),
+            error: error.to_string(),
+        });
+    }
+}
+
+impl fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} synced, {} failed", self.synced, self.failures.len())?;
+        for failure in &self.failures {
+            write!(f, "\n- `{}`: {}", failure.meeting, failure.error)?;
         }
 
-        bail!("authenticating zoom failed: {}", e);
+        Ok(())
     }
+}
 
-    let mut zoom = zoom_auth?;
+/// Return where the Zoom sync should start looking for recordings: right after the last
+/// recording we synced, but never further back than the lookback window.
+fn zoom_sync_from(cursor: Option<DateTime<Utc>>, lookback_days: i32, now: DateTime<Utc>) -> DateTime<Utc> {
+    let lookback_days = if lookback_days > 0 {
+        lookback_days
+    } else {
+        DEFAULT_ZOOM_SYNC_LOOKBACK_DAYS
+    };
+    let window_start = now - Duration::days(lookback_days.into());
 
-    // List all the recorded meetings.
-    let recordings = zoom
-        .cloud_recording()
+    match cursor {
+        Some(cursor) if cursor > window_start => cursor,
+        _ => window_start,
+    }
+}
+
+/// Return where the Zoom sync cursor can move to after a sync: the start of the newest
+/// recording that synced, as long as every older recording synced too. The recordings are
+/// synced biggest first, so this is not the last one that went through.
+fn zoom_sync_cursor_after(results: &[(DateTime<Utc>, bool)]) -> Option<DateTime<Utc>> {
+    let first_failure = results.iter().filter(|(_, synced)| !synced).map(|(t, _)| *t).min();
+
+    results
+        .iter()
+        .filter(|(t, synced)| *synced && first_failure.map(|f| *t < f).unwrap_or(true))
+        .map(|(t, _)| *t)
+        .max()
+}
+
+/// Save the start time of the newest Zoom recording we synced for the company.
+async fn set_zoom_sync_cursor(db: &Database, company: &Company, cursor: DateTime<Utc>) -> Result<()> {
+    diesel::update(companys::dsl::companys.filter(companys::dsl::id.eq(company.id)))
+        .set(companys::dsl::zoom_sync_cursor.eq(Some(cursor)))
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Sync the recorded meetings from zoom.
+pub async fn refresh_zoom_recorded_meetings(db: &Database, company: &Company) -> Result<SyncReport> {
+    let mut report = SyncReport::default();
+
+    let zoom_auth = company.authenticate_zoom(db).await;
+    if let Err(e) = zoom_auth {
+        if e.to_string().contains("no token") {
+            // Return early, this company does not use Zoom.
+            return Ok(report);
+        }
+
+        bail!("authenticating zoom failed: {}", e);
+    }
+
+    let mut zoom = zoom_auth?;
+
+    // List all the recorded meetings of the lookback window, they are what takes up the cloud
+    // storage of the account.
+    let now = Utc::now();
+    let from = zoom_sync_from(None, company.zoom_sync_lookback_days, now);
+    let mut recordings = zoom
+        .cloud_recording()
         .get_all_account(
             "me", // we set account to me since the autorized user is an admin
-            Some(Utc::now().checked_sub_signed(Duration::days(30)).unwrap()), // from: the max date range is a month.
-            Some(Utc::now()), // to
+            Some(from),
+            Some(now), // to
         )
         .await?;
 
+    // We need the zoom token to get the storage of the plan and download the URL.
+    let at = zoom.refresh_access_token().await?;
+
+    if let Err(e) = record_zoom_storage_usage(db, company, &at.access_token, &recordings).await {
+        warn!(
+            "recording the zoom cloud storage usage of `{}` failed: {}",
+            company.name, e
+        );
+    }
+
+    // Skip the recordings we already synced.
+    if let Some(cursor) = company.zoom_sync_cursor {
+        recordings.retain(|r| r.start_time.map(|t| t > cursor).unwrap_or(true));
+    }
+
     if recordings.is_empty() {
         // Return early.
-        return Ok(());
+        return Ok(report);
     }
 
     // Initialize the Google Drive client.
@@ -248,154 +1159,1013 @@ pub async fn refresh_zoom_recorded_meetings(db: &Database, company: &Company) ->
         .create_folder(&shared_drive.id, "", "zoom_recordings")
         .await?;
 
-    // We need the zoom token to download the URL.
-    let at = zoom.refresh_access_token().await?;
-
+    // Move the biggest recordings first, they free up the most storage in Zoom. If one fails
+    // the cursor stays before it and we try again on the next sync.
+    prioritize_zoom_recordings(&mut recordings);
+    let mut results: Vec<(DateTime<Utc>, bool)> = Vec::new();
     for meeting in recordings {
-        if meeting.topic.is_empty() {
-            // Continue early.
-            warn!("meeting must have a topic: {:?}", meeting);
-            continue;
+        let result = ingest_zoom_recorded_meeting(
+            db,
+            company,
+            &zoom,
+            &drive,
+            &shared_drive.id,
+            &recordings_folder_id,
+            &at.access_token,
+            &at.access_token,
+            &meeting,
+        )
+        .await;
+        if let Some(start_time) = meeting.start_time {
+            results.push((start_time, result.is_ok()));
         }
 
-        // Create the folder for our zoom recordings.
-        let start_folder_id = drive
-            .files()
-            .create_folder(
-                &shared_drive.id,
-                &recordings_folder_id,
-                &meeting.start_time.unwrap().to_string(),
-            )
+        match result {
+            Ok(_) => report.synced += 1,
+            Err(e) => report.add_failure(&meeting.topic, e),
+        }
+    }
+
+    // Only move the cursor up to the first failure.
+    if let Some(cursor) = zoom_sync_cursor_after(&results) {
+        if company.zoom_sync_cursor.map(|c| cursor > c).unwrap_or(true) {
+            set_zoom_sync_cursor(db, company, cursor).await?;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Sync a single recorded meeting from zoom. This is used when Zoom notifies us that a
+/// recording has completed, so we do not have to wait for the next full sync.
+/// The download token is the one sent with the webhook, if we have one.
+pub async fn refresh_zoom_recorded_meeting(
+    db: &Database,
+    company: &Company,
+    meeting: &GetAccountCloudRecordingResponseMeetings,
+    download_token: &str,
+) -> Result<Option<RecordedMeeting>> {
+    let mut zoom = company.authenticate_zoom(db).await?;
+
+    // Initialize the Google Drive client.
+    let drive = company.authenticate_google_drive(db).await?;
+
+    // Get the shared drive.
+    let shared_drive = drive.drives().get_by_name("Automated Documents").await?;
+
+    // Create the folder for our zoom recordings.
+    let recordings_folder_id = drive
+        .files()
+        .create_folder(&shared_drive.id, "", "zoom_recordings")
+        .await?;
+
+    // Prefer the token sent with the webhook to download, otherwise use our own access token.
+    // The participant report needs our own.
+    let api_access_token = zoom.refresh_access_token().await?.access_token;
+    let access_token = if download_token.is_empty() {
+        api_access_token.to_string()
+    } else {
+        download_token.to_string()
+    };
+
+    ingest_zoom_recorded_meeting(
+        db,
+        company,
+        &zoom,
+        &drive,
+        &shared_drive.id,
+        &recordings_folder_id,
+        &access_token,
+        &api_access_token,
+        meeting,
+    )
+    .await
+}
+
+/// The page of a Zoom meeting participant report.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ZoomParticipantsReport {
+    #[serde(default)]
+    participants: Vec<ZoomParticipant>,
+    #[serde(default)]
+    next_page_token: String,
+}
+
+/// One time someone joined a Zoom meeting. People who drop and come back are in the report
+/// once for each time they joined.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ZoomParticipant {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    user_email: String,
+    #[serde(default)]
+    join_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    leave_time: Option<DateTime<Utc>>,
+    /// How long they stayed, in seconds.
+    #[serde(default)]
+    duration: i32,
+}
+
+/// Encode a meeting UUID for a Zoom API path. UUIDs that start with a slash or contain a
+/// double slash must be encoded twice.
+fn encode_zoom_meeting_uuid(uuid: &str) -> String {
+    let encoded = uuid.replace('/', "%2F").replace('+', "%2B").replace('=', "%3D");
+    if uuid.starts_with('/') || uuid.contains("//") {
+        encoded.replace('%', "%25")
+    } else {
+        encoded
+    }
+}
+
+/// Get the participant report of a past Zoom meeting, every time someone joined it.
+async fn zoom_meeting_participant_report(access_token: &str, meeting_uuid: &str) -> Result<Vec<ZoomParticipant>> {
+    let client = reqwest::Client::new();
+    let mut participants: Vec<ZoomParticipant> = Vec::new();
+    let mut next_page_token = String::new();
+    loop {
+        let report: ZoomParticipantsReport = client
+            .get(&format!(
+                "https://api.zoom.us/v2/report/meetings/{}/participants",
+                encode_zoom_meeting_uuid(meeting_uuid)
+            ))
+            .bearer_auth(access_token)
+            .query(&[("page_size", "300"), ("next_page_token", &next_page_token)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
             .await?;
 
-        let mut transcript = String::new();
-        let mut transcript_id = String::new();
-        let mut video = String::new();
-        let mut video_html_link = String::new();
-        let mut chat_log_link = String::new();
-        let mut chat_log = String::new();
-        let mut end_time = Utc::now();
-
-        // Move the recordings to the Google Drive folder.
-        for recording in &meeting.recording_files {
-            let file_type = recording.file_type.as_ref().unwrap();
-            if *file_type == GetAccountCloudRecordingResponseMeetingsFilesFileType::Noop
-                || *file_type == GetAccountCloudRecordingResponseMeetingsFilesFileType::FallthroughString
-            {
+        participants.extend(report.participants);
+
+        if report.next_page_token.is_empty() {
+            break;
+        }
+        next_page_token = report.next_page_token;
+    }
+
+    Ok(participants)
+}
+
+/// Get the emails of the people who joined a meeting from its participant report. Guests who
+/// joined without signing in have no email, and are left out.
+fn zoom_participant_emails(report: &[ZoomParticipant]) -> Vec<String> {
+    let mut emails: Vec<String> = Vec::new();
+    for participant in report {
+        let email = participant.user_email.trim().to_lowercase();
+        if !email.is_empty() && !emails.contains(&email) {
+            emails.push(email);
+        }
+    }
+
+    emails
+}
+
+/// Find the event on the host's calendar a Zoom meeting was for, from the meeting id in its
+/// location or description.
+async fn find_zoom_meeting_event(
+    company: &Company,
+    host_email: &str,
+    meeting_id: &str,
+    start_time: DateTime<Utc>,
+) -> Result<Option<google_calendar::types::Event>> {
+    let gcal = company
+        .authenticate_google_calendar_with_service_account(host_email)
+        .await?;
+    let events = gcal
+        .events()
+        .list_all(
+            host_email, // Calendar id.
+            "",         // iCalID
+            0,          // Max attendees, set to 0 to ignore.
+            google_calendar::types::OrderBy::StartTime,
+            &[],                                             // private_extended_property
+            meeting_id,                                      // q
+            &[],                                             // shared_extended_property
+            false,                                           // show_deleted
+            false,                                           // show_hidden_invitations
+            true,                                            // single_events
+            &(start_time + Duration::hours(1)).to_rfc3339(), // time_max
+            &(start_time - Duration::hours(1)).to_rfc3339(), // time_min
+            "",                                              // time_zone
+            "",                                              // updated_min
+        )
+        .await?;
+
+    // People join a bit early or late, so take the event that starts the closest.
+    Ok(events.into_iter().min_by_key(|e| {
+        e.start
+            .as_ref()
+            .and_then(|s| s.date_time)
+            .map(|t| (t - start_time).num_seconds().abs())
+            .unwrap_or(i64::MAX)
+    }))
+}
+
+/// Move the recordings for a zoom meeting into Google Drive and save the meeting in the
+/// database. The recordings are deleted from Zoom once they are in Google Drive.
+#[allow(clippy::too_many_arguments)]
+async fn ingest_zoom_recorded_meeting(
+    db: &Database,
+    company: &Company,
+    zoom: &zoom_api::Client,
+    drive: &google_drive::Client,
+    shared_drive_id: &str,
+    recordings_folder_id: &str,
+    access_token: &str,
+    api_access_token: &str,
+    meeting: &GetAccountCloudRecordingResponseMeetings,
+) -> Result<Option<RecordedMeeting>> {
+    if meeting.topic.is_empty() {
+        // Return early.
+        warn!("meeting must have a topic: {:?}", meeting);
+        return Ok(None);
+    }
+
+    let start_time = match meeting.start_time {
+        Some(start_time) => start_time,
+        None => bail!("zoom meeting `{}` has no start time", meeting.topic),
+    };
+
+    // Create the folder for our zoom recordings.
+    let start_folder_id = drive
+        .files()
+        .create_folder(shared_drive_id, recordings_folder_id, &start_time.to_string())
+        .await?;
+
+    let mut transcript = String::new();
+    let mut transcript_id = String::new();
+    let mut transcript_segments: Vec<TranscriptSegment> = Vec::new();
+    let mut video = String::new();
+    let mut video_html_link = String::new();
+    let mut chat_log_link = String::new();
+    let mut chat_log = String::new();
+    let mut end_time = Utc::now();
+
+    // Move the recordings to the Google Drive folder.
+    for recording in &meeting.recording_files {
+        let file_type = match recording.file_type.as_ref() {
+            Some(file_type) => file_type,
+            None => {
                 // Continue early.
-                warn!("zoom got bad recording file type: {:?}", recording);
+                warn!("zoom got recording without a file type: {:?}", recording);
                 continue;
             }
+        };
+        if *file_type == GetAccountCloudRecordingResponseMeetingsFilesFileType::Noop
+            || *file_type == GetAccountCloudRecordingResponseMeetingsFilesFileType::FallthroughString
+        {
+            // Continue early.
+            warn!("zoom got bad recording file type: {:?}", recording);
+            continue;
+        }
 
-            if let Some(status) = &recording.status {
-                if *status != zoom_api::types::GetAccountCloudRecordingResponseMeetingsFilesStatus::Completed {
-                    // Continue early.
-                    warn!("zoom got bad recording status: {:?}", recording);
-                    continue;
-                }
+        if let Some(status) = &recording.status {
+            if *status != zoom_api::types::GetAccountCloudRecordingResponseMeetingsFilesStatus::Completed {
+                // Continue early.
+                warn!("zoom got bad recording status: {:?}", recording);
+                continue;
             }
+        }
 
-            // Download the file to memory.
-            info!(
-                "zoom meeting {} -> downloading recording {}... This might take a bit...",
-                meeting.topic, recording.download_url,
-            );
-            let resp = reqwest::get(&format!("{}?access_token={}", recording.download_url, at.access_token)).await?;
-            let b = resp.bytes().await?;
+        // Download the file to memory.
+        info!(
+            "zoom meeting {} -> downloading recording {}... This might take a bit...",
+            meeting.topic, recording.download_url,
+        );
+        let resp = reqwest::get(&format!("{}?access_token={}", recording.download_url, access_token))
+            .await?
+            .error_for_status()?;
+        let b = resp.bytes().await?;
+
+        // Get the mime type.
+        let mime_type = file_type.get_mime_type();
+
+        // Upload the recording to Google drive.
+        info!(
+            "zoom uploading meeting {} recording to Google drive... This might take a bit...",
+            meeting.topic
+        );
+        // If the upload fails it is retried by the upload queue, which deletes the recording
+        // from Zoom once it is uploaded. Until then Zoom still has it.
+        let field = match *file_type {
+            GetAccountCloudRecordingResponseMeetingsFilesFileType::Mp4 => "video",
+            GetAccountCloudRecordingResponseMeetingsFilesFileType::Chat => "chat_log_link",
+            _ => "",
+        };
+        let link = upload_to_drive_or_enqueue(
+            db,
+            company.id,
+            drive,
+            shared_drive_id,
+            &start_folder_id,
+            &format!(
+                "{}{}",
+                to_kebab_case(meeting.topic.replace("'s", "").trim()),
+                file_type.to_extension()
+            ),
+            &mime_type,
+            &b,
+            &DriveUploadTarget::recorded_meeting(&meeting.uuid, field)
+                .deleting_zoom_recording(&recording.meeting_id, &recording.id),
+        )
+        .await?;
 
-            // Get the mime type.
-            let mime_type = file_type.get_mime_type();
+        match *file_type {
+            GetAccountCloudRecordingResponseMeetingsFilesFileType::Mp4 => {
+                video = link.to_string();
+                // TODO: get a better link
+                video_html_link = video.to_string();
+                end_time = DateTime::parse_from_rfc3339(&recording.recording_end)?.with_timezone(&Utc);
+            }
+            GetAccountCloudRecordingResponseMeetingsFilesFileType::Transcript => {
+                let (text, segments) = transcript_from_vtt(from_utf8(&b)?);
+                transcript = text;
+                transcript_segments = segments;
+                transcript_id = recording.id.to_string();
+            }
+            GetAccountCloudRecordingResponseMeetingsFilesFileType::Chat => {
+                chat_log_link = link.to_string();
+                chat_log = from_utf8(&b)?.to_string();
+            }
+            _ => (),
+        }
 
-            // Upload the recording to Google drive.
+        if link.is_empty() {
             info!(
-                "zoom uploading meeting {} recording to Google drive... This might take a bit...",
+                "zoom meeting {} recording is in the upload queue, keeping it in Zoom until it is uploaded",
                 meeting.topic
             );
-            let drive_file = drive
-                .files()
-                .create_or_update(
-                    &shared_drive.id,
-                    &start_folder_id,
-                    &format!(
-                        "{}{}",
-                        to_kebab_case(meeting.topic.replace("'s", "").trim()),
-                        file_type.to_extension()
-                    ),
-                    &mime_type,
-                    &b,
-                )
-                .await?;
+            continue;
+        }
 
-            match *file_type {
-                GetAccountCloudRecordingResponseMeetingsFilesFileType::Mp4 => {
-                    video = format!("https://drive.google.com/open?id={}", drive_file.id);
-                    // TODO: get a better link
-                    video_html_link = video.to_string();
-                    end_time = DateTime::parse_from_rfc3339(&recording.recording_end)?.with_timezone(&Utc);
-                }
-                GetAccountCloudRecordingResponseMeetingsFilesFileType::Transcript => {
-                    transcript = from_utf8(&b)?.to_string();
-                    transcript_id = recording.id.to_string();
-                }
-                GetAccountCloudRecordingResponseMeetingsFilesFileType::Chat => {
-                    chat_log_link = format!("https://drive.google.com/open?id={}", drive_file.id);
-                    chat_log = from_utf8(&b)?.to_string();
+        zoom.cloud_recording()
+            .recording_delete_one(
+                &recording.meeting_id,
+                &recording.id,
+                zoom_api::types::RecordingDeleteAction::Trash,
+            )
+            .await?;
+        info!(
+            "zoom deleted meeting {} recording in Zoom since it is now in Google drive at {}",
+            meeting.topic, link
+        );
+    }
+
+    let host = users::dsl::users
+        .filter(
+            users::dsl::zoom_id
+                .eq(meeting.host_id.to_string())
+                .and(users::dsl::cio_company_id.eq(company.id)),
+        )
+        .first_async::<User>(db.pool())
+        .await?;
+
+    // Cross-reference who was invited on the calendar with who actually joined. Neither is
+    // worth failing the sync over.
+    let mut attendees = vec![host.email.to_string()];
+    let mut is_recurring = false;
+    match find_zoom_meeting_event(company, &host.email, &meeting.id.to_string(), start_time).await {
+        Ok(Some(event)) => {
+            for attendee in event.attendees.iter().filter(|a| !a.resource) {
+                if !attendees.iter().any(|a| a.eq_ignore_ascii_case(&attendee.email)) {
+                    attendees.push(attendee.email.to_string());
                 }
-                _ => (),
             }
+            is_recurring = !event.recurring_event_id.is_empty();
+        }
+        Ok(None) => (),
+        Err(e) => warn!(
+            "finding the calendar event of zoom meeting `{}` failed: {}",
+            meeting.topic, e
+        ),
+    }
+    let participant_report = zoom_meeting_participant_report(api_access_token, &meeting.uuid)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(
+                "getting the participants of zoom meeting `{}` failed: {}",
+                meeting.topic, e
+            );
+            vec![]
+        });
+    let participants = zoom_participant_emails(&participant_report);
+
+    // Create the meeting in the database.
+    let m = NewRecordedMeeting {
+        name: meeting.topic.trim().to_string(),
+        description: "".to_string(),
+        start_time,
+        end_time,
+        video,
+        chat_log_link,
+        chat_log,
+        is_recurring,
+        attendees,
+        transcript,
+        transcript_id,
+        transcript_callback_secret: Default::default(),
+        location: format!("Meeting hosted by {}", host.full_name()),
+        location_id: None,
+        host: host.email.to_string(),
+        tags: Default::default(),
+        purged_at: None,
+        summary: Default::default(),
+        action_items: Default::default(),
+        summary_attempts: 0,
+        link_to_people: Default::default(),
+        visibility: MeetingVisibility::default().to_string(),
+        participants,
+        slack_channel: Default::default(),
+        transcript_doc: Default::default(),
+        chat_log_file: Default::default(),
+        transcript_file: Default::default(),
+        thumbnail: Default::default(),
+        // We save the meeting ID here, even tho its in Zoom.
+        // TODO: clean this up.
+        google_event_id: meeting.uuid.to_string(),
+        event_link: video_html_link,
+        cio_company_id: company.id,
+    };
+    let mut new = m.upsert(db).await?;
+    if !transcript_segments.is_empty() {
+        new.set_transcript_segments(db, &transcript_segments).await?;
+    }
+    if !participant_report.is_empty() {
+        new.set_participant_sessions(db, &participant_report).await?;
+    }
+    new.set_attendee_users(db, company).await?;
+    new.send_slack_notification(db, company).await?;
+
+    Ok(Some(new))
+}
+
+/// Sync the recorded meetings from Microsoft Teams. The recordings and transcripts are
+/// copied into Google Drive next to our Zoom recordings.
+pub async fn refresh_teams_recorded_meetings(db: &Database, company: &Company) -> Result<()> {
+    let graph = match MicrosoftGraph::new_from_env().await? {
+        Some(graph) => graph,
+        // Return early, this company does not use Teams.
+        None => return Ok(()),
+    };
+
+    // Initialize the Google Drive client.
+    let drive = company.authenticate_google_drive(db).await?;
+
+    // Get the shared drive.
+    let shared_drive = drive.drives().get_by_name("Automated Documents").await?;
 
-            zoom.cloud_recording()
-                .recording_delete_one(
-                    &recording.meeting_id,
-                    &recording.id,
-                    zoom_api::types::RecordingDeleteAction::Trash,
+    // Create the folder for our teams recordings.
+    let recordings_folder_id = drive
+        .files()
+        .create_folder(&shared_drive.id, "", "teams_recordings")
+        .await?;
+
+    let since = Utc::now().checked_sub_signed(Duration::days(30)).unwrap();
+
+    let users = Users::get_from_db(db, company.id).await?;
+    for user in users {
+        if user.is_system_account() || user.email.is_empty() {
+            continue;
+        }
+
+        // Users without a Teams license do not have any meetings, Graph returns an error for them.
+        let recordings = match graph.list_recordings(&user.email, since).await {
+            Ok(recordings) => recordings,
+            Err(e) => {
+                warn!("listing teams recordings for `{}` failed: {}", user.email, e);
+                continue;
+            }
+        };
+        if recordings.is_empty() {
+            continue;
+        }
+
+        let transcripts = match graph.list_transcripts(&user.email, since).await {
+            Ok(transcripts) => transcripts,
+            Err(e) => {
+                warn!("listing teams transcripts for `{}` failed: {}", user.email, e);
+                vec![]
+            }
+        };
+
+        for recording in recordings {
+            if let Err(e) = ingest_teams_recorded_meeting(
+                db,
+                company,
+                &graph,
+                &drive,
+                &shared_drive.id,
+                &recordings_folder_id,
+                &user,
+                &recording,
+                &transcripts,
+            )
+            .await
+            {
+                warn!(
+                    "ingesting teams recording `{}` for `{}` failed: {}",
+                    recording.id, user.email, e
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy a Teams recording and its transcript into Google Drive and save the meeting in
+/// the database.
+#[allow(clippy::too_many_arguments)]
+async fn ingest_teams_recorded_meeting(
+    db: &Database,
+    company: &Company,
+    graph: &MicrosoftGraph,
+    drive: &google_drive::Client,
+    shared_drive_id: &str,
+    recordings_folder_id: &str,
+    organizer: &User,
+    recording: &CallRecording,
+    transcripts: &[CallTranscript],
+) -> Result<Option<RecordedMeeting>> {
+    // A meeting can be recorded more than once, so we key on the recording.
+    // We save the recording ID here, even tho its in Teams.
+    // TODO: clean this up.
+    let key = format!("teams-{}", recording.id);
+
+    let existing = RecordedMeeting::get_from_db(db, key.to_string()).await;
+    if let Some(ref existing) = existing {
+        if !existing.video.is_empty()
+            || existing.purged_at.is_some()
+            || has_pending_drive_upload(db, company.id, &DriveUploadTarget::recorded_meeting(&key, "video")).await?
+        {
+            // We already copied this recording, Teams does not let us delete it after, so
+            // we check here to avoid copying it again.
+            return Ok(None);
+        }
+    }
+
+    let meeting = graph
+        .get_online_meeting(&organizer.email, &recording.meeting_id)
+        .await?;
+    let name = if meeting.subject.trim().is_empty() {
+        format!("Teams meeting hosted by {}", organizer.full_name())
+    } else {
+        meeting.subject.trim().to_string()
+    };
+
+    let start_time = meeting
+        .start_date_time
+        .or(recording.created_date_time)
+        .unwrap_or_else(Utc::now);
+    let end_time = recording.end_date_time.or(meeting.end_date_time).unwrap_or(start_time);
+
+    // Create the folder for the meeting.
+    let start_folder_id = drive
+        .files()
+        .create_folder(shared_drive_id, recordings_folder_id, &start_time.to_string())
+        .await?;
+
+    let file_name = to_kebab_case(name.replace("'s", "").trim());
+
+    info!(
+        "teams uploading meeting {} recording to Google drive... This might take a bit...",
+        name
+    );
+    let b = graph.get_content(&recording.recording_content_url).await?;
+    let video = upload_to_drive_or_enqueue(
+        db,
+        company.id,
+        drive,
+        shared_drive_id,
+        &start_folder_id,
+        &format!("{}.mp4", file_name),
+        "video/mp4",
+        &b,
+        &DriveUploadTarget::recorded_meeting(&key, "video"),
+    )
+    .await?;
+
+    // Get the transcript for the meeting, if it was transcribed.
+    let mut transcript = String::new();
+    let mut transcript_id = String::new();
+    let mut transcript_segments: Vec<TranscriptSegment> = Vec::new();
+    if let Some(t) = transcripts.iter().find(|t| t.meeting_id == recording.meeting_id) {
+        let b = graph
+            .get_content(&format!("{}?$format=text/vtt", t.transcript_content_url))
+            .await?;
+        drive
+            .files()
+            .create_or_update(
+                shared_drive_id,
+                &start_folder_id,
+                &format!("{}.vtt", file_name),
+                "text/vtt",
+                &b,
+            )
+            .await?;
+
+        let (text, segments) = transcript_from_vtt(from_utf8(&b)?);
+        transcript = text;
+        transcript_segments = segments;
+        transcript_id = t.id.to_string();
+    }
+
+    let mut attendees = meeting.attendee_emails();
+    if attendees.is_empty() {
+        attendees.push(organizer.email.to_string());
+    }
+
+    let m = NewRecordedMeeting {
+        name,
+        description: "".to_string(),
+        start_time,
+        end_time,
+        video: video.to_string(),
+        chat_log_link: "".to_string(),
+        chat_log: "".to_string(),
+        is_recurring: false,
+        attendees,
+        transcript,
+        transcript_id,
+        transcript_callback_secret: Default::default(),
+        location: format!("Meeting hosted by {}", organizer.full_name()),
+        location_id: None,
+        host: organizer.email.to_string(),
+        tags: Default::default(),
+        purged_at: None,
+        summary: Default::default(),
+        action_items: Default::default(),
+        summary_attempts: 0,
+        link_to_people: Default::default(),
+        visibility: MeetingVisibility::default().to_string(),
+        participants: Default::default(),
+        slack_channel: Default::default(),
+        transcript_doc: Default::default(),
+        chat_log_file: Default::default(),
+        transcript_file: Default::default(),
+        thumbnail: Default::default(),
+        google_event_id: key,
+        event_link: if meeting.join_web_url.is_empty() {
+            video
+        } else {
+            meeting.join_web_url.to_string()
+        },
+        cio_company_id: company.id,
+    };
+    let mut new = m.upsert(db).await?;
+    if !transcript_segments.is_empty() {
+        new.set_transcript_segments(db, &transcript_segments).await?;
+    }
+    new.set_attendee_users(db, company).await?;
+    if existing.is_none() {
+        new.send_slack_notification(db, company).await?;
+    }
+
+    Ok(Some(new))
+}
+
+/// What we know about a meeting that was recorded outside of Zoom, Meet, or Teams, like with a
+/// camera at an offsite or a phone, since there is no calendar event or call to get it from.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct LocalRecordingMetadata {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub start_time: DateTime<Utc>,
+    /// When the meeting ended, the same as its start if we do not know.
+    #[serde(default)]
+    pub end_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub location: String,
+    /// The email of the person who recorded the meeting.
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub attendees: Vec<String>,
+    /// Who can see the meeting: `public-internal`, `restricted`, or `private`. There is no
+    /// calendar event to tell, so it has to be set.
+    #[serde(default)]
+    pub visibility: String,
+}
+
+impl LocalRecordingMetadata {
+    /// The key of the meeting, in place of the id of its event. Uploading the recording of the
+    /// same meeting again updates it. The key has the company in it, since two companies can
+    /// have a meeting of the same name at the same time.
+    fn key(&self, company: &Company) -> String {
+        format!(
+            "local-{}-{}-{}",
+            company.id,
+            self.start_time.format("%Y%m%dT%H%M%S"),
+            to_kebab_case(self.name.replace("'s", "").trim())
+        )
+    }
+}
+
+/// Save a recording made outside of Zoom, Meet, or Teams: we store the file in Google Drive,
+/// save the meeting in the database, and submit the recording to be transcribed unless we
+/// have its transcript from an earlier upload. The
+/// transcription provider lets us know when the transcript is done, like for the other
+/// recordings.
+pub async fn ingest_local_recorded_meeting(
+    db: &Database,
+    company: &Company,
+    metadata: &LocalRecordingMetadata,
+    file_name: &str,
+    content_type: &str,
+    contents: Vec<u8>,
+) -> Result<RecordedMeeting> {
+    if metadata.name.trim().is_empty() {
+        bail!("a recorded meeting needs a name");
+    }
+    if file_name.is_empty() {
+        bail!("a recording needs a file name");
+    }
+    if contents.is_empty() {
+        bail!("the recording `{}` is empty", file_name);
+    }
+    if metadata.visibility.trim().is_empty() {
+        bail!("a recorded meeting needs a visibility: public-internal, restricted, or private");
+    }
+    let visibility = MeetingVisibility::from_str(&metadata.visibility)?;
+    let end_time = metadata.end_time.unwrap_or(metadata.start_time);
+    if end_time < metadata.start_time {
+        bail!("a recorded meeting cannot end before it starts");
+    }
+
+    let key = metadata.key(company);
+    let existing = RecordedMeeting::get_from_db(db, key.to_string()).await;
+
+    // Initialize the Google Drive client.
+    let drive = company.authenticate_google_drive(db).await?;
+
+    // Get the shared drive.
+    let shared_drive = drive.drives().get_by_name("Automated Documents").await?;
+
+    // Create the folder for the meeting.
+    let recordings_folder_id = drive
+        .files()
+        .create_folder(&shared_drive.id, "", "local_recordings")
+        .await?;
+    let start_folder_id = drive
+        .files()
+        .create_folder(
+            &shared_drive.id,
+            &recordings_folder_id,
+            &metadata.start_time.to_string(),
+        )
+        .await?;
+
+    info!(
+        "uploading local recording `{}` of meeting {} to Google drive... This might take a bit...",
+        file_name,
+        metadata.name.trim()
+    );
+    let video = upload_to_drive_or_enqueue(
+        db,
+        company.id,
+        &drive,
+        &shared_drive.id,
+        &start_folder_id,
+        file_name,
+        content_type,
+        &contents,
+        &DriveUploadTarget::recorded_meeting(&key, "video"),
+    )
+    .await?;
+
+    let mut attendees: Vec<String> = metadata
+        .attendees
+        .iter()
+        .map(|a| a.trim().to_lowercase())
+        .filter(|a| !a.is_empty())
+        .collect();
+    let host = metadata.host.trim().to_lowercase();
+    if attendees.is_empty() && !host.is_empty() {
+        attendees.push(host.to_string());
+    }
+
+    let mut meeting = NewRecordedMeeting {
+        name: metadata.name.trim().to_string(),
+        description: metadata.description.trim().to_string(),
+        start_time: metadata.start_time,
+        end_time,
+        video: video.to_string(),
+        chat_log_link: Default::default(),
+        chat_log: Default::default(),
+        is_recurring: false,
+        attendees,
+        transcript: Default::default(),
+        transcript_id: Default::default(),
+        transcript_callback_secret: Default::default(),
+        location: metadata.location.trim().to_string(),
+        location_id: None,
+        host,
+        tags: Default::default(),
+        purged_at: None,
+        summary: Default::default(),
+        action_items: Default::default(),
+        summary_attempts: 0,
+        link_to_people: Default::default(),
+        visibility: visibility.to_string(),
+        participants: Default::default(),
+        slack_channel: Default::default(),
+        transcript_doc: Default::default(),
+        chat_log_file: Default::default(),
+        transcript_file: Default::default(),
+        thumbnail: Default::default(),
+        google_event_id: key,
+        event_link: video,
+        cio_company_id: company.id,
+    };
+    if let Some(ref m) = existing {
+        // Keep what we got from the recording we had before.
+        meeting.transcript = m.transcript.to_string();
+        meeting.transcript_id = m.transcript_id.to_string();
+        meeting.transcript_callback_secret = m.transcript_callback_secret.to_string();
+        meeting.tags = m.tags.clone();
+        meeting.summary = m.summary.to_string();
+        meeting.action_items = m.action_items.clone();
+        meeting.summary_attempts = m.summary_attempts;
+        meeting.link_to_people = m.link_to_people.clone();
+        meeting.slack_channel = m.slack_channel.to_string();
+        meeting.transcript_doc = m.transcript_doc.to_string();
+        meeting.location_id = m.location_id;
+        meeting.chat_log_file = m.chat_log_file.to_string();
+        meeting.transcript_file = m.transcript_file.to_string();
+        meeting.thumbnail = m.thumbnail.to_string();
+    }
+
+    let mut db_meeting = meeting.upsert(db).await?;
+    db_meeting.set_attendee_users(db, company).await?;
+    if existing.is_none() && visibility != MeetingVisibility::Private {
+        let _ = db_meeting.send_slack_notification(db, company).await.map_err(|err| {
+            warn!("Failed to post new meeting message to Slack. err: {:?}", err);
+            err
+        });
+    }
+
+    // We already have the transcript of the meeting from the first upload.
+    if !db_meeting.transcript.is_empty() {
+        return Ok(db_meeting);
+    }
+
+    // Now let's upload it to the transcription provider so it can start a job.
+    let transcriber = get_transcription_provider(company)?;
+    match transcriber.submit(contents).await {
+        Ok(t) => {
+            db_meeting.transcript_id = t.id.to_string();
+            db_meeting.transcript = t.transcript.to_string();
+            db_meeting.update(db).await?;
+        }
+        Err(e) => {
+            // The meeting is saved, uploading the recording again submits it again.
+            warn!(
+                "submitting local recording `{}` of meeting `{}` for transcription failed: {}",
+                file_name, db_meeting.name, e
+            );
+        }
+    }
+
+    Ok(db_meeting)
+}
+
+/// Get the visibility of the events of a calendar that set one with the
+/// `meeting-visibility` extended property, keyed by event id.
+async fn list_event_visibilities(
+    gcal: &google_calendar::Client,
+    calendar_id: &str,
+) -> Result<HashMap<String, MeetingVisibility>> {
+    let mut visibilities: HashMap<String, MeetingVisibility> = HashMap::new();
+    // Private comes last so it wins if an event sets both.
+    for visibility in [MeetingVisibility::Restricted, MeetingVisibility::Private] {
+        let filter = vec![format!("{}={}", MEETING_VISIBILITY_PROPERTY, visibility)];
+        for (private_filter, shared_filter) in [(&filter[..], &[][..]), (&[][..], &filter[..])] {
+            let events = gcal
+                .events()
+                .list_all(
+                    calendar_id, // Calendar id.
+                    "",          // iCalID
+                    0,           // Max attendees, set to 0 to ignore.
+                    google_calendar::types::OrderBy::StartTime,
+                    private_filter,           // private_extended_property
+                    "",                       // q
+                    shared_filter,            // shared_extended_property
+                    true,                     // show_deleted
+                    true,                     // show_hidden_invitations
+                    true,                     // single_events
+                    &Utc::now().to_rfc3339(), // time_max
+                    "",                       // time_min
+                    "",                       // time_zone
+                    "",                       // updated_min
                 )
                 .await?;
-            info!(
-            "zoom deleted meeting {} recording in Zoom since they are now in Google drive at https://drive.google.com/open?id={}",
-                meeting.topic,
-            drive_file.id
-        );
+            for event in events {
+                visibilities.insert(event.id, visibility);
+            }
         }
+    }
+
+    Ok(visibilities)
+}
 
-        let host = users::dsl::users
-            .filter(
-                users::dsl::zoom_id
-                    .eq(meeting.host_id.to_string())
-                    .and(users::dsl::cio_company_id.eq(company.id)),
+/// Share a file of a recorded meeting as far as its visibility allows: with the whole company
+/// for public meetings, with the attendees from the company for restricted ones, and with
+/// nobody for private ones. When the visibility became stricter, the permissions it no longer
+/// allows are removed.
+async fn share_meeting_file(
+    drive: &google_drive::Client,
+    company: &Company,
+    file_id: &str,
+    visibility: MeetingVisibility,
+    attendees: &[String],
+) -> Result<()> {
+    let domain = format!("@{}", company.gsuite_domain);
+    if visibility != MeetingVisibility::PublicInternal {
+        let allowed: Vec<String> = if visibility == MeetingVisibility::Restricted {
+            attendees.iter().map(|a| a.to_lowercase()).collect()
+        } else {
+            vec![]
+        };
+
+        let permissions = drive
+            .permissions()
+            .list_all(
+                file_id, // file id
+                "",      // include permissions for view
+                false,   // supports all drives
+                false,   // supports team drives
+                false,   // use domain admin access
             )
-            .first_async::<User>(db.pool())
             .await?;
+        for permission in permissions {
+            // We only ever share with the company, and never take the file from its owner.
+            let email = permission.email_address.to_lowercase();
+            if permission.role == "owner" || !email.ends_with(&domain) || allowed.contains(&email) {
+                continue;
+            }
 
-        // Create the meeting in the database.
-        let m = NewRecordedMeeting {
-            name: meeting.topic.trim().to_string(),
-            description: "".to_string(),
-            start_time: meeting.start_time.unwrap(),
-            end_time,
-            video,
-            chat_log_link,
-            chat_log,
-            is_recurring: false,
-            attendees: vec![host.email.to_string()],
-            transcript,
-            transcript_id,
-            location: format!("Meeting hosted by {}", host.full_name()),
-            // We save the meeting ID here, even tho its in Zoom.
-            // TODO: clean this up.
-            google_event_id: meeting.uuid.to_string(),
-            event_link: video_html_link,
-            cio_company_id: company.id,
-        };
-        let new = m.upsert(db).await?;
-        new.send_slack_notification(db, company).await?;
+            drive
+                .permissions()
+                .delete(
+                    file_id,        // file id
+                    &permission.id, // permission id
+                    false,          // supports all drives
+                    false,          // supports team drives
+                    false,          // use domain admin access
+                )
+                .await?;
+            info!(
+                "removed the access of {} to recorded meeting file {}, it is now {}",
+                email, file_id, visibility
+            );
+        }
+    }
+
+    match visibility {
+        MeetingVisibility::PublicInternal => {
+            drive
+                .permissions()
+                .add_if_not_exists(
+                    file_id,
+                    &format!("all@{}", company.gsuite_domain),
+                    "",
+                    "writer",
+                    "group",
+                    false, // use domain admin access
+                    false, // send notification email
+                )
+                .await?;
+        }
+        MeetingVisibility::Restricted => {
+            for attendee in attendees.iter().filter(|a| a.ends_with(&domain)) {
+                drive
+                    .permissions()
+                    .add_if_not_exists(
+                        file_id,  // file id
+                        attendee, // email
+                        "",       // domain
+                        "reader", // role
+                        "user",   // type
+                        false,    // use domain admin access
+                        false,    // send notification email
+                    )
+                    .await?;
+            }
+        }
+        MeetingVisibility::Private => (),
     }
 
     Ok(())
 }
 
 /// Sync the recorded meetings from Google.
-pub async fn refresh_google_recorded_meetings(db: &Database, company: &Company) -> Result<()> {
+pub async fn refresh_google_recorded_meetings(db: &Database, company: &Company) -> Result<SyncReport> {
+    let mut report = SyncReport::default();
+
     let mut gcal = match company.authenticate_google_calendar_with_service_account("").await {
         Ok(dc) => dc,
         // If we can't auth as the owner, then let's just just do a normal auth.
@@ -408,7 +2178,7 @@ pub async fn refresh_google_recorded_meetings(db: &Database, company: &Company)
         }
     };
 
-    let revai = RevAI::new_from_env();
+    let transcriber = get_transcription_provider(company)?;
 
     // Get the list of our calendars.
     let calendars = gcal
@@ -443,7 +2213,7 @@ pub async fn refresh_google_recorded_meetings(db: &Database, company: &Company)
         // Let's get all the events on this calendar and try and see if they
         // have a meeting recorded.
         info!("getting events for {}", calendar.id);
-        let events = gcal
+        let events = match gcal
             .events()
             .list_all(
                 &calendar.id, // Calendar id.
@@ -461,7 +2231,21 @@ pub async fn refresh_google_recorded_meetings(db: &Database, company: &Company)
                 "",                       // time_zone
                 "",                       // updated_min
             )
-            .await?;
+            .await
+        {
+            Ok(events) => events,
+            Err(e) => {
+                report.add_failure(&format!("calendar {}", calendar.id), e.into());
+                continue;
+            }
+        };
+        let visibilities = match list_event_visibilities(&gcal, &calendar.id).await {
+            Ok(visibilities) => visibilities,
+            Err(e) => {
+                report.add_failure(&format!("calendar {}", calendar.id), e);
+                continue;
+            }
+        };
 
         for event in events {
             // Make sure we haven't already done this event.
@@ -470,396 +2254,779 @@ pub async fn refresh_google_recorded_meetings(db: &Database, company: &Company)
                 continue;
             }
 
-            // Let's check if there are attachments. We only care if there are attachments.
-            if event.attachments.is_empty() {
-                // Continue early.
-                continue;
-            }
-
-            let mut owner = "".to_string();
-            let mut attendees: Vec<String> = Default::default();
-            for attendee in &event.attendees {
-                if !attendee.resource {
-                    attendees.push(attendee.email.to_string());
+            let id = event.id.to_string();
+            let name = event.summary.trim().to_string();
+            let visibility = visibilities.get(&event.id).copied().unwrap_or_default();
+            match ingest_google_recorded_meeting(db, company, transcriber.as_ref(), &calendar.id, visibility, event)
+                .await
+            {
+                Ok(true) => {
+                    report.synced += 1;
+                    completed_events.push(id);
                 }
-                if attendee.organizer && attendee.email.ends_with(&company.gsuite_domain) && owner.is_empty() {
-                    // Make sure the person is still a user.
-                    if let Some(_user) = User::get_from_db(
-                        db,
-                        company.id,
-                        attendee
-                            .email
-                            .trim_end_matches(&company.gsuite_domain)
-                            .trim_end_matches('@')
-                            .to_string(),
-                    )
-                    .await
-                    {
-                        owner = attendee.email.to_string()
-                    }
+                Ok(false) => (),
+                Err(e) => {
+                    report.add_failure(&name, e);
+                    // Don't try the same event again from another calendar.
+                    completed_events.push(id);
                 }
             }
+        }
+    }
 
-            let mut video = "".to_string();
-            let mut chat_log_link = "".to_string();
-            for attachment in &event.attachments {
-                if attachment.mime_type == "video/mp4" && attachment.title.starts_with(&event.summary) {
-                    video = attachment.file_url.to_string();
-                }
-                if attachment.mime_type == "text/plain" && attachment.title.starts_with(&event.summary) {
-                    chat_log_link = attachment.file_url.to_string();
+    RecordedMeetings::get_from_db(db, company.id)
+        .await?
+        .update_airtable(db)
+        .await?;
+
+    Ok(report)
+}
+
+/// Save a recorded meeting from a calendar event with a recording attached, and submit the
+/// video to be transcribed. Returns false if the event has no recording we can save.
+async fn ingest_google_recorded_meeting(
+    db: &Database,
+    company: &Company,
+    transcriber: &dyn TranscriptionProvider,
+    calendar_id: &str,
+    visibility: MeetingVisibility,
+    event: google_calendar::types::Event,
+) -> Result<bool> {
+    // Let's check if there are attachments. We only care if there are attachments.
+    if event.attachments.is_empty() {
+        // Return early.
+        return Ok(false);
+    }
+
+    let mut owner = "".to_string();
+    let mut attendees: Vec<String> = Default::default();
+    for attendee in &event.attendees {
+        if !attendee.resource {
+            attendees.push(attendee.email.to_string());
+        }
+        if attendee.organizer && attendee.email.ends_with(&company.gsuite_domain) && owner.is_empty() {
+            // Make sure the person is still a user.
+            if let Some(_user) = User::get_from_db(
+                db,
+                company.id,
+                attendee
+                    .email
+                    .trim_end_matches(&company.gsuite_domain)
+                    .trim_end_matches('@')
+                    .to_string(),
+            )
+            .await
+            {
+                owner = attendee.email.to_string()
+            }
+        }
+    }
+
+    let mut video = "".to_string();
+    let mut chat_log_link = "".to_string();
+    for attachment in &event.attachments {
+        if attachment.mime_type == "video/mp4" && attachment.title.starts_with(&event.summary) {
+            video = attachment.file_url.to_string();
+        }
+        if attachment.mime_type == "text/plain" && attachment.title.starts_with(&event.summary) {
+            chat_log_link = attachment.file_url.to_string();
+        }
+    }
+
+    // Get the IDs for the files.
+    let chat_log_id = chat_log_link
+        .trim_start_matches("https://drive.google.com/open?id=")
+        .trim_start_matches("https://drive.google.com/file/d/")
+        .trim_end_matches("/view?usp=drive_web")
+        .to_string();
+    let video_id = video
+        .trim_start_matches("https://drive.google.com/open?id=")
+        .trim_start_matches("https://drive.google.com/file/d/")
+        .trim_end_matches("/view?usp=drive_web")
+        .to_string();
+
+    if video.is_empty() {
+        // Return early, we don't care.
+        return Ok(false);
+    }
+
+    if let Some(m) = RecordedMeeting::get_from_db(db, event.id.to_string()).await {
+        if m.purged_at.is_some() {
+            // The retention policy already purged this meeting, don't bring it back.
+            return Ok(false);
+        }
+    }
+
+    if owner.is_empty() {
+        // We need a drive client to get information for the file.
+        let drive_client = match company.authenticate_google_drive_with_service_account("").await {
+            Ok(dc) => dc,
+            // If we can't auth as the owner, then let's just just do a normal auth.
+            Err(e) => {
+                info!(
+                    "using oauth2 token since getting google drive token with service account failed: {}",
+                    e
+                );
+                company.authenticate_google_drive(db).await?
+            }
+        };
+
+        // Let's get the owner of the video so we can auth as them.
+        if let Ok(file) = drive_client
+            .files()
+            .get(
+                &video_id, false, // acknowledge_abuse
+                "",    // include_permissions_for_view
+                true,  // supports_all_drives
+                true,  // supports_team_drives
+            )
+            .await
+        {
+            // The file is not owned by me, so we need to make ourselves an owner.
+            for o in file.owners {
+                // Iterate over the owners and try to find one we can authenticate as.
+                if let Some(_user) = User::get_from_db(
+                    db,
+                    company.id,
+                    o.email_address
+                        .trim_end_matches(&company.gsuite_domain)
+                        .trim_end_matches('@')
+                        .to_string(),
+                )
+                .await
+                {
+                    owner = o.email_address.to_string();
+                    break;
                 }
             }
+        }
+    }
 
-            // Get the IDs for the files.
-            let chat_log_id = chat_log_link
-                .trim_start_matches("https://drive.google.com/open?id=")
-                .trim_start_matches("https://drive.google.com/file/d/")
-                .trim_end_matches("/view?usp=drive_web")
-                .to_string();
-            let video_id = video
-                .trim_start_matches("https://drive.google.com/open?id=")
-                .trim_start_matches("https://drive.google.com/file/d/")
-                .trim_end_matches("/view?usp=drive_web")
-                .to_string();
-
-            if video.is_empty() {
-                // Continue early, we don't care.
-                continue;
+    if !owner.is_empty() {
+        // Authenticate as the specific user, if we can.
+        info!(
+            "authenticating google drive with service account as `{}` for `{}`",
+            owner,
+            event.summary.trim().to_string()
+        );
+    }
+    let drive_client = match company.authenticate_google_drive_with_service_account(&owner).await {
+        Ok(dc) => dc,
+        // If we can't auth as the owner, then let's just just do a normal auth.
+        Err(e) => {
+            info!(
+                "using oauth2 token since getting google drive token with service account failed: {}",
+                e
+            );
+            company.authenticate_google_drive(db).await?
+        }
+    };
+
+    // If we have a chat log, we should download it.
+    let mut chat_log = "".to_string();
+    if !chat_log_link.is_empty() {
+        // Let's add our perms to the file to ensure we have access.
+        match share_meeting_file(&drive_client, company, &chat_log_id, visibility, &attendees).await {
+            Ok(_) => (),
+            Err(e) => {
+                info!(
+                    "adding permission for event `{}` chat log `{}` with owner `{}` event_id `{}` calendar_id `{}` failed: {}",
+                    event.summary.trim().to_string(),
+                    chat_log_link,
+                    owner,
+                    event.id,
+                    calendar_id,
+                    e
+                );
+            }
+        };
+
+        // Download the file.
+        let contents = drive_client
+            .files()
+            .download_by_id(&chat_log_id)
+            .await
+            .unwrap_or_default();
+        chat_log = from_utf8(&contents).unwrap_or_default().trim().to_string();
+    }
+
+    // Let's add our perms to the file to ensure we have access.
+    match share_meeting_file(&drive_client, company, &video_id, visibility, &attendees).await {
+        Ok(_) => (),
+        Err(e) => {
+            info!(
+                "adding permission for event `{}` video `{}` with owner `{}` event_id `{}` calendar_id `{}` failed: {}",
+                event.summary.trim().to_string(),
+                video,
+                owner,
+                event.id,
+                calendar_id,
+                e
+            );
+        }
+    };
+
+    let (start_time, end_time) = match (
+        event.start.as_ref().and_then(|s| s.date_time),
+        event.end.as_ref().and_then(|e| e.date_time),
+    ) {
+        (Some(start_time), Some(end_time)) => (start_time, end_time),
+        _ => bail!("event `{}` has no start or end time", event.summary.trim()),
+    };
+
+    let mut meeting = NewRecordedMeeting {
+        name: event.summary.trim().to_string(),
+        description: event.description.trim().to_string(),
+        start_time,
+        end_time,
+        video,
+        chat_log_link,
+        chat_log,
+        is_recurring: !event.recurring_event_id.is_empty(),
+        attendees,
+        transcript: "".to_string(),
+        transcript_id: "".to_string(),
+        transcript_callback_secret: Default::default(),
+        location: event.location.to_string(),
+        location_id: None,
+        host: owner.to_string(),
+        tags: Default::default(),
+        purged_at: None,
+        summary: Default::default(),
+        action_items: Default::default(),
+        summary_attempts: 0,
+        link_to_people: Default::default(),
+        visibility: visibility.to_string(),
+        participants: Default::default(),
+        slack_channel: Default::default(),
+        transcript_doc: Default::default(),
+        chat_log_file: Default::default(),
+        transcript_file: Default::default(),
+        thumbnail: Default::default(),
+        google_event_id: event.id.to_string(),
+        event_link: event.html_link.to_string(),
+        cio_company_id: company.id,
+    };
+
+    // Let's try to get the meeting.
+    let existing = RecordedMeeting::get_from_db(db, event.id.to_string());
+    if let Some(m) = existing.await {
+        // Update the meeting.
+        meeting.transcript = m.transcript.to_string();
+        meeting.transcript_id = m.transcript_id.to_string();
+        meeting.transcript_callback_secret = m.transcript_callback_secret.to_string();
+        meeting.tags = m.tags.clone();
+        meeting.summary = m.summary.to_string();
+        meeting.action_items = m.action_items.clone();
+        meeting.summary_attempts = m.summary_attempts;
+        meeting.link_to_people = m.link_to_people.clone();
+        meeting.slack_channel = m.slack_channel.to_string();
+        meeting.transcript_doc = m.transcript_doc.to_string();
+        meeting.location_id = m.location_id;
+        meeting.chat_log_file = m.chat_log_file.to_string();
+        meeting.transcript_file = m.transcript_file.to_string();
+        meeting.thumbnail = m.thumbnail.to_string();
+
+        // Get it from Airtable.
+        if let Some(existing_airtable) = m.get_existing_airtable_record(db).await {
+            if meeting.transcript.is_empty() {
+                meeting.transcript = existing_airtable.fields.transcript.to_string();
+            }
+            if meeting.transcript_id.is_empty() {
+                meeting.transcript_id = existing_airtable.fields.transcript_id.to_string();
+            }
+        }
+    } else if visibility != MeetingVisibility::Private {
+        // We have a new meeting, let's send the notification.
+        let _ = meeting.send_slack_notification(db, company).await.map_err(|err| {
+            warn!("Failed to post new meeting message to Slack. err: {:?}", err);
+            err
+        });
+    }
+
+    // Upsert the meeting in the database.
+    let mut db_meeting = meeting.upsert(db).await?;
+    db_meeting.set_attendee_users(db, company).await?;
+
+    // If we have a transcript and a transcript id, continue early.
+    if !db_meeting.transcript.is_empty() && !db_meeting.transcript_id.is_empty() {
+        return Ok(true);
+    }
+
+    // Only do this if we have the video contents.
+    // Check if we have a transcript id.
+    if db_meeting.transcript_id.is_empty() && db_meeting.transcript.is_empty() {
+        // Download the video.
+        // We are downloading it here only because this will significantly make things
+        // faster.
+        let video_contents = drive_client.files().download_by_id(&video_id).await.unwrap_or_default();
+
+        // Get the size of the file.
+        // Because some providers, like rev.ai, can only do uploads under 2GB.
+        let b = byte_unit::Byte::from_unit(video_contents.len() as f64, byte_unit::ByteUnit::B)?;
+        let b = b.get_adjusted_unit(byte_unit::ByteUnit::GB);
+        info!("video for meeting `{}` has size `{}`", event.summary.trim(), b);
+
+        // If we don't have a transcript ID, let's post the video to be
+        // transcribed.
+        // Now let's upload it to the transcription provider so it can start a job.
+        match transcriber.submit(video_contents).await {
+            Ok(t) => {
+                // Set the transcript id, and the transcript if the provider
+                // already has it.
+                db_meeting.transcript_id = t.id.to_string();
+                db_meeting.transcript = t.transcript.to_string();
+                db_meeting.update(db).await?;
             }
+            Err(e) => {
+                info!(
+                    "Failed submitting video data for transcription directly. meeting: {} event: {} video: {} err: {}",
+                    db_meeting.id,
+                    event.summary.trim(),
+                    video_id,
+                    e
+                );
 
-            if owner.is_empty() {
-                // We need a drive client to get information for the file.
-                let drive_client = match company.authenticate_google_drive_with_service_account("").await {
-                    Ok(dc) => dc,
-                    // If we can't auth as the owner, then let's just just do a normal auth.
-                    Err(e) => {
-                        info!(
-                            "using oauth2 token since getting google drive token with service account failed: {}",
-                            e
-                        );
-                        company.authenticate_google_drive(db).await?
-                    }
-                };
-
-                // Let's get the owner of the video so we can auth as them.
-                if let Ok(file) = drive_client
-                    .files()
-                    .get(
-                        &video_id, false, // acknowledge_abuse
-                        "",    // include_permissions_for_view
-                        true,  // supports_all_drives
-                        true,  // supports_team_drives
-                    )
-                    .await
-                {
-                    // The file is not owned by me, so we need to make ourselves an owner.
-                    for o in file.owners {
-                        // Iterate over the owners and try to find one we can authenticate as.
-                        if let Some(_user) = User::get_from_db(
-                            db,
-                            company.id,
-                            o.email_address
-                                .trim_end_matches(&company.gsuite_domain)
-                                .trim_end_matches('@')
-                                .to_string(),
+                if e.to_string().contains("413") {
+                    // The video is too large, lets add permissions for an hour and do it
+                    // another way.
+                    match drive_client
+                        .permissions()
+                        .create(
+                            &video_id,
+                            "",    // email_message
+                            false, // move_to_new_owners_root
+                            false, // send_notification_email
+                            true,  // supports_all_drives
+                            true,  // supports_team_drives
+                            false, // transfer_ownership
+                            false, // use_domain_admin_access
+                            &google_drive::types::Permission {
+                                allow_file_discovery: None,
+                                deleted: None,
+                                display_name: "".to_string(),
+                                domain: "".to_string(),
+                                email_address: "".to_string(),
+                                // Add an hour for the duration.
+                                expiration_time: Some(Utc::now().checked_add_signed(Duration::hours(1)).unwrap()),
+                                id: "".to_string(),
+                                kind: "".to_string(),
+                                permission_details: vec![],
+                                photo_link: "".to_string(),
+                                // Writer means they can download.
+                                role: "writer".to_string(),
+                                team_drive_permission_details: vec![],
+                                type_: "anyone".to_string(),
+                                view: "".to_string(),
+                            },
                         )
                         .await
-                        {
-                            owner = o.email_address.to_string();
-                            break;
+                    {
+                        Ok(_) => {
+                            let callback_secret = generate_callback_secret();
+                            let r = transcriber
+                                .submit_url(
+                                    &format!("https://drive.google.com/uc?id={}", video_id),
+                                    &callback_secret,
+                                )
+                                .await;
+                            if let Err(err) = r {
+                                warn!(
+                                    "submitting video `{}` with size `{}` for transcription with link failed: {}",
+                                    db_meeting.name,
+                                    b.to_string(),
+                                    err
+                                );
+                                return Ok(true);
+                            }
+
+                            let t = r?;
+                            // Set the transcript id.
+                            db_meeting.transcript_id = t.id.to_string();
+                            db_meeting.transcript_callback_secret = callback_secret;
+                            db_meeting.transcript = t.transcript.to_string();
+                            db_meeting.update(db).await?;
+                            return Ok(true);
+                        }
+                        Err(err) => {
+                            warn!(
+                                "could not change perms for video `{}` with size `{}`: {}",
+                                db_meeting.name,
+                                b.to_string(),
+                                err
+                            );
                         }
                     }
-                }
-            }
-
-            if !owner.is_empty() {
-                // Authenticate as the specific user, if we can.
-                info!(
-                    "authenticating google drive with service account as `{}` for `{}`",
-                    owner,
-                    event.summary.trim().to_string()
-                );
-            }
-            let drive_client = match company.authenticate_google_drive_with_service_account(&owner).await {
-                Ok(dc) => dc,
-                // If we can't auth as the owner, then let's just just do a normal auth.
-                Err(e) => {
-                    info!(
-                        "using oauth2 token since getting google drive token with service account failed: {}",
+                } else {
+                    debug!(
+                        "failed to upload video for `{}` with size `{}` for transcription: {}",
+                        db_meeting.name,
+                        b.to_string(),
                         e
                     );
-                    company.authenticate_google_drive(db).await?
+                    return Ok(true);
                 }
-            };
+            }
+        }
+    } else if db_meeting.transcript.is_empty() && !db_meeting.transcript_id.is_empty() {
+        // We have a transcript id, let's try and get the transcript if we don't have
+        // it already.
+        if let Err(e) = db_meeting.fetch_transcript(db, transcriber).await {
+            info!("getting transcript for id `{}` failed: {}", db_meeting.transcript_id, e);
+            db_meeting.transcript = String::new();
+        }
 
-            // If we have a chat log, we should download it.
-            let mut chat_log = "".to_string();
-            if !chat_log_link.is_empty() {
-                // Let's add our perms to the file to ensure we have access.
-                match drive_client
-                    .permissions()
-                    .add_if_not_exists(
-                        &chat_log_id,
-                        &format!("all@{}", company.gsuite_domain),
-                        "",
-                        "writer",
-                        "group",
-                        false, // use domain admin access
-                        false, // send notification email
-                    )
-                    .await
-                {
-                    Ok(_) => (),
-                    Err(e) => {
-                        info!(
-                            "adding permission for event `{}` chat log `{}` with owner `{}` event_id `{}` calendar_id `{}` failed: {}",
-                            event.summary.trim().to_string(),
-                            chat_log_link,
-                            owner,
-                            event.id,
-                            calendar.id,
-                            e
-                        );
-                    }
-                };
+        db_meeting.update(db).await?;
+    }
+
+    Ok(true)
+}
+
+/// Handle Rev.ai telling us a transcription job is done. We store the transcript right away
+/// rather than on the next sync. If the job failed, we forget its id so the next sync submits
+/// the recording again.
+/// Handle Rev.ai telling us a transcription job is done. Returns false if the secret of the
+/// callback is not the one we gave the job.
+pub async fn handle_revai_job_notification(
+    db: &Database,
+    notification: &RevAIJobNotification,
+    callback_secret: &str,
+) -> Result<bool> {
+    let job = &notification.job;
+    let meetings = recorded_meetings::dsl::recorded_meetings
+        .filter(recorded_meetings::dsl::transcript_id.eq(job.id.to_string()))
+        .limit(1)
+        .load_async::<RecordedMeeting>(db.pool())
+        .await?;
+    // Whoever is calling does not get to know which job ids exist.
+    let mut meeting = match meetings.into_iter().next() {
+        Some(meeting) => meeting,
+        None => return Ok(false),
+    };
+    if !verify_callback_secret(&meeting.transcript_callback_secret, callback_secret) {
+        return Ok(false);
+    }
+
+    if job.is_failed() {
+        warn!(
+            "rev.ai transcription `{}` of recorded meeting `{}` failed: {}",
+            job.id, meeting.name, job.failure_detail
+        );
+        meeting.transcript_id = String::new();
+        meeting.transcript_callback_secret = String::new();
+        meeting.update(db).await?;
+
+        return Ok(true);
+    }
+    if !job.is_transcribed() {
+        bail!("rev.ai job `{}` has unexpected status `{}`", job.id, job.status);
+    }
+    // Rev.ai retries callbacks it did not get an answer for, don't fetch twice.
+    if !meeting.transcript.is_empty() {
+        return Ok(true);
+    }
+
+    let company = Company::get_from_id(db, meeting.cio_company_id).await?;
+    let transcriber = get_transcription_provider(&company)?;
+    meeting.fetch_transcript(db, transcriber.as_ref()).await?;
+    meeting.update(db).await?;
+    info!("stored transcript `{}` of recorded meeting `{}`", job.id, meeting.name);
+
+    Ok(true)
+}
 
-                // Download the file.
-                let contents = drive_client
-                    .files()
-                    .download_by_id(&chat_log_id)
-                    .await
-                    .unwrap_or_default();
-                chat_log = from_utf8(&contents).unwrap_or_default().trim().to_string();
+/// The name of the folder Google Meet saves recordings to in the organizer's Drive.
+static MEET_RECORDINGS_FOLDER: &str = "Meet Recordings";
+
+/// Return the title of the meeting from the name of a Meet recording file. Meet names
+/// recordings like `Weekly sync (2021-05-20 at 09:59 GMT-7)`.
+fn meet_recording_title(name: &str) -> String {
+    let name = name.trim().trim_end_matches(".mp4").trim();
+    if name.ends_with(')') {
+        if let Some(i) = name.rfind(" (") {
+            return name[..i].trim().to_string();
+        }
+    }
+
+    name.to_string()
+}
+
+/// Return the meeting code from a Meet link, ie. `abc-defg-hij`.
+fn meet_code(hangout_link: &str) -> String {
+    hangout_link
+        .trim()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Find the calendar event a Meet recording belongs to. The recording file is created
+/// when the recording stops, so the event must have started before then and the title of
+/// the recording must match either the event summary or the Meet code.
+fn find_event_for_recording<'a>(
+    events: &'a [google_calendar::types::Event],
+    title: &str,
+    recorded_at: DateTime<Utc>,
+) -> Option<&'a google_calendar::types::Event> {
+    let title = title.trim().to_lowercase();
+
+    events
+        .iter()
+        .filter(|event| {
+            let start = event.start.as_ref().and_then(|s| s.date_time);
+            let end = event.end.as_ref().and_then(|e| e.date_time);
+            match (start, end) {
+                // Recordings often run a bit longer than the scheduled time.
+                (Some(start), Some(end)) => start <= recorded_at && recorded_at <= end + Duration::hours(2),
+                _ => false,
             }
+        })
+        .filter(|event| {
+            event.summary.trim().to_lowercase() == title
+                || (!event.hangout_link.is_empty() && meet_code(&event.hangout_link) == title)
+        })
+        // Pick the event that started closest to the recording.
+        .max_by_key(|event| event.start.as_ref().and_then(|s| s.date_time))
+}
 
-            // Let's add our perms to the file to ensure we have access.
-            match drive_client
-                .permissions()
-                .add_if_not_exists(
-                    &video_id,
-                    &format!("all@{}", company.gsuite_domain),
-                    "",
-                    "writer",
-                    "group",
-                    false, // use domain admin access
-                    false, // send notification email
-                )
-                .await
-            {
-                Ok(_) => (),
-                Err(e) => {
-                    info!(
-                        "adding permission for event `{}` video `{}` with owner `{}` event_id `{}` calendar_id `{}` failed: {}",
-                        event.summary.trim().to_string(),
-                        video,
-                        owner,
-                        event.id,
-                        calendar.id,
-                        e
-                    );
-                }
-            };
+/// Sync the recorded meetings from the "Meet Recordings" folder in each user's Google Drive.
+/// Meet recordings are not always attached to calendar events, so we find the recordings
+/// directly and match them back to the events they were recorded for.
+pub async fn refresh_google_meet_recordings(db: &Database, company: &Company) -> Result<()> {
+    let users = Users::get_from_db(db, company.id).await?;
+
+    for user in users {
+        if user.is_system_account() || !user.email.ends_with(&company.gsuite_domain) {
+            continue;
+        }
+
+        if let Err(e) = refresh_google_meet_recordings_for_user(db, company, &user).await {
+            warn!("syncing meet recordings for `{}` failed: {}", user.email, e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn refresh_google_meet_recordings_for_user(db: &Database, company: &Company, user: &User) -> Result<()> {
+    let drive = company
+        .authenticate_google_drive_with_service_account(&user.email)
+        .await?;
+
+    // Find the user's recordings folder.
+    let folders = drive
+        .files()
+        .list_all(
+            "user", // corpora
+            "",     // drive_id
+            false,  // include_items_from_all_drives
+            "",     // include_permissions_for_view
+            false,  // include_team_drive_items
+            "",     // order_by
+            &format!(
+                "name = '{}' and mimeType = 'application/vnd.google-apps.folder' and 'me' in owners and trashed = false",
+                MEET_RECORDINGS_FOLDER
+            ),
+            "",    // spaces
+            false, // supports_all_drives
+            false, // supports_team_drives
+            "",    // team_drive_id
+        )
+        .await?;
+    let folder = match folders.into_iter().next() {
+        Some(folder) => folder,
+        // The user has never recorded a meeting.
+        None => return Ok(()),
+    };
+
+    let recordings = drive
+        .files()
+        .list_all(
+            "user", // corpora
+            "",     // drive_id
+            false,  // include_items_from_all_drives
+            "",     // include_permissions_for_view
+            false,  // include_team_drive_items
+            "createdTime desc",
+            &format!(
+                "'{}' in parents and mimeType = 'video/mp4' and trashed = false",
+                folder.id
+            ),
+            "",    // spaces
+            false, // supports_all_drives
+            false, // supports_team_drives
+            "",    // team_drive_id
+        )
+        .await?;
+
+    if recordings.is_empty() {
+        return Ok(());
+    }
+
+    let gcal = company
+        .authenticate_google_calendar_with_service_account(&user.email)
+        .await?;
+
+    let visibilities = list_event_visibilities(&gcal, &user.email).await?;
+
+    for recording in recordings {
+        let recorded_at = recording.created_time.unwrap_or_else(Utc::now);
+        let title = meet_recording_title(&recording.name);
+        let video = format!("https://drive.google.com/open?id={}", recording.id);
+
+        // Get the events around the time of the recording.
+        let events = gcal
+            .events()
+            .list_all(
+                &user.email, // Calendar id.
+                "",          // iCalID
+                0,           // Max attendees, set to 0 to ignore.
+                google_calendar::types::OrderBy::StartTime,
+                &[],                                              // private_extended_property
+                "",                                               // q
+                &[],                                              // shared_extended_property
+                false,                                            // show_deleted
+                false,                                            // show_hidden_invitations
+                true,                                             // single_events
+                &(recorded_at + Duration::hours(1)).to_rfc3339(), // time_max
+                &(recorded_at - Duration::hours(6)).to_rfc3339(), // time_min
+                "",                                               // time_zone
+                "",                                               // updated_min
+            )
+            .await?;
 
-            let mut meeting = NewRecordedMeeting {
+        let event = find_event_for_recording(&events, &title, recorded_at);
+        let visibility = event.and_then(|e| visibilities.get(&e.id).copied()).unwrap_or_default();
+        let mut meeting = match event {
+            Some(event) => NewRecordedMeeting {
                 name: event.summary.trim().to_string(),
                 description: event.description.trim().to_string(),
-                start_time: event.start.unwrap().date_time.unwrap(),
-                end_time: event.end.unwrap().date_time.unwrap(),
-                video,
-                chat_log_link,
-                chat_log,
+                start_time: event.start.as_ref().and_then(|s| s.date_time).unwrap_or(recorded_at),
+                end_time: event.end.as_ref().and_then(|e| e.date_time).unwrap_or(recorded_at),
+                video: video.to_string(),
+                chat_log_link: "".to_string(),
+                chat_log: "".to_string(),
                 is_recurring: !event.recurring_event_id.is_empty(),
-                attendees,
+                attendees: event
+                    .attendees
+                    .iter()
+                    .filter(|attendee| !attendee.resource)
+                    .map(|attendee| attendee.email.to_string())
+                    .collect(),
                 transcript: "".to_string(),
                 transcript_id: "".to_string(),
+                transcript_callback_secret: Default::default(),
                 location: event.location.to_string(),
+                location_id: None,
+                host: user.email.to_string(),
+                tags: Default::default(),
+                purged_at: None,
+                summary: Default::default(),
+                action_items: Default::default(),
+                summary_attempts: 0,
+                link_to_people: Default::default(),
+                visibility: visibility.to_string(),
+                participants: Default::default(),
+                slack_channel: Default::default(),
+                transcript_doc: Default::default(),
+                chat_log_file: Default::default(),
+                transcript_file: Default::default(),
+                thumbnail: Default::default(),
                 google_event_id: event.id.to_string(),
                 event_link: event.html_link.to_string(),
                 cio_company_id: company.id,
-            };
+            },
+            None => {
+                info!(
+                    "could not find a calendar event for meet recording `{}` of `{}`",
+                    recording.name, user.email
+                );
 
-            // Let's try to get the meeting.
-            let existing = RecordedMeeting::get_from_db(db, event.id.to_string());
-            if let Some(m) = existing.await {
-                // Update the meeting.
-                meeting.transcript = m.transcript.to_string();
-                meeting.transcript_id = m.transcript_id.to_string();
-
-                // Get it from Airtable.
-                if let Some(existing_airtable) = m.get_existing_airtable_record(db).await {
-                    if meeting.transcript.is_empty() {
-                        meeting.transcript = existing_airtable.fields.transcript.to_string();
-                    }
-                    if meeting.transcript_id.is_empty() {
-                        meeting.transcript_id = existing_airtable.fields.transcript_id.to_string();
-                    }
+                NewRecordedMeeting {
+                    name: title.to_string(),
+                    description: "".to_string(),
+                    start_time: recorded_at,
+                    end_time: recorded_at,
+                    video: video.to_string(),
+                    chat_log_link: "".to_string(),
+                    chat_log: "".to_string(),
+                    is_recurring: false,
+                    attendees: vec![user.email.to_string()],
+                    transcript: "".to_string(),
+                    transcript_id: "".to_string(),
+                    transcript_callback_secret: Default::default(),
+                    location: format!("Meeting recorded by {}", user.full_name()),
+                    location_id: None,
+                    host: user.email.to_string(),
+                    tags: Default::default(),
+                    purged_at: None,
+                    summary: Default::default(),
+                    action_items: Default::default(),
+                    summary_attempts: 0,
+                    link_to_people: Default::default(),
+                    visibility: MeetingVisibility::default().to_string(),
+                    participants: Default::default(),
+                    slack_channel: Default::default(),
+                    transcript_doc: Default::default(),
+                    chat_log_file: Default::default(),
+                    transcript_file: Default::default(),
+                    thumbnail: Default::default(),
+                    // We save the file ID here since we do not have an event.
+                    google_event_id: recording.id.to_string(),
+                    event_link: video.to_string(),
+                    cio_company_id: company.id,
                 }
-            } else {
-                // We have a new meeting, let's send the notification.
-                let _ = meeting.send_slack_notification(db, company).await.map_err(|err| {
-                    warn!("Failed to post new meeting message to Slack. err: {:?}", err);
-                    err
-                });
             }
+        };
 
-            // Upsert the meeting in the database.
-            let mut db_meeting = meeting.upsert(db).await?;
-
-            // Add to our completed events.
-            completed_events.push(event.id.to_string());
-
-            // If we have a transcript and a transcript id, continue early.
-            if !db_meeting.transcript.is_empty() && !db_meeting.transcript_id.is_empty() {
+        // Let's try to get the meeting.
+        if let Some(existing) = RecordedMeeting::get_from_db(db, meeting.google_event_id.to_string()).await {
+            if existing.purged_at.is_some() {
+                // The retention policy already purged this recording, don't bring it back.
                 continue;
             }
 
-            // Only do this if we have the video contents.
-            // Check if we have a transcript id.
-            if db_meeting.transcript_id.is_empty() && db_meeting.transcript.is_empty() {
-                // Download the video.
-                // We are downloading it here only because this will significantly make things
-                // faster.
-                let video_contents = drive_client.files().download_by_id(&video_id).await.unwrap_or_default();
-
-                // Get the size of the file.
-                // Because rev.ai can only do uploads under 2GB.
-                let b = byte_unit::Byte::from_unit(video_contents.len() as f64, byte_unit::ByteUnit::B)?;
-                let b = b.get_adjusted_unit(byte_unit::ByteUnit::GB);
-                info!("video for meeting `{}` has size `{}`", event.summary.trim(), b);
-
-                // If we don't have a transcript ID, let's post the video to be
-                // transcribed.
-                // Now let's upload it to rev.ai so it can start a job.
-                match revai.jobs().post(video_contents).await {
-                    Ok(job) => {
-                        // Set the transcript id.
-                        db_meeting.transcript_id = job.id.to_string();
-                        db_meeting.update(db).await?;
-                    }
-                    Err(e) => {
-                        info!(
-                            "Failed submitting video data to Rev.ai directly. meeting: {} event: {} video: {} err: {}",
-                            db_meeting.id,
-                            event.summary.trim(),
-                            video_id,
-                            e
-                        );
-
-                        if e.to_string().contains("413") {
-                            // The video is too large, lets add permissions for an hour and do it
-                            // another way.
-                            match drive_client
-                                .permissions()
-                                .create(
-                                    &video_id,
-                                    "",    // email_message
-                                    false, // move_to_new_owners_root
-                                    false, // send_notification_email
-                                    true,  // supports_all_drives
-                                    true,  // supports_team_drives
-                                    false, // transfer_ownership
-                                    false, // use_domain_admin_access
-                                    &google_drive::types::Permission {
-                                        allow_file_discovery: None,
-                                        deleted: None,
-                                        display_name: "".to_string(),
-                                        domain: "".to_string(),
-                                        email_address: "".to_string(),
-                                        // Add an hour for the duration.
-                                        expiration_time: Some(
-                                            Utc::now().checked_add_signed(Duration::hours(1)).unwrap(),
-                                        ),
-                                        id: "".to_string(),
-                                        kind: "".to_string(),
-                                        permission_details: vec![],
-                                        photo_link: "".to_string(),
-                                        // Writer means they can download.
-                                        role: "writer".to_string(),
-                                        team_drive_permission_details: vec![],
-                                        type_: "anyone".to_string(),
-                                        view: "".to_string(),
-                                    },
-                                )
-                                .await
-                            {
-                                Ok(_) => {
-                                    let r = revai
-                                        .jobs()
-                                        .submit_transcription(&SubmitJobMediaUrlOptionsAllOf {
-                                            submit_job_media_url_options: SubmitJobMediaUrlOptions {
-                                                media_url: format!("https://drive.google.com/uc?id={}", video_id),
-                                            },
-                                            submit_job_options_all_of: Default::default(),
-                                        })
-                                        .await;
-                                    if let Err(err) = r {
-                                        warn!(
-                                            "submitting video `{}` with size `{}` to revai with link failed: {}",
-                                            db_meeting.name,
-                                            b.to_string(),
-                                            err
-                                        );
-                                        continue;
-                                    }
-
-                                    let job = r?.job;
-                                    // Set the transcript id.
-                                    db_meeting.transcript_id = job.id.to_string();
-                                    db_meeting.update(db).await?;
-                                    continue;
-                                }
-                                Err(err) => {
-                                    warn!(
-                                        "could not change perms for video `{}` with size `{}`: {}",
-                                        db_meeting.name,
-                                        b.to_string(),
-                                        err
-                                    );
-                                }
-                            }
-                        } else {
-                            debug!(
-                                "failed to upload video for `{}` with size `{}` to rev.ai: {}",
-                                db_meeting.name,
-                                b.to_string(),
-                                e
-                            );
-                            continue;
-                        }
-                    }
-                }
-            } else if db_meeting.transcript.is_empty() && !db_meeting.transcript_id.is_empty() {
-                // We have a transcript id, let's try and get the transcript if we don't have
-                // it already.
-                // Now let's try to get the transcript.
-                let transcript = match revai
-                    .transcript()
-                    .get(&db_meeting.transcript_id, revai::types::AcceptTranscript::TextPlain)
-                    .await
-                {
-                    Ok(t) => {
-                        info!(
-                            "Fetched transcript from Rev.ai to be stored. meeting: {} transcript: {}",
-                            db_meeting.id, db_meeting.transcript_id
-                        );
-                        t
-                    }
-                    Err(e) => {
-                        info!("getting transcript for id `{}` failed: {}", db_meeting.transcript_id, e);
-                        String::new()
-                    }
-                };
-                db_meeting.transcript = transcript.trim().to_string();
-                db_meeting.update(db).await?;
-            }
+            // Keep the tags and the transcript we already have.
+            meeting.tags = existing.tags.clone();
+            meeting.transcript = existing.transcript.to_string();
+            meeting.transcript_id = existing.transcript_id.to_string();
+            meeting.transcript_callback_secret = existing.transcript_callback_secret.to_string();
+            meeting.chat_log = existing.chat_log.to_string();
+            meeting.chat_log_link = existing.chat_log_link.to_string();
+            meeting.summary = existing.summary.to_string();
+            meeting.action_items = existing.action_items.clone();
+            meeting.summary_attempts = existing.summary_attempts;
+            meeting.link_to_people = existing.link_to_people.clone();
+            meeting.slack_channel = existing.slack_channel.to_string();
+            meeting.transcript_doc = existing.transcript_doc.to_string();
+            meeting.location_id = existing.location_id;
+            meeting.chat_log_file = existing.chat_log_file.to_string();
+            meeting.transcript_file = existing.transcript_file.to_string();
+            meeting.thumbnail = existing.thumbnail.to_string();
+        } else if visibility != MeetingVisibility::Private {
+            // We have a new meeting, let's send the notification.
+            let _ = meeting.send_slack_notification(db, company).await.map_err(|err| {
+                warn!("Failed to post new meeting message to Slack. err: {:?}", err);
+                err
+            });
         }
-    }
 
-    RecordedMeetings::get_from_db(db, company.id)
-        .await?
-        .update_airtable(db)
-        .await?;
+        // Let's add our perms to the file to ensure we have access.
+        if let Err(e) = share_meeting_file(&drive, company, &recording.id, visibility, &meeting.attendees).await {
+            info!(
+                "adding permission for meet recording `{}` of `{}` failed: {}",
+                recording.name, user.email, e
+            );
+        }
+
+        let mut meeting = meeting.upsert(db).await?;
+        meeting.set_attendee_users(db, company).await?;
+    }
 
     Ok(())
 }
@@ -898,3 +3065,535 @@ impl FileInfo for GetAccountCloudRecordingResponseMeetingsFilesFileType {
         }
     }
 }
+
+impl RecordedMeeting {
+    /// If someone can watch the recording of the meeting: anyone in the company for public
+    /// meetings, the attendees and the host for restricted ones, and only the host for private
+    /// ones.
+    pub fn can_view(&self, company: &Company, email: &str) -> bool {
+        let email = email.trim().to_lowercase();
+        if email.is_empty() {
+            return false;
+        }
+        let is = |other: &String| other.trim().eq_ignore_ascii_case(&email);
+
+        match MeetingVisibility::from_field(&self.visibility) {
+            MeetingVisibility::PublicInternal => email.ends_with(&format!("@{}", company.gsuite_domain.to_lowercase())),
+            MeetingVisibility::Restricted => {
+                is(&self.host) || self.attendees.iter().any(is) || self.participants.iter().any(is)
+            }
+            MeetingVisibility::Private => is(&self.host),
+        }
+    }
+}
+
+/// The most of a recording we send at once. Players ask for the rest of the video with ranged
+/// requests as they play it.
+static MEETING_VIDEO_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Turn the range a player asks for into the range we get from Drive, so we never hold more
+/// than a chunk of the video in memory. Players asking for the whole video get its first chunk.
+pub fn meeting_video_range(range: Option<&str>) -> String {
+    let chunk = |start: u64| format!("bytes={}-{}", start, start + MEETING_VIDEO_CHUNK_SIZE - 1);
+
+    let (start, end) = match range
+        .and_then(|r| r.trim().strip_prefix("bytes="))
+        .and_then(|r| r.split_once('-'))
+    {
+        Some((start, end)) => (start.trim(), end.trim()),
+        None => return chunk(0),
+    };
+
+    match (start.parse::<u64>(), end.parse::<u64>()) {
+        (Ok(start), Ok(end)) if end >= start => {
+            format!("bytes={}-{}", start, end.min(start + MEETING_VIDEO_CHUNK_SIZE - 1))
+        }
+        (Ok(start), Err(_)) if end.is_empty() => chunk(start),
+        // The last bytes of the video, like `bytes=-500`, players ask for them to find the index
+        // of the video.
+        (Err(_), Ok(suffix)) if start.is_empty() => format!("bytes=-{}", suffix.min(MEETING_VIDEO_CHUNK_SIZE)),
+        _ => chunk(0),
+    }
+}
+
+/// A part of the recording of a meeting, with what we need to send it back as the response to a
+/// ranged request.
+#[derive(Debug, Clone, Default)]
+pub struct RecordedMeetingVideo {
+    /// Either 200 or 206.
+    pub status: u16,
+    pub content_type: String,
+    /// Empty if Drive sent the whole video.
+    pub content_range: String,
+    pub body: Vec<u8>,
+}
+
+/// Get a part of the recording of a meeting from Drive, so internal tools can play it without
+/// the file being shared with the people watching. Check who is watching with `can_view` first.
+pub async fn get_recorded_meeting_video(
+    db: &Database,
+    company: &Company,
+    meeting: &RecordedMeeting,
+    range: Option<&str>,
+) -> Result<RecordedMeetingVideo> {
+    if meeting.cio_company_id != company.id {
+        bail!("recorded meeting {} is not from company `{}`", meeting.id, company.name);
+    }
+    if meeting.purged_at.is_some() || meeting.video.is_empty() {
+        bail!("recorded meeting {} has no video", meeting.id);
+    }
+
+    let video_id = meeting
+        .video
+        .trim_start_matches("https://drive.google.com/open?id=")
+        .trim_start_matches("https://drive.google.com/file/d/")
+        .trim_end_matches("/view?usp=drive_web")
+        .to_string();
+
+    crate::rate_limit::acquire(crate::rate_limit::Provider::Google).await;
+    let token = company.google_access_token(db).await?;
+    let resp = reqwest::Client::new()
+        .get(&format!(
+            "https://www.googleapis.com/drive/v3/files/{}?alt=media&supportsAllDrives=true",
+            video_id
+        ))
+        .bearer_auth(token)
+        .header(reqwest::header::RANGE, meeting_video_range(range))
+        .send()
+        .await?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        bail!(
+            "getting the video of recorded meeting {} from Drive failed with {}: {}",
+            meeting.id,
+            status,
+            resp.text().await.unwrap_or_default()
+        );
+    }
+
+    let header = |name: reqwest::header::HeaderName| -> String {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let content_type = header(reqwest::header::CONTENT_TYPE);
+    let content_range = header(reqwest::header::CONTENT_RANGE);
+
+    Ok(RecordedMeetingVideo {
+        status: status.as_u16(),
+        content_type: if content_type.is_empty() {
+            "video/mp4".to_string()
+        } else {
+            content_type
+        },
+        content_range,
+        body: resp.bytes().await?.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use google_calendar::types::{Event, EventDateTime};
+
+    use super::*;
+    use crate::tests::mock_record;
+
+    fn mock_event(summary: &str, hangout_link: &str, start_hour: u32, end_hour: u32) -> Event {
+        let mut event: Event = Default::default();
+        event.summary = summary.to_string();
+        event.hangout_link = hangout_link.to_string();
+        event.start = Some(EventDateTime {
+            time_zone: "".to_string(),
+            date: None,
+            date_time: Some(Utc.ymd(2022, 5, 20).and_hms(start_hour, 0, 0)),
+        });
+        event.end = Some(EventDateTime {
+            time_zone: "".to_string(),
+            date: None,
+            date_time: Some(Utc.ymd(2022, 5, 20).and_hms(end_hour, 0, 0)),
+        });
+        event
+    }
+
+    #[test]
+    fn test_zoom_sync_from() {
+        let now = Utc.ymd(2023, 3, 31).and_hms(12, 0, 0);
+
+        assert_eq!(Utc.ymd(2023, 3, 1).and_hms(12, 0, 0), zoom_sync_from(None, 0, now));
+        assert_eq!(Utc.ymd(2023, 3, 24).and_hms(12, 0, 0), zoom_sync_from(None, 7, now));
+
+        let cursor = Utc.ymd(2023, 3, 30).and_hms(9, 30, 0);
+        assert_eq!(cursor, zoom_sync_from(Some(cursor), 7, now));
+
+        // A cursor from before the window is ignored.
+        let cursor = Utc.ymd(2023, 1, 2).and_hms(9, 30, 0);
+        assert_eq!(
+            Utc.ymd(2023, 3, 24).and_hms(12, 0, 0),
+            zoom_sync_from(Some(cursor), 7, now)
+        );
+    }
+
+    #[test]
+    fn test_zoom_sync_cursor_after() {
+        let at = |hour: u32| Utc.ymd(2023, 3, 30).and_hms(hour, 0, 0);
+
+        assert_eq!(None, zoom_sync_cursor_after(&[]));
+        assert_eq!(
+            Some(at(11)),
+            zoom_sync_cursor_after(&[(at(11), true), (at(9), true), (at(10), true)])
+        );
+        // The recordings after a failure are synced, but the cursor stays before it.
+        assert_eq!(
+            Some(at(9)),
+            zoom_sync_cursor_after(&[(at(11), true), (at(10), false), (at(9), true)])
+        );
+        assert_eq!(None, zoom_sync_cursor_after(&[(at(11), true), (at(9), false)]));
+    }
+
+    #[test]
+    fn test_meet_recording_title() {
+        assert_eq!(
+            "Weekly sync",
+            meet_recording_title("Weekly sync (2021-05-20 at 09:59 GMT-7)")
+        );
+        assert_eq!(
+            "abc-defg-hij",
+            meet_recording_title("abc-defg-hij (2021-05-20 09:59 GMT-7).mp4")
+        );
+        assert_eq!(
+            "Demo (internal)",
+            meet_recording_title("Demo (internal) (2021-05-20 09:59 GMT-7)")
+        );
+        assert_eq!("Hardware", meet_recording_title("Hardware"));
+    }
+
+    #[test]
+    fn test_meet_code() {
+        assert_eq!("abc-defg-hij", meet_code("https://meet.google.com/abc-defg-hij"));
+        assert_eq!(
+            "abc-defg-hij",
+            meet_code("https://meet.google.com/abc-defg-hij?authuser=0")
+        );
+    }
+
+    #[test]
+    fn test_local_recording_key() {
+        let metadata = LocalRecordingMetadata {
+            name: " Offsite's Roadmap Review ".to_string(),
+            start_time: Utc.ymd(2024, 4, 15).and_hms(14, 30, 0),
+            ..Default::default()
+        };
+
+        let mut company = crate::companies::tests::mock_company();
+        company.id = 1;
+        assert_eq!("local-1-20240415T143000-offsite-roadmap-review", metadata.key(&company));
+
+        company.id = 2;
+        assert_eq!("local-2-20240415T143000-offsite-roadmap-review", metadata.key(&company));
+    }
+
+    #[test]
+    fn test_find_event_for_recording() {
+        let events = vec![
+            mock_event("Weekly sync", "https://meet.google.com/aaa-bbbb-ccc", 9, 10),
+            mock_event("Hardware", "https://meet.google.com/ddd-eeee-fff", 10, 11),
+            mock_event("Weekly sync", "https://meet.google.com/aaa-bbbb-ccc", 14, 15),
+        ];
+
+        let recorded_at = Utc.ymd(2022, 5, 20).and_hms(10, 5, 0);
+        assert_eq!(
+            Some("Weekly sync"),
+            find_event_for_recording(&events, "weekly sync", recorded_at).map(|e| e.summary.as_str())
+        );
+
+        // Match on the meet code when the recording is named after it.
+        let found = find_event_for_recording(&events, "ddd-eeee-fff", recorded_at).unwrap();
+        assert_eq!("Hardware", found.summary);
+
+        // The later weekly sync had not started yet.
+        let found = find_event_for_recording(&events, "Weekly sync", Utc.ymd(2022, 5, 20).and_hms(15, 30, 0)).unwrap();
+        assert_eq!(
+            Some(Utc.ymd(2022, 5, 20).and_hms(14, 0, 0)),
+            found.start.as_ref().unwrap().date_time
+        );
+
+        assert!(find_event_for_recording(&events, "Unknown", recorded_at).is_none());
+    }
+
+    #[test]
+    fn test_render_transcript_segments() {
+        let segment = |position: i32, speaker: &str, start_offset: f64, text: &str| RecordedMeetingTranscriptSegment {
+            id: position,
+            recorded_meeting_id: 1,
+            position,
+            speaker: speaker.to_string(),
+            start_offset,
+            end_offset: start_offset + 1.0,
+            text: text.to_string(),
+        };
+
+        assert_eq!(
+            "[00:00:02] Speaker 1: Hello, everyone.\n[01:02:05] Jane: Thanks for coming.",
+            render_transcript_segments(&[
+                segment(0, "Speaker 1", 2.5, "Hello, everyone."),
+                segment(1, "Jane", 3725.0, "Thanks for coming."),
+            ])
+        );
+        assert_eq!("", render_transcript_segments(&[]));
+    }
+
+    #[test]
+    fn test_find_user_by_email() {
+        let mut company = crate::companies::tests::mock_company();
+        company.gsuite_domain = "corp.super.computer".to_string();
+        let users = vec![crate::configs::tests::mock_user()];
+
+        let found = |email: &str| find_user_by_email(&users, &company, email).map(|u| u.id);
+        assert_eq!(Some(1), found("random-test@testemaildomain.com"));
+        assert_eq!(Some(1), found("Random_Username@super.computer"));
+        assert_eq!(Some(1), found("al2@corp.super.computer"));
+        assert_eq!(None, found("al2@elsewhere.com"));
+        assert_eq!(None, found("someone@super.computer"));
+        assert_eq!(None, found("random_username"));
+    }
+
+    #[test]
+    fn test_parse_transcript_cues() {
+        let cue = |speaker: &str, start_offset: f64, end_offset: f64, text: &str| TranscriptSegment {
+            speaker: speaker.to_string(),
+            start_offset,
+            end_offset,
+            text: text.to_string(),
+        };
+
+        let vtt = "WEBVTT
+
+NOTE written by hand
+
+1
+00:00:01.000 --> 00:00:04.500 align:start
+Jane Doe: Hello, everyone.
+
+2
+00:00:04.500 --> 00:00:06.000
+Jane Doe: Let's get started.
+
+00:01.000 --> 01:02:03.250
+<v.loud Sam>Thanks <b>Jane</b>.</v>
+";
+        let cues = parse_transcript_cues(vtt);
+        assert_eq!(
+            vec![
+                cue("Jane Doe", 1.0, 4.5, "Hello, everyone."),
+                cue("Jane Doe", 4.5, 6.0, "Let's get started."),
+                cue("Sam", 1.0, 3723.25, "Thanks Jane."),
+            ],
+            cues
+        );
+        assert_eq!(
+            "Jane Doe: Hello, everyone. Let's get started.\nSam: Thanks Jane.",
+            transcript_cues_to_text(&cues)
+        );
+
+        let srt = "1\r\n00:00:01,000 --> 00:00:02,000\r\nNo speaker here\r\non two lines\r\n\r\n2\r\n00:00:02,000 --> 00:00:03,000\r\nSam: Bye\r\n";
+        assert_eq!(
+            vec![
+                cue("", 1.0, 2.0, "No speaker here on two lines"),
+                cue("Sam", 2.0, 3.0, "Bye"),
+            ],
+            parse_transcript_cues(srt)
+        );
+
+        assert_eq!(
+            ("not a transcript".to_string(), vec![]),
+            transcript_from_vtt("not a transcript")
+        );
+    }
+
+    #[test]
+    fn test_meeting_visibility() {
+        assert_eq!(MeetingVisibility::PublicInternal, MeetingVisibility::from_field(""));
+        assert_eq!(
+            MeetingVisibility::Restricted,
+            MeetingVisibility::from_field(" Restricted ")
+        );
+        assert_eq!(
+            "public-internal",
+            MeetingVisibility::from_field("public-internal").to_string()
+        );
+        // A typo must not make a meeting more visible.
+        assert_eq!(MeetingVisibility::Private, MeetingVisibility::from_field("restircted"));
+    }
+
+    #[test]
+    fn test_sync_report() {
+        let mut report = SyncReport {
+            synced: 2,
+            ..Default::default()
+        };
+        assert_eq!("2 synced, 0 failed", report.to_string());
+
+        report.add_failure("Standup", anyhow::anyhow!("no start time"));
+        assert_eq!("2 synced, 1 failed\n- `Standup`: no start time", report.to_string());
+    }
+
+    fn mock_meeting(name: &str, attendees: &[&str], participants: &[&str]) -> RecordedMeeting {
+        let mut meeting: RecordedMeeting = mock_record(json!({
+            "start_time": "2023-06-26T17:00:00Z",
+            "end_time": "2023-06-26T17:30:00Z",
+        }));
+        meeting.name = name.to_string();
+        meeting.is_recurring = true;
+        meeting.attendees = attendees.iter().map(|a| a.to_string()).collect();
+        meeting.participants = participants.iter().map(|p| p.to_string()).collect();
+
+        meeting
+    }
+
+    #[test]
+    fn test_can_view() {
+        let mut company = crate::companies::tests::mock_company();
+        company.gsuite_domain = "example.com".to_string();
+
+        let mut meeting = mock_meeting("1:1", &["jess@example.com", "sam@example.com"], &["kim@example.com"]);
+        meeting.host = "jess@example.com".to_string();
+
+        assert!(meeting.can_view(&company, "alex@example.com"));
+        assert!(!meeting.can_view(&company, "alex@elsewhere.com"));
+        assert!(!meeting.can_view(&company, ""));
+
+        meeting.visibility = "restricted".to_string();
+        assert!(meeting.can_view(&company, "Sam@Example.com"));
+        assert!(meeting.can_view(&company, "kim@example.com"));
+        assert!(!meeting.can_view(&company, "alex@example.com"));
+
+        meeting.visibility = "private".to_string();
+        assert!(meeting.can_view(&company, "jess@example.com"));
+        assert!(!meeting.can_view(&company, "sam@example.com"));
+
+        // A typo never makes a meeting more visible.
+        meeting.visibility = "pubic".to_string();
+        assert!(!meeting.can_view(&company, "sam@example.com"));
+    }
+
+    #[test]
+    fn test_meeting_video_range() {
+        assert_eq!("bytes=0-8388607", meeting_video_range(None));
+        assert_eq!("bytes=0-8388607", meeting_video_range(Some("bytes=0-")));
+        assert_eq!("bytes=100-199", meeting_video_range(Some("bytes=100-199")));
+        assert_eq!("bytes=100-8388707", meeting_video_range(Some("bytes=100-999999999")));
+        assert_eq!("bytes=-500", meeting_video_range(Some("bytes=-500")));
+        assert_eq!("bytes=0-8388607", meeting_video_range(Some("items=0-10")));
+        assert_eq!("bytes=0-8388607", meeting_video_range(Some("bytes=10-5")));
+    }
+
+    #[test]
+    fn test_recurring_meeting_attendance() {
+        let everyone = [
+            "jess@example.com",
+            "sam@example.com",
+            "alex@example.com",
+            "kim@example.com",
+        ];
+        let meetings = vec![
+            mock_meeting("Standup", &everyone, &["jess@example.com", "sam@example.com"]),
+            mock_meeting(
+                "Standup",
+                &everyone,
+                &["jess@example.com", "Sam@Example.com", "kim@example.com"],
+            ),
+            // We do not know who joined this one, it does not count.
+            mock_meeting("Standup", &everyone, &[]),
+            mock_meeting("Demo day", &["jess@example.com"], &["jess@example.com"]),
+        ];
+
+        assert_eq!(
+            vec!["alex@example.com".to_string(), "kim@example.com".to_string()],
+            meetings[0].no_shows()
+        );
+        assert!(meetings[2].no_shows().is_empty());
+
+        let report = recurring_meeting_attendance(&meetings);
+        assert_eq!(
+            vec!["Standup", "Demo day"],
+            report.iter().map(|r| r.name.as_str()).collect::<Vec<&str>>()
+        );
+        assert_eq!(2, report[0].meetings);
+        assert_eq!(5.0 / 8.0, report[0].attendance_rate);
+        assert_eq!(
+            InviteeAttendance {
+                email: "alex@example.com".to_string(),
+                invited: 2,
+                attended: 0,
+            },
+            report[0].invitees[0]
+        );
+        assert_eq!("kim@example.com", report[0].invitees[1].email);
+    }
+
+    #[test]
+    fn test_meeting_engagement() {
+        let mut meeting = mock_meeting("All hands", &[], &[]);
+        meeting.start_time = Utc.ymd(2023, 8, 3).and_hms(17, 0, 0);
+        meeting.end_time = Utc.ymd(2023, 8, 3).and_hms(18, 0, 0);
+
+        let session = |name: &str, email: &str, join: (u32, u32), leave: (u32, u32)| {
+            let join_time = Utc.ymd(2023, 8, 3).and_hms(join.0, join.1, 0);
+            let leave_time = Utc.ymd(2023, 8, 3).and_hms(leave.0, leave.1, 0);
+            RecordedMeetingParticipant {
+                id: 1,
+                recorded_meeting_id: 1,
+                name: name.to_string(),
+                email: email.to_string(),
+                join_time: Some(join_time),
+                leave_time: Some(leave_time),
+                duration: (leave_time - join_time).num_seconds() as i32,
+            }
+        };
+        let sessions = vec![
+            session("Jess", "jess@example.com", (17, 0), (17, 30)),
+            // Jess dropped and came back.
+            session("Jess", "jess@example.com", (17, 35), (18, 0)),
+            session("Sam", "sam@example.com", (17, 10), (17, 40)),
+            session("Guest", "", (17, 30), (17, 35)),
+        ];
+
+        let engagement = meeting_engagement(&meeting, &sessions);
+        assert_eq!(3, engagement.participants);
+        assert_eq!(30.0, engagement.average_minutes);
+        assert_eq!(1.0 / 3.0, engagement.stayed_rate);
+        assert_eq!(2, engagement.peak_participants);
+
+        let report = vec![
+            ZoomParticipant {
+                user_email: "Jess@Example.com ".to_string(),
+                ..Default::default()
+            },
+            ZoomParticipant {
+                user_email: "jess@example.com".to_string(),
+                ..Default::default()
+            },
+            ZoomParticipant {
+                name: "Guest".to_string(),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(vec!["jess@example.com".to_string()], zoom_participant_emails(&report));
+    }
+
+    #[test]
+    fn test_encode_zoom_meeting_uuid() {
+        assert_eq!(
+            "4444AAAiAAAAAiAiAiiAii%3D%3D",
+            encode_zoom_meeting_uuid("4444AAAiAAAAAiAiAiiAii==")
+        );
+        assert_eq!(
+            "%252Fajf%252B%252F%252Fg%253D%253D",
+            encode_zoom_meeting_uuid("/ajf+//g==")
+        );
+    }
+}
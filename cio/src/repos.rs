@@ -12,7 +12,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     airtable::AIRTABLE_GITHUB_REPOS_TABLE, app_config::AppConfig, companies::Company, core::UpdateAirtableRecord,
-    db::Database, github_prs::FromSimpleUser, schema::github_repos,
+    db::Database, features::Features, github_prs::FromSimpleUser, schema::github_repos,
 };
 
 /// The data type for a GitHub user.
@@ -365,76 +365,39 @@ impl NewRepo {
     }
 }
 
-impl GithubRepo {
-    /**
-     * Set default configurations for the repo in the GitHub organization.
-     *
-     * The defaults are as follows:
-     *
-     * - Give the GitHub teams: "eng" and "all", push access to every repository.
-     * - Turns off the wiki.
-     * - Adds protection to the default branch to disallow force pushes.
-     * - Adds outside collaborators to their specified repositories.
-     */
-    pub async fn sync_settings(&self, github: &octorust::Client, company: &Company) -> Result<()> {
-        // Skip archived repositories.
-        if self.archived {
-            return Ok(());
-        }
+/// The GitHub teams we give push access to every repository.
+// TODO: do not hard code these.
+pub static DEFAULT_REPO_TEAMS: &[&str] = &["all", "eng"];
 
-        // Skip "fluffy-tribble"
-        if self.name == "fluffy-tribble" {
-            return Ok(());
-        }
+/// Returns if a team can already push to a repository.
+pub fn team_has_push_access(team: &octorust::types::Team) -> bool {
+    team.permission == octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission::Push.to_string()
+        || team.permission.to_lowercase() == *"admin"
+}
 
-        // Set the array of default teams to add to the repo.
-        // TODO: do not hard code these.
-        let default_teams = vec!["all".to_string(), "eng".to_string()];
+impl GithubRepo {
+    /// Returns if we manage the settings of the repo.
+    pub fn has_managed_settings(&self) -> bool {
+        // Skip archived repositories, and "fluffy-tribble".
+        !self.archived && self.name != "fluffy-tribble"
+    }
 
-        // Get the branch protection for the repo.
-        let branch = github
+    /// Returns if the default branch of the repo is protected, or `None` if the repo does not
+    /// have its default branch yet.
+    pub async fn is_default_branch_protected(
+        &self,
+        github: &octorust::Client,
+        company: &Company,
+    ) -> Result<Option<bool>> {
+        match github
             .repos()
             .get_branch(&company.github_org, &self.name, &self.default_branch)
-            .await;
-
-        match branch {
-            Ok(default_branch) => {
-                // Add branch protection to disallow force pushing to the default branch.
-                // Only do this if it is not already protected.
-                if !default_branch.protected {
-                    match github
-                        .repos()
-                        .update_branch_protection(
-                            &company.github_org,
-                            &self.name,
-                            &self.default_branch,
-                            &octorust::types::ReposUpdateBranchProtectionRequest {
-                                allow_deletions: Default::default(),
-                                allow_force_pushes: Default::default(),
-                                enforce_admins: Some(true),
-                                required_conversation_resolution: Default::default(),
-                                required_linear_history: Default::default(),
-                                required_pull_request_reviews: None,
-                                required_status_checks: None,
-                                restrictions: None,
-                            },
-                        )
-                        .await
-                    {
-                        Ok(_) => (),
-                        Err(e) => {
-                            info!("Failed to update branch protection on {}. err: {:?}", self.name, e);
-
-                            if !e.to_string().contains("empty repository") {
-                                bail!("could not update protection for repo {}: {}", self.name, e);
-                            }
-                        }
-                    }
-                }
-            }
+            .await
+        {
+            Ok(default_branch) => Ok(Some(default_branch.protected)),
             Err(err) => {
                 info!(
-                    "Failed to find default branch {} on {}. Returning early. err: {:?}",
+                    "Failed to find default branch {} on {}. err: {:?}",
                     self.default_branch, self.name, err
                 );
 
@@ -446,9 +409,52 @@ impl GithubRepo {
                         err
                     );
                 }
+
+                Ok(None)
+            }
+        }
+    }
+
+    /// Add branch protection to disallow force pushing to the default branch.
+    pub async fn protect_default_branch(&self, github: &octorust::Client, company: &Company) -> Result<()> {
+        match github
+            .repos()
+            .update_branch_protection(
+                &company.github_org,
+                &self.name,
+                &self.default_branch,
+                &octorust::types::ReposUpdateBranchProtectionRequest {
+                    allow_deletions: Default::default(),
+                    allow_force_pushes: Default::default(),
+                    enforce_admins: Some(true),
+                    required_conversation_resolution: Default::default(),
+                    required_linear_history: Default::default(),
+                    required_pull_request_reviews: None,
+                    required_status_checks: None,
+                    restrictions: None,
+                },
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                info!("Failed to update branch protection on {}. err: {:?}", self.name, e);
+
+                if !e.to_string().contains("empty repository") {
+                    bail!("could not update protection for repo {}: {}", self.name, e);
+                }
+
+                Ok(())
             }
         }
+    }
 
+    /// Get the teams of the repo, by name.
+    pub async fn list_teams(
+        &self,
+        github: &octorust::Client,
+        company: &Company,
+    ) -> Result<BTreeMap<String, octorust::types::Team>> {
         // Get this repository's teams.
         let mut ts: Vec<octorust::types::Team> = Default::default();
         match github.repos().list_all_teams(&company.github_org, &self.name).await {
@@ -469,13 +475,76 @@ impl GithubRepo {
             teams.insert(t.name.to_string(), t);
         }
 
-        // For each team id, add the team to the permissions.
-        for team_name in &default_teams {
-            let perms = octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission::Push;
+        Ok(teams)
+    }
+
+    /// Give a team push access to the repo.
+    pub async fn give_team_push_access(
+        &self,
+        github: &octorust::Client,
+        company: &Company,
+        team_name: &str,
+    ) -> Result<()> {
+        match github
+            .teams()
+            .add_or_update_repo_permissions_in_org(
+                &company.github_org,
+                team_name,
+                &company.github_org,
+                &self.name,
+                &octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequest {
+                    permission: Some(octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequestPermission::Push),
+                },
+            )
+            .await
+        {
+            Ok(_) => (),
+            Err(e) => {
+                info!("Failed to update repo permissions on {}. err: {:?}", self.name, e);
+                bail!(
+                    "adding repo permission for team {} in repo {} failed: {}",
+                    team_name,
+                    self.name,
+                    e
+                )
+            }
+        }
+
+        info!(
+            "gave team {} push access to {}/{}",
+            team_name, company.github_org, self.name
+        );
 
+        Ok(())
+    }
+
+    /**
+     * Set default configurations for the repo in the GitHub organization.
+     *
+     * The defaults are as follows:
+     *
+     * - Give the GitHub teams: "eng" and "all", push access to every repository.
+     * - Turns off the wiki.
+     * - Adds protection to the default branch to disallow force pushes.
+     * - Adds outside collaborators to their specified repositories.
+     */
+    pub async fn sync_settings(&self, github: &octorust::Client, company: &Company) -> Result<()> {
+        if !self.has_managed_settings() {
+            return Ok(());
+        }
+
+        // Only add the protection if it is not already protected.
+        if self.is_default_branch_protected(github, company).await? == Some(false) {
+            self.protect_default_branch(github, company).await?;
+        }
+
+        let teams = self.list_teams(github, company).await?;
+
+        // For each team id, add the team to the permissions.
+        for team_name in DEFAULT_REPO_TEAMS {
             // Check if the team already has the permission.
-            if let Some(val) = teams.get(team_name) {
-                if val.permission == perms.to_string() || val.permission.to_lowercase() == *"admin" {
+            if let Some(val) = teams.get(*team_name) {
+                if team_has_push_access(val) {
                     // Continue since they already have permission.
                     info!(
                         "team {} already has push access to {}/{}",
@@ -486,35 +555,7 @@ impl GithubRepo {
                 }
             }
 
-            match github
-                .teams()
-                .add_or_update_repo_permissions_in_org(
-                    &company.github_org,
-                    team_name,
-                    &company.github_org,
-                    &self.name,
-                    &octorust::types::TeamsAddUpdateRepoPermissionsInOrgRequest {
-                        permission: Some(perms),
-                    },
-                )
-                .await
-            {
-                Ok(_) => (),
-                Err(e) => {
-                    info!("Failed to update repo permissions on {}. err: {:?}", self.name, e);
-                    bail!(
-                        "adding repo permission for team {} in repo {} failed: {}",
-                        team_name,
-                        self.name,
-                        e
-                    )
-                }
-            }
-
-            info!(
-                "gave team {} push access to {}/{}",
-                team_name, company.github_org, self.name
-            );
+            self.give_team_push_access(github, company, team_name).await?;
         }
 
         Ok(())
@@ -594,6 +635,12 @@ pub async fn refresh_db_github_repos(db: &Database, company: &Company) -> Result
  * - Adds outside collaborators to their specified repositories.
  */
 pub async fn sync_all_repo_settings(db: &Database, company: &Company, app_config: &AppConfig) -> Result<()> {
+    if Features::is_enabled("RECONCILE_PLANS") {
+        // The settings are changed by applying the plans of the repo settings reconciler.
+        info!("reconcile plans are enabled, not syncing the settings of the repos");
+        return Ok(());
+    }
+
     let github = company.authenticate_github()?;
     let repos = GithubRepos::get_from_db(db, company.id).await?;
 
@@ -8,10 +8,14 @@ use std::{
     path::{Path, PathBuf},
     process::Command,
     str::from_utf8,
+    sync::Arc,
 };
 use uuid::Uuid;
 
-use super::{GitHubRFDBranch, RFDNumber, RFDPdf};
+use super::{
+    renderer::{run_command, RFDPdfRenderer},
+    GitHubRFDBranch, RFDNumber, RFDPdf,
+};
 use crate::utils::{decode_base64, write_file};
 
 // TODO: RFDNumber should probably be stored with the content as it doesn't parsing content with a
@@ -112,13 +116,14 @@ impl<'a> RFDContent<'a> {
     /// RFD number on a specific branch. Markdown documents do not support PDF generation
     pub async fn to_pdf(
         &self,
+        renderer: Arc<dyn RFDPdfRenderer>,
         title: &str,
         number: &RFDNumber,
         branch: &GitHubRFDBranch,
     ) -> Result<RFDPdf, RFDOutputError> {
         match self {
             Self::Asciidoc(adoc) => adoc
-                .to_pdf(title, number, branch)
+                .to_pdf(renderer, title, number, branch)
                 .await
                 .map_err(RFDOutputError::Generic),
             _ => Err(RFDOutputError::FormatNotSupported(RFDOutputFormat::Pdf)),
@@ -237,6 +242,22 @@ impl<'a> RFDContent<'a> {
         }
     }
 
+    /// Get the repositories, components and issues the document says it affects, from an
+    /// `affects:` line with a comma separated list. If there is none, then an empty list is
+    /// returned
+    pub fn get_affects(&self) -> Vec<String> {
+        let re = Regex::new(r"(?m)^:?affects:(.*$)").unwrap();
+
+        match re.captures(self.raw()) {
+            Some(c) => c[1]
+                .split(',')
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect(),
+            None => Default::default(),
+        }
+    }
+
     /// Get the authors line stored within the document. The returned string may contain multiple
     /// names. If none can be found, then and empty string is returned
     pub fn get_authors(&self) -> String {
@@ -307,10 +328,19 @@ impl<'a> RFDAsciidoc<'a> {
 
     /// Generate a PDF by combining RFD contents with static resources that are stored for a given
     /// RFD number on a specific branch. Markdown documents do not support PDF generation
-    pub async fn to_pdf(&self, title: &str, number: &RFDNumber, branch: &GitHubRFDBranch) -> Result<RFDPdf> {
+    pub async fn to_pdf(
+        &self,
+        renderer: Arc<dyn RFDPdfRenderer>,
+        title: &str,
+        number: &RFDNumber,
+        branch: &GitHubRFDBranch,
+    ) -> Result<RFDPdf> {
         self.download_images(number, branch).await?;
 
-        let content = self.parse(RFDOutputFormat::Pdf).await?;
+        info!("[asciidoc] Rendering PDF with {}", renderer.name());
+        let content = self
+            .render(move |storage_path, file_path| renderer.render(storage_path, file_path))
+            .await?;
 
         let filename = format!(
             "RFD {} {}.pdf",
@@ -330,6 +360,25 @@ impl<'a> RFDAsciidoc<'a> {
     async fn parse(&self, format: RFDOutputFormat) -> Result<Vec<u8>> {
         info!("[asciidoc] Parsing asciidoc file");
 
+        self.render(move |storage_path, file_path| {
+            info!(
+                "[asciidoc] Shelling out to asciidoctor {:?} / {:?}",
+                storage_path, file_path
+            );
+            run_command(
+                &mut format.command(&storage_path.to_path_buf(), file_path),
+                "asciidoctor",
+            )
+        })
+        .await
+    }
+
+    /// Write the asciidoc content to the temporary directory, next to the images, and run the
+    /// blocking `render` on it.
+    async fn render<F>(&self, render: F) -> Result<Vec<u8>>
+    where
+        F: FnOnce(&Path, &Path) -> Result<Vec<u8>> + Send + 'static,
+    {
         // Create the path to the local tmp file for holding the asciidoc contents
         let storage_path = self.tmp_path();
         let file_path = storage_path.join("contents.adoc");
@@ -339,38 +388,17 @@ impl<'a> RFDAsciidoc<'a> {
 
         info!("[asciidoc] Wrote file to temp dir {:?}", file_path);
 
-        let cmd_output = tokio::task::spawn_blocking(enclose! { (storage_path, file_path) move || {
-            info!("[asciidoc] Shelling out to asciidoctor {:?} / {:?}", storage_path, file_path);
-            let out = format.command(&storage_path, &file_path).output();
-
-            match &out {
-                Ok(_) => info!("[asciidoc] Command succeeded {:?} / {:?}", storage_path, file_path),
-                Err(err) => info!("[asciidoc] Command failed: {} {:?} / {:?}", err, storage_path, file_path)
-            };
-
-            out
-        }})
-        .await??;
+        let result = tokio::task::spawn_blocking(move || render(&storage_path, &file_path)).await?;
 
         info!("[asciidoc] Completed asciidoc rendering");
 
-        let result = if cmd_output.status.success() {
-            cmd_output.stdout
-        } else {
-            bail!(
-                "[rfds] running asciidoctor failed: {} {}",
-                from_utf8(&cmd_output.stdout)?,
-                from_utf8(&cmd_output.stderr)?
-            );
-        };
-
         if let Err(err) = self.cleanup_tmp_path() {
-            log::error!("Failed to clean up temporary working files for {:?} {:?}", format, err);
+            log::error!("Failed to clean up temporary working files {:?}", err);
         }
 
         info!("[asciidoc] Finished cleanup and returning");
 
-        Ok(result)
+        result
     }
 
     /// Downloads images that are stored on the provided GitHub branch for the given RFD number.
@@ -601,6 +629,38 @@ discussion: https://github.com/company/repo/pull/123
         }
     }
 
+    #[test]
+    fn test_get_affects() {
+        let content = RFDContent::new(
+            r#"
+---
+authors: FirstName LastName <fname@company.org>
+state: discussion
+affects: omicron, company/propolis#12 ,
+---
+
+# RFD 123"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["omicron".to_string(), "company/propolis#12".to_string()],
+            content.get_affects()
+        );
+
+        let content = RFDContent::new(
+            r#"
+:showtitle:
+:state: published
+:affects: crucible
+"#,
+        )
+        .unwrap();
+        assert_eq!(vec!["crucible".to_string()], content.get_affects());
+
+        let content = RFDContent::new("state: discussion\n\n# RFD 123").unwrap();
+        assert!(content.get_affects().is_empty());
+    }
+
     #[test]
     fn test_inspect_fails_on_indeterminate_content() {
         let content = RFDContent::new(
@@ -0,0 +1,217 @@
+/// A section of an RFD, from one heading to the next.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RFDSection {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/// Returns the title of a heading line, for asciidoc (`== Title`) and markdown (`## Title`).
+/// The document title (`= Title` or `# Title`) is not a section.
+fn heading_title(line: &str) -> Option<String> {
+    for marker in ['=', '#'] {
+        let level = line.chars().take_while(|c| *c == marker).count();
+        if level >= 2 && line[level..].starts_with(' ') {
+            return Some(line[level..].trim().to_string());
+        }
+    }
+
+    None
+}
+
+/// Split the content of an RFD into its sections. What comes before the first section, like
+/// the title and the metadata the bot updates, is left out.
+pub fn rfd_sections(content: &str) -> Vec<RFDSection> {
+    let mut sections: Vec<RFDSection> = Vec::new();
+    let mut in_code_block = false;
+    for line in content.lines() {
+        // Comments in code blocks can look like markdown headings.
+        if line.starts_with("```") || line.starts_with("----") {
+            in_code_block = !in_code_block;
+        }
+
+        if !in_code_block {
+            if let Some(title) = heading_title(line) {
+                sections.push(RFDSection { title, lines: vec![] });
+                continue;
+            }
+        }
+
+        if let Some(section) = sections.last_mut() {
+            section.lines.push(line.trim_end().to_string());
+        }
+    }
+
+    sections
+}
+
+/// Count the lines added and removed between two versions of a section, from their longest
+/// common subsequence.
+fn count_changed_lines(old: &[String], new: &[String]) -> (usize, usize) {
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let common = lengths[0][0];
+    (new.len() - common, old.len() - common)
+}
+
+/// A section that changed, with the number of lines added and removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RFDSectionChange {
+    pub title: String,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+}
+
+/// How the sections of an RFD changed between two versions of its content.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RFDChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub updated: Vec<RFDSectionChange>,
+}
+
+impl RFDChanges {
+    /// Compare the sections of two versions of the content of an RFD. Sections are matched
+    /// by title, so a renamed section is removed and added.
+    pub fn new(old_content: &str, new_content: &str) -> Self {
+        let old = rfd_sections(old_content);
+        let new = rfd_sections(new_content);
+
+        let mut changes = RFDChanges::default();
+        for section in &new {
+            match old.iter().find(|s| s.title == section.title) {
+                Some(previous) if previous.lines == section.lines => {}
+                Some(previous) => {
+                    let (added_lines, removed_lines) = count_changed_lines(&previous.lines, &section.lines);
+                    changes.updated.push(RFDSectionChange {
+                        title: section.title.to_string(),
+                        added_lines,
+                        removed_lines,
+                    });
+                }
+                None => changes.added.push(section.title.to_string()),
+            }
+        }
+        for section in &old {
+            if !new.iter().any(|s| s.title == section.title) {
+                changes.removed.push(section.title.to_string());
+            }
+        }
+
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+
+    /// Summarize the changes, like `sections Background and Goals updated, Risks added`.
+    pub fn summary(&self) -> String {
+        let list = |titles: Vec<&str>| -> String {
+            match titles.as_slice() {
+                [] => String::new(),
+                [title] => format!("section {}", title),
+                [rest @ .., last] => format!("sections {} and {}", rest.join(", "), last),
+            }
+        };
+
+        let mut parts: Vec<String> = Vec::new();
+        if !self.updated.is_empty() {
+            parts.push(format!(
+                "{} updated",
+                list(self.updated.iter().map(|c| c.title.as_str()).collect())
+            ));
+        }
+        if !self.added.is_empty() {
+            parts.push(format!(
+                "{} added",
+                list(self.added.iter().map(|t| t.as_str()).collect())
+            ));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!(
+                "{} removed",
+                list(self.removed.iter().map(|t| t.as_str()).collect())
+            ));
+        }
+
+        parts.join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rfd_sections, RFDChanges, RFDSectionChange};
+
+    #[test]
+    fn test_rfd_sections() {
+        let content = r#":state: discussion
+
+= RFD 123 Things
+
+== Background
+
+Some background.
+
+```
+## not a heading
+```
+
+=== Details
+
+#### Markdown works too
+"#;
+        let sections = rfd_sections(content);
+        let titles: Vec<&str> = sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(vec!["Background", "Details", "Markdown works too"], titles);
+        assert!(sections[0].lines.contains(&"## not a heading".to_string()));
+    }
+
+    #[test]
+    fn test_rfd_changes() {
+        let old = "= RFD 1 Things\n:state: discussion\n\n== Background\n\nOne.\nTwo.\n\n== Goals\n\nGoal.\n\n== Old\n\nGone.\n";
+        let new = "= RFD 1 Things\n:state: published\n\n== Background\n\nOne.\nTwo and a half.\nThree.\n\n== Goals\n\nGoal.\n\n== Risks\n\nRisk.\n";
+
+        let changes = RFDChanges::new(old, new);
+        assert_eq!(
+            vec![RFDSectionChange {
+                title: "Background".to_string(),
+                added_lines: 2,
+                removed_lines: 1,
+            }],
+            changes.updated
+        );
+        assert_eq!(vec!["Risks".to_string()], changes.added);
+        assert_eq!(vec!["Old".to_string()], changes.removed);
+        assert_eq!(
+            "section Background updated; section Risks added; section Old removed",
+            changes.summary()
+        );
+
+        // Only the metadata changed.
+        assert!(RFDChanges::new(old, &old.replace("discussion", "published")).is_empty());
+
+        let mut more = changes.clone();
+        more.updated.push(RFDSectionChange {
+            title: "Goals".to_string(),
+            added_lines: 1,
+            removed_lines: 0,
+        });
+        more.updated.push(RFDSectionChange {
+            title: "Risks".to_string(),
+            added_lines: 1,
+            removed_lines: 0,
+        });
+        more.added.clear();
+        more.removed.clear();
+        assert_eq!("sections Background, Goals and Risks updated", more.summary());
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    airtable::AIRTABLE_RFD_DISCUSSION_COMMENTS_TABLE,
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    rfd::{reviews::get_rfd_reviews, RFDs, RFD},
+    schema::{rfd_discussion_comments as r_f_d_discussion_comments, rfd_discussion_comments},
+};
+
+/// The kinds of comments in the discussion of an RFD.
+pub static RFD_DISCUSSION_COMMENT_KIND_REVIEW: &str = "review";
+pub static RFD_DISCUSSION_COMMENT_KIND_ISSUE: &str = "issue";
+
+/// A comment in the discussion of an RFD, either a review comment on a line of the pull
+/// request, or a comment on the pull request or issue itself.
+#[db {
+    new_struct_name = "RFDDiscussionComment",
+    airtable_base = "roadmap",
+    airtable_table = "AIRTABLE_RFD_DISCUSSION_COMMENTS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "kind" = "String",
+        "github_id" = "i64",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = rfd_discussion_comments)]
+pub struct NewRFDDiscussionComment {
+    pub rfd_number: i32,
+    /// `review` or `issue`.
+    pub kind: String,
+    pub github_id: i64,
+    /// The first comment of the review thread the comment is in. Comments that are not in a
+    /// review thread are their own thread.
+    pub thread_id: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub author: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub body: String,
+    /// The file a review comment is on.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub html_url: String,
+    /// If the lines a review comment is on changed since.
+    #[serde(default)]
+    pub outdated: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// The RFD in Airtable.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub link_to_rfd: Vec<String>,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for an RFDDiscussionComment.
+#[async_trait]
+impl UpdateAirtableRecord<RFDDiscussionComment> for RFDDiscussionComment {
+    async fn update_airtable_record(&mut self, _record: RFDDiscussionComment) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The pull request or issue an RFD is discussed in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RFDDiscussionLink {
+    pub owner: String,
+    pub repo: String,
+    pub number: i64,
+    pub is_pull_request: bool,
+}
+
+impl RFDDiscussionLink {
+    /// Parse the discussion link of an RFD, like `https://github.com/company/rfd/pull/123`.
+    pub fn parse(link: &str) -> Option<Self> {
+        let path = link.trim().trim_start_matches("https://").strip_prefix("github.com/")?;
+        let parts: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+        match parts.as_slice() {
+            [owner, repo, kind @ ("pull" | "pulls" | "issues"), number, ..] => Some(RFDDiscussionLink {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                number: number.parse().ok()?,
+                is_pull_request: *kind != "issues",
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Count the review threads of a discussion that nobody resolved, from the first comment of
+/// each thread.
+pub fn count_unresolved_threads(comments: &[NewRFDDiscussionComment], resolved: &HashSet<i64>) -> i32 {
+    comments
+        .iter()
+        .filter(|c| {
+            c.kind == RFD_DISCUSSION_COMMENT_KIND_REVIEW
+                && c.thread_id == c.github_id
+                && !resolved.contains(&c.thread_id)
+        })
+        .count() as i32
+}
+
+static REVIEW_THREADS_QUERY: &str = r#"query($owner: String!, $repo: String!, $number: Int!, $cursor: String) {
+  repository(owner: $owner, name: $repo) {
+    pullRequest(number: $number) {
+      reviewThreads(first: 100, after: $cursor) {
+        pageInfo { hasNextPage endCursor }
+        nodes { isResolved comments(first: 1) { nodes { databaseId } } }
+      }
+    }
+  }
+}"#;
+
+/// Get the review threads of a pull request that someone resolved, by their first comment.
+/// Only the GraphQL API tells us if a thread was resolved.
+async fn get_resolved_review_threads(company: &Company, link: &RFDDiscussionLink) -> Result<HashSet<i64>> {
+    let token = company.github_installation_token().await?;
+    let client = reqwest::Client::new();
+
+    let mut resolved = HashSet::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let resp: serde_json::Value = client
+            .post("https://api.github.com/graphql")
+            .bearer_auth(&token)
+            .header(
+                "User-Agent",
+                concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")),
+            )
+            .json(&serde_json::json!({
+                "query": REVIEW_THREADS_QUERY,
+                "variables": {
+                    "owner": link.owner,
+                    "repo": link.repo,
+                    "number": link.number,
+                    "cursor": cursor,
+                },
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(errors) = resp.get("errors") {
+            bail!(
+                "getting the review threads of {}/{}#{} failed: {}",
+                link.owner,
+                link.repo,
+                link.number,
+                errors
+            );
+        }
+
+        let threads = &resp["data"]["repository"]["pullRequest"]["reviewThreads"];
+        for thread in threads["nodes"].as_array().into_iter().flatten() {
+            if thread["isResolved"].as_bool().unwrap_or_default() {
+                if let Some(id) = thread["comments"]["nodes"][0]["databaseId"].as_i64() {
+                    resolved.insert(id);
+                }
+            }
+        }
+
+        match threads["pageInfo"]["endCursor"].as_str() {
+            Some(end) if threads["pageInfo"]["hasNextPage"].as_bool().unwrap_or_default() => {
+                cursor = Some(end.to_string())
+            }
+            _ => break,
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Get the comments of the discussion of an RFD from GitHub.
+async fn get_discussion_comments(
+    github: &octorust::Client,
+    rfd: &RFD,
+    link: &RFDDiscussionLink,
+) -> Result<Vec<NewRFDDiscussionComment>> {
+    let link_to_rfd = if rfd.airtable_record_id.is_empty() {
+        vec![]
+    } else {
+        vec![rfd.airtable_record_id.to_string()]
+    };
+
+    let mut comments: Vec<NewRFDDiscussionComment> = github
+        .issues()
+        .list_all_comments(&link.owner, &link.repo, link.number, None)
+        .await?
+        .into_iter()
+        .map(|c| NewRFDDiscussionComment {
+            rfd_number: rfd.number,
+            kind: RFD_DISCUSSION_COMMENT_KIND_ISSUE.to_string(),
+            github_id: c.id,
+            thread_id: c.id,
+            author: c.user.map(|u| u.login).unwrap_or_default(),
+            body: c.body,
+            path: Default::default(),
+            html_url: c.html_url,
+            outdated: false,
+            created_at: c.created_at.unwrap_or_else(Utc::now),
+            updated_at: c.updated_at.or(c.created_at).unwrap_or_else(Utc::now),
+            link_to_rfd: link_to_rfd.clone(),
+            cio_company_id: rfd.cio_company_id,
+        })
+        .collect();
+
+    if link.is_pull_request {
+        let review_comments = github
+            .pulls()
+            .list_all_review_comments(
+                &link.owner,
+                &link.repo,
+                link.number,
+                // sort
+                Default::default(),
+                // direction
+                Default::default(),
+                // since
+                None,
+            )
+            .await?;
+
+        for c in review_comments {
+            // GitHub takes the position of a comment away once its lines changed.
+            let outdated = c.position == 0;
+            comments.push(NewRFDDiscussionComment {
+                rfd_number: rfd.number,
+                kind: RFD_DISCUSSION_COMMENT_KIND_REVIEW.to_string(),
+                github_id: c.id,
+                thread_id: if c.in_reply_to_id > 0 { c.in_reply_to_id } else { c.id },
+                author: c.user.login,
+                body: c.body,
+                path: c.path,
+                html_url: c.html_url,
+                outdated,
+                created_at: c.created_at.unwrap_or_else(Utc::now),
+                updated_at: c.updated_at.or(c.created_at).unwrap_or_else(Utc::now),
+                link_to_rfd: link_to_rfd.clone(),
+                cio_company_id: rfd.cio_company_id,
+            });
+        }
+    }
+
+    Ok(comments)
+}
+
+/// Sync the comments of the discussions of the RFDs from GitHub, and count the threads still
+/// open on each RFD, so Airtable shows which RFDs are still contentious. We also sync who
+/// reviews each RFD and who approved it.
+pub async fn refresh_rfd_discussions(db: &Database, company: &Company) -> Result<()> {
+    let github = company.authenticate_github()?;
+    let rfds = RFDs::get_from_db(db, company.id).await?;
+
+    for mut rfd in rfds.0 {
+        let link = match RFDDiscussionLink::parse(&rfd.discussion) {
+            Some(link) => link,
+            None => continue,
+        };
+
+        let comments = match get_discussion_comments(&github, &rfd, &link).await {
+            Ok(comments) => comments,
+            Err(e) => {
+                warn!("getting the discussion of RFD {} failed: {}", rfd.number, e);
+                continue;
+            }
+        };
+
+        // Remove the comments that were deleted on GitHub.
+        let existing = rfd_discussion_comments::dsl::rfd_discussion_comments
+            .filter(rfd_discussion_comments::dsl::cio_company_id.eq(company.id))
+            .filter(rfd_discussion_comments::dsl::rfd_number.eq(rfd.number))
+            .load_async::<RFDDiscussionComment>(db.pool())
+            .await?;
+        for comment in &existing {
+            if !comments
+                .iter()
+                .any(|c| c.kind == comment.kind && c.github_id == comment.github_id)
+            {
+                comment.delete(db).await?;
+            }
+        }
+
+        let unresolved = if link.is_pull_request {
+            match get_resolved_review_threads(company, &link).await {
+                Ok(resolved) => count_unresolved_threads(&comments, &resolved),
+                Err(e) => {
+                    warn!("getting the resolved threads of RFD {} failed: {}", rfd.number, e);
+                    rfd.unresolved_discussion_threads
+                }
+            }
+        } else {
+            0
+        };
+
+        // Only the new and edited comments need to go to Airtable.
+        for comment in comments {
+            let unchanged = existing.iter().any(|e| {
+                e.kind == comment.kind
+                    && e.github_id == comment.github_id
+                    && NewRFDDiscussionComment::from(e.clone()) == comment
+            });
+            if !unchanged {
+                comment.upsert(db).await?;
+            }
+        }
+
+        let (reviewers, approvals) = match get_rfd_reviews(&github, &link).await {
+            Ok(reviews) => reviews,
+            Err(e) => {
+                warn!("getting the reviews of RFD {} failed: {}", rfd.number, e);
+                (rfd.reviewers.clone(), rfd.approvals.clone())
+            }
+        };
+
+        if rfd.unresolved_discussion_threads != unresolved || rfd.reviewers != reviewers || rfd.approvals != approvals {
+            info!(
+                "RFD {} has {} unresolved discussion threads, it had {}, and {} of {} reviewers approved",
+                rfd.number,
+                unresolved,
+                rfd.unresolved_discussion_threads,
+                approvals.len(),
+                reviewers.len()
+            );
+            rfd.unresolved_discussion_threads = unresolved;
+            rfd.reviewers = reviewers;
+            rfd.approvals = approvals;
+            rfd.update(db).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use chrono::Utc;
+
+    use super::{
+        count_unresolved_threads, NewRFDDiscussionComment, RFDDiscussionLink, RFD_DISCUSSION_COMMENT_KIND_ISSUE,
+        RFD_DISCUSSION_COMMENT_KIND_REVIEW,
+    };
+
+    #[test]
+    fn test_parse_rfd_discussion_link() {
+        assert_eq!(
+            Some(RFDDiscussionLink {
+                owner: "company".to_string(),
+                repo: "rfd".to_string(),
+                number: 123,
+                is_pull_request: true,
+            }),
+            RFDDiscussionLink::parse("https://github.com/company/rfd/pull/123")
+        );
+        assert_eq!(
+            Some(false),
+            RFDDiscussionLink::parse("https://github.com/company/rfd/issues/4/").map(|l| l.is_pull_request)
+        );
+        assert_eq!(
+            None,
+            RFDDiscussionLink::parse("https://example.com/company/rfd/pull/123")
+        );
+        assert_eq!(None, RFDDiscussionLink::parse(""));
+    }
+
+    #[test]
+    fn test_count_unresolved_threads() {
+        let comment = |kind: &str, github_id: i64, thread_id: i64, outdated: bool| NewRFDDiscussionComment {
+            rfd_number: 1,
+            kind: kind.to_string(),
+            github_id,
+            thread_id,
+            author: Default::default(),
+            body: Default::default(),
+            path: Default::default(),
+            html_url: Default::default(),
+            outdated,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            link_to_rfd: Default::default(),
+            cio_company_id: 1,
+        };
+
+        let comments = vec![
+            comment(RFD_DISCUSSION_COMMENT_KIND_ISSUE, 1, 1, false),
+            comment(RFD_DISCUSSION_COMMENT_KIND_REVIEW, 2, 2, false),
+            comment(RFD_DISCUSSION_COMMENT_KIND_REVIEW, 3, 2, false),
+            comment(RFD_DISCUSSION_COMMENT_KIND_REVIEW, 4, 4, true),
+            comment(RFD_DISCUSSION_COMMENT_KIND_REVIEW, 5, 5, false),
+        ];
+        // Outdated or not, only the threads someone resolved are done.
+        assert_eq!(3, count_unresolved_threads(&comments, &HashSet::new()));
+        assert_eq!(2, count_unresolved_threads(&comments, &vec![5].into_iter().collect()));
+    }
+}
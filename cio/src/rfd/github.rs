@@ -63,6 +63,47 @@ impl GitHubRFDRepo {
         }
     }
 
+    /// Get the numbers of the RFDs that have a branch, whether or not they are in the rfd.csv
+    /// file yet.
+    pub async fn get_rfd_branch_numbers(&self) -> Result<Vec<RFDNumber>> {
+        let branches = self
+            .client
+            .repos()
+            .list_all_branches(&self.owner, &self.repo, false)
+            .await?;
+
+        Ok(branches
+            .iter()
+            .filter_map(|b| RFDNumber::from_branch(&b.name))
+            .collect())
+    }
+
+    /// Create a branch from the head of the default branch. This fails if the branch exists.
+    pub async fn create_branch(&self, branch: String) -> Result<GitHubRFDBranch> {
+        let default_branch = self
+            .client
+            .repos()
+            .get_branch(&self.owner, &self.repo, &self.default_branch)
+            .await?;
+
+        self.client
+            .git()
+            .create_ref(
+                &self.owner,
+                &self.repo,
+                &octorust::types::GitCreateRefRequest {
+                    key: String::new(),
+                    ref_: format!("refs/heads/{}", branch),
+                    sha: default_branch.commit.sha,
+                },
+            )
+            .await?;
+
+        info!("created branch `{}` in {}/{}", branch, self.owner, self.repo);
+
+        Ok(self.branch(branch))
+    }
+
     /// Read the remote rfd.csv file stored in GitHub and return a map from RFD number to RFD. The
     /// RFDs returned may or may have already been persisted
     pub async fn get_rfd_sync_updates(&self) -> Result<Vec<GitHubRFDUpdate>> {
@@ -140,6 +181,78 @@ impl GitHubRFDBranch {
             .is_ok()
     }
 
+    /// Get the SHA of the last commit on this branch that touched the directory of an RFD, or
+    /// an empty string if there is none.
+    pub async fn get_latest_commit_sha(&self, rfd_number: &RFDNumber) -> Result<String> {
+        let commits = self
+            .client
+            .repos()
+            .list_commits(
+                &self.owner,
+                &self.repo,
+                &self.branch,
+                &rfd_number.repo_directory(),
+                "",
+                None,
+                None,
+                1,
+                0,
+            )
+            .await?;
+
+        Ok(commits.get(0).map(|c| c.sha.to_string()).unwrap_or_default())
+    }
+
+    /// Get the states an RFD went through on this branch, oldest first, from the state line of
+    /// its README at each commit that touched its directory, with the date of the commit that
+    /// changed it.
+    pub async fn get_state_history(&self, rfd_number: &RFDNumber) -> Result<Vec<(DateTime<Utc>, String)>> {
+        let mut commits = self
+            .client
+            .repos()
+            .list_all_commits(
+                &self.owner,
+                &self.repo,
+                &self.branch,
+                &rfd_number.repo_directory(),
+                "",
+                None,
+                None,
+            )
+            .await?;
+        // GitHub lists the newest commits first.
+        commits.reverse();
+
+        let mut history: Vec<(DateTime<Utc>, String)> = Vec::new();
+        for commit in commits {
+            let date = match commit
+                .commit
+                .committer
+                .as_ref()
+                .map(|c| c.date.parse::<DateTime<Utc>>())
+            {
+                Some(Ok(date)) => date,
+                _ => continue,
+            };
+
+            let at_commit = GitHubRFDBranch {
+                branch: commit.sha.to_string(),
+                ..self.clone()
+            };
+            let state = match at_commit.get_readme_contents(rfd_number).await {
+                Ok(readme) => readme.content.get_state().to_lowercase(),
+                // The commit can be from before the README existed.
+                Err(_) => continue,
+            };
+
+            if !state.is_empty() && history.last().map(|(_, last)| last != &state).unwrap_or(true) {
+                history.push((date, state));
+            }
+        }
+
+        Ok(history)
+    }
+
     /// Try to get the markdown or asciidoc contents from the repo.
     pub async fn get_readme_contents<'a>(&self, rfd_number: &RFDNumber) -> Result<GitHubRFDReadme<'a>> {
         info!("[rfd.contents] Enter {} / {}", self.repo, self.branch);
@@ -0,0 +1,251 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{companies::Company, db::Database, rfd::RFD, schema::rfd_impacts};
+
+/// A repository, or an issue in a repository, that an RFD says it affects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RFDImpact {
+    pub owner: String,
+    pub repo: String,
+    /// The issue in the repository the RFD affects, or zero if it affects the whole repository.
+    pub issue_number: i32,
+}
+
+impl RFDImpact {
+    /// Parse an entry of an RFD's `affects:` line. Entries can be a repository (`omicron`,
+    /// `owner/omicron`), an issue in a repository (`omicron#12`), or a link to either on GitHub.
+    /// Repositories without an owner are assumed to belong to the company's GitHub org.
+    pub fn parse(entry: &str, default_owner: &str) -> Option<Self> {
+        let entry = entry
+            .trim()
+            .trim_start_matches("https://")
+            .trim_start_matches("github.com/")
+            .trim_end_matches('/')
+            .replace("/issues/", "#")
+            .replace("/pull/", "#");
+
+        let (path, issue_number) = match entry.split_once('#') {
+            Some((path, issue)) => (path.to_string(), issue.trim().parse().ok()?),
+            None => (entry, 0),
+        };
+
+        let (owner, repo) = match path.split_once('/') {
+            Some((owner, repo)) => (owner.trim(), repo.trim()),
+            None => (default_owner, path.trim()),
+        };
+        if owner.is_empty() || repo.is_empty() || repo.contains('/') || repo.contains(' ') {
+            return None;
+        }
+
+        Some(RFDImpact {
+            owner: owner.to_lowercase(),
+            repo: repo.to_lowercase(),
+            issue_number,
+        })
+    }
+}
+
+/// A link from a repository to an RFD that affects it.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RFDImpactLink {
+    pub id: i32,
+    pub rfd_number: i32,
+    pub owner: String,
+    pub repo: String,
+    /// The issue in the repository the RFD affects, or zero if it affects the whole repository.
+    pub issue_number: i32,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = rfd_impacts)]
+struct NewRFDImpactLink {
+    rfd_number: i32,
+    owner: String,
+    repo: String,
+    issue_number: i32,
+    cio_company_id: i32,
+}
+
+impl RFD {
+    /// Get the repositories and issues the RFD says it affects.
+    pub fn impacts(&self, company: &Company) -> Result<Vec<RFDImpact>> {
+        let mut impacts: Vec<RFDImpact> = Vec::new();
+        for entry in self.content()?.get_affects() {
+            match RFDImpact::parse(&entry, &company.github_org) {
+                Some(impact) if !impacts.contains(&impact) => impacts.push(impact),
+                Some(_) => (),
+                None => info!("RFD {} affects `{}`, which is not a repository", self.number, entry),
+            }
+        }
+
+        Ok(impacts)
+    }
+
+    /// Replace the links from repositories to the RFD with the ones it currently declares.
+    pub async fn set_impact_links(&self, db: &Database, company: &Company, impacts: &[RFDImpact]) -> Result<()> {
+        diesel::delete(
+            rfd_impacts::dsl::rfd_impacts
+                .filter(rfd_impacts::dsl::cio_company_id.eq(company.id))
+                .filter(rfd_impacts::dsl::rfd_number.eq(self.number)),
+        )
+        .execute_async(db.pool())
+        .await?;
+
+        if impacts.is_empty() {
+            return Ok(());
+        }
+
+        let links: Vec<NewRFDImpactLink> = impacts
+            .iter()
+            .map(|i| NewRFDImpactLink {
+                rfd_number: self.number,
+                owner: i.owner.to_string(),
+                repo: i.repo.to_string(),
+                issue_number: i.issue_number,
+                cio_company_id: company.id,
+            })
+            .collect();
+        diesel::insert_into(rfd_impacts::table)
+            .values(links)
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Let the affected repositories know the RFD was published. We comment on the affected
+    /// issues, and open an issue in repositories that are affected as a whole. RFDs are
+    /// internal, so we only ever notify private repositories of the company's GitHub org.
+    pub async fn notify_impacts_of_publish(
+        &self,
+        github: &octorust::Client,
+        company: &Company,
+        impacts: &[RFDImpact],
+    ) -> Result<()> {
+        for impact in impacts {
+            if impact.owner != company.github_org.to_lowercase() {
+                info!(
+                    "not notifying {}/{} about RFD {}, it is outside of our org",
+                    impact.owner, impact.repo, self.number
+                );
+                continue;
+            }
+            let is_private = match github.repos().get(&impact.owner, &impact.repo).await {
+                Ok(repo) => repo.private,
+                Err(e) => {
+                    info!(
+                        "not notifying {}/{} about RFD {}, getting the repository failed: {}",
+                        impact.owner, impact.repo, self.number, e
+                    );
+                    continue;
+                }
+            };
+            if !is_private {
+                info!(
+                    "not notifying {}/{} about RFD {}, it is a public repository",
+                    impact.owner, impact.repo, self.number
+                );
+                continue;
+            }
+
+            let body = format!(
+                "[{}]({}) was published and affects this {}.",
+                self.name,
+                self.rendered_link,
+                if impact.issue_number > 0 { "issue" } else { "repository" }
+            );
+
+            if impact.issue_number > 0 {
+                github
+                    .issues()
+                    .create_comment(
+                        &impact.owner,
+                        &impact.repo,
+                        impact.issue_number.into(),
+                        &octorust::types::PullsUpdateReviewRequest { body },
+                    )
+                    .await?;
+            } else {
+                github
+                    .issues()
+                    .create(
+                        &impact.owner,
+                        &impact.repo,
+                        &octorust::types::IssuesCreateRequest {
+                            title: format!("{} was published", self.name),
+                            body,
+                            assignee: "".to_string(),
+                            assignees: Default::default(),
+                            labels: Default::default(),
+                            milestone: Default::default(),
+                        },
+                    )
+                    .await?;
+            }
+
+            info!(
+                "notified {}/{}#{} that RFD {} was published",
+                impact.owner, impact.repo, impact.issue_number, self.number
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the links to the RFDs that affect a repository, including the ones that affect an issue
+/// in it.
+pub async fn get_rfd_impact_links_for_repo(
+    db: &Database,
+    cio_company_id: i32,
+    owner: &str,
+    repo: &str,
+) -> Result<Vec<RFDImpactLink>> {
+    Ok(rfd_impacts::dsl::rfd_impacts
+        .filter(rfd_impacts::dsl::cio_company_id.eq(cio_company_id))
+        .filter(rfd_impacts::dsl::owner.eq(owner.to_lowercase()))
+        .filter(rfd_impacts::dsl::repo.eq(repo.to_lowercase()))
+        .order_by(rfd_impacts::dsl::rfd_number)
+        .load_async::<RFDImpactLink>(db.pool())
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RFDImpact;
+
+    #[test]
+    fn test_parse_rfd_impact() {
+        let impact = |owner: &str, repo: &str, issue_number: i32| RFDImpact {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            issue_number,
+        };
+
+        assert_eq!(
+            Some(impact("company", "omicron", 0)),
+            RFDImpact::parse("omicron", "company")
+        );
+        assert_eq!(
+            Some(impact("other", "propolis", 12)),
+            RFDImpact::parse("Other/Propolis#12", "company")
+        );
+        assert_eq!(
+            Some(impact("company", "crucible", 34)),
+            RFDImpact::parse("https://github.com/company/crucible/issues/34", "company")
+        );
+        assert_eq!(
+            Some(impact("company", "crucible", 0)),
+            RFDImpact::parse("https://github.com/company/crucible/", "company")
+        );
+        assert_eq!(None, RFDImpact::parse("the storage team", "company"));
+        assert_eq!(None, RFDImpact::parse("omicron#abc", "company"));
+        assert_eq!(None, RFDImpact::parse("", "company"));
+    }
+}
@@ -0,0 +1,527 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use super::{GitHubRFDRepo, RFDState, RFDs, RFD};
+use crate::{
+    companies::Company,
+    db::Database,
+    schema::{rfd_state_transitions, rfds},
+    tenancy::CompanyScoped,
+};
+
+/// The number of days an RFD sits in discussion before we call the discussion stale.
+static STALE_RFD_DISCUSSION_DAYS: i64 = 30;
+
+/// A move of an RFD from a state to another. The first one we saw of an RFD, when it was
+/// created, has an empty `from_state`.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RFDStateTransition {
+    pub id: i32,
+    pub rfd_number: i32,
+    pub from_state: String,
+    pub to_state: String,
+    pub transitioned_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+impl CompanyScoped for RFDStateTransition {
+    fn cio_company_id(&self) -> i32 {
+        self.cio_company_id
+    }
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = rfd_state_transitions)]
+struct NewRFDStateTransition {
+    rfd_number: i32,
+    from_state: String,
+    to_state: String,
+    transitioned_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+impl RFD {
+    /// Record that the RFD moved to its current state, from `from`. It is empty if we just saw
+    /// the RFD for the first time.
+    pub async fn record_state_transition(&self, db: &Database, from: &str) -> Result<()> {
+        diesel::insert_into(rfd_state_transitions::table)
+            .values(NewRFDStateTransition {
+                rfd_number: self.number,
+                from_state: from.trim().to_lowercase(),
+                to_state: self.state.trim().to_lowercase(),
+                transitioned_at: Utc::now(),
+                cio_company_id: self.cio_company_id,
+            })
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Get the transitions of an RFD from the states it went through, oldest first. The first
+/// state is the one it was created in.
+fn transitions_from_state_history(
+    rfd_number: i32,
+    cio_company_id: i32,
+    history: &[(DateTime<Utc>, String)],
+) -> Vec<NewRFDStateTransition> {
+    let mut from = String::new();
+    let mut transitions = Vec::new();
+    for (transitioned_at, state) in history {
+        let to = state.trim().to_lowercase();
+        if to.is_empty() || to == from {
+            continue;
+        }
+
+        transitions.push(NewRFDStateTransition {
+            rfd_number,
+            from_state: from,
+            to_state: to.to_string(),
+            transitioned_at: *transitioned_at,
+            cio_company_id,
+        });
+        from = to;
+    }
+
+    transitions
+}
+
+/// Record the transitions of the RFDs we have none of yet, from the history of their README
+/// on GitHub. The RFDs from before we recorded transitions would otherwise never count, and
+/// never show up as stale.
+pub async fn backfill_rfd_state_transitions(db: &Database, company: &Company) -> Result<()> {
+    let recorded: BTreeSet<i32> = get_rfd_state_transitions(db, company.id)
+        .await?
+        .into_iter()
+        .map(|t| t.rfd_number)
+        .collect();
+    let rfds: Vec<RFD> = RFDs::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .filter(|rfd| !recorded.contains(&rfd.number))
+        .collect();
+    if rfds.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let repo = GitHubRFDRepo::new(company).await?;
+    for rfd in rfds {
+        // The RFDs that are not merged yet are on their own branch.
+        let branch = if rfd.link.contains(&format!("/{}/", repo.default_branch)) {
+            repo.default_branch.to_string()
+        } else {
+            rfd.number_string.to_string()
+        };
+
+        let history = match repo.branch(branch).get_state_history(&rfd.number.into()).await {
+            Ok(history) => history,
+            Err(e) => {
+                warn!("getting the state history of RFD {} failed: {}", rfd.number, e);
+                continue;
+            }
+        };
+
+        let transitions = transitions_from_state_history(rfd.number, rfd.cio_company_id, &history);
+        if transitions.is_empty() {
+            continue;
+        }
+
+        diesel::insert_into(rfd_state_transitions::table)
+            .values(&transitions)
+            .execute_async(db.pool())
+            .await?;
+        info!(
+            "backfilled {} state transitions of RFD {}",
+            transitions.len(),
+            rfd.number
+        );
+    }
+
+    Ok(())
+}
+
+/// Get how long an RFD spent in each state, in days, from its transitions. It is still in the
+/// state of the last one.
+pub fn rfd_state_durations(transitions: &[RFDStateTransition], now: DateTime<Utc>) -> BTreeMap<String, f64> {
+    let mut transitions: Vec<&RFDStateTransition> = transitions.iter().collect();
+    transitions.sort_by_key(|t| t.transitioned_at);
+
+    let mut durations: BTreeMap<String, f64> = BTreeMap::new();
+    for (i, transition) in transitions.iter().enumerate() {
+        let until = transitions.get(i + 1).map(|t| t.transitioned_at).unwrap_or(now);
+        *durations.entry(transition.to_state.to_string()).or_default() += days(until - transition.transitioned_at);
+    }
+
+    durations
+}
+
+/// Get how many days it took an RFD to be published, from when it was created. We only know it
+/// for the RFDs we saw get created.
+pub fn rfd_days_to_published(transitions: &[RFDStateTransition]) -> Option<f64> {
+    let created = transitions
+        .iter()
+        .filter(|t| t.from_state.is_empty())
+        .map(|t| t.transitioned_at)
+        .min()?;
+    let published = transitions
+        .iter()
+        .filter(|t| t.to_state.parse::<RFDState>().ok() == Some(RFDState::Published))
+        .map(|t| t.transitioned_at)
+        .min()?;
+
+    Some(days(published - created))
+}
+
+/// Get when an RFD moved to the state it is in, from its transitions. We do not know it if we
+/// did not see the RFD move to that state.
+pub fn rfd_state_since(transitions: &[RFDStateTransition], state: &str) -> Option<DateTime<Utc>> {
+    let state = state.trim().to_lowercase();
+    transitions
+        .iter()
+        .max_by_key(|t| t.transitioned_at)
+        .filter(|t| t.to_state == state)
+        .map(|t| t.transitioned_at)
+}
+
+fn days(duration: Duration) -> f64 {
+    duration.num_seconds().max(0) as f64 / 86400.0
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let middle = values.len() / 2;
+    if values.len() % 2 == 0 {
+        Some((values[middle - 1] + values[middle]) / 2.0)
+    } else {
+        Some(values[middle])
+    }
+}
+
+/// An RFD that sat in discussion for too long.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct StaleRFDDiscussion {
+    pub number: i32,
+    pub title: String,
+    pub days_in_discussion: f64,
+}
+
+/// How the RFDs of a company move through their states. We only know how long they spent in a
+/// state since we started recording their transitions.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RFDLifecycleMetrics {
+    pub generated_at: DateTime<Utc>,
+    pub count_by_state: BTreeMap<String, i64>,
+    /// The median number of days from creation to published, of the RFDs published.
+    pub median_days_to_published: Option<f64>,
+    /// The median number of days the RFDs spent in each state, so far for their current one.
+    pub median_days_in_state: BTreeMap<String, f64>,
+    /// The RFDs in discussion for longer than we expect, the longest first.
+    pub stale_discussions: Vec<StaleRFDDiscussion>,
+}
+
+impl RFDLifecycleMetrics {
+    /// Compute the metrics of the RFDs, as their number, title and state, from their transitions.
+    pub fn new(
+        rfds: &[(i32, String, String)],
+        transitions: &[RFDStateTransition],
+        stale_days: i64,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut by_rfd: BTreeMap<i32, Vec<RFDStateTransition>> = BTreeMap::new();
+        for transition in transitions {
+            by_rfd
+                .entry(transition.rfd_number)
+                .or_default()
+                .push(transition.clone());
+        }
+
+        let mut count_by_state: BTreeMap<String, i64> = BTreeMap::new();
+        let mut in_state: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        let mut to_published: Vec<f64> = Vec::new();
+        let mut stale_discussions: Vec<StaleRFDDiscussion> = Vec::new();
+        for (number, title, state) in rfds {
+            let state = state.trim().to_lowercase();
+            *count_by_state.entry(state.to_string()).or_default() += 1;
+
+            let transitions = match by_rfd.get(number) {
+                Some(transitions) => transitions,
+                None => continue,
+            };
+
+            let durations = rfd_state_durations(transitions, now);
+            for (in_state_name, duration) in &durations {
+                in_state.entry(in_state_name.to_string()).or_default().push(*duration);
+            }
+            if let Some(duration) = rfd_days_to_published(transitions) {
+                to_published.push(duration);
+            }
+
+            // The RFD is still in the state of its last transition.
+            if state.parse::<RFDState>().ok() == Some(RFDState::Discussion) {
+                if let Some(since) = rfd_state_since(transitions, &state) {
+                    let days_in_discussion = days(now - since);
+                    if days_in_discussion > stale_days as f64 {
+                        stale_discussions.push(StaleRFDDiscussion {
+                            number: *number,
+                            title: title.to_string(),
+                            days_in_discussion,
+                        });
+                    }
+                }
+            }
+        }
+        stale_discussions.sort_by(|a, b| {
+            b.days_in_discussion
+                .partial_cmp(&a.days_in_discussion)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        RFDLifecycleMetrics {
+            generated_at: now,
+            count_by_state,
+            median_days_to_published: median(to_published),
+            median_days_in_state: in_state
+                .into_iter()
+                .filter_map(|(state, durations)| median(durations).map(|m| (state, m)))
+                .collect(),
+            stale_discussions,
+        }
+    }
+
+    /// Format the metrics for the weekly digest in Slack.
+    pub fn format_digest(&self) -> String {
+        let counts: Vec<String> = self
+            .count_by_state
+            .iter()
+            .map(|(state, count)| format!("{} {}", count, state))
+            .collect();
+        let mut text = format!("*RFDs:* {}", counts.join(", "));
+
+        if let Some(days) = self.median_days_to_published {
+            text += &format!("\n*Median time to published:* {:.1} days", days);
+        }
+        if !self.median_days_in_state.is_empty() {
+            let in_state: Vec<String> = self
+                .median_days_in_state
+                .iter()
+                .map(|(state, days)| format!("{} {:.1} days", state, days))
+                .collect();
+            text += &format!("\n*Median time in each state:* {}", in_state.join(", "));
+        }
+
+        if self.stale_discussions.is_empty() {
+            text += "\nNo discussion is stale, nice!";
+        } else {
+            text += "\n*Stale discussions:*";
+            for rfd in &self.stale_discussions {
+                text += &format!(
+                    "\n• RFD {} {} _({:.0} days in discussion)_",
+                    rfd.number, rfd.title, rfd.days_in_discussion
+                );
+            }
+        }
+
+        text
+    }
+}
+
+/// Get the state transitions of the RFDs of a company.
+pub async fn get_rfd_state_transitions(db: &Database, cio_company_id: i32) -> Result<Vec<RFDStateTransition>> {
+    Ok(rfd_state_transitions::dsl::rfd_state_transitions
+        .filter(rfd_state_transitions::dsl::cio_company_id.eq(cio_company_id))
+        .load_async::<RFDStateTransition>(db.pool())
+        .await?)
+}
+
+/// Get the lifecycle metrics of the RFDs of a company.
+pub async fn get_rfd_lifecycle_metrics(db: &Database, cio_company_id: i32) -> Result<RFDLifecycleMetrics> {
+    let rfds = rfds::dsl::rfds
+        .filter(rfds::dsl::cio_company_id.eq(cio_company_id))
+        .select((rfds::dsl::number, rfds::dsl::title, rfds::dsl::state))
+        .load_async::<(i32, String, String)>(db.pool())
+        .await?;
+
+    let transitions = get_rfd_state_transitions(db, cio_company_id).await?;
+
+    Ok(RFDLifecycleMetrics::new(
+        &rfds,
+        &transitions,
+        STALE_RFD_DISCUSSION_DAYS,
+        Utc::now(),
+    ))
+}
+
+/// Post the lifecycle metrics of the RFDs to the RFD channel, every week.
+pub async fn send_rfd_lifecycle_digest(db: &Database, company: &Company) -> Result<()> {
+    if let Err(e) = backfill_rfd_state_transitions(db, company).await {
+        warn!("backfilling the RFD state transitions failed: {}", e);
+    }
+
+    let metrics = get_rfd_lifecycle_metrics(db, company.id).await?;
+    if metrics.count_by_state.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    if company.slack_channel_rfds.is_empty() {
+        warn!(
+            "company `{}` has no RFD channel, not sending the RFD digest",
+            company.name
+        );
+        return Ok(());
+    }
+    let channel = company.slack_channel_rfds.to_string();
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: format!("RFDs for the week of {}", metrics.generated_at.format("%B %-d")),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: metrics.format_digest(),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+    info!(
+        "sent the RFD lifecycle digest with {} stale discussions",
+        metrics.stale_discussions.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{
+        rfd_days_to_published, rfd_state_durations, transitions_from_state_history, RFDLifecycleMetrics,
+        RFDStateTransition,
+    };
+
+    fn transition(rfd_number: i32, from: &str, to: &str, day: u32) -> RFDStateTransition {
+        RFDStateTransition {
+            id: 0,
+            rfd_number,
+            from_state: from.to_string(),
+            to_state: to.to_string(),
+            transitioned_at: Utc.ymd(2024, 1, day).and_hms(0, 0, 0),
+            cio_company_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_rfd_state_durations() {
+        let now = Utc.ymd(2024, 1, 31).and_hms(0, 0, 0);
+        let transitions = vec![
+            transition(1, "discussion", "published", 11),
+            transition(1, "", "ideation", 1),
+            transition(1, "ideation", "discussion", 4),
+        ];
+
+        let durations = rfd_state_durations(&transitions, now);
+        assert_eq!(Some(&3.0), durations.get("ideation"));
+        assert_eq!(Some(&7.0), durations.get("discussion"));
+        assert_eq!(Some(&20.0), durations.get("published"));
+
+        assert_eq!(Some(10.0), rfd_days_to_published(&transitions));
+        // We did not see the RFD get created, so we do not know when it started.
+        assert_eq!(None, rfd_days_to_published(&transitions[..1]));
+    }
+
+    #[test]
+    fn test_rfd_lifecycle_metrics() {
+        let rfd = |number: i32, state: &str| (number, format!("Title {}", number), state.to_string());
+        let now = Utc.ymd(2024, 1, 1).and_hms(0, 0, 0) + Duration::days(60);
+        let transitions = vec![
+            transition(1, "", "discussion", 1),
+            transition(1, "discussion", "published", 5),
+            transition(2, "", "discussion", 1),
+            transition(2, "discussion", "published", 11),
+            transition(3, "ideation", "discussion", 20),
+            transition(4, "", "discussion", 25),
+        ];
+
+        let metrics = RFDLifecycleMetrics::new(
+            &[
+                rfd(1, "published"),
+                rfd(2, "Published"),
+                rfd(3, "discussion"),
+                rfd(4, "discussion"),
+                rfd(5, "ideation"),
+            ],
+            &transitions,
+            30,
+            now,
+        );
+
+        assert_eq!(Some(&2), metrics.count_by_state.get("published"));
+        assert_eq!(Some(&2), metrics.count_by_state.get("discussion"));
+        assert_eq!(Some(&1), metrics.count_by_state.get("ideation"));
+        assert_eq!(Some(7.0), metrics.median_days_to_published);
+        assert_eq!(
+            vec![3, 4],
+            metrics.stale_discussions.iter().map(|r| r.number).collect::<Vec<_>>()
+        );
+        assert_eq!(41.0, metrics.stale_discussions[0].days_in_discussion);
+
+        let digest = metrics.format_digest();
+        assert!(digest.contains("*Median time to published:* 7.0 days"));
+        assert!(digest.contains("• RFD 3 Title 3 _(41 days in discussion)_"));
+    }
+
+    #[test]
+    fn test_transitions_from_state_history() {
+        let day = |day: u32| Utc.ymd(2024, 1, day).and_hms(0, 0, 0);
+        let history = vec![
+            (day(1), "ideation".to_string()),
+            (day(4), "Discussion".to_string()),
+            (day(5), "discussion".to_string()),
+            (day(9), "".to_string()),
+        ];
+
+        let transitions = transitions_from_state_history(3, 1, &history);
+        assert_eq!(
+            vec![("", "ideation", day(1)), ("ideation", "discussion", day(4))],
+            transitions
+                .iter()
+                .map(|t| (t.from_state.as_str(), t.to_state.as_str(), t.transitioned_at))
+                .collect::<Vec<_>>()
+        );
+        assert!(transitions.iter().all(|t| t.rfd_number == 3 && t.cio_company_id == 1));
+    }
+}
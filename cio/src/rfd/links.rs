@@ -0,0 +1,213 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    companies::Company,
+    db::Database,
+    rfd::RFD,
+    schema::{rfd_links, rfds},
+    tenancy::CompanyScoped,
+};
+
+/// The references to other RFDs. The migration that creates the links backfills them with
+/// the same expression, keep them in sync.
+static RFD_REFERENCE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\brfd[ /-]?0*(\d{1,4})\b|\b0*(\d{1,4})\.rfd\.").unwrap());
+
+/// Get the numbers of the other RFDs some content references, like `RFD 123`, `RFD-0123`, a
+/// path in the RFD repo like `rfd/0123`, or a short link like `123.rfd.example.com`.
+pub fn rfd_references(content: &str, own_number: i32) -> Vec<i32> {
+    let references: BTreeSet<i32> = RFD_REFERENCE_RE
+        .captures_iter(content)
+        .filter_map(|c| c.get(1).or_else(|| c.get(2)))
+        .filter_map(|m| m.as_str().parse().ok())
+        .filter(|n| *n > 0 && *n != own_number)
+        .collect();
+
+    references.into_iter().collect()
+}
+
+/// A reference from an RFD to another.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RFDLink {
+    pub id: i32,
+    pub rfd_number: i32,
+    pub referenced_rfd_number: i32,
+    pub cio_company_id: i32,
+}
+
+impl CompanyScoped for RFDLink {
+    fn cio_company_id(&self) -> i32 {
+        self.cio_company_id
+    }
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = rfd_links)]
+struct NewRFDLink {
+    rfd_number: i32,
+    referenced_rfd_number: i32,
+    cio_company_id: i32,
+}
+
+impl RFD {
+    /// Get the other RFDs the RFD references.
+    pub fn references(&self) -> Vec<i32> {
+        rfd_references(&self.content, self.number)
+    }
+
+    /// Replace the links from the RFD to the ones it currently references.
+    pub async fn set_rfd_links(&self, db: &Database, company: &Company, references: &[i32]) -> Result<()> {
+        diesel::delete(
+            rfd_links::dsl::rfd_links
+                .filter(rfd_links::dsl::cio_company_id.eq(company.id))
+                .filter(rfd_links::dsl::rfd_number.eq(self.number)),
+        )
+        .execute_async(db.pool())
+        .await?;
+
+        if references.is_empty() {
+            return Ok(());
+        }
+
+        let links: Vec<NewRFDLink> = references
+            .iter()
+            .map(|n| NewRFDLink {
+                rfd_number: self.number,
+                referenced_rfd_number: *n,
+                cio_company_id: company.id,
+            })
+            .collect();
+        diesel::insert_into(rfd_links::table)
+            .values(links)
+            .execute_async(db.pool())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// An RFD of the citation graph, with the RFDs it references and the ones that reference it.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RFDGraphNode {
+    pub number: i32,
+    pub title: String,
+    pub state: String,
+    pub references: Vec<i32>,
+    pub referenced_by: Vec<i32>,
+}
+
+/// How the RFDs reference each other.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RFDGraph {
+    pub nodes: Vec<RFDGraphNode>,
+    /// The RFDs no other RFD references.
+    pub orphans: Vec<i32>,
+    /// The RFDs that are referenced but we do not have, like ones that were never written.
+    pub missing: Vec<i32>,
+}
+
+impl RFDGraph {
+    /// Build the graph from the RFDs, as their number, title and state, and the links between
+    /// them, as the number of the RFD referencing and the one referenced.
+    pub fn new(rfds: &[(i32, String, String)], links: &[(i32, i32)]) -> Self {
+        let mut nodes: BTreeMap<i32, RFDGraphNode> = rfds
+            .iter()
+            .map(|(number, title, state)| {
+                (
+                    *number,
+                    RFDGraphNode {
+                        number: *number,
+                        title: title.to_string(),
+                        state: state.to_string(),
+                        references: vec![],
+                        referenced_by: vec![],
+                    },
+                )
+            })
+            .collect();
+
+        let mut missing: BTreeSet<i32> = BTreeSet::new();
+        for (from, to) in links {
+            if !nodes.contains_key(to) {
+                missing.insert(*to);
+            }
+            if let Some(node) = nodes.get_mut(from) {
+                node.references.push(*to);
+            }
+            if let Some(node) = nodes.get_mut(to) {
+                node.referenced_by.push(*from);
+            }
+        }
+
+        for node in nodes.values_mut() {
+            node.references.sort_unstable();
+            node.references.dedup();
+            node.referenced_by.sort_unstable();
+            node.referenced_by.dedup();
+        }
+
+        RFDGraph {
+            orphans: nodes
+                .values()
+                .filter(|n| n.referenced_by.is_empty())
+                .map(|n| n.number)
+                .collect(),
+            nodes: nodes.into_values().collect(),
+            missing: missing.into_iter().collect(),
+        }
+    }
+}
+
+/// Get the citation graph of the RFDs of a company.
+pub async fn get_rfd_graph(db: &Database, cio_company_id: i32) -> Result<RFDGraph> {
+    let rfds = rfds::dsl::rfds
+        .filter(rfds::dsl::cio_company_id.eq(cio_company_id))
+        .select((rfds::dsl::number, rfds::dsl::title, rfds::dsl::state))
+        .load_async::<(i32, String, String)>(db.pool())
+        .await?;
+
+    let links = rfd_links::dsl::rfd_links
+        .filter(rfd_links::dsl::cio_company_id.eq(cio_company_id))
+        .select((rfd_links::dsl::rfd_number, rfd_links::dsl::referenced_rfd_number))
+        .load_async::<(i32, i32)>(db.pool())
+        .await?;
+
+    Ok(RFDGraph::new(&rfds, &links))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rfd_references, RFDGraph};
+
+    #[test]
+    fn test_rfd_references() {
+        let content = r#"= RFD 12 Things
+
+As RFD 4 and RFD-0021 say, and rfd 21 again, see
+https://github.com/company/rfd/tree/0033/rfd/0033 and https://44.rfd.example.com.
+This is not RFD 12345, and RFD 12 is us. image::rfd/0012/diagram.svg[]
+"#;
+
+        assert_eq!(vec![4, 21, 33, 44], rfd_references(content, 12));
+        assert!(rfd_references("No references here, 2023.", 1).is_empty());
+    }
+
+    #[test]
+    fn test_rfd_graph() {
+        let rfd = |number: i32| (number, format!("RFD {}", number), "published".to_string());
+        let graph = RFDGraph::new(&[rfd(1), rfd(2), rfd(3)], &[(2, 1), (3, 1), (3, 2), (3, 9)]);
+
+        assert_eq!(vec![2, 3], graph.nodes[0].referenced_by);
+        assert_eq!(vec![1, 2, 9], graph.nodes[2].references);
+        assert_eq!(vec![3], graph.orphans);
+        assert_eq!(vec![9], graph.missing);
+    }
+}
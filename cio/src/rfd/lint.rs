@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::{RFDContent, RFDNumber, RFDState};
+use crate::{companies::Company, configs::User, db::Database, schema::users};
+
+static EMAIL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]+").unwrap());
+
+fn author_emails(authors: &str) -> impl Iterator<Item = String> + '_ {
+    EMAIL_RE.find_iter(authors).map(|m| m.as_str().to_lowercase())
+}
+
+/// Check the front matter of an RFD, returning the problems with it. Authors with an email at
+/// one of the company's domains have to be someone in the configs, but anyone else can be an
+/// author. Authors the RFD already had are fine even if they are no longer in the configs,
+/// people leave and their RFDs still need updates.
+pub fn lint_rfd_front_matter(
+    content: &RFDContent,
+    existing_authors: &str,
+    domains: &[&str],
+    known_emails: &BTreeSet<String>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if content.get_title().trim().is_empty() {
+        problems.push("The RFD has no title, it should start with `= RFD <number> <title>`.".to_string());
+    }
+
+    let state = content.get_state();
+    if state.is_empty() {
+        problems.push("The RFD has no `state`.".to_string());
+    } else if state.parse::<RFDState>().is_err() {
+        problems.push(format!(
+            "The state `{}` is not one of `{}`.",
+            state,
+            RFDState::ALL
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join("`, `")
+        ));
+    }
+
+    let authors = content.get_authors();
+    if authors.trim().is_empty() {
+        problems.push("The RFD has no `authors`.".to_string());
+    }
+
+    let existing: BTreeSet<String> = author_emails(existing_authors).collect();
+    for email in author_emails(&authors).filter(|e| !existing.contains(e)) {
+        let ours = domains
+            .iter()
+            .any(|d| !d.is_empty() && email.ends_with(&format!("@{}", d.to_lowercase())));
+        if ours && !known_emails.contains(&email) {
+            problems.push(format!("The author `{}` is not anyone in the configs.", email));
+        }
+    }
+
+    problems
+}
+
+/// Check the front matter of an RFD against the people of a company, and the authors it had
+/// before.
+pub async fn lint_rfd(
+    db: &Database,
+    company: &Company,
+    content: &RFDContent<'_>,
+    existing_authors: &str,
+) -> Result<Vec<String>> {
+    let users = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .load_async::<User>(db.pool())
+        .await?;
+
+    let mut known_emails = BTreeSet::new();
+    for user in users {
+        known_emails.insert(user.email.to_lowercase());
+        known_emails.insert(format!("{}@{}", user.username, company.gsuite_domain).to_lowercase());
+        for alias in &user.aliases {
+            known_emails.insert(format!("{}@{}", alias, company.gsuite_domain).to_lowercase());
+        }
+    }
+
+    Ok(lint_rfd_front_matter(
+        content,
+        existing_authors,
+        &[&company.gsuite_domain, &company.domain],
+        &known_emails,
+    ))
+}
+
+/// The comment we leave on the pull request of an RFD whose front matter has problems.
+pub fn format_rfd_lint_comment(number: &RFDNumber, problems: &[String]) -> String {
+    format!(
+        "The front matter of RFD {} has problems, so we did not update it:\n\n{}\n\nFix them and push again \
+         to update the RFD.",
+        number.as_number_string(),
+        problems
+            .iter()
+            .map(|p| format!("- {}", p))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::lint_rfd_front_matter;
+    use crate::rfd::RFDContent;
+
+    #[test]
+    fn test_lint_rfd_front_matter() {
+        let known: BTreeSet<String> = vec!["jess@oxide.computer".to_string()].into_iter().collect();
+
+        let content = r#":authors: Jess <jess@oxide.computer>, Friend <friend@example.com>
+:state: discussion
+
+= RFD 123 Things
+{authors}
+"#;
+        let problems = lint_rfd_front_matter(&RFDContent::new_asciidoc(content), "", &["oxide.computer"], &known);
+        assert!(problems.is_empty(), "{:?}", problems);
+
+        let content = r#":authors: Jess <jess@oxide.computer>, Nobody <nobody@oxide.computer>
+:state: thinking
+
+= RFD 123 Things
+{authors}
+"#;
+        let problems = lint_rfd_front_matter(&RFDContent::new_asciidoc(content), "", &["oxide.computer"], &known);
+        assert_eq!(
+            vec![
+                "The state `thinking` is not one of `prediscussion`, `ideation`, `discussion`, `published`, \
+                 `committed`, `abandoned`."
+                    .to_string(),
+                "The author `nobody@oxide.computer` is not anyone in the configs.".to_string(),
+            ],
+            problems
+        );
+
+        // Someone who left can stay an author of the RFDs they wrote.
+        let problems = lint_rfd_front_matter(
+            &RFDContent::new_asciidoc(content),
+            "Nobody <nobody@oxide.computer>",
+            &["oxide.computer"],
+            &known,
+        );
+        assert_eq!(
+            vec![
+                "The state `thinking` is not one of `prediscussion`, `ideation`, `discussion`, `published`, \
+                 `committed`, `abandoned`."
+                    .to_string()
+            ],
+            problems
+        );
+
+        let content = r#"# RFD 123 Things
+state: discussion
+"#;
+        let problems = lint_rfd_front_matter(&RFDContent::new_markdown(content), "", &["oxide.computer"], &known);
+        assert_eq!(vec!["The RFD has no `authors`.".to_string()], problems);
+    }
+}
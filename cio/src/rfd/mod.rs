@@ -3,18 +3,51 @@ use std::fmt;
 
 mod changelog;
 mod content;
+mod diff;
+mod discussion;
 pub mod drive;
 mod github;
+mod impact;
+mod lifecycle;
+mod links;
+mod lint;
 mod model;
 mod pdf;
+mod renderer;
+mod reservations;
+mod reviews;
+mod scaffold;
 mod search;
+mod state;
+mod sync;
 
 pub use changelog::send_rfd_changelog;
 pub use content::{RFDContent, RFDOutputError, RFDOutputFormat};
+pub use diff::{rfd_sections, RFDChanges, RFDSection, RFDSectionChange};
+pub use discussion::{
+    count_unresolved_threads, refresh_rfd_discussions, NewRFDDiscussionComment, RFDDiscussionComment,
+    RFDDiscussionComments, RFDDiscussionLink,
+};
 pub use github::{GitHubRFDBranch, GitHubRFDReadme, GitHubRFDReadmeLocation, GitHubRFDRepo, GitHubRFDUpdate};
+pub use impact::{get_rfd_impact_links_for_repo, RFDImpact, RFDImpactLink};
+pub use lifecycle::{
+    get_rfd_lifecycle_metrics, get_rfd_state_transitions, rfd_days_to_published, rfd_state_durations, rfd_state_since,
+    send_rfd_lifecycle_digest, RFDLifecycleMetrics, RFDStateTransition, StaleRFDDiscussion,
+};
+pub use links::{get_rfd_graph, rfd_references, RFDGraph, RFDGraphNode, RFDLink};
+pub use lint::{format_rfd_lint_comment, lint_rfd, lint_rfd_front_matter};
 pub use model::{NewRFD, RFDEntry, RFDIndexEntry, RFDs, RemoteRFD, RFD};
 pub use pdf::{PDFStorage, RFDPdf};
-pub use search::{IndexDocument, RFDSearchIndex};
+pub use renderer::{get_rfd_pdf_renderer, AsciidoctorPdfRenderer, ChromiumPdfRenderer, RFDPdfRenderer};
+pub use reservations::{
+    get_rfd_number_reservation, reserve_rfd_number, use_rfd_number_reservation, RFDNumberReservation,
+    RFDReservationsConfig,
+};
+pub use reviews::{review_approvals, rfd_reviewers, rfds_to_remind, send_rfd_review_reminders};
+pub use scaffold::{next_rfd_number, rfd_template, scaffold_rfd, taken_rfd_numbers, ScaffoldedRFD};
+pub use search::{search_rfds, IndexDocument, RFDSearchIndex, RFDSearchResult, RFD_SEARCH_MAX_RESULTS};
+pub use state::RFDState;
+pub use sync::{get_rendered_commit_sha, is_rendered, set_rendered_commit_sha, RFDBranchSync};
 
 #[derive(Debug, Copy, Clone, Deserialize, Serialize)]
 pub struct RFDNumber(i32);
@@ -25,6 +58,16 @@ impl RFDNumber {
         format!("/rfd/{}", self.as_number_string())
     }
 
+    /// Get the number of the RFD a branch of the RFD repo is for. Branches of RFDs are named
+    /// after their number in its expanded form, like `0123`.
+    pub fn from_branch(branch: &str) -> Option<Self> {
+        if branch.len() != 4 || !branch.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        branch.parse().ok().map(Self)
+    }
+
     /// Get an RFD number in its expanded form with leading 0s
     pub fn as_number_string(&self) -> String {
         let mut number_string = self.0.to_string();
@@ -84,6 +84,18 @@ pub struct NewRFD {
     pub pdf_link_github: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub pdf_link_google_drive: String,
+    /// The review threads of the discussion that are still open, see `refresh_rfd_discussions`.
+    #[serde(default)]
+    #[partial(RFDIndexEntry(skip))]
+    pub unresolved_discussion_threads: i32,
+    /// The GitHub logins of who was asked to review the pull request of the RFD, or reviewed it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[partial(RFDIndexEntry(skip))]
+    pub reviewers: Vec<String>,
+    /// The reviewers whose latest review approves the RFD.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[partial(RFDIndexEntry(skip))]
+    pub approvals: Vec<String>,
     /// The CIO company ID.
     #[serde(default)]
     #[partial(RFDIndexEntry(skip))]
@@ -94,6 +106,8 @@ pub struct NewRFD {
 pub struct RemoteRFD {
     pub rfd: NewRFD,
     pub location: GitHubRFDReadmeLocation,
+    /// The last commit that touched the RFD on its branch.
+    pub commit_sha: String,
 }
 
 impl NewRFD {
@@ -196,9 +210,14 @@ impl NewRFD {
                 // while new PDFs are generated
                 pdf_link_github: Default::default(),
                 pdf_link_google_drive: Default::default(),
+                // Counted from the discussion on GitHub, separately from the contents.
+                unresolved_discussion_threads: Default::default(),
+                reviewers: Default::default(),
+                approvals: Default::default(),
                 cio_company_id: company.id,
             },
             location: readme.location,
+            commit_sha: latest_commit.sha.to_string(),
         })
     }
 
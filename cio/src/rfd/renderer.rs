@@ -0,0 +1,159 @@
+use anyhow::{bail, Result};
+use log::{info, warn};
+use std::{fs, path::Path, process::Command, str::from_utf8, sync::Arc};
+
+use super::RFDOutputFormat;
+use crate::companies::Company;
+
+/// Something that renders the PDF of an asciidoc RFD.
+pub trait RFDPdfRenderer: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Render the asciidoc file at `file_path`. The images of the RFD are next to it in
+    /// `working_dir`.
+    fn render(&self, working_dir: &Path, file_path: &Path) -> Result<Vec<u8>>;
+}
+
+/// Run a command and return what it printed, or fail with what it complained about.
+pub(super) fn run_command(command: &mut Command, program: &str) -> Result<Vec<u8>> {
+    let output = command.output()?;
+    if !output.status.success() {
+        bail!(
+            "[rfds] running {} failed: {} {}",
+            program,
+            from_utf8(&output.stdout)?,
+            from_utf8(&output.stderr)?
+        );
+    }
+
+    Ok(output.stdout)
+}
+
+/// Render PDFs with `asciidoctor-pdf`.
+pub struct AsciidoctorPdfRenderer;
+
+impl RFDPdfRenderer for AsciidoctorPdfRenderer {
+    fn name(&self) -> &str {
+        "asciidoctor"
+    }
+
+    fn render(&self, working_dir: &Path, file_path: &Path) -> Result<Vec<u8>> {
+        run_command(
+            &mut RFDOutputFormat::Pdf.command(&working_dir.to_path_buf(), file_path),
+            "asciidoctor-pdf",
+        )
+    }
+}
+
+/// Render PDFs by printing the HTML of the RFD with headless Chromium, for the deployments
+/// without the Ruby `asciidoctor-pdf`. The HTML comes from Asciidoctor.js, so neither needs
+/// Ruby. Mermaid diagrams are left as code.
+pub struct ChromiumPdfRenderer {
+    binary: String,
+    asciidoctor_js: String,
+}
+
+impl ChromiumPdfRenderer {
+    pub fn new(binary: &str, asciidoctor_js: &str) -> Self {
+        ChromiumPdfRenderer {
+            binary: binary.to_string(),
+            asciidoctor_js: asciidoctor_js.to_string(),
+        }
+    }
+
+    /// Create a renderer using the Chromium binary at `CHROMIUM_PATH` and the Asciidoctor.js
+    /// binary at `ASCIIDOCTOR_JS_PATH`, or the ones in the path.
+    pub fn new_from_env() -> Self {
+        Self::new(
+            &std::env::var("CHROMIUM_PATH").unwrap_or_else(|_| "chromium".to_string()),
+            &std::env::var("ASCIIDOCTOR_JS_PATH").unwrap_or_else(|_| "asciidoctorjs".to_string()),
+        )
+    }
+}
+
+/// The arguments to print a page to a PDF with headless Chromium.
+fn chromium_print_args(html_path: &Path, pdf_path: &Path) -> Vec<String> {
+    vec![
+        "--headless".to_string(),
+        "--disable-gpu".to_string(),
+        "--no-sandbox".to_string(),
+        "--no-pdf-header-footer".to_string(),
+        format!("--print-to-pdf={}", pdf_path.display()),
+        format!("file://{}", html_path.display()),
+    ]
+}
+
+impl RFDPdfRenderer for ChromiumPdfRenderer {
+    fn name(&self) -> &str {
+        "chromium"
+    }
+
+    fn render(&self, working_dir: &Path, file_path: &Path) -> Result<Vec<u8>> {
+        let html_path = working_dir.join("contents.html");
+        let pdf_path = working_dir.join("contents.pdf");
+
+        // Render a standalone page, with its styles, so the PDF looks like the RFD site.
+        run_command(
+            Command::new(&self.asciidoctor_js).current_dir(working_dir).args(&[
+                "-o",
+                html_path.to_str().unwrap(),
+                file_path.to_str().unwrap(),
+            ]),
+            &self.asciidoctor_js,
+        )?;
+
+        info!("[rfds] Printing {:?} to a PDF with {}", html_path, self.binary);
+        run_command(
+            Command::new(&self.binary)
+                .current_dir(working_dir)
+                .args(chromium_print_args(&html_path, &pdf_path)),
+            &self.binary,
+        )?;
+
+        Ok(fs::read(&pdf_path)?)
+    }
+}
+
+/// Get the PDF renderer the company picked. Defaults to asciidoctor, a renderer we do not know
+/// should not keep the RFD from being updated.
+pub fn get_rfd_pdf_renderer(company: &Company) -> Arc<dyn RFDPdfRenderer> {
+    match company.rfd_pdf_renderer.trim().to_lowercase().as_str() {
+        "" | "asciidoctor" => Arc::new(AsciidoctorPdfRenderer),
+        "chromium" => Arc::new(ChromiumPdfRenderer::new_from_env()),
+        r => {
+            warn!(
+                "company `{}` has unknown RFD PDF renderer `{}`, rendering with asciidoctor",
+                company.name, r
+            );
+            Arc::new(AsciidoctorPdfRenderer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{chromium_print_args, get_rfd_pdf_renderer};
+    use crate::companies::tests::mock_company;
+
+    #[test]
+    fn test_get_rfd_pdf_renderer() {
+        let mut company = mock_company();
+        assert_eq!("asciidoctor", get_rfd_pdf_renderer(&company).name());
+
+        company.rfd_pdf_renderer = " Chromium".to_string();
+        assert_eq!("chromium", get_rfd_pdf_renderer(&company).name());
+
+        company.rfd_pdf_renderer = "wkhtmltopdf".to_string();
+        assert_eq!("asciidoctor", get_rfd_pdf_renderer(&company).name());
+    }
+
+    #[test]
+    fn test_chromium_print_args() {
+        let args = chromium_print_args(Path::new("/tmp/rfd/contents.html"), Path::new("/tmp/rfd/contents.pdf"));
+        assert!(args.contains(&"--headless".to_string()));
+        assert!(args.contains(&"--print-to-pdf=/tmp/rfd/contents.pdf".to_string()));
+        assert_eq!(Some(&"file:///tmp/rfd/contents.html".to_string()), args.last());
+    }
+}
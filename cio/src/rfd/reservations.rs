@@ -0,0 +1,211 @@
+use std::ops::DerefMut;
+
+use anyhow::{anyhow, Result};
+use async_bb8_diesel::{AsyncConnection, AsyncRunQueryDsl};
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{next_rfd_number, RFDNumber};
+use crate::{db::Database, schema::rfd_number_reservations, tenancy::CompanyScoped};
+
+/// How long we hold the numbers we reserve for new RFDs before releasing the unused ones.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RFDReservationsConfig {
+    /// How long we hold a number when the reservation does not ask for a time.
+    pub ttl_minutes: i64,
+    /// The longest a reservation can ask to hold a number for.
+    pub max_ttl_minutes: i64,
+    /// How many numbers someone can hold at once, before they have to use or release one.
+    pub max_open_per_person: i64,
+}
+
+impl Default for RFDReservationsConfig {
+    fn default() -> Self {
+        RFDReservationsConfig {
+            ttl_minutes: 24 * 60,
+            max_ttl_minutes: 7 * 24 * 60,
+            max_open_per_person: 3,
+        }
+    }
+}
+
+impl RFDReservationsConfig {
+    /// How long to hold a number for a reservation asking for `requested_minutes`.
+    pub fn ttl(&self, requested_minutes: Option<i64>) -> Duration {
+        let minutes = requested_minutes
+            .filter(|m| *m > 0)
+            .unwrap_or(self.ttl_minutes)
+            .min(self.max_ttl_minutes);
+
+        Duration::minutes(minutes)
+    }
+}
+
+/// A number held for an RFD someone is about to write.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RFDNumberReservation {
+    pub id: i32,
+    pub number: i32,
+    pub title: String,
+    pub reserved_by: String,
+    pub reserved_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// When the RFD was created with the number, we never release it after that.
+    pub used_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+}
+
+impl CompanyScoped for RFDNumberReservation {
+    fn cio_company_id(&self) -> i32 {
+        self.cio_company_id
+    }
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = rfd_number_reservations)]
+struct NewRFDNumberReservation {
+    number: i32,
+    title: String,
+    reserved_by: String,
+    expires_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// Reserve the next number after the ones taken, by an RFD or by a branch of one, and the ones
+/// other reservations hold. The table is locked while we pick the number, so two reservations
+/// at the same time always get different numbers. With `max_open`, someone already holding that
+/// many numbers cannot reserve another one.
+pub async fn reserve_rfd_number(
+    db: &Database,
+    cio_company_id: i32,
+    taken: &[RFDNumber],
+    title: &str,
+    reserved_by: &str,
+    ttl: Duration,
+    max_open: Option<i64>,
+) -> Result<RFDNumberReservation> {
+    let mut taken: Vec<RFDNumber> = taken.to_vec();
+    let title = title.trim().to_string();
+    let reserved_by = reserved_by.trim().to_string();
+
+    let reservation = db
+        .pool()
+        .transaction(move |conn| {
+            diesel::sql_query("LOCK TABLE rfd_number_reservations IN SHARE ROW EXCLUSIVE MODE")
+                .execute(conn.deref_mut())?;
+
+            // Release the numbers held for RFDs that were never created.
+            diesel::delete(
+                rfd_number_reservations::dsl::rfd_number_reservations
+                    .filter(rfd_number_reservations::dsl::cio_company_id.eq(cio_company_id))
+                    .filter(rfd_number_reservations::dsl::used_at.is_null())
+                    .filter(rfd_number_reservations::dsl::expires_at.le(Utc::now())),
+            )
+            .execute(conn.deref_mut())?;
+
+            // Counted under the lock, so reservations at the same time cannot go over the cap.
+            if let Some(max_open) = max_open {
+                let open = rfd_number_reservations::dsl::rfd_number_reservations
+                    .filter(rfd_number_reservations::dsl::cio_company_id.eq(cio_company_id))
+                    .filter(rfd_number_reservations::dsl::reserved_by.eq(reserved_by.to_string()))
+                    .filter(rfd_number_reservations::dsl::used_at.is_null())
+                    .filter(rfd_number_reservations::dsl::expires_at.gt(Utc::now()))
+                    .count()
+                    .get_result::<i64>(conn.deref_mut())?;
+                if open >= max_open {
+                    return Err(anyhow!(
+                        "`{}` already holds {} RFD numbers, use or let them expire first",
+                        reserved_by,
+                        open
+                    ));
+                }
+            }
+
+            let reserved = rfd_number_reservations::dsl::rfd_number_reservations
+                .filter(rfd_number_reservations::dsl::cio_company_id.eq(cio_company_id))
+                .select(rfd_number_reservations::dsl::number)
+                .load::<i32>(conn.deref_mut())?;
+            taken.extend(reserved.iter().map(RFDNumber::from));
+
+            diesel::insert_into(rfd_number_reservations::table)
+                .values(NewRFDNumberReservation {
+                    number: next_rfd_number(&taken).into(),
+                    title,
+                    reserved_by,
+                    expires_at: Utc::now() + ttl,
+                    cio_company_id,
+                })
+                .get_result::<RFDNumberReservation>(conn.deref_mut())
+                .map_err(anyhow::Error::from)
+        })
+        .await?;
+
+    info!(
+        "reserved RFD {} for `{}` until {}",
+        reservation.number, reservation.reserved_by, reservation.expires_at
+    );
+
+    Ok(reservation)
+}
+
+/// Get the reservation of a number, if it is still held for who reserved it.
+pub async fn get_rfd_number_reservation(
+    db: &Database,
+    cio_company_id: i32,
+    number: i32,
+    reserved_by: &str,
+) -> Result<RFDNumberReservation> {
+    rfd_number_reservations::dsl::rfd_number_reservations
+        .filter(rfd_number_reservations::dsl::cio_company_id.eq(cio_company_id))
+        .filter(rfd_number_reservations::dsl::number.eq(number))
+        .filter(rfd_number_reservations::dsl::reserved_by.eq(reserved_by.trim().to_string()))
+        .filter(rfd_number_reservations::dsl::used_at.is_null())
+        .filter(rfd_number_reservations::dsl::expires_at.gt(Utc::now()))
+        .first_async::<RFDNumberReservation>(db.pool())
+        .await
+        .map_err(|e| anyhow!("RFD {} is not reserved for `{}`: {}", number, reserved_by, e))
+}
+
+/// Mark the reservation of a number as used by the RFD created with it, if it is still held
+/// for who reserved it.
+pub async fn use_rfd_number_reservation(
+    db: &Database,
+    cio_company_id: i32,
+    number: i32,
+    reserved_by: &str,
+) -> Result<RFDNumberReservation> {
+    let target = rfd_number_reservations::dsl::rfd_number_reservations
+        .filter(rfd_number_reservations::dsl::cio_company_id.eq(cio_company_id))
+        .filter(rfd_number_reservations::dsl::number.eq(number))
+        .filter(rfd_number_reservations::dsl::reserved_by.eq(reserved_by.trim().to_string()))
+        .filter(rfd_number_reservations::dsl::used_at.is_null())
+        .filter(rfd_number_reservations::dsl::expires_at.gt(Utc::now()));
+
+    diesel::update(target)
+        .set(rfd_number_reservations::dsl::used_at.eq(Utc::now()))
+        .get_result_async::<RFDNumberReservation>(db.pool())
+        .await
+        .map_err(|e| anyhow!("RFD {} is not reserved for `{}` anymore: {}", number, reserved_by, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::RFDReservationsConfig;
+
+    #[test]
+    fn test_rfd_reservation_ttl() {
+        let config = RFDReservationsConfig::default();
+
+        assert_eq!(Duration::hours(24), config.ttl(None));
+        assert_eq!(Duration::minutes(30), config.ttl(Some(30)));
+        assert_eq!(Duration::hours(24), config.ttl(Some(0)));
+        assert_eq!(Duration::days(7), config.ttl(Some(60 * 24 * 30)));
+        assert_eq!(3, config.max_open_per_person);
+    }
+}
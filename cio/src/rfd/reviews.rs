@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use super::{get_rfd_state_transitions, rfd_state_since, RFDDiscussionLink, RFDState, RFDStateTransition, RFDs, RFD};
+use crate::{
+    companies::Company,
+    configs::{User, Users},
+    db::Database,
+    notifications::NOTIFICATION_RFD_REVIEW_REMINDERS,
+};
+
+/// The number of days an RFD sits in discussion before we remind its reviewers, if the
+/// company did not pick one.
+static DEFAULT_RFD_REVIEW_REMINDER_DAYS: i32 = 7;
+
+/// Returns who approved, from the reviews of a pull request as `(login, state)` in the order
+/// they were submitted. Comments do not change what someone thinks of the RFD, so the latest
+/// review that approves or requests changes is the one that counts.
+pub fn review_approvals(reviews: &[(String, String)]) -> Vec<String> {
+    let mut latest: BTreeMap<String, &str> = BTreeMap::new();
+    for (login, state) in reviews {
+        if state == "APPROVED" || state == "CHANGES_REQUESTED" {
+            latest.insert(login.to_string(), state);
+        }
+    }
+
+    latest
+        .into_iter()
+        .filter(|(_, state)| *state == "APPROVED")
+        .map(|(login, _)| login)
+        .collect()
+}
+
+/// Returns the reviewers of a pull request: who was asked to review it, and who reviewed it.
+/// GitHub stops listing someone as requested once they review.
+pub fn rfd_reviewers(requested: &[String], reviews: &[(String, String)]) -> Vec<String> {
+    let mut reviewers: Vec<String> = requested
+        .iter()
+        .chain(reviews.iter().map(|(login, _)| login))
+        .filter(|login| !login.is_empty())
+        .cloned()
+        .collect();
+    reviewers.sort();
+    reviewers.dedup();
+
+    reviewers
+}
+
+/// Get the reviewers and approvals of the pull request an RFD is discussed in.
+pub(super) async fn get_rfd_reviews(
+    github: &octorust::Client,
+    link: &RFDDiscussionLink,
+) -> Result<(Vec<String>, Vec<String>)> {
+    if !link.is_pull_request {
+        return Ok((vec![], vec![]));
+    }
+
+    let requested: Vec<String> = github
+        .pulls()
+        .list_requested_reviewers(&link.owner, &link.repo, link.number, 100, 1)
+        .await?
+        .users
+        .into_iter()
+        .map(|u| u.login)
+        .collect();
+
+    let reviews: Vec<(String, String)> = github
+        .pulls()
+        .list_all_reviews(&link.owner, &link.repo, link.number)
+        .await?
+        .into_iter()
+        .map(|r| (r.user.map(|u| u.login).unwrap_or_default(), r.state))
+        .collect();
+
+    Ok((rfd_reviewers(&requested, &reviews), review_approvals(&reviews)))
+}
+
+/// Return the RFDs that sat in discussion for more than `days` without their reviewers
+/// approving, with when they moved to discussion, by the GitHub login of the reviewers we
+/// should remind. We do not know how long the RFDs we did not see move to discussion sat there.
+pub fn rfds_to_remind(
+    rfds: Vec<RFD>,
+    transitions: &[RFDStateTransition],
+    now: DateTime<Utc>,
+    days: i32,
+) -> BTreeMap<String, Vec<(RFD, DateTime<Utc>)>> {
+    let before = now - Duration::days(i64::from(days));
+
+    let mut by_reviewer: BTreeMap<String, Vec<(RFD, DateTime<Utc>)>> = BTreeMap::new();
+    for rfd in rfds {
+        if rfd.state.parse::<RFDState>().ok() != Some(RFDState::Discussion) {
+            continue;
+        }
+        let rfd_transitions: Vec<RFDStateTransition> = transitions
+            .iter()
+            .filter(|t| t.rfd_number == rfd.number)
+            .cloned()
+            .collect();
+        let since = match rfd_state_since(&rfd_transitions, &rfd.state) {
+            Some(since) if since <= before => since,
+            _ => continue,
+        };
+
+        for reviewer in &rfd.reviewers {
+            if rfd.approvals.iter().any(|a| a.eq_ignore_ascii_case(reviewer)) {
+                continue;
+            }
+
+            by_reviewer
+                .entry(reviewer.to_lowercase())
+                .or_default()
+                .push((rfd.clone(), since));
+        }
+    }
+
+    by_reviewer
+}
+
+/// Remind the reviewers of the RFDs that sat in discussion for too long about them in a
+/// Slack direct message, until they approve.
+pub async fn send_rfd_review_reminders(db: &Database, company: &Company) -> Result<()> {
+    let days = if company.rfd_review_reminder_days > 0 {
+        company.rfd_review_reminder_days
+    } else {
+        DEFAULT_RFD_REVIEW_REMINDER_DAYS
+    };
+
+    let rfds: Vec<RFD> = RFDs::get_from_db(db, company.id).await?.into_iter().collect();
+    let transitions = get_rfd_state_transitions(db, company.id).await?;
+    let by_reviewer = rfds_to_remind(rfds, &transitions, Utc::now(), days);
+    if by_reviewer.is_empty() {
+        return Ok(());
+    }
+
+    let users: Vec<User> = Users::get_from_db(db, company.id).await?.into_iter().collect();
+    let slack = company.authenticate_slack(db).await?;
+    for (reviewer, rfds) in by_reviewer {
+        // We only know the reviewers by their GitHub login.
+        let user = match users.iter().find(|u| u.github.eq_ignore_ascii_case(&reviewer)) {
+            Some(user) => user,
+            None => {
+                warn!("could not find the user with GitHub login {}", reviewer);
+                continue;
+            }
+        };
+        if !user.wants_notification(NOTIFICATION_RFD_REVIEW_REMINDERS) {
+            continue;
+        }
+
+        let channel = match slack.lookup_user_by_email(&user.email).await {
+            Ok(user) => user.id,
+            Err(e) => {
+                warn!("could not find the slack user of {}: {}", user.email, e);
+                continue;
+            }
+        };
+
+        let lines: Vec<String> = rfds
+            .iter()
+            .map(|(rfd, since)| {
+                format!(
+                    "• <{}|{}> _(in discussion since {})_",
+                    if rfd.discussion.is_empty() {
+                        &rfd.link
+                    } else {
+                        &rfd.discussion
+                    },
+                    rfd.name,
+                    since.format("%B %-d")
+                )
+            })
+            .collect();
+        let msg = FormattedMessage {
+            channel,
+            blocks: vec![MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        "These RFDs are waiting on your review for more than {} days:\n{}",
+                        days,
+                        lines.join("\n")
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            }],
+            attachments: Default::default(),
+        };
+        company.post_to_slack_channel(db, &msg).await?;
+        info!("reminded {} about {} RFDs to review", user.email, rfds.len());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::{review_approvals, rfd_reviewers, rfds_to_remind};
+    use crate::rfd::{RFDStateTransition, RFD};
+    use crate::tests::mock_record;
+
+    #[test]
+    fn test_rfd_reviews() {
+        let reviews = vec![
+            ("alice".to_string(), "CHANGES_REQUESTED".to_string()),
+            ("bob".to_string(), "APPROVED".to_string()),
+            ("alice".to_string(), "APPROVED".to_string()),
+            ("carol".to_string(), "APPROVED".to_string()),
+            ("carol".to_string(), "COMMENTED".to_string()),
+            ("bob".to_string(), "CHANGES_REQUESTED".to_string()),
+            ("dave".to_string(), "COMMENTED".to_string()),
+        ];
+        assert_eq!(
+            vec!["alice".to_string(), "carol".to_string()],
+            review_approvals(&reviews)
+        );
+
+        let requested = vec!["erin".to_string(), "alice".to_string()];
+        assert_eq!(
+            vec!["alice", "bob", "carol", "dave", "erin"],
+            rfd_reviewers(&requested, &reviews)
+        );
+    }
+
+    #[test]
+    fn test_rfds_to_remind() {
+        let now = Utc::now();
+        let mut rfd: RFD = mock_record(
+            json!({"number": 12, "title": "Things", "name": "RFD 12 Things", "state": "discussion", "link": ""}),
+        );
+        // Commits do not reset how long the RFD has been in discussion.
+        rfd.commit_date = now - Duration::days(1);
+        rfd.reviewers = vec!["Alice".to_string(), "bob".to_string()];
+        rfd.approvals = vec!["bob".to_string()];
+
+        let mut recent = rfd.clone();
+        recent.number = 13;
+        let mut published = rfd.clone();
+        published.number = 14;
+        published.state = "published".to_string();
+        // We never saw this one move to discussion.
+        let mut unknown = rfd.clone();
+        unknown.number = 15;
+
+        let transition = |number: i32, to_state: &str, days_ago: i64| RFDStateTransition {
+            id: number,
+            rfd_number: number,
+            from_state: "ideation".to_string(),
+            to_state: to_state.to_string(),
+            transitioned_at: now - Duration::days(days_ago),
+            cio_company_id: 1,
+        };
+        let transitions = vec![
+            transition(12, "discussion", 10),
+            transition(13, "discussion", 2),
+            transition(14, "discussion", 20),
+            transition(14, "published", 10),
+        ];
+
+        let by_reviewer = rfds_to_remind(vec![rfd, recent, published, unknown], &transitions, now, 7);
+        assert_eq!(vec!["alice"], by_reviewer.keys().cloned().collect::<Vec<String>>());
+        assert_eq!(1, by_reviewer["alice"].len());
+        assert_eq!(12, by_reviewer["alice"][0].0.number);
+        assert_eq!(now - Duration::days(10), by_reviewer["alice"][0].1);
+    }
+}
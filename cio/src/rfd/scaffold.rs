@@ -0,0 +1,213 @@
+use anyhow::{bail, Result};
+use chrono::Duration;
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    get_rfd_number_reservation, reserve_rfd_number, use_rfd_number_reservation, GitHubRFDRepo, RFDNumber, RFDs,
+};
+use crate::{companies::Company, db::Database, utils::create_or_update_file_in_github_repo};
+
+/// How many numbers we try when someone creates the branch of the one we reserved without
+/// reserving it first.
+static MAX_SCAFFOLD_ATTEMPTS: usize = 5;
+
+/// How long we hold the number of an RFD we scaffold without a reservation, while we create
+/// its branch.
+static SCAFFOLD_RESERVATION_MINUTES: i64 = 10;
+
+/// A new RFD, on its own branch with only its front matter.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ScaffoldedRFD {
+    pub number: i32,
+    pub number_string: String,
+    pub title: String,
+    pub branch: String,
+    /// The link to the README of the RFD on its branch.
+    pub link: String,
+}
+
+/// The number after the highest one that is taken, by an RFD or by a branch of one.
+pub fn next_rfd_number(taken: &[RFDNumber]) -> RFDNumber {
+    let highest = taken.iter().map(|n| i32::from(*n)).max().unwrap_or_default();
+
+    RFDNumber::from(highest + 1)
+}
+
+/// The README of a new RFD, with the front matter of the RFD template.
+pub fn rfd_template(number: &RFDNumber, title: &str, authors: &str) -> String {
+    format!(
+        r#":showtitle:
+:toc: left
+:numbered:
+:icons: font
+:state: prediscussion
+:discussion:
+:authors: {}
+
+= RFD {} {}
+{{authors}}
+
+== Background
+
+== Determinations
+"#,
+        authors.trim(),
+        number,
+        title.trim()
+    )
+}
+
+/// The numbers taken by the RFDs we have and by the branches of the RFD repo.
+pub async fn taken_rfd_numbers(db: &Database, company: &Company, repo: &GitHubRFDRepo) -> Result<Vec<RFDNumber>> {
+    let mut taken: Vec<RFDNumber> = RFDs::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .map(|rfd| RFDNumber::from(rfd.number))
+        .collect();
+    taken.append(&mut repo.get_rfd_branch_numbers().await?);
+
+    Ok(taken)
+}
+
+/// Create a new RFD: its branch, and a README with the front matter of the template. It uses
+/// the `reserved` number if there is one, it has to be still held for `requested_by`, or
+/// reserves the next free one for them.
+pub async fn scaffold_rfd(
+    db: &Database,
+    company: &Company,
+    title: &str,
+    authors: &str,
+    requested_by: &str,
+    reserved: Option<i32>,
+) -> Result<ScaffoldedRFD> {
+    let title = title.trim();
+    if title.is_empty() {
+        bail!("a new RFD needs a title");
+    }
+    if authors.trim().is_empty() {
+        bail!("a new RFD needs authors");
+    }
+
+    let repo = GitHubRFDRepo::new(company).await?;
+
+    let (number, branch) = if let Some(reserved) = reserved {
+        let number = RFDNumber::from(
+            get_rfd_number_reservation(db, company.id, reserved, requested_by)
+                .await?
+                .number,
+        );
+        let branch = match repo.create_branch(number.as_number_string()).await {
+            Ok(branch) => branch,
+            Err(e) => bail!("creating the branch of RFD {} failed: {}", number, e),
+        };
+
+        // The branch holds the number now, even if the reservation ran out in between.
+        if let Err(e) = use_rfd_number_reservation(db, company.id, reserved, requested_by).await {
+            warn!("marking the reservation of RFD {} used failed: {}", number, e);
+        }
+
+        (number, branch)
+    } else {
+        let mut taken = taken_rfd_numbers(db, company, &repo).await?;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let reservation = reserve_rfd_number(
+                db,
+                company.id,
+                &taken,
+                title,
+                requested_by,
+                Duration::minutes(SCAFFOLD_RESERVATION_MINUTES),
+                // The number is used right away, it is not held.
+                None,
+            )
+            .await?;
+            let number = RFDNumber::from(reservation.number);
+
+            match repo.create_branch(number.as_number_string()).await {
+                Ok(branch) => {
+                    use_rfd_number_reservation(db, company.id, reservation.number, requested_by).await?;
+                    break (number, branch);
+                }
+                // Someone created the branch for the number without reserving it.
+                Err(e) if attempts < MAX_SCAFFOLD_ATTEMPTS && e.to_string().contains("Reference already exists") => {
+                    info!("the branch of RFD {} exists already, trying the next number", number);
+                    taken.push(number);
+                }
+                Err(e) => bail!("creating the branch of RFD {} failed: {}", number, e),
+            }
+        }
+    };
+
+    let path = format!("{}/README.adoc", number.repo_directory());
+    create_or_update_file_in_github_repo(
+        branch.client(),
+        &branch.owner,
+        &branch.repo,
+        &branch.branch,
+        &path,
+        rfd_template(&number, title, authors).into_bytes(),
+    )
+    .await?;
+
+    info!("scaffolded RFD {} `{}` on branch `{}`", number, title, branch.branch);
+
+    Ok(ScaffoldedRFD {
+        number: number.into(),
+        number_string: number.as_number_string(),
+        title: title.to_string(),
+        link: format!(
+            "https://github.com/{}/{}/blob/{}{}",
+            branch.owner, branch.repo, branch.branch, path
+        ),
+        branch: branch.branch,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{next_rfd_number, rfd_template};
+    use crate::rfd::{lint_rfd_front_matter, RFDContent, RFDNumber};
+
+    #[test]
+    fn test_next_rfd_number() {
+        assert_eq!(1, i32::from(next_rfd_number(&[])));
+        assert_eq!(
+            124,
+            i32::from(next_rfd_number(&[
+                RFDNumber::from(12),
+                RFDNumber::from(123),
+                RFDNumber::from(7)
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_rfd_number_from_branch() {
+        assert_eq!(Some(123), RFDNumber::from_branch("0123").map(i32::from));
+        assert!(RFDNumber::from_branch("master").is_none());
+        assert!(RFDNumber::from_branch("123").is_none());
+        assert!(RFDNumber::from_branch("0123-fix").is_none());
+    }
+
+    #[test]
+    fn test_rfd_template() {
+        let readme = rfd_template(&RFDNumber::from(123), " Things ", "Ada Lovelace <ada@example.com>");
+        let content = RFDContent::new_asciidoc(readme);
+
+        assert_eq!("Things", content.get_title());
+        assert_eq!("prediscussion", content.get_state());
+        assert_eq!("Ada Lovelace <ada@example.com>", content.get_authors());
+        assert!(lint_rfd_front_matter(
+            &content,
+            &["example.com"],
+            &BTreeSet::from(["ada@example.com".to_string()])
+        )
+        .is_empty());
+    }
+}
@@ -1,12 +1,73 @@
 use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::sql_types::{BigInt, Float, Integer, Text};
 use hmac::{Hmac, Mac};
 use md5::Md5;
 use meilisearch_minimal_api::{IndexClient, MeiliClient, SearchQuery};
 use parse_rfd::{parse, ParsedDoc, Section};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{cmp::min, collections::HashMap};
 
 use super::RFDNumber;
+use crate::db::Database;
+
+/// The most results a full-text search of the RFDs returns.
+pub const RFD_SEARCH_MAX_RESULTS: i64 = 50;
+
+// The expression has to be the same as the one of the `idx_rfds_full_text_search` index for
+// Postgres to use it. Tags in the rendered HTML are not indexed.
+const RFD_FULL_TEXT_SEARCH_QUERY: &str = r#"
+SELECT number, number_string, title, state, rendered_link, rank,
+    ts_headline('english', regexp_replace(html, '<[^>]*>', ' ', 'g'), query, 'MaxFragments=2, MaxWords=30, MinWords=10') AS snippet
+FROM (
+    SELECT number, number_string, title, state, rendered_link, html, query,
+        ts_rank(setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', html), 'B'), query) AS rank
+    FROM rfds, websearch_to_tsquery('english', $1) query
+    WHERE cio_company_id = $2
+        AND (setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', html), 'B')) @@ query
+    ORDER BY rank DESC, number DESC
+    LIMIT $3
+) AS results
+ORDER BY rank DESC, number DESC
+"#;
+
+/// An RFD matching a full-text search, best match first.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize, QueryableByName)]
+pub struct RFDSearchResult {
+    #[diesel(sql_type = Integer)]
+    pub number: i32,
+    #[diesel(sql_type = Text)]
+    pub number_string: String,
+    #[diesel(sql_type = Text)]
+    pub title: String,
+    #[diesel(sql_type = Text)]
+    pub state: String,
+    #[diesel(sql_type = Text)]
+    pub rendered_link: String,
+    #[diesel(sql_type = Float)]
+    pub rank: f32,
+    /// The parts of the RFD matching the search, with the matching words in `<b>` tags.
+    #[diesel(sql_type = Text)]
+    pub snippet: String,
+}
+
+/// Search the titles and the rendered content of the RFDs of a company. The query is in the
+/// syntax of web search engines: `"quoted phrases"`, `or`, and `-excluded` words.
+pub async fn search_rfds(db: &Database, cio_company_id: i32, query: &str, limit: i64) -> Result<Vec<RFDSearchResult>> {
+    if query.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let results = diesel::sql_query(RFD_FULL_TEXT_SEARCH_QUERY)
+        .bind::<Text, _>(query.trim().to_string())
+        .bind::<Integer, _>(cio_company_id)
+        .bind::<BigInt, _>(limit.clamp(1, RFD_SEARCH_MAX_RESULTS))
+        .load_async::<RFDSearchResult>(db.pool())
+        .await?;
+
+    Ok(results)
+}
 
 pub struct RFDSearchIndex {}
 
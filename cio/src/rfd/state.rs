@@ -0,0 +1,118 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Error, Result};
+
+/// The states of an RFD. They go from ideation to discussion to published, and then committed
+/// once the work is done. An RFD can be abandoned from any state before it is committed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RFDState {
+    /// The older name of ideation, that some RFDs still use.
+    Prediscussion,
+    Ideation,
+    Discussion,
+    Published,
+    Committed,
+    Abandoned,
+}
+
+impl RFDState {
+    /// Every state, in the order an RFD goes through them.
+    pub const ALL: [RFDState; 6] = [
+        RFDState::Prediscussion,
+        RFDState::Ideation,
+        RFDState::Discussion,
+        RFDState::Published,
+        RFDState::Committed,
+        RFDState::Abandoned,
+    ];
+
+    /// Return if an RFD can move from this state to another. Staying in the same state is
+    /// always fine, committed and abandoned RFDs do not move anymore.
+    pub fn can_transition_to(&self, next: RFDState) -> bool {
+        use RFDState::*;
+
+        *self == next
+            || matches!(
+                (*self, next),
+                (Prediscussion, Ideation | Discussion | Abandoned)
+                    | (Ideation, Discussion | Abandoned)
+                    | (Discussion, Published | Abandoned)
+                    | (Published, Committed | Abandoned)
+            )
+    }
+
+    /// Check the move of an RFD from a state to another, as they are in the front matter.
+    /// Returns the move if the state changed, and an error if the RFD cannot make it. RFDs we
+    /// stored in a state we do not know can go anywhere, so they are not stuck.
+    pub fn check_transition(from: &str, to: &str) -> Result<Option<(RFDState, RFDState)>> {
+        let to: RFDState = to.parse()?;
+        let from: RFDState = match from.parse() {
+            Ok(from) => from,
+            Err(_) => return Ok(None),
+        };
+
+        if from == to {
+            return Ok(None);
+        }
+        if !from.can_transition_to(to) {
+            bail!("an RFD in {} cannot move to {}", from, to);
+        }
+
+        Ok(Some((from, to)))
+    }
+}
+
+impl fmt::Display for RFDState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RFDState::Prediscussion => write!(f, "prediscussion"),
+            RFDState::Ideation => write!(f, "ideation"),
+            RFDState::Discussion => write!(f, "discussion"),
+            RFDState::Published => write!(f, "published"),
+            RFDState::Committed => write!(f, "committed"),
+            RFDState::Abandoned => write!(f, "abandoned"),
+        }
+    }
+}
+
+impl FromStr for RFDState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "prediscussion" => Ok(RFDState::Prediscussion),
+            "ideation" => Ok(RFDState::Ideation),
+            "discussion" => Ok(RFDState::Discussion),
+            "published" => Ok(RFDState::Published),
+            "committed" => Ok(RFDState::Committed),
+            "abandoned" => Ok(RFDState::Abandoned),
+            _ => bail!("`{}` is not an RFD state", s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RFDState;
+
+    #[test]
+    fn test_rfd_state_transitions() {
+        assert!(RFDState::Ideation.can_transition_to(RFDState::Discussion));
+        assert!(RFDState::Discussion.can_transition_to(RFDState::Published));
+        assert!(RFDState::Published.can_transition_to(RFDState::Committed));
+        assert!(RFDState::Published.can_transition_to(RFDState::Abandoned));
+        assert!(RFDState::Committed.can_transition_to(RFDState::Committed));
+        assert!(!RFDState::Ideation.can_transition_to(RFDState::Published));
+        assert!(!RFDState::Published.can_transition_to(RFDState::Discussion));
+        assert!(!RFDState::Abandoned.can_transition_to(RFDState::Ideation));
+
+        assert_eq!(
+            Some((RFDState::Discussion, RFDState::Published)),
+            RFDState::check_transition("discussion", "published").unwrap()
+        );
+        assert_eq!(None, RFDState::check_transition("published", "published").unwrap());
+        assert_eq!(None, RFDState::check_transition("", "published").unwrap());
+        assert!(RFDState::check_transition("committed", "discussion").is_err());
+        assert!(RFDState::check_transition("discussion", "thinking").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{db::Database, schema::rfd_branch_syncs};
+
+/// The last commit of an RFD on a branch that we rendered, so the sync can skip the RFDs that
+/// did not change since.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RFDBranchSync {
+    pub id: i32,
+    pub rfd_number: i32,
+    pub branch: String,
+    pub commit_sha: String,
+    pub synced_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone)]
+#[diesel(table_name = rfd_branch_syncs)]
+struct NewRFDBranchSync {
+    rfd_number: i32,
+    branch: String,
+    commit_sha: String,
+    synced_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// Returns if we already rendered the latest commit of an RFD. We do not know the latest
+/// commit if it is empty, so we render it again.
+pub fn is_rendered(rendered_commit_sha: Option<&str>, latest_commit_sha: &str) -> bool {
+    !latest_commit_sha.is_empty() && rendered_commit_sha == Some(latest_commit_sha)
+}
+
+/// Get the last commit of an RFD on a branch that we rendered.
+pub async fn get_rendered_commit_sha(
+    db: &Database,
+    cio_company_id: i32,
+    rfd_number: i32,
+    branch: &str,
+) -> Result<Option<String>> {
+    let syncs = rfd_branch_syncs::dsl::rfd_branch_syncs
+        .filter(rfd_branch_syncs::dsl::cio_company_id.eq(cio_company_id))
+        .filter(rfd_branch_syncs::dsl::rfd_number.eq(rfd_number))
+        .filter(rfd_branch_syncs::dsl::branch.eq(branch.to_string()))
+        .load_async::<RFDBranchSync>(db.pool())
+        .await?;
+
+    Ok(syncs.into_iter().next().map(|s| s.commit_sha))
+}
+
+/// Record the last commit of an RFD on a branch that we rendered.
+pub async fn set_rendered_commit_sha(
+    db: &Database,
+    cio_company_id: i32,
+    rfd_number: i32,
+    branch: &str,
+    commit_sha: &str,
+) -> Result<()> {
+    let sync = NewRFDBranchSync {
+        rfd_number,
+        branch: branch.to_string(),
+        commit_sha: commit_sha.to_string(),
+        synced_at: Utc::now(),
+        cio_company_id,
+    };
+
+    diesel::insert_into(rfd_branch_syncs::table)
+        .values(sync.clone())
+        .on_conflict((
+            rfd_branch_syncs::dsl::cio_company_id,
+            rfd_branch_syncs::dsl::rfd_number,
+            rfd_branch_syncs::dsl::branch,
+        ))
+        .do_update()
+        .set(sync)
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_rendered;
+
+    #[test]
+    fn test_is_rendered() {
+        assert!(is_rendered(Some("abc"), "abc"));
+        assert!(!is_rendered(Some("abc"), "def"));
+        assert!(!is_rendered(None, "abc"));
+        assert!(!is_rendered(Some(""), ""));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{companies::Company, configs::UserConfig, db::Database, schema::role_address_holders};
+
+/// An address that belongs to a role rather than a person, like `press` or `security`. It is
+/// an alias of whoever holds the role, and moves with it.
+#[derive(Debug, Default, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RoleAddressConfig {
+    /// The username of who holds the role.
+    pub holder: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+/// Someone holding a role address, from when they got it until they handed it over.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct RoleAddressHolder {
+    pub id: i32,
+    /// The role address, without the domain, like `press`.
+    pub address: String,
+    pub username: String,
+    pub started_at: DateTime<Utc>,
+    /// None while they still hold it.
+    pub ended_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = role_address_holders)]
+struct NewRoleAddressHolder {
+    address: String,
+    username: String,
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+    cio_company_id: i32,
+}
+
+/// Add the role addresses to the aliases of the users holding them, so they end up in Google
+/// Workspace with their other aliases. When a role is handed over, the next sync moves the
+/// alias from the old holder to the new one.
+pub fn assign_role_addresses(
+    users: &mut BTreeMap<String, UserConfig>,
+    role_addresses: &BTreeMap<String, RoleAddressConfig>,
+) {
+    for (address, role) in role_addresses {
+        let address = address.trim().to_lowercase();
+        match users.values_mut().find(|u| u.username == role.holder.trim()) {
+            Some(user) => {
+                if !user.aliases.contains(&address) {
+                    user.aliases.push(address);
+                }
+            }
+            None => warn!(
+                "the role address `{}` is held by `{}`, who is not in the configs",
+                address, role.holder
+            ),
+        }
+    }
+}
+
+/// Return who held a role address at a point in time.
+pub fn holder_at<'a>(
+    history: &'a [RoleAddressHolder],
+    address: &str,
+    at: DateTime<Utc>,
+) -> Option<&'a RoleAddressHolder> {
+    history
+        .iter()
+        .find(|h| h.address == address && h.started_at <= at && h.ended_at.map(|end| at < end).unwrap_or(true))
+}
+
+/// Record who holds each role address, closing the stints of the ones who handed it over.
+pub async fn sync_role_address_holders(
+    db: &Database,
+    company: &Company,
+    role_addresses: &BTreeMap<String, RoleAddressConfig>,
+) -> Result<()> {
+    let current = role_address_holders::dsl::role_address_holders
+        .filter(role_address_holders::dsl::cio_company_id.eq(company.id))
+        .filter(role_address_holders::dsl::ended_at.is_null())
+        .load_async::<RoleAddressHolder>(db.pool())
+        .await?;
+
+    let now = Utc::now();
+    for holder in &current {
+        let still_holds = role_addresses.iter().any(|(address, role)| {
+            address.trim().to_lowercase() == holder.address && role.holder.trim() == holder.username
+        });
+        if still_holds {
+            continue;
+        }
+
+        diesel::update(
+            role_address_holders::dsl::role_address_holders.filter(role_address_holders::dsl::id.eq(holder.id)),
+        )
+        .set(role_address_holders::dsl::ended_at.eq(Some(now)))
+        .execute_async(db.pool())
+        .await?;
+        info!(
+            "`{}` handed over the role address `{}@{}`",
+            holder.username, holder.address, company.gsuite_domain
+        );
+    }
+
+    for (address, role) in role_addresses {
+        let address = address.trim().to_lowercase();
+        let username = role.holder.trim().to_string();
+        if current.iter().any(|h| h.address == address && h.username == username) {
+            continue;
+        }
+
+        diesel::insert_into(role_address_holders::table)
+            .values(NewRoleAddressHolder {
+                address: address.to_string(),
+                username: username.to_string(),
+                started_at: now,
+                ended_at: None,
+                cio_company_id: company.id,
+            })
+            .execute_async(db.pool())
+            .await?;
+        info!(
+            "`{}` now holds the role address `{}@{}`",
+            username, address, company.gsuite_domain
+        );
+    }
+
+    Ok(())
+}
+
+/// Get who held a role address of a company at a point in time.
+pub async fn get_role_address_holder(
+    db: &Database,
+    company: &Company,
+    address: &str,
+    at: DateTime<Utc>,
+) -> Result<Option<RoleAddressHolder>> {
+    let address = address.split('@').next().unwrap_or_default().trim().to_lowercase();
+    let history = role_address_holders::dsl::role_address_holders
+        .filter(role_address_holders::dsl::cio_company_id.eq(company.id))
+        .filter(role_address_holders::dsl::address.eq(address.to_string()))
+        .load_async::<RoleAddressHolder>(db.pool())
+        .await?;
+
+    Ok(holder_at(&history, &address, at).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::{assign_role_addresses, holder_at, RoleAddressConfig, RoleAddressHolder};
+    use crate::configs::UserConfig;
+
+    #[test]
+    fn test_role_addresses() {
+        let user: UserConfig = toml::from_str(
+            r#"
+first_name = 'Test'
+last_name = 'User'
+username = 'test'
+aliases = ['test.user']
+"#,
+        )
+        .unwrap();
+        let mut users: BTreeMap<String, UserConfig> = BTreeMap::new();
+        users.insert("test".to_string(), user);
+
+        let mut role_addresses = BTreeMap::new();
+        role_addresses.insert(
+            "Press".to_string(),
+            RoleAddressConfig {
+                holder: "test".to_string(),
+                ..Default::default()
+            },
+        );
+        role_addresses.insert(
+            "security".to_string(),
+            RoleAddressConfig {
+                holder: "nobody".to_string(),
+                ..Default::default()
+            },
+        );
+        assign_role_addresses(&mut users, &role_addresses);
+        assert_eq!(
+            vec!["test.user".to_string(), "press".to_string()],
+            users["test"].aliases
+        );
+
+        let holder = |id: i32, username: &str, start: u32, end: Option<u32>| RoleAddressHolder {
+            id,
+            address: "press".to_string(),
+            username: username.to_string(),
+            started_at: Utc.ymd(2023, start, 1).and_hms(0, 0, 0),
+            ended_at: end.map(|end| Utc.ymd(2023, end, 1).and_hms(0, 0, 0)),
+            cio_company_id: 1,
+        };
+        let history = vec![holder(1, "test", 1, Some(6)), holder(2, "alex", 6, None)];
+        assert_eq!(
+            Some("test"),
+            holder_at(&history, "press", Utc.ymd(2023, 3, 1).and_hms(0, 0, 0)).map(|h| h.username.as_str())
+        );
+        assert_eq!(
+            Some("alex"),
+            holder_at(&history, "press", Utc.ymd(2023, 6, 1).and_hms(0, 0, 0)).map(|h| h.username.as_str())
+        );
+        assert_eq!(
+            None,
+            holder_at(&history, "press", Utc.ymd(2022, 12, 1).and_hms(0, 0, 0))
+        );
+    }
+}
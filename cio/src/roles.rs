@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl};
+use log::info;
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    airtable::AIRTABLE_ROLES_TABLE, companies::Company, core::UpdateAirtableRecord, db::Database, schema::roles,
+};
+
+/// The Checkr package used when a role does not specify one.
+pub static DEFAULT_CHECKR_PACKAGE: &str = "premium_criminal";
+
+/// The data type for a role in our leveling catalog. Roles are defined in the
+/// configs repo and referenced by applicants, offers, and background checks.
+#[db {
+    new_struct_name = "Role",
+    airtable_base = "hiring",
+    airtable_table = "AIRTABLE_ROLES_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "name" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, Default, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = roles)]
+pub struct NewRole {
+    /// name will not be used in config files.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// The title of the role as it is shown to applicants, ie. "Product Engineer".
+    pub title: String,
+    /// The ladder the role belongs to, ie. "engineering".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ladder: String,
+    #[serde(default)]
+    pub level: i32,
+    /// A reference to the compensation band for the role. This is an identifier
+    /// and not the compensation itself.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub comp_band: String,
+    /// The Checkr package to use for background checks for this role.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub checkr_package: String,
+    /// The locale applicant-facing emails for the role are sent in, ie. "de". Applicants can
+    /// override this with their own preference.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub locale: String,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a Role.
+#[async_trait]
+impl UpdateAirtableRecord<Role> for Role {
+    async fn update_airtable_record(&mut self, _record: Role) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Role {
+    /// Find the role matching the free-form role string on an applicant. We match
+    /// on either the title or the name of the role.
+    pub async fn get_for_applicant_role(db: &Database, cio_company_id: i32, role: &str) -> Result<Option<Self>> {
+        let role = role.trim().to_string();
+        if role.is_empty() {
+            return Ok(None);
+        }
+
+        let roles = roles::dsl::roles
+            .filter(roles::dsl::cio_company_id.eq(cio_company_id))
+            .filter(roles::dsl::title.eq(role.to_string()).or(roles::dsl::name.eq(role)))
+            .order_by(roles::dsl::id.asc())
+            .load_async::<Role>(db.pool())
+            .await?;
+
+        Ok(roles.into_iter().next())
+    }
+
+    /// Return the Checkr package to use for background checks for this role.
+    pub fn background_check_package(&self) -> &str {
+        if self.checkr_package.is_empty() {
+            DEFAULT_CHECKR_PACKAGE
+        } else {
+            &self.checkr_package
+        }
+    }
+
+    /// Fill in the role placeholders in an offer envelope.
+    pub fn fill_envelope(&self, envelope: &mut docusign::Envelope) {
+        for template_role in envelope.template_roles.iter_mut() {
+            template_role.email_notification.email_subject =
+                self.replace_placeholders(&template_role.email_notification.email_subject);
+            template_role.email_notification.email_body =
+                self.replace_placeholders(&template_role.email_notification.email_body);
+        }
+    }
+
+    fn replace_placeholders(&self, s: &str) -> String {
+        s.replace("{role_title}", &self.title)
+            .replace("{role_ladder}", &self.ladder)
+            .replace("{role_level}", &self.level.to_string())
+            .replace("{role_comp_band}", &self.comp_band)
+    }
+}
+
+/// Sync our roles with our database and then update Airtable from the database.
+pub async fn sync_roles(db: &Database, roles: BTreeMap<String, NewRole>, company: &Company) -> Result<()> {
+    // Get all the roles.
+    let db_roles = Roles::get_from_db(db, company.id).await?;
+    // Create a BTreeMap
+    let mut role_map: BTreeMap<String, Role> = Default::default();
+    for r in db_roles {
+        role_map.insert(r.name.to_string(), r);
+    }
+    // Sync roles.
+    for (name, mut role) in roles {
+        role.name = name.to_string();
+        role.cio_company_id = company.id;
+
+        role.upsert(db).await?;
+
+        // Remove the role from the BTreeMap.
+        role_map.remove(&role.name);
+    }
+    // Remove any roles that should no longer be in the database.
+    // This is found by the remaining roles that are in the map since we removed
+    // the existing roles from the map above.
+    for (_, role) in role_map {
+        role.delete(db).await?;
+    }
+    info!("updated configs roles in the database");
+
+    // Update roles in airtable.
+    Roles::get_from_db(db, company.id).await?.update_airtable(db).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_role() -> Role {
+        Role {
+            id: 1,
+            name: "product-engineer".to_string(),
+            title: "Product Engineer".to_string(),
+            ladder: "engineering".to_string(),
+            level: 3,
+            comp_band: "eng-3".to_string(),
+            checkr_package: "".to_string(),
+            locale: "".to_string(),
+            cio_company_id: 1,
+            airtable_record_id: "".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_background_check_package_defaults() {
+        let mut role = mock_role();
+        assert_eq!(DEFAULT_CHECKR_PACKAGE, role.background_check_package());
+
+        role.checkr_package = "driver_pro".to_string();
+        assert_eq!("driver_pro", role.background_check_package());
+    }
+
+    #[test]
+    fn test_replaces_role_placeholders() {
+        let role = mock_role();
+
+        assert_eq!(
+            "Offer for Product Engineer (engineering, level 3, band eng-3)",
+            role.replace_placeholders(
+                "Offer for {role_title} ({role_ladder}, level {role_level}, band {role_comp_band})"
+            )
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::Database,
+    functions::Function,
+    schema::{functions, saga_steps},
+};
+
+pub const SAGA_STEP_PENDING: &str = "pending";
+pub const SAGA_STEP_RUNNING: &str = "running";
+pub const SAGA_STEP_SUCCEEDED: &str = "succeeded";
+pub const SAGA_STEP_FAILED: &str = "failed";
+pub const SAGA_STEP_UNDOING: &str = "undoing";
+pub const SAGA_STEP_UNDONE: &str = "undone";
+
+/// The most sagas we list at once.
+static MAX_SAGAS_LISTED: i64 = 200;
+
+/// A node of a saga execution, with where it is at. The steps are created with the saga, and
+/// updated from the events the saga executor records as it runs them.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct SagaStep {
+    pub id: i32,
+    pub saga_id: String,
+    pub node_id: i32,
+    /// The name of the action, empty for the nodes the executor adds on its own, like the
+    /// start and end of the saga.
+    pub name: String,
+    pub label: String,
+    pub status: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = saga_steps)]
+struct NewSagaStep {
+    saga_id: String,
+    node_id: i32,
+    name: String,
+    label: String,
+    status: String,
+}
+
+impl SagaStep {
+    /// Move the step to a status, keeping track of when it started and finished.
+    pub fn apply(&mut self, status: &str, error: &str, at: DateTime<Utc>) {
+        match status {
+            SAGA_STEP_RUNNING => {
+                self.started_at = Some(at);
+                self.finished_at = None;
+            }
+            SAGA_STEP_SUCCEEDED | SAGA_STEP_FAILED | SAGA_STEP_UNDONE => self.finished_at = Some(at),
+            _ => (),
+        }
+        if !error.is_empty() {
+            self.error = error.to_string();
+        }
+        self.status = status.to_string();
+    }
+
+    /// How long the step ran, or has been running for if it is not done.
+    pub fn duration_seconds(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.started_at
+            .map(|started_at| (self.finished_at.unwrap_or(now) - started_at).num_seconds())
+    }
+}
+
+/// The status and error of a step after a saga node event.
+fn step_status(event_type: &steno::SagaNodeEventType) -> (&'static str, String) {
+    match event_type {
+        steno::SagaNodeEventType::Started => (SAGA_STEP_RUNNING, String::new()),
+        steno::SagaNodeEventType::Succeeded(_) => (SAGA_STEP_SUCCEEDED, String::new()),
+        steno::SagaNodeEventType::Failed(err) => (SAGA_STEP_FAILED, format!("{:?}", err)),
+        steno::SagaNodeEventType::UndoStarted => (SAGA_STEP_UNDOING, String::new()),
+        steno::SagaNodeEventType::UndoFinished => (SAGA_STEP_UNDONE, String::new()),
+    }
+}
+
+/// Create the steps of a saga before it starts, from the names and labels of its actions in
+/// the order they were appended to its template.
+pub async fn create_saga_steps(db: &Database, saga_id: &uuid::Uuid, actions: &[(&str, &str)]) -> Result<()> {
+    let steps: Vec<NewSagaStep> = actions
+        .iter()
+        .enumerate()
+        .map(|(i, (name, label))| NewSagaStep {
+            saga_id: saga_id.to_string(),
+            node_id: i as i32,
+            name: name.to_string(),
+            label: label.to_string(),
+            status: SAGA_STEP_PENDING.to_string(),
+        })
+        .collect();
+
+    diesel::insert_into(saga_steps::table)
+        .values(steps)
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Update the step of a saga from an event of the saga executor.
+pub async fn record_saga_step_event(db: &Database, event: &steno::SagaNodeEvent) -> Result<()> {
+    // Node ids serialize as their index in the saga graph.
+    let node_id = match serde_json::to_value(&event.node_id)?.as_i64() {
+        Some(node_id) => node_id as i32,
+        None => return Ok(()),
+    };
+    let saga_id = event.saga_id.to_string();
+
+    let existing = saga_steps::dsl::saga_steps
+        .filter(saga_steps::dsl::saga_id.eq(saga_id.to_string()))
+        .filter(saga_steps::dsl::node_id.eq(node_id))
+        .load_async::<SagaStep>(db.pool())
+        .await?;
+    let mut step = match existing.into_iter().next() {
+        Some(step) => step,
+        None => {
+            diesel::insert_into(saga_steps::table)
+                .values(NewSagaStep {
+                    saga_id,
+                    node_id,
+                    name: String::new(),
+                    label: String::new(),
+                    status: SAGA_STEP_PENDING.to_string(),
+                })
+                .get_result_async::<SagaStep>(db.pool())
+                .await?
+        }
+    };
+
+    let (status, error) = step_status(&event.event_type);
+    step.apply(status, &error, Utc::now());
+
+    diesel::update(saga_steps::dsl::saga_steps)
+        .filter(saga_steps::dsl::id.eq(step.id))
+        .set((
+            saga_steps::dsl::status.eq(step.status),
+            saga_steps::dsl::started_at.eq(step.started_at),
+            saga_steps::dsl::finished_at.eq(step.finished_at),
+            saga_steps::dsl::error.eq(step.error),
+        ))
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// A saga execution, without its logs.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SagaSummary {
+    pub saga_id: String,
+    pub name: String,
+    pub status: String,
+    pub conclusion: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<&Function> for SagaSummary {
+    fn from(f: &Function) -> Self {
+        SagaSummary {
+            saga_id: f.saga_id.to_string(),
+            name: f.name.to_string(),
+            status: f.status.to_string(),
+            conclusion: f.conclusion.to_string(),
+            created_at: f.created_at,
+            completed_at: f.completed_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SagaStepStatus {
+    pub node_id: i32,
+    pub name: String,
+    pub label: String,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<i64>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// An edge of the saga graph, from a step to the one that runs after it.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SagaEdge {
+    pub from: i32,
+    pub to: i32,
+}
+
+/// A saga execution with its step graph, so we can see where it stalled.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SagaExecution {
+    #[serde(flatten)]
+    pub summary: SagaSummary,
+    pub steps: Vec<SagaStepStatus>,
+    pub edges: Vec<SagaEdge>,
+    /// The name of the first step that did not succeed, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stalled_at: Option<String>,
+}
+
+impl SagaExecution {
+    pub fn new(function: &Function, steps: &[SagaStep], now: DateTime<Utc>) -> Self {
+        // The nodes the executor adds on its own are not steps anyone wrote.
+        let mut steps: Vec<&SagaStep> = steps.iter().filter(|s| !s.name.is_empty()).collect();
+        steps.sort_by_key(|s| s.node_id);
+
+        // Our actions are appended one after the other, so the graph is a chain.
+        let edges = steps
+            .windows(2)
+            .map(|w| SagaEdge {
+                from: w[0].node_id,
+                to: w[1].node_id,
+            })
+            .collect();
+        let stalled_at = steps
+            .iter()
+            .find(|s| s.status != SAGA_STEP_SUCCEEDED)
+            .map(|s| s.name.to_string());
+
+        SagaExecution {
+            summary: function.into(),
+            steps: steps
+                .iter()
+                .map(|s| SagaStepStatus {
+                    node_id: s.node_id,
+                    name: s.name.to_string(),
+                    label: s.label.to_string(),
+                    status: s.status.to_string(),
+                    started_at: s.started_at,
+                    finished_at: s.finished_at,
+                    duration_seconds: s.duration_seconds(now),
+                    error: s.error.to_string(),
+                })
+                .collect(),
+            edges,
+            stalled_at,
+        }
+    }
+}
+
+/// List the most recent saga executions, newest first.
+pub async fn list_recent_sagas(db: &Database, limit: i64) -> Result<Vec<SagaSummary>> {
+    let fns = functions::dsl::functions
+        .order_by(functions::dsl::created_at.desc())
+        .limit(limit.clamp(1, MAX_SAGAS_LISTED))
+        .load_async::<Function>(db.pool())
+        .await?;
+
+    Ok(fns.iter().map(SagaSummary::from).collect())
+}
+
+/// Get a saga execution with its steps, None if there is no saga with that id.
+pub async fn get_saga_execution(db: &Database, saga_id: &str) -> Result<Option<SagaExecution>> {
+    let function = match Function::get_from_db(db, saga_id.to_string()).await {
+        Some(function) => function,
+        None => return Ok(None),
+    };
+
+    let steps = saga_steps::dsl::saga_steps
+        .filter(saga_steps::dsl::saga_id.eq(saga_id.to_string()))
+        .load_async::<SagaStep>(db.pool())
+        .await?;
+
+    Ok(Some(SagaExecution::new(&function, &steps, Utc::now())))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+
+    fn mock_step(node_id: i32, name: &str) -> SagaStep {
+        SagaStep {
+            id: node_id,
+            saga_id: "saga".to_string(),
+            node_id,
+            name: name.to_string(),
+            label: name.to_string(),
+            status: SAGA_STEP_PENDING.to_string(),
+            started_at: None,
+            finished_at: None,
+            error: String::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_saga_execution() {
+        let start = Utc.ymd(2023, 6, 12).and_hms(10, 0, 0);
+
+        let mut create = mock_step(0, "create-user");
+        create.apply(SAGA_STEP_RUNNING, "", start);
+        create.apply(SAGA_STEP_SUCCEEDED, "", start + Duration::seconds(30));
+        let mut groups = mock_step(1, "add-groups");
+        groups.apply(SAGA_STEP_RUNNING, "", start + Duration::seconds(30));
+        groups.apply(
+            SAGA_STEP_FAILED,
+            "ActionFailed(\"no such group\")",
+            start + Duration::seconds(40),
+        );
+        let email = mock_step(2, "send-email");
+        // The start node of the executor.
+        let mut start_node = mock_step(3, "");
+        start_node.apply(SAGA_STEP_SUCCEEDED, "", start);
+
+        let function: Function = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "airtable_record_id": "",
+            "saga_id": "saga",
+            "name": "onboard",
+            "created_at": "2023-06-12T10:00:00Z",
+        }))
+        .unwrap();
+
+        let execution = SagaExecution::new(
+            &function,
+            &[email, start_node, groups, create],
+            start + Duration::minutes(5),
+        );
+        assert_eq!(
+            vec!["create-user", "add-groups", "send-email"],
+            execution.steps.iter().map(|s| s.name.as_str()).collect::<Vec<&str>>()
+        );
+        assert_eq!(
+            vec![SagaEdge { from: 0, to: 1 }, SagaEdge { from: 1, to: 2 }],
+            execution.edges
+        );
+        assert_eq!(Some(30), execution.steps[0].duration_seconds);
+        assert_eq!(Some(10), execution.steps[1].duration_seconds);
+        assert_eq!("ActionFailed(\"no such group\")", execution.steps[1].error);
+        assert_eq!(None, execution.steps[2].duration_seconds);
+        assert_eq!(Some("add-groups".to_string()), execution.stalled_at);
+    }
+}
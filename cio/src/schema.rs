@@ -51,6 +51,8 @@ table! {
         google_event_id -> Varchar,
         event_link -> Varchar,
         applicant -> Array<Text>,
+        conflicts -> Array<Text>,
+        cancelled_at -> Nullable<Timestamptz>,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
@@ -90,6 +92,22 @@ table! {
     }
 }
 
+table! {
+    applicant_withdrawals (id) {
+        id -> Int4,
+        applicant_id -> Int4,
+        email -> Varchar,
+        role -> Varchar,
+        stage -> Varchar,
+        reason -> Text,
+        reason_category -> Varchar,
+        source -> Varchar,
+        withdrawn_at -> Timestamptz,
+        confirmed_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     applicants (id) {
         id -> Int4,
@@ -167,6 +185,72 @@ table! {
         piia_envelope_created -> Nullable<Timestamptz>,
         piia_envelope_completed -> Nullable<Timestamptz>,
         link_to_reviews -> Array<Text>,
+        debrief_event_id -> Varchar,
+        debrief_event_link -> Varchar,
+        locale -> Varchar,
+        previous_employers -> Array<Text>,
+        referred_by -> Varchar,
+        interview_scorecards_count -> Int4,
+        interview_scorecard_average -> Float4,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    asset_audit_scans (id) {
+        id -> Int4,
+        asset_audit_id -> Int4,
+        barcode -> Varchar,
+        asset_item_name -> Varchar,
+        location -> Varchar,
+        scanned_by -> Varchar,
+        scanned_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    asset_audits (id) {
+        id -> Int4,
+        name -> Varchar,
+        location -> Varchar,
+        location_id -> Nullable<Int4>,
+        started_by -> Varchar,
+        started_at -> Timestamptz,
+        finished_at -> Nullable<Timestamptz>,
+        expected_count -> Int4,
+        scanned_count -> Int4,
+        not_scanned -> Array<Text>,
+        unknown_barcodes -> Array<Text>,
+        location_mismatches -> Array<Text>,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    asset_checkouts (id) {
+        id -> Int4,
+        asset_item_name -> Varchar,
+        email -> Varchar,
+        checked_out_at -> Timestamptz,
+        checked_in_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    asset_depreciations (id) {
+        id -> Int4,
+        name -> Varchar,
+        month -> Date,
+        asset_type -> Varchar,
+        item_count -> Int4,
+        purchase_price -> Float4,
+        book_value -> Float4,
+        depreciation -> Float4,
+        accumulated_depreciation -> Float4,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
@@ -196,13 +280,75 @@ table! {
         model_number -> Varchar,
         serial_number -> Varchar,
         purchase_price -> Float4,
+        purchase_date -> Nullable<Date>,
+        depreciation_schedule -> Varchar,
+        current_book_value -> Float4,
         current_employee_borrowing -> Varchar,
         conference_room_using -> Array<Text>,
         notes -> Varchar,
         barcode -> Varchar,
+        barcode_symbology -> Varchar,
         barcode_png -> Varchar,
         barcode_svg -> Varchar,
         barcode_pdf_label -> Varchar,
+        qr_code -> Varchar,
+        qr_code_png -> Varchar,
+        qr_code_svg -> Varchar,
+        supplier -> Varchar,
+        order_number -> Varchar,
+        expected_delivery_date -> Nullable<Date>,
+        is_loaner -> Bool,
+        warranty_expiration_date -> Nullable<Date>,
+        maintenance_interval_days -> Int4,
+        last_maintenance_date -> Nullable<Date>,
+        last_counted_date -> Nullable<Date>,
+        snipeit_asset_tag -> Varchar,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    asset_refresh_forecasts (id) {
+        id -> Int4,
+        name -> Varchar,
+        asset_item_id -> Int4,
+        asset_type -> Varchar,
+        employee -> Varchar,
+        purchase_date -> Date,
+        refresh_date -> Date,
+        quarter -> Varchar,
+        estimated_cost -> Float4,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    asset_reservations (id) {
+        id -> Int4,
+        asset_name -> Varchar,
+        email -> Varchar,
+        start_date -> Date,
+        end_date -> Date,
+        purpose -> Varchar,
+        status -> Varchar,
+        pickup_reminder_sent -> Bool,
+        return_reminder_sent -> Bool,
+        created_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    asset_tasks (id) {
+        id -> Int4,
+        name -> Varchar,
+        asset_item_name -> Varchar,
+        kind -> Varchar,
+        due_date -> Date,
+        status -> Varchar,
+        notes -> Varchar,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
@@ -275,6 +421,8 @@ table! {
         item -> Varchar,
         barcode -> Varchar,
         link_to_item -> Array<Text>,
+        action -> Varchar,
+        quantity -> Int4,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
@@ -352,6 +500,21 @@ table! {
     }
 }
 
+table! {
+    community_events (id) {
+        id -> Int4,
+        provider -> Varchar,
+        provider_event_id -> Varchar,
+        name -> Varchar,
+        starts_at -> Nullable<Timestamptz>,
+        ends_at -> Nullable<Timestamptz>,
+        location -> Varchar,
+        url -> Varchar,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     companys (id) {
         id -> Int4,
@@ -372,6 +535,8 @@ table! {
         printer_url -> Varchar,
         tailscale_api_key -> Varchar,
         shipbob_pat -> Varchar,
+        printful_api_key -> Varchar,
+        eventbrite_api_key -> Varchar,
         tripactions_client_id -> Varchar,
         tripactions_client_secret -> Varchar,
         airtable_api_key -> Varchar,
@@ -395,8 +560,88 @@ table! {
         slack_channel_mailing_lists -> Varchar,
         slack_channel_finance -> Varchar,
         slack_channel_debug -> Varchar,
+        slack_channel_alerts -> Varchar,
+        slack_channel_security -> Varchar,
+        slack_channel_low_stock -> Varchar,
+        slack_channel_office -> Varchar,
+        slack_channel_rfds -> Varchar,
         google_service_account -> Varchar,
+        google_service_account_email -> Varchar,
         nginx_ip -> Varchar,
+        transcription_provider -> Varchar,
+        whisper_url -> Varchar,
+        rfd_pdf_renderer -> Varchar,
+        rfd_review_reminder_days -> Int4,
+        recorded_meeting_retention_days -> Int4,
+        interview_recording_retention_days -> Int4,
+        zoom_sync_lookback_days -> Int4,
+        zoom_sync_cursor -> Nullable<Timestamptz>,
+        zoom_cloud_storage_limit_gb -> Int4,
+        zoom_account_id -> Varchar,
+        barcode_symbology -> Varchar,
+        label_template -> Varchar,
+        greenhouse_api_key -> Varchar,
+        greenhouse_user_id -> Varchar,
+        lever_api_key -> Varchar,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    conference_leads (id) {
+        id -> Int4,
+        conference -> Varchar,
+        email -> Varchar,
+        first_name -> Varchar,
+        last_name -> Varchar,
+        organization -> Varchar,
+        job_title -> Varchar,
+        notes -> Varchar,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    conference_tasks (id) {
+        id -> Int4,
+        conference_id -> Int4,
+        conference -> Varchar,
+        key -> Varchar,
+        title -> Varchar,
+        assignee -> Varchar,
+        due_on -> Date,
+        swag_bundle -> Varchar,
+        outbound_shipment_id -> Nullable<Int4>,
+        completed_at -> Nullable<Timestamptz>,
+        notified_at -> Nullable<Timestamptz>,
+        error -> Varchar,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    conferences (id) {
+        id -> Int4,
+        name -> Varchar,
+        starts_on -> Nullable<Date>,
+        ends_on -> Nullable<Date>,
+        location -> Varchar,
+        url -> Varchar,
+        sponsorship_level -> Varchar,
+        booth_staff -> Array<Text>,
+        swag_bundles -> Array<Text>,
+        ship_to_name -> Varchar,
+        ship_to_email -> Varchar,
+        ship_to_phone -> Varchar,
+        ship_to_street_1 -> Varchar,
+        ship_to_street_2 -> Varchar,
+        ship_to_city -> Varchar,
+        ship_to_state -> Varchar,
+        ship_to_zipcode -> Varchar,
+        ship_to_country -> Varchar,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
@@ -441,6 +686,48 @@ table! {
     }
 }
 
+table! {
+    drive_uploads (id) {
+        id -> Int4,
+        drive_id -> Varchar,
+        parent_id -> Varchar,
+        name -> Varchar,
+        mime_type -> Varchar,
+        content_path -> Varchar,
+        target_kind -> Varchar,
+        target_key -> Varchar,
+        target_field -> Varchar,
+        attempts -> Int4,
+        last_error -> Varchar,
+        created_at -> Timestamptz,
+        completed_at -> Nullable<Timestamptz>,
+        drive_file_id -> Varchar,
+        zoom_meeting_id -> Varchar,
+        zoom_recording_id -> Varchar,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    event_registrants (id) {
+        id -> Int4,
+        provider -> Varchar,
+        provider_registration_id -> Varchar,
+        provider_event_id -> Varchar,
+        event_name -> Varchar,
+        email -> Varchar,
+        first_name -> Varchar,
+        last_name -> Varchar,
+        status -> Varchar,
+        checked_in -> Bool,
+        marketing_consent -> Bool,
+        consented_at -> Nullable<Timestamptz>,
+        registered_at -> Timestamptz,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     expensed_items (id) {
         id -> Int4,
@@ -463,6 +750,21 @@ table! {
     }
 }
 
+table! {
+    focus_time_conflicts (id) {
+        id -> Int4,
+        user_email -> Varchar,
+        google_event_id -> Varchar,
+        event_summary -> Varchar,
+        event_link -> Varchar,
+        start_time -> Timestamptz,
+        end_time -> Timestamptz,
+        action -> Varchar,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     functions (id) {
         id -> Int4,
@@ -552,6 +854,18 @@ table! {
     }
 }
 
+table! {
+    greenhouse_applications (id) {
+        id -> Int4,
+        greenhouse_application_id -> Int8,
+        greenhouse_candidate_id -> Int8,
+        applicant_id -> Int4,
+        synced_status -> Varchar,
+        synced_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     groups (id) {
         id -> Int4,
@@ -572,11 +886,22 @@ table! {
         who_can_view_group -> Varchar,
         who_can_view_membership -> Varchar,
         enable_collaborative_inbox -> Bool,
+        okta_apps -> Array<Text>,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
 }
 
+table! {
+    gsuite_aliases (id) {
+        id -> Int4,
+        alias -> Varchar,
+        email -> Varchar,
+        is_group -> Bool,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     inbound_shipments (id) {
         id -> Int4,
@@ -590,6 +915,9 @@ table! {
         eta -> Nullable<Timestamptz>,
         messages -> Varchar,
         order_number -> Varchar,
+        requested_by -> Varchar,
+        received_time -> Nullable<Timestamptz>,
+        received_by -> Varchar,
         name -> Varchar,
         notes -> Varchar,
         cio_company_id -> Int4,
@@ -597,6 +925,51 @@ table! {
     }
 }
 
+table! {
+    incidents (id) {
+        id -> Int4,
+        name -> Varchar,
+        summary -> Text,
+        channel_id -> Varchar,
+        thread_ts -> Varchar,
+        link -> Varchar,
+        message_count -> Int4,
+        participants -> Array<Text>,
+        summarized_by -> Varchar,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    interview_scorecards (id) {
+        id -> Int4,
+        interview_id -> Int4,
+        applicant_id -> Int4,
+        interviewer -> Varchar,
+        technical_rating -> Int4,
+        communication_rating -> Int4,
+        values_rating -> Int4,
+        overall_rating -> Int4,
+        notes -> Text,
+        requested_at -> Timestamptz,
+        submitted_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    inventory_reconciliations (id) {
+        id -> Int4,
+        generated_at -> Timestamptz,
+        item_count -> Int4,
+        flagged -> Int4,
+        csv_link -> Varchar,
+        pdf_link -> Varchar,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     journal_club_meetings (id) {
         id -> Int4,
@@ -625,6 +998,17 @@ table! {
     }
 }
 
+table! {
+    lever_opportunities (id) {
+        id -> Int4,
+        lever_opportunity_id -> Varchar,
+        applicant_id -> Int4,
+        synced_stage -> Varchar,
+        synced_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     links (id) {
         id -> Int4,
@@ -638,6 +1022,19 @@ table! {
     }
 }
 
+table! {
+    locations (id) {
+        id -> Int4,
+        name -> Varchar,
+        kind -> Varchar,
+        address -> Varchar,
+        timezone -> Varchar,
+        building -> Varchar,
+        aliases -> Array<Text>,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     mailing_list_subscribers (id) {
         id -> Int4,
@@ -671,6 +1068,37 @@ table! {
     }
 }
 
+table! {
+    meeting_action_items (id) {
+        id -> Int4,
+        name -> Varchar,
+        recorded_meeting_id -> Int4,
+        meeting_name -> Varchar,
+        description -> Text,
+        assignee -> Varchar,
+        status -> Varchar,
+        last_reminded_at -> Nullable<Timestamptz>,
+        reminder_count -> Int4,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    metrics_snapshots (id) {
+        id -> Int4,
+        week -> Date,
+        headcount -> Int4,
+        open_roles -> Int4,
+        applicants_in_pipeline -> Int4,
+        assets_deployed -> Int4,
+        rfds_published -> Int4,
+        mailing_list_subscribers -> Int4,
+        created_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     outbound_shipments (id) {
         id -> Int4,
@@ -700,6 +1128,8 @@ table! {
         shipped_time -> Nullable<Timestamptz>,
         delivered_time -> Nullable<Timestamptz>,
         eta -> Nullable<Timestamptz>,
+        service_level -> Varchar,
+        overdue -> Bool,
         provider -> Varchar,
         provider_id -> Varchar,
         messages -> Varchar,
@@ -720,6 +1150,7 @@ table! {
         carrier -> Varchar,
         status -> Varchar,
         location -> Varchar,
+        location_id -> Nullable<Int4>,
         transactions -> Array<Text>,
         link_to_outbound_shipments -> Array<Text>,
         requested_start_time -> Timestamptz,
@@ -747,6 +1178,21 @@ table! {
     }
 }
 
+table! {
+    printers (id) {
+        id -> Int4,
+        name -> Varchar,
+        location -> Varchar,
+        kind -> Varchar,
+        url -> Varchar,
+        is_default -> Bool,
+        cio_company_id -> Int4,
+        status_alert -> Varchar,
+        status_checked_at -> Nullable<Timestamptz>,
+        location_id -> Nullable<Int4>,
+    }
+}
+
 table! {
     rack_line_subscribers (id) {
         id -> Int4,
@@ -768,6 +1214,79 @@ table! {
     }
 }
 
+table! {
+    rate_limit_windows (provider, window_start) {
+        provider -> Varchar,
+        window_start -> Int8,
+        requests -> Int4,
+    }
+}
+
+table! {
+    reconcile_plans (id) {
+        id -> Int4,
+        reconciler -> Varchar,
+        changes -> Varchar,
+        status -> Varchar,
+        error -> Varchar,
+        created_at -> Timestamptz,
+        applied_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    recorded_meeting_attendees (id) {
+        id -> Int4,
+        recorded_meeting_id -> Int4,
+        user_id -> Int4,
+        email -> Varchar,
+    }
+}
+
+table! {
+    recorded_meeting_participants (id) {
+        id -> Int4,
+        recorded_meeting_id -> Int4,
+        name -> Varchar,
+        email -> Varchar,
+        join_time -> Nullable<Timestamptz>,
+        leave_time -> Nullable<Timestamptz>,
+        duration -> Int4,
+    }
+}
+
+table! {
+    recorded_meeting_purges (id) {
+        id -> Int4,
+        recorded_meeting_id -> Int4,
+        name -> Varchar,
+        google_event_id -> Varchar,
+        start_time -> Timestamptz,
+        video -> Varchar,
+        chat_log_link -> Varchar,
+        transcript_id -> Varchar,
+        transcript_segments -> Int4,
+        deleted_files -> Array<Text>,
+        retention_days -> Int4,
+        reason -> Varchar,
+        purged_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    recorded_meeting_transcript_segments (id) {
+        id -> Int4,
+        recorded_meeting_id -> Int4,
+        position -> Int4,
+        speaker -> Varchar,
+        start_offset -> Float8,
+        end_offset -> Float8,
+        text -> Text,
+    }
+}
+
 table! {
     recorded_meetings (id) {
         id -> Int4,
@@ -782,14 +1301,105 @@ table! {
         attendees -> Array<Text>,
         transcript -> Text,
         transcript_id -> Varchar,
+        transcript_callback_secret -> Varchar,
         google_event_id -> Varchar,
         event_link -> Varchar,
         location -> Varchar,
+        location_id -> Nullable<Int4>,
+        host -> Varchar,
+        tags -> Array<Text>,
+        purged_at -> Nullable<Timestamptz>,
+        summary -> Text,
+        action_items -> Array<Text>,
+        summary_attempts -> Int4,
+        link_to_people -> Array<Text>,
+        visibility -> Varchar,
+        participants -> Array<Text>,
+        slack_channel -> Varchar,
+        transcript_doc -> Varchar,
+        chat_log_file -> Varchar,
+        transcript_file -> Varchar,
+        thumbnail -> Varchar,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    rfd_branch_syncs (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        branch -> Varchar,
+        commit_sha -> Varchar,
+        synced_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    rfd_discussion_comments (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        kind -> Varchar,
+        github_id -> Int8,
+        thread_id -> Int8,
+        author -> Varchar,
+        body -> Text,
+        path -> Varchar,
+        html_url -> Varchar,
+        outdated -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        link_to_rfd -> Array<Text>,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
 }
 
+table! {
+    rfd_impacts (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        owner -> Varchar,
+        repo -> Varchar,
+        issue_number -> Int4,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    rfd_links (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        referenced_rfd_number -> Int4,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    rfd_number_reservations (id) {
+        id -> Int4,
+        number -> Int4,
+        title -> Varchar,
+        reserved_by -> Varchar,
+        reserved_at -> Timestamptz,
+        expires_at -> Timestamptz,
+        used_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    rfd_state_transitions (id) {
+        id -> Int4,
+        rfd_number -> Int4,
+        from_state -> Varchar,
+        to_state -> Varchar,
+        transitioned_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     rfds (id) {
         id -> Int4,
@@ -811,11 +1421,85 @@ table! {
         relevant_components -> Array<Text>,
         pdf_link_github -> Varchar,
         pdf_link_google_drive -> Varchar,
+        unresolved_discussion_threads -> Int4,
+        reviewers -> Array<Text>,
+        approvals -> Array<Text>,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    role_address_holders (id) {
+        id -> Int4,
+        address -> Varchar,
+        username -> Varchar,
+        started_at -> Timestamptz,
+        ended_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    roles (id) {
+        id -> Int4,
+        name -> Varchar,
+        title -> Varchar,
+        ladder -> Varchar,
+        level -> Int4,
+        comp_band -> Varchar,
+        checkr_package -> Varchar,
+        locale -> Varchar,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
 }
 
+table! {
+    saga_steps (id) {
+        id -> Int4,
+        saga_id -> Varchar,
+        node_id -> Int4,
+        name -> Varchar,
+        label -> Varchar,
+        status -> Varchar,
+        started_at -> Nullable<Timestamptz>,
+        finished_at -> Nullable<Timestamptz>,
+        error -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    shopify_orders (id) {
+        id -> Int4,
+        shopify_order_id -> Int8,
+        name -> Varchar,
+        email -> Varchar,
+        outbound_shipment_id -> Nullable<Int4>,
+        unmatched_items -> Array<Text>,
+        synced_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
+table! {
+    sign_in_events (id) {
+        id -> Int4,
+        provider -> Varchar,
+        event_id -> Varchar,
+        email -> Varchar,
+        ip_address -> Varchar,
+        country -> Varchar,
+        city -> Varchar,
+        latitude -> Nullable<Float8>,
+        longitude -> Nullable<Float8>,
+        signed_in_at -> Timestamptz,
+        anomaly -> Varchar,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     software_vendors (id) {
         id -> Int4,
@@ -842,6 +1526,17 @@ table! {
     }
 }
 
+table! {
+    swag_bundles (id) {
+        id -> Int4,
+        name -> Varchar,
+        description -> Varchar,
+        contents -> Varchar,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     swag_inventory_items (id) {
         id -> Int4,
@@ -854,6 +1549,30 @@ table! {
         barcode_svg -> Varchar,
         barcode_pdf_label -> Varchar,
         print_barcode_label_quantity -> Int4,
+        reorder_threshold -> Int4,
+        printful_variant_id -> Varchar,
+        counted_stock -> Int4,
+        counted_at -> Nullable<Timestamptz>,
+        link_to_item -> Array<Text>,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    swag_item_variants (id) {
+        id -> Int4,
+        item -> Varchar,
+        size -> Varchar,
+        color -> Varchar,
+        sku -> Varchar,
+        barcode -> Varchar,
+        barcode_png -> Varchar,
+        barcode_svg -> Varchar,
+        barcode_pdf_label -> Varchar,
+        price -> Float4,
+        current_stock -> Int4,
+        print_barcode_label_quantity -> Int4,
         link_to_item -> Array<Text>,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
@@ -877,6 +1596,60 @@ table! {
     }
 }
 
+table! {
+    swag_reorders (id) {
+        id -> Int4,
+        name -> Varchar,
+        swag_inventory_item_id -> Int4,
+        item -> Varchar,
+        size -> Varchar,
+        current_stock -> Int4,
+        reorder_threshold -> Int4,
+        status -> Varchar,
+        requested_at -> Timestamptz,
+        link_to_inventory -> Array<Text>,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    swag_requests (id) {
+        id -> Int4,
+        name -> Varchar,
+        email -> Varchar,
+        item -> Varchar,
+        size -> Varchar,
+        quantity -> Int4,
+        swag_inventory_item_id -> Int4,
+        phone -> Varchar,
+        street_1 -> Varchar,
+        street_2 -> Varchar,
+        city -> Varchar,
+        state -> Varchar,
+        zipcode -> Varchar,
+        country -> Varchar,
+        reason -> Varchar,
+        status -> Varchar,
+        requested_at -> Timestamptz,
+        outbound_shipment_id -> Nullable<Int4>,
+        link_to_inventory -> Array<Text>,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
+table! {
+    swag_shipment_items (id) {
+        id -> Int4,
+        outbound_shipment_id -> Int4,
+        swag_inventory_item_id -> Int4,
+        quantity -> Int4,
+        subtracted_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+    }
+}
+
 table! {
     users (id) {
         id -> Int4,
@@ -893,10 +1666,12 @@ table! {
         department -> Varchar,
         manager -> Varchar,
         link_to_manager -> Array<Text>,
+        onboarding_buddy -> Varchar,
         groups -> Array<Text>,
         is_group_admin -> Bool,
         building -> Varchar,
         link_to_building -> Array<Text>,
+        location_id -> Nullable<Int4>,
         aws_role -> Varchar,
         denied_services -> Array<Text>,
         home_address_street_1 -> Varchar,
@@ -922,6 +1697,7 @@ table! {
         public_ssh_keys -> Array<Text>,
         typev -> Varchar,
         google_anniversary_event_id -> Varchar,
+        first_week_event_ids -> Array<Text>,
         email -> Varchar,
         gusto_id -> Varchar,
         okta_id -> Varchar,
@@ -932,45 +1708,143 @@ table! {
         geocode_cache -> Varchar,
         working_on -> Array<Text>,
         gusto_pull_permission -> Bool,
+        is_contractor -> Bool,
+        end_date -> Nullable<Date>,
+        requested_end_date -> Nullable<Date>,
+        extended_end_date -> Nullable<Date>,
+        expiration_warning_sent_for -> Nullable<Date>,
+        access_suspended_for -> Nullable<Date>,
+        focus_blocks -> Array<Text>,
+        focus_time_zone -> Varchar,
+        decline_meetings_over_focus_time -> Bool,
+        previous_employers -> Array<Text>,
+        muted_notifications -> Array<Text>,
+        phone -> Varchar,
+        pronouns -> Varchar,
+        emergency_contact_name -> Varchar,
+        emergency_contact_phone -> Varchar,
+        emergency_contact_relationship -> Varchar,
+        skills -> Array<Text>,
+        interests -> Array<Text>,
+        previous_domains -> Array<Text>,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
     }
 }
 
+table! {
+    zoom_storage_snapshots (id) {
+        id -> Int4,
+        used_bytes -> Int8,
+        recordings -> Int4,
+        limit_bytes -> Int8,
+        taken_at -> Timestamptz,
+        cio_company_id -> Int4,
+    }
+}
+
 joinable!(accounts_payables -> companys (cio_company_id));
 joinable!(api_tokens -> companys (auth_company_id));
 joinable!(applicant_interviews -> companys (cio_company_id));
 joinable!(applicant_reviewers -> companys (cio_company_id));
 joinable!(applicant_reviews -> companys (cio_company_id));
+joinable!(applicant_withdrawals -> applicants (applicant_id));
+joinable!(applicant_withdrawals -> companys (cio_company_id));
 joinable!(applicants -> companys (cio_company_id));
+joinable!(asset_audit_scans -> asset_audits (asset_audit_id));
+joinable!(asset_audit_scans -> companys (cio_company_id));
+joinable!(asset_audits -> companys (cio_company_id));
+joinable!(asset_audits -> locations (location_id));
+joinable!(asset_checkouts -> companys (cio_company_id));
+joinable!(asset_depreciations -> companys (cio_company_id));
 joinable!(asset_items -> companys (cio_company_id));
+joinable!(asset_refresh_forecasts -> companys (cio_company_id));
+joinable!(asset_refresh_forecasts -> asset_items (asset_item_id));
+joinable!(asset_reservations -> companys (cio_company_id));
+joinable!(asset_tasks -> companys (cio_company_id));
 joinable!(auth_user_logins -> companys (cio_company_id));
 joinable!(auth_users -> companys (cio_company_id));
 joinable!(barcode_scans -> companys (cio_company_id));
 joinable!(bookings -> companys (cio_company_id));
 joinable!(buildings -> companys (cio_company_id));
 joinable!(certificates -> companys (cio_company_id));
+joinable!(community_events -> companys (cio_company_id));
+joinable!(conference_leads -> companys (cio_company_id));
+joinable!(conference_tasks -> companys (cio_company_id));
+joinable!(conference_tasks -> conferences (conference_id));
+joinable!(conference_tasks -> outbound_shipments (outbound_shipment_id));
+joinable!(conferences -> companys (cio_company_id));
 joinable!(credit_card_transactions -> companys (cio_company_id));
+joinable!(drive_uploads -> companys (cio_company_id));
+joinable!(event_registrants -> companys (cio_company_id));
 joinable!(expensed_items -> companys (cio_company_id));
+joinable!(focus_time_conflicts -> companys (cio_company_id));
 joinable!(functions -> companys (cio_company_id));
 joinable!(github_repos -> companys (cio_company_id));
+joinable!(greenhouse_applications -> applicants (applicant_id));
+joinable!(greenhouse_applications -> companys (cio_company_id));
 joinable!(groups -> companys (cio_company_id));
+joinable!(gsuite_aliases -> companys (cio_company_id));
 joinable!(inbound_shipments -> companys (cio_company_id));
+joinable!(incidents -> companys (cio_company_id));
+joinable!(interview_scorecards -> applicant_interviews (interview_id));
+joinable!(interview_scorecards -> applicants (applicant_id));
+joinable!(interview_scorecards -> companys (cio_company_id));
+joinable!(inventory_reconciliations -> companys (cio_company_id));
 joinable!(journal_club_meetings -> companys (cio_company_id));
 joinable!(journal_club_papers -> companys (cio_company_id));
+joinable!(lever_opportunities -> applicants (applicant_id));
+joinable!(lever_opportunities -> companys (cio_company_id));
 joinable!(links -> companys (cio_company_id));
+joinable!(locations -> companys (cio_company_id));
 joinable!(mailing_list_subscribers -> companys (cio_company_id));
+joinable!(meeting_action_items -> companys (cio_company_id));
+joinable!(meeting_action_items -> recorded_meetings (recorded_meeting_id));
+joinable!(metrics_snapshots -> companys (cio_company_id));
 joinable!(outbound_shipments -> companys (cio_company_id));
 joinable!(package_pickups -> companys (cio_company_id));
+joinable!(package_pickups -> locations (location_id));
 joinable!(page_views -> companys (cio_company_id));
+joinable!(printers -> companys (cio_company_id));
+joinable!(printers -> locations (location_id));
 joinable!(rack_line_subscribers -> companys (cio_company_id));
+joinable!(reconcile_plans -> companys (cio_company_id));
+joinable!(recorded_meeting_attendees -> recorded_meetings (recorded_meeting_id));
+joinable!(recorded_meeting_attendees -> users (user_id));
+joinable!(recorded_meeting_participants -> recorded_meetings (recorded_meeting_id));
+joinable!(recorded_meeting_purges -> companys (cio_company_id));
+joinable!(recorded_meeting_transcript_segments -> recorded_meetings (recorded_meeting_id));
 joinable!(recorded_meetings -> companys (cio_company_id));
+joinable!(recorded_meetings -> locations (location_id));
 joinable!(resources -> companys (cio_company_id));
+joinable!(rfd_branch_syncs -> companys (cio_company_id));
+joinable!(rfd_discussion_comments -> companys (cio_company_id));
+joinable!(rfd_impacts -> companys (cio_company_id));
+joinable!(rfd_links -> companys (cio_company_id));
+joinable!(rfd_number_reservations -> companys (cio_company_id));
+joinable!(rfd_state_transitions -> companys (cio_company_id));
 joinable!(rfds -> companys (cio_company_id));
+joinable!(role_address_holders -> companys (cio_company_id));
+joinable!(roles -> companys (cio_company_id));
+joinable!(shopify_orders -> companys (cio_company_id));
+joinable!(shopify_orders -> outbound_shipments (outbound_shipment_id));
+joinable!(sign_in_events -> companys (cio_company_id));
 joinable!(software_vendors -> companys (cio_company_id));
+joinable!(swag_bundles -> companys (cio_company_id));
 joinable!(swag_inventory_items -> companys (cio_company_id));
+joinable!(swag_item_variants -> companys (cio_company_id));
 joinable!(swag_items -> companys (cio_company_id));
+joinable!(swag_reorders -> companys (cio_company_id));
+joinable!(swag_reorders -> swag_inventory_items (swag_inventory_item_id));
+joinable!(swag_requests -> companys (cio_company_id));
+joinable!(swag_requests -> outbound_shipments (outbound_shipment_id));
+joinable!(swag_requests -> swag_inventory_items (swag_inventory_item_id));
+joinable!(swag_shipment_items -> companys (cio_company_id));
+joinable!(swag_shipment_items -> outbound_shipments (outbound_shipment_id));
+joinable!(swag_shipment_items -> swag_inventory_items (swag_inventory_item_id));
 joinable!(users -> companys (cio_company_id));
+joinable!(users -> locations (location_id));
+joinable!(zoom_storage_snapshots -> companys (cio_company_id));
 
 allow_tables_to_appear_in_same_query!(
     accounts_payables,
@@ -978,34 +1852,82 @@ allow_tables_to_appear_in_same_query!(
     applicant_interviews,
     applicant_reviewers,
     applicant_reviews,
+    applicant_withdrawals,
     applicants,
+    asset_audit_scans,
+    asset_audits,
+    asset_checkouts,
+    asset_depreciations,
     asset_items,
+    asset_refresh_forecasts,
+    asset_reservations,
+    asset_tasks,
     auth_user_logins,
     auth_users,
     barcode_scans,
     bookings,
     buildings,
     certificates,
+    community_events,
     companys,
+    conference_leads,
+    conference_tasks,
+    conferences,
     credit_card_transactions,
+    drive_uploads,
+    event_registrants,
     expensed_items,
+    focus_time_conflicts,
     functions,
     github_repos,
+    greenhouse_applications,
     groups,
+    gsuite_aliases,
     inbound_shipments,
+    incidents,
+    interview_scorecards,
+    inventory_reconciliations,
     journal_club_meetings,
     journal_club_papers,
+    lever_opportunities,
     links,
+    locations,
     mailing_list_subscribers,
+    meeting_action_items,
+    metrics_snapshots,
     outbound_shipments,
     package_pickups,
     page_views,
+    printers,
     rack_line_subscribers,
+    rate_limit_windows,
+    reconcile_plans,
+    recorded_meeting_attendees,
+    recorded_meeting_participants,
+    recorded_meeting_purges,
+    recorded_meeting_transcript_segments,
     recorded_meetings,
     resources,
+    rfd_branch_syncs,
+    rfd_discussion_comments,
+    rfd_impacts,
+    rfd_links,
+    rfd_number_reservations,
+    rfd_state_transitions,
     rfds,
+    role_address_holders,
+    roles,
+    saga_steps,
+    shopify_orders,
+    sign_in_events,
     software_vendors,
+    swag_bundles,
     swag_inventory_items,
+    swag_item_variants,
     swag_items,
+    swag_reorders,
+    swag_requests,
+    swag_shipment_items,
     users,
+    zoom_storage_snapshots,
 );
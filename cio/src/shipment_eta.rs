@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+
+use crate::{business_calendar::BusinessCalendar, shipment_status::Status, shipments::OutboundShipment};
+
+/// How many business days a carrier takes to deliver with a service level, like `usps_priority`
+/// or `fedex_2_day`. Carriers name their service levels differently, but they all say how fast
+/// they go in the name, which is all we need for an estimate.
+pub fn transit_business_days(service_level: &str) -> u32 {
+    let level = service_level.to_lowercase().replace('-', "_");
+    let has = |words: &[&str]| words.iter().any(|w| level.contains(w));
+
+    if has(&["international", "worldwide", "global"]) {
+        return if has(&["express", "priority"]) { 5 } else { 10 };
+    }
+
+    if has(&["overnight", "next_day", "nextday", "1_day", "one_day"]) {
+        1
+    } else if has(&["2_day", "2day", "second_day", "two_day"]) {
+        2
+    } else if has(&["3_day", "3day", "three_day", "express_saver"]) {
+        3
+    } else if has(&["express"]) {
+        2
+    } else if has(&["priority"]) {
+        3
+    } else {
+        // Ground, first class, and whatever we do not know.
+        5
+    }
+}
+
+/// Estimate when a package sent at a point in time arrives. Carriers take it on the next
+/// business day, and deliver by the end of the last business day of transit.
+pub fn estimate_delivery(calendar: &BusinessCalendar, sent: DateTime<Utc>, days: u32) -> Option<DateTime<Utc>> {
+    let first_transit_day = calendar.next_business_day(calendar.local_date(sent));
+    let date = calendar.nth_business_day(first_transit_day, days);
+
+    calendar.at(date, calendar.end)
+}
+
+impl OutboundShipment {
+    /// Return if the package already made it where it is going. Packages picked up locally are
+    /// never late.
+    pub fn is_done(&self) -> bool {
+        self.local_pickup
+            || self.delivered_time.is_some()
+            || self.status == Status::Delivered.to_string()
+            || self.status == Status::Returned.to_string()
+            || self.status == Status::Cancelled.to_string()
+            || self.status == Status::PickedUp.to_string()
+    }
+
+    /// Set our estimate of when the shipment arrives, if the carrier did not give us one. We
+    /// only estimate once the shipment has a label, from when it shipped or was picked up.
+    pub fn estimate_eta(&mut self, calendar: &BusinessCalendar) {
+        if self.eta.is_some() || self.tracking_number.is_empty() || self.is_done() {
+            return;
+        }
+
+        let sent = self
+            .shipped_time
+            .or_else(|| self.pickup_date.and_then(|d| calendar.at(d, calendar.start)))
+            .unwrap_or(self.created_time);
+
+        self.eta = estimate_delivery(calendar, sent, transit_business_days(&self.service_level));
+    }
+
+    /// Return if the shipment blew past its ETA without being delivered.
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        match self.eta {
+            Some(eta) => eta < now && !self.is_done(),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{estimate_delivery, transit_business_days};
+    use crate::business_calendar::BusinessCalendar;
+
+    #[test]
+    fn test_transit_business_days() {
+        assert_eq!(1, transit_business_days("fedex_standard_overnight"));
+        assert_eq!(2, transit_business_days("ups_second_day_air"));
+        assert_eq!(2, transit_business_days("fedex_2_day"));
+        assert_eq!(3, transit_business_days("fedex_express_saver"));
+        assert_eq!(2, transit_business_days("usps_priority_express"));
+        assert_eq!(3, transit_business_days("usps_priority"));
+        assert_eq!(5, transit_business_days("ups_ground"));
+        assert_eq!(5, transit_business_days(""));
+        assert_eq!(5, transit_business_days("dhl_express_worldwide"));
+        assert_eq!(
+            10,
+            transit_business_days("usps_first_class_package_international_service")
+        );
+    }
+
+    #[test]
+    fn test_estimate_delivery() {
+        let calendar = BusinessCalendar::default();
+
+        // Sent on a Thursday, picked up on Friday, and three days of transit over the weekend.
+        let sent = Utc.ymd(2023, 9, 28).and_hms(18, 0, 0);
+        assert_eq!(
+            Some(Utc.ymd(2023, 10, 4).and_hms(0, 0, 0)),
+            estimate_delivery(&calendar, sent, 3)
+        );
+    }
+}
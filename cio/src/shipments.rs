@@ -1,10 +1,10 @@
 #![allow(clippy::from_over_into)]
 use std::convert::From;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
-use chrono::{naive::NaiveDate, offset::Utc, DateTime, Duration, NaiveTime};
+use chrono::{naive::NaiveDate, offset::Utc, DateTime};
 use chrono_humanize::HumanTime;
 use google_geocode::Geocode;
 use log::{info, warn};
@@ -20,11 +20,12 @@ use slack_chat_api::{
 
 use crate::{
     airtable::{AIRTABLE_INBOUND_TABLE, AIRTABLE_OUTBOUND_TABLE, AIRTABLE_PACKAGE_PICKUPS_TABLE},
+    business_calendar::BusinessCalendar,
     companies::Company,
     configs::User,
     core::UpdateAirtableRecord,
     db::Database,
-    printer::Printer,
+    printer::{find_print_url, PrintTarget, Printer, PRINTER_KIND_RECEIPT, PRINTER_KIND_ROLLO},
     schema::{inbound_shipments, outbound_shipments, package_pickups},
 };
 
@@ -61,6 +62,17 @@ pub struct NewInboundShipment {
     pub messages: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub order_number: String,
+    /// The email of the person who is expecting the package, we let them know when it is
+    /// received.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub requested_by: String,
+    /// When the package was scanned in at the office, as opposed to when the carrier says it
+    /// was delivered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_time: Option<DateTime<Utc>>,
+    /// The email of the person who scanned the package in.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub received_by: String,
 
     /// These fields are filled in by the Airtable and should not be edited by the
     /// API updating.
@@ -101,6 +113,15 @@ impl UpdateAirtableRecord<InboundShipment> for InboundShipment {
         if self.notes.is_empty() {
             self.notes = record.notes;
         }
+        if self.requested_by.is_empty() {
+            self.requested_by = record.requested_by;
+        }
+        if self.received_time.is_none() {
+            self.received_time = record.received_time;
+        }
+        if self.received_by.is_empty() {
+            self.received_by = record.received_by;
+        }
 
         Ok(())
     }
@@ -426,6 +447,14 @@ pub struct NewOutboundShipment {
     pub delivered_time: Option<DateTime<Utc>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub eta: Option<DateTime<Utc>>,
+    /// The carrier's service level, like `usps_priority`. We estimate the ETA from it when the
+    /// carrier does not give us one.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub service_level: String,
+    /// The shipment is past its ETA and was not delivered, someone should follow up with the
+    /// carrier.
+    #[serde(default)]
+    pub overdue: bool,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub provider: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -479,6 +508,8 @@ impl From<User> for NewOutboundShipment {
             cost: Default::default(),
             label_link: Default::default(),
             eta: None,
+            service_level: Default::default(),
+            overdue: false,
             messages: Default::default(),
             notes: Default::default(),
             geocode_cache: Default::default(),
@@ -555,6 +586,8 @@ impl From<shipbob::types::Order> for NewOutboundShipment {
             status: status.to_string(),
             cost,
             eta: None,
+            service_level: Default::default(),
+            overdue: false,
 
             // These will be poulated when we expand the record.
             address_formatted: Default::default(),
@@ -725,6 +758,9 @@ pub struct NewPackagePickup {
     pub status: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub location: String,
+    /// The location of the pickup, matched from `location` by the config sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location_id: Option<i32>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub transactions: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -756,7 +792,7 @@ impl OutboundShipments {
     // Always schedule the pickup for the next business day.
     // It will create a pickup for all the shipments that have "Label printed"
     // status and no pickup date currently.
-    pub async fn create_pickup(db: &Database, company: &Company) -> Result<()> {
+    pub async fn create_pickup(db: &Database, company: &Company, calendar: &BusinessCalendar) -> Result<()> {
         // We should only do this for USPS, OR if we use DHL in the future.
         let shipments = outbound_shipments::dsl::outbound_shipments
             .filter(
@@ -809,7 +845,7 @@ impl OutboundShipments {
         }
 
         // Get the next buisness day for pickup.
-        let (start_time, end_time) = get_next_business_day();
+        let (start_time, end_time) = get_next_business_day(calendar)?;
 
         let pickup_date = start_time.date().naive_utc();
 
@@ -845,6 +881,7 @@ impl OutboundShipments {
             carrier: "USPS".to_string(),
             status: pickup.status.to_string(),
             location: "HQ".to_string(),
+            location_id: None,
             transactions: transaction_ids,
             link_to_outbound_shipments,
             requested_start_time: start_time,
@@ -873,25 +910,13 @@ impl OutboundShipments {
 }
 
 /// Returns the next buisness day in terms of start and end.
-pub fn get_next_business_day() -> (DateTime<Utc>, DateTime<Utc>) {
-    let now = Utc::now();
-    let pacific_time = now.with_timezone(&chrono_tz::US::Pacific);
-
-    let mut next_day = pacific_time.checked_add_signed(Duration::days(1)).unwrap();
-    let day_of_week_string = next_day.format("%A").to_string();
-    if day_of_week_string == "Saturday" {
-        next_day = pacific_time.checked_add_signed(Duration::days(3)).unwrap();
-    } else if day_of_week_string == "Sunday" {
-        next_day = pacific_time.checked_add_signed(Duration::days(2)).unwrap();
-    }
+/// Return the business hours of the next business day, for the carrier to come in.
+pub fn get_next_business_day(calendar: &BusinessCalendar) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let next_day = calendar.next_business_day(calendar.local_date(Utc::now()));
 
-    // Let's create the start time, which should be around 9am.
-    let start_time = next_day.date().and_time(NaiveTime::from_hms(8, 59, 59)).unwrap();
-
-    // Let's create the end time, which should be around 5pm.
-    let end_time = next_day.date().and_time(NaiveTime::from_hms(16, 59, 59)).unwrap();
-
-    (start_time.with_timezone(&Utc), end_time.with_timezone(&Utc))
+    calendar
+        .business_hours(next_day)
+        .ok_or_else(|| anyhow!("no business hours on {}", next_day))
 }
 
 /// Implement updating the Airtable record for an OutboundShipment.
@@ -935,6 +960,9 @@ impl UpdateAirtableRecord<OutboundShipment> for OutboundShipment {
         if self.eta.is_none() {
             self.eta = record.eta;
         }
+        if self.service_level.is_empty() {
+            self.service_level = record.service_level;
+        }
         if self.cost == 0.0 {
             self.cost = record.cost;
         }
@@ -966,8 +994,8 @@ impl OutboundShipment {
         format!("https://track.oxide.computer/{}/{}", self.carrier, self.tracking_number)
     }
 
-    /// Send the receipt to our printer.
-    pub async fn print_receipt(&self, db: &Database) -> Result<()> {
+    /// Send the receipt to the printer for the target.
+    pub async fn print_receipt(&self, db: &Database, target: &PrintTarget) -> Result<()> {
         if self.contents.trim().is_empty() {
             // Return early.
             return Ok(());
@@ -975,12 +1003,11 @@ impl OutboundShipment {
 
         let company = self.company(db).await?;
 
-        if company.printer_url.is_empty() {
+        let printer_url = match find_print_url(db, &company, PRINTER_KIND_RECEIPT, target).await? {
+            Some(printer_url) => printer_url,
             // Return early.
-            return Ok(());
-        }
-
-        let printer_url = format!("{}/receipt", company.printer_url);
+            None => return Ok(()),
+        };
         let client = reqwest::Client::new();
         let resp = client
             .post(&printer_url)
@@ -1007,8 +1034,8 @@ impl OutboundShipment {
         Ok(())
     }
 
-    /// Send the label to our printer.
-    pub async fn print_label(&self, db: &Database) -> Result<()> {
+    /// Send the label to the printer for the target.
+    pub async fn print_label(&self, db: &Database, target: &PrintTarget) -> Result<()> {
         if self.label_link.trim().is_empty() {
             warn!("[print]: Failed to print label due to missing label link");
 
@@ -1018,14 +1045,15 @@ impl OutboundShipment {
 
         let company = self.company(db).await?;
 
-        if company.printer_url.is_empty() {
-            warn!("[print]: Failed to print label due to missing printer url");
-
-            // Return early.
-            return Ok(());
-        }
+        let printer_url = match find_print_url(db, &company, PRINTER_KIND_ROLLO, target).await? {
+            Some(printer_url) => printer_url,
+            None => {
+                warn!("[print]: Failed to print label due to missing printer url");
 
-        let printer_url = format!("{}/rollo", company.printer_url);
+                // Return early.
+                return Ok(());
+            }
+        };
         let printer_key = Printer::key();
 
         info!(
@@ -1149,7 +1177,7 @@ xoxo,
 
 **Tracking link:**
 {}
-
+{}
 If you have any questions or concerns, please respond to this email!
 Have a splendid day!
 
@@ -1158,7 +1186,10 @@ xoxo,
                     self.contents,
                     self.name,
                     self.format_address(),
-                    self.oxide_tracking_link
+                    self.oxide_tracking_link,
+                    self.eta
+                        .map(|eta| format!("\n**Estimated delivery:**\n{}\n", eta.format("%A, %B %-d")))
+                        .unwrap_or_default(),
                 ),
                 &[self.email.to_string()],
                 &[format!("packages@{}", &company.gsuite_domain)],
@@ -1532,6 +1563,7 @@ The Shipping Bot",
 
                 // Set the additional fields.
                 self.carrier = clean_carrier_name(&rate.provider);
+                self.service_level = rate.servicelevel.token.to_string();
                 self.cost = rate.amount_local.parse()?;
                 self.tracking_number = label.tracking_number.to_string();
                 self.tracking_link = label.tracking_url_provider.to_string();
@@ -1560,9 +1592,9 @@ The Shipping Bot",
                     .await?;
 
                 // Print the label.
-                self.print_label(db).await?;
+                self.print_label(db, &Default::default()).await?;
                 // Print the receipt.
-                self.print_receipt(db).await?;
+                self.print_receipt(db, &Default::default()).await?;
                 self.set_status(crate::shipment_status::Status::LabelPrinted).await?;
 
                 // Send an email to us that we need to package the shipment.
@@ -1578,8 +1610,9 @@ The Shipping Bot",
     }
 }
 
-// Sync the outbound shipments.
-pub async fn refresh_outbound_shipments(db: &Database, company: &Company) -> Result<()> {
+// Sync the outbound shipments. The calendar is the one carriers deliver on, for the ETAs we
+// estimate.
+pub async fn refresh_outbound_shipments(db: &Database, company: &Company, calendar: &BusinessCalendar) -> Result<()> {
     if company.airtable_base_id_shipments.is_empty() {
         // Return early.
         return Ok(());
@@ -1623,6 +1656,11 @@ pub async fn refresh_outbound_shipments(db: &Database, company: &Company) -> Res
         }
     }
 
+    // Get the tracking numbers of the shipments Printful fulfills.
+    if let Err(e) = crate::printful::refresh_printful_shipments(db, company).await {
+        warn!("refreshing printful shipments failed: {}", e);
+    }
+
     // Iterate over all the shipments in the database and update them.
     // This ensures that any one offs (that don't come from spreadsheets) are also updated.
     // TODO: if we decide to accept one-offs straight in airtable support that, but for now
@@ -1637,12 +1675,20 @@ pub async fn refresh_outbound_shipments(db: &Database, company: &Company) -> Res
         // Update the shipment from shippo, this will only apply if the provider is set as "Shippo".
         s.create_or_get_shippo_shipment(db).await?;
 
+        // Not every carrier tells us when the package will arrive.
+        s.estimate_eta(calendar);
+
         // Update airtable and the database again.
         s.update(db).await?;
+
+        // The swag in the shipment leaves the inventory once it has a label.
+        crate::swag_shipments::subtract_shipped_swag(db, company, &s).await?;
     }
 
     update_manual_shippo_shipments(db, company).await?;
 
+    flag_overdue_outbound_shipments(db, company).await?;
+
     OutboundShipments::get_from_db(db, company.id)
         .await?
         .update_airtable(db)
@@ -1650,6 +1696,53 @@ pub async fn refresh_outbound_shipments(db: &Database, company: &Company) -> Res
     Ok(())
 }
 
+/// Flag the shipments that blew past their ETA, and let the shipments channel know about the
+/// newly late ones so someone follows up with the carrier.
+async fn flag_overdue_outbound_shipments(db: &Database, company: &Company) -> Result<()> {
+    let now = Utc::now();
+    for mut s in OutboundShipments::get_from_db(db, company.id).await? {
+        let overdue = s.is_overdue(now);
+        if overdue == s.overdue {
+            continue;
+        }
+
+        s.overdue = overdue;
+        s.update_in_db(db).await?;
+
+        if overdue {
+            let msg = FormattedMessage {
+                channel: company.slack_channel_shipments.to_string(),
+                blocks: vec![MessageBlock {
+                    block_type: MessageBlockType::Section,
+                    text: Some(MessageBlockText {
+                        text_type: MessageType::Markdown,
+                        text: format!(
+                            "The {} shipment to *{}* was due {} and is still `{}`, someone should follow \
+                             up with the carrier: {}",
+                            s.carrier,
+                            s.name,
+                            s.eta
+                                .map(|eta| HumanTime::from(eta - now).to_string())
+                                .unwrap_or_default(),
+                            s.status,
+                            s.oxide_tracking_link
+                        ),
+                    }),
+                    elements: Default::default(),
+                    accessory: Default::default(),
+                    block_id: Default::default(),
+                    fields: Default::default(),
+                }],
+                attachments: Default::default(),
+            };
+
+            company.post_to_slack_channel(db, &msg).await?;
+        }
+    }
+
+    Ok(())
+}
+
 pub fn clean_carrier_name(s: &str) -> String {
     let l = s.to_lowercase();
     if l == "ups" || l.starts_with("ups") {
@@ -1708,6 +1801,8 @@ async fn update_manual_shippo_shipments(db: &Database, company: &Company) -> Res
             cost: Default::default(),
             label_link: Default::default(),
             eta: None,
+            service_level: Default::default(),
+            overdue: false,
             messages: Default::default(),
             notes: Default::default(),
             geocode_cache: Default::default(),
@@ -1725,6 +1820,7 @@ async fn update_manual_shippo_shipments(db: &Database, company: &Company) -> Res
         let rate = shippo.get_rate(&label.rate).await?;
         ns.cost = rate.amount_local.parse()?;
         ns.carrier = clean_carrier_name(&rate.provider);
+        ns.service_level = rate.servicelevel.token.to_string();
 
         // Only add the shipment if it doesn't already exist. Since we update it
         // in the loop above. Otherwise the email notifications get stuck and you get
@@ -0,0 +1,422 @@
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api_tokens::APIToken,
+    companies::Company,
+    db::Database,
+    schema::shopify_orders,
+    swag_inventory::{SwagInventoryItem, SwagInventoryItems},
+    swag_store::{Order, OrderItem},
+};
+
+/// The version of the Shopify Admin API we use.
+static SHOPIFY_API_VERSION: &str = "2023-07";
+/// How many orders we get from Shopify at once, it caps it at 250.
+static SHOPIFY_PAGE_SIZE: usize = 250;
+
+/// An order of the Shopify store we synced into the swag inventory and the outbound shipments.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct ShopifyOrder {
+    pub id: i32,
+    pub shopify_order_id: i64,
+    /// The name of the order in Shopify, like `#1001`.
+    pub name: String,
+    pub email: String,
+    /// The outbound shipment we created for the order, None if nothing in it was swag we ship.
+    pub outbound_shipment_id: Option<i32>,
+    /// The line items we could not match to the swag inventory, like `Poster (SKU POSTER-1)`.
+    pub unmatched_items: Vec<String>,
+    pub synced_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = shopify_orders)]
+struct NewShopifyOrder {
+    shopify_order_id: i64,
+    name: String,
+    email: String,
+    outbound_shipment_id: Option<i32>,
+    unmatched_items: Vec<String>,
+    synced_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// The address an order ships to.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ShopifyAddress {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    address1: Option<String>,
+    #[serde(default)]
+    address2: Option<String>,
+    #[serde(default)]
+    city: Option<String>,
+    #[serde(default)]
+    province_code: Option<String>,
+    #[serde(default)]
+    zip: Option<String>,
+    #[serde(default)]
+    country_code: Option<String>,
+    #[serde(default)]
+    phone: Option<String>,
+}
+
+/// A line of an order, a variant of a product.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ShopifyLineItem {
+    #[serde(default)]
+    sku: Option<String>,
+    #[serde(default)]
+    title: String,
+    /// The variant, like the size. Products without variants have none.
+    #[serde(default)]
+    variant_title: Option<String>,
+    #[serde(default)]
+    quantity: i32,
+    #[serde(default = "default_requires_shipping")]
+    requires_shipping: bool,
+}
+
+fn default_requires_shipping() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ShopifyStoreOrder {
+    id: i64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    phone: Option<String>,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    shipping_address: Option<ShopifyAddress>,
+    #[serde(default)]
+    line_items: Vec<ShopifyLineItem>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ShopifyOrdersPage {
+    #[serde(default)]
+    orders: Vec<ShopifyStoreOrder>,
+}
+
+fn or_empty(s: &Option<String>) -> String {
+    s.as_deref().unwrap_or_default().trim().to_string()
+}
+
+impl ShopifyLineItem {
+    fn describe(&self) -> String {
+        let mut name = self.title.trim().to_string();
+        if !or_empty(&self.variant_title).is_empty() {
+            name = format!("{} / {}", name, or_empty(&self.variant_title));
+        }
+        if !or_empty(&self.sku).is_empty() {
+            name = format!("{} (SKU {})", name, or_empty(&self.sku));
+        }
+        name
+    }
+
+    /// Find the swag inventory item of the line. The SKU of a variant is the barcode of the
+    /// inventory item, stores that do not set SKUs match on the product and variant titles
+    /// instead.
+    fn inventory_item<'a>(&self, inventory: &'a [SwagInventoryItem]) -> Option<&'a SwagInventoryItem> {
+        let sku = or_empty(&self.sku);
+        if !sku.is_empty() {
+            if let Some(item) = inventory.iter().find(|i| i.barcode.trim().eq_ignore_ascii_case(&sku)) {
+                return Some(item);
+            }
+        }
+
+        let sizes: Vec<&SwagInventoryItem> = inventory
+            .iter()
+            .filter(|i| i.item.trim().eq_ignore_ascii_case(self.title.trim()))
+            .collect();
+        let variant = or_empty(&self.variant_title);
+        if variant.is_empty() && sizes.len() == 1 {
+            return Some(sizes[0]);
+        }
+
+        sizes.into_iter().find(|i| i.size.trim().eq_ignore_ascii_case(&variant))
+    }
+}
+
+impl ShopifyStoreOrder {
+    /// Map the order to a swag store order, along with the line items we could not match to
+    /// the swag inventory.
+    fn to_order(&self, company: &Company, inventory: &[SwagInventoryItem]) -> (Order, Vec<String>) {
+        let address = self.shipping_address.clone().unwrap_or_default();
+
+        let mut items: Vec<OrderItem> = Vec::new();
+        let mut unmatched = Vec::new();
+        for line in self.line_items.iter().filter(|l| l.requires_shipping && l.quantity > 0) {
+            match line.inventory_item(inventory) {
+                Some(inventory_item) => match items.iter_mut().find(|i| i.id == inventory_item.id) {
+                    Some(item) => item.quantity += line.quantity,
+                    None => items.push(OrderItem {
+                        id: inventory_item.id,
+                        quantity: line.quantity,
+                    }),
+                },
+                None => unmatched.push(line.describe()),
+            }
+        }
+
+        let phone = if or_empty(&address.phone).is_empty() {
+            or_empty(&self.phone)
+        } else {
+            or_empty(&address.phone)
+        };
+
+        let order = Order {
+            name: or_empty(&address.name),
+            email: or_empty(&self.email).to_lowercase(),
+            phone,
+            street_1: or_empty(&address.address1),
+            street_2: or_empty(&address.address2),
+            city: or_empty(&address.city),
+            state: or_empty(&address.province_code),
+            zipcode: or_empty(&address.zip),
+            country: or_empty(&address.country_code),
+            notes: format!("Shopify order {}. {}", self.name, or_empty(&self.note))
+                .trim()
+                .to_string(),
+            items,
+            cio_company_id: company.id,
+        };
+
+        (order, unmatched)
+    }
+}
+
+/// A client for the Admin API of a company's Shopify store.
+struct Shopify {
+    store: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl Shopify {
+    /// The client for the store of a company, from its `shopify` API token. The endpoint of
+    /// the token is the domain of the store, like `oxide.myshopify.com`.
+    async fn for_company(db: &Database, company: &Company) -> Option<Self> {
+        let t = APIToken::get_from_db(db, company.id, "shopify".to_string()).await?;
+        if t.endpoint.trim().is_empty() || t.access_token.trim().is_empty() {
+            return None;
+        }
+
+        Some(Shopify {
+            store: t
+                .endpoint
+                .trim()
+                .trim_start_matches("https://")
+                .trim_end_matches('/')
+                .to_string(),
+            token: t.access_token.trim().to_string(),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Get all the open orders that are paid but not fulfilled yet.
+    async fn list_unfulfilled_orders(&self) -> Result<Vec<ShopifyStoreOrder>> {
+        let mut orders: Vec<ShopifyStoreOrder> = Vec::new();
+        // We only use since_id to page, an order placed a while ago can be paid after newer
+        // ones, so we always start from the first order.
+        let mut since_id = 0;
+        loop {
+            let resp = self
+                .client
+                .get(&format!(
+                    "https://{}/admin/api/{}/orders.json",
+                    self.store, SHOPIFY_API_VERSION
+                ))
+                .header("X-Shopify-Access-Token", &self.token)
+                .query(&[
+                    ("status", "open".to_string()),
+                    ("financial_status", "paid".to_string()),
+                    ("fulfillment_status", "unfulfilled".to_string()),
+                    ("limit", SHOPIFY_PAGE_SIZE.to_string()),
+                    ("since_id", since_id.to_string()),
+                ])
+                .send()
+                .await?;
+            if resp.status() != StatusCode::OK {
+                bail!(
+                    "listing Shopify orders failed, status_code: {}, body: {}",
+                    resp.status(),
+                    resp.text().await?
+                );
+            }
+
+            let page: ShopifyOrdersPage = resp.json().await?;
+            let last = page.orders.len() < SHOPIFY_PAGE_SIZE;
+            // Orders come sorted by id when we page with since_id.
+            since_id = page.orders.iter().map(|o| o.id).max().unwrap_or(since_id);
+            orders.extend(page.orders);
+            if last {
+                return Ok(orders);
+            }
+        }
+    }
+}
+
+/// Sync the orders of the Shopify store: subtract what they ordered from the swag inventory,
+/// and create their outbound shipments. Each order is only synced once, and companies
+/// without a Shopify store are skipped.
+pub async fn refresh_shopify_orders(db: &Database, company: &Company) -> Result<()> {
+    let shopify = match Shopify::for_company(db, company).await {
+        Some(shopify) => shopify,
+        // Return early, this company does not have a store.
+        None => return Ok(()),
+    };
+
+    let synced: Vec<i64> = shopify_orders::dsl::shopify_orders
+        .filter(shopify_orders::dsl::cio_company_id.eq(company.id))
+        .select(shopify_orders::dsl::shopify_order_id)
+        .load_async::<i64>(db.pool())
+        .await?;
+
+    let inventory: Vec<SwagInventoryItem> = SwagInventoryItems::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .collect();
+
+    let orders = shopify.list_unfulfilled_orders().await?;
+    for store_order in orders.iter().filter(|o| !synced.contains(&o.id)) {
+        let (order, unmatched_items) = store_order.to_order(company, &inventory);
+        if !unmatched_items.is_empty() {
+            warn!(
+                "Shopify order {} has items that are not in the swag inventory: {}",
+                store_order.name,
+                unmatched_items.join(", ")
+            );
+        }
+
+        // Claim the order before we create its shipment, so if another sync is running at
+        // the same time only one of us ships it.
+        let claimed = diesel::insert_into(shopify_orders::table)
+            .values(NewShopifyOrder {
+                shopify_order_id: store_order.id,
+                name: store_order.name.to_string(),
+                email: order.email.to_string(),
+                outbound_shipment_id: None,
+                unmatched_items,
+                synced_at: Utc::now(),
+                cio_company_id: company.id,
+            })
+            .on_conflict((
+                shopify_orders::dsl::cio_company_id,
+                shopify_orders::dsl::shopify_order_id,
+            ))
+            .do_nothing()
+            .execute_async(db.pool())
+            .await?;
+        if claimed == 0 {
+            continue;
+        }
+
+        if !order.items.is_empty() && !order.street_1.is_empty() {
+            let shipment = match order.create_shipment_for_order(db).await {
+                Ok(shipment) => shipment,
+                Err(e) => {
+                    // Give the order back so the next sync tries it again.
+                    diesel::delete(
+                        shopify_orders::dsl::shopify_orders
+                            .filter(shopify_orders::dsl::cio_company_id.eq(company.id))
+                            .filter(shopify_orders::dsl::shopify_order_id.eq(store_order.id)),
+                    )
+                    .execute_async(db.pool())
+                    .await?;
+                    return Err(e);
+                }
+            };
+
+            diesel::update(
+                shopify_orders::dsl::shopify_orders
+                    .filter(shopify_orders::dsl::cio_company_id.eq(company.id))
+                    .filter(shopify_orders::dsl::shopify_order_id.eq(store_order.id)),
+            )
+            .set(shopify_orders::dsl::outbound_shipment_id.eq(Some(shipment.id)))
+            .execute_async(db.pool())
+            .await?;
+        }
+
+        info!("synced Shopify order {} for {}", store_order.name, order.email);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShopifyStoreOrder;
+    use crate::tests::mock_record;
+    use crate::{companies::tests::mock_company, swag_inventory::SwagInventoryItem};
+
+    fn mock_inventory_item(id: i32, item: &str, size: &str, barcode: &str) -> SwagInventoryItem {
+        let mut inventory_item: SwagInventoryItem = mock_record(json!({}));
+        inventory_item.id = id;
+        inventory_item.item = item.to_string();
+        inventory_item.size = size.to_string();
+        inventory_item.barcode = barcode.to_string();
+        inventory_item
+    }
+
+    #[test]
+    fn test_shopify_order_to_order() {
+        let inventory = vec![
+            mock_inventory_item(1, "Hoodie", "M", "HOODIE-M"),
+            mock_inventory_item(2, "Hoodie", "L", "HOODIE-L"),
+            mock_inventory_item(3, "Sticker", "N/A", ""),
+        ];
+        let store_order: ShopifyStoreOrder = serde_json::from_str(
+            r##"{
+                "id": 450789469,
+                "name": "#1001",
+                "email": "Jane@Example.com",
+                "phone": "+15555550100",
+                "note": null,
+                "shipping_address": {
+                    "name": "Jane Doe",
+                    "address1": "1 Main St",
+                    "address2": null,
+                    "city": "Oakland",
+                    "province_code": "CA",
+                    "zip": "94607",
+                    "country_code": "US",
+                    "phone": ""
+                },
+                "line_items": [
+                    {"sku": "hoodie-l", "title": "Oxide Hoodie", "variant_title": "L", "quantity": 1},
+                    {"sku": "", "title": "Sticker", "variant_title": null, "quantity": 2},
+                    {"sku": null, "title": "Sticker", "quantity": 1},
+                    {"sku": "POSTER-1", "title": "Poster", "variant_title": null, "quantity": 1},
+                    {"sku": "GIFT", "title": "Gift card", "quantity": 1, "requires_shipping": false}
+                ]
+            }"##,
+        )
+        .unwrap();
+
+        let (order, unmatched) = store_order.to_order(&mock_company(), &inventory);
+        assert_eq!("jane@example.com", order.email);
+        assert_eq!("+15555550100", order.phone);
+        assert_eq!("CA", order.state);
+        assert_eq!("Shopify order #1001.", order.notes);
+        assert_eq!(2, order.items.len());
+        assert_eq!((2, 1), (order.items[0].id, order.items[0].quantity));
+        assert_eq!((3, 3), (order.items[1].id, order.items[1].quantity));
+        assert_eq!(vec!["Poster (SKU POSTER-1)".to_string()], unmatched);
+    }
+}
@@ -6,23 +6,14 @@ use crate::{
     configs::Links,
     db::Database,
     dns_providers::{DNSProviderOps, DnsRecord, DnsRecordType, DnsUpdateMode},
+    features::Features,
     repos::GithubRepos,
     rfd::RFDs,
     templates::generate_nginx_files_for_shorturls,
 };
 
-/// Generate the files for the GitHub repository short URLs.
-pub async fn generate_shorturls_for_repos<C>(
-    db: &Database,
-    github: &octorust::Client,
-    company: &Company,
-    dns: &C,
-    out_repos: &[String],
-) -> Result<()>
-where
-    C: DNSProviderOps,
-{
-    let owner = &company.github_org;
+/// Get the short URLs of the GitHub repositories.
+pub async fn get_shorturls_for_repos(db: &Database, company: &Company) -> Result<Vec<ShortUrl>> {
     let subdomain = "git";
     // Initialize the array of links.
     let mut links: Vec<ShortUrl> = Default::default();
@@ -47,18 +38,11 @@ where
         links.push(link.clone());
     }
 
-    log::info!("Collected {} repo links to check", links.len());
-
-    // Generate the files for the links.
-    generate_nginx_files_for_shorturls(github, owner, out_repos, links.clone()).await?;
-
-    create_dns_records_for_links(dns, company, links).await?;
-
-    Ok(())
+    Ok(links)
 }
 
-/// Generate the files for the RFD short URLs.
-pub async fn generate_shorturls_for_rfds<C>(
+/// Generate the files for the GitHub repository short URLs.
+pub async fn generate_shorturls_for_repos<C>(
     db: &Database,
     github: &octorust::Client,
     company: &Company,
@@ -69,6 +53,20 @@ where
     C: DNSProviderOps,
 {
     let owner = &company.github_org;
+    let links = get_shorturls_for_repos(db, company).await?;
+
+    log::info!("Collected {} repo links to check", links.len());
+
+    // Generate the files for the links.
+    generate_nginx_files_for_shorturls(github, owner, out_repos, links.clone()).await?;
+
+    create_dns_records_for_links(dns, company, links).await?;
+
+    Ok(())
+}
+
+/// Get the short URLs of the RFDs.
+pub async fn get_shorturls_for_rfds(db: &Database, company: &Company) -> Result<Vec<ShortUrl>> {
     let subdomain = "rfd";
     // Initialize the array of links.
     let mut links: Vec<ShortUrl> = Default::default();
@@ -104,18 +102,11 @@ where
         links.push(link.clone());
     }
 
-    log::info!("Collected {} rfd links to check", links.len());
-
-    // Generate the files for the links.
-    generate_nginx_files_for_shorturls(github, owner, out_repos, links.clone()).await?;
-
-    create_dns_records_for_links(dns, company, links).await?;
-
-    Ok(())
+    Ok(links)
 }
 
-/// Generate the files for the configs links.
-pub async fn generate_shorturls_for_configs_links<C>(
+/// Generate the files for the RFD short URLs.
+pub async fn generate_shorturls_for_rfds<C>(
     db: &Database,
     github: &octorust::Client,
     company: &Company,
@@ -126,6 +117,20 @@ where
     C: DNSProviderOps,
 {
     let owner = &company.github_org;
+    let links = get_shorturls_for_rfds(db, company).await?;
+
+    log::info!("Collected {} rfd links to check", links.len());
+
+    // Generate the files for the links.
+    generate_nginx_files_for_shorturls(github, owner, out_repos, links.clone()).await?;
+
+    create_dns_records_for_links(dns, company, links).await?;
+
+    Ok(())
+}
+
+/// Get the short URLs of the configs links.
+pub async fn get_shorturls_for_configs_links(db: &Database, company: &Company) -> Result<Vec<ShortUrl>> {
     let subdomain = "corp";
     // Initialize the array of links.
     let mut links: Vec<ShortUrl> = Default::default();
@@ -164,6 +169,23 @@ where
         }
     }
 
+    Ok(links)
+}
+
+/// Generate the files for the configs links.
+pub async fn generate_shorturls_for_configs_links<C>(
+    db: &Database,
+    github: &octorust::Client,
+    company: &Company,
+    dns: &C,
+    out_repos: &[String],
+) -> Result<()>
+where
+    C: DNSProviderOps,
+{
+    let owner = &company.github_org;
+    let links = get_shorturls_for_configs_links(db, company).await?;
+
     log::info!("Collected {} config links to check", links.len());
 
     // Generate the files for the links.
@@ -278,19 +300,31 @@ pub struct ShortUrl {
     pub discussion: String,
 }
 
+impl ShortUrl {
+    /// The name of the DNS record of the short URL.
+    pub fn dns_name(&self, company: &Company) -> String {
+        // Make sure the name does not start with a dot ".".
+        format!(
+            "{}.{}.{}",
+            self.name.trim_start_matches('.'),
+            self.subdomain,
+            company.domain
+        )
+    }
+}
+
 async fn create_dns_records_for_links<C>(dns_client: &C, company: &Company, shorturls: Vec<ShortUrl>) -> Result<()>
 where
     C: DNSProviderOps,
 {
-    for s in shorturls {
-        // Make sure the name does not start with a dot ".".
-        let mut name = if s.name.starts_with('.') {
-            s.name.trim_start_matches('.').to_string()
-        } else {
-            s.name.to_string()
-        };
+    if Features::is_enabled("RECONCILE_PLANS") {
+        // The records are changed by applying the plans of the dns reconciler.
+        log::info!("reconcile plans are enabled, not creating the DNS records of the short URLs");
+        return Ok(());
+    }
 
-        name = format!("{}.{}.{}", name, s.subdomain, company.domain);
+    for s in shorturls {
+        let name = s.dns_name(company);
         if dns_client
             .ensure_record(
                 DnsRecord {
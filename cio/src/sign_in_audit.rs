@@ -0,0 +1,881 @@
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{companies::Company, db::Database, schema::sign_in_events};
+
+/// How long we keep sign-in events for, they are the history we compare new sign-ins to.
+static SIGN_IN_EVENT_RETENTION_DAYS: i64 = 90;
+/// How far back we look the first time we ingest the sign-ins of a provider.
+static SIGN_IN_INITIAL_LOOKBACK_DAYS: i64 = 7;
+/// How late a sign-in can show up in the logs after it happened, Google's login report can lag
+/// by a day or more. We look this far behind the last sign-in we have each time.
+static SIGN_IN_LATE_EVENT_HOURS: i64 = 48;
+/// The fastest anyone travels between two sign-ins, a bit faster than a plane.
+static MAX_TRAVEL_SPEED_KPH: f64 = 1000.0;
+/// Sign-ins closer than this are never impossible travel, IP geolocation is not that precise.
+static MIN_TRAVEL_DISTANCE_KM: f64 = 500.0;
+
+/// The ISO 3166 codes of the country names Okta uses, Google only gives us the code so we
+/// compare sign-ins on the code.
+static COUNTRY_CODES: &[(&str, &str)] = &[
+    ("Afghanistan", "AF"),
+    ("Åland", "AX"),
+    ("Aland Islands", "AX"),
+    ("Albania", "AL"),
+    ("Algeria", "DZ"),
+    ("American Samoa", "AS"),
+    ("Andorra", "AD"),
+    ("Angola", "AO"),
+    ("Anguilla", "AI"),
+    ("Antarctica", "AQ"),
+    ("Antigua and Barbuda", "AG"),
+    ("Argentina", "AR"),
+    ("Armenia", "AM"),
+    ("Aruba", "AW"),
+    ("Australia", "AU"),
+    ("Austria", "AT"),
+    ("Azerbaijan", "AZ"),
+    ("Bahamas", "BS"),
+    ("Bahrain", "BH"),
+    ("Bangladesh", "BD"),
+    ("Barbados", "BB"),
+    ("Belarus", "BY"),
+    ("Belgium", "BE"),
+    ("Belize", "BZ"),
+    ("Benin", "BJ"),
+    ("Bermuda", "BM"),
+    ("Bhutan", "BT"),
+    ("Bolivia", "BO"),
+    ("Bonaire, Sint Eustatius, and Saba", "BQ"),
+    ("Bosnia and Herzegovina", "BA"),
+    ("Botswana", "BW"),
+    ("Bouvet Island", "BV"),
+    ("Brazil", "BR"),
+    ("British Indian Ocean Territory", "IO"),
+    ("British Virgin Islands", "VG"),
+    ("Brunei", "BN"),
+    ("Bulgaria", "BG"),
+    ("Burkina Faso", "BF"),
+    ("Burundi", "BI"),
+    ("Cabo Verde", "CV"),
+    ("Cape Verde", "CV"),
+    ("Cambodia", "KH"),
+    ("Cameroon", "CM"),
+    ("Canada", "CA"),
+    ("Cayman Islands", "KY"),
+    ("Central African Republic", "CF"),
+    ("Chad", "TD"),
+    ("Chile", "CL"),
+    ("China", "CN"),
+    ("Christmas Island", "CX"),
+    ("Cocos (Keeling) Islands", "CC"),
+    ("Colombia", "CO"),
+    ("Comoros", "KM"),
+    ("Congo", "CG"),
+    ("Congo Republic", "CG"),
+    ("Republic of the Congo", "CG"),
+    ("DR Congo", "CD"),
+    ("Democratic Republic of the Congo", "CD"),
+    ("Cook Islands", "CK"),
+    ("Costa Rica", "CR"),
+    ("Croatia", "HR"),
+    ("Cuba", "CU"),
+    ("Curaçao", "CW"),
+    ("Cyprus", "CY"),
+    ("Czechia", "CZ"),
+    ("Czech Republic", "CZ"),
+    ("Denmark", "DK"),
+    ("Djibouti", "DJ"),
+    ("Dominica", "DM"),
+    ("Dominican Republic", "DO"),
+    ("Ecuador", "EC"),
+    ("Egypt", "EG"),
+    ("El Salvador", "SV"),
+    ("Equatorial Guinea", "GQ"),
+    ("Eritrea", "ER"),
+    ("Estonia", "EE"),
+    ("Eswatini", "SZ"),
+    ("Swaziland", "SZ"),
+    ("Ethiopia", "ET"),
+    ("Falkland Islands", "FK"),
+    ("Faroe Islands", "FO"),
+    ("Fiji", "FJ"),
+    ("Finland", "FI"),
+    ("France", "FR"),
+    ("French Guiana", "GF"),
+    ("French Polynesia", "PF"),
+    ("French Southern Territories", "TF"),
+    ("Gabon", "GA"),
+    ("Gambia", "GM"),
+    ("Georgia", "GE"),
+    ("Germany", "DE"),
+    ("Ghana", "GH"),
+    ("Gibraltar", "GI"),
+    ("Greece", "GR"),
+    ("Greenland", "GL"),
+    ("Grenada", "GD"),
+    ("Guadeloupe", "GP"),
+    ("Guam", "GU"),
+    ("Guatemala", "GT"),
+    ("Guernsey", "GG"),
+    ("Guinea", "GN"),
+    ("Guinea-Bissau", "GW"),
+    ("Guyana", "GY"),
+    ("Haiti", "HT"),
+    ("Heard Island and McDonald Islands", "HM"),
+    ("Honduras", "HN"),
+    ("Hong Kong", "HK"),
+    ("Hungary", "HU"),
+    ("Iceland", "IS"),
+    ("India", "IN"),
+    ("Indonesia", "ID"),
+    ("Iran", "IR"),
+    ("Iraq", "IQ"),
+    ("Ireland", "IE"),
+    ("Isle of Man", "IM"),
+    ("Israel", "IL"),
+    ("Italy", "IT"),
+    ("Ivory Coast", "CI"),
+    ("Côte d'Ivoire", "CI"),
+    ("Jamaica", "JM"),
+    ("Japan", "JP"),
+    ("Jersey", "JE"),
+    ("Jordan", "JO"),
+    ("Hashemite Kingdom of Jordan", "JO"),
+    ("Kazakhstan", "KZ"),
+    ("Kenya", "KE"),
+    ("Kiribati", "KI"),
+    ("Kosovo", "XK"),
+    ("Kuwait", "KW"),
+    ("Kyrgyzstan", "KG"),
+    ("Laos", "LA"),
+    ("Latvia", "LV"),
+    ("Lebanon", "LB"),
+    ("Lesotho", "LS"),
+    ("Liberia", "LR"),
+    ("Libya", "LY"),
+    ("Liechtenstein", "LI"),
+    ("Lithuania", "LT"),
+    ("Republic of Lithuania", "LT"),
+    ("Luxembourg", "LU"),
+    ("Macao", "MO"),
+    ("Macau", "MO"),
+    ("Madagascar", "MG"),
+    ("Malawi", "MW"),
+    ("Malaysia", "MY"),
+    ("Maldives", "MV"),
+    ("Mali", "ML"),
+    ("Malta", "MT"),
+    ("Marshall Islands", "MH"),
+    ("Martinique", "MQ"),
+    ("Mauritania", "MR"),
+    ("Mauritius", "MU"),
+    ("Mayotte", "YT"),
+    ("Mexico", "MX"),
+    ("Micronesia", "FM"),
+    ("Federated States of Micronesia", "FM"),
+    ("Moldova", "MD"),
+    ("Republic of Moldova", "MD"),
+    ("Monaco", "MC"),
+    ("Mongolia", "MN"),
+    ("Montenegro", "ME"),
+    ("Montserrat", "MS"),
+    ("Morocco", "MA"),
+    ("Mozambique", "MZ"),
+    ("Myanmar", "MM"),
+    ("Namibia", "NA"),
+    ("Nauru", "NR"),
+    ("Nepal", "NP"),
+    ("Netherlands", "NL"),
+    ("The Netherlands", "NL"),
+    ("New Caledonia", "NC"),
+    ("New Zealand", "NZ"),
+    ("Nicaragua", "NI"),
+    ("Niger", "NE"),
+    ("Nigeria", "NG"),
+    ("Niue", "NU"),
+    ("Norfolk Island", "NF"),
+    ("North Korea", "KP"),
+    ("North Macedonia", "MK"),
+    ("Macedonia", "MK"),
+    ("Northern Mariana Islands", "MP"),
+    ("Norway", "NO"),
+    ("Oman", "OM"),
+    ("Pakistan", "PK"),
+    ("Palau", "PW"),
+    ("Palestine", "PS"),
+    ("Panama", "PA"),
+    ("Papua New Guinea", "PG"),
+    ("Paraguay", "PY"),
+    ("Peru", "PE"),
+    ("Philippines", "PH"),
+    ("Pitcairn Islands", "PN"),
+    ("Poland", "PL"),
+    ("Portugal", "PT"),
+    ("Puerto Rico", "PR"),
+    ("Qatar", "QA"),
+    ("Réunion", "RE"),
+    ("Romania", "RO"),
+    ("Russia", "RU"),
+    ("Russian Federation", "RU"),
+    ("Rwanda", "RW"),
+    ("Saint Barthélemy", "BL"),
+    ("Saint Helena", "SH"),
+    ("Saint Kitts and Nevis", "KN"),
+    ("St Kitts and Nevis", "KN"),
+    ("Saint Lucia", "LC"),
+    ("Saint Martin", "MF"),
+    ("Saint Pierre and Miquelon", "PM"),
+    ("Saint Vincent and the Grenadines", "VC"),
+    ("St Vincent and Grenadines", "VC"),
+    ("Samoa", "WS"),
+    ("San Marino", "SM"),
+    ("São Tomé and Príncipe", "ST"),
+    ("Sao Tome and Principe", "ST"),
+    ("Saudi Arabia", "SA"),
+    ("Senegal", "SN"),
+    ("Serbia", "RS"),
+    ("Seychelles", "SC"),
+    ("Sierra Leone", "SL"),
+    ("Singapore", "SG"),
+    ("Sint Maarten", "SX"),
+    ("Slovakia", "SK"),
+    ("Slovenia", "SI"),
+    ("Solomon Islands", "SB"),
+    ("Somalia", "SO"),
+    ("South Africa", "ZA"),
+    ("South Georgia and the South Sandwich Islands", "GS"),
+    ("South Korea", "KR"),
+    ("Republic of Korea", "KR"),
+    ("South Sudan", "SS"),
+    ("Spain", "ES"),
+    ("Sri Lanka", "LK"),
+    ("Sudan", "SD"),
+    ("Suriname", "SR"),
+    ("Svalbard and Jan Mayen", "SJ"),
+    ("Sweden", "SE"),
+    ("Switzerland", "CH"),
+    ("Syria", "SY"),
+    ("Taiwan", "TW"),
+    ("Tajikistan", "TJ"),
+    ("Tanzania", "TZ"),
+    ("Thailand", "TH"),
+    ("Timor-Leste", "TL"),
+    ("East Timor", "TL"),
+    ("Togo", "TG"),
+    ("Tokelau", "TK"),
+    ("Tonga", "TO"),
+    ("Trinidad and Tobago", "TT"),
+    ("Tunisia", "TN"),
+    ("Turkey", "TR"),
+    ("Türkiye", "TR"),
+    ("Turkmenistan", "TM"),
+    ("Turks and Caicos Islands", "TC"),
+    ("Tuvalu", "TV"),
+    ("U.S. Outlying Islands", "UM"),
+    ("U.S. Virgin Islands", "VI"),
+    ("Uganda", "UG"),
+    ("Ukraine", "UA"),
+    ("United Arab Emirates", "AE"),
+    ("United Kingdom", "GB"),
+    ("United States", "US"),
+    ("United States of America", "US"),
+    ("Uruguay", "UY"),
+    ("Uzbekistan", "UZ"),
+    ("Vanuatu", "VU"),
+    ("Vatican City", "VA"),
+    ("Venezuela", "VE"),
+    ("Vietnam", "VN"),
+    ("Wallis and Futuna", "WF"),
+    ("Western Sahara", "EH"),
+    ("Yemen", "YE"),
+    ("Zambia", "ZM"),
+    ("Zimbabwe", "ZW"),
+];
+
+pub static SIGN_IN_PROVIDER_OKTA: &str = "okta";
+pub static SIGN_IN_PROVIDER_GOOGLE: &str = "google";
+
+/// A sign-in of someone at the company, from the sign-in logs of Okta or Google.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct SignInEvent {
+    pub id: i32,
+    /// Either `okta` or `google`.
+    pub provider: String,
+    /// The id of the event in the provider's log.
+    pub event_id: String,
+    pub email: String,
+    pub ip_address: String,
+    /// The ISO 3166 code of the country the sign-in came from, if the provider knows it.
+    pub country: String,
+    pub city: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub signed_in_at: DateTime<Utc>,
+    /// Why the sign-in looked wrong, empty if it did not.
+    pub anomaly: String,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone, Default)]
+#[diesel(table_name = sign_in_events)]
+pub struct NewSignInEvent {
+    pub provider: String,
+    pub event_id: String,
+    pub email: String,
+    pub ip_address: String,
+    pub country: String,
+    pub city: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub signed_in_at: DateTime<Utc>,
+    pub anomaly: String,
+    pub cio_company_id: i32,
+}
+
+impl NewSignInEvent {
+    fn location(&self) -> String {
+        match (self.city.is_empty(), self.country.is_empty()) {
+            (true, true) => "an unknown location".to_string(),
+            (true, false) => self.country.to_string(),
+            (false, true) => self.city.to_string(),
+            (false, false) => format!("{}, {}", self.city, self.country),
+        }
+    }
+}
+
+impl SignInEvent {
+    fn location(&self) -> String {
+        NewSignInEvent {
+            city: self.city.to_string(),
+            country: self.country.to_string(),
+            ..Default::default()
+        }
+        .location()
+    }
+}
+
+/// Get the ISO 3166 code of a country from its name, codes are returned uppercased and names we
+/// do not know are kept as they are.
+pub fn country_code(country: &str) -> String {
+    let country = country.trim();
+    if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) {
+        return country.to_uppercase();
+    }
+
+    COUNTRY_CODES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(country))
+        .map(|(_, code)| code.to_string())
+        .unwrap_or_else(|| country.to_string())
+}
+
+/// The distance between two points on the earth, in kilometers.
+fn haversine_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * 6371.0 * a.sqrt().asin()
+}
+
+/// Decide if a sign-in looks wrong compared to the earlier sign-ins of the same person. People
+/// we have never seen sign in are not anomalies, their first sign-in is what we compare the
+/// next ones to.
+pub fn detect_sign_in_anomaly(event: &NewSignInEvent, history: &[SignInEvent]) -> Option<String> {
+    let history: Vec<&SignInEvent> = history
+        .iter()
+        .filter(|h| h.email.eq_ignore_ascii_case(&event.email) && h.signed_in_at <= event.signed_in_at)
+        .collect();
+    if history.is_empty() {
+        return None;
+    }
+
+    if let (Some(latitude), Some(longitude)) = (event.latitude, event.longitude) {
+        let previous = history
+            .iter()
+            .filter(|h| h.latitude.is_some() && h.longitude.is_some())
+            .max_by_key(|h| h.signed_in_at);
+        if let Some(previous) = previous {
+            let distance = haversine_km(
+                (
+                    previous.latitude.unwrap_or_default(),
+                    previous.longitude.unwrap_or_default(),
+                ),
+                (latitude, longitude),
+            );
+            let hours = (event.signed_in_at - previous.signed_in_at).num_seconds() as f64 / 3600.0;
+            if distance >= MIN_TRAVEL_DISTANCE_KM && distance > MAX_TRAVEL_SPEED_KPH * hours {
+                return Some(format!(
+                    "impossible travel: {:.0} km from {} in {:.1} hours",
+                    distance,
+                    previous.location(),
+                    hours
+                ));
+            }
+        }
+    }
+
+    // Sign-ins we ingested before we stored codes have the name of the country.
+    let country = country_code(&event.country);
+    if !country.is_empty() && !history.iter().any(|h| country_code(&h.country) == country) {
+        return Some(format!("first sign-in from {}", country));
+    }
+
+    None
+}
+
+/// An event of the Okta System Log.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct OktaLogEvent {
+    #[serde(default)]
+    uuid: String,
+    published: DateTime<Utc>,
+    #[serde(default)]
+    actor: OktaLogActor,
+    #[serde(default)]
+    client: OktaLogClient,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct OktaLogActor {
+    #[serde(default, rename = "alternateId")]
+    alternate_id: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct OktaLogClient {
+    #[serde(default, rename = "ipAddress")]
+    ip_address: Option<String>,
+    #[serde(default, rename = "geographicalContext")]
+    geographical_context: Option<OktaGeographicalContext>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct OktaGeographicalContext {
+    #[serde(default)]
+    city: Option<String>,
+    #[serde(default)]
+    country: Option<String>,
+    #[serde(default)]
+    geolocation: Option<OktaGeolocation>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct OktaGeolocation {
+    lat: f64,
+    lon: f64,
+}
+
+impl OktaLogEvent {
+    fn to_sign_in_event(&self, company: &Company) -> NewSignInEvent {
+        let geo = self.client.geographical_context.clone().unwrap_or_default();
+        NewSignInEvent {
+            provider: SIGN_IN_PROVIDER_OKTA.to_string(),
+            event_id: self.uuid.to_string(),
+            email: self.actor.alternate_id.trim().to_lowercase(),
+            ip_address: self.client.ip_address.clone().unwrap_or_default(),
+            country: country_code(&geo.country.unwrap_or_default()),
+            city: geo.city.unwrap_or_default(),
+            latitude: geo.geolocation.as_ref().map(|g| g.lat),
+            longitude: geo.geolocation.as_ref().map(|g| g.lon),
+            signed_in_at: self.published,
+            anomaly: Default::default(),
+            cio_company_id: company.id,
+        }
+    }
+}
+
+/// Get the sign-ins of the Okta System Log since a time.
+async fn list_okta_sign_ins(company: &Company, since: DateTime<Utc>) -> Result<Vec<NewSignInEvent>> {
+    let client = reqwest::Client::new();
+    let mut url = reqwest::Url::parse_with_params(
+        &format!("{}/api/v1/logs", company.okta_endpoint()),
+        &[
+            ("since", since.to_rfc3339()),
+            (
+                "filter",
+                r#"eventType eq "user.session.start" and outcome.result eq "SUCCESS""#.to_string(),
+            ),
+            ("limit", "1000".to_string()),
+        ],
+    )?
+    .to_string();
+
+    let mut events = Vec::new();
+    loop {
+        let resp = client
+            .get(&url)
+            .header("Authorization", format!("SSWS {}", company.okta_api_key))
+            .header("Accept", "application/json")
+            .send()
+            .await?;
+        if resp.status() != StatusCode::OK {
+            bail!(
+                "listing the Okta System Log failed, status_code: {}, body: {}",
+                resp.status(),
+                resp.text().await?
+            );
+        }
+
+        // Okta always links to the next page, we are done once a page is empty.
+        let next = resp
+            .headers()
+            .get_all(reqwest::header::LINK)
+            .iter()
+            .filter_map(|l| l.to_str().ok())
+            .find(|l| l.contains("rel=\"next\""))
+            .and_then(|l| l.split(';').next())
+            .map(|l| l.trim().trim_start_matches('<').trim_end_matches('>').to_string());
+
+        let page: Vec<OktaLogEvent> = resp.json().await?;
+        if page.is_empty() {
+            return Ok(events);
+        }
+        events.extend(page.iter().map(|e| e.to_sign_in_event(company)));
+
+        match next {
+            Some(next) => url = next,
+            None => return Ok(events),
+        }
+    }
+}
+
+/// An activity of the login report of the Google Admin SDK.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct GoogleLoginActivity {
+    id: GoogleActivityId,
+    #[serde(default)]
+    actor: GoogleActivityActor,
+    #[serde(default, rename = "ipAddress")]
+    ip_address: Option<String>,
+    #[serde(default, rename = "networkInfo")]
+    network_info: Option<GoogleNetworkInfo>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct GoogleActivityId {
+    time: DateTime<Utc>,
+    #[serde(default, rename = "uniqueQualifier")]
+    unique_qualifier: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct GoogleActivityActor {
+    #[serde(default)]
+    email: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct GoogleNetworkInfo {
+    #[serde(default, rename = "regionCode")]
+    region_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct GoogleActivities {
+    #[serde(default)]
+    items: Vec<GoogleLoginActivity>,
+    #[serde(default, rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+impl GoogleLoginActivity {
+    /// Google only tells us the country of a sign-in, not where in it.
+    fn to_sign_in_event(&self, company: &Company) -> NewSignInEvent {
+        NewSignInEvent {
+            provider: SIGN_IN_PROVIDER_GOOGLE.to_string(),
+            event_id: self.id.unique_qualifier.to_string(),
+            email: self.actor.email.trim().to_lowercase(),
+            ip_address: self.ip_address.clone().unwrap_or_default(),
+            country: country_code(
+                &self
+                    .network_info
+                    .as_ref()
+                    .and_then(|n| n.region_code.clone())
+                    .unwrap_or_default(),
+            ),
+            city: Default::default(),
+            latitude: None,
+            longitude: None,
+            signed_in_at: self.id.time,
+            anomaly: Default::default(),
+            cio_company_id: company.id,
+        }
+    }
+}
+
+/// Get the successful sign-ins of the Google login report since a time.
+async fn list_google_sign_ins(company: &Company, since: DateTime<Utc>) -> Result<Vec<NewSignInEvent>> {
+    let token = company.google_reports_token().await?;
+    let client = reqwest::Client::new();
+
+    let mut events = Vec::new();
+    let mut page_token = String::new();
+    loop {
+        crate::rate_limit::acquire(crate::rate_limit::Provider::Google).await;
+        let mut query = vec![
+            ("eventName", "login_success".to_string()),
+            ("startTime", since.to_rfc3339()),
+            ("maxResults", "1000".to_string()),
+        ];
+        if !page_token.is_empty() {
+            query.push(("pageToken", page_token.to_string()));
+        }
+
+        let resp = client
+            .get("https://admin.googleapis.com/admin/reports/v1/activity/users/all/applications/login")
+            .bearer_auth(&token)
+            .query(&query)
+            .send()
+            .await?;
+        if resp.status() != StatusCode::OK {
+            bail!(
+                "listing the Google login report failed, status_code: {}, body: {}",
+                resp.status(),
+                resp.text().await?
+            );
+        }
+
+        let page: GoogleActivities = resp.json().await?;
+        events.extend(page.items.iter().map(|a| a.to_sign_in_event(company)));
+
+        match page.next_page_token {
+            Some(next) if !next.is_empty() => page_token = next,
+            _ => return Ok(events),
+        }
+    }
+}
+
+/// Ingest the sign-ins of a company from Okta and Google, alert the security channel about the
+/// ones that look wrong, and forget the ones we no longer need.
+pub async fn refresh_sign_in_events(db: &Database, company: &Company) -> Result<()> {
+    let mut history = sign_in_events::dsl::sign_in_events
+        .filter(sign_in_events::dsl::cio_company_id.eq(company.id))
+        .filter(sign_in_events::dsl::signed_in_at.gt(Utc::now() - Duration::days(SIGN_IN_EVENT_RETENTION_DAYS)))
+        .order_by(sign_in_events::dsl::signed_in_at)
+        .load_async::<SignInEvent>(db.pool())
+        .await?;
+
+    let since = |provider: &str| -> DateTime<Utc> {
+        history
+            .iter()
+            .filter(|h| h.provider == provider)
+            .map(|h| h.signed_in_at - Duration::hours(SIGN_IN_LATE_EVENT_HOURS))
+            .max()
+            .unwrap_or_else(|| Utc::now() - Duration::days(SIGN_IN_INITIAL_LOOKBACK_DAYS))
+    };
+
+    let mut events = Vec::new();
+    if !company.okta_api_key.is_empty() && !company.okta_domain.is_empty() {
+        match list_okta_sign_ins(company, since(SIGN_IN_PROVIDER_OKTA)).await {
+            Ok(okta) => events.extend(okta),
+            Err(e) => warn!("getting the Okta sign-ins of company `{}` failed: {}", company.name, e),
+        }
+    }
+    if company.has_google_service_account() {
+        match list_google_sign_ins(company, since(SIGN_IN_PROVIDER_GOOGLE)).await {
+            Ok(google) => events.extend(google),
+            Err(e) => warn!(
+                "getting the Google sign-ins of company `{}` failed: {}",
+                company.name, e
+            ),
+        }
+    }
+    events.sort_by_key(|e| e.signed_in_at);
+
+    let mut anomalies = 0;
+    for mut event in events {
+        if event.email.is_empty()
+            || history
+                .iter()
+                .any(|h| h.provider == event.provider && h.event_id == event.event_id)
+        {
+            continue;
+        }
+
+        event.anomaly = detect_sign_in_anomaly(&event, &history).unwrap_or_default();
+        let event = diesel::insert_into(sign_in_events::table)
+            .values(event)
+            .get_result_async::<SignInEvent>(db.pool())
+            .await?;
+
+        if !event.anomaly.is_empty() {
+            anomalies += 1;
+            post_sign_in_anomaly(db, company, &event, &history).await?;
+        }
+        history.push(event);
+    }
+
+    // We only need the recent sign-ins to compare new ones to.
+    diesel::delete(
+        sign_in_events::dsl::sign_in_events
+            .filter(sign_in_events::dsl::cio_company_id.eq(company.id))
+            .filter(sign_in_events::dsl::signed_in_at.lt(Utc::now() - Duration::days(SIGN_IN_EVENT_RETENTION_DAYS))),
+    )
+    .execute_async(db.pool())
+    .await?;
+
+    info!(
+        "ingested the sign-ins of company `{}`, {} looked wrong",
+        company.name, anomalies
+    );
+
+    Ok(())
+}
+
+async fn post_sign_in_anomaly(
+    db: &Database,
+    company: &Company,
+    event: &SignInEvent,
+    history: &[SignInEvent],
+) -> Result<()> {
+    // Sign-ins are not something to share with everyone in the debug channel, the anomaly is
+    // still in the database for whoever looks.
+    if company.slack_channel_security.is_empty() {
+        warn!(
+            "company `{}` has no security channel, not alerting about the sign-in of {}",
+            company.name, event.email
+        );
+        return Ok(());
+    }
+    let channel = company.slack_channel_security.to_string();
+
+    let previous = history
+        .iter()
+        .filter(|h| h.email == event.email)
+        .max_by_key(|h| h.signed_in_at)
+        .map(|h| {
+            format!(
+                "Their previous sign-in was from {} (`{}`) {} through {}.",
+                h.location(),
+                h.ip_address,
+                h.signed_in_at.format("%Y-%m-%d %H:%M UTC"),
+                h.provider
+            )
+        })
+        .unwrap_or_default();
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: format!("Unusual sign-in by {}", event.email),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        "*{}*\n{} signed in from {} (`{}`) {} through {}. {}",
+                        event.anomaly,
+                        event.email,
+                        event.location(),
+                        event.ip_address,
+                        event.signed_in_at.format("%Y-%m-%d %H:%M UTC"),
+                        event.provider,
+                        previous
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{country_code, detect_sign_in_anomaly, NewSignInEvent, SignInEvent};
+
+    fn mock_event(country: &str, coordinates: Option<(f64, f64)>, hour: u32) -> NewSignInEvent {
+        NewSignInEvent {
+            provider: "okta".to_string(),
+            event_id: format!("event-{}", hour),
+            email: "jess@example.com".to_string(),
+            country: country.to_string(),
+            latitude: coordinates.map(|c| c.0),
+            longitude: coordinates.map(|c| c.1),
+            signed_in_at: Utc.ymd(2023, 9, 25).and_hms(hour, 0, 0),
+            ..Default::default()
+        }
+    }
+
+    fn into_history(event: NewSignInEvent) -> SignInEvent {
+        SignInEvent {
+            id: 1,
+            provider: event.provider,
+            event_id: event.event_id,
+            email: event.email,
+            ip_address: event.ip_address,
+            country: event.country,
+            city: event.city,
+            latitude: event.latitude,
+            longitude: event.longitude,
+            signed_in_at: event.signed_in_at,
+            anomaly: event.anomaly,
+            cio_company_id: event.cio_company_id,
+        }
+    }
+
+    #[test]
+    fn test_detect_sign_in_anomaly() {
+        let oakland = Some((37.80, -122.27));
+        let seattle = Some((47.61, -122.33));
+        let london = Some((51.51, -0.13));
+
+        // The first sign-in of someone is what we compare the next ones to.
+        assert_eq!(
+            None,
+            detect_sign_in_anomaly(&mock_event("United States", oakland, 1), &[])
+        );
+
+        let history = vec![into_history(mock_event("United States", oakland, 1))];
+        // Oakland to Seattle in five hours is a flight.
+        assert_eq!(
+            None,
+            detect_sign_in_anomaly(&mock_event("United States", seattle, 6), &history)
+        );
+        // Oakland to London in two hours is not.
+        let anomaly = detect_sign_in_anomaly(&mock_event("United Kingdom", london, 3), &history).unwrap();
+        assert!(anomaly.starts_with("impossible travel: 86"), "{}", anomaly);
+        // Google only tells us the code of the country, Okta its name.
+        assert_eq!(None, detect_sign_in_anomaly(&mock_event("US", None, 12), &history));
+        assert_eq!(
+            Some("first sign-in from GB".to_string()),
+            detect_sign_in_anomaly(&mock_event("GB", None, 12), &history)
+        );
+    }
+
+    #[test]
+    fn test_country_code() {
+        assert_eq!("US", country_code("United States"));
+        assert_eq!("GB", country_code("united kingdom"));
+        assert_eq!("GB", country_code("gb"));
+        assert_eq!("Atlantis", country_code("Atlantis"));
+        assert_eq!("", country_code(""));
+    }
+}
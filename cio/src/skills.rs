@@ -0,0 +1,283 @@
+use anyhow::{bail, Result};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    configs::{User, UserConfig, Users},
+    db::Database,
+};
+
+/// The most entries we accept in each list of a skills profile.
+static MAX_SKILLS_PROFILE_ENTRIES: usize = 30;
+/// The longest entry we accept in a skills profile.
+static MAX_SKILLS_PROFILE_ENTRY_LENGTH: usize = 60;
+/// The most people we list in reply to `/who-knows`.
+static MAX_WHO_KNOWS_RESULTS: usize = 10;
+
+/// The skills, interests, and previous domains a user shares so others can find them, when
+/// staffing a project or an interview panel.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SkillsProfile {
+    #[serde(default)]
+    pub skills: Vec<String>,
+    #[serde(default)]
+    pub interests: Vec<String>,
+    #[serde(default)]
+    pub previous_domains: Vec<String>,
+}
+
+impl From<&User> for SkillsProfile {
+    fn from(user: &User) -> Self {
+        SkillsProfile {
+            skills: user.skills.clone(),
+            interests: user.interests.clone(),
+            previous_domains: user.previous_domains.clone(),
+        }
+    }
+}
+
+/// Split a list typed into the skills profile modal into its entries. Entries are separated
+/// by commas or new lines.
+pub fn parse_skills_list(text: &str) -> Vec<String> {
+    text.split(|c| c == ',' || c == '\n')
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+impl SkillsProfile {
+    /// Trim the whitespace around the entries, and remove the empty and duplicate ones.
+    pub fn normalize(&mut self) {
+        for list in [&mut self.skills, &mut self.interests, &mut self.previous_domains] {
+            let mut entries: Vec<String> = Vec::new();
+            for entry in list.iter() {
+                let entry = entry.trim();
+                if !entry.is_empty() && !entries.iter().any(|e| e.eq_ignore_ascii_case(entry)) {
+                    entries.push(entry.to_string());
+                }
+            }
+            *list = entries;
+        }
+    }
+
+    /// Validate a list of the profile, `name` is what we call it in the error.
+    pub fn validate_list(name: &str, list: &[String]) -> Result<()> {
+        if list.len() > MAX_SKILLS_PROFILE_ENTRIES {
+            bail!("{} can have at most {} entries", name, MAX_SKILLS_PROFILE_ENTRIES);
+        }
+        if let Some(entry) = list.iter().find(|e| e.len() > MAX_SKILLS_PROFILE_ENTRY_LENGTH) {
+            bail!(
+                "{} entry `{}` must be at most {} characters",
+                name,
+                entry,
+                MAX_SKILLS_PROFILE_ENTRY_LENGTH
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Validate the profile before we save it.
+    pub fn validate(&self) -> Result<()> {
+        SkillsProfile::validate_list("skills", &self.skills)?;
+        SkillsProfile::validate_list("interests", &self.interests)?;
+        SkillsProfile::validate_list("previous domains", &self.previous_domains)?;
+
+        Ok(())
+    }
+}
+
+impl User {
+    /// Update the user's skills profile from the Slack modal.
+    pub async fn update_skills_profile(&self, db: &Database, mut profile: SkillsProfile) -> Result<User> {
+        profile.normalize();
+        profile.validate()?;
+
+        if profile == SkillsProfile::from(self) {
+            return Ok(self.clone());
+        }
+
+        let mut config: UserConfig = self.clone().into();
+        config.skills = profile.skills;
+        config.interests = profile.interests;
+        config.previous_domains = profile.previous_domains;
+
+        let user = config.upsert(db).await?;
+        info!("user `{}` updated their skills profile", user.username);
+
+        Ok(user)
+    }
+}
+
+/// A user who knows about a topic, with the entries of their skills profile that matched it.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SkillMatch {
+    pub username: String,
+    pub name: String,
+    pub email: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interests: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_domains: Vec<String>,
+    /// How well the user matched, higher is better.
+    pub score: i32,
+}
+
+/// Match a user's skills profile against a topic. Skills weigh the most, then previous
+/// domains, then interests, and exact matches weigh more than partial ones.
+fn match_user(user: &User, topic: &str) -> Option<SkillMatch> {
+    let mut score = 0;
+    let mut matching = |entries: &[String], weight: i32| -> Vec<String> {
+        entries
+            .iter()
+            .filter(|e| {
+                let e = e.to_lowercase();
+                if e == topic {
+                    score += weight * 2;
+                    true
+                } else if e.contains(topic) || topic.contains(&e) {
+                    score += weight;
+                    true
+                } else {
+                    false
+                }
+            })
+            .cloned()
+            .collect()
+    };
+
+    let skills = matching(&user.skills, 3);
+    let previous_domains = matching(&user.previous_domains, 2);
+    let interests = matching(&user.interests, 1);
+    if score == 0 {
+        return None;
+    }
+
+    Some(SkillMatch {
+        username: user.username.to_string(),
+        name: user.full_name(),
+        email: user.email.to_string(),
+        skills,
+        interests,
+        previous_domains,
+        score,
+    })
+}
+
+/// Rank the users whose skills profile matches the topic, best match first.
+pub fn rank_skill_matches(users: &[User], topic: &str) -> Vec<SkillMatch> {
+    let topic = topic.trim().to_lowercase();
+    if topic.is_empty() {
+        return vec![];
+    }
+
+    let mut matches: Vec<SkillMatch> = users
+        .iter()
+        .filter(|u| !u.is_system_account())
+        .filter_map(|u| match_user(u, &topic))
+        .collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.username.cmp(&b.username)));
+
+    matches
+}
+
+/// Find the users of a company who know about a topic.
+pub async fn who_knows(db: &Database, cio_company_id: i32, topic: &str) -> Result<Vec<SkillMatch>> {
+    let users = Users::get_from_db(db, cio_company_id).await?;
+
+    Ok(rank_skill_matches(&users.0, topic))
+}
+
+/// Convert the users who know about a topic into a Slack message.
+pub fn who_knows_slack_message(topic: &str, matches: &[SkillMatch]) -> FormattedMessage {
+    let text = if matches.is_empty() {
+        format!(
+            "Nobody has `{}` in their skills profile yet. You can fill in yours with `/skills`.",
+            topic
+        )
+    } else {
+        let people = matches
+            .iter()
+            .take(MAX_WHO_KNOWS_RESULTS)
+            .map(|m| {
+                let entries = [&m.skills, &m.previous_domains, &m.interests]
+                    .iter()
+                    .flat_map(|l| l.iter().map(|e| e.as_str()))
+                    .collect::<Vec<&str>>()
+                    .join(", ");
+                format!("• *{}* ({}): {}", m.name, m.email, entries)
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!("*Who knows about `{}`*\n{}", topic, people)
+    };
+
+    FormattedMessage {
+        channel: Default::default(),
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text,
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+        attachments: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::tests::mock_user;
+
+    #[test]
+    fn test_normalize_skills_profile() {
+        let mut profile = SkillsProfile {
+            skills: parse_skills_list("Rust, rust ,\nPostgres,,"),
+            interests: vec![" ".to_string()],
+            previous_domains: vec!["Storage".to_string()],
+        };
+        profile.normalize();
+
+        assert_eq!(vec!["Rust".to_string(), "Postgres".to_string()], profile.skills);
+        assert!(profile.interests.is_empty());
+        assert!(profile.validate().is_ok());
+
+        profile.skills = vec!["x".repeat(MAX_SKILLS_PROFILE_ENTRY_LENGTH + 1)];
+        assert!(profile.validate().is_err());
+    }
+
+    #[test]
+    fn test_rank_skill_matches() {
+        let mut jess = mock_user();
+        jess.username = "jess".to_string();
+        jess.skills = vec!["Rust".to_string(), "Postgres".to_string()];
+
+        let mut sam = mock_user();
+        sam.username = "sam".to_string();
+        sam.interests = vec!["rust embedded".to_string()];
+
+        let mut bot = mock_user();
+        bot.username = "bot".to_string();
+        bot.typev = "system account".to_string();
+        bot.skills = vec!["rust".to_string()];
+
+        let matches = rank_skill_matches(&[sam, bot, jess], " RUST ");
+        assert_eq!(
+            vec!["jess", "sam"],
+            matches.iter().map(|m| m.username.as_str()).collect::<Vec<&str>>()
+        );
+        assert_eq!(vec!["Rust".to_string()], matches[0].skills);
+        assert_eq!(vec!["rust embedded".to_string()], matches[1].interests);
+
+        assert!(rank_skill_matches(&[mock_user()], "").is_empty());
+    }
+}
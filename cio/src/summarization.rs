@@ -0,0 +1,131 @@
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// The default endpoint for the summarization provider. Any API that speaks the
+/// OpenAI chat completions format can be used by setting `SUMMARIZATION_API_URL`.
+pub static DEFAULT_SUMMARIZATION_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+pub static DEFAULT_SUMMARIZATION_MODEL: &str = "gpt-3.5-turbo";
+
+/// A provider that can summarize free-form text.
+#[async_trait]
+pub trait SummarizationProvider: Send + Sync {
+    /// Summarize the text, following the given instructions.
+    async fn summarize(&self, instructions: &str, text: &str) -> Result<String>;
+}
+
+/// A summarization provider backed by a chat completions API.
+pub struct ChatCompletionsProvider {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+}
+
+impl ChatCompletionsProvider {
+    pub fn new(url: &str, api_key: &str, model: &str) -> Self {
+        ChatCompletionsProvider {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+        }
+    }
+
+    /// Create a new provider from the `SUMMARIZATION_API_KEY`, `SUMMARIZATION_API_URL`
+    /// and `SUMMARIZATION_MODEL` environment variables.
+    pub fn new_from_env() -> Result<Self> {
+        let api_key = std::env::var("SUMMARIZATION_API_KEY")
+            .map_err(|_| anyhow!("SUMMARIZATION_API_KEY must be set to use the summarization provider"))?;
+        let url = std::env::var("SUMMARIZATION_API_URL").unwrap_or_else(|_| DEFAULT_SUMMARIZATION_API_URL.to_string());
+        let model = std::env::var("SUMMARIZATION_MODEL").unwrap_or_else(|_| DEFAULT_SUMMARIZATION_MODEL.to_string());
+
+        Ok(ChatCompletionsProvider::new(&url, &api_key, &model))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ChatMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ChatCompletionRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ChatCompletionChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ChatCompletionResponse {
+    #[serde(default)]
+    choices: Vec<ChatCompletionChoice>,
+}
+
+fn parse_chat_completion(body: &str) -> Result<String> {
+    let resp: ChatCompletionResponse = serde_json::from_str(body)?;
+
+    match resp.choices.into_iter().next() {
+        Some(choice) if !choice.message.content.trim().is_empty() => Ok(choice.message.content.trim().to_string()),
+        _ => bail!("summarization provider returned an empty summary"),
+    }
+}
+
+#[async_trait]
+impl SummarizationProvider for ChatCompletionsProvider {
+    async fn summarize(&self, instructions: &str, text: &str) -> Result<String> {
+        let body = ChatCompletionRequest {
+            model: self.model.to_string(),
+            messages: vec![
+                ChatMessage {
+                    role: "system".to_string(),
+                    content: instructions.to_string(),
+                },
+                ChatMessage {
+                    role: "user".to_string(),
+                    content: text.to_string(),
+                },
+            ],
+        };
+
+        let resp = self
+            .client
+            .post(&self.url)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            bail!("summarization request failed: status code: {}, body: {}", status, text);
+        }
+
+        parse_chat_completion(&text)
+    }
+}
+
+/// Get the configured summarization provider.
+pub fn get_summarization_provider() -> Result<Box<dyn SummarizationProvider>> {
+    Ok(Box::new(ChatCompletionsProvider::new_from_env()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_chat_completion;
+
+    #[test]
+    fn test_parse_chat_completion() {
+        let body =
+            r#"{"id":"1","choices":[{"index":0,"message":{"role":"assistant","content":"  The rack lost power.\n"}}]}"#;
+        assert_eq!("The rack lost power.", parse_chat_completion(body).unwrap());
+
+        assert!(parse_chat_completion(r#"{"choices":[]}"#).is_err());
+    }
+}
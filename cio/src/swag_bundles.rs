@@ -0,0 +1,419 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::Utc;
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    airtable::AIRTABLE_SWAG_BUNDLES_TABLE,
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    schema::swag_bundles,
+    shipments::{NewOutboundShipment, OutboundShipment},
+    swag_inventory::{SwagInventoryItem, SwagInventoryItems},
+    swag_shipments::reserve_swag_shipment_items,
+    swag_store::OrderItem,
+};
+
+/// A named set of swag we send together, like the new hire kit or the conference kit.
+#[db {
+    new_struct_name = "SwagBundle",
+    airtable_base = "swag",
+    airtable_table = "AIRTABLE_SWAG_BUNDLES_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "name" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = swag_bundles)]
+pub struct NewSwagBundle {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// One line per item, like the contents of a shipment: `2 x Sticker` or
+    /// `1 x Hoodie, Size: M`. Items without a size get the size asked for the shipment, or
+    /// their only size.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub contents: String,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a SwagBundle.
+#[async_trait]
+impl UpdateAirtableRecord<SwagBundle> for SwagBundle {
+    async fn update_airtable_record(&mut self, _record: SwagBundle) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Sync swag bundles from Airtable.
+pub async fn refresh_swag_bundles(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_swag.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    // Get all the records from Airtable.
+    let results: Vec<airtable_api::Record<SwagBundle>> = company
+        .authenticate_airtable(&company.airtable_base_id_swag)
+        .list_records(&SwagBundle::airtable_table(), "Grid view", vec![])
+        .await?;
+    for bundle_record in results {
+        let mut bundle: NewSwagBundle = bundle_record.fields.into();
+        bundle.cio_company_id = company.id;
+
+        let mut db_bundle = bundle.upsert_in_db(db).await?;
+        db_bundle.airtable_record_id = bundle_record.id.to_string();
+        db_bundle.update(db).await?;
+    }
+
+    SwagBundles::get_from_db(db, company.id)
+        .await?
+        .update_airtable(db)
+        .await?;
+
+    Ok(())
+}
+
+/// A line of the contents of a bundle.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SwagBundleLine {
+    pub quantity: i32,
+    pub item: String,
+    /// Empty if the size is chosen for each shipment.
+    pub size: String,
+}
+
+/// Parse the contents of a bundle.
+pub fn parse_bundle_contents(contents: &str) -> Result<Vec<SwagBundleLine>> {
+    let mut lines = Vec::new();
+    for line in contents.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        let (quantity, rest) = match line.split_once(" x ") {
+            Some((quantity, rest)) => match quantity.trim().parse::<i32>() {
+                Ok(quantity) if quantity > 0 => (quantity, rest),
+                _ => bail!("`{}` does not start with a quantity", line),
+            },
+            // A line without a quantity is a single item.
+            None => (1, line),
+        };
+        let (item, size) = match rest.split_once(", Size:") {
+            Some((item, size)) => (item, size),
+            None => (rest, ""),
+        };
+
+        lines.push(SwagBundleLine {
+            quantity,
+            item: item.trim().to_string(),
+            size: size.trim().to_string(),
+        });
+    }
+
+    Ok(lines)
+}
+
+/// A line of the pick list of a shipment, what to take from the shelves.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct PickListLine {
+    /// The swag inventory item id.
+    pub swag_inventory_item_id: i32,
+    pub item: String,
+    pub size: String,
+    pub quantity: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub barcode: String,
+}
+
+/// Expand the lines of a bundle to the inventory items to pick, with the sizes of the shipment
+/// by item. Lines of the same inventory item are added up. If we do not have enough of
+/// everything, this returns every item we are short of.
+pub fn expand_swag_bundle(
+    lines: &[SwagBundleLine],
+    sizes: &BTreeMap<String, String>,
+    inventory: &[SwagInventoryItem],
+) -> Result<Vec<PickListLine>> {
+    let mut pick_list: Vec<PickListLine> = Vec::new();
+    for line in lines {
+        let sizes_of_item: Vec<&SwagInventoryItem> = inventory
+            .iter()
+            .filter(|i| i.item.trim().eq_ignore_ascii_case(&line.item))
+            .collect();
+        if sizes_of_item.is_empty() {
+            bail!("there is no `{}` in the swag inventory", line.item);
+        }
+
+        let size = if !line.size.is_empty() {
+            line.size.to_string()
+        } else if let Some((_, size)) = sizes
+            .iter()
+            .find(|(item, _)| item.trim().eq_ignore_ascii_case(&line.item))
+        {
+            size.trim().to_string()
+        } else if sizes_of_item.len() == 1 {
+            sizes_of_item[0].size.to_string()
+        } else {
+            bail!("the shipment needs a size for `{}`", line.item);
+        };
+
+        let inventory_item = match sizes_of_item.iter().find(|i| i.size.trim().eq_ignore_ascii_case(&size)) {
+            Some(inventory_item) => inventory_item,
+            None => bail!("there is no `{}` in size `{}` in the swag inventory", line.item, size),
+        };
+
+        match pick_list
+            .iter_mut()
+            .find(|p| p.swag_inventory_item_id == inventory_item.id)
+        {
+            Some(pick) => pick.quantity += line.quantity,
+            None => pick_list.push(PickListLine {
+                swag_inventory_item_id: inventory_item.id,
+                item: inventory_item.item.to_string(),
+                size: inventory_item.size.to_string(),
+                quantity: line.quantity,
+                barcode: inventory_item.barcode.to_string(),
+            }),
+        }
+    }
+
+    let mut shortages = Vec::new();
+    for pick in &pick_list {
+        let stock = inventory
+            .iter()
+            .find(|i| i.id == pick.swag_inventory_item_id)
+            .map(|i| i.current_stock)
+            .unwrap_or_default();
+        if stock < pick.quantity {
+            shortages.push(format!(
+                "`{}` in size `{}` (need {}, have {})",
+                pick.item, pick.size, pick.quantity, stock
+            ));
+        }
+    }
+    if !shortages.is_empty() {
+        bail!("we do not have enough {}", shortages.join(", "));
+    }
+
+    Ok(pick_list)
+}
+
+/// Format a pick list like the contents of a shipment.
+pub fn format_pick_list(pick_list: &[PickListLine]) -> String {
+    pick_list
+        .iter()
+        .map(|p| format!("{} x {}, Size: {}", p.quantity, p.item, p.size))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Who to send a bundle to.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SwagBundleRecipient {
+    pub name: String,
+    pub email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub phone: String,
+    pub street_1: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub street_2: String,
+    pub city: String,
+    pub state: String,
+    pub zipcode: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub country: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+/// A shipment of a bundle, and what to pick for it.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SwagBundleShipment {
+    pub shipment: OutboundShipment,
+    pub pick_list: Vec<PickListLine>,
+}
+
+/// Create a shipment of a bundle. We make sure we have everything in stock before creating the
+/// shipment, then post the pick list to the swag channel. The bundle comes out of the inventory
+/// with the shipment, so two shipments can not both be promised the same stock.
+pub async fn create_shipment_from_bundle(
+    db: &Database,
+    company: &Company,
+    bundle_name: &str,
+    sizes: &BTreeMap<String, String>,
+    recipient: &SwagBundleRecipient,
+) -> Result<SwagBundleShipment> {
+    let bundle = match SwagBundle::get_from_db(db, company.id, bundle_name.trim().to_string()).await {
+        Some(bundle) => bundle,
+        None => bail!("there is no swag bundle named `{}`", bundle_name),
+    };
+
+    let inventory: Vec<SwagInventoryItem> = SwagInventoryItems::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .collect();
+    let pick_list = expand_swag_bundle(&parse_bundle_contents(&bundle.contents)?, sizes, &inventory)?;
+
+    let shipment = NewOutboundShipment {
+        created_time: Utc::now(),
+        name: recipient.name.to_string(),
+        email: recipient.email.to_string(),
+        phone: recipient.phone.to_string(),
+        street_1: recipient.street_1.to_string(),
+        street_2: recipient.street_2.to_string(),
+        city: recipient.city.to_string(),
+        state: recipient.state.to_string(),
+        zipcode: recipient.zipcode.to_string(),
+        country: recipient.country.to_string(),
+        notes: format!("The {} swag bundle. {}", bundle.name, recipient.notes)
+            .trim()
+            .to_string(),
+        // This will be populated when we update shippo.
+        address_formatted: Default::default(),
+        latitude: Default::default(),
+        longitude: Default::default(),
+        contents: format_pick_list(&pick_list),
+        // The rest will be populated when we update shippo and create a label.
+        carrier: Default::default(),
+        pickup_date: None,
+        delivered_time: None,
+        shipped_time: None,
+        provider: "Shippo".to_string(),
+        provider_id: Default::default(),
+        status: "Queued".to_string(),
+        tracking_link: Default::default(),
+        oxide_tracking_link: Default::default(),
+        tracking_number: Default::default(),
+        tracking_status: Default::default(),
+        cost: Default::default(),
+        label_link: Default::default(),
+        eta: None,
+        service_level: Default::default(),
+        overdue: false,
+        messages: Default::default(),
+        geocode_cache: Default::default(),
+        local_pickup: false,
+        link_to_package_pickup: Default::default(),
+        cio_company_id: company.id,
+    };
+
+    // Add the shipment to the database.
+    let mut new_shipment = shipment.upsert_in_db(db).await?;
+    let items: Vec<OrderItem> = pick_list
+        .iter()
+        .map(|pick| OrderItem {
+            id: pick.swag_inventory_item_id,
+            quantity: pick.quantity,
+        })
+        .collect();
+    // The stock could have gone since we checked it, if it did, the shipment can not go.
+    if let Err(e) = reserve_swag_shipment_items(db, company, &new_shipment, &items).await {
+        new_shipment.delete_from_db(db).await?;
+        bail!("shipping the {} swag bundle failed: {}", bundle.name, e);
+    }
+    // Create or update the shipment from shippo.
+    new_shipment.create_or_get_shippo_shipment(db).await?;
+    // Update airtable and the database again.
+    new_shipment.update(db).await?;
+
+    // Send an email to the person that we are sending them a package and what is in it.
+    new_shipment.send_email_to_recipient_pre_shipping(db).await?;
+
+    let msg = FormattedMessage {
+        channel: company.slack_channel_swag.to_string(),
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: format!("Pick list: {} for {}", bundle.name, new_shipment.name),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: pick_list
+                        .iter()
+                        .map(|p| format!("• {} x *{}*, Size: {} `{}`", p.quantity, p.item, p.size, p.barcode))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(SwagBundleShipment {
+        shipment: new_shipment,
+        pick_list,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{expand_swag_bundle, format_pick_list, parse_bundle_contents};
+    use crate::swag_inventory::SwagInventoryItem;
+    use crate::tests::mock_record;
+
+    fn mock_inventory_item(id: i32, item: &str, size: &str, current_stock: i32) -> SwagInventoryItem {
+        let mut inventory_item: SwagInventoryItem = mock_record(json!({}));
+        inventory_item.id = id;
+        inventory_item.item = item.to_string();
+        inventory_item.size = size.to_string();
+        inventory_item.current_stock = current_stock;
+        inventory_item
+    }
+
+    #[test]
+    fn test_expand_swag_bundle() {
+        let inventory = vec![
+            mock_inventory_item(1, "Hoodie", "M", 3),
+            mock_inventory_item(2, "Hoodie", "L", 0),
+            mock_inventory_item(3, "Sticker", "N/A", 100),
+            mock_inventory_item(4, "Hat", "N/A", 1),
+        ];
+        let lines = parse_bundle_contents("1 x Hoodie\n2 x Sticker\n\nSticker\n1 x Hat, Size: N/A").unwrap();
+        assert_eq!(4, lines.len());
+        assert_eq!("", lines[0].size);
+        assert_eq!(1, lines[2].quantity);
+        assert!(parse_bundle_contents("a x Hoodie").is_err());
+
+        // The hoodie needs a size, the stickers only have one.
+        assert!(expand_swag_bundle(&lines, &Default::default(), &inventory).is_err());
+
+        let mut sizes = BTreeMap::new();
+        sizes.insert("hoodie".to_string(), "M".to_string());
+        let pick_list = expand_swag_bundle(&lines, &sizes, &inventory).unwrap();
+        assert_eq!(3, pick_list.len());
+        assert_eq!(3, pick_list[1].quantity);
+        assert_eq!(
+            "1 x Hoodie, Size: M\n3 x Sticker, Size: N/A\n1 x Hat, Size: N/A",
+            format_pick_list(&pick_list)
+        );
+
+        // We are out of large hoodies, and the second hat is one too many.
+        sizes.insert("hoodie".to_string(), "L".to_string());
+        let lines = parse_bundle_contents("1 x Hoodie\n2 x Hat").unwrap();
+        let err = expand_swag_bundle(&lines, &sizes, &inventory).unwrap_err().to_string();
+        assert!(err.contains("`Hoodie` in size `L` (need 1, have 0)"));
+        assert!(err.contains("`Hat` in size `N/A` (need 2, have 1)"));
+    }
+}
@@ -1,7 +1,7 @@
-use std::io::BufWriter;
+use std::{fmt, io::BufWriter, ops::DerefMut};
 
-use anyhow::{bail, Result};
-use async_bb8_diesel::AsyncRunQueryDsl;
+use anyhow::{anyhow, bail, Result};
+use async_bb8_diesel::{AsyncConnection, AsyncRunQueryDsl};
 use async_trait::async_trait;
 use barcoders::{
     generators::{image::Image, svg::SVG},
@@ -24,12 +24,15 @@ use slack_chat_api::{
 };
 
 use crate::{
-    airtable::{AIRTABLE_BARCODE_SCANS_TABLE, AIRTABLE_SWAG_INVENTORY_ITEMS_TABLE, AIRTABLE_SWAG_ITEMS_TABLE},
+    airtable::{
+        AIRTABLE_BARCODE_SCANS_TABLE, AIRTABLE_SWAG_INVENTORY_ITEMS_TABLE, AIRTABLE_SWAG_ITEMS_TABLE,
+        AIRTABLE_SWAG_ITEM_VARIANTS_TABLE,
+    },
     companies::Company,
     core::UpdateAirtableRecord,
     db::Database,
-    printer::Printer,
-    schema::{barcode_scans, swag_inventory_items, swag_items},
+    printer::{find_print_url, PrintTarget, Printer, PRINTER_KIND_ZEBRA},
+    schema::{barcode_scans, swag_inventory_items, swag_item_variants, swag_items},
 };
 
 // The zebra label printer's dpi is 300.
@@ -170,6 +173,24 @@ pub struct NewSwagInventoryItem {
     /// This field will be set and updated in Airtable.
     #[serde(default)]
     pub print_barcode_label_quantity: i32,
+    /// When the current stock goes below this, we alert the swag team to order more. Zero if
+    /// we do not reorder the item.
+    /// This field will be set and updated in Airtable.
+    #[serde(default)]
+    pub reorder_threshold: i32,
+    /// The variant of the product in the Printful warehouse, if Printful keeps the item and
+    /// ships it.
+    /// This field will be set and updated in Airtable.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub printful_variant_id: String,
+    /// How many of the item someone found when they last counted it by hand.
+    /// This field will be set and updated in Airtable.
+    #[serde(default)]
+    pub counted_stock: i32,
+    /// When someone last counted the item by hand.
+    /// This field will be set and updated in Airtable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counted_at: Option<DateTime<Utc>>,
 
     /// This is populated by Airtable.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -192,11 +213,48 @@ impl UpdateAirtableRecord<SwagInventoryItem> for SwagInventoryItem {
 
         // This is set in airtable so we need to keep it.
         self.print_barcode_label_quantity = record.print_barcode_label_quantity;
+        self.reorder_threshold = record.reorder_threshold;
+        self.printful_variant_id = record.printful_variant_id;
+        self.counted_stock = record.counted_stock;
+        self.counted_at = record.counted_at;
 
         Ok(())
     }
 }
 
+/// Shorten the name of a swag item, or one of its sizes or colors, so it fits in a barcode.
+pub fn abbreviate_swag_name(name: &str) -> String {
+    name.to_uppercase()
+        .replace("FIRST EDITION", "1ED")
+        .replace("SECOND EDITION", "2ED")
+        .replace("THIRD EDITION", "3ED")
+        // TODO: Find another way to do this so that it doesn't break eventually.
+        .replace("FOURTH EDITION", "4ED")
+        .replace(' ', "")
+        .replace('/', "")
+        .replace('(', "")
+        .replace(')', "")
+        .replace('-', "")
+        .replace('\'', "")
+        .replace("UNISEX", "U")
+        .replace("WOMENS", "W")
+        .replace("MENS", "M")
+        .replace("TODDLERS", "T")
+        .replace("YOUTH", "Y")
+        .replace("ONESIE", "B")
+        .replace("MOLESKINE", "MS")
+        .replace("NOTEBOOK", "NB")
+        .replace("TEE", "T")
+        .replace("DIGITALCOMPUTER", "DEC")
+        .replace("TURBOBUTTON", "TURBO")
+        .replace("HOODIE", "HOOD")
+        .replace("SWEATSHIRT", "SWS")
+        .replace("RAMBLER", "R")
+        .replace("RULED", "RULE")
+        .trim()
+        .to_string()
+}
+
 impl NewSwagInventoryItem {
     pub async fn send_slack_notification(&self, db: &Database, company: &Company) -> Result<()> {
         let mut msg: FormattedMessage = self.clone().into();
@@ -209,37 +267,7 @@ impl NewSwagInventoryItem {
     }
 
     pub fn generate_barcode(&self) -> String {
-        let mut barcode = self
-            .name
-            .to_uppercase()
-            .replace("FIRST EDITION", "1ED")
-            .replace("SECOND EDITION", "2ED")
-            .replace("THIRD EDITION", "3ED")
-            // TODO: Find another way to do this so that it doesn't break eventually.
-            .replace("FOURTH EDITION", "4ED")
-            .replace(' ', "")
-            .replace('/', "")
-            .replace('(', "")
-            .replace(')', "")
-            .replace('-', "")
-            .replace('\'', "")
-            .replace("UNISEX", "U")
-            .replace("WOMENS", "W")
-            .replace("MENS", "M")
-            .replace("TODDLERS", "T")
-            .replace("YOUTH", "Y")
-            .replace("ONESIE", "B")
-            .replace("MOLESKINE", "MS")
-            .replace("NOTEBOOK", "NB")
-            .replace("TEE", "T")
-            .replace("DIGITALCOMPUTER", "DEC")
-            .replace("TURBOBUTTON", "TURBO")
-            .replace("HOODIE", "HOOD")
-            .replace("SWEATSHIRT", "SWS")
-            .replace("RAMBLER", "R")
-            .replace("RULED", "RULE")
-            .trim()
-            .to_string();
+        let mut barcode = abbreviate_swag_name(&self.name);
 
         // Add zeros to start of barcode til it is 39 chars long.
         // This makes sure the barcodes are all of uniform length.
@@ -266,6 +294,7 @@ impl NewSwagInventoryItem {
         drive_client: &GoogleDrive,
         drive_id: &str,
         parent_id: &str,
+        template: &LabelTemplate,
     ) -> Result<String> {
         let barcode_value = self.generate_barcode();
 
@@ -288,56 +317,106 @@ impl NewSwagInventoryItem {
             // we do not store a barcode value and then fail to generate images
             self.barcode = barcode_value;
 
-            // Generate the barcode svg and png.
-            let barcode = Code39::new(&self.barcode)?;
-            let png = Image::png(60); // You must specify the height in pixels.
-            let encoded = barcode.encode();
+            let images = upload_barcode_images(
+                drive_client,
+                drive_id,
+                parent_id,
+                template,
+                &self.name,
+                &self.barcode,
+                &[
+                    ("barcode", &self.barcode),
+                    ("item", &self.item),
+                    ("size", &format!("Size: {}", self.size)),
+                ],
+            )
+            .await?;
+            self.barcode_png = images.png;
+            self.barcode_svg = images.svg;
+            self.barcode_pdf_label = images.pdf_label;
+        }
 
-            // Image generators return a Result<Vec<u8>, barcoders::error::Error) of encoded bytes.
-            let png_bytes = png.generate(&encoded[..])?;
-            let mut file_name = format!("{}.png", self.name.replace('/', ""));
+        Ok(self.barcode_pdf_label.to_string())
+    }
 
-            // Create or update the file in the google drive.
-            let png_file = drive_client
-                .files()
-                .create_or_update(drive_id, parent_id, &file_name, "image/png", &png_bytes)
-                .await?;
-            self.barcode_png = format!("https://drive.google.com/uc?export=download&id={}", png_file.id);
+    pub async fn expand(
+        &mut self,
+        drive_client: &GoogleDrive,
+        drive_id: &str,
+        parent_id: &str,
+        template: &LabelTemplate,
+    ) -> Result<String> {
+        self.generate_barcode_images(drive_client, drive_id, parent_id, template)
+            .await
+    }
+}
 
-            // Now do the SVG.
-            let svg = SVG::new(200); // You must specify the height in pixels.
-            let svg_data: String = svg.generate(&encoded)?;
-            let svg_bytes = svg_data.as_bytes();
+/// The links to the images of a barcode in Google Drive.
+pub struct BarcodeImages {
+    pub png: String,
+    pub svg: String,
+    pub pdf_label: String,
+}
 
-            file_name = format!("{}.svg", self.name.replace('/', ""));
+/// Generate the PNG, the SVG and the printable label of a barcode, and upload them to Google
+/// Drive with the name of what they are for.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_barcode_images(
+    drive_client: &GoogleDrive,
+    drive_id: &str,
+    parent_id: &str,
+    template: &LabelTemplate,
+    name: &str,
+    barcode: &str,
+    fields: &[(&str, &str)],
+) -> Result<BarcodeImages> {
+    let name = name.replace('/', "");
+
+    // Generate the barcode svg and png.
+    let barcode = Code39::new(barcode)?;
+    let png = Image::png(60); // You must specify the height in pixels.
+    let encoded = barcode.encode();
+
+    // Image generators return a Result<Vec<u8>, barcoders::error::Error) of encoded bytes.
+    let png_bytes = png.generate(&encoded[..])?;
+    let mut file_name = format!("{}.png", name);
+
+    // Create or update the file in the google drive.
+    let png_file = drive_client
+        .files()
+        .create_or_update(drive_id, parent_id, &file_name, "image/png", &png_bytes)
+        .await?;
 
-            // Create or update the file in the google drive.
-            let svg_file = drive_client
-                .files()
-                .create_or_update(drive_id, parent_id, &file_name, "image/svg+xml", svg_bytes)
-                .await?;
-            self.barcode_svg = format!("https://drive.google.com/uc?export=download&id={}", svg_file.id);
-
-            // Generate the barcode label.
-            let im = Image::jpeg(400);
-            let b = im.generate(&encoded[..])?;
-            let label_bytes =
-                generate_pdf_barcode_label(&b, &self.barcode, &self.item, &format!("Size: {}", self.size))?;
-            file_name = format!("{} - Barcode Label.pdf", self.name.replace('/', ""));
-            // Create or update the file in the google drive.
-            let label_file = drive_client
-                .files()
-                .create_or_update(drive_id, parent_id, &file_name, "application/pdf", &label_bytes)
-                .await?;
-            self.barcode_pdf_label = format!("https://drive.google.com/uc?export=download&id={}", label_file.id);
-        }
+    // Now do the SVG.
+    let svg = SVG::new(200); // You must specify the height in pixels.
+    let svg_data: String = svg.generate(&encoded)?;
+    let svg_bytes = svg_data.as_bytes();
 
-        Ok(self.barcode_pdf_label.to_string())
-    }
+    file_name = format!("{}.svg", name);
 
-    pub async fn expand(&mut self, drive_client: &GoogleDrive, drive_id: &str, parent_id: &str) -> Result<String> {
-        self.generate_barcode_images(drive_client, drive_id, parent_id).await
-    }
+    // Create or update the file in the google drive.
+    let svg_file = drive_client
+        .files()
+        .create_or_update(drive_id, parent_id, &file_name, "image/svg+xml", svg_bytes)
+        .await?;
+
+    // Generate the barcode label.
+    let im = Image::jpeg(400);
+    let b = im.generate(&encoded[..])?;
+    let logo = template.logo().await?;
+    let label_bytes = generate_pdf_barcode_label(template, logo.as_deref(), &b, None, fields)?;
+    file_name = format!("{} - Barcode Label.pdf", name);
+    // Create or update the file in the google drive.
+    let label_file = drive_client
+        .files()
+        .create_or_update(drive_id, parent_id, &file_name, "application/pdf", &label_bytes)
+        .await?;
+
+    Ok(BarcodeImages {
+        png: format!("https://drive.google.com/uc?export=download&id={}", png_file.id),
+        svg: format!("https://drive.google.com/uc?export=download&id={}", svg_file.id),
+        pdf_label: format!("https://drive.google.com/uc?export=download&id={}", label_file.id),
+    })
 }
 
 /// Convert the swag inventory item into a Slack message.
@@ -407,45 +486,165 @@ impl SwagInventoryItem {
     }
 }
 
-// Get the bytes for a pdf barcode label.
+fn default_label_width_inches() -> f64 {
+    3.0
+}
+
+fn default_label_height_inches() -> f64 {
+    2.0
+}
+
+fn default_label_margin_mm() -> f64 {
+    1.0
+}
+
+fn default_label_font_size() -> f64 {
+    10.0
+}
+
+fn default_label_line_height() -> f64 {
+    12.0
+}
+
+/// The layout of the barcode labels a company prints, so they match its printer and branding.
+/// It is stored as JSON on the company, and every field is optional: the default is the 3"x2"
+/// label with the Oxide logo.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct LabelTemplate {
+    #[serde(default = "default_label_width_inches")]
+    pub width_inches: f64,
+    #[serde(default = "default_label_height_inches")]
+    pub height_inches: f64,
+    #[serde(default = "default_label_margin_mm")]
+    pub margin_mm: f64,
+    /// The link to the logo printed at the top of the label, as a PNG or JPEG. The Oxide logo
+    /// is printed if it is empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub logo_url: String,
+    #[serde(default)]
+    pub hide_logo: bool,
+    /// The fields printed under the logo, in order, like `barcode` or `name`. All the fields
+    /// of the label are printed if it is empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    #[serde(default = "default_label_font_size")]
+    pub font_size: f64,
+    #[serde(default = "default_label_line_height")]
+    pub line_height: f64,
+    #[serde(default)]
+    pub hide_qr_code: bool,
+}
+
+impl Default for LabelTemplate {
+    fn default() -> Self {
+        LabelTemplate {
+            width_inches: default_label_width_inches(),
+            height_inches: default_label_height_inches(),
+            margin_mm: default_label_margin_mm(),
+            logo_url: String::new(),
+            hide_logo: false,
+            fields: vec![],
+            font_size: default_label_font_size(),
+            line_height: default_label_line_height(),
+            hide_qr_code: false,
+        }
+    }
+}
+
+impl LabelTemplate {
+    /// Return the label template of the company, the default one if it does not have one.
+    pub fn for_company(company: &Company) -> Self {
+        if company.label_template.trim().is_empty() {
+            return LabelTemplate::default();
+        }
+
+        serde_json::from_str(&company.label_template).unwrap_or_else(|e| {
+            warn!("invalid label template for company `{}`: {}", company.name, e);
+            LabelTemplate::default()
+        })
+    }
+
+    /// Get the bytes of the logo to print, None if the label has no logo.
+    pub async fn logo(&self) -> Result<Option<Vec<u8>>> {
+        if self.hide_logo {
+            return Ok(None);
+        }
+        if self.logo_url.trim().is_empty() {
+            return Ok(Some(include_bytes!("oxide_logo.png").to_vec()));
+        }
+
+        let resp = reqwest::get(self.logo_url.trim()).await?.error_for_status()?;
+        Ok(Some(resp.bytes().await?.to_vec()))
+    }
+
+    /// Pick the lines of text to print from the fields of a label, by name.
+    pub fn lines<'a>(&self, fields: &[(&str, &'a str)]) -> Vec<&'a str> {
+        if self.fields.is_empty() {
+            return fields.iter().map(|(_, value)| *value).collect();
+        }
+
+        self.fields
+            .iter()
+            .filter_map(|name| {
+                fields
+                    .iter()
+                    .find(|(n, _)| n.eq_ignore_ascii_case(name.trim()))
+                    .map(|(_, value)| *value)
+            })
+            .collect()
+    }
+}
+
+// Get the bytes for a pdf barcode label laid out from the template. `fields` are the name and
+// value of the text the label can print. If a QR code is given, it is printed to the right of
+// the text.
 pub fn generate_pdf_barcode_label(
+    template: &LabelTemplate,
+    logo_bytes: Option<&[u8]>,
     image_bytes: &[u8],
-    text_line_1: &str,
-    text_line_2: &str,
-    text_line_3: &str,
+    qr_code_bytes: Option<&[u8]>,
+    fields: &[(&str, &str)],
 ) -> Result<Vec<u8>> {
-    let pdf_margin = Mm(1.0);
-    let pdf_width = Mm(3.0 * 25.4);
-    let pdf_height = Mm(2.0 * 25.4);
-    let (doc, page1, layer1) = PdfDocument::new(text_line_2, pdf_width, pdf_height, "Layer 1");
+    let lines = template.lines(fields);
+    let pdf_margin = Mm(template.margin_mm);
+    let pdf_width = Mm(template.width_inches * 25.4);
+    let pdf_height = Mm(template.height_inches * 25.4);
+    let title = fields
+        .get(1)
+        .or_else(|| fields.first())
+        .map(|(_, v)| *v)
+        .unwrap_or_default();
+    let (doc, page1, layer1) = PdfDocument::new(title, pdf_width, pdf_height, "Layer 1");
     let current_layer = doc.get_page(page1).get_layer(layer1);
 
-    // currently, the only reliable file formats are bmp/jpeg/png
-    // this is an issue of the image library, not a fault of printpdf
-    let logo_bytes = include_bytes!("oxide_logo.png");
-    let logo_image = PdfImage::from_dynamic_image(&image::load_from_memory(logo_bytes)?);
-
-    // We want the logo width to fit.
-    let original_width = logo_image.image.width.into_pt(DPI);
-    let new_width: Pt = (pdf_width - (pdf_margin * 2.0)).into();
-    let width_scale = new_width / original_width;
-    let logo_height: Pt = logo_image.image.height.into_pt(DPI) * width_scale;
-    let logo_height_mm: Mm = From::from(logo_height);
-    // translate x, translate y, rotate, scale x, scale y
-    // rotations and translations are always in relation to the lower left corner
-    logo_image.add_to_layer(
-        current_layer.clone(),
-        printpdf::ImageTransform {
-            translate_x: Some(pdf_margin),
-            translate_y: Some(pdf_height - pdf_margin - logo_height_mm),
-            rotate: None,
-            scale_x: Some(width_scale),
-            scale_y: Some(width_scale),
-            dpi: Some(DPI),
-        },
-    );
+    let mut logo_height_mm = Mm(0.0);
+    if let Some(logo_bytes) = logo_bytes {
+        // currently, the only reliable file formats are bmp/jpeg/png
+        // this is an issue of the image library, not a fault of printpdf
+        let logo_image = PdfImage::from_dynamic_image(&image::load_from_memory(logo_bytes)?);
+
+        // We want the logo width to fit.
+        let original_width = logo_image.image.width.into_pt(DPI);
+        let new_width: Pt = (pdf_width - (pdf_margin * 2.0)).into();
+        let width_scale = new_width / original_width;
+        let logo_height: Pt = logo_image.image.height.into_pt(DPI) * width_scale;
+        logo_height_mm = From::from(logo_height);
+        // translate x, translate y, rotate, scale x, scale y
+        // rotations and translations are always in relation to the lower left corner
+        logo_image.add_to_layer(
+            current_layer.clone(),
+            printpdf::ImageTransform {
+                translate_x: Some(pdf_margin),
+                translate_y: Some(pdf_height - pdf_margin - logo_height_mm),
+                rotate: None,
+                scale_x: Some(width_scale),
+                scale_y: Some(width_scale),
+                dpi: Some(DPI),
+            },
+        );
+    }
 
-    let line_height = 12.0;
+    let line_height = template.line_height;
     let h = Pt(line_height * 2.0);
     let hmm: Mm = From::from(h);
 
@@ -458,18 +657,38 @@ pub fn generate_pdf_barcode_label(
     // in a `begin_text_section()` and `end_text_section()` wrapper
     current_layer.begin_text_section();
 
-    current_layer.set_font(&font, line_height - 2.0);
+    current_layer.set_font(&font, template.font_size);
     current_layer.set_text_cursor(pdf_margin, pdf_height - (pdf_margin * 2.0) - hmm - logo_height_mm);
     current_layer.set_line_height(line_height);
 
-    current_layer.write_text(text_line_1, &font);
-    current_layer.add_line_break();
-    current_layer.write_text(text_line_2, &font);
-    current_layer.add_line_break();
-    current_layer.write_text(text_line_3, &font);
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            current_layer.add_line_break();
+        }
+        current_layer.write_text(*line, &font);
+    }
 
     current_layer.end_text_section();
 
+    if let Some(qr_code_bytes) = qr_code_bytes.filter(|_| !template.hide_qr_code) {
+        let qr_code_image = PdfImage::from_dynamic_image(&image::load_from_memory(qr_code_bytes)?);
+        // Make the QR code as tall as the lines of text.
+        let qr_code_size: Pt = Pt(line_height * lines.len().max(1) as f64);
+        let qr_code_size_mm: Mm = From::from(qr_code_size);
+        let scale = qr_code_size / qr_code_image.image.height.into_pt(DPI);
+        qr_code_image.add_to_layer(
+            current_layer.clone(),
+            printpdf::ImageTransform {
+                translate_x: Some(pdf_width - pdf_margin - qr_code_size_mm),
+                translate_y: Some(pdf_height - (pdf_margin * 2.0) - logo_height_mm - qr_code_size_mm),
+                rotate: None,
+                scale_x: Some(scale),
+                scale_y: Some(scale),
+                dpi: Some(DPI),
+            },
+        );
+    }
+
     let barcode_image = PdfImage::from_dynamic_image(&image::load_from_memory(image_bytes)?);
     // We want the barcode width to fit.
     let original_width = barcode_image.image.width.into_pt(DPI);
@@ -500,15 +719,88 @@ pub fn generate_pdf_barcode_label(
     Ok(bw.into_inner()?)
 }
 
+/// Rasterize a label PDF to a PNG, at the resolution of the printer so a preview shows the
+/// barcode as it will be printed.
+pub async fn rasterize_pdf_label(pdf: &[u8]) -> Result<Vec<u8>> {
+    let name = format!("label-preview-{}", uuid::Uuid::new_v4());
+    let mut path = std::env::temp_dir();
+    path.push(format!("{}.pdf", name));
+    let mut output = std::env::temp_dir();
+    output.push(&name);
+    let png = output.with_extension("png");
+
+    tokio::fs::write(&path, pdf).await?;
+
+    // `-singlefile` writes the first page to `<output>.png`, the labels are a single page.
+    let cmd_output = tokio::task::spawn_blocking({
+        let (path, output) = (path.clone(), output.clone());
+        move || {
+            std::process::Command::new("pdftoppm")
+                .args(&["-png", "-singlefile", "-r", &DPI.to_string()])
+                .arg(&path)
+                .arg(&output)
+                .output()
+        }
+    })
+    .await??;
+
+    let result = tokio::fs::read(&png).await;
+
+    // Delete the temporary files, if they exist.
+    for p in [&path, &png] {
+        if p.exists() {
+            tokio::fs::remove_file(p).await?;
+        }
+    }
+
+    match result {
+        Ok(bytes) => Ok(bytes),
+        Err(e) => bail!(
+            "rasterizing the label failed: {}\nstderr: {}",
+            e,
+            String::from_utf8_lossy(&cmd_output.stderr)
+        ),
+    }
+}
+
+/// Send a label to a zebra printer.
+async fn send_label_to_printer(printer_url: &str, url: String, quantity: i32) -> Result<()> {
+    let printer_key = Printer::key();
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(printer_url)
+        .bearer_auth(printer_key)
+        .body(
+            json!(cio_api_types::swag_inventory::PrintRequest {
+                url,
+                quantity,
+                content: String::new(),
+            })
+            .to_string(),
+        )
+        .send()
+        .await?;
+    match resp.status() {
+        StatusCode::ACCEPTED => (),
+        s => {
+            bail!("print zebra status_code: {}, body: {}", s, resp.text().await?);
+        }
+    };
+
+    Ok(())
+}
+
 impl SwagInventoryItem {
-    /// Send the label to our printer.
-    pub async fn print_label(&self, db: &Database) -> Result<()> {
+    /// Send the label to the printer for the target.
+    pub async fn print_label(&self, db: &Database, target: &PrintTarget) -> Result<()> {
         let company = self.company(db).await?;
 
-        if company.printer_url.is_empty() {
+        let printer_url = match find_print_url(db, &company, PRINTER_KIND_ZEBRA, target).await? {
+            Some(printer_url) => printer_url,
             // Return early.
-            return Ok(());
-        }
+            None => return Ok(()),
+        };
 
         let url = if self.barcode_pdf_label.trim().is_empty() {
             // Get the URL to the google item directly.
@@ -524,36 +816,18 @@ impl SwagInventoryItem {
             let parent_id = drive_client.files().create_folder(&drive_id, "", "swag").await?;
 
             let mut sw: NewSwagInventoryItem = From::from(self.clone());
-            sw.expand(&drive_client, &drive_id, &parent_id).await?
+            sw.expand(
+                &drive_client,
+                &drive_id,
+                &parent_id,
+                &LabelTemplate::for_company(&company),
+            )
+            .await?
         } else {
             self.barcode_pdf_label.trim().to_string()
         };
 
-        let printer_url = format!("{}/zebra", company.printer_url);
-        let printer_key = Printer::key();
-
-        let client = reqwest::Client::new();
-        let resp = client
-            .post(&printer_url)
-            .bearer_auth(printer_key)
-            .body(
-                json!(cio_api_types::swag_inventory::PrintRequest {
-                    url,
-                    quantity: self.print_barcode_label_quantity,
-                    content: String::new(),
-                })
-                .to_string(),
-            )
-            .send()
-            .await?;
-        match resp.status() {
-            StatusCode::ACCEPTED => (),
-            s => {
-                bail!("print zebra status_code: {}, body: {}", s, resp.text().await?);
-            }
-        };
-
-        Ok(())
+        send_label_to_printer(&printer_url, url, self.print_barcode_label_quantity).await
     }
 
     pub async fn get_item(&self, db: &Database) -> Option<SwagItem> {
@@ -636,16 +910,30 @@ pub async fn refresh_swag_inventory_items(db: &Database, company: &Company) -> R
         .authenticate_airtable(&company.airtable_base_id_swag)
         .list_records(&SwagInventoryItem::airtable_table(), "Grid view", vec![])
         .await?;
+    let template = LabelTemplate::for_company(company);
     for inventory_item_record in results {
         let mut inventory_item: NewSwagInventoryItem = inventory_item_record.fields.into();
-        inventory_item.expand(&drive_client, &drive_id, &parent_id).await?;
+        inventory_item
+            .expand(&drive_client, &drive_id, &parent_id, &template)
+            .await?;
         inventory_item.cio_company_id = company.id;
 
         // TODO: send a slack notification for a new item (?)
 
-        let mut db_inventory_item = inventory_item.upsert_in_db(db).await?;
-        db_inventory_item.airtable_record_id = inventory_item_record.id.to_string();
-        db_inventory_item.update(db).await?;
+        let existing =
+            SwagInventoryItem::get_from_db(db, inventory_item.item.to_string(), inventory_item.size.to_string()).await;
+        match existing {
+            Some(existing) if existing.cio_company_id == company.id => {
+                keep_swag_inventory_stock(db, existing.id, inventory_item, inventory_item_record.id.to_string())
+                    .await?;
+            }
+            _ => {
+                // A new item starts with the stock it has in Airtable.
+                let mut db_inventory_item = inventory_item.upsert_in_db(db).await?;
+                db_inventory_item.airtable_record_id = inventory_item_record.id.to_string();
+                db_inventory_item.update_in_db(db).await?;
+            }
+        }
     }
 
     SwagInventoryItems::get_from_db(db, company.id)
@@ -656,6 +944,326 @@ pub async fn refresh_swag_inventory_items(db: &Database, company: &Company) -> R
     Ok(())
 }
 
+/// Update an inventory item from Airtable, but keep the stock we have in the database. Scans and
+/// shipments change the stock in the database first, so what Airtable has can be behind. The
+/// row is locked while we update it, so none of those changes get lost in between.
+async fn keep_swag_inventory_stock(
+    db: &Database,
+    id: i32,
+    mut inventory_item: NewSwagInventoryItem,
+    airtable_record_id: String,
+) -> Result<()> {
+    db.pool()
+        .transaction(move |conn| {
+            let target = swag_inventory_items::dsl::swag_inventory_items.filter(swag_inventory_items::dsl::id.eq(id));
+            inventory_item.current_stock = target
+                .select(swag_inventory_items::dsl::current_stock)
+                .for_update()
+                .first::<i32>(conn.deref_mut())?;
+
+            diesel::update(target)
+                .set((
+                    &inventory_item,
+                    swag_inventory_items::dsl::airtable_record_id.eq(airtable_record_id),
+                ))
+                .execute(conn.deref_mut())
+                .map_err(anyhow::Error::from)
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[db {
+    new_struct_name = "SwagItemVariant",
+    airtable_base = "swag",
+    airtable_table = "AIRTABLE_SWAG_ITEM_VARIANTS_TABLE",
+    match_on = {
+        "sku" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = swag_item_variants)]
+pub struct NewSwagItemVariant {
+    /// The name of the swag item this is a variant of.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub item: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub size: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub color: String,
+    /// We generate the SKU from the item, the color and the size if it is empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sku: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        serialize_with = "airtable_api::barcode_format_as_string::serialize",
+        deserialize_with = "airtable_api::barcode_format_as_string::deserialize"
+    )]
+    pub barcode: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub barcode_png: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub barcode_svg: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "airtable_api::attachment_format_as_string::deserialize"
+    )]
+    pub barcode_pdf_label: String,
+    /// The price of the variant, in dollars. Zero if we do not sell it.
+    /// This field will be set and updated in Airtable.
+    #[serde(default)]
+    pub price: f32,
+    #[serde(default)]
+    pub current_stock: i32,
+    /// The quantity of labels to print.
+    /// This field will be set and updated in Airtable.
+    #[serde(default)]
+    pub print_barcode_label_quantity: i32,
+
+    /// This is populated by Airtable.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub link_to_item: Vec<String>,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a SwagItemVariant.
+#[async_trait]
+impl UpdateAirtableRecord<SwagItemVariant> for SwagItemVariant {
+    async fn update_airtable_record(&mut self, record: SwagItemVariant) -> Result<()> {
+        if !record.link_to_item.is_empty() {
+            self.link_to_item = record.link_to_item;
+        }
+
+        // This is set in airtable so we need to keep it.
+        self.price = record.price;
+        self.print_barcode_label_quantity = record.print_barcode_label_quantity;
+
+        Ok(())
+    }
+}
+
+impl NewSwagItemVariant {
+    /// Return the name of the variant, like `Oxide Tee - Black - M`.
+    pub fn name(&self) -> String {
+        [&self.item, &self.color, &self.size]
+            .iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" - ")
+    }
+
+    /// Return the SKU of the variant, generating one from the item, the color and the size if
+    /// it does not have one.
+    pub fn generate_sku(&self) -> String {
+        if !self.sku.trim().is_empty() {
+            return self.sku.trim().to_uppercase();
+        }
+
+        [&self.item, &self.color, &self.size]
+            .iter()
+            .map(|s| abbreviate_swag_name(s))
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Generate the barcode and the label of the variant. The barcode is the SKU, so every
+    /// SKU gets its own label.
+    pub async fn expand(
+        &mut self,
+        drive_client: &GoogleDrive,
+        drive_id: &str,
+        parent_id: &str,
+        template: &LabelTemplate,
+    ) -> Result<String> {
+        if self.item.trim().is_empty() {
+            // Return early.
+            return Ok(String::new());
+        }
+
+        self.sku = self.generate_sku();
+        if self.barcode == self.sku
+            && !self.barcode_png.is_empty()
+            && !self.barcode_svg.is_empty()
+            && !self.barcode_pdf_label.is_empty()
+        {
+            return Ok(self.barcode_pdf_label.to_string());
+        }
+        self.barcode = self.sku.to_string();
+
+        let price = if self.price > 0.0 {
+            format!("${:.2}", self.price)
+        } else {
+            String::new()
+        };
+        let images = upload_barcode_images(
+            drive_client,
+            drive_id,
+            parent_id,
+            template,
+            &self.name(),
+            &self.barcode,
+            &[
+                ("barcode", &self.barcode),
+                ("item", &self.item),
+                ("color", &format!("Color: {}", self.color)),
+                ("size", &format!("Size: {}", self.size)),
+                ("sku", &format!("SKU: {}", self.sku)),
+                ("price", &price),
+            ],
+        )
+        .await?;
+        self.barcode_png = images.png;
+        self.barcode_svg = images.svg;
+        self.barcode_pdf_label = images.pdf_label;
+
+        Ok(self.barcode_pdf_label.to_string())
+    }
+}
+
+impl SwagItemVariant {
+    /// Send the label of the variant to the printer for the target.
+    pub async fn print_label(&self, db: &Database, target: &PrintTarget) -> Result<()> {
+        let company = self.company(db).await?;
+
+        let printer_url = match find_print_url(db, &company, PRINTER_KIND_ZEBRA, target).await? {
+            Some(printer_url) => printer_url,
+            // Return early.
+            None => return Ok(()),
+        };
+
+        let url = if self.barcode_pdf_label.trim().is_empty() {
+            let drive_client = company.authenticate_google_drive(db).await?;
+            let shared_drive = drive_client.drives().get_by_name("Automated Documents").await?;
+            let drive_id = shared_drive.id.to_string();
+            let parent_id = drive_client.files().create_folder(&drive_id, "", "swag").await?;
+
+            let mut variant: NewSwagItemVariant = From::from(self.clone());
+            variant
+                .expand(
+                    &drive_client,
+                    &drive_id,
+                    &parent_id,
+                    &LabelTemplate::for_company(&company),
+                )
+                .await?
+        } else {
+            self.barcode_pdf_label.trim().to_string()
+        };
+
+        send_label_to_printer(&printer_url, url, self.print_barcode_label_quantity).await
+    }
+}
+
+/// Sync swag item variants from Airtable.
+pub async fn refresh_swag_item_variants(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_swag.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    // Initialize the Google Drive client.
+    let drive_client = company.authenticate_google_drive(db).await?;
+
+    // The labels go with the ones of the inventory items: "Automated Documents"/"swag".
+    let shared_drive = drive_client.drives().get_by_name("Automated Documents").await?;
+    let drive_id = shared_drive.id.to_string();
+    let parent_id = drive_client.files().create_folder(&drive_id, "", "swag").await?;
+
+    // Get all the records from Airtable.
+    let results: Vec<airtable_api::Record<SwagItemVariant>> = company
+        .authenticate_airtable(&company.airtable_base_id_swag)
+        .list_records(&SwagItemVariant::airtable_table(), "Grid view", vec![])
+        .await?;
+    let template = LabelTemplate::for_company(company);
+    for variant_record in results {
+        let mut variant: NewSwagItemVariant = variant_record.fields.into();
+        variant.cio_company_id = company.id;
+        // One bad SKU should not hold up the other variants.
+        if let Err(e) = refresh_swag_item_variant(
+            db,
+            &drive_client,
+            &drive_id,
+            &parent_id,
+            &template,
+            &mut variant,
+            &variant_record.id,
+        )
+        .await
+        {
+            warn!("refreshing swag item variant `{}` failed: {}", variant.name(), e);
+        }
+    }
+
+    SwagItemVariants::get_from_db(db, company.id)
+        .await?
+        .update_airtable(db)
+        .await?;
+
+    Ok(())
+}
+
+async fn refresh_swag_item_variant(
+    db: &Database,
+    drive_client: &GoogleDrive,
+    drive_id: &str,
+    parent_id: &str,
+    template: &LabelTemplate,
+    variant: &mut NewSwagItemVariant,
+    airtable_record_id: &str,
+) -> Result<()> {
+    variant.expand(drive_client, drive_id, parent_id, template).await?;
+
+    match SwagItemVariant::get_from_db(db, variant.sku.to_string()).await {
+        Some(existing) if existing.cio_company_id == variant.cio_company_id => {
+            // Scans change the stock in the database first, so keep it like we do for the
+            // inventory items.
+            let id = existing.id;
+            let mut variant = variant.clone();
+            let airtable_record_id = airtable_record_id.to_string();
+            db.pool()
+                .transaction(move |conn| {
+                    let target = swag_item_variants::dsl::swag_item_variants.filter(swag_item_variants::dsl::id.eq(id));
+                    variant.current_stock = target
+                        .select(swag_item_variants::dsl::current_stock)
+                        .for_update()
+                        .first::<i32>(conn.deref_mut())?;
+
+                    diesel::update(target)
+                        .set((
+                            &variant,
+                            swag_item_variants::dsl::airtable_record_id.eq(airtable_record_id),
+                        ))
+                        .execute(conn.deref_mut())
+                        .map_err(anyhow::Error::from)
+                })
+                .await?;
+        }
+        _ => {
+            // A new variant starts with the stock it has in Airtable.
+            let mut db_variant = variant.upsert_in_db(db).await?;
+            db_variant.airtable_record_id = airtable_record_id.to_string();
+            db_variant.update(db).await?;
+        }
+    }
+
+    Ok(())
+}
+
 #[db {
     new_struct_name = "BarcodeScan",
     airtable_base = "swag",
@@ -686,6 +1294,12 @@ pub struct NewBarcodeScan {
     /// This is populated by Airtable.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub link_to_item: Vec<String>,
+    /// What the scan did, `receive` or `ship`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub action: String,
+    /// How many of the item the scan received or shipped.
+    #[serde(default)]
+    pub quantity: i32,
     /// The CIO company ID.
     #[serde(default)]
     pub cio_company_id: i32,
@@ -737,6 +1351,8 @@ impl BarcodeScan {
                     link_to_item: swag_inventory_item.link_to_item,
                     barcode: barcode.to_string(),
                     name: swag_inventory_item.name.to_string(),
+                    action: SwagBarcodeScanAction::Ship.to_string(),
+                    quantity: 1,
                     cio_company_id: swag_inventory_item.cio_company_id,
                 };
 
@@ -750,6 +1366,190 @@ impl BarcodeScan {
     }
 }
 
+/// What a scan of a swag barcode in the warehouse does to the stock.
+#[derive(Debug, Clone, Copy, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwagBarcodeScanAction {
+    /// We got more of the item.
+    Receive,
+    /// We sent some of the item out.
+    Ship,
+}
+
+impl fmt::Display for SwagBarcodeScanAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SwagBarcodeScanAction::Receive => write!(f, "receive"),
+            SwagBarcodeScanAction::Ship => write!(f, "ship"),
+        }
+    }
+}
+
+impl SwagBarcodeScanAction {
+    /// How much the stock changes for a quantity.
+    pub fn stock_change(&self, quantity: i32) -> i32 {
+        match self {
+            SwagBarcodeScanAction::Receive => quantity,
+            SwagBarcodeScanAction::Ship => -quantity,
+        }
+    }
+}
+
+fn default_swag_barcode_scan_quantity() -> i32 {
+    1
+}
+
+/// A scan of a swag barcode in the warehouse.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SwagBarcodeScan {
+    pub barcode: String,
+    pub action: SwagBarcodeScanAction,
+    /// How many of the item were received or shipped, one by default.
+    #[serde(default = "default_swag_barcode_scan_quantity")]
+    pub quantity: i32,
+}
+
+/// Receive or ship swag by its barcode. The stock is changed in a single update so scans at the
+/// same time all count, and we never ship more than we have. Every scan is kept as a barcode
+/// scan for the record, in the same transaction as the change to the stock. Scanning the SKU of
+/// a variant changes the stock of the variant and of its inventory item.
+pub async fn scan_swag_barcode(db: &Database, company: &Company, scan: &SwagBarcodeScan) -> Result<SwagInventoryItem> {
+    if scan.quantity < 1 {
+        bail!("a barcode scan has to be for at least one item, not {}", scan.quantity);
+    }
+
+    let barcode = scan.barcode.trim().to_uppercase();
+    let change = scan.action.stock_change(scan.quantity);
+    // When shipping, the stock has to cover what we ship.
+    let min_stock = if change < 0 { -change } else { i32::MIN };
+
+    let cio_company_id = company.id;
+    let action = scan.action;
+    let quantity = scan.quantity;
+    let scanned = barcode.to_string();
+    let (mut swag_inventory_item, variant, mut barcode_scan) = db
+        .pool()
+        .transaction(move |conn| {
+            let inventory_items = swag_inventory_items::dsl::swag_inventory_items
+                .filter(swag_inventory_items::dsl::cio_company_id.eq(cio_company_id));
+
+            // The barcode is either the one of an inventory item, or the SKU of one of its
+            // variants. A variant also moves the stock of the inventory item it is a variant of.
+            let (id, variant) = match inventory_items
+                .filter(swag_inventory_items::dsl::barcode.eq(scanned.to_string()))
+                .select(swag_inventory_items::dsl::id)
+                .first::<i32>(conn.deref_mut())
+                .optional()?
+            {
+                Some(id) => (id, None),
+                None => {
+                    let variants = swag_item_variants::dsl::swag_item_variants
+                        .filter(swag_item_variants::dsl::cio_company_id.eq(cio_company_id))
+                        .filter(swag_item_variants::dsl::barcode.eq(scanned.to_string()));
+                    let variant =
+                        match diesel::update(variants.filter(swag_item_variants::dsl::current_stock.ge(min_stock)))
+                            .set(
+                                swag_item_variants::dsl::current_stock
+                                    .eq(swag_item_variants::dsl::current_stock + change),
+                            )
+                            .get_result::<SwagItemVariant>(conn.deref_mut())
+                            .optional()?
+                        {
+                            Some(variant) => variant,
+                            None => {
+                                return match variants.first::<SwagItemVariant>(conn.deref_mut()).optional()? {
+                                    Some(variant) => Err(anyhow!(
+                                        "cannot ship {} of {}, we only have {}",
+                                        quantity,
+                                        NewSwagItemVariant::from(variant.clone()).name(),
+                                        variant.current_stock
+                                    )),
+                                    None => Err(anyhow!("could not find inventory item with barcode {}", scanned)),
+                                };
+                            }
+                        };
+
+                    match inventory_items
+                        .filter(swag_inventory_items::dsl::item.eq(variant.item.to_string()))
+                        .filter(swag_inventory_items::dsl::size.eq(variant.size.to_string()))
+                        .select(swag_inventory_items::dsl::id)
+                        .first::<i32>(conn.deref_mut())
+                        .optional()?
+                    {
+                        Some(id) => (id, Some(variant)),
+                        None => {
+                            return Err(anyhow!(
+                                "could not find the inventory item of variant {}",
+                                NewSwagItemVariant::from(variant).name()
+                            ))
+                        }
+                    }
+                }
+            };
+
+            let target = swag_inventory_items::dsl::swag_inventory_items.filter(swag_inventory_items::dsl::id.eq(id));
+            let updated = diesel::update(target.filter(swag_inventory_items::dsl::current_stock.ge(min_stock)))
+                .set(swag_inventory_items::dsl::current_stock.eq(swag_inventory_items::dsl::current_stock + change))
+                .get_result::<SwagInventoryItem>(conn.deref_mut())
+                .optional()?;
+            let swag_inventory_item = match updated {
+                Some(swag_inventory_item) => swag_inventory_item,
+                None => {
+                    let swag_inventory_item = target.first::<SwagInventoryItem>(conn.deref_mut())?;
+                    return Err(anyhow!(
+                        "cannot ship {} of {}, we only have {}",
+                        quantity,
+                        swag_inventory_item.name,
+                        swag_inventory_item.current_stock
+                    ));
+                }
+            };
+
+            // Every scan is its own row.
+            let barcode_scan = diesel::insert_into(barcode_scans::table)
+                .values(NewBarcodeScan {
+                    time: Utc::now(),
+                    item: swag_inventory_item.item.to_string(),
+                    size: swag_inventory_item.size.to_string(),
+                    link_to_item: swag_inventory_item.link_to_item.clone(),
+                    barcode: scanned,
+                    name: match &variant {
+                        Some(variant) => NewSwagItemVariant::from(variant.clone()).name(),
+                        None => swag_inventory_item.name.to_string(),
+                    },
+                    action: action.to_string(),
+                    quantity,
+                    cio_company_id,
+                })
+                .get_result::<BarcodeScan>(conn.deref_mut())?;
+
+            Ok::<_, anyhow::Error>((swag_inventory_item, variant, barcode_scan))
+        })
+        .await?;
+    info!(
+        "{} {} of {} by barcode, we now have {}",
+        scan.action, scan.quantity, swag_inventory_item.name, swag_inventory_item.current_stock
+    );
+
+    // Let the swag channel know, from the stock we had before the scan.
+    let mut before = swag_inventory_item.clone();
+    before.current_stock -= change;
+    before
+        .send_slack_notification_if_inventory_changed(db, company, swag_inventory_item.current_stock)
+        .await?;
+
+    // The database already has the new stock and the scan, Airtable only needs to catch up.
+    swag_inventory_item.upsert_in_airtable(db).await?;
+    if let Some(mut variant) = variant {
+        variant.upsert_in_airtable(db).await?;
+    }
+    let record = barcode_scan.create_in_airtable(db).await?;
+    barcode_scan.airtable_record_id = record.id.to_string();
+    barcode_scan.update_in_db(db).await?;
+
+    Ok(swag_inventory_item)
+}
+
 pub async fn refresh_barcode_scans(db: &Database, company: &Company) -> Result<()> {
     if company.airtable_base_id_swag.is_empty() {
         // Return early.
@@ -763,3 +1563,59 @@ pub async fn refresh_barcode_scans(db: &Database, company: &Company) -> Result<(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{NewSwagItemVariant, SwagBarcodeScan, SwagBarcodeScanAction};
+
+    #[test]
+    fn test_generate_sku() {
+        let mut variant = NewSwagItemVariant {
+            item: "Oxide Unisex Tee".to_string(),
+            size: "M".to_string(),
+            color: "Black".to_string(),
+            sku: String::new(),
+            barcode: String::new(),
+            barcode_png: String::new(),
+            barcode_svg: String::new(),
+            barcode_pdf_label: String::new(),
+            price: 0.0,
+            current_stock: 0,
+            print_barcode_label_quantity: 0,
+            link_to_item: vec![],
+            cio_company_id: 1,
+        };
+        assert_eq!("Oxide Unisex Tee - Black - M", variant.name());
+        assert_eq!("OXIDEUT-BLACK-M", variant.generate_sku());
+
+        variant.color = String::new();
+        assert_eq!("Oxide Unisex Tee - M", variant.name());
+        assert_eq!("OXIDEUT-M", variant.generate_sku());
+
+        // The SKU from Airtable wins.
+        variant.sku = " ox-tee-m ".to_string();
+        assert_eq!("OX-TEE-M", variant.generate_sku());
+    }
+
+    #[test]
+    fn test_swag_barcode_scan() {
+        let scan: SwagBarcodeScan =
+            serde_json::from_str(r#"{"barcode": "OXIDEUT-BLACK-M", "action": "ship"}"#).unwrap();
+        assert_eq!(
+            SwagBarcodeScan {
+                barcode: "OXIDEUT-BLACK-M".to_string(),
+                action: SwagBarcodeScanAction::Ship,
+                quantity: 1,
+            },
+            scan
+        );
+        assert_eq!(-1, scan.action.stock_change(scan.quantity));
+
+        let scan: SwagBarcodeScan =
+            serde_json::from_str(r#"{"barcode": "OXIDEUT-BLACK-M", "action": "receive", "quantity": 12}"#).unwrap();
+        assert_eq!(12, scan.action.stock_change(scan.quantity));
+        assert_eq!("receive", scan.action.to_string());
+
+        assert!(serde_json::from_str::<SwagBarcodeScan>(r#"{"barcode": "X", "action": "steal"}"#).is_err());
+    }
+}
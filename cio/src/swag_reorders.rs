@@ -0,0 +1,225 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::info;
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    airtable::AIRTABLE_SWAG_REORDERS_TABLE,
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    schema::swag_reorders,
+    swag_inventory::{SwagInventoryItem, SwagInventoryItems},
+};
+
+/// The statuses of a reorder. The swag team sets `Ordered` in Airtable once they ordered more,
+/// and we set `Restocked` once the stock is back over the threshold.
+pub static REORDER_NEEDED_STATUS: &str = "Needed";
+pub static REORDER_ORDERED_STATUS: &str = "Ordered";
+pub static REORDER_RESTOCKED_STATUS: &str = "Restocked";
+
+/// A swag inventory item we need to order more of, a row of the "To Order" table.
+#[db {
+    new_struct_name = "SwagReorder",
+    airtable_base = "swag",
+    airtable_table = "AIRTABLE_SWAG_REORDERS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "name" = "String",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = swag_reorders)]
+pub struct NewSwagReorder {
+    /// The item, size, and day it ran low, like `Hoodie, Size: M (2023-09-25)`.
+    pub name: String,
+    #[serde(default)]
+    pub swag_inventory_item_id: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub item: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub size: String,
+    /// The stock when it ran low.
+    #[serde(default)]
+    pub current_stock: i32,
+    #[serde(default)]
+    pub reorder_threshold: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    pub requested_at: DateTime<Utc>,
+    /// link to another table in Airtable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub link_to_inventory: Vec<String>,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a SwagReorder.
+#[async_trait]
+impl UpdateAirtableRecord<SwagReorder> for SwagReorder {
+    async fn update_airtable_record(&mut self, record: SwagReorder) -> Result<()> {
+        // The swag team marks what they ordered in Airtable, and we only ever move it forward
+        // to restocked.
+        if self.status != REORDER_RESTOCKED_STATUS && !record.status.is_empty() {
+            self.status = record.status;
+        }
+
+        Ok(())
+    }
+}
+
+/// What a check of the stock of an item does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LowStockAction {
+    Nothing,
+    /// The item ran low and nobody asked for more yet.
+    Reorder,
+    /// The item is back over its threshold, its open reorder is done.
+    Restocked,
+}
+
+/// Decide what to do about the stock of an item, given if it has an open reorder.
+pub fn low_stock_action(item: &SwagInventoryItem, has_open_reorder: bool) -> LowStockAction {
+    if item.reorder_threshold <= 0 {
+        return LowStockAction::Nothing;
+    }
+
+    let low = item.current_stock < item.reorder_threshold;
+    match (low, has_open_reorder) {
+        (true, false) => LowStockAction::Reorder,
+        (false, true) => LowStockAction::Restocked,
+        _ => LowStockAction::Nothing,
+    }
+}
+
+/// Check the stock of the swag inventory items against their reorder thresholds. Items that
+/// ran low get a row in the "To Order" table and an alert in Slack, once until they are
+/// restocked.
+pub async fn check_swag_low_stock(db: &Database, company: &Company) -> Result<Vec<SwagReorder>> {
+    let mut reorders: Vec<SwagReorder> = SwagReorders::get_from_db(db, company.id).await?.into_iter().collect();
+
+    let mut new_reorders = Vec::new();
+    for item in SwagInventoryItems::get_from_db(db, company.id).await? {
+        let open = reorders
+            .iter_mut()
+            .find(|r| r.swag_inventory_item_id == item.id && r.status != REORDER_RESTOCKED_STATUS);
+
+        match low_stock_action(&item, open.is_some()) {
+            LowStockAction::Nothing => (),
+            LowStockAction::Restocked => {
+                if let Some(reorder) = open {
+                    info!("swag inventory item `{}` was restocked", reorder.name);
+                    reorder.status = REORDER_RESTOCKED_STATUS.to_string();
+                    reorder.update(db).await?;
+                }
+            }
+            LowStockAction::Reorder => {
+                let size = if item.size.is_empty() {
+                    String::new()
+                } else {
+                    format!(", Size: {}", item.size)
+                };
+                let now = Utc::now();
+                let reorder = NewSwagReorder {
+                    name: format!("{}{} ({})", item.item, size, now.format("%Y-%m-%d")),
+                    swag_inventory_item_id: item.id,
+                    item: item.item.to_string(),
+                    size: item.size.to_string(),
+                    current_stock: item.current_stock,
+                    reorder_threshold: item.reorder_threshold,
+                    status: REORDER_NEEDED_STATUS.to_string(),
+                    requested_at: now,
+                    link_to_inventory: if item.airtable_record_id.is_empty() {
+                        Default::default()
+                    } else {
+                        vec![item.airtable_record_id.to_string()]
+                    },
+                    cio_company_id: company.id,
+                };
+                let reorder = reorder.upsert(db).await?;
+
+                post_low_stock_alert(db, company, &item).await?;
+                info!("swag inventory item `{}` is low on stock", reorder.name);
+                new_reorders.push(reorder);
+            }
+        }
+    }
+
+    Ok(new_reorders)
+}
+
+async fn post_low_stock_alert(db: &Database, company: &Company, item: &SwagInventoryItem) -> Result<()> {
+    let channel = if company.slack_channel_low_stock.is_empty() {
+        company.slack_channel_swag.to_string()
+    } else {
+        company.slack_channel_low_stock.to_string()
+    };
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: format!("Running low on {}", item.item),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        "We have `{}` of *{}* in size `{}` left, below the reorder threshold of `{}`. It is on the \
+                         to order list.",
+                        item.current_stock, item.item, item.size, item.reorder_threshold
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{low_stock_action, LowStockAction};
+    use crate::swag_inventory::SwagInventoryItem;
+    use crate::tests::mock_record;
+
+    #[test]
+    fn test_low_stock_action() {
+        let mut item: SwagInventoryItem = mock_record(json!({}));
+        item.current_stock = 3;
+
+        // Items without a threshold are never reordered.
+        assert_eq!(LowStockAction::Nothing, low_stock_action(&item, false));
+
+        item.reorder_threshold = 5;
+        assert_eq!(LowStockAction::Reorder, low_stock_action(&item, false));
+        // We already asked for more.
+        assert_eq!(LowStockAction::Nothing, low_stock_action(&item, true));
+
+        item.current_stock = 5;
+        assert_eq!(LowStockAction::Restocked, low_stock_action(&item, true));
+        assert_eq!(LowStockAction::Nothing, low_stock_action(&item, false));
+    }
+}
@@ -0,0 +1,360 @@
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    airtable::AIRTABLE_SWAG_REQUESTS_TABLE,
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    schema::swag_requests,
+    swag_bundles::SwagBundleRecipient,
+    swag_inventory::SwagInventoryItem,
+    swag_store::{Order, OrderItem},
+};
+
+/// The statuses of a swag request. The swag team approves or denies requests in Airtable, and
+/// we ship the approved ones.
+pub static SWAG_REQUEST_REQUESTED_STATUS: &str = "Requested";
+pub static SWAG_REQUEST_APPROVED_STATUS: &str = "Approved";
+pub static SWAG_REQUEST_DENIED_STATUS: &str = "Denied";
+pub static SWAG_REQUEST_SHIPPED_STATUS: &str = "Shipped";
+
+/// Swag someone at the company asked for, a row of the "Requests" table.
+#[db {
+    new_struct_name = "SwagRequest",
+    airtable_base = "swag",
+    airtable_table = "AIRTABLE_SWAG_REQUESTS_TABLE",
+    match_on = {
+        "cio_company_id" = "i32",
+        "email" = "String",
+        "requested_at" = "DateTime<Utc>",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = swag_requests)]
+pub struct NewSwagRequest {
+    /// Who to ship the swag to.
+    pub name: String,
+    /// Who asked for the swag.
+    pub email: String,
+    pub item: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub size: String,
+    #[serde(default)]
+    pub quantity: i32,
+    #[serde(default)]
+    pub swag_inventory_item_id: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub phone: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub street_1: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub street_2: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub city: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub state: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub zipcode: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub country: String,
+    /// Why they need the swag, for whoever approves it.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    pub requested_at: DateTime<Utc>,
+    /// The shipment we created once the request was approved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outbound_shipment_id: Option<i32>,
+    /// link to another table in Airtable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub link_to_inventory: Vec<String>,
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for a SwagRequest.
+#[async_trait]
+impl UpdateAirtableRecord<SwagRequest> for SwagRequest {
+    async fn update_airtable_record(&mut self, record: SwagRequest) -> Result<()> {
+        // The swag team approves and denies requests in Airtable, once we shipped it we are
+        // the ones that know best.
+        if self.status != SWAG_REQUEST_SHIPPED_STATUS && !record.status.is_empty() {
+            self.status = record.status;
+        }
+
+        Ok(())
+    }
+}
+
+/// What someone asks for through the swag request portal.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SwagRequestForm {
+    pub item: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub size: String,
+    #[serde(default)]
+    pub quantity: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+    /// Where to ship it to.
+    #[serde(flatten)]
+    pub ship_to: SwagBundleRecipient,
+}
+
+/// Check a swag request form is complete, returning what is wrong with it.
+pub fn validate_swag_request_form(form: &SwagRequestForm) -> Result<()> {
+    let ship_to = &form.ship_to;
+    let mut missing = Vec::new();
+    for (field, value) in [
+        ("item", &form.item),
+        ("name", &ship_to.name),
+        ("street_1", &ship_to.street_1),
+        ("city", &ship_to.city),
+        ("state", &ship_to.state),
+        ("zipcode", &ship_to.zipcode),
+    ] {
+        if value.trim().is_empty() {
+            missing.push(field);
+        }
+    }
+    if !missing.is_empty() {
+        bail!("the swag request is missing `{}`", missing.join("`, `"));
+    }
+
+    if form.quantity < 1 {
+        bail!("the swag request has to be for at least one `{}`", form.item);
+    }
+
+    Ok(())
+}
+
+/// Add a swag request of someone signed in to the approval queue.
+pub async fn create_swag_request(
+    db: &Database,
+    company: &Company,
+    requested_by: &str,
+    form: &SwagRequestForm,
+) -> Result<SwagRequest> {
+    validate_swag_request_form(form)?;
+    if requested_by.trim().is_empty() {
+        bail!("we do not know who is requesting the swag");
+    }
+
+    let item =
+        match SwagInventoryItem::get_from_db(db, form.item.trim().to_string(), form.size.trim().to_string()).await {
+            Some(item) if item.cio_company_id == company.id => item,
+            _ => bail!(
+                "there is no swag inventory item `{}` in size `{}`",
+                form.item,
+                form.size
+            ),
+        };
+    if item.current_stock < form.quantity {
+        bail!(
+            "we only have `{}` of `{}` in size `{}` left",
+            item.current_stock,
+            item.item,
+            item.size
+        );
+    }
+
+    let ship_to = &form.ship_to;
+    let request = NewSwagRequest {
+        name: ship_to.name.trim().to_string(),
+        email: requested_by.trim().to_lowercase(),
+        item: item.item.to_string(),
+        size: item.size.to_string(),
+        quantity: form.quantity,
+        swag_inventory_item_id: item.id,
+        phone: ship_to.phone.trim().to_string(),
+        street_1: ship_to.street_1.trim().to_string(),
+        street_2: ship_to.street_2.trim().to_string(),
+        city: ship_to.city.trim().to_string(),
+        state: ship_to.state.trim().to_string(),
+        zipcode: ship_to.zipcode.trim().to_string(),
+        country: ship_to.country.trim().to_string(),
+        reason: form.reason.trim().to_string(),
+        status: SWAG_REQUEST_REQUESTED_STATUS.to_string(),
+        requested_at: Utc::now(),
+        outbound_shipment_id: None,
+        link_to_inventory: if item.airtable_record_id.is_empty() {
+            Default::default()
+        } else {
+            vec![item.airtable_record_id.to_string()]
+        },
+        cio_company_id: company.id,
+    };
+
+    let request = request.upsert(db).await?;
+    info!(
+        "{} requested {} x {} in size `{}`",
+        request.email, request.quantity, request.item, request.size
+    );
+
+    Ok(request)
+}
+
+/// Get the swag requests someone made, newest first.
+pub async fn list_swag_requests(db: &Database, company: &Company, email: &str) -> Result<Vec<SwagRequest>> {
+    let requests = swag_requests::dsl::swag_requests
+        .filter(swag_requests::dsl::cio_company_id.eq(company.id))
+        .filter(swag_requests::dsl::email.eq(email.trim().to_lowercase()))
+        .order_by(swag_requests::dsl::requested_at.desc())
+        .load_async::<SwagRequest>(db.pool())
+        .await?;
+
+    Ok(requests)
+}
+
+impl SwagRequest {
+    fn to_order(&self) -> Order {
+        Order {
+            name: self.name.to_string(),
+            email: self.email.to_string(),
+            phone: self.phone.to_string(),
+            street_1: self.street_1.to_string(),
+            street_2: self.street_2.to_string(),
+            city: self.city.to_string(),
+            state: self.state.to_string(),
+            zipcode: self.zipcode.to_string(),
+            country: self.country.to_string(),
+            notes: format!(
+                "Requested by {} through the swag request portal. {}",
+                self.email, self.reason
+            )
+            .trim()
+            .to_string(),
+            items: vec![OrderItem {
+                id: self.swag_inventory_item_id,
+                quantity: self.quantity,
+            }],
+            cio_company_id: self.cio_company_id,
+        }
+    }
+
+    /// Ship an approved request. It comes out of the inventory once the shipment has a label.
+    async fn ship(&mut self, db: &Database) -> Result<()> {
+        let item =
+            SwagInventoryItem::get_by_id_for_company(db, self.cio_company_id, self.swag_inventory_item_id).await?;
+        if item.current_stock < self.quantity {
+            bail!(
+                "we only have `{}` of `{}` in size `{}` left",
+                item.current_stock,
+                item.item,
+                item.size
+            );
+        }
+
+        let order = self.to_order();
+        let shipment = order.create_shipment_for_order(db).await?;
+
+        self.outbound_shipment_id = Some(shipment.id);
+        self.status = SWAG_REQUEST_SHIPPED_STATUS.to_string();
+        self.update(db).await?;
+
+        info!("shipped the swag request `{}` of {}", self.id, self.email);
+
+        Ok(())
+    }
+}
+
+/// Sync the approvals of swag requests from Airtable, and create the shipments of the approved
+/// ones.
+pub async fn refresh_swag_requests(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_swag.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    // Get all the records from Airtable.
+    let results: Vec<airtable_api::Record<SwagRequest>> = company
+        .authenticate_airtable(&company.airtable_base_id_swag)
+        .list_records(&SwagRequest::airtable_table(), "Grid view", vec![])
+        .await?;
+    for record in results {
+        // Match the record on the request it was created from, the email and time are
+        // whatever someone typed in Airtable.
+        let mut request = match SwagRequest::get_by_id_for_company(db, company.id, record.fields.id).await {
+            Ok(request) => request,
+            // Requests only come from the portal.
+            Err(_) => continue,
+        };
+        if !request.airtable_record_id.is_empty() && request.airtable_record_id != record.id {
+            warn!(
+                "Airtable record `{}` claims to be the swag request `{}` of record `{}`, skipping it",
+                record.id, request.id, request.airtable_record_id
+            );
+            continue;
+        }
+
+        if request.status == SWAG_REQUEST_SHIPPED_STATUS || record.fields.status.is_empty() {
+            continue;
+        }
+
+        request.status = record.fields.status.to_string();
+        request.airtable_record_id = record.id.to_string();
+        request.update_in_db(db).await?;
+
+        if request.status == SWAG_REQUEST_APPROVED_STATUS && request.outbound_shipment_id.is_none() {
+            // One request we cannot ship should not hold up the others, it stays approved and
+            // we try again the next time.
+            if let Err(e) = request.ship(db).await {
+                warn!("shipping the swag request `{}` failed: {}", request.id, e);
+            }
+        }
+    }
+
+    SwagRequests::get_from_db(db, company.id)
+        .await?
+        .update_airtable(db)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_swag_request_form, SwagRequestForm};
+    use crate::swag_bundles::SwagBundleRecipient;
+
+    #[test]
+    fn test_validate_swag_request_form() {
+        let mut form = SwagRequestForm {
+            item: "Hoodie".to_string(),
+            size: "M".to_string(),
+            quantity: 1,
+            reason: "Going to a conference".to_string(),
+            ship_to: SwagBundleRecipient {
+                name: "Jess".to_string(),
+                email: "jess@example.com".to_string(),
+                street_1: "1 Main St".to_string(),
+                city: "Oakland".to_string(),
+                state: "CA".to_string(),
+                zipcode: "94607".to_string(),
+                ..Default::default()
+            },
+        };
+        assert!(validate_swag_request_form(&form).is_ok());
+
+        form.quantity = 0;
+        assert!(validate_swag_request_form(&form).is_err());
+
+        form.quantity = 2;
+        form.ship_to.street_1 = " ".to_string();
+        form.ship_to.zipcode = String::new();
+        assert_eq!(
+            "the swag request is missing `street_1`, `zipcode`",
+            validate_swag_request_form(&form).unwrap_err().to_string()
+        );
+    }
+}
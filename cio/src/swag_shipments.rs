@@ -0,0 +1,265 @@
+use std::ops::DerefMut;
+
+use anyhow::{anyhow, Result};
+use async_bb8_diesel::{AsyncConnection, AsyncRunQueryDsl};
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    companies::Company,
+    db::Database,
+    schema::{swag_inventory_items, swag_shipment_items},
+    shipment_status::Status,
+    shipments::OutboundShipment,
+    swag_inventory::SwagInventoryItem,
+    swag_store::OrderItem,
+};
+
+/// The swag that is in an outbound shipment. We only take it out of the inventory once the
+/// shipment has a label, so stock we never send does not go missing.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct SwagShipmentItem {
+    pub id: i32,
+    pub outbound_shipment_id: i32,
+    pub swag_inventory_item_id: i32,
+    pub quantity: i32,
+    /// When we took the swag out of the inventory, None until the shipment has a label.
+    pub subtracted_at: Option<DateTime<Utc>>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = swag_shipment_items)]
+struct NewSwagShipmentItem {
+    outbound_shipment_id: i32,
+    swag_inventory_item_id: i32,
+    quantity: i32,
+    subtracted_at: Option<DateTime<Utc>>,
+    cio_company_id: i32,
+}
+
+/// Return if a shipment is on its way, so the swag in it left the inventory. Printful only
+/// gives us a tracking number, and packages picked up locally never get a label.
+pub fn has_left_inventory(shipment: &OutboundShipment) -> bool {
+    if shipment.status == Status::Cancelled.to_string() {
+        return false;
+    }
+
+    !shipment.label_link.trim().is_empty()
+        || !shipment.tracking_number.trim().is_empty()
+        || shipment.status == Status::PickedUp.to_string()
+}
+
+/// Get the swag in a shipment, as the items of the order it came from.
+pub async fn get_swag_shipment_items(db: &Database, shipment: &OutboundShipment) -> Result<Vec<OrderItem>> {
+    let items = swag_shipment_items::dsl::swag_shipment_items
+        .filter(swag_shipment_items::dsl::outbound_shipment_id.eq(shipment.id))
+        .order_by(swag_shipment_items::dsl::id)
+        .load_async::<SwagShipmentItem>(db.pool())
+        .await?;
+
+    Ok(items
+        .into_iter()
+        .map(|item| OrderItem {
+            id: item.swag_inventory_item_id,
+            quantity: item.quantity,
+        })
+        .collect())
+}
+
+/// Link the swag in a shipment to the shipment.
+pub async fn record_swag_shipment_items(db: &Database, shipment: &OutboundShipment, items: &[OrderItem]) -> Result<()> {
+    let rows: Vec<NewSwagShipmentItem> = items
+        .iter()
+        .filter(|item| item.quantity > 0)
+        .map(|item| NewSwagShipmentItem {
+            outbound_shipment_id: shipment.id,
+            swag_inventory_item_id: item.id,
+            quantity: item.quantity,
+            subtracted_at: None,
+            cio_company_id: shipment.cio_company_id,
+        })
+        .collect();
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    diesel::insert_into(swag_shipment_items::table)
+        .values(rows)
+        .execute_async(db.pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Take swag out of the inventory right away, for a shipment we only create if we have all of it,
+/// like a bundle. Each item only comes out if we have enough of it left, in the same statement, so
+/// two shipments can never both take the last of something, and either all of the items come out
+/// or none do. The items are recorded as subtracted, so syncing the shipment does not take them
+/// out again.
+pub async fn reserve_swag_shipment_items(
+    db: &Database,
+    company: &Company,
+    shipment: &OutboundShipment,
+    items: &[OrderItem],
+) -> Result<()> {
+    let rows: Vec<NewSwagShipmentItem> = items
+        .iter()
+        .filter(|item| item.quantity > 0)
+        .map(|item| NewSwagShipmentItem {
+            outbound_shipment_id: shipment.id,
+            swag_inventory_item_id: item.id,
+            quantity: item.quantity,
+            subtracted_at: Some(Utc::now()),
+            cio_company_id: shipment.cio_company_id,
+        })
+        .collect();
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let reserved = rows.clone();
+    db.pool()
+        .transaction(move |conn| {
+            for row in &reserved {
+                let target = swag_inventory_items::dsl::swag_inventory_items
+                    .filter(swag_inventory_items::dsl::id.eq(row.swag_inventory_item_id))
+                    .filter(swag_inventory_items::dsl::cio_company_id.eq(row.cio_company_id))
+                    .filter(swag_inventory_items::dsl::current_stock.ge(row.quantity));
+                let updated = diesel::update(target)
+                    .set(
+                        swag_inventory_items::dsl::current_stock
+                            .eq(swag_inventory_items::dsl::current_stock - row.quantity),
+                    )
+                    .execute(conn.deref_mut())?;
+                if updated == 0 {
+                    return Err(anyhow!(
+                        "we do not have {} left of swag inventory item {}",
+                        row.quantity,
+                        row.swag_inventory_item_id
+                    ));
+                }
+            }
+
+            diesel::insert_into(swag_shipment_items::table)
+                .values(reserved)
+                .execute(conn.deref_mut())
+                .map_err(anyhow::Error::from)
+        })
+        .await?;
+
+    for row in rows {
+        notify_subtracted_swag(db, company, shipment, row.swag_inventory_item_id, row.quantity).await?;
+    }
+
+    Ok(())
+}
+
+/// Take the swag in a shipment out of the inventory once it has a label. Every item is only
+/// ever subtracted once, so this is fine to call on every sync of the shipment.
+pub async fn subtract_shipped_swag(db: &Database, company: &Company, shipment: &OutboundShipment) -> Result<()> {
+    if !has_left_inventory(shipment) {
+        return Ok(());
+    }
+
+    let items = swag_shipment_items::dsl::swag_shipment_items
+        .filter(swag_shipment_items::dsl::outbound_shipment_id.eq(shipment.id))
+        .filter(swag_shipment_items::dsl::subtracted_at.is_null())
+        .load_async::<SwagShipmentItem>(db.pool())
+        .await?;
+
+    for item in items {
+        // Claim the item and take it out of the stock together, so two syncs of the shipment
+        // running at once do not both subtract it, and a change to the stock in between does
+        // not get lost.
+        let subtracted = db
+            .pool()
+            .transaction(move |conn| {
+                let claimed = diesel::update(
+                    swag_shipment_items::dsl::swag_shipment_items
+                        .filter(swag_shipment_items::dsl::id.eq(item.id))
+                        .filter(swag_shipment_items::dsl::subtracted_at.is_null()),
+                )
+                .set(swag_shipment_items::dsl::subtracted_at.eq(Some(Utc::now())))
+                .execute(conn.deref_mut())?;
+                if claimed == 0 {
+                    return Ok(false);
+                }
+
+                // Lock the stock until we commit, so nothing changes it in between.
+                let target = swag_inventory_items::dsl::swag_inventory_items
+                    .filter(swag_inventory_items::dsl::id.eq(item.swag_inventory_item_id));
+                let stock = target
+                    .select(swag_inventory_items::dsl::current_stock)
+                    .for_update()
+                    .first::<i32>(conn.deref_mut())?;
+                // The stores _should_ only allow people to order what is in stock, but just in
+                // case let's make sure this does not go negative.
+                diesel::update(target)
+                    .set(swag_inventory_items::dsl::current_stock.eq((stock - item.quantity).max(0)))
+                    .execute(conn.deref_mut())?;
+
+                Ok::<bool, anyhow::Error>(true)
+            })
+            .await?;
+
+        if subtracted {
+            notify_subtracted_swag(db, company, shipment, item.swag_inventory_item_id, item.quantity).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Tell the swag channel and Airtable about swag we took out of the inventory for a shipment.
+async fn notify_subtracted_swag(
+    db: &Database,
+    company: &Company,
+    shipment: &OutboundShipment,
+    swag_inventory_item_id: i32,
+    quantity: i32,
+) -> Result<()> {
+    let mut swag_inventory_item =
+        SwagInventoryItem::get_by_id_for_company(db, company.id, swag_inventory_item_id).await?;
+    let new = swag_inventory_item.current_stock;
+    // The stock is already taken out in the database, show the change from what it was.
+    swag_inventory_item.current_stock = new + quantity;
+    swag_inventory_item
+        .send_slack_notification_if_inventory_changed(db, company, new)
+        .await?;
+
+    info!(
+        "subtracted `{}` from current stock of `{}` for shipment `{}` making the total now `{}`",
+        quantity, swag_inventory_item.name, shipment.id, swag_inventory_item.current_stock
+    );
+
+    swag_inventory_item.upsert_in_airtable(db).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::has_left_inventory;
+    use crate::shipments::OutboundShipment;
+    use crate::tests::mock_record;
+
+    #[test]
+    fn test_has_left_inventory() {
+        let mut shipment: OutboundShipment = mock_record(json!({"created_time": "2023-11-20T00:00:00Z"}));
+        assert!(!has_left_inventory(&shipment));
+
+        shipment.label_link = "https://shippo.com/label.pdf".to_string();
+        assert!(has_left_inventory(&shipment));
+
+        shipment.status = "Cancelled".to_string();
+        assert!(!has_left_inventory(&shipment));
+
+        shipment.label_link = String::new();
+        shipment.status = "Picked up".to_string();
+        assert!(has_left_inventory(&shipment));
+    }
+}
@@ -1,11 +1,18 @@
 use anyhow::Result;
 
 use chrono::Utc;
-use log::info;
+use log::warn;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{companies::Company, db::Database, shipments::NewOutboundShipment, swag_inventory::SwagInventoryItem};
+use crate::{
+    companies::Company,
+    db::Database,
+    printful::{fulfill_with_printful, printful_items_for_order, PRINTFUL_PROVIDER},
+    shipments::{NewOutboundShipment, OutboundShipment},
+    swag_inventory::SwagInventoryItem,
+    swag_shipments::{record_swag_shipment_items, subtract_shipped_swag},
+};
 
 #[derive(Debug, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
 pub struct Order {
@@ -52,7 +59,8 @@ impl Order {
         let mut contents = String::new();
         for item in &self.items {
             // Get the swag item from the database.
-            let swag_inventory_item = SwagInventoryItem::get_by_id(&db, item.id).await?;
+            let swag_inventory_item =
+                SwagInventoryItem::get_by_id_for_company(&db, self.cio_company_id, item.id).await?;
             contents = format!(
                 "{} x {}, Size: {}\n{}",
                 item.quantity, swag_inventory_item.item, swag_inventory_item.size, contents
@@ -62,51 +70,47 @@ impl Order {
         Ok(contents.trim().to_string())
     }
 
-    pub async fn create_shipment_for_order(&self, db: &Database) -> Result<()> {
+    pub async fn create_shipment_for_order(&self, db: &Database) -> Result<OutboundShipment> {
         // Convert the shipment to an order.
-        let shipment: NewOutboundShipment = self.to_outbound_shipment().await?;
+        let mut shipment: NewOutboundShipment = self.to_outbound_shipment().await?;
+
+        // Swag that Printful keeps ships from their warehouse. We never print a Shippo label
+        // for it, even if creating the Printful order fails.
+        let company = Company::get_from_id(db, self.cio_company_id).await?;
+        let printful_items = printful_items_for_order(db, &company, self).await?;
+        if printful_items.is_some() {
+            shipment.provider = PRINTFUL_PROVIDER.to_string();
+        }
 
         // Add the shipment to the database.
         let mut new_shipment = shipment.upsert_in_db(db).await?;
-        // Create or update the shipment from shippo.
-        new_shipment.create_or_get_shippo_shipment(db).await?;
+        record_swag_shipment_items(db, &new_shipment, &self.items).await?;
+        match printful_items {
+            Some(items) => {
+                // Printful being down should not lose the order, we try again when we sync the
+                // Printful shipments.
+                if let Err(e) = fulfill_with_printful(&company, &mut new_shipment, items).await {
+                    warn!(
+                        "creating the Printful order of shipment `{}` failed, we will try again: {}",
+                        new_shipment.id, e
+                    );
+                }
+            }
+            // Create or update the shipment from shippo.
+            None => new_shipment.create_or_get_shippo_shipment(db).await?,
+        }
         // Update airtable and the database again.
         new_shipment.update(db).await?;
 
+        // Take the swag out of the inventory if it has a label already, otherwise we do
+        // when we sync the shipment.
+        subtract_shipped_swag(db, &company, &new_shipment).await?;
+
         // Send an email to the person that we recieved their order and what they are
         // getting.
         new_shipment.send_email_to_recipient_pre_shipping(db).await?;
 
-        Ok(())
-    }
-
-    pub async fn subtract_order_from_inventory(&self, db: &Database) -> Result<()> {
-        for item in &self.items {
-            // Get the swag item from the database.
-            let mut swag_inventory_item = SwagInventoryItem::get_by_id(db, item.id).await?;
-            let mut new = swag_inventory_item.current_stock - item.quantity;
-            if swag_inventory_item.current_stock < 0 {
-                // TODO: Hopefully this never happens. The store code _should_ only allow people
-                // to order what is in stock but just in case let's make sure this does not
-                // go negative.
-                new = 0;
-            }
-
-            let company = swag_inventory_item.company(db).await?;
-
-            // This will also set the value.
-            swag_inventory_item
-                .send_slack_notification_if_inventory_changed(db, &company, new)
-                .await?;
-
-            info!(
-                "subtracted `{}` from current stock of `{}` making the total now `{}`",
-                item.quantity, swag_inventory_item.name, swag_inventory_item.current_stock
-            );
-            swag_inventory_item.update(db).await?;
-        }
-
-        Ok(())
+        Ok(new_shipment)
     }
 
     pub async fn do_order(&self, db: &Database) -> Result<()> {
@@ -126,14 +130,13 @@ impl Order {
         }
 
         self.create_shipment_for_order(db).await?;
-        self.subtract_order_from_inventory(db).await?;
 
         Ok(())
     }
 
     async fn to_outbound_shipment(&self) -> Result<NewOutboundShipment> {
         let db = Database::new().await;
-        let company = Company::get_by_id(&db, self.cio_company_id).await?;
+        let company = Company::get_from_id(&db, self.cio_company_id).await?;
 
         Ok(NewOutboundShipment {
             created_time: Utc::now(),
@@ -170,6 +173,8 @@ impl Order {
             cost: Default::default(),
             label_link: Default::default(),
             eta: None,
+            service_level: Default::default(),
+            overdue: false,
             messages: Default::default(),
             geocode_cache: Default::default(),
             local_pickup: false,
@@ -0,0 +1,42 @@
+/// A record that belongs to a company. The records generated by the `db` macro implement it,
+/// and so should the other tables keyed by `cio_company_id`.
+pub trait CompanyScoped {
+    fn cio_company_id(&self) -> i32;
+
+    /// If the record belongs to the company a request or webhook is for. Check it before
+    /// reading or changing a record found with something else than its company, like its
+    /// number or an id from the request. Records without a company are never anyone's.
+    fn belongs_to(&self, cio_company_id: i32) -> bool {
+        cio_company_id > 0 && self.cio_company_id() == cio_company_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CompanyScoped;
+    use crate::{companies::tests::mock_company, configs::tests::mock_user, rfd::RFDLink};
+
+    #[test]
+    fn test_belongs_to() {
+        let mut user = mock_user();
+        user.cio_company_id = 1;
+        assert!(user.belongs_to(1));
+        assert!(!user.belongs_to(2));
+
+        user.cio_company_id = 0;
+        assert!(!user.belongs_to(0));
+
+        let mut company = mock_company();
+        company.cio_company_id = 1;
+        assert!(!company.belongs_to(3));
+
+        let link = RFDLink {
+            id: 1,
+            rfd_number: 2,
+            referenced_rfd_number: 1,
+            cio_company_id: 2,
+        };
+        assert!(link.belongs_to(2));
+        assert!(!link.belongs_to(1));
+    }
+}
@@ -0,0 +1,14 @@
+//! Fixtures shared by the tests of the modules.
+
+use serde::de::DeserializeOwned;
+
+/// Returns a record with the given fields, and every other field defaulted. Use this rather
+/// than deserializing a record by hand, so the tests keep building as tables get new fields.
+pub fn mock_record<T: DeserializeOwned>(fields: serde_json::Value) -> T {
+    let mut record = json!({"id": 1, "airtable_record_id": ""});
+    if let (Some(record), serde_json::Value::Object(fields)) = (record.as_object_mut(), fields) {
+        record.extend(fields);
+    }
+
+    serde_json::from_value(record).unwrap()
+}
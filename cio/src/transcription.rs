@@ -0,0 +1,463 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use revai::{
+    traits::JobOps,
+    types::{AcceptTranscript, SubmitJobMediaUrlOptions, SubmitJobMediaUrlOptionsAllOf},
+    Client as RevAI,
+};
+use ring::constant_time::verify_slices_are_equal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::companies::Company;
+
+/// The transcription provider we use for a company that has not picked one.
+pub static DEFAULT_TRANSCRIPTION_PROVIDER: &str = "revai";
+
+/// A transcription that was submitted to a provider.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcription {
+    /// The id of the transcription job at the provider.
+    pub id: String,
+    /// The transcript, if the provider transcribed the recording right away. Empty if we need
+    /// to come back for it later.
+    pub transcript: String,
+}
+
+/// A part of a transcript spoken by a single speaker.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct TranscriptSegment {
+    pub speaker: String,
+    /// The offset from the start of the recording, in seconds.
+    pub start_offset: f64,
+    /// The offset of the end of the segment from the start of the recording, in seconds.
+    pub end_offset: f64,
+    pub text: String,
+}
+
+/// A provider that can transcribe meeting recordings.
+#[async_trait]
+pub trait TranscriptionProvider: Send + Sync {
+    /// Submit the contents of a recording to be transcribed.
+    async fn submit(&self, contents: Vec<u8>) -> Result<Transcription>;
+
+    /// Submit a recording to be transcribed by its url. This is used for recordings that
+    /// are too large to upload directly. Providers that call us back when they are done send
+    /// the callback secret along, see `verify_callback_secret`.
+    async fn submit_url(&self, url: &str, callback_secret: &str) -> Result<Transcription>;
+
+    /// Get the plain text transcript for a transcription we submitted before.
+    async fn get_transcript(&self, id: &str) -> Result<String>;
+
+    /// Get the transcript split up by speaker. Providers that can't tell speakers apart
+    /// return no segments.
+    async fn get_transcript_segments(&self, _id: &str) -> Result<Vec<TranscriptSegment>> {
+        Ok(vec![])
+    }
+}
+
+/// A transcription provider backed by Rev.ai.
+pub struct RevAIProvider {
+    client: RevAI,
+    api_key: String,
+    /// The url Rev.ai calls when a job we submitted by url is done, so we don't have to
+    /// wait for the next sync to get the transcript. The secret of the job is added to it.
+    callback_url: String,
+}
+
+impl RevAIProvider {
+    /// Create a new provider from the `REVAI_API_KEY` and `REVAI_CALLBACK_URL` environment
+    /// variables.
+    pub fn new_from_env() -> Self {
+        RevAIProvider {
+            client: RevAI::new_from_env(),
+            api_key: std::env::var("REVAI_API_KEY").unwrap_or_default(),
+            callback_url: std::env::var("REVAI_CALLBACK_URL").unwrap_or_default(),
+        }
+    }
+}
+
+/// The notification Rev.ai sends to the callback url of a job once it is done.
+/// FROM: https://docs.rev.ai/api/asynchronous/webhooks/
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RevAIJobNotification {
+    pub job: RevAIJob,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RevAIJob {
+    pub id: String,
+    /// Either `transcribed` or `failed`.
+    #[serde(default)]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub failure_detail: String,
+}
+
+impl RevAIJob {
+    pub fn is_transcribed(&self) -> bool {
+        self.status == "transcribed"
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.status == "failed"
+    }
+}
+
+/// Generate the secret the provider sends back to us when the transcription job is done.
+pub fn generate_callback_secret() -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect()
+}
+
+/// Returns if the secret of a callback is the one we gave the job. A job without a secret
+/// never matches.
+pub fn verify_callback_secret(expected: &str, given: &str) -> bool {
+    !expected.is_empty() && verify_slices_are_equal(expected.as_bytes(), given.as_bytes()).is_ok()
+}
+
+fn callback_url_with_secret(callback_url: &str, secret: &str) -> String {
+    let separator = if callback_url.contains('?') { '&' } else { '?' };
+    format!("{}{}secret={}", callback_url, separator, secret)
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct RevAISubmittedJob {
+    id: String,
+}
+
+/// A diarized Rev.ai transcript.
+/// FROM: https://docs.rev.ai/api/asynchronous/reference/#operation/GetTranscriptById
+#[derive(Debug, Clone, Default, Deserialize)]
+struct RevAITranscript {
+    #[serde(default)]
+    monologues: Vec<RevAIMonologue>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct RevAIMonologue {
+    #[serde(default)]
+    speaker: i32,
+    #[serde(default)]
+    speaker_name: Option<String>,
+    #[serde(default)]
+    elements: Vec<RevAIElement>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct RevAIElement {
+    #[serde(default, rename = "type")]
+    type_: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default)]
+    ts: Option<f64>,
+    #[serde(default)]
+    end_ts: Option<f64>,
+}
+
+/// Turn each monologue of a diarized Rev.ai transcript into a segment.
+fn parse_revai_transcript(body: &str) -> Result<Vec<TranscriptSegment>> {
+    let transcript: RevAITranscript = serde_json::from_str(body)?;
+
+    Ok(transcript
+        .monologues
+        .into_iter()
+        .filter_map(|m| {
+            let text: String = m.elements.iter().map(|e| e.value.as_str()).collect();
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                return None;
+            }
+
+            let words: Vec<&RevAIElement> = m.elements.iter().filter(|e| e.type_ == "text").collect();
+            let speaker = match m.speaker_name {
+                Some(name) if !name.trim().is_empty() => name.trim().to_string(),
+                // Rev.ai numbers speakers from 0.
+                _ => format!("Speaker {}", m.speaker + 1),
+            };
+
+            Some(TranscriptSegment {
+                speaker,
+                start_offset: words.first().and_then(|e| e.ts).unwrap_or_default(),
+                end_offset: words.last().and_then(|e| e.end_ts).unwrap_or_default(),
+                text,
+            })
+        })
+        .collect())
+}
+
+#[async_trait]
+impl TranscriptionProvider for RevAIProvider {
+    async fn submit(&self, contents: Vec<u8>) -> Result<Transcription> {
+        let job = self.client.jobs().post(contents).await?;
+
+        Ok(Transcription {
+            id: job.id.to_string(),
+            transcript: String::new(),
+        })
+    }
+
+    async fn submit_url(&self, url: &str, callback_secret: &str) -> Result<Transcription> {
+        if !self.callback_url.is_empty() && !callback_secret.is_empty() {
+            // Our Rev.ai client can't set the callback url of a job, so we submit it ourselves.
+            let resp = reqwest::Client::new()
+                .post("https://api.rev.ai/speechtotext/v1/jobs")
+                .bearer_auth(&self.api_key)
+                .json(&serde_json::json!({
+                    "media_url": url,
+                    "callback_url": callback_url_with_secret(&self.callback_url, callback_secret),
+                }))
+                .send()
+                .await?;
+            let status = resp.status();
+            let text = resp.text().await?;
+            if !status.is_success() {
+                bail!("submitting rev.ai job failed: status code: {}, body: {}", status, text);
+            }
+
+            let job: RevAISubmittedJob = serde_json::from_str(&text)?;
+            return Ok(Transcription {
+                id: job.id,
+                transcript: String::new(),
+            });
+        }
+
+        let job = self
+            .client
+            .jobs()
+            .submit_transcription(&SubmitJobMediaUrlOptionsAllOf {
+                submit_job_media_url_options: SubmitJobMediaUrlOptions {
+                    media_url: url.to_string(),
+                },
+                submit_job_options_all_of: Default::default(),
+            })
+            .await?
+            .job;
+
+        Ok(Transcription {
+            id: job.id.to_string(),
+            transcript: String::new(),
+        })
+    }
+
+    async fn get_transcript(&self, id: &str) -> Result<String> {
+        Ok(self.client.transcript().get(id, AcceptTranscript::TextPlain).await?)
+    }
+
+    async fn get_transcript_segments(&self, id: &str) -> Result<Vec<TranscriptSegment>> {
+        // Our Rev.ai client only returns the plain text transcript, so we get the JSON
+        // one ourselves.
+        let resp = reqwest::Client::new()
+            .get(&format!("https://api.rev.ai/speechtotext/v1/jobs/{}/transcript", id))
+            .bearer_auth(&self.api_key)
+            .header(reqwest::header::ACCEPT, "application/vnd.rev.transcript.v1.0+json")
+            .send()
+            .await?;
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            bail!(
+                "getting rev.ai transcript `{}` failed: status code: {}, body: {}",
+                id,
+                status,
+                text
+            );
+        }
+
+        parse_revai_transcript(&text)
+    }
+}
+
+/// A transcription provider backed by a self-hosted Whisper HTTP endpoint. The endpoint
+/// takes the recording as the body of a `POST /transcribe` and responds with the transcript
+/// as `{"text": "..."}` once it is done, so the transcript is returned right away.
+pub struct WhisperProvider {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct WhisperResponse {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    text: String,
+}
+
+impl WhisperProvider {
+    pub fn new(url: &str, api_key: &str) -> Self {
+        WhisperProvider {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+fn parse_whisper_response(body: &str) -> Result<Transcription> {
+    let resp: WhisperResponse = serde_json::from_str(body)?;
+    if resp.text.trim().is_empty() {
+        bail!("whisper returned an empty transcript");
+    }
+
+    let id = if resp.id.is_empty() {
+        uuid::Uuid::new_v4().to_string()
+    } else {
+        resp.id
+    };
+
+    Ok(Transcription {
+        id: format!("whisper-{}", id),
+        transcript: resp.text.trim().to_string(),
+    })
+}
+
+#[async_trait]
+impl TranscriptionProvider for WhisperProvider {
+    async fn submit(&self, contents: Vec<u8>) -> Result<Transcription> {
+        let mut req = self
+            .client
+            .post(&format!("{}/transcribe", self.url))
+            .header(reqwest::header::CONTENT_TYPE, "video/mp4")
+            .body(contents);
+        if !self.api_key.is_empty() {
+            req = req.bearer_auth(&self.api_key);
+        }
+
+        let resp = req.send().await?;
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            bail!("whisper transcription failed: status code: {}, body: {}", status, text);
+        }
+
+        parse_whisper_response(&text)
+    }
+
+    async fn submit_url(&self, _url: &str, _callback_secret: &str) -> Result<Transcription> {
+        // Our Whisper endpoint is not on the internet, so it can't fetch the recording itself.
+        bail!("whisper only supports uploading recordings directly")
+    }
+
+    async fn get_transcript(&self, id: &str) -> Result<String> {
+        // Whisper returns the transcript when we submit the recording, so if we get here the
+        // transcription was submitted to a different provider.
+        bail!("whisper has no pending transcript `{}`", id)
+    }
+}
+
+/// Get the transcription provider the company picked. Defaults to Rev.ai.
+pub fn get_transcription_provider(company: &Company) -> Result<Box<dyn TranscriptionProvider>> {
+    match company.transcription_provider.trim().to_lowercase().as_str() {
+        "" | "revai" => Ok(Box::new(RevAIProvider::new_from_env())),
+        "whisper" => {
+            if company.whisper_url.is_empty() {
+                bail!("company `{}` uses whisper but has no whisper url set", company.name);
+            }
+
+            Ok(Box::new(WhisperProvider::new(
+                &company.whisper_url,
+                &std::env::var("WHISPER_API_KEY").unwrap_or_default(),
+            )))
+        }
+        p => bail!("company `{}` has unknown transcription provider `{}`", company.name, p),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        callback_url_with_secret, generate_callback_secret, parse_revai_transcript, parse_whisper_response,
+        verify_callback_secret, RevAIJobNotification, TranscriptSegment,
+    };
+
+    #[test]
+    fn test_parse_whisper_response() {
+        let t = parse_whisper_response(r#"{"id":"abc","text":"  Welcome to the all hands.\n"}"#).unwrap();
+        assert_eq!("whisper-abc", t.id);
+        assert_eq!("Welcome to the all hands.", t.transcript);
+
+        let t = parse_whisper_response(r#"{"text":"Hello."}"#).unwrap();
+        assert!(t.id.starts_with("whisper-"));
+
+        assert!(parse_whisper_response(r#"{"text":" "}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_revai_job_notification() {
+        let notification: RevAIJobNotification = serde_json::from_str(
+            r#"{"job":{"id":"Umx5c6F7pH7r","status":"failed","created_on":"2018-05-05T23:23:22.29Z",
+                "callback_url":"https://example.com/revai/jobs/update","failure":"download_failure",
+                "failure_detail":"Failed to download media file."}}"#,
+        )
+        .unwrap();
+        assert_eq!("Umx5c6F7pH7r", notification.job.id);
+        assert!(notification.job.is_failed());
+        assert!(!notification.job.is_transcribed());
+        assert_eq!("Failed to download media file.", notification.job.failure_detail);
+    }
+
+    #[test]
+    fn test_callback_secret() {
+        let secret = generate_callback_secret();
+        assert_eq!(40, secret.len());
+        assert_ne!(secret, generate_callback_secret());
+
+        assert!(verify_callback_secret(&secret, &secret));
+        assert!(!verify_callback_secret(&secret, "guess"));
+        assert!(!verify_callback_secret("", ""));
+
+        assert_eq!(
+            "https://example.com/revai/jobs/update?secret=abc",
+            callback_url_with_secret("https://example.com/revai/jobs/update", "abc")
+        );
+        assert_eq!(
+            "https://example.com/revai/jobs/update?a=b&secret=abc",
+            callback_url_with_secret("https://example.com/revai/jobs/update?a=b", "abc")
+        );
+    }
+
+    #[test]
+    fn test_parse_revai_transcript() {
+        let segments = parse_revai_transcript(
+            r#"{"monologues":[
+                {"speaker":0,"elements":[
+                    {"type":"text","value":"Hello","ts":0.5,"end_ts":1.0},
+                    {"type":"punct","value":","},
+                    {"type":"punct","value":" "},
+                    {"type":"text","value":"everyone","ts":1.1,"end_ts":1.6},
+                    {"type":"punct","value":"."}
+                ]},
+                {"speaker":1,"speaker_name":"Jane","elements":[
+                    {"type":"text","value":"Hi","ts":2.0,"end_ts":2.3}
+                ]},
+                {"speaker":1,"elements":[{"type":"punct","value":" "}]}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                TranscriptSegment {
+                    speaker: "Speaker 1".to_string(),
+                    start_offset: 0.5,
+                    end_offset: 1.6,
+                    text: "Hello, everyone.".to_string(),
+                },
+                TranscriptSegment {
+                    speaker: "Jane".to_string(),
+                    start_offset: 2.0,
+                    end_offset: 2.3,
+                    text: "Hi".to_string(),
+                },
+            ],
+            segments
+        );
+    }
+}
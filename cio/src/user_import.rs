@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    companies::Company,
+    octorust_utils::{into_octorust_error, OctorustErrorKind},
+    utils::{create_or_update_file_in_github_repo, get_file_content_from_repo},
+};
+
+/// The file of the configs repo the users are defined in. The configs sync creates and updates
+/// the users from it, and removes the ones that are no longer in it, so imported users have to
+/// end up there.
+static USERS_CONFIG_PATH: &str = "/configs/users.toml";
+
+/// The most users an import can remove from the config. Imports only add and update users, so
+/// if more are missing from what we would write, something went wrong reading the config.
+static MAX_DROPPED_USERS: usize = 2;
+
+/// The HR systems we can import an employee export from.
+#[derive(Debug, Clone, Copy, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HrisExport {
+    Gusto,
+    BambooHR,
+}
+
+impl HrisExport {
+    /// The names of the columns of the export for each of the fields we import, in the order
+    /// we look for them.
+    fn columns(&self, field: &str) -> &'static [&'static str] {
+        match (self, field) {
+            (_, "first_name") => &["First Name", "Preferred First Name"],
+            (_, "last_name") => &["Last Name"],
+            (_, "work_email") => &["Work Email"],
+            (HrisExport::Gusto, "personal_email") => &["Personal Email", "Email"],
+            (HrisExport::BambooHR, "personal_email") => &["Home Email", "Personal Email"],
+            (HrisExport::Gusto, "phone") => &["Phone", "Phone Number"],
+            (HrisExport::BambooHR, "phone") => &["Mobile Phone", "Home Phone"],
+            (_, "department") => &["Department"],
+            (HrisExport::Gusto, "manager_email") => &["Manager Email", "Manager Work Email"],
+            (HrisExport::BambooHR, "manager_email") => &["Supervisor Email", "Reports To Email"],
+            (HrisExport::Gusto, "start_date") => &["Hire Date", "Start Date"],
+            (HrisExport::BambooHR, "start_date") => &["Hire Date", "Original Hire Date"],
+            (HrisExport::Gusto, "building") => &["Work Location", "Work Address Name"],
+            (HrisExport::BambooHR, "building") => &["Location"],
+            _ => &[],
+        }
+    }
+}
+
+/// An employee of an HR system export, as the fields of their user config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedUser {
+    pub username: String,
+    pub fields: BTreeMap<&'static str, String>,
+}
+
+/// Parse an employee export. Employees are matched to users by the username of their work
+/// email, so the ones without a work email at the company's domain are skipped.
+pub fn parse_user_import(export: HrisExport, csv: &[u8], company: &Company) -> Result<Vec<ImportedUser>> {
+    let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(csv);
+    let headers: Vec<String> = rdr.headers()?.iter().map(|h| h.trim().to_string()).collect();
+
+    let column = |field: &str| -> Option<usize> {
+        export
+            .columns(field)
+            .iter()
+            .find_map(|name| headers.iter().position(|h| h.eq_ignore_ascii_case(name)))
+    };
+    let work_email = match column("work_email") {
+        Some(c) => c,
+        None => bail!("the {:?} export has no work email column", export),
+    };
+
+    let username_of = |email: &str| -> Option<String> {
+        let email = email.trim().to_lowercase();
+        let (username, domain) = email.split_once('@')?;
+        if domain != company.gsuite_domain.to_lowercase() || username.is_empty() {
+            return None;
+        }
+        Some(username.to_string())
+    };
+
+    let mut users = Vec::new();
+    for record in rdr.records() {
+        let record = record?;
+        let value = |c: Option<usize>| -> String {
+            c.and_then(|c| record.get(c))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        let email = value(Some(work_email));
+        let username = match username_of(&email) {
+            Some(username) => username,
+            None => {
+                info!(
+                    "skipping employee `{}` without a work email at {}",
+                    email, company.gsuite_domain
+                );
+                continue;
+            }
+        };
+
+        let mut fields: BTreeMap<&'static str, String> = BTreeMap::new();
+        for field in ["first_name", "last_name", "department", "building"] {
+            fields.insert(field, value(column(field)));
+        }
+        fields.insert("recovery_email", value(column("personal_email")).to_lowercase());
+        fields.insert("recovery_phone", value(column("phone")));
+        if let Some(manager) = username_of(&value(column("manager_email"))) {
+            fields.insert("manager", manager);
+        }
+        if let Some(start_date) = parse_export_date(&value(column("start_date"))) {
+            fields.insert("start_date", start_date.format("%Y-%m-%d").to_string());
+        }
+        fields.retain(|_, v| !v.is_empty());
+
+        users.push(ImportedUser { username, fields });
+    }
+
+    Ok(users)
+}
+
+/// Exports write dates either like `2023-09-04` or like `09/04/2023`.
+fn parse_export_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%m/%d/%Y"))
+        .ok()
+}
+
+/// A field of a user config an import changes.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct UserImportFieldChange {
+    pub field: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub from: String,
+    pub to: String,
+}
+
+/// What an import does to a user config.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct UserImportChange {
+    pub username: String,
+    /// If the user is new.
+    pub created: bool,
+    pub changes: Vec<UserImportFieldChange>,
+}
+
+/// Apply an import to the users of the configs, and return what changed. Fields the export
+/// leaves empty are left alone, so an import never clears what people filled in by hand.
+pub fn merge_user_import(users: &mut toml::value::Table, imported: &[ImportedUser]) -> Vec<UserImportChange> {
+    let mut changes = Vec::new();
+
+    for import in imported {
+        let created = !users.contains_key(&import.username);
+        let user = users
+            .entry(import.username.to_string())
+            .or_insert(toml::Value::Table(Default::default()));
+        let user = match user.as_table_mut() {
+            Some(user) => user,
+            None => continue,
+        };
+
+        let mut fields = import.fields.clone();
+        if created {
+            fields.insert("username", import.username.to_string());
+        }
+
+        let mut change = UserImportChange {
+            username: import.username.to_string(),
+            created,
+            changes: Default::default(),
+        };
+        for (field, to) in fields {
+            let from = user.get(field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            if from == to {
+                continue;
+            }
+
+            user.insert(field.to_string(), toml::Value::String(to.to_string()));
+            change.changes.push(UserImportFieldChange {
+                field: field.to_string(),
+                from,
+                to,
+            });
+        }
+
+        if !change.changes.is_empty() {
+            changes.push(change);
+        }
+    }
+
+    changes
+}
+
+/// Import the employees of an HR system export into the users of the configs repo, and return
+/// what changed. The configs sync then creates and updates the users. With `dry_run`, nothing is
+/// written and the changes are only returned.
+/// Fail if the users config we are about to write is missing more than `MAX_DROPPED_USERS` of
+/// the users it had.
+fn check_dropped_users(existing: &[String], rendered: &str) -> Result<()> {
+    let config: toml::value::Table = toml::from_str(rendered)?;
+    let users = config.get("users").and_then(|u| u.as_table());
+    let dropped: Vec<&String> = existing
+        .iter()
+        .filter(|username| !users.map(|u| u.contains_key(*username)).unwrap_or(false))
+        .collect();
+    if dropped.len() > MAX_DROPPED_USERS {
+        bail!(
+            "refusing to write {}, it would remove {} users: {:?}",
+            USERS_CONFIG_PATH,
+            dropped.len(),
+            dropped
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn import_users(
+    company: &Company,
+    export: HrisExport,
+    csv: &[u8],
+    dry_run: bool,
+) -> Result<Vec<UserImportChange>> {
+    let imported = parse_user_import(export, csv, company)?;
+
+    let github = company.authenticate_github()?;
+    let owner = &company.github_org;
+    let repo = "configs";
+
+    // Companies that are just adopting cio might not have any users yet. Any other error means
+    // we don't know who is in the config, and writing it would remove them.
+    let contents = match get_file_content_from_repo(&github, owner, repo, "", USERS_CONFIG_PATH).await {
+        Ok((contents, _)) => String::from_utf8(contents)?,
+        Err(e) => {
+            let e = into_octorust_error(e);
+            if e.kind != OctorustErrorKind::NotFound {
+                return Err(e.into_inner());
+            }
+            String::new()
+        }
+    };
+    let mut config: toml::value::Table = toml::from_str(&contents)?;
+
+    let mut users = match config.remove("users") {
+        Some(toml::Value::Table(users)) => users,
+        Some(_) => bail!("`users` in {} is not a table", USERS_CONFIG_PATH),
+        None => Default::default(),
+    };
+    let existing: Vec<String> = users.keys().cloned().collect();
+    let changes = merge_user_import(&mut users, &imported);
+    config.insert("users".to_string(), toml::Value::Table(users));
+    let rendered = toml::to_string_pretty(&config)?;
+    check_dropped_users(&existing, &rendered)?;
+
+    info!(
+        "importing {} employees from {:?} changes {} users",
+        imported.len(),
+        export,
+        changes.len()
+    );
+    if dry_run || changes.is_empty() {
+        return Ok(changes);
+    }
+
+    create_or_update_file_in_github_repo(
+        &github,
+        owner,
+        repo,
+        "", // leaving the branch blank gives us the default branch
+        USERS_CONFIG_PATH,
+        rendered.into_bytes(),
+    )
+    .await?;
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_dropped_users, merge_user_import, parse_user_import, HrisExport};
+    use crate::companies::tests::mock_company;
+
+    #[test]
+    fn test_import_users() {
+        let mut company = mock_company();
+        company.gsuite_domain = "example.com".to_string();
+
+        let csv = "First Name,Last Name,Work Email,Home Email,Department,Supervisor Email,Hire Date\n\
+                   Jane,Doe,Jane@example.com,jane@home.com,Hardware,bob@example.com,09/04/2023\n\
+                   Bob,Smith,bob@example.com,,Hardware,,2020-01-06\n\
+                   Eve,Contractor,eve@elsewhere.com,,,,\n";
+        let imported = parse_user_import(HrisExport::BambooHR, csv.as_bytes(), &company).unwrap();
+        assert_eq!(2, imported.len());
+        assert_eq!("jane", imported[0].username);
+        assert_eq!(Some(&"bob".to_string()), imported[0].fields.get("manager"));
+        assert_eq!(Some(&"2023-09-04".to_string()), imported[0].fields.get("start_date"));
+        assert_eq!(None, imported[1].fields.get("recovery_email"));
+
+        let mut users: toml::value::Table = toml::from_str(
+            r#"
+[bob]
+first_name = 'Bob'
+last_name = 'Smith'
+username = 'bob'
+department = 'Software'
+recovery_email = 'bob@home.com'
+"#,
+        )
+        .unwrap();
+        let changes = merge_user_import(&mut users, &imported);
+
+        assert_eq!(2, changes.len());
+        assert!(changes[0].created);
+        assert_eq!("jane", users["jane"]["username"].as_str().unwrap());
+
+        // Bob moved to hardware, his empty personal email does not clear the one we have.
+        assert!(!changes[1].created);
+        let fields: Vec<&str> = changes[1].changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(vec!["department", "start_date"], fields);
+        assert_eq!("Software", changes[1].changes[0].from);
+        assert_eq!("bob@home.com", users["bob"]["recovery_email"].as_str().unwrap());
+
+        // Importing again changes nothing.
+        assert!(merge_user_import(&mut users, &imported).is_empty());
+    }
+
+    #[test]
+    fn test_check_dropped_users() {
+        let existing: Vec<String> = vec!["a", "b", "c", "d"].into_iter().map(String::from).collect();
+        assert!(check_dropped_users(&existing, "[users.a]\n[users.b]\n[users.c]\n[users.d]\n[users.e]\n").is_ok());
+        assert!(check_dropped_users(&existing, "[users.a]\n[users.b]\n").is_ok());
+        assert!(check_dropped_users(&existing, "[users.a]\n").is_err());
+        assert!(check_dropped_users(&existing, "").is_err());
+    }
+}
@@ -455,7 +455,7 @@ mod tests {
     async fn test_create_and_get_repo_secret() {
         // Initialize our database.
         let db = crate::db::Database::new().await;
-        let company = crate::companies::Company::get_by_id(&db, 1).await.unwrap();
+        let company = crate::companies::Company::get_from_id(&db, 1).await.unwrap();
         let github = company.authenticate_github().unwrap();
 
         let repo = "cio";
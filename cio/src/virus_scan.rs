@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use log::warn;
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+
+use crate::{companies::Company, db::Database};
+
+/// The size of the chunks we stream to clamd. It has to be under clamd's `StreamMaxLength`.
+static CLAMD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How long we wait for clamd to scan a file before giving up on it.
+static CLAMD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// What scanning a file found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanVerdict {
+    Clean,
+    /// The file is infected, with the name of what was found.
+    Infected(String),
+}
+
+#[async_trait]
+pub trait VirusScanner: Send + Sync {
+    /// Scan the contents of a file.
+    async fn scan(&self, contents: &[u8]) -> Result<ScanVerdict>;
+}
+
+/// A virus scanner backed by a ClamAV daemon.
+pub struct ClamAVScanner {
+    /// The `host:port` clamd listens on.
+    address: String,
+}
+
+impl ClamAVScanner {
+    pub fn new(address: &str) -> Self {
+        ClamAVScanner {
+            address: address.to_string(),
+        }
+    }
+}
+
+/// Parse the reply of clamd to a scan, like `stream: OK` or `stream: Eicar-Signature FOUND`.
+fn parse_clamd_response(response: &str) -> Result<ScanVerdict> {
+    let response = response.trim_end_matches('\0').trim();
+    let result = response.strip_prefix("stream:").unwrap_or(response).trim();
+
+    if result == "OK" {
+        Ok(ScanVerdict::Clean)
+    } else if let Some(signature) = result.strip_suffix("FOUND") {
+        Ok(ScanVerdict::Infected(signature.trim().to_string()))
+    } else {
+        bail!("clamd could not scan the file: {}", response)
+    }
+}
+
+impl ClamAVScanner {
+    async fn scan_stream(&self, contents: &[u8]) -> Result<ScanVerdict> {
+        let mut stream = TcpStream::connect(&self.address).await?;
+
+        // Stream the file in chunks prefixed by their length, and end with an empty chunk.
+        stream.write_all(b"zINSTREAM\0").await?;
+        for chunk in contents.chunks(CLAMD_CHUNK_SIZE) {
+            stream.write_all(&(chunk.len() as u32).to_be_bytes()).await?;
+            stream.write_all(chunk).await?;
+        }
+        stream.write_all(&0u32.to_be_bytes()).await?;
+        stream.flush().await?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+
+        parse_clamd_response(&response)
+    }
+}
+
+#[async_trait]
+impl VirusScanner for ClamAVScanner {
+    async fn scan(&self, contents: &[u8]) -> Result<ScanVerdict> {
+        match timeout(CLAMD_TIMEOUT, self.scan_stream(contents)).await {
+            Ok(verdict) => verdict,
+            Err(_) => bail!("clamd did not scan the file in {:?}", CLAMD_TIMEOUT),
+        }
+    }
+}
+
+/// Get the virus scanner we have set up, if any. Without one, uploads have to be refused.
+pub fn get_virus_scanner() -> Option<Box<dyn VirusScanner>> {
+    match std::env::var("CLAMAV_ADDRESS") {
+        Ok(address) if !address.trim().is_empty() => Some(Box::new(ClamAVScanner::new(address.trim()))),
+        _ => None,
+    }
+}
+
+/// Let the security channel know we quarantined an uploaded file. It falls back to the debug
+/// channel if the company has no security channel.
+pub async fn alert_quarantined_file(
+    db: &Database,
+    company: &Company,
+    file_name: &str,
+    signature: &str,
+    uploaded_by: &str,
+    quarantine_link: &str,
+) -> Result<()> {
+    let channel = if company.slack_channel_security.is_empty() {
+        warn!(
+            "company `{}` has no security channel, alerting the debug channel",
+            company.name
+        );
+        company.slack_channel_debug.to_string()
+    } else {
+        company.slack_channel_security.to_string()
+    };
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![
+            MessageBlock {
+                block_type: MessageBlockType::Header,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: "Quarantined an infected upload".to_string(),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+            MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        "`{}` uploaded by {} was flagged as *{}*. It was not linked to any record, \
+                         it is <{}|in quarantine>.",
+                        file_name, uploaded_by, signature, quarantine_link
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            },
+        ],
+        attachments: Default::default(),
+    };
+
+    company.post_to_slack_channel(db, &msg).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_clamd_response, ScanVerdict};
+
+    #[test]
+    fn test_parse_clamd_response() {
+        assert_eq!(ScanVerdict::Clean, parse_clamd_response("stream: OK\0").unwrap());
+        assert_eq!(
+            ScanVerdict::Infected("Win.Test.EICAR_HDB-1".to_string()),
+            parse_clamd_response("stream: Win.Test.EICAR_HDB-1 FOUND\0").unwrap()
+        );
+        assert!(parse_clamd_response("INSTREAM size limit exceeded. ERROR\0").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A change to the payload of a webhook before we decode it, so we can keep up with small
+/// changes providers make without a release. Paths are JSON pointers, like `/data/status`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WebhookTransform {
+    /// Move a field the provider renamed back to where we expect it.
+    Move { from: String, to: String },
+    /// Set a field the provider stopped sending, or sends as null.
+    Default { path: String, value: Value },
+    /// Map values the provider added to one we know, like a new status.
+    MapValue {
+        path: String,
+        values: BTreeMap<String, Value>,
+    },
+}
+
+/// The transforms of the webhooks of each provider, by provider, like `shippo`.
+pub type WebhookTransformsConfig = HashMap<String, Vec<WebhookTransform>>;
+
+/// Split a JSON pointer into its unescaped tokens.
+fn pointer_tokens(pointer: &str) -> Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !pointer.starts_with('/') {
+        bail!("`{}` is not a JSON pointer, it should start with `/`", pointer);
+    }
+
+    Ok(pointer[1..]
+        .split('/')
+        .map(|t| t.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Take the value out of a payload at a path, if there is one.
+fn take(payload: &mut Value, pointer: &str) -> Result<Option<Value>> {
+    let mut tokens = pointer_tokens(pointer)?;
+    let last = match tokens.pop() {
+        Some(last) => last,
+        None => bail!("cannot take the whole payload"),
+    };
+
+    let parent = tokens.iter().fold(Some(payload), |v, t| match v? {
+        Value::Object(map) => map.get_mut(t),
+        Value::Array(list) => t.parse::<usize>().ok().and_then(move |i| list.get_mut(i)),
+        _ => None,
+    });
+
+    Ok(match parent {
+        Some(Value::Object(map)) => map.remove(&last),
+        Some(Value::Array(list)) => match last.parse::<usize>() {
+            Ok(i) if i < list.len() => Some(list.remove(i)),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Set the value in a payload at a path, creating the objects on the way.
+fn set(payload: &mut Value, pointer: &str, value: Value) -> Result<()> {
+    let mut current = payload;
+    for token in pointer_tokens(pointer)? {
+        if current.is_null() {
+            *current = Value::Object(Default::default());
+        }
+        current = match current {
+            Value::Object(map) => map.entry(token).or_insert(Value::Null),
+            Value::Array(list) => match token.parse::<usize>().ok().and_then(move |i| list.get_mut(i)) {
+                Some(v) => v,
+                None => bail!("`{}` is not in the array at `{}`", token, pointer),
+            },
+            _ => bail!(
+                "cannot set `{}`, it goes through a value that is not an object",
+                pointer
+            ),
+        };
+    }
+    *current = value;
+
+    Ok(())
+}
+
+/// Apply the transforms of a provider to the payload of one of its webhooks, in order.
+pub fn apply_webhook_transforms(transforms: &[WebhookTransform], payload: &mut Value) -> Result<()> {
+    for transform in transforms {
+        match transform {
+            WebhookTransform::Move { from, to } => {
+                if let Some(value) = take(payload, from)? {
+                    set(payload, to, value)?;
+                }
+            }
+            WebhookTransform::Default { path, value } => {
+                if payload.pointer(path).map(|v| v.is_null()).unwrap_or(true) {
+                    set(payload, path, value.clone())?;
+                }
+            }
+            WebhookTransform::MapValue { path, values } => {
+                if let Some(current) = payload.pointer_mut(path) {
+                    let key = match current {
+                        Value::String(s) => s.to_string(),
+                        other => other.to_string(),
+                    };
+                    if let Some(value) = values.get(&key) {
+                        *current = value.clone();
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{apply_webhook_transforms, WebhookTransformsConfig};
+
+    #[test]
+    fn test_apply_webhook_transforms() {
+        let config: WebhookTransformsConfig = toml::from_str(
+            r#"
+[[shippo]]
+op = "move"
+from = "/data/tracking_state"
+to = "/data/tracking_status/status"
+
+[[shippo]]
+op = "default"
+path = "/data/carrier"
+value = "usps"
+
+[[shippo]]
+op = "map_value"
+path = "/data/tracking_status/status"
+values = { IN_TRANSIT = "TRANSIT" }
+"#,
+        )
+        .unwrap();
+
+        let mut payload = json!({
+            "event": "track_updated",
+            "data": {
+                "tracking_number": "123",
+                "carrier": null,
+                "tracking_state": "IN_TRANSIT",
+            },
+        });
+        apply_webhook_transforms(&config["shippo"], &mut payload).unwrap();
+        assert_eq!(
+            json!({
+                "event": "track_updated",
+                "data": {
+                    "tracking_number": "123",
+                    "carrier": "usps",
+                    "tracking_status": {"status": "TRANSIT"},
+                },
+            }),
+            payload
+        );
+
+        // Payloads the provider did not change go through as they are.
+        let mut payload = json!({"data": {"carrier": "ups", "tracking_status": {"status": "DELIVERED"}}});
+        let before = payload.clone();
+        apply_webhook_transforms(&config["shippo"], &mut payload).unwrap();
+        assert_eq!(before, payload);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Duration, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+
+use crate::{
+    app_config::DocuSignConfig,
+    applicants::Applicant,
+    asset_loaners::{list_reservations, AssetReservation, RESERVATION_PICKED_UP},
+    companies::Company,
+    configs::{User, Users},
+    db::Database,
+    interviews::ApplicantInterview,
+    notifications::NOTIFICATION_WEEKLY_DIGEST,
+    rfd::{RFDDiscussionComment, RFD},
+    schema::{applicant_interviews, applicants, rfd_discussion_comments, rfds},
+};
+
+/// The statuses of DocuSign envelopes that still wait on a signature.
+static PENDING_ENVELOPE_STATUSES: &[&str] = &["sent", "delivered"];
+
+/// What cio knows about the week of the company, that the digests of everyone are made of.
+#[derive(Debug, Clone, Default)]
+pub struct WeeklyDigestSources {
+    pub interviews: Vec<ApplicantInterview>,
+    pub reservations: Vec<AssetReservation>,
+    pub applicants: Vec<Applicant>,
+    pub rfds: Vec<RFD>,
+    /// The GitHub logins of the people who commented on the discussion of each RFD, by RFD
+    /// number, in lowercase.
+    pub rfd_commenters: BTreeMap<i32, BTreeSet<String>>,
+    pub users: Vec<User>,
+}
+
+impl WeeklyDigestSources {
+    /// Get what cio knows about the week starting now.
+    pub async fn get(db: &Database, company: &Company, now: DateTime<Utc>) -> Result<Self> {
+        let interviews = applicant_interviews::dsl::applicant_interviews
+            .filter(applicant_interviews::dsl::cio_company_id.eq(company.id))
+            .filter(applicant_interviews::dsl::cancelled_at.is_null())
+            .filter(applicant_interviews::dsl::start_time.ge(now))
+            .filter(applicant_interviews::dsl::start_time.lt(now + Duration::weeks(1)))
+            .order_by(applicant_interviews::dsl::start_time)
+            .load_async::<ApplicantInterview>(db.pool())
+            .await?;
+
+        let reservations = list_reservations(db, company.id, (now - Duration::days(30)).date().naive_utc()).await?;
+
+        let applicants = applicants::dsl::applicants
+            .filter(applicants::dsl::cio_company_id.eq(company.id))
+            .filter(
+                applicants::dsl::docusign_envelope_status
+                    .eq_any(PENDING_ENVELOPE_STATUSES)
+                    .or(applicants::dsl::docusign_piia_envelope_status.eq_any(PENDING_ENVELOPE_STATUSES)),
+            )
+            .load_async::<Applicant>(db.pool())
+            .await?;
+
+        let rfds = rfds::dsl::rfds
+            .filter(rfds::dsl::cio_company_id.eq(company.id))
+            .filter(rfds::dsl::commit_date.ge(now - Duration::weeks(1)))
+            .order_by(rfds::dsl::number)
+            .load_async::<RFD>(db.pool())
+            .await?;
+
+        let mut rfd_commenters: BTreeMap<i32, BTreeSet<String>> = BTreeMap::new();
+        for comment in rfd_discussion_comments::dsl::rfd_discussion_comments
+            .filter(rfd_discussion_comments::dsl::cio_company_id.eq(company.id))
+            .filter(rfd_discussion_comments::dsl::rfd_number.eq_any(rfds.iter().map(|r| r.number).collect::<Vec<_>>()))
+            .load_async::<RFDDiscussionComment>(db.pool())
+            .await?
+        {
+            rfd_commenters
+                .entry(comment.rfd_number)
+                .or_default()
+                .insert(comment.author.to_lowercase());
+        }
+
+        Ok(WeeklyDigestSources {
+            interviews,
+            reservations,
+            applicants,
+            rfds,
+            rfd_commenters,
+            users: Users::get_from_db(db, company.id).await?.into_iter().collect(),
+        })
+    }
+}
+
+/// The summary of someone's week, one line per thing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WeeklyDigest {
+    pub interviews: Vec<String>,
+    pub assets_due: Vec<String>,
+    pub envelopes: Vec<String>,
+    pub rfds: Vec<String>,
+    pub approvals: Vec<String>,
+}
+
+impl WeeklyDigest {
+    /// Put together the week of a user. The RFDs they follow are the ones they wrote, or
+    /// commented on the discussion of.
+    pub fn new(user: &User, sources: &WeeklyDigestSources, envelopes: &DocuSignConfig, now: DateTime<Utc>) -> Self {
+        let email = user.email.to_lowercase();
+        let mut digest = WeeklyDigest::default();
+
+        for interview in &sources.interviews {
+            if interview.start_time >= now
+                && interview.start_time < now + Duration::weeks(1)
+                && interview.interviewers.iter().any(|i| i.to_lowercase() == email)
+            {
+                digest.interviews.push(format!(
+                    "{}: <{}|{}>",
+                    interview.start_time.format("%A %H:%M UTC"),
+                    interview.event_link,
+                    interview.name
+                ));
+            }
+        }
+
+        let end_of_week = (now + Duration::weeks(1)).date().naive_utc();
+        for reservation in &sources.reservations {
+            if reservation.status == RESERVATION_PICKED_UP
+                && reservation.email.to_lowercase() == email
+                && reservation.end_date < end_of_week
+            {
+                digest.assets_due.push(format!(
+                    "`{}` is due back on {}",
+                    reservation.asset_name, reservation.end_date
+                ));
+            }
+        }
+
+        let signs_offers = envelopes.signs_offer_letters(&user.email);
+        let signs_piias = envelopes.signs_piia_letters(&user.email);
+        for applicant in &sources.applicants {
+            if signs_offers && PENDING_ENVELOPE_STATUSES.contains(&applicant.docusign_envelope_status.as_str()) {
+                digest
+                    .envelopes
+                    .push(format!("The offer letter of {} ({})", applicant.name, applicant.role));
+            }
+            if signs_piias && PENDING_ENVELOPE_STATUSES.contains(&applicant.docusign_piia_envelope_status.as_str()) {
+                digest
+                    .envelopes
+                    .push(format!("The PIIA of {} ({})", applicant.name, applicant.role));
+            }
+        }
+
+        let full_name = user.full_name().to_lowercase();
+        let github = user.github.to_lowercase();
+        for rfd in &sources.rfds {
+            if rfd.commit_date < now - Duration::weeks(1) {
+                continue;
+            }
+
+            let authors = rfd.authors.to_lowercase();
+            let wrote = authors.contains(&email) || (!user.first_name.is_empty() && authors.contains(&full_name));
+            let commented = !github.is_empty()
+                && sources
+                    .rfd_commenters
+                    .get(&rfd.number)
+                    .map(|c| c.contains(&github))
+                    .unwrap_or_default();
+            if wrote || commented {
+                digest
+                    .rfds
+                    .push(format!("<{}|{}> is in {}", rfd.rendered_link, rfd.name, rfd.state));
+            }
+        }
+
+        for contractor in &sources.users {
+            if contractor.is_contractor && contractor.manager == user.username {
+                if let Some(requested) = contractor.requested_end_date {
+                    digest.approvals.push(format!(
+                        "Extending the engagement of {} until {}",
+                        contractor.full_name(),
+                        requested
+                    ));
+                }
+            }
+        }
+
+        digest
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interviews.is_empty()
+            && self.assets_due.is_empty()
+            && self.envelopes.is_empty()
+            && self.rfds.is_empty()
+            && self.approvals.is_empty()
+    }
+
+    /// The Slack message of the digest, with a section per kind of thing.
+    pub fn message(&self, channel: &str) -> FormattedMessage {
+        let mut blocks = vec![MessageBlock {
+            block_type: MessageBlockType::Header,
+            text: Some(MessageBlockText {
+                text_type: MessageType::PlainText,
+                text: "Your week".to_string(),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }];
+
+        for (title, lines) in [
+            ("Interviews you are on", &self.interviews),
+            ("Loaners due back", &self.assets_due),
+            ("Waiting on your signature", &self.envelopes),
+            ("RFDs you follow that changed", &self.rfds),
+            ("Waiting on your approval", &self.approvals),
+        ] {
+            if lines.is_empty() {
+                continue;
+            }
+
+            blocks.push(MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        "*{}*\n{}",
+                        title,
+                        lines.iter().map(|l| format!("• {}", l)).collect::<Vec<_>>().join("\n")
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            });
+        }
+
+        FormattedMessage {
+            channel: channel.to_string(),
+            blocks,
+            attachments: Default::default(),
+        }
+    }
+}
+
+/// Send everyone who wants it a direct message with what is on their plate this week.
+pub async fn send_weekly_digests(db: &Database, company: &Company, envelopes: &DocuSignConfig) -> Result<()> {
+    let now = Utc::now();
+    let sources = WeeklyDigestSources::get(db, company, now).await?;
+    let slack = company.authenticate_slack(db).await?;
+
+    for user in &sources.users {
+        if user.is_system_account() || !user.wants_notification(NOTIFICATION_WEEKLY_DIGEST) {
+            continue;
+        }
+
+        let digest = WeeklyDigest::new(user, &sources, envelopes, now);
+        if digest.is_empty() {
+            continue;
+        }
+
+        let channel = match slack.lookup_user_by_email(&user.email).await {
+            Ok(user) => user.id,
+            Err(e) => {
+                warn!("could not find the slack user of {}: {}", user.email, e);
+                continue;
+            }
+        };
+        if let Err(e) = company.post_to_slack_channel(db, &digest.message(&channel)).await {
+            warn!("could not send the weekly digest of {}: {}", user.username, e);
+            continue;
+        }
+        info!("sent the weekly digest of {}", user.username);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{WeeklyDigest, WeeklyDigestSources};
+    use crate::{
+        app_config::DocuSignConfig,
+        applicants::tests::mock_applicant,
+        asset_loaners::{AssetReservation, RESERVATION_PICKED_UP},
+        configs::tests::mock_user,
+        interviews::ApplicantInterview,
+        rfd::RFD,
+    };
+
+    #[test]
+    fn test_weekly_digest() {
+        let now = Utc.ymd(2024, 1, 8).and_hms(8, 0, 0);
+        let mut user = mock_user();
+        user.email = "test@example.com".to_string();
+        user.github = "TestUser".to_string();
+
+        let mut interview: ApplicantInterview = serde_json::from_str(
+            r#"{"start_time":"2024-01-09T17:00:00Z","end_time":"2024-01-09T18:00:00Z","name":"Applicant (test)"}"#,
+        )
+        .unwrap();
+        interview.interviewers = vec!["Test@example.com".to_string()];
+        let mut later = interview.clone();
+        later.start_time = now + Duration::weeks(2);
+
+        let reservation = AssetReservation {
+            id: 1,
+            asset_name: "Loaner laptop".to_string(),
+            email: "test@example.com".to_string(),
+            start_date: now.date().naive_utc() - Duration::days(7),
+            end_date: now.date().naive_utc() + Duration::days(2),
+            purpose: String::new(),
+            status: RESERVATION_PICKED_UP.to_string(),
+            pickup_reminder_sent: true,
+            return_reminder_sent: false,
+            created_at: now,
+            cio_company_id: 1,
+        };
+
+        let mut applicant = mock_applicant();
+        applicant.docusign_envelope_status = "sent".to_string();
+
+        let mut rfd: RFD = serde_json::from_str(
+            r#"{"number":12,"title":"Things","name":"RFD 12 Things","state":"discussion","link":""}"#,
+        )
+        .unwrap();
+        rfd.commit_date = now - Duration::days(2);
+        let mut unrelated = rfd.clone();
+        unrelated.number = 13;
+
+        let mut contractor = mock_user();
+        contractor.is_contractor = true;
+        contractor.manager = user.username.to_string();
+        contractor.requested_end_date = Some(now.date().naive_utc() + Duration::days(60));
+
+        let mut sources = WeeklyDigestSources {
+            interviews: vec![interview, later],
+            reservations: vec![reservation],
+            applicants: vec![applicant],
+            rfds: vec![rfd, unrelated],
+            users: vec![contractor],
+            ..Default::default()
+        };
+        sources
+            .rfd_commenters
+            .insert(12, vec!["testuser".to_string()].into_iter().collect());
+
+        let digest = WeeklyDigest::new(&user, &sources, &DocuSignConfig::default(), now);
+        assert_eq!(1, digest.interviews.len());
+        assert_eq!(1, digest.assets_due.len());
+        // They do not sign the letters.
+        assert!(digest.envelopes.is_empty());
+        assert_eq!(1, digest.rfds.len());
+        assert_eq!(1, digest.approvals.len());
+        assert!(!digest.is_empty());
+
+        // The message skips the sections with nothing in them.
+        assert_eq!(5, digest.message("U123").blocks.len());
+    }
+}
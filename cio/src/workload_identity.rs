@@ -0,0 +1,133 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Where we exchange a signed assertion for an access token.
+static GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+/// The scope the identity of the workload needs to sign assertions for service accounts.
+pub static GOOGLE_CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+
+/// How long the assertions we sign are valid for, Google does not take more than an hour.
+static ASSERTION_LIFETIME_MINUTES: i64 = 60;
+
+/// The claims of the assertion we exchange for a token of a service account, acting as a user
+/// of the domain when it has a subject.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DelegationClaims {
+    pub iss: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub sub: String,
+    pub scope: String,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+impl DelegationClaims {
+    pub fn new(service_account: &str, subject: &str, scopes: &[&str], now: DateTime<Utc>) -> Self {
+        DelegationClaims {
+            iss: service_account.to_string(),
+            sub: subject.to_string(),
+            scope: scopes.join(" "),
+            aud: GOOGLE_TOKEN_URL.to_string(),
+            iat: now.timestamp(),
+            exp: (now + Duration::minutes(ASSERTION_LIFETIME_MINUTES)).timestamp(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SignJwtResponse {
+    #[serde(rename = "signedJwt")]
+    signed_jwt: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    #[serde(default)]
+    access_token: String,
+}
+
+/// Get a short-lived token of a service account without its key. The IAM Credentials API signs
+/// the assertion as the identity of the workload, whose token is `workload_token`, so that
+/// identity needs the Service Account Token Creator role on the service account. A leaked
+/// token of the workload expires within the hour, unlike a leaked key.
+pub async fn get_keyless_service_account_token(
+    workload_token: &str,
+    service_account: &str,
+    subject: &str,
+    scopes: &[&str],
+) -> Result<String> {
+    let claims = DelegationClaims::new(service_account, subject, scopes, Utc::now());
+
+    let client = reqwest::Client::new();
+    let signed: SignJwtResponse = client
+        .post(&format!(
+            "https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/{}:signJwt",
+            service_account
+        ))
+        .bearer_auth(workload_token)
+        .json(&serde_json::json!({ "payload": serde_json::to_string(&claims)? }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let token: TokenResponse = client
+        .post(GOOGLE_TOKEN_URL)
+        .form(&[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", &signed.signed_jwt),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if token.access_token.is_empty() {
+        bail!("empty token returned for service account `{}`", service_account);
+    }
+
+    Ok(token.access_token)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    use super::DelegationClaims;
+
+    #[test]
+    fn test_delegation_claims() {
+        let now = Utc.ymd(2024, 4, 8).and_hms(10, 0, 0);
+        let claims = DelegationClaims::new(
+            "cio@project.iam.gserviceaccount.com",
+            "admin@example.com",
+            &[
+                "https://www.googleapis.com/auth/drive",
+                "https://www.googleapis.com/auth/calendar",
+            ],
+            now,
+        );
+
+        assert_eq!(
+            json!({
+                "iss": "cio@project.iam.gserviceaccount.com",
+                "sub": "admin@example.com",
+                "scope": "https://www.googleapis.com/auth/drive https://www.googleapis.com/auth/calendar",
+                "aud": "https://oauth2.googleapis.com/token",
+                "iat": now.timestamp(),
+                "exp": now.timestamp() + 3600,
+            }),
+            serde_json::to_value(&claims).unwrap()
+        );
+
+        // Without a subject the token is the service account's own.
+        let claims = DelegationClaims::new("cio@project.iam.gserviceaccount.com", "", &[], now);
+        assert!(serde_json::to_value(&claims).unwrap().get("sub").is_none());
+    }
+}
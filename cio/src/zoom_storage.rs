@@ -0,0 +1,267 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+use zoom_api::types::GetAccountCloudRecordingResponseMeetings;
+
+use crate::{companies::Company, db::Database, schema::zoom_storage_snapshots};
+
+/// The share of the cloud storage of the Zoom plan we alert at.
+pub static ZOOM_STORAGE_ALERT_RATIO: f64 = 0.8;
+
+static BYTES_PER_GB: i64 = 1024 * 1024 * 1024;
+
+/// How much of the Zoom cloud recording storage a company used at a point in time.
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct ZoomStorageSnapshot {
+    pub id: i32,
+    pub used_bytes: i64,
+    /// The number of recorded meetings of the sync window, waiting to be moved to Drive.
+    pub recordings: i32,
+    /// The storage of the plan when we took the snapshot, zero if we do not know it.
+    pub limit_bytes: i64,
+    pub taken_at: DateTime<Utc>,
+    pub cio_company_id: i32,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = zoom_storage_snapshots)]
+struct NewZoomStorageSnapshot {
+    used_bytes: i64,
+    recordings: i32,
+    limit_bytes: i64,
+    taken_at: DateTime<Utc>,
+    cio_company_id: i32,
+}
+
+/// The usage of the plans of a Zoom account.
+/// FROM: https://developers.zoom.us/docs/api/rest/reference/account/methods/#operation/getPlanUsage
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ZoomPlanUsage {
+    #[serde(default)]
+    plan_recording: ZoomPlanRecording,
+}
+
+/// The cloud recording storage of a Zoom account. Zoom gives the sizes with their unit, like
+/// `1 TB` or `512.5 MB`.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ZoomPlanRecording {
+    #[serde(default)]
+    free_storage: String,
+    #[serde(default)]
+    free_storage_usage: String,
+    #[serde(default)]
+    plan_storage: String,
+    #[serde(default)]
+    plan_storage_usage: String,
+}
+
+/// Parse a size Zoom gives with its unit, like `1.5 GB`, into bytes. Zero if we cannot read it.
+fn parse_zoom_storage_size(size: &str) -> i64 {
+    let size = size.trim().to_uppercase();
+    let (number, unit) = match size.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => size.split_at(i),
+        None => (size.as_str(), "B"),
+    };
+    let multiplier: i64 = match unit.trim() {
+        "B" => 1,
+        "KB" => 1024,
+        "MB" => 1024 * 1024,
+        "GB" => BYTES_PER_GB,
+        "TB" => 1024 * BYTES_PER_GB,
+        _ => return 0,
+    };
+
+    (number.trim().parse::<f64>().unwrap_or_default() * multiplier as f64) as i64
+}
+
+/// Get how much of the cloud recording storage of the Zoom account is used, and how much the
+/// plan has, in bytes.
+async fn get_zoom_storage_usage(access_token: &str) -> Result<(i64, i64)> {
+    let usage: ZoomPlanUsage = reqwest::Client::new()
+        .get("https://api.zoom.us/v2/accounts/me/plans/usage")
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let recording = usage.plan_recording;
+    Ok((
+        parse_zoom_storage_size(&recording.plan_storage_usage) + parse_zoom_storage_size(&recording.free_storage_usage),
+        parse_zoom_storage_size(&recording.plan_storage) + parse_zoom_storage_size(&recording.free_storage),
+    ))
+}
+
+/// Returns if the storage used is close to the limit of the plan.
+pub fn is_near_zoom_storage_limit(used_bytes: i64, limit_bytes: i64) -> bool {
+    limit_bytes > 0 && used_bytes as f64 >= limit_bytes as f64 * ZOOM_STORAGE_ALERT_RATIO
+}
+
+/// Returns if we should alert about the storage used. We only alert when it gets close to the
+/// limit, not on every sync while it stays there.
+pub fn should_alert_zoom_storage(previous: Option<&ZoomStorageSnapshot>, used_bytes: i64, limit_bytes: i64) -> bool {
+    is_near_zoom_storage_limit(used_bytes, limit_bytes)
+        && !previous
+            .map(|p| is_near_zoom_storage_limit(p.used_bytes, p.limit_bytes))
+            .unwrap_or_default()
+}
+
+/// Format a number of bytes in gigabytes.
+pub fn format_gb(bytes: i64) -> String {
+    format!("{:.1} GB", bytes as f64 / BYTES_PER_GB as f64)
+}
+
+/// Sort the recordings biggest first, so the sync frees up the most storage first.
+pub fn prioritize_zoom_recordings(recordings: &mut [GetAccountCloudRecordingResponseMeetings]) {
+    recordings.sort_by_key(|r| std::cmp::Reverse(r.total_size));
+}
+
+/// Get the latest snapshot of the Zoom cloud storage of a company.
+pub async fn get_latest_zoom_storage_snapshot(
+    db: &Database,
+    cio_company_id: i32,
+) -> Result<Option<ZoomStorageSnapshot>> {
+    let snapshots = zoom_storage_snapshots::dsl::zoom_storage_snapshots
+        .filter(zoom_storage_snapshots::dsl::cio_company_id.eq(cio_company_id))
+        .order_by(zoom_storage_snapshots::dsl::taken_at.desc())
+        .limit(1)
+        .load_async::<ZoomStorageSnapshot>(db.pool())
+        .await?;
+
+    Ok(snapshots.into_iter().next())
+}
+
+/// Record how much of the cloud recording storage of the Zoom account is used, and alert the
+/// alerts channel when it gets close to the limit of the plan. We use the limit of the company
+/// if Zoom does not tell us the one of the plan.
+pub async fn record_zoom_storage_usage(
+    db: &Database,
+    company: &Company,
+    access_token: &str,
+    recordings: &[GetAccountCloudRecordingResponseMeetings],
+) -> Result<()> {
+    let (used_bytes, plan_bytes) = get_zoom_storage_usage(access_token).await?;
+    let limit_bytes = if plan_bytes > 0 {
+        plan_bytes
+    } else {
+        i64::from(company.zoom_cloud_storage_limit_gb) * BYTES_PER_GB
+    };
+
+    let previous = get_latest_zoom_storage_snapshot(db, company.id).await?;
+
+    diesel::insert_into(zoom_storage_snapshots::table)
+        .values(NewZoomStorageSnapshot {
+            used_bytes,
+            recordings: recordings.len() as i32,
+            limit_bytes,
+            taken_at: Utc::now(),
+            cio_company_id: company.id,
+        })
+        .execute_async(db.pool())
+        .await?;
+    info!(
+        "zoom cloud recordings of `{}` take up {} of {}",
+        company.name,
+        format_gb(used_bytes),
+        format_gb(limit_bytes)
+    );
+
+    if should_alert_zoom_storage(previous.as_ref(), used_bytes, limit_bytes) {
+        let channel = match company.alerts_channel("") {
+            Some(channel) => channel,
+            None => {
+                warn!(
+                    "company `{}` has no alerts channel, not alerting about the zoom cloud storage",
+                    company.name
+                );
+                return Ok(());
+            }
+        };
+
+        let msg = FormattedMessage {
+            channel,
+            blocks: vec![MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        ":warning: The Zoom cloud recordings take up {} of the {} of the plan. {} \
+                         meetings are waiting to be synced, the sync moves the biggest ones to \
+                         Google Drive first.",
+                        format_gb(used_bytes),
+                        format_gb(limit_bytes),
+                        recordings.len()
+                    ),
+                }),
+                elements: Default::default(),
+                accessory: Default::default(),
+                block_id: Default::default(),
+                fields: Default::default(),
+            }],
+            attachments: Default::default(),
+        };
+
+        company.post_to_slack_channel(db, &msg).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::{
+        format_gb, is_near_zoom_storage_limit, parse_zoom_storage_size, should_alert_zoom_storage, ZoomStorageSnapshot,
+        BYTES_PER_GB,
+    };
+
+    #[test]
+    fn test_zoom_storage_alerts() {
+        let limit = 100 * BYTES_PER_GB;
+        assert!(!is_near_zoom_storage_limit(79 * BYTES_PER_GB, limit));
+        assert!(is_near_zoom_storage_limit(80 * BYTES_PER_GB, limit));
+        // We do not know the limit.
+        assert!(!is_near_zoom_storage_limit(80 * BYTES_PER_GB, 0));
+
+        let snapshot = |used_bytes: i64| ZoomStorageSnapshot {
+            id: 1,
+            used_bytes,
+            recordings: 3,
+            limit_bytes: limit,
+            taken_at: Utc::now(),
+            cio_company_id: 1,
+        };
+        assert!(should_alert_zoom_storage(None, 90 * BYTES_PER_GB, limit));
+        assert!(should_alert_zoom_storage(
+            Some(&snapshot(50 * BYTES_PER_GB)),
+            90 * BYTES_PER_GB,
+            limit
+        ));
+        // We already alerted.
+        assert!(!should_alert_zoom_storage(
+            Some(&snapshot(85 * BYTES_PER_GB)),
+            90 * BYTES_PER_GB,
+            limit
+        ));
+        assert!(!should_alert_zoom_storage(None, 10 * BYTES_PER_GB, limit));
+
+        assert_eq!("1.5 GB", format_gb(3 * BYTES_PER_GB / 2));
+    }
+
+    #[test]
+    fn test_parse_zoom_storage_size() {
+        assert_eq!(BYTES_PER_GB, parse_zoom_storage_size("1 GB"));
+        assert_eq!(1024 * BYTES_PER_GB, parse_zoom_storage_size("1 TB"));
+        assert_eq!(3 * 1024 * 1024 / 2, parse_zoom_storage_size("1.5 MB"));
+        assert_eq!(512, parse_zoom_storage_size("512"));
+        assert_eq!(0, parse_zoom_storage_size(""));
+        assert_eq!(0, parse_zoom_storage_size("a lot"));
+    }
+}
@@ -11,7 +11,7 @@ async fn test_pushes_lead() {
         .expect("Failed to find company");
 
     let lead_id = std::env::var("LEAD_ID").unwrap().parse::<i32>().unwrap();
-    let subscriber = RackLineSubscriber::get_by_id(&db, lead_id).await.unwrap();
+    let subscriber = RackLineSubscriber::get_by_id_for_company(&db, company.id, lead_id).await.unwrap();
     let mut subscribers = vec![subscriber];
 
     let push_result = push_new_rack_line_subscribers_to_zoho(&mut subscribers, &db, &company).await;
@@ -31,6 +31,14 @@ struct Params {
     custom_partial_eq: bool,
     /// The struct item and type that we will filter on to find unique database entries.
     match_on: BTreeMap<String, String>,
+    /// The fields that are formulas, rollups or lookups in Airtable. Airtable computes them,
+    /// so we never write them back, nor compare them to know if a record changed.
+    #[serde(default)]
+    airtable_read_only: Vec<String>,
+    /// The fields that link to records of other tables. People link records from Airtable
+    /// too, so when we do not have links for one of these we keep the ones in Airtable.
+    #[serde(default)]
+    airtable_links: Vec<String>,
 }
 
 #[proc_macro_attribute]
@@ -80,6 +88,44 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
             let ident = field.ident.clone();
             struct_inners = quote!(#struct_inners#ident: item.#ident.clone(),);
         }
+
+        // Get what we need to keep the fields Airtable owns as they are in Airtable.
+        let mut clear_read_only = quote!();
+        for name in &params.airtable_read_only {
+            let field = find_field(&fields, name, &og_struct.ident);
+            // Airtable rejects writes to computed fields, so once cleared they must be skipped.
+            if !has_serde_attr(field, "skip_serializing") {
+                panic!(
+                    "`{}` is read only in Airtable, it needs `skip_serializing_if` so we do not write it",
+                    name
+                );
+            }
+            let f = format_ident!("{}", name);
+            clear_read_only = quote!(#clear_read_only self.#f = Default::default(););
+        }
+        let mut keep_links = quote!();
+        for name in &params.airtable_links {
+            let field = find_field(&fields, name, &og_struct.ident);
+            let type_ = &field.ty;
+            if quote!(#type_).to_string().replace(' ', "") != "Vec<String>" {
+                panic!(
+                    "`{}` links to records of another table, it needs to be a `Vec<String>`",
+                    name
+                );
+            }
+            let f = format_ident!("{}", name);
+            keep_links = quote! {
+                #keep_links
+                let mut links: Vec<String> = Default::default();
+                for link in self.#f.iter().chain(existing.#f.iter()) {
+                    if !link.trim().is_empty() && !links.contains(link) {
+                        links.push(link.to_string());
+                    }
+                }
+                self.#f = links;
+            };
+        }
+
         let og_struct_name = og_struct.ident;
 
         // Get the Airtable information.
@@ -157,7 +203,7 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
 
             /// Get the company object for a record.
             pub async fn company(&self, db: &crate::db::Database) -> anyhow::Result<crate::companies::Company> {
-                match crate::companies::Company::get_by_id(db, self.cio_company_id).await {
+                match crate::companies::Company::get_from_id(db, self.cio_company_id).await {
                     Ok(c) => Ok(c),
                     Err(e) => Err(anyhow::anyhow!("getting company for record `{:?}` failed: {}", self, e))
                 }
@@ -180,6 +226,18 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
             }
         }
 
+        impl crate::tenancy::CompanyScoped for #og_struct_name {
+            fn cio_company_id(&self) -> i32 {
+                self.cio_company_id
+            }
+        }
+
+        impl crate::tenancy::CompanyScoped for #new_struct_name {
+            fn cio_company_id(&self) -> i32 {
+                self.cio_company_id
+            }
+        }
+
         impl #new_struct_name {
             /// Update the record in the database and Airtable.
             pub async fn update(&self, db: &crate::db::Database) -> anyhow::Result<Self> {
@@ -205,7 +263,8 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
                 Ok(record)
             }
 
-            /// Get a record from the database.
+            /// Get a record from the database. The keys it matches on might not have the company,
+            /// check `belongs_to` before using a record found with keys from a request or webhook.
             pub async fn get_from_db(db: &crate::db::Database#args) -> Option<Self> {
                 match #db_schema::dsl::#db_schema#filter.first_async::<#new_struct_name>(db.pool()).await {
                     Ok(r) => {
@@ -218,7 +277,8 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
                 }
             }
 
-            /// Get a record by its id.
+            /// Get a record by its id, whatever company it belongs to.
+            #[deprecated(note = "the id might be of a record of another company, use `get_by_id_for_company`")]
             pub async fn get_by_id(db: &crate::db::Database, id: i32) -> anyhow::Result<Self> {
                 let record = #db_schema::dsl::#db_schema.find(id)
                     .first_async::<#new_struct_name>(db.pool()).await?;
@@ -226,9 +286,19 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
                 Ok(record)
             }
 
+            /// Get a record by its id, only if it belongs to the company. Use this rather than
+            /// `get_by_id` for the ids that come from requests and webhooks.
+            pub async fn get_by_id_for_company(db: &crate::db::Database, cio_company_id: i32, id: i32) -> anyhow::Result<Self> {
+                let record = #db_schema::dsl::#db_schema.find(id)
+                    .filter(#db_schema::dsl::cio_company_id.eq(cio_company_id))
+                    .first_async::<#new_struct_name>(db.pool()).await?;
+
+                Ok(record)
+            }
+
             /// Get the company object for a record.
             pub async fn company(&self, db: &crate::db::Database) -> anyhow::Result<crate::companies::Company> {
-                match crate::companies::Company::get_by_id(db, self.cio_company_id).await {
+                match crate::companies::Company::get_from_id(db, self.cio_company_id).await {
                     Ok(c) => Ok(c),
                     Err(e) => Err(anyhow::anyhow!("getting company for record `{:?}` failed: {}", self, e))
                 }
@@ -275,7 +345,7 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
             /// We do this in it's own function so our other functions are more DRY.
             async fn airtable_from_company_id(db: &crate::db::Database, cio_company_id: i32) -> anyhow::Result<airtable_api::Airtable> {
                 // Get the company for the company_id.
-                let company = crate::companies::Company::get_by_id(db, cio_company_id).await?;
+                let company = crate::companies::Company::get_from_id(db, cio_company_id).await?;
                 Ok(company.authenticate_airtable(&company.#airtable_base))
             }
 
@@ -285,6 +355,18 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
                 #airtable_table.to_string()
             }
 
+            /// Clear the fields Airtable computes, so we do not try to write them.
+            fn clear_airtable_read_only_fields(&mut self) {
+                #clear_read_only
+            }
+
+            /// Keep the links to other tables the Airtable record has, next to ours, so we do not
+            /// drop the links people added in Airtable.
+            #[allow(unused_variables)]
+            fn keep_airtable_links(&mut self, existing: &Self) {
+                #keep_links
+            }
+
             /// Create the row in the Airtable base.
             pub async fn create_in_airtable(&mut self, db: &crate::db::Database) -> anyhow::Result<airtable_api::Record<#new_struct_name>> {
                 let mut mut_self = self.clone();
@@ -292,6 +374,7 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
                 // We do this because where we join Airtable tables, things tend to get a little
                 // weird if we aren't nit picky about this.
                 mut_self.update_airtable_record(self.clone()).await?;
+                mut_self.clear_airtable_read_only_fields();
 
                 // Create the record.
                 let record = airtable_api::Record {
@@ -319,12 +402,17 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
                 // We do this because where we join Airtable tables, things tend to get a little
                 // weird if we aren't nit picky about this.
                 mut_self.update_airtable_record(existing_record.fields.clone()).await?;
+                mut_self.keep_airtable_links(&existing_record.fields);
+                mut_self.clear_airtable_read_only_fields();
 
                 // If the Airtable record and the record that was passed in are the same, then we can return early since
                 // we do not need to update it in Airtable.
                 // We do this after we update the record so that any fields that are links to other
                 // tables match as well and this can return true even if we have linked records.
-                if mut_self == existing_record.fields {
+                // The fields Airtable computes are left out, they never match what we have.
+                let mut existing_fields = existing_record.fields.clone();
+                existing_fields.clear_airtable_read_only_fields();
+                if mut_self == existing_fields {
                     log::info!("[airtable] id={} table={} in given object equals Airtable record, skipping update", self.id, #new_struct_name::airtable_table());
                     return Ok(existing_record.clone());
                 }
@@ -481,7 +569,7 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
                     // Get the latest of this record from the database.
                     // This make this less racy if we have a bunch and things got
                     // out of sync.
-                    if let Ok(mut vec_record) = #new_struct_name::get_by_id(db, vec_record.id).await {
+                    if let Ok(mut vec_record) = #new_struct_name::get_by_id_for_company(db, vec_record.cio_company_id, vec_record.id).await {
                         // See if we have it in our Airtable records.
                         match records.get(&vec_record.id) {
                             Some(r) => {
@@ -568,3 +656,22 @@ fn do_db(attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     }
 }
+
+/// Find a field of the struct by its name.
+fn find_field<'a>(fields: &[&'a Field], name: &str, struct_name: &syn::Ident) -> &'a Field {
+    match fields
+        .iter()
+        .find(|f| f.ident.as_ref().map(|i| i == name).unwrap_or(false))
+    {
+        Some(f) => f,
+        None => panic!("`{}` is not a field of `{}`", name, struct_name),
+    }
+}
+
+/// Return if the field has a serde attribute containing `attr`.
+fn has_serde_attr(field: &Field, attr: &str) -> bool {
+    field
+        .attrs
+        .iter()
+        .any(|a| a.path.is_ident("serde") && a.tokens.to_string().contains(attr))
+}
@@ -1,15 +1,20 @@
-use std::{env, fs::File, io::Write, process::Command, str::from_utf8, sync::Arc};
+use std::{collections::BTreeMap, env, fs::File, io::Write, process::Command, str::from_utf8, sync::Arc};
 
 use dropshot::{
     endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpResponseAccepted,
-    HttpResponseOk, HttpServerStarter, RequestContext, TypedBody,
+    HttpResponseOk, HttpServerStarter, Path, RequestContext, TypedBody,
 };
 use dropshot_verify_request::bearer::Bearer;
 use log::{info, warn};
+use schemars::JsonSchema;
 use sentry::IntoDsn;
+use serde::Deserialize;
 use uuid::Uuid;
 
-use cio_api_types::swag_inventory::PrintRequest;
+use cio_api_types::{
+    printer::{PrinterStatus, PrinterSupply},
+    swag_inventory::PrintRequest,
+};
 
 mod bearer;
 
@@ -99,6 +104,7 @@ async fn main() -> Result<(), String> {
     api.register(listen_print_receipt_requests).unwrap();
     api.register(listen_print_rollo_requests).unwrap();
     api.register(listen_print_zebra_requests).unwrap();
+    api.register(get_printer_status).unwrap();
 
     let mut api_definition = &mut api.openapi(&"Print API", &"0.0.1");
     api_definition = api_definition
@@ -255,6 +261,121 @@ async fn listen_print_receipt_requests(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
+#[derive(Deserialize, JsonSchema)]
+struct PrinterKindPathParams {
+    kind: String,
+}
+
+/** Return the state, errors and supply levels of a printer. */
+#[endpoint {
+    method = GET,
+    path = "/status/{kind}",
+}]
+async fn get_printer_status(
+    _rqctx: Arc<RequestContext<Context>>,
+    _auth: Bearer<EnvToken>,
+    path_params: Path<PrinterKindPathParams>,
+) -> Result<HttpResponseOk<PrinterStatus>, HttpError> {
+    let kind = path_params.into_inner().kind;
+    let printer = get_printer(&kind);
+    if printer.is_empty() {
+        return Err(HttpError::for_not_found(
+            None,
+            format!("there is no {} printer connected", kind),
+        ));
+    }
+
+    let output = Command::new("lpoptions")
+        .args(&["-p", &printer])
+        .output()
+        .expect("failed to execute process");
+    if !output.status.success() {
+        let e = format!(
+            "lpoptions stderr: {}\nstdout: {}",
+            from_utf8(&output.stderr).unwrap(),
+            from_utf8(&output.stdout).unwrap()
+        );
+        warn!("{}", e);
+        return Err(HttpError::for_internal_error(e));
+    }
+
+    Ok(HttpResponseOk(parse_printer_status(from_utf8(&output.stdout).unwrap())))
+}
+
+// Parse the printer attributes CUPS gives us from `lpoptions -p`, like
+// `marker-levels=12,80 marker-names=Labels,Ribbon printer-state=3`.
+fn parse_printer_status(output: &str) -> PrinterStatus {
+    let mut attributes: BTreeMap<String, String> = BTreeMap::new();
+    let mut token = String::new();
+    let mut chars = output.trim().chars();
+    let mut quote: Option<char> = None;
+    let mut tokens = Vec::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    token.push(next);
+                }
+            }
+            '\'' | '"' if quote.is_none() => quote = Some(c),
+            c if Some(c) == quote => quote = None,
+            ' ' if quote.is_none() => tokens.push(std::mem::take(&mut token)),
+            c => token.push(c),
+        }
+    }
+    tokens.push(token);
+    for token in tokens {
+        if let Some((key, value)) = token.split_once('=') {
+            attributes.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    let list = |key: &str| -> Vec<String> {
+        attributes
+            .get(key)
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let names = list("marker-names");
+    let kinds = list("marker-types");
+    let levels = list("marker-levels");
+    let supplies = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| PrinterSupply {
+            name: name.to_string(),
+            kind: kinds.get(i).cloned().unwrap_or_default(),
+            level: levels.get(i).and_then(|l| l.parse().ok()).unwrap_or(-1),
+        })
+        .collect();
+
+    let state = match attributes.get("printer-state").map(|s| s.as_str()) {
+        Some("3") => "idle",
+        Some("4") => "processing",
+        Some("5") => "stopped",
+        _ => "",
+    };
+
+    PrinterStatus {
+        state: state.to_string(),
+        accepting_jobs: attributes
+            .get("printer-is-accepting-jobs")
+            .map(|v| v == "true")
+            .unwrap_or_default(),
+        reasons: list("printer-state-reasons")
+            .into_iter()
+            .filter(|r| r != "none")
+            .collect(),
+        supplies,
+    }
+}
+
 // Return the printer we are looking for.
 fn get_printer(name: &str) -> String {
     let output = Command::new("lpstat")
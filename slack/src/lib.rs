@@ -244,6 +244,62 @@ impl Slack {
         Ok(r.user)
     }
 
+    /// Get a user.
+    /// FROM: https://api.slack.com/methods/users.info
+    pub async fn user_info(&self, user_id: &str) -> Result<User> {
+        // Build the request.
+        let request = self.request(
+            &self.token,
+            Method::GET,
+            "users.info",
+            (),
+            Some(vec![("user", user_id.to_string())]),
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: UserResponse = resp.json().await?;
+        if !r.ok {
+            bail!("getting user {} failed: {}", user_id, r.error);
+        }
+
+        Ok(r.user)
+    }
+
+    /// Find a user by their email.
+    /// FROM: https://api.slack.com/methods/users.lookupByEmail
+    pub async fn lookup_user_by_email(&self, email: &str) -> Result<User> {
+        // Build the request.
+        let request = self.request(
+            &self.token,
+            Method::GET,
+            "users.lookupByEmail",
+            (),
+            Some(vec![("email", email.to_string())]),
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: UserResponse = resp.json().await?;
+        if !r.ok {
+            bail!("looking up user with email {} failed: {}", email, r.error);
+        }
+
+        Ok(r.user)
+    }
+
     /// Get billable info.
     /// FROM: https://api.slack.com/methods/team.billableInfo
     pub async fn billable_info(&self) -> Result<HashMap<String, BillableInfo>> {
@@ -457,6 +513,111 @@ impl Slack {
         Ok(f)
     }
 
+    /// Reply to a thread.
+    /// FROM: https://api.slack.com/methods/chat.postMessage
+    pub async fn post_thread_reply(&self, channel: &str, thread_ts: &str, text: &str) -> Result<()> {
+        let body = ThreadReply {
+            channel: channel.to_string(),
+            thread_ts: thread_ts.to_string(),
+            text: text.to_string(),
+        };
+        let request = self.request(&self.token, Method::POST, "chat.postMessage", body, None)?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let f: FormattedMessageResponse = resp.json().await?;
+        if !f.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(f).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get all the messages of a thread, starting with its parent message.
+    /// FROM: https://api.slack.com/methods/conversations.replies
+    pub async fn thread_messages(&self, channel: &str, thread_ts: &str) -> Result<Vec<ThreadMessage>> {
+        let mut messages: Vec<ThreadMessage> = Default::default();
+        let mut cursor = String::new();
+        loop {
+            let mut query = vec![("channel", channel.to_string()), ("ts", thread_ts.to_string())];
+            if !cursor.is_empty() {
+                query.push(("cursor", cursor.to_string()));
+            }
+            let request = self.request(&self.token, Method::GET, "conversations.replies", (), Some(query))?;
+
+            let resp = self.client.execute(request).await?;
+            match resp.status() {
+                StatusCode::OK => (),
+                s => {
+                    bail!("status code: {}, body: {}", s, resp.text().await?);
+                }
+            };
+
+            let mut r: ThreadMessagesResponse = resp.json().await?;
+            if !r.ok {
+                bail!(
+                    "status code: {}, body: {}",
+                    StatusCode::OK,
+                    serde_json::json!(r).to_string()
+                );
+            }
+
+            messages.append(&mut r.messages);
+
+            // Paginate.
+            if r.response_metadata.next_cursor.is_empty() {
+                break;
+            }
+            cursor = r.response_metadata.next_cursor;
+        }
+
+        Ok(messages)
+    }
+
+    /// Get the permalink of a message.
+    /// FROM: https://api.slack.com/methods/chat.getPermalink
+    pub async fn permalink(&self, channel: &str, message_ts: &str) -> Result<String> {
+        let request = self.request(
+            &self.token,
+            Method::GET,
+            "chat.getPermalink",
+            (),
+            Some(vec![
+                ("channel", channel.to_string()),
+                ("message_ts", message_ts.to_string()),
+            ]),
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: PermalinkResponse = resp.json().await?;
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.permalink)
+    }
+
     /// Remove users from a workspace.
     /// FROM: https://api.slack.com/methods/admin.users.remove
     pub async fn remove_user(&self, user_id: &str) -> Result<()> {
@@ -614,6 +775,9 @@ pub struct Modal {
     pub close: MessageBlockText,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub blocks: Vec<InputBlock>,
+    /// Passed back to us when the modal is submitted, like the id of what it is about.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub private_metadata: String,
     // We only need this on deserializing.
     #[serde(default, skip_serializing)]
     pub state: State,
@@ -670,6 +834,57 @@ pub struct JoinChannelResponse {
     pub warning: String,
 }
 
+/// A reply to a thread.
+///
+/// Docs: https://api.slack.com/methods/chat.postMessage
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ThreadReply {
+    pub channel: String,
+    pub thread_ts: String,
+    pub text: String,
+}
+
+/// A message in a thread.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ThreadMessage {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub bot_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ts: String,
+}
+
+/// The messages of a thread.
+///
+/// Docs: https://api.slack.com/methods/conversations.replies
+#[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
+pub struct ThreadMessagesResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default)]
+    pub messages: Vec<ThreadMessage>,
+    #[serde(default)]
+    pub response_metadata: ResponseMetadata,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// The permalink of a message.
+///
+/// Docs: https://api.slack.com/methods/chat.getPermalink
+#[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
+pub struct PermalinkResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub permalink: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
 /// A channel list response.
 #[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
 pub struct ListChannelsResponse {
@@ -882,6 +1097,9 @@ pub struct InputBlockElement {
     pub type_: InputType,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub action_id: String,
+    /// This only applies to plain text input.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub initial_value: String,
 
     // These two only apply to static select.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -1132,6 +1350,16 @@ pub struct APIResponse {
     pub users: Vec<User>,
 }
 
+/// The data type for a user response.
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct UserResponse {
+    pub ok: bool,
+    #[serde(default)]
+    pub user: User,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
 /// The data type for a User.
 /// FROM: https://api.slack.com/types/user
 #[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
@@ -1,6 +1,15 @@
+use std::{any::Any, sync::Arc};
+
 use anyhow::Result;
 use async_trait::async_trait;
-use dropshot_verify_request::{bearer::BearerProvider, query::QueryTokenProvider};
+use cio_api::configs::User;
+use dropshot::{ApiEndpointBodyContentType, Extractor, ExtractorMetadata, HttpError, RequestContext, ServerContext};
+use dropshot_verify_request::{
+    bearer::{BearerProvider, BearerToken},
+    query::QueryTokenProvider,
+};
+use ring::constant_time::verify_slices_are_equal;
+use serde::Deserialize;
 
 pub struct InternalToken;
 
@@ -54,6 +63,24 @@ impl QueryTokenProvider for ShippoToken {
     }
 }
 
+pub struct EventbriteToken;
+
+#[async_trait]
+impl QueryTokenProvider for EventbriteToken {
+    async fn token() -> Result<String> {
+        Ok(std::env::var("EVENTBRITE_WH_KEY")?)
+    }
+}
+
+pub struct LumaToken;
+
+#[async_trait]
+impl QueryTokenProvider for LumaToken {
+    async fn token() -> Result<String> {
+        Ok(std::env::var("LUMA_WH_KEY")?)
+    }
+}
+
 pub struct MailChimpToken;
 
 #[async_trait]
@@ -62,3 +89,241 @@ impl QueryTokenProvider for MailChimpToken {
         Ok(std::env::var("MAILCHIMP_WH_KEY")?)
     }
 }
+
+/// An audience of the API. Callers get their scopes from the token they use, and only see the
+/// fields of a response their scopes allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Finance,
+    Recruiting,
+    Operations,
+}
+
+/// The environment variables holding the tokens, and the scopes each of them grants. The
+/// internal token grants all of them.
+static SCOPED_TOKENS: &[(&str, &[Scope])] = &[
+    (
+        "INTERNAL_AUTH_BEARER",
+        &[Scope::Finance, Scope::Recruiting, Scope::Operations],
+    ),
+    ("FINANCE_AUTH_BEARER", &[Scope::Finance]),
+    ("HIRING_AUTH_BEARER", &[Scope::Recruiting]),
+    ("OPERATIONS_AUTH_BEARER", &[Scope::Operations]),
+];
+
+/// The scopes of a request, from the bearer token it was made with. Extracting it fails if the
+/// token is not one of the scoped tokens.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scopes(pub Vec<Scope>);
+
+impl Scopes {
+    pub fn has(&self, scope: Scope) -> bool {
+        self.0.contains(&scope)
+    }
+
+    /// Fail with a forbidden error unless the caller has one of the scopes.
+    pub fn require_any(&self, scopes: &[Scope]) -> Result<(), HttpError> {
+        if scopes.iter().any(|s| self.has(*s)) {
+            Ok(())
+        } else {
+            Err(HttpError::for_status(None, http::StatusCode::FORBIDDEN))
+        }
+    }
+}
+
+#[async_trait]
+impl Extractor for Scopes {
+    async fn from_request<Context: ServerContext>(rqctx: Arc<RequestContext<Context>>) -> Result<Scopes, HttpError> {
+        let user_token = BearerToken::from_request(rqctx.clone())
+            .await
+            .ok()
+            .and_then(|token| token.inner().cloned());
+
+        if let Some(user_token) = user_token {
+            for (var, scopes) in SCOPED_TOKENS {
+                let expected_token = std::env::var(var).unwrap_or_default();
+                // Compare in constant time, so the time it takes does not tell how much of a token
+                // someone guessed right.
+                if !expected_token.is_empty()
+                    && verify_slices_are_equal(expected_token.as_bytes(), user_token.as_bytes()).is_ok()
+                {
+                    return Ok(Scopes(scopes.to_vec()));
+                }
+            }
+        }
+
+        log::info!(
+            "Failed to find the scopes of request. req_id: {} uri: {}",
+            rqctx.request_id,
+            rqctx.request.lock().await.uri()
+        );
+
+        Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
+    }
+
+    fn metadata(_body_content_type: ApiEndpointBodyContentType) -> ExtractorMetadata {
+        ExtractorMetadata {
+            paginated: false,
+            parameters: vec![],
+        }
+    }
+}
+
+/// The user making a request, from the Google ID token they signed in with. Extracting it fails
+/// unless the token is valid, was issued for our sign in client, and the email is verified.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserIdentity {
+    pub email: String,
+}
+
+impl UserIdentity {
+    /// Fail with a forbidden error unless the caller is the owner of the email.
+    pub fn require_email(&self, email: &str) -> Result<(), HttpError> {
+        if !self.email.is_empty() && self.email == email.trim().to_lowercase() {
+            Ok(())
+        } else {
+            Err(HttpError::for_status(None, http::StatusCode::FORBIDDEN))
+        }
+    }
+}
+
+/// The claims of a Google ID token.
+/// FROM: https://developers.google.com/identity/sign-in/web/backend-auth
+#[derive(Debug, Clone, Default, Deserialize)]
+struct GoogleTokenInfo {
+    #[serde(default)]
+    aud: String,
+    #[serde(default)]
+    email: String,
+    /// Google returns this as a string.
+    #[serde(default)]
+    email_verified: String,
+}
+
+/// Verify a Google ID token and return the email it was issued for. Google checks the
+/// signature and expiration of the token, we check it was issued for our client.
+async fn verify_google_id_token(token: &str) -> Result<String> {
+    let client_id = std::env::var("GOOGLE_SIGN_IN_CLIENT_ID")?;
+    if client_id.is_empty() {
+        anyhow::bail!("GOOGLE_SIGN_IN_CLIENT_ID is empty");
+    }
+
+    let resp = reqwest::Client::new()
+        .get("https://oauth2.googleapis.com/tokeninfo")
+        .query(&[("id_token", token)])
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("google rejected the id token: {}", resp.status());
+    }
+
+    let info: GoogleTokenInfo = resp.json().await?;
+    if info.aud != client_id {
+        anyhow::bail!("id token was issued for another client: {}", info.aud);
+    }
+    if info.email.is_empty() || info.email_verified != "true" {
+        anyhow::bail!("id token does not have a verified email");
+    }
+
+    Ok(info.email.to_lowercase())
+}
+
+#[async_trait]
+impl Extractor for UserIdentity {
+    async fn from_request<Context: ServerContext>(
+        rqctx: Arc<RequestContext<Context>>,
+    ) -> Result<UserIdentity, HttpError> {
+        let user_token = BearerToken::from_request(rqctx.clone())
+            .await
+            .ok()
+            .and_then(|token| token.inner().cloned());
+
+        if let Some(user_token) = user_token {
+            match verify_google_id_token(&user_token).await {
+                Ok(email) => return Ok(UserIdentity { email }),
+                Err(err) => log::info!(
+                    "Failed to verify the identity of request. req_id: {} err: {}",
+                    rqctx.request_id,
+                    err
+                ),
+            }
+        }
+
+        Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
+    }
+
+    fn metadata(_body_content_type: ApiEndpointBodyContentType) -> ExtractorMetadata {
+        ExtractorMetadata {
+            paginated: false,
+            parameters: vec![],
+        }
+    }
+}
+
+/// One of our users making a request. Extracting it fails unless the caller signed in with Google,
+/// like for `UserIdentity`, with the email of a user of the company. Anyone can sign in with a
+/// Google account, so the endpoints for employees need this rather than `UserIdentity`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompanyUser {
+    pub email: String,
+    pub user: User,
+}
+
+#[async_trait]
+impl Extractor for CompanyUser {
+    async fn from_request<Context: ServerContext>(
+        rqctx: Arc<RequestContext<Context>>,
+    ) -> Result<CompanyUser, HttpError> {
+        let identity = UserIdentity::from_request(rqctx.clone()).await?;
+
+        let api_context = match (rqctx.context() as &dyn Any).downcast_ref::<crate::context::ServerContext>() {
+            Some(api_context) => api_context,
+            None => {
+                return Err(HttpError::for_internal_error(
+                    "company users need the webhooky server context".to_string(),
+                ))
+            }
+        };
+
+        match crate::handlers_profile::get_user(&api_context.app, &identity.email).await {
+            Ok(user) => Ok(CompanyUser {
+                email: identity.email,
+                user,
+            }),
+            Err(err) => {
+                log::info!(
+                    "{} is not a user of the company. req_id: {} err: {}",
+                    identity.email,
+                    rqctx.request_id,
+                    err
+                );
+
+                Err(HttpError::for_status(None, http::StatusCode::FORBIDDEN))
+            }
+        }
+    }
+
+    fn metadata(_body_content_type: ApiEndpointBodyContentType) -> ExtractorMetadata {
+        ExtractorMetadata {
+            paginated: false,
+            parameters: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserIdentity;
+
+    #[test]
+    fn test_require_email() {
+        let identity = UserIdentity {
+            email: "jane@example.com".to_string(),
+        };
+
+        assert!(identity.require_email("jane@example.com").is_ok());
+        assert!(identity.require_email(" Jane@Example.com").is_ok());
+        assert!(identity.require_email("john@example.com").is_err());
+        assert!(UserIdentity::default().require_email("").is_err());
+    }
+}
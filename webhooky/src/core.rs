@@ -21,28 +21,52 @@ pub struct Opts {
 pub enum SubCommand {
     Server(Server),
 
+    Anonymize(Anonymize),
     CreateServerSpec(SpecOut),
+    PlanReconcilers(PlanReconcilers),
+    Replay(Replay),
+    SendActionItemReminders(SendActionItemReminders),
+    SendFocusTimeReport(SendFocusTimeReport),
+    SendInventoryReconciliation(SendInventoryReconciliation),
+    SendMetricsDigest(SendMetricsDigest),
     SendRFDChangelog(SendRFDChangelog),
+    SendRFDLifecycleDigest(SendRFDLifecycleDigest),
+    SendRFDReviewReminders(SendRFDReviewReminders),
+    SendWeeklyDigest(SendWeeklyDigest),
     SyncAnalytics(SyncAnalytics),
     #[clap(name = "sync-api-tokens")]
     SyncAPITokens(SyncAPITokens),
     SyncApplications(SyncApplications),
+    SyncAssetDepreciation(SyncAssetDepreciation),
     SyncAssetInventory(SyncAssetInventory),
+    SyncAssetLoaners(SyncAssetLoaners),
+    SyncAssetMaintenance(SyncAssetMaintenance),
+    SyncCommunityEvents(SyncCommunityEvents),
     SyncCompanies(SyncCompanies),
+    SyncConferences(SyncConferences),
     SyncConfigs(SyncConfigs),
+    SyncContractors(SyncContractors),
+    SyncCredentialsInventory(SyncCredentialsInventory),
+    SyncDriveUploads(SyncDriveUploads),
     SyncFinance(SyncFinance),
+    SyncFirstWeekSchedules(SyncFirstWeekSchedules),
+    SyncFocusTime(SyncFocusTime),
     SyncFunctions(SyncFunctions),
     SyncHuddles(SyncHuddles),
     SyncInterviews(SyncInterviews),
     SyncJournalClubs(SyncJournalClubs),
     SyncMailingLists(SyncMailingLists),
     SyncOther(SyncOther),
+    SyncPrinterStatus(SyncPrinterStatus),
+    SyncRecordedMeetingRetention(SyncRecordedMeetingRetention),
     SyncRecordedMeetings(SyncRecordedMeetings),
     SyncRepos(SyncRepos),
     #[clap(name = "sync-rfds")]
     SyncRFDs(SyncRFDs),
     SyncShipments(SyncShipments),
+    SyncShopify(SyncShopify),
     SyncShorturls(SyncShorturls),
+    SyncSignInAudit(SyncSignInAudit),
     SyncSwagInventory(SyncSwagInventory),
     SyncTravel(SyncTravel),
     SyncZoho(SyncZoho),
@@ -68,10 +92,70 @@ pub struct SpecOut {
     pub spec_file: std::path::PathBuf,
 }
 
+/// A subcommand for anonymizing the PII in a database restored from a production snapshot.
+#[derive(Parser, Clone, Debug)]
+pub struct Anonymize {
+    /// The salt used to derive the fake values, the same salt always produces the same fakes
+    #[clap(long, env = "CIO_ANONYMIZE_SALT")]
+    pub salt: String,
+
+    /// Confirm that the database is a staging database, this rewrites data in place
+    #[clap(long)]
+    pub confirm_staging: bool,
+}
+
+/// A subcommand for planning the changes of the syncs that reconcile services with our configs.
+#[derive(Parser, Clone, Debug)]
+pub struct PlanReconcilers {}
+
+/// A subcommand for replaying archived webhook payloads against a local server and database.
+#[derive(Parser, Clone, Debug)]
+pub struct Replay {
+    /// A file or directory of archived webhook payloads to replay
+    #[clap(parse(from_os_str), value_hint = clap::ValueHint::AnyPath)]
+    pub payloads: std::path::PathBuf,
+
+    /// IP address and port that the local server should listen on
+    #[clap(short, long, default_value = "127.0.0.1:8989")]
+    pub address: String,
+
+    /// Allow replaying against a database that is not running locally
+    #[clap(long)]
+    pub allow_remote_db: bool,
+}
+
+/// A subcommand for reminding people about their open meeting action items.
+#[derive(Parser, Clone, Debug)]
+pub struct SendActionItemReminders {}
+
+/// A subcommand for sending the weekly focus time report.
+#[derive(Parser, Clone, Debug)]
+pub struct SendFocusTimeReport {}
+
+/// A subcommand for reconciling the inventory, once a month.
+#[derive(Parser, Clone, Debug)]
+pub struct SendInventoryReconciliation {}
+
+/// A subcommand for snapshotting the metrics and sending their weekly digest.
+#[derive(Parser, Clone, Debug)]
+pub struct SendMetricsDigest {}
+
 /// A subcommand for sending the RFD changelog.
 #[derive(Parser, Clone, Debug)]
 pub struct SendRFDChangelog {}
 
+/// A subcommand for sending how long the RFDs spend in each state.
+#[derive(Parser, Clone, Debug)]
+pub struct SendRFDLifecycleDigest {}
+
+/// A subcommand for reminding reviewers about the RFDs waiting on their review.
+#[derive(Parser, Clone, Debug)]
+pub struct SendRFDReviewReminders {}
+
+/// A subcommand for sending everyone the digest of their week.
+#[derive(Parser, Clone, Debug)]
+pub struct SendWeeklyDigest {}
+
 /// A subcommand for running the background job of syncing analytics.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncAnalytics {}
@@ -84,22 +168,63 @@ pub struct SyncAPITokens {}
 #[derive(Parser, Debug, Clone)]
 pub struct SyncApplications {}
 
+/// A subcommand for running the background job of recalculating the depreciation of assets.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncAssetDepreciation {}
+
 /// A subcommand for running the background job of syncing asset inventory.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncAssetInventory {}
 
+/// A subcommand for running the background job of sending the loaner pickup and return reminders.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncAssetLoaners {}
+
+/// A subcommand for running the background job of reminding about asset warranties and maintenance.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncAssetMaintenance {}
+
+/// A subcommand for running the background job of syncing who checked in to community events.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncCommunityEvents {}
+
 /// A subcommand for running the background job of syncing companies.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncCompanies {}
 
+/// A subcommand for running the background job of syncing the conferences we sponsor, their
+/// tasks, and the swag we ship to them.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncConferences {}
+
 /// A subcommand for running the background job of syncing configs.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncConfigs {}
 
+/// A subcommand for running the background job of syncing contractor accounts.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncContractors {}
+
+/// A subcommand for running the background job of exporting the encrypted credentials inventory.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncCredentialsInventory {}
+
+/// A subcommand for running the background job of retrying failed uploads to Google Drive.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncDriveUploads {}
+
 /// A subcommand for running the background job of syncing finance data.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncFinance {}
 
+/// A subcommand for running the background job of creating the first week schedules of new hires.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncFirstWeekSchedules {}
+
+/// A subcommand for running the background job of enforcing focus time.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncFocusTime {}
+
 /// A subcommand for running the background job of syncing functions.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncFunctions {}
@@ -124,6 +249,15 @@ pub struct SyncMailingLists {}
 #[derive(Parser, Debug, Clone)]
 pub struct SyncOther {}
 
+/// A subcommand for checking on the supplies and errors of the printers.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncPrinterStatus {}
+
+/// A subcommand for running the background job of applying the retention policies to recorded meetings
+/// and interview recordings.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncRecordedMeetingRetention {}
+
 /// A subcommand for running the background job of syncing recorded_meetings.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncRecordedMeetings {}
@@ -140,10 +274,18 @@ pub struct SyncRFDs {}
 #[derive(Parser, Debug, Clone)]
 pub struct SyncShipments {}
 
+/// A subcommand for running the background job of syncing Shopify orders.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncShopify {}
+
 /// A subcommand for running the background job of syncing shorturls.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncShorturls {}
 
+/// A subcommand for running the background job of syncing sign-in audit logs.
+#[derive(Parser, Debug, Clone)]
+pub struct SyncSignInAudit {}
+
 /// A subcommand for running the background job of syncing swag inventory.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncSwagInventory {}
@@ -158,25 +300,51 @@ pub struct SyncZoho {}
 
 pub fn into_job_command(cmd: &str) -> Option<SubCommand> {
     match cmd {
+        "plan-reconcilers" => Some(SubCommand::PlanReconcilers(PlanReconcilers {})),
+        "send-action-item-reminders" => Some(SubCommand::SendActionItemReminders(SendActionItemReminders {})),
+        "send-focus-time-report" => Some(SubCommand::SendFocusTimeReport(SendFocusTimeReport {})),
+        "send-inventory-reconciliation" => {
+            Some(SubCommand::SendInventoryReconciliation(SendInventoryReconciliation {}))
+        }
+        "send-metrics-digest" => Some(SubCommand::SendMetricsDigest(SendMetricsDigest {})),
         "send-rfd-changelog" => Some(SubCommand::SendRFDChangelog(SendRFDChangelog {})),
+        "send-rfd-lifecycle-digest" => Some(SubCommand::SendRFDLifecycleDigest(SendRFDLifecycleDigest {})),
+        "send-rfd-review-reminders" => Some(SubCommand::SendRFDReviewReminders(SendRFDReviewReminders {})),
+        "send-weekly-digest" => Some(SubCommand::SendWeeklyDigest(SendWeeklyDigest {})),
         "sync-analytics" => Some(SubCommand::SyncAnalytics(SyncAnalytics {})),
         "sync-api-tokens" => Some(SubCommand::SyncAPITokens(SyncAPITokens {})),
         "sync-applications" => Some(SubCommand::SyncApplications(SyncApplications {})),
+        "sync-asset-depreciation" => Some(SubCommand::SyncAssetDepreciation(SyncAssetDepreciation {})),
         "sync-asset-inventory" => Some(SubCommand::SyncAssetInventory(SyncAssetInventory {})),
+        "sync-asset-loaners" => Some(SubCommand::SyncAssetLoaners(SyncAssetLoaners {})),
+        "sync-asset-maintenance" => Some(SubCommand::SyncAssetMaintenance(SyncAssetMaintenance {})),
+        "sync-community-events" => Some(SubCommand::SyncCommunityEvents(SyncCommunityEvents {})),
         "sync-companies" => Some(SubCommand::SyncCompanies(SyncCompanies {})),
+        "sync-conferences" => Some(SubCommand::SyncConferences(SyncConferences {})),
         "sync-configs" => Some(SubCommand::SyncConfigs(SyncConfigs {})),
+        "sync-contractors" => Some(SubCommand::SyncContractors(SyncContractors {})),
+        "sync-credentials-inventory" => Some(SubCommand::SyncCredentialsInventory(SyncCredentialsInventory {})),
+        "sync-drive-uploads" => Some(SubCommand::SyncDriveUploads(SyncDriveUploads {})),
         "sync-finance" => Some(SubCommand::SyncFinance(SyncFinance {})),
+        "sync-first-week-schedules" => Some(SubCommand::SyncFirstWeekSchedules(SyncFirstWeekSchedules {})),
+        "sync-focus-time" => Some(SubCommand::SyncFocusTime(SyncFocusTime {})),
         "sync-functions" => Some(SubCommand::SyncFunctions(SyncFunctions {})),
         "sync-huddles" => Some(SubCommand::SyncHuddles(SyncHuddles {})),
         "sync-interviews" => Some(SubCommand::SyncInterviews(SyncInterviews {})),
         "sync-journal-clubs" => Some(SubCommand::SyncJournalClubs(SyncJournalClubs {})),
         "sync-mailing-lists" => Some(SubCommand::SyncMailingLists(SyncMailingLists {})),
         "sync-other" => Some(SubCommand::SyncOther(SyncOther {})),
+        "sync-printer-status" => Some(SubCommand::SyncPrinterStatus(SyncPrinterStatus {})),
+        "sync-recorded-meeting-retention" => Some(SubCommand::SyncRecordedMeetingRetention(
+            SyncRecordedMeetingRetention {},
+        )),
         "sync-recorded-meetings" => Some(SubCommand::SyncRecordedMeetings(SyncRecordedMeetings {})),
         "sync-repos" => Some(SubCommand::SyncRepos(SyncRepos {})),
         "sync-rfds" => Some(SubCommand::SyncRFDs(SyncRFDs {})),
         "sync-shipments" => Some(SubCommand::SyncShipments(SyncShipments {})),
+        "sync-shopify" => Some(SubCommand::SyncShopify(SyncShopify {})),
         "sync-shorturls" => Some(SubCommand::SyncShorturls(SyncShorturls {})),
+        "sync-sign-in-audit" => Some(SubCommand::SyncSignInAudit(SyncSignInAudit {})),
         "sync-swag-inventory" => Some(SubCommand::SyncSwagInventory(SyncSwagInventory {})),
         "sync-travel" => Some(SubCommand::SyncTravel(SyncTravel {})),
         "sync-zoho" => Some(SubCommand::SyncZoho(SyncZoho {})),
@@ -7,20 +7,53 @@ use chrono_humanize::HumanTime;
 use cio_api::{
     analytics::NewPageView,
     applicants::Applicant,
-    asset_inventory::AssetItem,
+    asset_audits::{finish_asset_audit, scan_asset_audit, start_asset_audit, AssetAudit, AssetAuditScan},
+    asset_insurance::{generate_insurance_report, InsuranceReport},
+    asset_inventory::{
+        asset_item_airtable_link, assign_asset_item, find_asset_item, import_snipeit_assets, scan_asset_item,
+        AssetCheckout, AssetItem,
+    },
+    asset_loaners::{
+        generate_loaner_utilization_report, get_reservation, list_loaners, list_reservations, parse_slack_reservation,
+        reserve_loaner, AssetReservation, LoanerUtilization,
+    },
+    asset_orders::create_asset_items_from_email,
+    asset_refresh::{get_refresh_forecast, AssetRefreshQuarter},
     certs::Certificate,
+    community_events::{record_registration, EventbriteWebhook, LumaWebhook},
     companies::Company,
+    conferences::{import_conference_leads, ConferenceLead},
     configs::User,
+    inbound_email::InboundEmail,
+    interview_scorecards::{
+        parse_scorecard_rating, InterviewScorecard, ScorecardSubmission, INTERVIEW_SCORECARD_ACTION_ID,
+        SCORECARD_RATINGS,
+    },
     journal_clubs::JournalClubMeeting,
-    rfd::RFD,
-    schema::{applicants, inbound_shipments, journal_club_meetings, outbound_shipments},
+    printer::PrintTarget,
+    receiving::{receive_package, ReceivedPackage},
+    reconcile::{apply_reconcile_plan, get_reconcile_plan, ReconcilePlan, ReconcilePlanEntry},
+    recorded_meetings::{
+        generate_meeting_engagement_report, generate_recurring_meeting_attendance_report, get_recorded_meeting_video,
+        handle_revai_job_notification, ingest_local_recorded_meeting, MeetingEngagement, RecordedMeeting,
+        RecordedMeetingVideo, RecurringMeetingAttendance,
+    },
+    rfd::scaffold_rfd,
+    schema::{applicants, inbound_shipments, journal_club_meetings, outbound_shipments, users},
     shipments::{InboundShipment, NewInboundShipment, OutboundShipment, OutboundShipments},
-    swag_inventory::SwagInventoryItem,
+    skills::{parse_skills_list, who_knows, who_knows_slack_message, SkillsProfile},
+    swag_bundles::{create_shipment_from_bundle, SwagBundleShipment},
+    swag_inventory::{scan_swag_barcode, SwagInventoryItem, SwagItemVariant},
+    swag_requests::{create_swag_request, list_swag_requests, SwagRequest, SwagRequestForm},
     swag_store::Order,
+    tenancy::CompanyScoped,
+    transcription::RevAIJobNotification,
+    user_import::{import_users, UserImportChange},
     utils::{decode_base64, merge_json},
+    virus_scan::{alert_quarantined_file, get_virus_scanner, ScanVerdict},
 };
 use diesel::{BoolExpressionMethods, ExpressionMethods, PgTextExpressionMethods, QueryDsl};
-use dropshot::{Path, RequestContext};
+use dropshot::RequestContext;
 use google_drive::traits::{DriveOps, FileOps};
 use log::{info, warn};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
@@ -31,11 +64,17 @@ use slack_chat_api::{
 };
 
 use crate::{
-    context::ServerContext,
+    auth::{CompanyUser, UserIdentity},
+    context::{Context, ServerContext},
     handlers_github::RFDUpdater,
+    handlers_rfd::company_rfd,
     server::{
-        AirtableRowEvent, ApplicationFileUploadData, CounterResponse, GitHubRateLimit, RFDPathParams,
-        ShippoTrackingUpdateEvent,
+        AirtableRowEvent, ApplicationFileUploadData, AssetAuditScanRequest, AssetAuditStartRequest,
+        AssetRefreshForecastRequest, AssetScanRequest, ConferenceLeadsImportRequest, CounterResponse, GitHubRateLimit,
+        LoanerReservationApiRequest, LoanerReservationUpdateRequest, LoanerUtilizationReportRequest,
+        MeetingAttendanceReportRequest, MeetingEngagementReportRequest, ReceivePackageRequest,
+        RecordedMeetingUploadRequest, ShippoTrackingUpdateEvent, SwagBarcodeScanRequest, SwagBundleShipmentRequest,
+        UserImportRequest,
     },
     slack_commands::SlackCommand,
 };
@@ -65,27 +104,16 @@ pub async fn handle_products_sold_count(rqctx: Arc<RequestContext<ServerContext>
     }
 }
 
-pub async fn handle_rfd_update_by_number(
-    rqctx: Arc<RequestContext<ServerContext>>,
-    path_params: Path<RFDPathParams>,
-) -> Result<()> {
-    let num = path_params.into_inner().num;
+pub async fn handle_rfd_update_by_number(ctx: &Context, num: i32) -> Result<()> {
     info!("triggering an update for RFD number `{}`", num);
 
-    let api_context = rqctx.context();
-    let db = &api_context.app.db;
-
-    // Get the company id for Oxide.
-    // TODO: split this out per company.
-    let oxide = Company::get_from_db(db, "Oxide".to_string()).await.unwrap();
-
-    let rfd = RFD::get_from_db(db, num)
-        .await
+    let rfd = company_rfd(ctx, num)
+        .await?
         .ok_or_else(|| anyhow::anyhow!("no RFD was found with number `{}`", num))?;
 
-    let update = rfd.create_sync(&oxide).await?;
+    let update = rfd.create_sync(&ctx.company).await?;
     let updater = RFDUpdater::default();
-    updater.handle(&api_context.app, &[update]).await?;
+    updater.handle(ctx, &[update]).await?;
 
     Ok(())
 }
@@ -179,6 +207,8 @@ pub async fn handle_slack_commands(
                 status = cio_api::applicant_status::Status::Onboarding;
             } else if text.to_lowercase() == "interviewing" {
                 status = cio_api::applicant_status::Status::Interviewing;
+            } else if text.to_lowercase() == "debrief" {
+                status = cio_api::applicant_status::Status::Debrief;
             } else if text.to_lowercase() == "giving offer" {
                 status = cio_api::applicant_status::Status::GivingOffer;
             } else if text.to_lowercase() == "next steps" {
@@ -430,6 +460,149 @@ pub async fn handle_slack_commands(
                 })
             }
         }
+        SlackCommand::Skills => {
+            let user = get_user_from_slack_id(db, &company, &bot_command.user_id).await?;
+            let modal = create_slack_skills_profile_modal(&SkillsProfile::from(&user));
+
+            let slack = company.authenticate_slack(db).await?;
+            if let Err(e) = slack
+                .open_view(&View {
+                    trigger_id: bot_command.trigger_id.to_string(),
+                    view: modal.clone(),
+                })
+                .await
+            {
+                bail!("failed to open view `{}`: {}", json!(modal).to_string(), e)
+            }
+
+            json!(MessageResponse {
+                response_type: MessageResponseType::Ephemeral,
+                text: "Your skills profile is open, save it to let others find you with `/who-knows`.".to_string(),
+            })
+        }
+        SlackCommand::WhoKnows => {
+            if text.is_empty() {
+                json!(MessageResponse {
+                    response_type: MessageResponseType::Ephemeral,
+                    text: "Tell me what you are looking for, like `/who-knows postgres`.".to_string(),
+                })
+            } else {
+                let matches = who_knows(db, company.id, text).await?;
+                json!(who_knows_slack_message(text, &matches))
+            }
+        }
+        SlackCommand::Loaner => {
+            let (subcommand, args) = text.split_once(' ').unwrap_or((text, ""));
+            let text = match subcommand {
+                "reserve" => {
+                    let user = get_user_from_slack_id(db, &company, &bot_command.user_id).await?;
+                    let loaners = list_loaners(db, company.id).await?;
+                    match parse_slack_reservation(args, &user.email, &loaners) {
+                        Ok(request) => match reserve_loaner(db, &company, &request).await {
+                            Ok(reservation) => format!(
+                                "Reserved {}. I'll remind you the day before you can pick it up.",
+                                reservation.summary()
+                            ),
+                            Err(e) => format!("Sorry <@{}> :scream: {}", bot_command.user_id, e),
+                        },
+                        Err(e) => format!("Sorry <@{}> :scream: {}", bot_command.user_id, e),
+                    }
+                }
+                "cancel" => {
+                    let user = get_user_from_slack_id(db, &company, &bot_command.user_id).await?;
+                    let id: i32 = args.trim().trim_start_matches('#').parse()?;
+                    let reservation = get_reservation(db, company.id, id).await?;
+                    if reservation.email != user.email {
+                        format!(
+                            "Sorry <@{}> :scream: reservation #{} is not yours",
+                            bot_command.user_id, id
+                        )
+                    } else {
+                        match reservation.cancel(db).await {
+                            Ok(r) => format!("Cancelled {}", r.summary()),
+                            Err(e) => format!("Sorry <@{}> :scream: {}", bot_command.user_id, e),
+                        }
+                    }
+                }
+                "" | "list" => {
+                    let reservations = list_reservations(db, company.id, Utc::now().date().naive_utc()).await?;
+                    let reservations: Vec<String> = reservations
+                        .iter()
+                        .filter(|r| r.is_active())
+                        .map(|r| format!("• {}", r.summary()))
+                        .collect();
+                    if reservations.is_empty() {
+                        "No loaners are reserved, reserve one with `/loaner reserve <start> <end> <loaner or \
+                         type>[: purpose]`."
+                            .to_string()
+                    } else {
+                        format!("*Upcoming loaner reservations*\n{}", reservations.join("\n"))
+                    }
+                }
+                _ => "Try `/loaner list`, `/loaner reserve <start> <end> <loaner or type>[: purpose]`, or \
+                      `/loaner cancel <reservation>`."
+                    .to_string(),
+            };
+
+            json!(MessageResponse {
+                response_type: MessageResponseType::Ephemeral,
+                text,
+            })
+        }
+        SlackCommand::Asset => {
+            let (subcommand, args) = text.split_once(' ').unwrap_or((text, ""));
+            let text = match subcommand {
+                "" => "Try `/asset <barcode or name>` or `/asset assign <barcode or name> @user`.".to_string(),
+                "assign" => match args.trim().rsplit_once(' ') {
+                    Some((query, mention)) => match get_user_from_slack_mention(db, &company, mention).await {
+                        Ok(user) => {
+                            let assigned_by = get_user_from_slack_id(db, &company, &bot_command.user_id).await?;
+                            let config = api_context.app.app_config.read().unwrap().asset_assignment.clone();
+                            match assign_asset_item(db, &company, &config, &assigned_by, query, &user.email).await {
+                                Ok(item) => format!("Assigned {}", asset_item_slack_summary(&company, &item)),
+                                Err(e) => format!("Sorry <@{}> :scream: {}", bot_command.user_id, e),
+                            }
+                        }
+                        Err(e) => format!("Sorry <@{}> :scream: {}", bot_command.user_id, e),
+                    },
+                    None => format!(
+                        "Sorry <@{}> :scream: try `/asset assign <barcode or name> @user`",
+                        bot_command.user_id
+                    ),
+                },
+                _ => match find_asset_item(db, &company, text).await {
+                    Ok(item) => asset_item_slack_summary(&company, &item),
+                    Err(e) => format!("Sorry <@{}> :scream: {}", bot_command.user_id, e),
+                },
+            };
+
+            json!(MessageResponse {
+                response_type: MessageResponseType::Ephemeral,
+                text,
+            })
+        }
+        SlackCommand::RFD => {
+            let (subcommand, title) = text.split_once(' ').unwrap_or((text, ""));
+            let text = match (subcommand, title.trim()) {
+                ("new", title) if !title.is_empty() => {
+                    let user = get_user_from_slack_id(db, &company, &bot_command.user_id).await?;
+                    let authors = format!("{} <{}>", user.full_name(), user.email);
+                    match scaffold_rfd(db, &company, title, &authors, &user.email, None).await {
+                        Ok(rfd) => format!(
+                            "RFD {} is yours: <{}|{}> is on branch `{}`, write away!",
+                            rfd.number, rfd.link, rfd.title, rfd.branch
+                        ),
+                        Err(e) => format!("Sorry <@{}> :scream: {}", bot_command.user_id, e),
+                    }
+                }
+                _ => "Try `/rfd new <title>`.".to_string(),
+            };
+
+            json!(MessageResponse {
+                response_type: MessageResponseType::Ephemeral,
+                text,
+            })
+        }
     };
 
     Ok(response)
@@ -461,6 +634,122 @@ pub async fn handle_slack_interactive(
 
     let slack = company.authenticate_slack(db).await?;
 
+    // Handle the skills profile modal.
+    if payload.interactive_slack_payload_type == "view_submission"
+        && payload.view.callback_id == SLACK_SKILLS_PROFILE_MODAL_CALLBACK_ID
+    {
+        let mut profile = SkillsProfile::default();
+        let mut block_ids: HashMap<String, String> = HashMap::new();
+
+        if let serde_json::Value::Object(ref map) = payload.view.state.values {
+            for (block_id, v) in map {
+                if let serde_json::Value::Object(obj) = v {
+                    for (name, o) in obj {
+                        if let serde_json::Value::Object(j) = o {
+                            let list = parse_skills_list(&from_json_value_to_string(j));
+                            if name == "skills" {
+                                profile.skills = list;
+                            } else if name == "interests" {
+                                profile.interests = list;
+                            } else if name == "previous_domains" {
+                                profile.previous_domains = list;
+                            }
+                            block_ids.insert(name.to_string(), block_id.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        profile.normalize();
+        for (name, list) in [
+            ("skills", &profile.skills),
+            ("interests", &profile.interests),
+            ("previous_domains", &profile.previous_domains),
+        ] {
+            if let Err(e) = SkillsProfile::validate_list(&name.replace('_', " "), list) {
+                interactive_response.response_action = "errors".to_string();
+                interactive_response
+                    .errors
+                    .insert(block_ids.get(name).cloned().unwrap_or_default(), e.to_string());
+            }
+        }
+
+        if interactive_response.response_action.is_empty() {
+            let user = get_user_from_slack_id(db, &company, &payload.user.id).await?;
+            user.update_skills_profile(db, profile).await?;
+
+            interactive_response.response_action = "clear".to_string();
+        }
+
+        return Ok(interactive_response);
+    }
+
+    // Handle the interview scorecard modal.
+    if payload.interactive_slack_payload_type == "view_submission"
+        && payload.view.callback_id == SLACK_INTERVIEW_SCORECARD_MODAL_CALLBACK_ID
+    {
+        let mut submission = ScorecardSubmission::default();
+        let mut overall_rating_block_id = String::new();
+
+        if let serde_json::Value::Object(ref map) = payload.view.state.values {
+            for (block_id, v) in map {
+                if let serde_json::Value::Object(obj) = v {
+                    for (name, o) in obj {
+                        if let serde_json::Value::Object(j) = o {
+                            if name == "notes" {
+                                submission.notes = from_json_value_to_string(j);
+                                continue;
+                            }
+
+                            let rating = match j.get("selected_option") {
+                                Some(serde_json::Value::Object(s)) => {
+                                    parse_scorecard_rating(&from_json_value_to_string(s))
+                                }
+                                _ => 0,
+                            };
+                            if name == "technical_rating" {
+                                submission.technical_rating = rating;
+                            } else if name == "communication_rating" {
+                                submission.communication_rating = rating;
+                            } else if name == "values_rating" {
+                                submission.values_rating = rating;
+                            } else if name == "overall_rating" {
+                                overall_rating_block_id = block_id.to_string();
+                                submission.overall_rating = rating;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Err(e) = submission.validate() {
+            interactive_response.response_action = "errors".to_string();
+            interactive_response
+                .errors
+                .insert(overall_rating_block_id, e.to_string());
+            return Ok(interactive_response);
+        }
+
+        let scorecard =
+            InterviewScorecard::get_by_id_for_company(db, company.id, payload.view.private_metadata.parse()?).await?;
+        // Only the interviewer we asked can fill in their scorecard.
+        let user = get_user_from_slack_id(db, &company, &payload.user.id).await?;
+        if !user.email.eq_ignore_ascii_case(&scorecard.interviewer) {
+            bail!(
+                "{} cannot submit the scorecard of {} for applicant {}",
+                user.email,
+                scorecard.interviewer,
+                scorecard.applicant_id
+            );
+        }
+        scorecard.submit(db, &submission).await?;
+
+        interactive_response.response_action = "clear".to_string();
+        return Ok(interactive_response);
+    }
+
     // Handle the view_submission modal.
     if payload.interactive_slack_payload_type == "view_submission" {
         let values = payload.view.state.values;
@@ -524,12 +813,22 @@ pub async fn handle_slack_interactive(
             );
         } else {
             // Okay, neither are empty.
-            // Let's create the inbound shipment.
+            // Let's create the inbound shipment, for the person who is tracking it.
+            let requested_by = match get_user_from_slack_id(db, &company, &payload.user.id).await {
+                Ok(user) => user.email,
+                Err(e) => {
+                    warn!("could not find who is tracking shipment {}: {}", tracking_number, e);
+                    String::new()
+                }
+            };
             let mut shipment = NewInboundShipment {
                 name: package_name,
                 carrier: carrier.to_string(),
                 tracking_number: tracking_number.to_string(),
                 order_number,
+                requested_by,
+                received_time: None,
+                received_by: Default::default(),
                 notes,
                 cio_company_id: company.id,
                 delivered_time: None,
@@ -578,6 +877,83 @@ pub async fn handle_slack_interactive(
         return Ok(interactive_response);
     }
 
+    // Handle the summarize thread message shortcut.
+    if payload.interactive_slack_payload_type == "message_action" && payload.callback_id == "summarize_thread" {
+        // Our InteractivePayload does not have the message timestamps, so get them
+        // from the raw payload.
+        let raw: serde_json::Value = serde_json::from_str(&decoded)?;
+        let message = raw.get("message").cloned().unwrap_or_default();
+        let thread_ts = message
+            .get("thread_ts")
+            .or_else(|| message.get("ts"))
+            .and_then(|ts| ts.as_str())
+            .unwrap_or_default()
+            .to_string();
+        if thread_ts.is_empty() || payload.channel.id.is_empty() {
+            bail!(
+                "summarize thread shortcut is missing the channel or message: {}",
+                decoded
+            );
+        }
+
+        // Slack wants a response within 3 seconds and summarizing takes longer than
+        // that, so do the work in the background.
+        let db = db.clone();
+        let channel_id = payload.channel.id.to_string();
+        let summarized_by = payload.user.id.to_string();
+        tokio::spawn(async move {
+            let result = match cio_api::summarization::get_summarization_provider() {
+                Ok(provider) => {
+                    cio_api::incidents::summarize_slack_thread(
+                        &db,
+                        &company,
+                        provider.as_ref(),
+                        &channel_id,
+                        &thread_ts,
+                        &summarized_by,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = result {
+                warn!("summarizing slack thread {} in {} failed: {}", thread_ts, channel_id, e);
+                sentry::integrations::anyhow::capture_anyhow(&e);
+            }
+        });
+
+        // Return early.
+        return Ok(interactive_response);
+    }
+
+    // Handle the button in the direct message asking an interviewer for their scorecard.
+    if let Some(action) = payload
+        .actions
+        .iter()
+        .find(|a| a.action_id == INTERVIEW_SCORECARD_ACTION_ID)
+    {
+        let scorecard = InterviewScorecard::get_by_id_for_company(db, company.id, action.value.parse()?).await?;
+        let applicant = scorecard.applicant(db).await?;
+
+        // Create the modal for the scorecard.
+        let modal = create_slack_interview_scorecard_modal(&scorecard, &applicant);
+
+        // Open the view.
+        if let Err(e) = slack
+            .open_view(&View {
+                trigger_id: payload.trigger_id.to_string(),
+                view: modal.clone(),
+            })
+            .await
+        {
+            bail!("failed to open view `{}`: {}", json!(modal).to_string(), e)
+        }
+
+        // Return early.
+        return Ok(interactive_response);
+    }
+
     // Handle the actions for re-running functions.
     for action in payload.actions {
         // Trigger the action if it's a function.
@@ -603,7 +979,7 @@ pub async fn handle_airtable_employees_print_home_address_label(
     }
 
     // Get the row from airtable.
-    let user = User::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+    let user = User::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
 
     // Create a new shipment for the employee and print the label.
     user.create_shipment_to_home_address(&api_context.app.db).await?;
@@ -611,6 +987,30 @@ pub async fn handle_airtable_employees_print_home_address_label(
     Ok(())
 }
 
+pub async fn handle_airtable_employees_approve_end_date_extension(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    event: AirtableRowEvent,
+) -> Result<()> {
+    let api_context = rqctx.context();
+    let db = &api_context.app.db;
+
+    if event.record_id.is_empty() {
+        bail!("record id is empty");
+    }
+
+    // Get the row from airtable, this is where the extension is requested.
+    let requested = User::get_from_airtable(&event.record_id, db, api_context.app.company.id).await?;
+
+    // Approve the extension on the user in the database.
+    let mut user = User::get_from_db(db, api_context.app.company.id, requested.username.to_string())
+        .await
+        .ok_or_else(|| anyhow::anyhow!("no user was found with username `{}`", requested.username))?;
+    user.approve_end_date_extension(db, requested.requested_end_date)
+        .await?;
+
+    Ok(())
+}
+
 pub async fn handle_airtable_certificates_renew(
     rqctx: Arc<RequestContext<ServerContext>>,
     event: AirtableRowEvent,
@@ -622,7 +1022,8 @@ pub async fn handle_airtable_certificates_renew(
     }
 
     // Get the row from airtable.
-    let mut cert = Certificate::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+    let mut cert =
+        Certificate::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
 
     let company = cert.company(&api_context.app.db).await?;
     let storage = company.cert_storage().await?;
@@ -638,6 +1039,16 @@ pub async fn handle_airtable_certificates_renew(
     Ok(())
 }
 
+/// Return where to print for a button pressed in Airtable: on the printer it names, or in the
+/// office of who pressed it.
+async fn print_target_for_event(ctx: &Context, event: &AirtableRowEvent) -> Result<PrintTarget> {
+    if !event.printer.is_empty() {
+        return Ok(PrintTarget::printer(&event.printer));
+    }
+
+    Ok(PrintTarget::for_user(&ctx.db, &ctx.company, &event.email).await)
+}
+
 pub async fn handle_airtable_assets_items_print_barcode_label(
     rqctx: Arc<RequestContext<ServerContext>>,
     event: AirtableRowEvent,
@@ -649,126 +1060,352 @@ pub async fn handle_airtable_assets_items_print_barcode_label(
     }
 
     // Get the row from airtable.
-    let asset_item = AssetItem::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+    let asset_item =
+        AssetItem::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
 
     // Print the barcode label(s).
-    asset_item.print_label(&api_context.app.db).await?;
+    let target = print_target_for_event(&api_context.app, &event).await?;
+    asset_item.print_label(&api_context.app.db, &target).await?;
     info!("asset item {} printed label", asset_item.name);
 
     Ok(())
 }
 
-pub async fn handle_airtable_swag_inventory_items_print_barcode_labels(
-    rqctx: Arc<RequestContext<ServerContext>>,
-    event: AirtableRowEvent,
-) -> Result<()> {
+pub async fn handle_assets_insurance_report(rqctx: Arc<RequestContext<ServerContext>>) -> Result<InsuranceReport> {
     let api_context = rqctx.context();
 
-    if event.record_id.is_empty() {
-        bail!("record id is empty");
-    }
+    let company = &api_context.app.company;
 
-    // Get the row from airtable.
-    let swag_inventory_item =
-        SwagInventoryItem::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+    generate_insurance_report(&api_context.app.db, company).await
+}
 
-    // Print the barcode label(s).
-    swag_inventory_item.print_label(&api_context.app.db).await?;
-    info!("swag inventory item {} printed label", swag_inventory_item.name);
+pub async fn handle_assets_refresh_forecast(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: AssetRefreshForecastRequest,
+) -> Result<Vec<AssetRefreshQuarter>> {
+    let api_context = rqctx.context();
 
-    Ok(())
+    let company = &api_context.app.company;
+    let config = api_context.app.app_config.read().unwrap().asset_refresh.clone();
+
+    get_refresh_forecast(&api_context.app.db, company, &config, request.quarters).await
 }
 
-pub async fn handle_airtable_applicants_request_background_check(
+pub async fn handle_assets_checkout_scan(
     rqctx: Arc<RequestContext<ServerContext>>,
-    event: AirtableRowEvent,
-) -> Result<()> {
+    request: AssetScanRequest,
+) -> Result<Option<AssetCheckout>> {
     let api_context = rqctx.context();
 
-    if event.record_id.is_empty() {
-        bail!("record id is empty");
-    }
+    let company = &api_context.app.company;
 
-    // Get the row from airtable.
-    let mut applicant =
-        Applicant::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
-    if applicant.criminal_background_check_status.is_empty() {
-        // Request the background check, since we previously have not requested one.
-        applicant.send_background_check_invitation(&api_context.app.db).await?;
-        info!("sent background check invitation to applicant: {}", applicant.email);
-    }
+    scan_asset_item(&api_context.app.db, company, &request.scan).await
+}
 
-    Ok(())
+pub async fn handle_assets_snipeit_import(rqctx: Arc<RequestContext<ServerContext>>) -> Result<Vec<AssetItem>> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    import_snipeit_assets(&api_context.app.db, company).await
 }
 
-pub async fn handle_airtable_applicants_update(
+pub async fn handle_assets_audit_start(
     rqctx: Arc<RequestContext<ServerContext>>,
-    event: AirtableRowEvent,
-) -> Result<()> {
+    request: AssetAuditStartRequest,
+) -> Result<AssetAudit> {
     let api_context = rqctx.context();
 
-    if event.record_id.is_empty() {
-        bail!("record id is empty");
-    }
+    let company = &api_context.app.company;
 
-    // Get the row from airtable.
-    let applicant = Applicant::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+    start_asset_audit(&api_context.app.db, company, &request.location, &request.email).await
+}
 
-    if applicant.status.is_empty() {
-        bail!("got an empty applicant status for row: {}", applicant.email);
-    }
+pub async fn handle_assets_label_preview(ctx: &Context, id: i32) -> Result<Vec<u8>> {
+    let item = AssetItem::get_by_id_for_company(&ctx.db, ctx.company.id, id).await?;
 
-    // Grab our old applicant from the database.
-    let mut db_applicant = Applicant::get_by_id(&api_context.app.db, applicant.id).await?;
+    item.preview_label(&ctx.company).await
+}
 
-    // Grab the status and the status raw.
-    let status = cio_api::applicant_status::Status::from_str(&applicant.status).unwrap();
+pub async fn handle_assets_audit_scan(
+    ctx: &Context,
+    id: i32,
+    request: AssetAuditScanRequest,
+) -> Result<AssetAuditScan> {
+    scan_asset_audit(&ctx.db, &ctx.company, id, &request.scan).await
+}
 
-    let status_changed = db_applicant.status != status.to_string();
+pub async fn handle_assets_audit_finish(ctx: &Context, id: i32) -> Result<AssetAudit> {
+    finish_asset_audit(&ctx.db, &ctx.company, id).await
+}
 
-    db_applicant.status = status.to_string();
-    if !applicant.raw_status.is_empty() {
-        // Update the raw status if it had changed.
-        db_applicant.raw_status = applicant.raw_status.to_string();
+pub async fn handle_reconcile_plan(ctx: &Context, id: i32) -> Result<Option<ReconcilePlanEntry>> {
+    match get_reconcile_plan(&ctx.db, ctx.company.id, id).await? {
+        Some(plan) => Ok(Some(plan.into_entry()?)),
+        None => Ok(None),
     }
+}
 
-    // TODO: should we also update the start date if set in airtable?
-    // If we do this, we need to update the airtable webhook settings to include it as
-    // well.
+pub async fn handle_reconcile_plan_apply(ctx: &Context, id: i32) -> Result<Option<ReconcilePlan>> {
+    let db = &ctx.db;
+    let company = &ctx.company;
 
-    // If the status is now Giving Offer we should and it's changed from whatever it was before,
-    // let do the docusign stuff.
-    if status_changed && status == cio_api::applicant_status::Status::GivingOffer {
-        // Update the row in our database, first just in case..
-        db_applicant.update(&api_context.app.db).await?;
+    let plan = match get_reconcile_plan(db, company.id, id).await? {
+        Some(plan) => plan,
+        None => return Ok(None),
+    };
 
-        // Create our docusign client.
-        let company = db_applicant.company(&api_context.app.db).await?;
-        let dsa = company.authenticate_docusign(&api_context.app.db).await;
-        if let Ok(ds) = dsa {
-            let offer_letter = api_context
-                .app
-                .app_config
-                .read()
-                .unwrap()
-                .envelopes
-                .create_offer_letter(&db_applicant);
-            db_applicant
-                .do_docusign_offer(&api_context.app.db, &ds, offer_letter)
-                .await?;
+    info!("applying plan {} of {}", plan.id, plan.reconciler);
+    let app_config = ctx.app_config.read().unwrap().clone();
+    Ok(Some(apply_reconcile_plan(db, company, &app_config, &plan).await?))
+}
 
-            let piia_letter = api_context
-                .app
-                .app_config
-                .read()
-                .unwrap()
-                .envelopes
-                .create_piia_letter(&db_applicant);
-            db_applicant
-                .do_docusign_piia(&api_context.app.db, &ds, piia_letter)
-                .await?;
-        }
-    }
+pub async fn handle_users_import(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: UserImportRequest,
+) -> Result<Vec<UserImportChange>> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    import_users(company, request.export, request.csv.as_bytes(), request.dry_run).await
+}
+
+pub async fn handle_conference_leads_import(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: ConferenceLeadsImportRequest,
+) -> Result<Vec<ConferenceLead>> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    import_conference_leads(
+        &api_context.app.db,
+        company,
+        &request.conference,
+        request.csv.as_bytes(),
+    )
+    .await
+}
+
+pub async fn handle_recorded_meeting_upload(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: RecordedMeetingUploadRequest,
+) -> Result<RecordedMeeting> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    let contents = base64::decode(request.contents.trim())?;
+    let content_type = if request.content_type.trim().is_empty() {
+        "video/mp4"
+    } else {
+        request.content_type.trim()
+    };
+
+    ingest_local_recorded_meeting(
+        &api_context.app.db,
+        company,
+        &request.meeting,
+        request.file_name.trim(),
+        content_type,
+        contents,
+    )
+    .await
+}
+
+pub async fn handle_shipments_inbound_receive(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: ReceivePackageRequest,
+) -> Result<ReceivedPackage> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    receive_package(&api_context.app.db, company, &request.scan).await
+}
+
+pub async fn handle_assets_loaner_reservation(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: LoanerReservationApiRequest,
+) -> Result<AssetReservation> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    reserve_loaner(&api_context.app.db, company, &request.reservation).await
+}
+
+pub async fn handle_assets_loaner_reservation_update(
+    ctx: &Context,
+    request: LoanerReservationUpdateRequest,
+) -> Result<AssetReservation> {
+    let db = &ctx.db;
+
+    let reservation = get_reservation(db, ctx.company.id, request.id).await?;
+    match request.status.as_str() {
+        "cancelled" => reservation.cancel(db).await,
+        "picked up" => reservation.pick_up(db).await,
+        "returned" => reservation.return_loaner(db).await,
+        status => bail!("invalid reservation status `{}`", status),
+    }
+}
+
+pub async fn handle_assets_loaner_utilization_report(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: LoanerUtilizationReportRequest,
+) -> Result<Vec<LoanerUtilization>> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    generate_loaner_utilization_report(&api_context.app.db, company, request.days).await
+}
+
+pub async fn handle_assets_order_email(rqctx: Arc<RequestContext<ServerContext>>, email: InboundEmail) -> Result<()> {
+    let api_context = rqctx.context();
+
+    create_asset_items_from_email(&api_context.app.db, &api_context.app.company, &email).await?;
+
+    Ok(())
+}
+
+pub async fn handle_airtable_swag_inventory_items_print_barcode_labels(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    event: AirtableRowEvent,
+) -> Result<()> {
+    let api_context = rqctx.context();
+
+    if event.record_id.is_empty() {
+        bail!("record id is empty");
+    }
+
+    // Get the row from airtable.
+    let swag_inventory_item =
+        SwagInventoryItem::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
+
+    // Print the barcode label(s).
+    let target = print_target_for_event(&api_context.app, &event).await?;
+    swag_inventory_item.print_label(&api_context.app.db, &target).await?;
+    info!("swag inventory item {} printed label", swag_inventory_item.name);
+
+    Ok(())
+}
+
+pub async fn handle_airtable_swag_item_variants_print_barcode_labels(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    event: AirtableRowEvent,
+) -> Result<()> {
+    let api_context = rqctx.context();
+
+    if event.record_id.is_empty() {
+        bail!("record id is empty");
+    }
+
+    // Get the row from airtable.
+    let variant =
+        SwagItemVariant::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
+
+    // Print the barcode label(s).
+    let target = print_target_for_event(&api_context.app, &event).await?;
+    variant.print_label(&api_context.app.db, &target).await?;
+    info!("swag item variant {} printed label", variant.sku);
+
+    Ok(())
+}
+
+pub async fn handle_airtable_applicants_request_background_check(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    event: AirtableRowEvent,
+) -> Result<()> {
+    let api_context = rqctx.context();
+
+    if event.record_id.is_empty() {
+        bail!("record id is empty");
+    }
+
+    // Get the row from airtable.
+    let mut applicant =
+        Applicant::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
+    if applicant.criminal_background_check_status.is_empty() {
+        // Request the background check, since we previously have not requested one.
+        applicant.send_background_check_invitation(&api_context.app.db).await?;
+        info!("sent background check invitation to applicant: {}", applicant.email);
+    }
+
+    Ok(())
+}
+
+pub async fn handle_airtable_applicants_update(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    event: AirtableRowEvent,
+) -> Result<()> {
+    let api_context = rqctx.context();
+
+    if event.record_id.is_empty() {
+        bail!("record id is empty");
+    }
+
+    // Get the row from airtable.
+    let applicant =
+        Applicant::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
+
+    if applicant.status.is_empty() {
+        bail!("got an empty applicant status for row: {}", applicant.email);
+    }
+
+    // Grab our old applicant from the database.
+    let mut db_applicant =
+        Applicant::get_by_id_for_company(&api_context.app.db, api_context.app.company.id, applicant.id).await?;
+
+    // Grab the status and the status raw.
+    let status = cio_api::applicant_status::Status::from_str(&applicant.status).unwrap();
+
+    let status_changed = db_applicant.status != status.to_string();
+
+    db_applicant.status = status.to_string();
+    if !applicant.raw_status.is_empty() {
+        // Update the raw status if it had changed.
+        db_applicant.raw_status = applicant.raw_status.to_string();
+    }
+
+    // TODO: should we also update the start date if set in airtable?
+    // If we do this, we need to update the airtable webhook settings to include it as
+    // well.
+
+    // If the status is now Giving Offer we should and it's changed from whatever it was before,
+    // let do the docusign stuff.
+    if status_changed && status == cio_api::applicant_status::Status::GivingOffer {
+        // Update the row in our database, first just in case..
+        db_applicant.update(&api_context.app.db).await?;
+
+        // Create our docusign client.
+        let company = db_applicant.company(&api_context.app.db).await?;
+        let dsa = company.authenticate_docusign(&api_context.app.db).await;
+        if let Ok(ds) = dsa {
+            let offer_letter = api_context
+                .app
+                .app_config
+                .read()
+                .unwrap()
+                .envelopes
+                .create_offer_letter(&db_applicant);
+            db_applicant
+                .do_docusign_offer(&api_context.app.db, &ds, offer_letter)
+                .await?;
+
+            let piia_letter = api_context
+                .app
+                .app_config
+                .read()
+                .unwrap()
+                .envelopes
+                .create_piia_letter(&db_applicant);
+            db_applicant
+                .do_docusign_piia(&api_context.app.db, &ds, piia_letter)
+                .await?;
+        }
+    }
 
     // Update the row in our database.
     db_applicant.update(&api_context.app.db).await?;
@@ -788,14 +1425,16 @@ pub async fn listen_airtable_applicants_recreate_piia_webhooks(
     }
 
     // Get the row from airtable.
-    let applicant = Applicant::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+    let applicant =
+        Applicant::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
 
     if applicant.status.is_empty() {
         bail!("got an empty applicant status for row: {}", applicant.email);
     }
 
     // Grab our old applicant from the database.
-    let mut db_applicant = Applicant::get_by_id(&api_context.app.db, applicant.id).await?;
+    let mut db_applicant =
+        Applicant::get_by_id_for_company(&api_context.app.db, api_context.app.company.id, applicant.id).await?;
 
     // Create our docusign client.
     let company = db_applicant.company(&api_context.app.db).await?;
@@ -829,7 +1468,7 @@ pub async fn handle_airtable_shipments_outbound_create(
 
     // Get the row from airtable.
     let shipment =
-        OutboundShipment::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+        OutboundShipment::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
 
     // If it is a row we created from our internal store do nothing.
     if shipment.notes.contains("Oxide store")
@@ -867,10 +1506,11 @@ pub async fn handle_airtable_shipments_outbound_reprint_label(
 
     // Get the row from airtable.
     let mut shipment =
-        OutboundShipment::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+        OutboundShipment::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
 
     // Reprint the label.
-    shipment.print_label(&api_context.app.db).await?;
+    let target = print_target_for_event(&api_context.app, &event).await?;
+    shipment.print_label(&api_context.app.db, &target).await?;
     info!("shipment {} reprinted label", shipment.email);
 
     // Update the field.
@@ -894,10 +1534,11 @@ pub async fn handle_airtable_shipments_outbound_reprint_receipt(
 
     // Get the row from airtable.
     let shipment =
-        OutboundShipment::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+        OutboundShipment::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
 
     // Reprint the receipt.
-    shipment.print_receipt(&api_context.app.db).await?;
+    let target = print_target_for_event(&api_context.app, &event).await?;
+    shipment.print_receipt(&api_context.app.db, &target).await?;
     info!("shipment {} reprinted receipt", shipment.email);
 
     // Update Airtable.
@@ -918,7 +1559,7 @@ pub async fn handle_airtable_shipments_outbound_resend_shipment_status_email_to_
 
     // Get the row from airtable.
     let shipment =
-        OutboundShipment::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+        OutboundShipment::get_from_airtable(&event.record_id, &api_context.app.db, api_context.app.company.id).await?;
 
     // Resend the email to the recipient.
     shipment.send_email_to_recipient(&api_context.app.db).await?;
@@ -937,17 +1578,26 @@ pub async fn handle_airtable_shipments_outbound_schedule_pickup(
 
     // Schedule the pickup.
     let api_context = rqctx.context();
-    let company = Company::get_by_id(&api_context.app.db, event.cio_company_id).await?;
-    OutboundShipments::create_pickup(&api_context.app.db, &company).await?;
+    let company = &api_context.app.company;
+    let calendar = api_context
+        .app
+        .app_config
+        .read()
+        .unwrap()
+        .business_calendar
+        .default_calendar();
+    OutboundShipments::create_pickup(&api_context.app.db, company, &calendar).await?;
 
     Ok(())
 }
 
 pub async fn handle_applicant_review(
     rqctx: Arc<RequestContext<ServerContext>>,
-    event: cio_api::applicant_reviews::NewApplicantReview,
+    mut event: cio_api::applicant_reviews::NewApplicantReview,
 ) -> Result<()> {
     let api_context = rqctx.context();
+    // The review is for the company of the server, whatever the form says.
+    event.cio_company_id = api_context.app.company.id;
 
     if event.name.is_empty() || event.applicant.is_empty() || event.reviewer.is_empty() || event.evaluation.is_empty() {
         bail!("review is empty");
@@ -967,7 +1617,7 @@ pub async fn handle_applicant_review(
         // Get the record id for the applicant.
         review.applicant.get(0).unwrap(),
         &api_context.app.db,
-        event.cio_company_id,
+        api_context.app.company.id,
     )
     .await?;
 
@@ -999,9 +1649,11 @@ pub async fn handle_test_application_submit(
 
 pub async fn handle_application_submit(
     rqctx: Arc<RequestContext<ServerContext>>,
-    event: cio_api::application_form::ApplicationForm,
+    mut event: cio_api::application_form::ApplicationForm,
 ) -> Result<()> {
     let api_context = rqctx.context();
+    // The application is for the company of the server, whatever the form says.
+    event.cio_company_id = api_context.app.company.id;
 
     let app_config = api_context.app.app_config.read().unwrap().clone();
     event.do_form(&api_context.app.db, app_config).await?;
@@ -1020,7 +1672,6 @@ pub async fn handle_test_application_files_upload(
 
     if data.email.is_empty()
         || data.role.is_empty()
-        || data.cio_company_id <= 0
         || data.materials.is_empty()
         || data.resume.is_empty()
         || data.materials_contents.is_empty()
@@ -1089,7 +1740,6 @@ pub async fn handle_application_files_upload(
 
     if data.email.is_empty()
         || data.role.is_empty()
-        || data.cio_company_id <= 0
         || data.materials.is_empty()
         || data.resume.is_empty()
         || data.materials_contents.is_empty()
@@ -1103,7 +1753,7 @@ pub async fn handle_application_files_upload(
     let api_context = rqctx.context();
     let db = &api_context.app.db;
 
-    let company = Company::get_by_id(db, data.cio_company_id).await?;
+    let company = &api_context.app.company;
 
     // Initialize the Google Drive client.
     let drive = company.authenticate_google_drive(db).await?;
@@ -1150,6 +1800,12 @@ pub async fn handle_application_files_upload(
         );
     }
 
+    // We never store files we could not scan.
+    let scanner = match get_virus_scanner() {
+        Some(scanner) => scanner,
+        None => bail!("no virus scanner is set up, refusing to store the application files unscanned"),
+    };
+
     // Iterate over our files and create them in google drive.
     // Create or update the file in the google_drive.
     for (name, (file_path, contents)) in files {
@@ -1158,17 +1814,47 @@ pub async fn handle_application_files_upload(
         let ct = mime_guess_2::from_ext(ext).first().unwrap();
         let content_type = ct.essence_str().to_string();
         let file_name = format!("{} - {}.{}", data.user_name, name, ext);
+        let raw_bytes = decode_base64(&contents);
+
+        // Scan the file before it goes anywhere near the applicant's folder. If we cannot
+        // scan it we do not store it at all.
+        let verdict = scanner.scan(&raw_bytes).await?;
+        if let ScanVerdict::Infected(signature) = verdict {
+            warn!(
+                "application file `{}` from {} is infected with `{}`, quarantining it",
+                file_name, data.email, signature
+            );
+
+            // Keep the file for the security team, away from the application content and
+            // without linking it to the applicant.
+            let quarantine_id = drive.files().create_folder(&shared_drive.id, "", "quarantine").await?;
+            let quarantined = drive
+                .files()
+                .create_or_update(
+                    &shared_drive.id,
+                    &quarantine_id,
+                    &format!("{} - {}", data.email, file_name),
+                    "application/octet-stream",
+                    &raw_bytes,
+                )
+                .await?;
+            alert_quarantined_file(
+                db,
+                &company,
+                &file_name,
+                &signature,
+                &data.email,
+                &format!("https://drive.google.com/open?id={}", quarantined.id),
+            )
+            .await?;
+
+            continue;
+        }
 
         // Upload our file to drive.
         let drive_file = drive
             .files()
-            .create_or_update(
-                &shared_drive.id,
-                &role_folder_id,
-                &file_name,
-                &content_type,
-                &decode_base64(&contents),
-            )
+            .create_or_update(&shared_drive.id, &role_folder_id, &file_name, &content_type, &raw_bytes)
             .await?;
         // Add the file to our links.
         response.insert(
@@ -1196,7 +1882,7 @@ pub async fn handle_airtable_shipments_inbound_create(
     let db = &api_context.app.db;
 
     // Get the row from airtable.
-    let record = InboundShipment::get_from_airtable(&event.record_id, db, event.cio_company_id).await?;
+    let record = InboundShipment::get_from_airtable(&event.record_id, db, api_context.app.company.id).await?;
 
     if record.tracking_number.is_empty() || record.carrier.is_empty() {
         // Return early, we don't care.
@@ -1211,7 +1897,7 @@ pub async fn handle_airtable_shipments_inbound_create(
     if shipment.airtable_record_id.is_empty() {
         shipment.airtable_record_id = event.record_id;
     }
-    shipment.cio_company_id = event.cio_company_id;
+    shipment.cio_company_id = api_context.app.company.id;
     shipment.update(db).await?;
 
     info!("inbound shipment {} updated successfully", shipment.tracking_number);
@@ -1227,6 +1913,89 @@ pub async fn handle_store_order_create(rqctx: Arc<RequestContext<ServerContext>>
     Ok(())
 }
 
+pub async fn handle_swag_bundle_shipment_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: SwagBundleShipmentRequest,
+) -> Result<SwagBundleShipment> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    let shipment = create_shipment_from_bundle(
+        &api_context.app.db,
+        company,
+        &request.bundle,
+        &request.sizes,
+        &request.recipient,
+    )
+    .await?;
+
+    info!(
+        "{} bundle for {} created successfully",
+        request.bundle, request.recipient.email
+    );
+    Ok(shipment)
+}
+
+pub async fn handle_swag_inventory_scan(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: SwagBarcodeScanRequest,
+) -> Result<SwagInventoryItem> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    scan_swag_barcode(&api_context.app.db, company, &request.scan).await
+}
+
+pub async fn handle_swag_request_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    user: CompanyUser,
+    form: SwagRequestForm,
+) -> Result<SwagRequest> {
+    let api_context = rqctx.context();
+
+    create_swag_request(&api_context.app.db, &api_context.app.company, &user.email, &form).await
+}
+
+pub async fn handle_swag_request_list(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    user: CompanyUser,
+) -> Result<Vec<SwagRequest>> {
+    let api_context = rqctx.context();
+
+    list_swag_requests(&api_context.app.db, &api_context.app.company, &user.email).await
+}
+
+pub async fn handle_eventbrite_webhook(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    webhook: EventbriteWebhook,
+) -> Result<()> {
+    let api_context = rqctx.context();
+    let config = api_context.app.app_config.read().unwrap().community_events.clone();
+
+    for registration in webhook.registrations(&api_context.app.company).await? {
+        record_registration(&api_context.app.db, &api_context.app.company, &config, registration).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn handle_luma_webhook(rqctx: Arc<RequestContext<ServerContext>>, webhook: LumaWebhook) -> Result<()> {
+    let api_context = rqctx.context();
+    let config = api_context.app.app_config.read().unwrap().community_events.clone();
+
+    if !webhook.event_type.starts_with("guest.") {
+        info!("ignoring luma webhook `{}`", webhook.event_type);
+        return Ok(());
+    }
+
+    let registration = webhook.registration(&api_context.app.company);
+    record_registration(&api_context.app.db, &api_context.app.company, &config, registration).await?;
+
+    Ok(())
+}
+
 pub async fn handle_easypost_tracking_update(
     _rqctx: Arc<RequestContext<ServerContext>>,
     event: crate::server::EasyPostTrackingUpdateEvent,
@@ -1238,6 +2007,101 @@ pub async fn handle_easypost_tracking_update(
     Ok(())
 }
 
+pub async fn handle_revai_job_update(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    notification: RevAIJobNotification,
+    callback_secret: String,
+) -> Result<bool> {
+    handle_revai_job_notification(&rqctx.context().app.db, &notification, &callback_secret).await
+}
+
+pub async fn handle_recorded_meetings_attendance_report(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: MeetingAttendanceReportRequest,
+) -> Result<Vec<RecurringMeetingAttendance>> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    generate_recurring_meeting_attendance_report(&api_context.app.db, company, request.days).await
+}
+
+pub async fn handle_recorded_meetings_engagement_report(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    request: MeetingEngagementReportRequest,
+) -> Result<Vec<MeetingEngagement>> {
+    let api_context = rqctx.context();
+
+    let company = &api_context.app.company;
+
+    generate_meeting_engagement_report(&api_context.app.db, company, request.days, request.min_participants).await
+}
+
+/// Get the part of the recording of a meeting a player asks for, or None if the person watching
+/// cannot see the meeting.
+pub async fn handle_recorded_meeting_video(
+    ctx: &Context,
+    id: i32,
+    identity: UserIdentity,
+    range: Option<String>,
+) -> Result<Option<RecordedMeetingVideo>> {
+    let db = &ctx.db;
+
+    let company = &ctx.company;
+    let meeting = RecordedMeeting::get_by_id_for_company(db, company.id, id).await?;
+    if !meeting.can_view(company, &identity.email) {
+        info!("{} cannot watch recorded meeting {}", identity.email, id);
+        return Ok(None);
+    }
+
+    Ok(Some(
+        get_recorded_meeting_video(db, company, &meeting, range.as_deref()).await?,
+    ))
+}
+
+pub async fn handle_recorded_meeting_transcript_doc(
+    ctx: &Context,
+    id: i32,
+    identity: UserIdentity,
+) -> Result<Option<String>> {
+    let db = &ctx.db;
+
+    let company = &ctx.company;
+    let mut meeting = RecordedMeeting::get_by_id_for_company(db, company.id, id).await?;
+    if !meeting.can_view(company, &identity.email) {
+        info!(
+            "{} cannot export the transcript of recorded meeting {}",
+            identity.email, id
+        );
+        return Ok(None);
+    }
+
+    Ok(Some(meeting.export_transcript_to_doc(db, company).await?))
+}
+
+pub async fn handle_airtable_recorded_meetings_export_transcript(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    event: AirtableRowEvent,
+) -> Result<()> {
+    let api_context = rqctx.context();
+    let db = &api_context.app.db;
+
+    if event.record_id.is_empty() {
+        bail!("record id is empty");
+    }
+
+    // Get the row from airtable, then the meeting from the database since the transcript
+    // segments are keyed on it.
+    let record = RecordedMeeting::get_from_airtable(&event.record_id, db, api_context.app.company.id).await?;
+    let mut meeting = RecordedMeeting::get_from_db(db, record.google_event_id.to_string())
+        .await
+        .filter(|meeting| meeting.belongs_to(api_context.app.company.id))
+        .ok_or_else(|| anyhow::anyhow!("no recorded meeting was found for `{}`", record.google_event_id))?;
+    meeting.export_transcript_to_doc(db, &api_context.app.company).await?;
+
+    Ok(())
+}
+
 pub async fn handle_shippo_tracking_update(
     rqctx: Arc<RequestContext<ServerContext>>,
     event: serde_json::Value,
@@ -1256,6 +2120,9 @@ pub async fn handle_shippo_tracking_update(
         return Ok(());
     }
 
+    // Shipments are found by their tracking number, only update ours.
+    let company = &api_context.app.company;
+
     // Update the inbound shipment, if it exists.
     if let Some(mut shipment) = InboundShipment::get_from_db(
         &api_context.app.db,
@@ -1263,6 +2130,7 @@ pub async fn handle_shippo_tracking_update(
         ts.tracking_number.to_string(),
     )
     .await
+    .filter(|shipment| shipment.belongs_to(company.id))
     {
         shipment.expand(&api_context.app.db).await?;
     }
@@ -1274,6 +2142,7 @@ pub async fn handle_shippo_tracking_update(
         ts.tracking_number.to_string(),
     )
     .await
+    .filter(|shipment| shipment.belongs_to(company.id))
     {
         // Update the shipment in shippo.
         // TODO: we likely don't need the extra request here, but it makes the code more DRY.
@@ -1488,6 +2357,7 @@ fn create_slack_shipment_tracking_modal() -> Result<slack_chat_api::Modal> {
                 element: Some(InputBlockElement {
                     type_: InputType::PlainText,
                     action_id: "name".to_string(),
+                    initial_value: Default::default(),
                     options: vec![],
                     placeholder: None,
                 }),
@@ -1507,6 +2377,7 @@ fn create_slack_shipment_tracking_modal() -> Result<slack_chat_api::Modal> {
                 element: Some(InputBlockElement {
                     type_: InputType::StaticSelect,
                     action_id: "carrier".to_string(),
+                    initial_value: Default::default(),
                     placeholder: Some(MessageBlockText {
                         text_type: MessageType::PlainText,
                         text: "Select a shipping carrier".to_string(),
@@ -1555,6 +2426,7 @@ fn create_slack_shipment_tracking_modal() -> Result<slack_chat_api::Modal> {
                 element: Some(InputBlockElement {
                     type_: InputType::PlainText,
                     action_id: "tracking_number".to_string(),
+                    initial_value: Default::default(),
                     options: vec![],
                     placeholder: None,
                 }),
@@ -1571,6 +2443,7 @@ fn create_slack_shipment_tracking_modal() -> Result<slack_chat_api::Modal> {
                 element: Some(InputBlockElement {
                     type_: InputType::PlainText,
                     action_id: "order_number".to_string(),
+                    initial_value: Default::default(),
                     options: vec![],
                     placeholder: None,
                 }),
@@ -1587,6 +2460,7 @@ fn create_slack_shipment_tracking_modal() -> Result<slack_chat_api::Modal> {
                 element: Some(InputBlockElement {
                     type_: InputType::PlainText,
                     action_id: "notes".to_string(),
+                    initial_value: Default::default(),
                     options: vec![],
                     placeholder: None,
                 }),
@@ -1601,10 +2475,260 @@ fn create_slack_shipment_tracking_modal() -> Result<slack_chat_api::Modal> {
                 }),
             },
         ],
+        private_metadata: Default::default(),
         state: Default::default(),
     })
 }
 
+const SLACK_SKILLS_PROFILE_MODAL_CALLBACK_ID: &str = "skills_profile_modal";
+
+const SLACK_SKILLS_PROFILE_MODAL_DESCRIPTION: &str = "Share what you know and what you are interested in, separated by commas, so others can find you with `/who-knows` when staffing a project or an interview panel.";
+
+fn skills_profile_input(action_id: &str, label: &str, hint: &str, entries: &[String]) -> InputBlock {
+    InputBlock {
+        type_: MessageBlockType::Input,
+        text: None,
+        element: Some(InputBlockElement {
+            type_: InputType::PlainText,
+            action_id: action_id.to_string(),
+            initial_value: entries.join(", "),
+            options: vec![],
+            placeholder: None,
+        }),
+        label: Some(MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: label.to_string(),
+        }),
+        optional: Some(true),
+        hint: Some(MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: hint.to_string(),
+        }),
+    }
+}
+
+fn create_slack_skills_profile_modal(profile: &SkillsProfile) -> slack_chat_api::Modal {
+    slack_chat_api::Modal {
+        type_: slack_chat_api::ModalType::Modal,
+        title: MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: "Skills profile".to_string(),
+        },
+        callback_id: SLACK_SKILLS_PROFILE_MODAL_CALLBACK_ID.to_string(),
+        submit: MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: "Save".to_string(),
+        },
+        close: MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: "Cancel".to_string(),
+        },
+
+        blocks: vec![
+            InputBlock {
+                type_: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: SLACK_SKILLS_PROFILE_MODAL_DESCRIPTION.to_string(),
+                }),
+                element: None,
+                label: None,
+                optional: None,
+                hint: Default::default(),
+            },
+            skills_profile_input(
+                "skills",
+                "Skills",
+                "Things you know well, like Rust, Postgres, or PCB layout.",
+                &profile.skills,
+            ),
+            skills_profile_input(
+                "interests",
+                "Interests",
+                "Things you want to learn more about or get involved in.",
+                &profile.interests,
+            ),
+            skills_profile_input(
+                "previous_domains",
+                "Previous domains",
+                "The fields you worked in before, like storage or payments.",
+                &profile.previous_domains,
+            ),
+        ],
+        private_metadata: Default::default(),
+        state: Default::default(),
+    }
+}
+
+const SLACK_INTERVIEW_SCORECARD_MODAL_CALLBACK_ID: &str = "interview_scorecard_modal";
+
+fn scorecard_rating_input(action_id: &str, label: &str, optional: bool) -> InputBlock {
+    InputBlock {
+        type_: MessageBlockType::Input,
+        text: None,
+        element: Some(InputBlockElement {
+            type_: InputType::StaticSelect,
+            action_id: action_id.to_string(),
+            initial_value: Default::default(),
+            placeholder: Some(MessageBlockText {
+                text_type: MessageType::PlainText,
+                text: "Select a rating".to_string(),
+            }),
+            options: SCORECARD_RATINGS
+                .iter()
+                .enumerate()
+                .map(|(i, rating)| SelectInputOption {
+                    text: MessageBlockText {
+                        text_type: MessageType::PlainText,
+                        text: rating.to_string(),
+                    },
+                    value: (i + 1).to_string(),
+                })
+                .collect(),
+        }),
+        label: Some(MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: label.to_string(),
+        }),
+        optional: Some(optional),
+        hint: Default::default(),
+    }
+}
+
+fn create_slack_interview_scorecard_modal(
+    scorecard: &InterviewScorecard,
+    applicant: &Applicant,
+) -> slack_chat_api::Modal {
+    slack_chat_api::Modal {
+        type_: slack_chat_api::ModalType::Modal,
+        title: MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: "Interview scorecard".to_string(),
+        },
+        callback_id: SLACK_INTERVIEW_SCORECARD_MODAL_CALLBACK_ID.to_string(),
+        submit: MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: "Submit".to_string(),
+        },
+        close: MessageBlockText {
+            text_type: MessageType::PlainText,
+            text: "Cancel".to_string(),
+        },
+
+        blocks: vec![
+            InputBlock {
+                type_: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: format!(
+                        "Your scorecard for *{}*, who applied for {}. The hiring team sees the scorecards of the whole panel in Airtable.",
+                        applicant.name, applicant.role
+                    ),
+                }),
+                element: None,
+                label: None,
+                optional: None,
+                hint: Default::default(),
+            },
+            scorecard_rating_input("technical_rating", "Technical ability", true),
+            scorecard_rating_input("communication_rating", "Communication", true),
+            scorecard_rating_input("values_rating", "Values", true),
+            scorecard_rating_input("overall_rating", "Overall", false),
+            InputBlock {
+                type_: MessageBlockType::Input,
+                text: None,
+                element: Some(InputBlockElement {
+                    type_: InputType::PlainText,
+                    action_id: "notes".to_string(),
+                    initial_value: scorecard.notes.to_string(),
+                    options: vec![],
+                    placeholder: None,
+                }),
+                label: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: "Notes".to_string(),
+                }),
+                optional: Some(true),
+                hint: Some(MessageBlockText {
+                    text_type: MessageType::PlainText,
+                    text: "What stood out, good or bad, and what the debrief should dig into.".to_string(),
+                }),
+            },
+        ],
+        private_metadata: scorecard.id.to_string(),
+        state: Default::default(),
+    }
+}
+
+/// Get the user who sent a Slack command or interaction.
+async fn get_user_from_slack_id(db: &cio_api::db::Database, company: &Company, slack_user_id: &str) -> Result<User> {
+    let slack_user = company.authenticate_slack(db).await?.user_info(slack_user_id).await?;
+    let email = slack_user.profile.email.to_lowercase();
+    if email.is_empty() {
+        bail!("slack user `{}` does not have an email", slack_user_id);
+    }
+
+    match users::dsl::users
+        .filter(users::dsl::email.eq(email.to_string()))
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .first_async::<User>(db.pool())
+        .await
+    {
+        Ok(user) => Ok(user),
+        Err(e) => bail!("could not find user with slack email `{}`: {}", email, e),
+    }
+}
+
+/// Get the user from a mention in the text of a Slack command. Slack escapes mentions like
+/// `<@U123|jane>`, if the command does not escape them we get `@jane`, which we take as a
+/// username.
+async fn get_user_from_slack_mention(db: &cio_api::db::Database, company: &Company, mention: &str) -> Result<User> {
+    let mention = mention.trim();
+    if let Some(escaped) = mention.strip_prefix("<@").and_then(|m| m.strip_suffix('>')) {
+        let slack_user_id = escaped.split('|').next().unwrap_or_default();
+        return get_user_from_slack_id(db, company, slack_user_id).await;
+    }
+
+    let username = mention.trim_start_matches('@').to_lowercase();
+    match users::dsl::users
+        .filter(
+            users::dsl::username
+                .eq(username.to_string())
+                .or(users::dsl::email.eq(username.to_string())),
+        )
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .first_async::<User>(db.pool())
+        .await
+    {
+        Ok(user) => Ok(user),
+        Err(e) => bail!("could not find user `{}`: {}", mention, e),
+    }
+}
+
+fn asset_item_slack_summary(company: &Company, item: &AssetItem) -> String {
+    let mut summary = format!("*{}*", item.name);
+    let airtable_link = asset_item_airtable_link(company, &item.airtable_record_id);
+    if !airtable_link.is_empty() {
+        summary = format!("<{}|{}>", airtable_link, summary);
+    }
+    if !item.type_.is_empty() {
+        summary = format!("{} ({})", summary, item.type_);
+    }
+    if !item.status.is_empty() {
+        summary = format!("{}, {}", summary, item.status);
+    }
+    if !item.current_employee_borrowing.is_empty() {
+        summary = format!("{}, with {}", summary, item.current_employee_borrowing);
+    } else if !item.conference_room_using.is_empty() {
+        summary = format!("{}, in {}", summary, item.conference_room_using.join(", "));
+    }
+    if !item.barcode.is_empty() {
+        summary = format!("{} `{}`", summary, item.barcode);
+    }
+
+    summary
+}
+
 fn from_json_value_to_string(t: &serde_json::Map<String, serde_json::Value>) -> String {
     let v = t.get("value").unwrap();
     match serde_json::from_value::<String>(v.clone()) {
@@ -1612,3 +2736,248 @@ fn from_json_value_to_string(t: &serde_json::Map<String, serde_json::Value>) ->
         Err(_) => String::new(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, RwLock};
+
+    use async_bb8_diesel::AsyncRunQueryDsl;
+    use chrono::Utc;
+    use cio_api::{
+        applicant_uploads::UploadTokenStore,
+        applicant_withdrawals::ApplicantWithdrawal,
+        applicants::NewApplicant,
+        asset_audits::NewAssetAudit,
+        asset_inventory::NewAssetItem,
+        companies::NewCompany,
+        configs::UserConfig,
+        db::Database,
+        profiles::UserProfile,
+        reconcile::{ReconcilePlan, RECONCILE_PLAN_PLANNED},
+        recorded_meetings::NewRecordedMeeting,
+        rfd::NewRFD,
+        schema::{applicant_withdrawals, reconcile_plans},
+    };
+    use diesel::{ExpressionMethods, QueryDsl};
+    use serde_json::json;
+
+    use super::{
+        handle_assets_audit_finish, handle_assets_audit_scan, handle_assets_label_preview, handle_reconcile_plan,
+        handle_reconcile_plan_apply, handle_recorded_meeting_transcript_doc, handle_recorded_meeting_video,
+        handle_rfd_update_by_number,
+    };
+    use crate::{
+        auth::UserIdentity,
+        context::Context,
+        handlers_hiring::{handle_applicant_info, handle_applicant_withdrawal_confirm},
+        handlers_profile::{handle_get_profile, handle_update_profile},
+        handlers_rfd::{handle_api_rfd, handle_rfd_view},
+        server::AssetAuditScanRequest,
+    };
+
+    /// Get a context serving a company saved in the database, like the server has for the
+    /// company it runs for.
+    async fn mock_context(db: &Database, name: &str) -> Context {
+        let company: NewCompany = serde_json::from_value(json!({ "name": name })).unwrap();
+        let company = company.upsert_in_db(db).await.unwrap();
+
+        Context {
+            app_config: Arc::new(RwLock::new(Default::default())),
+            db: db.clone(),
+            company,
+            upload_token_store: UploadTokenStore::new(db.clone(), chrono::Duration::minutes(10)),
+        }
+    }
+
+    /// Save the records of one company, and try to get to them through the handlers with the
+    /// context of another company, the way a request would send their ids. The Airtable buttons
+    /// are not here: they look up their record id in the Airtable base of the server's company,
+    /// where the rows of another company are not.
+    #[ignore]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_handlers_cross_company() {
+        let db = Database::new().await;
+        let ours = mock_context(&db, "Tenancy Test Ours").await;
+        let theirs = mock_context(&db, "Tenancy Test Theirs").await;
+
+        let rfd: NewRFD = serde_json::from_value(json!({
+            "number": 9997,
+            "number_string": "9997",
+            "title": "Ours",
+            "name": "RFD 9997 Ours",
+            "state": "discussion",
+            "link": "https://github.com/example/rfd/tree/9997/rfd/9997",
+            "html": "<h1>Ours</h1>",
+            "content": ":state: discussion",
+            "pdf_link_google_drive": "",
+            "commit_date": "2024-01-09T17:00:00Z",
+            "cio_company_id": ours.company.id,
+        }))
+        .unwrap();
+        let rfd = rfd.upsert_in_db(&db).await.unwrap();
+
+        let item: NewAssetItem = serde_json::from_value(json!({
+            "name": "tenancy-test-laptop",
+            "cio_company_id": ours.company.id,
+        }))
+        .unwrap();
+        let item = item.upsert_in_db(&db).await.unwrap();
+
+        let audit = NewAssetAudit {
+            name: "Tenancy Test Audit".to_string(),
+            location: String::new(),
+            location_id: None,
+            started_by: "jane@example.com".to_string(),
+            started_at: Utc::now(),
+            finished_at: None,
+            expected_count: 0,
+            scanned_count: 0,
+            not_scanned: Default::default(),
+            unknown_barcodes: Default::default(),
+            location_mismatches: Default::default(),
+            cio_company_id: ours.company.id,
+        }
+        .upsert_in_db(&db)
+        .await
+        .unwrap();
+
+        let applicant: NewApplicant = serde_json::from_value(json!({
+            "name": "Tenancy Test",
+            "role": "Product Engineer",
+            "status": "Interviewing",
+            "submitted_time": "2024-01-09T17:00:00Z",
+            "email": "tenancy-test@example.com",
+            "cio_company_id": ours.company.id,
+        }))
+        .unwrap();
+        let applicant = applicant.upsert_in_db(&db).await.unwrap();
+
+        let withdrawal = diesel::insert_into(applicant_withdrawals::table)
+            .values((
+                applicant_withdrawals::dsl::applicant_id.eq(applicant.id),
+                applicant_withdrawals::dsl::email.eq(applicant.email.to_string()),
+                applicant_withdrawals::dsl::role.eq(applicant.role.to_string()),
+                applicant_withdrawals::dsl::stage.eq(applicant.status.to_string()),
+                applicant_withdrawals::dsl::reason.eq("I took another offer."),
+                applicant_withdrawals::dsl::reason_category.eq("other_offer"),
+                applicant_withdrawals::dsl::source.eq("email"),
+                applicant_withdrawals::dsl::cio_company_id.eq(ours.company.id),
+            ))
+            .get_result_async::<ApplicantWithdrawal>(db.pool())
+            .await
+            .unwrap();
+
+        let plan = diesel::insert_into(reconcile_plans::table)
+            .values((
+                reconcile_plans::dsl::reconciler.eq("tenancy-test"),
+                reconcile_plans::dsl::changes.eq("[]"),
+                reconcile_plans::dsl::status.eq(RECONCILE_PLAN_PLANNED),
+                reconcile_plans::dsl::error.eq(""),
+                reconcile_plans::dsl::created_at.eq(Utc::now()),
+                reconcile_plans::dsl::cio_company_id.eq(ours.company.id),
+            ))
+            .get_result_async::<ReconcilePlan>(db.pool())
+            .await
+            .unwrap();
+
+        let meeting: NewRecordedMeeting = serde_json::from_value(json!({
+            "name": "Tenancy Test Meeting",
+            "start_time": "2024-01-09T17:00:00Z",
+            "end_time": "2024-01-09T18:00:00Z",
+            "google_event_id": "tenancy-test-meeting",
+            "cio_company_id": ours.company.id,
+        }))
+        .unwrap();
+        let meeting = meeting.upsert_in_db(&db).await.unwrap();
+
+        let user: UserConfig = serde_json::from_value(json!({
+            "first_name": "Tenancy",
+            "last_name": "Test",
+            "username": "tenancy-test",
+            "email": "tenancy-test@ours.example.com",
+            "cio_company_id": ours.company.id,
+        }))
+        .unwrap();
+        let user = user.upsert_in_db(&db).await.unwrap();
+        let identity = UserIdentity {
+            email: user.email.to_string(),
+        };
+
+        assert!(handle_rfd_view(&ours, rfd.number).await.unwrap().is_some());
+        assert!(handle_rfd_view(&theirs, rfd.number).await.unwrap().is_none());
+        assert!(handle_api_rfd(&ours, rfd.number).await.unwrap().is_some());
+        assert!(handle_api_rfd(&theirs, rfd.number).await.unwrap().is_none());
+        assert!(handle_rfd_update_by_number(&theirs, rfd.number).await.is_err());
+
+        let info = handle_applicant_info(&ours, applicant.email.to_string()).await.unwrap();
+        assert!(!serde_json::to_value(info).unwrap()["application"].is_null());
+        let info = handle_applicant_info(&theirs, applicant.email.to_string())
+            .await
+            .unwrap();
+        assert!(serde_json::to_value(info).unwrap()["application"].is_null());
+        assert!(handle_applicant_withdrawal_confirm(&theirs, withdrawal.id)
+            .await
+            .is_err());
+
+        assert!(handle_reconcile_plan(&ours, plan.id).await.unwrap().is_some());
+        assert!(handle_reconcile_plan(&theirs, plan.id).await.unwrap().is_none());
+        assert!(handle_reconcile_plan_apply(&theirs, plan.id).await.unwrap().is_none());
+
+        assert!(
+            handle_recorded_meeting_video(&theirs, meeting.id, identity.clone(), None)
+                .await
+                .is_err()
+        );
+        assert!(
+            handle_recorded_meeting_transcript_doc(&theirs, meeting.id, identity.clone())
+                .await
+                .is_err()
+        );
+
+        assert!(handle_get_profile(&ours, user.email.to_string()).await.is_ok());
+        assert!(handle_get_profile(&theirs, user.email.to_string()).await.is_err());
+        assert!(
+            handle_update_profile(&theirs, user.email.to_string(), UserProfile::from(&user))
+                .await
+                .is_err()
+        );
+
+        assert!(handle_assets_label_preview(&theirs, item.id).await.is_err());
+
+        let scan = AssetAuditScanRequest {
+            scan: cio_api::asset_audits::AuditScan {
+                barcode: item.name.to_string(),
+                location: String::new(),
+                email: "john@example.com".to_string(),
+            },
+        };
+        assert!(handle_assets_audit_scan(&theirs, audit.id, scan).await.is_err());
+        assert!(handle_assets_audit_finish(&theirs, audit.id).await.is_err());
+
+        // The withdrawal was never confirmed, so the applicant is still there to delete.
+        assert!(applicant_withdrawals::dsl::applicant_withdrawals
+            .find(withdrawal.id)
+            .first_async::<ApplicantWithdrawal>(db.pool())
+            .await
+            .unwrap()
+            .confirmed_at
+            .is_none());
+        diesel::delete(applicant_withdrawals::dsl::applicant_withdrawals.find(withdrawal.id))
+            .execute_async(db.pool())
+            .await
+            .unwrap();
+        diesel::delete(reconcile_plans::dsl::reconcile_plans.find(plan.id))
+            .execute_async(db.pool())
+            .await
+            .unwrap();
+
+        rfd.delete_from_db(&db).await.unwrap();
+        item.delete_from_db(&db).await.unwrap();
+        audit.delete_from_db(&db).await.unwrap();
+        applicant.delete_from_db(&db).await.unwrap();
+        meeting.delete_from_db(&db).await.unwrap();
+        user.delete_from_db(&db).await.unwrap();
+        ours.company.delete_from_db(&db).await.unwrap();
+        theirs.company.delete_from_db(&db).await.unwrap();
+    }
+}
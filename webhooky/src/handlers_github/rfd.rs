@@ -3,7 +3,10 @@ use async_trait::async_trait;
 use cio_api::{
     core::GitHubPullRequest,
     features::Features,
-    rfd::{GitHubRFDReadmeLocation, GitHubRFDUpdate, NewRFD, RFDOutputError, RFDSearchIndex, RemoteRFD, RFD},
+    rfd::{
+        format_rfd_lint_comment, get_rfd_pdf_renderer, lint_rfd, set_rendered_commit_sha, GitHubRFDReadmeLocation,
+        GitHubRFDUpdate, NewRFD, RFDChanges, RFDContent, RFDOutputError, RFDSearchIndex, RFDState, RemoteRFD, RFD,
+    },
     shorturls::generate_shorturls_for_rfds,
     utils::{create_or_update_file_in_github_repo, decode_base64, get_file_content_from_repo},
 };
@@ -15,7 +18,10 @@ use google_storage1::{
 use log::{info, warn};
 use std::cmp::Ordering;
 
-use crate::context::Context;
+use crate::{
+    context::Context,
+    handlers_rfd::{company_rfd, notify_rfd_content_changes, notify_rfd_state_transition},
+};
 
 trait Validate {
     fn is_valid(&self) -> bool;
@@ -33,6 +39,40 @@ impl Validate for GitHubRFDUpdate {
     }
 }
 
+/// Leave a comment on the pull request of the branch of an update, if it has exactly one open.
+/// Every push runs the update again, so we do not leave the same comment twice.
+async fn comment_on_open_pull_request(update: &GitHubRFDUpdate, body: String) -> Result<()> {
+    let pull_requests = update.branch.find_pull_requests().await?;
+    let open_prs = pull_requests
+        .iter()
+        .filter(|pr| pr.state == "open")
+        .collect::<Vec<&GitHubPullRequest>>();
+
+    if let [pull_request] = open_prs.as_slice() {
+        let comments = update
+            .client()
+            .issues()
+            .list_all_comments(&update.branch.owner, &update.branch.repo, pull_request.number, None)
+            .await?;
+        if comments.iter().any(|c| c.body == body) {
+            return Ok(());
+        }
+
+        update
+            .client()
+            .issues()
+            .create_comment(
+                &update.branch.owner,
+                &update.branch.repo,
+                pull_request.number,
+                &octorust::types::PullsUpdateReviewRequest { body },
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
 pub struct RFDUpdater {
     actions: Vec<Box<dyn RFDUpdateAction + Send + Sync>>,
 }
@@ -46,6 +86,8 @@ impl Default for RFDUpdater {
             Box::new(GenerateShortUrls),
             Box::new(CreatePullRequest),
             Box::new(UpdatePullRequest),
+            Box::new(UpdateImpactLinks),
+            Box::new(UpdateRFDLinks),
             Box::new(UpdateDiscussionUrl),                    // Stops on error
             Box::new(EnsureRFDWithPullRequestIsInValidState), // Stops on error
             Box::new(EnsureRFDOnDefaultIsInValidState),       // Stops on error
@@ -93,7 +135,11 @@ impl RFDUpdater {
         info!("Updating RFD {} on the {} branch", update.number, update.branch.branch);
 
         // Fetch the latest RFD information from GitHub
-        let RemoteRFD { rfd: new_rfd, location } = NewRFD::new_from_update(&api_context.company, update).await?;
+        let RemoteRFD {
+            rfd: mut new_rfd,
+            location,
+            commit_sha,
+        } = NewRFD::new_from_update(&api_context.company, update).await?;
 
         info!(
             "Generated RFD {} from branch {} on GitHub",
@@ -103,7 +149,7 @@ impl RFDUpdater {
         // Get the old RFD from the database.
         // DO THIS BEFORE UPDATING THE RFD.
         // We will need this later to check if the RFD's state changed.
-        let old_rfd = RFD::get_from_db(&api_context.db, new_rfd.number).await;
+        let old_rfd = company_rfd(api_context, new_rfd.number).await?;
 
         info!(
             "Checked for existing version of RFD {} in the database: {}",
@@ -111,18 +157,96 @@ impl RFDUpdater {
             old_rfd.is_some()
         );
 
+        // Make sure the front matter is sound before we store anything, a broken record is
+        // worse than a stale one.
+        let content = RFDContent::new(new_rfd.content.as_str())?;
+        let existing_authors = old_rfd.as_ref().map(|r| r.authors.as_str()).unwrap_or_default();
+        let problems = lint_rfd(&api_context.db, &api_context.company, &content, existing_authors).await?;
+        if !problems.is_empty() {
+            comment_on_open_pull_request(update, format_rfd_lint_comment(&update.number, &problems)).await?;
+
+            return Err(anyhow!(
+                "RFD {} on {} has problems with its front matter: {}",
+                update.number,
+                update.branch.branch,
+                problems.join(" ")
+            ));
+        }
+
+        if let Some(old_rfd) = &old_rfd {
+            // RFDs only move forward, a branch with an older state can not take them back.
+            if let Err(e) = RFDState::check_transition(&old_rfd.state, &new_rfd.state) {
+                comment_on_open_pull_request(
+                    update,
+                    format!(
+                        "RFD {} is in `{}` and can not move to `{}`, so we did not update it. RFDs go from \
+                         ideation to discussion to published, and then committed or abandoned.",
+                        update.number.as_number_string(),
+                        old_rfd.state,
+                        new_rfd.state
+                    ),
+                )
+                .await?;
+
+                return Err(anyhow!("RFD {} on {}: {}", update.number, update.branch.branch, e));
+            }
+
+            // The discussion is synced on its own, keep what we know about it.
+            new_rfd.unresolved_discussion_threads = old_rfd.unresolved_discussion_threads;
+            new_rfd.reviewers = old_rfd.reviewers.clone();
+            new_rfd.approvals = old_rfd.approvals.clone();
+        }
+
         // Update the RFD in the database.
         let mut rfd = new_rfd.upsert(&api_context.db).await?;
 
         info!("Upserted RFD {} in to the database", rfd.number);
 
         // The RFD has been stored internally, now trigger the update actions
-        self.run_actions(api_context, update, &location, old_rfd.as_ref(), &mut rfd)
+        let completed = self
+            .run_actions(api_context, update, &location, old_rfd.as_ref(), &mut rfd)
             .await?;
 
         // Perform a final update to capture and modifications made during update actions
         rfd.update(&api_context.db).await?;
 
+        // The update actions can move the RFD too, so we announce where it ended up.
+        if let Some(old_rfd) = &old_rfd {
+            if let Ok(Some((from, to))) = RFDState::check_transition(&old_rfd.state, &rfd.state) {
+                if let Err(e) = rfd.record_state_transition(&api_context.db, &from.to_string()).await {
+                    warn!("Failed to record the state change of RFD {}: {}", rfd.number, e);
+                }
+
+                if let Err(e) = notify_rfd_state_transition(api_context, &rfd, from, to).await {
+                    warn!("Failed to announce the state change of RFD {}: {}", rfd.number, e);
+                }
+            }
+
+            // Changes to the title and metadata are left out, we only summarize the sections.
+            let changes = RFDChanges::new(&old_rfd.content, &rfd.content);
+            if !changes.is_empty() {
+                if let Err(e) = notify_rfd_content_changes(api_context, &rfd, &changes).await {
+                    warn!("Failed to announce the changes to RFD {}: {}", rfd.number, e);
+                }
+            }
+        } else if let Err(e) = rfd.record_state_transition(&api_context.db, "").await {
+            // The first state of the RFD, so we know how long it takes to be published.
+            warn!("Failed to record the state of the new RFD {}: {}", rfd.number, e);
+        }
+
+        // Only skip this commit in the next syncs if every action went through, so the ones
+        // that failed get another try.
+        if completed {
+            set_rendered_commit_sha(
+                &api_context.db,
+                api_context.company.id,
+                rfd.number,
+                &update.branch.branch,
+                &commit_sha,
+            )
+            .await?;
+        }
+
         info!(
             "Update for RFD {} via the {} branch completed",
             rfd.number, update.branch.branch
@@ -138,7 +262,7 @@ impl RFDUpdater {
         location: &GitHubRFDReadmeLocation,
         old_rfd: Option<&RFD>,
         rfd: &mut RFD,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let github = update.client();
         let pull_requests = update.branch.find_pull_requests().await?;
 
@@ -152,12 +276,14 @@ impl RFDUpdater {
         };
 
         let mut responses = vec![];
+        let mut completed = true;
 
         for action in &self.actions {
             match action.run(&mut ctx, rfd).await {
                 Ok(response) => responses.push(response),
                 Err(err) => match err {
                     RFDUpdateActionErr::Continue(action_err) => {
+                        completed = false;
                         warn!(
                             "Updating RFD {} on {} errored with non-fatal error {:?}",
                             update.number, update.branch.branch, action_err
@@ -191,7 +317,7 @@ impl RFDUpdater {
             .await?;
         }
 
-        Ok(())
+        Ok(completed)
     }
 }
 
@@ -327,8 +453,13 @@ pub struct UpdatePDFs;
 
 impl UpdatePDFs {
     async fn upload(api_context: &Context, update: &GitHubRFDUpdate, rfd: &mut RFD) -> Result<()> {
-        // Generate the PDFs for the RFD
-        let pdf = match rfd.content()?.to_pdf(&rfd.title, &update.number, &update.branch).await {
+        // Generate the PDFs for the RFD, with the renderer the company picked
+        let renderer = get_rfd_pdf_renderer(&api_context.company);
+        let pdf = match rfd
+            .content()?
+            .to_pdf(renderer, &rfd.title, &update.number, &update.branch)
+            .await
+        {
             Ok(pdf) => pdf,
             Err(err) => {
                 match &err {
@@ -741,6 +872,68 @@ impl RFDUpdateAction for UpdateDiscussionUrl {
     }
 }
 
+pub struct UpdateImpactLinks;
+
+#[async_trait]
+impl RFDUpdateAction for UpdateImpactLinks {
+    async fn run(
+        &self,
+        ctx: &mut RFDUpdateActionContext,
+        rfd: &mut RFD,
+    ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
+        let RFDUpdateActionContext {
+            api_context,
+            github,
+            old_rfd,
+            ..
+        } = ctx;
+
+        let impacts = rfd
+            .impacts(&api_context.company)
+            .map_err(RFDUpdateActionErr::Continue)?;
+        rfd.set_impact_links(&api_context.db, &api_context.company, &impacts)
+            .await
+            .map_err(RFDUpdateActionErr::Continue)?;
+
+        // Only let the affected repositories know the first time the RFD is published.
+        let was_published = old_rfd.map(|rfd| rfd.state == "published").unwrap_or(false);
+        if rfd.state == "published" && !was_published && !impacts.is_empty() {
+            rfd.notify_impacts_of_publish(github, &api_context.company, &impacts)
+                .await
+                .map_err(RFDUpdateActionErr::Continue)?;
+
+            info!(
+                "[SUCCESS]: RFD {} was published, notified {} affected repositories and issues",
+                rfd.number_string,
+                impacts.len()
+            );
+        }
+
+        Ok(RFDUpdateActionResponse::default())
+    }
+}
+
+pub struct UpdateRFDLinks;
+
+#[async_trait]
+impl RFDUpdateAction for UpdateRFDLinks {
+    async fn run(
+        &self,
+        ctx: &mut RFDUpdateActionContext,
+        rfd: &mut RFD,
+    ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
+        let RFDUpdateActionContext { api_context, .. } = ctx;
+
+        let references = rfd.references();
+        rfd.set_rfd_links(&api_context.db, &api_context.company, &references)
+            .await
+            .map_err(RFDUpdateActionErr::Continue)?;
+        info!("RFD {} references {} other RFDs", rfd.number, references.len());
+
+        Ok(RFDUpdateActionResponse::default())
+    }
+}
+
 pub struct EnsureRFDWithPullRequestIsInValidState;
 
 #[async_trait]
@@ -1,16 +1,27 @@
 use anyhow::Result;
 use async_bb8_diesel::AsyncRunQueryDsl;
 use chrono::{DateTime, Utc};
-use cio_api::{applicants::Applicant, schema::applicants};
+use cio_api::{
+    applicant_withdrawals::{
+        confirm_applicant_withdrawal, queue_withdrawal_from_email, withdraw_applicant, ApplicantWithdrawal,
+        WITHDRAWAL_SOURCE_REQUEST,
+    },
+    applicants::Applicant,
+    inbound_email::InboundEmail,
+    interviews::{schedule_interview, ApplicantInterview, ScheduleInterviewRequest},
+    roles::{Role, Roles},
+    schema::applicants,
+};
 use diesel::{ExpressionMethods, QueryDsl};
 use schemars::JsonSchema;
 use serde::Serialize;
 
-use crate::context::Context;
+use crate::{context::Context, server::ApplicantWithdrawRequest};
 
 #[derive(Debug, Serialize, JsonSchema)]
 pub struct ApplicationView {
     role: String,
+    catalog_role: Option<Role>,
     submitted_at: DateTime<Utc>,
     status: String,
 }
@@ -27,18 +38,33 @@ pub async fn handle_applicant_info(ctx: &Context, email: String) -> Result<Appli
     // records being created
     let applicants = applicants::dsl::applicants
         .filter(applicants::dsl::email.eq(email.clone()))
+        .filter(applicants::dsl::cio_company_id.eq(ctx.company.id))
         .order_by(applicants::dsl::id.desc())
         .load_async::<Applicant>(ctx.db.pool())
         .await?;
 
-    Ok(ApplicantInfo {
-        email,
-        application: applicants.into_iter().next().map(|applicant| ApplicationView {
+    let application = match applicants.into_iter().next() {
+        Some(applicant) => Some(ApplicationView {
+            catalog_role: applicant.catalog_role(&ctx.db).await?,
             role: applicant.role,
             submitted_at: applicant.submitted_time,
             status: applicant.status,
         }),
-    })
+        None => None,
+    };
+
+    Ok(ApplicantInfo { email, application })
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RoleCatalog {
+    roles: Vec<Role>,
+}
+
+pub async fn handle_roles(ctx: &Context) -> Result<RoleCatalog> {
+    let roles = Roles::get_from_db(&ctx.db, ctx.company.id).await?;
+
+    Ok(RoleCatalog { roles: roles.0 })
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
@@ -55,3 +81,44 @@ pub async fn handle_applicant_upload_token(ctx: &Context, email: String) -> Resu
         token: token.token,
     })
 }
+
+pub async fn handle_applicant_withdraw(
+    ctx: &Context,
+    request: ApplicantWithdrawRequest,
+) -> Result<ApplicantWithdrawal> {
+    withdraw_applicant(
+        &ctx.db,
+        &ctx.company,
+        &request.email,
+        request.role.as_deref(),
+        &request.reason,
+        WITHDRAWAL_SOURCE_REQUEST,
+    )
+    .await
+}
+
+pub async fn handle_applicant_withdraw_email(ctx: &Context, email: InboundEmail) -> Result<()> {
+    queue_withdrawal_from_email(&ctx.db, &ctx.company, &email).await?;
+
+    Ok(())
+}
+
+pub async fn handle_applicant_withdrawal_confirm(ctx: &Context, id: i32) -> Result<ApplicantWithdrawal> {
+    confirm_applicant_withdrawal(&ctx.db, &ctx.company, id).await
+}
+
+pub async fn handle_applicant_interview_schedule(
+    ctx: &Context,
+    request: ScheduleInterviewRequest,
+) -> Result<ApplicantInterview> {
+    let app_config = ctx.app_config.read().unwrap().clone();
+
+    schedule_interview(
+        &ctx.db,
+        &ctx.company,
+        &app_config.apply,
+        &app_config.business_calendar,
+        &request,
+    )
+    .await
+}
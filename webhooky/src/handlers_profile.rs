@@ -0,0 +1,37 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use cio_api::{
+    configs::User,
+    profiles::UserProfile,
+    schema::users,
+    skills::{who_knows, SkillMatch},
+};
+use diesel::{ExpressionMethods, QueryDsl};
+
+use crate::context::Context;
+
+pub async fn get_user(ctx: &Context, email: &str) -> Result<User> {
+    Ok(users::dsl::users
+        .filter(users::dsl::email.eq(email.to_lowercase()))
+        .filter(users::dsl::cio_company_id.eq(ctx.company.id))
+        .first_async::<User>(ctx.db.pool())
+        .await?)
+}
+
+pub async fn handle_get_profile(ctx: &Context, email: String) -> Result<UserProfile> {
+    let user = get_user(ctx, &email).await?;
+
+    Ok(UserProfile::from(&user))
+}
+
+pub async fn handle_update_profile(ctx: &Context, email: String, profile: UserProfile) -> Result<UserProfile> {
+    let user = get_user(ctx, &email).await?;
+
+    let (user, _) = user.update_profile(&ctx.db, &ctx.company, profile).await?;
+
+    Ok(UserProfile::from(&user))
+}
+
+pub async fn handle_search_skills(ctx: &Context, topic: String) -> Result<Vec<SkillMatch>> {
+    who_knows(&ctx.db, ctx.company.id, &topic).await
+}
@@ -1,19 +1,28 @@
 use anyhow::Result;
 use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
 use cio_api::{
-    rfd::{GitHubRFDRepo, NewRFD, RFDEntry, RFDIndexEntry, RFDs, RFD},
+    rfd::{
+        get_rendered_commit_sha, get_rfd_graph, get_rfd_impact_links_for_repo, get_rfd_lifecycle_metrics, is_rendered,
+        reserve_rfd_number, scaffold_rfd, search_rfds, taken_rfd_numbers, GitHubRFDRepo, GitHubRFDUpdate, NewRFD,
+        RFDChanges, RFDEntry, RFDGraph, RFDIndexEntry, RFDLifecycleMetrics, RFDNumberReservation, RFDSearchResult,
+        RFDState, RFDs, ScaffoldedRFD, RFD, RFD_SEARCH_MAX_RESULTS,
+    },
     schema::rfds,
 };
 use diesel::{ExpressionMethods, QueryDsl};
 use log::{info, warn};
+use schemars::JsonSchema;
+use serde::Serialize;
+use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
 
 use crate::{
     context::Context,
     handlers_github::{
         rfd::{
             CopyImagesToGCP, CreatePullRequest, EnsureRFDOnDefaultIsInValidState,
-            EnsureRFDWithPullRequestIsInValidState, GenerateShortUrls, UpdateDiscussionUrl, UpdatePDFs,
-            UpdatePullRequest, UpdateSearch,
+            EnsureRFDWithPullRequestIsInValidState, GenerateShortUrls, UpdateDiscussionUrl, UpdateImpactLinks,
+            UpdatePDFs, UpdatePullRequest, UpdateRFDLinks, UpdateSearch,
         },
         RFDUpdater,
     },
@@ -21,6 +30,7 @@ use crate::{
 
 pub async fn handle_rfd_index(ctx: &Context, offset: i32, limit: u32) -> Result<Vec<RFDIndexEntry>> {
     let rfds = rfds::dsl::rfds
+        .filter(rfds::dsl::cio_company_id.eq(ctx.company.id))
         .order_by(rfds::dsl::number)
         .offset(offset as i64)
         .limit(limit as i64)
@@ -48,23 +58,299 @@ pub async fn handle_rfd_index(ctx: &Context, offset: i32, limit: u32) -> Result<
 }
 
 pub async fn handle_rfd_view(ctx: &Context, num: i32) -> Result<Option<RFDEntry>> {
-    let mut rfd = rfds::dsl::rfds
-        .filter(rfds::dsl::number.eq(num))
+    Ok(company_rfd(ctx, num).await?.map(|rfd| {
+        let new_rfd: NewRFD = rfd.into();
+        new_rfd.into()
+    }))
+}
+
+/// Get an RFD of the company of the context. RFDs are found by their number, which every
+/// company has its own of.
+pub(crate) async fn company_rfd(ctx: &Context, number: i32) -> Result<Option<RFD>> {
+    let mut rfds = rfds::dsl::rfds
+        .filter(rfds::dsl::cio_company_id.eq(ctx.company.id))
+        .filter(rfds::dsl::number.eq(number))
+        .limit(1)
         .load_async::<RFD>(ctx.db.pool())
         .await?;
 
-    if !rfd.is_empty() {
-        let new_rfd: NewRFD = rfd.pop().unwrap().into();
-        Ok(Some(new_rfd.into()))
-    } else {
-        Ok(None)
+    Ok(rfds.pop())
+}
+
+pub async fn handle_rfd_graph(ctx: &Context) -> Result<RFDGraph> {
+    get_rfd_graph(&ctx.db, ctx.company.id).await
+}
+
+pub async fn handle_rfd_metrics(ctx: &Context) -> Result<RFDLifecycleMetrics> {
+    get_rfd_lifecycle_metrics(&ctx.db, ctx.company.id).await
+}
+
+pub async fn handle_rfd_new(
+    ctx: &Context,
+    title: &str,
+    authors: &str,
+    requested_by: &str,
+    number: Option<i32>,
+) -> Result<ScaffoldedRFD> {
+    scaffold_rfd(&ctx.db, &ctx.company, title, authors, requested_by, number).await
+}
+
+pub async fn handle_rfd_reserve(
+    ctx: &Context,
+    title: &str,
+    reserved_by: &str,
+    ttl_minutes: Option<i64>,
+) -> Result<RFDNumberReservation> {
+    let (ttl, max_open) = {
+        let config = &ctx.app_config.read().unwrap().rfd_reservations;
+        (config.ttl(ttl_minutes), config.max_open_per_person)
+    };
+
+    let repo = GitHubRFDRepo::new(&ctx.company).await?;
+    let taken = taken_rfd_numbers(&ctx.db, &ctx.company, &repo).await?;
+
+    reserve_rfd_number(&ctx.db, ctx.company.id, &taken, title, reserved_by, ttl, Some(max_open)).await
+}
+
+/// Let the company know an RFD moved to another state.
+pub async fn notify_rfd_state_transition(ctx: &Context, rfd: &RFD, from: RFDState, to: RFDState) -> Result<()> {
+    if ctx.company.slack_channel_rfds.is_empty() {
+        warn!(
+            "company `{}` has no RFD channel, not announcing RFD {} moved to {}",
+            ctx.company.name, rfd.number, to
+        );
+        return Ok(());
+    }
+    let channel = ctx.company.slack_channel_rfds.to_string();
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!(
+                    "*<{}|{}>* moved from `{}` to `{}`.",
+                    rfd.rendered_link, rfd.name, from, to
+                ),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+        attachments: Default::default(),
+    };
+
+    ctx.company.post_to_slack_channel(&ctx.db, &msg).await?;
+    info!("RFD {} moved from {} to {}", rfd.number, from, to);
+
+    Ok(())
+}
+
+/// Let the company know which sections of an RFD changed in a push to its branch.
+pub async fn notify_rfd_content_changes(ctx: &Context, rfd: &RFD, changes: &RFDChanges) -> Result<()> {
+    if ctx.company.slack_channel_rfds.is_empty() {
+        warn!(
+            "company `{}` has no RFD channel, not announcing the changes to RFD {}",
+            ctx.company.name, rfd.number
+        );
+        return Ok(());
+    }
+    let channel = ctx.company.slack_channel_rfds.to_string();
+
+    let msg = FormattedMessage {
+        channel,
+        blocks: vec![MessageBlock {
+            block_type: MessageBlockType::Section,
+            text: Some(MessageBlockText {
+                text_type: MessageType::Markdown,
+                text: format!("*<{}|{}>* changed: {}.", rfd.rendered_link, rfd.name, changes.summary()),
+            }),
+            elements: Default::default(),
+            accessory: Default::default(),
+            block_id: Default::default(),
+            fields: Default::default(),
+        }],
+        attachments: Default::default(),
+    };
+
+    ctx.company.post_to_slack_channel(&ctx.db, &msg).await?;
+    info!("RFD {} changed: {}", rfd.number, changes.summary());
+
+    Ok(())
+}
+
+pub async fn handle_rfd_search(ctx: &Context, query: &str, limit: Option<i64>) -> Result<Vec<RFDSearchResult>> {
+    search_rfds(
+        &ctx.db,
+        ctx.company.id,
+        query,
+        limit.unwrap_or(RFD_SEARCH_MAX_RESULTS / 2),
+    )
+    .await
+}
+
+/// An RFD that affects a repository.
+#[derive(Debug, Clone, JsonSchema, Serialize)]
+pub struct RFDImpactEntry {
+    pub number: i32,
+    pub name: String,
+    pub state: String,
+    pub rendered_link: String,
+    /// The issue in the repository the RFD affects, or zero if it affects the whole repository.
+    pub issue_number: i32,
+}
+
+pub async fn handle_rfd_impacts(ctx: &Context, owner: &str, repo: &str) -> Result<Vec<RFDImpactEntry>> {
+    let links = get_rfd_impact_links_for_repo(&ctx.db, ctx.company.id, owner, repo).await?;
+
+    let mut entries: Vec<RFDImpactEntry> = Vec::new();
+    for link in links {
+        if let Some(rfd) = company_rfd(ctx, link.rfd_number).await? {
+            entries.push(RFDImpactEntry {
+                number: rfd.number,
+                name: rfd.name,
+                state: rfd.state,
+                rendered_link: rfd.rendered_link,
+                issue_number: link.issue_number,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// The version of the JSON API the RFD site reads. We only add fields within a version.
+pub static RFD_API_VERSION: i32 = 1;
+
+/// The metadata of an RFD in the JSON API.
+#[derive(Debug, Clone, JsonSchema, Serialize)]
+pub struct RFDApiSummary {
+    pub number: i32,
+    pub number_string: String,
+    pub title: String,
+    pub name: String,
+    pub state: String,
+    pub authors: String,
+    pub link: String,
+    pub short_link: String,
+    pub rendered_link: String,
+    pub discussion: String,
+    pub pdf_link: String,
+    /// The SHA of the last commit that modified the RFD.
+    pub sha: String,
+    pub commit_date: DateTime<Utc>,
+}
+
+impl From<RFDIndexEntry> for RFDApiSummary {
+    fn from(rfd: RFDIndexEntry) -> Self {
+        RFDApiSummary {
+            number: rfd.number,
+            number_string: rfd.number_string,
+            title: rfd.title,
+            name: rfd.name,
+            state: rfd.state,
+            authors: rfd.authors,
+            link: rfd.link,
+            short_link: rfd.short_link,
+            rendered_link: rfd.rendered_link,
+            discussion: rfd.discussion,
+            pdf_link: rfd.pdf_link_google_drive,
+            sha: rfd.sha,
+            commit_date: rfd.commit_date,
+        }
     }
 }
 
+/// All the RFDs in the JSON API, by number.
+#[derive(Debug, Clone, JsonSchema, Serialize)]
+pub struct RFDApiIndex {
+    pub api_version: i32,
+    pub rfds: Vec<RFDApiSummary>,
+}
+
+/// An RFD in the JSON API, with its rendered HTML.
+#[derive(Debug, Clone, JsonSchema, Serialize)]
+pub struct RFDApiEntry {
+    pub api_version: i32,
+    #[serde(flatten)]
+    pub rfd: RFDApiSummary,
+    pub html: String,
+}
+
+pub async fn handle_api_rfds(ctx: &Context) -> Result<RFDApiIndex> {
+    // There are only a few hundred RFDs, the site gets them all at once.
+    let rfds = handle_rfd_index(ctx, 0, u32::MAX).await?;
+
+    Ok(RFDApiIndex {
+        api_version: RFD_API_VERSION,
+        rfds: rfds.into_iter().map(RFDApiSummary::from).collect(),
+    })
+}
+
+impl From<NewRFD> for RFDApiEntry {
+    fn from(rfd: NewRFD) -> Self {
+        let html = rfd.html.to_string();
+        let entry: RFDIndexEntry = rfd.into();
+
+        RFDApiEntry {
+            api_version: RFD_API_VERSION,
+            rfd: entry.into(),
+            html,
+        }
+    }
+}
+
+pub async fn handle_api_rfd(ctx: &Context, number: i32) -> Result<Option<RFDApiEntry>> {
+    Ok(company_rfd(ctx, number).await?.map(|rfd| {
+        let rfd: NewRFD = rfd.into();
+        rfd.into()
+    }))
+}
+
+/// Drop the updates of the RFDs whose last commit we already rendered, so the sync only renders
+/// and uploads the RFDs that changed.
+async fn changed_rfd_updates(context: &Context, updates: Vec<GitHubRFDUpdate>) -> Result<Vec<GitHubRFDUpdate>> {
+    let mut changed = vec![];
+    let mut unchanged = 0;
+
+    for update in updates {
+        let number: i32 = update.number.into();
+        let latest = match update.branch.get_latest_commit_sha(&update.number).await {
+            Ok(sha) => sha,
+            Err(e) => {
+                warn!(
+                    "[rfd] Getting the latest commit of RFD {} on {} failed, rendering it anyway: {}",
+                    number, update.branch.branch, e
+                );
+                changed.push(update);
+                continue;
+            }
+        };
+
+        let rendered = get_rendered_commit_sha(&context.db, context.company.id, number, &update.branch.branch).await?;
+        if is_rendered(rendered.as_deref(), &latest) && company_rfd(context, number).await?.is_some() {
+            unchanged += 1;
+            continue;
+        }
+
+        changed.push(update);
+    }
+
+    info!(
+        "[rfd] {} RFDs changed since the last sync, {} did not",
+        changed.len(),
+        unchanged
+    );
+
+    Ok(changed)
+}
+
 // Sync the rfds with our database.
 pub async fn refresh_db_rfds(context: &Context) -> Result<()> {
     let repo = GitHubRFDRepo::new(&context.company).await?;
-    let updates = repo.get_rfd_sync_updates().await?;
+    let updates = changed_rfd_updates(context, repo.get_rfd_sync_updates().await?).await?;
 
     let batches = chunk(updates, 3);
 
@@ -81,6 +367,8 @@ pub async fn refresh_db_rfds(context: &Context) -> Result<()> {
                     Box::new(UpdatePDFs),
                     Box::new(CreatePullRequest),
                     Box::new(UpdatePullRequest),
+                    Box::new(UpdateImpactLinks),
+                    Box::new(UpdateRFDLinks),
                     Box::new(UpdateDiscussionUrl),
                     Box::new(EnsureRFDWithPullRequestIsInValidState),
                     Box::new(EnsureRFDOnDefaultIsInValidState),
@@ -134,3 +422,41 @@ fn chunk<T>(mut source: Vec<T>, chunk_size: usize) -> Vec<Vec<T>> {
 
     chunks
 }
+
+#[cfg(test)]
+mod tests {
+    use cio_api::rfd::NewRFD;
+
+    use super::{RFDApiEntry, RFD_API_VERSION};
+
+    #[test]
+    fn test_rfd_api_entry() {
+        let rfd: NewRFD = serde_json::from_value(serde_json::json!({
+            "number": 3,
+            "number_string": "0003",
+            "title": "Things",
+            "name": "RFD 3 Things",
+            "state": "published",
+            "link": "https://github.com/example/rfd/tree/master/rfd/0003",
+            "html": "<h1>Things</h1>",
+            "content": ":state: published",
+            "pdf_link_google_drive": "https://drive.google.com/open?id=abc",
+            "commit_date": "2024-01-09T17:00:00Z",
+            "cio_company_id": 1,
+        }))
+        .unwrap();
+
+        let entry: RFDApiEntry = rfd.into();
+        assert_eq!(RFD_API_VERSION, entry.api_version);
+        assert_eq!("<h1>Things</h1>", entry.html);
+        assert_eq!("https://drive.google.com/open?id=abc", entry.rfd.pdf_link);
+
+        // The metadata is at the top of the entry, next to the HTML, and the source is not.
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(3, json["number"]);
+        assert_eq!("RFD 3 Things", json["name"]);
+        assert_eq!("<h1>Things</h1>", json["html"]);
+        assert!(json.get("rfd").is_none());
+        assert!(json.get("content").is_none());
+    }
+}
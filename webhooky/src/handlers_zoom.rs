@@ -0,0 +1,233 @@
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use cio_api::companies::Company;
+use dropshot::{Extractor, RequestContext, ServerContext, UntypedBody};
+use dropshot_verify_request::sig::HmacSignatureVerifier;
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{borrow::Cow, sync::Arc};
+use zoom_api::types::GetAccountCloudRecordingResponseMeetings;
+
+use crate::{context::Context, http::Headers};
+
+#[derive(Debug)]
+pub struct ZoomWebhookVerification;
+
+/// How far from now, in seconds, the timestamp of a Zoom webhook can be. The timestamp is
+/// signed with the body, checking it keeps a webhook someone captured from being replayed later.
+const ZOOM_WEBHOOK_MAX_SKEW_SECONDS: i64 = 5 * 60;
+
+/// Check the timestamp of a Zoom webhook, in seconds since the epoch, is close enough to now.
+fn check_zoom_webhook_timestamp(timestamp: &str, now: DateTime<Utc>) -> Result<()> {
+    let seconds: i64 = timestamp
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Zoom webhook timestamp `{}` is not a number: {}", timestamp, e))?;
+    if (now.timestamp() - seconds).abs() > ZOOM_WEBHOOK_MAX_SKEW_SECONDS {
+        bail!(
+            "Zoom webhook timestamp {} is more than {} seconds from now",
+            seconds,
+            ZOOM_WEBHOOK_MAX_SKEW_SECONDS
+        );
+    }
+
+    Ok(())
+}
+
+fn zoom_webhook_key() -> Result<Vec<u8>> {
+    Ok(std::env::var("ZOOM_WH_KEY")
+        .map(|key| key.into_bytes())
+        .map_err(|err| {
+            warn!("Failed to find webhook key for verifying Zoom webhooks: {}", err);
+            err
+        })?)
+}
+
+#[async_trait]
+impl HmacSignatureVerifier for ZoomWebhookVerification {
+    type Algo = Hmac<Sha256>;
+
+    async fn key<Context: ServerContext>(_: Arc<RequestContext<Context>>) -> Result<Vec<u8>> {
+        zoom_webhook_key()
+    }
+
+    async fn signature<Context: ServerContext>(rqctx: Arc<RequestContext<Context>>) -> Result<Vec<u8>> {
+        let headers = Headers::from_request(rqctx.clone()).await?;
+        let signature = headers
+            .0
+            .get("x-zm-signature")
+            .ok_or_else(|| anyhow!("Zoom webhook is missing signature"))
+            .and_then(|header_value| Ok(header_value.to_str()?))
+            .and_then(|header| {
+                log::debug!("Found Zoom signature header {}", header);
+                Ok(hex::decode(header.trim_start_matches("v0="))?)
+            })
+            .map_err(|err| {
+                info!("Zoom webhook is missing a well-formed signature: {}", err);
+                err
+            })?;
+
+        Ok(signature)
+    }
+
+    async fn content<'a, 'b, Context: ServerContext>(
+        rqctx: &'a Arc<RequestContext<Context>>,
+        body: &'b UntypedBody,
+    ) -> anyhow::Result<Cow<'b, [u8]>> {
+        let headers = Headers::from_request(rqctx.clone()).await?;
+        let timestamp = headers
+            .0
+            .get("x-zm-request-timestamp")
+            .ok_or_else(|| anyhow!("Zoom webhook is missing timestamp"))
+            .and_then(|header_value| Ok(header_value.to_str()?))
+            .and_then(|timestamp| {
+                check_zoom_webhook_timestamp(timestamp, Utc::now())?;
+                Ok(timestamp)
+            })
+            .map_err(|err| {
+                info!("Zoom webhook does not have a valid timestamp: {}", err);
+                err
+            })?;
+
+        let mut content = ("v0".to_string() + ":" + timestamp + ":").into_bytes();
+        content.append(&mut body.as_bytes().to_vec());
+
+        Ok(Cow::Owned(content))
+    }
+}
+
+/// A webhook event sent by Zoom.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ZoomWebhookEvent {
+    pub event: String,
+    #[serde(default)]
+    pub event_ts: i64,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+    /// The token to use for downloading recording files, only sent with recording events.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub download_token: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoomUrlValidationResponse {
+    pub plain_token: String,
+    pub encrypted_token: String,
+}
+
+/// Compute the response to a Zoom endpoint url validation challenge.
+pub fn zoom_url_validation_response(key: &[u8], plain_token: &str) -> Result<ZoomUrlValidationResponse> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|e| anyhow!("invalid zoom webhook key: {}", e))?;
+    mac.update(plain_token.as_bytes());
+
+    Ok(ZoomUrlValidationResponse {
+        plain_token: plain_token.to_string(),
+        encrypted_token: hex::encode(mac.finalize().into_bytes()),
+    })
+}
+
+pub async fn handle_zoom_webhook(ctx: &Context, event: ZoomWebhookEvent) -> Result<serde_json::Value> {
+    match event.event.as_str() {
+        // Zoom sends this when the webhook is first configured and periodically afterwards
+        // to make sure we still own the endpoint.
+        "endpoint.url_validation" => {
+            let plain_token = event
+                .payload
+                .get("plainToken")
+                .and_then(|token| token.as_str())
+                .ok_or_else(|| anyhow!("Zoom url validation is missing a plain token"))?;
+
+            Ok(serde_json::to_value(zoom_url_validation_response(
+                &zoom_webhook_key()?,
+                plain_token,
+            )?)?)
+        }
+        "recording.completed" => {
+            // The webhook says which Zoom account it is for, only ingest the recordings of
+            // accounts we know.
+            let account_id = event
+                .payload
+                .get("account_id")
+                .and_then(|id| id.as_str())
+                .unwrap_or_default();
+            if account_id.is_empty() {
+                info!("ignoring zoom recording completed event without an account");
+                return Ok(serde_json::json!({}));
+            }
+            let company = match Company::get_from_zoom_account_id(&ctx.db, account_id).await {
+                Ok(company) => company,
+                Err(e) => {
+                    info!(
+                        "ignoring zoom recording completed event for unknown account {}: {}",
+                        account_id, e
+                    );
+                    return Ok(serde_json::json!({}));
+                }
+            };
+
+            let object = event
+                .payload
+                .get("object")
+                .cloned()
+                .ok_or_else(|| anyhow!("Zoom recording completed event is missing the meeting"))?;
+            let meeting: GetAccountCloudRecordingResponseMeetings = serde_json::from_value(object)?;
+
+            info!("zoom recording completed for meeting {}", meeting.uuid);
+
+            // Zoom expects a response within a few seconds, and moving the recordings to
+            // Google Drive takes much longer than that, so do the work in the background.
+            let db = ctx.db.clone();
+            let download_token = event.download_token.to_string();
+            tokio::spawn(async move {
+                if let Err(e) =
+                    cio_api::recorded_meetings::refresh_zoom_recorded_meeting(&db, &company, &meeting, &download_token)
+                        .await
+                {
+                    warn!("ingesting zoom recording for meeting {} failed: {}", meeting.uuid, e);
+                    sentry::integrations::anyhow::capture_anyhow(&e);
+                }
+            });
+
+            Ok(serde_json::json!({}))
+        }
+        other => {
+            info!("ignoring zoom webhook event {}", other);
+            Ok(serde_json::json!({}))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{check_zoom_webhook_timestamp, zoom_url_validation_response};
+
+    #[test]
+    fn test_check_zoom_webhook_timestamp() {
+        let now = Utc.timestamp(1_700_000_000, 0);
+
+        assert!(check_zoom_webhook_timestamp("1700000000", now).is_ok());
+        assert!(check_zoom_webhook_timestamp("1699999760", now).is_ok());
+        assert!(check_zoom_webhook_timestamp("1700000240", now).is_ok());
+        assert!(check_zoom_webhook_timestamp("1699999000", now).is_err());
+        assert!(check_zoom_webhook_timestamp("1700001000", now).is_err());
+        assert!(check_zoom_webhook_timestamp("not a timestamp", now).is_err());
+    }
+
+    #[test]
+    fn test_zoom_url_validation_response() {
+        let response = zoom_url_validation_response(b"secret", "qgg8vlvZRS6UYooatFL8Aw").unwrap();
+
+        assert_eq!("qgg8vlvZRS6UYooatFL8Aw", response.plain_token);
+        assert_eq!(
+            "72cef096bfd47c0b8664df30d07721641e4abd7e885ba432204260db477a9a3e",
+            response.encrypted_token
+        );
+    }
+}
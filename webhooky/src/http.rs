@@ -1,6 +1,9 @@
 use async_trait::async_trait;
-use dropshot::{ApiEndpointBodyContentType, Extractor, ExtractorMetadata, HttpError, RequestContext, ServerContext};
-use http::header::HeaderMap;
+use cio_api::inbound_email::InboundEmail;
+use dropshot::{
+    ApiEndpointBodyContentType, Extractor, ExtractorMetadata, HttpError, RequestContext, ServerContext, UntypedBody,
+};
+use http::header::{HeaderMap, CONTENT_TYPE};
 use std::sync::Arc;
 
 pub struct Headers(pub HeaderMap);
@@ -19,3 +22,34 @@ impl Extractor for Headers {
         }
     }
 }
+
+/// An email posted by the SendGrid inbound parse webhook. It posts multipart form data, which
+/// dropshot can't parse into a typed body.
+pub struct InboundEmailBody(pub InboundEmail);
+
+#[async_trait]
+impl Extractor for InboundEmailBody {
+    async fn from_request<Context: ServerContext>(
+        rqctx: Arc<RequestContext<Context>>,
+    ) -> Result<InboundEmailBody, HttpError> {
+        let headers = Headers::from_request(rqctx.clone()).await?;
+        let content_type = headers
+            .0
+            .get(CONTENT_TYPE)
+            .and_then(|header_value| header_value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let body = UntypedBody::from_request(rqctx.clone()).await?;
+
+        InboundEmail::from_multipart(&content_type, body.as_bytes())
+            .map(InboundEmailBody)
+            .map_err(|err| HttpError::for_bad_request(None, err.to_string()))
+    }
+
+    fn metadata(_body_content_type: ApiEndpointBodyContentType) -> ExtractorMetadata {
+        ExtractorMetadata {
+            paginated: false,
+            parameters: vec![],
+        }
+    }
+}
@@ -3,10 +3,61 @@ use anyhow::Result;
 
 pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Result<()> {
     match cmd {
+        crate::core::SubCommand::PlanReconcilers(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::reconcile::plan_reconcilers(&db, &company, &app_config).await?;
+        }
+        crate::core::SubCommand::SendActionItemReminders(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::meeting_action_items::send_action_item_reminders(&db, &company).await?;
+        }
+        crate::core::SubCommand::SendFocusTimeReport(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::focus_time::send_focus_time_report(&db, &company).await?;
+        }
+        crate::core::SubCommand::SendInventoryReconciliation(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::inventory_reconciliation::reconcile_inventory(&db, &company, &app_config.inventory_reconciliation)
+                .await?;
+        }
+        crate::core::SubCommand::SendMetricsDigest(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::metrics::send_metrics_digest(&db, &company).await?;
+        }
         crate::core::SubCommand::SendRFDChangelog(_) => {
             let Context { db, company, .. } = context;
             cio_api::rfd::send_rfd_changelog(&db, &company).await?;
         }
+        crate::core::SubCommand::SendRFDLifecycleDigest(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::rfd::send_rfd_lifecycle_digest(&db, &company).await?;
+        }
+        crate::core::SubCommand::SendRFDReviewReminders(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::rfd::send_rfd_review_reminders(&db, &company).await?;
+        }
+        crate::core::SubCommand::SendWeeklyDigest(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let envelopes = app_config.read().unwrap().envelopes.clone();
+            cio_api::weekly_digest::send_weekly_digests(&db, &company, &envelopes).await?;
+        }
         crate::core::SubCommand::SyncAnalytics(_) => {
             let Context { db, company, .. } = context;
             cio_api::analytics::refresh_analytics(&db, &company).await?;
@@ -28,17 +79,62 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             cio_api::applicants::refresh_new_applicants_and_reviews(&db, &company, &app_config).await?;
             cio_api::applicant_reviews::refresh_reviews(&db, &company).await?;
 
+            // Sync the candidates of the companies hiring with Greenhouse. Greenhouse being down
+            // should not hold up the offer letters.
+            if let Err(e) = cio_api::greenhouse::refresh_greenhouse(&db, &company).await {
+                log::warn!("refreshing greenhouse for company {} failed: {}", company.name, e);
+            }
+            // And mirror the opportunities of the ones hiring with Lever.
+            if let Err(e) = cio_api::lever::refresh_lever(&db, &company).await {
+                log::warn!("refreshing lever for company {} failed: {}", company.name, e);
+            }
+
             // Refresh DocuSign for the applicants.
             cio_api::applicants::refresh_docusign_for_applicants(&db, &company, &app_config).await?;
         }
+        crate::core::SubCommand::SyncAssetDepreciation(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            cio_api::asset_depreciation::refresh_asset_depreciation(&db, &company).await?;
+
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::asset_refresh::refresh_asset_refresh_forecast(&db, &company, &app_config.asset_refresh).await?;
+        }
         crate::core::SubCommand::SyncAssetInventory(_) => {
             let Context { db, company, .. } = context;
             cio_api::asset_inventory::refresh_asset_items(&db, &company).await?;
         }
+        crate::core::SubCommand::SyncAssetLoaners(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::asset_loaners::send_loaner_reminders(&db, &company).await?;
+        }
+        crate::core::SubCommand::SyncAssetMaintenance(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::asset_maintenance::refresh_asset_maintenance(&db, &company).await?;
+        }
+        crate::core::SubCommand::SyncCommunityEvents(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::community_events::refresh_event_registrants(&db, &company).await?;
+        }
         crate::core::SubCommand::SyncCompanies(_) => {
             let Context { db, .. } = context;
             cio_api::companies::refresh_companies(&db).await?;
         }
+        crate::core::SubCommand::SyncConferences(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::conferences::refresh_conferences(&db, &company).await?;
+            cio_api::conferences::refresh_conference_tasks(&db, &company, &app_config.conferences).await?;
+        }
         crate::core::SubCommand::SyncConfigs(_) => {
             let Context {
                 app_config,
@@ -49,6 +145,18 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             let config = app_config.read().unwrap().clone();
             cio_api::configs::refresh_db_configs_and_airtable(&db, &company, &config).await?;
         }
+        crate::core::SubCommand::SyncContractors(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::contractors::refresh_contractor_accounts(&db, &company).await?;
+        }
+        crate::core::SubCommand::SyncCredentialsInventory(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::credentials_export::export_credentials_inventory(&db, &company).await?;
+        }
+        crate::core::SubCommand::SyncDriveUploads(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::drive_uploads::process_drive_upload_queue(&db, &company).await?;
+        }
         crate::core::SubCommand::SyncFinance(_) => {
             let Context {
                 app_config,
@@ -59,6 +167,26 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             let app_config = app_config.read().unwrap().clone();
             cio_api::finance::refresh_all_finance(&db, &company, &app_config.finance).await?;
         }
+        crate::core::SubCommand::SyncFirstWeekSchedules(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::first_week::refresh_first_week_schedules(
+                &db,
+                &company,
+                &app_config.onboarding.first_week,
+                &app_config.business_calendar,
+            )
+            .await?;
+        }
+        crate::core::SubCommand::SyncFocusTime(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::focus_time::refresh_focus_time(&db, &company).await?;
+        }
         crate::core::SubCommand::SyncFunctions(_) => {
             let Context { db, company, .. } = context;
             cio_api::functions::refresh_functions(&db, &company).await?;
@@ -71,9 +199,17 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             cio_api::huddles::sync_huddle_meeting_notes(&company).await?;
         }
         crate::core::SubCommand::SyncInterviews(_) => {
-            let Context { db, company, .. } = context;
-            cio_api::interviews::refresh_interviews(&db, &company).await?;
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let app_config = app_config.read().unwrap().clone();
+            cio_api::interviews::refresh_interviews(&db, &company, &app_config.apply).await?;
             cio_api::interviews::compile_packets(&db, &company).await?;
+            cio_api::interviews::schedule_debriefs(&db, &company, &app_config.business_calendar).await?;
+            cio_api::interview_scorecards::request_interview_scorecards(&db, &company).await?;
         }
         crate::core::SubCommand::SyncJournalClubs(_) => {
             let Context { db, company, .. } = context;
@@ -90,10 +226,21 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
                 crate::mailing_lists::sync_pending_wait_list_subscribers(&db).await?;
             }
         }
+        crate::core::SubCommand::SyncRecordedMeetingRetention(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::meeting_retention::apply_recorded_meetings_retention(&db, &company).await?;
+            cio_api::meeting_retention::apply_interview_recordings_retention(&db, &company).await?;
+        }
         crate::core::SubCommand::SyncRecordedMeetings(_) => {
             let Context { db, company, .. } = context;
-            cio_api::recorded_meetings::refresh_zoom_recorded_meetings(&db, &company).await?;
-            cio_api::recorded_meetings::refresh_google_recorded_meetings(&db, &company).await?;
+            let zoom = cio_api::recorded_meetings::refresh_zoom_recorded_meetings(&db, &company).await?;
+            log::info!("synced zoom recorded meetings: {}", zoom);
+            cio_api::recorded_meetings::refresh_teams_recorded_meetings(&db, &company).await?;
+            cio_api::recorded_meetings::refresh_google_meet_recordings(&db, &company).await?;
+            let google = cio_api::recorded_meetings::refresh_google_recorded_meetings(&db, &company).await?;
+            log::info!("synced google recorded meetings: {}", google);
+            cio_api::meeting_summaries::summarize_recorded_meetings(&db, &company).await?;
+            cio_api::meeting_attachments::upload_recorded_meetings_airtable_attachments(&db, &company).await?;
         }
         crate::core::SubCommand::SyncRepos(_) => {
             let Context {
@@ -122,6 +269,7 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             let Context { db, company, .. } = &context;
             crate::handlers_rfd::refresh_db_rfds(&context).await?;
             cio_api::rfd::drive::cleanup_rfd_pdfs(db, company).await?;
+            cio_api::rfd::refresh_rfd_discussions(db, company).await?;
         }
         crate::core::SubCommand::SyncOther(_) => {
             let Context { company, .. } = context;
@@ -129,10 +277,20 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             cio_api::tailscale::cleanup_old_tailscale_cloudflare_dns(&company).await?;
             cio_api::customers::sync_customer_meeting_notes(&company).await?;
         }
-        crate::core::SubCommand::SyncShipments(_) => {
+        crate::core::SubCommand::SyncPrinterStatus(_) => {
             let Context { db, company, .. } = context;
+            cio_api::printer::check_printer_supplies(&db, &company).await?;
+        }
+        crate::core::SubCommand::SyncShipments(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+            let calendar = app_config.read().unwrap().business_calendar.default_calendar();
             let inbound_result = cio_api::shipments::refresh_inbound_shipments(&db, &company).await;
-            let outbound_result = cio_api::shipments::refresh_outbound_shipments(&db, &company).await;
+            let outbound_result = cio_api::shipments::refresh_outbound_shipments(&db, &company, &calendar).await;
 
             if let Err(ref e) = inbound_result {
                 log::error!("Failed to refresh inbound shipments {:?}", e);
@@ -145,15 +303,28 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             inbound_result?;
             outbound_result?;
         }
+        crate::core::SubCommand::SyncShopify(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::shopify::refresh_shopify_orders(&db, &company).await?;
+        }
         crate::core::SubCommand::SyncShorturls(_) => {
             let Context { db, company, .. } = context;
             cio_api::shorturls::refresh_shorturls(&db, &company).await?;
         }
+        crate::core::SubCommand::SyncSignInAudit(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::sign_in_audit::refresh_sign_in_events(&db, &company).await?;
+        }
         crate::core::SubCommand::SyncSwagInventory(_) => {
             let Context { db, company, .. } = context;
             cio_api::swag_inventory::refresh_swag_items(&db, &company).await?;
             cio_api::swag_inventory::refresh_swag_inventory_items(&db, &company).await?;
+            cio_api::swag_inventory::refresh_swag_item_variants(&db, &company).await?;
+            cio_api::printful::reconcile_printful_stock(&db, &company).await?;
+            cio_api::swag_bundles::refresh_swag_bundles(&db, &company).await?;
+            cio_api::swag_requests::refresh_swag_requests(&db, &company).await?;
             cio_api::swag_inventory::refresh_barcode_scans(&db, &company).await?;
+            cio_api::swag_reorders::check_swag_low_stock(&db, &company).await?;
         }
         crate::core::SubCommand::SyncTravel(_) => {
             let Context { db, company, .. } = context;
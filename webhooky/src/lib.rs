@@ -14,18 +14,23 @@ pub mod handlers_cron;
 pub mod handlers_docusign;
 pub mod handlers_github;
 pub mod handlers_hiring;
+pub mod handlers_profile;
 pub mod handlers_rfd;
 pub mod handlers_slack;
+pub mod handlers_zoom;
 // mod handlers_sendgrid;
 mod health;
 mod http;
 mod job;
 mod mailing_lists;
+mod redact;
+pub mod replay;
 mod repos;
 mod sagas;
 pub mod server;
 mod slack_commands;
 // mod tracking_numbers;
+mod transforms;
 #[macro_use]
 extern crate serde_json;
 #[macro_use]
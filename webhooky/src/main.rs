@@ -14,18 +14,23 @@ mod handlers_cron;
 mod handlers_docusign;
 mod handlers_github;
 mod handlers_hiring;
+mod handlers_profile;
 mod handlers_rfd;
 mod handlers_slack;
+mod handlers_zoom;
 // mod handlers_sendgrid;
 mod health;
 mod http;
 mod job;
 mod mailing_lists;
+mod redact;
+mod replay;
 mod repos;
 mod sagas;
 mod server;
 mod slack_commands;
 // mod tracking_numbers;
+mod transforms;
 #[macro_use]
 extern crate serde_json;
 #[macro_use]
@@ -163,6 +168,17 @@ pub async fn run_main_cmd(opts: crate::core::Opts, api: APIConfig, context: Serv
             let mut buffer = File::create(spec_file)?;
             api.open_api().write(&mut buffer)?;
         }
+        crate::core::SubCommand::Anonymize(a) => {
+            if !a.confirm_staging || env::var("SENTRY_ENV").unwrap_or_default() == "production" {
+                bail!("refusing to anonymize a database that has not been confirmed as staging");
+            }
+
+            let report = cio_api::anonymize::anonymize_database(&context.app.db, &context.app.company, &a.salt).await?;
+            info!("anonymized database: {:?}", report);
+        }
+        crate::core::SubCommand::Replay(r) => {
+            crate::replay::run_replay(r, api.api, context, opts.debug).await?;
+        }
         job => crate::job::run_job_cmd(job, context.app).await?,
     }
 
@@ -0,0 +1,312 @@
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{ser::Error, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::auth::{Scope, Scopes};
+
+/// A field of a response, and the scope callers need to see it. The path is the name of the
+/// field, with dots to reach into nested objects, like `groups.items.replacement_value`.
+/// Arrays along the way are redacted element by element.
+pub struct FieldRule {
+    pub path: String,
+    /// The scope callers need to see the field, None if everyone can.
+    pub scope: Option<Scope>,
+}
+
+impl FieldRule {
+    /// A field everyone can see.
+    pub fn public(path: &str) -> Self {
+        FieldRule {
+            path: path.to_string(),
+            scope: None,
+        }
+    }
+
+    /// A field only the callers with the scope can see.
+    pub fn scoped(path: &str, scope: Scope) -> Self {
+        FieldRule {
+            path: path.to_string(),
+            scope: Some(scope),
+        }
+    }
+
+    /// The rules of a response type that is nested in a field of another.
+    pub fn nested<T: Redact>(field: &str) -> Vec<FieldRule> {
+        T::rules()
+            .into_iter()
+            .map(|rule| FieldRule {
+                path: format!("{}.{}", field, rule.path),
+                scope: rule.scope,
+            })
+            .collect()
+    }
+}
+
+/// The field level visibility of a response type. This is an allow list: the fields without a
+/// rule are never sent, so a field added to a response type stays out of it until it is given
+/// one.
+pub trait Redact {
+    fn rules() -> Vec<FieldRule>;
+}
+
+impl<T: Redact> Redact for Vec<T> {
+    fn rules() -> Vec<FieldRule> {
+        T::rules()
+    }
+}
+
+impl<T: Redact> Redact for Option<T> {
+    fn rules() -> Vec<FieldRule> {
+        T::rules()
+    }
+}
+
+/// A response serialized with only the fields the caller's scopes allow. The schema is the one
+/// of the full response, the redacted fields are left out rather than emptied.
+pub struct Redacted<T> {
+    value: T,
+    scopes: Scopes,
+}
+
+impl<T: Redact> Redacted<T> {
+    pub fn new(value: T, scopes: &Scopes) -> Self {
+        Redacted {
+            value,
+            scopes: scopes.clone(),
+        }
+    }
+}
+
+fn retain_paths(value: &mut Value, prefix: &str, allowed: &[&str]) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                retain_paths(value, prefix, allowed);
+            }
+        }
+        Value::Object(fields) => fields.retain(|name, value| {
+            let path = if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            if allowed.contains(&path.as_str()) {
+                return true;
+            }
+
+            // Keep the objects that have fields the caller can see, with only those fields.
+            let nested = format!("{}.", path);
+            if (value.is_object() || value.is_array()) && allowed.iter().any(|a| a.starts_with(&nested)) {
+                retain_paths(value, &path, allowed);
+                return true;
+            }
+
+            false
+        }),
+        _ => (),
+    }
+}
+
+/// Remove the fields of a serialized response that the scopes do not allow.
+pub fn redact(value: &mut Value, rules: &[FieldRule], scopes: &Scopes) {
+    let allowed = rules
+        .iter()
+        .filter(|rule| rule.scope.map(|scope| scopes.has(scope)).unwrap_or(true))
+        .map(|rule| rule.path.as_str())
+        .collect::<Vec<&str>>();
+
+    retain_paths(value, "", &allowed);
+}
+
+impl<T: Serialize + Redact> Serialize for Redacted<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value = serde_json::to_value(&self.value).map_err(S::Error::custom)?;
+        redact(&mut value, &T::rules(), &self.scopes);
+
+        value.serialize(serializer)
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Redacted<T> {
+    fn is_referenceable() -> bool {
+        T::is_referenceable()
+    }
+
+    fn schema_name() -> String {
+        T::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        T::json_schema(gen)
+    }
+}
+
+impl Redact for cio_api::asset_insurance::InsuranceReport {
+    fn rules() -> Vec<FieldRule> {
+        vec![
+            FieldRule::public("generated_at"),
+            FieldRule::public("count"),
+            FieldRule::scoped("replacement_value", Scope::Finance),
+            FieldRule::public("groups.location"),
+            FieldRule::public("groups.category"),
+            FieldRule::public("groups.count"),
+            FieldRule::scoped("groups.replacement_value", Scope::Finance),
+            FieldRule::public("groups.items.name"),
+            FieldRule::public("groups.items.manufacturer"),
+            FieldRule::public("groups.items.model_number"),
+            FieldRule::public("groups.items.serial_number"),
+            FieldRule::public("groups.items.status"),
+            FieldRule::scoped("groups.items.replacement_value", Scope::Finance),
+            // The exports have the values in them.
+            FieldRule::scoped("csv_link", Scope::Finance),
+            FieldRule::scoped("pdf_link", Scope::Finance),
+        ]
+    }
+}
+
+impl Redact for cio_api::asset_inventory::AssetItem {
+    fn rules() -> Vec<FieldRule> {
+        let mut rules = [
+            "id",
+            "name",
+            "picture",
+            "type",
+            "qualities",
+            "status",
+            "manufacturer",
+            "model_number",
+            "serial_number",
+            "purchase_date",
+            "depreciation_schedule",
+            "current_employee_borrowing",
+            "conference_room_using",
+            "notes",
+            "barcode",
+            "barcode_symbology",
+            "barcode_png",
+            "barcode_svg",
+            "barcode_pdf_label",
+            "qr_code",
+            "qr_code_png",
+            "qr_code_svg",
+            "supplier",
+            "order_number",
+            "expected_delivery_date",
+            "is_loaner",
+            "warranty_expiration_date",
+            "maintenance_interval_days",
+            "last_maintenance_date",
+            "cio_company_id",
+            "airtable_record_id",
+        ]
+        .iter()
+        .map(|path| FieldRule::public(path))
+        .collect::<Vec<FieldRule>>();
+        rules.push(FieldRule::scoped("purchase_price", Scope::Finance));
+        rules.push(FieldRule::scoped("current_book_value", Scope::Finance));
+
+        rules
+    }
+}
+
+impl Redact for cio_api::shipments::InboundShipment {
+    fn rules() -> Vec<FieldRule> {
+        [
+            "id",
+            "tracking_number",
+            "carrier",
+            "tracking_link",
+            "oxide_tracking_link",
+            "tracking_status",
+            "shipped_time",
+            "delivered_time",
+            "eta",
+            "messages",
+            "order_number",
+            "requested_by",
+            "received_time",
+            "received_by",
+            "name",
+            "notes",
+            "cio_company_id",
+            "airtable_record_id",
+        ]
+        .iter()
+        .map(|path| FieldRule::public(path))
+        .collect()
+    }
+}
+
+impl Redact for cio_api::receiving::ReceivedPackage {
+    fn rules() -> Vec<FieldRule> {
+        let mut rules = vec![FieldRule::public("notified")];
+        rules.extend(FieldRule::nested::<cio_api::shipments::InboundShipment>("shipment"));
+        rules.extend(FieldRule::nested::<cio_api::asset_inventory::AssetItem>("asset_items"));
+        rules.extend(FieldRule::nested::<cio_api::asset_inventory::AssetItem>(
+            "pending_asset_items",
+        ));
+
+        rules
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{redact, Redact};
+    use crate::auth::{Scope, Scopes};
+
+    #[test]
+    fn test_redact() {
+        let report = json!({
+            "count": 1,
+            "replacement_value": 2000.0,
+            "groups": [{
+                "location": "Oakland",
+                "replacement_value": 2000.0,
+                "items": [{"name": "zeus", "replacement_value": 2000.0}],
+            }],
+            "csv_link": "https://drive.google.com/csv",
+        });
+        let rules = &cio_api::asset_insurance::InsuranceReport::rules();
+
+        let mut value = report.clone();
+        redact(&mut value, rules, &Scopes(vec![Scope::Operations]));
+        assert_eq!(
+            json!({
+                "count": 1,
+                "groups": [{"location": "Oakland", "items": [{"name": "zeus"}]}],
+            }),
+            value
+        );
+
+        let mut value = report.clone();
+        redact(&mut value, rules, &Scopes(vec![Scope::Finance]));
+        assert_eq!(report, value);
+
+        // The fields without a rule are never sent, whoever is asking.
+        let mut value = json!({"count": 1, "secret": "hunter2", "groups": [{"count": 1, "secret": "hunter2"}]});
+        redact(&mut value, rules, &Scopes(vec![Scope::Finance]));
+        assert_eq!(json!({"count": 1, "groups": [{"count": 1}]}), value);
+
+        let mut value = json!({
+            "notified": ["jane@example.com"],
+            "asset_items": [{"name": "w123-1-1", "purchase_price": 1000.0}],
+            "pending_asset_items": [],
+        });
+        redact(
+            &mut value,
+            &cio_api::receiving::ReceivedPackage::rules(),
+            &Scopes(vec![Scope::Operations]),
+        );
+        assert_eq!(
+            json!({
+                "notified": ["jane@example.com"],
+                "asset_items": [{"name": "w123-1-1"}],
+                "pending_asset_items": [],
+            }),
+            value
+        );
+    }
+}
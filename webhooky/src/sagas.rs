@@ -99,6 +99,7 @@ pub async fn do_saga(
     id: &uuid::Uuid,
     template: steno::SagaTemplate<Saga>,
     cmd_name: &str,
+    actions: &[(&str, &str)],
 ) -> Result<()> {
     let context = Arc::new(Context { db: db.clone() });
     let params = Params {
@@ -110,6 +111,9 @@ pub async fn do_saga(
 
     let saga_id = steno::SagaId(*id);
 
+    // Keep track of the steps, so we can see where the saga stalled.
+    cio_api::saga_steps::create_saga_steps(db, id, actions).await?;
+
     // Create the saga.
     sec.saga_create(saga_id, Arc::new(context), saga_template, cmd_name.to_string(), params)
         .await?;
@@ -135,7 +139,7 @@ pub async fn run_cmd(db: &Database, sec: &steno::SecClient, id: &uuid::Uuid, cmd
         ),
     );
 
-    do_saga(db, sec, id, builder.build(), cmd_name).await
+    do_saga(db, sec, id, builder.build(), cmd_name, &[(cmd_name, cmd_name)]).await
 }
 
 async fn action_run_cmd(action_context: steno::ActionContext<Saga>) -> Result<FnOutput, steno::ActionError> {
@@ -6,7 +6,9 @@ use chrono::{DateTime, Utc};
 use cio_api::{
     analytics::NewPageView,
     functions::Function,
+    profiles::UserProfile,
     rfd::{RFDEntry, RFDIndexEntry},
+    skills::SkillMatch,
     swag_store::Order,
 };
 use clokwerk::{AsyncScheduler, Job, TimeUnits};
@@ -23,7 +25,8 @@ use dropshot_verify_request::{
 };
 use google_drive::Client as GoogleDrive;
 use gusto_api::Client as Gusto;
-use http::{header::HeaderValue, StatusCode};
+use http::{header::HeaderValue, Response, StatusCode};
+use hyper::Body;
 use log::{info, warn};
 use quickbooks::QuickBooks;
 use schemars::JsonSchema;
@@ -37,11 +40,18 @@ use slack_chat_api::{BotCommand, Slack};
 use zoom_api::Client as Zoom;
 
 use crate::{
-    auth::{AirtableToken, HiringToken, InternalToken, RFDToken, ShippoToken},
+    auth::{
+        AirtableToken, CompanyUser, EventbriteToken, HiringToken, InternalToken, LumaToken, RFDToken, Scope, Scopes,
+        ShippoToken, UserIdentity,
+    },
     context::ServerContext,
     github_types::GitHubWebhook,
-    handlers_hiring::{ApplicantInfo, ApplicantUploadToken},
+    handlers_hiring::{ApplicantInfo, ApplicantUploadToken, RoleCatalog},
     handlers_slack::InteractiveEvent,
+    handlers_zoom::ZoomWebhookEvent,
+    http::InboundEmailBody,
+    redact::Redacted,
+    transforms::{decode_webhook, transform_webhook, RawWebhook},
 };
 
 pub struct APIConfig {
@@ -74,15 +84,34 @@ fn create_api() -> ApiDescription<ServerContext> {
      */
     api.register(ping).unwrap();
     api.register(github_rate_limit).unwrap();
+    api.register(listen_rate_limit_stats_requests).unwrap();
     api.register(listen_airtable_applicants_request_background_check_webhooks)
         .unwrap();
     api.register(listen_airtable_applicants_update_webhooks).unwrap();
     api.register(listen_airtable_applicants_recreate_piia_webhooks).unwrap();
     api.register(listen_airtable_assets_items_print_barcode_label_webhooks)
         .unwrap();
+    api.register(listen_assets_insurance_report_requests).unwrap();
+    api.register(listen_assets_label_preview_requests).unwrap();
+    api.register(listen_assets_refresh_forecast_requests).unwrap();
+    api.register(listen_assets_audit_finish_requests).unwrap();
+    api.register(listen_reconcile_plan_requests).unwrap();
+    api.register(listen_reconcile_plan_apply_requests).unwrap();
+    api.register(listen_assets_audit_scan_requests).unwrap();
+    api.register(listen_assets_audit_start_requests).unwrap();
+    api.register(listen_assets_checkout_scan_requests).unwrap();
+    api.register(listen_assets_loaner_reservation_requests).unwrap();
+    api.register(listen_assets_loaner_reservation_update_requests).unwrap();
+    api.register(listen_assets_loaner_utilization_report_requests).unwrap();
+    api.register(listen_assets_order_email_webhooks).unwrap();
+    api.register(listen_assets_snipeit_import_requests).unwrap();
     api.register(listen_airtable_employees_print_home_address_label_webhooks)
         .unwrap();
+    api.register(listen_airtable_employees_approve_end_date_extension_webhooks)
+        .unwrap();
     api.register(listen_airtable_certificates_renew_webhooks).unwrap();
+    api.register(listen_airtable_recorded_meetings_export_transcript_webhooks)
+        .unwrap();
     api.register(listen_airtable_shipments_inbound_create_webhooks).unwrap();
     api.register(listen_airtable_shipments_outbound_create_webhooks)
         .unwrap();
@@ -96,6 +125,8 @@ fn create_api() -> ApiDescription<ServerContext> {
         .unwrap();
     api.register(listen_airtable_swag_inventory_items_print_barcode_labels_webhooks)
         .unwrap();
+    api.register(listen_airtable_swag_item_variants_print_barcode_labels_webhooks)
+        .unwrap();
     api.register(listen_analytics_page_view_webhooks).unwrap();
 
     api.register(listen_application_submit_requests).unwrap();
@@ -108,6 +139,16 @@ fn create_api() -> ApiDescription<ServerContext> {
     api.register(listen_application_files_upload_requests).unwrap();
     api.register(listen_applicant_info).unwrap();
     api.register(listen_applicant_upload_token).unwrap();
+    api.register(listen_applicant_withdraw_requests).unwrap();
+    api.register(listen_applicant_withdraw_email_webhooks).unwrap();
+    api.register(listen_applicant_withdrawal_confirm_requests).unwrap();
+    api.register(listen_applicant_interview_schedule_requests).unwrap();
+    api.register(listen_roles).unwrap();
+    api.register(listen_get_profile).unwrap();
+    api.register(listen_update_profile).unwrap();
+    api.register(listen_users_import_requests).unwrap();
+    api.register(listen_conference_leads_import_requests).unwrap();
+    api.register(listen_search_skills).unwrap();
 
     api.register(listen_auth_docusign_callback).unwrap();
     api.register(listen_auth_docusign_consent).unwrap();
@@ -129,35 +170,73 @@ fn create_api() -> ApiDescription<ServerContext> {
     api.register(listen_docusign_envelope_update_webhooks).unwrap();
     api.register(listen_github_webhooks).unwrap();
     api.register(listen_products_sold_count_requests).unwrap();
+    api.register(listen_revai_job_update_webhooks).unwrap();
+    api.register(listen_recorded_meetings_attendance_report_requests)
+        .unwrap();
+    api.register(listen_recorded_meetings_engagement_report_requests)
+        .unwrap();
+    api.register(listen_recorded_meeting_video_requests).unwrap();
+    api.register(listen_recorded_meeting_upload_requests).unwrap();
+    api.register(listen_recorded_meeting_transcript_doc_requests).unwrap();
     api.register(listen_shippo_tracking_update_webhooks).unwrap();
+    api.register(listen_shipments_inbound_receive_requests).unwrap();
     api.register(listen_easypost_tracking_update_webhooks).unwrap();
+    api.register(listen_eventbrite_webhooks).unwrap();
+    api.register(listen_luma_webhooks).unwrap();
     api.register(listen_slack_commands_webhooks).unwrap();
     api.register(listen_slack_interactive_webhooks).unwrap();
+    api.register(listen_zoom_webhooks).unwrap();
     api.register(listen_shipbob_webhooks).unwrap();
     api.register(listen_store_order_create).unwrap();
+    api.register(listen_swag_bundle_shipment_create).unwrap();
+    api.register(listen_swag_inventory_scan).unwrap();
+    api.register(listen_swag_request_create).unwrap();
+    api.register(listen_swag_request_list).unwrap();
     api.register(listen_rfd_index).unwrap();
     api.register(listen_rfd_view).unwrap();
+    api.register(listen_rfd_impacts).unwrap();
+    api.register(listen_rfd_search).unwrap();
+    api.register(listen_rfd_graph).unwrap();
+    api.register(listen_rfd_metrics).unwrap();
+    api.register(listen_rfd_new_requests).unwrap();
+    api.register(listen_rfd_reserve_requests).unwrap();
+    api.register(listen_api_rfds).unwrap();
+    api.register(listen_api_rfd).unwrap();
     api.register(trigger_rfd_update_by_number).unwrap();
     api.register(trigger_cleanup_create).unwrap();
+    api.register(listen_admin_sagas).unwrap();
+    api.register(listen_metrics).unwrap();
+    api.register(listen_admin_saga_view).unwrap();
 
     api.register(trigger_sync_analytics_create).unwrap();
     api.register(trigger_sync_api_tokens_create).unwrap();
     api.register(trigger_sync_applications_create).unwrap();
+    api.register(trigger_sync_asset_depreciation_create).unwrap();
     api.register(trigger_sync_asset_inventory_create).unwrap();
+    api.register(trigger_sync_asset_loaners_create).unwrap();
+    api.register(trigger_sync_asset_maintenance_create).unwrap();
     api.register(trigger_sync_companies_create).unwrap();
     api.register(trigger_sync_configs_create).unwrap();
+    api.register(trigger_sync_contractors_create).unwrap();
+    api.register(trigger_sync_credentials_inventory_create).unwrap();
+    api.register(trigger_sync_drive_uploads_create).unwrap();
     api.register(trigger_sync_finance_create).unwrap();
+    api.register(trigger_sync_first_week_schedules_create).unwrap();
+    api.register(trigger_sync_focus_time_create).unwrap();
     api.register(trigger_sync_functions_create).unwrap();
     api.register(trigger_sync_huddles_create).unwrap();
     api.register(trigger_sync_interviews_create).unwrap();
     api.register(trigger_sync_journal_clubs_create).unwrap();
     api.register(trigger_sync_mailing_lists_create).unwrap();
     api.register(trigger_sync_other_create).unwrap();
+    api.register(trigger_sync_recorded_meeting_retention_create).unwrap();
     api.register(trigger_sync_recorded_meetings_create).unwrap();
     api.register(trigger_sync_repos_create).unwrap();
     api.register(trigger_sync_rfds_create).unwrap();
     api.register(trigger_sync_shipments_create).unwrap();
+    api.register(trigger_sync_shopify_create).unwrap();
     api.register(trigger_sync_shorturls_create).unwrap();
+    api.register(trigger_sync_sign_in_audit_create).unwrap();
     api.register(trigger_sync_swag_inventory_create).unwrap();
     api.register(trigger_sync_travel_create).unwrap();
     api.register(trigger_sync_zoho_create).unwrap();
@@ -239,18 +318,48 @@ pub async fn server(
         scheduler
             .every(7.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-applications")});
+        scheduler.every(1.day()).run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-asset-depreciation")},
+        );
         scheduler
             .every(2.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-asset-inventory")});
+        scheduler
+            .every(1.day())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-asset-loaners")});
+        scheduler.every(1.day()).run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-asset-maintenance")},
+        );
+        scheduler.every(1.hours()).run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-community-events")},
+        );
         scheduler
             .every(12.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-companies")});
+        scheduler
+            .every(1.day())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-conferences")});
         scheduler
             .every(1.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-configs")});
+        scheduler
+            .every(6.hours())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-contractors")});
+        scheduler.every(1.day()).run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-credentials-inventory")},
+        );
+        scheduler
+            .every(1.hours())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-drive-uploads")});
         scheduler
             .every(6.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-finance")});
+        scheduler.every(9.hours()).run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-first-week-schedules")},
+        );
+        scheduler
+            .every(1.hours())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-focus-time")});
         scheduler
             .every(12.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-functions")});
@@ -269,6 +378,12 @@ pub async fn server(
         scheduler
             .every(18.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-other")});
+        scheduler
+            .every(1.hours())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-printer-status")});
+        scheduler
+            .every(1.day())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-recorded-meeting-retention")});
         scheduler.every(3.hours()).run(
             enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-recorded-meetings")},
         );
@@ -281,9 +396,15 @@ pub async fn server(
         scheduler
             .every(2.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-shipments")});
+        scheduler
+            .every(3.hours())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-shopify")});
         scheduler
             .every(3.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-shorturls")});
+        scheduler
+            .every(1.hours())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-sign-in-audit")});
         scheduler
             .every(9.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-swag-inventory")});
@@ -302,6 +423,49 @@ pub async fn server(
             .every(clokwerk::Interval::Monday)
             .at("8:00 am")
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "send-rfd-changelog")});
+
+        // Send how long the RFDs spend in each state, with the discussions that went stale.
+        scheduler.every(clokwerk::Interval::Monday).at("8:15 am").run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "send-rfd-lifecycle-digest")},
+        );
+
+        // Plan the changes of the reconciling syncs, for someone to review and apply.
+        scheduler
+            .every(clokwerk::Interval::Weekday)
+            .at("8:30 am")
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "plan-reconcilers")});
+
+        // Remind the reviewers of the RFDs that sat in discussion for too long.
+        scheduler.every(clokwerk::Interval::Weekday).at("9:30 am").run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "send-rfd-review-reminders")},
+        );
+
+        // Send everyone the digest of their week.
+        scheduler
+            .every(clokwerk::Interval::Monday)
+            .at("8:00 am")
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "send-weekly-digest")});
+
+        // Run the focus time report.
+        scheduler.every(clokwerk::Interval::Monday).at("8:00 am").run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "send-focus-time-report")},
+        );
+
+        // Reconcile the inventory. This runs every day but only reconciles once a month.
+        scheduler.every(1.day()).at("7:00 am").run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "send-inventory-reconciliation")},
+        );
+
+        // Snapshot the metrics and send their digest.
+        scheduler
+            .every(clokwerk::Interval::Monday)
+            .at("8:00 am")
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "send-metrics-digest")});
+
+        // Remind people about their open action items.
+        scheduler.every(clokwerk::Interval::Weekday).at("9:00 am").run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "send-action-item-reminders")},
+        );
     }
 
     // For Cloud run & ctrl+c, shutdown gracefully.
@@ -423,9 +587,9 @@ async fn listen_products_sold_count_requests(
 }]
 async fn listen_github_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body: HmacVerifiedBody<crate::handlers_github::GitHubWebhookVerification, GitHubWebhook>,
+    body: HmacVerifiedBody<crate::handlers_github::GitHubWebhookVerification, RawWebhook<GitHubWebhook>>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let webhook = body.into_inner()?;
+    let webhook: GitHubWebhook = decode_webhook(&rqctx, "github", body.into_inner()?)?;
 
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
 
@@ -458,7 +622,7 @@ async fn trigger_rfd_update_by_number(
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_rfd_update_by_number(rqctx, path_params))
+        .run(|| crate::handlers::handle_rfd_update_by_number(&rqctx.context().app, path_params.into_inner().num))
         .await
     {
         // Send the error to sentry.
@@ -495,6 +659,21 @@ async fn github_rate_limit(
     }
 }
 
+/** Get how much the request budgets our jobs share for each provider throttled us. */
+#[endpoint {
+    method = GET,
+    path = "/ratelimits",
+}]
+async fn listen_rate_limit_stats_requests(
+    _rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+) -> Result<
+    HttpResponseOk<std::collections::BTreeMap<cio_api::rate_limit::Provider, cio_api::rate_limit::RateLimitStats>>,
+    HttpError,
+> {
+    Ok(HttpResponseOk(cio_api::rate_limit::rate_limit_stats().await))
+}
+
 /// A GitHub RateLimit
 #[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
 pub struct GitHubRateLimit {
@@ -536,13 +715,13 @@ async fn listen_airtable_employees_print_home_address_label_webhooks(
 }
 
 /**
- * Listen for a button pressed to renew a certificate.
+ * Listen for a button pressed to approve the requested end date extension for a contractor.
  */
 #[endpoint {
     method = POST,
-    path = "/airtable/certificates/renew",
+    path = "/airtable/employees/approve_end_date_extension",
 }]
-async fn listen_airtable_certificates_renew_webhooks(
+async fn listen_airtable_employees_approve_end_date_extension_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
@@ -551,7 +730,7 @@ async fn listen_airtable_certificates_renew_webhooks(
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_certificates_renew(rqctx, body))
+        .run(|| crate::handlers::handle_airtable_employees_approve_end_date_extension(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -565,13 +744,13 @@ async fn listen_airtable_certificates_renew_webhooks(
 }
 
 /**
- * Listen for a button pressed to print a barcode label for an asset item.
+ * Listen for a button pressed to renew a certificate.
  */
 #[endpoint {
     method = POST,
-    path = "/airtable/assets/items/print_barcode_label",
+    path = "/airtable/certificates/renew",
 }]
-async fn listen_airtable_assets_items_print_barcode_label_webhooks(
+async fn listen_airtable_certificates_renew_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
@@ -580,7 +759,7 @@ async fn listen_airtable_assets_items_print_barcode_label_webhooks(
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_assets_items_print_barcode_label(rqctx, body))
+        .run(|| crate::handlers::handle_airtable_certificates_renew(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -594,13 +773,13 @@ async fn listen_airtable_assets_items_print_barcode_label_webhooks(
 }
 
 /**
- * Listen for a button pressed to print barcode labels for a swag inventory item.
+ * Listen for a button pressed to export the transcript of a recorded meeting to a Google Doc.
  */
 #[endpoint {
     method = POST,
-    path = "/airtable/swag/inventory/items/print_barcode_labels",
+    path = "/airtable/recorded_meetings/export_transcript",
 }]
-async fn listen_airtable_swag_inventory_items_print_barcode_labels_webhooks(
+async fn listen_airtable_recorded_meetings_export_transcript_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
@@ -609,7 +788,7 @@ async fn listen_airtable_swag_inventory_items_print_barcode_labels_webhooks(
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_swag_inventory_items_print_barcode_labels(rqctx, body))
+        .run(|| crate::handlers::handle_airtable_recorded_meetings_export_transcript(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -623,13 +802,13 @@ async fn listen_airtable_swag_inventory_items_print_barcode_labels_webhooks(
 }
 
 /**
- * Listen for a button pressed to request a background check for an applicant.
+ * Listen for a button pressed to print a barcode label for an asset item.
  */
 #[endpoint {
     method = POST,
-    path = "/airtable/applicants/request_background_check",
+    path = "/airtable/assets/items/print_barcode_label",
 }]
-async fn listen_airtable_applicants_request_background_check_webhooks(
+async fn listen_airtable_assets_items_print_barcode_label_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
@@ -638,7 +817,7 @@ async fn listen_airtable_applicants_request_background_check_webhooks(
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_applicants_request_background_check(rqctx, body))
+        .run(|| crate::handlers::handle_airtable_assets_items_print_barcode_label(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -652,23 +831,24 @@ async fn listen_airtable_applicants_request_background_check_webhooks(
 }
 
 /**
- * Listen for rows updated in our Airtable workspace.
- * These are set up with an Airtable script on the workspaces themselves.
+ * Listen for supplier order confirmation emails from our inbound email webhook. The items on
+ * the order are added to the asset inventory as ordered.
  */
 #[endpoint {
     method = POST,
-    path = "/airtable/applicants/update",
+    path = "/assets/orders/email",
 }]
-async fn listen_airtable_applicants_update_webhooks(
+async fn listen_assets_order_email_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<AirtableToken>,
-    body_param: TypedBody<AirtableRowEvent>,
+    _auth: QueryToken<InternalToken>,
+    body_param: InboundEmailBody,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    let body = body_param.0;
+    // Emails are full of personal data, do not send them to Sentry.
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_applicants_update(rqctx, body))
+        .run(|| crate::handlers::handle_assets_order_email(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -682,628 +862,595 @@ async fn listen_airtable_applicants_update_webhooks(
 }
 
 /**
- * Listen for requests to recreate and resend PIIA documents for a given applicant
- * These are set up with an Airtable script on the workspaces themselves.
+ * Listen for requests to generate the asset insurance report. The CSV and PDF exports are
+ * uploaded to Google Drive and linked from the response. Only finance sees the values.
  */
 #[endpoint {
     method = POST,
-    path = "/airtable/applicants/recreate_piia",
+    path = "/assets/insurance-report",
 }]
-async fn listen_airtable_applicants_recreate_piia_webhooks(
+async fn listen_assets_insurance_report_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<AirtableToken>,
-    body_param: TypedBody<AirtableRowEvent>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    scopes: Scopes,
+) -> Result<HttpResponseOk<Redacted<cio_api::asset_insurance::InsuranceReport>>, HttpError> {
+    scopes.require_any(&[Scope::Finance, Scope::Operations])?;
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::listen_airtable_applicants_recreate_piia_webhooks(rqctx, body))
-        .await
-    {
+    match txn.run(|| crate::handlers::handle_assets_insurance_report(rqctx)).await {
+        Ok(report) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(Redacted::new(report, &scopes)))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
+}
 
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct AssetScanRequest {
+    #[serde(flatten)]
+    pub scan: cio_api::asset_inventory::AssetScan,
 }
 
 /**
- * Listen for rows created in our Airtable workspace.
- * These are set up with an Airtable script on the workspaces themselves.
+ * Listen for scans of an asset item's barcode or QR code. The person scanning the item checks
+ * it out, or back in if they already have it. The response is the item's open checkout, if
+ * it is still out.
  */
 #[endpoint {
     method = POST,
-    path = "/airtable/shipments/outbound/create",
+    path = "/assets/checkouts/scan",
 }]
-async fn listen_airtable_shipments_outbound_create_webhooks(
+async fn listen_assets_checkout_scan_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<AirtableToken>,
-    body_param: TypedBody<AirtableRowEvent>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<AssetScanRequest>,
+) -> Result<HttpResponseOk<Option<cio_api::asset_inventory::AssetCheckout>>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_shipments_outbound_create(rqctx, body))
+    match txn
+        .run(|| crate::handlers::handle_assets_checkout_scan(rqctx, body))
         .await
     {
+        Ok(checkout) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(checkout))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
+}
 
-    txn.finish(http::StatusCode::ACCEPTED);
+/** Import the assets of a company migrating off Snipe-IT into its asset inventory. */
+#[endpoint {
+    method = POST,
+    path = "/assets/import/snipeit",
+}]
+async fn listen_assets_snipeit_import_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseOk<Vec<cio_api::asset_inventory::AssetItem>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+    match txn.run(|| crate::handlers::handle_assets_snipeit_import(rqctx)).await {
+        Ok(items) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(items))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
 }
 
-/// An Airtable row event.
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct AirtableRowEvent {
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub record_id: String,
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct AssetAuditStartRequest {
+    /// The location to audit, or empty to audit everything.
     #[serde(default)]
-    pub cio_company_id: i32,
+    pub location: String,
+    pub email: String,
 }
 
-/**
- * Listen for a button pressed to reprint a label for an outbound shipment.
- */
+/** Start an audit of the asset items. */
 #[endpoint {
     method = POST,
-    path = "/airtable/shipments/outbound/reprint_label",
+    path = "/assets/audits",
 }]
-async fn listen_airtable_shipments_outbound_reprint_label_webhooks(
+async fn listen_assets_audit_start_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<AirtableToken>,
-    body_param: TypedBody<AirtableRowEvent>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<AssetAuditStartRequest>,
+) -> Result<HttpResponseOk<cio_api::asset_audits::AssetAudit>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_shipments_outbound_reprint_label(rqctx, body))
+    match txn
+        .run(|| crate::handlers::handle_assets_audit_start(rqctx, body))
         .await
     {
+        Ok(audit) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(audit))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
+}
 
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct AssetItemPathParams {
+    pub id: i32,
 }
 
 /**
- * Listen for a button pressed to reprint a receipt for an outbound shipment.
+ * Preview the label of an asset item as a PNG, to check its size, the text that does not fit,
+ * and that the barcode is readable before printing it.
  */
 #[endpoint {
-    method = POST,
-    path = "/airtable/shipments/outbound/reprint_receipt",
+    method = GET,
+    path = "/assets/{id}/label/preview",
 }]
-async fn listen_airtable_shipments_outbound_reprint_receipt_webhooks(
+async fn listen_assets_label_preview_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<AirtableToken>,
-    body_param: TypedBody<AirtableRowEvent>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    _auth: Bearer<InternalToken>,
+    path_params: Path<AssetItemPathParams>,
+) -> Result<Response<Body>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_shipments_outbound_reprint_receipt(rqctx, body))
+    let id = path_params.into_inner().id;
+    match txn
+        .run(|| crate::handlers::handle_assets_label_preview(&rqctx.context().app, id))
         .await
     {
+        Ok(png) => {
+            txn.finish(http::StatusCode::OK);
+
+            Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "image/png")
+                .header(http::header::CACHE_CONTROL, "private, no-store")
+                .body(Body::from(png))
+                .map_err(|e| HttpError::for_internal_error(e.to_string()))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
+}
 
-    txn.finish(http::StatusCode::ACCEPTED);
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct AssetAuditPathParams {
+    pub id: i32,
+}
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct AssetAuditScanRequest {
+    #[serde(flatten)]
+    pub scan: cio_api::asset_audits::AuditScan,
 }
 
-/**
- * Listen for a button pressed to resend a shipment status email to the recipient for an outbound shipment.
- */
+/** Listen for the barcodes scanned during an asset audit. */
 #[endpoint {
     method = POST,
-    path = "/airtable/shipments/outbound/resend_shipment_status_email_to_recipient",
+    path = "/assets/audits/{id}/scans",
 }]
-async fn listen_airtable_shipments_outbound_resend_shipment_status_email_to_recipient_webhooks(
+async fn listen_assets_audit_scan_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<AirtableToken>,
-    body_param: TypedBody<AirtableRowEvent>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _auth: Bearer<InternalToken>,
+    path_params: Path<AssetAuditPathParams>,
+    body_param: TypedBody<AssetAuditScanRequest>,
+) -> Result<HttpResponseOk<cio_api::asset_audits::AssetAuditScan>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    if let Err(e) = txn
-        .run(|| {
-            crate::handlers::handle_airtable_shipments_outbound_resend_shipment_status_email_to_recipient(rqctx, body)
-        })
+    match txn
+        .run(|| crate::handlers::handle_assets_audit_scan(&rqctx.context().app, path_params.into_inner().id, body))
         .await
     {
+        Ok(scan) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(scan))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
 /**
- * Listen for a button pressed to schedule a pickup for an outbound shipment.
+ * Finish an asset audit. The response is the audit with the items that were not scanned, the
+ * barcodes we do not know, and the items found somewhere else than we thought, which are also
+ * saved to Airtable.
  */
 #[endpoint {
     method = POST,
-    path = "/airtable/shipments/outbound/schedule_pickup",
+    path = "/assets/audits/{id}/finish",
 }]
-async fn listen_airtable_shipments_outbound_schedule_pickup_webhooks(
+async fn listen_assets_audit_finish_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<AirtableToken>,
-    body_param: TypedBody<AirtableRowEvent>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    _auth: Bearer<InternalToken>,
+    path_params: Path<AssetAuditPathParams>,
+) -> Result<HttpResponseOk<cio_api::asset_audits::AssetAudit>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_shipments_outbound_schedule_pickup(rqctx, body))
+    match txn
+        .run(|| crate::handlers::handle_assets_audit_finish(&rqctx.context().app, path_params.into_inner().id))
         .await
     {
+        Ok(audit) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(audit))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/// A SendGrid incoming email event.
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct IncomingEmail {
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub headers: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub dkim: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub email: String,
-    #[serde(default, skip_serializing_if = "String::is_empty", alias = "content-ids")]
-    pub content_ids: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub to: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub cc: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub html: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub from: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub text: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub sender_ip: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub spam_report: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub envelope: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub attachments: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub subject: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub spam_score: String,
-    #[serde(default, skip_serializing_if = "String::is_empty", alias = "attachment-info")]
-    pub attachment_info: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub charsets: String,
-    #[serde(default, skip_serializing_if = "String::is_empty", alias = "SPF")]
-    pub spf: String,
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct ReconcilePlanPathParams {
+    pub id: i32,
 }
 
-/**
- * Listen for applicant reviews being submitted for job applicants */
+/** Get a plan of the changes of a reconciling sync, to review it. */
 #[endpoint {
-    method = POST,
-    path = "/applicant/review/submit",
+    method = GET,
+    path = "/reconcile/plans/{id}",
 }]
-async fn listen_applicant_review_requests(
+async fn listen_reconcile_plan_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
-    body_param: TypedBody<cio_api::applicant_reviews::NewApplicantReview>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
-
-    if let Err(e) = txn.run(|| crate::handlers::handle_applicant_review(rqctx, body)).await {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
-    }
+    path_params: Path<ReconcilePlanPathParams>,
+) -> Result<HttpResponseOk<cio_api::reconcile::ReconcilePlanEntry>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    txn.finish(http::StatusCode::ACCEPTED);
+    match txn
+        .run(|| crate::handlers::handle_reconcile_plan(&rqctx.context().app, path_params.into_inner().id))
+        .await
+    {
+        Ok(Some(plan)) => {
+            txn.finish(http::StatusCode::OK);
 
-    Ok(HttpResponseAccepted("ok".to_string()))
-}
+            Ok(HttpResponseOk(plan))
+        }
+        Ok(None) => {
+            txn.finish(http::StatusCode::NOT_FOUND);
 
-#[derive(Deserialize, JsonSchema)]
-struct ApplicantInfoParams {
-    email: String,
+            Err(HttpError::for_not_found(None, "".to_string()))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
 }
 
-// Listen for applicant info requests. This assume that the caller has performed the necessary
-// authentication to verify ownership of the email that we are being sent
+/**
+ * Apply a plan of the changes of a reconciling sync. Only the changes of the plan are made, and
+ * the plan is aborted if what it changes moved since it was planned.
+ */
 #[endpoint {
-    method = GET,
-    path = "/applicant/info/{email}",
+    method = POST,
+    path = "/reconcile/plans/{id}/apply",
 }]
-async fn listen_applicant_info(
+async fn listen_reconcile_plan_apply_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<HiringToken>,
-    path_params: Path<ApplicantInfoParams>,
-) -> Result<HttpResponseOk<ApplicantInfo>, HttpError> {
-    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+    _auth: Bearer<InternalToken>,
+    path_params: Path<ReconcilePlanPathParams>,
+) -> Result<HttpResponseOk<cio_api::reconcile::ReconcilePlan>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    log::info!("Running applicant info handler");
+    match txn
+        .run(|| crate::handlers::handle_reconcile_plan_apply(&rqctx.context().app, path_params.into_inner().id))
+        .await
+    {
+        Ok(Some(plan)) => {
+            txn.finish(http::StatusCode::OK);
 
-    let result = txn
-        .run(|| crate::handlers_hiring::handle_applicant_info(&rqctx.context().app, path_params.into_inner().email))
-        .await;
+            Ok(HttpResponseOk(plan))
+        }
+        Ok(None) => {
+            txn.finish(http::StatusCode::NOT_FOUND);
 
-    match result {
-        Ok(login) => {
-            txn.finish(http::StatusCode::OK);
-            Ok(HttpResponseOk(login))
+            Err(HttpError::for_not_found(None, "".to_string()))
         }
-        Err(err) => {
+        // Send the error to sentry.
+        Err(e) => {
             txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-            Err(handle_anyhow_err_as_http_err(err))
+            Err(handle_anyhow_err_as_http_err(e))
         }
     }
 }
 
-// Listen for applicant upload token requests. This returns a short-lived, one time token that can
-// be used to upload materials against the supplied email address. This assume that the caller has
-// performed the necessary authentication to verify ownership of the email that we are being sent
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct ReceivePackageRequest {
+    #[serde(flatten)]
+    pub scan: cio_api::receiving::PackageScan,
+}
+
+/**
+ * Listen for scans of the tracking number of a package arriving at the office. The package is
+ * matched to the inbound shipment or asset order we expect and marked received, the labels of
+ * the asset items in it are printed, and whoever is waiting for it is emailed. The asset items
+ * the scan does not list stay ordered, unless it says the package has the whole order.
+ */
 #[endpoint {
-    method = GET,
-    path = "/applicant/info/{email}/upload-token",
+    method = POST,
+    path = "/shipments/inbound/receive",
 }]
-async fn listen_applicant_upload_token(
+async fn listen_shipments_inbound_receive_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<HiringToken>,
-    path_params: Path<ApplicantInfoParams>,
-) -> Result<HttpResponseOk<ApplicantUploadToken>, HttpError> {
-    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
-
-    log::info!("Running applicant upload token handler");
-
-    let result = txn
-        .run(|| {
-            crate::handlers_hiring::handle_applicant_upload_token(&rqctx.context().app, path_params.into_inner().email)
-        })
-        .await;
+    scopes: Scopes,
+    body_param: TypedBody<ReceivePackageRequest>,
+) -> Result<HttpResponseOk<Redacted<cio_api::receiving::ReceivedPackage>>, HttpError> {
+    scopes.require_any(&[Scope::Operations])?;
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    match result {
-        Ok(login) => {
+    match txn
+        .run(|| crate::handlers::handle_shipments_inbound_receive(rqctx, body))
+        .await
+    {
+        Ok(received) => {
             txn.finish(http::StatusCode::OK);
-            Ok(HttpResponseOk(login))
+
+            Ok(HttpResponseOk(Redacted::new(received, &scopes)))
         }
-        Err(err) => {
+        // Send the error to sentry.
+        Err(e) => {
             txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-            Err(handle_anyhow_err_as_http_err(err))
+            Err(handle_anyhow_err_as_http_err(e))
         }
     }
 }
 
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct LoanerReservationApiRequest {
+    #[serde(flatten)]
+    pub reservation: cio_api::asset_loaners::LoanerReservationRequest,
+}
+
 /**
- * Listen for applications being submitted for incoming job applications */
+ * Listen for requests to reserve a loaner asset for a range of days. Either a specific loaner
+ * or a type of loaner can be requested, the request fails if none is free on those days.
+ */
 #[endpoint {
     method = POST,
-    path = "/application-test/submit",
+    path = "/assets/loaners/reservations",
 }]
-async fn listen_test_application_submit_requests(
+async fn listen_assets_loaner_reservation_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<HiringToken>,
-    body_param: TypedBody<cio_api::application_form::ApplicationForm>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<LoanerReservationApiRequest>,
+) -> Result<HttpResponseOk<cio_api::asset_loaners::AssetReservation>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_test_application_submit(rqctx, body))
+    match txn
+        .run(|| crate::handlers::handle_assets_loaner_reservation(rqctx, body))
         .await
     {
+        Ok(reservation) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(reservation))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
+}
 
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct LoanerReservationUpdateRequest {
+    pub id: i32,
+    /// The new status: `picked up`, `returned`, or `cancelled`.
+    pub status: String,
 }
 
 /**
- * Listen for applications being submitted for incoming job applications */
+ * Listen for loaner reservations being picked up, returned, or cancelled.
+ */
 #[endpoint {
     method = POST,
-    path = "/application/submit",
+    path = "/assets/loaners/reservations/update",
 }]
-async fn listen_application_submit_requests(
+async fn listen_assets_loaner_reservation_update_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<HiringToken>,
-    body_param: TypedBody<cio_api::application_form::ApplicationForm>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<LoanerReservationUpdateRequest>,
+) -> Result<HttpResponseOk<cio_api::asset_loaners::AssetReservation>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_application_submit(rqctx, body))
+    match txn
+        .run(|| crate::handlers::handle_assets_loaner_reservation_update(&rqctx.context().app, body))
         .await
     {
+        Ok(reservation) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(reservation))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/// Application file upload data.
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct ApplicationFileUploadData {
-    #[serde(default)]
-    pub cio_company_id: i32,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub resume: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub materials: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub portfolio_pdf_name: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub email: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub role: String,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub interested_in: Vec<String>,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub user_name: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub resume_contents: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub materials_contents: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub portfolio_pdf_contents: String,
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct LoanerUtilizationReportRequest {
+    /// How many days back the report covers, including today.
+    pub days: i64,
 }
 
 /**
- * CORS functionality for file uploads
+ * Listen for requests to generate the loaner utilization report, least used loaners first.
  */
 #[endpoint {
-    method = OPTIONS,
-    path = "/application-test/files/upload",
+    method = POST,
+    path = "/assets/loaners/utilization-report",
 }]
-async fn listen_test_application_files_upload_requests_cors(
-    _qctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
-    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
-    let headers = resp.headers_mut();
+async fn listen_assets_loaner_utilization_report_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<LoanerUtilizationReportRequest>,
+) -> Result<HttpResponseOk<Vec<cio_api::asset_loaners::LoanerUtilization>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    headers.insert("Access-Control-Allow-Origin", HeaderValue::from_static("*"));
-    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
-    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+    match txn
+        .run(|| crate::handlers::handle_assets_loaner_utilization_report(rqctx, body))
+        .await
+    {
+        Ok(report) => {
+            txn.finish(http::StatusCode::OK);
 
-    Ok(resp)
+            Ok(HttpResponseOk(report))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct AssetRefreshForecastRequest {
+    /// How many quarters the forecast covers, including the current one. Between 1 and 20.
+    pub quarters: u32,
 }
 
 /**
- * Listen for files being uploaded for incoming job applications */
+ * Listen for requests to forecast the hardware we need to replace, and what it costs, by
+ * quarter.
+ */
 #[endpoint {
     method = POST,
-    path = "/application-test/files/upload",
+    path = "/assets/refresh-forecast",
 }]
-async fn listen_test_application_files_upload_requests(
+async fn listen_assets_refresh_forecast_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    bearer: BearerToken,
-    body_param: TypedBody<ApplicationFileUploadData>,
-) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
+    scopes: Scopes,
+    body_param: TypedBody<AssetRefreshForecastRequest>,
+) -> Result<HttpResponseOk<Vec<cio_api::asset_refresh::AssetRefreshQuarter>>, HttpError> {
+    scopes.require_any(&[Scope::Finance, Scope::Operations])?;
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    // We require that the user has supplied an upload token in the bearer header
-    if let Some(token) = bearer.inner() {
-        // Attempt to consume the token and mark it as unusable by other requests. A token may fail
-        // to be consumed due a number of reasons to:
-        //  1. Token was previously used
-        //  2. Token is invalid
-        //  3. Token does not match the email submitted
-        //  4. Token is expired
-        //
-        // We currently return a single error code, 409 Conflict so as not to expose which of these
-        // cases occurred. In the future we may want to relax this and return individual error codes
-        let token_result = rqctx
-            .context()
-            .app
-            .upload_token_store
-            .consume(&body.email, token)
-            .await
-            .map_err(|err| {
-                log::info!("Failed to consume upload token due to {:?}", err);
-                HttpError::for_status(None, http::StatusCode::CONFLICT)
-            });
-
-        match token_result {
-            Ok(_) => {
-                let upload_result = txn
-                    .run(|| crate::handlers::handle_test_application_files_upload(rqctx, body))
-                    .await;
-
-                match upload_result {
-                    Ok(r) => {
-                        txn.finish(http::StatusCode::OK);
-
-                        let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
-
-                        let headers = resp.headers_mut();
-                        headers.insert("Access-Control-Allow-Origin", http::HeaderValue::from_static("*"));
+    match txn
+        .run(|| crate::handlers::handle_assets_refresh_forecast(rqctx, body))
+        .await
+    {
+        Ok(forecast) => {
+            txn.finish(http::StatusCode::OK);
 
-                        Ok(resp)
-                    }
-                    // Send the error to sentry.
-                    Err(e) => {
-                        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-                        Err(handle_anyhow_err_as_http_err(e))
-                    }
-                }
-            }
-            Err(err) => {
-                txn.finish(err.status_code);
-                Err(err)
-            }
+            Ok(HttpResponseOk(forecast))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
         }
-    } else {
-        txn.finish(http::StatusCode::UNAUTHORIZED);
-        Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
     }
 }
 
 /**
- * CORS functionality for file uploads
+ * Listen for a button pressed to print barcode labels for a swag inventory item.
  */
 #[endpoint {
-    method = OPTIONS,
-    path = "/application/files/upload",
+    method = POST,
+    path = "/airtable/swag/inventory/items/print_barcode_labels",
 }]
-async fn listen_application_files_upload_requests_cors(
+async fn listen_airtable_swag_inventory_items_print_barcode_labels_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
-    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
-    let headers = resp.headers_mut();
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    let allowed_origins = crate::cors::get_cors_origin_header(
-        rqctx.clone(),
-        &["https://apply.oxide.computer", "https://oxide.computer"],
-    )
-    .await?;
-    headers.insert("Access-Control-Allow-Origin", allowed_origins);
-    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
-    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_airtable_swag_inventory_items_print_barcode_labels(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
 
-    Ok(resp)
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
 /**
- * Listen for files being uploaded for incoming job applications */
+ * Listen for a button pressed to print barcode labels for a swag item variant.
+ */
 #[endpoint {
     method = POST,
-    path = "/application/files/upload",
+    path = "/airtable/swag/variants/print_barcode_labels",
 }]
-async fn listen_application_files_upload_requests(
+async fn listen_airtable_swag_item_variants_print_barcode_labels_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    bearer: BearerToken,
-    body_param: TypedBody<ApplicationFileUploadData>,
-) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    // We require that the user has supplied an upload token in the bearer header
-    if let Some(token) = bearer.inner() {
-        // Attempt to consume the token marked it as unusable by other requests. A token may fail
-        // to be consumed due to:
-        //  1. Token was previously used
-        //  2. Token is invalid
-        //  3. Token does not match the email submitted
-        //  4. Token is expired
-        //
-        // We currently return a single error code, 409 Conflict so as not to expose which of these
-        // cases occurred. In the future we may want to relax this and return individual error codes
-        let token_result = rqctx
-            .context()
-            .app
-            .upload_token_store
-            .consume(&body.email, token)
-            .await
-            .map_err(|err| {
-                log::info!("Failed to consume upload token due to {:?}", err);
-                HttpError::for_status(None, http::StatusCode::CONFLICT)
-            });
-
-        log::info!("Application materials upload token consume result {:?}", token_result);
-
-        match token_result {
-            Ok(_) => {
-                // Check the origin header. In the future this may be upgraded to a hard failure
-                let origin_access = crate::cors::get_cors_origin_header(
-                    rqctx.clone(),
-                    &["https://apply.oxide.computer", "https://oxide.computer"],
-                )
-                .await;
-
-                let upload_result = txn
-                    .run(|| crate::handlers::handle_application_files_upload(rqctx, body))
-                    .await;
-
-                match upload_result {
-                    Ok(r) => {
-                        txn.finish(http::StatusCode::OK);
-
-                        let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
-
-                        match origin_access {
-                            Ok(origin) => {
-                                let headers = resp.headers_mut();
-                                headers.insert("Access-Control-Allow-Origin", origin);
-                            }
-                            Err(err) => {
-                                warn!(
-                                    "Submission to /application/files/upload failed CORS check. Err {:?}",
-                                    err
-                                );
-                            }
-                        }
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_airtable_swag_item_variants_print_barcode_labels(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
 
-                        Ok(resp)
-                    }
-                    // Send the error to sentry.
-                    Err(e) => {
-                        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-                        Err(handle_anyhow_err_as_http_err(e))
-                    }
-                }
-            }
-            Err(err) => {
-                txn.finish(err.status_code);
-                Err(err)
-            }
-        }
-    } else {
-        log::info!("Applicant upload request is missing a bearer token");
+    txn.finish(http::StatusCode::ACCEPTED);
 
-        txn.finish(http::StatusCode::UNAUTHORIZED);
-        Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
-    }
+    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
 /**
- * Listen for rows created in our Airtable workspace.
- * These are set up with an Airtable script on the workspaces themselves.
+ * Listen for a button pressed to request a background check for an applicant.
  */
 #[endpoint {
     method = POST,
-    path = "/airtable/shipments/inbound/create",
+    path = "/airtable/applicants/request_background_check",
 }]
-async fn listen_airtable_shipments_inbound_create_webhooks(
+async fn listen_airtable_applicants_request_background_check_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
@@ -1312,7 +1459,7 @@ async fn listen_airtable_shipments_inbound_create_webhooks(
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_airtable_shipments_inbound_create(rqctx, body))
+        .run(|| crate::handlers::handle_airtable_applicants_request_background_check(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -1326,22 +1473,23 @@ async fn listen_airtable_shipments_inbound_create_webhooks(
 }
 
 /**
- * Listen for orders being created by the Oxide store.
+ * Listen for rows updated in our Airtable workspace.
+ * These are set up with an Airtable script on the workspaces themselves.
  */
 #[endpoint {
     method = POST,
-    path = "/store/order",
+    path = "/airtable/applicants/update",
 }]
-async fn listen_store_order_create(
+async fn listen_airtable_applicants_update_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<InternalToken>,
-    body_param: TypedBody<Order>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_store_order_create(rqctx, body))
+        .run(|| crate::handlers::handle_airtable_applicants_update(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -1355,21 +1503,23 @@ async fn listen_store_order_create(
 }
 
 /**
- * Listen for shipment tracking updated from EasyPost.
+ * Listen for requests to recreate and resend PIIA documents for a given applicant
+ * These are set up with an Airtable script on the workspaces themselves.
  */
 #[endpoint {
     method = POST,
-    path = "/easypost/tracking/update",
+    path = "/airtable/applicants/recreate_piia",
 }]
-async fn listen_easypost_tracking_update_webhooks(
+async fn listen_airtable_applicants_recreate_piia_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<EasyPostTrackingUpdateEvent>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_easypost_tracking_update(rqctx, body))
+        .run(|| crate::handlers::listen_airtable_applicants_recreate_piia_webhooks(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -1382,66 +1532,24 @@ async fn listen_easypost_tracking_update_webhooks(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/// An EasyPost tracking update event.
-/// FROM: https://www.easypost.com/docs/api#events
-#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
-pub struct EasyPostTrackingUpdateEvent {
-    /// "Event".
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub object: String,
-    /// Unique identifier, begins with "evt_".
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub id: String,
-    /// "test" or "production"
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub mode: String,
-    /// Result type and event name, see the "Possible Event Types" section for more information.
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub description: String,
-    /* /// Previous values of relevant result attributes.
-    #[serde(default)]
-    pub previous_attributes: serde_json::Value,
-    /// The object associated with the Event. See the "object" attribute on the result to determine
-    /// its specific type. This field will not be returned when retrieving events directly from the
-    /// API.
-    #[serde(default)]
-    pub result: serde_json::Value,*/
-    /// The current status of the event. Possible values are "completed", "failed", "in_queue",
-    /// "retrying", or "pending" (deprecated).
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub status: String,
-    /// Webhook URLs that have not yet been successfully notified as of the time this webhook event
-    /// was sent. The URL receiving the Event will still be listed in pending_urls, as will any
-    /// other URLs that receive the Event at the same time.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub pending_urls: Vec<String>,
-    /// Webhook URLs that have already been successfully notified as of the time this webhook was
-    /// sent.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub completed_urls: Vec<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub created_at: Option<DateTime<Utc>>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<DateTime<Utc>>,
-}
-
 /**
- * Listen for shipment tracking updated from Shippo.
+ * Listen for rows created in our Airtable workspace.
+ * These are set up with an Airtable script on the workspaces themselves.
  */
 #[endpoint {
     method = POST,
-    path = "/shippo/tracking/update",
+    path = "/airtable/shipments/outbound/create",
 }]
-async fn listen_shippo_tracking_update_webhooks(
+async fn listen_airtable_shipments_outbound_create_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: QueryToken<ShippoToken>,
-    body_param: TypedBody<serde_json::Value>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_shippo_tracking_update(rqctx, body))
+        .run(|| crate::handlers::handle_airtable_shipments_outbound_create(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -1454,32 +1562,36 @@ async fn listen_shippo_tracking_update_webhooks(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/// A Shippo tracking update event.
+/// An Airtable row event.
 #[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct ShippoTrackingUpdateEvent {
-    #[serde(default)]
-    pub data: shippo::TrackingStatus,
+pub struct AirtableRowEvent {
     #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub event: String,
-    #[serde(default)]
-    pub test: bool,
+    pub record_id: String,
+    /// The printer to print on, for the buttons that print something.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub printer: String,
+    /// The email of who pressed the button, we print in their office if no printer is given.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email: String,
 }
 
-/** Listen for updates to our checkr background checks. */
+/**
+ * Listen for a button pressed to reprint a label for an outbound shipment.
+ */
 #[endpoint {
     method = POST,
-    path = "/checkr/background/update",
+    path = "/airtable/shipments/outbound/reprint_label",
 }]
-async fn listen_checkr_background_update_webhooks(
+async fn listen_airtable_shipments_outbound_reprint_label_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body: HmacVerifiedBodyAudit<crate::handlers_checkr::CheckrWebhookVerification, checkr::WebhookEvent>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let webhook = body.into_inner()?;
-
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers::handle_checkr_background_update(rqctx, webhook))
+        .run(|| crate::handlers::handle_airtable_shipments_outbound_reprint_label(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -1492,58 +1604,23 @@ async fn listen_checkr_background_update_webhooks(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct UserConsentURL {
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub url: String,
-}
-
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct AuthCallback {
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub code: String,
-    /// The state that we had passed in through the user consent URL.
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub state: String,
-    #[serde(default, skip_serializing_if = "String::is_empty", rename = "realmId")]
-    pub realm_id: String,
-}
-
-/** Get the consent URL for Google auth. */
-#[endpoint {
-    method = GET,
-    path = "/auth/google/consent",
-}]
-async fn listen_auth_google_consent(
-    rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
-
-    // Initialize the Google client.
-    // You can use any of the libs here, they all use the same endpoint
-    // for tokens and we will send all the scopes.
-    let g = txn.run(|| GoogleDrive::new_from_env("", "")).await;
-
-    txn.finish(http::StatusCode::OK);
-
-    Ok(HttpResponseOk(UserConsentURL {
-        url: g.user_consent_url(&cio_api::companies::get_google_scopes()),
-    }))
-}
-
-/** Listen for callbacks to Google auth. */
+/**
+ * Listen for a button pressed to reprint a receipt for an outbound shipment.
+ */
 #[endpoint {
-    method = GET,
-    path = "/auth/google/callback",
+    method = POST,
+    path = "/airtable/shipments/outbound/reprint_receipt",
 }]
-async fn listen_auth_google_callback(
+async fn listen_airtable_shipments_outbound_reprint_receipt_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_google_callback(rqctx, query_args))
+        .run(|| crate::handlers::handle_airtable_shipments_outbound_reprint_receipt(rqctx, body))
         .await
     {
         // Send the error to sentry.
@@ -1556,78 +1633,25 @@ async fn listen_auth_google_callback(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Get the consent URL for GitHub auth. */
-#[endpoint {
-    method = GET,
-    path = "/auth/github/consent",
-}]
-async fn listen_auth_github_consent(
-    rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
-
-    txn.finish(http::StatusCode::OK);
-
-    Ok(HttpResponseOk(UserConsentURL {
-        url: "https://github.com/apps/oxidecomputerbot/installations/new".to_string(),
-    }))
-}
-
-/** Listen for callbacks to GitHub auth. */
+/**
+ * Listen for a button pressed to resend a shipment status email to the recipient for an outbound shipment.
+ */
 #[endpoint {
-    method = GET,
-    path = "/auth/github/callback",
+    method = POST,
+    path = "/airtable/shipments/outbound/resend_shipment_status_email_to_recipient",
 }]
-async fn listen_auth_github_callback(
+async fn listen_airtable_shipments_outbound_resend_shipment_status_email_to_recipient_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<serde_json::Value>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    txn.run(|| {
-        warn!("github callback: {:?}", body);
-    });
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
-}
-
-/** Get the consent URL for Gusto auth. */
-#[endpoint {
-    method = GET,
-    path = "/auth/gusto/consent",
-}]
-async fn listen_auth_gusto_consent(
-    rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
-
-    // Initialize the Gusto client.
-    let g = txn.run(|| Gusto::new_from_env("", ""));
-
-    txn.finish(http::StatusCode::OK);
-
-    Ok(HttpResponseOk(UserConsentURL {
-        // We don't need to define scopes for Gusto.
-        url: g.user_consent_url(&[]),
-    }))
-}
-
-/** Listen for callbacks to Gusto auth. */
-#[endpoint {
-    method = GET,
-    path = "/auth/gusto/callback",
-}]
-async fn listen_auth_gusto_callback(
-    rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
-
     if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_gusto_callback(rqctx, query_args))
+        .run(|| {
+            crate::handlers::handle_airtable_shipments_outbound_resend_shipment_status_email_to_recipient(rqctx, body)
+        })
         .await
     {
         // Send the error to sentry.
@@ -1640,62 +1664,91 @@ async fn listen_auth_gusto_callback(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Listen to deauthorization requests for our Zoom app. */
+/**
+ * Listen for a button pressed to schedule a pickup for an outbound shipment.
+ */
 #[endpoint {
-    method = GET,
-    path = "/auth/zoom/deauthorization",
+    method = POST,
+    path = "/airtable/shipments/outbound/schedule_pickup",
 }]
-async fn listen_auth_zoom_deauthorization(
+async fn listen_airtable_shipments_outbound_schedule_pickup_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<serde_json::Value>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    txn.run(|| {
-        warn!("zoom deauthorization: {:?}", body);
-    });
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_airtable_shipments_outbound_schedule_pickup(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
 
     txn.finish(http::StatusCode::ACCEPTED);
 
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Get the consent URL for Zoom auth. */
-#[endpoint {
-    method = GET,
-    path = "/auth/zoom/consent",
-}]
-async fn listen_auth_zoom_consent(
-    rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
-
-    // Initialize the Zoom client.
-    let g = txn.run(|| Zoom::new_from_env("", ""));
-
-    txn.finish(http::StatusCode::OK);
-
-    Ok(HttpResponseOk(UserConsentURL {
-        url: g.user_consent_url(&[]),
-    }))
-}
-
-/** Listen for callbacks to Zoom auth. */
-#[endpoint {
-    method = GET,
-    path = "/auth/zoom/callback",
+/// A SendGrid incoming email event.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct IncomingEmail {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub headers: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub dkim: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", alias = "content-ids")]
+    pub content_ids: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub to: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub cc: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub html: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub from: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sender_ip: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub spam_report: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub envelope: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub attachments: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subject: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub spam_score: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", alias = "attachment-info")]
+    pub attachment_info: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub charsets: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", alias = "SPF")]
+    pub spf: String,
+}
+
+/**
+ * Listen for applicant reviews being submitted for job applicants */
+#[endpoint {
+    method = POST,
+    path = "/applicant/review/submit",
 }]
-async fn listen_auth_zoom_callback(
+async fn listen_applicant_review_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<cio_api::applicant_reviews::NewApplicantReview>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_zoom_callback(rqctx, query_args))
-        .await
-    {
+    if let Err(e) = txn.run(|| crate::handlers::handle_applicant_review(rqctx, body)).await {
         // Send the error to sentry.
         txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
         return Err(handle_anyhow_err_as_http_err(e));
@@ -1706,84 +1759,138 @@ async fn listen_auth_zoom_callback(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Get the consent URL for Slack auth. */
+#[derive(Deserialize, JsonSchema)]
+struct ApplicantInfoParams {
+    email: String,
+}
+
+// Listen for applicant info requests. This assume that the caller has performed the necessary
+// authentication to verify ownership of the email that we are being sent
 #[endpoint {
     method = GET,
-    path = "/auth/slack/consent",
+    path = "/applicant/info/{email}",
 }]
-async fn listen_auth_slack_consent(
+async fn listen_applicant_info(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+    _auth: Bearer<HiringToken>,
+    path_params: Path<ApplicantInfoParams>,
+) -> Result<HttpResponseOk<ApplicantInfo>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
-    // Initialize the Slack client.
-    let s = txn.run(|| Slack::new_from_env("", "", ""));
+    log::info!("Running applicant info handler");
 
-    txn.finish(http::StatusCode::OK);
+    let result = txn
+        .run(|| crate::handlers_hiring::handle_applicant_info(&rqctx.context().app, path_params.into_inner().email))
+        .await;
 
-    Ok(HttpResponseOk(UserConsentURL {
-        url: s.user_consent_url(),
-    }))
+    match result {
+        Ok(login) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(login))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
 }
 
-/** Listen for callbacks to Slack auth. */
+// Listen for applicant upload token requests. This returns a short-lived, one time token that can
+// be used to upload materials against the supplied email address. This assume that the caller has
+// performed the necessary authentication to verify ownership of the email that we are being sent
 #[endpoint {
     method = GET,
-    path = "/auth/slack/callback",
+    path = "/applicant/info/{email}/upload-token",
 }]
-async fn listen_auth_slack_callback(
+async fn listen_applicant_upload_token(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+    _auth: Bearer<HiringToken>,
+    path_params: Path<ApplicantInfoParams>,
+) -> Result<HttpResponseOk<ApplicantUploadToken>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_slack_callback(rqctx, query_args))
-        .await
-    {
-        // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
-    }
+    log::info!("Running applicant upload token handler");
 
-    txn.finish(http::StatusCode::ACCEPTED);
+    let result = txn
+        .run(|| {
+            crate::handlers_hiring::handle_applicant_upload_token(&rqctx.context().app, path_params.into_inner().email)
+        })
+        .await;
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+    match result {
+        Ok(login) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(login))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
 }
 
-/** Get the consent URL for QuickBooks auth. */
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct ApplicantWithdrawRequest {
+    pub email: String,
+    /// The role to withdraw from, the newest application is withdrawn if there is none.
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub reason: String,
+}
+
+// Listen for candidates withdrawing their application. This assume that the caller has performed
+// the necessary authentication to verify ownership of the email that we are being sent
 #[endpoint {
-    method = GET,
-    path = "/auth/quickbooks/consent",
+    method = POST,
+    path = "/applicant/withdraw",
 }]
-async fn listen_auth_quickbooks_consent(
+async fn listen_applicant_withdraw_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+    _auth: Bearer<HiringToken>,
+    body_param: TypedBody<ApplicantWithdrawRequest>,
+) -> Result<HttpResponseOk<cio_api::applicant_withdrawals::ApplicantWithdrawal>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    // Initialize the QuickBooks client.
-    let g = txn.run(|| QuickBooks::new_from_env("", "", ""));
+    log::info!("Running applicant withdraw handler");
 
-    txn.finish(http::StatusCode::OK);
+    let result = txn
+        .run(|| crate::handlers_hiring::handle_applicant_withdraw(&rqctx.context().app, body))
+        .await;
 
-    Ok(HttpResponseOk(UserConsentURL {
-        url: g.user_consent_url(),
-    }))
+    match result {
+        Ok(withdrawal) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(withdrawal))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
 }
 
-/** Listen for callbacks to QuickBooks auth. */
+/**
+ * Listen for the replies of candidates from our inbound email webhook. The withdrawals of the
+ * candidates asking to withdraw wait for the recruiting team to confirm them, the other emails
+ * are ignored.
+ */
 #[endpoint {
-    method = GET,
-    path = "/auth/quickbooks/callback",
+    method = POST,
+    path = "/applicant/withdraw/email",
 }]
-async fn listen_auth_quickbooks_callback(
+async fn listen_applicant_withdraw_email_webhooks(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
+    _auth: QueryToken<InternalToken>,
+    body_param: InboundEmailBody,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.0;
+    // Emails are full of personal data, do not send them to Sentry.
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_quickbooks_callback(rqctx, query_args))
+        .run(|| crate::handlers_hiring::handle_applicant_withdraw_email(&rqctx.context().app, body))
         .await
     {
         // Send the error to sentry.
@@ -1796,144 +1903,2352 @@ async fn listen_auth_quickbooks_callback(
     Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Listen for webhooks from Plaid. */
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct ApplicantWithdrawalPathParams {
+    pub id: i32,
+}
+
+/**
+ * Listen for the recruiting team confirming a withdrawal we found in the email of a candidate.
+ * The candidate is withdrawn and their upcoming interviews are cancelled.
+ */
 #[endpoint {
     method = POST,
-    path = "/plaid",
+    path = "/applicant/withdrawals/{id}/confirm",
 }]
-async fn listen_auth_plaid_callback(
+async fn listen_applicant_withdrawal_confirm_requests(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<serde_json::Value>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _auth: Bearer<HiringToken>,
+    path_params: Path<ApplicantWithdrawalPathParams>,
+) -> Result<HttpResponseOk<cio_api::applicant_withdrawals::ApplicantWithdrawal>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| {
+            crate::handlers_hiring::handle_applicant_withdrawal_confirm(
+                &rqctx.context().app,
+                path_params.into_inner().id,
+            )
+        })
+        .await
+    {
+        Ok(withdrawal) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(withdrawal))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/**
+ * Listen for requests to schedule an interview. The interview is put at the first time the
+ * whole panel is free, and the applicant is emailed the time and the Meet link.
+ */
+#[endpoint {
+    method = POST,
+    path = "/applicant/interviews/schedule",
+}]
+async fn listen_applicant_interview_schedule_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<HiringToken>,
+    body_param: TypedBody<cio_api::interviews::ScheduleInterviewRequest>,
+) -> Result<HttpResponseOk<cio_api::interviews::ApplicantInterview>, HttpError> {
     let body = body_param.into_inner();
     let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
 
-    txn.run(|| {
-        warn!("plaid callback: {:?}", body);
-    });
+    let result = txn
+        .run(|| crate::handlers_hiring::handle_applicant_interview_schedule(&rqctx.context().app, body))
+        .await;
 
-    txn.finish(http::StatusCode::ACCEPTED);
+    match result {
+        Ok(interview) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(interview))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
 
-    Ok(HttpResponseAccepted("ok".to_string()))
+// Listen for requests for the roles catalog.
+#[endpoint {
+    method = GET,
+    path = "/roles",
+}]
+async fn listen_roles(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<HiringToken>,
+) -> Result<HttpResponseOk<RoleCatalog>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    log::info!("Running roles handler");
+
+    let result = txn
+        .run(|| crate::handlers_hiring::handle_roles(&rqctx.context().app))
+        .await;
+
+    match result {
+        Ok(roles) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(roles))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ProfileParams {
+    email: String,
+}
+
+// Listen for requests for a user's profile. Users can only get their own profile, the email
+// has to match the Google account they signed in with.
+#[endpoint {
+    method = GET,
+    path = "/profile/{email}",
+}]
+async fn listen_get_profile(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    identity: UserIdentity,
+    path_params: Path<ProfileParams>,
+) -> Result<HttpResponseOk<UserProfile>, HttpError> {
+    let email = path_params.into_inner().email;
+    identity.require_email(&email)?;
+
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    log::info!("Running get profile handler");
+
+    let result = txn
+        .run(|| crate::handlers_profile::handle_get_profile(&rqctx.context().app, email))
+        .await;
+
+    match result {
+        Ok(profile) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(profile))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+// Listen for users updating their profile. Users can only update their own profile, the email
+// has to match the Google account they signed in with.
+#[endpoint {
+    method = POST,
+    path = "/profile/{email}",
+}]
+async fn listen_update_profile(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    identity: UserIdentity,
+    path_params: Path<ProfileParams>,
+    body_param: TypedBody<UserProfile>,
+) -> Result<HttpResponseOk<UserProfile>, HttpError> {
+    let email = path_params.into_inner().email;
+    identity.require_email(&email)?;
+
+    let body = body_param.into_inner();
+    // Profiles hold home addresses, do not send them to Sentry.
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    log::info!("Running update profile handler");
+
+    // Let the caller know what they need to fix, rather than failing the request.
+    let mut profile = body.clone();
+    profile.normalize();
+    if let Err(err) = profile.validate() {
+        txn.finish(http::StatusCode::BAD_REQUEST);
+        return Err(HttpError::for_bad_request(None, err.to_string()));
+    }
+
+    let result = txn
+        .run(|| crate::handlers_profile::handle_update_profile(&rqctx.context().app, email, profile))
+        .await;
+
+    match result {
+        Ok(profile) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(profile))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct UserImportRequest {
+    /// The HR system the export is from.
+    pub export: cio_api::user_import::HrisExport,
+    /// The contents of the CSV export.
+    pub csv: String,
+    /// Only return what the import would change.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/**
+ * Listen for imports of the employees of a Gusto or BambooHR export. They are added to the
+ * users of the configs repo, and the response is what changed for each user.
+ */
+#[endpoint {
+    method = POST,
+    path = "/users/import",
+}]
+async fn listen_users_import_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<UserImportRequest>,
+) -> Result<HttpResponseOk<Vec<cio_api::user_import::UserImportChange>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn.run(|| crate::handlers::handle_users_import(rqctx, body)).await {
+        Ok(changes) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(changes))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct ConferenceLeadsImportRequest {
+    /// The name of the conference the leads were captured at.
+    pub conference: String,
+    /// The contents of the CSV export of the badge scanner.
+    pub csv: String,
+}
+
+/**
+ * Listen for imports of the leads captured at the booth of a conference we sponsor. The
+ * response is the leads as we saved them.
+ */
+#[endpoint {
+    method = POST,
+    path = "/conferences/leads/import",
+}]
+async fn listen_conference_leads_import_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<ConferenceLeadsImportRequest>,
+) -> Result<HttpResponseOk<Vec<cio_api::conferences::ConferenceLead>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers::handle_conference_leads_import(rqctx, body))
+        .await
+    {
+        Ok(leads) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(leads))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SkillsSearchQuery {
+    topic: String,
+}
+
+// Listen for searches of who knows about a topic, from the skills profiles users shared. Only
+// our users can search them.
+#[endpoint {
+    method = GET,
+    path = "/skills/search",
+}]
+async fn listen_search_skills(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _user: CompanyUser,
+    query_args: Query<SkillsSearchQuery>,
+) -> Result<HttpResponseOk<Vec<SkillMatch>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    log::info!("Running search skills handler");
+
+    let result = txn
+        .run(|| crate::handlers_profile::handle_search_skills(&rqctx.context().app, query_args.into_inner().topic))
+        .await;
+
+    match result {
+        Ok(matches) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(matches))
+        }
+        Err(err) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/**
+ * Listen for applications being submitted for incoming job applications */
+#[endpoint {
+    method = POST,
+    path = "/application-test/submit",
+}]
+async fn listen_test_application_submit_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<HiringToken>,
+    body_param: TypedBody<cio_api::application_form::ApplicationForm>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_test_application_submit(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Listen for applications being submitted for incoming job applications */
+#[endpoint {
+    method = POST,
+    path = "/application/submit",
+}]
+async fn listen_application_submit_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<HiringToken>,
+    body_param: TypedBody<cio_api::application_form::ApplicationForm>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_application_submit(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/// Application file upload data.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ApplicationFileUploadData {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub resume: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub materials: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub portfolio_pdf_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub role: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interested_in: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub resume_contents: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub materials_contents: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub portfolio_pdf_contents: String,
+}
+
+/**
+ * CORS functionality for file uploads
+ */
+#[endpoint {
+    method = OPTIONS,
+    path = "/application-test/files/upload",
+}]
+async fn listen_test_application_files_upload_requests_cors(
+    _qctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
+    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
+    let headers = resp.headers_mut();
+
+    headers.insert("Access-Control-Allow-Origin", HeaderValue::from_static("*"));
+    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
+    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+
+    Ok(resp)
+}
+
+/**
+ * Listen for files being uploaded for incoming job applications */
+#[endpoint {
+    method = POST,
+    path = "/application-test/files/upload",
+}]
+async fn listen_test_application_files_upload_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    bearer: BearerToken,
+    body_param: TypedBody<ApplicationFileUploadData>,
+) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    // We require that the user has supplied an upload token in the bearer header
+    if let Some(token) = bearer.inner() {
+        // Attempt to consume the token and mark it as unusable by other requests. A token may fail
+        // to be consumed due a number of reasons to:
+        //  1. Token was previously used
+        //  2. Token is invalid
+        //  3. Token does not match the email submitted
+        //  4. Token is expired
+        //
+        // We currently return a single error code, 409 Conflict so as not to expose which of these
+        // cases occurred. In the future we may want to relax this and return individual error codes
+        let token_result = rqctx
+            .context()
+            .app
+            .upload_token_store
+            .consume(&body.email, token)
+            .await
+            .map_err(|err| {
+                log::info!("Failed to consume upload token due to {:?}", err);
+                HttpError::for_status(None, http::StatusCode::CONFLICT)
+            });
+
+        match token_result {
+            Ok(_) => {
+                let upload_result = txn
+                    .run(|| crate::handlers::handle_test_application_files_upload(rqctx, body))
+                    .await;
+
+                match upload_result {
+                    Ok(r) => {
+                        txn.finish(http::StatusCode::OK);
+
+                        let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
+
+                        let headers = resp.headers_mut();
+                        headers.insert("Access-Control-Allow-Origin", http::HeaderValue::from_static("*"));
+
+                        Ok(resp)
+                    }
+                    // Send the error to sentry.
+                    Err(e) => {
+                        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+                        Err(handle_anyhow_err_as_http_err(e))
+                    }
+                }
+            }
+            Err(err) => {
+                txn.finish(err.status_code);
+                Err(err)
+            }
+        }
+    } else {
+        txn.finish(http::StatusCode::UNAUTHORIZED);
+        Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
+    }
+}
+
+/**
+ * CORS functionality for file uploads
+ */
+#[endpoint {
+    method = OPTIONS,
+    path = "/application/files/upload",
+}]
+async fn listen_application_files_upload_requests_cors(
+    rqctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseHeaders<HttpResponseOk<String>>, HttpError> {
+    let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk("".to_string()));
+    let headers = resp.headers_mut();
+
+    let allowed_origins = crate::cors::get_cors_origin_header(
+        rqctx.clone(),
+        &["https://apply.oxide.computer", "https://oxide.computer"],
+    )
+    .await?;
+    headers.insert("Access-Control-Allow-Origin", allowed_origins);
+    headers.insert("Access-Control-Allow-Headers", HeaderValue::from_static("*"));
+    headers.insert("Access-Control-Allow-Method", HeaderValue::from_static("*"));
+
+    Ok(resp)
+}
+
+/**
+ * Listen for files being uploaded for incoming job applications */
+#[endpoint {
+    method = POST,
+    path = "/application/files/upload",
+}]
+async fn listen_application_files_upload_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    bearer: BearerToken,
+    body_param: TypedBody<ApplicationFileUploadData>,
+) -> Result<HttpResponseHeaders<HttpResponseOk<HashMap<String, String>>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    // We require that the user has supplied an upload token in the bearer header
+    if let Some(token) = bearer.inner() {
+        // Attempt to consume the token marked it as unusable by other requests. A token may fail
+        // to be consumed due to:
+        //  1. Token was previously used
+        //  2. Token is invalid
+        //  3. Token does not match the email submitted
+        //  4. Token is expired
+        //
+        // We currently return a single error code, 409 Conflict so as not to expose which of these
+        // cases occurred. In the future we may want to relax this and return individual error codes
+        let token_result = rqctx
+            .context()
+            .app
+            .upload_token_store
+            .consume(&body.email, token)
+            .await
+            .map_err(|err| {
+                log::info!("Failed to consume upload token due to {:?}", err);
+                HttpError::for_status(None, http::StatusCode::CONFLICT)
+            });
+
+        log::info!("Application materials upload token consume result {:?}", token_result);
+
+        match token_result {
+            Ok(_) => {
+                // Check the origin header. In the future this may be upgraded to a hard failure
+                let origin_access = crate::cors::get_cors_origin_header(
+                    rqctx.clone(),
+                    &["https://apply.oxide.computer", "https://oxide.computer"],
+                )
+                .await;
+
+                let upload_result = txn
+                    .run(|| crate::handlers::handle_application_files_upload(rqctx, body))
+                    .await;
+
+                match upload_result {
+                    Ok(r) => {
+                        txn.finish(http::StatusCode::OK);
+
+                        let mut resp = HttpResponseHeaders::new_unnamed(HttpResponseOk(r));
+
+                        match origin_access {
+                            Ok(origin) => {
+                                let headers = resp.headers_mut();
+                                headers.insert("Access-Control-Allow-Origin", origin);
+                            }
+                            Err(err) => {
+                                warn!(
+                                    "Submission to /application/files/upload failed CORS check. Err {:?}",
+                                    err
+                                );
+                            }
+                        }
+
+                        Ok(resp)
+                    }
+                    // Send the error to sentry.
+                    Err(e) => {
+                        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+                        Err(handle_anyhow_err_as_http_err(e))
+                    }
+                }
+            }
+            Err(err) => {
+                txn.finish(err.status_code);
+                Err(err)
+            }
+        }
+    } else {
+        log::info!("Applicant upload request is missing a bearer token");
+
+        txn.finish(http::StatusCode::UNAUTHORIZED);
+        Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
+    }
+}
+
+/**
+ * Listen for rows created in our Airtable workspace.
+ * These are set up with an Airtable script on the workspaces themselves.
+ */
+#[endpoint {
+    method = POST,
+    path = "/airtable/shipments/inbound/create",
+}]
+async fn listen_airtable_shipments_inbound_create_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_airtable_shipments_inbound_create(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Listen for orders being created by the Oxide store.
+ */
+#[endpoint {
+    method = POST,
+    path = "/store/order",
+}]
+async fn listen_store_order_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<Order>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_store_order_create(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct SwagBundleShipmentRequest {
+    /// The name of the bundle to send.
+    pub bundle: String,
+    /// The sizes of the items of the bundle that are chosen for each shipment, by item.
+    #[serde(default)]
+    pub sizes: std::collections::BTreeMap<String, String>,
+    #[serde(flatten)]
+    pub recipient: cio_api::swag_bundles::SwagBundleRecipient,
+}
+
+/** Create a shipment of a swag bundle, and return its pick list. */
+#[endpoint {
+    method = POST,
+    path = "/swag/bundles/shipments",
+}]
+async fn listen_swag_bundle_shipment_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<SwagBundleShipmentRequest>,
+) -> Result<HttpResponseOk<cio_api::swag_bundles::SwagBundleShipment>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    match txn
+        .run(|| crate::handlers::handle_swag_bundle_shipment_create(rqctx, body))
+        .await
+    {
+        Ok(shipment) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(shipment))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+pub struct SwagBarcodeScanRequest {
+    #[serde(flatten)]
+    pub scan: cio_api::swag_inventory::SwagBarcodeScan,
+}
+
+/**
+ * Listen for swag barcodes scanned in the warehouse to receive or ship items. The response is
+ * the inventory item with its new stock.
+ */
+#[endpoint {
+    method = POST,
+    path = "/swag/inventory/scan",
+}]
+async fn listen_swag_inventory_scan(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<SwagBarcodeScanRequest>,
+) -> Result<HttpResponseOk<cio_api::swag_inventory::SwagInventoryItem>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    match txn
+        .run(|| crate::handlers::handle_swag_inventory_scan(rqctx, body))
+        .await
+    {
+        Ok(item) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(item))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Request swag, the request waits in the approval queue until the swag team approves it. */
+#[endpoint {
+    method = POST,
+    path = "/swag/requests",
+}]
+async fn listen_swag_request_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    user: CompanyUser,
+    body_param: TypedBody<cio_api::swag_requests::SwagRequestForm>,
+) -> Result<HttpResponseOk<cio_api::swag_requests::SwagRequest>, HttpError> {
+    let form = body_param.into_inner();
+    // The form has the address of whoever the swag ships to, it stays out of sentry.
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers::handle_swag_request_create(rqctx, user, form))
+        .await
+    {
+        Ok(request) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(request))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** List the swag requests of whoever is signed in, and where they are in the approval queue. */
+#[endpoint {
+    method = GET,
+    path = "/swag/requests",
+}]
+async fn listen_swag_request_list(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    user: CompanyUser,
+) -> Result<HttpResponseOk<Vec<cio_api::swag_requests::SwagRequest>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn.run(|| crate::handlers::handle_swag_request_list(rqctx, user)).await {
+        Ok(requests) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(requests))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/**
+ * Listen for shipment tracking updated from EasyPost.
+ */
+#[endpoint {
+    method = POST,
+    path = "/easypost/tracking/update",
+}]
+async fn listen_easypost_tracking_update_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body_param: TypedBody<RawWebhook<EasyPostTrackingUpdateEvent>>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body: EasyPostTrackingUpdateEvent = decode_webhook(&rqctx, "easypost", body_param.into_inner())?;
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_easypost_tracking_update(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/// An EasyPost tracking update event.
+/// FROM: https://www.easypost.com/docs/api#events
+#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
+pub struct EasyPostTrackingUpdateEvent {
+    /// "Event".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub object: String,
+    /// Unique identifier, begins with "evt_".
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// "test" or "production"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub mode: String,
+    /// Result type and event name, see the "Possible Event Types" section for more information.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /* /// Previous values of relevant result attributes.
+    #[serde(default)]
+    pub previous_attributes: serde_json::Value,
+    /// The object associated with the Event. See the "object" attribute on the result to determine
+    /// its specific type. This field will not be returned when retrieving events directly from the
+    /// API.
+    #[serde(default)]
+    pub result: serde_json::Value,*/
+    /// The current status of the event. Possible values are "completed", "failed", "in_queue",
+    /// "retrying", or "pending" (deprecated).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    /// Webhook URLs that have not yet been successfully notified as of the time this webhook event
+    /// was sent. The URL receiving the Event will still be listed in pending_urls, as will any
+    /// other URLs that receive the Event at the same time.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending_urls: Vec<String>,
+    /// Webhook URLs that have already been successfully notified as of the time this webhook was
+    /// sent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub completed_urls: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/**
+ * Listen for registrations to our events on Eventbrite.
+ */
+#[endpoint {
+    method = POST,
+    path = "/events/eventbrite",
+}]
+async fn listen_eventbrite_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: QueryToken<EventbriteToken>,
+    body_param: TypedBody<RawWebhook<cio_api::community_events::EventbriteWebhook>>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body: cio_api::community_events::EventbriteWebhook =
+        decode_webhook(&rqctx, "eventbrite", body_param.into_inner())?;
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_eventbrite_webhook(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Listen for registrations to our events on Luma.
+ */
+#[endpoint {
+    method = POST,
+    path = "/events/luma",
+}]
+async fn listen_luma_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: QueryToken<LumaToken>,
+    body_param: TypedBody<RawWebhook<cio_api::community_events::LumaWebhook>>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body: cio_api::community_events::LumaWebhook = decode_webhook(&rqctx, "luma", body_param.into_inner())?;
+    // The body has the guest in it, so it stays out of Sentry.
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    if let Err(e) = txn.run(|| crate::handlers::handle_luma_webhook(rqctx, body)).await {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/**
+ * Listen for shipment tracking updated from Shippo.
+ */
+#[endpoint {
+    method = POST,
+    path = "/shippo/tracking/update",
+}]
+async fn listen_shippo_tracking_update_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: QueryToken<ShippoToken>,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut body = body_param.into_inner();
+    transform_webhook(&rqctx, "shippo", &mut body)?;
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_shippo_tracking_update(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/// A Shippo tracking update event.
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct ShippoTrackingUpdateEvent {
+    #[serde(default)]
+    pub data: shippo::TrackingStatus,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event: String,
+    #[serde(default)]
+    pub test: bool,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct RevAICallbackQuery {
+    /// The secret we gave the job when we submitted it.
+    #[serde(default)]
+    pub secret: String,
+}
+
+/**
+ * Listen for Rev.ai telling us a transcription job is done. Every job we submit gets its own
+ * secret in its callback url, so this only accepts callbacks for the jobs we submitted.
+ */
+#[endpoint {
+    method = POST,
+    path = "/revai/jobs/update",
+}]
+async fn listen_revai_job_update_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    query_args: Query<RevAICallbackQuery>,
+    body_param: TypedBody<RawWebhook<cio_api::transcription::RevAIJobNotification>>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body: cio_api::transcription::RevAIJobNotification = decode_webhook(&rqctx, "revai", body_param.into_inner())?;
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    let secret = query_args.into_inner().secret;
+    match txn
+        .run(|| crate::handlers::handle_revai_job_update(rqctx, body, secret))
+        .await
+    {
+        Ok(true) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted("ok".to_string()))
+        }
+        Ok(false) => {
+            txn.finish(http::StatusCode::UNAUTHORIZED);
+            Err(HttpError::for_status(None, http::StatusCode::UNAUTHORIZED))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct MeetingEngagementReportRequest {
+    /// How many days back the report covers.
+    pub days: i64,
+    /// Only report on the meetings at least this many people joined, like all-hands and
+    /// trainings.
+    #[serde(default)]
+    pub min_participants: usize,
+}
+
+/**
+ * Listen for requests to generate the engagement report of meetings, from their Zoom
+ * participant reports.
+ */
+#[endpoint {
+    method = POST,
+    path = "/recorded-meetings/engagement-report",
+}]
+async fn listen_recorded_meetings_engagement_report_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<MeetingEngagementReportRequest>,
+) -> Result<HttpResponseOk<Vec<cio_api::recorded_meetings::MeetingEngagement>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    match txn
+        .run(|| crate::handlers::handle_recorded_meetings_engagement_report(rqctx, body))
+        .await
+    {
+        Ok(report) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(report))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct RecordedMeetingUploadRequest {
+    /// The name of the file in Google Drive, like `offsite-roadmap-review.mp4`.
+    pub file_name: String,
+    /// The type of the file, `video/mp4` if empty.
+    #[serde(default)]
+    pub content_type: String,
+    /// The contents of the recording, encoded as base64.
+    pub contents: String,
+    #[serde(flatten)]
+    pub meeting: cio_api::recorded_meetings::LocalRecordingMetadata,
+}
+
+/**
+ * Listen for uploads of meetings recorded outside of Zoom, Meet, or Teams, like with a camera
+ * at an offsite. The recording is stored in Google Drive and transcribed like the others.
+ */
+#[endpoint {
+    method = POST,
+    path = "/recorded-meetings/upload",
+}]
+async fn listen_recorded_meeting_upload_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<RecordedMeetingUploadRequest>,
+) -> Result<HttpResponseOk<cio_api::recorded_meetings::RecordedMeeting>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers::handle_recorded_meeting_upload(rqctx, body))
+        .await
+    {
+        Ok(meeting) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(meeting))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct RecordedMeetingPathParams {
+    pub id: i32,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+/**
+ * Play the recording of a meeting, for the internal tools embedding it. Players get the video
+ * with ranged requests, so the Drive file does not have to be shared with everyone watching.
+ * The recording is only sent to the people signed in who can see it.
+ */
+#[endpoint {
+    method = GET,
+    path = "/meetings/{id}/video",
+}]
+async fn listen_recorded_meeting_video_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    identity: UserIdentity,
+    path_params: Path<RecordedMeetingPathParams>,
+) -> Result<Response<Body>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    let id = path_params.into_inner().id;
+    let range = rqctx
+        .request
+        .lock()
+        .await
+        .headers()
+        .get(http::header::RANGE)
+        .and_then(|r| r.to_str().ok())
+        .map(|r| r.to_string());
+    match txn
+        .run(|| crate::handlers::handle_recorded_meeting_video(&rqctx.context().app, id, identity, range))
+        .await
+    {
+        Ok(Some(video)) => {
+            let status = http::StatusCode::from_u16(video.status).unwrap_or(http::StatusCode::OK);
+            txn.finish(status);
+
+            let mut resp = Response::builder()
+                .status(status)
+                .header(http::header::CONTENT_TYPE, video.content_type)
+                .header(http::header::ACCEPT_RANGES, "bytes")
+                .header(http::header::CACHE_CONTROL, "private, no-store");
+            if !video.content_range.is_empty() {
+                resp = resp.header(http::header::CONTENT_RANGE, video.content_range);
+            }
+
+            resp.body(Body::from(video.body))
+                .map_err(|e| HttpError::for_internal_error(e.to_string()))
+        }
+        Ok(None) => {
+            txn.finish(http::StatusCode::FORBIDDEN);
+            Err(HttpError::for_status(None, http::StatusCode::FORBIDDEN))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/**
+ * Export the transcript of a meeting to a Google Doc in the Drive folder of the meeting, and
+ * return the link to the Doc. The transcript is only exported for the people signed in who can
+ * see it.
+ */
+#[endpoint {
+    method = POST,
+    path = "/meetings/{id}/transcript-doc",
+}]
+async fn listen_recorded_meeting_transcript_doc_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    identity: UserIdentity,
+    path_params: Path<RecordedMeetingPathParams>,
+) -> Result<HttpResponseOk<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    let id = path_params.into_inner().id;
+    match txn
+        .run(|| crate::handlers::handle_recorded_meeting_transcript_doc(&rqctx.context().app, id, identity))
+        .await
+    {
+        Ok(Some(link)) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(link))
+        }
+        Ok(None) => {
+            txn.finish(http::StatusCode::FORBIDDEN);
+            Err(HttpError::for_status(None, http::StatusCode::FORBIDDEN))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
+pub struct MeetingAttendanceReportRequest {
+    /// How many days back the report covers.
+    pub days: i64,
+}
+
+/**
+ * Listen for requests to generate the attendance report of recurring meetings, least attended
+ * first.
+ */
+#[endpoint {
+    method = POST,
+    path = "/recorded-meetings/attendance-report",
+}]
+async fn listen_recorded_meetings_attendance_report_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    body_param: TypedBody<MeetingAttendanceReportRequest>,
+) -> Result<HttpResponseOk<Vec<cio_api::recorded_meetings::RecurringMeetingAttendance>>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    match txn
+        .run(|| crate::handlers::handle_recorded_meetings_attendance_report(rqctx, body))
+        .await
+    {
+        Ok(report) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(report))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Listen for updates to our checkr background checks. */
+#[endpoint {
+    method = POST,
+    path = "/checkr/background/update",
+}]
+async fn listen_checkr_background_update_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body: HmacVerifiedBodyAudit<crate::handlers_checkr::CheckrWebhookVerification, RawWebhook<checkr::WebhookEvent>>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let webhook: checkr::WebhookEvent = decode_webhook(&rqctx, "checkr", body.into_inner()?)?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_checkr_background_update(rqctx, webhook))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct UserConsentURL {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
+pub struct AuthCallback {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub code: String,
+    /// The state that we had passed in through the user consent URL.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub state: String,
+    #[serde(default, skip_serializing_if = "String::is_empty", rename = "realmId")]
+    pub realm_id: String,
+}
+
+/** Get the consent URL for Google auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/google/consent",
+}]
+async fn listen_auth_google_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the Google client.
+    // You can use any of the libs here, they all use the same endpoint
+    // for tokens and we will send all the scopes.
+    let g = txn.run(|| GoogleDrive::new_from_env("", "")).await;
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: g.user_consent_url(&cio_api::companies::get_google_scopes()),
+    }))
+}
+
+/** Listen for callbacks to Google auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/google/callback",
+}]
+async fn listen_auth_google_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_google_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for GitHub auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/github/consent",
+}]
+async fn listen_auth_github_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: "https://github.com/apps/oxidecomputerbot/installations/new".to_string(),
+    }))
+}
+
+/** Listen for callbacks to GitHub auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/github/callback",
+}]
+async fn listen_auth_github_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    txn.run(|| {
+        warn!("github callback: {:?}", body);
+    });
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for Gusto auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/gusto/consent",
+}]
+async fn listen_auth_gusto_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the Gusto client.
+    let g = txn.run(|| Gusto::new_from_env("", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        // We don't need to define scopes for Gusto.
+        url: g.user_consent_url(&[]),
+    }))
+}
+
+/** Listen for callbacks to Gusto auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/gusto/callback",
+}]
+async fn listen_auth_gusto_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_gusto_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Listen to deauthorization requests for our Zoom app. */
+#[endpoint {
+    method = GET,
+    path = "/auth/zoom/deauthorization",
+}]
+async fn listen_auth_zoom_deauthorization(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    txn.run(|| {
+        warn!("zoom deauthorization: {:?}", body);
+    });
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for Zoom auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/zoom/consent",
+}]
+async fn listen_auth_zoom_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the Zoom client.
+    let g = txn.run(|| Zoom::new_from_env("", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: g.user_consent_url(&[]),
+    }))
+}
+
+/** Listen for callbacks to Zoom auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/zoom/callback",
+}]
+async fn listen_auth_zoom_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_zoom_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for Slack auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/slack/consent",
+}]
+async fn listen_auth_slack_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the Slack client.
+    let s = txn.run(|| Slack::new_from_env("", "", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: s.user_consent_url(),
+    }))
+}
+
+/** Listen for callbacks to Slack auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/slack/callback",
+}]
+async fn listen_auth_slack_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_slack_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for QuickBooks auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/quickbooks/consent",
+}]
+async fn listen_auth_quickbooks_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the QuickBooks client.
+    let g = txn.run(|| QuickBooks::new_from_env("", "", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: g.user_consent_url(),
+    }))
+}
+
+/** Listen for callbacks to QuickBooks auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/quickbooks/callback",
+}]
+async fn listen_auth_quickbooks_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_quickbooks_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Listen for webhooks from Plaid. */
+#[endpoint {
+    method = POST,
+    path = "/plaid",
+}]
+async fn listen_auth_plaid_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    txn.run(|| {
+        warn!("plaid callback: {:?}", body);
+    });
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Get the consent URL for DocuSign auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/docusign/consent",
+}]
+async fn listen_auth_docusign_consent(
+    rqctx: Arc<RequestContext<ServerContext>>,
+) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    // Initialize the DocuSign client.
+    let g = txn.run(|| DocuSign::new_from_env("", "", "", ""));
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk(UserConsentURL {
+        url: g.user_consent_url(),
+    }))
+}
+
+/** Listen for callbacks to DocuSign auth. */
+#[endpoint {
+    method = GET,
+    path = "/auth/docusign/callback",
+}]
+async fn listen_auth_docusign_callback(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    query_args: Query<AuthCallback>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers_auth::handle_auth_docusign_callback(rqctx, query_args))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Listen for updates to our docusign envelopes. */
+#[endpoint {
+    method = POST,
+    path = "/docusign/envelope/update",
+}]
+async fn listen_docusign_envelope_update_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body: HmacVerifiedBody<crate::handlers_docusign::DocusignWebhookVerification, RawWebhook<docusign::Envelope>>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let webhook: docusign::Envelope = decode_webhook(&rqctx, "docusign", body.into_inner()?)?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_docusign_envelope_update(rqctx, webhook))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Listen for Zoom webhooks. */
+#[endpoint {
+    method = POST,
+    path = "/zoom/webhooks",
+}]
+async fn listen_zoom_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body: HmacVerifiedBody<crate::handlers_zoom::ZoomWebhookVerification, RawWebhook<ZoomWebhookEvent>>,
+) -> Result<HttpResponseOk<serde_json::Value>, HttpError> {
+    let event: ZoomWebhookEvent = decode_webhook(&rqctx, "zoom", body.into_inner()?)?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&event)).await;
+
+    match txn
+        .run(|| crate::handlers_zoom::handle_zoom_webhook(&rqctx.context().app, event))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Listen for analytics page view events. */
+#[endpoint {
+    method = POST,
+    path = "/analytics/page_view",
+}]
+async fn listen_analytics_page_view_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body_param: TypedBody<NewPageView>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_analytics_page_view(rqctx, body))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::ACCEPTED);
+
+    Ok(HttpResponseAccepted("ok".to_string()))
+}
+
+/** Listen for Slack commands webhooks. */
+#[endpoint {
+    method = POST,
+    path = "/slack/commands",
+    content_type = "application/x-www-form-urlencoded"
+}]
+async fn listen_slack_commands_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body: HmacVerifiedBodyAudit<crate::handlers_slack::SlackWebhookVerification, BotCommand>,
+) -> Result<HttpResponseOk<serde_json::Value>, HttpError> {
+    let command = body.into_inner()?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&command)).await;
+
+    match txn.run(|| crate::handlers::handle_slack_commands(rqctx, command)).await {
+        Ok(r) => {
+            txn.finish(http::StatusCode::OK);
+
+            Ok(HttpResponseOk(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Listen for Slack interactive webhooks. */
+#[endpoint {
+    method = POST,
+    path = "/slack/interactive",
+    content_type = "application/x-www-form-urlencoded"
+}]
+async fn listen_slack_interactive_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    body: HmacVerifiedBodyAudit<crate::handlers_slack::SlackWebhookVerification, InteractiveEvent>,
+) -> Result<HttpResponseOk<String>, HttpError> {
+    let event = body.into_inner()?;
+
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&event.payload)).await;
+
+    if let Err(e) = txn
+        .run(|| crate::handlers::handle_slack_interactive(rqctx, event.payload))
+        .await
+    {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk("ok".to_string()))
+}
+
+/** Listen for shipbob webhooks. */
+#[endpoint {
+    method = POST,
+    path = "/shipbob",
+}]
+async fn listen_shipbob_webhooks(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: QueryTokenAudit<InternalToken>,
+    body_param: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseOk<String>, HttpError> {
+    let mut body = body_param.into_inner();
+    transform_webhook(&rqctx, "shipbob", &mut body)?;
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    if let Err(e) = txn.run(|| crate::handlers::handle_shipbob(rqctx, body)).await {
+        // Send the error to sentry.
+        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+        return Err(handle_anyhow_err_as_http_err(e));
+    }
+
+    txn.finish(http::StatusCode::OK);
+
+    Ok(HttpResponseOk("ok".to_string()))
+}
+
+// The RFD index does not support any sorting mechanisms
+#[derive(Debug, Deserialize, JsonSchema)]
+struct RFDIndexScanParam {}
+
+// The RFD index is always sorted by number in ascending order
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+enum RFDIndexPageSelector {
+    Number(PaginationOrder, i32),
+}
+
+fn rfd_index_scan_params(_params: &WhichPage<RFDIndexScanParam, RFDIndexPageSelector>) -> RFDIndexScanParam {
+    RFDIndexScanParam {}
+}
+
+fn rfd_index_page_selector(item: &RFDIndexEntry, _scan_params: &RFDIndexScanParam) -> RFDIndexPageSelector {
+    RFDIndexPageSelector::Number(PaginationOrder::Ascending, item.number)
+}
+
+/// List metadata of all RFDs
+#[endpoint {
+    method = GET,
+    path = "/rfds",
+}]
+async fn listen_rfd_index(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<RFDToken>,
+    query: Query<PaginationParams<RFDIndexScanParam, RFDIndexPageSelector>>,
+) -> Result<HttpResponseOk<ResultsPage<RFDIndexEntry>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    let params = query.into_inner();
+    let offset = match params.page {
+        WhichPage::First(_) => 0,
+        WhichPage::Next(RFDIndexPageSelector::Number(_dir, offset)) => offset,
+    };
+    let limit = rqctx.page_limit(&params)?.get();
+    let scan_params = rfd_index_scan_params(&params.page);
+
+    match txn
+        .run(|| crate::handlers_rfd::handle_rfd_index(&rqctx.context().app, offset, limit))
+        .await
+    {
+        Ok(entries) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(ResultsPage::new(
+                entries,
+                &scan_params,
+                rfd_index_page_selector,
+            )?))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct RFDImpactPathParams {
+    pub owner: String,
+    pub repo: String,
+}
+
+/// List the RFDs that affect a repository
+#[endpoint {
+    method = GET,
+    path = "/rfds/impacts/{owner}/{repo}",
+}]
+async fn listen_rfd_impacts(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<RFDToken>,
+    path_params: Path<RFDImpactPathParams>,
+) -> Result<HttpResponseOk<Vec<crate::handlers_rfd::RFDImpactEntry>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+    let path_params = path_params.into_inner();
+
+    match txn
+        .run(|| crate::handlers_rfd::handle_rfd_impacts(&rqctx.context().app, &path_params.owner, &path_params.repo))
+        .await
+    {
+        Ok(entries) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(entries))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct RFDSearchParams {
+    /// The search, like `"disk encryption" -tpm`.
+    pub q: String,
+    /// How many RFDs to return, at most 50.
+    pub limit: Option<i64>,
+}
+
+/// Search the titles and content of the RFDs, best match first
+#[endpoint {
+    method = GET,
+    path = "/rfds/search",
+}]
+async fn listen_rfd_search(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<RFDToken>,
+    query: Query<RFDSearchParams>,
+) -> Result<HttpResponseOk<Vec<cio_api::rfd::RFDSearchResult>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+    let query = query.into_inner();
+
+    match txn
+        .run(|| crate::handlers_rfd::handle_rfd_search(&rqctx.context().app, &query.q, query.limit))
+        .await
+    {
+        Ok(results) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(results))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/// Get how the RFDs reference each other, with the RFDs no other RFD references
+#[endpoint {
+    method = GET,
+    path = "/rfds/graph",
+}]
+async fn listen_rfd_graph(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<RFDToken>,
+) -> Result<HttpResponseOk<cio_api::rfd::RFDGraph>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers_rfd::handle_rfd_graph(&rqctx.context().app))
+        .await
+    {
+        Ok(graph) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(graph))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/// Get how long the RFDs spend in each state, how many are in each and the stale discussions
+#[endpoint {
+    method = GET,
+    path = "/rfds/metrics",
+}]
+async fn listen_rfd_metrics(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<RFDToken>,
+) -> Result<HttpResponseOk<cio_api::rfd::RFDLifecycleMetrics>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers_rfd::handle_rfd_metrics(&rqctx.context().app))
+        .await
+    {
+        Ok(metrics) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(metrics))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct RFDNewRequest {
+    pub title: String,
+    /// The authors line of the front matter, like `Jane Doe <jane@example.com>`.
+    pub authors: String,
+    /// The number the caller reserved for the RFD, the next free one is used if there is none.
+    #[serde(default)]
+    pub number: Option<i32>,
+}
+
+/// Create a new RFD with the next free number, on its own branch with the template front matter
+#[endpoint {
+    method = POST,
+    path = "/rfds/new",
+}]
+async fn listen_rfd_new_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    user: CompanyUser,
+    body_param: TypedBody<RFDNewRequest>,
+) -> Result<HttpResponseOk<cio_api::rfd::ScaffoldedRFD>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    match txn
+        .run(|| {
+            crate::handlers_rfd::handle_rfd_new(
+                &rqctx.context().app,
+                &body.title,
+                &body.authors,
+                &user.email,
+                body.number,
+            )
+        })
+        .await
+    {
+        Ok(rfd) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(rfd))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema)]
+pub struct RFDReserveRequest {
+    pub title: String,
+    /// How long to hold the number for, the configured time if there is none.
+    #[serde(default)]
+    pub ttl_minutes: Option<i64>,
+}
+
+/// Reserve the next free RFD number for the caller, it is held until they use it to create the RFD or it expires
+#[endpoint {
+    method = POST,
+    path = "/rfds/reserve",
+}]
+async fn listen_rfd_reserve_requests(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    user: CompanyUser,
+    body_param: TypedBody<RFDReserveRequest>,
+) -> Result<HttpResponseOk<cio_api::rfd::RFDNumberReservation>, HttpError> {
+    let body = body_param.into_inner();
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+
+    match txn
+        .run(|| {
+            crate::handlers_rfd::handle_rfd_reserve(&rqctx.context().app, &body.title, &user.email, body.ttl_minutes)
+        })
+        .await
+    {
+        Ok(reservation) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(reservation))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/// Get an rfd
+#[endpoint {
+    method = GET,
+    path = "/rfd/{num}",
+}]
+async fn listen_rfd_view(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<RFDToken>,
+    path_params: Path<RFDPathParams>,
+) -> Result<HttpResponseOk<RFDEntry>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers_rfd::handle_rfd_view(&rqctx.context().app, path_params.into_inner().num))
+        .await
+    {
+        Ok(Some(rfd)) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(rfd))
+        }
+        Ok(None) => {
+            txn.finish(http::StatusCode::NOT_FOUND);
+            Err(HttpError::for_not_found(None, "".to_string()))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/// List the metadata of all the RFDs, for the RFD site
+#[endpoint {
+    method = GET,
+    path = "/api/rfds",
+}]
+async fn listen_api_rfds(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<RFDToken>,
+) -> Result<HttpResponseOk<crate::handlers_rfd::RFDApiIndex>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers_rfd::handle_api_rfds(&rqctx.context().app))
+        .await
+    {
+        Ok(index) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(index))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct RFDNumberPathParams {
+    pub number: i32,
+}
+
+/// Get the metadata and rendered HTML of an RFD, for the RFD site
+#[endpoint {
+    method = GET,
+    path = "/api/rfds/{number}",
+}]
+async fn listen_api_rfd(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<RFDToken>,
+    path_params: Path<RFDNumberPathParams>,
+) -> Result<HttpResponseOk<crate::handlers_rfd::RFDApiEntry>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+
+    match txn
+        .run(|| crate::handlers_rfd::handle_api_rfd(&rqctx.context().app, path_params.into_inner().number))
+        .await
+    {
+        Ok(Some(rfd)) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(rfd))
+        }
+        Ok(None) => {
+            txn.finish(http::StatusCode::NOT_FOUND);
+            Err(HttpError::for_not_found(None, "".to_string()))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/** Listen for triggering a function run of sync repos. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-repos",
+}]
+async fn trigger_sync_repos_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-repos"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
+}
+
+/** Listen for triggering a function run of sync RFDs. */
+#[endpoint {
+    method = POST,
+    path = "/run/sync-rfds",
+}]
+async fn trigger_sync_rfds_create(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
+
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-rfds"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
 }
 
-/** Get the consent URL for DocuSign auth. */
+/** Listen for triggering a function run of sync travel. */
 #[endpoint {
-    method = GET,
-    path = "/auth/docusign/consent",
+    method = POST,
+    path = "/run/sync-travel",
 }]
-async fn listen_auth_docusign_consent(
+async fn trigger_sync_travel_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-) -> Result<HttpResponseOk<UserConsentURL>, HttpError> {
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    // Initialize the DocuSign client.
-    let g = txn.run(|| DocuSign::new_from_env("", "", "", ""));
-
-    txn.finish(http::StatusCode::OK);
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-travel"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
 
-    Ok(HttpResponseOk(UserConsentURL {
-        url: g.user_consent_url(),
-    }))
+            Ok(HttpResponseAccepted(r))
+        }
+        // Send the error to sentry.
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
+    }
 }
 
-/** Listen for callbacks to DocuSign auth. */
+/** Listen for triggering a function run of sync zoho. */
 #[endpoint {
-    method = GET,
-    path = "/auth/docusign/callback",
+    method = POST,
+    path = "/run/sync-zoho",
 }]
-async fn listen_auth_docusign_callback(
+async fn trigger_sync_zoho_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    query_args: Query<AuthCallback>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers_auth::handle_auth_docusign_callback(rqctx, query_args))
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-zoho"))
         .await
     {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Listen for updates to our docusign envelopes. */
+/** Listen for triggering a function run of sync functions. */
 #[endpoint {
     method = POST,
-    path = "/docusign/envelope/update",
+    path = "/run/sync-functions",
 }]
-async fn listen_docusign_envelope_update_webhooks(
+async fn trigger_sync_functions_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body: HmacVerifiedBody<crate::handlers_docusign::DocusignWebhookVerification, docusign::Envelope>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let webhook = body.into_inner()?;
-
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&webhook)).await;
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_docusign_envelope_update(rqctx, webhook))
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-functions"))
         .await
     {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Listen for analytics page view events. */
+/** Listen for triggering a function run of sync finance. */
 #[endpoint {
     method = POST,
-    path = "/analytics/page_view",
+    path = "/run/sync-finance",
 }]
-async fn listen_analytics_page_view_webhooks(
+async fn trigger_sync_finance_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body_param: TypedBody<NewPageView>,
-) -> Result<HttpResponseAccepted<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_analytics_page_view(rqctx, body))
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-finance"))
         .await
     {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::ACCEPTED);
-
-    Ok(HttpResponseAccepted("ok".to_string()))
 }
 
-/** Listen for Slack commands webhooks. */
+/** Listen for triggering a function run of sync shipments. */
 #[endpoint {
     method = POST,
-    path = "/slack/commands",
-    content_type = "application/x-www-form-urlencoded"
+    path = "/run/sync-shipments",
 }]
-async fn listen_slack_commands_webhooks(
+async fn trigger_sync_shipments_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body: HmacVerifiedBodyAudit<crate::handlers_slack::SlackWebhookVerification, BotCommand>,
-) -> Result<HttpResponseOk<serde_json::Value>, HttpError> {
-    let command = body.into_inner()?;
-
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&command)).await;
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    match txn.run(|| crate::handlers::handle_slack_commands(rqctx, command)).await {
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-shipments"))
+        .await
+    {
         Ok(r) => {
-            txn.finish(http::StatusCode::OK);
+            txn.finish(http::StatusCode::ACCEPTED);
 
-            Ok(HttpResponseOk(r))
+            Ok(HttpResponseAccepted(r))
         }
         // Send the error to sentry.
         Err(e) => {
@@ -1943,161 +4258,131 @@ async fn listen_slack_commands_webhooks(
     }
 }
 
-/** Listen for Slack interactive webhooks. */
+/** Listen for triggering a function run of sync shorturls. */
 #[endpoint {
     method = POST,
-    path = "/slack/interactive",
-    content_type = "application/x-www-form-urlencoded"
+    path = "/run/sync-shorturls",
 }]
-async fn listen_slack_interactive_webhooks(
+async fn trigger_sync_shorturls_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    body: HmacVerifiedBodyAudit<crate::handlers_slack::SlackWebhookVerification, InteractiveEvent>,
-) -> Result<HttpResponseOk<String>, HttpError> {
-    let event = body.into_inner()?;
-
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&event.payload)).await;
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn
-        .run(|| crate::handlers::handle_slack_interactive(rqctx, event.payload))
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-shorturls"))
         .await
     {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::OK);
-
-    Ok(HttpResponseOk("ok".to_string()))
 }
 
-/** Listen for shipbob webhooks. */
+/** Listen for triggering a function run of sync configs. */
 #[endpoint {
     method = POST,
-    path = "/shipbob",
+    path = "/run/sync-configs",
 }]
-async fn listen_shipbob_webhooks(
+async fn trigger_sync_configs_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: QueryTokenAudit<InternalToken>,
-    body_param: TypedBody<serde_json::Value>,
-) -> Result<HttpResponseOk<String>, HttpError> {
-    let body = body_param.into_inner();
-    let mut txn = start_sentry_http_transaction(rqctx.clone(), Some(&body)).await;
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
-    if let Err(e) = txn.run(|| crate::handlers::handle_shipbob(rqctx, body)).await {
+    match txn
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-configs"))
+        .await
+    {
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
+        }
         // Send the error to sentry.
-        txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-        return Err(handle_anyhow_err_as_http_err(e));
+        Err(e) => {
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(e))
+        }
     }
-
-    txn.finish(http::StatusCode::OK);
-
-    Ok(HttpResponseOk("ok".to_string()))
-}
-
-// The RFD index does not support any sorting mechanisms
-#[derive(Debug, Deserialize, JsonSchema)]
-struct RFDIndexScanParam {}
-
-// The RFD index is always sorted by number in ascending order
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
-enum RFDIndexPageSelector {
-    Number(PaginationOrder, i32),
-}
-
-fn rfd_index_scan_params(_params: &WhichPage<RFDIndexScanParam, RFDIndexPageSelector>) -> RFDIndexScanParam {
-    RFDIndexScanParam {}
 }
 
-fn rfd_index_page_selector(item: &RFDIndexEntry, _scan_params: &RFDIndexScanParam) -> RFDIndexPageSelector {
-    RFDIndexPageSelector::Number(PaginationOrder::Ascending, item.number)
-}
-
-/// List metadata of all RFDs
+/** Listen for triggering a function run of sync recorded meetings. */
 #[endpoint {
-    method = GET,
-    path = "/rfds",
+    method = POST,
+    path = "/run/sync-recorded-meetings",
 }]
-async fn listen_rfd_index(
+async fn trigger_sync_recorded_meetings_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<RFDToken>,
-    query: Query<PaginationParams<RFDIndexScanParam, RFDIndexPageSelector>>,
-) -> Result<HttpResponseOk<ResultsPage<RFDIndexEntry>>, HttpError> {
-    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
-
-    let params = query.into_inner();
-    let offset = match params.page {
-        WhichPage::First(_) => 0,
-        WhichPage::Next(RFDIndexPageSelector::Number(_dir, offset)) => offset,
-    };
-    let limit = rqctx.page_limit(&params)?.get();
-    let scan_params = rfd_index_scan_params(&params.page);
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_rfd::handle_rfd_index(&rqctx.context().app, offset, limit))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-recorded-meetings"))
         .await
     {
-        Ok(entries) => {
-            txn.finish(http::StatusCode::OK);
-            Ok(HttpResponseOk(ResultsPage::new(
-                entries,
-                &scan_params,
-                rfd_index_page_selector,
-            )?))
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
         }
-        Err(err) => {
-            // Send the error to sentry.
+        // Send the error to sentry.
+        Err(e) => {
             txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-            Err(handle_anyhow_err_as_http_err(err))
+            Err(handle_anyhow_err_as_http_err(e))
         }
     }
 }
 
-/// Get an rfd
+/** Listen for triggering a function run of sync asset inventory. */
 #[endpoint {
-    method = GET,
-    path = "/rfd/{num}",
+    method = POST,
+    path = "/run/sync-asset-inventory",
 }]
-async fn listen_rfd_view(
+async fn trigger_sync_asset_inventory_create(
     rqctx: Arc<RequestContext<ServerContext>>,
-    _auth: Bearer<RFDToken>,
-    path_params: Path<RFDPathParams>,
-) -> Result<HttpResponseOk<RFDEntry>, HttpError> {
-    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_rfd::handle_rfd_view(&rqctx.context().app, path_params.into_inner().num))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-asset-inventory"))
         .await
     {
-        Ok(Some(rfd)) => {
-            txn.finish(http::StatusCode::OK);
-            Ok(HttpResponseOk(rfd))
-        }
-        Ok(None) => {
-            txn.finish(http::StatusCode::NOT_FOUND);
-            Err(HttpError::for_not_found(None, "".to_string()))
+        Ok(r) => {
+            txn.finish(http::StatusCode::ACCEPTED);
+
+            Ok(HttpResponseAccepted(r))
         }
-        Err(err) => {
-            // Send the error to sentry.
+        // Send the error to sentry.
+        Err(e) => {
             txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
-            Err(handle_anyhow_err_as_http_err(err))
+            Err(handle_anyhow_err_as_http_err(e))
         }
     }
 }
 
-/** Listen for triggering a function run of sync repos. */
+/** Listen for triggering a function run of sync swag inventory. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-repos",
+    path = "/run/sync-swag-inventory",
 }]
-async fn trigger_sync_repos_create(
+async fn trigger_sync_swag_inventory_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-repos"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-swag-inventory"))
         .await
     {
         Ok(r) => {
@@ -2113,19 +4398,19 @@ async fn trigger_sync_repos_create(
     }
 }
 
-/** Listen for triggering a function run of sync RFDs. */
+/** Listen for triggering a function run of sync interviews. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-rfds",
+    path = "/run/sync-interviews",
 }]
-async fn trigger_sync_rfds_create(
+async fn trigger_sync_interviews_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-rfds"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-interviews"))
         .await
     {
         Ok(r) => {
@@ -2141,19 +4426,19 @@ async fn trigger_sync_rfds_create(
     }
 }
 
-/** Listen for triggering a function run of sync travel. */
+/** Listen for triggering a function run of sync applications. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-travel",
+    path = "/run/sync-applications",
 }]
-async fn trigger_sync_travel_create(
+async fn trigger_sync_applications_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-travel"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-applications"))
         .await
     {
         Ok(r) => {
@@ -2169,19 +4454,19 @@ async fn trigger_sync_travel_create(
     }
 }
 
-/** Listen for triggering a function run of sync zoho. */
+/** Listen for triggering a function run of sync analytics. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-zoho",
+    path = "/run/sync-analytics",
 }]
-async fn trigger_sync_zoho_create(
+async fn trigger_sync_analytics_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-zoho"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-analytics"))
         .await
     {
         Ok(r) => {
@@ -2197,19 +4482,19 @@ async fn trigger_sync_zoho_create(
     }
 }
 
-/** Listen for triggering a function run of sync functions. */
+/** Listen for triggering a function run of sync companies. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-functions",
+    path = "/run/sync-companies",
 }]
-async fn trigger_sync_functions_create(
+async fn trigger_sync_companies_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-functions"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-companies"))
         .await
     {
         Ok(r) => {
@@ -2225,19 +4510,19 @@ async fn trigger_sync_functions_create(
     }
 }
 
-/** Listen for triggering a function run of sync finance. */
+/** Listen for triggering a function run of sync other. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-finance",
+    path = "/run/sync-other",
 }]
-async fn trigger_sync_finance_create(
+async fn trigger_sync_other_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-finance"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-other"))
         .await
     {
         Ok(r) => {
@@ -2253,19 +4538,19 @@ async fn trigger_sync_finance_create(
     }
 }
 
-/** Listen for triggering a function run of sync shipments. */
+/** Listen for triggering a function run of sync huddles. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-shipments",
+    path = "/run/sync-huddles",
 }]
-async fn trigger_sync_shipments_create(
+async fn trigger_sync_huddles_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-shipments"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-huddles"))
         .await
     {
         Ok(r) => {
@@ -2281,19 +4566,19 @@ async fn trigger_sync_shipments_create(
     }
 }
 
-/** Listen for triggering a function run of sync shorturls. */
+/** Listen for triggering a function run of sync mailing lists. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-shorturls",
+    path = "/run/sync-mailing-lists",
 }]
-async fn trigger_sync_shorturls_create(
+async fn trigger_sync_mailing_lists_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-shorturls"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-mailing-lists"))
         .await
     {
         Ok(r) => {
@@ -2309,19 +4594,19 @@ async fn trigger_sync_shorturls_create(
     }
 }
 
-/** Listen for triggering a function run of sync configs. */
+/** Listen for triggering a function run of sync journal clubs. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-configs",
+    path = "/run/sync-journal-clubs",
 }]
-async fn trigger_sync_configs_create(
+async fn trigger_sync_journal_clubs_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-configs"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-journal-clubs"))
         .await
     {
         Ok(r) => {
@@ -2337,19 +4622,19 @@ async fn trigger_sync_configs_create(
     }
 }
 
-/** Listen for triggering a function run of sync recorded meetings. */
+/** Listen for triggering a function run of sync api tokens. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-recorded-meetings",
+    path = "/run/sync-api-tokens",
 }]
-async fn trigger_sync_recorded_meetings_create(
+async fn trigger_sync_api_tokens_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-recorded-meetings"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-api-tokens"))
         .await
     {
         Ok(r) => {
@@ -2365,19 +4650,19 @@ async fn trigger_sync_recorded_meetings_create(
     }
 }
 
-/** Listen for triggering a function run of sync asset inventory. */
+/** Listen for triggering a function run of sync contractors. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-asset-inventory",
+    path = "/run/sync-contractors",
 }]
-async fn trigger_sync_asset_inventory_create(
+async fn trigger_sync_contractors_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-asset-inventory"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-contractors"))
         .await
     {
         Ok(r) => {
@@ -2393,19 +4678,19 @@ async fn trigger_sync_asset_inventory_create(
     }
 }
 
-/** Listen for triggering a function run of sync swag inventory. */
+/** Listen for triggering a function run of sync focus time. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-swag-inventory",
+    path = "/run/sync-focus-time",
 }]
-async fn trigger_sync_swag_inventory_create(
+async fn trigger_sync_focus_time_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-swag-inventory"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-focus-time"))
         .await
     {
         Ok(r) => {
@@ -2421,19 +4706,19 @@ async fn trigger_sync_swag_inventory_create(
     }
 }
 
-/** Listen for triggering a function run of sync interviews. */
+/** Listen for triggering a function run of applying the recorded meeting retention policy. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-interviews",
+    path = "/run/sync-recorded-meeting-retention",
 }]
-async fn trigger_sync_interviews_create(
+async fn trigger_sync_recorded_meeting_retention_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-interviews"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-recorded-meeting-retention"))
         .await
     {
         Ok(r) => {
@@ -2449,19 +4734,19 @@ async fn trigger_sync_interviews_create(
     }
 }
 
-/** Listen for triggering a function run of sync applications. */
+/** Listen for triggering a function run of sync drive uploads. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-applications",
+    path = "/run/sync-drive-uploads",
 }]
-async fn trigger_sync_applications_create(
+async fn trigger_sync_drive_uploads_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-applications"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-drive-uploads"))
         .await
     {
         Ok(r) => {
@@ -2477,19 +4762,19 @@ async fn trigger_sync_applications_create(
     }
 }
 
-/** Listen for triggering a function run of sync analytics. */
+/** Listen for triggering a function run of first week schedules. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-analytics",
+    path = "/run/sync-first-week-schedules",
 }]
-async fn trigger_sync_analytics_create(
+async fn trigger_sync_first_week_schedules_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-analytics"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-first-week-schedules"))
         .await
     {
         Ok(r) => {
@@ -2505,19 +4790,19 @@ async fn trigger_sync_analytics_create(
     }
 }
 
-/** Listen for triggering a function run of sync companies. */
+/** Listen for triggering a function run of Sync asset loaners. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-companies",
+    path = "/run/sync-asset-loaners",
 }]
-async fn trigger_sync_companies_create(
+async fn trigger_sync_asset_loaners_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-companies"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-asset-loaners"))
         .await
     {
         Ok(r) => {
@@ -2533,19 +4818,19 @@ async fn trigger_sync_companies_create(
     }
 }
 
-/** Listen for triggering a function run of sync other. */
+/** Listen for triggering a function run of Sync credentials inventory. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-other",
+    path = "/run/sync-credentials-inventory",
 }]
-async fn trigger_sync_other_create(
+async fn trigger_sync_credentials_inventory_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-other"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-credentials-inventory"))
         .await
     {
         Ok(r) => {
@@ -2561,19 +4846,19 @@ async fn trigger_sync_other_create(
     }
 }
 
-/** Listen for triggering a function run of sync huddles. */
+/** Listen for triggering a function run of sync asset depreciation. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-huddles",
+    path = "/run/sync-asset-depreciation",
 }]
-async fn trigger_sync_huddles_create(
+async fn trigger_sync_asset_depreciation_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-huddles"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-asset-depreciation"))
         .await
     {
         Ok(r) => {
@@ -2589,19 +4874,19 @@ async fn trigger_sync_huddles_create(
     }
 }
 
-/** Listen for triggering a function run of sync mailing lists. */
+/** Listen for triggering a function run of sync asset maintenance. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-mailing-lists",
+    path = "/run/sync-asset-maintenance",
 }]
-async fn trigger_sync_mailing_lists_create(
+async fn trigger_sync_asset_maintenance_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-mailing-lists"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-asset-maintenance"))
         .await
     {
         Ok(r) => {
@@ -2617,19 +4902,19 @@ async fn trigger_sync_mailing_lists_create(
     }
 }
 
-/** Listen for triggering a function run of sync journal clubs. */
+/** Listen for triggering a function run of sync Shopify orders. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-journal-clubs",
+    path = "/run/sync-shopify",
 }]
-async fn trigger_sync_journal_clubs_create(
+async fn trigger_sync_shopify_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-journal-clubs"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-shopify"))
         .await
     {
         Ok(r) => {
@@ -2645,19 +4930,19 @@ async fn trigger_sync_journal_clubs_create(
     }
 }
 
-/** Listen for triggering a function run of sync api tokens. */
+/** Listen for triggering a function run of Sync sign-in audit logs. */
 #[endpoint {
     method = POST,
-    path = "/run/sync-api-tokens",
+    path = "/run/sync-sign-in-audit",
 }]
-async fn trigger_sync_api_tokens_create(
+async fn trigger_sync_sign_in_audit_create(
     rqctx: Arc<RequestContext<ServerContext>>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
     let mut txn = start_sentry_http_transaction(rqctx.clone(), None::<()>).await;
 
     match txn
-        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-api-tokens"))
+        .run(|| crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-sign-in-audit"))
         .await
     {
         Ok(r) => {
@@ -2704,6 +4989,120 @@ pub struct FunctionPathParams {
     pub uuid: String,
 }
 
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct MetricsQuery {
+    /// How many weeks of snapshots to return, including the current one.
+    pub weeks: Option<i64>,
+}
+
+/** Get the weekly snapshots of a company's key operational numbers, oldest first. */
+#[endpoint {
+    method = GET,
+    path = "/metrics",
+}]
+async fn listen_metrics(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    query_args: Query<MetricsQuery>,
+) -> Result<HttpResponseOk<Vec<cio_api::metrics::MetricsSnapshot>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+    let query = query_args.into_inner();
+
+    match txn
+        .run(|| {
+            cio_api::metrics::list_metrics_snapshots(
+                &rqctx.context().app.db,
+                rqctx.context().app.company.id,
+                query.weeks.unwrap_or(12),
+            )
+        })
+        .await
+    {
+        Ok(snapshots) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(snapshots))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct SagaListQuery {
+    /// How many sagas to list, the most recent first.
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct SagaPathParams {
+    pub id: String,
+}
+
+/** List the most recent saga executions. */
+#[endpoint {
+    method = GET,
+    path = "/admin/sagas",
+}]
+async fn listen_admin_sagas(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    query_args: Query<SagaListQuery>,
+) -> Result<HttpResponseOk<Vec<cio_api::saga_steps::SagaSummary>>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+    let limit = query_args.into_inner().limit.unwrap_or(50);
+
+    match txn
+        .run(|| cio_api::saga_steps::list_recent_sagas(&rqctx.context().app.db, limit))
+        .await
+    {
+        Ok(sagas) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(sagas))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
+/** Get a saga execution with the status, timing, and errors of each of its steps. */
+#[endpoint {
+    method = GET,
+    path = "/admin/sagas/{id}",
+}]
+async fn listen_admin_saga_view(
+    rqctx: Arc<RequestContext<ServerContext>>,
+    _auth: Bearer<InternalToken>,
+    path_params: Path<SagaPathParams>,
+) -> Result<HttpResponseOk<cio_api::saga_steps::SagaExecution>, HttpError> {
+    let mut txn = start_sentry_http_transaction::<()>(rqctx.clone(), None).await;
+    let id = path_params.into_inner().id;
+
+    match txn
+        .run(|| cio_api::saga_steps::get_saga_execution(&rqctx.context().app.db, &id))
+        .await
+    {
+        Ok(Some(saga)) => {
+            txn.finish(http::StatusCode::OK);
+            Ok(HttpResponseOk(saga))
+        }
+        Ok(None) => {
+            txn.finish(http::StatusCode::NOT_FOUND);
+            Err(HttpError::for_not_found(None, "".to_string()))
+        }
+        Err(err) => {
+            // Send the error to sentry.
+            txn.finish(http::StatusCode::INTERNAL_SERVER_ERROR);
+            Err(handle_anyhow_err_as_http_err(err))
+        }
+    }
+}
+
 async fn do_cleanup(ctx: &ServerContext) -> Result<()> {
     let sec = &ctx.sec;
     // Get all our sagas.
@@ -15,6 +15,16 @@ pub enum SlackCommand {
     Paper,
 
     Shipments,
+
+    Skills,
+
+    WhoKnows,
+
+    Loaner,
+
+    Asset,
+
+    RFD,
 }
 
 impl SlackCommand {
@@ -27,6 +37,11 @@ impl SlackCommand {
             SlackCommand::Papers => "/papers",
             SlackCommand::Paper => "/paper",
             SlackCommand::Shipments => "/shipments",
+            SlackCommand::Skills => "/skills",
+            SlackCommand::WhoKnows => "/who-knows",
+            SlackCommand::Loaner => "/loaner",
+            SlackCommand::Asset => "/asset",
+            SlackCommand::RFD => "/rfd",
         }
     }
 }
@@ -42,6 +57,11 @@ impl FromStr for SlackCommand {
             "/papers" => Ok(SlackCommand::Papers),
             "/paper" => Ok(SlackCommand::Paper),
             "/shipments" => Ok(SlackCommand::Shipments),
+            "/skills" => Ok(SlackCommand::Skills),
+            "/who-knows" => Ok(SlackCommand::WhoKnows),
+            "/loaner" => Ok(SlackCommand::Loaner),
+            "/asset" => Ok(SlackCommand::Asset),
+            "/rfd" => Ok(SlackCommand::RFD),
             _ => Err(format!("invalid Slack command: `{}`", s)),
         }
     }
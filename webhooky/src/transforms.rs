@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use cio_api::webhook_transforms::apply_webhook_transforms;
+use dropshot::{HttpError, RequestContext};
+use log::warn;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+use crate::context::ServerContext;
+
+/// Apply the transforms we configured for a provider to the payload of one of its webhooks.
+pub fn transform_webhook(
+    rqctx: &RequestContext<ServerContext>,
+    provider: &str,
+    payload: &mut serde_json::Value,
+) -> Result<(), HttpError> {
+    let app_config = rqctx.context().app.app_config.read().unwrap();
+    let transforms = match app_config.webhook_transforms.get(provider) {
+        Some(transforms) => transforms,
+        None => return Ok(()),
+    };
+
+    apply_webhook_transforms(transforms, payload).map_err(|e| {
+        warn!("transforming the payload of a {} webhook failed: {}", provider, e);
+        HttpError::for_internal_error(format!("Failed to transform body: {}", e))
+    })
+}
+
+/// The payload of a webhook as it was sent, before we decode it into a `T`. It is documented as
+/// a `T` in the API spec, since that is still what the provider sends us.
+#[derive(Debug)]
+pub struct RawWebhook<T> {
+    payload: serde_json::Value,
+    decoded: PhantomData<fn() -> T>,
+}
+
+impl<'de, T> Deserialize<'de> for RawWebhook<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(RawWebhook {
+            payload: serde_json::Value::deserialize(deserializer)?,
+            decoded: PhantomData,
+        })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for RawWebhook<T> {
+    fn is_referenceable() -> bool {
+        T::is_referenceable()
+    }
+
+    fn schema_name() -> String {
+        T::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        T::json_schema(gen)
+    }
+}
+
+/// Decode the payload of a webhook, after the transforms we configured for its provider.
+pub fn decode_webhook<T: DeserializeOwned>(
+    rqctx: &RequestContext<ServerContext>,
+    provider: &str,
+    webhook: RawWebhook<T>,
+) -> Result<T, HttpError> {
+    let mut payload = webhook.payload;
+    transform_webhook(rqctx, provider, &mut payload)?;
+
+    serde_json::from_value(payload)
+        .map_err(|e| HttpError::for_bad_request(None, format!("Failed to parse body: {}", e)))
+}